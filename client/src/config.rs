@@ -1,48 +1,132 @@
-use crate::net::heartbeat;
+use crate::data_dir::DataDir;
+use crate::net::lookup::{ImportMode, PortOverrides};
 use crate::net::speed::SpeedUnitPerSecond;
-use crate::ui::styles::themes;
+use crate::net::{arp, conflicts, heartbeat, routers};
+use crate::notifications;
+use crate::ui;
+use crate::ui::format;
+use crate::ui::styles::{colors, protocols, themes};
+use crate::ui::tabs::inspector;
+use crate::ws::address;
 use common::io::FileKind;
 use common::logging;
+use dpi::analysis::ports::DatabaseFormat;
+use dpi::protocols::ProtocolId;
+use dpi::protocols::ethernet::mac::MacAddress;
+use egui::Color32;
 use log::LevelFilter;
 use serde::ser::SerializeStruct;
 use serde::{Deserialize, Serialize, Serializer};
+use std::collections::BTreeMap;
 use std::fs;
+use std::path::PathBuf;
 use std::str::FromStr;
+use strum::IntoEnumIterator;
 use strum_macros::{Display, EnumIter, EnumString};
 use thiserror::Error;
 
 const CONFIG_FILENAME: &str = "config.toml";
 const CONFIG_FILETYPE: FileKind = FileKind::Config;
+const DEFAULT_STORAGE_SOFT_LIMIT_MB: usize = 512;
+const DEFAULT_UI_SCALE: f32 = 1.0;
 
 #[derive(Debug, Clone)]
 pub struct Config {
+    pub anonymize_preserve_oui: bool,
+    pub anonymize_preserve_private: bool,
+    pub arp_reply_window_ms: u32,
+    pub arp_unanswered_min_requests: u32,
+    pub arp_unanswered_ratio_percent: u32,
+    pub byte_prefixes: format::Prefixes,
+    pub color_palette: colors::Palette,
     pub compression: bool,
+    pub idn_form: format::IdnForm,
+    // Records on the page of the inspector
+    pub inspector_page_size: usize,
+    pub ip_conflict_failover_alerts: bool,
+    pub ip_conflict_window_seconds: u32,
     pub language: Language,
     pub log_format: String,
     pub log_level: LevelFilter,
+    pub notification_desktop: bool,
+    pub notification_interval_seconds: u32,
+    pub notification_severity: notifications::Severity,
+    pub notification_sound: bool,
+    pub notification_sound_file: String,
     pub parsed_frames_limit: Option<usize>,
     pub plot_display_window_seconds: u32,
     pub plot_speed_units: SpeedUnitPerSecond,
+    pub port_overrides: PortOverrides,
+    // Empty, if the bundled port database isn't extended by the file
+    pub ports_import_file: String,
+    pub ports_import_format: DatabaseFormat,
+    pub ports_import_mode: ImportMode,
+    pub protocol_colors: protocols::Overrides,
+    pub recent_addresses: Vec<String>,
+    pub router_learning_seconds: u32,
+    pub storage_soft_limit_mb: Option<usize>,
     pub sync_delay_seconds: i64,
     pub theme: themes::Preference,
+    // IPv6 routers, that are never reported as rogue
+    pub trusted_routers: Vec<MacAddress>,
+    pub ui_scale: f32,
     pub unparsed_frames_drop: bool,
+    pub unparsed_frames_spill_mb: Option<usize>,
     pub unparsed_frames_threshold: Option<usize>,
+    pub window_persistence: bool,
+    pub window_title_alerts: bool,
+    pub window_title_devices: bool,
+    // Live stats are appended to the window title
+    pub window_title_stats: bool,
+    pub window_title_throughput: bool,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            anonymize_preserve_oui: false,
+            anonymize_preserve_private: true,
+            arp_reply_window_ms: arp::DEFAULT_REPLY_WINDOW_MS,
+            arp_unanswered_min_requests: arp::DEFAULT_UNANSWERED_MIN_REQUESTS,
+            arp_unanswered_ratio_percent: arp::DEFAULT_UNANSWERED_RATIO_PERCENT,
+            byte_prefixes: format::Prefixes::default(),
+            color_palette: colors::Palette::default(),
             compression: true,
+            idn_form: format::IdnForm::default(),
+            inspector_page_size: inspector::PAGE_SIZE_DEFAULT,
+            ip_conflict_failover_alerts: false,
+            ip_conflict_window_seconds: conflicts::DEFAULT_WINDOW_SECONDS,
             language: Language::English,
             log_format: logging::DEFAULT_FORMAT.to_string(),
             log_level: LevelFilter::Info,
+            notification_desktop: false,
+            notification_interval_seconds: notifications::DEFAULT_INTERVAL_SECONDS,
+            notification_severity: notifications::Severity::Warning,
+            notification_sound: false,
+            notification_sound_file: String::new(),
             parsed_frames_limit: Some(100000),
             plot_display_window_seconds: 10,
             plot_speed_units: SpeedUnitPerSecond::Kilobytes,
+            port_overrides: Default::default(),
+            ports_import_file: String::new(),
+            ports_import_format: DatabaseFormat::default(),
+            ports_import_mode: ImportMode::default(),
+            protocol_colors: Default::default(),
+            recent_addresses: vec![],
+            router_learning_seconds: routers::DEFAULT_LEARNING_SECONDS,
+            storage_soft_limit_mb: Some(DEFAULT_STORAGE_SOFT_LIMIT_MB),
             theme: themes::Preference::default(),
+            trusted_routers: vec![],
             sync_delay_seconds: heartbeat::DEFAULT_PING_DELAY_SECONDS,
+            ui_scale: DEFAULT_UI_SCALE,
             unparsed_frames_drop: true,
+            unparsed_frames_spill_mb: None,
             unparsed_frames_threshold: Some(10000),
+            window_persistence: true,
+            window_title_alerts: true,
+            window_title_devices: true,
+            window_title_stats: false,
+            window_title_throughput: true,
         }
     }
 }
@@ -53,10 +137,48 @@ impl Serialize for Config {
         S: Serializer,
     {
         let mut state = serializer.serialize_struct("Config", 3)?;
+        state.serialize_field("anonymize_preserve_oui", &self.anonymize_preserve_oui)?;
+        state.serialize_field(
+            "anonymize_preserve_private",
+            &self.anonymize_preserve_private,
+        )?;
+        state.serialize_field("arp_reply_window_ms", &self.arp_reply_window_ms)?;
+        state.serialize_field(
+            "arp_unanswered_min_requests",
+            &self.arp_unanswered_min_requests,
+        )?;
+        state.serialize_field(
+            "arp_unanswered_ratio_percent",
+            &self.arp_unanswered_ratio_percent,
+        )?;
+        state.serialize_field("byte_prefixes", &self.byte_prefixes.to_string())?;
+        state.serialize_field("color_palette", &self.color_palette.to_string())?;
         state.serialize_field("compression", &self.compression)?;
+        state.serialize_field("idn_form", &self.idn_form.to_string())?;
+        state.serialize_field("inspector_page_size", &self.inspector_page_size)?;
+        state.serialize_field(
+            "ip_conflict_failover_alerts",
+            &self.ip_conflict_failover_alerts,
+        )?;
+        state.serialize_field(
+            "ip_conflict_window_seconds",
+            &self.ip_conflict_window_seconds,
+        )?;
         state.serialize_field("language", &self.language.to_string())?;
         state.serialize_field("log_format", &self.log_format.to_string())?;
         state.serialize_field("log_level", &self.log_level.to_string())?;
+        state.serialize_field("notification_desktop", &self.notification_desktop)?;
+        state.serialize_field(
+            "notification_interval_seconds",
+            &self.notification_interval_seconds,
+        )?;
+        state.serialize_field(
+            "notification_severity",
+            &self.notification_severity.to_string(),
+        )?;
+        state.serialize_field("notification_sound", &self.notification_sound)?;
+        state
+            .serialize_field("notification_sound_file", &self.notification_sound_file)?;
         let limit = match &self.parsed_frames_limit {
             Some(value) => &value.to_string(),
             None => "none",
@@ -67,44 +189,102 @@ impl Serialize for Config {
             &self.plot_display_window_seconds,
         )?;
         state.serialize_field("plot_speed_units", &self.plot_speed_units.to_string())?;
+        state.serialize_field("ports_import_file", &self.ports_import_file)?;
+        state.serialize_field(
+            "ports_import_format",
+            &self.ports_import_format.to_string(),
+        )?;
+        state
+            .serialize_field("ports_import_mode", &self.ports_import_mode.to_string())?;
+        state.serialize_field("recent_addresses", &self.recent_addresses)?;
+        state
+            .serialize_field("router_learning_seconds", &self.router_learning_seconds)?;
+        let storage_limit = match &self.storage_soft_limit_mb {
+            Some(value) => &value.to_string(),
+            None => "none",
+        };
+        state.serialize_field("storage_soft_limit_mb", storage_limit)?;
         state.serialize_field("sync_delay_seconds", &self.sync_delay_seconds)?;
         state.serialize_field("theme", &self.theme.to_string())?;
+        let trusted_routers = self
+            .trusted_routers
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        state.serialize_field("trusted_routers", &trusted_routers)?;
+        state.serialize_field("ui_scale", &self.ui_scale)?;
 
         state.serialize_field("unparsed_frames_drop", &self.unparsed_frames_drop)?;
+        let spill_limit = match &self.unparsed_frames_spill_mb {
+            Some(value) => &value.to_string(),
+            None => "none",
+        };
+        state.serialize_field("unparsed_frames_spill_mb", spill_limit)?;
         let threshold = match &self.unparsed_frames_threshold {
             Some(value) => &value.to_string(),
             None => "none",
         };
         state.serialize_field("unparsed_frames_threshold", threshold)?;
+        state.serialize_field("window_persistence", &self.window_persistence)?;
+        state.serialize_field("window_title_alerts", &self.window_title_alerts)?;
+        state.serialize_field("window_title_devices", &self.window_title_devices)?;
+        state.serialize_field("window_title_stats", &self.window_title_stats)?;
+        state
+            .serialize_field("window_title_throughput", &self.window_title_throughput)?;
+
+        // Table goes after the plain values
+        let protocol_colors = self
+            .protocol_colors
+            .iter()
+            .map(|(protocol, color)| (protocol, color.to_hex()))
+            .collect::<BTreeMap<_, _>>();
+        state.serialize_field("protocol_colors", &protocol_colors)?;
+        let port_overrides = self
+            .port_overrides
+            .iter()
+            .map(|(port, name)| (port.to_string(), name))
+            .collect::<BTreeMap<_, _>>();
+        state.serialize_field("port_overrides", &port_overrides)?;
 
         state.end()
     }
 }
 
 impl Config {
-    pub fn from_file() -> Result<Self, ConfigError> {
-        match common::io::get_storage_file_path(CONFIG_FILENAME, CONFIG_FILETYPE) {
+    pub fn from_file(data_dir: &DataDir) -> Result<Self, ConfigError> {
+        match Self::file_path(data_dir) {
             Ok(path) => {
-                let data = fs::read_to_string(path);
-                if data.is_err() {
-                    let config = Config::default();
-                    config.save_to_file()?;
-                    return Ok(config);
-                }
+                // Only the missing config is replaced by the default one
+                let data = match fs::read_to_string(path) {
+                    Ok(data) => data,
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                        let config = Config::default();
+                        config.save_to_file(data_dir)?;
+                        return Ok(config);
+                    },
+                    Err(err) => return Err(ConfigError::IOError(err)),
+                };
 
-                let dto: Result<ConfigDto, ConfigError> =
-                    toml::from_str(&data.unwrap_or_default())
-                        .map_err(ConfigError::TomlDeserializationError);
-                dto?.into_config()
+                Self::parse(&data)
             },
             Err(_) => Ok(Self::default()),
         }
     }
 
-    pub fn save_to_file(&self) -> Result<(), ConfigError> {
+    pub fn parse(data: &str) -> Result<Self, ConfigError> {
+        let dto: ConfigDto =
+            toml::from_str(data).map_err(ConfigError::TomlDeserializationError)?;
+        dto.into_config()
+    }
+
+    pub fn file_path(data_dir: &DataDir) -> Result<PathBuf, std::io::Error> {
+        data_dir.file_path(CONFIG_FILENAME, CONFIG_FILETYPE)
+    }
+
+    pub fn save_to_file(&self, data_dir: &DataDir) -> Result<(), ConfigError> {
         let data = toml::to_string(&self).map_err(ConfigError::TomlSerializationError)?;
 
-        let path = common::io::get_storage_file_path(CONFIG_FILENAME, CONFIG_FILETYPE)?;
+        let path = Self::file_path(data_dir)?;
         common::io::create_parent_directories(&path)?;
         fs::write(path, data)?;
 
@@ -114,44 +294,287 @@ impl Config {
 
 #[derive(Deserialize)]
 struct ConfigDto {
+    // Absent in the configs of the older versions
+    #[serde(default)]
+    anonymize_preserve_oui: bool,
+    #[serde(default = "default_anonymize_preserve_private")]
+    anonymize_preserve_private: bool,
+    #[serde(default = "default_arp_reply_window")]
+    arp_reply_window_ms: u32,
+    #[serde(default = "default_arp_unanswered_min_requests")]
+    arp_unanswered_min_requests: u32,
+    #[serde(default = "default_arp_unanswered_ratio")]
+    arp_unanswered_ratio_percent: u32,
+    #[serde(default = "default_byte_prefixes")]
+    byte_prefixes: String,
+    #[serde(default = "default_color_palette")]
+    color_palette: String,
     compression: bool,
+    #[serde(default = "default_idn_form")]
+    idn_form: String,
+    #[serde(default = "default_inspector_page_size")]
+    inspector_page_size: usize,
+    // Absent in the configs of the older versions
+    #[serde(default)]
+    ip_conflict_failover_alerts: bool,
+    #[serde(default = "default_ip_conflict_window")]
+    ip_conflict_window_seconds: u32,
     language: String,
     log_format: String,
     log_level: String,
+    // Notifications are absent in the configs of the older versions
+    #[serde(default)]
+    notification_desktop: bool,
+    #[serde(default = "default_notification_interval")]
+    notification_interval_seconds: u32,
+    #[serde(default = "default_notification_severity")]
+    notification_severity: String,
+    #[serde(default)]
+    notification_sound: bool,
+    #[serde(default)]
+    notification_sound_file: String,
     parsed_frames_limit: String,
     plot_display_window_seconds: u32,
     plot_speed_units: String,
+    // Port overrides & import are absent in the configs of the older versions
+    #[serde(default)]
+    port_overrides: BTreeMap<String, String>,
+    #[serde(default)]
+    ports_import_file: String,
+    #[serde(default = "default_ports_import_format")]
+    ports_import_format: String,
+    #[serde(default = "default_ports_import_mode")]
+    ports_import_mode: String,
+    #[serde(default)]
+    protocol_colors: BTreeMap<String, String>,
+    #[serde(default)]
+    recent_addresses: Vec<String>,
+    #[serde(default = "default_router_learning")]
+    router_learning_seconds: u32,
+    // Absent in the configs of the older versions
+    #[serde(default = "default_storage_soft_limit")]
+    storage_soft_limit_mb: String,
     sync_delay_seconds: i64,
     theme: String,
+    #[serde(default)]
+    trusted_routers: Vec<String>,
+    #[serde(default = "default_ui_scale")]
+    ui_scale: f32,
     unparsed_frames_drop: bool,
+    // Absent in the configs of the older versions
+    #[serde(default)]
+    unparsed_frames_spill_mb: String,
     unparsed_frames_threshold: String,
+    #[serde(default = "default_window_persistence")]
+    window_persistence: bool,
+    // Window title is absent in the configs of the older versions
+    #[serde(default = "default_window_title_part")]
+    window_title_alerts: bool,
+    #[serde(default = "default_window_title_part")]
+    window_title_devices: bool,
+    #[serde(default)]
+    window_title_stats: bool,
+    #[serde(default = "default_window_title_part")]
+    window_title_throughput: bool,
 }
 
 impl ConfigDto {
     pub fn into_config(self) -> Result<Config, ConfigError> {
         let config = Config {
+            anonymize_preserve_oui: self.anonymize_preserve_oui,
+            anonymize_preserve_private: self.anonymize_preserve_private,
+            arp_reply_window_ms: self.arp_reply_window_ms.max(1),
+            arp_unanswered_min_requests: self.arp_unanswered_min_requests,
+            arp_unanswered_ratio_percent: self.arp_unanswered_ratio_percent.min(100),
+            byte_prefixes: format::Prefixes::from_str(
+                self.byte_prefixes.to_ascii_lowercase().trim(),
+            )
+            .map_err(|_| ConfigError::UnknownBytePrefixes)?,
+            color_palette: colors::Palette::from_str(
+                self.color_palette.to_ascii_lowercase().trim(),
+            )
+            .map_err(|_| ConfigError::UnknownColorPalette)?,
             compression: self.compression,
+            idn_form: format::IdnForm::from_str(
+                self.idn_form.to_ascii_lowercase().trim(),
+            )
+            .map_err(|_| ConfigError::UnknownIdnForm)?,
+            inspector_page_size: self.inspector_page_size.clamp(
+                *inspector::PAGE_SIZE_RANGE.start(),
+                *inspector::PAGE_SIZE_RANGE.end(),
+            ),
+            ip_conflict_failover_alerts: self.ip_conflict_failover_alerts,
+            ip_conflict_window_seconds: self.ip_conflict_window_seconds,
             language: Language::from_str(&self.language)
                 .map_err(|_| ConfigError::UnknownLanguage)?,
             log_format: self.log_format.trim().to_string(),
             log_level: LevelFilter::from_str(self.log_level.to_ascii_lowercase().trim())
                 .map_err(|_| ConfigError::UnknownLogLevel)?,
+            notification_desktop: self.notification_desktop,
+            notification_interval_seconds: self.notification_interval_seconds.max(1),
+            notification_severity: notifications::Severity::from_str(
+                self.notification_severity.trim(),
+            )
+            .map_err(|_| ConfigError::UnknownNotificationSeverity)?,
+            notification_sound: self.notification_sound,
+            notification_sound_file: self.notification_sound_file.trim().to_string(),
             parsed_frames_limit: usize::from_str(&self.parsed_frames_limit).ok(),
             plot_display_window_seconds: self.plot_display_window_seconds,
             plot_speed_units: SpeedUnitPerSecond::try_from(
                 self.plot_speed_units.as_str(),
             )
             .map_err(|_| ConfigError::UnknownSpeedUnits)?,
+            port_overrides: Self::port_overrides(self.port_overrides)?,
+            ports_import_file: self.ports_import_file.trim().to_string(),
+            ports_import_format: DatabaseFormat::from_str(
+                self.ports_import_format.trim(),
+            )
+            .map_err(|_| ConfigError::UnknownPortsImportFormat)?,
+            ports_import_mode: ImportMode::from_str(self.ports_import_mode.trim())
+                .map_err(|_| ConfigError::UnknownPortsImportMode)?,
+            protocol_colors: Self::protocol_colors(self.protocol_colors)?,
+            recent_addresses: self
+                .recent_addresses
+                .into_iter()
+                .take(address::RECENT_LIMIT)
+                .collect(),
+            router_learning_seconds: self.router_learning_seconds,
+            storage_soft_limit_mb: usize::from_str(&self.storage_soft_limit_mb).ok(),
             sync_delay_seconds: self.sync_delay_seconds,
             theme: themes::Preference::from_str(self.theme.to_ascii_lowercase().trim())
                 .map_err(|_| ConfigError::UnknownTheme)?,
+            trusted_routers: Self::trusted_routers(self.trusted_routers)?,
+            ui_scale: self
+                .ui_scale
+                .clamp(*ui::UI_SCALE_RANGE.start(), *ui::UI_SCALE_RANGE.end()),
             unparsed_frames_drop: self.unparsed_frames_drop,
+            unparsed_frames_spill_mb: usize::from_str(&self.unparsed_frames_spill_mb)
+                .ok(),
             unparsed_frames_threshold: usize::from_str(&self.unparsed_frames_threshold)
                 .ok(),
+            window_persistence: self.window_persistence,
+            window_title_alerts: self.window_title_alerts,
+            window_title_devices: self.window_title_devices,
+            window_title_stats: self.window_title_stats,
+            window_title_throughput: self.window_title_throughput,
         };
 
         Ok(config)
     }
+
+    fn protocol_colors(
+        colors: BTreeMap<String, String>,
+    ) -> Result<protocols::Overrides, ConfigError> {
+        colors
+            .into_iter()
+            .map(|(protocol, color)| {
+                if !ProtocolId::iter().any(|id| id.to_string() == protocol) {
+                    return Err(ConfigError::UnknownProtocol(protocol));
+                }
+                let color = Color32::from_hex(color.trim())
+                    .map_err(|_| ConfigError::InvalidProtocolColor(protocol.clone()))?;
+
+                Ok((protocol, color))
+            })
+            .collect()
+    }
+
+    fn port_overrides(
+        overrides: BTreeMap<String, String>,
+    ) -> Result<PortOverrides, ConfigError> {
+        overrides
+            .into_iter()
+            .map(|(port, name)| {
+                let name = name.trim().to_string();
+                match (u16::from_str(port.trim()), name.is_empty()) {
+                    (Ok(port), false) => Ok((port, name)),
+                    _ => Err(ConfigError::InvalidPortOverride(port)),
+                }
+            })
+            .collect()
+    }
+
+    fn trusted_routers(routers: Vec<String>) -> Result<Vec<MacAddress>, ConfigError> {
+        routers
+            .into_iter()
+            .map(|mac| {
+                MacAddress::try_from(mac.trim())
+                    .map_err(|_| ConfigError::InvalidRouterMac(mac.clone()))
+            })
+            .collect()
+    }
+}
+
+fn default_anonymize_preserve_private() -> bool {
+    true
+}
+
+fn default_arp_reply_window() -> u32 {
+    arp::DEFAULT_REPLY_WINDOW_MS
+}
+
+fn default_arp_unanswered_min_requests() -> u32 {
+    arp::DEFAULT_UNANSWERED_MIN_REQUESTS
+}
+
+fn default_arp_unanswered_ratio() -> u32 {
+    arp::DEFAULT_UNANSWERED_RATIO_PERCENT
+}
+
+fn default_byte_prefixes() -> String {
+    format::Prefixes::default().to_string()
+}
+
+fn default_color_palette() -> String {
+    colors::Palette::default().to_string()
+}
+
+fn default_idn_form() -> String {
+    format::IdnForm::default().to_string()
+}
+
+fn default_inspector_page_size() -> usize {
+    inspector::PAGE_SIZE_DEFAULT
+}
+
+fn default_ip_conflict_window() -> u32 {
+    conflicts::DEFAULT_WINDOW_SECONDS
+}
+
+fn default_notification_interval() -> u32 {
+    notifications::DEFAULT_INTERVAL_SECONDS
+}
+
+fn default_notification_severity() -> String {
+    notifications::Severity::Warning.to_string()
+}
+
+fn default_ports_import_format() -> String {
+    DatabaseFormat::default().to_string()
+}
+
+fn default_ports_import_mode() -> String {
+    ImportMode::default().to_string()
+}
+
+fn default_router_learning() -> u32 {
+    routers::DEFAULT_LEARNING_SECONDS
+}
+
+fn default_storage_soft_limit() -> String {
+    DEFAULT_STORAGE_SOFT_LIMIT_MB.to_string()
+}
+
+fn default_ui_scale() -> f32 {
+    DEFAULT_UI_SCALE
+}
+
+fn default_window_persistence() -> bool {
+    true
+}
+
+fn default_window_title_part() -> bool {
+    true
 }
 
 #[derive(Error, Debug)]
@@ -165,12 +588,42 @@ pub enum ConfigError {
     #[error("TOML Deserialization Error.")]
     TomlDeserializationError(#[from] toml::de::Error),
 
+    #[error("Invalid color of the protocol {0}.")]
+    InvalidProtocolColor(String),
+
+    #[error("Invalid name override of the port {0}.")]
+    InvalidPortOverride(String),
+
+    #[error("Invalid MAC address of the trusted router {0}.")]
+    InvalidRouterMac(String),
+
+    #[error("Unknown byte prefixes.")]
+    UnknownBytePrefixes,
+
+    #[error("Unknown color palette.")]
+    UnknownColorPalette,
+
+    #[error("Unknown form of the domain names.")]
+    UnknownIdnForm,
+
     #[error("Unknown language.")]
     UnknownLanguage,
 
     #[error("Unknown log level.")]
     UnknownLogLevel,
 
+    #[error("Unknown notification severity.")]
+    UnknownNotificationSeverity,
+
+    #[error("Unknown format of the imported ports.")]
+    UnknownPortsImportFormat,
+
+    #[error("Unknown mode of the imported ports.")]
+    UnknownPortsImportMode,
+
+    #[error("Unknown protocol {0}.")]
+    UnknownProtocol(String),
+
     #[error("Unknown speed units.")]
     UnknownSpeedUnits,
 
@@ -181,6 +634,7 @@ pub enum ConfigError {
 impl ConfigError {
     pub fn additional_info(&self) -> Option<String> {
         match self {
+            ConfigError::IOError(err) => Some(err.to_string()),
             ConfigError::TomlSerializationError(err) => Some(err.to_string()),
             ConfigError::TomlDeserializationError(err) => Some(err.to_string()),
             _ => None,
@@ -1,36 +1,73 @@
 use crate::config::Config;
+use crate::data_dir::DataDir;
 use crate::net::NetStorage;
+use crate::net::anonymize::AnonymizeSettings;
 use crate::net::device::DeviceStorage;
+use crate::net::filter::TimeRange;
 use crate::net::heartbeat::Heartbeat;
 use crate::net::lookup::Lookup;
 use crate::net::raw::RawStorage;
+use crate::net::routers::RouterStorage;
 use crate::net::speed::PlotSettings;
+use crate::net::timeline::TimelineEvent;
+use crate::notifications::{NotificationSettings, Notifier};
 use crate::profiles::ProfilesStorage;
+use crate::ui::format;
 use crate::ui::modals::Modal;
-use crate::ui::styles::themes;
+use crate::ui::styles::{colors, protocols, themes};
+use crate::ui::tabs::inspector::InspectorRequest;
+use crate::ui::title::TitleSettings;
+use crate::ws::link::LinkInfo;
 use crate::ws::request::UiClientRequest;
+use crate::ws::state::{ConnectionState, ConnectionUpdate};
 use chrono::{DateTime, Local};
-use common::messages::Response;
-use crossbeam::channel::{Receiver, Sender, unbounded};
+use common::messages::{
+    CaptureOptionsDto, FailureSamplesDto, FlowExporterDto, InterfaceDto, MirrorDto,
+    PcapWriterDto, Request, Response,
+};
+use common::recording::Entry;
+use common::schedule::{CaptureWindow, ScheduleStatus};
+use crossbeam::channel::{Receiver, Sender, bounded, unbounded};
+use dpi::protocols::port_table::PortTable;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
 
+// Data frames, that wait for the UI. Over it, the frames are dropped, so the backlog of
+// a burst doesn't grow without a limit
+const DATA_CHANNEL_CAPACITY: usize = 50_000;
+
 pub struct Context {
     // Runtime context
     pub client_settings: ClientSettings,
+    pub connection: ConnectionState,
+    // Messages of the server, that are dropped as unreadable since the last readable one
+    pub dropped_messages: u64,
+    // Handshake & counters of the last connection, kept after it's lost for the diagnostics
+    pub link: Option<LinkInfo>,
     pub settings_server: ServerSettings,
     pub heartbeat: Heartbeat,
     pub net_storage: NetStorage,
+    pub notifier: Notifier,
+    pub session: Option<Session>,
+    // Selected on the speed plot, limits records shown by Inspector
+    pub time_range: Option<TimeRange>,
+    // Records to open in Inspector, asked by other tabs
+    pub inspector_request: Option<InspectorRequest>,
 
     // Used for saving into config file
     pub config: Config,
     // Connection profiles
     pub profiles_storage: ProfilesStorage,
+    // Directory with config, device aliases & profiles
+    pub data_dir: DataDir,
 
     // Shutdown flag
     pub shutdown_flag: Arc<AtomicBool>,
 
     // Channels
+    pub connection_tx: Sender<ConnectionUpdate>,
+    pub connection_rx: Receiver<ConnectionUpdate>,
     pub modals_tx: Sender<Box<dyn Modal>>,
     pub modals_rx: Receiver<Box<dyn Modal>>,
     pub data_response_tx: Sender<Response>,
@@ -39,52 +76,131 @@ pub struct Context {
     pub server_response_rx: Receiver<Response>,
     pub ui_client_requests_tx: Sender<UiClientRequest>,
     pub ui_client_requests_rx: Receiver<UiClientRequest>,
+    // Set, if the client is started with the recording
+    pub recording_tx: Option<Sender<Entry<Response>>>,
 }
 
 impl Context {
-    pub fn new(config: Config) -> Self {
+    pub fn new(config: Config, data_dir: DataDir) -> Self {
+        let (connection_tx, connection_rx) = unbounded::<ConnectionUpdate>();
         let (modals_tx, modals_rx) = unbounded::<Box<dyn Modal>>();
         let (server_response_tx, server_response_rx) = unbounded::<Response>();
-        let (data_response_tx, data_response_rx) = unbounded::<Response>();
+        let (data_response_tx, data_response_rx) =
+            bounded::<Response>(DATA_CHANNEL_CAPACITY);
         let (ui_client_requests_tx, ui_client_requests_rx) =
             unbounded::<UiClientRequest>();
 
-        let lookup = match Lookup::load() {
+        // Reported by the self-check
+        let mut lookup = match Lookup::load() {
             Ok(lookup) => lookup,
             Err(err) => {
                 log::error!("Failed to load lookup database: {err}");
-                std::process::exit(1);
+                Lookup::empty()
             },
         };
+        lookup.port_overrides = config.port_overrides.clone();
+        if !config.ports_import_file.is_empty() {
+            match lookup.import_ports(
+                &config.ports_import_file,
+                config.ports_import_format,
+                config.ports_import_mode,
+            ) {
+                Ok(import) => log::info!(
+                    "Imported ports from {}: {} ports, {} malformed lines skipped.",
+                    import.path,
+                    import.table.len(),
+                    import.skipped
+                ),
+                Err(err) => log::error!(
+                    "Failed to import ports from {}: {err}",
+                    config.ports_import_file
+                ),
+            }
+        }
 
         Self {
             client_settings: ClientSettings {
+                anonymization: AnonymizeSettings {
+                    preserve_oui: config.anonymize_preserve_oui,
+                    preserve_private: config.anonymize_preserve_private,
+                },
+                arp_reply_window_ms: config.arp_reply_window_ms,
+                arp_unanswered_min_requests: config.arp_unanswered_min_requests,
+                arp_unanswered_ratio_percent: config.arp_unanswered_ratio_percent,
+                byte_prefixes: config.byte_prefixes,
+                color_palette: config.color_palette,
                 compression: config.compression,
+                idn_form: config.idn_form,
+                inspector_page_size: config.inspector_page_size,
+                debug_overlay: false,
+                ip_conflict_failover_alerts: config.ip_conflict_failover_alerts,
+                ip_conflict_window_seconds: config.ip_conflict_window_seconds,
+                notifications: NotificationSettings {
+                    desktop: config.notification_desktop,
+                    interval_seconds: config.notification_interval_seconds,
+                    severity: config.notification_severity,
+                    sound: config.notification_sound,
+                    sound_file: config.notification_sound_file.clone(),
+                },
                 parsed_frames_limit: config.parsed_frames_limit,
                 plot: PlotSettings {
                     display_window_seconds: config.plot_display_window_seconds,
                     units: config.plot_speed_units.clone(),
                 },
+                protocol_colors: config.protocol_colors.clone(),
+                router_learning_seconds: config.router_learning_seconds,
+                storage_soft_limit_mb: config.storage_soft_limit_mb,
                 sync_delay_seconds: config.sync_delay_seconds,
                 theme: config.theme,
+                ui_scale: config.ui_scale,
                 unparsed_frames_drop: config.unparsed_frames_drop,
+                unparsed_frames_spill_mb: config.unparsed_frames_spill_mb,
                 unparsed_frames_threshold: config.unparsed_frames_threshold,
+                window_title: TitleSettings {
+                    enabled: config.window_title_stats,
+                    throughput: config.window_title_throughput,
+                    devices: config.window_title_devices,
+                    alerts: config.window_title_alerts,
+                },
             },
+            connection: Default::default(),
+            dropped_messages: 0,
+            link: None,
             settings_server: Default::default(),
             heartbeat: Default::default(),
             net_storage: NetStorage {
-                devices: DeviceStorage::from_file().unwrap_or_default(),
+                capture: Default::default(),
+                conflicts: Default::default(),
+                devices: DeviceStorage::from_file(&data_dir).unwrap_or_default(),
+                dhcp_servers: Default::default(),
+                endpoints: Default::default(),
                 inspector: Default::default(),
                 lookup,
-                raw: RawStorage::new(config.unparsed_frames_threshold),
+                notes: Default::default(),
+                nxdomain_bursts: Default::default(),
+                raw: RawStorage::new(
+                    config.unparsed_frames_threshold,
+                    config.unparsed_frames_spill_mb,
+                ),
+                routers: RouterStorage::with_trusted(&config.trusted_routers),
+                sampling: Default::default(),
+                snapshots: vec![],
                 speed: Default::default(),
+                timeline: Default::default(),
             },
+            notifier: Default::default(),
+            session: None,
+            time_range: None,
+            inspector_request: None,
 
             config,
-            profiles_storage: ProfilesStorage::from_file().unwrap_or_default(),
+            profiles_storage: ProfilesStorage::from_file(&data_dir).unwrap_or_default(),
+            data_dir,
 
             shutdown_flag: Arc::new(Default::default()),
 
+            connection_tx,
+            connection_rx,
             modals_tx,
             modals_rx,
             data_response_tx,
@@ -93,40 +209,78 @@ impl Context {
             server_response_rx,
             ui_client_requests_tx,
             ui_client_requests_rx,
+            recording_tx: None,
         }
     }
 
     pub fn logout(&mut self) {
         // Cloning client settings, config, devices, lookup, profiles storage.
 
+        let (connection_tx, connection_rx) = unbounded::<ConnectionUpdate>();
         let (modals_tx, modals_rx) = unbounded::<Box<dyn Modal>>();
         let (server_response_tx, server_response_rx) = unbounded::<Response>();
-        let (data_response_tx, data_response_rx) = unbounded::<Response>();
+        let (data_response_tx, data_response_rx) =
+            bounded::<Response>(DATA_CHANNEL_CAPACITY);
         let (ui_client_requests_tx, ui_client_requests_rx) =
             unbounded::<UiClientRequest>();
 
         let profiles_storage = self.profiles_storage.clone();
+        let mut timeline = std::mem::take(&mut self.net_storage.timeline);
+        if self.connection.is_connected() {
+            timeline.push(Local::now(), TimelineEvent::Disconnected { reason: None });
+        }
 
         let new_context = Self {
             client_settings: self.client_settings.clone(),
+            // Keeping the reason of the lost connection
+            connection: match &self.connection {
+                ConnectionState::Disconnected(_) => self.connection.clone(),
+                _ => Default::default(),
+            },
+            dropped_messages: 0,
+            link: None,
             settings_server: Default::default(),
             heartbeat: Default::default(),
             net_storage: NetStorage {
+                capture: Default::default(),
+                conflicts: Default::default(),
                 devices: DeviceStorage {
                     list: Default::default(),
                     aliases: self.net_storage.devices.aliases.clone(),
+                    types: self.net_storage.devices.types.clone(),
+                    tags: self.net_storage.devices.tags.clone(),
+                    signals: Default::default(),
                 },
+                dhcp_servers: Default::default(),
+                endpoints: Default::default(),
                 inspector: Default::default(),
                 lookup: self.net_storage.lookup.clone(),
-                raw: RawStorage::new(self.config.unparsed_frames_threshold),
+                notes: Default::default(),
+                nxdomain_bursts: Default::default(),
+                raw: RawStorage::new(
+                    self.config.unparsed_frames_threshold,
+                    self.config.unparsed_frames_spill_mb,
+                ),
+                routers: RouterStorage::with_trusted(&self.config.trusted_routers),
+                sampling: Default::default(),
+                snapshots: std::mem::take(&mut self.net_storage.snapshots),
                 speed: Default::default(),
+                timeline,
             },
+            // Rate limit isn't reset by reconnect
+            notifier: std::mem::take(&mut self.notifier),
+            session: self.session.take(),
+            time_range: None,
+            inspector_request: None,
 
             config: self.config.clone(),
             profiles_storage,
+            data_dir: self.data_dir.clone(),
 
             shutdown_flag: Arc::new(Default::default()),
 
+            connection_tx,
+            connection_rx,
             modals_tx,
             modals_rx,
             data_response_tx,
@@ -135,20 +289,60 @@ impl Context {
             server_response_rx,
             ui_client_requests_tx,
             ui_client_requests_rx,
+            // Recording continues after reconnect
+            recording_tx: self.recording_tx.take(),
         };
 
         *self = new_context;
     }
+
+    // Revokes the session token on the server. If the connection is already lost,
+    // the token is kept, so it's possible to reconnect without the password.
+    pub fn end_session(&mut self, is_connected: bool) {
+        if !is_connected {
+            return;
+        }
+        if let Some(session) = self.session.take() {
+            let request = UiClientRequest::Request(Request::Logout(session.token));
+            if let Err(err) = self.ui_client_requests_tx.try_send(request) {
+                log::error!("Failed to send command (Logout): {err}");
+            }
+        }
+    }
+}
+
+pub struct Session {
+    pub address: SocketAddr,
+    pub token: String,
 }
 
 #[derive(Default)]
 pub struct ServerSettings {
+    pub capture_options_active: Option<CaptureOptionsDto>,
+    pub capture_options_config: CaptureOptionsDto,
     pub compression_active: bool,
     pub compression_config: bool,
-    pub interfaces_available: Vec<String>,
+    // Revisions of the password, the connection is authorized with & the current one
+    pub credentials_active: u64,
+    pub credentials_config: u64,
+    pub failure_samples_config: bool,
+    // Requested by the developer panel, not with the settings
+    pub failure_samples: Option<FailureSamplesDto>,
+    pub flow_export_active: Option<FlowExporterDto>,
+    pub flow_export_config: bool,
+    pub interfaces_available: Vec<InterfaceDto>,
     pub interface_active: Option<String>,
     pub interface_config: Option<String>,
     pub link_type: Option<pcap::Linktype>,
+    pub mirror_active: Option<MirrorDto>,
+    pub pcap_writer_active: Option<PcapWriterDto>,
+    pub pcap_writer_config: bool,
+    pub protocol_ports: PortTable,
+    pub schedule: Vec<CaptureWindow>,
+    pub schedule_status: ScheduleStatus,
+    // Of the connection, the new ones get the config value
+    pub send_delay_active: u64,
+    pub send_delay_config: u64,
     pub send_unparsed_frames_active: bool,
     pub send_unparsed_frames_config: bool,
 
@@ -157,11 +351,30 @@ pub struct ServerSettings {
 
 #[derive(Clone)]
 pub struct ClientSettings {
+    pub anonymization: AnonymizeSettings,
+    pub arp_reply_window_ms: u32,
+    pub arp_unanswered_min_requests: u32,
+    pub arp_unanswered_ratio_percent: u32,
+    pub byte_prefixes: format::Prefixes,
+    pub color_palette: colors::Palette,
     pub compression: bool,
+    pub idn_form: format::IdnForm,
+    pub inspector_page_size: usize,
+    // Not saved, it's needed only for the profiling
+    pub debug_overlay: bool,
+    pub ip_conflict_failover_alerts: bool,
+    pub ip_conflict_window_seconds: u32,
+    pub notifications: NotificationSettings,
     pub sync_delay_seconds: i64,
     pub parsed_frames_limit: Option<usize>,
     pub plot: PlotSettings,
+    pub protocol_colors: protocols::Overrides,
+    pub router_learning_seconds: u32,
+    pub storage_soft_limit_mb: Option<usize>,
     pub theme: themes::Preference,
+    pub ui_scale: f32,
     pub unparsed_frames_drop: bool,
+    pub unparsed_frames_spill_mb: Option<usize>,
     pub unparsed_frames_threshold: Option<usize>,
+    pub window_title: TitleSettings,
 }
@@ -0,0 +1,179 @@
+use common::io::FileKind;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::{env, fmt};
+
+// File next to the executable that enables portable mode
+pub const PORTABLE_MARKER: &str = "xailyser-client.toml";
+pub const CONFIG_DIR_ARGUMENT: &str = "--config-dir";
+pub const CONFIG_DIR_VARIABLE: &str = "XAILYSER_CLIENT_DIR";
+
+/// Directory with the client files: config, window state, device aliases
+/// & connection profiles. <br>
+/// Precedence: CLI argument > environment variable > portable marker > platform default.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DataDir {
+    Argument(PathBuf),
+    Variable(PathBuf),
+    Portable(PathBuf),
+    // Config & data directories of the platform, used if nothing else is set
+    Platform,
+}
+
+impl DataDir {
+    pub fn resolve() -> Self {
+        let executable_dir = env::current_exe()
+            .ok()
+            .and_then(|path| path.parent().map(Path::to_path_buf));
+
+        Self::resolve_from(
+            env::args_os().skip(1),
+            env::var_os(CONFIG_DIR_VARIABLE),
+            executable_dir.as_deref(),
+        )
+    }
+
+    fn resolve_from(
+        args: impl IntoIterator<Item = OsString>, variable: Option<OsString>,
+        executable_dir: Option<&Path>,
+    ) -> Self {
+        if let Some(path) = Self::argument(args) {
+            return Self::Argument(path);
+        }
+        if let Some(path) = variable.filter(|value| !value.is_empty()) {
+            return Self::Variable(PathBuf::from(path));
+        }
+        if let Some(dir) = executable_dir {
+            if dir.join(PORTABLE_MARKER).is_file() {
+                return Self::Portable(dir.to_path_buf());
+            }
+        }
+
+        Self::Platform
+    }
+
+    // Both "--config-dir <path>" and "--config-dir=<path>" are accepted
+    fn argument(args: impl IntoIterator<Item = OsString>) -> Option<PathBuf> {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let arg = arg.to_string_lossy();
+            if arg == CONFIG_DIR_ARGUMENT {
+                return args.next().map(PathBuf::from);
+            }
+            if let Some(value) = arg
+                .strip_prefix(CONFIG_DIR_ARGUMENT)
+                .and_then(|rest| rest.strip_prefix('='))
+            {
+                return Some(PathBuf::from(value));
+            }
+        }
+
+        None
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Argument(path) | Self::Variable(path) | Self::Portable(path) => {
+                Some(path)
+            },
+            Self::Platform => None,
+        }
+    }
+
+    pub fn file_path(
+        &self, file_name: &str, file_kind: FileKind,
+    ) -> Result<PathBuf, std::io::Error> {
+        Ok(self.dir_path(file_kind)?.join(file_name))
+    }
+
+    pub fn dir_path(&self, file_kind: FileKind) -> Result<PathBuf, std::io::Error> {
+        match self.path() {
+            Some(path) => Ok(path.to_path_buf()),
+            None => common::io::get_storage_dir_path(file_kind),
+        }
+    }
+
+    pub fn localized_source(&self) -> String {
+        match self {
+            Self::Argument(_) => t!("DataDir.Source.Argument").to_string(),
+            Self::Variable(_) => t!("DataDir.Source.Variable").to_string(),
+            Self::Portable(_) => t!("DataDir.Source.Portable").to_string(),
+            Self::Platform => t!("DataDir.Source.Platform").to_string(),
+        }
+    }
+}
+
+impl fmt::Display for DataDir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Argument(path) => write!(f, "{} (CLI argument)", path.display()),
+            Self::Variable(path) => {
+                write!(f, "{} ({CONFIG_DIR_VARIABLE})", path.display())
+            },
+            Self::Portable(path) => write!(f, "{} (portable)", path.display()),
+            Self::Platform => write!(f, "Platform default"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<OsString> {
+        list.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn test_resolution_precedence() {
+        let portable = env::temp_dir().join("xailyser-data-dir-test");
+        let is_created = std::fs::create_dir_all(&portable).is_ok()
+            && std::fs::write(portable.join(PORTABLE_MARKER), "").is_ok();
+        assert!(is_created);
+
+        let variable = Some(OsString::from("/from/env"));
+
+        assert_eq!(
+            DataDir::resolve_from(
+                args(&["--config-dir", "/from/cli"]),
+                variable.clone(),
+                Some(&portable)
+            ),
+            DataDir::Argument(PathBuf::from("/from/cli"))
+        );
+        assert_eq!(
+            DataDir::resolve_from(
+                args(&["--config-dir=/from/cli"]),
+                variable.clone(),
+                None
+            ),
+            DataDir::Argument(PathBuf::from("/from/cli"))
+        );
+        assert_eq!(
+            DataDir::resolve_from(args(&[]), variable, Some(&portable)),
+            DataDir::Variable(PathBuf::from("/from/env"))
+        );
+        assert_eq!(
+            DataDir::resolve_from(args(&[]), Some(OsString::new()), Some(&portable)),
+            DataDir::Portable(portable.clone())
+        );
+        assert_eq!(
+            DataDir::resolve_from(args(&["--config-dir"]), None, None),
+            DataDir::Platform
+        );
+
+        // Marker is absent
+        let _ = std::fs::remove_file(portable.join(PORTABLE_MARKER));
+        assert_eq!(
+            DataDir::resolve_from(args(&[]), None, Some(&portable)),
+            DataDir::Platform
+        );
+    }
+
+    #[test]
+    fn test_file_path() {
+        let dir = DataDir::Portable(PathBuf::from("/portable"));
+        let path = dir.file_path("config.toml", FileKind::Config).ok();
+        assert_eq!(path, Some(PathBuf::from("/portable/config.toml")));
+    }
+}
@@ -10,6 +10,9 @@ pub enum FileError {
 
     #[error("TOML Deserialization Error.")]
     TomlDeserialization(#[from] toml::de::Error),
+
+    #[error("JSON Error.")]
+    Json(#[from] serde_json::Error),
 }
 
 impl FileError {
@@ -18,6 +21,7 @@ impl FileError {
             Self::IO(err) => Some(err.to_string()),
             Self::TomlSerialization(err) => Some(err.to_string()),
             Self::TomlDeserialization(err) => Some(err.to_string()),
+            Self::Json(err) => Some(err.to_string()),
         }
     }
 }
@@ -8,10 +8,17 @@ extern crate rust_i18n;
 rust_i18n::i18n!("locales", fallback = "English");
 
 use crate::config::Config;
+use crate::data_dir::DataDir;
+use crate::ws::recording;
 
 fn main() {
+    // Resolving the directory with config, device aliases & profiles
+    let data_dir = DataDir::resolve();
+    // Recording or replay of the session
+    let mode = recording::Mode::resolve();
+
     // Reading config
-    let config = match Config::from_file() {
+    let config = match Config::from_file(&data_dir) {
         Ok(value) => value,
         Err(err) => {
             let mut message = format!("Config initialization failed. Error: {err}.");
@@ -25,6 +32,9 @@ fn main() {
 
     // Setting language
     rust_i18n::set_locale(&config.language.to_string());
+    ui::format::set_prefixes(config.byte_prefixes);
+    ui::format::set_idn_form(config.idn_form);
+    ui::styles::colors::set_palette(config.color_palette);
 
     // Logging setup
     logging::setup(&config.log_level, config.log_format.clone()).unwrap_or_else(|err| {
@@ -37,10 +47,12 @@ fn main() {
     });
 
     log::info!("Starting...");
+    log::info!("Data directory: {data_dir}");
     log::info!("Config loaded: {config:#?}");
+    log::info!("Session mode: {mode:?}");
     log::info!("Logger initialized.");
 
-    ui::start(config).unwrap_or_else(|err| {
+    ui::start(config, data_dir, mode).unwrap_or_else(|err| {
         log::error!("{err}");
         std::process::exit(1);
     });
@@ -48,9 +60,12 @@ fn main() {
 
 mod config;
 mod context;
+mod data_dir;
 mod errors;
 mod logging;
 mod net;
+mod notifications;
 mod profiles;
+mod self_check;
 mod ui;
 mod ws;
@@ -1,23 +1,65 @@
+use crate::net::anomalies::{DhcpServers, NxDomainBursts};
+use crate::net::capture::CaptureStats;
+use crate::net::conflicts::ConflictStorage;
 use crate::net::device::DeviceStorage;
+use crate::net::endpoints::EndpointStorage;
 use crate::net::inspector::InspectorStorage;
 use crate::net::lookup::Lookup;
+use crate::net::notes::RecordNotes;
 use crate::net::raw::RawStorage;
+use crate::net::routers::RouterStorage;
+use crate::net::sampling::SamplingStats;
+use crate::net::snapshot::Snapshot;
 use crate::net::speed::SpeedData;
+use crate::net::timeline::Timeline;
 
 pub const PCAP_FILTER_NAME: &str = "PCAP";
 pub const PCAP_FILTER_EXTENSIONS: &[&str] = &["pcap"];
 
 pub struct NetStorage {
+    pub capture: CaptureStats,
+    pub conflicts: ConflictStorage,
     pub devices: DeviceStorage,
+    pub dhcp_servers: DhcpServers,
+    pub endpoints: EndpointStorage,
     pub inspector: InspectorStorage,
     pub lookup: Lookup,
+    pub notes: RecordNotes,
+    pub nxdomain_bursts: NxDomainBursts,
     pub raw: RawStorage,
+    pub routers: RouterStorage,
+    pub sampling: SamplingStats,
+    // Kept through reconnects
+    pub snapshots: Vec<Snapshot>,
     pub speed: SpeedData,
+    // Kept through reconnects
+    pub timeline: Timeline,
 }
 
+pub mod anomalies;
+pub mod anonymize;
+pub mod arp;
+pub mod capture;
+pub mod classification;
+pub mod conflicts;
+pub mod conversation;
 pub mod device;
+pub mod endpoints;
+pub mod filter;
+pub mod flows;
 pub mod heartbeat;
+pub mod idna;
 pub mod inspector;
+pub mod inventory;
 pub mod lookup;
+pub mod memory;
+pub mod notes;
 pub mod raw;
+pub mod routers;
+pub mod sampling;
+pub mod search;
+pub mod snapshot;
 pub mod speed;
+pub mod timeline;
+pub mod ttl;
+pub mod voip;
@@ -0,0 +1,170 @@
+use crate::net::timeline::TimelineEvent;
+use chrono::{DateTime, Duration, Local};
+use dpi::protocols::dhcpv4::{DHCPv4Dto, OperationType};
+use dpi::protocols::dns::{DnsDto, ResponseCode};
+use dpi::protocols::ethernet::mac::MacAddress;
+use std::collections::{HashSet, VecDeque};
+use std::net::Ipv4Addr;
+
+// NXDOMAIN responses in the window, that are considered a burst
+pub const NXDOMAIN_BURST_RESPONSES: usize = 20;
+pub const NXDOMAIN_BURST_WINDOW_SECONDS: i64 = 10;
+
+/// The first DHCP server, that answers, is taken as the known one.
+/// Others are reported once, they may be rogue or misconfigured.
+#[derive(Default)]
+pub struct DhcpServers {
+    known: Option<Ipv4Addr>,
+    reported: HashSet<Ipv4Addr>,
+}
+
+impl DhcpServers {
+    pub fn observe(
+        &mut self, dhcp: &DHCPv4Dto, ip: Ipv4Addr, mac: &MacAddress,
+    ) -> Option<TimelineEvent> {
+        if dhcp.message_type != OperationType::BootReply {
+            return None;
+        }
+
+        let known = *self.known.get_or_insert(ip);
+        if known == ip || !self.reported.insert(ip) {
+            return None;
+        }
+
+        Some(TimelineEvent::UnknownDhcpServer {
+            ip,
+            mac: mac.clone(),
+            known,
+        })
+    }
+}
+
+/// Many failed lookups in a short time: malware, DGA or misconfigured resolver.
+#[derive(Default)]
+pub struct NxDomainBursts {
+    recent: VecDeque<DateTime<Local>>,
+    // Burst is reported once, until the window passes
+    last_reported: Option<DateTime<Local>>,
+}
+
+impl NxDomainBursts {
+    pub fn observe(
+        &mut self, dns: &DnsDto, captured: DateTime<Local>,
+    ) -> Option<TimelineEvent> {
+        if dns.response_code != ResponseCode::NameError {
+            return None;
+        }
+
+        let window = Duration::seconds(NXDOMAIN_BURST_WINDOW_SECONDS);
+        self.recent.push_back(captured);
+        while self
+            .recent
+            .front()
+            .is_some_and(|first| captured - *first > window)
+        {
+            self.recent.pop_front();
+        }
+
+        let is_reported = self
+            .last_reported
+            .is_some_and(|last| captured - last < window);
+        if self.recent.len() < NXDOMAIN_BURST_RESPONSES || is_reported {
+            return None;
+        }
+
+        self.last_reported = Some(captured);
+        let responses = self.recent.len();
+        self.recent.clear();
+        Some(TimelineEvent::NxDomainBurst {
+            responses,
+            seconds: NXDOMAIN_BURST_WINDOW_SECONDS,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dpi::protocols::dns::{MessageType, OperationCode};
+
+    fn dhcp(message_type: OperationType) -> DHCPv4Dto {
+        DHCPv4Dto {
+            message_type,
+            old_client_address: Ipv4Addr::UNSPECIFIED,
+            new_client_address: Ipv4Addr::new(192, 168, 0, 10),
+            server_address: Ipv4Addr::UNSPECIFIED,
+            relay_agent_address: Ipv4Addr::UNSPECIFIED,
+            hardware_address_client: MacAddress([0, 0, 0, 0, 0, 10]),
+            vendor_class: None,
+            parameter_request_list: vec![],
+        }
+    }
+
+    fn dns(response_code: ResponseCode) -> DnsDto {
+        DnsDto {
+            message_type: MessageType::Response,
+            operation_code: OperationCode::StandardQuery,
+            authoritative_answer: false,
+            response_code,
+            question_section: vec![],
+            answer_section: vec![],
+            authority_section: vec![],
+            additional_section: vec![],
+        }
+    }
+
+    #[test]
+    fn test_unknown_dhcp_server() {
+        let mut servers = DhcpServers::default();
+        let router = Ipv4Addr::new(192, 168, 0, 1);
+        let rogue = Ipv4Addr::new(192, 168, 0, 66);
+        let mac = MacAddress([0, 0, 0, 0, 0, 66]);
+
+        let reply = dhcp(OperationType::BootReply);
+        assert_eq!(servers.observe(&reply, router, &mac), None);
+        // Requests come from the clients
+        let request = dhcp(OperationType::BootRequest);
+        assert_eq!(servers.observe(&request, rogue, &mac), None);
+
+        assert_eq!(
+            servers.observe(&reply, rogue, &mac),
+            Some(TimelineEvent::UnknownDhcpServer {
+                ip: rogue,
+                mac: mac.clone(),
+                known: router,
+            })
+        );
+        // Reported once
+        assert_eq!(servers.observe(&reply, rogue, &mac), None);
+    }
+
+    #[test]
+    fn test_nxdomain_burst() {
+        let mut bursts = NxDomainBursts::default();
+        let now = Local::now();
+        let name_error = dns(ResponseCode::NameError);
+
+        // Spread over the time, so there's no burst
+        for index in 0..NXDOMAIN_BURST_RESPONSES {
+            let captured = now + Duration::seconds(index as i64);
+            assert_eq!(bursts.observe(&name_error, captured), None);
+        }
+        assert_eq!(
+            bursts.observe(&dns(ResponseCode::NoErrorCondition), now),
+            None
+        );
+
+        let later = now + Duration::minutes(1);
+        let mut events = vec![];
+        for _ in 0..(NXDOMAIN_BURST_RESPONSES * 2) {
+            events.extend(bursts.observe(&name_error, later));
+        }
+        assert_eq!(
+            events,
+            vec![TimelineEvent::NxDomainBurst {
+                responses: NXDOMAIN_BURST_RESPONSES,
+                seconds: NXDOMAIN_BURST_WINDOW_SECONDS,
+            }]
+        );
+    }
+}
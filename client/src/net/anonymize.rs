@@ -0,0 +1,1346 @@
+// Anonymization of the exports, so they're shared without the internal addressing.
+// Every export gets its own random key, that isn't kept: pseudonyms are consistent within
+// the export, but differ between the exports & can't be reversed.
+//
+// Addresses are pseudonymized prefix-preserving (as Crypto-PAn): every bit is flipped by
+// the keyed hash of the bits before it. So the addresses of the same subnet get the same
+// pseudonymous subnet.
+
+use crate::net::device::LocalDevice;
+use common::cryptography;
+use dpi::dto::frame::LinkType;
+use dpi::dto::metadata::{FrameMetadataDto, ProtocolDto};
+use dpi::protocols::arp::{HardwareAddress, ProtocolAddress};
+use dpi::protocols::dns::{DnsDto, DnsTypeData};
+use dpi::protocols::ethernet::mac::MacAddress;
+use dpi::protocols::http::{Header, HttpDto};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::ops::Range;
+
+const KEY_LENGTH_BYTES: usize = 32;
+
+const ETHERNET_HEADER_BYTES: usize = 14;
+const ETHER_TYPE_IPV4: u16 = 0x0800;
+const ETHER_TYPE_ARP: u16 = 0x0806;
+const ETHER_TYPE_VLAN: u16 = 0x8100;
+const ETHER_TYPE_IPV6: u16 = 0x86DD;
+const IP_PROTOCOL_ICMP: u8 = 1;
+const IP_PROTOCOL_TCP: u8 = 6;
+const IP_PROTOCOL_UDP: u8 = 17;
+const IP_PROTOCOL_ICMPV6: u8 = 58;
+
+const PORT_DHCP_SERVER: u16 = 67;
+const PORT_DHCP_CLIENT: u16 = 68;
+const PORT_DNS: u16 = 53;
+const PORT_HTTP: u16 = 80;
+const PORT_HTTP_ALT: u16 = 8080;
+const PORT_LLMNR: u16 = 5355;
+const PORT_MDNS: u16 = 5353;
+
+// Destination unreachable, redirect, time exceeded & parameter problem
+const ICMP_ERRORS: [u8; 4] = [3, 5, 11, 12];
+const ICMP_REDIRECT: u8 = 5;
+const ICMPV6_ERRORS: Range<u8> = 1..5;
+const NDP_ROUTER_SOLICITATION: u8 = 133;
+const NDP_ROUTER_ADVERTISEMENT: u8 = 134;
+const NDP_NEIGHBOR_SOLICITATION: u8 = 135;
+const NDP_NEIGHBOR_ADVERTISEMENT: u8 = 136;
+const NDP_REDIRECT: u8 = 137;
+const NDP_OPTION_SOURCE_LINK_LAYER: u8 = 1;
+const NDP_OPTION_TARGET_LINK_LAYER: u8 = 2;
+const NDP_OPTION_PREFIX: u8 = 3;
+const NDP_OPTION_REDIRECTED_HEADER: u8 = 4;
+
+const DNS_HEADER_BYTES: usize = 12;
+const DNS_TYPE_A: u16 = 1;
+const DNS_TYPE_NS: u16 = 2;
+const DNS_TYPE_CNAME: u16 = 5;
+const DNS_TYPE_SOA: u16 = 6;
+const DNS_TYPE_PTR: u16 = 12;
+const DNS_TYPE_MX: u16 = 15;
+const DNS_TYPE_AAAA: u16 = 28;
+const DNS_TYPE_SRV: u16 = 33;
+const DNS_TYPE_DNAME: u16 = 39;
+
+const DHCP_MAGIC_COOKIE: [u8; 4] = [99, 130, 83, 99];
+const DHCP_OPTIONS_OFFSET: usize = 240;
+const DHCP_OPTION_PAD: u8 = 0;
+const DHCP_OPTION_ROUTER: u8 = 3;
+const DHCP_OPTION_DNS_SERVER: u8 = 6;
+const DHCP_OPTION_HOSTNAME: u8 = 12;
+const DHCP_OPTION_DOMAIN_NAME: u8 = 15;
+const DHCP_OPTION_REQUESTED_IP: u8 = 50;
+const DHCP_OPTION_SERVER_ID: u8 = 54;
+const DHCP_OPTION_CLIENT_ID: u8 = 61;
+const DHCP_OPTION_CLIENT_FQDN: u8 = 81;
+const DHCP_OPTION_END: u8 = 255;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnonymizeSettings {
+    // Vendor part of the MAC addresses, the vendor names are exported too
+    pub preserve_oui: bool,
+    // Private addresses stay private (within their range), public ones stay public
+    pub preserve_private: bool,
+}
+
+impl Default for AnonymizeSettings {
+    fn default() -> Self {
+        Self {
+            preserve_oui: false,
+            preserve_private: true,
+        }
+    }
+}
+
+pub struct Anonymizer {
+    key: [u8; KEY_LENGTH_BYTES],
+    settings: AnonymizeSettings,
+    // Address costs a hash per bit, so they're mapped once
+    ips: HashMap<IpAddr, IpAddr>,
+}
+
+impl Anonymizer {
+    pub fn new(settings: AnonymizeSettings) -> Self {
+        Self {
+            key: rand::random(),
+            settings,
+            ips: HashMap::new(),
+        }
+    }
+
+    pub fn ip(&mut self, ip: IpAddr) -> IpAddr {
+        if let Some(mapped) = self.ips.get(&ip) {
+            return *mapped;
+        }
+        let mapped = match ip {
+            IpAddr::V4(ip) => IpAddr::V4(self.map_ipv4(ip)),
+            IpAddr::V6(ip) => IpAddr::V6(self.map_ipv6(ip)),
+        };
+        self.ips.insert(ip, mapped);
+
+        mapped
+    }
+
+    pub fn ipv4(&mut self, ip: Ipv4Addr) -> Ipv4Addr {
+        match self.ip(IpAddr::V4(ip)) {
+            IpAddr::V4(mapped) => mapped,
+            IpAddr::V6(_) => ip,
+        }
+    }
+
+    pub fn ipv6(&mut self, ip: Ipv6Addr) -> Ipv6Addr {
+        match self.ip(IpAddr::V6(ip)) {
+            IpAddr::V6(mapped) => mapped,
+            IpAddr::V4(_) => ip,
+        }
+    }
+
+    /// Group addresses (broadcast, multicast) aren't of the hosts, they're kept.
+    /// Without the OUI, the pseudonym is locally administered, so it isn't of any vendor.
+    pub fn mac(&self, mac: &MacAddress) -> MacAddress {
+        if mac.0[0] & 0x01 == 0x01 {
+            return mac.clone();
+        }
+
+        let digest = self.digest(b"mac", &mac.0);
+        let mut bytes = [0; 6];
+        bytes.copy_from_slice(&digest[..6]);
+        match self.settings.preserve_oui {
+            true => bytes[..3].copy_from_slice(&mac.0[..3]),
+            false => bytes[0] = (bytes[0] | 0x02) & !0x01,
+        }
+
+        MacAddress(bytes)
+    }
+
+    /// Labels are replaced one by one, so the names of the same domain stay related.
+    /// Top-level label (e.g. "com", "local") is kept.
+    pub fn hostname(&self, name: &str) -> String {
+        let name = name.trim().trim_end_matches('.').to_ascii_lowercase();
+        if name.is_empty() {
+            return name;
+        }
+
+        let labels: Vec<&str> = name.split('.').collect();
+        let top = labels.len().saturating_sub(1);
+        labels
+            .iter()
+            .enumerate()
+            .map(|(index, label)| match index == top && index > 0 {
+                true => label.to_string(),
+                false => self.label(label),
+            })
+            .collect::<Vec<String>>()
+            .join(".")
+    }
+
+    /// Vendor is told by the OUI, so it's exported only with it.
+    pub fn vendor<T: Clone>(&self, vendor: Option<&T>) -> Option<T> {
+        match self.settings.preserve_oui {
+            true => vendor.cloned(),
+            false => None,
+        }
+    }
+
+    pub fn device(&mut self, device: &LocalDevice) -> LocalDevice {
+        LocalDevice {
+            mac: self.mac(&device.mac),
+            ip: device.ip.iter().map(|ip| self.ipv4(*ip)).collect(),
+            ipv6: device.ipv6.iter().map(|ip| self.ipv6(*ip)).collect(),
+            vendor: self.vendor(device.vendor.as_ref()),
+            hostname: device.hostname.as_ref().map(|name| self.hostname(name)),
+            first_seen: device.first_seen,
+            last_seen: device.last_seen,
+        }
+    }
+
+    pub fn is_supported(link_type: LinkType) -> bool {
+        [LinkType::ETHERNET, LinkType::IPV4, LinkType::IPV6].contains(&link_type)
+    }
+
+    /// Addresses of the Ethernet, ARP, IPv4 & IPv6 headers are replaced in place. So are
+    /// the addresses & names of the payloads: DNS, DHCP, HTTP Host, NDP & the packets
+    /// inside the ICMP errors. DNS records, that can't be rewritten, are cleared.
+    /// Lengths are kept, the checksums of IPv4, TCP, UDP, ICMP & ICMPv6 are updated.
+    pub fn frame(&mut self, link_type: LinkType, data: &mut [u8]) {
+        match link_type {
+            LinkType::ETHERNET => self.ethernet(data),
+            LinkType::IPV4 => self.ipv4_packet(data, 0, false),
+            LinkType::IPV6 => self.ipv6_packet(data, 0, false),
+            _ => {},
+        }
+    }
+
+    /// Parsed layers are replaced as the bytes of the frame: addresses of the Ethernet,
+    /// 802.11, ARP, IPv4, IPv6 & DHCP, names & addresses of DNS and the HTTP Host.
+    /// Other layers are kept.
+    pub fn metadata(&mut self, metadata: &mut FrameMetadataDto) {
+        for layer in &mut metadata.layers {
+            match layer {
+                ProtocolDto::Ethernet(ethernet) => {
+                    ethernet.destination_mac = self.mac(&ethernet.destination_mac);
+                    ethernet.source_mac = self.mac(&ethernet.source_mac);
+                },
+                ProtocolDto::IEEE80211(frame) => {
+                    frame.receiver = self.mac(&frame.receiver);
+                    frame.destination = self.mac(&frame.destination);
+                    for mac in
+                        [&mut frame.transmitter, &mut frame.source, &mut frame.bssid]
+                            .into_iter()
+                            .flatten()
+                    {
+                        *mac = self.mac(mac);
+                    }
+                },
+                ProtocolDto::Arp(arp) => {
+                    for hardware in [&mut arp.sender_hardware, &mut arp.target_hardware] {
+                        if let HardwareAddress::Mac(mac) = hardware {
+                            *mac = self.mac(mac);
+                        }
+                    }
+                    for protocol in [&mut arp.sender_protocol, &mut arp.target_protocol] {
+                        if let ProtocolAddress::IPv4(ip) = protocol {
+                            *ip = self.ipv4(*ip);
+                        }
+                    }
+                },
+                ProtocolDto::IPv4(ip) => {
+                    ip.address_source = self.ipv4(ip.address_source);
+                    ip.address_destination = self.ipv4(ip.address_destination);
+                },
+                ProtocolDto::IPv6(ip) => {
+                    ip.address_source = self.ipv6(ip.address_source);
+                    ip.address_destination = self.ipv6(ip.address_destination);
+                },
+                ProtocolDto::DHCPv4(dhcp) => {
+                    for ip in [
+                        &mut dhcp.old_client_address,
+                        &mut dhcp.new_client_address,
+                        &mut dhcp.server_address,
+                        &mut dhcp.relay_agent_address,
+                    ] {
+                        // Unset addresses are zeros
+                        if !ip.is_unspecified() {
+                            *ip = self.ipv4(*ip);
+                        }
+                    }
+                    dhcp.hardware_address_client =
+                        self.mac(&dhcp.hardware_address_client);
+                },
+                ProtocolDto::DNS(dns) => self.dns_records(dns),
+                ProtocolDto::HTTP(HttpDto::Request(request)) => {
+                    self.http_headers(&mut request.headers)
+                },
+                _ => {},
+            }
+        }
+    }
+
+    fn dns_records(&mut self, dns: &mut DnsDto) {
+        for question in &mut dns.question_section {
+            question.name = self.hostname(&question.name);
+        }
+        let records = dns
+            .answer_section
+            .iter_mut()
+            .chain(dns.authority_section.iter_mut())
+            .chain(dns.additional_section.iter_mut());
+        for record in records {
+            record.name = self.hostname(&record.name);
+            match &mut record.data {
+                DnsTypeData::AIPv4(ip) => *ip = self.ipv4(*ip),
+                DnsTypeData::AIPv6(ip) | DnsTypeData::AAAA(ip) => *ip = self.ipv6(*ip),
+                DnsTypeData::CNAME(name) | DnsTypeData::NS(name) => {
+                    *name = self.hostname(name)
+                },
+                DnsTypeData::SOA {
+                    primary_name_server,
+                    mailbox,
+                    ..
+                } => {
+                    *primary_name_server = self.hostname(primary_name_server);
+                    *mailbox = self.hostname(mailbox);
+                },
+                DnsTypeData::Opt { .. } | DnsTypeData::Unknown => {},
+            }
+        }
+    }
+
+    // Host with the optional port, IPv6 address is in the brackets
+    fn http_headers(&mut self, headers: &mut [Header]) {
+        for (name, value) in headers {
+            if !name.eq_ignore_ascii_case("host") {
+                continue;
+            }
+            let (host, port) = match value.strip_prefix('[') {
+                Some(inner) => inner.split_once(']').unwrap_or((inner, "")),
+                None => value.split_at(value.find(':').unwrap_or(value.len())),
+            };
+            let host = match host.parse::<IpAddr>() {
+                Ok(IpAddr::V6(ip)) => format!("[{}]", self.ipv6(ip)),
+                Ok(ip) => self.ip(ip).to_string(),
+                Err(_) => self.hostname(host),
+            };
+            *value = format!("{host}{port}");
+        }
+    }
+
+    fn ethernet(&mut self, data: &mut [u8]) {
+        if data.len() < ETHERNET_HEADER_BYTES {
+            return;
+        }
+        self.replace_mac(data, 0);
+        self.replace_mac(data, 6);
+
+        let mut offset = 12;
+        let mut ether_type = read_u16(data, offset);
+        // Single VLAN tag
+        if ether_type == Some(ETHER_TYPE_VLAN) {
+            offset += 4;
+            ether_type = read_u16(data, offset);
+        }
+        let payload = offset + 2;
+        match ether_type {
+            Some(ETHER_TYPE_IPV4) => self.ipv4_packet(data, payload, false),
+            Some(ETHER_TYPE_IPV6) => self.ipv6_packet(data, payload, false),
+            Some(ETHER_TYPE_ARP) => self.arp(data, payload),
+            _ => {},
+        }
+    }
+
+    // Ethernet & IPv4 only, other ARP packets are left as they are
+    fn arp(&mut self, data: &mut [u8], offset: usize) {
+        let is_ipv4 =
+            data.get(offset..offset + 6) == Some(&[0x00, 0x01, 0x08, 0x00, 6, 4][..]);
+        if !is_ipv4 {
+            return;
+        }
+        self.replace_mac(data, offset + 8);
+        self.replace_ipv4(data, offset + 14);
+        self.replace_mac(data, offset + 18);
+        self.replace_ipv4(data, offset + 24);
+    }
+
+    // Packet inside the ICMP error is embedded, it's truncated by the sender
+    fn ipv4_packet(&mut self, data: &mut [u8], offset: usize, is_embedded: bool) {
+        let Some(&first) = data.get(offset) else {
+            return;
+        };
+        let header_length = usize::from(first & 0x0F) * 4;
+        if first >> 4 != 4 || header_length < 20 || data.len() < offset + header_length {
+            return;
+        }
+
+        let old: Vec<u8> = data[offset + 12..offset + 20].to_vec();
+        self.replace_ipv4(data, offset + 12);
+        self.replace_ipv4(data, offset + 16);
+        let new: Vec<u8> = data[offset + 12..offset + 20].to_vec();
+        update_checksum(data, offset + 10, &old, &new);
+
+        // Only the first fragment has the header of the transport
+        let fragment_offset = read_u16(data, offset + 6).unwrap_or(0) & 0x1FFF;
+        if fragment_offset != 0 {
+            return;
+        }
+        let transport = offset + header_length;
+        // Padding of the frame isn't a part of the packet
+        let total_length = usize::from(read_u16(data, offset + 2).unwrap_or(0));
+        let end = (offset + total_length).clamp(transport, data.len());
+        let protocol = data[offset + 9];
+        self.transport(data, transport..end, protocol, &old, &new, is_embedded);
+    }
+
+    // Extension headers aren't followed
+    fn ipv6_packet(&mut self, data: &mut [u8], offset: usize, is_embedded: bool) {
+        let Some(&first) = data.get(offset) else {
+            return;
+        };
+        if first >> 4 != 6 || data.len() < offset + 40 {
+            return;
+        }
+
+        let old: Vec<u8> = data[offset + 8..offset + 40].to_vec();
+        self.replace_ipv6(data, offset + 8);
+        self.replace_ipv6(data, offset + 24);
+        let new: Vec<u8> = data[offset + 8..offset + 40].to_vec();
+
+        let transport = offset + 40;
+        let payload_length = usize::from(read_u16(data, offset + 4).unwrap_or(0));
+        let end = (transport + payload_length).min(data.len());
+        let protocol = data[offset + 6];
+        self.transport(data, transport..end, protocol, &old, &new, is_embedded);
+    }
+
+    // Checksum is updated by the difference of the addresses in the pseudo header & of the
+    // rewritten segment. ICMP has no pseudo header
+    fn transport(
+        &mut self, data: &mut [u8], segment: Range<usize>, protocol: u8,
+        old_addresses: &[u8], new_addresses: &[u8], is_embedded: bool,
+    ) {
+        let old_segment = data[segment.clone()].to_vec();
+        self.segment(&mut data[segment.clone()], protocol, is_embedded);
+        let new_segment = data[segment.clone()].to_vec();
+
+        let old = [old_addresses, &old_segment].concat();
+        let new = [new_addresses, &new_segment].concat();
+        let start = segment.start;
+        match protocol {
+            IP_PROTOCOL_TCP => update_checksum(data, start + 16, &old, &new),
+            IP_PROTOCOL_UDP => update_udp_checksum(data, start + 6, &old, &new),
+            IP_PROTOCOL_ICMP => {
+                update_checksum(data, start + 2, &old_segment, &new_segment)
+            },
+            IP_PROTOCOL_ICMPV6 => update_checksum(data, start + 2, &old, &new),
+            _ => {},
+        }
+    }
+
+    fn segment(&mut self, segment: &mut [u8], protocol: u8, is_embedded: bool) {
+        let ports = [read_u16(segment, 0), read_u16(segment, 2)];
+        let has_port =
+            |expected: &[u16]| ports.iter().flatten().any(|port| expected.contains(port));
+        match protocol {
+            IP_PROTOCOL_UDP => {
+                let Some(payload) = segment.get_mut(8..) else {
+                    return;
+                };
+                if has_port(&[PORT_DNS, PORT_MDNS, PORT_LLMNR]) {
+                    self.dns(payload);
+                } else if has_port(&[PORT_DHCP_SERVER, PORT_DHCP_CLIENT]) {
+                    self.dhcp(payload);
+                }
+            },
+            IP_PROTOCOL_TCP => {
+                let data_offset =
+                    segment.get(12).map_or(0, |byte| usize::from(byte >> 4) * 4);
+                if data_offset < 20 || !has_port(&[PORT_HTTP, PORT_HTTP_ALT]) {
+                    return;
+                }
+                if let Some(payload) = segment.get_mut(data_offset..) {
+                    self.http(payload);
+                }
+            },
+            IP_PROTOCOL_ICMP => match segment.first().copied() {
+                Some(kind) if ICMP_ERRORS.contains(&kind) => {
+                    if kind == ICMP_REDIRECT {
+                        self.replace_ipv4(segment, 4);
+                    }
+                    // Errors aren't sent on the errors, such packet is cleared
+                    match is_embedded {
+                        true => clear(segment, 8..segment.len()),
+                        false => self.ipv4_packet(segment, 8, true),
+                    }
+                },
+                _ => {},
+            },
+            IP_PROTOCOL_ICMPV6 => match segment.first().copied() {
+                Some(kind) if ICMPV6_ERRORS.contains(&kind) => match is_embedded {
+                    true => clear(segment, 8..segment.len()),
+                    false => self.ipv6_packet(segment, 8, true),
+                },
+                Some(kind) => self.ndp(segment, kind),
+                None => {},
+            },
+            _ => {},
+        }
+    }
+
+    // Target addresses & the options of the Neighbor Discovery
+    fn ndp(&mut self, message: &mut [u8], kind: u8) {
+        let options = match kind {
+            NDP_ROUTER_SOLICITATION => 8,
+            NDP_ROUTER_ADVERTISEMENT => 16,
+            NDP_NEIGHBOR_SOLICITATION | NDP_NEIGHBOR_ADVERTISEMENT => {
+                self.replace_ipv6(message, 8);
+                24
+            },
+            NDP_REDIRECT => {
+                self.replace_ipv6(message, 8);
+                self.replace_ipv6(message, 24);
+                40
+            },
+            _ => return,
+        };
+
+        let mut offset = options;
+        while let Some(&[option, length]) = message.get(offset..offset + 2) {
+            // Length is in the units of 8 bytes, zero one is invalid
+            let size = usize::from(length) * 8;
+            if size == 0 {
+                return;
+            }
+            match option {
+                NDP_OPTION_SOURCE_LINK_LAYER | NDP_OPTION_TARGET_LINK_LAYER => {
+                    self.replace_mac(message, offset + 2)
+                },
+                NDP_OPTION_PREFIX => self.replace_ipv6(message, offset + 16),
+                NDP_OPTION_REDIRECTED_HEADER => clear(message, offset + 8..offset + size),
+                _ => {},
+            }
+            offset += size;
+        }
+    }
+
+    fn dns(&mut self, message: &mut [u8]) -> Option<()> {
+        let questions = read_u16(message, 4)?;
+        let records = [6, 8, 10]
+            .into_iter()
+            .map(|offset| read_u16(message, offset).map(usize::from))
+            .sum::<Option<usize>>()?;
+
+        let mut offset = DNS_HEADER_BYTES;
+        for _ in 0..questions {
+            // Type & class
+            offset = self.dns_name(message, offset)? + 4;
+        }
+        for _ in 0..records {
+            // Type, class, TTL & length of the data
+            let data = self.dns_name(message, offset)? + 10;
+            let record_type = read_u16(message, data - 10)?;
+            let length = usize::from(read_u16(message, data - 2)?);
+            offset = data + length;
+            // Data of the truncated record can't be read, so it's cleared
+            if offset > message.len() {
+                clear(message, data..offset);
+                return None;
+            }
+
+            match record_type {
+                DNS_TYPE_A if length == 4 => self.replace_ipv4(message, data),
+                DNS_TYPE_AAAA if length == 16 => self.replace_ipv6(message, data),
+                DNS_TYPE_NS | DNS_TYPE_CNAME | DNS_TYPE_PTR | DNS_TYPE_DNAME => {
+                    self.dns_name(message, data);
+                },
+                DNS_TYPE_MX => {
+                    self.dns_name(message, data + 2);
+                },
+                DNS_TYPE_SRV => {
+                    self.dns_name(message, data + 6);
+                },
+                DNS_TYPE_SOA => {
+                    if let Some(next) = self.dns_name(message, data) {
+                        self.dns_name(message, next);
+                    }
+                },
+                _ => clear(message, data..offset),
+            }
+        }
+
+        Some(())
+    }
+
+    // Labels are rewritten where they are, the compressed ones are rewritten at their
+    // place. Top-level label is kept, as of the hostnames. Returns the end of the name
+    fn dns_name(&self, message: &mut [u8], offset: usize) -> Option<usize> {
+        let mut labels = vec![];
+        let mut offset = offset;
+        let mut is_root_terminated = false;
+        let end = loop {
+            match message.get(offset).copied() {
+                Some(0) => {
+                    is_root_terminated = true;
+                    break Some(offset + 1);
+                },
+                Some(length) if length & 0xC0 == 0xC0 => break Some(offset + 2),
+                Some(length) if length & 0xC0 == 0 => {
+                    let start = offset + 1;
+                    offset = start + usize::from(length);
+                    labels.push(start..offset.min(message.len()));
+                },
+                _ => break None,
+            }
+        };
+
+        if is_root_terminated && labels.len() > 1 {
+            labels.pop();
+        }
+        for label in labels {
+            self.rewrite_label(&mut message[label]);
+        }
+
+        end
+    }
+
+    // Client is told by its hardware address & names. Server name & boot file are rarely
+    // used, so they're cleared
+    fn dhcp(&mut self, message: &mut [u8]) {
+        for offset in [12, 16, 20, 24] {
+            self.replace_ipv4(message, offset);
+        }
+        match message.get(1..3) == Some(&[0x01, 6][..]) {
+            true => self.replace_mac(message, 28),
+            false => clear(message, 28..44),
+        }
+        clear(message, 44..236);
+        if message.get(236..DHCP_OPTIONS_OFFSET) != Some(&DHCP_MAGIC_COOKIE[..]) {
+            return;
+        }
+
+        let mut offset = DHCP_OPTIONS_OFFSET;
+        while let Some(&option) = message.get(offset) {
+            match option {
+                DHCP_OPTION_PAD => {
+                    offset += 1;
+                    continue;
+                },
+                DHCP_OPTION_END => return,
+                _ => {},
+            }
+            let Some(&length) = message.get(offset + 1) else {
+                return;
+            };
+            let start = offset + 2;
+            offset = start + usize::from(length);
+            let value = start..offset.min(message.len());
+
+            match option {
+                DHCP_OPTION_ROUTER
+                | DHCP_OPTION_DNS_SERVER
+                | DHCP_OPTION_REQUESTED_IP
+                | DHCP_OPTION_SERVER_ID => {
+                    for address in value.step_by(4) {
+                        self.replace_ipv4(message, address);
+                    }
+                },
+                DHCP_OPTION_HOSTNAME | DHCP_OPTION_DOMAIN_NAME => {
+                    self.rewrite_name(&mut message[value])
+                },
+                // Hardware type & address
+                DHCP_OPTION_CLIENT_ID
+                    if length == 7 && message.get(start) == Some(&0x01) =>
+                {
+                    self.replace_mac(message, start + 1)
+                },
+                DHCP_OPTION_CLIENT_ID => clear(message, value),
+                // Flags & the codes of the result, then the name
+                DHCP_OPTION_CLIENT_FQDN => clear(message, start + 3..value.end),
+                _ => {},
+            }
+        }
+    }
+
+    // Request line & the headers are before the empty line, the body isn't read
+    fn http(&self, payload: &mut [u8]) {
+        let mut start = 0;
+        while start < payload.len() {
+            let end = payload[start..]
+                .iter()
+                .position(|byte| *byte == b'\n')
+                .map_or(payload.len(), |position| start + position);
+            let line = &mut payload[start..end];
+            let line = match line.last() {
+                Some(b'\r') => {
+                    let length = line.len() - 1;
+                    &mut line[..length]
+                },
+                _ => line,
+            };
+            if line.is_empty() {
+                return;
+            }
+            if line.len() > 5 && line[..5].eq_ignore_ascii_case(b"host:") {
+                let value = &mut line[5..];
+                let spaces = value.iter().take_while(|byte| **byte == b' ').count();
+                self.http_host(&mut value[spaces..]);
+            }
+            start = end + 1;
+        }
+    }
+
+    // Host with the optional port, IPv6 address is in the brackets
+    fn http_host(&self, value: &mut [u8]) {
+        match value.first() {
+            Some(b'[') => {
+                let end = value
+                    .iter()
+                    .position(|byte| *byte == b']')
+                    .unwrap_or(value.len());
+                self.rewrite_label(&mut value[1..end]);
+            },
+            _ => {
+                let end = value
+                    .iter()
+                    .position(|byte| *byte == b':')
+                    .unwrap_or(value.len());
+                self.rewrite_name(&mut value[..end]);
+            },
+        }
+    }
+
+    // Dotted name in place, of the same length. Top-level label is kept, if it isn't a part
+    // of the address
+    fn rewrite_name(&self, name: &mut [u8]) {
+        let mut labels: Vec<&mut [u8]> = name.split_mut(|byte| *byte == b'.').collect();
+        let is_top_kept = labels.len() > 1
+            && labels
+                .last()
+                .is_some_and(|label| !label.iter().all(u8::is_ascii_digit));
+        if is_top_kept {
+            labels.pop();
+        }
+        for label in labels {
+            self.rewrite_label(label);
+        }
+    }
+
+    // Same label gets the same pseudonym of its length
+    fn rewrite_label(&self, label: &mut [u8]) {
+        if label.is_empty() {
+            return;
+        }
+        let digest = self.digest(b"name", &label.to_ascii_lowercase());
+        let hex: Vec<u8> = digest
+            .iter()
+            .flat_map(|byte| format!("{byte:02x}").into_bytes())
+            .collect();
+        for (byte, pseudonym) in label.iter_mut().zip(hex.iter().cycle()) {
+            *byte = *pseudonym;
+        }
+    }
+
+    fn replace_mac(&self, data: &mut [u8], offset: usize) {
+        let Some(field) = data.get_mut(offset..offset + 6) else {
+            return;
+        };
+        let mut bytes = [0; 6];
+        bytes.copy_from_slice(field);
+        field.copy_from_slice(&self.mac(&MacAddress(bytes)).0);
+    }
+
+    fn replace_ipv4(&mut self, data: &mut [u8], offset: usize) {
+        let Some(field) = data.get_mut(offset..offset + 4) else {
+            return;
+        };
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(field);
+        field.copy_from_slice(&self.ipv4(Ipv4Addr::from(bytes)).octets());
+    }
+
+    fn replace_ipv6(&mut self, data: &mut [u8], offset: usize) {
+        let Some(field) = data.get_mut(offset..offset + 16) else {
+            return;
+        };
+        let mut bytes = [0; 16];
+        bytes.copy_from_slice(field);
+        field.copy_from_slice(&self.ipv6(Ipv6Addr::from(bytes)).octets());
+    }
+
+    // Loopback, multicast & broadcast addresses are the same in every network, they're kept
+    fn map_ipv4(&self, ip: Ipv4Addr) -> Ipv4Addr {
+        if ip.is_unspecified()
+            || ip.is_loopback()
+            || ip.is_multicast()
+            || ip.is_broadcast()
+        {
+            return ip;
+        }
+        let value = u128::from(u32::from(ip));
+        if !self.settings.preserve_private {
+            return Ipv4Addr::from(self.prefix_preserving(value, 32, 0) as u32);
+        }
+
+        match private_prefix_v4(ip) {
+            Some(kept) => Ipv4Addr::from(self.prefix_preserving(value, 32, kept) as u32),
+            None => {
+                let mapped = Ipv4Addr::from(self.prefix_preserving(value, 32, 0) as u32);
+                public_v4(mapped)
+            },
+        }
+    }
+
+    fn map_ipv6(&mut self, ip: Ipv6Addr) -> Ipv6Addr {
+        if let Some(ipv4) = ip.to_ipv4_mapped() {
+            return self.ipv4(ipv4).to_ipv6_mapped();
+        }
+        if ip.is_unspecified() || ip.is_loopback() || ip.is_multicast() {
+            return ip;
+        }
+        let value = u128::from(ip);
+        if !self.settings.preserve_private {
+            return Ipv6Addr::from(self.prefix_preserving(value, 128, 0));
+        }
+
+        match private_prefix_v6(ip) {
+            Some(kept) => Ipv6Addr::from(self.prefix_preserving(value, 128, kept)),
+            None => {
+                let mapped = Ipv6Addr::from(self.prefix_preserving(value, 128, 0));
+                public_v6(mapped)
+            },
+        }
+    }
+
+    // First `kept` bits of the value stay as they are
+    fn prefix_preserving(&self, value: u128, bits: u32, kept: u32) -> u128 {
+        let mut result = value;
+        for index in kept..bits {
+            let prefix = match index {
+                0 => 0,
+                _ => value >> (bits - index),
+            };
+            let mut data = [0; 18];
+            data[0] = bits as u8;
+            data[1] = index as u8;
+            data[2..].copy_from_slice(&prefix.to_be_bytes());
+            if self.digest(b"ip", &data)[0] & 0x01 == 0x01 {
+                result ^= 1 << (bits - 1 - index);
+            }
+        }
+
+        result
+    }
+
+    fn label(&self, label: &str) -> String {
+        let digest = self.digest(b"name", label.as_bytes());
+        let hex: String = digest[..4]
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+
+        format!("n{hex}")
+    }
+
+    // Domain separates the kinds of the values, so the same bytes map differently
+    fn digest(&self, domain: &[u8], value: &[u8]) -> [u8; 32] {
+        let mut data = Vec::with_capacity(domain.len() + value.len() + 1);
+        data.extend_from_slice(domain);
+        data.push(b':');
+        data.extend_from_slice(value);
+
+        cryptography::keyed_digest(&self.key, &data)
+    }
+}
+
+// Bits of the private range, that are kept
+fn private_prefix_v4(ip: Ipv4Addr) -> Option<u32> {
+    match ip.octets() {
+        [10, ..] => Some(8),
+        [172, second, ..] if second & 0xF0 == 16 => Some(12),
+        [192, 168, ..] => Some(16),
+        [169, 254, ..] => Some(16),
+        [100, second, ..] if second & 0xC0 == 64 => Some(10), // Shared (CGNAT)
+        _ => None,
+    }
+}
+
+fn private_prefix_v6(ip: Ipv6Addr) -> Option<u32> {
+    match ip.segments()[0] {
+        segment if segment & 0xFE00 == 0xFC00 => Some(7), // Unique local
+        segment if segment & 0xFFC0 == 0xFE80 => Some(10), // Link-local
+        _ => None,
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let first = ip.octets()[0];
+    private_prefix_v4(ip).is_none() && first != 0 && first != 127 && first < 224
+}
+
+// Public address, that got into the special ranges, is moved out of them by the first
+// bits. So it stays consistent with the other addresses of its subnet
+fn public_v4(mapped: Ipv4Addr) -> Ipv4Addr {
+    if is_public_v4(mapped) {
+        return mapped;
+    }
+    let value = u32::from(mapped);
+    let flipped = Ipv4Addr::from(value ^ 0x8000_0000);
+    match is_public_v4(flipped) {
+        true => flipped,
+        false => Ipv4Addr::from(value ^ 0xC000_0000),
+    }
+}
+
+fn public_v6(mapped: Ipv6Addr) -> Ipv6Addr {
+    let first = mapped.segments()[0];
+    let is_public = private_prefix_v6(mapped).is_none()
+        && first & 0xFF00 != 0x0000
+        && first & 0xFF00 != 0xFF00;
+    match is_public {
+        true => mapped,
+        false => Ipv6Addr::from(u128::from(mapped) ^ (1 << 127)),
+    }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+fn clear(data: &mut [u8], range: Range<usize>) {
+    let end = range.end.min(data.len());
+    if let Some(field) = data.get_mut(range.start..end) {
+        field.fill(0);
+    }
+}
+
+// Checksum is updated by the difference of the words (RFC 1624), so the truncated
+// segments stay consistent. Odd byte at the end is padded
+fn update_checksum(data: &mut [u8], offset: usize, old: &[u8], new: &[u8]) {
+    let Some(checksum) = read_u16(data, offset) else {
+        return;
+    };
+    let word = |bytes: &[u8]| u16::from_be_bytes([bytes[0], *bytes.get(1).unwrap_or(&0)]);
+    let mut sum = u64::from(!checksum);
+    for (old, new) in old.chunks(2).zip(new.chunks(2)) {
+        sum += u64::from(!word(old));
+        sum += u64::from(word(new));
+    }
+    while sum >> 16 != 0 {
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+
+    data[offset..offset + 2].copy_from_slice(&(!(sum as u16)).to_be_bytes());
+}
+
+// Zero checksum of UDP is "not computed", it stays so. Computed zero is sent as all ones
+fn update_udp_checksum(data: &mut [u8], offset: usize, old: &[u8], new: &[u8]) {
+    if read_u16(data, offset).is_none_or(|checksum| checksum == 0) {
+        return;
+    }
+    update_checksum(data, offset, old, new);
+    if read_u16(data, offset) == Some(0) {
+        data[offset..offset + 2].copy_from_slice(&[0xFF, 0xFF]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dpi::dto::frame::{FrameDirection, FrameHeader};
+    use dpi::protocols::dns::{
+        Class, DnsType, MessageType, OperationCode, QuestionEntry, ResourceRecord,
+        ResponseCode,
+    };
+    use dpi::protocols::ethernet::EthernetDto;
+    use dpi::protocols::http::{HTTPRequestDto, Methods};
+    use dpi::protocols::ip::checksum::ChecksumStatus;
+    use dpi::protocols::ipv4::IPv4Dto;
+
+    const MAC_HOST: [u8; 6] = [0x00, 0x1A, 0x8C, 0x10, 0xAD, 0x30];
+    const MAC_GATEWAY: [u8; 6] = [0x00, 0x1E, 0x68, 0x51, 0x4F, 0xA9];
+    const IP_HOST: Ipv4Addr = Ipv4Addr::new(192, 168, 1, 10);
+    const IP_SERVER: Ipv4Addr = Ipv4Addr::new(93, 184, 216, 34);
+    const HTTP_REQUEST: &[u8] =
+        b"GET / HTTP/1.1\r\nHost: fileserver.corp.local:8080\r\nAccept: */*\r\n\r\n";
+
+    fn anonymizer(preserve_oui: bool, preserve_private: bool) -> Anonymizer {
+        Anonymizer::new(AnonymizeSettings {
+            preserve_oui,
+            preserve_private,
+        })
+    }
+
+    // One's complement sum of the words
+    fn sum(data: &[u8]) -> u32 {
+        let mut sum: u32 = data
+            .chunks(2)
+            .map(|word| (u32::from(word[0]) << 8) | u32::from(*word.get(1).unwrap_or(&0)))
+            .sum();
+        while sum >> 16 != 0 {
+            sum = (sum & 0xFFFF) + (sum >> 16);
+        }
+        sum
+    }
+
+    // Ethernet + IPv4 with the valid checksums
+    fn ipv4_frame(protocol: u8, mut segment: Vec<u8>) -> Vec<u8> {
+        let mut frame = vec![];
+        frame.extend_from_slice(&MAC_GATEWAY);
+        frame.extend_from_slice(&MAC_HOST);
+        frame.extend_from_slice(&ETHER_TYPE_IPV4.to_be_bytes());
+
+        let mut ip = vec![0x45, 0x00];
+        ip.extend_from_slice(&((20 + segment.len()) as u16).to_be_bytes());
+        ip.extend_from_slice(&[0x12, 0x34, 0x00, 0x00, 64, protocol, 0, 0]);
+        ip.extend_from_slice(&IP_HOST.octets());
+        ip.extend_from_slice(&IP_SERVER.octets());
+        let checksum = !(sum(&ip) as u16);
+        ip[10..12].copy_from_slice(&checksum.to_be_bytes());
+
+        let offset = match protocol {
+            IP_PROTOCOL_TCP => 16,
+            IP_PROTOCOL_UDP => 6,
+            _ => 2,
+        };
+        let checksum = !(transport_sum(&ip, protocol, &segment) as u16);
+        segment[offset..offset + 2].copy_from_slice(&checksum.to_be_bytes());
+
+        frame.extend_from_slice(&ip);
+        frame.extend_from_slice(&segment);
+        frame
+    }
+
+    // ICMP has no pseudo header
+    fn transport_sum(ip: &[u8], protocol: u8, segment: &[u8]) -> u32 {
+        let mut data = vec![];
+        if protocol != IP_PROTOCOL_ICMP {
+            data.extend_from_slice(&ip[12..20]);
+            data.extend_from_slice(&[0, protocol]);
+            data.extend_from_slice(&(segment.len() as u16).to_be_bytes());
+        }
+        data.extend_from_slice(segment);
+        sum(&data)
+    }
+
+    fn assert_checksums(frame: &[u8]) {
+        let ip = &frame[14..34];
+        assert_eq!(sum(ip), 0xFFFF);
+        assert_eq!(transport_sum(ip, ip[9], &frame[34..]), 0xFFFF);
+    }
+
+    fn udp(source: u16, destination: u16, payload: &[u8]) -> Vec<u8> {
+        let mut udp = vec![];
+        udp.extend_from_slice(&source.to_be_bytes());
+        udp.extend_from_slice(&destination.to_be_bytes());
+        udp.extend_from_slice(&((8 + payload.len()) as u16).to_be_bytes());
+        udp.extend_from_slice(&[0, 0]);
+        udp.extend_from_slice(payload);
+        udp
+    }
+
+    fn tcp(source: u16, destination: u16, payload: &[u8]) -> Vec<u8> {
+        let mut tcp = vec![];
+        tcp.extend_from_slice(&source.to_be_bytes());
+        tcp.extend_from_slice(&destination.to_be_bytes());
+        tcp.extend_from_slice(&[0, 0, 0, 1, 0, 0, 0, 1, 0x50, 0x18, 0xFF, 0xFF]);
+        tcp.extend_from_slice(&[0, 0, 0, 0]);
+        tcp.extend_from_slice(payload);
+        tcp
+    }
+
+    // Response of "fileserver.corp.local", that is "nas01.corp.local" by the compressed
+    // names
+    fn dns_response() -> Vec<u8> {
+        let mut message = vec![0x12, 0x34, 0x81, 0x80, 0, 1, 0, 2, 0, 0, 0, 0];
+        message.extend_from_slice(b"\x0afileserver\x04corp\x05local\x00");
+        message.extend_from_slice(&[0, 1, 0, 1]);
+        message.extend_from_slice(&[0xC0, 0x0C, 0, 5, 0, 1, 0, 0, 0x0E, 0x10, 0, 8]);
+        message.extend_from_slice(b"\x05nas01\xC0\x17");
+        message.extend_from_slice(&[0xC0, 0x33, 0, 1, 0, 1, 0, 0, 0x0E, 0x10, 0, 4]);
+        message.extend_from_slice(&IP_HOST.octets());
+        message
+    }
+
+    fn dhcp_ack() -> Vec<u8> {
+        let mut message = vec![2, 1, 6, 0, 0x12, 0x34, 0x56, 0x78, 0, 0, 0, 0];
+        message.extend_from_slice(&[0; 4]);
+        message.extend_from_slice(&IP_HOST.octets());
+        message.extend_from_slice(&IP_SERVER.octets());
+        message.extend_from_slice(&[0; 4]);
+        message.extend_from_slice(&MAC_HOST);
+        message.extend_from_slice(&[0; 10]);
+        // Server name
+        message.extend_from_slice(b"fileserver");
+        message.resize(236, 0);
+        message.extend_from_slice(&DHCP_MAGIC_COOKIE);
+        message.extend_from_slice(&[53, 1, 5, DHCP_OPTION_SERVER_ID, 4]);
+        message.extend_from_slice(&IP_SERVER.octets());
+        message.extend_from_slice(&[DHCP_OPTION_HOSTNAME, 10]);
+        message.extend_from_slice(b"fileserver");
+        message.extend_from_slice(&[DHCP_OPTION_DOMAIN_NAME, 10]);
+        message.extend_from_slice(b"corp.local");
+        message.extend_from_slice(&[DHCP_OPTION_CLIENT_ID, 7, 0x01]);
+        message.extend_from_slice(&MAC_HOST);
+        message.push(DHCP_OPTION_END);
+        message
+    }
+
+    // Port unreachable, of the DNS query
+    fn icmp_error() -> Vec<u8> {
+        let query = ipv4_frame(IP_PROTOCOL_UDP, udp(50000, PORT_DNS, &dns_response()));
+        let mut icmp = vec![3, 3, 0, 0, 0, 0, 0, 0];
+        icmp.extend_from_slice(&query[14..14 + 28]);
+        ipv4_frame(IP_PROTOCOL_ICMP, icmp)
+    }
+
+    fn contains(data: &[u8], needle: &[u8]) -> bool {
+        data.windows(needle.len()).any(|window| window == needle)
+    }
+
+    #[test]
+    fn test_consistency() {
+        // Every bit is pseudonymized, so the address isn't mapped to itself by chance
+        let mut first = anonymizer(false, false);
+        let mut second = anonymizer(false, false);
+        let ips = [
+            IP_HOST,
+            IP_SERVER,
+            Ipv4Addr::new(10, 0, 0, 1),
+            Ipv4Addr::new(172, 16, 5, 4),
+        ];
+        for ip in ips {
+            let mapped = first.ipv4(ip);
+            assert_ne!(mapped, ip);
+            assert_eq!(first.ipv4(ip), mapped);
+        }
+        // Key is random, so other export gets the other pseudonyms
+        let mapped_first: Vec<Ipv4Addr> = ips.iter().map(|ip| first.ipv4(*ip)).collect();
+        let mapped_second: Vec<Ipv4Addr> =
+            ips.iter().map(|ip| second.ipv4(*ip)).collect();
+        assert_ne!(mapped_first, mapped_second);
+
+        assert_eq!(
+            first.hostname("NAS.corp.local"),
+            first.hostname("nas.corp.local.")
+        );
+        let mac = MacAddress(MAC_HOST);
+        assert_eq!(first.mac(&mac), first.mac(&mac));
+    }
+
+    #[test]
+    fn test_prefix_preserving() {
+        let mut anonymizer = anonymizer(false, true);
+        let first = anonymizer.ipv4(IP_HOST);
+        let second = anonymizer.ipv4(Ipv4Addr::new(192, 168, 1, 200));
+        let other = anonymizer.ipv4(Ipv4Addr::new(192, 168, 7, 1));
+        // Same /24, private range is kept
+        assert_eq!(first.octets()[..3], second.octets()[..3]);
+        assert_eq!(first.octets()[..2], [192, 168]);
+        assert_eq!(other.octets()[..2], [192, 168]);
+        assert_ne!(first.octets()[..3], other.octets()[..3]);
+
+        for public in [
+            IP_SERVER,
+            Ipv4Addr::new(8, 8, 8, 8),
+            Ipv4Addr::new(1, 1, 1, 1),
+        ] {
+            assert!(is_public_v4(anonymizer.ipv4(public)));
+        }
+        let ula: Ipv6Addr = "fd12:3456:789a::10".parse().unwrap();
+        assert_eq!(anonymizer.ipv6(ula).segments()[0] & 0xFE00, 0xFC00);
+        let global: Ipv6Addr = "2001:db8:85a3::8a2e:370:7334".parse().unwrap();
+        assert!(private_prefix_v6(anonymizer.ipv6(global)).is_none());
+
+        // Special addresses don't tell anything of the network
+        let broadcast = Ipv4Addr::BROADCAST;
+        assert_eq!(anonymizer.ipv4(broadcast), broadcast);
+    }
+
+    #[test]
+    fn test_mac_and_hostname() {
+        let mac = MacAddress(MAC_HOST);
+        let with_oui = anonymizer(true, true).mac(&mac);
+        assert_eq!(with_oui.0[..3], MAC_HOST[..3]);
+        assert_ne!(with_oui, mac);
+
+        let anonymizer = anonymizer(false, true);
+        let without_oui = anonymizer.mac(&mac);
+        assert_eq!(without_oui.0[0] & 0x03, 0x02);
+        let broadcast = MacAddress([0xFF; 6]);
+        assert_eq!(anonymizer.mac(&broadcast), broadcast);
+        assert_eq!(anonymizer.vendor(Some(&"Cisco".to_string())), None);
+
+        let name = anonymizer.hostname("fileserver.corp.local");
+        assert!(name.ends_with(".local"));
+        assert!(!name.contains("fileserver") && !name.contains("corp"));
+        let sibling = anonymizer.hostname("printer.corp.local");
+        // Same domain, same pseudonym of it
+        assert_eq!(
+            name.split_once('.').unwrap().1,
+            sibling.split_once('.').unwrap().1
+        );
+    }
+
+    #[test]
+    fn test_frame() {
+        let mut anonymizer = anonymizer(false, false);
+        let frames = [
+            ipv4_frame(IP_PROTOCOL_UDP, udp(50000, 9, b"anonymized")),
+            ipv4_frame(IP_PROTOCOL_UDP, udp(PORT_DNS, 50000, &dns_response())),
+            ipv4_frame(
+                IP_PROTOCOL_UDP,
+                udp(PORT_DHCP_SERVER, PORT_DHCP_CLIENT, &dhcp_ack()),
+            ),
+            ipv4_frame(IP_PROTOCOL_TCP, tcp(50000, PORT_HTTP, HTTP_REQUEST)),
+            icmp_error(),
+        ];
+        let frames: Vec<Vec<u8>> = frames
+            .into_iter()
+            .map(|original| {
+                let mut frame = original.clone();
+                anonymizer.frame(LinkType::ETHERNET, &mut frame);
+                assert_eq!(frame.len(), original.len());
+                frame
+            })
+            .collect();
+
+        // Nothing of the original addresses & names is left, checksums stay valid
+        for frame in &frames {
+            for original in [
+                &MAC_HOST[..],
+                &MAC_GATEWAY,
+                &IP_HOST.octets(),
+                &IP_SERVER.octets(),
+                b"fileserver",
+                b"corp",
+                b"nas01",
+            ] {
+                assert!(!contains(frame, original));
+            }
+            assert_checksums(frame);
+        }
+
+        let (other, dns, http, icmp) = (&frames[0], &frames[1], &frames[3], &frames[4]);
+        assert!(contains(other, b"anonymized"));
+        // Top-level label is kept, the address is of the IP header
+        assert!(contains(dns, b"local"));
+        assert!(contains(dns, &anonymizer.ipv4(IP_HOST).octets()));
+        assert!(contains(http, b"Host: "));
+        assert!(contains(http, b".local:8080\r\n"));
+        // Header inside the error is valid too
+        assert_eq!(sum(&icmp[42..62]), 0xFFFF);
+    }
+
+    #[test]
+    fn test_ndp_frame() {
+        let host: Ipv6Addr = "fe80::21a:8cff:fe10:ad30".parse().unwrap();
+        let gateway: Ipv6Addr = "fe80::21e:68ff:fe51:4fa9".parse().unwrap();
+        // Neighbor advertisement with the target link-layer address
+        let mut icmp = vec![NDP_NEIGHBOR_ADVERTISEMENT, 0, 0, 0, 0x60, 0, 0, 0];
+        icmp.extend_from_slice(&host.octets());
+        icmp.extend_from_slice(&[NDP_OPTION_TARGET_LINK_LAYER, 1]);
+        icmp.extend_from_slice(&MAC_HOST);
+
+        let mut ip = vec![0x60, 0, 0, 0];
+        ip.extend_from_slice(&(icmp.len() as u16).to_be_bytes());
+        ip.extend_from_slice(&[IP_PROTOCOL_ICMPV6, 255]);
+        ip.extend_from_slice(&host.octets());
+        ip.extend_from_slice(&gateway.octets());
+        let checksum = !(icmpv6_sum(&ip, &icmp) as u16);
+        icmp[2..4].copy_from_slice(&checksum.to_be_bytes());
+        let mut frame = ip;
+        frame.extend_from_slice(&icmp);
+
+        anonymizer(false, false).frame(LinkType::IPV6, &mut frame);
+        for original in [&MAC_HOST[..], &host.octets(), &gateway.octets()] {
+            assert!(!contains(&frame, original));
+        }
+        assert_eq!(icmpv6_sum(&frame[..40], &frame[40..]), 0xFFFF);
+    }
+
+    fn icmpv6_sum(ip: &[u8], icmp: &[u8]) -> u32 {
+        let mut data = ip[8..40].to_vec();
+        data.extend_from_slice(&(icmp.len() as u32).to_be_bytes());
+        data.extend_from_slice(&[0, 0, 0, IP_PROTOCOL_ICMPV6]);
+        data.extend_from_slice(icmp);
+        sum(&data)
+    }
+
+    #[test]
+    fn test_metadata() {
+        let mut anonymizer = anonymizer(false, false);
+        let mut metadata = FrameMetadataDto {
+            header: FrameHeader::new(0),
+            layers: vec![
+                ProtocolDto::Ethernet(EthernetDto {
+                    destination_mac: MacAddress(MAC_GATEWAY),
+                    source_mac: MacAddress(MAC_HOST),
+                }),
+                ProtocolDto::IPv4(IPv4Dto {
+                    address_source: IP_HOST,
+                    address_destination: IP_SERVER,
+                    time_to_live: 64,
+                    checksum_status: ChecksumStatus::NotChecked,
+                    options: vec![],
+                }),
+                ProtocolDto::DNS(DnsDto {
+                    message_type: MessageType::Response,
+                    operation_code: OperationCode::StandardQuery,
+                    authoritative_answer: false,
+                    response_code: ResponseCode::NoErrorCondition,
+                    question_section: vec![QuestionEntry {
+                        name: "fileserver.corp.local".to_string(),
+                        entry_type: DnsType::A,
+                        class: Class::IN,
+                    }],
+                    answer_section: vec![ResourceRecord {
+                        name: "fileserver.corp.local".to_string(),
+                        record_type: DnsType::A,
+                        class: Some(Class::IN),
+                        time_to_live: 300,
+                        data_length: 4,
+                        data: DnsTypeData::AIPv4(IP_SERVER),
+                    }],
+                    authority_section: vec![],
+                    additional_section: vec![],
+                }),
+                ProtocolDto::HTTP(HttpDto::Request(HTTPRequestDto {
+                    method: Methods::GET,
+                    target: "/".to_string(),
+                    headers: vec![(
+                        "Host".to_string(),
+                        "fileserver.corp.local:8080".to_string(),
+                    )],
+                    body_anomaly: None,
+                })),
+            ],
+            direction: FrameDirection::Unknown,
+            is_truncated: false,
+        };
+        anonymizer.metadata(&mut metadata);
+
+        // Same pseudonyms, as of the other exports
+        let name = anonymizer.hostname("fileserver.corp.local");
+        let server = anonymizer.ipv4(IP_SERVER);
+        let ProtocolDto::Ethernet(ethernet) = &metadata.layers[0] else {
+            panic!();
+        };
+        assert_eq!(ethernet.source_mac, anonymizer.mac(&MacAddress(MAC_HOST)));
+        assert_ne!(ethernet.destination_mac, MacAddress(MAC_GATEWAY));
+        let ProtocolDto::IPv4(ip) = &metadata.layers[1] else {
+            panic!();
+        };
+        assert_eq!(ip.address_source, anonymizer.ipv4(IP_HOST));
+        assert_eq!(ip.address_destination, server);
+        let ProtocolDto::DNS(dns) = &metadata.layers[2] else {
+            panic!();
+        };
+        assert_eq!(dns.question_section[0].name, name);
+        assert_eq!(dns.answer_section[0].name, name);
+        assert_eq!(dns.answer_section[0].data, DnsTypeData::AIPv4(server));
+        let ProtocolDto::HTTP(HttpDto::Request(request)) = &metadata.layers[3] else {
+            panic!();
+        };
+        assert_eq!(request.headers[0].1, format!("{name}:8080"));
+    }
+}
@@ -0,0 +1,331 @@
+use crate::net::conflicts;
+use chrono::{DateTime, Duration, Local};
+use dpi::protocols::arp::{ArpDto, Operation, ProtocolAddress};
+use std::collections::HashMap;
+use std::net::Ipv4Addr;
+
+pub const DEFAULT_REPLY_WINDOW_MS: u32 = 1000;
+pub const DEFAULT_UNANSWERED_MIN_REQUESTS: u32 = 10;
+pub const DEFAULT_UNANSWERED_RATIO_PERCENT: u32 = 50;
+// Requesters, that are shown in the top
+pub const TOP_REQUESTERS: usize = 10;
+
+// Oldest pending requests are counted as unanswered beyond that
+const PENDING_LIMIT: usize = 4096;
+// New requesters aren't counted per address beyond that, only in the totals
+const REQUESTERS_LIMIT: usize = 4096;
+
+/// Pairs the ARP requests with their replies, keyed by the requester & the asked address. <br>
+/// Gratuitous ARP and probes don't wait for a reply, so they don't enter the pairing.
+#[derive(Default)]
+pub struct ArpPairing {
+    pub counters: ArpCounters,
+
+    // Requester & the asked address
+    pending: HashMap<(Ipv4Addr, Ipv4Addr), Pending>,
+    // Earliest moment, when any of the pending requests expires
+    next_expiry: Option<DateTime<Local>>,
+    // By the index of the ARP record
+    outcomes: HashMap<usize, Outcome>,
+    requesters: HashMap<Ipv4Addr, RequesterCounters>,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ArpCounters {
+    pub requests: u64,
+    pub answered: u64,
+    pub unanswered: u64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RequesterCounters {
+    pub requests: u64,
+    pub unanswered: u64,
+}
+
+impl RequesterCounters {
+    pub fn unanswered_percent(&self) -> f64 {
+        match self.requests {
+            0 => 0.0,
+            requests => self.unanswered as f64 * 100.0 / requests as f64,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    Answered(Duration),
+    Unanswered,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Pending {
+    sent: DateTime<Local>,
+    record: usize,
+}
+
+impl ArpPairing {
+    /// `record` is the index of the ARP record, the packet is stored with.
+    pub fn observe(
+        &mut self, arp: &ArpDto, captured: DateTime<Local>, window: Duration,
+        record: usize,
+    ) {
+        self.expire(captured, window);
+
+        if conflicts::gratuitous_arp(arp).is_some() {
+            return;
+        }
+        let (ProtocolAddress::IPv4(sender), ProtocolAddress::IPv4(target)) =
+            (&arp.sender_protocol, &arp.target_protocol)
+        else {
+            return;
+        };
+
+        match arp.operation {
+            // Probes (RFC 5227) have no sender address to answer to
+            Operation::Request if !sender.is_unspecified() => {
+                self.request((*sender, *target), captured, window, record)
+            },
+            // Reply is sent by the asked address to the requester
+            Operation::Reply => self.reply((*target, *sender), captured, window),
+            _ => {},
+        }
+    }
+
+    /// Requests without a reply within the window are counted as unanswered.
+    pub fn expire(&mut self, now: DateTime<Local>, window: Duration) {
+        if self.next_expiry.is_none_or(|expiry| now < expiry) {
+            return;
+        }
+
+        let expired: Vec<(Ipv4Addr, Ipv4Addr)> = self
+            .pending
+            .iter()
+            .filter(|(_, pending)| now - pending.sent > window)
+            .map(|(key, _)| *key)
+            .collect();
+        for key in expired {
+            if let Some(pending) = self.pending.remove(&key) {
+                self.unanswered(key.0, pending);
+            }
+        }
+        self.next_expiry = self
+            .pending
+            .values()
+            .map(|pending| pending.sent + window)
+            .min();
+    }
+
+    pub fn outcome(&self, record: usize) -> Option<&Outcome> {
+        self.outcomes.get(&record)
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Requesters with the unanswered ratio at least the threshold, the most unanswered first.
+    pub fn top_requesters(
+        &self, min_requests: u32, ratio_percent: u32,
+    ) -> Vec<(Ipv4Addr, RequesterCounters)> {
+        let mut top: Vec<(Ipv4Addr, RequesterCounters)> = self
+            .requesters
+            .iter()
+            .filter(|(_, counters)| {
+                counters.requests >= u64::from(min_requests)
+                    && counters.unanswered_percent() >= f64::from(ratio_percent)
+            })
+            .map(|(ip, counters)| (*ip, *counters))
+            .collect();
+        top.sort_by(|(first_ip, first), (second_ip, second)| {
+            second
+                .unanswered
+                .cmp(&first.unanswered)
+                .then(first_ip.cmp(second_ip))
+        });
+        top.truncate(TOP_REQUESTERS);
+
+        top
+    }
+
+    pub fn estimated_size(&self) -> usize {
+        self.pending.len() * size_of::<((Ipv4Addr, Ipv4Addr), Pending)>()
+            + self.outcomes.len() * size_of::<(usize, Outcome)>()
+            + self.requesters.len() * size_of::<(Ipv4Addr, RequesterCounters)>()
+    }
+
+    pub fn clear(&mut self) {
+        self.counters = ArpCounters::default();
+        self.pending.clear();
+        self.next_expiry = None;
+        self.outcomes.clear();
+        self.requesters.clear();
+    }
+
+    fn request(
+        &mut self, key: (Ipv4Addr, Ipv4Addr), sent: DateTime<Local>, window: Duration,
+        record: usize,
+    ) {
+        self.counters.requests = self.counters.requests.saturating_add(1);
+        if let Some(counters) = self.requester(key.0) {
+            counters.requests = counters.requests.saturating_add(1);
+        }
+
+        if !self.pending.contains_key(&key) && self.pending.len() >= PENDING_LIMIT {
+            let oldest = self
+                .pending
+                .iter()
+                .min_by_key(|(_, pending)| pending.sent)
+                .map(|(key, _)| *key);
+            if let Some((oldest, pending)) =
+                oldest.and_then(|oldest| self.pending.remove_entry(&oldest))
+            {
+                self.unanswered(oldest.0, pending);
+            }
+        }
+
+        // Retry means, that the previous request wasn't answered
+        if let Some(previous) = self.pending.insert(key, Pending { sent, record }) {
+            self.unanswered(key.0, previous);
+        }
+        let expiry = sent + window;
+        self.next_expiry = Some(
+            self.next_expiry
+                .map_or(expiry, |next_expiry| next_expiry.min(expiry)),
+        );
+    }
+
+    fn reply(
+        &mut self, key: (Ipv4Addr, Ipv4Addr), captured: DateTime<Local>, window: Duration,
+    ) {
+        // Replies without the request are unsolicited, they aren't counted
+        let Some(pending) = self.pending.remove(&key) else {
+            return;
+        };
+
+        let elapsed = captured - pending.sent;
+        match elapsed > window {
+            true => self.unanswered(key.0, pending),
+            false => {
+                self.counters.answered = self.counters.answered.saturating_add(1);
+                self.outcomes
+                    .insert(pending.record, Outcome::Answered(elapsed));
+            },
+        }
+    }
+
+    fn unanswered(&mut self, requester: Ipv4Addr, pending: Pending) {
+        self.counters.unanswered = self.counters.unanswered.saturating_add(1);
+        if let Some(counters) = self.requester(requester) {
+            counters.unanswered = counters.unanswered.saturating_add(1);
+        }
+        self.outcomes.insert(pending.record, Outcome::Unanswered);
+    }
+
+    fn requester(&mut self, ip: Ipv4Addr) -> Option<&mut RequesterCounters> {
+        let is_full = self.requesters.len() >= REQUESTERS_LIMIT;
+        if is_full && !self.requesters.contains_key(&ip) {
+            return None;
+        }
+        Some(self.requesters.entry(ip).or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dpi::protocols::arp::HardwareAddress;
+    use dpi::protocols::ethernet::mac::MacAddress;
+
+    const REQUESTER: Ipv4Addr = Ipv4Addr::new(192, 168, 1, 10);
+    const ASKED: Ipv4Addr = Ipv4Addr::new(192, 168, 1, 1);
+
+    fn arp(operation: Operation, sender: Ipv4Addr, target: Ipv4Addr) -> ArpDto {
+        ArpDto {
+            operation,
+            sender_hardware: HardwareAddress::Mac(MacAddress::from([
+                0x00, 0x1E, 0x68, 0x51, 0x4F, 0xA9,
+            ])),
+            sender_protocol: ProtocolAddress::IPv4(sender),
+            target_hardware: HardwareAddress::Mac(MacAddress::from([0x00; 6])),
+            target_protocol: ProtocolAddress::IPv4(target),
+        }
+    }
+
+    #[test]
+    fn test_matched_pair() {
+        let mut pairing = ArpPairing::default();
+        let window = Duration::milliseconds(1000);
+        let start = Local::now();
+
+        let request = arp(Operation::Request, REQUESTER, ASKED);
+        pairing.observe(&request, start, window, 0);
+        assert_eq!(pairing.pending(), 1);
+        assert_eq!(pairing.outcome(0), None);
+
+        let reply = arp(Operation::Reply, ASKED, REQUESTER);
+        pairing.observe(&reply, start + Duration::milliseconds(3), window, 1);
+        assert_eq!(pairing.pending(), 0);
+        assert_eq!(
+            pairing.outcome(0),
+            Some(&Outcome::Answered(Duration::milliseconds(3)))
+        );
+        assert_eq!(pairing.outcome(1), None);
+        assert_eq!(pairing.counters.requests, 1);
+        assert_eq!(pairing.counters.answered, 1);
+        assert_eq!(pairing.counters.unanswered, 0);
+    }
+
+    #[test]
+    fn test_unanswered_request() {
+        let mut pairing = ArpPairing::default();
+        let window = Duration::milliseconds(1000);
+        let start = Local::now();
+
+        let request = arp(Operation::Request, REQUESTER, ASKED);
+        pairing.observe(&request, start, window, 0);
+        pairing.expire(start + Duration::milliseconds(500), window);
+        assert_eq!(pairing.pending(), 1);
+
+        pairing.expire(start + Duration::milliseconds(1500), window);
+        assert_eq!(pairing.pending(), 0);
+        assert_eq!(pairing.outcome(0), Some(&Outcome::Unanswered));
+        assert_eq!(pairing.counters.unanswered, 1);
+
+        // Late reply isn't paired anymore
+        let reply = arp(Operation::Reply, ASKED, REQUESTER);
+        pairing.observe(&reply, start + Duration::milliseconds(2000), window, 1);
+        assert_eq!(pairing.counters.answered, 0);
+
+        assert_eq!(
+            pairing.top_requesters(1, 50),
+            vec![(
+                REQUESTER,
+                RequesterCounters {
+                    requests: 1,
+                    unanswered: 1,
+                }
+            )]
+        );
+        assert!(pairing.top_requesters(2, 50).is_empty());
+    }
+
+    #[test]
+    fn test_gratuitous_not_paired() {
+        let mut pairing = ArpPairing::default();
+        let window = Duration::milliseconds(1000);
+        let start = Local::now();
+
+        let gratuitous = arp(Operation::Request, REQUESTER, REQUESTER);
+        pairing.observe(&gratuitous, start, window, 0);
+        let probe = arp(Operation::Request, Ipv4Addr::UNSPECIFIED, ASKED);
+        pairing.observe(&probe, start, window, 1);
+        assert_eq!(pairing.pending(), 0);
+
+        pairing.expire(start + Duration::milliseconds(1500), window);
+        assert_eq!(pairing.outcome(0), None);
+        assert_eq!(pairing.outcome(1), None);
+        assert_eq!(pairing.counters.requests, 0);
+        assert_eq!(pairing.counters.unanswered, 0);
+    }
+}
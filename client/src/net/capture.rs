@@ -0,0 +1,184 @@
+use chrono::{DateTime, Local, TimeDelta};
+use common::messages::{CaptureStatsDto, StatisticsDto};
+
+#[derive(Default)]
+pub struct CaptureStats {
+    last: Option<CaptureStatsDto>,
+    last_updated: Option<DateTime<Local>>,
+
+    // Dropped since the previous poll
+    dropped_recently: u64,
+    // Dropped during the whole session, survives server capture restarts
+    dropped_total: u64,
+
+    // No packets on the server interface since then. Cleared when frames resume
+    idle_since: Option<DateTime<Local>>,
+
+    // Aggregates of the server, sent at the same interval
+    statistics: Option<StatisticsDto>,
+}
+
+impl CaptureStats {
+    pub fn update(&mut self, stats: CaptureStatsDto) {
+        let delta = match &self.last {
+            Some(previous) => Self::dropped_delta(previous, &stats),
+            None => Self::dropped(&stats),
+        };
+
+        // The first poll only sets the baseline, there's nothing to compare with.
+        self.dropped_recently = if self.last.is_some() { delta } else { 0 };
+        self.dropped_total = self.dropped_total.saturating_add(delta);
+        self.last = Some(stats);
+        self.last_updated = Some(Local::now());
+    }
+
+    pub fn is_dropping(&self) -> bool {
+        self.dropped_recently > 0
+    }
+
+    pub fn dropped_recently(&self) -> u64 {
+        self.dropped_recently
+    }
+
+    pub fn dropped_total(&self) -> u64 {
+        self.dropped_total
+    }
+
+    pub fn last(&self) -> Option<&CaptureStatsDto> {
+        self.last.as_ref()
+    }
+
+    pub fn last_updated(&self) -> Option<&DateTime<Local>> {
+        self.last_updated.as_ref()
+    }
+
+    pub fn set_idle(&mut self, seconds: u64) {
+        let idle = i64::try_from(seconds)
+            .ok()
+            .and_then(TimeDelta::try_seconds)
+            .unwrap_or_default();
+        let now = Local::now();
+        self.idle_since = Some(now.checked_sub_signed(idle).unwrap_or(now));
+    }
+
+    pub fn clear_idle(&mut self) {
+        self.idle_since = None;
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.idle_since.is_some()
+    }
+
+    pub fn idle_seconds(&self) -> Option<i64> {
+        self.idle_since
+            .map(|since| (Local::now() - since).num_seconds())
+    }
+
+    pub fn set_statistics(&mut self, dto: StatisticsDto) {
+        self.statistics = Some(dto);
+    }
+
+    pub fn statistics(&self) -> Option<&StatisticsDto> {
+        self.statistics.as_ref()
+    }
+
+    fn dropped(stats: &CaptureStatsDto) -> u64 {
+        u64::from(stats.dropped).saturating_add(u64::from(stats.if_dropped))
+    }
+
+    // Counters are reset when the server reopens the capture (reboot, interface change).
+    // In that case the current value is the amount dropped since the reset.
+    fn dropped_delta(previous: &CaptureStatsDto, current: &CaptureStatsDto) -> u64 {
+        let is_reset = current.received < previous.received
+            || current.dropped < previous.dropped
+            || current.if_dropped < previous.if_dropped;
+        if is_reset {
+            return Self::dropped(current);
+        }
+
+        Self::dropped(current).saturating_sub(Self::dropped(previous))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(received: u32, dropped: u32, if_dropped: u32) -> CaptureStatsDto {
+        CaptureStatsDto {
+            received,
+            dropped,
+            if_dropped,
+        }
+    }
+
+    #[test]
+    fn test_first_poll_is_baseline() {
+        let mut capture = CaptureStats::default();
+        capture.update(stats(1000, 20, 3));
+
+        assert!(!capture.is_dropping());
+        assert_eq!(capture.dropped_recently(), 0);
+        assert_eq!(capture.dropped_total(), 23);
+    }
+
+    #[test]
+    fn test_drops_increase() {
+        let mut capture = CaptureStats::default();
+        capture.update(stats(1000, 0, 0));
+        capture.update(stats(2000, 15, 2));
+
+        assert!(capture.is_dropping());
+        assert_eq!(capture.dropped_recently(), 17);
+        assert_eq!(capture.dropped_total(), 17);
+    }
+
+    #[test]
+    fn test_warning_cleared_without_new_drops() {
+        let mut capture = CaptureStats::default();
+        capture.update(stats(1000, 0, 0));
+        capture.update(stats(2000, 15, 0));
+        capture.update(stats(3000, 15, 0));
+
+        assert!(!capture.is_dropping());
+        assert_eq!(capture.dropped_recently(), 0);
+        assert_eq!(capture.dropped_total(), 15);
+    }
+
+    #[test]
+    fn test_counters_reset() {
+        let mut capture = CaptureStats::default();
+        capture.update(stats(1000, 0, 0));
+        capture.update(stats(5000, 40, 0));
+        // Server capture restarted
+        capture.update(stats(300, 5, 1));
+
+        assert!(capture.is_dropping());
+        assert_eq!(capture.dropped_recently(), 6);
+        assert_eq!(capture.dropped_total(), 46);
+    }
+
+    #[test]
+    fn test_idle() {
+        let mut capture = CaptureStats::default();
+        assert!(capture.idle_seconds().is_none());
+
+        capture.set_idle(30);
+        assert!(capture.is_idle());
+        assert!(capture.idle_seconds().is_some_and(|seconds| seconds >= 30));
+
+        // Frames resumed
+        capture.clear_idle();
+        assert!(!capture.is_idle());
+    }
+
+    #[test]
+    fn test_reset_without_drops() {
+        let mut capture = CaptureStats::default();
+        capture.update(stats(5000, 10, 0));
+        capture.update(stats(100, 0, 0));
+
+        assert!(!capture.is_dropping());
+        assert_eq!(capture.dropped_total(), 10);
+    }
+}
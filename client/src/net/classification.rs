@@ -0,0 +1,498 @@
+use dpi::protocols::dhcpv4::{DHCPv4Dto, OperationType};
+use dpi::protocols::dns::{DnsDto, MessageType};
+use dpi::protocols::ethernet::mac::Vendor;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+use strum_macros::EnumIter;
+
+// Device type is guessed by the traffic heuristics, the first matched rule wins.
+// So, rules are ordered from the strongest signals (advertised services) to the weakest (vendor).
+
+// DHCP option 55 lists (fingerbank.org)
+const ANDROID_FINGERPRINTS: &[&[u8]] = &[
+    &[1, 3, 6, 15, 26, 28, 51, 58, 59, 43],
+    &[1, 3, 6, 15, 26, 28, 51, 58, 59, 43, 114],
+    &[1, 3, 6, 15, 26, 28, 51, 58, 59, 43, 114, 108],
+];
+const WINDOWS_FINGERPRINTS: &[&[u8]] = &[
+    &[1, 3, 6, 15, 31, 33, 43, 44, 46, 47, 119, 121, 249, 252],
+    &[1, 3, 6, 15, 31, 33, 43, 44, 46, 47, 119, 121, 249, 252, 12],
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceType {
+    Computer,
+    Phone,
+    Printer,
+    Tv,
+    Iot,
+}
+
+impl DeviceType {
+    pub fn icon(&self) -> &'static str {
+        match self {
+            DeviceType::Computer => "💻",
+            DeviceType::Phone => "📱",
+            DeviceType::Printer => "🖶",
+            DeviceType::Tv => "📺",
+            DeviceType::Iot => "💡",
+        }
+    }
+}
+
+impl fmt::Display for DeviceType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            DeviceType::Computer => t!("Tab.Status.Devices.Type.Computer"),
+            DeviceType::Phone => t!("Tab.Status.Devices.Type.Phone"),
+            DeviceType::Printer => t!("Tab.Status.Devices.Type.Printer"),
+            DeviceType::Tv => t!("Tab.Status.Devices.Type.Tv"),
+            DeviceType::Iot => t!("Tab.Status.Devices.Type.Iot"),
+        };
+
+        write!(f, "{} {text}", self.icon())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+impl Confidence {
+    pub fn localized(&self) -> String {
+        match self {
+            Confidence::Low => t!("Tab.Status.Devices.Confidence.Low").to_string(),
+            Confidence::Medium => t!("Tab.Status.Devices.Confidence.Medium").to_string(),
+            Confidence::High => t!("Tab.Status.Devices.Confidence.High").to_string(),
+        }
+    }
+}
+
+/// Observed value, that triggered the rule.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Signal {
+    MdnsService(String),
+    DhcpVendorClass(String),
+    DhcpFingerprint(String),
+    Hostname(String),
+    Vendor(String),
+}
+
+impl Signal {
+    pub fn localized(&self) -> String {
+        match self {
+            Signal::MdnsService(value) => {
+                t!("Tab.Status.Devices.Signal.MdnsService", "value" = value).to_string()
+            },
+            Signal::DhcpVendorClass(value) => {
+                t!("Tab.Status.Devices.Signal.DhcpVendorClass", "value" = value)
+                    .to_string()
+            },
+            Signal::DhcpFingerprint(value) => {
+                t!("Tab.Status.Devices.Signal.DhcpFingerprint", "value" = value)
+                    .to_string()
+            },
+            Signal::Hostname(value) => {
+                t!("Tab.Status.Devices.Signal.Hostname", "value" = value).to_string()
+            },
+            Signal::Vendor(value) => {
+                t!("Tab.Status.Devices.Signal.Vendor", "value" = value).to_string()
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Classification {
+    pub device_type: DeviceType,
+    pub confidence: Confidence,
+    pub signal: Signal,
+}
+
+/// Signals of the device, collected from the DHCP & mDNS traffic.
+#[derive(Clone, Debug, Default)]
+pub struct DeviceSignals {
+    pub mdns_services: BTreeSet<String>,
+    pub dhcp_vendor_class: Option<String>,
+    pub dhcp_fingerprint: Vec<u8>,
+}
+
+impl DeviceSignals {
+    /// Client's options are taken from requests only, replies belong to the server.
+    pub fn observe_dhcp(&mut self, dto: &DHCPv4Dto) {
+        if dto.message_type != OperationType::BootRequest {
+            return;
+        }
+        if let Some(vendor_class) = &dto.vendor_class {
+            self.dhcp_vendor_class = Some(vendor_class.clone());
+        }
+        if !dto.parameter_request_list.is_empty() {
+            self.dhcp_fingerprint = dto.parameter_request_list.clone();
+        }
+    }
+
+    pub fn observe_mdns(&mut self, services: Vec<String>) {
+        self.mdns_services.extend(services);
+    }
+}
+
+/// Service types (e.g. `_googlecast._tcp`), advertised by the sender of the mDNS response.
+/// <br> Queries are skipped: the device looks for the service, but doesn't provide it.
+pub fn mdns_services(dto: &DnsDto) -> Vec<String> {
+    if dto.message_type != MessageType::Response {
+        return vec![];
+    }
+
+    let mut services = vec![];
+    for record in dto
+        .answer_section
+        .iter()
+        .chain(dto.additional_section.iter())
+    {
+        let name = record.name.to_ascii_lowercase();
+        let Some(name) = name.strip_suffix(".local") else {
+            continue;
+        };
+        // Instance name may contain dots, so the service is taken from the end
+        let labels = name.split('.').collect::<Vec<_>>();
+        if let [.., service, transport] = labels.as_slice() {
+            let is_service = service.starts_with('_')
+                && (*transport == "_tcp" || *transport == "_udp");
+            if is_service {
+                let service = format!("{service}.{transport}");
+                if !services.contains(&service) {
+                    services.push(service);
+                }
+            }
+        }
+    }
+
+    services
+}
+
+/// Everything known about the device.
+pub struct Evidence<'a> {
+    pub signals: Option<&'a DeviceSignals>,
+    pub hostname: Option<&'a str>,
+    pub vendor: Option<&'a Vendor>,
+}
+
+impl Evidence<'_> {
+    fn mdns_service(&self, services: &[&str]) -> Option<Signal> {
+        self.signals?
+            .mdns_services
+            .iter()
+            .find(|service| services.contains(&service.as_str()))
+            .map(|service| Signal::MdnsService(service.clone()))
+    }
+
+    fn dhcp_vendor_class(&self, prefixes: &[&str]) -> Option<Signal> {
+        let vendor_class = self.signals?.dhcp_vendor_class.as_ref()?;
+        prefixes
+            .iter()
+            .any(|prefix| vendor_class.starts_with(prefix))
+            .then(|| Signal::DhcpVendorClass(vendor_class.clone()))
+    }
+
+    fn dhcp_fingerprint(&self, fingerprints: &[&[u8]]) -> Option<Signal> {
+        let fingerprint = &self.signals?.dhcp_fingerprint;
+        fingerprints.contains(&fingerprint.as_slice()).then(|| {
+            let list = fingerprint.iter().map(u8::to_string).collect::<Vec<_>>();
+            Signal::DhcpFingerprint(list.join(","))
+        })
+    }
+
+    fn hostname(&self, keywords: &[&str]) -> Option<Signal> {
+        let hostname = self.hostname?;
+        let lowercase = hostname.to_ascii_lowercase();
+        keywords
+            .iter()
+            .any(|keyword| lowercase.contains(keyword))
+            .then(|| Signal::Hostname(hostname.to_string()))
+    }
+
+    fn vendor(&self, keywords: &[&str]) -> Option<Signal> {
+        let vendor = self.vendor?;
+        let lowercase = vendor.full.to_ascii_lowercase();
+        keywords
+            .iter()
+            .any(|keyword| lowercase.contains(keyword))
+            .then(|| Signal::Vendor(vendor.full.clone()))
+    }
+}
+
+struct Rule {
+    device_type: DeviceType,
+    confidence: Confidence,
+    matcher: fn(&Evidence) -> Option<Signal>,
+}
+
+const RULES: &[Rule] = &[
+    Rule {
+        device_type: DeviceType::Tv,
+        confidence: Confidence::High,
+        matcher: |evidence| evidence.mdns_service(&["_googlecast._tcp"]),
+    },
+    Rule {
+        device_type: DeviceType::Printer,
+        confidence: Confidence::High,
+        matcher: |evidence| {
+            evidence.mdns_service(&[
+                "_ipp._tcp",
+                "_ipps._tcp",
+                "_printer._tcp",
+                "_pdl-datastream._tcp",
+            ])
+        },
+    },
+    Rule {
+        device_type: DeviceType::Iot,
+        confidence: Confidence::High,
+        matcher: |evidence| {
+            evidence.mdns_service(&[
+                "_hap._tcp",
+                "_hap._udp",
+                "_matter._tcp",
+                "_esphomelib._tcp",
+            ])
+        },
+    },
+    Rule {
+        device_type: DeviceType::Computer,
+        confidence: Confidence::High,
+        matcher: |evidence| evidence.dhcp_vendor_class(&["MSFT"]),
+    },
+    // AirPlay receivers are mostly TVs & set-top boxes, but macOS advertises it too
+    Rule {
+        device_type: DeviceType::Tv,
+        confidence: Confidence::Medium,
+        matcher: |evidence| {
+            evidence.mdns_service(&[
+                "_airplay._tcp",
+                "_raop._tcp",
+                "_androidtvremote2._tcp",
+            ])
+        },
+    },
+    Rule {
+        device_type: DeviceType::Phone,
+        confidence: Confidence::Medium,
+        matcher: |evidence| evidence.mdns_service(&["_apple-mobdev2._tcp"]),
+    },
+    // Android TVs send the same vendor class, so it's not certain
+    Rule {
+        device_type: DeviceType::Phone,
+        confidence: Confidence::Medium,
+        matcher: |evidence| evidence.dhcp_vendor_class(&["android-dhcp"]),
+    },
+    Rule {
+        device_type: DeviceType::Phone,
+        confidence: Confidence::Medium,
+        matcher: |evidence| evidence.dhcp_fingerprint(ANDROID_FINGERPRINTS),
+    },
+    Rule {
+        device_type: DeviceType::Computer,
+        confidence: Confidence::Medium,
+        matcher: |evidence| evidence.dhcp_fingerprint(WINDOWS_FINGERPRINTS),
+    },
+    // Busybox DHCP client of the embedded Linux
+    Rule {
+        device_type: DeviceType::Iot,
+        confidence: Confidence::Medium,
+        matcher: |evidence| evidence.dhcp_vendor_class(&["udhcp"]),
+    },
+    Rule {
+        device_type: DeviceType::Phone,
+        confidence: Confidence::Low,
+        matcher: |evidence| evidence.hostname(&["iphone", "android", "galaxy", "pixel"]),
+    },
+    Rule {
+        device_type: DeviceType::Tv,
+        confidence: Confidence::Low,
+        matcher: |evidence| {
+            evidence.hostname(&["chromecast", "roku", "appletv", "bravia"])
+        },
+    },
+    Rule {
+        device_type: DeviceType::Printer,
+        confidence: Confidence::Low,
+        matcher: |evidence| evidence.hostname(&["printer", "epson", "brother"]),
+    },
+    Rule {
+        device_type: DeviceType::Printer,
+        confidence: Confidence::Low,
+        matcher: |evidence| {
+            evidence.vendor(&["brother", "canon", "epson", "kyocera", "lexmark", "xerox"])
+        },
+    },
+    Rule {
+        device_type: DeviceType::Tv,
+        confidence: Confidence::Low,
+        matcher: |evidence| evidence.vendor(&["roku"]),
+    },
+    Rule {
+        device_type: DeviceType::Iot,
+        confidence: Confidence::Low,
+        matcher: |evidence| evidence.vendor(&["espressif", "tuya", "shelly"]),
+    },
+];
+
+pub fn classify(evidence: &Evidence) -> Option<Classification> {
+    RULES.iter().find_map(|rule| {
+        (rule.matcher)(evidence).map(|signal| Classification {
+            device_type: rule.device_type,
+            confidence: rule.confidence,
+            signal,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dpi::protocols::dns::{
+        Class, DnsType, DnsTypeData, OperationCode, ResourceRecord, ResponseCode,
+    };
+    use dpi::protocols::ethernet::mac::MacAddress;
+    use std::net::Ipv4Addr;
+
+    fn dhcp(vendor_class: Option<&str>, parameter_request_list: Vec<u8>) -> DHCPv4Dto {
+        DHCPv4Dto {
+            message_type: OperationType::BootRequest,
+            old_client_address: Ipv4Addr::UNSPECIFIED,
+            new_client_address: Ipv4Addr::UNSPECIFIED,
+            server_address: Ipv4Addr::UNSPECIFIED,
+            relay_agent_address: Ipv4Addr::UNSPECIFIED,
+            hardware_address_client: MacAddress::try_from("3C:5A:B4:1E:2F:01").unwrap(),
+            vendor_class: vendor_class.map(str::to_string),
+            parameter_request_list,
+        }
+    }
+
+    fn mdns(message_type: MessageType, names: &[&str]) -> DnsDto {
+        DnsDto {
+            message_type,
+            operation_code: OperationCode::StandardQuery,
+            authoritative_answer: true,
+            response_code: ResponseCode::NoErrorCondition,
+            question_section: vec![],
+            answer_section: names
+                .iter()
+                .map(|name| ResourceRecord {
+                    name: name.to_string(),
+                    record_type: DnsType::PTR,
+                    class: Some(Class::IN),
+                    time_to_live: 120,
+                    data_length: 0,
+                    data: DnsTypeData::Unknown,
+                })
+                .collect(),
+            authority_section: vec![],
+            additional_section: vec![],
+        }
+    }
+
+    fn evidence(signals: &DeviceSignals) -> Evidence {
+        Evidence {
+            signals: Some(signals),
+            hostname: None,
+            vendor: None,
+        }
+    }
+
+    #[test]
+    fn test_mdns_services() {
+        let response = mdns(
+            MessageType::Response,
+            &[
+                "_googlecast._tcp.local",
+                "Living.Room._googlecast._tcp.local",
+                "Living.Room.local",
+                "_printer._tcp.example.com",
+            ],
+        );
+        assert_eq!(mdns_services(&response), vec!["_googlecast._tcp"]);
+
+        let query = mdns(MessageType::Query, &["_googlecast._tcp.local"]);
+        assert!(mdns_services(&query).is_empty());
+    }
+
+    #[test]
+    fn test_mdns_rules() {
+        let mut signals = DeviceSignals::default();
+        signals.observe_mdns(vec!["_airplay._tcp".to_string()]);
+        let classification = classify(&evidence(&signals)).unwrap();
+        assert_eq!(classification.device_type, DeviceType::Tv);
+        assert_eq!(classification.confidence, Confidence::Medium);
+
+        // Stronger signal wins
+        signals.observe_mdns(vec!["_ipp._tcp".to_string()]);
+        let classification = classify(&evidence(&signals)).unwrap();
+        assert_eq!(classification.device_type, DeviceType::Printer);
+        assert_eq!(
+            classification.signal,
+            Signal::MdnsService("_ipp._tcp".to_string())
+        );
+    }
+
+    #[test]
+    fn test_dhcp_rules() {
+        let mut signals = DeviceSignals::default();
+        signals.observe_dhcp(&dhcp(Some("MSFT 5.0"), vec![1, 3, 6]));
+        let classification = classify(&evidence(&signals)).unwrap();
+        assert_eq!(classification.device_type, DeviceType::Computer);
+        assert_eq!(classification.confidence, Confidence::High);
+
+        let mut signals = DeviceSignals::default();
+        signals.observe_dhcp(&dhcp(None, vec![1, 3, 6, 15, 26, 28, 51, 58, 59, 43, 114]));
+        let classification = classify(&evidence(&signals)).unwrap();
+        assert_eq!(classification.device_type, DeviceType::Phone);
+        assert_eq!(
+            classification.signal,
+            Signal::DhcpFingerprint("1,3,6,15,26,28,51,58,59,43,114".to_string())
+        );
+
+        // Server's reply doesn't describe the client
+        let mut reply = dhcp(Some("MSFT 5.0"), vec![]);
+        reply.message_type = OperationType::BootReply;
+        let mut signals = DeviceSignals::default();
+        signals.observe_dhcp(&reply);
+        assert!(classify(&evidence(&signals)).is_none());
+    }
+
+    #[test]
+    fn test_weak_rules() {
+        let vendor = Vendor {
+            short: "Espressif".to_string(),
+            full: "Espressif Inc.".to_string(),
+        };
+        let classification = classify(&Evidence {
+            signals: None,
+            hostname: Some("Johns-iPhone"),
+            vendor: Some(&vendor),
+        })
+        .unwrap();
+        assert_eq!(classification.device_type, DeviceType::Phone);
+        assert_eq!(classification.confidence, Confidence::Low);
+
+        let classification = classify(&Evidence {
+            signals: None,
+            hostname: None,
+            vendor: Some(&vendor),
+        })
+        .unwrap();
+        assert_eq!(classification.device_type, DeviceType::Iot);
+
+        let unknown = Evidence {
+            signals: None,
+            hostname: Some("DESKTOP"),
+            vendor: None,
+        };
+        assert!(classify(&unknown).is_none());
+    }
+}
@@ -0,0 +1,275 @@
+use chrono::{DateTime, Duration, Local};
+use dpi::protocols::arp::{ArpDto, HardwareAddress, ProtocolAddress};
+use dpi::protocols::ethernet::mac::{MacAddress, Vendor};
+use std::collections::HashMap;
+use std::net::Ipv4Addr;
+
+pub const DEFAULT_WINDOW_SECONDS: u32 = 60;
+
+// Virtual router MAC addresses (VRRP, RFC 5798): 00:00:5E:00:01:{VRID}
+const VRRP_MAC_PREFIX: [u8; 5] = [0x00, 0x00, 0x5E, 0x00, 0x01];
+
+/// Detects IPv4 addresses used by several MAC addresses at the same time. <br>
+/// Fed with the source addresses of IP traffic, so ARP replies alone can't raise a conflict.
+#[derive(Default)]
+pub struct ConflictStorage {
+    pub list: Vec<IpConflict>,
+
+    sightings: HashMap<Ipv4Addr, Vec<Sighting>>,
+    // MAC addresses that claimed the address by gratuitous ARP
+    announcements: HashMap<Ipv4Addr, Vec<MacAddress>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Sighting {
+    pub mac: MacAddress,
+    pub vendor: Option<Vendor>,
+    pub first_seen: DateTime<Local>,
+    pub last_seen: DateTime<Local>,
+}
+
+#[derive(Clone, Debug)]
+pub struct IpConflict {
+    pub ip: Ipv4Addr,
+    pub previous: MacAddress,
+    pub current: MacAddress,
+    // Address taken over by gratuitous ARP between the same vendor or VRRP MACs
+    pub is_failover: bool,
+}
+
+impl IpConflict {
+    pub fn involves(&self, mac: &MacAddress) -> bool {
+        self.previous.eq(mac) || self.current.eq(mac)
+    }
+}
+
+impl ConflictStorage {
+    /// Records the address as used by the MAC. Returns conflicts found by this sighting.
+    pub fn observe(
+        &mut self, ip: Ipv4Addr, mac: &MacAddress, captured: DateTime<Local>,
+        window: Duration, vendor: impl FnOnce(&MacAddress) -> Option<Vendor>,
+    ) -> Vec<IpConflict> {
+        let sightings = self.sightings.entry(ip).or_default();
+        match sightings.iter_mut().find(|sighting| sighting.mac.eq(mac)) {
+            Some(sighting) => {
+                sighting.first_seen = sighting.first_seen.min(captured);
+                sighting.last_seen = sighting.last_seen.max(captured);
+            },
+            None => sightings.push(Sighting {
+                mac: mac.clone(),
+                vendor: vendor(mac),
+                first_seen: captured,
+                last_seen: captured,
+            }),
+        }
+
+        let Some(current) = sightings.iter().find(|sighting| sighting.mac.eq(mac)) else {
+            return vec![];
+        };
+
+        let mut found = vec![];
+        for other in sightings.iter() {
+            if other.mac.eq(mac) || (captured - other.last_seen).abs() > window {
+                continue;
+            }
+            let is_known = self.list.iter().any(|conflict| {
+                conflict.ip == ip
+                    && conflict.involves(mac)
+                    && conflict.involves(&other.mac)
+            });
+            if is_known {
+                continue;
+            }
+
+            let conflict = IpConflict {
+                ip,
+                previous: other.mac.clone(),
+                current: mac.clone(),
+                is_failover: Self::is_failover(
+                    self.announcements.get(&ip),
+                    other,
+                    current,
+                ),
+            };
+            self.list.push(conflict.clone());
+            found.push(conflict);
+        }
+
+        found
+    }
+
+    /// Remembers the sender of a gratuitous ARP as a legitimate owner of the address.
+    pub fn announce(&mut self, ip: Ipv4Addr, mac: &MacAddress) {
+        let macs = self.announcements.entry(ip).or_default();
+        if !macs.contains(mac) {
+            macs.push(mac.clone());
+        }
+    }
+
+    pub fn sighting(&self, ip: &Ipv4Addr, mac: &MacAddress) -> Option<&Sighting> {
+        self.sightings
+            .get(ip)?
+            .iter()
+            .find(|sighting| sighting.mac.eq(mac))
+    }
+
+    /// Conflicts that should be reported. Failovers are reported only if asked to.
+    pub fn alerts(&self, with_failover: bool) -> impl Iterator<Item = &IpConflict> {
+        self.list
+            .iter()
+            .filter(move |conflict| with_failover || !conflict.is_failover)
+    }
+
+    pub fn clear(&mut self) {
+        self.list.clear();
+        self.sightings.clear();
+        self.announcements.clear();
+    }
+
+    fn is_failover(
+        announcements: Option<&Vec<MacAddress>>, first: &Sighting, second: &Sighting,
+    ) -> bool {
+        let Some(announcements) = announcements else {
+            return false;
+        };
+        if !announcements.contains(&first.mac) && !announcements.contains(&second.mac) {
+            return false;
+        }
+
+        let is_same_vendor = first.vendor.is_some() && first.vendor == second.vendor;
+        is_same_vendor || is_vrrp(&first.mac) || is_vrrp(&second.mac)
+    }
+}
+
+pub fn is_vrrp(mac: &MacAddress) -> bool {
+    mac.0.starts_with(&VRRP_MAC_PREFIX)
+}
+
+/// Gratuitous ARP: the sender announces its own address (sender and target IP are equal).
+pub fn gratuitous_arp(arp: &ArpDto) -> Option<(Ipv4Addr, MacAddress)> {
+    match (
+        &arp.sender_hardware,
+        &arp.sender_protocol,
+        &arp.target_protocol,
+    ) {
+        (
+            HardwareAddress::Mac(mac),
+            ProtocolAddress::IPv4(sender),
+            ProtocolAddress::IPv4(target),
+        ) if sender == target => Some((*sender, mac.clone())),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mac(last: u8) -> MacAddress {
+        MacAddress::from([0x00, 0x1E, 0x68, 0x51, 0x4F, last])
+    }
+
+    fn vendor(name: &str) -> Option<Vendor> {
+        Some(Vendor {
+            short: name.to_string(),
+            full: name.to_string(),
+        })
+    }
+
+    #[test]
+    fn test_conflict_within_window() {
+        let mut storage = ConflictStorage::default();
+        let ip = Ipv4Addr::new(192, 168, 1, 10);
+        let window = Duration::seconds(60);
+        let start = Local::now();
+
+        let first = mac(0xA9);
+        let second = mac(0x30);
+
+        assert!(
+            storage
+                .observe(ip, &first, start, window, |_| None)
+                .is_empty()
+        );
+        assert!(
+            storage
+                .observe(ip, &first, start, window, |_| None)
+                .is_empty()
+        );
+
+        let found =
+            storage.observe(ip, &second, start + Duration::seconds(30), window, |_| {
+                vendor("Other")
+            });
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].previous, first);
+        assert_eq!(found[0].current, second);
+        assert!(!found[0].is_failover);
+
+        // Already known
+        let found =
+            storage.observe(ip, &first, start + Duration::seconds(40), window, |_| None);
+        assert!(found.is_empty());
+        assert_eq!(storage.alerts(false).count(), 1);
+
+        let seen = storage
+            .sighting(&ip, &first)
+            .map(|sighting| (sighting.first_seen, sighting.last_seen));
+        assert_eq!(seen, Some((start, start + Duration::seconds(40))));
+    }
+
+    #[test]
+    fn test_no_conflict_outside_window() {
+        let mut storage = ConflictStorage::default();
+        let ip = Ipv4Addr::new(192, 168, 1, 10);
+        let window = Duration::seconds(60);
+        let start = Local::now();
+
+        storage.observe(ip, &mac(0xA9), start, window, |_| None);
+        let found = storage.observe(
+            ip,
+            &mac(0x30),
+            start + Duration::seconds(120),
+            window,
+            |_| None,
+        );
+        assert!(found.is_empty());
+        assert!(storage.list.is_empty());
+    }
+
+    #[test]
+    fn test_failover() {
+        let mut storage = ConflictStorage::default();
+        let ip = Ipv4Addr::new(192, 168, 1, 1);
+        let window = Duration::seconds(60);
+        let start = Local::now();
+
+        let primary = mac(0xA9);
+        let backup = mac(0xB0);
+
+        storage.observe(ip, &primary, start, window, |_| vendor("Quanta"));
+        storage.announce(ip, &backup);
+        let found = storage.observe(ip, &backup, start, window, |_| vendor("Quanta"));
+        assert_eq!(found.len(), 1);
+        assert!(found[0].is_failover);
+        assert_eq!(storage.alerts(false).count(), 0);
+        assert_eq!(storage.alerts(true).count(), 1);
+
+        // Virtual router MAC
+        let ip = Ipv4Addr::new(192, 168, 2, 1);
+        let virtual_mac = MacAddress::from([0x00, 0x00, 0x5E, 0x00, 0x01, 0x0A]);
+        assert!(is_vrrp(&virtual_mac));
+        storage.observe(ip, &primary, start, window, |_| vendor("Quanta"));
+        storage.announce(ip, &virtual_mac);
+        let found = storage.observe(ip, &virtual_mac, start, window, |_| None);
+        assert!(found[0].is_failover);
+
+        // Gratuitous ARP from another vendor doesn't make it a failover
+        let ip = Ipv4Addr::new(192, 168, 3, 1);
+        let other = mac(0x30);
+        storage.observe(ip, &primary, start, window, |_| vendor("Quanta"));
+        storage.announce(ip, &other);
+        let found = storage.observe(ip, &other, start, window, |_| vendor("Other"));
+        assert!(!found[0].is_failover);
+    }
+}
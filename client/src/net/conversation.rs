@@ -0,0 +1,264 @@
+use crate::net::filter::TimeRange;
+use dpi::protocols::ProtocolId;
+use dpi::protocols::ethernet::mac::MacAddress;
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+/// Side of the conversation. IP address, if it's known, MAC otherwise.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Endpoint {
+    Ip(IpAddr),
+    Mac(MacAddress),
+}
+
+impl std::fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ip(ip) => write!(f, "{ip}"),
+            Self::Mac(mac) => write!(f, "{mac}"),
+        }
+    }
+}
+
+/// Pair of the endpoints, that doesn't depend on the direction. The lower one goes first.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Conversation {
+    pub first: Endpoint,
+    pub second: Endpoint,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Direction {
+    // From the first endpoint to the second one
+    Forward,
+    Backward,
+}
+
+impl Conversation {
+    /// Canonical pair of the frame addresses & direction of the frame in it.
+    pub fn new(
+        mac: (&MacAddress, &MacAddress), ip: Option<(IpAddr, IpAddr)>,
+    ) -> (Self, Direction) {
+        let (source, destination, is_forward) = match ip {
+            Some((source, destination)) => (
+                Endpoint::Ip(source),
+                Endpoint::Ip(destination),
+                source <= destination,
+            ),
+            None => (
+                Endpoint::Mac(mac.0.clone()),
+                Endpoint::Mac(mac.1.clone()),
+                mac.0.0 <= mac.1.0,
+            ),
+        };
+
+        match is_forward {
+            true => (
+                Self {
+                    first: source,
+                    second: destination,
+                },
+                Direction::Forward,
+            ),
+            false => (
+                Self {
+                    first: destination,
+                    second: source,
+                },
+                Direction::Backward,
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConversationRow {
+    pub conversation: Conversation,
+    // Records from the first endpoint to the second one
+    pub forward: usize,
+    pub backward: usize,
+    // Positions of the last records of each direction in the source list
+    pub last_forward: Option<usize>,
+    pub last_backward: Option<usize>,
+}
+
+impl ConversationRow {
+    pub fn arrow(&self) -> &'static str {
+        match (self.forward > 0, self.backward > 0) {
+            (true, false) => "→",
+            (false, true) => "←",
+            _ => "⇄",
+        }
+    }
+}
+
+/// Records, that the conversations are built from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ConversationSource {
+    pub protocol: ProtocolId,
+    pub generation: u64,
+    pub filter_revision: Option<u64>,
+    pub time_range: Option<TimeRange>,
+}
+
+/// Both directions of the records, merged into the rows. Storage isn't changed,
+/// new records are grouped incrementally, like the filtered indices.
+#[derive(Default)]
+pub struct ConversationIndex {
+    source: Option<ConversationSource>,
+    // Records (positions of the source list), that are already grouped
+    scanned: usize,
+    rows: Vec<ConversationRow>,
+    positions: HashMap<Conversation, usize>,
+}
+
+impl ConversationIndex {
+    /// `locate` gives the conversation of the record at the position of the source list.
+    pub fn refresh<F>(&mut self, source: ConversationSource, total: usize, mut locate: F)
+    where
+        F: FnMut(usize) -> Option<(Conversation, Direction)>,
+    {
+        if self.source != Some(source) || self.scanned > total {
+            self.reset();
+            self.source = Some(source);
+        }
+
+        for position in self.scanned..total {
+            let Some((conversation, direction)) = locate(position) else {
+                continue;
+            };
+            let index = match self.positions.get(&conversation) {
+                Some(index) => *index,
+                None => {
+                    let index = self.rows.len();
+                    self.positions.insert(conversation.clone(), index);
+                    self.rows.push(ConversationRow {
+                        conversation,
+                        forward: 0,
+                        backward: 0,
+                        last_forward: None,
+                        last_backward: None,
+                    });
+                    index
+                },
+            };
+            if let Some(row) = self.rows.get_mut(index) {
+                match direction {
+                    Direction::Forward => {
+                        row.forward = row.forward.saturating_add(1);
+                        row.last_forward = Some(position);
+                    },
+                    Direction::Backward => {
+                        row.backward = row.backward.saturating_add(1);
+                        row.last_backward = Some(position);
+                    },
+                }
+            }
+        }
+        self.scanned = total;
+    }
+
+    pub fn reset(&mut self) {
+        self.rows.clear();
+        self.positions.clear();
+        self.scanned = 0;
+    }
+
+    // In order of the first record
+    pub fn rows(&self) -> &[ConversationRow] {
+        &self.rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    const MAC_LOW: MacAddress = MacAddress([0x00, 0x1A, 0x2B, 0x3C, 0x4D, 0x5E]);
+    const MAC_HIGH: MacAddress = MacAddress([0xF0, 0x1A, 0x2B, 0x3C, 0x4D, 0x5E]);
+
+    #[test]
+    fn test_ipv4_canonical() {
+        let low = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let high = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1));
+
+        let (forward, direction) =
+            Conversation::new((&MAC_HIGH, &MAC_LOW), Some((low, high)));
+        assert_eq!(direction, Direction::Forward);
+        let (backward, direction) =
+            Conversation::new((&MAC_LOW, &MAC_HIGH), Some((high, low)));
+        assert_eq!(direction, Direction::Backward);
+
+        // IP addresses take precedence over the MAC ones
+        assert_eq!(forward, backward);
+        assert_eq!(forward.first, Endpoint::Ip(low));
+        assert_eq!(forward.second, Endpoint::Ip(high));
+    }
+
+    #[test]
+    fn test_ipv6_canonical() {
+        let low = IpAddr::V6(Ipv6Addr::LOCALHOST);
+        let high = IpAddr::V6(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1));
+
+        let (conversation, direction) =
+            Conversation::new((&MAC_LOW, &MAC_HIGH), Some((high, low)));
+        assert_eq!(direction, Direction::Backward);
+        assert_eq!(conversation.first, Endpoint::Ip(low));
+        assert_eq!(conversation.second, Endpoint::Ip(high));
+    }
+
+    #[test]
+    fn test_mac_canonical() {
+        let (forward, direction) = Conversation::new((&MAC_LOW, &MAC_HIGH), None);
+        assert_eq!(direction, Direction::Forward);
+        let (backward, direction) = Conversation::new((&MAC_HIGH, &MAC_LOW), None);
+        assert_eq!(direction, Direction::Backward);
+
+        assert_eq!(forward, backward);
+        assert_eq!(forward.first, Endpoint::Mac(MAC_LOW));
+
+        // Frame to itself
+        let (_, direction) = Conversation::new((&MAC_LOW, &MAC_LOW), None);
+        assert_eq!(direction, Direction::Forward);
+    }
+
+    #[test]
+    fn test_index_counts_directions() {
+        let source = ConversationSource {
+            protocol: ProtocolId::Ethernet,
+            generation: 0,
+            filter_revision: None,
+            time_range: None,
+        };
+        let frames = [
+            (&MAC_LOW, &MAC_HIGH),
+            (&MAC_HIGH, &MAC_LOW),
+            (&MAC_LOW, &MAC_HIGH),
+        ];
+        let locate = |position: usize| {
+            frames
+                .get(position)
+                .map(|&addresses| Conversation::new(addresses, None))
+        };
+
+        let mut index = ConversationIndex::default();
+        index.refresh(source, 2, locate);
+        index.refresh(source, frames.len(), locate);
+        assert_eq!(index.rows().len(), 1);
+        assert_eq!(index.rows()[0].forward, 2);
+        assert_eq!(index.rows()[0].backward, 1);
+        assert_eq!(index.rows()[0].arrow(), "⇄");
+        assert_eq!(index.rows()[0].last_forward, Some(2));
+        assert_eq!(index.rows()[0].last_backward, Some(1));
+
+        // Storage is cleared
+        let cleared = ConversationSource {
+            generation: 1,
+            ..source
+        };
+        index.refresh(cleared, 1, locate);
+        assert_eq!(index.rows()[0].forward, 1);
+        assert_eq!(index.rows()[0].arrow(), "→");
+    }
+}
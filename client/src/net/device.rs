@@ -1,70 +1,352 @@
+use crate::data_dir::DataDir;
 use crate::errors::FileError;
+use crate::net::anonymize::Anonymizer;
+use crate::net::classification;
+use crate::net::classification::{Classification, DeviceSignals, DeviceType, Evidence};
+use chrono::{DateTime, Local};
 use common::io::FileKind;
 use dpi::protocols::ethernet::mac::{MacAddress, Vendor};
-use std::collections::HashMap;
+use egui::Color32;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::net::{Ipv4Addr, Ipv6Addr};
+use std::path::Path;
 
 const ALIASES_FILENAME: &str = "device_aliases.toml";
 const ALIASES_FILETYPE: FileKind = FileKind::Data;
 pub type DeviceAliases = HashMap<MacAddress, String>;
 
+pub const CSV_FILTER_NAME: &str = "CSV";
+pub const CSV_FILTER_EXTENSIONS: &[&str] = &["csv"];
+
+// Randomized MACs make a new device every few minutes. Beyond that amount,
+// the least recently seen device without the alias is evicted
+pub const CAPACITY: usize = 1024;
+
 #[derive(Default)]
 pub struct DeviceStorage {
-    pub list: Vec<LocalDevice>,
+    list: Vec<LocalDevice>,
+    // Positions of the devices in the list
+    positions: HashMap<MacAddress, usize>,
+    // Since the last clear of the list
+    pub evicted: usize,
     pub aliases: DeviceAliases,
+    // Set manually, stored with the aliases
+    pub types: HashMap<MacAddress, DeviceType>,
+    pub tags: HashMap<MacAddress, DeviceTag>,
+    pub signals: HashMap<MacAddress, DeviceSignals>,
+    // Still tracked, but not listed & not announced as the new ones. Stored with the aliases
+    pub hidden: HashSet<MacAddress>,
+}
+
+/// Note & color, set by user to make the device stand out.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DeviceTag {
+    pub note: Option<String>,
+    pub color: Option<Color32>,
+}
+
+impl DeviceTag {
+    pub fn is_empty(&self) -> bool {
+        self.note.is_none() && self.color.is_none()
+    }
+}
+
+// Value of the aliases file. Plain alias is kept for the files of older versions.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum FileEntry {
+    Alias(String),
+    Device {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        alias: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        device_type: Option<DeviceType>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        note: Option<String>,
+        // Hex, as the protocol colors in the config
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        color: Option<String>,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        hidden: bool,
+    },
 }
 
 impl DeviceStorage {
+    // In order of the discovery
+    pub fn list(&self) -> &[LocalDevice] {
+        &self.list
+    }
+
+    pub fn contains(&self, mac: &MacAddress) -> bool {
+        self.positions.contains_key(mac)
+    }
+
     pub fn find_by_mac(&mut self, mac: &MacAddress) -> Option<&mut LocalDevice> {
-        self.list.iter_mut().find(|dev| dev.mac.eq(mac))
+        let position = *self.positions.get(mac)?;
+        self.list.get_mut(position)
+    }
+
+    /// New device goes last. If there's no room, an inactive one is evicted.
+    pub fn push(&mut self, device: LocalDevice) {
+        if self.positions.contains_key(&device.mac) {
+            return;
+        }
+        if self.list.len() >= CAPACITY {
+            self.evict();
+        }
+        self.positions.insert(device.mac.clone(), self.list.len());
+        self.list.push(device);
+    }
+
+    pub fn clear(&mut self) {
+        self.list.clear();
+        self.positions.clear();
+        self.evicted = 0;
+    }
+
+    // Devices with the aliases are never evicted, the list may outgrow the capacity
+    fn evict(&mut self) {
+        let Some(position) = self
+            .list
+            .iter()
+            .enumerate()
+            .filter(|(_, device)| !self.aliases.contains_key(&device.mac))
+            .min_by_key(|(_, device)| device.last_seen)
+            .map(|(position, _)| position)
+        else {
+            return;
+        };
+
+        let device = self.list.remove(position);
+        self.positions.remove(&device.mac);
+        self.signals.remove(&device.mac);
+        for (position, device) in self.list.iter().enumerate().skip(position) {
+            self.positions.insert(device.mac.clone(), position);
+        }
+        self.evicted = self.evicted.saturating_add(1);
+    }
+
+    pub fn find_by_ipv4(&mut self, ip: &Ipv4Addr) -> Option<&mut LocalDevice> {
+        self.list.iter_mut().find(|dev| dev.ip.contains(ip))
+    }
+
+    pub fn tag(&self, mac: &MacAddress) -> Option<&DeviceTag> {
+        self.tags.get(mac)
+    }
+
+    pub fn is_hidden(&self, mac: &MacAddress) -> bool {
+        self.hidden.contains(mac)
+    }
+
+    pub fn set_hidden(&mut self, macs: &HashSet<MacAddress>, is_hidden: bool) {
+        match is_hidden {
+            true => self.hidden.extend(macs.iter().cloned()),
+            false => self.hidden.retain(|mac| !macs.contains(mac)),
+        }
+    }
+
+    /// Prefix goes before the alias, the hostname or the MAC, if there's no alias.
+    /// Aliases, that already start with it, are kept.
+    pub fn prefix_aliases(&mut self, macs: &HashSet<MacAddress>, prefix: &str) {
+        if prefix.is_empty() {
+            return;
+        }
+
+        for device in self.list.iter().filter(|device| macs.contains(&device.mac)) {
+            let name = match self.aliases.get(&device.mac) {
+                Some(alias) if alias.starts_with(prefix) => continue,
+                Some(alias) => alias.clone(),
+                None => device
+                    .hostname
+                    .clone()
+                    .unwrap_or_else(|| device.mac.to_string()),
+            };
+            self.aliases
+                .insert(device.mac.clone(), format!("{prefix}{name}"));
+        }
     }
 
-    pub fn from_file() -> Result<Self, FileError> {
-        match common::io::get_storage_file_path(ALIASES_FILENAME, ALIASES_FILETYPE) {
+    /// Details of the devices, in the order of the list. <br>
+    /// Aliases & notes are set by the user, they're kept by the anonymization.
+    pub fn to_csv(
+        &self, macs: &HashSet<MacAddress>, mut anonymizer: Option<Anonymizer>,
+    ) -> String {
+        const COLUMNS: [&str; 8] = [
+            "MAC", "Alias", "IPv4", "IPv6", "Hostname", "Vendor", "Type", "Note",
+        ];
+        let join = |addresses: Vec<String>| addresses.join(" ");
+
+        let mut csv = COLUMNS.join(",");
+        csv.push('\n');
+        for device in self.list.iter().filter(|device| macs.contains(&device.mac)) {
+            let device_type = self.device_type(device);
+            let anonymized = anonymizer.as_mut().map(|value| value.device(device));
+            let shown = anonymized.as_ref().unwrap_or(device);
+            let row = [
+                shown.mac.to_string(),
+                self.aliases.get(&device.mac).cloned().unwrap_or_default(),
+                join(shown.ip.iter().map(|ip| ip.to_string()).collect()),
+                join(shown.ipv6.iter().map(|ip| ip.to_string()).collect()),
+                shown.hostname.clone().unwrap_or_default(),
+                shown
+                    .vendor
+                    .as_ref()
+                    .map(|vendor| vendor.full.clone())
+                    .unwrap_or_default(),
+                device_type
+                    .map(|value| value.to_string())
+                    .unwrap_or_default(),
+                self.tag(&device.mac)
+                    .and_then(|tag| tag.note.clone())
+                    .unwrap_or_default(),
+            ];
+            let row: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+
+        csv
+    }
+
+    pub fn export_csv(
+        &self, macs: &HashSet<MacAddress>, path: &Path, anonymizer: Option<Anonymizer>,
+    ) -> Result<(), FileError> {
+        std::fs::write(path, self.to_csv(macs, anonymizer))?;
+
+        Ok(())
+    }
+
+    pub fn from_file(data_dir: &DataDir) -> Result<Self, FileError> {
+        match data_dir.file_path(ALIASES_FILENAME, ALIASES_FILETYPE) {
             Ok(path) => {
                 let data = std::fs::read_to_string(path);
                 if data.is_err() {
                     let storage = DeviceStorage::default();
-                    storage.save_aliases_to_file()?;
+                    storage.save_aliases_to_file(data_dir)?;
                     return Ok(storage);
                 }
 
-                let mut aliases = HashMap::new();
-                let raw_map: std::collections::BTreeMap<String, String> =
-                    toml::from_str(&data.unwrap_or_default())
-                        .map_err(FileError::TomlDeserialization)?;
-                for (key, value) in raw_map {
-                    let mac = MacAddress::try_from(key.as_str()).map_err(|_| {
-                        FileError::TomlDeserialization(serde::de::Error::missing_field(
-                            "MAC",
-                        ))
-                    })?;
-                    aliases.insert(mac, value);
-                }
-                Ok(DeviceStorage {
-                    list: Default::default(),
-                    aliases,
-                })
+                Self::from_toml(&data.unwrap_or_default())
             },
             Err(_) => Ok(DeviceStorage::default()),
         }
     }
 
-    pub fn save_aliases_to_file(&self) -> Result<(), FileError> {
-        let string_map: std::collections::BTreeMap<String, String> = self
-            .aliases
-            .iter()
-            .map(|(mac, alias)| (mac.to_string(), alias.clone()))
-            .collect();
-
-        let data = toml::to_string(&string_map).map_err(FileError::TomlSerialization)?;
+    pub fn save_aliases_to_file(&self, data_dir: &DataDir) -> Result<(), FileError> {
+        let data = self.to_toml()?;
 
-        let path = common::io::get_storage_file_path(ALIASES_FILENAME, ALIASES_FILETYPE)?;
+        let path = data_dir.file_path(ALIASES_FILENAME, ALIASES_FILETYPE)?;
         common::io::create_parent_directories(&path)?;
         std::fs::write(path, data)?;
 
         Ok(())
     }
+
+    fn from_toml(data: &str) -> Result<Self, FileError> {
+        let raw_map: BTreeMap<String, FileEntry> =
+            toml::from_str(data).map_err(FileError::TomlDeserialization)?;
+        let mut storage = DeviceStorage::default();
+        for (key, value) in raw_map {
+            let mac = MacAddress::try_from(key.as_str()).map_err(|_| {
+                FileError::TomlDeserialization(serde::de::Error::missing_field("MAC"))
+            })?;
+            match value {
+                FileEntry::Alias(alias) => {
+                    storage.aliases.insert(mac, alias);
+                },
+                FileEntry::Device {
+                    alias,
+                    device_type,
+                    note,
+                    color,
+                    hidden,
+                } => {
+                    if let Some(alias) = alias {
+                        storage.aliases.insert(mac.clone(), alias);
+                    }
+                    if let Some(device_type) = device_type {
+                        storage.types.insert(mac.clone(), device_type);
+                    }
+                    let color = color
+                        .map(|color| Color32::from_hex(color.trim()))
+                        .transpose()
+                        .map_err(|_| {
+                            FileError::TomlDeserialization(serde::de::Error::custom(
+                                format!("Invalid color of {key}"),
+                            ))
+                        })?;
+                    let tag = DeviceTag { note, color };
+                    if !tag.is_empty() {
+                        storage.tags.insert(mac.clone(), tag);
+                    }
+                    if hidden {
+                        storage.hidden.insert(mac);
+                    }
+                },
+            }
+        }
+
+        Ok(storage)
+    }
+
+    fn to_toml(&self) -> Result<String, FileError> {
+        let macs = self
+            .aliases
+            .keys()
+            .chain(self.types.keys())
+            .chain(self.tags.keys())
+            .chain(self.hidden.iter())
+            .collect::<HashSet<_>>();
+        let string_map: BTreeMap<String, FileEntry> = macs
+            .into_iter()
+            .map(|mac| {
+                let alias = self.aliases.get(mac).cloned();
+                let device_type = self.types.get(mac).copied();
+                let tag = self.tags.get(mac).cloned().unwrap_or_default();
+                let hidden = self.hidden.contains(mac);
+                let entry = match (alias, device_type, tag.is_empty(), hidden) {
+                    // Written as in the older versions
+                    (Some(alias), None, true, false) => FileEntry::Alias(alias),
+                    (alias, device_type, _, _) => FileEntry::Device {
+                        alias,
+                        device_type,
+                        note: tag.note,
+                        color: tag.color.map(|color| color.to_hex()),
+                        hidden,
+                    },
+                };
+                (mac.to_string(), entry)
+            })
+            .collect();
+
+        toml::to_string(&string_map).map_err(FileError::TomlSerialization)
+    }
+
+    /// Type, set manually or guessed by the traffic.
+    pub fn device_type(&self, device: &LocalDevice) -> Option<DeviceType> {
+        self.types
+            .get(&device.mac)
+            .copied()
+            .or_else(|| self.classify(device).map(|guess| guess.device_type))
+    }
+
+    /// Type, guessed by the traffic of the device. Manual type is kept in `types`.
+    pub fn classify(&self, device: &LocalDevice) -> Option<Classification> {
+        classification::classify(&Evidence {
+            signals: self.signals.get(&device.mac),
+            hostname: device.hostname.as_deref(),
+            vendor: device.vendor.as_ref(),
+        })
+    }
+}
+
+// Quoted, if it has the separators or the quotes
+pub fn csv_field(value: &str) -> String {
+    match value.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", value.replace('"', "\"\"")),
+        false => value.to_string(),
+    }
 }
 
 pub struct LocalDevice {
@@ -72,4 +354,160 @@ pub struct LocalDevice {
     pub ip: Vec<Ipv4Addr>,
     pub ipv6: Vec<Ipv6Addr>,
     pub vendor: Option<Vendor>,
+    // Learned from NetBIOS name registrations & node status responses
+    pub hostname: Option<String>,
+    pub first_seen: DateTime<Local>,
+    pub last_seen: DateTime<Local>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::anonymize::AnonymizeSettings;
+    use chrono::TimeDelta;
+
+    #[test]
+    fn test_aliases_of_older_versions() {
+        let data = r#"
+            "00:1A:8C:15:F9:80" = "Router"
+
+            ["40:61:86:9A:F1:F5"]
+            alias = "Laptop"
+            device_type = "computer"
+        "#;
+
+        let storage = DeviceStorage::from_toml(data).unwrap();
+        let router = MacAddress::try_from("00:1A:8C:15:F9:80").unwrap();
+        let laptop = MacAddress::try_from("40:61:86:9A:F1:F5").unwrap();
+        assert_eq!(storage.aliases.get(&router).unwrap(), "Router");
+        assert_eq!(storage.aliases.get(&laptop).unwrap(), "Laptop");
+        assert_eq!(storage.types.get(&laptop), Some(&DeviceType::Computer));
+        assert!(storage.tags.is_empty());
+    }
+
+    #[test]
+    fn test_tags_round_trip() {
+        let router = MacAddress::try_from("00:1A:8C:15:F9:80").unwrap();
+        let camera = MacAddress::try_from("40:61:86:9A:F1:F5").unwrap();
+        let mut storage = DeviceStorage::default();
+        storage.aliases.insert(router.clone(), "Router".to_string());
+        storage.tags.insert(
+            camera.clone(),
+            DeviceTag {
+                note: Some("IoT - isolate".to_string()),
+                color: Some(Color32::from_rgb(255, 128, 0)),
+            },
+        );
+
+        let data = storage.to_toml().unwrap();
+        // Alias without the tag is kept readable by the older versions
+        assert!(data.contains(r#""00:1A:8C:15:F9:80" = "Router""#));
+
+        let restored = DeviceStorage::from_toml(&data).unwrap();
+        assert_eq!(restored.aliases, storage.aliases);
+        assert_eq!(restored.tags, storage.tags);
+        assert!(restored.types.is_empty());
+
+        let data = r#"["40:61:86:9A:F1:F5"]
+            color = "orange""#;
+        assert!(DeviceStorage::from_toml(data).is_err());
+    }
+
+    fn device(mac: &MacAddress, hostname: Option<&str>) -> LocalDevice {
+        LocalDevice {
+            mac: mac.clone(),
+            ip: vec![Ipv4Addr::new(192, 168, 0, 10), Ipv4Addr::new(10, 0, 0, 10)],
+            ipv6: vec![],
+            vendor: None,
+            hostname: hostname.map(str::to_string),
+            first_seen: Local::now(),
+            last_seen: Local::now(),
+        }
+    }
+
+    #[test]
+    fn test_bulk_operations() {
+        let router = MacAddress::try_from("00:1A:8C:15:F9:80").unwrap();
+        let laptop = MacAddress::try_from("40:61:86:9A:F1:F5").unwrap();
+        let printer = MacAddress::try_from("40:61:86:9A:F1:F6").unwrap();
+        let mut storage = DeviceStorage::default();
+        storage.push(device(&router, None));
+        storage.push(device(&laptop, Some("laptop")));
+        storage.push(device(&printer, None));
+        storage.aliases.insert(router.clone(), "Router".to_string());
+        storage
+            .aliases
+            .insert(printer.clone(), "Office-Printer".to_string());
+
+        let selection = HashSet::from([router.clone(), laptop.clone(), printer.clone()]);
+        storage.prefix_aliases(&selection, "Office-");
+        assert_eq!(storage.aliases[&router], "Office-Router");
+        assert_eq!(storage.aliases[&laptop], "Office-laptop");
+        assert_eq!(storage.aliases[&printer], "Office-Printer");
+
+        // Hidden set is kept along with the aliases
+        storage.set_hidden(&HashSet::from([printer.clone()]), true);
+        let restored = DeviceStorage::from_toml(&storage.to_toml().unwrap()).unwrap();
+        assert_eq!(restored.hidden, HashSet::from([printer.clone()]));
+        assert_eq!(restored.aliases, storage.aliases);
+        storage.set_hidden(&selection, false);
+        assert!(!storage.is_hidden(&printer));
+
+        // Only the selected devices, the fields with separators are quoted
+        storage
+            .aliases
+            .insert(laptop.clone(), "Laptop, \"work\"".to_string());
+        let csv = storage.to_csv(&HashSet::from([laptop.clone()]), None);
+        assert_eq!(
+            csv,
+            "MAC,Alias,IPv4,IPv6,Hostname,Vendor,Type,Note\n\
+             40:61:86:9A:F1:F5,\"Laptop, \"\"work\"\"\",192.168.0.10 10.0.0.10,,laptop,,,\n"
+        );
+
+        // Addresses & hostname aren't left, the alias is of the user
+        let anonymizer = Anonymizer::new(AnonymizeSettings {
+            preserve_oui: false,
+            preserve_private: false,
+        });
+        let csv = storage.to_csv(&HashSet::from([laptop.clone()]), Some(anonymizer));
+        for original in ["40:61:86", "192.168.0.10", "10.0.0.10", ",laptop,"] {
+            assert!(!csv.contains(original), "{original} is left");
+        }
+        assert!(csv.contains("Laptop, \"\"work\"\""));
+    }
+
+    #[test]
+    fn test_eviction() {
+        let now = Local::now();
+        let mac =
+            |index: usize| MacAddress([0x02, 0, 0, 0, (index >> 8) as u8, index as u8]);
+        let mut storage = DeviceStorage::default();
+        for index in 0..CAPACITY {
+            let mut device = device(&mac(index), None);
+            device.last_seen = now + TimeDelta::seconds(index as i64);
+            storage.push(device);
+        }
+        // The first device is active again, the second one has the alias
+        if let Some(device) = storage.find_by_mac(&mac(0)) {
+            device.last_seen = now + TimeDelta::days(1);
+        }
+        storage.aliases.insert(mac(1), "Phone".to_string());
+
+        storage.push(device(&mac(CAPACITY), None));
+        assert_eq!(storage.list().len(), CAPACITY);
+        assert_eq!(storage.evicted, 1);
+        assert!(storage.find_by_mac(&mac(0)).is_some());
+        assert!(storage.find_by_mac(&mac(1)).is_some());
+        assert!(storage.find_by_mac(&mac(2)).is_none());
+
+        // Index points to the same devices, as the list
+        for (position, device) in storage.list().iter().enumerate() {
+            assert_eq!(storage.positions.get(&device.mac), Some(&position));
+        }
+        assert_eq!(storage.positions.len(), CAPACITY);
+
+        storage.clear();
+        assert!(storage.find_by_mac(&mac(0)).is_none());
+        assert_eq!(storage.evicted, 0);
+    }
 }
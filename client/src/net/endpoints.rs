@@ -0,0 +1,403 @@
+use chrono::{DateTime, Local};
+use dpi::dto::frame::FrameDirection;
+use dpi::protocols::dns::{DnsDto, DnsTypeData, MessageType};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::net::IpAddr;
+
+pub const DEFAULT_CAPACITY: usize = 1000;
+pub const TOP_AMOUNT: usize = 20;
+
+/// Traffic between the local network & the external hosts, keyed by the remote address. <br>
+/// Bounded: the least recently seen host is evicted, when the capacity is reached.
+/// Hosts are named by the addresses of the DNS answers seen before.
+pub struct EndpointStorage {
+    hosts: HashMap<IpAddr, RemoteHost>,
+    // Recency tick -> address, the first one is the least recently seen
+    recency: BTreeMap<u64, IpAddr>,
+    tick: u64,
+    capacity: usize,
+    evictions: usize,
+
+    // Reverse cache of the DNS answers, bounded by the same capacity
+    names: HashMap<IpAddr, String>,
+    names_order: VecDeque<IpAddr>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Traffic {
+    pub bytes: usize,
+    pub packets: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct RemoteHost {
+    pub ip: IpAddr,
+    pub name: Option<String>,
+    // Sent by the local network to the host
+    pub sent: Traffic,
+    pub received: Traffic,
+    pub last_seen: DateTime<Local>,
+
+    tick: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Flow {
+    Sent,
+    Received,
+}
+
+impl Traffic {
+    fn add(&mut self, bytes: usize) {
+        self.bytes = self.bytes.saturating_add(bytes);
+        self.packets = self.packets.saturating_add(1);
+    }
+}
+
+impl RemoteHost {
+    pub fn total_bytes(&self) -> usize {
+        self.sent.bytes.saturating_add(self.received.bytes)
+    }
+}
+
+impl Default for EndpointStorage {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+}
+
+impl EndpointStorage {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            hosts: Default::default(),
+            recency: Default::default(),
+            tick: 0,
+            capacity: capacity.max(1),
+            evictions: 0,
+            names: Default::default(),
+            names_order: Default::default(),
+        }
+    }
+
+    pub fn observe(
+        &mut self, ip: IpAddr, flow: Flow, bytes: usize, captured: DateTime<Local>,
+    ) {
+        self.tick = self.tick.saturating_add(1);
+        let tick = self.tick;
+
+        match self.hosts.get_mut(&ip) {
+            Some(host) => {
+                self.recency.remove(&host.tick);
+                host.tick = tick;
+                host.last_seen = host.last_seen.max(captured);
+            },
+            None => {
+                if self.hosts.len() >= self.capacity {
+                    self.evict();
+                }
+                self.hosts.insert(
+                    ip,
+                    RemoteHost {
+                        ip,
+                        name: self.names.get(&ip).cloned(),
+                        sent: Traffic::default(),
+                        received: Traffic::default(),
+                        last_seen: captured,
+                        tick,
+                    },
+                );
+            },
+        }
+        self.recency.insert(tick, ip);
+
+        if let Some(host) = self.hosts.get_mut(&ip) {
+            match flow {
+                Flow::Sent => host.sent.add(bytes),
+                Flow::Received => host.received.add(bytes),
+            }
+        }
+    }
+
+    /// Remembers the names of the addresses, answered by the DNS response.
+    /// The name asked by the question is taken, so the CDN aliases don't hide it.
+    pub fn resolve_names(&mut self, dto: &DnsDto) {
+        if dto.message_type != MessageType::Response {
+            return;
+        }
+
+        for record in dto.answer_section.iter() {
+            let ip = match record.data {
+                DnsTypeData::AIPv4(address) => IpAddr::V4(address),
+                DnsTypeData::AIPv6(address) | DnsTypeData::AAAA(address) => {
+                    IpAddr::V6(address)
+                },
+                _ => continue,
+            };
+            if !is_external(&ip) {
+                continue;
+            }
+            let name = dto
+                .question_section
+                .first()
+                .map(|question| question.name.clone())
+                .unwrap_or(record.name.clone());
+
+            if let Some(host) = self.hosts.get_mut(&ip) {
+                host.name = Some(name.clone());
+            }
+            if self.names.insert(ip, name).is_none() {
+                self.names_order.push_back(ip);
+                if self.names_order.len() > self.capacity {
+                    if let Some(oldest) = self.names_order.pop_front() {
+                        self.names.remove(&oldest);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Hosts sorted by the bytes in both directions. All of them, if the amount isn't set.
+    pub fn top(&self, amount: Option<usize>) -> Vec<&RemoteHost> {
+        let mut hosts = self.hosts.values().collect::<Vec<_>>();
+        hosts.sort_by(|first, second| {
+            second
+                .total_bytes()
+                .cmp(&first.total_bytes())
+                .then(first.ip.cmp(&second.ip))
+        });
+        if let Some(amount) = amount {
+            hosts.truncate(amount);
+        }
+
+        hosts
+    }
+
+    pub fn len(&self) -> usize {
+        self.hosts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hosts.is_empty()
+    }
+
+    pub fn evictions(&self) -> usize {
+        self.evictions
+    }
+
+    // Names are kept, the hosts could come back
+    pub fn clear(&mut self) {
+        self.hosts.clear();
+        self.recency.clear();
+        self.evictions = 0;
+    }
+
+    fn evict(&mut self) {
+        if let Some((_, ip)) = self.recency.pop_first() {
+            self.hosts.remove(&ip);
+            self.evictions = self.evictions.saturating_add(1);
+        }
+    }
+}
+
+/// Remote side of the frame & the direction of the traffic. <br>
+/// Direction hint of the server takes precedence, otherwise the local side is the private one.
+/// Frames between the local hosts, multicast & broadcast aren't counted.
+pub fn remote_side(
+    source: IpAddr, destination: IpAddr, hint: FrameDirection, is_source_local: bool,
+    is_destination_local: bool,
+) -> Option<(IpAddr, Flow)> {
+    let (remote, flow, is_remote_local) = match hint {
+        FrameDirection::Egress => (destination, Flow::Sent, is_destination_local),
+        FrameDirection::Ingress => (source, Flow::Received, is_source_local),
+        FrameDirection::Unknown if is_source_local => {
+            (destination, Flow::Sent, is_destination_local)
+        },
+        FrameDirection::Unknown if is_destination_local => {
+            (source, Flow::Received, is_source_local)
+        },
+        FrameDirection::Unknown => return None,
+    };
+    if is_remote_local || !is_external(&remote) {
+        return None;
+    }
+
+    Some((remote, flow))
+}
+
+fn is_external(ip: &IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            !ip.is_private()
+                && !ip.is_loopback()
+                && !ip.is_link_local()
+                && !ip.is_multicast()
+                && !ip.is_broadcast()
+                && !ip.is_unspecified()
+        },
+        IpAddr::V6(ip) => {
+            !ip.is_unique_local()
+                && !ip.is_loopback()
+                && !ip.is_unicast_link_local()
+                && !ip.is_multicast()
+                && !ip.is_unspecified()
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use dpi::protocols::dns::{
+        Class, DnsType, OperationCode, QuestionEntry, ResourceRecord, ResponseCode,
+    };
+    use std::net::Ipv4Addr;
+
+    fn ip(last: u8) -> IpAddr {
+        IpAddr::V4(Ipv4Addr::new(93, 184, 216, last))
+    }
+
+    #[test]
+    fn test_accumulation() {
+        let mut storage = EndpointStorage::default();
+        let start = Local::now();
+
+        storage.observe(ip(1), Flow::Sent, 100, start);
+        storage.observe(ip(1), Flow::Received, 1500, start + Duration::seconds(2));
+        storage.observe(ip(1), Flow::Received, 1500, start + Duration::seconds(1));
+        storage.observe(ip(2), Flow::Sent, 60, start);
+
+        let top = storage.top(None);
+        assert_eq!(top.len(), 2);
+        let host = top[0];
+        assert_eq!(host.ip, ip(1));
+        assert_eq!(
+            host.sent,
+            Traffic {
+                bytes: 100,
+                packets: 1
+            }
+        );
+        assert_eq!(
+            host.received,
+            Traffic {
+                bytes: 3000,
+                packets: 2
+            }
+        );
+        assert_eq!(host.last_seen, start + Duration::seconds(2));
+        assert_eq!(storage.top(Some(1)).len(), 1);
+    }
+
+    #[test]
+    fn test_eviction() {
+        let mut storage = EndpointStorage::with_capacity(2);
+        let now = Local::now();
+
+        storage.observe(ip(1), Flow::Sent, 10, now);
+        storage.observe(ip(2), Flow::Sent, 10, now);
+        // First host is used again, so the second one is the least recently seen
+        storage.observe(ip(1), Flow::Received, 10, now);
+        storage.observe(ip(3), Flow::Sent, 10, now);
+
+        let mut hosts = storage
+            .top(None)
+            .iter()
+            .map(|host| host.ip)
+            .collect::<Vec<_>>();
+        hosts.sort();
+        assert_eq!(hosts, vec![ip(1), ip(3)]);
+        assert_eq!(storage.evictions(), 1);
+
+        storage.observe(ip(2), Flow::Sent, 10, now);
+        assert_eq!(storage.len(), 2);
+        assert_eq!(storage.evictions(), 2);
+        assert!(storage.top(None).iter().all(|host| host.ip != ip(1)));
+    }
+
+    #[test]
+    fn test_remote_side() {
+        let local = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 10));
+        let multicast = IpAddr::V4(Ipv4Addr::new(224, 0, 0, 251));
+
+        assert_eq!(
+            remote_side(local, ip(1), FrameDirection::Unknown, true, false),
+            Some((ip(1), Flow::Sent))
+        );
+        assert_eq!(
+            remote_side(ip(1), local, FrameDirection::Unknown, false, true),
+            Some((ip(1), Flow::Received))
+        );
+        assert_eq!(
+            remote_side(ip(2), ip(1), FrameDirection::Ingress, false, false),
+            Some((ip(2), Flow::Received))
+        );
+        assert_eq!(
+            remote_side(local, multicast, FrameDirection::Unknown, true, false),
+            None
+        );
+        assert_eq!(
+            remote_side(ip(2), ip(1), FrameDirection::Unknown, false, false),
+            None
+        );
+    }
+
+    #[test]
+    fn test_resolve_names() {
+        let mut storage = EndpointStorage::default();
+        storage.observe(ip(1), Flow::Sent, 10, Local::now());
+
+        let record = |name: &str, data: DnsTypeData| ResourceRecord {
+            name: name.to_string(),
+            record_type: DnsType::A,
+            class: Some(Class::IN),
+            time_to_live: 60,
+            data_length: 4,
+            data,
+        };
+        storage.resolve_names(&DnsDto {
+            message_type: MessageType::Response,
+            operation_code: OperationCode::StandardQuery,
+            authoritative_answer: false,
+            response_code: ResponseCode::NoErrorCondition,
+            question_section: vec![QuestionEntry {
+                name: "example.com".to_string(),
+                entry_type: DnsType::A,
+                class: Class::IN,
+            }],
+            answer_section: vec![
+                record(
+                    "edge.example.net",
+                    DnsTypeData::AIPv4(Ipv4Addr::new(93, 184, 216, 1)),
+                ),
+                record(
+                    "edge.example.net",
+                    DnsTypeData::AIPv4(Ipv4Addr::new(93, 184, 216, 2)),
+                ),
+                // Local addresses aren't named
+                record(
+                    "router.lan",
+                    DnsTypeData::AIPv4(Ipv4Addr::new(192, 168, 1, 1)),
+                ),
+            ],
+            authority_section: vec![],
+            additional_section: vec![],
+        });
+
+        // Existing host gets the name, the new one is named on the first frame
+        storage.observe(ip(2), Flow::Sent, 10, Local::now());
+        let names = storage
+            .top(None)
+            .iter()
+            .map(|host| host.name.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            vec![
+                Some("example.com".to_string()),
+                Some("example.com".to_string())
+            ]
+        );
+        assert_eq!(storage.names.len(), 2);
+    }
+}
@@ -0,0 +1,912 @@
+use crate::net::idna;
+use crate::net::memory::Records;
+use crate::ws::data::{Locator, PortDto, WirelessDto};
+use chrono::{DateTime, Local, TimeDelta};
+use dpi::protocols::ProtocolId;
+use dpi::protocols::arp::{ArpDto, ProtocolAddress};
+use dpi::protocols::dhcpv4::DHCPv4Dto;
+use dpi::protocols::dhcpv6::DHCPv6Dto;
+use dpi::protocols::dns::DnsDto;
+use dpi::protocols::http::{Header, HttpDto};
+use dpi::protocols::icmpv4::ICMPv4Dto;
+use dpi::protocols::icmpv6::ICMPv6Dto;
+use dpi::protocols::ipv4::IPv4Dto;
+use dpi::protocols::ipv6::IPv6Dto;
+use dpi::protocols::nbns::NbnsDto;
+use dpi::protocols::rtp::RtpDto;
+use dpi::protocols::sip::SipDto;
+use dpi::protocols::smb2::Smb2Dto;
+use dpi::protocols::syslog::SyslogDto;
+use dpi::protocols::tftp::TftpDto;
+use regex::Regex;
+use std::time::{Duration, Instant};
+use strum_macros::EnumIter;
+
+/// Pattern is compiled only after the user stops typing for this long.
+pub const DEBOUNCE: Duration = Duration::from_millis(300);
+
+pub const HOST_HEADER: &str = "Host";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter)]
+pub enum FilterField {
+    DnsQuestionName,
+    DnsAnswerData,
+    HttpTarget,
+    HttpHost,
+    HttpHeaderValue,
+    SyslogSeverity,
+    SyslogMessage,
+    SourceIp,
+    DestinationIp,
+    Mac,
+}
+
+impl FilterField {
+    const GENERIC: &'static [Self] = &[Self::SourceIp, Self::DestinationIp, Self::Mac];
+
+    pub fn available(protocol: &ProtocolId) -> &'static [Self] {
+        match protocol {
+            ProtocolId::DNS => &[Self::DnsQuestionName, Self::DnsAnswerData],
+            ProtocolId::HTTP => &[
+                Self::HttpTarget,
+                Self::HttpHost,
+                Self::HttpHeaderValue,
+                Self::SourceIp,
+                Self::DestinationIp,
+                Self::Mac,
+            ],
+            ProtocolId::Syslog => &[
+                Self::SyslogSeverity,
+                Self::SyslogMessage,
+                Self::SourceIp,
+                Self::DestinationIp,
+                Self::Mac,
+            ],
+            ProtocolId::Arp
+            | ProtocolId::Ethernet
+            | ProtocolId::ICMPv4
+            | ProtocolId::ICMPv6
+            | ProtocolId::IPv4
+            | ProtocolId::IPv6
+            | ProtocolId::NBNS
+            | ProtocolId::RTP
+            | ProtocolId::SIP
+            | ProtocolId::SMB2
+            | ProtocolId::TCP
+            | ProtocolId::TFTP
+            | ProtocolId::UDP => Self::GENERIC,
+            ProtocolId::IEEE80211 => &[Self::Mac],
+            ProtocolId::DHCPv4 | ProtocolId::DHCPv6 | ProtocolId::Radiotap => &[],
+        }
+    }
+}
+
+enum Matcher {
+    // Case-insensitive, pattern is stored in lowercase
+    Substring(String),
+    Regex(Regex),
+}
+
+pub struct Filter {
+    field: FilterField,
+    pattern: String,
+    is_regex: bool,
+
+    matcher: Option<Matcher>,
+    error: Option<String>,
+    // Set when the pattern was edited, but not compiled yet
+    edited_at: Option<Instant>,
+    // Incremented every time the matcher changes
+    revision: u64,
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Self {
+            field: FilterField::SourceIp,
+            pattern: String::new(),
+            is_regex: false,
+
+            matcher: None,
+            error: None,
+            edited_at: None,
+            revision: 0,
+        }
+    }
+}
+
+impl Filter {
+    /// Filter, that is compiled at once, without the debounce of the typed pattern.
+    pub fn compiled(field: FilterField, pattern: &str, is_regex: bool) -> Self {
+        let mut filter = Self {
+            field,
+            pattern: pattern.to_string(),
+            is_regex,
+            ..Self::default()
+        };
+        filter.compile();
+
+        filter
+    }
+
+    pub fn field(&self) -> FilterField {
+        self.field
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn is_regex(&self) -> bool {
+        self.is_regex
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.edited_at.is_some()
+    }
+
+    /// Invalid & empty patterns don't filter anything.
+    pub fn is_active(&self) -> bool {
+        self.matcher.is_some()
+    }
+
+    pub fn set_field(&mut self, field: FilterField) {
+        if self.field != field {
+            self.field = field;
+            self.revision = self.revision.wrapping_add(1);
+        }
+    }
+
+    pub fn set_regex(&mut self, is_regex: bool) {
+        if self.is_regex != is_regex {
+            self.is_regex = is_regex;
+            self.compile();
+        }
+    }
+
+    /// Pattern is compiled later, by [`Filter::update`], once the debounce has elapsed.
+    pub fn set_pattern(&mut self, pattern: &str, now: Instant) {
+        if self.pattern != pattern {
+            self.pattern = pattern.to_string();
+            self.edited_at = Some(now);
+        }
+    }
+
+    /// Returns `true` if the matcher was recompiled.
+    pub fn update(&mut self, now: Instant) -> bool {
+        match self.edited_at {
+            Some(edited_at) if now.saturating_duration_since(edited_at) >= DEBOUNCE => {
+                self.compile();
+                true
+            },
+            _ => false,
+        }
+    }
+
+    pub fn is_match(&self, value: &str) -> bool {
+        match &self.matcher {
+            None => true,
+            Some(Matcher::Substring(pattern)) => {
+                value.to_lowercase().contains(pattern.as_str())
+            },
+            Some(Matcher::Regex(regex)) => regex.is_match(value),
+        }
+    }
+
+    /// Internationalized domain name matches by its punycode or by the Unicode form.
+    pub fn is_domain_match(&self, name: &str) -> bool {
+        self.is_match(name)
+            || idna::to_unicode(name).is_some_and(|unicode| self.is_match(&unicode))
+    }
+
+    fn compile(&mut self) {
+        self.edited_at = None;
+        self.error = None;
+        self.revision = self.revision.wrapping_add(1);
+
+        if self.pattern.is_empty() {
+            self.matcher = None;
+            return;
+        }
+
+        self.matcher = if self.is_regex {
+            match Regex::new(&self.pattern) {
+                Ok(regex) => Some(Matcher::Regex(regex)),
+                Err(err) => {
+                    self.error = Some(err.to_string());
+                    None
+                },
+            }
+        } else {
+            Some(Matcher::Substring(self.pattern.to_lowercase()))
+        };
+    }
+}
+
+pub trait Filterable {
+    /// Records without the chosen field never match an active filter.
+    fn matches(&self, filter: &Filter) -> bool;
+}
+
+impl Filterable for Locator {
+    fn matches(&self, filter: &Filter) -> bool {
+        match filter.field() {
+            // Tunneled packets match by the inner & the outer addresses
+            FilterField::SourceIp => {
+                let outer = self.tunnel.as_ref().map(|tunnel| tunnel.outer.0);
+                [self.ip().map(|(source, _)| source), outer]
+                    .iter()
+                    .flatten()
+                    .any(|ip| filter.is_match(&ip.to_string()))
+            },
+            FilterField::DestinationIp => {
+                let outer = self.tunnel.as_ref().map(|tunnel| tunnel.outer.1);
+                [self.ip().map(|(_, destination)| destination), outer]
+                    .iter()
+                    .flatten()
+                    .any(|ip| filter.is_match(&ip.to_string()))
+            },
+            FilterField::Mac => {
+                filter.is_match(&self.mac.0.to_string())
+                    || filter.is_match(&self.mac.1.to_string())
+            },
+            _ => false,
+        }
+    }
+}
+
+impl Filterable for ArpDto {
+    fn matches(&self, filter: &Filter) -> bool {
+        let address = |address: &ProtocolAddress| match address {
+            ProtocolAddress::IPv4(address) => filter.is_match(&address.to_string()),
+            ProtocolAddress::Raw(_) => false,
+        };
+
+        match filter.field() {
+            FilterField::SourceIp => address(&self.sender_protocol),
+            FilterField::DestinationIp => address(&self.target_protocol),
+            FilterField::Mac => {
+                filter.is_match(&self.sender_hardware.to_string())
+                    || filter.is_match(&self.target_hardware.to_string())
+            },
+            _ => false,
+        }
+    }
+}
+
+impl Filterable for DnsDto {
+    fn matches(&self, filter: &Filter) -> bool {
+        match filter.field() {
+            FilterField::DnsQuestionName => self
+                .question_section
+                .iter()
+                .any(|question| filter.is_domain_match(&question.name)),
+            FilterField::DnsAnswerData => self
+                .answer_section
+                .iter()
+                .any(|record| filter.is_domain_match(&record.data.to_string())),
+            _ => false,
+        }
+    }
+}
+
+impl Filterable for (HttpDto, Locator) {
+    fn matches(&self, filter: &Filter) -> bool {
+        let headers: &[Header] = match &self.0 {
+            HttpDto::Request(request) => &request.headers,
+            HttpDto::Response(response) => &response.headers,
+        };
+
+        match filter.field() {
+            FilterField::HttpTarget => match &self.0 {
+                HttpDto::Request(request) => filter.is_match(&request.target),
+                HttpDto::Response(_) => false,
+            },
+            FilterField::HttpHost => headers
+                .iter()
+                .filter(|(name, _)| name.trim().eq_ignore_ascii_case(HOST_HEADER))
+                .any(|(_, value)| filter.is_domain_match(value.trim())),
+            FilterField::HttpHeaderValue => headers
+                .iter()
+                .any(|(_, value)| filter.is_match(value.trim())),
+            _ => self.1.matches(filter),
+        }
+    }
+}
+
+impl Filterable for (SyslogDto, Locator) {
+    fn matches(&self, filter: &Filter) -> bool {
+        match filter.field() {
+            FilterField::SyslogSeverity => filter.is_match(&self.0.severity.to_string()),
+            FilterField::SyslogMessage => filter.is_match(&self.0.message),
+            _ => self.1.matches(filter),
+        }
+    }
+}
+
+// Protocols without own filterable fields
+impl Filterable for DHCPv4Dto {
+    fn matches(&self, _: &Filter) -> bool {
+        false
+    }
+}
+
+impl Filterable for DHCPv6Dto {
+    fn matches(&self, _: &Filter) -> bool {
+        false
+    }
+}
+
+macro_rules! filterable_by_locator {
+    ($($type:ty),* $(,)?) => {
+        $(
+            impl Filterable for ($type, Locator) {
+                fn matches(&self, filter: &Filter) -> bool {
+                    self.1.matches(filter)
+                }
+            }
+        )*
+    };
+}
+
+filterable_by_locator!(
+    ICMPv4Dto,
+    ICMPv6Dto,
+    IPv4Dto,
+    IPv6Dto,
+    NbnsDto,
+    PortDto,
+    RtpDto,
+    SipDto,
+    Smb2Dto,
+    TftpDto,
+    WirelessDto,
+);
+
+/// Capture time interval, selected on the speed plot.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeRange {
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+}
+
+impl TimeRange {
+    /// Plot X axis shows how many seconds ago the frames were captured.
+    pub fn from_plot(x_first: f64, x_second: f64, now: DateTime<Local>) -> Self {
+        let seconds_ago = |x: f64| TimeDelta::milliseconds((x.max(0.0) * 1000.0) as i64);
+
+        Self {
+            start: now - seconds_ago(x_first.max(x_second)),
+            end: now - seconds_ago(x_first.min(x_second)),
+        }
+    }
+
+    pub fn to_plot(&self, now: DateTime<Local>) -> (f64, f64) {
+        let seconds_ago =
+            |time: DateTime<Local>| (now - time).num_milliseconds() as f64 / 1000.0;
+
+        (seconds_ago(self.end), seconds_ago(self.start))
+    }
+
+    pub fn contains(&self, time: &DateTime<Local>) -> bool {
+        self.start <= *time && *time <= self.end
+    }
+}
+
+/// Indices of records that match the filter and the time range. <br>
+/// Only records added since the previous refresh are scanned, the whole storage
+/// is rescanned only when the filter, the time range or the storage itself was reset.
+#[derive(Default)]
+pub struct FilteredIndices {
+    indices: Vec<usize>,
+    scanned: usize,
+    // Storages of the protocols have their own generations, so they may be equal
+    protocol: Option<ProtocolId>,
+    filter_revision: Option<u64>,
+    time_range: Option<TimeRange>,
+    storage_generation: u64,
+}
+
+impl FilteredIndices {
+    pub fn refresh<T: Filterable>(
+        &mut self, protocol: ProtocolId, records: &Records<T>, filter: Option<&Filter>,
+        time_range: Option<TimeRange>,
+    ) {
+        let filter_revision = filter.map(Filter::revision);
+        let is_stale = self.protocol != Some(protocol)
+            || self.filter_revision != filter_revision
+            || self.time_range != time_range
+            || self.storage_generation != records.generation()
+            || self.scanned > records.len();
+        if is_stale {
+            self.reset();
+            self.protocol = Some(protocol);
+            self.filter_revision = filter_revision;
+            self.time_range = time_range;
+            self.storage_generation = records.generation();
+        }
+
+        for (index, record) in records.iter().enumerate().skip(self.scanned) {
+            let is_in_range = match &time_range {
+                Some(range) => records
+                    .captured_at(index)
+                    .is_some_and(|captured| range.contains(captured)),
+                None => true,
+            };
+            if is_in_range && filter.is_none_or(|filter| record.matches(filter)) {
+                self.indices.push(index);
+            }
+        }
+        self.scanned = records.len();
+    }
+
+    pub fn reset(&mut self) {
+        self.indices.clear();
+        self.scanned = 0;
+    }
+
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+}
+
+/// Amount of the records in the time range. <br>
+/// As with [`FilteredIndices`], only records added since the previous refresh are checked.
+#[derive(Default)]
+pub struct RangeCount {
+    amount: usize,
+    scanned: usize,
+    time_range: Option<TimeRange>,
+    storage_generation: u64,
+}
+
+impl RangeCount {
+    pub fn refresh(
+        &mut self, captured: &[DateTime<Local>], storage_generation: u64,
+        time_range: TimeRange,
+    ) -> usize {
+        let is_stale = self.time_range != Some(time_range)
+            || self.storage_generation != storage_generation
+            || self.scanned > captured.len();
+        if is_stale {
+            *self = Self {
+                time_range: Some(time_range),
+                storage_generation,
+                ..Default::default()
+            };
+        }
+
+        let added = captured.get(self.scanned..).unwrap_or_default();
+        self.amount += added
+            .iter()
+            .filter(|time| time_range.contains(time))
+            .count();
+        self.scanned = captured.len();
+        self.amount
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dpi::protocols::dns::{
+        Class, DnsType, DnsTypeData, MessageType, OperationCode, QuestionEntry,
+        ResourceRecord, ResponseCode,
+    };
+    use dpi::protocols::ethernet::mac::MacAddress;
+    use dpi::protocols::http::{HTTPRequestDto, HTTPResponseDto, Methods};
+    use dpi::protocols::ip::checksum::ChecksumStatus;
+    use dpi::protocols::syslog::{Facility, Severity};
+    use std::cell::Cell;
+    use std::net::Ipv4Addr;
+
+    fn filter(field: FilterField, pattern: &str, is_regex: bool) -> Filter {
+        let mut filter = Filter::default();
+        let now = Instant::now();
+        filter.set_field(field);
+        filter.set_regex(is_regex);
+        filter.set_pattern(pattern, now);
+        filter.update(now + DEBOUNCE);
+        filter
+    }
+
+    fn locator(source: Ipv4Addr, destination: Ipv4Addr) -> Locator {
+        Locator {
+            mac: (
+                MacAddress([0x00, 0x1A, 0x2B, 0x3C, 0x4D, 0x5E]),
+                MacAddress([0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]),
+            ),
+            ipv4: Some((source, destination)),
+            ipv6: None,
+            tunnel: None,
+        }
+    }
+
+    fn dns(question: &str, answer: DnsTypeData) -> DnsDto {
+        DnsDto {
+            message_type: MessageType::Response,
+            operation_code: OperationCode::StandardQuery,
+            authoritative_answer: false,
+            response_code: ResponseCode::NoErrorCondition,
+            question_section: vec![QuestionEntry {
+                name: question.to_string(),
+                entry_type: DnsType::A,
+                class: Class::IN,
+            }],
+            answer_section: vec![ResourceRecord {
+                name: question.to_string(),
+                record_type: DnsType::CNAME,
+                class: Some(Class::IN),
+                time_to_live: 300,
+                data_length: 0,
+                data: answer,
+            }],
+            authority_section: vec![],
+            additional_section: vec![],
+        }
+    }
+
+    fn http_request(target: &str, host: &str) -> (HttpDto, Locator) {
+        (
+            HttpDto::Request(HTTPRequestDto {
+                method: Methods::GET,
+                target: target.to_string(),
+                headers: vec![
+                    ("Host".to_string(), host.to_string()),
+                    ("User-Agent".to_string(), "curl/8.5.0".to_string()),
+                ],
+                body_anomaly: None,
+            }),
+            locator(
+                Ipv4Addr::new(192, 168, 0, 10),
+                Ipv4Addr::new(93, 184, 216, 34),
+            ),
+        )
+    }
+
+    #[test]
+    fn test_dns_question_regex() {
+        let records = [
+            dns("tracker.ads.example.com", DnsTypeData::Unknown),
+            dns("ads.mydomain.org", DnsTypeData::Unknown),
+            dns("example.com", DnsTypeData::Unknown),
+        ];
+        let filter = filter(
+            FilterField::DnsQuestionName,
+            r"\.ads\.|^ads\.(?:[^m]|m[^y])",
+            true,
+        );
+
+        let matched: Vec<bool> = records.iter().map(|r| r.matches(&filter)).collect();
+        assert_eq!(matched, vec![true, false, false]);
+    }
+
+    #[test]
+    fn test_dns_answer_data() {
+        let record = dns(
+            "www.example.com",
+            DnsTypeData::CNAME("edge.cdn.example.net".to_string()),
+        );
+
+        assert!(record.matches(&filter(FilterField::DnsAnswerData, "CDN", false)));
+        assert!(!record.matches(&filter(FilterField::DnsAnswerData, "www", false)));
+    }
+
+    #[test]
+    fn test_idn_names() {
+        let record = dns("xn--mnchen-3ya.de", DnsTypeData::Unknown);
+        // Either form matches
+        for pattern in ["münchen", "xn--mnchen", "MÜNCHEN.DE"] {
+            let filter = filter(FilterField::DnsQuestionName, pattern, false);
+            assert!(record.matches(&filter), "{pattern}");
+        }
+        let request = http_request("/", "xn--e1afmkfd.xn--80akhbyknj4f");
+        assert!(request.matches(&filter(FilterField::HttpHost, r"^пример\.", true)));
+
+        // Malformed punycode is matched as is
+        let malformed = dns("xn--mnchen-3y.de", DnsTypeData::Unknown);
+        assert!(!malformed.matches(&filter(FilterField::DnsQuestionName, "ü", false)));
+        assert!(malformed.matches(&filter(FilterField::DnsQuestionName, "3y.de", false)));
+    }
+
+    #[test]
+    fn test_http_fields() {
+        let request = http_request("/ads/banner.png", "static.ads.net");
+        let response = (
+            HttpDto::Response(HTTPResponseDto {
+                status_code: 200,
+                reason: "OK".to_string(),
+                headers: vec![("Server".to_string(), "nginx".to_string())],
+                body_anomaly: None,
+            }),
+            locator(
+                Ipv4Addr::new(93, 184, 216, 34),
+                Ipv4Addr::new(192, 168, 0, 10),
+            ),
+        );
+
+        let target = filter(FilterField::HttpTarget, r"^/ads/", true);
+        assert!(request.matches(&target));
+        assert!(!response.matches(&target));
+
+        let host = filter(FilterField::HttpHost, r"\.ads\.net$", true);
+        assert!(request.matches(&host));
+        assert!(!response.matches(&host));
+
+        let header = filter(FilterField::HttpHeaderValue, "NGINX", false);
+        assert!(!request.matches(&header));
+        assert!(response.matches(&header));
+    }
+
+    #[test]
+    fn test_syslog_fields() {
+        let message = (
+            SyslogDto {
+                facility: Facility::Security,
+                severity: Severity::Critical,
+                version: None,
+                timestamp: Some("Oct 11 22:14:15".to_string()),
+                hostname: Some("router".to_string()),
+                app_name: Some("sshd".to_string()),
+                process_id: Some("812".to_string()),
+                message_id: None,
+                structured_data: None,
+                message: "Failed password for root from 203.0.113.7".to_string(),
+            },
+            locator(
+                Ipv4Addr::new(192, 168, 0, 1),
+                Ipv4Addr::new(192, 168, 0, 10),
+            ),
+        );
+
+        assert!(message.matches(&filter(FilterField::SyslogSeverity, "crit", false)));
+        assert!(!message.matches(&filter(FilterField::SyslogSeverity, "^Error$", true)));
+        assert!(message.matches(&filter(
+            FilterField::SyslogMessage,
+            "failed password",
+            false
+        )));
+        assert!(message.matches(&filter(FilterField::SourceIp, "192.168.0.1", false)));
+    }
+
+    #[test]
+    fn test_generic_fields() {
+        let packet = (
+            PortDto {
+                port_source: 52000,
+                port_destination: 443,
+                possible_application: "https".to_string(),
+                checksum_status: ChecksumStatus::NotChecked,
+                length_mismatch: None,
+                window: None,
+            },
+            locator(Ipv4Addr::new(10, 0, 0, 2), Ipv4Addr::new(1, 1, 1, 1)),
+        );
+
+        assert!(packet.matches(&filter(FilterField::SourceIp, r"^10\.", true)));
+        assert!(!packet.matches(&filter(FilterField::DestinationIp, r"^10\.", true)));
+        assert!(packet.matches(&filter(FilterField::Mac, "1a:2b", false)));
+    }
+
+    #[test]
+    fn test_invalid_regex_does_not_filter() {
+        let filter = filter(FilterField::DnsQuestionName, "(ads", true);
+
+        assert!(filter.error().is_some());
+        assert!(!filter.is_active());
+        assert!(filter.is_match("anything"));
+    }
+
+    #[test]
+    fn test_debounce() {
+        let mut filter = Filter::default();
+        let start = Instant::now();
+        let revision = filter.revision();
+
+        // Typing: nothing is compiled between keystrokes
+        for (offset, pattern) in ["a", "ad", "ads"].iter().enumerate() {
+            let now = start + Duration::from_millis(100 * offset as u64);
+            filter.set_pattern(pattern, now);
+            assert!(!filter.update(now));
+        }
+        assert!(filter.is_pending());
+        assert_eq!(filter.revision(), revision);
+
+        assert!(filter.update(start + Duration::from_millis(200) + DEBOUNCE));
+        assert!(filter.is_active());
+        assert_eq!(filter.revision(), revision + 1);
+        assert!(!filter.update(start + DEBOUNCE * 10));
+    }
+
+    struct Counted<'a> {
+        value: &'static str,
+        checks: &'a Cell<usize>,
+    }
+
+    impl Filterable for Counted<'_> {
+        fn matches(&self, filter: &Filter) -> bool {
+            self.checks.set(self.checks.get() + 1);
+            filter.is_match(self.value)
+        }
+    }
+
+    impl crate::net::memory::EstimatedSize for Counted<'_> {
+        fn estimated_size(&self) -> usize {
+            0
+        }
+    }
+
+    #[test]
+    fn test_filtered_indices_scan_incrementally() {
+        let checks = Cell::new(0);
+        let filter = filter(FilterField::SourceIp, "ads", false);
+        let mut records = Records::default();
+        let mut indices = FilteredIndices::default();
+        for (frame, value) in [(0, "ads.com"), (1, "example.com"), (2, "my-ads.org")] {
+            records.push(
+                Counted {
+                    value,
+                    checks: &checks,
+                },
+                Local::now(),
+                frame,
+            );
+        }
+
+        indices.refresh(ProtocolId::DNS, &records, Some(&filter), None);
+        assert_eq!(indices.indices(), &[0, 2]);
+        assert_eq!(checks.get(), 3);
+
+        // Repaint without changes: nothing is rescanned
+        indices.refresh(ProtocolId::DNS, &records, Some(&filter), None);
+        assert_eq!(checks.get(), 3);
+
+        // Only the new record is checked
+        records.push(
+            Counted {
+                value: "ads.net",
+                checks: &checks,
+            },
+            Local::now(),
+            3,
+        );
+        indices.refresh(ProtocolId::DNS, &records, Some(&filter), None);
+        assert_eq!(indices.indices(), &[0, 2, 3]);
+        assert_eq!(checks.get(), 4);
+
+        // Storage cleared & refilled
+        records.clear();
+        records.push(
+            Counted {
+                value: "example.com",
+                checks: &checks,
+            },
+            Local::now(),
+            4,
+        );
+        indices.refresh(ProtocolId::DNS, &records, Some(&filter), None);
+        assert!(indices.indices().is_empty());
+        assert_eq!(checks.get(), 5);
+    }
+
+    #[test]
+    fn test_time_range() {
+        let now = Local::now();
+        let checks = Cell::new(0);
+        let mut records = Records::default();
+        let mut indices = FilteredIndices::default();
+        for (frame, value, seconds_ago) in [
+            (0, "ads.com", 30),
+            (1, "example.com", 12),
+            (2, "ads.org", 5),
+        ] {
+            records.push(
+                Counted {
+                    value,
+                    checks: &checks,
+                },
+                now - TimeDelta::seconds(seconds_ago),
+                frame,
+            );
+        }
+
+        // Selection is dragged from right to left
+        let range = TimeRange::from_plot(3.0, 15.0, now);
+        assert_eq!(range.start, now - TimeDelta::seconds(15));
+        assert_eq!(range.end, now - TimeDelta::seconds(3));
+        assert_eq!(range.to_plot(now), (3.0, 15.0));
+
+        indices.refresh(ProtocolId::DNS, &records, None, Some(range));
+        assert_eq!(indices.indices(), &[1, 2]);
+        assert_eq!(checks.get(), 0);
+
+        let filter = filter(FilterField::SourceIp, "ads", false);
+        indices.refresh(ProtocolId::DNS, &records, Some(&filter), Some(range));
+        assert_eq!(indices.indices(), &[2]);
+
+        // New range replaces the old one
+        indices.refresh(
+            ProtocolId::DNS,
+            &records,
+            Some(&filter),
+            Some(TimeRange::from_plot(0.0, 60.0, now)),
+        );
+        assert_eq!(indices.indices(), &[0, 2]);
+    }
+
+    #[test]
+    fn test_range_count() {
+        let now = Local::now();
+        let range = TimeRange::from_plot(3.0, 15.0, now);
+        let mut captured: Vec<DateTime<Local>> = [30, 12, 5]
+            .into_iter()
+            .map(|seconds_ago| now - TimeDelta::seconds(seconds_ago))
+            .collect();
+        let mut count = RangeCount::default();
+
+        assert_eq!(count.refresh(&captured, 0, range), 2);
+        assert_eq!(count.refresh(&captured, 0, range), 2);
+
+        // Only the new records are checked
+        captured.push(now - TimeDelta::seconds(4));
+        captured.push(now);
+        assert_eq!(count.refresh(&captured, 0, range), 3);
+        assert_eq!(count.scanned, 5);
+
+        // New range & cleared storage are counted from the start
+        let wider = TimeRange::from_plot(0.0, 60.0, now);
+        assert_eq!(count.refresh(&captured, 0, wider), 5);
+        captured.clear();
+        captured.push(now - TimeDelta::seconds(10));
+        assert_eq!(count.refresh(&captured, 1, range), 1);
+    }
+
+    #[test]
+    fn test_protocol_switch() {
+        let checks = Cell::new(0);
+        let filter = filter(FilterField::SourceIp, "ads", false);
+        let mut indices = FilteredIndices::default();
+        // Both storages are of the same generation
+        let mut dns = Records::default();
+        let mut http = Records::default();
+        for (frame, value) in [(0, "example.com"), (1, "ads.com"), (2, "ads.org")] {
+            dns.push(
+                Counted {
+                    value,
+                    checks: &checks,
+                },
+                Local::now(),
+                frame,
+            );
+        }
+        http.push(
+            Counted {
+                value: "ads.net",
+                checks: &checks,
+            },
+            Local::now(),
+            3,
+        );
+
+        indices.refresh(ProtocolId::DNS, &dns, Some(&filter), None);
+        assert_eq!(indices.indices(), &[1, 2]);
+
+        indices.refresh(ProtocolId::HTTP, &http, Some(&filter), None);
+        assert_eq!(indices.indices(), &[0]);
+
+        // Only the time range is set
+        let range = TimeRange::from_plot(0.0, 60.0, Local::now());
+        indices.refresh(ProtocolId::DNS, &dns, None, Some(range));
+        assert_eq!(indices.indices(), &[0, 1, 2]);
+        indices.refresh(ProtocolId::HTTP, &http, None, Some(range));
+        assert_eq!(indices.indices(), &[0]);
+    }
+}
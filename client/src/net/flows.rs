@@ -0,0 +1,284 @@
+use chrono::{DateTime, Local, TimeDelta};
+use dpi::protocols::tcp::TcpDto;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+
+// Oldest flow is evicted after that
+pub const CAPACITY: usize = 4096;
+// Flow without segments is considered closed, its FIN may be lost
+const IDLE_TIMEOUT_SECONDS: i64 = 300;
+// RFC 7323, 2.3: Larger shifts are treated as 14
+const MAX_WINDOW_SCALE: u8 = 14;
+
+/// Handshake options of the TCP flows (by 4-tuple), so the windows of the segments are
+/// scaled. Flow is forgotten on FIN, RST & idle timeout.
+#[derive(Default)]
+pub struct TcpFlows {
+    flows: HashMap<FlowKey, Flow>,
+}
+
+/// Derived metrics of the segment. Unknown, if the handshake of the flow wasn't captured.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct TcpWindow {
+    // Bytes
+    pub effective: Option<u32>,
+    // Smaller one of the announced sizes
+    pub maximum_segment_size: Option<u16>,
+}
+
+// Endpoints of the flow, the lower one goes first
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+struct FlowKey(SocketAddr, SocketAddr);
+
+struct Flow {
+    // By the order of the key endpoints
+    sides: [Option<Handshake>; 2],
+    last_seen: DateTime<Local>,
+}
+
+// Options of the SYN segment, that the side has sent
+#[derive(Clone, Copy)]
+struct Handshake {
+    maximum_segment_size: Option<u16>,
+    window_scale: Option<u8>,
+}
+
+impl Flow {
+    fn window(&self, tcp: &TcpDto, side: usize) -> TcpWindow {
+        let [Some(first), Some(second)] = self.sides else {
+            return TcpWindow {
+                // Window of SYN isn't scaled
+                effective: tcp.syn.then_some(u32::from(tcp.window)),
+                maximum_segment_size: None,
+            };
+        };
+
+        // Scaling is used only if both sides offered it
+        let shift = match (first.window_scale, second.window_scale) {
+            (Some(_), Some(_)) if !tcp.syn => [first, second]
+                .get(side)
+                .and_then(|handshake| handshake.window_scale)
+                .unwrap_or_default()
+                .min(MAX_WINDOW_SCALE),
+            _ => 0,
+        };
+        let maximum_segment_size = [first, second]
+            .iter()
+            .filter_map(|handshake| handshake.maximum_segment_size)
+            .min();
+
+        TcpWindow {
+            effective: Some(u32::from(tcp.window) << shift),
+            maximum_segment_size,
+        }
+    }
+}
+
+impl TcpFlows {
+    pub fn observe(
+        &mut self, tcp: &TcpDto, endpoints: (SocketAddr, SocketAddr),
+        captured: DateTime<Local>,
+    ) -> TcpWindow {
+        let (source, destination) = endpoints;
+        let (key, side) = match source <= destination {
+            true => (FlowKey(source, destination), 0),
+            false => (FlowKey(destination, source), 1),
+        };
+
+        let timeout = TimeDelta::seconds(IDLE_TIMEOUT_SECONDS);
+        if self
+            .flows
+            .get(&key)
+            .is_some_and(|flow| captured - flow.last_seen > timeout)
+        {
+            self.flows.remove(&key);
+        }
+        if tcp.syn && !self.flows.contains_key(&key) {
+            self.make_room(captured);
+            self.flows.insert(
+                key,
+                Flow {
+                    sides: [None, None],
+                    last_seen: captured,
+                },
+            );
+        }
+
+        let Some(flow) = self.flows.get_mut(&key) else {
+            return TcpWindow::default();
+        };
+        if tcp.syn {
+            if let Some(handshake) = flow.sides.get_mut(side) {
+                *handshake = Some(Handshake {
+                    maximum_segment_size: tcp.maximum_segment_size,
+                    window_scale: tcp.window_scale,
+                });
+            }
+        }
+        flow.last_seen = captured;
+        let window = flow.window(tcp, side);
+
+        if tcp.fin || tcp.reset {
+            self.flows.remove(&key);
+        }
+
+        window
+    }
+
+    pub fn estimated_size(&self) -> usize {
+        self.flows.len() * size_of::<(FlowKey, Flow)>()
+    }
+
+    pub fn clear(&mut self) {
+        self.flows.clear();
+    }
+
+    // Idle flows go first, then the least recent one
+    fn make_room(&mut self, now: DateTime<Local>) {
+        if self.flows.len() < CAPACITY {
+            return;
+        }
+
+        let timeout = TimeDelta::seconds(IDLE_TIMEOUT_SECONDS);
+        self.flows.retain(|_, flow| now - flow.last_seen <= timeout);
+        if self.flows.len() < CAPACITY {
+            return;
+        }
+        let oldest = self
+            .flows
+            .iter()
+            .min_by_key(|(_, flow)| flow.last_seen)
+            .map(|(key, _)| *key);
+        if let Some(key) = oldest {
+            self.flows.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dpi::protocols::ip::checksum::ChecksumStatus;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    fn client() -> SocketAddr {
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 10)), 50000)
+    }
+
+    fn server() -> SocketAddr {
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::new(93, 184, 216, 34)), 443)
+    }
+
+    fn segment(window: u16) -> TcpDto {
+        TcpDto {
+            port_source: 0,
+            port_destination: 0,
+            checksum_status: ChecksumStatus::NotChecked,
+            window,
+            syn: false,
+            fin: false,
+            reset: false,
+            maximum_segment_size: None,
+            window_scale: None,
+        }
+    }
+
+    fn syn(window: u16, maximum_segment_size: u16, window_scale: Option<u8>) -> TcpDto {
+        TcpDto {
+            syn: true,
+            maximum_segment_size: Some(maximum_segment_size),
+            window_scale,
+            ..segment(window)
+        }
+    }
+
+    #[test]
+    fn test_handshake_scaling() {
+        let now = Local::now();
+        let mut flows = TcpFlows::default();
+
+        // Window of the handshake itself isn't scaled
+        let window = flows.observe(&syn(64240, 1460, Some(8)), (client(), server()), now);
+        assert_eq!(window.effective, Some(64240));
+        assert_eq!(window.maximum_segment_size, None);
+        let window = flows.observe(&syn(65535, 1400, Some(7)), (server(), client()), now);
+        assert_eq!(window.effective, Some(65535));
+        assert_eq!(window.maximum_segment_size, Some(1400));
+
+        // Each side is scaled by its own shift
+        let window = flows.observe(&segment(502), (client(), server()), now);
+        assert_eq!(window.effective, Some(502 << 8));
+        assert_eq!(window.maximum_segment_size, Some(1400));
+        let window = flows.observe(&segment(1000), (server(), client()), now);
+        assert_eq!(window.effective, Some(1000 << 7));
+
+        // FIN closes the flow
+        let fin = TcpDto {
+            fin: true,
+            ..segment(502)
+        };
+        assert_eq!(
+            flows.observe(&fin, (client(), server()), now).effective,
+            Some(502 << 8)
+        );
+        assert!(flows.flows.is_empty());
+        let window = flows.observe(&segment(502), (server(), client()), now);
+        assert_eq!(window, TcpWindow::default());
+    }
+
+    #[test]
+    fn test_scaling_of_one_side() {
+        let now = Local::now();
+        let mut flows = TcpFlows::default();
+
+        // Option, offered by only one side, isn't used
+        flows.observe(&syn(64240, 1460, Some(8)), (client(), server()), now);
+        flows.observe(&syn(8192, 536, None), (server(), client()), now);
+        let window = flows.observe(&segment(502), (client(), server()), now);
+        assert_eq!(window.effective, Some(502));
+        assert_eq!(window.maximum_segment_size, Some(536));
+    }
+
+    #[test]
+    fn test_unknown_handshake() {
+        let now = Local::now();
+        let mut flows = TcpFlows::default();
+
+        // Flow started before the capture
+        let window = flows.observe(&segment(502), (client(), server()), now);
+        assert_eq!(window, TcpWindow::default());
+        assert!(flows.flows.is_empty());
+
+        // SYN-ACK is lost, so the shift is unknown
+        flows.observe(&syn(64240, 1460, Some(8)), (client(), server()), now);
+        let window = flows.observe(&segment(502), (client(), server()), now);
+        assert_eq!(window.effective, None);
+
+        // Idle flow is forgotten
+        let later = now + TimeDelta::seconds(IDLE_TIMEOUT_SECONDS + 1);
+        flows.observe(&segment(502), (client(), server()), later);
+        assert!(flows.flows.is_empty());
+    }
+
+    #[test]
+    fn test_capacity() {
+        let now = Local::now();
+        let mut flows = TcpFlows::default();
+        for port in 0..=CAPACITY as u16 {
+            let client = SocketAddr::new(client().ip(), 1024 + port);
+            let seen = now + TimeDelta::milliseconds(i64::from(port));
+            flows.observe(&syn(64240, 1460, None), (client, server()), seen);
+        }
+
+        assert_eq!(flows.flows.len(), CAPACITY);
+        // The first flow is the least recent one
+        let first = SocketAddr::new(client().ip(), 1024);
+        assert!(
+            !flows
+                .flows
+                .keys()
+                .any(|key| key.1 == first || key.0 == first)
+        );
+    }
+}
@@ -2,6 +2,7 @@ use crate::context::ClientSettings;
 use crate::ws::request::UiClientRequest;
 use chrono::{DateTime, Local};
 use crossbeam::channel::Sender;
+use std::time::Duration;
 
 pub const DEFAULT_PING_DELAY_SECONDS: i64 = 5;
 pub const PING_TIMEOUT_SECONDS: i64 = 5;
@@ -46,6 +47,14 @@ impl Heartbeat {
         false
     }
 
+    // Told to the server on connection, it's the period of the keepalive pings
+    pub fn interval(client_settings: &ClientSettings) -> Duration {
+        let seconds = u64::try_from(client_settings.sync_delay_seconds)
+            .unwrap_or_default()
+            .max(1);
+        Duration::from_secs(seconds)
+    }
+
     pub fn update(&mut self) {
         self.last_sync = Some(Local::now());
         self.ping_sent = false;
@@ -0,0 +1,164 @@
+// Internationalized domain names (IDN). On the wire they're ASCII: every label with
+// the non-ASCII characters is encoded by punycode (RFC 3492) and prefixed with "xn--".
+// Only the decoding is needed, the names are shown & matched, but never sent.
+
+const ACE_PREFIX: &str = "xn--";
+const DELIMITER: char = '-';
+
+// Parameters of the bootstring, chosen by RFC 3492 for the punycode
+const BASE: u32 = 36;
+const T_MIN: u32 = 1;
+const T_MAX: u32 = 26;
+const SKEW: u32 = 38;
+const DAMP: u32 = 700;
+const INITIAL_BIAS: u32 = 72;
+const INITIAL_N: u32 = 0x80;
+
+/// Unicode form of the name. <br>
+/// None, if the name has no encoded labels, or one of them is malformed:
+/// the name is shown as is then.
+pub fn to_unicode(name: &str) -> Option<String> {
+    // Most of the names are plain ASCII, they're checked without the allocations
+    if !name.split('.').any(|label| strip_prefix(label).is_some()) {
+        return None;
+    }
+
+    let labels = name
+        .split('.')
+        .map(|label| match strip_prefix(label) {
+            Some(encoded) => decode(encoded),
+            None => Some(label.to_string()),
+        })
+        .collect::<Option<Vec<String>>>()?;
+
+    Some(labels.join("."))
+}
+
+fn strip_prefix(label: &str) -> Option<&str> {
+    let prefix = label.get(..ACE_PREFIX.len())?;
+    match prefix.eq_ignore_ascii_case(ACE_PREFIX) {
+        true => label.get(ACE_PREFIX.len()..),
+        false => None,
+    }
+}
+
+// Label without the prefix. Only letters, digits & hyphens are valid in the DNS labels
+fn decode(encoded: &str) -> Option<String> {
+    if encoded.is_empty()
+        || !encoded
+            .chars()
+            .all(|value| value.is_ascii_alphanumeric() || value == DELIMITER)
+    {
+        return None;
+    }
+
+    // Basic code points go before the last delimiter
+    let (basic, extended) = match encoded.rfind(DELIMITER) {
+        Some(position) => (
+            encoded.get(..position)?,
+            encoded.get(position.checked_add(1)?..)?,
+        ),
+        None => ("", encoded),
+    };
+    let mut output: Vec<char> = basic.chars().collect();
+
+    let mut n = INITIAL_N;
+    let mut i: u32 = 0;
+    let mut bias = INITIAL_BIAS;
+    let mut digits = extended.chars();
+    let mut next = digits.next();
+    while next.is_some() {
+        let old_i = i;
+        let mut weight: u32 = 1;
+        let mut k = BASE;
+        loop {
+            let digit = digit(next?)?;
+            next = digits.next();
+            i = i.checked_add(digit.checked_mul(weight)?)?;
+            let threshold = match k {
+                k if k <= bias => T_MIN,
+                k if k >= bias.saturating_add(T_MAX) => T_MAX,
+                k => k.checked_sub(bias)?,
+            };
+            if digit < threshold {
+                break;
+            }
+            weight = weight.checked_mul(BASE.checked_sub(threshold)?)?;
+            k = k.checked_add(BASE)?;
+        }
+
+        let length = u32::try_from(output.len()).ok()?.checked_add(1)?;
+        bias = adapt(i.checked_sub(old_i)?, length, old_i == 0)?;
+        n = n.checked_add(i.checked_div(length)?)?;
+        i = i.checked_rem(length)?;
+        // Basic code points can't be encoded
+        if n < INITIAL_N {
+            return None;
+        }
+        output.insert(usize::try_from(i).ok()?, char::from_u32(n)?);
+        i = i.checked_add(1)?;
+    }
+
+    Some(output.into_iter().collect())
+}
+
+fn digit(value: char) -> Option<u32> {
+    match value {
+        'a'..='z' => Some(u32::from(value) - u32::from('a')),
+        'A'..='Z' => Some(u32::from(value) - u32::from('A')),
+        '0'..='9' => Some(u32::from(value) - u32::from('0') + 26),
+        _ => None,
+    }
+}
+
+fn adapt(delta: u32, length: u32, is_first: bool) -> Option<u32> {
+    let mut delta = match is_first {
+        true => delta / DAMP,
+        false => delta / 2,
+    };
+    delta = delta.checked_add(delta.checked_div(length)?)?;
+
+    let mut k: u32 = 0;
+    while delta > ((BASE - T_MIN) * T_MAX) / 2 {
+        delta /= BASE - T_MIN;
+        k = k.checked_add(BASE)?;
+    }
+
+    k.checked_add(((BASE - T_MIN + 1) * delta).checked_div(delta.checked_add(SKEW)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decoding() {
+        let cases = [
+            ("xn--mnchen-3ya.de", "münchen.de"),
+            ("www.xn--bcher-kva.example", "www.bücher.example"),
+            ("xn--fiqs8s", "中国"),
+            ("XN--E1AFMKFD.xn--80akhbyknj4f", "пример.испытание"),
+            ("xn--j1amh.", "укр."),
+        ];
+        for (encoded, decoded) in cases {
+            assert_eq!(to_unicode(encoded).as_deref(), Some(decoded), "{encoded}");
+        }
+    }
+
+    #[test]
+    fn test_raw_names() {
+        // Nothing to decode
+        assert_eq!(to_unicode("example.com"), None);
+        assert_eq!(to_unicode(""), None);
+        // Malformed: cut off, invalid characters & overflow
+        for malformed in [
+            "xn--mnchen-3y.de",
+            "xn--.com",
+            "xn--a_b",
+            "xn--99999999999999",
+            "xn--ls8h.xn--mnchen-3y",
+        ] {
+            assert_eq!(to_unicode(malformed), None, "{malformed}");
+        }
+    }
+}
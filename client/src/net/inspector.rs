@@ -1,4 +1,12 @@
-use crate::ws::data::{Locator, PortDto};
+use crate::net::arp::ArpPairing;
+use crate::net::flows::TcpFlows;
+use crate::net::memory::{FrameId, Records};
+use crate::net::notes::RecordId;
+use crate::net::voip::RtpStreams;
+use crate::ws::data::{Locator, PortDto, WirelessDto};
+use chrono::{DateTime, Local};
+use dpi::dto::fields::FieldIter;
+use dpi::protocols::ProtocolId;
 use dpi::protocols::arp::ArpDto;
 use dpi::protocols::dhcpv4::DHCPv4Dto;
 use dpi::protocols::dhcpv6::DHCPv6Dto;
@@ -6,27 +14,81 @@ use dpi::protocols::dns::DnsDto;
 use dpi::protocols::http::HttpDto;
 use dpi::protocols::icmpv4::ICMPv4Dto;
 use dpi::protocols::icmpv6::ICMPv6Dto;
+use dpi::protocols::ip::checksum::ChecksumStatus;
 use dpi::protocols::ipv4::IPv4Dto;
 use dpi::protocols::ipv6::IPv6Dto;
+use dpi::protocols::nbns::NbnsDto;
+use dpi::protocols::rtp::RtpDto;
+use dpi::protocols::sip::SipDto;
+use dpi::protocols::smb2::Smb2Dto;
+use dpi::protocols::syslog::SyslogDto;
+use dpi::protocols::tftp::TftpDto;
 use strum::IntoEnumIterator;
 use strum_macros::{Display, EnumIter};
 
 #[derive(Default)]
 pub struct InspectorStorage {
-    pub arp: Vec<ArpDto>,
-    pub dhcpv4: Vec<DHCPv4Dto>,
-    pub dhcpv6: Vec<DHCPv6Dto>,
-    pub dns: Vec<DnsDto>,
-    pub ethernet: Vec<Locator>,
-    pub http: Vec<(HttpDto, Locator)>,
-    pub icmpv4: Vec<(ICMPv4Dto, Locator)>,
-    pub icmpv6: Vec<(ICMPv6Dto, Locator)>,
-    pub ipv4: Vec<(IPv4Dto, Locator)>,
-    pub ipv6: Vec<(IPv6Dto, Locator)>,
-    pub tcp: Vec<(PortDto, Locator)>,
-    pub udp: Vec<(PortDto, Locator)>,
+    pub arp: Records<ArpDto>,
+    // Cleared along with the ARP records
+    pub arp_pairing: ArpPairing,
+    pub dhcpv4: Records<DHCPv4Dto>,
+    pub dhcpv6: Records<DHCPv6Dto>,
+    pub dns: Records<DnsDto>,
+    pub ethernet: Records<Locator>,
+    pub http: Records<(HttpDto, Locator)>,
+    pub icmpv4: Records<(ICMPv4Dto, Locator)>,
+    pub icmpv6: Records<(ICMPv6Dto, Locator)>,
+    pub ieee80211: Records<(WirelessDto, Locator)>,
+    pub ipv4: Records<(IPv4Dto, Locator)>,
+    pub ipv6: Records<(IPv6Dto, Locator)>,
+    pub nbns: Records<(NbnsDto, Locator)>,
+    pub rtp: Records<(RtpDto, Locator)>,
+    // Cleared along with the RTP records
+    pub rtp_streams: RtpStreams,
+    pub sip: Records<(SipDto, Locator)>,
+    pub smb2: Records<(Smb2Dto, Locator)>,
+    pub syslog: Records<(SyslogDto, Locator)>,
+    pub tcp: Records<(PortDto, Locator)>,
+    // Cleared along with the TCP records
+    pub tcp_flows: TcpFlows,
+    pub tftp: Records<(TftpDto, Locator)>,
+    pub udp: Records<(PortDto, Locator)>,
+
+    // Ids aren't reset by the clear, so a stale id never points to another frame
+    frames_ingested: FrameId,
+}
+
+/// Record of the frame, shown as a section of its layer stack.
+pub struct FrameLayer {
+    pub protocol: ProtocolsRegistered,
+    // Position in the storage of the protocol
+    pub index: usize,
+    pub fields: Vec<(&'static str, String)>,
 }
 
+// From the datalink to the application, like the layers of the frame are parsed
+const LAYER_ORDER: [ProtocolsRegistered; 19] = [
+    ProtocolsRegistered::Ethernet,
+    ProtocolsRegistered::IEEE80211,
+    ProtocolsRegistered::Arp,
+    ProtocolsRegistered::IPv4,
+    ProtocolsRegistered::IPv6,
+    ProtocolsRegistered::ICMPv4,
+    ProtocolsRegistered::ICMPv6,
+    ProtocolsRegistered::Tcp,
+    ProtocolsRegistered::Udp,
+    ProtocolsRegistered::DHCPv4,
+    ProtocolsRegistered::DHCPv6,
+    ProtocolsRegistered::Dns,
+    ProtocolsRegistered::Http,
+    ProtocolsRegistered::Nbns,
+    ProtocolsRegistered::Rtp,
+    ProtocolsRegistered::Sip,
+    ProtocolsRegistered::Smb2,
+    ProtocolsRegistered::Syslog,
+    ProtocolsRegistered::Tftp,
+];
+
 impl InspectorStorage {
     pub fn len(&self) -> usize {
         let mut sum: usize = 0;
@@ -42,6 +104,42 @@ impl InspectorStorage {
         }
     }
 
+    pub fn estimated_size(&self) -> usize {
+        ProtocolsRegistered::iter()
+            .map(|protocol| self.estimated_size_by_protocol(&protocol))
+            .sum()
+    }
+
+    pub fn estimated_size_by_protocol(&self, protocol: &ProtocolsRegistered) -> usize {
+        match protocol {
+            ProtocolsRegistered::Arp => {
+                self.arp.estimated_size() + self.arp_pairing.estimated_size()
+            },
+            ProtocolsRegistered::DHCPv4 => self.dhcpv4.estimated_size(),
+            ProtocolsRegistered::DHCPv6 => self.dhcpv6.estimated_size(),
+            ProtocolsRegistered::Dns => self.dns.estimated_size(),
+            ProtocolsRegistered::Ethernet => self.ethernet.estimated_size(),
+            ProtocolsRegistered::Http => self.http.estimated_size(),
+            ProtocolsRegistered::ICMPv4 => self.icmpv4.estimated_size(),
+            ProtocolsRegistered::ICMPv6 => self.icmpv6.estimated_size(),
+            ProtocolsRegistered::IEEE80211 => self.ieee80211.estimated_size(),
+            ProtocolsRegistered::IPv4 => self.ipv4.estimated_size(),
+            ProtocolsRegistered::IPv6 => self.ipv6.estimated_size(),
+            ProtocolsRegistered::Nbns => self.nbns.estimated_size(),
+            ProtocolsRegistered::Rtp => {
+                self.rtp.estimated_size() + self.rtp_streams.estimated_size()
+            },
+            ProtocolsRegistered::Sip => self.sip.estimated_size(),
+            ProtocolsRegistered::Smb2 => self.smb2.estimated_size(),
+            ProtocolsRegistered::Syslog => self.syslog.estimated_size(),
+            ProtocolsRegistered::Tcp => {
+                self.tcp.estimated_size() + self.tcp_flows.estimated_size()
+            },
+            ProtocolsRegistered::Tftp => self.tftp.estimated_size(),
+            ProtocolsRegistered::Udp => self.udp.estimated_size(),
+        }
+    }
+
     pub fn records_captured(&self, protocol: &ProtocolsRegistered) -> usize {
         match protocol {
             ProtocolsRegistered::Arp => self.arp.len(),
@@ -52,16 +150,232 @@ impl InspectorStorage {
             ProtocolsRegistered::Http => self.http.len(),
             ProtocolsRegistered::ICMPv4 => self.icmpv4.len(),
             ProtocolsRegistered::ICMPv6 => self.icmpv6.len(),
+            ProtocolsRegistered::IEEE80211 => self.ieee80211.len(),
             ProtocolsRegistered::IPv4 => self.ipv4.len(),
             ProtocolsRegistered::IPv6 => self.ipv6.len(),
+            ProtocolsRegistered::Nbns => self.nbns.len(),
+            ProtocolsRegistered::Rtp => self.rtp.len(),
+            ProtocolsRegistered::Sip => self.sip.len(),
+            ProtocolsRegistered::Smb2 => self.smb2.len(),
+            ProtocolsRegistered::Syslog => self.syslog.len(),
             ProtocolsRegistered::Tcp => self.tcp.len(),
+            ProtocolsRegistered::Tftp => self.tftp.len(),
             ProtocolsRegistered::Udp => self.udp.len(),
         }
     }
 
-    fn clear_by_protocol(&mut self, protocol: &ProtocolsRegistered) {
+    /// Capture times of the protocol records, in the order of the records.
+    pub fn captured(&self, protocol: &ProtocolsRegistered) -> &[DateTime<Local>] {
+        match protocol {
+            ProtocolsRegistered::Arp => self.arp.captured(),
+            ProtocolsRegistered::DHCPv4 => self.dhcpv4.captured(),
+            ProtocolsRegistered::DHCPv6 => self.dhcpv6.captured(),
+            ProtocolsRegistered::Dns => self.dns.captured(),
+            ProtocolsRegistered::Ethernet => self.ethernet.captured(),
+            ProtocolsRegistered::Http => self.http.captured(),
+            ProtocolsRegistered::ICMPv4 => self.icmpv4.captured(),
+            ProtocolsRegistered::ICMPv6 => self.icmpv6.captured(),
+            ProtocolsRegistered::IEEE80211 => self.ieee80211.captured(),
+            ProtocolsRegistered::IPv4 => self.ipv4.captured(),
+            ProtocolsRegistered::IPv6 => self.ipv6.captured(),
+            ProtocolsRegistered::Nbns => self.nbns.captured(),
+            ProtocolsRegistered::Rtp => self.rtp.captured(),
+            ProtocolsRegistered::Sip => self.sip.captured(),
+            ProtocolsRegistered::Smb2 => self.smb2.captured(),
+            ProtocolsRegistered::Syslog => self.syslog.captured(),
+            ProtocolsRegistered::Tcp => self.tcp.captured(),
+            ProtocolsRegistered::Tftp => self.tftp.captured(),
+            ProtocolsRegistered::Udp => self.udp.captured(),
+        }
+    }
+
+    /// Generation of the protocol records, changed by every clear.
+    pub fn generation(&self, protocol: &ProtocolsRegistered) -> u64 {
         match protocol {
-            ProtocolsRegistered::Arp => self.arp.clear(),
+            ProtocolsRegistered::Arp => self.arp.generation(),
+            ProtocolsRegistered::DHCPv4 => self.dhcpv4.generation(),
+            ProtocolsRegistered::DHCPv6 => self.dhcpv6.generation(),
+            ProtocolsRegistered::Dns => self.dns.generation(),
+            ProtocolsRegistered::Ethernet => self.ethernet.generation(),
+            ProtocolsRegistered::Http => self.http.generation(),
+            ProtocolsRegistered::ICMPv4 => self.icmpv4.generation(),
+            ProtocolsRegistered::ICMPv6 => self.icmpv6.generation(),
+            ProtocolsRegistered::IEEE80211 => self.ieee80211.generation(),
+            ProtocolsRegistered::IPv4 => self.ipv4.generation(),
+            ProtocolsRegistered::IPv6 => self.ipv6.generation(),
+            ProtocolsRegistered::Nbns => self.nbns.generation(),
+            ProtocolsRegistered::Rtp => self.rtp.generation(),
+            ProtocolsRegistered::Sip => self.sip.generation(),
+            ProtocolsRegistered::Smb2 => self.smb2.generation(),
+            ProtocolsRegistered::Syslog => self.syslog.generation(),
+            ProtocolsRegistered::Tcp => self.tcp.generation(),
+            ProtocolsRegistered::Tftp => self.tftp.generation(),
+            ProtocolsRegistered::Udp => self.udp.generation(),
+        }
+    }
+
+    /// Stored IPv4, TCP & UDP records with the wrong checksum.
+    /// <br> Checksums are validated by the server, if it's enabled in its config.
+    pub fn invalid_checksums(&self) -> usize {
+        let is_invalid = |status: &ChecksumStatus| *status == ChecksumStatus::Invalid;
+
+        let ipv4 = self
+            .ipv4
+            .iter()
+            .filter(|(dto, _)| is_invalid(&dto.checksum_status))
+            .count();
+        let transport = self
+            .tcp
+            .iter()
+            .chain(self.udp.iter())
+            .filter(|(dto, _)| is_invalid(&dto.checksum_status))
+            .count();
+
+        ipv4 + transport
+    }
+
+    /// Stored UDP records, which length field disagrees with the IP payload.
+    pub fn udp_length_mismatches(&self) -> usize {
+        self.udp
+            .iter()
+            .filter(|(dto, _)| dto.length_mismatch.is_some())
+            .count()
+    }
+
+    /// Id of the frame, which records are going to be pushed.
+    pub fn next_frame(&mut self) -> FrameId {
+        let frame = self.frames_ingested;
+        self.frames_ingested = self.frames_ingested.wrapping_add(1);
+        frame
+    }
+
+    /// Frame of the record, `None` if the record is cleared since it was shown.
+    pub fn frame_of(&self, id: &RecordId) -> Option<FrameId> {
+        if self.generation(&id.protocol) != id.generation {
+            return None;
+        }
+
+        let index = id.index;
+        match id.protocol {
+            ProtocolsRegistered::Arp => self.arp.frame_at(index),
+            ProtocolsRegistered::DHCPv4 => self.dhcpv4.frame_at(index),
+            ProtocolsRegistered::DHCPv6 => self.dhcpv6.frame_at(index),
+            ProtocolsRegistered::Dns => self.dns.frame_at(index),
+            ProtocolsRegistered::Ethernet => self.ethernet.frame_at(index),
+            ProtocolsRegistered::Http => self.http.frame_at(index),
+            ProtocolsRegistered::ICMPv4 => self.icmpv4.frame_at(index),
+            ProtocolsRegistered::ICMPv6 => self.icmpv6.frame_at(index),
+            ProtocolsRegistered::IEEE80211 => self.ieee80211.frame_at(index),
+            ProtocolsRegistered::IPv4 => self.ipv4.frame_at(index),
+            ProtocolsRegistered::IPv6 => self.ipv6.frame_at(index),
+            ProtocolsRegistered::Nbns => self.nbns.frame_at(index),
+            ProtocolsRegistered::Rtp => self.rtp.frame_at(index),
+            ProtocolsRegistered::Sip => self.sip.frame_at(index),
+            ProtocolsRegistered::Smb2 => self.smb2.frame_at(index),
+            ProtocolsRegistered::Syslog => self.syslog.frame_at(index),
+            ProtocolsRegistered::Tcp => self.tcp.frame_at(index),
+            ProtocolsRegistered::Tftp => self.tftp.frame_at(index),
+            ProtocolsRegistered::Udp => self.udp.frame_at(index),
+        }
+    }
+
+    /// Stored records of the frame, from the datalink layer to the application one.
+    /// <br> Layers, that are cleared or dropped by the frames limit, are missing.
+    pub fn frame_layers(&self, frame: FrameId) -> Vec<FrameLayer> {
+        LAYER_ORDER
+            .iter()
+            .flat_map(|protocol| {
+                self.layer_fields(protocol, frame)
+                    .into_iter()
+                    .map(|(index, fields)| FrameLayer {
+                        protocol: *protocol,
+                        index,
+                        fields,
+                    })
+            })
+            .collect()
+    }
+
+    fn layer_fields(
+        &self, protocol: &ProtocolsRegistered, frame: FrameId,
+    ) -> Vec<(usize, Vec<(&'static str, String)>)> {
+        match protocol {
+            ProtocolsRegistered::Arp => {
+                Self::fields_of(&self.arp, frame, |dto| dto.fields())
+            },
+            ProtocolsRegistered::DHCPv4 => {
+                Self::fields_of(&self.dhcpv4, frame, |dto| dto.fields())
+            },
+            ProtocolsRegistered::DHCPv6 => {
+                Self::fields_of(&self.dhcpv6, frame, |dto| dto.fields())
+            },
+            ProtocolsRegistered::Dns => {
+                Self::fields_of(&self.dns, frame, |dto| dto.fields())
+            },
+            ProtocolsRegistered::Ethernet => {
+                Self::fields_of(&self.ethernet, frame, |locator| locator.fields())
+            },
+            ProtocolsRegistered::Http => {
+                Self::fields_of(&self.http, frame, |(dto, _)| dto.fields())
+            },
+            ProtocolsRegistered::ICMPv4 => {
+                Self::fields_of(&self.icmpv4, frame, |(dto, _)| dto.fields())
+            },
+            ProtocolsRegistered::ICMPv6 => {
+                Self::fields_of(&self.icmpv6, frame, |(dto, _)| dto.fields())
+            },
+            ProtocolsRegistered::IEEE80211 => {
+                Self::fields_of(&self.ieee80211, frame, |(dto, _)| dto.fields())
+            },
+            ProtocolsRegistered::IPv4 => {
+                Self::fields_of(&self.ipv4, frame, |(dto, _)| dto.fields())
+            },
+            ProtocolsRegistered::IPv6 => {
+                Self::fields_of(&self.ipv6, frame, |(dto, _)| dto.fields())
+            },
+            ProtocolsRegistered::Nbns => {
+                Self::fields_of(&self.nbns, frame, |(dto, _)| dto.fields())
+            },
+            ProtocolsRegistered::Rtp => {
+                Self::fields_of(&self.rtp, frame, |(dto, _)| dto.fields())
+            },
+            ProtocolsRegistered::Sip => {
+                Self::fields_of(&self.sip, frame, |(dto, _)| dto.fields())
+            },
+            ProtocolsRegistered::Smb2 => {
+                Self::fields_of(&self.smb2, frame, |(dto, _)| dto.fields())
+            },
+            ProtocolsRegistered::Syslog => {
+                Self::fields_of(&self.syslog, frame, |(dto, _)| dto.fields())
+            },
+            ProtocolsRegistered::Tcp => {
+                Self::fields_of(&self.tcp, frame, |(dto, _)| dto.fields())
+            },
+            ProtocolsRegistered::Tftp => {
+                Self::fields_of(&self.tftp, frame, |(dto, _)| dto.fields())
+            },
+            ProtocolsRegistered::Udp => {
+                Self::fields_of(&self.udp, frame, |(dto, _)| dto.fields())
+            },
+        }
+    }
+
+    fn fields_of<T>(
+        records: &Records<T>, frame: FrameId,
+        fields: impl Fn(&T) -> Vec<(&'static str, String)>,
+    ) -> Vec<(usize, Vec<(&'static str, String)>)> {
+        records
+            .indices_of_frame(frame)
+            .filter_map(|index| records.get(index).map(|record| (index, fields(record))))
+            .collect()
+    }
+
+    pub fn clear_by_protocol(&mut self, protocol: &ProtocolsRegistered) {
+        match protocol {
+            ProtocolsRegistered::Arp => {
+                self.arp.clear();
+                self.arp_pairing.clear();
+            },
             ProtocolsRegistered::DHCPv4 => self.dhcpv4.clear(),
             ProtocolsRegistered::DHCPv6 => self.dhcpv6.clear(),
             ProtocolsRegistered::Dns => self.dns.clear(),
@@ -69,15 +383,28 @@ impl InspectorStorage {
             ProtocolsRegistered::Http => self.http.clear(),
             ProtocolsRegistered::ICMPv4 => self.icmpv4.clear(),
             ProtocolsRegistered::ICMPv6 => self.icmpv6.clear(),
+            ProtocolsRegistered::IEEE80211 => self.ieee80211.clear(),
             ProtocolsRegistered::IPv4 => self.ipv4.clear(),
             ProtocolsRegistered::IPv6 => self.ipv6.clear(),
-            ProtocolsRegistered::Tcp => self.tcp.clear(),
+            ProtocolsRegistered::Nbns => self.nbns.clear(),
+            ProtocolsRegistered::Rtp => {
+                self.rtp.clear();
+                self.rtp_streams.clear();
+            },
+            ProtocolsRegistered::Sip => self.sip.clear(),
+            ProtocolsRegistered::Smb2 => self.smb2.clear(),
+            ProtocolsRegistered::Syslog => self.syslog.clear(),
+            ProtocolsRegistered::Tcp => {
+                self.tcp.clear();
+                self.tcp_flows.clear();
+            },
+            ProtocolsRegistered::Tftp => self.tftp.clear(),
             ProtocolsRegistered::Udp => self.udp.clear(),
         }
     }
 }
 
-#[derive(Debug, Clone, Display, EnumIter)]
+#[derive(Debug, Clone, Copy, Display, EnumIter, PartialEq, Eq, Hash)]
 pub enum ProtocolsRegistered {
     #[strum(to_string = "ARP")]
     Arp,
@@ -95,11 +422,73 @@ pub enum ProtocolsRegistered {
 
     ICMPv4,
     ICMPv6,
+
+    #[strum(to_string = "IEEE 802.11")]
+    IEEE80211,
+
     IPv4,
     IPv6,
 
+    #[strum(to_string = "NBNS")]
+    Nbns,
+    #[strum(to_string = "RTP")]
+    Rtp,
+    #[strum(to_string = "SIP")]
+    Sip,
+    #[strum(to_string = "SMB2")]
+    Smb2,
+    Syslog,
+
     #[strum(to_string = "TCP")]
     Tcp,
+    #[strum(to_string = "TFTP")]
+    Tftp,
     #[strum(to_string = "UDP")]
     Udp,
 }
+
+impl ProtocolsRegistered {
+    pub fn id(&self) -> ProtocolId {
+        match self {
+            ProtocolsRegistered::Arp => ProtocolId::Arp,
+            ProtocolsRegistered::DHCPv4 => ProtocolId::DHCPv4,
+            ProtocolsRegistered::DHCPv6 => ProtocolId::DHCPv6,
+            ProtocolsRegistered::Dns => ProtocolId::DNS,
+            ProtocolsRegistered::Ethernet => ProtocolId::Ethernet,
+            ProtocolsRegistered::Http => ProtocolId::HTTP,
+            ProtocolsRegistered::ICMPv4 => ProtocolId::ICMPv4,
+            ProtocolsRegistered::ICMPv6 => ProtocolId::ICMPv6,
+            ProtocolsRegistered::IEEE80211 => ProtocolId::IEEE80211,
+            ProtocolsRegistered::IPv4 => ProtocolId::IPv4,
+            ProtocolsRegistered::IPv6 => ProtocolId::IPv6,
+            ProtocolsRegistered::Nbns => ProtocolId::NBNS,
+            ProtocolsRegistered::Rtp => ProtocolId::RTP,
+            ProtocolsRegistered::Sip => ProtocolId::SIP,
+            ProtocolsRegistered::Smb2 => ProtocolId::SMB2,
+            ProtocolsRegistered::Syslog => ProtocolId::Syslog,
+            ProtocolsRegistered::Tcp => ProtocolId::TCP,
+            ProtocolsRegistered::Tftp => ProtocolId::TFTP,
+            ProtocolsRegistered::Udp => ProtocolId::UDP,
+        }
+    }
+
+    /// Storage of the protocol records. Radio information is kept along with 802.11 frames.
+    pub fn from_id(id: &ProtocolId) -> Option<Self> {
+        match id {
+            ProtocolId::Radiotap => Some(Self::IEEE80211),
+            id => Self::iter().find(|protocol| protocol.id() == *id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layer_order_complete() {
+        for protocol in ProtocolsRegistered::iter() {
+            assert!(LAYER_ORDER.contains(&protocol), "{protocol} has no layer");
+        }
+    }
+}
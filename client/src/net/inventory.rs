@@ -0,0 +1,296 @@
+use crate::errors::FileError;
+use crate::net::anonymize::Anonymizer;
+use crate::net::device;
+use crate::net::device::DeviceStorage;
+use chrono::{DateTime, Local};
+use std::path::Path;
+use strum_macros::{Display, EnumIter};
+
+pub const HTML_FILTER_NAME: &str = "HTML";
+pub const HTML_FILTER_EXTENSIONS: &[&str] = &["html"];
+
+// Reports are read outside the client, so the times aren't localized
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+const TITLE: &str = "Device Inventory";
+const COLUMNS: [&str; 9] = [
+    "Alias",
+    "Hostname",
+    "MAC",
+    "Vendor",
+    "IPv4",
+    "IPv6",
+    "Type",
+    "First Seen",
+    "Last Seen",
+];
+const STYLE: &str = "body { font-family: sans-serif; margin: 24px; color: #222; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 4px 8px; text-align: left; vertical-align: top; }
+th { background: #eee; }
+tr:nth-child(even) td { background: #fafafa; }";
+
+#[derive(Clone, Copy, Debug, Display, EnumIter, PartialEq)]
+pub enum InventoryFormat {
+    #[strum(to_string = "CSV")]
+    Csv,
+    #[strum(to_string = "HTML")]
+    Html,
+}
+
+impl InventoryFormat {
+    pub fn filter_name(&self) -> &'static str {
+        match self {
+            Self::Csv => device::CSV_FILTER_NAME,
+            Self::Html => HTML_FILTER_NAME,
+        }
+    }
+
+    pub fn filter_extensions(&self) -> &'static [&'static str] {
+        match self {
+            Self::Csv => device::CSV_FILTER_EXTENSIONS,
+            Self::Html => HTML_FILTER_EXTENSIONS,
+        }
+    }
+
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            Self::Csv => "inventory.csv",
+            Self::Html => "inventory.html",
+        }
+    }
+}
+
+/// Devices of the list at the moment of the export. Hidden ones aren't listed, as in the tab.
+pub struct Inventory {
+    pub interface: Option<String>,
+    pub generated: DateTime<Local>,
+    pub rows: Vec<InventoryRow>,
+}
+
+pub struct InventoryRow {
+    pub alias: Option<String>,
+    pub hostname: Option<String>,
+    pub mac: String,
+    pub vendor: Option<String>,
+    pub ipv4: Vec<String>,
+    pub ipv6: Vec<String>,
+    // Set manually or guessed by the traffic
+    pub device_type: Option<String>,
+    pub first_seen: DateTime<Local>,
+    pub last_seen: DateTime<Local>,
+}
+
+impl InventoryRow {
+    fn fields(&self, separator: &str) -> [String; 9] {
+        [
+            self.alias.clone().unwrap_or_default(),
+            self.hostname.clone().unwrap_or_default(),
+            self.mac.clone(),
+            self.vendor.clone().unwrap_or_default(),
+            self.ipv4.join(separator),
+            self.ipv6.join(separator),
+            self.device_type.clone().unwrap_or_default(),
+            self.first_seen.format(TIME_FORMAT).to_string(),
+            self.last_seen.format(TIME_FORMAT).to_string(),
+        ]
+    }
+}
+
+impl Inventory {
+    /// Aliases are set by the user, they're kept by the anonymization.
+    pub fn new(
+        devices: &DeviceStorage, interface: Option<String>,
+        mut anonymizer: Option<Anonymizer>,
+    ) -> Self {
+        let rows = devices
+            .list()
+            .iter()
+            .filter(|device| !devices.is_hidden(&device.mac))
+            .map(|device| {
+                let anonymized = anonymizer.as_mut().map(|value| value.device(device));
+                let shown = anonymized.as_ref().unwrap_or(device);
+                InventoryRow {
+                    alias: devices.aliases.get(&device.mac).cloned(),
+                    hostname: shown.hostname.clone(),
+                    mac: shown.mac.to_string(),
+                    vendor: shown.vendor.as_ref().map(|vendor| vendor.full.clone()),
+                    ipv4: shown.ip.iter().map(ToString::to_string).collect(),
+                    ipv6: shown.ipv6.iter().map(ToString::to_string).collect(),
+                    device_type: devices
+                        .device_type(device)
+                        .map(|value| value.to_string()),
+                    first_seen: device.first_seen,
+                    last_seen: device.last_seen,
+                }
+            })
+            .collect();
+
+        Self {
+            interface,
+            generated: Local::now(),
+            rows,
+        }
+    }
+
+    /// Earliest & latest sightings of the listed devices.
+    pub fn time_range(&self) -> Option<(DateTime<Local>, DateTime<Local>)> {
+        let start = self.rows.iter().map(|row| row.first_seen).min()?;
+        let end = self.rows.iter().map(|row| row.last_seen).max()?;
+
+        Some((start, end))
+    }
+
+    pub fn save(&self, format: InventoryFormat, path: &Path) -> Result<(), FileError> {
+        let data = match format {
+            InventoryFormat::Csv => self.to_csv(),
+            InventoryFormat::Html => self.to_html(),
+        };
+        std::fs::write(path, data)?;
+
+        Ok(())
+    }
+
+    // Report header goes as the comment lines before the table
+    fn to_csv(&self) -> String {
+        let mut csv = String::new();
+        for (key, value) in self.header() {
+            csv.push_str(&format!("# {key}: {value}\n"));
+        }
+        csv.push_str(&COLUMNS.join(","));
+        csv.push('\n');
+        for row in &self.rows {
+            let fields: Vec<String> = row
+                .fields(" ")
+                .iter()
+                .map(|field| device::csv_field(field))
+                .collect();
+            csv.push_str(&fields.join(","));
+            csv.push('\n');
+        }
+
+        csv
+    }
+
+    // Single file with the inline style, so it's opened anywhere
+    fn to_html(&self) -> String {
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>{TITLE}</title>\n<style>\n{STYLE}\n</style>\n</head>\n<body>\n\
+             <h1>{TITLE}</h1>\n<p>\n"
+        );
+        let header: Vec<String> = self
+            .header()
+            .iter()
+            .map(|(key, value)| format!("<b>{key}:</b> {}", escape_html(value)))
+            .collect();
+        html.push_str(&header.join("<br>\n"));
+        html.push_str("\n</p>\n<table>\n<tr>");
+        for column in COLUMNS {
+            html.push_str(&format!("<th>{column}</th>"));
+        }
+        html.push_str("</tr>\n");
+        for row in &self.rows {
+            html.push_str("<tr>");
+            for field in row.fields("\n") {
+                let field = escape_html(&field).replace('\n', "<br>");
+                html.push_str(&format!("<td>{field}</td>"));
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</table>\n</body>\n</html>\n");
+
+        html
+    }
+
+    fn header(&self) -> Vec<(&'static str, String)> {
+        let range = match self.time_range() {
+            Some((start, end)) => format!(
+                "{} - {}",
+                start.format(TIME_FORMAT),
+                end.format(TIME_FORMAT)
+            ),
+            None => "-".to_string(),
+        };
+
+        vec![
+            (
+                "Interface",
+                self.interface.clone().unwrap_or_else(|| "-".to_string()),
+            ),
+            ("Time Range", range),
+            ("Generated", self.generated.format(TIME_FORMAT).to_string()),
+            ("Devices", self.rows.len().to_string()),
+        ]
+    }
+}
+
+fn escape_html(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn time(hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2025, 3, 7, hour, minute, 0)
+            .single()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_csv() {
+        let inventory = Inventory {
+            interface: Some("eth0".to_string()),
+            generated: time(12, 0),
+            rows: vec![
+                InventoryRow {
+                    alias: Some("Router, main".to_string()),
+                    hostname: None,
+                    mac: "00:1A:8C:15:F9:80".to_string(),
+                    vendor: Some("Sophos Ltd".to_string()),
+                    ipv4: vec!["192.168.0.1".to_string()],
+                    ipv6: vec!["fd00::1".to_string()],
+                    device_type: Some("Router".to_string()),
+                    first_seen: time(9, 5),
+                    last_seen: time(11, 30),
+                },
+                InventoryRow {
+                    alias: None,
+                    hostname: Some("LAPTOP".to_string()),
+                    mac: "40:61:86:9A:F1:F5".to_string(),
+                    vendor: None,
+                    ipv4: vec!["192.168.0.10".to_string(), "10.0.0.10".to_string()],
+                    ipv6: vec![],
+                    device_type: None,
+                    first_seen: time(9, 0),
+                    last_seen: time(10, 15),
+                },
+            ],
+        };
+
+        assert_eq!(
+            inventory.to_csv(),
+            "# Interface: eth0\n\
+             # Time Range: 2025-03-07 09:00:00 - 2025-03-07 11:30:00\n\
+             # Generated: 2025-03-07 12:00:00\n\
+             # Devices: 2\n\
+             Alias,Hostname,MAC,Vendor,IPv4,IPv6,Type,First Seen,Last Seen\n\
+             \"Router, main\",,00:1A:8C:15:F9:80,Sophos Ltd,192.168.0.1,fd00::1,Router,\
+             2025-03-07 09:05:00,2025-03-07 11:30:00\n\
+             ,LAPTOP,40:61:86:9A:F1:F5,,192.168.0.10 10.0.0.10,,,\
+             2025-03-07 09:00:00,2025-03-07 10:15:00\n"
+        );
+
+        let html = inventory.to_html();
+        assert!(html.contains("<td>Router, main</td>"));
+        assert!(html.contains("<td>192.168.0.10<br>10.0.0.10</td>"));
+        assert!(!html.contains("<link") && !html.contains("<script"));
+    }
+}
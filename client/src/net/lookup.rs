@@ -1,15 +1,58 @@
-use dpi::analysis::ports::{PortInfo, PortServiceTable};
+use dpi::analysis::ports::{DatabaseFormat, PortInfo, PortServiceTable};
 use dpi::analysis::vendor::OuiRadixTree;
+use dpi::protocols::ProtocolId;
 use dpi::protocols::ethernet::mac::{MacAddress, Vendor};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
+use strum_macros::{Display, EnumIter, EnumString};
+
+/// Display names of the ports, set by the user. They're above any database.
+pub type PortOverrides = BTreeMap<u16, String>;
 
 #[derive(Clone)]
 pub struct Lookup {
+    // Bundled database
     pub port_service: PortServiceTable,
+    pub port_import: Option<PortImport>,
+    pub port_overrides: PortOverrides,
     pub vendors: OuiRadixTree,
     pub vendors_amount: usize,
 }
 
+/// Database, imported from the file (e.g. taken from Wireshark or Nmap).
+#[derive(Clone)]
+pub struct PortImport {
+    pub path: String,
+    pub format: DatabaseFormat,
+    pub mode: ImportMode,
+    pub table: PortServiceTable,
+    pub skipped: usize,
+}
+
+#[derive(Clone, Copy, Debug, Default, Display, EnumIter, EnumString, PartialEq)]
+pub enum ImportMode {
+    // Bundled database is consulted for the ports, missing in the imported one
+    #[default]
+    Merge,
+    Replace,
+}
+
+impl ImportMode {
+    pub fn localize(&self) -> String {
+        match self {
+            ImportMode::Merge => t!("Lookup.ImportMode.Merge").to_string(),
+            ImportMode::Replace => t!("Lookup.ImportMode.Replace").to_string(),
+        }
+    }
+}
+
+pub fn localize_format(format: &DatabaseFormat) -> String {
+    match format {
+        DatabaseFormat::IanaCsv => t!("Lookup.Format.IanaCsv").to_string(),
+        DatabaseFormat::NmapServices => t!("Lookup.Format.NmapServices").to_string(),
+    }
+}
+
 const PORTS_DATABASE_PATH: &str = "resources/iana-port-service-database.csv";
 const OUI_DATABASE_PATH: &str = "resources/oui-database.txt";
 
@@ -22,16 +65,134 @@ impl Lookup {
 
         Ok(Self {
             port_service,
+            port_import: None,
+            port_overrides: Default::default(),
             vendors,
             vendors_amount,
         })
     }
 
-    pub fn find_port(&self, port: &u16) -> Option<&Vec<PortInfo>> {
-        self.port_service.get(port)
+    // Used by the tests & if the databases aren't read, so ports and vendors aren't found
+    pub fn empty() -> Self {
+        Self {
+            port_service: Default::default(),
+            port_import: None,
+            port_overrides: Default::default(),
+            vendors: OuiRadixTree {
+                path: vec![],
+                data: None,
+                indices: vec![],
+                nodes: vec![],
+            },
+            vendors_amount: 0,
+        }
+    }
+
+    /// Previous import is replaced. Malformed lines of the file are skipped & counted.
+    pub fn import_ports(
+        &mut self, path: &str, format: DatabaseFormat, mode: ImportMode,
+    ) -> std::io::Result<&PortImport> {
+        let data = std::fs::read_to_string(path)?;
+        let database = dpi::analysis::ports::parse_database(&data, format);
+
+        Ok(self.port_import.insert(PortImport {
+            path: path.to_string(),
+            format,
+            mode,
+            table: database.table,
+            skipped: database.skipped,
+        }))
+    }
+
+    /// Name of the service on the port: user overrides, then the imported database,
+    /// then the bundled one (unless it's replaced by the import).
+    pub fn find_service(&self, port: &u16, protocol: ProtocolId) -> Option<String> {
+        if let Some(name) = self.port_overrides.get(port) {
+            return Some(name.clone());
+        }
+
+        if let Some(import) = &self.port_import {
+            let name = import
+                .table
+                .get(port)
+                .and_then(|info_vec| Self::service_by_protocol(info_vec, protocol));
+            if name.is_some() || import.mode == ImportMode::Replace {
+                return name;
+            }
+        }
+
+        self.port_service
+            .get(port)
+            .and_then(|info_vec| Self::service_by_protocol(info_vec, protocol))
+    }
+
+    fn service_by_protocol(vec: &[PortInfo], protocol: ProtocolId) -> Option<String> {
+        vec.iter()
+            .find(|info| {
+                info.transport_protocol
+                    .to_ascii_lowercase()
+                    .eq(&protocol.to_string().to_ascii_lowercase())
+            })
+            .map(|info| info.service_name.clone())
     }
 
     pub fn find_vendor(&self, mac: &MacAddress) -> Option<Vendor> {
         dpi::analysis::vendor::lookup_vendor(&self.vendors, mac)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(port: u16, service_name: &str) -> PortInfo {
+        PortInfo {
+            port,
+            service_name: service_name.to_string(),
+            transport_protocol: "tcp".to_string(),
+            description: String::new(),
+        }
+    }
+
+    fn lookup_with(mode: ImportMode) -> Lookup {
+        let mut lookup = Lookup::empty();
+        lookup.port_service = PortServiceTable::from([
+            (80, vec![info(80, "http")]),
+            (8443, vec![info(8443, "pcsync-https")]),
+            (9000, vec![info(9000, "cslistener")]),
+        ]);
+        lookup.port_import = Some(PortImport {
+            path: String::new(),
+            format: DatabaseFormat::NmapServices,
+            mode,
+            table: PortServiceTable::from([
+                (8443, vec![info(8443, "https-alt")]),
+                (9000, vec![info(9000, "cslistener")]),
+            ]),
+            skipped: 0,
+        });
+        lookup
+            .port_overrides
+            .insert(9000, "our-admin-panel".to_string());
+        lookup
+    }
+
+    #[test]
+    fn test_precedence() {
+        let lookup = lookup_with(ImportMode::Merge);
+        let find = |port: u16| lookup.find_service(&port, ProtocolId::TCP);
+        assert_eq!(find(9000).as_deref(), Some("our-admin-panel"));
+        assert_eq!(find(8443).as_deref(), Some("https-alt"));
+        assert_eq!(find(80).as_deref(), Some("http"));
+        assert_eq!(find(22), None);
+        // Protocol of the entry is matched
+        assert_eq!(lookup.find_service(&8443, ProtocolId::UDP), None);
+
+        // Bundled database isn't consulted, overrides are
+        let lookup = lookup_with(ImportMode::Replace);
+        let find = |port: u16| lookup.find_service(&port, ProtocolId::TCP);
+        assert_eq!(find(80), None);
+        assert_eq!(find(8443).as_deref(), Some("https-alt"));
+        assert_eq!(find(9000).as_deref(), Some("our-admin-panel"));
+    }
+}
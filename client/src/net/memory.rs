@@ -0,0 +1,543 @@
+use crate::ws::data::{Locator, PortDto, WirelessDto};
+use chrono::{DateTime, Local};
+use dpi::dto::frame::OwnedFrame;
+use dpi::protocols::arp::{ArpDto, HardwareAddress, ProtocolAddress};
+use dpi::protocols::dhcpv4::DHCPv4Dto;
+use dpi::protocols::dhcpv6;
+use dpi::protocols::dhcpv6::DHCPv6Dto;
+use dpi::protocols::dns::{
+    DnsDto, DnsTypeData, EdnsOption, QuestionEntry, ResourceRecord,
+};
+use dpi::protocols::http::{Header, HttpDto};
+use dpi::protocols::icmpv4::ICMPv4Dto;
+use dpi::protocols::icmpv6::ICMPv6Dto;
+use dpi::protocols::ipv4::IPv4Dto;
+use dpi::protocols::ipv4::option::OptionData;
+use dpi::protocols::ipv6::IPv6Dto;
+use dpi::protocols::nbns::{NameRecord, NameRecordData, NbnsDto};
+use dpi::protocols::rtp::RtpDto;
+use dpi::protocols::sip::{RtpMap, SdpMedia, SipDto};
+use dpi::protocols::smb2::{SessionUser, Smb2Dto};
+use dpi::protocols::syslog::SyslogDto;
+use dpi::protocols::tftp::{TftpDto, TftpOption};
+use std::ops::{Deref, Range};
+
+pub const BYTES_IN_MEGABYTE: usize = 1024 * 1024;
+
+/// Number of the frame, assigned at ingest. Records of the same frame share it.
+pub type FrameId = u64;
+
+/// Approximate amount of memory taken by the value, including heap allocations.
+pub trait EstimatedSize {
+    fn estimated_size(&self) -> usize;
+}
+
+/// Vector that keeps the estimated size of its elements, their capture time & frame. <br>
+/// Size is maintained on push & clear, so it's not recomputed every frame.
+pub struct Records<T> {
+    vec: Vec<T>,
+    captured: Vec<DateTime<Local>>,
+    // Frame ids are assigned in order of the ingest, so they're sorted
+    frames: Vec<FrameId>,
+    // Indices of the records from the frames, cut off by the snapshot length. Sorted.
+    truncated: Vec<usize>,
+    bytes: usize,
+    // Incremented on every clear, so consumers caching indices know they're stale.
+    generation: u64,
+}
+
+impl<T> Default for Records<T> {
+    fn default() -> Self {
+        Self {
+            vec: Vec::new(),
+            captured: Vec::new(),
+            frames: Vec::new(),
+            truncated: Vec::new(),
+            bytes: 0,
+            generation: 0,
+        }
+    }
+}
+
+impl<T: EstimatedSize> Records<T> {
+    pub fn push(&mut self, value: T, captured: DateTime<Local>, frame: FrameId) {
+        let size =
+            value.estimated_size() + size_of::<DateTime<Local>>() + size_of::<FrameId>();
+        self.bytes = self.bytes.saturating_add(size);
+        self.vec.push(value);
+        self.captured.push(captured);
+        self.frames.push(frame);
+    }
+
+    pub fn push_truncated(
+        &mut self, value: T, captured: DateTime<Local>, frame: FrameId,
+    ) {
+        self.truncated.push(self.vec.len());
+        self.bytes = self.bytes.saturating_add(size_of::<usize>());
+        self.push(value, captured, frame);
+    }
+}
+
+impl<T> Records<T> {
+    pub fn clear(&mut self) {
+        self.vec.clear();
+        self.captured.clear();
+        self.frames.clear();
+        self.truncated.clear();
+        self.bytes = 0;
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    pub fn estimated_size(&self) -> usize {
+        self.bytes
+    }
+
+    pub fn captured_at(&self, index: usize) -> Option<&DateTime<Local>> {
+        self.captured.get(index)
+    }
+
+    pub fn captured(&self) -> &[DateTime<Local>] {
+        &self.captured
+    }
+
+    pub fn frame_at(&self, index: usize) -> Option<FrameId> {
+        self.frames.get(index).copied()
+    }
+
+    /// Indices of the records, that belong to the frame. Tunnels may give a few of them.
+    pub fn indices_of_frame(&self, frame: FrameId) -> Range<usize> {
+        let start = self.frames.partition_point(|id| *id < frame);
+        let end = self.frames.partition_point(|id| *id <= frame);
+        start..end
+    }
+
+    pub fn is_truncated(&self, index: usize) -> bool {
+        self.truncated.binary_search(&index).is_ok()
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+}
+
+impl<T> Deref for Records<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &Self::Target {
+        &self.vec
+    }
+}
+
+fn string_size(value: &str) -> usize {
+    value.len()
+}
+
+fn header_size(header: &Header) -> usize {
+    size_of::<Header>() + string_size(&header.0) + string_size(&header.1)
+}
+
+fn resource_records_size(records: &[ResourceRecord]) -> usize {
+    records
+        .iter()
+        .map(|record| {
+            let data = match &record.data {
+                DnsTypeData::CNAME(name) | DnsTypeData::NS(name) => string_size(name),
+                DnsTypeData::SOA {
+                    primary_name_server,
+                    mailbox,
+                    ..
+                } => string_size(primary_name_server) + string_size(mailbox),
+                DnsTypeData::Opt { options, .. } => options
+                    .iter()
+                    .map(|option| size_of::<EdnsOption>() + option.data.len())
+                    .sum(),
+                _ => 0,
+            };
+            size_of::<ResourceRecord>() + string_size(&record.name) + data
+        })
+        .sum()
+}
+
+impl<A: EstimatedSize, B: EstimatedSize> EstimatedSize for (A, B) {
+    fn estimated_size(&self) -> usize {
+        self.0.estimated_size() + self.1.estimated_size()
+    }
+}
+
+impl EstimatedSize for ArpDto {
+    fn estimated_size(&self) -> usize {
+        let hardware = |address: &HardwareAddress| match address {
+            HardwareAddress::Mac(_) => 0,
+            HardwareAddress::Raw(raw) => raw.0.len(),
+        };
+        let protocol = |address: &ProtocolAddress| match address {
+            ProtocolAddress::IPv4(_) => 0,
+            ProtocolAddress::Raw(raw) => raw.0.len(),
+        };
+
+        size_of::<Self>()
+            + hardware(&self.sender_hardware)
+            + hardware(&self.target_hardware)
+            + protocol(&self.sender_protocol)
+            + protocol(&self.target_protocol)
+    }
+}
+
+impl EstimatedSize for DHCPv4Dto {
+    // Capacity, the options are moved from the parsed packet as they are
+    fn estimated_size(&self) -> usize {
+        let vendor_class = self
+            .vendor_class
+            .as_ref()
+            .map(String::capacity)
+            .unwrap_or_default();
+
+        size_of::<Self>() + vendor_class + self.parameter_request_list.capacity()
+    }
+}
+
+impl EstimatedSize for DHCPv6Dto {
+    fn estimated_size(&self) -> usize {
+        let options: usize = self
+            .options
+            .iter()
+            .map(|option| match option {
+                dhcpv6::OptionData::ClientIdentifier(data)
+                | dhcpv6::OptionData::ServerIdentifier(data)
+                | dhcpv6::OptionData::IANA { options: data, .. }
+                | dhcpv6::OptionData::VendorData {
+                    vendor_class_data: data,
+                    ..
+                }
+                | dhcpv6::OptionData::DomainSearchList(data) => data.len(),
+                dhcpv6::OptionData::OptionRequest(options) => {
+                    size_of_val(options.as_slice())
+                },
+                dhcpv6::OptionData::DnsRecursiveServers(addresses) => {
+                    size_of_val(addresses.as_slice())
+                },
+                dhcpv6::OptionData::ClientFQDN { domain_name, .. } => {
+                    string_size(domain_name)
+                },
+                dhcpv6::OptionData::ElapsedTime(_) => 0,
+            })
+            .sum();
+
+        size_of::<Self>() + size_of_val(self.options.as_slice()) + options
+    }
+}
+
+impl EstimatedSize for DnsDto {
+    fn estimated_size(&self) -> usize {
+        let questions: usize = self
+            .question_section
+            .iter()
+            .map(|question| size_of::<QuestionEntry>() + string_size(&question.name))
+            .sum();
+
+        size_of::<Self>()
+            + questions
+            + resource_records_size(&self.answer_section)
+            + resource_records_size(&self.authority_section)
+            + resource_records_size(&self.additional_section)
+    }
+}
+
+impl EstimatedSize for HttpDto {
+    fn estimated_size(&self) -> usize {
+        let (text, headers) = match self {
+            HttpDto::Request(request) => (&request.target, &request.headers),
+            HttpDto::Response(response) => (&response.reason, &response.headers),
+        };
+
+        size_of::<Self>()
+            + string_size(text)
+            + headers.iter().map(header_size).sum::<usize>()
+    }
+}
+
+impl EstimatedSize for NbnsDto {
+    fn estimated_size(&self) -> usize {
+        let questions: usize = self
+            .question_section
+            .iter()
+            .map(|question| size_of_val(question) + string_size(&question.name.name))
+            .sum();
+        let records: usize = self
+            .records
+            .iter()
+            .map(|record| {
+                let data = match &record.data {
+                    NameRecordData::Addresses(addresses) => {
+                        size_of_val(addresses.as_slice())
+                    },
+                    NameRecordData::NodeStatus(names) => names
+                        .iter()
+                        .map(|entry| size_of_val(entry) + string_size(&entry.name.name))
+                        .sum(),
+                    NameRecordData::Unknown => 0,
+                };
+                size_of::<NameRecord>() + string_size(&record.name.name) + data
+            })
+            .sum();
+
+        size_of::<Self>() + questions + records
+    }
+}
+
+impl EstimatedSize for SipDto {
+    fn estimated_size(&self) -> usize {
+        let text = match self {
+            SipDto::Request(request) => &request.request_uri,
+            SipDto::Response(response) => &response.reason,
+        };
+        let media: usize = self
+            .media()
+            .iter()
+            .map(|media| {
+                let rtp_maps: usize = media
+                    .rtp_maps
+                    .iter()
+                    .map(|rtp_map| size_of::<RtpMap>() + string_size(&rtp_map.encoding))
+                    .sum();
+                size_of::<SdpMedia>()
+                    + string_size(&media.media)
+                    + string_size(&media.protocol)
+                    + media.payload_types.len()
+                    + rtp_maps
+            })
+            .sum();
+
+        size_of::<Self>()
+            + string_size(text)
+            + self.headers().iter().map(header_size).sum::<usize>()
+            + media
+    }
+}
+
+impl EstimatedSize for Smb2Dto {
+    fn estimated_size(&self) -> usize {
+        let share = self.share.as_deref().map(string_size).unwrap_or_default();
+        let user = self
+            .user
+            .as_ref()
+            .map(|user| {
+                size_of::<SessionUser>()
+                    + string_size(&user.domain)
+                    + string_size(&user.user)
+                    + string_size(&user.workstation)
+            })
+            .unwrap_or_default();
+
+        size_of::<Self>() + share + user
+    }
+}
+
+impl EstimatedSize for SyslogDto {
+    fn estimated_size(&self) -> usize {
+        let optional = [
+            &self.timestamp,
+            &self.hostname,
+            &self.app_name,
+            &self.process_id,
+            &self.message_id,
+            &self.structured_data,
+        ]
+        .into_iter()
+        .filter_map(Option::as_deref)
+        .map(string_size)
+        .sum::<usize>();
+
+        size_of::<Self>() + optional + string_size(&self.message)
+    }
+}
+
+impl EstimatedSize for TftpDto {
+    fn estimated_size(&self) -> usize {
+        let filename = self
+            .filename
+            .as_deref()
+            .map(string_size)
+            .unwrap_or_default();
+        let error_message = self
+            .error_message
+            .as_deref()
+            .map(string_size)
+            .unwrap_or_default();
+        let options: usize = self
+            .options
+            .iter()
+            .map(|option| {
+                size_of::<TftpOption>()
+                    + string_size(&option.name)
+                    + string_size(&option.value)
+            })
+            .sum();
+
+        size_of::<Self>() + filename + error_message + options
+    }
+}
+
+impl EstimatedSize for IPv4Dto {
+    fn estimated_size(&self) -> usize {
+        let options: usize = self
+            .options
+            .iter()
+            .map(|option| match option {
+                OptionData::Timestamp(timestamp) => {
+                    size_of_val(timestamp.entries.as_slice())
+                },
+                OptionData::Unknown { data, .. } => data.len(),
+                _ => 0,
+            })
+            .sum();
+
+        size_of::<Self>() + size_of_val(self.options.as_slice()) + options
+    }
+}
+
+impl EstimatedSize for IPv6Dto {
+    fn estimated_size(&self) -> usize {
+        size_of::<Self>() + size_of_val(self.extension_headers.as_slice())
+    }
+}
+
+impl EstimatedSize for ICMPv6Dto {
+    fn estimated_size(&self) -> usize {
+        let prefixes = self
+            .router_advertisement
+            .as_ref()
+            .map(|advertisement| size_of_val(advertisement.prefixes.as_slice()))
+            .unwrap_or_default();
+
+        size_of::<Self>() + prefixes
+    }
+}
+
+impl EstimatedSize for PortDto {
+    fn estimated_size(&self) -> usize {
+        size_of::<Self>() + string_size(&self.possible_application)
+    }
+}
+
+impl EstimatedSize for WirelessDto {
+    fn estimated_size(&self) -> usize {
+        size_of::<Self>()
+            + self
+                .frame
+                .ssid
+                .as_deref()
+                .map(string_size)
+                .unwrap_or_default()
+    }
+}
+
+// Exact: Header & captured bytes
+impl EstimatedSize for OwnedFrame {
+    fn estimated_size(&self) -> usize {
+        size_of::<Self>() + self.data.len()
+    }
+}
+
+// Types without heap allocations
+macro_rules! fixed_size {
+    ($($type:ty),* $(,)?) => {
+        $(
+            impl EstimatedSize for $type {
+                fn estimated_size(&self) -> usize {
+                    size_of::<Self>()
+                }
+            }
+        )*
+    };
+}
+
+fixed_size!(ICMPv4Dto, Locator, RtpDto);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dpi::dto::frame::FrameHeader;
+    use dpi::protocols::dhcpv4::OperationType;
+    use dpi::protocols::ethernet::mac::MacAddress;
+    use std::net::Ipv4Addr;
+
+    fn frame(length: usize) -> OwnedFrame {
+        OwnedFrame {
+            header: FrameHeader::new(length),
+            data: vec![0; length],
+        }
+    }
+
+    #[test]
+    fn test_records_size_maintained() {
+        let mut records = Records::default();
+        records.push(frame(60), Local::now(), 0);
+        records.push(frame(1500), Local::now(), 1);
+
+        let expected = 2
+            * (size_of::<OwnedFrame>()
+                + size_of::<DateTime<Local>>()
+                + size_of::<FrameId>())
+            + 60
+            + 1500;
+        assert_eq!(records.len(), 2);
+        assert_eq!(records.estimated_size(), expected);
+
+        records.clear();
+        assert!(records.is_empty());
+        assert_eq!(records.estimated_size(), 0);
+    }
+
+    #[test]
+    fn test_records_truncated() {
+        let mut records = Records::default();
+        records.push(frame(60), Local::now(), 0);
+        records.push_truncated(frame(96), Local::now(), 1);
+        assert!(!records.is_truncated(0));
+        assert!(records.is_truncated(1));
+
+        records.clear();
+        records.push(frame(60), Local::now(), 2);
+        assert!(!records.is_truncated(1));
+    }
+
+    #[test]
+    fn test_records_frames() {
+        let mut records = Records::default();
+        records.push(frame(60), Local::now(), 3);
+        // Tunnel: both IP layers of the frame are stored
+        records.push(frame(60), Local::now(), 5);
+        records.push(frame(60), Local::now(), 5);
+        records.push(frame(60), Local::now(), 8);
+
+        assert_eq!(records.frame_at(1), Some(5));
+        assert_eq!(records.frame_at(4), None);
+        assert_eq!(records.indices_of_frame(5), 1..3);
+        assert_eq!(records.indices_of_frame(8), 3..4);
+        assert!(records.indices_of_frame(4).is_empty());
+
+        records.clear();
+        assert!(records.indices_of_frame(5).is_empty());
+    }
+
+    #[test]
+    fn test_dhcp_size() {
+        let mut dhcp = DHCPv4Dto {
+            message_type: OperationType::BootRequest,
+            old_client_address: Ipv4Addr::UNSPECIFIED,
+            new_client_address: Ipv4Addr::UNSPECIFIED,
+            server_address: Ipv4Addr::UNSPECIFIED,
+            relay_agent_address: Ipv4Addr::UNSPECIFIED,
+            hardware_address_client: MacAddress([0x3C, 0x5A, 0xB4, 0x1E, 0x2F, 0x01]),
+            vendor_class: None,
+            parameter_request_list: vec![],
+        };
+        assert_eq!(dhcp.estimated_size(), size_of::<DHCPv4Dto>());
+
+        dhcp.vendor_class = Some(String::from("MSFT 5.0"));
+        dhcp.parameter_request_list = Vec::with_capacity(16);
+        dhcp.parameter_request_list.extend([1, 3, 6, 15]);
+        assert_eq!(dhcp.estimated_size(), size_of::<DHCPv4Dto>() + 8 + 16);
+    }
+}
@@ -0,0 +1,241 @@
+use crate::net::inspector::{InspectorStorage, ProtocolsRegistered};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use strum::IntoEnumIterator;
+
+/// Stable address of the inspector record. <br>
+/// Records are only appended & cleared all at once, so the index is valid
+/// until the generation of the storage changes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RecordId {
+    pub protocol: ProtocolsRegistered,
+    pub generation: u64,
+    pub index: usize,
+}
+
+/// Generation of the storage, that is cleared. Its notes become orphaned.
+pub type ClearedGeneration = (ProtocolsRegistered, u64);
+
+/// Note in the exports. Generation isn't valid outside of the session,
+/// so the record is found again by the index & the capture time.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ExportedNote {
+    pub protocol: String,
+    pub index: usize,
+    pub captured: DateTime<Local>,
+    pub text: String,
+}
+
+/// Free-text notes of the inspector records.
+#[derive(Default)]
+pub struct RecordNotes {
+    notes: HashMap<RecordId, String>,
+}
+
+impl RecordNotes {
+    pub fn get(&self, id: &RecordId) -> Option<&str> {
+        self.notes.get(id).map(String::as_str)
+    }
+
+    /// Blank text removes the note.
+    pub fn set(&mut self, id: RecordId, text: &str) {
+        let text = text.trim();
+        if text.is_empty() {
+            self.notes.remove(&id);
+        } else {
+            self.notes.insert(id, text.to_string());
+        }
+    }
+
+    /// Amount of notes, that belong to the cleared records.
+    pub fn orphaned(&self, cleared: &[ClearedGeneration]) -> usize {
+        self.notes
+            .keys()
+            .filter(|id| Self::is_orphaned(id, cleared))
+            .count()
+    }
+
+    pub fn remove_orphaned(&mut self, cleared: &[ClearedGeneration]) {
+        self.notes.retain(|id, _| !Self::is_orphaned(id, cleared));
+    }
+
+    /// Notes of the records, that are still stored. Sorted by the protocol & the index.
+    pub fn export(&self, inspector: &InspectorStorage) -> Vec<ExportedNote> {
+        let mut exported: Vec<ExportedNote> = self
+            .notes
+            .iter()
+            .filter(|(id, _)| inspector.generation(&id.protocol) == id.generation)
+            .filter_map(|(id, text)| {
+                let captured = inspector.captured(&id.protocol).get(id.index)?;
+                Some(ExportedNote {
+                    protocol: id.protocol.to_string(),
+                    index: id.index,
+                    captured: *captured,
+                    text: text.clone(),
+                })
+            })
+            .collect();
+        exported.sort_by(|first, second| {
+            (&first.protocol, first.index).cmp(&(&second.protocol, second.index))
+        });
+
+        exported
+    }
+
+    /// Notes are restored only on the records with the same capture time.
+    /// Returns the amount of the restored notes.
+    pub fn import(
+        &mut self, exported: &[ExportedNote], inspector: &InspectorStorage,
+    ) -> usize {
+        let mut restored = 0;
+        for note in exported {
+            let Some(protocol) = ProtocolsRegistered::iter()
+                .find(|protocol| protocol.to_string() == note.protocol)
+            else {
+                continue;
+            };
+            if inspector.captured(&protocol).get(note.index) != Some(&note.captured) {
+                continue;
+            }
+
+            let id = RecordId {
+                protocol,
+                generation: inspector.generation(&protocol),
+                index: note.index,
+            };
+            self.set(id, &note.text);
+            restored += 1;
+        }
+
+        restored
+    }
+
+    pub fn len(&self) -> usize {
+        self.notes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+
+    pub fn estimated_size(&self) -> usize {
+        self.notes
+            .values()
+            .map(|text| size_of::<(RecordId, String)>() + text.len())
+            .sum()
+    }
+
+    pub fn clear(&mut self) {
+        self.notes.clear();
+    }
+
+    fn is_orphaned(id: &RecordId, cleared: &[ClearedGeneration]) -> bool {
+        cleared.contains(&(id.protocol, id.generation))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ws::data::Locator;
+    use chrono::Duration;
+    use dpi::protocols::ethernet::mac::MacAddress;
+
+    fn id(protocol: ProtocolsRegistered, generation: u64, index: usize) -> RecordId {
+        RecordId {
+            protocol,
+            generation,
+            index,
+        }
+    }
+
+    #[test]
+    fn test_set_note() {
+        let mut notes = RecordNotes::default();
+        let record = id(ProtocolsRegistered::Arp, 0, 7);
+
+        notes.set(record, "  Printer went crazy\n");
+        assert_eq!(notes.get(&record), Some("Printer went crazy"));
+        assert_eq!(notes.get(&id(ProtocolsRegistered::Arp, 1, 7)), None);
+
+        // Blank text removes the note
+        notes.set(record, " \n");
+        assert_eq!(notes.get(&record), None);
+        assert!(notes.is_empty());
+    }
+
+    #[test]
+    fn test_orphaned_notes() {
+        let mut notes = RecordNotes::default();
+        notes.set(id(ProtocolsRegistered::Arp, 0, 1), "first");
+        notes.set(id(ProtocolsRegistered::Arp, 0, 2), "second");
+        notes.set(id(ProtocolsRegistered::Arp, 1, 1), "newer");
+        notes.set(id(ProtocolsRegistered::Dns, 0, 1), "other protocol");
+
+        let cleared = [(ProtocolsRegistered::Arp, 0)];
+        assert_eq!(notes.orphaned(&cleared), 2);
+
+        notes.remove_orphaned(&cleared);
+        assert_eq!(notes.len(), 2);
+        assert_eq!(
+            notes.get(&id(ProtocolsRegistered::Arp, 1, 1)),
+            Some("newer")
+        );
+        assert_eq!(notes.orphaned(&cleared), 0);
+    }
+
+    fn locator() -> Locator {
+        Locator {
+            mac: (
+                MacAddress([0, 0, 0, 0, 0, 1]),
+                MacAddress([0, 0, 0, 0, 0, 2]),
+            ),
+            ipv4: None,
+            ipv6: None,
+            tunnel: None,
+        }
+    }
+
+    #[test]
+    fn test_export_import() {
+        let now = Local::now();
+        let mut inspector = InspectorStorage::default();
+        let frame = inspector.next_frame();
+        inspector.ethernet.push(locator(), now, frame);
+        inspector
+            .ethernet
+            .push(locator(), now + Duration::seconds(1), frame);
+
+        let mut notes = RecordNotes::default();
+        notes.set(id(ProtocolsRegistered::Ethernet, 0, 1), "second");
+        // Not stored
+        notes.set(id(ProtocolsRegistered::Ethernet, 0, 5), "missing");
+        notes.set(id(ProtocolsRegistered::Arp, 0, 0), "missing");
+
+        let exported = notes.export(&inspector);
+        assert_eq!(exported.len(), 1);
+        assert_eq!(exported[0].protocol, "Ethernet");
+        assert_eq!(exported[0].text, "second");
+
+        let json = serde_json::to_string(&exported).unwrap();
+        let exported: Vec<ExportedNote> = serde_json::from_str(&json).unwrap();
+
+        let mut restored = RecordNotes::default();
+        assert_eq!(restored.import(&exported, &inspector), 1);
+        assert_eq!(
+            restored.get(&id(ProtocolsRegistered::Ethernet, 0, 1)),
+            Some("second")
+        );
+
+        // Records of the other capture aren't annotated
+        inspector.ethernet.clear();
+        inspector.ethernet.push(locator(), now, frame);
+        inspector
+            .ethernet
+            .push(locator(), now + Duration::seconds(5), frame);
+        let mut restored = RecordNotes::default();
+        assert_eq!(restored.import(&exported, &inspector), 0);
+        assert!(restored.is_empty());
+    }
+}
@@ -1,53 +1,451 @@
-use dpi::dto::frame::OwnedFrame;
+use crate::net::anonymize::Anonymizer;
+use crate::net::memory;
+use crate::net::memory::EstimatedSize;
+use chrono::Local;
+use dpi::dto::frame::{LinkType, OwnedFrame};
 use std::collections::VecDeque;
-use std::path::PathBuf;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+use std::{env, fs, mem, process};
+
+// Size of the frame header in the pcap file
+const PCAP_RECORD_HEADER_BYTES: usize = 16;
 
 pub struct RawStorage {
     vec: VecDeque<OwnedFrame>,
     threshold: Option<usize>,
+    bytes: usize,
+    // Frames over the limits are moved to the disk instead of being dropped
+    spill_limit_mb: Option<usize>,
+    spill: Option<SpillFile>,
 }
 
 impl RawStorage {
-    pub fn new(threshold: Option<usize>) -> Self {
+    pub fn new(threshold: Option<usize>, spill_limit_mb: Option<usize>) -> Self {
         Self {
             vec: Default::default(),
             threshold,
+            bytes: 0,
+            spill_limit_mb,
+            spill: None,
         }
     }
 
-    pub fn add(&mut self, frame: OwnedFrame) {
+    /// Link type is required for the spilling, the frames are dropped without it.
+    pub fn add(&mut self, frame: OwnedFrame, link_type: Option<pcap::Linktype>) {
+        self.bytes = self.bytes.saturating_add(frame.estimated_size());
         self.vec.push_back(frame);
-        if let Some(threshold) = self.threshold {
-            if self.vec.len() > threshold {
-                self.vec.pop_front();
+        while self.is_over_limit() {
+            let Some(frame) = self.vec.pop_front() else {
+                break;
+            };
+            self.bytes = self.bytes.saturating_sub(frame.estimated_size());
+            if self.spill_limit_mb.is_some() {
+                self.spill(&frame, link_type);
             }
         }
     }
 
+    fn is_over_limit(&self) -> bool {
+        let is_over_threshold = self
+            .threshold
+            .is_some_and(|threshold| self.vec.len() > threshold);
+        let is_over_spill_limit = self.spill_limit_mb.is_some_and(|limit| {
+            self.bytes > limit.saturating_mul(memory::BYTES_IN_MEGABYTE)
+        });
+
+        is_over_threshold || is_over_spill_limit
+    }
+
+    fn spill(&mut self, frame: &OwnedFrame, link_type: Option<pcap::Linktype>) {
+        if self.spill.is_none() {
+            let Some(link_type) = link_type else {
+                return;
+            };
+            match SpillFile::create(link_type) {
+                Ok(spill) => self.spill = Some(spill),
+                Err(err) => {
+                    log::error!("Unparsed frames: Failed to create a spill file. {err}");
+                    return;
+                },
+            }
+        }
+
+        if let Some(spill) = &mut self.spill {
+            spill.write(frame);
+        }
+    }
+
     pub fn set_threshold(&mut self, threshold: Option<usize>) {
         self.threshold = threshold;
     }
 
+    pub fn set_spill_limit(&mut self, spill_limit_mb: Option<usize>) {
+        self.spill_limit_mb = spill_limit_mb;
+    }
+
     pub fn amount(&self) -> usize {
         self.vec.len()
     }
 
+    pub fn estimated_size(&self) -> usize {
+        self.bytes
+    }
+
+    pub fn spilled_amount(&self) -> usize {
+        self.spill.as_ref().map_or(0, |spill| spill.amount)
+    }
+
+    pub fn spilled_size(&self) -> usize {
+        self.spill.as_ref().map_or(0, |spill| spill.bytes)
+    }
+
+    /// Clears the memory and deletes the spill file.
     pub fn clear(&mut self) {
         self.vec.clear();
+        self.bytes = 0;
+        self.spill = None;
     }
 
     pub fn is_empty(&self) -> bool {
-        self.vec.is_empty()
+        self.vec.is_empty() && self.spill.is_none()
+    }
+
+    /// Takes the frames out for the saving in the background, the storage starts over.
+    pub fn take_export(&mut self, link_type: pcap::Linktype) -> RawExport {
+        if let Some(spill) = &mut self.spill {
+            spill.flush();
+        }
+        self.bytes = 0;
+
+        RawExport {
+            frames: mem::take(&mut self.vec),
+            spill: self.spill.take(),
+            link_type,
+        }
+    }
+
+    /// Gives back the frames of the unfinished export. They are older than the ones
+    /// added meanwhile, so they go first.
+    pub fn restore(&mut self, export: RawExport, link_type: Option<pcap::Linktype>) {
+        if link_type != Some(export.link_type) {
+            log::warn!("Link type changed. Unparsed frames of the export are dropped.");
+            return;
+        }
+
+        let newer_frames = mem::replace(&mut self.vec, export.frames);
+        let newer_spill = mem::replace(&mut self.spill, export.spill);
+        self.bytes = self.vec.iter().map(EstimatedSize::estimated_size).sum();
+
+        if let Some(newer_spill) = newer_spill {
+            // Older frames from the memory go to the disk before the newer spilled ones
+            for frame in mem::take(&mut self.vec) {
+                self.spill(&frame, link_type);
+            }
+            self.bytes = 0;
+            match &mut self.spill {
+                Some(spill) => {
+                    if let Err(err) = spill.append(newer_spill) {
+                        log::error!(
+                            "Unparsed frames: Failed to merge spill files. {err}"
+                        );
+                    }
+                },
+                None => self.spill = Some(newer_spill),
+            }
+        }
+
+        for frame in newer_frames {
+            self.add(frame, link_type);
+        }
     }
+}
+
+/// Frames taken out of the storage for saving.
+pub struct RawExport {
+    frames: VecDeque<OwnedFrame>,
+    spill: Option<SpillFile>,
+    link_type: pcap::Linktype,
+}
+
+pub enum ExportOutcome {
+    Saved,
+    // Frames are given back to the storage
+    Cancelled(RawExport),
+    Failed(RawExport, pcap::Error),
+}
 
+impl RawExport {
+    pub fn amount(&self) -> usize {
+        let spilled = self.spill.as_ref().map_or(0, |spill| spill.amount);
+
+        spilled.saturating_add(self.frames.len())
+    }
+
+    /// Spilled frames are older, so they go first. <br>
+    /// `on_progress` gets the amount of the written frames and breaks the saving.
+    /// The unfinished file is deleted. Anonymizer rewrites the copies, not the kept frames.
     pub fn save_pcap(
-        &mut self, path: PathBuf, link_type: pcap::Linktype,
-    ) -> Result<(), pcap::Error> {
-        let result = dpi::dto::frame::save_pcap(path, &self.vec, link_type);
-        if result.is_ok() {
-            self.vec.clear();
+        self, path: &Path, mut anonymizer: Option<Anonymizer>,
+        mut on_progress: impl FnMut(usize) -> ControlFlow<()>,
+    ) -> ExportOutcome {
+        match self.write(path, &mut anonymizer, &mut on_progress) {
+            Ok(ControlFlow::Continue(())) => ExportOutcome::Saved,
+            Ok(ControlFlow::Break(())) => {
+                Self::remove_unfinished(path);
+                ExportOutcome::Cancelled(self)
+            },
+            Err(err) => {
+                Self::remove_unfinished(path);
+                ExportOutcome::Failed(self, err)
+            },
+        }
+    }
+
+    fn write(
+        &self, path: &Path, anonymizer: &mut Option<Anonymizer>,
+        on_progress: &mut impl FnMut(usize) -> ControlFlow<()>,
+    ) -> Result<ControlFlow<()>, pcap::Error> {
+        let mut output = pcap::Capture::dead(self.link_type)?.savefile(path)?;
+        let link_type = LinkType::from(self.link_type);
+        let mut written = 0_usize;
+
+        if let Some(spill) = &self.spill {
+            let mut spilled = pcap::Capture::from_file(&spill.path)?;
+            loop {
+                match spilled.next_packet() {
+                    Ok(packet) => match anonymizer {
+                        Some(anonymizer) => {
+                            let mut data = packet.data.to_vec();
+                            anonymizer.frame(link_type, &mut data);
+                            output.write(&pcap::Packet::new(packet.header, &data));
+                        },
+                        None => output.write(&packet),
+                    },
+                    Err(pcap::Error::NoMorePackets) => break,
+                    Err(err) => return Err(err),
+                }
+                written = written.saturating_add(1);
+                if on_progress(written).is_break() {
+                    return Ok(ControlFlow::Break(()));
+                }
+            }
         }
 
-        result
+        for frame in &self.frames {
+            match anonymizer {
+                Some(anonymizer) => {
+                    let mut frame = frame.clone();
+                    anonymizer.frame(link_type, &mut frame.data);
+                    dpi::dto::frame::write_frames(&mut output, [&frame]);
+                },
+                None => dpi::dto::frame::write_frames(&mut output, [frame]),
+            }
+            written = written.saturating_add(1);
+            if on_progress(written).is_break() {
+                return Ok(ControlFlow::Break(()));
+            }
+        }
+
+        output.flush()?;
+        Ok(ControlFlow::Continue(()))
+    }
+
+    fn remove_unfinished(path: &Path) {
+        if let Err(err) = fs::remove_file(path) {
+            log::warn!(
+                "Unparsed frames: Failed to delete the unfinished file {}. {err}",
+                path.display()
+            );
+        }
+    }
+}
+
+// Temporary pcap file with the oldest frames. It's deleted on drop, so on reset & logout too.
+struct SpillFile {
+    path: PathBuf,
+    // Closed before the deletion
+    file: Option<pcap::Savefile>,
+    amount: usize,
+    bytes: usize,
+}
+
+impl SpillFile {
+    fn create(link_type: pcap::Linktype) -> Result<Self, pcap::Error> {
+        let path = env::temp_dir().join(format!(
+            "xailyser-unparsed-{}-{}.pcap",
+            process::id(),
+            Local::now().timestamp_millis()
+        ));
+        let file = pcap::Capture::dead(link_type)?.savefile(&path)?;
+        log::info!("Unparsed frames: Spilling to {}", path.display());
+
+        Ok(Self {
+            path,
+            file: Some(file),
+            amount: 0,
+            bytes: 0,
+        })
+    }
+
+    fn write(&mut self, frame: &OwnedFrame) {
+        if let Some(file) = &mut self.file {
+            dpi::dto::frame::write_frames(file, [frame]);
+            self.amount = self.amount.saturating_add(1);
+            self.bytes = self.bytes.saturating_add(
+                PCAP_RECORD_HEADER_BYTES.saturating_add(frame.data.len()),
+            );
+        }
+    }
+
+    fn flush(&mut self) {
+        if let Some(file) = &mut self.file {
+            if let Err(err) = file.flush() {
+                log::error!("Unparsed frames: Failed to flush the spill file. {err}");
+            }
+        }
+    }
+
+    // Newer spill file goes to the end, then it's deleted
+    fn append(&mut self, mut newer: SpillFile) -> Result<(), pcap::Error> {
+        newer.flush();
+        let Some(file) = &mut self.file else {
+            return Ok(());
+        };
+
+        let mut spilled = pcap::Capture::from_file(&newer.path)?;
+        loop {
+            match spilled.next_packet() {
+                Ok(packet) => file.write(&packet),
+                Err(pcap::Error::NoMorePackets) => break,
+                Err(err) => return Err(err),
+            }
+        }
+        self.amount = self.amount.saturating_add(newer.amount);
+        self.bytes = self.bytes.saturating_add(newer.bytes);
+
+        Ok(())
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        self.file = None;
+        if let Err(err) = fs::remove_file(&self.path) {
+            log::warn!(
+                "Unparsed frames: Failed to delete the spill file {}. {err}",
+                self.path.display()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dpi::dto::frame::FrameHeader;
+
+    fn frame(index: u8) -> OwnedFrame {
+        OwnedFrame {
+            header: FrameHeader {
+                tv_sec: i64::from(index),
+                tv_usec: 0,
+                caplen: 4,
+                len: 4,
+            },
+            data: vec![index; 4],
+        }
+    }
+
+    #[test]
+    fn test_spill_and_save() {
+        let mut storage = RawStorage::new(Some(2), Some(usize::MAX));
+        for index in 0..5 {
+            storage.add(frame(index), Some(pcap::Linktype::ETHERNET));
+        }
+        assert_eq!(storage.amount(), 2);
+        assert_eq!(storage.spilled_amount(), 3);
+        assert_eq!(storage.spilled_size(), 3 * (PCAP_RECORD_HEADER_BYTES + 4));
+        let spill_path = storage.spill.as_ref().map(|spill| spill.path.clone());
+        let spill_path = spill_path.unwrap();
+        assert!(spill_path.exists());
+
+        let path = env::temp_dir().join(format!("xailyser-test-{}.pcap", process::id()));
+        let export = storage.take_export(pcap::Linktype::ETHERNET);
+        assert!(storage.is_empty());
+        assert_eq!(export.amount(), 5);
+        let mut progress = vec![];
+        let outcome = export.save_pcap(&path, None, |written| {
+            progress.push(written);
+            ControlFlow::Continue(())
+        });
+        assert!(matches!(outcome, ExportOutcome::Saved));
+        assert_eq!(progress, vec![1, 2, 3, 4, 5]);
+        assert!(!spill_path.exists());
+
+        // Spilled frames go first
+        let mut saved = pcap::Capture::from_file(&path).unwrap();
+        let mut seconds = vec![];
+        while let Ok(packet) = saved.next_packet() {
+            seconds.push(packet.header.ts.tv_sec);
+        }
+        assert_eq!(seconds, vec![0, 1, 2, 3, 4]);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_drop_without_spill() {
+        let mut storage = RawStorage::new(Some(2), None);
+        for index in 0..5 {
+            storage.add(frame(index), Some(pcap::Linktype::ETHERNET));
+        }
+        assert_eq!(storage.amount(), 2);
+        assert_eq!(storage.spilled_amount(), 0);
+
+        // Link type is unknown yet
+        let mut storage = RawStorage::new(Some(2), Some(usize::MAX));
+        for index in 0..5 {
+            storage.add(frame(index), None);
+        }
+        assert_eq!(storage.amount(), 2);
+        assert!(storage.spill.is_none());
+    }
+
+    #[test]
+    fn test_cancel_and_restore() {
+        let link_type = Some(pcap::Linktype::ETHERNET);
+        let mut storage = RawStorage::new(Some(3), None);
+        for index in 0..3 {
+            storage.add(frame(index), link_type);
+        }
+
+        let path =
+            env::temp_dir().join(format!("xailyser-cancel-{}.pcap", process::id()));
+        let export = storage.take_export(pcap::Linktype::ETHERNET);
+        // Frames keep coming during the saving
+        storage.add(frame(3), link_type);
+        let outcome = export.save_pcap(&path, None, |written| match written {
+            2 => ControlFlow::Break(()),
+            _ => ControlFlow::Continue(()),
+        });
+        let ExportOutcome::Cancelled(export) = outcome else {
+            panic!();
+        };
+        assert!(!path.exists());
+
+        storage.restore(export, link_type);
+        let seconds = storage
+            .vec
+            .iter()
+            .map(|frame| frame.header.tv_sec)
+            .collect::<Vec<_>>();
+        // Threshold drops the oldest one
+        assert_eq!(seconds, vec![1, 2, 3]);
+        assert_eq!(storage.bytes, 3 * frame(0).estimated_size());
+
+        // Frames of the other link type can't be saved together
+        let export = storage.take_export(pcap::Linktype::ETHERNET);
+        storage.restore(export, Some(pcap::Linktype::IEEE802_11));
+        assert!(storage.is_empty());
     }
 }
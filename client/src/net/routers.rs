@@ -0,0 +1,232 @@
+use chrono::{DateTime, Duration, Local};
+use dpi::protocols::ethernet::mac::MacAddress;
+use dpi::protocols::icmpv6::{PrefixInformation, RouterAdvertisement};
+use std::collections::{HashMap, HashSet};
+use std::net::Ipv6Addr;
+
+pub const DEFAULT_LEARNING_SECONDS: u32 = 300;
+// Tools flooding the advertisements use random addresses
+pub const CAPACITY: usize = 256;
+
+/// Sources of the IPv6 router advertisements. <br>
+/// Routers, that advertise during the learning period, are taken as the known ones.
+/// New router after it, or more than one router at all, is reported once:
+/// it may be rogue (SLAAC spoofing) or misconfigured. Trusted routers are never reported.
+#[derive(Default)]
+pub struct RouterStorage {
+    sources: HashMap<MacAddress, RouterSource>,
+    // Persisted in the config
+    trusted: HashSet<MacAddress>,
+    // Time of the first advertisement
+    learning_started: Option<DateTime<Local>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RouterSource {
+    pub mac: MacAddress,
+    // Link-local address, advertisements are sent from it
+    pub ip: Ipv6Addr,
+    pub prefixes: Vec<PrefixInformation>,
+    // Seconds. Zero, if the router isn't a default one
+    pub router_lifetime: u16,
+    pub advertisements: usize,
+    pub first_seen: DateTime<Local>,
+    pub last_seen: DateTime<Local>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RogueRouter {
+    pub mac: MacAddress,
+    pub ip: Ipv6Addr,
+    // Routers, that were advertising before
+    pub known: Vec<MacAddress>,
+}
+
+impl RouterStorage {
+    pub fn with_trusted(trusted: &[MacAddress]) -> Self {
+        Self {
+            trusted: trusted.iter().cloned().collect(),
+            ..Default::default()
+        }
+    }
+
+    pub fn observe(
+        &mut self, advertisement: &RouterAdvertisement, mac: &MacAddress, ip: Ipv6Addr,
+        captured: DateTime<Local>, learning: Duration,
+    ) -> Option<RogueRouter> {
+        let started = *self.learning_started.get_or_insert(captured);
+        let is_learning = captured - started < learning;
+
+        if let Some(source) = self.sources.get_mut(mac) {
+            source.ip = ip;
+            source.prefixes = advertisement.prefixes.clone();
+            source.router_lifetime = advertisement.router_lifetime;
+            source.advertisements = source.advertisements.saturating_add(1);
+            source.last_seen = source.last_seen.max(captured);
+            return None;
+        }
+
+        let mut known = self.sources.keys().cloned().collect::<Vec<_>>();
+        known.sort_by_key(|mac| mac.0);
+        if self.sources.len() >= CAPACITY {
+            self.evict();
+        }
+        self.sources.insert(
+            mac.clone(),
+            RouterSource {
+                mac: mac.clone(),
+                ip,
+                prefixes: advertisement.prefixes.clone(),
+                router_lifetime: advertisement.router_lifetime,
+                advertisements: 1,
+                first_seen: captured,
+                last_seen: captured,
+            },
+        );
+
+        // The only router of the learning period is the legitimate one
+        if self.trusted.contains(mac) || (is_learning && known.is_empty()) {
+            return None;
+        }
+
+        Some(RogueRouter {
+            mac: mac.clone(),
+            ip,
+            known,
+        })
+    }
+
+    /// Sources sorted by the first advertisement.
+    pub fn sources(&self) -> Vec<&RouterSource> {
+        let mut sources = self.sources.values().collect::<Vec<_>>();
+        sources.sort_by_key(|source| (source.first_seen, source.mac.0));
+
+        sources
+    }
+
+    pub fn is_trusted(&self, mac: &MacAddress) -> bool {
+        self.trusted.contains(mac)
+    }
+
+    pub fn set_trusted(&mut self, mac: &MacAddress, is_trusted: bool) {
+        match is_trusted {
+            true => self.trusted.insert(mac.clone()),
+            false => self.trusted.remove(mac),
+        };
+    }
+
+    /// Sorted, so the config isn't reordered on every save.
+    pub fn trusted_list(&self) -> Vec<MacAddress> {
+        let mut trusted = self.trusted.iter().cloned().collect::<Vec<_>>();
+        trusted.sort_by_key(|mac| mac.0);
+
+        trusted
+    }
+
+    fn evict(&mut self) {
+        let oldest = self
+            .sources
+            .values()
+            .min_by_key(|source| source.last_seen)
+            .map(|source| source.mac.clone());
+        if let Some(mac) = oldest {
+            self.sources.remove(&mac);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn advertisement(prefix: &str) -> RouterAdvertisement {
+        RouterAdvertisement {
+            router_lifetime: 1800,
+            link_address: None,
+            prefixes: vec![PrefixInformation {
+                prefix: prefix.parse().unwrap(),
+                length: 64,
+                valid_lifetime: 2592000,
+                preferred_lifetime: 604800,
+            }],
+        }
+    }
+
+    fn ip(last: u16) -> Ipv6Addr {
+        Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, last)
+    }
+
+    #[test]
+    fn test_rogue_after_learning() {
+        let mut routers = RouterStorage::default();
+        let learning = Duration::seconds(60);
+        let now = Local::now();
+        let legit = MacAddress([0, 0, 0, 0, 0, 1]);
+        let rogue = MacAddress([0, 0, 0, 0, 0, 66]);
+        let legit_ra = advertisement("2001:db8:1::");
+
+        assert_eq!(
+            routers.observe(&legit_ra, &legit, ip(1), now, learning),
+            None
+        );
+        // Periodic advertisements of the same router
+        let later = now + Duration::minutes(10);
+        assert_eq!(
+            routers.observe(&legit_ra, &legit, ip(1), later, learning),
+            None
+        );
+
+        let rogue_ra = advertisement("2001:db8:66::");
+        assert_eq!(
+            routers.observe(&rogue_ra, &rogue, ip(66), later, learning),
+            Some(RogueRouter {
+                mac: rogue.clone(),
+                ip: ip(66),
+                known: vec![legit.clone()],
+            })
+        );
+        // Reported once
+        assert_eq!(
+            routers.observe(&rogue_ra, &rogue, ip(66), later, learning),
+            None
+        );
+
+        let sources = routers.sources();
+        assert_eq!(sources.len(), 2);
+        assert_eq!(sources[0].mac, legit);
+        assert_eq!(sources[0].advertisements, 2);
+        assert_eq!(sources[1].prefixes, rogue_ra.prefixes);
+    }
+
+    #[test]
+    fn test_second_router_while_learning() {
+        let mut routers = RouterStorage::default();
+        let learning = Duration::seconds(60);
+        let now = Local::now();
+        let first = MacAddress([0, 0, 0, 0, 0, 1]);
+        let second = MacAddress([0, 0, 0, 0, 0, 2]);
+        let ra = advertisement("2001:db8:1::");
+
+        assert_eq!(routers.observe(&ra, &first, ip(1), now, learning), None);
+        assert!(
+            routers
+                .observe(&ra, &second, ip(2), now, learning)
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_trusted_router() {
+        let first = MacAddress([0, 0, 0, 0, 0, 1]);
+        let backup = MacAddress([0, 0, 0, 0, 0, 2]);
+        let mut routers = RouterStorage::with_trusted(&[backup.clone()]);
+        let learning = Duration::seconds(60);
+        let now = Local::now();
+        let ra = advertisement("2001:db8:1::");
+
+        assert_eq!(routers.observe(&ra, &first, ip(1), now, learning), None);
+        let later = now + Duration::minutes(10);
+        assert_eq!(routers.observe(&ra, &backup, ip(2), later, learning), None);
+        assert_eq!(routers.trusted_list(), vec![backup]);
+    }
+}
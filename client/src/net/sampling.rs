@@ -0,0 +1,137 @@
+use crate::context::ClientSettings;
+use crate::net::filter::TimeRange;
+use crate::net::speed::Sample;
+use chrono::{DateTime, Local, TimeDelta};
+use common::messages::SummaryDto;
+use std::collections::VecDeque;
+
+/// Server sends only 1 of N frames while the connection can't keep up with the traffic.
+/// In that case the speed & counters are lower than the real ones.
+#[derive(Default)]
+pub struct SamplingStats {
+    sample_rate: Option<u32>,
+    // Capture time, covered by the sampled data
+    periods: VecDeque<TimeRange>,
+    // Counters of all the frames, sent & not sent
+    last: Option<SummaryDto>,
+}
+
+impl SamplingStats {
+    pub fn update(&mut self, summary: SummaryDto) {
+        if let Some(period) = Self::period(&summary) {
+            match self.periods.back_mut() {
+                // Summaries of the same sampled period
+                Some(last) if self.sample_rate.is_some() && last.end <= period.end => {
+                    last.end = period.end;
+                },
+                _ => self.periods.push_back(period),
+            }
+        }
+
+        self.sample_rate = summary.sampled.then_some(summary.sample_rate);
+        self.last = Some(summary);
+    }
+
+    pub fn is_sampled(&self) -> bool {
+        self.sample_rate.is_some()
+    }
+
+    pub fn sample_rate(&self) -> Option<u32> {
+        self.sample_rate
+    }
+
+    pub fn last(&self) -> Option<&SummaryDto> {
+        self.last.as_ref()
+    }
+
+    pub fn periods(&self) -> impl Iterator<Item = &TimeRange> {
+        self.periods.iter()
+    }
+
+    pub fn is_sampled_at(&self, time: &DateTime<Local>) -> bool {
+        self.periods.iter().any(|period| period.contains(time))
+    }
+
+    // Periods that aren't shown on the plot anymore
+    pub fn clear_outdated(&mut self, settings: &ClientSettings) {
+        let window = TimeDelta::seconds(i64::from(settings.plot.display_window_seconds));
+        let oldest = Local::now() - window;
+        while let Some(period) = self.periods.front() {
+            if period.end < oldest {
+                self.periods.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn period(summary: &SummaryDto) -> Option<TimeRange> {
+        let start = Sample::try_from(summary.first.as_ref()?).ok()?;
+        let end = Sample::try_from(summary.last.as_ref()?).ok()?;
+
+        Some(TimeRange {
+            start: start.time_captured,
+            end: end.time_captured,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dpi::dto::frame::FrameHeader;
+
+    fn header(tv_sec: i64) -> FrameHeader {
+        FrameHeader {
+            tv_sec,
+            tv_usec: 0,
+            caplen: 60,
+            len: 60,
+        }
+    }
+
+    fn summary(sampled: bool, first: i64, last: i64) -> SummaryDto {
+        SummaryDto {
+            sampled,
+            sample_rate: 10,
+            interval_ms: 1000,
+            frames: 100,
+            bytes: 6000,
+            first: Some(header(first)),
+            last: Some(header(last)),
+            protocols: vec![],
+        }
+    }
+
+    #[test]
+    fn test_periods_merged() {
+        let mut sampling = SamplingStats::default();
+        sampling.update(summary(true, 1_700_000_000, 1_700_000_001));
+        sampling.update(summary(true, 1_700_000_001, 1_700_000_002));
+
+        assert!(sampling.is_sampled());
+        assert_eq!(sampling.sample_rate(), Some(10));
+        assert_eq!(sampling.periods().count(), 1);
+
+        // Last summary of the period
+        sampling.update(summary(false, 1_700_000_002, 1_700_000_003));
+        assert!(!sampling.is_sampled());
+        assert_eq!(sampling.periods().count(), 1);
+
+        let period = sampling
+            .periods()
+            .next()
+            .map(|period| period.to_plot(period.end));
+        assert_eq!(period, Some((0.0, 3.0)));
+    }
+
+    #[test]
+    fn test_new_period() {
+        let mut sampling = SamplingStats::default();
+        sampling.update(summary(false, 1_700_000_000, 1_700_000_001));
+        sampling.update(summary(true, 1_700_000_010, 1_700_000_011));
+
+        assert_eq!(sampling.periods().count(), 2);
+        assert!(sampling.is_sampled());
+    }
+}
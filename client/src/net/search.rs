@@ -0,0 +1,336 @@
+use crate::net::filter::{Filter, FilterField, Filterable};
+use crate::net::inspector::{InspectorStorage, ProtocolsRegistered};
+use crate::net::memory::Records;
+use crate::net::notes::RecordId;
+use chrono::{DateTime, Local};
+use std::ops::ControlFlow;
+use strum::IntoEnumIterator;
+
+// Progress is reported once per that amount of the scanned records
+const PROGRESS_STEP: usize = 1024;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchQuery {
+    pub pattern: String,
+    pub is_regex: bool,
+}
+
+impl SearchQuery {
+    /// Error of the regex, so the search isn't started with it.
+    pub fn error(&self) -> Option<String> {
+        Filter::compiled(FilterField::SourceIp, &self.pattern, self.is_regex)
+            .error()
+            .map(ToString::to_string)
+    }
+}
+
+/// Records of every protocol, that match the query. <br>
+/// Ids are kept instead of the positions, so they stay valid while the capture goes on.
+pub struct SearchResults {
+    pub query: SearchQuery,
+    pub groups: Vec<SearchGroup>,
+}
+
+pub struct SearchGroup {
+    pub protocol: ProtocolsRegistered,
+    pub matches: Vec<SearchMatch>,
+}
+
+pub struct SearchMatch {
+    pub id: RecordId,
+    // First field of the protocol, that matches
+    pub field: FilterField,
+    pub captured: Option<DateTime<Local>>,
+}
+
+impl SearchResults {
+    pub fn total(&self) -> usize {
+        self.groups.iter().map(|group| group.matches.len()).sum()
+    }
+
+    /// Groups of the cleared storages are dropped, their ids point nowhere.
+    pub fn retain_valid(&mut self, storage: &InspectorStorage) {
+        self.groups.retain(|group| {
+            group.matches.first().is_some_and(|found| {
+                storage.generation(&group.protocol) == found.id.generation
+            })
+        });
+    }
+}
+
+/// Copy of the inspector storages at the start of the search. <br>
+/// It's scanned by the background task, while the capture keeps appending to the storages.
+/// Protocols without the filterable fields aren't copied.
+pub struct SearchSnapshot {
+    parts: Vec<Part>,
+}
+
+struct Part {
+    protocol: ProtocolsRegistered,
+    generation: u64,
+    captured: Vec<DateTime<Local>>,
+    records: Box<dyn Scan>,
+}
+
+trait Scan: Send {
+    fn len(&self) -> usize;
+    fn matches(&self, index: usize, filter: &Filter) -> bool;
+}
+
+impl<T: Filterable + Send> Scan for Vec<T> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn matches(&self, index: usize, filter: &Filter) -> bool {
+        self.get(index).is_some_and(|record| record.matches(filter))
+    }
+}
+
+impl Part {
+    fn new<T: Filterable + Clone + Send + 'static>(
+        protocol: ProtocolsRegistered, records: &Records<T>,
+    ) -> Self {
+        Self {
+            protocol,
+            generation: records.generation(),
+            captured: records.captured().to_vec(),
+            records: Box::new(records.to_vec()),
+        }
+    }
+
+    fn of(storage: &InspectorStorage, protocol: ProtocolsRegistered) -> Self {
+        match protocol {
+            ProtocolsRegistered::Arp => Self::new(protocol, &storage.arp),
+            ProtocolsRegistered::DHCPv4 => Self::new(protocol, &storage.dhcpv4),
+            ProtocolsRegistered::DHCPv6 => Self::new(protocol, &storage.dhcpv6),
+            ProtocolsRegistered::Dns => Self::new(protocol, &storage.dns),
+            ProtocolsRegistered::Ethernet => Self::new(protocol, &storage.ethernet),
+            ProtocolsRegistered::Http => Self::new(protocol, &storage.http),
+            ProtocolsRegistered::ICMPv4 => Self::new(protocol, &storage.icmpv4),
+            ProtocolsRegistered::ICMPv6 => Self::new(protocol, &storage.icmpv6),
+            ProtocolsRegistered::IEEE80211 => Self::new(protocol, &storage.ieee80211),
+            ProtocolsRegistered::IPv4 => Self::new(protocol, &storage.ipv4),
+            ProtocolsRegistered::IPv6 => Self::new(protocol, &storage.ipv6),
+            ProtocolsRegistered::Nbns => Self::new(protocol, &storage.nbns),
+            ProtocolsRegistered::Rtp => Self::new(protocol, &storage.rtp),
+            ProtocolsRegistered::Sip => Self::new(protocol, &storage.sip),
+            ProtocolsRegistered::Smb2 => Self::new(protocol, &storage.smb2),
+            ProtocolsRegistered::Syslog => Self::new(protocol, &storage.syslog),
+            ProtocolsRegistered::Tcp => Self::new(protocol, &storage.tcp),
+            ProtocolsRegistered::Tftp => Self::new(protocol, &storage.tftp),
+            ProtocolsRegistered::Udp => Self::new(protocol, &storage.udp),
+        }
+    }
+}
+
+impl SearchSnapshot {
+    pub fn new(storage: &InspectorStorage) -> Self {
+        let parts = ProtocolsRegistered::iter()
+            .filter(|protocol| !FilterField::available(&protocol.id()).is_empty())
+            .filter(|protocol| storage.records_captured(protocol) > 0)
+            .map(|protocol| Part::of(storage, protocol))
+            .collect();
+
+        Self { parts }
+    }
+
+    pub fn total(&self) -> usize {
+        self.parts.iter().map(|part| part.records.len()).sum()
+    }
+
+    /// Every record is checked by the same fields, as the filter of its protocol view.
+    /// <br> `on_progress` gets the amount of the scanned records & may stop the search.
+    pub fn search(
+        &self, query: &SearchQuery, mut on_progress: impl FnMut(usize) -> ControlFlow<()>,
+    ) -> Option<SearchResults> {
+        let mut groups = Vec::new();
+        let mut scanned: usize = 0;
+
+        for part in &self.parts {
+            let filters: Vec<Filter> = FilterField::available(&part.protocol.id())
+                .iter()
+                .map(|field| Filter::compiled(*field, &query.pattern, query.is_regex))
+                .collect();
+            if filters.iter().any(|filter| !filter.is_active()) {
+                continue;
+            }
+
+            let mut matches = Vec::new();
+            for index in 0..part.records.len() {
+                let field = filters
+                    .iter()
+                    .find(|filter| part.records.matches(index, filter))
+                    .map(Filter::field);
+                if let Some(field) = field {
+                    matches.push(SearchMatch {
+                        id: RecordId {
+                            protocol: part.protocol,
+                            generation: part.generation,
+                            index,
+                        },
+                        field,
+                        captured: part.captured.get(index).copied(),
+                    });
+                }
+
+                scanned = scanned.saturating_add(1);
+                if scanned % PROGRESS_STEP == 0 && on_progress(scanned).is_break() {
+                    return None;
+                }
+            }
+
+            if !matches.is_empty() {
+                groups.push(SearchGroup {
+                    protocol: part.protocol,
+                    matches,
+                });
+            }
+        }
+
+        Some(SearchResults {
+            query: query.clone(),
+            groups,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ws::data::Locator;
+    use dpi::protocols::arp::{ArpDto, HardwareAddress, Operation, ProtocolAddress};
+    use dpi::protocols::ethernet::mac::MacAddress;
+    use std::net::Ipv4Addr;
+
+    const HOST: Ipv4Addr = Ipv4Addr::new(192, 168, 0, 10);
+    const GATEWAY: Ipv4Addr = Ipv4Addr::new(192, 168, 0, 1);
+    const MAC: MacAddress = MacAddress([0x00, 0x1A, 0x2B, 0x3C, 0x4D, 0x5E]);
+
+    fn query(pattern: &str) -> SearchQuery {
+        SearchQuery {
+            pattern: pattern.to_string(),
+            is_regex: false,
+        }
+    }
+
+    fn arp(sender: Ipv4Addr, target: Ipv4Addr) -> ArpDto {
+        ArpDto {
+            operation: Operation::Request,
+            sender_hardware: HardwareAddress::Mac(MAC),
+            sender_protocol: ProtocolAddress::IPv4(sender),
+            target_hardware: HardwareAddress::Mac(MacAddress([0x00; 6])),
+            target_protocol: ProtocolAddress::IPv4(target),
+        }
+    }
+
+    fn locator(source: Ipv4Addr, destination: Ipv4Addr) -> Locator {
+        Locator {
+            mac: (MAC, MacAddress([0xFF; 6])),
+            ipv4: Some((source, destination)),
+            ipv6: None,
+            tunnel: None,
+        }
+    }
+
+    fn storage() -> InspectorStorage {
+        let mut storage = InspectorStorage::default();
+        let now = Local::now();
+        storage.arp.push(arp(GATEWAY, HOST), now, 0);
+        storage
+            .arp
+            .push(arp(GATEWAY, Ipv4Addr::new(192, 168, 0, 20)), now, 1);
+        storage.ethernet.push(locator(HOST, GATEWAY), now, 2);
+        storage.ethernet.push(locator(GATEWAY, HOST), now, 3);
+        storage
+    }
+
+    #[test]
+    fn test_search_groups() {
+        let snapshot = SearchSnapshot::new(&storage());
+        assert_eq!(snapshot.total(), 4);
+
+        let results = snapshot
+            .search(&query("192.168.0.10"), |_| ControlFlow::Continue(()))
+            .unwrap();
+        assert_eq!(results.total(), 3);
+
+        let groups: Vec<(ProtocolsRegistered, Vec<(usize, FilterField)>)> = results
+            .groups
+            .iter()
+            .map(|group| {
+                let matches = group
+                    .matches
+                    .iter()
+                    .map(|found| (found.id.index, found.field))
+                    .collect();
+                (group.protocol, matches)
+            })
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                (
+                    ProtocolsRegistered::Arp,
+                    vec![(0, FilterField::DestinationIp)]
+                ),
+                (
+                    ProtocolsRegistered::Ethernet,
+                    vec![(0, FilterField::SourceIp), (1, FilterField::DestinationIp)]
+                ),
+            ]
+        );
+
+        // Invalid regex doesn't match anything
+        let invalid = SearchQuery {
+            pattern: "192.(168".to_string(),
+            is_regex: true,
+        };
+        assert!(invalid.error().is_some());
+        let results = snapshot.search(&invalid, |_| ControlFlow::Continue(()));
+        assert_eq!(results.map(|results| results.total()), Some(0));
+    }
+
+    #[test]
+    fn test_ids_stay_valid() {
+        let mut storage = storage();
+        let snapshot = SearchSnapshot::new(&storage);
+        let mut results = snapshot
+            .search(&query("00:1A:2B"), |_| ControlFlow::Continue(()))
+            .unwrap();
+        assert_eq!(results.total(), 4);
+
+        // Appended records don't move the found ones
+        storage
+            .ethernet
+            .push(locator(HOST, GATEWAY), Local::now(), 4);
+        results.retain_valid(&storage);
+        assert_eq!(results.total(), 4);
+        let first = &results.groups[1].matches[0];
+        assert_eq!(storage.frame_of(&first.id), Some(2));
+
+        storage.arp.clear();
+        results.retain_valid(&storage);
+        assert_eq!(results.groups.len(), 1);
+        assert_eq!(results.groups[0].protocol, ProtocolsRegistered::Ethernet);
+    }
+
+    #[test]
+    fn test_search_cancelled() {
+        let mut storage = InspectorStorage::default();
+        for frame in 0..(PROGRESS_STEP as u64 * 2) {
+            storage
+                .ethernet
+                .push(locator(HOST, GATEWAY), Local::now(), frame);
+        }
+        let snapshot = SearchSnapshot::new(&storage);
+
+        let mut reported = vec![];
+        let results = snapshot.search(&query("192.168"), |scanned| {
+            reported.push(scanned);
+            ControlFlow::Break(())
+        });
+        assert!(results.is_none());
+        assert_eq!(reported, vec![PROGRESS_STEP]);
+    }
+}
@@ -0,0 +1,369 @@
+use crate::errors::FileError;
+use crate::net::NetStorage;
+use crate::net::anonymize::Anonymizer;
+use crate::net::inspector::ProtocolsRegistered;
+use crate::net::notes::ExportedNote;
+use chrono::{DateTime, Local};
+use dpi::protocols::ethernet::mac::MacAddress;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::Path;
+use strum::IntoEnumIterator;
+
+pub const FILTER_NAME: &str = "JSON";
+pub const FILTER_EXTENSIONS: &[&str] = &["json"];
+// Percentage points of the frames
+pub const DEFAULT_SHARE_THRESHOLD: f64 = 5.0;
+
+/// Aggregates of the capture at some moment, taken from the existing counters. <br>
+/// Counters are cumulative since the last reset, so it's compared with the later snapshot.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Snapshot {
+    pub name: String,
+    pub taken: DateTime<Local>,
+    pub frames: usize,
+    // Records by protocol, as shown on the Stats tab
+    pub protocols: BTreeMap<String, usize>,
+    // Sorted by the bytes in both directions
+    pub hosts: Vec<HostSnapshot>,
+    pub devices: Vec<DeviceSnapshot>,
+    // Absent in the snapshots of the older versions
+    #[serde(default)]
+    pub notes: Vec<ExportedNote>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct HostSnapshot {
+    pub ip: IpAddr,
+    pub name: Option<String>,
+    pub bytes: usize,
+    pub packets: usize,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct DeviceSnapshot {
+    pub mac: MacAddress,
+    pub ip: Vec<Ipv4Addr>,
+    // Alias or hostname
+    pub name: Option<String>,
+    pub vendor: Option<String>,
+}
+
+impl Snapshot {
+    pub fn capture(name: String, storage: &NetStorage, now: DateTime<Local>) -> Self {
+        let protocols = ProtocolsRegistered::iter()
+            .map(|protocol| {
+                let records = storage.inspector.records_captured(&protocol);
+                (protocol.to_string(), records)
+            })
+            .collect();
+        let hosts = storage
+            .endpoints
+            .top(None)
+            .into_iter()
+            .map(|host| HostSnapshot {
+                ip: host.ip,
+                name: host.name.clone(),
+                bytes: host.total_bytes(),
+                packets: host.sent.packets.saturating_add(host.received.packets),
+            })
+            .collect();
+        let devices = storage
+            .devices
+            .list()
+            .iter()
+            .map(|device| DeviceSnapshot {
+                mac: device.mac.clone(),
+                ip: device.ip.clone(),
+                name: storage
+                    .devices
+                    .aliases
+                    .get(&device.mac)
+                    .or(device.hostname.as_ref())
+                    .cloned(),
+                vendor: device.vendor.as_ref().map(|vendor| vendor.short.clone()),
+            })
+            .collect();
+
+        Self {
+            name,
+            taken: now,
+            frames: storage.inspector.ethernet.len(),
+            protocols,
+            hosts,
+            devices,
+            notes: storage.notes.export(&storage.inspector),
+        }
+    }
+
+    /// Share of the frames with the protocol, in percents.
+    pub fn share(&self, protocol: &str) -> f64 {
+        let records = self.protocols.get(protocol).copied().unwrap_or(0);
+        if self.frames == 0 {
+            return 0.0;
+        }
+
+        records as f64 * 100.0 / self.frames as f64
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, FileError> {
+        let data = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    pub fn save_to_file(
+        &self, path: &Path, anonymizer: Option<Anonymizer>,
+    ) -> Result<(), FileError> {
+        let data = match anonymizer {
+            Some(mut anonymizer) => {
+                serde_json::to_string_pretty(&self.anonymized(&mut anonymizer))?
+            },
+            None => serde_json::to_string_pretty(self)?,
+        };
+        fs::write(path, data)?;
+
+        Ok(())
+    }
+
+    /// Name of the device may be its alias, it's replaced as the hostname.
+    /// Notes are set by the user, they're kept.
+    pub fn anonymized(&self, anonymizer: &mut Anonymizer) -> Self {
+        let hosts = self
+            .hosts
+            .iter()
+            .map(|host| HostSnapshot {
+                ip: anonymizer.ip(host.ip),
+                name: host.name.as_ref().map(|name| anonymizer.hostname(name)),
+                ..host.clone()
+            })
+            .collect();
+        let devices = self
+            .devices
+            .iter()
+            .map(|device| DeviceSnapshot {
+                mac: anonymizer.mac(&device.mac),
+                ip: device.ip.iter().map(|ip| anonymizer.ipv4(*ip)).collect(),
+                name: device.name.as_ref().map(|name| anonymizer.hostname(name)),
+                vendor: anonymizer.vendor(device.vendor.as_ref()),
+            })
+            .collect();
+
+        Self {
+            hosts,
+            devices,
+            ..self.clone()
+        }
+    }
+}
+
+pub struct ProtocolDelta {
+    pub protocol: String,
+    pub before: usize,
+    pub after: usize,
+    pub share_before: f64,
+    pub share_after: f64,
+}
+
+impl ProtocolDelta {
+    // In percentage points
+    pub fn share_change(&self) -> f64 {
+        self.share_after - self.share_before
+    }
+
+    pub fn is_significant(&self, threshold: f64) -> bool {
+        self.share_change().abs() > threshold
+    }
+}
+
+/// Difference between two snapshots. The earlier one is taken as "before".
+pub struct SnapshotDiff<'a> {
+    pub before: &'a Snapshot,
+    pub after: &'a Snapshot,
+    // Protocols, seen in any of the snapshots
+    pub protocols: Vec<ProtocolDelta>,
+    pub devices_new: Vec<&'a DeviceSnapshot>,
+    pub devices_gone: Vec<&'a DeviceSnapshot>,
+    pub hosts_new: HashSet<IpAddr>,
+    pub hosts_gone: HashSet<IpAddr>,
+}
+
+impl<'a> SnapshotDiff<'a> {
+    pub fn compare(first: &'a Snapshot, second: &'a Snapshot) -> Self {
+        let (before, after) = if second.taken < first.taken {
+            (second, first)
+        } else {
+            (first, second)
+        };
+
+        let protocols = before
+            .protocols
+            .keys()
+            .chain(after.protocols.keys())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .filter_map(|protocol| {
+                let delta = ProtocolDelta {
+                    protocol: protocol.clone(),
+                    before: before.protocols.get(protocol).copied().unwrap_or(0),
+                    after: after.protocols.get(protocol).copied().unwrap_or(0),
+                    share_before: before.share(protocol),
+                    share_after: after.share(protocol),
+                };
+                (delta.before > 0 || delta.after > 0).then_some(delta)
+            })
+            .collect();
+
+        let macs = |snapshot: &Snapshot| {
+            snapshot
+                .devices
+                .iter()
+                .map(|device| device.mac.clone())
+                .collect::<HashSet<_>>()
+        };
+        let (macs_before, macs_after) = (macs(before), macs(after));
+        let ips = |snapshot: &Snapshot| {
+            snapshot
+                .hosts
+                .iter()
+                .map(|host| host.ip)
+                .collect::<HashSet<_>>()
+        };
+        let (ips_before, ips_after) = (ips(before), ips(after));
+
+        Self {
+            before,
+            after,
+            protocols,
+            devices_new: after
+                .devices
+                .iter()
+                .filter(|device| !macs_before.contains(&device.mac))
+                .collect(),
+            devices_gone: before
+                .devices
+                .iter()
+                .filter(|device| !macs_after.contains(&device.mac))
+                .collect(),
+            hosts_new: ips_after.difference(&ips_before).copied().collect(),
+            hosts_gone: ips_before.difference(&ips_after).copied().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::anonymize::AnonymizeSettings;
+    use chrono::Duration;
+
+    fn snapshot(
+        taken: DateTime<Local>, frames: usize, dns: usize, hosts: &[&str], macs: &[u8],
+    ) -> Snapshot {
+        Snapshot {
+            name: String::new(),
+            taken,
+            frames,
+            protocols: BTreeMap::from([
+                ("DNS".to_string(), dns),
+                ("HTTP".to_string(), 0),
+            ]),
+            hosts: hosts
+                .iter()
+                .map(|ip| HostSnapshot {
+                    ip: ip.parse().unwrap(),
+                    name: None,
+                    bytes: 100,
+                    packets: 1,
+                })
+                .collect(),
+            devices: macs
+                .iter()
+                .map(|last| DeviceSnapshot {
+                    mac: MacAddress([0, 0, 0, 0, 0, *last]),
+                    ip: vec![],
+                    name: None,
+                    vendor: None,
+                })
+                .collect(),
+            notes: vec![],
+        }
+    }
+
+    #[test]
+    fn test_compare() {
+        let now = Local::now();
+        let before = snapshot(now, 200, 50, &["1.1.1.1", "8.8.8.8"], &[1, 2]);
+        let after = snapshot(
+            now + Duration::minutes(10),
+            1000,
+            100,
+            &["8.8.8.8", "9.9.9.9"],
+            &[2, 3],
+        );
+
+        // Order of the arguments doesn't matter
+        let diff = SnapshotDiff::compare(&after, &before);
+        assert_eq!(diff.before, &before);
+
+        // HTTP isn't seen in both
+        assert_eq!(diff.protocols.len(), 1);
+        let dns = &diff.protocols[0];
+        assert_eq!((dns.before, dns.after), (50, 100));
+        assert_eq!(dns.share_change(), -15.0);
+        assert!(dns.is_significant(DEFAULT_SHARE_THRESHOLD));
+        assert!(!dns.is_significant(20.0));
+
+        assert_eq!(diff.devices_new.len(), 1);
+        assert_eq!(diff.devices_new[0].mac, MacAddress([0, 0, 0, 0, 0, 3]));
+        assert_eq!(diff.devices_gone.len(), 1);
+        assert_eq!(diff.devices_gone[0].mac, MacAddress([0, 0, 0, 0, 0, 1]));
+        assert_eq!(
+            diff.hosts_new,
+            HashSet::from(["9.9.9.9".parse::<IpAddr>().unwrap()])
+        );
+        assert_eq!(
+            diff.hosts_gone,
+            HashSet::from(["1.1.1.1".parse::<IpAddr>().unwrap()])
+        );
+    }
+
+    #[test]
+    fn test_share_without_frames() {
+        let empty = snapshot(Local::now(), 0, 0, &[], &[]);
+        assert_eq!(empty.share("DNS"), 0.0);
+        assert_eq!(empty.share("Unknown"), 0.0);
+    }
+
+    #[test]
+    fn test_anonymized() {
+        let mut snapshot = snapshot(Local::now(), 10, 5, &["1.1.1.1", "8.8.8.8"], &[7]);
+        snapshot.hosts[0].name = Some("one.one.one.one".to_string());
+        snapshot.devices[0].ip = vec![Ipv4Addr::new(10, 0, 0, 7)];
+        snapshot.devices[0].name = Some("fileserver.corp.local".to_string());
+        snapshot.devices[0].vendor = Some("Xerox".to_string());
+
+        let mut anonymizer = Anonymizer::new(AnonymizeSettings {
+            preserve_oui: false,
+            preserve_private: false,
+        });
+        let anonymized = snapshot.anonymized(&mut anonymizer);
+        let json = serde_json::to_string(&anonymized).unwrap();
+        for original in [
+            "1.1.1.1",
+            "8.8.8.8",
+            "10.0.0.7",
+            "fileserver",
+            "corp",
+            "Xerox",
+        ] {
+            assert!(!json.contains(original), "{original} is left");
+        }
+        assert_ne!(anonymized.devices[0].mac, snapshot.devices[0].mac);
+        // Counters aren't changed
+        assert_eq!(anonymized.hosts[1].bytes, snapshot.hosts[1].bytes);
+        // Same input, same pseudonym within the export
+        assert_eq!(anonymized, snapshot.anonymized(&mut anonymizer));
+    }
+}
@@ -1,20 +1,32 @@
 use crate::context::ClientSettings;
-use chrono::{DateTime, Local, TimeZone};
-use dpi::dto::frame::FrameHeader;
+use chrono::{DateTime, Local, TimeDelta, TimeZone};
+use dpi::dto::frame::{FrameDirection, FrameHeader};
+use dpi::protocols::ethernet::mac::MacAddress;
 use std::collections::VecDeque;
 use std::fmt::Formatter;
 use strum_macros::EnumIter;
 use thiserror::Error;
 
+// Oldest markers are dropped after that
+pub const MARKERS_CAPACITY: usize = 100;
+// Devices, plotted at the same time. Samples are kept only for them
+pub const DEVICE_SERIES_MAX: usize = 3;
+
 #[derive(Default)]
 pub struct SpeedData {
     throughput: VecDeque<Sample>,
     send: VecDeque<Sample>,
     receive: VecDeque<Sample>,
+    // Notable events, shown on the plot as the vertical lines
+    markers: VecDeque<PlotMarker>,
+    // Selected devices, in the order of the selection
+    devices: Vec<DeviceSeries>,
 
     bucket_throughput: Vec<f64>,
     bucket_send: Vec<f64>,
     bucket_receive: Vec<f64>,
+    // Time the buckets were computed at
+    updated: Option<DateTime<Local>>,
 }
 
 impl SpeedData {
@@ -36,12 +48,90 @@ impl SpeedData {
         self.throughput.push_back(sample);
     }
 
+    /// Frame is sent by the source device & received by the destination one.
+    pub fn load_device_sample(
+        &mut self, source: &MacAddress, destination: &MacAddress, sample: &Sample,
+    ) {
+        for series in &mut self.devices {
+            if series.mac == *source {
+                series.send.push_back(sample.clone());
+            } else if series.mac == *destination {
+                series.receive.push_back(sample.clone());
+            }
+        }
+    }
+
+    /// Device gets its own series. False, if the limit of the series is reached.
+    pub fn plot_device(&mut self, mac: &MacAddress) -> bool {
+        if self.is_device_plotted(mac) {
+            return true;
+        }
+        if self.devices.len() >= DEVICE_SERIES_MAX {
+            return false;
+        }
+        self.devices.push(DeviceSeries::new(mac.clone()));
+        true
+    }
+
+    /// Series is removed with its samples.
+    pub fn unplot_device(&mut self, mac: &MacAddress) {
+        self.devices.retain(|series| series.mac != *mac);
+    }
+
+    pub fn is_device_plotted(&self, mac: &MacAddress) -> bool {
+        self.devices.iter().any(|series| series.mac == *mac)
+    }
+
+    pub fn plotted_devices(&self) -> impl Iterator<Item = &MacAddress> {
+        self.devices.iter().map(|series| &series.mac)
+    }
+
+    /// Marks the moment on the plot. Called by the alerts, connection events & the user.
+    pub fn push_marker(
+        &mut self, time: DateTime<Local>, kind: MarkerKind, label: impl Into<String>,
+    ) {
+        if self.markers.len() >= MARKERS_CAPACITY {
+            self.markers.pop_front();
+        }
+        self.markers.push_back(PlotMarker {
+            time,
+            kind,
+            label: label.into(),
+        });
+    }
+
+    pub fn markers_amount(&self) -> usize {
+        self.markers.len()
+    }
+
+    pub fn clear_markers(&mut self) {
+        self.markers.clear();
+    }
+
+    // Amounts of the throughput, sent and received samples
+    #[cfg(test)]
+    pub fn samples_amount(&self) -> (usize, usize, usize) {
+        (self.throughput.len(), self.send.len(), self.receive.len())
+    }
+
     pub fn update_info(&mut self, settings: &ClientSettings) {
         let now = Local::now();
 
         Self::clear_deque_outdated(&mut self.throughput, settings, now);
         Self::clear_deque_outdated(&mut self.send, settings, now);
         Self::clear_deque_outdated(&mut self.receive, settings, now);
+        for series in &mut self.devices {
+            Self::clear_deque_outdated(&mut series.send, settings, now);
+            Self::clear_deque_outdated(&mut series.receive, settings, now);
+        }
+        let window = TimeDelta::seconds(i64::from(settings.plot.display_window_seconds));
+        while let Some(marker) = self.markers.front() {
+            if now - marker.time > window {
+                self.markers.pop_front();
+            } else {
+                break;
+            }
+        }
 
         Self::bucket_per_second(
             &mut self.bucket_throughput,
@@ -56,6 +146,10 @@ impl SpeedData {
             &settings.plot,
             now,
         );
+        for series in &mut self.devices {
+            series.update_buckets(&settings.plot, now);
+        }
+        self.updated = Some(now);
     }
 
     fn clear_deque_outdated(
@@ -70,25 +164,20 @@ impl SpeedData {
         }
     }
 
-    pub fn throughput_iter(&self) -> impl Iterator<Item = [f64; 2]> {
-        self.bucket_throughput
-            .iter()
-            .enumerate()
-            .map(|(i, value)| [i as f64, *value])
-    }
-
-    pub fn send_iter(&self) -> impl Iterator<Item = [f64; 2]> {
-        self.bucket_send
-            .iter()
-            .enumerate()
-            .map(|(i, value)| [i as f64, *value])
-    }
-
-    pub fn receive_iter(&self) -> impl Iterator<Item = [f64; 2]> {
-        self.bucket_receive
-            .iter()
-            .enumerate()
-            .map(|(i, value)| [i as f64, *value])
+    /// Copy of the buckets. Used by the plot, so it can be frozen while the data is collected.
+    pub fn snapshot(&self) -> SpeedSnapshot {
+        SpeedSnapshot {
+            taken: self.updated.unwrap_or_else(Local::now),
+            throughput: self.bucket_throughput.clone(),
+            send: self.bucket_send.clone(),
+            receive: self.bucket_receive.clone(),
+            markers: self.markers.iter().cloned().collect(),
+            devices: self
+                .devices
+                .iter()
+                .map(|series| (series.mac.clone(), series.bucket_throughput.clone()))
+                .collect(),
+        }
     }
 
     pub fn peak_throughput(&self) -> f64 {
@@ -103,6 +192,33 @@ impl SpeedData {
         Self::peak(&self.bucket_send)
     }
 
+    /// Peaks of the plotted device, sent & received are from its side.
+    pub fn device_peaks(&self, mac: &MacAddress) -> Option<SpeedValues> {
+        let series = self.devices.iter().find(|series| series.mac == *mac)?;
+        Some(SpeedValues {
+            throughput: Self::peak(&series.bucket_throughput),
+            send: Self::peak(&series.bucket_send),
+            receive: Self::peak(&series.bucket_receive),
+        })
+    }
+
+    /// Bytes per second, received & sent within the last second before `now`. <br>
+    /// They're shown outside the plot, so they're not in the units of the plot.
+    pub fn last_second(&self, now: DateTime<Local>) -> (f64, f64) {
+        let since = now - TimeDelta::seconds(1);
+        let bytes = |deque: &VecDeque<Sample>| {
+            deque
+                .iter()
+                .rev()
+                .take_while(|sample| sample.time_captured > since)
+                .filter(|sample| sample.time_captured <= now)
+                .map(|sample| f64::from(sample.wire_bytes))
+                .sum()
+        };
+
+        (bytes(&self.receive), bytes(&self.send))
+    }
+
     pub fn peak(bucket: &[f64]) -> f64 {
         bucket
             .iter()
@@ -128,11 +244,175 @@ impl SpeedData {
                 Err(_) => continue,
             };
             if second < seconds_max {
-                bucket[second] += settings.units.value(sample.captured_bytes as f64);
+                bucket[second] += settings.units.value(sample.wire_bytes as f64);
             }
         }
     }
 }
+struct DeviceSeries {
+    mac: MacAddress,
+    send: VecDeque<Sample>,
+    receive: VecDeque<Sample>,
+
+    bucket_throughput: Vec<f64>,
+    bucket_send: Vec<f64>,
+    bucket_receive: Vec<f64>,
+}
+
+impl DeviceSeries {
+    fn new(mac: MacAddress) -> Self {
+        Self {
+            mac,
+            send: VecDeque::new(),
+            receive: VecDeque::new(),
+            bucket_throughput: Vec::new(),
+            bucket_send: Vec::new(),
+            bucket_receive: Vec::new(),
+        }
+    }
+
+    fn update_buckets(&mut self, settings: &PlotSettings, now: DateTime<Local>) {
+        SpeedData::bucket_per_second(&mut self.bucket_send, &self.send, settings, now);
+        SpeedData::bucket_per_second(
+            &mut self.bucket_receive,
+            &self.receive,
+            settings,
+            now,
+        );
+        self.bucket_throughput = self
+            .bucket_send
+            .iter()
+            .zip(&self.bucket_receive)
+            .map(|(send, receive)| send + receive)
+            .collect();
+    }
+}
+
+/// Speed per second. Index of the bucket is how many seconds ago the frames were captured.
+#[derive(Debug, Clone)]
+pub struct SpeedSnapshot {
+    pub taken: DateTime<Local>,
+    throughput: Vec<f64>,
+    send: Vec<f64>,
+    receive: Vec<f64>,
+    markers: Vec<PlotMarker>,
+    // Throughput of the plotted devices
+    devices: Vec<(MacAddress, Vec<f64>)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpeedValues {
+    pub throughput: f64,
+    pub send: f64,
+    pub receive: f64,
+}
+
+impl SpeedSnapshot {
+    pub fn throughput_iter(&self) -> impl Iterator<Item = [f64; 2]> {
+        Self::points(&self.throughput)
+    }
+
+    pub fn send_iter(&self) -> impl Iterator<Item = [f64; 2]> {
+        Self::points(&self.send)
+    }
+
+    pub fn receive_iter(&self) -> impl Iterator<Item = [f64; 2]> {
+        Self::points(&self.receive)
+    }
+
+    pub fn devices_iter(
+        &self,
+    ) -> impl Iterator<Item = (&MacAddress, impl Iterator<Item = [f64; 2]>)> {
+        self.devices
+            .iter()
+            .map(|(mac, bucket)| (mac, Self::points(bucket)))
+    }
+
+    /// Values of the bucket nearest to the plot X coordinate.
+    pub fn values_at(&self, x: f64) -> Option<SpeedValues> {
+        let second = Self::second(x)?;
+        Some(SpeedValues {
+            throughput: *self.throughput.get(second)?,
+            send: self.send.get(second).copied().unwrap_or(0.0),
+            receive: self.receive.get(second).copied().unwrap_or(0.0),
+        })
+    }
+
+    /// Throughput of the plotted devices at the bucket nearest to the plot X coordinate.
+    pub fn device_values_at(&self, x: f64) -> Vec<(&MacAddress, f64)> {
+        let Some(second) = Self::second(x) else {
+            return vec![];
+        };
+        self.devices
+            .iter()
+            .filter_map(|(mac, bucket)| Some((mac, *bucket.get(second)?)))
+            .collect()
+    }
+
+    /// Markers with their plot X coordinates. Markers out of the window are skipped.
+    pub fn markers_iter(&self) -> impl Iterator<Item = (f64, &PlotMarker)> {
+        let window = self.throughput.len() as f64;
+        self.markers.iter().filter_map(move |marker| {
+            let x = (self.taken - marker.time).num_milliseconds() as f64 / 1000.0;
+            (0.0..window).contains(&x).then_some((x, marker))
+        })
+    }
+
+    /// Markers, which line is nearest to the plot X coordinate.
+    pub fn markers_at(&self, x: f64) -> impl Iterator<Item = &PlotMarker> {
+        self.markers_iter()
+            .filter(move |(marker_x, _)| (marker_x - x).abs() <= 0.5)
+            .map(|(_, marker)| marker)
+    }
+
+    /// Capture time of the bucket nearest to the plot X coordinate.
+    pub fn time_at(&self, x: f64) -> Option<DateTime<Local>> {
+        let second = i64::try_from(Self::second(x)?).ok()?;
+        Some(self.taken - TimeDelta::seconds(second))
+    }
+
+    fn second(x: f64) -> Option<usize> {
+        if !x.is_finite() || x < 0.0 {
+            return None;
+        }
+        Some(x.round() as usize)
+    }
+
+    fn points(bucket: &[f64]) -> impl Iterator<Item = [f64; 2]> {
+        bucket
+            .iter()
+            .enumerate()
+            .map(|(i, value)| [i as f64, *value])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlotMarker {
+    pub time: DateTime<Local>,
+    pub kind: MarkerKind,
+    // Short, it's shown in the plot readout
+    pub label: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MarkerKind {
+    Alert,
+    Connection,
+    Interface,
+    Manual,
+}
+
+impl MarkerKind {
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Self::Alert => "⚠",
+            Self::Connection => "🔌",
+            Self::Interface => "🖧",
+            Self::Manual => "🚩",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PlotSettings {
     pub display_window_seconds: u32,
@@ -201,7 +481,8 @@ impl TryFrom<&str> for SpeedUnitPerSecond {
 
 #[derive(Debug, Clone)]
 pub struct Sample {
-    pub captured_bytes: u32,
+    // Length on the wire: bytes, cut off by the snapshot length, are counted too
+    pub wire_bytes: u32,
     pub time_captured: DateTime<Local>,
 }
 
@@ -211,6 +492,23 @@ pub enum SampleDirection {
     Throughput(Sample),
 }
 
+impl SampleDirection {
+    /// Direction hint of the server takes precedence. If it's unknown,
+    /// the frame is sent from the local (private) source or received by the local destination.
+    pub fn resolve(
+        sample: Sample, hint: FrameDirection, is_source_local: bool,
+        is_destination_local: bool,
+    ) -> Self {
+        match hint {
+            FrameDirection::Egress => Self::Send(sample),
+            FrameDirection::Ingress => Self::Receive(sample),
+            FrameDirection::Unknown if is_source_local => Self::Send(sample),
+            FrameDirection::Unknown if is_destination_local => Self::Receive(sample),
+            FrameDirection::Unknown => Self::Throughput(sample),
+        }
+    }
+}
+
 impl Sample {
     fn is_outdated(&self, now: DateTime<Local>, settings: &ClientSettings) -> bool {
         (now - self.time_captured).num_seconds()
@@ -223,7 +521,7 @@ impl TryFrom<&FrameHeader> for Sample {
 
     fn try_from(header: &FrameHeader) -> Result<Self, Self::Error> {
         Ok(Self {
-            captured_bytes: header.caplen,
+            wire_bytes: header.len.max(header.caplen),
             time_captured: Local
                 .timestamp_opt(header.tv_sec, header.tv_usec as u32)
                 .single()
@@ -240,3 +538,165 @@ pub enum SpeedError {
     #[error("Failed to convert speed unit.")]
     FailedToConvertSpeedUnit,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_values() {
+        let taken = Local::now();
+        let snapshot = SpeedSnapshot {
+            taken,
+            throughput: vec![3.0, 5.0, 0.0],
+            send: vec![1.0, 2.0, 0.0],
+            receive: vec![2.0, 3.0, 0.0],
+            markers: vec![],
+            devices: vec![],
+        };
+
+        let expected = SpeedValues {
+            throughput: 5.0,
+            send: 2.0,
+            receive: 3.0,
+        };
+        assert_eq!(snapshot.values_at(1.3), Some(expected));
+        assert_eq!(snapshot.time_at(1.3), Some(taken - TimeDelta::seconds(1)));
+
+        // Out of the window
+        assert_eq!(snapshot.values_at(-0.6), None);
+        assert_eq!(snapshot.values_at(3.0), None);
+        assert_eq!(snapshot.time_at(f64::NAN), None);
+    }
+
+    #[test]
+    fn test_last_second() {
+        let now = Local::now();
+        let sample = |wire_bytes: u32, millis: i64| Sample {
+            wire_bytes,
+            time_captured: now - TimeDelta::milliseconds(millis),
+        };
+        let mut speed = SpeedData::default();
+        speed.load_complete_sample(SampleDirection::Receive(sample(4000, 2500)));
+        speed.load_complete_sample(SampleDirection::Receive(sample(1000, 900)));
+        speed.load_complete_sample(SampleDirection::Send(sample(300, 500)));
+        speed.load_complete_sample(SampleDirection::Receive(sample(500, 100)));
+        speed.load_complete_sample(SampleDirection::Throughput(sample(9000, 50)));
+
+        assert_eq!(speed.last_second(now), (1500.0, 300.0));
+        assert_eq!(SpeedData::default().last_second(now), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_markers() {
+        let now = Local::now();
+        let mut speed = SpeedData::default();
+        for second in (0..MARKERS_CAPACITY as i64 + 5).rev() {
+            speed.push_marker(
+                now - TimeDelta::seconds(second),
+                MarkerKind::Manual,
+                second.to_string(),
+            );
+        }
+        // Oldest pushed are dropped
+        assert_eq!(speed.markers_amount(), MARKERS_CAPACITY);
+
+        let mut snapshot = speed.snapshot();
+        snapshot.taken = now;
+        snapshot.throughput = vec![0.0; 4];
+        let labels = snapshot
+            .markers_iter()
+            .map(|(x, marker)| (x, marker.label.as_str()))
+            .collect::<Vec<_>>();
+        // Only the visible window
+        assert_eq!(labels, vec![(3.0, "3"), (2.0, "2"), (1.0, "1"), (0.0, "0")]);
+        let hovered = snapshot
+            .markers_at(1.4)
+            .map(|marker| marker.label.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(hovered, vec!["1"]);
+
+        speed.clear_markers();
+        assert_eq!(speed.markers_amount(), 0);
+    }
+
+    #[test]
+    fn test_device_series() {
+        let device = |last: u8| MacAddress([0x00, 0x1A, 0x2B, 0x3C, 0x4D, last]);
+        let mut speed = SpeedData::default();
+        let now = Local::now();
+        let sample = |wire_bytes| Sample {
+            wire_bytes,
+            time_captured: now,
+        };
+
+        // Not plotted devices aren't counted
+        speed.load_device_sample(&device(1), &device(2), &sample(100));
+        for last in 1..=DEVICE_SERIES_MAX as u8 {
+            assert!(speed.plot_device(&device(last)));
+        }
+        assert!(!speed.plot_device(&device(10)));
+        assert!(speed.plot_device(&device(1)));
+
+        speed.load_device_sample(&device(1), &device(2), &sample(100));
+        speed.load_device_sample(&device(2), &device(1), &sample(60));
+        speed.load_device_sample(&device(1), &device(10), &sample(40));
+        let settings = PlotSettings {
+            display_window_seconds: 10,
+            units: SpeedUnitPerSecond::Bytes,
+        };
+        for series in &mut speed.devices {
+            series.update_buckets(&settings, now);
+        }
+
+        let peaks = speed.device_peaks(&device(1)).unwrap();
+        assert_eq!(
+            peaks,
+            SpeedValues {
+                throughput: 200.0,
+                send: 140.0,
+                receive: 60.0,
+            }
+        );
+        assert_eq!(speed.device_peaks(&device(2)).unwrap().send, 60.0);
+        assert_eq!(speed.device_peaks(&device(3)).unwrap().throughput, 0.0);
+
+        let snapshot = speed.snapshot();
+        let plotted: Vec<&MacAddress> =
+            snapshot.devices_iter().map(|(mac, _)| mac).collect();
+        assert_eq!(plotted, vec![&device(1), &device(2), &device(3)]);
+
+        // Freed, so another device fits
+        speed.unplot_device(&device(1));
+        assert!(speed.device_peaks(&device(1)).is_none());
+        assert!(speed.plot_device(&device(10)));
+    }
+
+    #[test]
+    fn test_sample_direction() {
+        let sample = || Sample {
+            wire_bytes: 60,
+            time_captured: Local::now(),
+        };
+        let resolve =
+            |hint, is_source_local, is_destination_local| match SampleDirection::resolve(
+                sample(),
+                hint,
+                is_source_local,
+                is_destination_local,
+            ) {
+                SampleDirection::Send(_) => "send",
+                SampleDirection::Receive(_) => "receive",
+                SampleDirection::Throughput(_) => "throughput",
+            };
+
+        // Hint wins over the addresses, e.g. both ends are private in the datacenter
+        assert_eq!(resolve(FrameDirection::Ingress, true, true), "receive");
+        assert_eq!(resolve(FrameDirection::Egress, false, false), "send");
+
+        // Fallback to the private addresses
+        assert_eq!(resolve(FrameDirection::Unknown, true, true), "send");
+        assert_eq!(resolve(FrameDirection::Unknown, false, true), "receive");
+        assert_eq!(resolve(FrameDirection::Unknown, false, false), "throughput");
+    }
+}
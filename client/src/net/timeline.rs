@@ -0,0 +1,449 @@
+use crate::errors::FileError;
+use crate::net::anonymize::Anonymizer;
+use crate::notifications::Severity;
+use chrono::{DateTime, Local};
+use dpi::dto::fields;
+use dpi::protocols::ProtocolId;
+use dpi::protocols::ethernet::mac::MacAddress;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::Path;
+
+pub const FILTER_NAME: &str = "JSON";
+pub const FILTER_EXTENSIONS: &[&str] = &["json"];
+// Oldest events are dropped after that
+pub const CAPACITY: usize = 1000;
+
+/// Chronological list of the notable events. Detectors push into it, it's never edited.
+pub struct Timeline {
+    entries: VecDeque<TimelineEntry>,
+    // Dropped because of the capacity
+    evicted: usize,
+    // Interface is changed by the server reboot, so it's compared between the connections
+    interface: Option<Option<String>>,
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Self {
+            entries: VecDeque::with_capacity(CAPACITY),
+            evicted: 0,
+            interface: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TimelineEntry {
+    pub time: DateTime<Local>,
+    pub event: TimelineEvent,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum TimelineEvent {
+    Connected {
+        address: String,
+    },
+    // Without the reason, if the user disconnected
+    Disconnected {
+        reason: Option<String>,
+    },
+    InterfaceChanged {
+        from: Option<String>,
+        to: Option<String>,
+    },
+    // Addresses of the server interface, e.g. renewed by DHCP
+    InterfaceAddressChanged {
+        interface: String,
+        from: Vec<IpAddr>,
+        to: Vec<IpAddr>,
+    },
+    CaptureIdle {
+        seconds: u64,
+    },
+    NewDevice {
+        mac: MacAddress,
+        ip: Option<Ipv4Addr>,
+    },
+    IpConflict {
+        ip: Ipv4Addr,
+        previous: MacAddress,
+        current: MacAddress,
+        is_failover: bool,
+    },
+    // DHCP server, other than the first one seen, answers the clients
+    UnknownDhcpServer {
+        ip: Ipv4Addr,
+        mac: MacAddress,
+        known: Ipv4Addr,
+    },
+    NxDomainBurst {
+        responses: usize,
+        seconds: i64,
+    },
+    // IPv6 router advertises after the learning period, or next to another one
+    RogueRouter {
+        mac: MacAddress,
+        ip: Ipv6Addr,
+        known: Vec<MacAddress>,
+    },
+}
+
+/// Record or device, related to the event.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TimelineTarget {
+    Device(MacAddress),
+    // Records of the protocol around the time of the event
+    Records(ProtocolId),
+}
+
+impl TimelineEvent {
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::Connected { .. }
+            | Self::Disconnected { reason: None }
+            | Self::InterfaceChanged { .. }
+            | Self::NewDevice { .. } => Severity::Info,
+            Self::IpConflict { is_failover, .. } => match is_failover {
+                true => Severity::Info,
+                false => Severity::Warning,
+            },
+            Self::Disconnected { reason: Some(_) }
+            | Self::InterfaceAddressChanged { .. }
+            | Self::CaptureIdle { .. }
+            | Self::NxDomainBurst { .. } => Severity::Warning,
+            Self::UnknownDhcpServer { .. } | Self::RogueRouter { .. } => {
+                Severity::Critical
+            },
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self {
+            Self::Connected { .. } => "🔌",
+            Self::Disconnected { .. } => "⛔",
+            Self::InterfaceChanged { .. } => "🖧",
+            Self::InterfaceAddressChanged { .. } => "🏷",
+            Self::CaptureIdle { .. } => "⏸",
+            Self::NewDevice { .. } => "🆕",
+            Self::IpConflict { .. } => "⚠",
+            Self::UnknownDhcpServer { .. } => "🎫",
+            Self::NxDomainBurst { .. } => "📖",
+            Self::RogueRouter { .. } => "📡",
+        }
+    }
+
+    pub fn target(&self) -> Option<TimelineTarget> {
+        match self {
+            Self::NewDevice { mac, .. } => Some(TimelineTarget::Device(mac.clone())),
+            Self::IpConflict { current, .. } => {
+                Some(TimelineTarget::Device(current.clone()))
+            },
+            Self::UnknownDhcpServer { .. } => {
+                Some(TimelineTarget::Records(ProtocolId::DHCPv4))
+            },
+            Self::NxDomainBurst { .. } => Some(TimelineTarget::Records(ProtocolId::DNS)),
+            Self::RogueRouter { mac, .. } => Some(TimelineTarget::Device(mac.clone())),
+            Self::Connected { .. }
+            | Self::Disconnected { .. }
+            | Self::InterfaceChanged { .. }
+            | Self::InterfaceAddressChanged { .. }
+            | Self::CaptureIdle { .. } => None,
+        }
+    }
+
+    pub fn localize(&self) -> String {
+        let none = || t!("Tab.Status.Timeline.None").to_string();
+        match self {
+            Self::Connected { address } => {
+                t!("Tab.Status.Timeline.Event.Connected", "address" = address)
+            },
+            Self::Disconnected { reason: None } => {
+                t!("Tab.Status.Timeline.Event.Disconnected")
+            },
+            Self::Disconnected {
+                reason: Some(reason),
+            } => t!(
+                "Tab.Status.Timeline.Event.ConnectionLost",
+                "reason" = reason
+            ),
+            Self::InterfaceChanged { from, to } => t!(
+                "Tab.Status.Timeline.Event.InterfaceChanged",
+                "from" = from.clone().unwrap_or_else(none),
+                "to" = to.clone().unwrap_or_else(none)
+            ),
+            Self::InterfaceAddressChanged {
+                interface,
+                from,
+                to,
+            } => t!(
+                "Tab.Status.Timeline.Event.InterfaceAddressChanged",
+                "interface" = interface,
+                "from" = fields::list(from),
+                "to" = fields::list(to)
+            ),
+            Self::CaptureIdle { seconds } => {
+                t!("Tab.Status.Timeline.Event.CaptureIdle", "seconds" = seconds)
+            },
+            Self::NewDevice { mac, ip } => t!(
+                "Tab.Status.Timeline.Event.NewDevice",
+                "mac" = mac,
+                "ip" = ip.map(|ip| ip.to_string()).unwrap_or_else(none)
+            ),
+            Self::IpConflict {
+                ip,
+                previous,
+                current,
+                is_failover: false,
+            } => t!(
+                "Tab.Status.Timeline.Event.IpConflict",
+                "ip" = ip,
+                "previous" = previous,
+                "current" = current
+            ),
+            Self::IpConflict {
+                ip,
+                previous,
+                current,
+                is_failover: true,
+            } => t!(
+                "Tab.Status.Timeline.Event.Failover",
+                "ip" = ip,
+                "previous" = previous,
+                "current" = current
+            ),
+            Self::UnknownDhcpServer { ip, mac, known } => t!(
+                "Tab.Status.Timeline.Event.UnknownDhcpServer",
+                "ip" = ip,
+                "mac" = mac,
+                "known" = known
+            ),
+            Self::NxDomainBurst { responses, seconds } => t!(
+                "Tab.Status.Timeline.Event.NxDomainBurst",
+                "responses" = responses,
+                "seconds" = seconds
+            ),
+            Self::RogueRouter { mac, ip, known } => t!(
+                "Tab.Status.Timeline.Event.RogueRouter",
+                "mac" = mac,
+                "ip" = ip,
+                "known" = fields::list(known)
+            ),
+        }
+        .to_string()
+    }
+
+    /// Names of the interfaces & the reasons of the disconnection are kept.
+    pub fn anonymized(&self, anonymizer: &mut Anonymizer) -> Self {
+        match self {
+            Self::Connected { address } => {
+                let address = match address.parse::<SocketAddr>() {
+                    Ok(socket) => {
+                        SocketAddr::new(anonymizer.ip(socket.ip()), socket.port())
+                            .to_string()
+                    },
+                    Err(_) => anonymizer.hostname(address),
+                };
+                Self::Connected { address }
+            },
+            Self::InterfaceAddressChanged {
+                interface,
+                from,
+                to,
+            } => Self::InterfaceAddressChanged {
+                interface: interface.clone(),
+                from: from.iter().map(|ip| anonymizer.ip(*ip)).collect(),
+                to: to.iter().map(|ip| anonymizer.ip(*ip)).collect(),
+            },
+            Self::NewDevice { mac, ip } => Self::NewDevice {
+                mac: anonymizer.mac(mac),
+                ip: ip.map(|ip| anonymizer.ipv4(ip)),
+            },
+            Self::IpConflict {
+                ip,
+                previous,
+                current,
+                is_failover,
+            } => Self::IpConflict {
+                ip: anonymizer.ipv4(*ip),
+                previous: anonymizer.mac(previous),
+                current: anonymizer.mac(current),
+                is_failover: *is_failover,
+            },
+            Self::UnknownDhcpServer { ip, mac, known } => Self::UnknownDhcpServer {
+                ip: anonymizer.ipv4(*ip),
+                mac: anonymizer.mac(mac),
+                known: anonymizer.ipv4(*known),
+            },
+            Self::RogueRouter { mac, ip, known } => Self::RogueRouter {
+                mac: anonymizer.mac(mac),
+                ip: anonymizer.ipv6(*ip),
+                known: known.iter().map(|mac| anonymizer.mac(mac)).collect(),
+            },
+            Self::Disconnected { .. }
+            | Self::InterfaceChanged { .. }
+            | Self::CaptureIdle { .. }
+            | Self::NxDomainBurst { .. } => self.clone(),
+        }
+    }
+}
+
+impl Timeline {
+    pub fn push(&mut self, time: DateTime<Local>, event: TimelineEvent) {
+        if self.entries.len() >= CAPACITY {
+            self.entries.pop_front();
+            self.evicted = self.evicted.saturating_add(1);
+        }
+        self.entries.push_back(TimelineEntry { time, event });
+    }
+
+    /// Adds the event, if the active capture interface differs from the previous one.
+    /// The added event is returned.
+    pub fn observe_interface(
+        &mut self, time: DateTime<Local>, interface: &Option<String>,
+    ) -> Option<&TimelineEvent> {
+        let previous = self.interface.replace(interface.clone())?;
+        if previous == *interface {
+            return None;
+        }
+        let event = TimelineEvent::InterfaceChanged {
+            from: previous,
+            to: interface.clone(),
+        };
+        self.push(time, event);
+        self.entries.back().map(|entry| &entry.event)
+    }
+
+    /// Events of the severity or higher, the newest first.
+    pub fn filtered(&self, severity: Severity) -> impl Iterator<Item = &TimelineEntry> {
+        self.entries
+            .iter()
+            .rev()
+            .filter(move |entry| entry.event.severity() >= severity)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn evicted(&self) -> usize {
+        self.evicted
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.evicted = 0;
+    }
+
+    pub fn save_to_file(
+        &self, path: &Path, anonymizer: Option<Anonymizer>,
+    ) -> Result<(), FileError> {
+        let data = match anonymizer {
+            Some(mut anonymizer) => {
+                let entries: Vec<TimelineEntry> = self
+                    .entries
+                    .iter()
+                    .map(|entry| TimelineEntry {
+                        time: entry.time,
+                        event: entry.event.anonymized(&mut anonymizer),
+                    })
+                    .collect();
+                serde_json::to_string_pretty(&entries)?
+            },
+            None => serde_json::to_string_pretty(&self.entries)?,
+        };
+        fs::write(path, data)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn idle(seconds: u64) -> TimelineEvent {
+        TimelineEvent::CaptureIdle { seconds }
+    }
+
+    #[test]
+    fn test_capacity() {
+        let mut timeline = Timeline::default();
+        let now = Local::now();
+        for seconds in 0..(CAPACITY as u64 + 5) {
+            timeline.push(now + Duration::seconds(seconds as i64), idle(seconds));
+        }
+
+        assert_eq!(timeline.len(), CAPACITY);
+        assert_eq!(timeline.evicted(), 5);
+        // Newest goes first
+        let newest = timeline.filtered(Severity::Info).next().unwrap();
+        assert_eq!(newest.event, idle(CAPACITY as u64 + 4));
+        let oldest = timeline.filtered(Severity::Info).last().unwrap();
+        assert_eq!(oldest.event, idle(5));
+    }
+
+    #[test]
+    fn test_interface_changed() {
+        let mut timeline = Timeline::default();
+        let now = Local::now();
+        let eth0 = Some("eth0".to_string());
+
+        // The first one is only remembered
+        assert!(timeline.observe_interface(now, &eth0).is_none());
+        assert!(timeline.observe_interface(now, &eth0).is_none());
+        assert!(timeline.is_empty());
+
+        assert!(timeline.observe_interface(now, &None).is_some());
+        let event = timeline
+            .filtered(Severity::Info)
+            .next()
+            .map(|entry| &entry.event);
+        assert_eq!(
+            event,
+            Some(&TimelineEvent::InterfaceChanged {
+                from: eth0,
+                to: None,
+            })
+        );
+    }
+
+    #[test]
+    fn test_severity_filter() {
+        let mut timeline = Timeline::default();
+        let now = Local::now();
+        let mac = MacAddress([0, 0, 0, 0, 0, 1]);
+        timeline.push(
+            now,
+            TimelineEvent::NewDevice {
+                mac: mac.clone(),
+                ip: None,
+            },
+        );
+        timeline.push(now, idle(60));
+        timeline.push(
+            now,
+            TimelineEvent::UnknownDhcpServer {
+                ip: Ipv4Addr::new(192, 168, 0, 2),
+                mac,
+                known: Ipv4Addr::new(192, 168, 0, 1),
+            },
+        );
+
+        assert_eq!(timeline.filtered(Severity::Info).count(), 3);
+        assert_eq!(timeline.filtered(Severity::Warning).count(), 2);
+        let critical = timeline.filtered(Severity::Critical).collect::<Vec<_>>();
+        assert_eq!(critical.len(), 1);
+        assert_eq!(
+            critical[0].event.target(),
+            Some(TimelineTarget::Records(ProtocolId::DHCPv4))
+        );
+    }
+}
@@ -0,0 +1,36 @@
+use chrono::{DateTime, Local};
+
+/// Seconds of the record TTL, that are left at the moment. Zero, if the record is
+/// expired: clients shouldn't keep it in their caches anymore.
+pub fn remaining(
+    time_to_live: u32, captured: &DateTime<Local>, now: &DateTime<Local>,
+) -> u32 {
+    // Frame from the future (e.g. the clock of the server is ahead) isn't aged
+    let elapsed = (*now - *captured).num_seconds().max(0);
+    let elapsed = u32::try_from(elapsed).unwrap_or(u32::MAX);
+
+    time_to_live.saturating_sub(elapsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeDelta;
+
+    #[test]
+    fn test_remaining() {
+        let captured = Local::now();
+        let after = |milliseconds: i64| captured + TimeDelta::milliseconds(milliseconds);
+
+        assert_eq!(remaining(300, &captured, &captured), 300);
+        assert_eq!(remaining(300, &captured, &after(120_000)), 180);
+        // Partial second isn't counted yet
+        assert_eq!(remaining(300, &captured, &after(299_999)), 1);
+        assert_eq!(remaining(300, &captured, &after(300_000)), 0);
+        assert_eq!(remaining(300, &captured, &after(86_400_000)), 0);
+
+        // Record isn't cached at all
+        assert_eq!(remaining(0, &captured, &captured), 0);
+        assert_eq!(remaining(300, &captured, &after(-5_000)), 300);
+    }
+}
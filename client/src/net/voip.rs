@@ -0,0 +1,235 @@
+use chrono::{DateTime, Local};
+use dpi::protocols::rtp;
+use dpi::protocols::rtp::RtpDto;
+use dpi::protocols::sip::{SdpMedia, SipDto};
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+
+// Gain of the interarrival jitter estimate (RFC 3550, 6.4.1)
+const JITTER_GAIN: f64 = 1.0 / 16.0;
+const MILLISECONDS_IN_SECOND: f64 = 1000.0;
+const MICROSECONDS_IN_SECOND: f64 = 1_000_000.0;
+
+/// Statistics of the RTP streams by SSRC. <br>
+/// Media ports of SIP sessions are remembered, so the streams on them are marked as negotiated
+/// and the clock rates of the dynamic payload types are known.
+#[derive(Default)]
+pub struct RtpStreams {
+    streams: BTreeMap<u32, RtpStream>,
+    negotiated: HashMap<u16, SdpMedia>,
+}
+
+pub struct RtpStream {
+    pub ssrc: u32,
+    pub payload_type: u8,
+    pub source: SocketAddr,
+    pub destination: SocketAddr,
+    pub packets: usize,
+    pub is_negotiated: bool,
+
+    encoding: Option<String>,
+    clock_rate: Option<u32>,
+    // Arrival time & RTP timestamp of the last packet
+    last: Option<(DateTime<Local>, u32)>,
+    // Seconds
+    jitter: f64,
+}
+
+impl RtpStream {
+    pub fn payload_type_name(&self) -> String {
+        rtp::payload_type_name(self.payload_type, self.encoding.as_deref())
+    }
+
+    /// Interarrival jitter in milliseconds. Unknown, if the clock rate isn't known.
+    pub fn jitter(&self) -> Option<f64> {
+        self.clock_rate
+            .map(|_| self.jitter * MILLISECONDS_IN_SECOND)
+    }
+
+    fn update(&mut self, rtp: &RtpDto, arrival: DateTime<Local>) {
+        self.packets = self.packets.saturating_add(1);
+        if let (Some(clock_rate), Some((last_arrival, last_timestamp))) =
+            (self.clock_rate, self.last)
+        {
+            let arrival_delta = (arrival - last_arrival)
+                .num_microseconds()
+                .unwrap_or_default() as f64
+                / MICROSECONDS_IN_SECOND;
+            // Timestamps wrap around
+            let timestamp_delta = rtp.timestamp.wrapping_sub(last_timestamp) as i32
+                as f64
+                / f64::from(clock_rate);
+            let difference = (arrival_delta - timestamp_delta).abs();
+            self.jitter += (difference - self.jitter) * JITTER_GAIN;
+        }
+        self.last = Some((arrival, rtp.timestamp));
+    }
+}
+
+impl RtpStreams {
+    /// Remembers the media ports of the SDP body, if the message has it.
+    pub fn negotiate(&mut self, sip: &SipDto) {
+        for media in sip.media() {
+            // Zero port rejects the stream
+            if media.port != 0 {
+                self.negotiated.insert(media.port, media.clone());
+            }
+        }
+    }
+
+    pub fn observe(
+        &mut self, rtp: &RtpDto, endpoints: (SocketAddr, SocketAddr),
+        arrival: DateTime<Local>,
+    ) {
+        let stream = self.streams.entry(rtp.ssrc).or_insert_with(|| {
+            let (source, destination) = endpoints;
+            let media = [source.port(), destination.port()]
+                .iter()
+                .find_map(|port| self.negotiated.get(port));
+            let rtp_map = media.and_then(|media| {
+                media
+                    .rtp_maps
+                    .iter()
+                    .find(|rtp_map| rtp_map.payload_type == rtp.payload_type)
+            });
+
+            RtpStream {
+                ssrc: rtp.ssrc,
+                payload_type: rtp.payload_type,
+                source,
+                destination,
+                packets: 0,
+                is_negotiated: media.is_some(),
+                encoding: rtp_map
+                    .map(|rtp_map| rtp_map.encoding.clone())
+                    .or(rtp::encoding(rtp.payload_type).map(str::to_string)),
+                clock_rate: rtp_map
+                    .map(|rtp_map| rtp_map.clock_rate)
+                    .or(rtp::clock_rate(rtp.payload_type)),
+                last: None,
+                jitter: 0.0,
+            }
+        });
+
+        stream.update(rtp, arrival);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &RtpStream> {
+        self.streams.values()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.streams.is_empty()
+    }
+
+    pub fn estimated_size(&self) -> usize {
+        let negotiated: usize = self
+            .negotiated
+            .values()
+            .map(|media| {
+                size_of::<(u16, SdpMedia)>()
+                    + media.media.len()
+                    + media.protocol.len()
+                    + media.payload_types.len()
+                    + size_of_val(media.rtp_maps.as_slice())
+            })
+            .sum();
+
+        let streams: usize = self
+            .streams
+            .values()
+            .map(|stream| {
+                size_of::<(u32, RtpStream)>()
+                    + stream.encoding.as_deref().map(str::len).unwrap_or_default()
+            })
+            .sum();
+
+        streams + negotiated
+    }
+
+    pub fn clear(&mut self) {
+        self.streams.clear();
+        self.negotiated.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use dpi::protocols::sip::{RtpMap, SIPRequestDto};
+    use std::net::{IpAddr, Ipv4Addr};
+
+    fn endpoints(port_source: u16, port_destination: u16) -> (SocketAddr, SocketAddr) {
+        let address = IpAddr::V4(Ipv4Addr::new(192, 168, 1, 10));
+        (
+            SocketAddr::new(address, port_source),
+            SocketAddr::new(address, port_destination),
+        )
+    }
+
+    fn packet(ssrc: u32, payload_type: u8, timestamp: u32) -> RtpDto {
+        RtpDto {
+            ssrc,
+            payload_type,
+            marker: false,
+            sequence_number: 0,
+            timestamp,
+            payload_length: 160,
+        }
+    }
+
+    #[test]
+    fn test_jitter() {
+        let start = Local::now();
+        let mut streams = RtpStreams::default();
+
+        // PCMU: 20 ms of 8 kHz audio in every packet, the third one is late by 16 ms
+        let delays = [0, 20, 56, 60];
+        for (index, delay) in delays.iter().enumerate() {
+            streams.observe(
+                &packet(1, 0, (u32::MAX - 160).wrapping_add(index as u32 * 160)),
+                endpoints(49172, 3456),
+                start + Duration::milliseconds(*delay),
+            );
+        }
+
+        let stream = streams.iter().next().unwrap();
+        assert_eq!(stream.packets, 4);
+        assert!(!stream.is_negotiated);
+        // |D| is 0, 16 & 16 ms
+        let expected = (16.0 / 16.0) + (16.0 - 1.0) / 16.0;
+        assert!((stream.jitter().unwrap() - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_negotiated_stream() {
+        let mut streams = RtpStreams::default();
+        streams.negotiate(&SipDto::Request(SIPRequestDto {
+            method: dpi::protocols::sip::Methods::INVITE,
+            request_uri: "sip:bob@biloxi.example.com".to_string(),
+            headers: vec![],
+            media: vec![SdpMedia {
+                media: "audio".to_string(),
+                port: 49172,
+                protocol: "RTP/AVP".to_string(),
+                payload_types: vec![111],
+                rtp_maps: vec![RtpMap {
+                    payload_type: 111,
+                    encoding: "opus".to_string(),
+                    clock_rate: 48000,
+                }],
+            }],
+        }));
+
+        streams.observe(&packet(1, 111, 0), endpoints(3456, 49172), Local::now());
+        // Dynamic type on the other ports
+        streams.observe(&packet(2, 111, 0), endpoints(3456, 40000), Local::now());
+
+        let actual = streams
+            .iter()
+            .map(|stream| (stream.is_negotiated, stream.jitter()))
+            .collect::<Vec<_>>();
+        assert_eq!(actual, vec![(true, Some(0.0)), (false, None)]);
+    }
+}
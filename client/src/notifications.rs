@@ -0,0 +1,303 @@
+use crate::ui::modals::Modal;
+use crate::ui::modals::message::MessageModal;
+use chrono::{DateTime, Duration, Local};
+use crossbeam::channel::Sender;
+use std::collections::HashMap;
+use std::error::Error as _;
+use std::fs::File;
+use std::io::{BufReader, Cursor};
+use std::thread;
+use strum_macros::{Display, EnumIter, EnumString};
+use thiserror::Error;
+
+pub const DEFAULT_INTERVAL_SECONDS: u32 = 30;
+pub const SOUND_FILTER_NAME: &str = "Audio";
+pub const SOUND_FILTER_EXTENSIONS: &[&str] = &["wav", "mp3", "ogg", "flac"];
+
+const APP_NAME: &str = "xailyser";
+// Played, if the custom sound file isn't set
+const BUNDLED_SOUND: &[u8] = include_bytes!("../assets/alert.wav");
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NotificationSettings {
+    pub desktop: bool,
+    pub interval_seconds: u32,
+    pub severity: Severity,
+    pub sound: bool,
+    // Empty, if the bundled sound is played
+    pub sound_file: String,
+}
+
+impl NotificationSettings {
+    pub fn is_enabled(&self) -> bool {
+        self.desktop || self.sound
+    }
+}
+
+/// Alerts below the threshold are shown only inside the window.
+#[derive(
+    Clone, Copy, Debug, Display, EnumIter, EnumString, Eq, Ord, PartialEq, PartialOrd,
+)]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Severity {
+    pub fn localize(&self) -> String {
+        match self {
+            Severity::Info => t!("Notification.Severity.Info").to_string(),
+            Severity::Warning => t!("Notification.Severity.Warning").to_string(),
+            Severity::Critical => t!("Notification.Severity.Critical").to_string(),
+        }
+    }
+}
+
+/// Source of the alert. Rate limit is counted for every rule separately.
+#[derive(Clone, Copy, Debug, Display, Eq, Hash, PartialEq)]
+pub enum AlertRule {
+    IpConflict,
+    RogueRouter,
+}
+
+pub struct Alert {
+    pub rule: AlertRule,
+    pub severity: Severity,
+    pub title: String,
+    pub summary: String,
+}
+
+#[derive(Default)]
+pub struct Notifier {
+    last_sent: HashMap<AlertRule, DateTime<Local>>,
+    // Warnings & critical alerts since the window was focused, shown by the window title
+    unseen: u64,
+}
+
+impl Notifier {
+    pub fn notify(
+        &mut self, settings: &NotificationSettings, alert: Alert, now: DateTime<Local>,
+    ) {
+        if alert.severity >= Severity::Warning {
+            self.unseen = self.unseen.saturating_add(1);
+        }
+        if !self.is_due(settings, &alert, now) {
+            return;
+        }
+
+        log::info!("Notification: {} ({})", alert.rule, alert.severity);
+        deliver(settings.clone(), alert.title, alert.summary, None);
+    }
+
+    pub fn unseen(&self) -> u64 {
+        self.unseen
+    }
+
+    pub fn mark_seen(&mut self) {
+        self.unseen = 0;
+    }
+
+    fn is_due(
+        &mut self, settings: &NotificationSettings, alert: &Alert, now: DateTime<Local>,
+    ) -> bool {
+        if !settings.is_enabled() || alert.severity < settings.severity {
+            return false;
+        }
+
+        let interval = Duration::seconds(i64::from(settings.interval_seconds));
+        if self
+            .last_sent
+            .get(&alert.rule)
+            .is_some_and(|last| now - *last < interval)
+        {
+            return false;
+        }
+        self.last_sent.insert(alert.rule, now);
+
+        true
+    }
+}
+
+/// Shows the desktop notification & plays the sound on the separate thread,
+/// since both are blocking. <br>
+/// If the modals channel is given, the result is reported there, otherwise it's only logged.
+pub fn deliver(
+    settings: NotificationSettings, title: String, summary: String,
+    modals_tx: Option<Sender<Box<dyn Modal>>>,
+) {
+    let spawned = thread::Builder::new()
+        .name("Notification-Thread".to_string())
+        .spawn(move || {
+            let mut errors = vec![];
+            if settings.desktop {
+                if let Err(err) = show_desktop(&title, &summary) {
+                    errors.push(err);
+                }
+            }
+            if settings.sound {
+                if let Err(err) = play_sound(&settings.sound_file) {
+                    errors.push(err);
+                }
+            }
+            for err in &errors {
+                log::error!("Notification: {err}");
+            }
+
+            let Some(modals_tx) = modals_tx else {
+                return;
+            };
+            if errors.is_empty() {
+                MessageModal::info(&t!("Message.Success.NotificationSent"))
+                    .try_send_by(&modals_tx);
+            } else {
+                let text = errors
+                    .iter()
+                    .map(|err| err.localize())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                MessageModal::error(&text).try_send_by(&modals_tx);
+            }
+        });
+    if let Err(err) = spawned {
+        log::error!("Notification: Failed to start the thread. Error: {err}");
+    }
+}
+
+fn show_desktop(title: &str, summary: &str) -> Result<(), NotificationError> {
+    notify_rust::Notification::new()
+        .appname(APP_NAME)
+        .summary(title)
+        .body(summary)
+        .show()?;
+
+    Ok(())
+}
+
+fn play_sound(path: &str) -> Result<(), NotificationError> {
+    // Stream has to live until the end of the playback
+    let (_stream, handle) = rodio::OutputStream::try_default()?;
+    let sink = rodio::Sink::try_new(&handle)?;
+
+    let path = path.trim();
+    if path.is_empty() {
+        sink.append(rodio::Decoder::new(Cursor::new(BUNDLED_SOUND))?);
+    } else {
+        let file = File::open(path)?;
+        sink.append(rodio::Decoder::new(BufReader::new(file))?);
+    }
+    sink.sleep_until_end();
+
+    Ok(())
+}
+
+#[derive(Debug, Error)]
+pub enum NotificationError {
+    #[error("Desktop notification error: {0}")]
+    Desktop(#[from] notify_rust::error::Error),
+
+    #[error("Sound decoding error: {0}")]
+    SoundDecoding(#[from] rodio::decoder::DecoderError),
+
+    #[error("Sound file error: {0}")]
+    SoundFile(#[from] std::io::Error),
+
+    #[error("Sound output error: {0}")]
+    SoundOutput(#[from] rodio::StreamError),
+
+    #[error("Sound playback error: {0}")]
+    SoundPlayback(#[from] rodio::PlayError),
+}
+
+impl NotificationError {
+    pub fn localize(&self) -> String {
+        let prefix = match self {
+            NotificationError::Desktop(_) => t!("Error.Notification.Desktop"),
+            NotificationError::SoundDecoding(_) => t!("Error.Notification.SoundDecoding"),
+            NotificationError::SoundFile(_) => t!("Error.Notification.SoundFile"),
+            NotificationError::SoundOutput(_) | NotificationError::SoundPlayback(_) => {
+                t!("Error.Notification.SoundOutput")
+            },
+        };
+        let details = self.source().map(ToString::to_string).unwrap_or_default();
+
+        format!("{prefix} {details}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> NotificationSettings {
+        NotificationSettings {
+            desktop: true,
+            interval_seconds: 30,
+            severity: Severity::Warning,
+            sound: false,
+            sound_file: String::new(),
+        }
+    }
+
+    fn alert(severity: Severity) -> Alert {
+        Alert {
+            rule: AlertRule::IpConflict,
+            severity,
+            title: String::new(),
+            summary: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let mut notifier = Notifier::default();
+        let settings = settings();
+        let now = Local::now();
+
+        assert!(notifier.is_due(&settings, &alert(Severity::Warning), now));
+        assert!(!notifier.is_due(
+            &settings,
+            &alert(Severity::Critical),
+            now + Duration::seconds(29)
+        ));
+        assert!(notifier.is_due(
+            &settings,
+            &alert(Severity::Warning),
+            now + Duration::seconds(30)
+        ));
+    }
+
+    #[test]
+    fn test_threshold_and_disabled() {
+        let mut notifier = Notifier::default();
+        let mut settings = settings();
+        let now = Local::now();
+
+        assert!(!notifier.is_due(&settings, &alert(Severity::Info), now));
+        // Skipped alert doesn't start the interval
+        assert!(notifier.is_due(&settings, &alert(Severity::Warning), now));
+
+        settings.desktop = false;
+        assert!(!notifier.is_due(
+            &settings,
+            &alert(Severity::Critical),
+            now + Duration::minutes(5)
+        ));
+    }
+
+    #[test]
+    fn test_unseen_alerts() {
+        let mut notifier = Notifier::default();
+        let mut settings = settings();
+        settings.desktop = false;
+        let now = Local::now();
+
+        // Counted even if they aren't delivered, failovers (info) aren't counted
+        for severity in [Severity::Info, Severity::Warning, Severity::Critical] {
+            notifier.notify(&settings, alert(severity), now);
+        }
+        assert_eq!(notifier.unseen(), 2);
+        notifier.mark_seen();
+        assert_eq!(notifier.unseen(), 0);
+    }
+}
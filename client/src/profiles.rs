@@ -1,3 +1,4 @@
+use crate::data_dir::DataDir;
 use crate::errors::FileError;
 use common::io::FileKind;
 use serde::{Deserialize, Serialize};
@@ -13,13 +14,13 @@ pub struct ProfilesStorage {
 }
 
 impl ProfilesStorage {
-    pub fn from_file() -> Result<Self, FileError> {
-        match common::io::get_storage_file_path(PROFILES_FILENAME, PROFILES_FILETYPE) {
+    pub fn from_file(data_dir: &DataDir) -> Result<Self, FileError> {
+        match data_dir.file_path(PROFILES_FILENAME, PROFILES_FILETYPE) {
             Ok(path) => {
                 let data = fs::read_to_string(path);
                 if data.is_err() {
                     let storage = Self::default();
-                    storage.save_to_file()?;
+                    storage.save_to_file(data_dir)?;
                     return Ok(storage);
                 }
 
@@ -31,11 +32,10 @@ impl ProfilesStorage {
         }
     }
 
-    pub fn save_to_file(&self) -> Result<(), FileError> {
+    pub fn save_to_file(&self, data_dir: &DataDir) -> Result<(), FileError> {
         let data = toml::to_string(&self).map_err(FileError::TomlSerialization)?;
 
-        let path =
-            common::io::get_storage_file_path(PROFILES_FILENAME, PROFILES_FILETYPE)?;
+        let path = data_dir.file_path(PROFILES_FILENAME, PROFILES_FILETYPE)?;
         common::io::create_parent_directories(&path)?;
         fs::write(path, data)?;
 
@@ -0,0 +1,316 @@
+use crate::config::{Config, Language};
+use crate::data_dir::DataDir;
+use crate::net::lookup::Lookup;
+use crate::ui::modals::Modal;
+use crate::ui::modals::message::MessageModal;
+use common::io::FileKind;
+use crossbeam::channel::Sender;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::{fmt, fs, io};
+use strum::IntoEnumIterator;
+
+// Written & removed by the check of the directories
+const PROBE_FILENAME: &str = ".xailyser-write-check";
+// Keys of the startup messages & of the report itself
+const REQUIRED_KEYS: [&str; 9] = [
+    "Button.Close",
+    "Language.English",
+    "Language.Ukrainian",
+    "Modal.Title.Error",
+    "Modal.Title.Info",
+    "Modal.Title.Warning",
+    "SelfCheck.Found",
+    "SelfCheck.Passed",
+    "SelfCheck.Title",
+];
+
+/// Problem of the client installation, that is found by the self-check.
+#[derive(Debug, PartialEq)]
+pub enum Problem {
+    ConfigInvalid(String),
+    LocaleNotLoaded(Language),
+    LocaleKeysMissing {
+        language: Language,
+        keys: Vec<&'static str>,
+    },
+    PortsDatabaseEmpty,
+    VendorsDatabaseEmpty,
+    DirectoryNotWritable {
+        path: PathBuf,
+        error: String,
+    },
+}
+
+impl Problem {
+    pub fn localize(&self) -> String {
+        match self {
+            Self::ConfigInvalid(error) => {
+                t!("SelfCheck.Problem.ConfigInvalid", "error" = error)
+            },
+            Self::LocaleNotLoaded(language) => t!(
+                "SelfCheck.Problem.LocaleNotLoaded",
+                "language" = language.localize()
+            ),
+            Self::LocaleKeysMissing { language, keys } => t!(
+                "SelfCheck.Problem.LocaleKeysMissing",
+                "language" = language.localize(),
+                "keys" = keys.join(", ")
+            ),
+            Self::PortsDatabaseEmpty => t!("SelfCheck.Problem.PortsDatabaseEmpty"),
+            Self::VendorsDatabaseEmpty => t!("SelfCheck.Problem.VendorsDatabaseEmpty"),
+            Self::DirectoryNotWritable { path, error } => t!(
+                "SelfCheck.Problem.DirectoryNotWritable",
+                "path" = path.display(),
+                "error" = error
+            ),
+        }
+        .to_string()
+    }
+}
+
+// Used by the log, that isn't localized
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ConfigInvalid(error) => write!(f, "Config is invalid: {error}"),
+            Self::LocaleNotLoaded(language) => {
+                write!(f, "Locale {language} isn't loaded")
+            },
+            Self::LocaleKeysMissing { language, keys } => {
+                write!(f, "Locale {language} misses keys: {}", keys.join(", "))
+            },
+            Self::PortsDatabaseEmpty => write!(f, "Ports database is empty"),
+            Self::VendorsDatabaseEmpty => write!(f, "Vendors database is empty"),
+            Self::DirectoryNotWritable { path, error } => {
+                write!(f, "Directory {} isn't writable: {error}", path.display())
+            },
+        }
+    }
+}
+
+/// Every check is run, so all the problems are reported at once.
+pub fn run(data_dir: &DataDir, lookup: &Lookup) -> Vec<Problem> {
+    let mut problems = Vec::new();
+
+    match Config::file_path(data_dir) {
+        Ok(path) => problems.extend(check_config(&path)),
+        Err(err) => log::warn!("Self-check: Config path isn't resolved. {err}"),
+    }
+
+    let available = rust_i18n::available_locales!();
+    problems.extend(Language::iter().filter_map(|language| {
+        let source = locale_source(&language);
+        check_locale(language, &available, source)
+    }));
+
+    problems.extend(check_lookup(lookup));
+
+    // Platform default keeps config & data in different directories
+    let mut directories: Vec<PathBuf> = [FileKind::Config, FileKind::Data]
+        .into_iter()
+        .filter_map(|kind| data_dir.dir_path(kind).ok())
+        .collect();
+    directories.dedup();
+    problems.extend(
+        directories
+            .iter()
+            .map(PathBuf::as_path)
+            .filter_map(check_writable),
+    );
+
+    problems
+}
+
+/// Problems are written to the log & shown by the single warning.
+/// <br> Passed check is shown only if it's asked by the user.
+pub fn report(
+    problems: &[Problem], modals_tx: &Sender<Box<dyn Modal>>, is_requested: bool,
+) {
+    if problems.is_empty() {
+        log::info!("Self-check: No problems found.");
+        if is_requested {
+            MessageModal::info(&t!("SelfCheck.Passed")).try_send_by(modals_tx);
+        }
+        return;
+    }
+
+    for problem in problems {
+        log::warn!("Self-check: {problem}");
+    }
+    let list = problems
+        .iter()
+        .map(|problem| format!("• {}", problem.localize()))
+        .collect::<Vec<_>>()
+        .join("\n");
+    MessageModal::warning(&format!("{}\n\n{list}", t!("SelfCheck.Found")))
+        .with_title(format!("⚠ {}", t!("SelfCheck.Title")))
+        .with_width(450.0)
+        .try_send_by(modals_tx);
+}
+
+// Missing config is created with the defaults, so it isn't a problem
+fn check_config(path: &Path) -> Option<Problem> {
+    let data = match fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return None,
+        Err(err) => return Some(Problem::ConfigInvalid(err.to_string())),
+    };
+
+    Config::parse(&data).err().map(|err| {
+        let message = match err.additional_info() {
+            Some(additional_info) => format!("{err} {additional_info}"),
+            None => err.to_string(),
+        };
+        Problem::ConfigInvalid(message)
+    })
+}
+
+// Locales are embedded at build, so the sources are the same files
+fn locale_source(language: &Language) -> &'static str {
+    match language {
+        Language::English => include_str!("../locales/English.json"),
+        Language::Ukrainian => include_str!("../locales/Ukrainian.json"),
+    }
+}
+
+// Fallback locale hides the missing keys from t!, so the source is checked
+fn check_locale(language: Language, available: &[&str], source: &str) -> Option<Problem> {
+    if !available.contains(&language.to_string().as_str()) {
+        return Some(Problem::LocaleNotLoaded(language));
+    }
+    let Ok(translations) = serde_json::from_str::<HashMap<String, Value>>(source) else {
+        return Some(Problem::LocaleNotLoaded(language));
+    };
+
+    let keys: Vec<&'static str> = REQUIRED_KEYS
+        .into_iter()
+        .filter(|key| {
+            !translations
+                .get(*key)
+                .and_then(Value::as_str)
+                .is_some_and(|value| !value.trim().is_empty())
+        })
+        .collect();
+    match keys.is_empty() {
+        true => None,
+        false => Some(Problem::LocaleKeysMissing { language, keys }),
+    }
+}
+
+fn check_lookup(lookup: &Lookup) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    if lookup.port_service.is_empty() {
+        problems.push(Problem::PortsDatabaseEmpty);
+    }
+    if lookup.vendors_amount == 0 {
+        problems.push(Problem::VendorsDatabaseEmpty);
+    }
+
+    problems
+}
+
+fn check_writable(directory: &Path) -> Option<Problem> {
+    let probe = directory.join(PROBE_FILENAME);
+    let result = fs::create_dir_all(directory)
+        .and_then(|_| fs::write(&probe, b""))
+        .and_then(|_| fs::remove_file(&probe));
+
+    result.err().map(|err| Problem::DirectoryNotWritable {
+        path: directory.to_path_buf(),
+        error: err.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, process};
+
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("xailyser-self-check-{name}-{}", process::id()))
+    }
+
+    #[test]
+    fn test_config_check() {
+        let path = temp_path("config.toml");
+        assert_eq!(check_config(&path), None);
+
+        let config = toml::to_string(&Config::default()).unwrap();
+        fs::write(&path, &config).unwrap();
+        assert_eq!(check_config(&path), None);
+
+        fs::write(&path, "language = ").unwrap();
+        assert!(matches!(
+            check_config(&path),
+            Some(Problem::ConfigInvalid(_))
+        ));
+
+        let unknown_language = config.replace("\"English\"", "\"Klingon\"");
+        fs::write(&path, unknown_language).unwrap();
+        assert_eq!(
+            check_config(&path),
+            Some(Problem::ConfigInvalid("Unknown language.".to_string()))
+        );
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_locale_check() {
+        let available = ["English", "Ukrainian"];
+        for language in Language::iter() {
+            let source = locale_source(&language);
+            assert_eq!(check_locale(language, &available, source), None);
+        }
+
+        assert_eq!(
+            check_locale(Language::Ukrainian, &["English"], "{}"),
+            Some(Problem::LocaleNotLoaded(Language::Ukrainian))
+        );
+        assert_eq!(
+            check_locale(Language::English, &available, "{ \"Button.Close\": "),
+            Some(Problem::LocaleNotLoaded(Language::English))
+        );
+
+        let broken = locale_source(&Language::English)
+            .replace("\"Button.Close\"", "\"Button.Closed\"")
+            .replace(
+                "\"Modal.Title.Info\": \"Info\"",
+                "\"Modal.Title.Info\": \" \"",
+            );
+        assert_eq!(
+            check_locale(Language::English, &available, &broken),
+            Some(Problem::LocaleKeysMissing {
+                language: Language::English,
+                keys: vec!["Button.Close", "Modal.Title.Info"],
+            })
+        );
+    }
+
+    #[test]
+    fn test_lookup_check() {
+        assert_eq!(
+            check_lookup(&Lookup::empty()),
+            vec![Problem::PortsDatabaseEmpty, Problem::VendorsDatabaseEmpty]
+        );
+    }
+
+    #[test]
+    fn test_writable_check() {
+        let directory = temp_path("dir");
+        assert_eq!(check_writable(&directory), None);
+        assert!(!directory.join(PROBE_FILENAME).exists());
+        let _ = fs::remove_dir_all(&directory);
+
+        // File in place of the directory
+        let file = temp_path("file");
+        fs::write(&file, b"").unwrap();
+        assert!(matches!(
+            check_writable(&file),
+            Some(Problem::DirectoryNotWritable { path, .. }) if path == file
+        ));
+        let _ = fs::remove_file(&file);
+    }
+}
@@ -1,15 +1,31 @@
 use crate::config::Config;
+use crate::data_dir::DataDir;
+use crate::ws::recording;
 use app::App;
+use common::io::FileKind;
+use std::fs;
+use std::path::PathBuf;
 
-pub const MIN_WINDOW_WIDTH: f32 = 950.0;
-pub const MIN_WINDOW_HEIGHT: f32 = 550.0;
-const WINDOW_TITLE: &str = "Xailyser";
+pub const MIN_WINDOW_WIDTH: f32 = 640.0;
+pub const MIN_WINDOW_HEIGHT: f32 = 480.0;
+const DEFAULT_WINDOW_WIDTH: f32 = 950.0;
+const DEFAULT_WINDOW_HEIGHT: f32 = 550.0;
+// Multiplier of the pixels per point, chosen by user
+pub const UI_SCALE_RANGE: std::ops::RangeInclusive<f32> = 0.5..=3.0;
+pub const WINDOW_TITLE: &str = "Xailyser";
+// Window geometry & egui memory, saved by eframe on exit
+const WINDOW_STATE_FILENAME: &str = "window.ron";
+const WINDOW_STATE_FILETYPE: FileKind = FileKind::Config;
+
+pub fn start(config: Config, data_dir: DataDir, mode: recording::Mode) -> eframe::Result {
+    let persistence_path = window_state_path(&config, &data_dir);
+    let is_restored = config.window_persistence
+        && persistence_path.as_ref().is_some_and(|path| path.is_file());
 
-pub fn start(config: Config) -> eframe::Result {
     let native_options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_title(WINDOW_TITLE)
-            .with_inner_size([MIN_WINDOW_WIDTH, MIN_WINDOW_HEIGHT])
+            .with_inner_size([DEFAULT_WINDOW_WIDTH, DEFAULT_WINDOW_HEIGHT])
             .with_min_inner_size([MIN_WINDOW_WIDTH, MIN_WINDOW_HEIGHT])
             .with_icon(
                 eframe::icon_data::from_png_bytes(
@@ -20,26 +36,55 @@ pub fn start(config: Config) -> eframe::Result {
                     std::process::exit(1);
                 }),
             ),
-        centered: true,
+        // Restored position is clamped by eframe into the area of the connected monitors
+        centered: !is_restored,
+        persist_window: config.window_persistence,
+        persistence_path,
         ..Default::default()
     };
 
     eframe::run_native(
         WINDOW_TITLE,
         native_options,
-        Box::new(|cc| Ok(Box::new(App::new(cc, config)))),
+        Box::new(|cc| Ok(Box::new(App::new(cc, config, data_dir, mode)))),
     )
 }
 
+fn window_state_path(config: &Config, data_dir: &DataDir) -> Option<PathBuf> {
+    let path = match data_dir.file_path(WINDOW_STATE_FILENAME, WINDOW_STATE_FILETYPE) {
+        Ok(path) => path,
+        Err(err) => {
+            log::warn!(
+                "Failed to get the window state path, eframe default is used: {err}"
+            );
+            return None;
+        },
+    };
+
+    // Otherwise eframe restores the egui memory from the file of the previous runs
+    if !config.window_persistence && path.is_file() {
+        if let Err(err) = fs::remove_file(&path) {
+            log::warn!("Failed to remove window state {}: {err}", path.display());
+        }
+    }
+
+    Some(path)
+}
+
 mod app;
 
 pub mod components {
     pub mod auth;
     pub mod connection_profiles;
+    pub mod connection_status;
+    pub mod debug_overlay;
     pub mod preauth_client_settings;
+    pub mod record_menu;
     pub mod root;
     pub mod throughput_settings;
 }
+pub mod format;
 pub mod modals;
 pub mod styles;
 pub mod tabs;
+pub mod title;
@@ -1,12 +1,25 @@
 use crate::config::Config;
 use crate::context::Context;
+use crate::data_dir::DataDir;
+use crate::net::anonymize::Anonymizer;
+use crate::self_check;
 use crate::ui::components::auth::AuthComponent;
 use crate::ui::components::root::RootComponent;
 use crate::ui::modals::Modal;
+use crate::ui::modals::message::MessageModal;
+use crate::ui::title::{TitleStats, WindowTitle};
 use crate::ws;
+use crate::ws::recording;
 use crate::ws::request::UiClientRequest;
+use chrono::Local;
 use std::sync::atomic::Ordering;
 use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+// Time of the UI frame, that the received frames are processed for. The rest wait in
+// the channel for the next UI frames, so a burst doesn't stall the rendering.
+// Channel is bounded, the frames over its capacity are dropped by the WS thread.
+const INGEST_BUDGET: Duration = Duration::from_millis(8);
 
 pub struct App {
     context: Context,
@@ -17,21 +30,59 @@ pub struct App {
     net_thread: Option<JoinHandle<()>>,
 
     modals: Vec<Box<dyn Modal>>,
+    window_title: WindowTitle,
 }
 
 impl App {
-    pub fn new(cc: &eframe::CreationContext<'_>, config: Config) -> Self {
-        let ctx = Context::new(config);
+    pub fn new(
+        cc: &eframe::CreationContext<'_>, config: Config, data_dir: DataDir,
+        mode: recording::Mode,
+    ) -> Self {
+        let mut ctx = Context::new(config, data_dir);
         cc.egui_ctx
             .set_style(ctx.config.theme.into_aesthetix_theme().custom_style());
+        cc.egui_ctx.set_zoom_factor(ctx.config.ui_scale);
+
+        let problems = self_check::run(&ctx.data_dir, &ctx.net_storage.lookup);
+        self_check::report(&problems, &ctx.modals_tx, false);
+
+        let mut auth_component = AuthComponent::new(&ctx);
+        match &mode {
+            recording::Mode::Live => {},
+            recording::Mode::Record { path, anonymize } => {
+                let anonymizer =
+                    anonymize.then(|| Anonymizer::new(ctx.client_settings.anonymization));
+                match recording::start_recorder(path, anonymizer) {
+                    Ok(recording_tx) => {
+                        log::info!(
+                            "Recording: Session is recorded to {}",
+                            path.display()
+                        );
+                        ctx.recording_tx = Some(recording_tx);
+                    },
+                    Err(err) => {
+                        log::error!(
+                            "Recording: Failed to create {}. Error: {err}",
+                            path.display()
+                        );
+                        MessageModal::error(&recording::localize_error(&err))
+                            .try_send_by(&ctx.modals_tx);
+                    },
+                }
+            },
+            recording::Mode::Replay { path, speed } => {
+                auth_component.replay(&mut ctx, path, *speed);
+            },
+        }
 
         Self {
             net_thread: None,
 
-            auth_component: AuthComponent::new(&ctx),
+            auth_component,
             root_component: RootComponent::new(&ctx),
 
             modals: vec![],
+            window_title: WindowTitle::default(),
 
             context: ctx,
         }
@@ -39,6 +90,11 @@ impl App {
 }
 
 impl eframe::App for App {
+    // Collapsed sections, scroll positions, etc. are kept, if the window state is restored
+    fn persist_egui_memory(&self) -> bool {
+        self.context.config.window_persistence
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         egui::CentralPanel::default().show(ctx, |ui| {
             // If not authenticated, showing `auth` window.
@@ -64,20 +120,27 @@ impl eframe::App for App {
                     &self.context.client_settings,
                     &self.context.ui_client_requests_tx,
                 );
+                self.context.connection.apply_heartbeat(
+                    &self.context.heartbeat,
+                    &self.context.client_settings,
+                );
 
                 // Showing the root component.
                 self.root_component.show(ui, &mut self.context);
 
                 // Logout from root component, if requested.
                 if self.root_component.logout_requested() {
+                    let is_connected = self.context.connection.is_connected();
+                    self.context.end_session(is_connected);
                     self.root_component.logout(&self.context);
-                    self.auth_component.logout(&self.context);
+                    self.auth_component
+                        .logout(&self.context, self.net_thread.take());
                     self.context.logout();
                 }
             }
 
             // Getting modals from the channels (in context).
-            if let Ok(modal) = self.context.modals_rx.try_recv() {
+            while let Ok(modal) = self.context.modals_rx.try_recv() {
                 self.modals.push(modal);
             }
 
@@ -86,12 +149,14 @@ impl eframe::App for App {
         });
 
         // Processing all responses
-        while let Ok(response) = self.context.data_response_rx.try_recv() {
-            ws::response::data(&mut self.context, response);
+        while let Ok(update) = self.context.connection_rx.try_recv() {
+            ws::state::process(&mut self.context, update);
         }
         while let Ok(response) = self.context.server_response_rx.try_recv() {
             ws::response::process(&mut self.context, response);
         }
+        self.ingest_data();
+        self.update_window_title(ctx);
         ctx.request_repaint();
     }
 
@@ -105,6 +170,8 @@ impl eframe::App for App {
         }
         log::info!("Shutdown started...");
         self.context.shutdown_flag.store(true, Ordering::Release);
+        // Recorder finishes the file, when every sender is dropped
+        self.context.recording_tx = None;
 
         if let Some(handle) = self.net_thread.take() {
             if handle.join().is_err() {
@@ -116,19 +183,54 @@ impl eframe::App for App {
 }
 
 impl App {
-    fn show_opened_modals(&mut self, ui: &egui::Ui) {
-        let mut closed_modals: Vec<usize> = vec![];
+    // Nothing is skipped: the frames beyond the budget are processed by the next UI frames
+    fn ingest_data(&mut self) {
+        let started = Instant::now();
+        let mut ingested: usize = 0;
+        while started.elapsed() < INGEST_BUDGET {
+            let Ok(response) = self.context.data_response_rx.try_recv() else {
+                break;
+            };
+            ws::response::data(&mut self.context, response);
+            ingested = ingested.saturating_add(1);
+        }
 
-        for (index, modal) in self.modals.iter_mut().enumerate() {
-            modal.show(ui, &mut self.context);
+        if self.context.client_settings.debug_overlay {
+            self.root_component.record_ingest(
+                started.elapsed(),
+                ingested,
+                self.context.data_response_rx.len(),
+            );
+        }
+    }
 
-            if modal.is_closed() {
-                closed_modals.push(index);
-            }
+    fn update_window_title(&mut self, ctx: &egui::Context) {
+        let settings = self.context.client_settings.window_title;
+        // Alerts are seen, when the window is focused
+        if settings.enabled && ctx.input(|input| input.viewport().focused == Some(true)) {
+            self.context.notifier.mark_seen();
         }
 
-        closed_modals.iter().for_each(|index| {
-            self.modals.remove(*index);
+        let context = &self.context;
+        let title = self.window_title.next(&settings, Instant::now(), || {
+            let (received, sent) = context.net_storage.speed.last_second(Local::now());
+            TitleStats {
+                received,
+                sent,
+                devices: context.net_storage.devices.list().len(),
+                alerts: context.notifier.unseen(),
+            }
         });
+        if let Some(title) = title {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title));
+        }
+    }
+
+    fn show_opened_modals(&mut self, ui: &egui::Ui) {
+        for modal in self.modals.iter_mut() {
+            modal.show(ui, &mut self.context);
+        }
+
+        self.modals.retain(|modal| !modal.is_closed());
     }
 }
@@ -1,12 +1,25 @@
-use crate::context::Context;
+use crate::context::{Context, Session};
+use crate::net::heartbeat::Heartbeat;
 use crate::profiles::Profile;
 use crate::ui::components::connection_profiles::ConnectionProfilesComponent;
+use crate::ui::components::connection_status;
 use crate::ui::components::preauth_client_settings::PreAuthClientSettingsComponent;
 use crate::ui::modals::message::MessageModal;
+use crate::ui::styles;
+use crate::ui::styles::colors::Role;
 use crate::ws;
-use crate::ws::WsHandler;
+use crate::ws::address::{AddressError, ServerAddress};
+use crate::ws::link::LinkInfo;
+use crate::ws::recording::ReplayHandler;
+use crate::ws::state::{ConnectionState, ConnectionUpdate};
+use crate::ws::{Credentials, Keepalive, WsHandler, address, recording, state};
+use chrono::Local;
+use common::recording::Player;
+use common::suppression::FailureStreak;
+use crossbeam::channel::{Receiver, TryRecvError};
 use egui::{Grid, RichText, TextEdit};
-use std::net::{IpAddr, SocketAddr};
+use std::net::SocketAddr;
+use std::path::Path;
 use std::sync::Arc;
 use std::thread;
 use std::thread::JoinHandle;
@@ -22,6 +35,11 @@ pub struct AuthComponent {
     // Internal fields
     authenticated: bool,
     auth_fields: AuthFields,
+    // Hostname, that is resolved by the worker thread
+    resolving: Option<(ServerAddress, Receiver<Result<SocketAddr, AddressError>>)>,
+    resolve_error: Option<AddressError>,
+    // Remembered in the recent addresses, when the connection is established
+    connecting_to: Option<ServerAddress>,
 }
 
 impl AuthComponent {
@@ -34,6 +52,9 @@ impl AuthComponent {
 
             authenticated: false,
             auth_fields: AuthFields::default(),
+            resolving: None,
+            resolve_error: None,
+            connecting_to: None,
         }
     }
 
@@ -41,7 +62,33 @@ impl AuthComponent {
         self.authenticated
     }
 
+    // Thread of the previous connection hasn't exited yet (e.g. right after logout)
+    fn is_closing(&self) -> bool {
+        self.net_thread
+            .as_ref()
+            .is_some_and(|handle| !handle.is_finished())
+    }
+
+    // Only one attempt at a time, the storage of the context is shared by the whole UI
+    fn is_busy(&self, ctx: &Context) -> bool {
+        ctx.connection.is_pending() || self.resolving.is_some() || self.is_closing()
+    }
+
     pub fn show(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        // WS thread reports the connection, so the root component can be shown
+        if ctx.connection.is_connected() {
+            // Token is used on reconnect, so the password is not needed anymore
+            if ctx.session.is_some() {
+                self.auth_fields.password.clear();
+            }
+            if let Some(address) = self.connecting_to.take() {
+                Self::remember_address(ctx, &address);
+            }
+            self.authenticated = true;
+            return;
+        }
+        self.poll_resolution(ctx);
+
         // Show settings if opened instead of auth window
         if self.pre_auth_settings_component.is_opened() {
             self.pre_auth_settings_component.show(ui, ctx);
@@ -55,6 +102,8 @@ impl AuthComponent {
         }
 
         let window_height = ui.available_size().y;
+        let address = ServerAddress::parse(&self.auth_fields.ip, &self.auth_fields.port);
+        let mut is_connect_requested = false;
 
         ui.columns(3, |columns| {
             const MAIN_COLUMN: usize = 1;
@@ -74,46 +123,91 @@ impl AuthComponent {
                     .striped(false)
                     .spacing([20.0, 20.0])
                     .show(ui, |ui| {
+                        if !ctx.config.recent_addresses.is_empty() {
+                            ui.label(format!("{}:", t!("Component.Auth.Recent")));
+                            self.recent_view(ui, ctx);
+                            ui.end_row();
+                        }
+
                         ui.label(format!("{}:", t!("Component.Auth.IP")));
-                        ui.add(
+                        let host_field = ui.add(
                             TextEdit::singleline(&mut self.auth_fields.ip)
+                                .hint_text(t!("Component.Auth.Hint.Host"))
                                 .desired_width(f32::INFINITY),
                         );
                         ui.end_row();
 
                         ui.label(format!("{}:", t!("Component.Auth.Port")));
-                        ui.add(
+                        let port_field = ui.add(
                             TextEdit::singleline(&mut self.auth_fields.port)
                                 .desired_width(f32::INFINITY),
                         );
                         ui.end_row();
 
+                        // Resolution error belongs to the address, that was entered
+                        if host_field.changed() || port_field.changed() {
+                            self.resolve_error = None;
+                        }
+
                         ui.label(format!("{}:", t!("Component.Auth.Password")));
-                        ui.add(
+                        let mut password_field =
                             TextEdit::singleline(&mut self.auth_fields.password)
                                 .password(true)
-                                .desired_width(f32::INFINITY),
-                        );
+                                .desired_width(f32::INFINITY);
+                        if ctx.session.is_some() {
+                            password_field = password_field
+                                .hint_text(t!("Component.Auth.Hint.SessionToken"));
+                        }
+                        let password_field = ui.add(password_field);
+                        if password_field.lost_focus()
+                            && ui.input(|input| input.key_pressed(egui::Key::Enter))
+                        {
+                            is_connect_requested = true;
+                        }
                         ui.end_row();
                     });
 
+                // Shown before any network attempt, empty fields aren't pointed at
+                let error = match &address {
+                    Err(err) if !err.is_empty_field() => Some(err),
+                    _ => self.resolve_error.as_ref(),
+                };
+                if let Some(err) = error {
+                    ui.add_space(styles::space::SMALL);
+                    ui.label(
+                        RichText::new(err.localize())
+                            .color(styles::colors::get(Role::FieldInvalid)),
+                    );
+                }
+
                 ui.add_space(window_height / 6.0);
 
                 ui.vertical_centered_justified(|ui| {
-                    if ui.button(t!("Component.Auth.Connect")).clicked() {
-                        match self.auth_fields.get_address() {
-                            Ok(address) => {
-                                self.try_connect(
-                                    ctx,
-                                    address,
-                                    &self.auth_fields.password.clone(),
-                                );
-                            },
-                            Err(err) => {
-                                let modal = MessageModal::error(&err.localize());
-                                let _ = ctx.modals_tx.try_send(Box::new(modal));
-                            },
-                        }
+                    let is_busy = self.is_busy(ctx);
+                    let text = match (is_busy, self.is_closing()) {
+                        (true, true) => t!("Component.Auth.Closing"),
+                        (true, false) => t!("Component.Auth.Connecting"),
+                        (false, _) => t!("Component.Auth.Connect"),
+                    };
+                    let is_enabled = !is_busy && address.is_ok();
+                    if ui
+                        .add_enabled(is_enabled, egui::Button::new(text))
+                        .clicked()
+                    {
+                        is_connect_requested = true;
+                    }
+
+                    ui.add_space(styles::space::SMALL);
+                    if let Some((address, _)) = &self.resolving {
+                        ui.horizontal(|ui| {
+                            ui.add(egui::Spinner::new());
+                            ui.label(t!(
+                                "Component.Auth.Resolving",
+                                "host" = address.host
+                            ));
+                        });
+                    } else {
+                        connection_status::show(ui, ctx);
                     }
                 });
             });
@@ -138,48 +232,281 @@ impl AuthComponent {
                 },
             );
         });
+
+        // Enter in the password field is checked here too, so the invalid fields
+        // aren't sent either way
+        if is_connect_requested && !self.is_busy(ctx) {
+            if let Ok(address) = address {
+                self.connect(ctx, address);
+            }
+        }
+    }
+
+    fn recent_view(&mut self, ui: &mut egui::Ui, ctx: &Context) {
+        egui::ComboBox::from_id_salt("Combobox.Auth.Recent")
+            .selected_text(t!("Component.Auth.Hint.Recent"))
+            .width(ui.available_width())
+            .show_ui(ui, |ui| {
+                for entry in &ctx.config.recent_addresses {
+                    if ui.selectable_label(false, entry).clicked() {
+                        if let Some((ip, port)) = address::split_recent(entry) {
+                            self.auth_fields.ip = ip;
+                            self.auth_fields.port = port;
+                            self.resolve_error = None;
+                        }
+                    }
+                }
+            });
     }
 
-    fn try_connect(&mut self, ctx: &Context, address: SocketAddr, password: &str) {
-        match ws::connect(address, password, ctx.client_settings.compression) {
-            Ok(stream) => {
+    // Hostname is resolved on the worker thread, so the UI isn't blocked
+    fn connect(&mut self, ctx: &mut Context, address: ServerAddress) {
+        self.resolve_error = None;
+        if let Some(socket) = address.socket() {
+            self.connect_to(ctx, address, socket);
+            return;
+        }
+
+        let (result_tx, result_rx) = crossbeam::channel::bounded(1);
+        let worker_address = address.clone();
+        let spawned = thread::Builder::new()
+            .name("Resolve-Thread".to_string())
+            .spawn(move || {
+                let _ = result_tx.send(worker_address.resolve());
+            });
+        match spawned {
+            Ok(_) => self.resolving = Some((address, result_rx)),
+            Err(err) => {
+                log::error!("Failed to spawn resolve thread: {err}");
+                self.resolve_error =
+                    Some(AddressError::Unresolved(address.host.to_string()));
+            },
+        }
+    }
+
+    fn poll_resolution(&mut self, ctx: &mut Context) {
+        let Some((address, result_rx)) = &self.resolving else {
+            return;
+        };
+        let result = match result_rx.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {
+                Err(AddressError::Unresolved(address.host.to_string()))
+            },
+        };
+        let Some((address, _)) = self.resolving.take() else {
+            return;
+        };
+
+        match result {
+            Ok(socket) => {
+                log::info!("Resolved {address} as {socket}");
+                self.connect_to(ctx, address, socket);
+            },
+            Err(err) => self.resolve_error = Some(err),
+        }
+    }
+
+    fn connect_to(
+        &mut self, ctx: &mut Context, address: ServerAddress, socket: SocketAddr,
+    ) {
+        let credentials = self.auth_fields.credentials(ctx, socket);
+        self.connecting_to = Some(address);
+        self.try_connect(ctx, socket, credentials);
+    }
+
+    fn remember_address(ctx: &mut Context, address: &ServerAddress) {
+        address::remember(&mut ctx.config.recent_addresses, address);
+        if let Err(err) = ctx.config.save_to_file(&ctx.data_dir) {
+            log::error!("Auth: Failed to save the recent addresses: {err}");
+        }
+    }
+
+    fn try_connect(
+        &mut self, ctx: &mut Context, address: SocketAddr, credentials: Credentials,
+    ) {
+        if !self.release_thread(ctx) {
+            return;
+        }
+        ctx.connection = ConnectionState::Connecting(address);
+
+        let compression = ctx.client_settings.compression;
+        let heartbeat = Heartbeat::interval(&ctx.client_settings);
+        let shutdown_flag = Arc::clone(&ctx.shutdown_flag);
+        let connection_tx = ctx.connection_tx.clone();
+        let data_response_tx = ctx.data_response_tx.clone();
+        let server_response_tx = ctx.server_response_tx.clone();
+        let ui_client_requests_rx = ctx.ui_client_requests_rx.clone();
+        let recording_tx = ctx.recording_tx.clone();
+
+        let handle = thread::Builder::new()
+            .name("WS-Thread".to_string())
+            .spawn(move || {
+                let result = ws::connect(
+                    address,
+                    &credentials,
+                    compression,
+                    heartbeat,
+                    &connection_tx,
+                );
+                let stream = match result {
+                    Ok((stream, session_token)) => {
+                        // Token is used on reconnect, so the password is not needed anymore
+                        if let Some(token) = session_token {
+                            let session = Session { address, token };
+                            let update = ConnectionUpdate::SessionIssued(session);
+                            state::try_send(&connection_tx, update);
+                        }
+                        stream
+                    },
+                    Err(err) => {
+                        // Token is invalid or expired, so the password is required again
+                        if let Credentials::SessionToken(_) = credentials {
+                            state::try_send(
+                                &connection_tx,
+                                ConnectionUpdate::SessionRejected,
+                            );
+                        }
+
+                        let reason = match err.additional_info_localized() {
+                            None => format!("{}.", err.localized()),
+                            Some(info) => format!("{}.\n{}", err.localized(), info),
+                        };
+                        let state = ConnectionState::Disconnected(Some(reason));
+                        state::try_send(&connection_tx, ConnectionUpdate::State(state));
+                        return;
+                    },
+                };
+
+                let link = LinkInfo {
+                    address,
+                    compression,
+                    heartbeat,
+                    is_session_resumed: matches!(
+                        credentials,
+                        Credentials::SessionToken(_)
+                    ),
+                    counters: Arc::clone(stream.get_ref().counters()),
+                };
+                state::try_send(&connection_tx, ConnectionUpdate::Link(link));
+                let state = ConnectionState::Connected {
+                    address,
+                    since: Local::now(),
+                };
+                state::try_send(&connection_tx, ConnectionUpdate::State(state));
+
                 let mut ws_handler = WsHandler {
-                    compression: ctx.client_settings.compression,
-                    shutdown_flag: Arc::clone(&ctx.shutdown_flag),
+                    compression,
+                    shutdown_flag,
                     stream,
-                    data_response_tx: ctx.data_response_tx.clone(),
-                    server_response_tx: ctx.server_response_tx.clone(),
-                    ui_client_requests_rx: ctx.ui_client_requests_rx.clone(),
+                    connection_tx: connection_tx.clone(),
+                    data_response_tx,
+                    server_response_tx,
+                    ui_client_requests_rx,
+                    recording_tx,
+                    keepalive: Keepalive::new(heartbeat),
+                    dropped_messages: FailureStreak::default(),
+                    overflowed_frames: FailureStreak::default(),
                 };
+                let reason = ws_handler
+                    .send_receive_messages()
+                    .err()
+                    .map(|err| ws::disconnect_reason(&err));
+                let state = ConnectionState::Disconnected(reason);
+                state::try_send(&connection_tx, ConnectionUpdate::State(state));
+            })
+            .unwrap_or_else(|err| {
+                log::error!("Failed to spawn WS thread: {err}");
+                std::process::exit(1);
+            });
 
-                let handle = thread::Builder::new()
-                    .name("WS-Thread".to_string())
-                    .spawn(move || {
-                        ws_handler.send_receive_messages();
-                    })
-                    .unwrap_or_else(|err| {
-                        log::error!("Failed to spawn WS thread: {err}");
-                        std::process::exit(1);
-                    });
+        self.net_thread = Some(handle);
+    }
 
-                self.net_thread = Some(handle);
-                self.authenticated = true;
-            },
-            Err(err) => {
-                let message = match err.additional_info_localized() {
-                    None => format!("{}.", err.localized()),
-                    Some(info) => format!("{}.\n{}", err.localized(), info),
-                };
+    // Second connection (e.g. to another server) isn't supported: it would be mixed
+    // into the same storage. Thread of the failed attempt is joined before the new one.
+    fn release_thread(&mut self, ctx: &Context) -> bool {
+        if !ctx.connection.is_connect_allowed() {
+            let message = t!(
+                "Component.Auth.Error.AlreadyConnected",
+                "state" = ctx.connection.localized()
+            );
+            MessageModal::error(&message).try_send_by(&ctx.modals_tx);
+            return false;
+        }
+        if self.is_closing() {
+            log::warn!(
+                "Auth: Previous net thread is still running, connection is skipped"
+            );
+            return false;
+        }
+        if let Some(handle) = self.net_thread.take() {
+            if handle.join().is_err() {
+                log::error!("Failed to join previous net-thread handle.");
+            }
+        }
+
+        true
+    }
 
-                let modal = MessageModal::error(&message);
-                let _ = ctx.modals_tx.try_send(Box::new(modal));
+    // Instead of connecting, responses are read from the session recording
+    pub fn replay(&mut self, ctx: &mut Context, path: &Path, speed: f64) {
+        let player = match Player::open(path) {
+            Ok(player) => player,
+            Err(err) => {
+                log::error!("Replay: Failed to open {}. Error: {err}", path.display());
+                MessageModal::error(&recording::localize_error(&err))
+                    .try_send_by(&ctx.modals_tx);
+                return;
             },
+        };
+        if !self.release_thread(ctx) {
+            return;
         }
+        log::info!("Replay: Started {} with speed x{speed}", path.display());
+        self.connecting_to = None;
+
+        ctx.connection = ConnectionState::Replaying {
+            file: path.display().to_string(),
+            speed,
+        };
+
+        let connection_tx = ctx.connection_tx.clone();
+        let replay_handler = ReplayHandler {
+            player,
+            speed,
+            shutdown_flag: Arc::clone(&ctx.shutdown_flag),
+            data_response_tx: ctx.data_response_tx.clone(),
+            server_response_tx: ctx.server_response_tx.clone(),
+            ui_client_requests_rx: ctx.ui_client_requests_rx.clone(),
+        };
+
+        let handle = thread::Builder::new()
+            .name("Replay-Thread".to_string())
+            .spawn(move || {
+                let reason = match replay_handler.run() {
+                    Ok(_) => t!("Connection.Reason.ReplayFinished").to_string(),
+                    Err(err) => {
+                        log::error!("Replay: {err}");
+                        recording::localize_error(&err)
+                    },
+                };
+                let state = ConnectionState::Disconnected(Some(reason));
+                state::try_send(&connection_tx, ConnectionUpdate::State(state));
+            })
+            .unwrap_or_else(|err| {
+                log::error!("Failed to spawn replay thread: {err}");
+                std::process::exit(1);
+            });
+
+        self.net_thread = Some(handle);
     }
 
-    pub fn logout(&mut self, ctx: &Context) {
+    // Thread of the closed connection is kept, so the next one waits for it to exit
+    pub fn logout(&mut self, ctx: &Context, net_thread: Option<JoinHandle<()>>) {
         self.authenticated = false;
-        self.net_thread = None;
+        self.net_thread = net_thread;
         self.pre_auth_settings_component.update_tab(ctx);
     }
 }
@@ -192,19 +519,16 @@ pub struct AuthFields {
 }
 
 impl AuthFields {
-    fn get_address(&self) -> Result<SocketAddr, AuthFieldError> {
-        let ip_address: IpAddr = self
-            .ip
-            .trim()
-            .parse()
-            .map_err(|_| AuthFieldError::WrongIpAddress)?;
-        let port: u16 = self
-            .port
-            .trim()
-            .parse()
-            .map_err(|_| AuthFieldError::WrongPort)?;
-
-        Ok(SocketAddr::new(ip_address, port))
+    // Session token is used if the password is not entered
+    fn credentials(&self, ctx: &Context, address: SocketAddr) -> Credentials {
+        match &ctx.session {
+            Some(session)
+                if session.address == address && self.password.trim().is_empty() =>
+            {
+                Credentials::SessionToken(session.token.clone())
+            },
+            _ => Credentials::Password(self.password.clone()),
+        }
     }
 
     pub fn into_profile(self, title: &str) -> Result<Profile, AuthFieldError> {
@@ -227,7 +227,7 @@ impl ConnectionProfilesComponent {
     }
 
     fn save_profiles(&mut self, ctx: &mut Context) {
-        let modal = if let Err(err) = ctx.profiles_storage.save_to_file() {
+        let modal = if let Err(err) = ctx.profiles_storage.save_to_file(&ctx.data_dir) {
             let mut text = format!(
                 "{}\n{}: {}.",
                 t!("Component.ConnectionProfiles.Modal.ErrorSave"),
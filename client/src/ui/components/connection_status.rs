@@ -0,0 +1,33 @@
+use crate::context::Context;
+use crate::ui::modals::connection::ConnectionModal;
+use crate::ui::styles;
+use crate::ui::styles::colors::Role;
+use crate::ws::state::ConnectionState;
+use egui::{Label, RichText, Sense};
+
+// Colored dot with the connection state. Click opens the details of the connection
+pub fn show(ui: &mut egui::Ui, ctx: &Context) {
+    let color = match &ctx.connection {
+        ConnectionState::Connecting(_) | ConnectionState::Authenticating(_) => {
+            styles::colors::get(Role::Connecting)
+        },
+        ConnectionState::Connected { .. } => styles::colors::get(Role::Connected),
+        ConnectionState::Replaying { .. } => styles::colors::get(Role::Replaying),
+        ConnectionState::Degraded { .. } => styles::colors::get(Role::Degraded),
+        ConnectionState::Disconnected(None) => styles::colors::get(Role::Disconnected),
+        ConnectionState::Disconnected(Some(_)) => {
+            styles::colors::get(Role::ConnectionError)
+        },
+    };
+
+    ui.horizontal_wrapped(|ui| {
+        ui.label(RichText::new("⏺").color(color));
+        let text = RichText::new(ctx.connection.localized()).size(styles::text::SMALL);
+        let response = ui
+            .add(Label::new(text).sense(Sense::click()))
+            .on_hover_text(t!("Connection.Hover.Details"));
+        if response.clicked() {
+            ConnectionModal::open(ctx);
+        }
+    });
+}
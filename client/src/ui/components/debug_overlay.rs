@@ -0,0 +1,93 @@
+use crate::ui::styles;
+use crate::ui::tabs::Tab;
+use egui::{Align2, Area, Frame, RichText};
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Duration;
+
+// Frames, that the FPS is averaged over
+const FRAMES_WINDOW: usize = 60;
+// Weight of the latest render time in the moving average
+const RENDER_TIME_WEIGHT: f64 = 0.1;
+
+/// FPS, render time of the tabs & processing time of the received frames,
+/// to verify the rendering performance.
+#[derive(Default)]
+pub struct DebugOverlay {
+    frame_times: VecDeque<f32>,
+    // Moving average in milliseconds
+    render_times: BTreeMap<Tab, f64>,
+    // Milliseconds of the last frames, so the spikes are seen
+    ingest_times: VecDeque<f64>,
+    // Frames processed by the last UI frame & left in the channel after it
+    ingested: usize,
+    backlog: usize,
+}
+
+impl DebugOverlay {
+    pub fn record_frame(&mut self, ctx: &egui::Context) {
+        if self.frame_times.len() >= FRAMES_WINDOW {
+            self.frame_times.pop_front();
+        }
+        self.frame_times
+            .push_back(ctx.input(|input| input.unstable_dt));
+    }
+
+    pub fn record_tab(&mut self, tab: Tab, elapsed: Duration) {
+        let milliseconds = elapsed.as_secs_f64() * 1000.0;
+        self.render_times
+            .entry(tab)
+            .and_modify(|average| {
+                *average += (milliseconds - *average) * RENDER_TIME_WEIGHT;
+            })
+            .or_insert(milliseconds);
+    }
+
+    pub fn record_ingest(&mut self, elapsed: Duration, ingested: usize, backlog: usize) {
+        if self.ingest_times.len() >= FRAMES_WINDOW {
+            self.ingest_times.pop_front();
+        }
+        self.ingest_times.push_back(elapsed.as_secs_f64() * 1000.0);
+        self.ingested = ingested;
+        self.backlog = backlog;
+    }
+
+    pub fn fps(&self) -> f32 {
+        let total = self.frame_times.iter().sum::<f32>();
+        if total <= 0.0 {
+            return 0.0;
+        }
+        self.frame_times.len() as f32 / total
+    }
+
+    pub fn show(&self, ctx: &egui::Context) {
+        Area::new(egui::Id::new("Debug.Overlay"))
+            .anchor(Align2::RIGHT_TOP, [-10.0, 10.0])
+            .interactable(false)
+            .show(ctx, |ui| {
+                Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(
+                        RichText::new(format!("FPS: {:.0}", self.fps()))
+                            .size(styles::text::SMALL)
+                            .monospace(),
+                    );
+                    let ingest_peak =
+                        self.ingest_times.iter().copied().fold(0.0, f64::max);
+                    ui.label(
+                        RichText::new(format!(
+                            "Ingest: {ingest_peak:.2} ms peak, {} frames, {} queued",
+                            self.ingested, self.backlog
+                        ))
+                        .size(styles::text::SMALL)
+                        .monospace(),
+                    );
+                    for (tab, average) in &self.render_times {
+                        ui.label(
+                            RichText::new(format!("{tab}: {average:.2} ms"))
+                                .size(styles::text::SMALL)
+                                .monospace(),
+                        );
+                    }
+                });
+            });
+    }
+}
@@ -0,0 +1,290 @@
+use crate::net::device::{DeviceAliases, DeviceTag};
+use crate::net::filter::FilterField;
+use crate::ui::format;
+use crate::ui::styles;
+use crate::ws::data::Locator;
+use dpi::protocols::ethernet::mac::MacAddress;
+use egui::{Label, Response, Sense, WidgetText};
+use serde::Serialize;
+use std::net::{IpAddr, SocketAddr};
+
+/// Chosen in the context menu, applied by the inspector after the table is drawn.
+pub enum RecordAction {
+    FilterBy(FilterField, String),
+}
+
+/// Cells of the record row with the context menu: copying & filtering by the value.
+/// <br> Menus are attached after the whole row is drawn, so "Copy row" gets every cell.
+#[derive(Default)]
+pub struct RowMenu {
+    cells: Vec<Cell>,
+}
+
+struct Cell {
+    response: Response,
+    text: String,
+    // Field & the raw value (e.g. MAC instead of its alias)
+    filter: Option<(FilterField, String)>,
+}
+
+impl RowMenu {
+    pub fn label(&mut self, ui: &mut egui::Ui, text: impl Into<WidgetText>) {
+        self.add(ui, text.into(), None);
+    }
+
+    pub fn hover_label(
+        &mut self, ui: &mut egui::Ui, text: impl Into<WidgetText>,
+        hover: impl Into<WidgetText>,
+    ) {
+        self.add_hover(ui, text.into(), hover.into(), None);
+    }
+
+    /// Cell, the records can be filtered by.
+    pub fn filter_label(
+        &mut self, ui: &mut egui::Ui, text: impl Into<WidgetText>, field: FilterField,
+        value: impl ToString,
+    ) {
+        self.add(ui, text.into(), Some((field, value.to_string())));
+    }
+
+    /// Cell with the hint, the records can be filtered by.
+    pub fn hover_filter_label(
+        &mut self, ui: &mut egui::Ui, text: impl Into<WidgetText>,
+        hover: impl Into<WidgetText>, field: FilterField, value: impl ToString,
+    ) {
+        self.add_hover(
+            ui,
+            text.into(),
+            hover.into(),
+            Some((field, value.to_string())),
+        );
+    }
+
+    /// Domain name in the chosen form, the other one is in the hint.
+    /// <br> Filtered by the name as it's on the wire, the filters match both forms.
+    pub fn domain_label<T: Into<WidgetText>>(
+        &mut self, ui: &mut egui::Ui, name: &str, style: impl FnOnce(String) -> T,
+        field: Option<FilterField>,
+    ) {
+        let (text, hover) = format::domain(name);
+        let text = style(text).into();
+        let filter = field.map(|field| (field, name.to_string()));
+        match hover {
+            Some(hover) => self.add_hover(ui, text, hover.into(), filter),
+            None => self.add(ui, text, filter),
+        }
+    }
+
+    /// Alias or MAC of the device with its tag, filtered by the MAC.
+    pub fn address_label(
+        &mut self, ui: &mut egui::Ui, text: String, tag: Option<&DeviceTag>,
+        mac: &MacAddress,
+    ) {
+        let response = ui
+            .horizontal(|ui| {
+                let response = ui.add(Label::new(text.as_str()).sense(Sense::click()));
+                if let Some(tag) = tag {
+                    styles::devices::tag_chip(ui, tag);
+                }
+                response
+            })
+            .inner;
+        self.cells.push(Cell {
+            response,
+            text,
+            filter: Some((FilterField::Mac, mac.to_string())),
+        });
+    }
+
+    /// Sender & target IP cells, "-" if the frame has no IP layer.
+    /// <br> Tunneled packets are shown as "outer → inner", filtered by the inner address.
+    pub fn ip_labels(&mut self, ui: &mut egui::Ui, locator: &Locator) {
+        match (locator.ip(), &locator.tunnel) {
+            (Some((source, target)), Some(tunnel)) => {
+                let hover = t!(
+                    "Tab.Inspector.Hover.Tunnel",
+                    "encapsulation" = tunnel.encapsulation.to_string()
+                )
+                .to_string();
+                let (source_text, target_text) = locator.ip_to_string();
+                self.hover_filter_label(
+                    ui,
+                    source_text,
+                    hover.as_str(),
+                    FilterField::SourceIp,
+                    source,
+                );
+                self.hover_filter_label(
+                    ui,
+                    target_text,
+                    hover.as_str(),
+                    FilterField::DestinationIp,
+                    target,
+                );
+            },
+            (Some((source, target)), None) => {
+                self.filter_label(ui, source.to_string(), FilterField::SourceIp, source);
+                self.filter_label(
+                    ui,
+                    target.to_string(),
+                    FilterField::DestinationIp,
+                    target,
+                );
+            },
+            (None, _) => {
+                self.label(ui, "-");
+                self.label(ui, "-");
+            },
+        }
+    }
+
+    /// Sender & target MAC cells, named by the aliases.
+    pub fn mac_labels(
+        &mut self, ui: &mut egui::Ui, locator: &Locator, aliases: &DeviceAliases,
+    ) {
+        let (source, target) = locator.mac_to_string(aliases);
+        self.filter_label(ui, source, FilterField::Mac, &locator.mac.0);
+        self.filter_label(ui, target, FilterField::Mac, &locator.mac.1);
+    }
+
+    /// Attaches the menus, the record is serialized only when it's copied.
+    /// Filters aren't offered for the fields, the protocol can't be filtered by.
+    pub fn show_record<T: Serialize>(
+        self, record: &T, filters: &[FilterField], action: &mut Option<RecordAction>,
+    ) {
+        let json = || serde_json::to_string_pretty(record).ok();
+        self.attach(Some(&json), filters, action);
+    }
+
+    /// Attaches the menus to the rows, that aren't the records (e.g. the aggregates).
+    pub fn show(self, filters: &[FilterField], action: &mut Option<RecordAction>) {
+        self.attach(None, filters, action);
+    }
+
+    fn add(
+        &mut self, ui: &mut egui::Ui, text: WidgetText,
+        filter: Option<(FilterField, String)>,
+    ) {
+        let plain = text.text().to_string();
+        let response = ui.add(Label::new(text).sense(Sense::click()));
+        self.cells.push(Cell {
+            response,
+            text: plain,
+            filter,
+        });
+    }
+
+    fn add_hover(
+        &mut self, ui: &mut egui::Ui, text: WidgetText, hover: WidgetText,
+        filter: Option<(FilterField, String)>,
+    ) {
+        let plain = text.text().to_string();
+        let response = ui
+            .add(Label::new(text).sense(Sense::click()))
+            .on_hover_text(hover);
+        self.cells.push(Cell {
+            response,
+            text: plain,
+            filter,
+        });
+    }
+
+    fn attach(
+        self, json: Option<&dyn Fn() -> Option<String>>, filters: &[FilterField],
+        action: &mut Option<RecordAction>,
+    ) {
+        let row = self
+            .cells
+            .iter()
+            .map(|cell| cell.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\t");
+
+        for cell in &self.cells {
+            cell.response.context_menu(|ui| {
+                if ui.button(t!("Tab.Inspector.Menu.CopyValue")).clicked() {
+                    ui.ctx().copy_text(cell.text.clone());
+                    ui.close_menu();
+                }
+                if ui.button(t!("Tab.Inspector.Menu.CopyRow")).clicked() {
+                    ui.ctx().copy_text(row.clone());
+                    ui.close_menu();
+                }
+                if let Some(json) = json {
+                    if ui.button(t!("Tab.Inspector.Menu.CopyJson")).clicked() {
+                        match json() {
+                            Some(json) => ui.ctx().copy_text(json),
+                            None => log::error!("Inspector: Record isn't serialized."),
+                        }
+                        ui.close_menu();
+                    }
+                }
+
+                let filter = cell
+                    .filter
+                    .as_ref()
+                    .filter(|(field, value)| filters.contains(field) && value != "-");
+                if let Some((field, value)) = filter {
+                    if ui.button(t!("Tab.Inspector.Menu.FilterBy")).clicked() {
+                        *action = Some(RecordAction::FilterBy(*field, value.clone()));
+                        ui.close_menu();
+                    }
+                }
+
+                let value = cell.filter.as_ref().map_or(&cell.text, |(_, value)| value);
+                if let Some(ip) = parse_ip(value) {
+                    ui.separator();
+                    if ui.button(t!("Tab.Inspector.Menu.CopyIp")).clicked() {
+                        ui.ctx().copy_text(ip.to_string());
+                        ui.close_menu();
+                    }
+                    let url = browser_url(ip);
+                    if ui
+                        .button(t!(
+                            "Tab.Inspector.Menu.OpenBrowser",
+                            "url" = url.as_str()
+                        ))
+                        .clicked()
+                    {
+                        ui.ctx().open_url(egui::OpenUrl::new_tab(url));
+                        ui.close_menu();
+                    }
+                }
+            });
+        }
+    }
+}
+
+// Endpoints of the streams are shown with the ports
+fn parse_ip(value: &str) -> Option<IpAddr> {
+    value
+        .parse::<IpAddr>()
+        .ok()
+        .or_else(|| value.parse::<SocketAddr>().ok().map(|address| address.ip()))
+}
+
+fn browser_url(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(ip) => format!("http://{ip}"),
+        IpAddr::V6(ip) => format!("http://[{ip}]"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ip_actions() {
+        assert_eq!(
+            parse_ip("192.168.1.1").map(browser_url),
+            Some("http://192.168.1.1".to_string())
+        );
+        assert_eq!(
+            parse_ip("[fe80::1]:5060").map(browser_url),
+            Some("http://[fe80::1]".to_string())
+        );
+        assert_eq!(parse_ip("-"), None);
+        assert_eq!(parse_ip("00:11:22:33:44:55"), None);
+    }
+}
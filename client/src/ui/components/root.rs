@@ -1,6 +1,10 @@
 use crate::context::Context;
 use crate::ui;
+use crate::ui::components::connection_status;
+use crate::ui::components::debug_overlay::DebugOverlay;
+use crate::ui::format;
 use crate::ui::styles;
+use crate::ui::styles::colors::Role;
 use crate::ui::tabs::Tab;
 use crate::ui::tabs::about::AboutTab;
 use crate::ui::tabs::inspector::InspectorTab;
@@ -11,6 +15,7 @@ use crate::ui::tabs::status::StatusTab;
 use crate::ws::request::UiClientRequest;
 use egui::{CentralPanel, RichText, SidePanel};
 use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
 
 pub const MENU_PANEL_MIN_WIDTH: f32 = ui::MIN_WINDOW_WIDTH * 0.25;
 
@@ -19,6 +24,7 @@ pub struct RootComponent {
     tabs: BTreeMap<Tab, String>,
 
     logout_requested: bool,
+    debug_overlay: DebugOverlay,
 
     pub status_tab: StatusTab,
     pub inspector_tab: InspectorTab,
@@ -47,6 +53,7 @@ impl RootComponent {
             .collect(),
 
             logout_requested: false,
+            debug_overlay: Default::default(),
 
             status_tab: StatusTab::new(ctx),
             inspector_tab: Default::default(),
@@ -61,6 +68,20 @@ impl RootComponent {
 impl RootComponent {
     pub fn show(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
         let theme = ctx.client_settings.theme.into_aesthetix_theme();
+        // Background tasks finish, even if their tab isn't shown
+        self.status_tab.poll_tasks(ctx);
+        self.inspector_tab.poll_tasks(ctx);
+        if ctx.inspector_request.is_some() {
+            self.active_tab = Tab::Inspector;
+        }
+
+        // Search across all the protocols is opened from any tab
+        let search_shortcut =
+            egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::F);
+        if ui.input_mut(|i| i.consume_shortcut(&search_shortcut)) {
+            self.active_tab = Tab::Inspector;
+            self.inspector_tab.focus_search();
+        }
 
         SidePanel::left("MENU_PANEL")
             .resizable(false)
@@ -98,26 +119,26 @@ impl RootComponent {
                         ui.label(
                             RichText::new(format!("{}: ", t!("Text.LastUpdate")))
                                 .size(styles::text::SMALL)
-                                .color(styles::colors::SILENT),
+                                .color(styles::colors::get(Role::Silent)),
                         );
                         match &ctx.heartbeat.last_sync {
                             None => {
                                 ui.label(
                                     RichText::new(t!("Text.LastUpdate.Never"))
                                         .size(styles::text::SMALL)
-                                        .color(styles::colors::OUTDATED_DARK),
+                                        .color(styles::colors::get(Role::OutdatedDark)),
                                 );
                             },
                             Some(last_sync) => {
-                                let mut text = RichText::new(
-                                    last_sync.format(styles::TIME_FORMAT).to_string(),
-                                )
-                                .size(styles::text::SMALL);
+                                let mut text = RichText::new(format::time(&last_sync))
+                                    .size(styles::text::SMALL);
 
                                 if ctx.heartbeat.is_timeout(&ctx.client_settings) {
-                                    text = text.color(styles::colors::OUTDATED_DARK);
+                                    text = text
+                                        .color(styles::colors::get(Role::OutdatedDark));
                                 } else {
-                                    text = text.color(styles::colors::UPDATED_DARK);
+                                    text = text
+                                        .color(styles::colors::get(Role::UpdatedDark));
                                 }
                                 ui.label(text);
                             },
@@ -135,49 +156,109 @@ impl RootComponent {
                             ctx.heartbeat.try_ping(&ctx.ui_client_requests_tx);
                         }
                     });
+
+                    // Going back to the auth window, if the connection is lost
+                    let text = match ctx.connection.is_connected() {
+                        true => t!("Component.Root.Disconnect"),
+                        false => t!("Component.Root.Connect"),
+                    };
+                    if ui.button(text).clicked() {
+                        self.logout_requested = true;
+                        self.active_tab = Tab::Status;
+                    }
+
+                    connection_status::show(ui, ctx);
                 });
             });
 
         // This builds the main central panel that holds the content of the active tab
+        let tab = self.active_tab;
+        let render_started = Instant::now();
         CentralPanel::default()
             .frame(
                 egui::Frame::new()
                     .inner_margin(theme.margin_style())
                     .fill(theme.bg_primary_color_visuals()),
             )
-            .show(ui.ctx(), |ui| match self.active_tab {
-                Tab::Status => {
-                    self.status_tab.show(ui, ctx);
-                },
-                Tab::Inspector => {
-                    self.inspector_tab.show(ui, ctx);
-                },
-                Tab::Stats => {
-                    self.stats_tab.show(ui, ctx);
-                },
-                Tab::ClientSettings => {
-                    self.settings_client_tab.show_with_header(ui, ctx);
-                },
-                Tab::ServerSettings => {
-                    self.settings_server_tab.show(ui, ctx);
-
-                    if self.settings_server_tab.reboot_requested {
-                        self.settings_server_tab.reboot_requested = false;
+            .show(ui.ctx(), |ui| {
+                self.dropped_messages_view(ui, ctx);
+                match self.active_tab {
+                    Tab::Status => {
+                        self.status_tab.show(ui, ctx);
+                    },
+                    Tab::Inspector => {
+                        self.inspector_tab.show(ui, ctx);
+                    },
+                    Tab::Stats => {
+                        self.stats_tab.show(ui, ctx);
+                    },
+                    Tab::ClientSettings => {
+                        self.settings_client_tab.show_with_header(ui, ctx);
+                    },
+                    Tab::ServerSettings => {
+                        self.settings_server_tab.show(ui, ctx);
+
+                        if self.settings_server_tab.reboot_requested {
+                            self.settings_server_tab.reboot_requested = false;
+                            self.logout_requested = true;
+                            self.active_tab = Tab::Status;
+                        }
+                    },
+                    Tab::About => {
+                        self.about_tab.show(ui, ctx);
+                    },
+                    Tab::Logout => {
+                        self.logout_requested = true;
+                        self.active_tab = Tab::Status;
+                    },
+                    Tab::Exit => {
+                        ui.ctx().send_viewport_cmd(egui::ViewportCommand::Close);
+                    },
+                }
+            });
+
+        if ctx.client_settings.debug_overlay {
+            self.debug_overlay.record_frame(ui.ctx());
+            self.debug_overlay.record_tab(tab, render_started.elapsed());
+            self.debug_overlay.show(ui.ctx());
+        }
+    }
+
+    // Shown over every tab, the messages keep being dropped until the disconnect
+    fn dropped_messages_view(&mut self, ui: &mut egui::Ui, ctx: &Context) {
+        if ctx.dropped_messages == 0 {
+            return;
+        }
+
+        egui::Frame::group(&egui::Style::default())
+            .fill(styles::colors::get(Role::WarningBackground))
+            .corner_radius(5.0)
+            .show(ui, |ui| {
+                ui.set_width(ui.available_width());
+                ui.horizontal_wrapped(|ui| {
+                    ui.label(
+                        RichText::new(format!(
+                            "⚠ {}",
+                            t!(
+                                "Component.Root.Warning.DroppedMessages",
+                                "amount" = format::count(ctx.dropped_messages)
+                            )
+                        ))
+                        .color(styles::colors::get(Role::WarningText))
+                        .strong(),
+                    );
+                    if ui.button(t!("Component.Root.Disconnect")).clicked() {
                         self.logout_requested = true;
                         self.active_tab = Tab::Status;
                     }
-                },
-                Tab::About => {
-                    self.about_tab.show(ui, ctx);
-                },
-                Tab::Logout => {
-                    self.logout_requested = true;
-                    self.active_tab = Tab::Status;
-                },
-                Tab::Exit => {
-                    ui.ctx().send_viewport_cmd(egui::ViewportCommand::Close);
-                },
+                });
             });
+        ui.add_space(4.0);
+    }
+
+    /// Measured by the app after the frame is shown, so it's shown with the next one.
+    pub fn record_ingest(&mut self, elapsed: Duration, ingested: usize, backlog: usize) {
+        self.debug_overlay.record_ingest(elapsed, ingested, backlog);
     }
 
     pub fn logout_requested(&self) -> bool {
@@ -189,6 +270,8 @@ impl RootComponent {
             .ui_client_requests_tx
             .try_send(UiClientRequest::CloseConnection);
         self.logout_requested = false;
+        self.status_tab.cancel_tasks();
+        self.inspector_tab.cancel_tasks();
         self.update_client_settings_info(ctx);
         log::info!("Logged out!");
     }
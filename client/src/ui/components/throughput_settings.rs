@@ -2,7 +2,7 @@ use crate::context::Context;
 use crate::net::speed::SpeedUnitPerSecond;
 use crate::ui::modals::message::MessageModal;
 use crate::ui::styles;
-use crate::ui::styles::spacing;
+use crate::ui::styles::{layout, spacing};
 use egui::{DragValue, Grid, RichText};
 use strum::IntoEnumIterator;
 
@@ -33,19 +33,14 @@ impl ThroughputSettings {
                     egui::Layout::top_down_justified(egui::Align::Center),
                     |ui| {
                         spacing::with_temp_y(ui, spacing::GRID, |ui| {
-                            Grid::new("Status.ThroughputSettings.Grid")
+                            let grid = Grid::new("Status.ThroughputSettings.Grid")
                                 .striped(false)
-                                .num_columns(GRID_COLUMNS)
-                                .show(ui, |ui| {
-                                    self.save_client_config_view(ui, ctx);
-                                    ui.end_row();
-
-                                    self.display_period_view(ui, ctx);
-                                    ui.end_row();
-
-                                    self.display_unit_view(ui, ctx);
-                                    ui.end_row();
-                                });
+                                .num_columns(GRID_COLUMNS);
+                            layout::Rows::show(ui, grid, |ui, rows| {
+                                rows.row(ui, |ui| self.save_client_config_view(ui, ctx));
+                                rows.row(ui, |ui| self.display_period_view(ui, ctx));
+                                rows.row(ui, |ui| self.display_unit_view(ui, ctx));
+                            });
                         });
                     },
                 );
@@ -86,7 +81,7 @@ impl ThroughputSettings {
                 ctx.client_settings.plot.display_window_seconds;
             ctx.config.plot_speed_units = ctx.client_settings.plot.units.clone();
 
-            match ctx.config.save_to_file() {
+            match ctx.config.save_to_file(&ctx.data_dir) {
                 Ok(_) => {
                     log::info!("Plot Settings: Successfully saved client config.");
                     MessageModal::info(&t!("Message.Success.ClientConfigSaved"))
@@ -0,0 +1,354 @@
+// Numbers, sizes & time, formatted by the conventions of the chosen language.
+// Separators, units & time patterns are taken from the locale files.
+// Byte prefixes are chosen in the client settings and apply to every size on the screen,
+// the form of the internationalized domain names - to every name.
+
+use crate::net::idna;
+use chrono::{DateTime, Local, TimeZone};
+use std::fmt;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use strum_macros::{Display, EnumIter, EnumString};
+
+const SECONDS_IN_MINUTE: u64 = 60;
+const SECONDS_IN_HOUR: u64 = 60 * SECONDS_IN_MINUTE;
+const SECONDS_IN_DAY: u64 = 24 * SECONDS_IN_HOUR;
+
+// False, if the binary prefixes (KiB) are used
+static DECIMAL_PREFIXES: AtomicBool = AtomicBool::new(false);
+// False, if the domain names are shown as they're on the wire
+static IDN_UNICODE: AtomicBool = AtomicBool::new(true);
+
+#[derive(Debug, Default, Clone, Copy, Display, EnumIter, EnumString, PartialEq, Eq)]
+pub enum Prefixes {
+    // 1 KiB = 1024 B
+    #[default]
+    #[strum(serialize = "binary")]
+    Binary,
+
+    // 1 KB = 1000 B
+    #[strum(serialize = "decimal")]
+    Decimal,
+}
+
+impl Prefixes {
+    pub fn localize(&self) -> String {
+        match self {
+            Prefixes::Binary => t!("Format.Prefixes.Binary").to_string(),
+            Prefixes::Decimal => t!("Format.Prefixes.Decimal").to_string(),
+        }
+    }
+
+    fn base(&self) -> f64 {
+        match self {
+            Prefixes::Binary => 1024.0,
+            Prefixes::Decimal => 1000.0,
+        }
+    }
+
+    fn units(&self, locale: &str) -> Vec<String> {
+        let units = match self {
+            Prefixes::Binary => t!("Format.Units.Binary", locale = locale),
+            Prefixes::Decimal => t!("Format.Units.Decimal", locale = locale),
+        };
+        units
+            .split(',')
+            .map(|unit| unit.trim().to_string())
+            .collect()
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, Display, EnumIter, EnumString, PartialEq, Eq)]
+pub enum IdnForm {
+    // "münchen.de", the punycode is in the hint
+    #[default]
+    #[strum(serialize = "unicode")]
+    Unicode,
+
+    // "xn--mnchen-3ya.de", the Unicode form is in the hint
+    #[strum(serialize = "punycode")]
+    Punycode,
+}
+
+impl IdnForm {
+    pub fn localize(&self) -> String {
+        match self {
+            IdnForm::Unicode => t!("Format.IdnForm.Unicode").to_string(),
+            IdnForm::Punycode => t!("Format.IdnForm.Punycode").to_string(),
+        }
+    }
+}
+
+pub fn set_prefixes(prefixes: Prefixes) {
+    DECIMAL_PREFIXES.store(prefixes == Prefixes::Decimal, Ordering::Relaxed);
+}
+
+pub fn prefixes() -> Prefixes {
+    match DECIMAL_PREFIXES.load(Ordering::Relaxed) {
+        true => Prefixes::Decimal,
+        false => Prefixes::Binary,
+    }
+}
+
+pub fn set_idn_form(form: IdnForm) {
+    IDN_UNICODE.store(form == IdnForm::Unicode, Ordering::Relaxed);
+}
+
+pub fn idn_form() -> IdnForm {
+    match IDN_UNICODE.load(Ordering::Relaxed) {
+        true => IdnForm::Unicode,
+        false => IdnForm::Punycode,
+    }
+}
+
+/// Domain name in the chosen form & the other form for the hint. <br>
+/// There's no hint, if the name isn't internationalized or its punycode is malformed.
+pub fn domain(name: &str) -> (String, Option<String>) {
+    let Some(unicode) = idna::to_unicode(name) else {
+        return (name.to_string(), None);
+    };
+    match idn_form() {
+        IdnForm::Unicode => (unicode, Some(name.to_string())),
+        IdnForm::Punycode => (name.to_string(), Some(unicode)),
+    }
+}
+
+/// Size with the largest unit, that keeps the value at least 1 (e.g. "1.5 KiB").
+pub fn bytes(bytes: u64) -> String {
+    format_bytes(bytes, prefixes(), &rust_i18n::locale())
+}
+
+pub fn rate(bytes_per_second: f64) -> String {
+    format_rate(bytes_per_second, prefixes(), &rust_i18n::locale())
+}
+
+/// Name of the unit, e.g. "MiB" for the power 2. Used by the fields, that are set
+/// in the fixed units.
+pub fn unit(power: usize, prefixes: Prefixes) -> String {
+    let units = prefixes.units(&rust_i18n::locale());
+    units
+        .get(power)
+        .or(units.last())
+        .cloned()
+        .unwrap_or_default()
+}
+
+pub fn decimal(value: f64, precision: usize) -> String {
+    format_decimal(value, precision, &rust_i18n::locale())
+}
+
+/// Same as [`decimal`], with the sign of the positive values.
+pub fn signed(value: f64, precision: usize) -> String {
+    localize_separator(format!("{value:+.precision$}"), &rust_i18n::locale())
+}
+
+/// Integer with the thousands separated (e.g. "1,234,567").
+pub fn count(value: u64) -> String {
+    format_count(value, &rust_i18n::locale())
+}
+
+/// Days are shown only for the longer durations (e.g. "2d 03:04:05").
+pub fn duration(seconds: u64) -> String {
+    format_duration(seconds, &rust_i18n::locale())
+}
+
+/// Date without the year & time of the day.
+pub fn time<Tz: TimeZone>(time: &DateTime<Tz>) -> String
+where
+    Tz::Offset: fmt::Display,
+{
+    let locale = rust_i18n::locale();
+    format_time(time, &t!("Format.Time", locale = &*locale))
+}
+
+/// Full date & time without the seconds.
+pub fn date_time<Tz: TimeZone>(time: &DateTime<Tz>) -> String
+where
+    Tz::Offset: fmt::Display,
+{
+    let locale = rust_i18n::locale();
+    format_time(time, &t!("Format.DateTime", locale = &*locale))
+}
+
+/// Same as [`date_time`] for the Unix seconds, in the local timezone.
+pub fn unix_date_time(seconds: u64) -> String {
+    i64::try_from(seconds)
+        .ok()
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+        .map(|time| date_time(&time.with_timezone(&Local)))
+        .unwrap_or_default()
+}
+
+fn format_bytes(bytes: u64, prefixes: Prefixes, locale: &str) -> String {
+    let units = prefixes.units(locale);
+    let base = prefixes.base();
+    let byte_unit = units.first().map(String::as_str).unwrap_or_default();
+
+    let mut value = bytes as f64;
+    if value < base {
+        return format!("{bytes} {byte_unit}");
+    }
+
+    // 1023.96 KiB is shown as 1.0 MiB, not 1024.0 KiB
+    let rounded = |value: f64| (value * 10.0).round() / 10.0;
+    let mut power = 0;
+    while power + 1 < units.len() && rounded(value) >= base {
+        value /= base;
+        power += 1;
+    }
+
+    let unit = units.get(power).map(String::as_str).unwrap_or(byte_unit);
+    format!("{} {unit}", format_decimal(value, 1, locale))
+}
+
+fn format_rate(bytes_per_second: f64, prefixes: Prefixes, locale: &str) -> String {
+    let bytes = match bytes_per_second.is_finite() {
+        true => bytes_per_second.max(0.0).round() as u64,
+        false => 0,
+    };
+    format!(
+        "{}{}",
+        format_bytes(bytes, prefixes, locale),
+        t!("Format.PerSecond", locale = locale)
+    )
+}
+
+fn format_decimal(value: f64, precision: usize, locale: &str) -> String {
+    localize_separator(format!("{value:.precision$}"), locale)
+}
+
+fn localize_separator(text: String, locale: &str) -> String {
+    let separator = t!("Format.DecimalSeparator", locale = locale);
+    match separator.as_ref() {
+        "." => text,
+        separator => text.replacen('.', separator, 1),
+    }
+}
+
+fn format_count(value: u64, locale: &str) -> String {
+    let separator = t!("Format.GroupSeparator", locale = locale);
+    let digits = value.to_string();
+
+    let mut text = String::with_capacity(digits.len() * 2);
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index) % 3 == 0 {
+            text.push_str(&separator);
+        }
+        text.push(digit);
+    }
+    text
+}
+
+fn format_duration(seconds: u64, locale: &str) -> String {
+    let days = seconds / SECONDS_IN_DAY;
+    let hours = seconds % SECONDS_IN_DAY / SECONDS_IN_HOUR;
+    let minutes = seconds % SECONDS_IN_HOUR / SECONDS_IN_MINUTE;
+    let seconds = seconds % SECONDS_IN_MINUTE;
+
+    let clock = format!("{hours:02}:{minutes:02}:{seconds:02}");
+    match days {
+        0 => clock,
+        days => format!("{days}{} {clock}", t!("Format.Days", locale = locale)),
+    }
+}
+
+// Broken pattern of the translation is shown as RFC 3339, chrono would panic on it
+fn format_time<Tz: TimeZone>(time: &DateTime<Tz>, pattern: &str) -> String
+where
+    Tz::Offset: fmt::Display,
+{
+    let mut text = String::new();
+    match write!(text, "{}", time.format(pattern)) {
+        Ok(()) => text,
+        Err(_) => time.to_rfc3339(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENGLISH: &str = "English";
+    const UKRAINIAN: &str = "Ukrainian";
+
+    #[test]
+    fn test_bytes() {
+        let binary = |bytes| format_bytes(bytes, Prefixes::Binary, ENGLISH);
+        assert_eq!(binary(0), "0 B");
+        assert_eq!(binary(1023), "1023 B");
+        assert_eq!(binary(1024), "1.0 KiB");
+        assert_eq!(binary(1536), "1.5 KiB");
+        assert_eq!(binary(1024 * 1024 - 1), "1.0 MiB");
+        assert_eq!(binary(3 * 1024 * 1024), "3.0 MiB");
+        assert_eq!(binary(u64::MAX), "16.0 EiB");
+
+        let decimal = |bytes| format_bytes(bytes, Prefixes::Decimal, ENGLISH);
+        assert_eq!(decimal(0), "0 B");
+        assert_eq!(decimal(1000), "1.0 kB");
+        assert_eq!(decimal(1024), "1.0 kB");
+        assert_eq!(decimal(1_500_000), "1.5 MB");
+        assert_eq!(decimal(u64::MAX), "18.4 EB");
+    }
+
+    #[test]
+    fn test_bytes_ukrainian() {
+        assert_eq!(format_bytes(0, Prefixes::Binary, UKRAINIAN), "0 Б");
+        assert_eq!(format_bytes(1024, Prefixes::Binary, UKRAINIAN), "1,0 КіБ");
+        assert_eq!(format_bytes(1536, Prefixes::Decimal, UKRAINIAN), "1,5 кБ");
+        assert_eq!(
+            format_bytes(u64::MAX, Prefixes::Binary, UKRAINIAN),
+            "16,0 ЕіБ"
+        );
+    }
+
+    #[test]
+    fn test_rate() {
+        assert_eq!(format_rate(1536.0, Prefixes::Binary, ENGLISH), "1.5 KiB/s");
+        assert_eq!(
+            format_rate(1536.0, Prefixes::Binary, UKRAINIAN),
+            "1,5 КіБ/с"
+        );
+        assert_eq!(format_rate(f64::NAN, Prefixes::Binary, ENGLISH), "0 B/s");
+        assert_eq!(format_rate(-5.0, Prefixes::Decimal, ENGLISH), "0 B/s");
+    }
+
+    #[test]
+    fn test_numbers() {
+        assert_eq!(format_decimal(2.346, 2, ENGLISH), "2.35");
+        assert_eq!(format_decimal(2.346, 2, UKRAINIAN), "2,35");
+        assert_eq!(format_decimal(0.0, 1, UKRAINIAN), "0,0");
+
+        assert_eq!(format_count(0, ENGLISH), "0");
+        assert_eq!(format_count(999, ENGLISH), "999");
+        assert_eq!(format_count(1_234_567, ENGLISH), "1,234,567");
+        assert_eq!(format_count(1_000, UKRAINIAN), "1\u{a0}000");
+        assert_eq!(
+            format_count(u64::MAX, ENGLISH),
+            "18,446,744,073,709,551,615"
+        );
+    }
+
+    #[test]
+    fn test_duration() {
+        assert_eq!(format_duration(0, ENGLISH), "00:00:00");
+        assert_eq!(format_duration(3 * 3600 + 4 * 60 + 5, ENGLISH), "03:04:05");
+        assert_eq!(
+            format_duration(2 * SECONDS_IN_DAY + 61, ENGLISH),
+            "2d 00:01:01"
+        );
+        assert_eq!(format_duration(SECONDS_IN_DAY, UKRAINIAN), "1д 00:00:00");
+    }
+
+    #[test]
+    fn test_time() {
+        let time = DateTime::parse_from_rfc3339("2025-03-07T09:05:02+00:00").unwrap();
+        let format =
+            |key: &str, locale: &str| format_time(&time, &t!(key, locale = locale));
+        assert_eq!(format("Format.Time", ENGLISH), "03/07 09:05:02");
+        assert_eq!(format("Format.Time", UKRAINIAN), "07.03 09:05:02");
+        assert_eq!(format("Format.DateTime", ENGLISH), "2025-03-07 09:05");
+        assert_eq!(format("Format.DateTime", UKRAINIAN), "07.03.2025 09:05");
+
+        assert_eq!(format_time(&time, "%Q"), "2025-03-07T09:05:02+00:00");
+    }
+}
@@ -61,6 +61,10 @@ pub trait Modal: Send + Sync {
     fn modal_fields(&self) -> &ModalFields;
 }
 
+pub mod connection;
 pub mod connection_profiles;
 pub mod device;
+pub mod frame;
 pub mod message;
+pub mod note;
+pub mod progress;
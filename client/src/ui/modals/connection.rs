@@ -0,0 +1,135 @@
+use crate::context::Context;
+use crate::ui::format;
+use crate::ui::modals::{Modal, ModalFields};
+use crate::ui::styles;
+use crate::ws::link::LinkInfo;
+use crate::ws::state::ConnectionState;
+use chrono::{DateTime, Local};
+use common::messages::PROTOCOL_VERSION;
+use egui::{Grid, Ui};
+use std::time::Duration;
+
+// Counters are alive, so they're redrawn while the modal is open
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What is negotiated with the server & how much the link carries.
+/// <br> It's read from the context on every frame, so it's opened from any state.
+pub struct ConnectionModal {
+    modal: ModalFields,
+}
+
+impl Modal for ConnectionModal {
+    fn show_content(&mut self, ui: &mut Ui, ctx: &mut Context) {
+        ui.ctx().request_repaint_after(REFRESH_INTERVAL);
+        let since = Self::since(&ctx.connection);
+
+        match &ctx.link {
+            Some(link) => Self::link_view(ui, link, since),
+            None => {
+                ui.vertical_centered(|ui| {
+                    ui.label(t!("Modal.Connection.NoLink"));
+                });
+            },
+        }
+
+        ui.add_space(16.0);
+
+        ui.vertical_centered_justified(|ui| {
+            if let Some(link) = &ctx.link {
+                if ui
+                    .button(t!("Modal.Connection.Button.CopyDiagnostics"))
+                    .on_hover_text(t!("Modal.Connection.Hover.CopyDiagnostics"))
+                    .clicked()
+                {
+                    let text = link.diagnostics(&ctx.connection.localized(), since);
+                    ui.ctx().copy_text(text);
+                }
+            }
+            if ui.button(t!("Button.Close")).clicked() {
+                self.close()
+            }
+        });
+    }
+
+    fn close(&mut self) {
+        self.modal.is_open = false;
+    }
+
+    fn modal_fields(&self) -> &ModalFields {
+        &self.modal
+    }
+}
+
+impl ConnectionModal {
+    pub fn open(ctx: &Context) {
+        let modal = Self {
+            modal: ModalFields::default()
+                .with_title(format!("🔗 {}", t!("Modal.Connection.Title")))
+                .with_width(400.0),
+        };
+        if let Err(err) = ctx.modals_tx.try_send(Box::new(modal)) {
+            log::error!("Failed to send modal: {err}");
+        }
+    }
+
+    // Start of the connection, if it's still alive
+    fn since(state: &ConnectionState) -> Option<DateTime<Local>> {
+        match state {
+            ConnectionState::Connected { since, .. }
+            | ConnectionState::Degraded { since, .. } => Some(*since),
+            _ => None,
+        }
+    }
+
+    fn link_view(ui: &mut Ui, link: &LinkInfo, since: Option<DateTime<Local>>) {
+        let stats = link.counters.stats();
+        let uptime = since.map_or("-".to_string(), |since| {
+            format::duration((Local::now() - since).num_seconds().max(0) as u64)
+        });
+
+        Grid::new("Connection-Info")
+            .num_columns(2)
+            .striped(true)
+            .show(ui, |ui| {
+                let mut row = |key: &str, value: String| {
+                    ui.label(styles::heading::grid(&t!(key)));
+                    ui.label(value);
+                    ui.end_row();
+                };
+                row("Modal.Connection.Label.Server", link.address.to_string());
+                row("Modal.Connection.Label.Uptime", uptime);
+                row("Modal.Connection.Label.Compression", link.compression());
+                row("Modal.Connection.Label.Format", link.message_format());
+                row(
+                    "Modal.Connection.Label.ProtocolVersion",
+                    PROTOCOL_VERSION.to_string(),
+                );
+                row(
+                    "Modal.Connection.Label.Heartbeat",
+                    format::duration(link.heartbeat.as_secs()),
+                );
+                row(
+                    "Modal.Connection.Label.Authentication",
+                    link.authentication(),
+                );
+                row(
+                    "Modal.Connection.Label.Messages",
+                    t!(
+                        "Modal.Connection.SentReceived",
+                        "sent" = format::count(stats.messages_sent),
+                        "received" = format::count(stats.messages_received)
+                    )
+                    .to_string(),
+                );
+                row(
+                    "Modal.Connection.Label.Bytes",
+                    t!(
+                        "Modal.Connection.SentReceived",
+                        "sent" = format::bytes(stats.bytes_sent),
+                        "received" = format::bytes(stats.bytes_received)
+                    )
+                    .to_string(),
+                );
+            });
+    }
+}
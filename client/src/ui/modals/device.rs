@@ -1,10 +1,21 @@
 use crate::context::Context;
+use crate::net::classification::DeviceType;
+use crate::net::device::DeviceTag;
 use crate::ui::modals::{Modal, ModalFields};
 use dpi::protocols::ethernet::mac::MacAddress;
-use egui::{Grid, TextEdit, Ui};
+use egui::{Color32, Grid, TextEdit, Ui};
+use strum::IntoEnumIterator;
+
+// Offered, if the device isn't colored yet
+const DEFAULT_TAG_COLOR: Color32 = Color32::from_rgb(255, 170, 0);
 
 pub struct DeviceModal {
     alias: String,
+    // None is guessed by the traffic
+    device_type: Option<DeviceType>,
+    note: String,
+    color: Color32,
+    is_colored: bool,
     mac: MacAddress,
     modal: ModalFields,
 }
@@ -25,6 +36,42 @@ impl Modal for DeviceModal {
                 ui.label(format!("{}:", t!("Modal.DeviceAlias.Label.MAC")));
                 ui.label(self.mac.to_string());
                 ui.end_row();
+
+                ui.label(format!("{}:", t!("Modal.DeviceAlias.Label.Type")));
+                egui::ComboBox::from_id_salt("Combobox.DeviceModal.Type")
+                    .selected_text(Self::type_title(self.device_type))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(
+                            &mut self.device_type,
+                            None,
+                            Self::type_title(None),
+                        );
+                        for device_type in DeviceType::iter() {
+                            ui.selectable_value(
+                                &mut self.device_type,
+                                Some(device_type),
+                                device_type.to_string(),
+                            );
+                        }
+                    });
+                ui.end_row();
+
+                ui.label(format!("{}:", t!("Modal.DeviceAlias.Label.Note")));
+                ui.add(
+                    TextEdit::singleline(&mut self.note)
+                        .hint_text(t!("Modal.DeviceAlias.Hint.Note"))
+                        .desired_width(f32::INFINITY),
+                );
+                ui.end_row();
+
+                ui.label(format!("{}:", t!("Modal.DeviceAlias.Label.Color")));
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.is_colored, "");
+                    ui.add_enabled_ui(self.is_colored, |ui| {
+                        ui.color_edit_button_srgba(&mut self.color);
+                    });
+                });
+                ui.end_row();
             });
 
         ui.add_space(16.0);
@@ -54,11 +101,21 @@ impl Modal for DeviceModal {
 
 impl DeviceModal {
     pub fn with_id(id: MacAddress, ctx: &Context) -> Self {
+        let tag = ctx
+            .net_storage
+            .devices
+            .tag(&id)
+            .cloned()
+            .unwrap_or_default();
         Self {
             alias: match ctx.net_storage.devices.aliases.get(&id) {
                 Some(alias) => alias.clone(),
                 None => Default::default(),
             },
+            device_type: ctx.net_storage.devices.types.get(&id).copied(),
+            note: tag.note.unwrap_or_default(),
+            color: tag.color.unwrap_or(DEFAULT_TAG_COLOR),
+            is_colored: tag.color.is_some(),
             mac: id,
             modal: ModalFields::default()
                 .with_title(format!("✏ {}", t!("Modal.DeviceAlias.Title")))
@@ -67,11 +124,35 @@ impl DeviceModal {
     }
 
     fn save(&mut self, ctx: &mut Context) {
-        ctx.net_storage
-            .devices
-            .aliases
-            .insert(self.mac.clone(), self.alias.trim().to_owned());
+        let devices = &mut ctx.net_storage.devices;
+        let alias = self.alias.trim();
+        if alias.is_empty() {
+            devices.aliases.remove(&self.mac);
+        } else {
+            devices.aliases.insert(self.mac.clone(), alias.to_owned());
+        }
+        match self.device_type {
+            Some(device_type) => devices.types.insert(self.mac.clone(), device_type),
+            None => devices.types.remove(&self.mac),
+        };
+        let note = self.note.trim();
+        let tag = DeviceTag {
+            note: (!note.is_empty()).then(|| note.to_owned()),
+            color: self.is_colored.then_some(self.color),
+        };
+        if tag.is_empty() {
+            devices.tags.remove(&self.mac);
+        } else {
+            devices.tags.insert(self.mac.clone(), tag);
+        }
 
         self.close();
     }
+
+    fn type_title(device_type: Option<DeviceType>) -> String {
+        match device_type {
+            Some(device_type) => device_type.to_string(),
+            None => t!("Modal.DeviceAlias.Type.Auto").to_string(),
+        }
+    }
 }
@@ -0,0 +1,98 @@
+use crate::context::Context;
+use crate::net::inspector::FrameLayer;
+use crate::net::notes::RecordId;
+use crate::ui::modals::{Modal, ModalFields};
+use crate::ui::styles;
+use egui::{CollapsingHeader, Grid, RichText, ScrollArea, Ui};
+
+/// Layer stack of the frame: records of every protocol, that share its id.
+/// <br> Layers are copied on open, so the view doesn't hold the storage.
+pub struct FrameModal {
+    // Record, the frame is opened from
+    id: RecordId,
+    layers: Vec<FrameLayer>,
+    modal: ModalFields,
+}
+
+impl Modal for FrameModal {
+    fn show_content(&mut self, ui: &mut Ui, _ctx: &mut Context) {
+        ScrollArea::vertical()
+            .max_height(450.0)
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                Self::layers_view(ui, &self.id, &self.layers);
+            });
+
+        ui.add_space(16.0);
+
+        ui.vertical_centered_justified(|ui| {
+            if ui.button(t!("Button.Close")).clicked() {
+                self.close()
+            }
+        });
+    }
+
+    fn close(&mut self) {
+        self.modal.is_open = false;
+    }
+
+    fn modal_fields(&self) -> &ModalFields {
+        &self.modal
+    }
+}
+
+impl FrameModal {
+    /// Nothing is opened, if the record is cleared since it was shown.
+    pub fn open(id: RecordId, ctx: &Context) {
+        let Some(frame) = ctx.net_storage.inspector.frame_of(&id) else {
+            return;
+        };
+
+        let modal = Self {
+            id,
+            layers: ctx.net_storage.inspector.frame_layers(frame),
+            modal: ModalFields::default()
+                .with_title(format!("🔍 {}", t!("Modal.Frame.Title")))
+                .with_width(500.0),
+        };
+        if let Err(err) = ctx.modals_tx.try_send(Box::new(modal)) {
+            log::error!("Failed to send modal: {err}");
+        }
+    }
+
+    // Every next layer is nested into the previous one, like it's carried by it
+    fn layers_view(ui: &mut Ui, id: &RecordId, layers: &[FrameLayer]) {
+        let Some((layer, rest)) = layers.split_first() else {
+            return;
+        };
+
+        let title = RichText::new(format!(
+            "{} #{}",
+            layer.protocol,
+            layer.index.saturating_add(1)
+        ));
+        let is_opened_from = layer.protocol == id.protocol && layer.index == id.index;
+        let title = match is_opened_from {
+            true => title.strong(),
+            false => title,
+        };
+
+        CollapsingHeader::new(title)
+            .id_salt(("Frame-Layer", layer.protocol, layer.index))
+            .default_open(true)
+            .show(ui, |ui| {
+                Grid::new(("Frame-Layer-Fields", layer.protocol, layer.index))
+                    .striped(true)
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        for (key, value) in &layer.fields {
+                            let key = format!("Tab.Inspector.Field.{key}");
+                            ui.label(styles::heading::grid(&t!(key.as_str())));
+                            ui.label(value);
+                            ui.end_row();
+                        }
+                    });
+                Self::layers_view(ui, id, rest);
+            });
+    }
+}
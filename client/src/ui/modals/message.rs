@@ -45,6 +45,13 @@ impl MessageModal {
             .with_width(300.0)
     }
 
+    pub fn warning(message: &str) -> Self {
+        MessageModal::default()
+            .with_message(message)
+            .with_title(format!("⚠ {}", t!("Modal.Title.Warning")))
+            .with_width(300.0)
+    }
+
     pub fn with_title(mut self, title: String) -> Self {
         self.modal_fields.title = title;
         self
@@ -0,0 +1,128 @@
+use crate::context::Context;
+use crate::net::notes::{ClearedGeneration, RecordId};
+use crate::ui::modals::{Modal, ModalFields};
+use egui::{TextEdit, Ui};
+
+pub struct NoteModal {
+    id: RecordId,
+    text: String,
+    modal: ModalFields,
+}
+
+impl Modal for NoteModal {
+    fn show_content(&mut self, ui: &mut Ui, ctx: &mut Context) {
+        ui.label(format!(
+            "{} #{}",
+            self.id.protocol,
+            self.id.index.saturating_add(1)
+        ));
+        ui.add(
+            TextEdit::multiline(&mut self.text)
+                .hint_text(t!("Modal.Note.Hint"))
+                .desired_rows(5)
+                .desired_width(f32::INFINITY),
+        );
+
+        ui.add_space(16.0);
+
+        ui.columns(2, |columns| {
+            columns[0].vertical_centered_justified(|ui| {
+                if ui.button(t!("Button.Save")).clicked() {
+                    ctx.net_storage.notes.set(self.id, &self.text);
+                    self.close();
+                }
+            });
+            columns[1].vertical_centered_justified(|ui| {
+                if ui.button(t!("Button.Close")).clicked() {
+                    self.close()
+                }
+            });
+        });
+    }
+
+    fn close(&mut self) {
+        self.modal.is_open = false;
+    }
+
+    fn modal_fields(&self) -> &ModalFields {
+        &self.modal
+    }
+}
+
+impl NoteModal {
+    pub fn with_id(id: RecordId, ctx: &Context) -> Self {
+        Self {
+            text: ctx
+                .net_storage
+                .notes
+                .get(&id)
+                .unwrap_or_default()
+                .to_string(),
+            id,
+            modal: ModalFields::default()
+                .with_title(format!("📝 {}", t!("Modal.Note.Title")))
+                .with_width(350.0),
+        }
+    }
+}
+
+/// Asks, whether the notes of the cleared records are kept.
+pub struct OrphanedNotesModal {
+    cleared: Vec<ClearedGeneration>,
+    amount: usize,
+    modal: ModalFields,
+}
+
+impl Modal for OrphanedNotesModal {
+    fn show_content(&mut self, ui: &mut Ui, ctx: &mut Context) {
+        ui.label(t!("Modal.OrphanedNotes.Label", "amount" = self.amount));
+
+        ui.add_space(16.0);
+
+        ui.columns(2, |columns| {
+            columns[0].vertical_centered_justified(|ui| {
+                if ui.button(t!("Modal.OrphanedNotes.Button.Keep")).clicked() {
+                    self.close();
+                }
+            });
+            columns[1].vertical_centered_justified(|ui| {
+                if ui.button(t!("Modal.OrphanedNotes.Button.Delete")).clicked() {
+                    ctx.net_storage.notes.remove_orphaned(&self.cleared);
+                    self.close();
+                }
+            });
+        });
+    }
+
+    fn close(&mut self) {
+        self.modal.is_open = false;
+    }
+
+    fn modal_fields(&self) -> &ModalFields {
+        &self.modal
+    }
+}
+
+impl OrphanedNotesModal {
+    /// Sent only if the cleared records had notes.
+    pub fn ask(cleared: Vec<ClearedGeneration>, ctx: &Context) {
+        if cleared.is_empty() {
+            return;
+        }
+        let amount = ctx.net_storage.notes.orphaned(&cleared);
+        if amount == 0 {
+            return;
+        }
+
+        let modal = Self {
+            cleared,
+            amount,
+            modal: ModalFields::default()
+                .with_title(format!("📝 {}", t!("Modal.OrphanedNotes.Title")))
+                .with_width(300.0),
+        };
+        if let Err(err) = ctx.modals_tx.try_send(Box::new(modal)) {
+            log::error!("Failed to send modal: {err}");
+        }
+    }
+}
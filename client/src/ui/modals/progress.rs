@@ -0,0 +1,217 @@
+use crate::context::Context;
+use crate::ui::modals::{Modal, ModalFields};
+use crossbeam::channel::{Receiver, Sender, TryRecvError, bounded, unbounded};
+use egui::{Button, ProgressBar, Ui};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use thiserror::Error;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Progress {
+    #[default]
+    Indeterminate,
+    // 0..=100
+    Percent(u8),
+}
+
+impl Progress {
+    pub fn of(done: usize, total: usize) -> Self {
+        if total == 0 {
+            return Self::Indeterminate;
+        }
+        let percent = done.saturating_mul(100) / total;
+
+        Self::Percent(u8::try_from(percent.min(100)).unwrap_or(100))
+    }
+}
+
+/// Given to the worker for reporting the progress & checking the cancellation.
+pub struct TaskReporter {
+    progress_tx: Sender<Progress>,
+    cancel_flag: Arc<AtomicBool>,
+}
+
+impl TaskReporter {
+    pub fn report(&self, progress: Progress) {
+        // Dialog could be closed already
+        let _ = self.progress_tx.try_send(progress);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel_flag.load(Ordering::Acquire)
+    }
+}
+
+/// Work running in the separate thread, while the progress dialog is shown. <br>
+/// The result is delivered to the owner of the handle. Dropped tasks are cancelled.
+pub struct BackgroundTask<T> {
+    result_rx: Receiver<T>,
+    cancel_flag: Arc<AtomicBool>,
+}
+
+impl<T: Send + 'static> BackgroundTask<T> {
+    pub fn spawn<F>(
+        title: String, modals_tx: &Sender<Box<dyn Modal>>, work: F,
+    ) -> Result<Self, TaskError>
+    where
+        F: FnOnce(&TaskReporter) -> T + Send + 'static,
+    {
+        let (progress_tx, progress_rx) = unbounded::<Progress>();
+        let (result_tx, result_rx) = bounded::<T>(1);
+        let cancel_flag = Arc::new(AtomicBool::new(false));
+
+        let reporter = TaskReporter {
+            progress_tx,
+            cancel_flag: Arc::clone(&cancel_flag),
+        };
+        thread::Builder::new()
+            .name("Task-Thread".to_string())
+            .spawn(move || {
+                let result = work(&reporter);
+                // Owner of the task could be gone
+                let _ = result_tx.send(result);
+            })?;
+
+        let modal = ProgressModal {
+            modal_fields: ModalFields::default().with_title(title).with_width(300.0),
+            progress: Progress::Indeterminate,
+            progress_rx,
+            cancel_flag: Arc::clone(&cancel_flag),
+        };
+        if let Err(err) = modals_tx.try_send(Box::new(modal)) {
+            log::error!("Failed to send modal: {err}");
+        }
+
+        Ok(Self {
+            result_rx,
+            cancel_flag,
+        })
+    }
+}
+
+impl<T> BackgroundTask<T> {
+    /// Result of the finished task, None while it's running.
+    pub fn try_result(&self) -> Option<Result<T, TaskError>> {
+        match self.result_rx.try_recv() {
+            Ok(result) => Some(Ok(result)),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(TaskError::Aborted)),
+        }
+    }
+
+    pub fn cancel(&self) {
+        self.cancel_flag.store(true, Ordering::Release);
+    }
+}
+
+impl<T> Drop for BackgroundTask<T> {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+// Closed by itself, when the worker stops
+pub struct ProgressModal {
+    modal_fields: ModalFields,
+    progress: Progress,
+    // Disconnected, when the worker stops
+    progress_rx: Receiver<Progress>,
+    cancel_flag: Arc<AtomicBool>,
+}
+
+impl Modal for ProgressModal {
+    fn show_content(&mut self, ui: &mut Ui, _ctx: &mut Context) {
+        loop {
+            match self.progress_rx.try_recv() {
+                Ok(progress) => self.progress = progress,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.modal_fields.is_open = false;
+                    return;
+                },
+            }
+        }
+
+        let progress_bar = match self.progress {
+            Progress::Indeterminate => ProgressBar::new(0.0).animate(true),
+            Progress::Percent(percent) => {
+                ProgressBar::new(f32::from(percent) / 100.0).show_percentage()
+            },
+        };
+        ui.add(progress_bar);
+
+        ui.add_space(16.0);
+
+        ui.vertical_centered_justified(|ui| {
+            let is_cancelled = self.cancel_flag.load(Ordering::Acquire);
+            let text = match is_cancelled {
+                true => t!("Modal.Progress.Cancelling"),
+                false => t!("Button.Cancel"),
+            };
+            if ui.add_enabled(!is_cancelled, Button::new(text)).clicked() {
+                self.close();
+            }
+        });
+    }
+
+    // Dialog stays until the worker actually stops
+    fn close(&mut self) {
+        self.cancel_flag.store(true, Ordering::Release);
+    }
+
+    fn modal_fields(&self) -> &ModalFields {
+        &self.modal_fields
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum TaskError {
+    #[error("Failed to start the thread.")]
+    FailedToSpawn(#[from] std::io::Error),
+
+    #[error("Task was aborted without the result.")]
+    Aborted,
+}
+
+impl TaskError {
+    pub fn localized(&self) -> String {
+        match self {
+            Self::FailedToSpawn(err) => {
+                format!("{}: {err}", t!("Error.Task.FailedToSpawn"))
+            },
+            Self::Aborted => t!("Error.Task.Aborted").to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_of() {
+        assert_eq!(Progress::of(0, 0), Progress::Indeterminate);
+        assert_eq!(Progress::of(0, 200), Progress::Percent(0));
+        assert_eq!(Progress::of(50, 200), Progress::Percent(25));
+        assert_eq!(Progress::of(300, 200), Progress::Percent(100));
+    }
+
+    #[test]
+    fn test_cancel_stops_worker() {
+        let (modals_tx, modals_rx) = unbounded::<Box<dyn Modal>>();
+        let task = BackgroundTask::spawn("Test".to_string(), &modals_tx, |reporter| {
+            while !reporter.is_cancelled() {
+                reporter.report(Progress::Indeterminate);
+                thread::sleep(std::time::Duration::from_millis(1));
+            }
+        })
+        .unwrap();
+        assert!(modals_rx.try_recv().is_ok());
+        assert!(task.try_result().is_none());
+
+        // Result is delivered only after the worker leaves its loop
+        task.cancel();
+        assert!(task.result_rx.recv().is_ok());
+    }
+}
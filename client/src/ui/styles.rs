@@ -1,17 +1,392 @@
-pub const TIME_FORMAT: &str = "%m/%d %H:%M:%S";
-
 pub mod colors {
-    pub const SILENT: egui::Color32 = egui::Color32::GRAY;
+    use core::fmt;
+    use egui::Color32;
+    use egui_plot::LineStyle;
+    use std::str::FromStr;
+    use std::sync::atomic::{AtomicU8, Ordering};
+    use strum_macros::EnumIter;
+
+    // Colors of the series, that go one after another. The next ones repeat them
+    pub const SERIES: usize = 8;
+
+    // Chosen by the user. It's read by every frame, so it isn't passed around
+    static PALETTE: AtomicU8 = AtomicU8::new(0);
+
+    #[derive(Default, Copy, Clone, EnumIter, PartialEq, Eq, Debug)]
+    pub enum Palette {
+        #[default]
+        Default,
+        Deuteranopia,
+        HighContrast,
+    }
+
+    /// Purpose of the color. The UI asks for the role, so the palette decides the color.
+    #[derive(Copy, Clone, EnumIter, PartialEq, Eq, Debug)]
+    pub enum Role {
+        Silent,
+
+        Enabled,
+        Disabled,
+
+        FieldNotApplied,
+        FieldInvalid,
+
+        OverLimit,
+        IpConflict,
+
+        Crosshair,
+
+        MarkerAlert,
+        MarkerConnection,
+        MarkerInterface,
+        MarkerManual,
+
+        TimeRange,
+        TimeRangeSelection,
+
+        Sampled,
+        SampledArea,
+
+        Outdated,
+        OutdatedDark,
+        Updated,
+        UpdatedDark,
+
+        InterfaceActive,
+
+        Connected,
+        Connecting,
+        Degraded,
+        Replaying,
+        Disconnected,
+        ConnectionError,
+        WritingFailed,
+
+        WarningBackground,
+        WarningText,
+
+        // Syslog, timeline & alert severities, the informational ones aren't colored
+        SeverityCritical,
+        SeverityError,
+        SeverityWarning,
+        SeverityDebug,
+
+        IdleBackground,
+        IdleText,
+
+        SnapshotNew,
+        SnapshotGone,
+        SnapshotChanged,
+    }
+
+    pub fn set_palette(palette: Palette) {
+        PALETTE.store(palette as u8, Ordering::Relaxed);
+    }
+
+    pub fn palette() -> Palette {
+        match PALETTE.load(Ordering::Relaxed) {
+            1 => Palette::Deuteranopia,
+            2 => Palette::HighContrast,
+            _ => Palette::Default,
+        }
+    }
+
+    /// Color of the role in the chosen palette.
+    pub fn get(role: Role) -> Color32 {
+        palette().color(role)
+    }
+
+    /// Color of the plot series (line, bar) in the chosen palette.
+    pub fn series(index: usize) -> Color32 {
+        palette().series(index)
+    }
+
+    /// Lines are told apart by the dash style too, so they're readable in grayscale.
+    pub fn series_style(index: usize) -> LineStyle {
+        match index % 3 {
+            0 => LineStyle::Solid,
+            1 => LineStyle::dashed_loose(),
+            _ => LineStyle::dotted_dense(),
+        }
+    }
+
+    impl Palette {
+        pub fn localize(&self) -> String {
+            match self {
+                Palette::Default => t!("Palette.Default"),
+                Palette::Deuteranopia => t!("Palette.Deuteranopia"),
+                Palette::HighContrast => t!("Palette.HighContrast"),
+            }
+            .to_string()
+        }
+
+        pub fn color(&self, role: Role) -> Color32 {
+            let color = match self {
+                Palette::Default => None,
+                Palette::Deuteranopia => deuteranopia(role),
+                Palette::HighContrast => high_contrast(role),
+            };
+
+            color.unwrap_or_else(|| standard(role))
+        }
 
-    pub const ENABLED: egui::Color32 = egui::Color32::GREEN;
-    pub const DISABLED: egui::Color32 = egui::Color32::RED;
+        pub fn series(&self, index: usize) -> Color32 {
+            let series = match self {
+                Palette::Default => &STANDARD_SERIES,
+                Palette::Deuteranopia => &DEUTERANOPIA_SERIES,
+                Palette::HighContrast => &HIGH_CONTRAST_SERIES,
+            };
 
-    pub const FIELD_NOT_APPLIED: egui::Color32 = egui::Color32::RED;
+            series[index % SERIES]
+        }
+    }
 
-    pub const OUTDATED: egui::Color32 = egui::Color32::RED;
-    pub const OUTDATED_DARK: egui::Color32 = egui::Color32::DARK_RED;
-    pub const UPDATED: egui::Color32 = egui::Color32::GREEN;
-    pub const UPDATED_DARK: egui::Color32 = egui::Color32::DARK_GREEN;
+    impl fmt::Display for Palette {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let string = match self {
+                Palette::Default => "default",
+                Palette::Deuteranopia => "deuteranopia",
+                Palette::HighContrast => "high_contrast",
+            };
+            write!(f, "{string}")
+        }
+    }
+
+    impl FromStr for Palette {
+        type Err = ();
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "default" => Ok(Palette::Default),
+                "deuteranopia" => Ok(Palette::Deuteranopia),
+                "high_contrast" => Ok(Palette::HighContrast),
+                _ => Err(()),
+            }
+        }
+    }
+
+    // Okabe & Ito colors, that stay distinct without the red-green difference
+    const OKABE_ITO_ORANGE: Color32 = Color32::from_rgb(230, 159, 0);
+    const OKABE_ITO_SKY_BLUE: Color32 = Color32::from_rgb(86, 180, 233);
+    const OKABE_ITO_GREEN: Color32 = Color32::from_rgb(0, 158, 115);
+    const OKABE_ITO_YELLOW: Color32 = Color32::from_rgb(240, 228, 66);
+    const OKABE_ITO_BLUE: Color32 = Color32::from_rgb(0, 114, 178);
+    const OKABE_ITO_VERMILLION: Color32 = Color32::from_rgb(213, 94, 0);
+    const OKABE_ITO_PURPLE: Color32 = Color32::from_rgb(204, 121, 167);
+
+    const STANDARD_SERIES: [Color32; SERIES] = [
+        Color32::from_rgb(100, 170, 255),
+        Color32::from_rgb(255, 160, 60),
+        Color32::from_rgb(120, 220, 120),
+        Color32::from_rgb(230, 100, 100),
+        Color32::from_rgb(200, 140, 255),
+        Color32::from_rgb(240, 220, 90),
+        Color32::from_rgb(90, 220, 220),
+        Color32::from_rgb(255, 130, 200),
+    ];
+    const DEUTERANOPIA_SERIES: [Color32; SERIES] = [
+        OKABE_ITO_SKY_BLUE,
+        OKABE_ITO_ORANGE,
+        OKABE_ITO_BLUE,
+        OKABE_ITO_YELLOW,
+        OKABE_ITO_VERMILLION,
+        OKABE_ITO_GREEN,
+        OKABE_ITO_PURPLE,
+        Color32::from_rgb(153, 153, 153),
+    ];
+    const HIGH_CONTRAST_SERIES: [Color32; SERIES] = [
+        Color32::from_rgb(0, 160, 255),
+        Color32::from_rgb(255, 140, 0),
+        Color32::from_rgb(255, 0, 255),
+        Color32::from_rgb(0, 210, 0),
+        Color32::from_rgb(255, 230, 0),
+        Color32::from_rgb(255, 40, 40),
+        Color32::from_rgb(0, 230, 230),
+        Color32::from_rgb(160, 160, 160),
+    ];
+
+    fn standard(role: Role) -> Color32 {
+        match role {
+            Role::Silent => Color32::GRAY,
+
+            Role::Enabled => Color32::GREEN,
+            Role::Disabled => Color32::RED,
+
+            Role::FieldNotApplied => Color32::RED,
+            Role::FieldInvalid => Color32::RED,
+
+            Role::OverLimit => Color32::ORANGE,
+            Role::IpConflict => Color32::ORANGE,
+
+            Role::Crosshair => Color32::LIGHT_GRAY,
+
+            Role::MarkerAlert => Color32::RED,
+            Role::MarkerConnection => Color32::YELLOW,
+            Role::MarkerInterface => Color32::LIGHT_BLUE,
+            Role::MarkerManual => Color32::LIGHT_GREEN,
+
+            Role::TimeRange => Color32::LIGHT_BLUE,
+            Role::TimeRangeSelection => Color32::from_rgba_premultiplied(40, 70, 110, 60),
+
+            Role::Sampled => Color32::ORANGE,
+            Role::SampledArea => Color32::from_rgba_premultiplied(90, 60, 10, 50),
+
+            Role::Outdated => Color32::RED,
+            Role::OutdatedDark => Color32::DARK_RED,
+            Role::Updated => Color32::GREEN,
+            Role::UpdatedDark => Color32::DARK_GREEN,
+
+            Role::InterfaceActive => Color32::LIGHT_BLUE,
+
+            Role::Connected => Color32::GREEN,
+            Role::Connecting => Color32::YELLOW,
+            Role::Degraded => Color32::ORANGE,
+            Role::Replaying => Color32::LIGHT_BLUE,
+            Role::Disconnected => Color32::GRAY,
+            Role::ConnectionError => Color32::RED,
+            Role::WritingFailed => Color32::RED,
+
+            Role::WarningBackground => Color32::DARK_RED,
+            Role::WarningText => Color32::WHITE,
+
+            Role::SeverityCritical => Color32::RED,
+            Role::SeverityError => Color32::from_rgb(255, 100, 60),
+            Role::SeverityWarning => Color32::ORANGE,
+            Role::SeverityDebug => Color32::GRAY,
+
+            Role::IdleBackground => Color32::from_rgb(110, 90, 0),
+            Role::IdleText => Color32::YELLOW,
+
+            Role::SnapshotNew => Color32::GREEN,
+            Role::SnapshotGone => Color32::RED,
+            Role::SnapshotChanged => Color32::ORANGE,
+        }
+    }
+
+    // Red & green pairs go to vermillion & blue, the rest is taken from the standard one
+    fn deuteranopia(role: Role) -> Option<Color32> {
+        let color = match role {
+            Role::Enabled
+            | Role::Updated
+            | Role::Connected
+            | Role::SnapshotNew
+            | Role::MarkerManual => OKABE_ITO_SKY_BLUE,
+            Role::UpdatedDark => OKABE_ITO_BLUE,
+
+            Role::Disabled
+            | Role::FieldNotApplied
+            | Role::FieldInvalid
+            | Role::MarkerAlert
+            | Role::Outdated
+            | Role::ConnectionError
+            | Role::WritingFailed
+            | Role::SeverityCritical
+            | Role::SnapshotGone => OKABE_ITO_VERMILLION,
+            Role::OutdatedDark | Role::WarningBackground => Color32::from_rgb(120, 50, 0),
+
+            Role::OverLimit
+            | Role::IpConflict
+            | Role::Sampled
+            | Role::Degraded
+            | Role::SeverityError => OKABE_ITO_ORANGE,
+            Role::SeverityWarning | Role::SnapshotChanged => OKABE_ITO_YELLOW,
+
+            Role::MarkerInterface => OKABE_ITO_PURPLE,
+            _ => return None,
+        };
+
+        Some(color)
+    }
+
+    // Saturated colors & plain backgrounds, the text stands out on any of them
+    fn high_contrast(role: Role) -> Option<Color32> {
+        let color = match role {
+            Role::Silent | Role::Crosshair | Role::SeverityDebug => Color32::LIGHT_GRAY,
+
+            Role::Enabled | Role::Updated | Role::Connected | Role::SnapshotNew => {
+                Color32::from_rgb(0, 255, 0)
+            },
+            Role::UpdatedDark => Color32::from_rgb(0, 150, 0),
+
+            Role::Disabled
+            | Role::FieldNotApplied
+            | Role::FieldInvalid
+            | Role::MarkerAlert
+            | Role::Outdated
+            | Role::ConnectionError
+            | Role::WritingFailed
+            | Role::SeverityCritical
+            | Role::SnapshotGone => Color32::from_rgb(255, 60, 60),
+            Role::OutdatedDark => Color32::from_rgb(200, 0, 0),
+
+            Role::SeverityError => Color32::from_rgb(255, 0, 255),
+            Role::OverLimit
+            | Role::IpConflict
+            | Role::Sampled
+            | Role::Degraded
+            | Role::SeverityWarning
+            | Role::SnapshotChanged
+            | Role::IdleText
+            | Role::MarkerConnection
+            | Role::Connecting => Color32::YELLOW,
+
+            Role::MarkerInterface
+            | Role::TimeRange
+            | Role::InterfaceActive
+            | Role::Replaying => Color32::from_rgb(0, 255, 255),
+            Role::MarkerManual => Color32::WHITE,
+
+            Role::TimeRangeSelection => Color32::from_rgba_premultiplied(0, 90, 90, 90),
+            Role::SampledArea => Color32::from_rgba_premultiplied(110, 100, 0, 80),
+
+            Role::WarningBackground | Role::IdleBackground => Color32::BLACK,
+            Role::WarningText => Color32::YELLOW,
+            _ => return None,
+        };
+
+        Some(color)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use strum::IntoEnumIterator;
+
+        #[test]
+        fn test_series_distinct() {
+            for palette in Palette::iter() {
+                let colors: Vec<Color32> =
+                    (0..SERIES).map(|index| palette.series(index)).collect();
+                for (index, color) in colors.iter().enumerate() {
+                    assert!(
+                        !colors[index + 1..].contains(color),
+                        "{palette:?}: series {index} is repeated"
+                    );
+                }
+                // Next ones go in a circle
+                assert_eq!(palette.series(SERIES), palette.series(0));
+            }
+        }
+
+        #[test]
+        fn test_paired_roles_distinct() {
+            let pairs = [
+                (Role::Enabled, Role::Disabled),
+                (Role::Updated, Role::Outdated),
+                (Role::UpdatedDark, Role::OutdatedDark),
+                (Role::SnapshotNew, Role::SnapshotGone),
+                (Role::Connected, Role::ConnectionError),
+                (Role::WarningText, Role::WarningBackground),
+                (Role::IdleText, Role::IdleBackground),
+            ];
+            for palette in Palette::iter() {
+                for (first, second) in pairs {
+                    assert_ne!(palette.color(first), palette.color(second));
+                }
+                assert_eq!(palette.to_string().parse::<Palette>(), Ok(palette));
+            }
+        }
+    }
 }
 
 pub mod heading {
@@ -33,6 +408,317 @@ pub mod heading {
     }
 }
 
+pub mod layout {
+    // Narrower panels get the wrapped rows instead of the grids & columns
+    pub const NARROW_WIDTH: f32 = 700.0;
+
+    pub fn is_narrow(ui: &egui::Ui) -> bool {
+        ui.available_width() < NARROW_WIDTH
+    }
+
+    /// Rows of the settings, shown by the grid. <br>
+    /// If the panel is narrow, every row is wrapped instead,
+    /// so the controls go under the label when they don't fit.
+    #[derive(Clone, Copy)]
+    pub struct Rows {
+        is_narrow: bool,
+    }
+
+    impl Rows {
+        pub fn show(
+            ui: &mut egui::Ui, grid: egui::Grid,
+            add_rows: impl FnOnce(&mut egui::Ui, Rows),
+        ) {
+            if is_narrow(ui) {
+                ui.vertical(|ui| add_rows(ui, Rows { is_narrow: true }));
+            } else {
+                grid.show(ui, |ui| add_rows(ui, Rows { is_narrow: false }));
+            }
+        }
+
+        pub fn row(self, ui: &mut egui::Ui, add_contents: impl FnOnce(&mut egui::Ui)) {
+            if self.is_narrow {
+                ui.horizontal_wrapped(add_contents);
+                ui.separator();
+            } else {
+                add_contents(ui);
+                ui.end_row();
+            }
+        }
+    }
+}
+
+pub mod protocols {
+    use crate::ui::styles::colors;
+    use crate::ui::styles::colors::Palette;
+    use dpi::protocols::ProtocolId;
+    use egui::{Color32, RichText};
+    use std::collections::BTreeMap;
+    use strum::IntoEnumIterator;
+
+    // Colors chosen by user, keyed by the protocol name
+    pub type Overrides = BTreeMap<String, Color32>;
+
+    // WCAG contrast ratio for the large text, against the panel background
+    const MIN_CONTRAST: f32 = 3.0;
+    const BLEND_STEPS: u8 = 10;
+
+    pub fn glyph(protocol: &ProtocolId) -> &'static str {
+        match protocol {
+            ProtocolId::Arp => "❓",
+            ProtocolId::DHCPv4 | ProtocolId::DHCPv6 => "🎫",
+            ProtocolId::DNS => "📖",
+            ProtocolId::Ethernet => "🖧",
+            ProtocolId::HTTP => "🌐",
+            ProtocolId::ICMPv4 | ProtocolId::ICMPv6 => "📣",
+            ProtocolId::IEEE80211 => "📶",
+            ProtocolId::IPv4 | ProtocolId::IPv6 => "🗺",
+            ProtocolId::NBNS => "🏷",
+            ProtocolId::Radiotap => "📡",
+            ProtocolId::RTP => "🎵",
+            ProtocolId::SIP => "☎",
+            ProtocolId::SMB2 => "🗀",
+            ProtocolId::Syslog => "📜",
+            ProtocolId::TCP => "🔗",
+            ProtocolId::TFTP => "💾",
+            ProtocolId::UDP => "✉",
+        }
+    }
+
+    /// Palette of the protocol: First color is for dark themes, second is for light ones.
+    pub fn palette(protocol: &ProtocolId) -> (Color32, Color32) {
+        match protocol {
+            ProtocolId::Arp => (
+                Color32::from_rgb(255, 203, 107),
+                Color32::from_rgb(156, 101, 0),
+            ),
+            ProtocolId::DHCPv4 => (
+                Color32::from_rgb(195, 232, 141),
+                Color32::from_rgb(70, 120, 20),
+            ),
+            ProtocolId::DHCPv6 => (
+                Color32::from_rgb(160, 220, 120),
+                Color32::from_rgb(40, 105, 40),
+            ),
+            ProtocolId::DNS => (
+                Color32::from_rgb(130, 170, 255),
+                Color32::from_rgb(30, 80, 190),
+            ),
+            ProtocolId::Ethernet => (
+                Color32::from_rgb(190, 190, 190),
+                Color32::from_rgb(85, 85, 85),
+            ),
+            ProtocolId::HTTP => (
+                Color32::from_rgb(100, 220, 200),
+                Color32::from_rgb(0, 120, 110),
+            ),
+            ProtocolId::ICMPv4 => (
+                Color32::from_rgb(255, 140, 140),
+                Color32::from_rgb(180, 30, 30),
+            ),
+            ProtocolId::ICMPv6 => (
+                Color32::from_rgb(255, 120, 180),
+                Color32::from_rgb(170, 20, 100),
+            ),
+            ProtocolId::IEEE80211 => (
+                Color32::from_rgb(240, 160, 255),
+                Color32::from_rgb(130, 40, 160),
+            ),
+            ProtocolId::IPv4 => (
+                Color32::from_rgb(137, 221, 255),
+                Color32::from_rgb(0, 105, 160),
+            ),
+            ProtocolId::IPv6 => (
+                Color32::from_rgb(120, 190, 255),
+                Color32::from_rgb(20, 90, 180),
+            ),
+            ProtocolId::NBNS => (
+                Color32::from_rgb(255, 220, 150),
+                Color32::from_rgb(140, 95, 20),
+            ),
+            ProtocolId::Radiotap => (
+                Color32::from_rgb(210, 170, 255),
+                Color32::from_rgb(100, 60, 170),
+            ),
+            ProtocolId::RTP => (
+                Color32::from_rgb(230, 190, 150),
+                Color32::from_rgb(125, 80, 40),
+            ),
+            ProtocolId::SIP => (
+                Color32::from_rgb(150, 240, 180),
+                Color32::from_rgb(0, 120, 60),
+            ),
+            ProtocolId::SMB2 => (
+                Color32::from_rgb(255, 170, 100),
+                Color32::from_rgb(170, 80, 0),
+            ),
+            ProtocolId::Syslog => (
+                Color32::from_rgb(220, 200, 120),
+                Color32::from_rgb(120, 100, 10),
+            ),
+            ProtocolId::TCP => (
+                Color32::from_rgb(199, 146, 234),
+                Color32::from_rgb(110, 50, 160),
+            ),
+            ProtocolId::TFTP => (
+                Color32::from_rgb(170, 200, 230),
+                Color32::from_rgb(50, 90, 130),
+            ),
+            ProtocolId::UDP => (
+                Color32::from_rgb(247, 140, 108),
+                Color32::from_rgb(170, 60, 30),
+            ),
+        }
+    }
+
+    /// Color chosen by user or the palette one for the current theme.
+    /// <br> Accessible palettes replace the protocol colors by their series.
+    pub fn base_color(
+        ui: &egui::Ui, protocol: &ProtocolId, overrides: &Overrides,
+    ) -> Color32 {
+        if let Some(color) = overrides.get(&protocol.to_string()) {
+            return *color;
+        }
+
+        match colors::palette() {
+            Palette::Default => {
+                let (dark, light) = palette(protocol);
+                if ui.visuals().dark_mode { dark } else { light }
+            },
+            accessible => {
+                let index = ProtocolId::iter()
+                    .position(|id| id == *protocol)
+                    .unwrap_or_default();
+                accessible.series(index)
+            },
+        }
+    }
+
+    /// Color of the protocol in the current theme. User colors are adjusted,
+    /// if they are hard to read on the theme background.
+    pub fn color(ui: &egui::Ui, protocol: &ProtocolId, overrides: &Overrides) -> Color32 {
+        readable(base_color(ui, protocol, overrides), ui.visuals().panel_fill)
+    }
+
+    pub fn title(protocol: &ProtocolId, name: &str) -> String {
+        format!("{} {name}", glyph(protocol))
+    }
+
+    /// Glyph & name of the protocol, colored.
+    pub fn label(
+        ui: &egui::Ui, protocol: &ProtocolId, name: &str, overrides: &Overrides,
+    ) -> RichText {
+        RichText::new(title(protocol, name)).color(color(ui, protocol, overrides))
+    }
+
+    /// Blends the color towards white or black, until it's readable on the background.
+    pub fn readable(color: Color32, background: Color32) -> Color32 {
+        let target = if contrast(Color32::WHITE, background)
+            > contrast(Color32::BLACK, background)
+        {
+            Color32::WHITE
+        } else {
+            Color32::BLACK
+        };
+
+        for step in 0..=BLEND_STEPS {
+            let candidate = blend(color, target, step as f32 / BLEND_STEPS as f32);
+            if contrast(candidate, background) >= MIN_CONTRAST {
+                return candidate;
+            }
+        }
+
+        target
+    }
+
+    fn blend(from: Color32, to: Color32, ratio: f32) -> Color32 {
+        let channel = |from: u8, to: u8| -> u8 {
+            (from as f32 + (to as f32 - from as f32) * ratio).round() as u8
+        };
+
+        Color32::from_rgb(
+            channel(from.r(), to.r()),
+            channel(from.g(), to.g()),
+            channel(from.b(), to.b()),
+        )
+    }
+
+    fn contrast(first: Color32, second: Color32) -> f32 {
+        let (first, second) = (luminance(first), luminance(second));
+        let (lighter, darker) = if first > second {
+            (first, second)
+        } else {
+            (second, first)
+        };
+
+        (lighter + 0.05) / (darker + 0.05)
+    }
+
+    // Relative luminance of sRGB color
+    fn luminance(color: Color32) -> f32 {
+        let linear = |channel: u8| -> f32 {
+            let channel = channel as f32 / 255.0;
+            if channel <= 0.04045 {
+                channel / 12.92
+            } else {
+                ((channel + 0.055) / 1.055).powf(2.4)
+            }
+        };
+
+        0.2126 * linear(color.r())
+            + 0.7152 * linear(color.g())
+            + 0.0722 * linear(color.b())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_readable() {
+            let dark_background = Color32::from_rgb(27, 27, 27);
+            let light_background = Color32::from_rgb(248, 248, 248);
+
+            // Palettes are readable as is
+            let (dark, light) = palette(&ProtocolId::DNS);
+            assert_eq!(readable(dark, dark_background), dark);
+            assert_eq!(readable(light, light_background), light);
+
+            // Dark blue on the dark background gets lighter
+            let color = Color32::from_rgb(30, 80, 190);
+            let adjusted = readable(color, dark_background);
+            assert_ne!(adjusted, color);
+            assert!(contrast(adjusted, dark_background) >= MIN_CONTRAST);
+
+            let adjusted = readable(Color32::YELLOW, light_background);
+            assert!(contrast(adjusted, light_background) >= MIN_CONTRAST);
+        }
+    }
+}
+
+pub mod devices {
+    use crate::net::device::DeviceTag;
+    use crate::ui::styles::protocols;
+    use egui::RichText;
+
+    // Shown instead of the note, if only the color is set
+    const COLOR_ONLY: &str = "⏺";
+
+    /// Chip with the note of the device, filled with its color.
+    pub fn tag_chip(ui: &mut egui::Ui, tag: &DeviceTag) {
+        let chip = match (&tag.note, tag.color) {
+            (Some(note), Some(color)) => RichText::new(format!(" {note} "))
+                .background_color(color)
+                .color(protocols::readable(ui.visuals().strong_text_color(), color)),
+            (Some(note), None) => RichText::new(format!(" {note} "))
+                .background_color(ui.visuals().widgets.inactive.weak_bg_fill),
+            (None, Some(color)) => RichText::new(COLOR_ONLY).color(color),
+            (None, None) => return,
+        };
+        ui.label(chip);
+    }
+}
+
 pub mod space {
     pub const SMALL: f32 = 10.0;
     pub const TAB: f32 = 13.0;
@@ -86,15 +772,16 @@ pub mod spacing {
 
 pub mod text {
     use crate::ui::styles::colors;
+    use crate::ui::styles::colors::Role;
     use egui::RichText;
 
     pub const SMALL: f32 = 10.0;
 
     pub fn is_enabled(is_enabled: bool) -> RichText {
         if is_enabled {
-            RichText::new(t!("Button.State.Enabled")).color(colors::ENABLED)
+            RichText::new(t!("Button.State.Enabled")).color(colors::get(Role::Enabled))
         } else {
-            RichText::new(t!("Button.State.Disabled")).color(colors::DISABLED)
+            RichText::new(t!("Button.State.Disabled")).color(colors::get(Role::Disabled))
         }
     }
 
@@ -110,7 +797,7 @@ pub mod text {
         ui: &mut egui::Ui, mut label: RichText, is_not_applied: bool,
     ) -> egui::Response {
         if is_not_applied {
-            label = label.color(colors::FIELD_NOT_APPLIED);
+            label = label.color(colors::get(Role::FieldNotApplied));
             ui.add(egui::Label::new(label))
                 .on_hover_text(t!("Styles.Hover.FieldNotApplied"))
         } else {
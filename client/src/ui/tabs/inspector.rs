@@ -1,28 +1,227 @@
 use crate::context::Context;
+use crate::net::arp::{ArpPairing, Outcome};
+use crate::net::conversation::{
+    Conversation, ConversationIndex, ConversationSource, Endpoint,
+};
+use crate::net::device::{DeviceAliases, DeviceStorage};
+use crate::net::filter;
+use crate::net::filter::{
+    Filter, FilterField, Filterable, FilteredIndices, RangeCount, TimeRange,
+};
+use crate::net::inspector::{InspectorStorage, ProtocolsRegistered};
+use crate::net::memory::Records;
+use crate::net::notes::{ClearedGeneration, RecordId, RecordNotes};
+use crate::net::search::{SearchQuery, SearchResults, SearchSnapshot};
+use crate::net::ttl;
+use crate::ui::components::record_menu::{RecordAction, RowMenu};
+use crate::ui::format;
+use crate::ui::modals::frame::FrameModal;
+use crate::ui::modals::message::MessageModal;
+use crate::ui::modals::note::{NoteModal, OrphanedNotesModal};
+use crate::ui::modals::progress::{BackgroundTask, Progress};
 use crate::ui::styles;
+use crate::ui::styles::colors::Role;
 use crate::ui::tabs::Tab;
+use crate::ws::data::Locator;
+use chrono::{DateTime, Local};
+use dpi::dto::fields::FieldIter;
 use dpi::protocols::ProtocolId;
-use dpi::protocols::http::HttpDto;
-use egui::{Grid, RichText, ScrollArea};
+use dpi::protocols::arp::{ArpDto, HardwareAddress, Operation};
+use dpi::protocols::dns::ResourceRecord;
+use dpi::protocols::ethernet::mac::MacAddress;
+use dpi::protocols::http::{BodyAnomaly, HttpDto};
+use dpi::protocols::sip::SipDto;
+use dpi::protocols::syslog::Severity;
+use dpi::protocols::tftp::Opcode;
+use egui::{DragValue, Grid, RichText, ScrollArea};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::mem;
+use std::ops::{ControlFlow, RangeInclusive};
+use std::time::Instant;
 use strum::IntoEnumIterator;
 
+pub const PAGE_SIZE_DEFAULT: usize = 100;
+// Big records (e.g. DNS) are slow in the hundreds, the dense tables are fine with more
+pub const PAGE_SIZE_RANGE: RangeInclusive<usize> = 25..=1000;
+
 pub struct InspectorTab {
     protocol_chosen: ProtocolId,
+    // Copy of the user colors, not every view gets the context
+    protocol_colors: styles::protocols::Overrides,
     page: usize,
+    // Copy of the setting, the page is recounted when it's changed
+    page_size: usize,
+
+    filter: Filter,
+    filter_input: String,
+    filtered: FilteredIndices,
+    time_range: Option<TimeRange>,
+
+    // Shown in the protocol list: total amount & amount in the time range
+    counts: HashMap<ProtocolsRegistered, (usize, Option<usize>)>,
+    range_counts: HashMap<ProtocolsRegistered, RangeCount>,
+    // Records of the chosen protocol, that match the filter
+    matched: Option<usize>,
+
+    // Tables, where both directions are merged into one row
+    combined: Vec<ProtocolId>,
+    conversations: ConversationIndex,
+
+    // Record, which note or frame is opened by the row buttons
+    requested: RowRequests,
+    // Chosen in the context menu of the row
+    record_action: Option<RecordAction>,
+    // Cleared storages, their notes are orphaned
+    cleared: Vec<ClearedGeneration>,
+
+    // DNS records with the elapsed TTL aren't shown
+    is_expired_hidden: bool,
+
+    page_cache: PageCache,
+
+    // Search across all the protocols, by the fields of their filters
+    search_input: String,
+    is_search_regex: bool,
+    search_error: Option<String>,
+    search_task: Option<BackgroundTask<Option<SearchResults>>>,
+    search_results: Option<SearchResults>,
+    // Requested by the shortcut, the field takes the focus with the next frame
+    is_search_focus_requested: bool,
+}
+
+// Matches of the protocol, that are listed in the search results
+const SEARCH_ROWS_SHOWN: usize = 20;
+
+// Opened after the table is drawn, the views borrow the storage
+#[derive(Default)]
+struct RowRequests {
+    note: Option<RecordId>,
+    // Layer stack of the record's frame
+    frame: Option<RecordId>,
+}
+
+// Records, that keep the addresses of the frame
+trait Located {
+    fn locator(&self) -> &Locator;
+}
+
+impl Located for Locator {
+    fn locator(&self) -> &Locator {
+        self
+    }
+}
+
+impl<T> Located for (T, Locator) {
+    fn locator(&self) -> &Locator {
+        &self.1
+    }
+}
+
+/// Formatted cells of the visible records, so the fields aren't formatted every frame.
+/// Records aren't changed after the push, so the cells are valid until the storage is cleared.
+#[derive(Default)]
+struct PageCache {
+    // Protocol, storage generation & page
+    key: Option<(ProtocolId, u64, usize)>,
+    headings: Vec<String>,
+    // By the record number
+    rows: HashMap<usize, Vec<String>>,
+}
+
+impl PageCache {
+    fn validate(
+        &mut self, protocol: ProtocolId, generation: u64, page: usize, page_size: usize,
+    ) {
+        let key = Some((protocol, generation, page));
+        // Filter changes the records of the page, only the visible ones are kept
+        if self.key != key || self.rows.len() > page_size {
+            self.key = key;
+            self.headings.clear();
+            self.rows.clear();
+        }
+    }
+}
+
+/// Remaining TTL of the DNS records, counted from the capture of the packet to the render.
+#[derive(Clone, Copy)]
+struct TtlClock {
+    captured: Option<DateTime<Local>>,
+    now: DateTime<Local>,
+    is_expired_hidden: bool,
+}
+
+impl TtlClock {
+    // OPT pseudo-record has the flags instead of the TTL
+    fn remaining(&self, record: &ResourceRecord) -> Option<u32> {
+        record.class.as_ref()?;
+        self.captured
+            .map(|captured| ttl::remaining(record.time_to_live, &captured, &self.now))
+    }
+}
+
+/// Opens the protocol, e.g. from the timeline. Records may be filtered by the device.
+pub struct InspectorRequest {
+    pub protocol: ProtocolId,
+    pub mac: Option<MacAddress>,
 }
 
 impl Default for InspectorTab {
     fn default() -> Self {
         Self {
             protocol_chosen: ProtocolId::Arp,
+            protocol_colors: Default::default(),
             page: 1,
+            page_size: PAGE_SIZE_DEFAULT,
+
+            filter: Default::default(),
+            filter_input: String::new(),
+            filtered: Default::default(),
+            time_range: None,
+
+            counts: HashMap::new(),
+            range_counts: HashMap::new(),
+            matched: None,
+
+            combined: vec![],
+            conversations: Default::default(),
+
+            requested: Default::default(),
+            record_action: None,
+            cleared: vec![],
+
+            is_expired_hidden: false,
+
+            page_cache: Default::default(),
+
+            search_input: String::new(),
+            is_search_regex: false,
+            search_error: None,
+            search_task: None,
+            search_results: None,
+            is_search_focus_requested: false,
         }
     }
 }
 
 impl InspectorTab {
     pub fn show(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        if self.protocol_colors != ctx.client_settings.protocol_colors {
+            self.protocol_colors = ctx.client_settings.protocol_colors.clone();
+        }
+        if self.page_size != ctx.client_settings.inspector_page_size {
+            let page_size = ctx.client_settings.inspector_page_size;
+            self.page = Self::page_resized(self.page, self.page_size, page_size);
+            self.page_size = page_size;
+        }
+        if let Some(request) = ctx.inspector_request.take() {
+            self.open(request);
+        }
+
         self.tab_heading(ui, ctx);
+        self.search_results_view(ui, ctx);
+        self.time_range_view(ui, ctx);
+        self.refresh_counts(&ctx.net_storage.inspector);
 
         match self.protocol_chosen {
             ProtocolId::Arp => self.arp_view(ui, ctx),
@@ -31,24 +230,90 @@ impl InspectorTab {
             ProtocolId::DNS => self.dns_view(ui, ctx),
             ProtocolId::Ethernet => self.ethernet_view(ui, ctx),
             ProtocolId::HTTP => self.http_view(ui, ctx),
-            ProtocolId::ICMPv4 => self.icmpv4_view(ui, ctx),
-            ProtocolId::ICMPv6 => self.icmpv6_view(ui, ctx),
-            ProtocolId::IPv4 => self.ipv4_view(ui, ctx),
-            ProtocolId::IPv6 => self.ipv6_view(ui, ctx),
+            ProtocolId::IEEE80211 | ProtocolId::Radiotap => self.ieee80211_view(ui, ctx),
+            ProtocolId::NBNS => self.nbns_view(ui, ctx),
+            ProtocolId::RTP => self.rtp_view(ui, ctx),
+            ProtocolId::SIP => self.sip_view(ui, ctx),
+            ProtocolId::SMB2 => self.smb2_view(ui, ctx),
+            ProtocolId::Syslog => self.syslog_view(ui, ctx),
+            ProtocolId::TFTP => self.tftp_view(ui, ctx),
+
+            // No bespoke view, fields are shown as is
+            ProtocolId::ICMPv4 => self.fields_view(
+                ui,
+                &mut ctx.net_storage.inspector.icmpv4,
+                &ctx.net_storage.notes,
+                &ctx.net_storage.devices,
+                "Inspector.ICMPv4.Packets",
+                true,
+            ),
+            ProtocolId::ICMPv6 => self.fields_view(
+                ui,
+                &mut ctx.net_storage.inspector.icmpv6,
+                &ctx.net_storage.notes,
+                &ctx.net_storage.devices,
+                "Inspector.ICMPv6.Packets",
+                true,
+            ),
+            ProtocolId::IPv4 => self.fields_view(
+                ui,
+                &mut ctx.net_storage.inspector.ipv4,
+                &ctx.net_storage.notes,
+                &ctx.net_storage.devices,
+                "Inspector.IPv4.Packets",
+                false,
+            ),
+            ProtocolId::IPv6 => self.fields_view(
+                ui,
+                &mut ctx.net_storage.inspector.ipv6,
+                &ctx.net_storage.notes,
+                &ctx.net_storage.devices,
+                "Inspector.IPv6.Packets",
+                false,
+            ),
             ProtocolId::TCP => self.tcp_view(ui, ctx),
-            ProtocolId::UDP => self.udp_view(ui, ctx),
+            ProtocolId::UDP => self.fields_view(
+                ui,
+                &mut ctx.net_storage.inspector.udp,
+                &ctx.net_storage.notes,
+                &ctx.net_storage.devices,
+                "Inspector.UDP.Packets",
+                true,
+            ),
         };
+
+        if let Some(action) = self.record_action.take() {
+            self.apply(action);
+        }
+        if let Some(id) = self.requested.note.take() {
+            let modal = NoteModal::with_id(id, ctx);
+            if let Err(err) = ctx.modals_tx.try_send(Box::new(modal)) {
+                log::error!("Failed to send modal: {err}");
+            }
+        }
+        if let Some(id) = self.requested.frame.take() {
+            FrameModal::open(id, ctx);
+        }
+        if !self.cleared.is_empty() {
+            OrphanedNotesModal::ask(mem::take(&mut self.cleared), ctx);
+        }
     }
 
-    fn protocol_view<T, F>(
-        &mut self, ui: &mut egui::Ui, storage: &mut Vec<T>, grid_id: &str,
-        num_columns: usize, headings: &[&str], mut render_row: F,
+    fn protocol_view<T: Filterable + Serialize, F>(
+        &mut self, ui: &mut egui::Ui, storage: &mut Records<T>, notes: &RecordNotes,
+        grid_id: &str, num_columns: usize, headings: &[&str], mut render_row: F,
     ) where
-        F: FnMut(&mut egui::Ui, usize, &T),
+        F: FnMut(&mut egui::Ui, &mut RowMenu, usize, &T),
     {
-        if self.clear_pages_buttons(ui, storage) {
+        let Some(indices) = self.page_indices(ui, storage) else {
             return;
-        }
+        };
+
+        let color = self.protocol_color(ui);
+        let record_id = self.record_id(storage);
+        let requested = &mut self.requested;
+        let filters = FilterField::available(&self.protocol_chosen);
+        let record_action = &mut self.record_action;
 
         // Table
         ScrollArea::both()
@@ -56,40 +321,744 @@ impl InspectorTab {
             .show(ui, |ui| {
                 Grid::new(grid_id)
                     .striped(true)
-                    .num_columns(num_columns)
+                    .num_columns(num_columns + 1)
                     .show(ui, |ui| {
                         // Headings row
-                        if !storage.is_empty() {
+                        if !indices.is_empty() {
                             for &h in headings {
-                                ui.label(styles::heading::grid(&t!(h)));
+                                ui.label(styles::heading::grid(&t!(h)).color(color));
                             }
+                            ui.label(
+                                styles::heading::grid(&t!("Tab.Inspector.Label.Note"))
+                                    .color(color),
+                            );
                             ui.end_row();
                         }
 
                         // Data rows
-                        for (id, packet) in
-                            Self::page_slice(storage, self.page).iter().enumerate()
-                        {
-                            render_row(
+                        for index in indices {
+                            if let Some(packet) = storage.get(index) {
+                                let mut menu = RowMenu::default();
+                                render_row(ui, &mut menu, index + 1, packet);
+                                menu.show_record(packet, filters, record_action);
+                                ui.horizontal(|ui| {
+                                    if let Some(id) = record_id(index) {
+                                        Self::record_buttons(ui, notes, id, requested);
+                                    }
+                                    if storage.is_truncated(index) {
+                                        Self::truncated_label(ui);
+                                    }
+                                });
+                                ui.end_row();
+                            }
+                        }
+                    });
+            });
+    }
+
+    /// Generic view: Every field of the record is a column, named by its key.
+    /// <br> IP columns are hidden for the IP records themselves.
+    fn fields_view<T: FieldIter>(
+        &mut self, ui: &mut egui::Ui, storage: &mut Records<(T, Locator)>,
+        notes: &RecordNotes, devices: &DeviceStorage, grid_id: &str, is_ip_shown: bool,
+    ) where
+        (T, Locator): Filterable + Serialize,
+    {
+        if self.is_combined() {
+            self.conversations_view(ui, storage, devices, grid_id, true, None);
+            return;
+        }
+
+        // Taken out, the rows are cached while the view borrows the tab
+        let mut cache = mem::take(&mut self.page_cache);
+        cache.validate(
+            self.protocol_chosen,
+            storage.generation(),
+            self.page,
+            self.page_size,
+        );
+        if cache.headings.is_empty() {
+            if let Some((packet, _)) = storage.first() {
+                cache.headings = Self::field_headings(packet, is_ip_shown);
+            }
+        }
+        let headings = cache
+            .headings
+            .iter()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        let rows = &mut cache.rows;
+
+        self.protocol_view(
+            ui,
+            storage,
+            notes,
+            grid_id,
+            headings.len(),
+            &headings,
+            |ui, menu, id, (packet, locator)| {
+                let cells = rows.entry(id).or_insert_with(|| {
+                    Self::field_cells(id, packet, locator, is_ip_shown)
+                });
+                // IP cells are the last ones
+                let ip_cells = if is_ip_shown { 2 } else { 0 };
+                let (fields, ips) = cells.split_at(cells.len().saturating_sub(ip_cells));
+                for cell in fields {
+                    menu.label(ui, cell.as_str());
+                }
+                for (cell, field) in ips
+                    .iter()
+                    .zip([FilterField::SourceIp, FilterField::DestinationIp])
+                {
+                    menu.filter_label(ui, cell.as_str(), field, cell);
+                }
+                // Aliases & tags may be edited, so they aren't cached
+                let (source_mac, target_mac) = locator.mac_to_string(&devices.aliases);
+                menu.address_label(
+                    ui,
+                    source_mac,
+                    devices.tag(&locator.mac.0),
+                    &locator.mac.0,
+                );
+                menu.address_label(
+                    ui,
+                    target_mac,
+                    devices.tag(&locator.mac.1),
+                    &locator.mac.1,
+                );
+            },
+        );
+        self.page_cache = cache;
+    }
+
+    fn field_headings<T: FieldIter>(packet: &T, is_ip_shown: bool) -> Vec<String> {
+        let mut headings = vec!["Tab.Inspector.Label.Number".to_string()];
+        headings.extend(
+            packet
+                .fields()
+                .into_iter()
+                .map(|(key, _)| format!("Tab.Inspector.Field.{key}")),
+        );
+        if is_ip_shown {
+            headings.push("Tab.Inspector.Protocol.IpSender".to_string());
+            headings.push("Tab.Inspector.Protocol.IpTarget".to_string());
+        }
+        headings.push("Tab.Inspector.Protocol.MacSender".to_string());
+        headings.push("Tab.Inspector.Protocol.MacTarget".to_string());
+        headings
+    }
+
+    fn field_cells<T: FieldIter>(
+        id: usize, packet: &T, locator: &Locator, is_ip_shown: bool,
+    ) -> Vec<String> {
+        let mut cells = vec![id.to_string()];
+        cells.extend(packet.fields().into_iter().map(|(_, value)| value));
+        if is_ip_shown {
+            let (source_ip, target_ip) = locator.ip_to_string();
+            cells.push(source_ip);
+            cells.push(target_ip);
+        }
+        cells
+    }
+
+    // Indices of the records on the current page, `None` if the view must be restarted.
+    fn page_indices<T: Filterable>(
+        &mut self, ui: &mut egui::Ui, storage: &mut Records<T>,
+    ) -> Option<Vec<usize>> {
+        let is_filtered = self.refresh_filtered(ui, storage);
+        let total_items = if is_filtered {
+            self.filtered.indices().len()
+        } else {
+            storage.len()
+        };
+
+        if self.clear_pages_buttons(ui, storage, total_items) {
+            return None;
+        }
+        self.filter_view(ui);
+        self.combine_view(ui);
+
+        let indices = if is_filtered {
+            Self::page_slice(self.filtered.indices(), self.page, self.page_size).to_vec()
+        } else {
+            let start = Self::page_start(self.page, self.page_size);
+            let end = start.saturating_add(self.page_size).min(storage.len());
+            (start..end).collect()
+        };
+
+        Some(indices)
+    }
+
+    // Returns `true` if the records are filtered.
+    fn refresh_filtered<T: Filterable>(
+        &mut self, ui: &egui::Ui, storage: &Records<T>,
+    ) -> bool {
+        let is_matched = self.update_filter(ui);
+        let is_filtered = is_matched || self.time_range.is_some();
+        if is_filtered {
+            let filter = is_matched.then_some(&self.filter);
+            self.filtered
+                .refresh(self.protocol_chosen, storage, filter, self.time_range);
+        }
+        self.matched = is_matched.then(|| self.filtered.indices().len());
+        is_filtered
+    }
+
+    // Amounts of the records of every protocol, so they're seen without choosing it.
+    fn refresh_counts(&mut self, storage: &InspectorStorage) {
+        // Set again by the view of the chosen protocol
+        self.matched = None;
+        if self.time_range.is_none() {
+            self.range_counts.clear();
+        }
+
+        for protocol in ProtocolsRegistered::iter() {
+            let in_range = self.time_range.map(|range| {
+                self.range_counts.entry(protocol).or_default().refresh(
+                    storage.captured(&protocol),
+                    storage.generation(&protocol),
+                    range,
+                )
+            });
+            self.counts
+                .insert(protocol, (storage.records_captured(&protocol), in_range));
+        }
+    }
+
+    /// Both directions of the records are merged, rows show the packets of every direction.
+    /// <br> Conversations are found by IP addresses, if they're known, or by MAC ones.
+    /// <br> `window` adds the columns with the last window of each direction.
+    fn conversations_view<T: Filterable + Located>(
+        &mut self, ui: &mut egui::Ui, storage: &mut Records<T>, devices: &DeviceStorage,
+        grid_id: &str, is_by_ip: bool, window: Option<fn(&T) -> Option<u32>>,
+    ) {
+        let is_filtered = self.refresh_filtered(ui, storage);
+        let source = ConversationSource {
+            protocol: self.protocol_chosen,
+            generation: storage.generation(),
+            filter_revision: self.filter.is_active().then(|| self.filter.revision()),
+            time_range: self.time_range,
+        };
+        let records = &*storage;
+        let locate = |record: Option<&T>| {
+            record.map(|record| {
+                let locator = record.locator();
+                match is_by_ip {
+                    true => locator.conversation(),
+                    false => Conversation::new((&locator.mac.0, &locator.mac.1), None),
+                }
+            })
+        };
+        if is_filtered {
+            let indices = self.filtered.indices();
+            self.conversations
+                .refresh(source, indices.len(), |position| {
+                    locate(indices.get(position).and_then(|index| records.get(*index)))
+                });
+        } else {
+            self.conversations
+                .refresh(source, records.len(), |position| {
+                    locate(records.get(position))
+                });
+        }
+
+        let total_items = self.conversations.rows().len();
+        if self.clear_pages_buttons(ui, storage, total_items) {
+            return;
+        }
+        self.filter_view(ui);
+        self.combine_view(ui);
+
+        let color = self.protocol_color(ui);
+        let skipped = Self::page_start(self.page, self.page_size);
+        let rows = Self::page_slice(self.conversations.rows(), self.page, self.page_size);
+        let filters = FilterField::available(&self.protocol_chosen);
+        let record_action = &mut self.record_action;
+        let records = &*storage;
+        let indices = self.filtered.indices();
+        // Positions of the rows are the ones of the source list
+        let window_label = |position: Option<usize>| {
+            let record = match is_filtered {
+                true => position
+                    .and_then(|position| indices.get(position))
+                    .and_then(|index| records.get(*index)),
+                false => position.and_then(|position| records.get(position)),
+            };
+            record
+                .zip(window)
+                .and_then(|(record, window)| window(record))
+                .map_or_else(|| "-".to_string(), |bytes| bytes.to_string())
+        };
+        let mut headings = vec![
+            "Tab.Inspector.Label.Number",
+            "Tab.Inspector.Conversations.First",
+            "Tab.Inspector.Conversations.Direction",
+            "Tab.Inspector.Conversations.Second",
+            "Tab.Inspector.Conversations.Forward",
+            "Tab.Inspector.Conversations.Backward",
+        ];
+        if window.is_some() {
+            headings.extend([
+                "Tab.Inspector.Conversations.WindowForward",
+                "Tab.Inspector.Conversations.WindowBackward",
+            ]);
+        }
+        ScrollArea::both()
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                Grid::new((grid_id, "Conversations"))
+                    .striped(true)
+                    .num_columns(headings.len())
+                    .show(ui, |ui| {
+                        if !rows.is_empty() {
+                            for &heading in &headings {
+                                ui.label(
+                                    styles::heading::grid(&t!(heading)).color(color),
+                                );
+                            }
+                            ui.end_row();
+                        }
+
+                        for (number, row) in (skipped + 1..).zip(rows) {
+                            let mut menu = RowMenu::default();
+                            menu.label(ui, number.to_string());
+                            Self::endpoint_label(
                                 ui,
-                                (self.page - 1)
-                                    .saturating_mul(Self::PAGE_SIZE)
-                                    .saturating_add(id + 1),
-                                packet,
+                                &mut menu,
+                                &row.conversation.first,
+                                devices,
                             );
+                            menu.label(ui, row.arrow());
+                            Self::endpoint_label(
+                                ui,
+                                &mut menu,
+                                &row.conversation.second,
+                                devices,
+                            );
+                            menu.label(ui, format::count(row.forward as u64));
+                            menu.label(ui, format::count(row.backward as u64));
+                            if window.is_some() {
+                                menu.label(ui, window_label(row.last_forward));
+                                menu.label(ui, window_label(row.last_backward));
+                            }
+                            menu.show(filters, record_action);
                             ui.end_row();
                         }
                     });
             });
     }
 
+    // Conversation has no direction, so the IP endpoints aren't filtered by
+    fn endpoint_label(
+        ui: &mut egui::Ui, menu: &mut RowMenu, endpoint: &Endpoint,
+        devices: &DeviceStorage,
+    ) {
+        match endpoint {
+            Endpoint::Ip(ip) => menu.label(ui, ip.to_string()),
+            Endpoint::Mac(mac) => {
+                let text = match devices.aliases.get(mac) {
+                    Some(alias) => alias.to_string(),
+                    None => mac.to_string(),
+                };
+                menu.address_label(ui, text, devices.tag(mac), mac);
+            },
+        }
+    }
+
+    fn combine_view(&mut self, ui: &mut egui::Ui) {
+        if !matches!(
+            self.protocol_chosen,
+            ProtocolId::Ethernet
+                | ProtocolId::IPv4
+                | ProtocolId::IPv6
+                | ProtocolId::TCP
+                | ProtocolId::UDP
+        ) {
+            return;
+        }
+
+        let protocol = self.protocol_chosen;
+        let mut is_combined = self.is_combined();
+        if ui
+            .checkbox(&mut is_combined, t!("Tab.Inspector.Conversations.Combine"))
+            .on_hover_text(t!("Tab.Inspector.Conversations.Combine.Hover"))
+            .changed()
+        {
+            self.combined.retain(|combined| *combined != protocol);
+            if is_combined {
+                self.combined.push(protocol);
+            }
+            self.page = 1;
+        }
+    }
+
+    fn is_combined(&self) -> bool {
+        self.combined.contains(&self.protocol_chosen)
+    }
+
+    fn apply(&mut self, action: RecordAction) {
+        match action {
+            RecordAction::FilterBy(field, value) => {
+                // Exact match, the substring one finds e.g. "10.0.0.1" in "10.0.0.10"
+                self.filter_input = format!("^{}$", regex::escape(&value));
+                self.filter.set_field(field);
+                self.filter.set_regex(true);
+                self.filter.set_pattern(&self.filter_input, Instant::now());
+                self.page = 1;
+            },
+        }
+    }
+
+    fn open(&mut self, request: InspectorRequest) {
+        self.protocol_chosen = request.protocol;
+        self.page = 1;
+
+        self.filter_input = match request.mac {
+            Some(mac) => {
+                self.filter.set_field(FilterField::Mac);
+                self.filter.set_regex(false);
+                mac.to_string()
+            },
+            None => String::new(),
+        };
+        self.filter.set_pattern(&self.filter_input, Instant::now());
+    }
+
+    // Filter of the protocol view takes the query, so the found records are listed there
+    fn open_filtered(
+        &mut self, protocol: ProtocolsRegistered, field: FilterField, query: &SearchQuery,
+    ) {
+        self.protocol_chosen = protocol.id();
+        self.page = 1;
+
+        self.filter_input = query.pattern.clone();
+        self.filter.set_field(field);
+        self.filter.set_regex(query.is_regex);
+        self.filter.set_pattern(&self.filter_input, Instant::now());
+    }
+
+    pub fn focus_search(&mut self) {
+        self.is_search_focus_requested = true;
+    }
+
+    fn search_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        ui.horizontal_wrapped(|ui| {
+            ui.label(format!("{}:", t!("Tab.Inspector.Search.Scope")));
+
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.search_input)
+                    .hint_text(t!("Tab.Inspector.Search.Hint"))
+                    .desired_width(150.0),
+            );
+            if self.is_search_focus_requested {
+                self.is_search_focus_requested = false;
+                response.request_focus();
+            }
+            let is_submitted =
+                response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+            if ui
+                .checkbox(&mut self.is_search_regex, t!("Tab.Inspector.Filter.Regex"))
+                .changed()
+            {
+                self.search_error = None;
+            }
+
+            let is_clicked = ui
+                .add_enabled(
+                    !self.search_input.is_empty(),
+                    egui::Button::new(t!("Tab.Inspector.Search.Button")),
+                )
+                .clicked();
+            if is_submitted || is_clicked {
+                self.search_start(ctx);
+            }
+        });
+
+        if let Some(error) = &self.search_error {
+            ui.label(
+                RichText::new(format!(
+                    "{}: {}",
+                    t!("Tab.Inspector.Filter.InvalidRegex"),
+                    error.lines().last().unwrap_or(error)
+                ))
+                .color(styles::colors::get(Role::FieldInvalid)),
+            );
+        }
+    }
+
+    fn search_start(&mut self, ctx: &mut Context) {
+        if self.search_input.is_empty() {
+            return;
+        }
+        let query = SearchQuery {
+            pattern: self.search_input.clone(),
+            is_regex: self.is_search_regex,
+        };
+        self.search_error = query.error();
+        if self.search_error.is_some() {
+            return;
+        }
+
+        // Copied at once, the capture keeps going while the copy is scanned
+        let snapshot = SearchSnapshot::new(&ctx.net_storage.inspector);
+        let total = snapshot.total();
+        let result = BackgroundTask::spawn(
+            t!("Tab.Inspector.Search.Progress").to_string(),
+            &ctx.modals_tx,
+            move |reporter| {
+                snapshot.search(&query, |scanned| {
+                    reporter.report(Progress::of(scanned, total));
+                    match reporter.is_cancelled() {
+                        true => ControlFlow::Break(()),
+                        false => ControlFlow::Continue(()),
+                    }
+                })
+            },
+        );
+
+        match result {
+            Ok(task) => self.search_task = Some(task),
+            Err(err) => MessageModal::error(&err.localized()).try_send_by(&ctx.modals_tx),
+        }
+    }
+
+    pub fn poll_tasks(&mut self, ctx: &mut Context) {
+        let Some(result) = self
+            .search_task
+            .as_ref()
+            .and_then(BackgroundTask::try_result)
+        else {
+            return;
+        };
+        self.search_task = None;
+
+        match result {
+            Ok(Some(results)) => self.search_results = Some(results),
+            // Cancelled, the previous results are kept
+            Ok(None) => {},
+            Err(err) => MessageModal::error(&err.localized()).try_send_by(&ctx.modals_tx),
+        }
+    }
+
+    /// Stops the workers. Their results belong to the old session.
+    pub fn cancel_tasks(&mut self) {
+        self.search_task = None;
+        self.search_results = None;
+    }
+
+    fn search_results_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        let Some(results) = &mut self.search_results else {
+            return;
+        };
+        results.retain_valid(&ctx.net_storage.inspector);
+
+        let mut to_open = None;
+        let mut is_closed = false;
+        ui.horizontal_wrapped(|ui| {
+            let text = match results.total() {
+                0 => t!(
+                    "Tab.Inspector.Search.Empty",
+                    "pattern" = results.query.pattern
+                ),
+                total => t!(
+                    "Tab.Inspector.Search.Results",
+                    "amount" = total,
+                    "pattern" = results.query.pattern
+                ),
+            };
+            ui.label(RichText::new(text).strong());
+            is_closed = ui.button(t!("Button.Close")).clicked();
+        });
+
+        for group in &results.groups {
+            let first_field = group.matches.first().map(|found| found.field);
+            let title = format!("{} ({})", group.protocol, group.matches.len());
+            egui::CollapsingHeader::new(title)
+                .id_salt(("Inspector-Search-Group", group.protocol))
+                .show(ui, |ui| {
+                    if let Some(field) = first_field {
+                        let response = ui.link(t!("Tab.Inspector.Search.Open"));
+                        if response.clicked() {
+                            to_open = Some((group.protocol, field));
+                        }
+                    }
+
+                    for found in group.matches.iter().take(SEARCH_ROWS_SHOWN) {
+                        let captured = found
+                            .captured
+                            .as_ref()
+                            .map(format::time)
+                            .unwrap_or_default();
+                        let text = format!(
+                            "#{} {captured} · {}",
+                            found.id.index + 1,
+                            Self::filter_field_title(&found.field)
+                        );
+                        if ui.link(text).clicked() {
+                            to_open = Some((group.protocol, found.field));
+                        }
+                    }
+
+                    let hidden = group.matches.len().saturating_sub(SEARCH_ROWS_SHOWN);
+                    if hidden > 0 {
+                        ui.label(
+                            RichText::new(t!(
+                                "Tab.Inspector.Search.More",
+                                "amount" = hidden
+                            ))
+                            .color(styles::colors::get(Role::Silent)),
+                        );
+                    }
+                });
+        }
+        ui.separator();
+
+        let query = results.query.clone();
+        if let Some((protocol, field)) = to_open {
+            self.open_filtered(protocol, field, &query);
+        }
+        if is_closed {
+            self.search_results = None;
+        }
+    }
+
+    fn time_range_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        if self.time_range != ctx.time_range {
+            self.time_range = ctx.time_range;
+            self.page = 1;
+        }
+        let Some(range) = ctx.time_range else {
+            return;
+        };
+
+        ui.horizontal_wrapped(|ui| {
+            ui.label(
+                RichText::new(t!(
+                    "Tab.Inspector.TimeRange.Showing",
+                    "start" = format::time(&range.start),
+                    "end" = format::time(&range.end)
+                ))
+                .color(styles::colors::get(Role::TimeRange)),
+            );
+            if ui.button(t!("Button.Clear")).clicked() {
+                ctx.time_range = None;
+            }
+        });
+    }
+
+    // Returns `true` if the records should be filtered.
+    fn update_filter(&mut self, ui: &egui::Ui) -> bool {
+        let fields = FilterField::available(&self.protocol_chosen);
+        if let Some(first) = fields.first() {
+            if !fields.contains(&self.filter.field()) {
+                self.filter.set_field(*first);
+                self.page = 1;
+            }
+        } else {
+            return false;
+        }
+
+        if self.filter.update(Instant::now()) {
+            self.page = 1;
+        }
+        if self.filter.is_pending() {
+            ui.ctx().request_repaint_after(filter::DEBOUNCE);
+        }
+
+        self.filter.is_active()
+    }
+
+    fn filter_view(&mut self, ui: &mut egui::Ui) {
+        let fields = FilterField::available(&self.protocol_chosen);
+        if fields.is_empty() {
+            return;
+        }
+
+        ui.horizontal_wrapped(|ui| {
+            ui.label(format!("{}:", t!("Tab.Inspector.Filter.Label")));
+
+            let mut field = self.filter.field();
+            egui::ComboBox::from_id_salt("Combobox.Inspector.Filter.Field")
+                .selected_text(Self::filter_field_title(&field))
+                .show_ui(ui, |ui| {
+                    for option in fields {
+                        ui.selectable_value(
+                            &mut field,
+                            *option,
+                            Self::filter_field_title(option),
+                        );
+                    }
+                });
+            if field != self.filter.field() {
+                self.filter.set_field(field);
+                self.page = 1;
+            }
+
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.filter_input)
+                    .hint_text(t!("Tab.Inspector.Filter.Hint")),
+            );
+            if response.changed() {
+                self.filter.set_pattern(&self.filter_input, Instant::now());
+            }
+
+            let mut is_regex = self.filter.is_regex();
+            if ui
+                .checkbox(&mut is_regex, t!("Tab.Inspector.Filter.Regex"))
+                .changed()
+            {
+                self.filter.set_regex(is_regex);
+                self.page = 1;
+            }
+
+            if let Some(error) = self.filter.error() {
+                ui.label(
+                    RichText::new(format!(
+                        "{}: {}",
+                        t!("Tab.Inspector.Filter.InvalidRegex"),
+                        error.lines().last().unwrap_or(error)
+                    ))
+                    .color(styles::colors::get(Role::FieldInvalid)),
+                );
+            }
+        });
+    }
+
+    fn filter_field_title(field: &FilterField) -> String {
+        match field {
+            FilterField::DnsQuestionName => {
+                t!("Tab.Inspector.Filter.Field.DnsQuestionName")
+            },
+            FilterField::DnsAnswerData => t!("Tab.Inspector.Filter.Field.DnsAnswerData"),
+            FilterField::HttpTarget => t!("Tab.Inspector.Filter.Field.HttpTarget"),
+            FilterField::HttpHost => t!("Tab.Inspector.Filter.Field.HttpHost"),
+            FilterField::HttpHeaderValue => {
+                t!("Tab.Inspector.Filter.Field.HttpHeaderValue")
+            },
+            FilterField::SyslogSeverity => {
+                t!("Tab.Inspector.Filter.Field.SyslogSeverity")
+            },
+            FilterField::SyslogMessage => t!("Tab.Inspector.Filter.Field.SyslogMessage"),
+            FilterField::SourceIp => t!("Tab.Inspector.Filter.Field.SourceIp"),
+            FilterField::DestinationIp => t!("Tab.Inspector.Filter.Field.DestinationIp"),
+            FilterField::Mac => t!("Tab.Inspector.Filter.Field.Mac"),
+        }
+        .to_string()
+    }
+
     pub fn arp_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        let color = self.protocol_color(ui);
+        Self::arp_requests_view(ui, ctx, color);
+
         let storage = &mut ctx.net_storage.inspector.arp;
+        let pairing = &ctx.net_storage.inspector.arp_pairing;
         self.protocol_view(
             ui,
             storage,
+            &ctx.net_storage.notes,
             "Inspector.Arp.Packets",
-            6,
+            7,
             &[
                 "Tab.Inspector.Label.Number",
                 "Tab.Inspector.Protocol.Arp.Operation",
@@ -97,23 +1066,142 @@ impl InspectorTab {
                 "Tab.Inspector.Protocol.Arp.IpTarget",
                 "Tab.Inspector.Protocol.Arp.MacSender",
                 "Tab.Inspector.Protocol.Arp.MacTarget",
+                "Tab.Inspector.Protocol.Arp.Reply",
             ],
-            |ui, id, packet| {
-                ui.label(id.to_string());
-                ui.label(packet.operation.to_string());
-                ui.label(packet.sender_ip.to_string());
-                ui.label(packet.target_ip.to_string());
-                ui.label(packet.sender_mac.to_string());
-                ui.label(packet.target_mac.to_string());
+            |ui, menu, id, packet| {
+                let aliases = &ctx.net_storage.devices.aliases;
+                menu.label(ui, id.to_string());
+                menu.label(ui, packet.operation.to_string());
+                menu.filter_label(
+                    ui,
+                    packet.sender_protocol.to_string(),
+                    FilterField::SourceIp,
+                    &packet.sender_protocol,
+                );
+                menu.filter_label(
+                    ui,
+                    packet.target_protocol.to_string(),
+                    FilterField::DestinationIp,
+                    &packet.target_protocol,
+                );
+                menu.filter_label(
+                    ui,
+                    Self::hardware_address_to_string(&packet.sender_hardware, aliases),
+                    FilterField::Mac,
+                    &packet.sender_hardware,
+                );
+                menu.filter_label(
+                    ui,
+                    Self::hardware_address_to_string(&packet.target_hardware, aliases),
+                    FilterField::Mac,
+                    &packet.target_hardware,
+                );
+                menu.label(
+                    ui,
+                    Self::arp_reply_to_string(pairing, id.saturating_sub(1), packet),
+                );
             },
         );
     }
 
+    // Counters of the paired requests & the requesters, that are often left unanswered
+    fn arp_requests_view(ui: &mut egui::Ui, ctx: &Context, color: egui::Color32) {
+        let pairing = &ctx.net_storage.inspector.arp_pairing;
+        if pairing.counters.requests == 0 {
+            return;
+        }
+
+        ui.collapsing(
+            RichText::new(t!("Tab.Inspector.Protocol.Arp.Requests")).color(color),
+            |ui| {
+                let counters = &pairing.counters;
+                ui.label(t!(
+                    "Tab.Inspector.Protocol.Arp.Requests.Counters",
+                    "requests" = counters.requests,
+                    "answered" = counters.answered,
+                    "unanswered" = counters.unanswered,
+                    "pending" = pairing.pending()
+                ));
+
+                let settings = &ctx.client_settings;
+                let top = pairing.top_requesters(
+                    settings.arp_unanswered_min_requests,
+                    settings.arp_unanswered_ratio_percent,
+                );
+                if top.is_empty() {
+                    return;
+                }
+                ui.label(t!(
+                    "Tab.Inspector.Protocol.Arp.Requesters",
+                    "requests" = settings.arp_unanswered_min_requests,
+                    "ratio" = settings.arp_unanswered_ratio_percent
+                ));
+                Grid::new("Inspector.Arp.Requesters")
+                    .striped(true)
+                    .num_columns(4)
+                    .show(ui, |ui| {
+                        for key in [
+                            "Tab.Inspector.Protocol.Arp.Requester",
+                            "Tab.Inspector.Protocol.Arp.Requests",
+                            "Tab.Inspector.Protocol.Arp.Unanswered",
+                            "Tab.Inspector.Protocol.Arp.UnansweredRatio",
+                        ] {
+                            ui.label(styles::heading::grid(&t!(key)).color(color));
+                        }
+                        ui.end_row();
+
+                        for (ip, counters) in top {
+                            ui.label(ip.to_string());
+                            ui.label(counters.requests.to_string());
+                            ui.label(counters.unanswered.to_string());
+                            ui.label(format!("{:.0}%", counters.unanswered_percent()));
+                            ui.end_row();
+                        }
+                    });
+            },
+        );
+        ui.add_space(5.0);
+    }
+
+    fn arp_reply_to_string(
+        pairing: &ArpPairing, index: usize, packet: &ArpDto,
+    ) -> String {
+        if packet.operation != Operation::Request {
+            return "-".to_string();
+        }
+        match pairing.outcome(index) {
+            Some(Outcome::Answered(elapsed)) => t!(
+                "Tab.Inspector.Protocol.Arp.Reply.Answered",
+                "ms" = elapsed.num_milliseconds()
+            )
+            .to_string(),
+            Some(Outcome::Unanswered) => {
+                t!("Tab.Inspector.Protocol.Arp.Reply.Unanswered").to_string()
+            },
+            // Gratuitous ARP & probes aren't paired, pending ones aren't expired yet
+            None => "-".to_string(),
+        }
+    }
+
+    // MAC addresses are replaced by aliases, other formats are shown as hex.
+    fn hardware_address_to_string(
+        address: &HardwareAddress, aliases: &DeviceAliases,
+    ) -> String {
+        match address {
+            HardwareAddress::Mac(mac) => match aliases.get(mac) {
+                Some(alias) => alias.to_string(),
+                None => mac.to_string(),
+            },
+            HardwareAddress::Raw(_) => address.to_string(),
+        }
+    }
+
     pub fn dhcpv4_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
         let storage = &mut ctx.net_storage.inspector.dhcpv4;
         self.protocol_view(
             ui,
             storage,
+            &ctx.net_storage.notes,
             "Inspector.DHCPv4.Packets",
             7,
             &[
@@ -125,58 +1213,149 @@ impl InspectorTab {
                 "Tab.Inspector.Protocol.DHCPv4.RelayAgentAddress",
                 "Tab.Inspector.Protocol.DHCPv4.ClientMAC",
             ],
-            |ui, id, packet| {
-                ui.label(id.to_string());
-                ui.label(packet.message_type.to_string());
-                ui.label(packet.old_client_address.to_string());
-                ui.label(packet.new_client_address.to_string());
-                ui.label(packet.server_address.to_string());
-                ui.label(packet.relay_agent_address.to_string());
-                ui.label(packet.hardware_address_client.to_string());
+            |ui, menu, id, packet| {
+                menu.label(ui, id.to_string());
+                menu.label(ui, packet.message_type.to_string());
+                menu.label(ui, packet.old_client_address.to_string());
+                menu.label(ui, packet.new_client_address.to_string());
+                menu.label(ui, packet.server_address.to_string());
+                menu.label(ui, packet.relay_agent_address.to_string());
+                menu.label(ui, packet.hardware_address_client.to_string());
             },
         );
     }
 
     pub fn dhcpv6_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
         let storage = &mut ctx.net_storage.inspector.dhcpv6;
-        self.protocol_view(
-            ui,
-            storage,
-            "Inspector.DHCPv6.Packets",
-            2,
-            &[
-                "Tab.Inspector.Label.Number",
-                "Tab.Inspector.Protocol.DHCPv6.MessageType",
-            ],
-            |ui, id, packet| {
-                ui.label(id.to_string());
-                ui.label(packet.message_type.to_string());
-            },
-        );
+        let Some(indices) = self.page_indices(ui, storage) else {
+            return;
+        };
+        let color = self.protocol_color(ui);
+        let notes = &ctx.net_storage.notes;
+        let record_id = self.record_id(storage);
+        let requested = &mut self.requested;
+        let filters = FilterField::available(&self.protocol_chosen);
+        let record_action = &mut self.record_action;
+
+        // Table
+        ScrollArea::both()
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                // Data rows
+                for index in indices {
+                    let Some(packet) = storage.get(index) else {
+                        continue;
+                    };
+                    let record_number = index + 1;
+
+                    let id = record_id(index);
+                    let title = RichText::new(format!(
+                        "DHCPv6 Packet #{record_number}: {}{}{}",
+                        packet.message_type,
+                        Self::note_mark(notes, id.as_ref()),
+                        Self::truncated_mark(storage.is_truncated(index))
+                    ))
+                    .color(color);
+                    let header = egui::CollapsingHeader::new(title)
+                        .id_salt(("DHCPv6-Packet-Header", record_number));
+                    let response = header.show(ui, |ui| {
+                        Self::note_row(ui, notes, id, requested);
+                        Grid::new(("DHCPv6-Packet", record_number))
+                            .striped(false)
+                            .num_columns(2)
+                            .show(ui, |ui| {
+                                ui.label(styles::heading::grid(&t!(
+                                    "Tab.Inspector.Protocol.DHCPv6.MessageType"
+                                )));
+                                ui.label(styles::heading::grid(&t!(
+                                    "Tab.Inspector.Protocol.DHCPv6.TransactionId"
+                                )));
+                                ui.end_row();
+
+                                let mut menu = RowMenu::default();
+                                menu.label(ui, packet.message_type.to_string());
+                                menu.label(ui, format!("{:#08x}", packet.transaction_id));
+                                menu.show_record(packet, filters, record_action);
+                                ui.end_row();
+                            });
+
+                        if !packet.options.is_empty() {
+                            ui.label(styles::heading::grid(&t!(
+                                "Tab.Inspector.Protocol.DHCPv6.Options"
+                            )));
+                            Grid::new(("DHCPv6-Options", record_number))
+                                .striped(false)
+                                .num_columns(2)
+                                .show(ui, |ui| {
+                                    for option in &packet.options {
+                                        let mut menu = RowMenu::default();
+                                        menu.label(ui, option.name());
+                                        menu.label(ui, option.value());
+                                        menu.show(filters, record_action);
+                                        ui.end_row();
+                                    }
+                                });
+                        }
+                    });
+                    if storage.is_truncated(index) {
+                        response
+                            .header_response
+                            .on_hover_text(t!("Tab.Inspector.Hover.Truncated"));
+                    }
+                }
+            });
     }
 
     pub fn dns_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
         let storage = &mut ctx.net_storage.inspector.dns;
-        if self.clear_pages_buttons(ui, storage) {
+        let Some(indices) = self.page_indices(ui, storage) else {
             return;
-        }
+        };
+        let color = self.protocol_color(ui);
+        let notes = &ctx.net_storage.notes;
+        let record_id = self.record_id(storage);
+        let requested = &mut self.requested;
+        let filters = FilterField::available(&self.protocol_chosen);
+        let record_action = &mut self.record_action;
+        ui.checkbox(
+            &mut self.is_expired_hidden,
+            t!("Tab.Inspector.Protocol.DNS.HideExpired"),
+        )
+        .on_hover_text(t!("Tab.Inspector.Protocol.DNS.HideExpired.Hover"));
+        let is_expired_hidden = self.is_expired_hidden;
+        // Counted once per render, there're no timers
+        let now = Local::now();
 
         // Table
         ScrollArea::both()
             .auto_shrink([false, true])
             .show(ui, |ui| {
                 // Data rows
-                for (index, packet) in
-                    Self::page_slice(storage, self.page).iter().enumerate()
-                {
-                    let record_number = (self.page - 1)
-                        .saturating_mul(Self::PAGE_SIZE)
-                        .saturating_add(index + 1);
+                for index in indices {
+                    let Some(packet) = storage.get(index) else {
+                        continue;
+                    };
+                    let record_number = index + 1;
+                    let clock = TtlClock {
+                        captured: storage.captured_at(index).copied(),
+                        now,
+                        is_expired_hidden,
+                    };
 
-                    ui.collapsing(format!("DNS Packet #{record_number}"), |ui| {
-                        Grid::new(format!("DNS-Headers-{record_number}"))
+                    let id = record_id(index);
+                    let title = RichText::new(format!(
+                        "DNS Packet #{record_number}{}{}",
+                        Self::note_mark(notes, id.as_ref()),
+                        Self::truncated_mark(storage.is_truncated(index))
+                    ))
+                    .color(color);
+                    let header = egui::CollapsingHeader::new(title)
+                        .id_salt(("DNS-Packet-Header", record_number));
+                    let response = header.show(ui, |ui| {
+                        Self::note_row(ui, notes, id, requested);
+                        Grid::new(("DNS-Headers", record_number))
                             .striped(false)
-                            .num_columns(4)
+                            .num_columns(6)
                             .show(ui, |ui| {
                                 ui.label(styles::heading::grid(&t!(
                                     "Tab.Inspector.Protocol.DNS.MessageType"
@@ -190,15 +1369,37 @@ impl InspectorTab {
                                 ui.label(styles::heading::grid(&t!(
                                     "Tab.Inspector.Protocol.DNS.ResponseCode"
                                 )));
+                                ui.label(styles::heading::grid(&t!(
+                                    "Tab.Inspector.Protocol.DNS.PayloadSize"
+                                )));
+                                ui.label(styles::heading::grid(&t!(
+                                    "Tab.Inspector.Protocol.DNS.DnssecOk"
+                                )));
                                 ui.end_row();
 
-                                ui.label(packet.message_type.to_string());
-                                ui.label(packet.operation_code.to_string());
+                                let mut menu = RowMenu::default();
+                                menu.label(ui, packet.message_type.to_string());
+                                menu.label(ui, packet.operation_code.to_string());
                                 match packet.authoritative_answer {
-                                    true => ui.label("+"),
-                                    false => ui.label("-"),
+                                    true => menu.label(ui, "+"),
+                                    false => menu.label(ui, "-"),
                                 };
-                                ui.label(packet.response_code.to_string());
+                                menu.label(ui, packet.response_code.to_string());
+                                // Without EDNS0, the payload is limited to 512 bytes
+                                match packet.edns() {
+                                    Some((payload_size, dnssec_ok)) => {
+                                        menu.label(ui, payload_size.to_string());
+                                        match dnssec_ok {
+                                            true => menu.label(ui, "+"),
+                                            false => menu.label(ui, "-"),
+                                        };
+                                    },
+                                    None => {
+                                        menu.label(ui, "-");
+                                        menu.label(ui, "-");
+                                    },
+                                }
+                                menu.show_record(packet, filters, record_action);
                                 ui.end_row();
                             });
 
@@ -210,7 +1411,7 @@ impl InspectorTab {
                                 t!("Tab.Inspector.Protocol.DNS.Records"),
                                 question_section_len
                             ));
-                            Grid::new(format!("DNS-Headers-Question-{record_number}"))
+                            Grid::new(("DNS-Headers-Question", record_number))
                                 .striped(false)
                                 .num_columns(4)
                                 .show(ui, |ui| {
@@ -231,10 +1432,21 @@ impl InspectorTab {
                                     for (index, question) in
                                         packet.question_section.iter().enumerate()
                                     {
-                                        ui.label((index + 1).to_string());
-                                        ui.label(question.name.to_string());
-                                        ui.label(question.entry_type.to_string());
-                                        ui.label(question.class.to_string());
+                                        let mut menu = RowMenu::default();
+                                        menu.label(ui, (index + 1).to_string());
+                                        menu.domain_label(
+                                            ui,
+                                            &question.name,
+                                            |text| text,
+                                            Some(FilterField::DnsQuestionName),
+                                        );
+                                        menu.label(ui, question.entry_type.to_string());
+                                        menu.label(ui, question.class.to_string());
+                                        menu.show_record(
+                                            question,
+                                            filters,
+                                            record_action,
+                                        );
                                         ui.end_row();
                                     }
                                 });
@@ -246,6 +1458,10 @@ impl InspectorTab {
                             "Answer",
                             "Tab.Inspector.Protocol.DNS.Answer",
                             &packet.answer_section,
+                            clock,
+                            Some(FilterField::DnsAnswerData),
+                            filters,
+                            record_action,
                         );
                         Self::dns_record_view(
                             ui,
@@ -253,6 +1469,10 @@ impl InspectorTab {
                             "Authority",
                             "Tab.Inspector.Protocol.DNS.Authority",
                             &packet.authority_section,
+                            clock,
+                            None,
+                            filters,
+                            record_action,
                         );
                         Self::dns_record_view(
                             ui,
@@ -260,15 +1480,27 @@ impl InspectorTab {
                             "Additional",
                             "Tab.Inspector.Protocol.DNS.Additional",
                             &packet.additional_section,
+                            clock,
+                            None,
+                            filters,
+                            record_action,
                         );
                     });
+                    if storage.is_truncated(index) {
+                        response
+                            .header_response
+                            .on_hover_text(t!("Tab.Inspector.Hover.Truncated"));
+                    }
                 }
             });
     }
 
+    // Data of the records is filterable only in the answer section.
+    // Expired records are struck through, or hidden
     fn dns_record_view(
         ui: &mut egui::Ui, packet_id: usize, section_id: &str, name: &str,
-        section: &[dpi::protocols::dns::ResourceRecord],
+        section: &[ResourceRecord], clock: TtlClock, data_field: Option<FilterField>,
+        filters: &[FilterField], record_action: &mut Option<RecordAction>,
     ) {
         let len = section.len();
         if len > 0 {
@@ -278,9 +1510,9 @@ impl InspectorTab {
                 t!("Tab.Inspector.Protocol.DNS.Records"),
                 len
             ));
-            Grid::new(format!("DNS-Records-{section_id}-{packet_id}"))
+            Grid::new(("DNS-Records", section_id, packet_id))
                 .striped(false)
-                .num_columns(6)
+                .num_columns(7)
                 .show(ui, |ui| {
                     ui.label(styles::heading::grid(&t!("Tab.Inspector.Label.Number")));
                     ui.label(styles::heading::grid(&t!(
@@ -295,29 +1527,89 @@ impl InspectorTab {
                     ui.label(styles::heading::grid(&t!(
                         "Tab.Inspector.Protocol.DNS.Record.TimeToLive"
                     )));
+                    ui.label(styles::heading::grid(&t!(
+                        "Tab.Inspector.Protocol.DNS.Record.RemainingTimeToLive"
+                    )));
                     ui.label(styles::heading::grid(&t!(
                         "Tab.Inspector.Protocol.DNS.Record.Data"
                     )));
                     ui.end_row();
 
                     for (index, record) in section.iter().enumerate() {
-                        ui.label((index + 1).to_string());
-                        ui.label(record.name.to_string());
-                        ui.label(record.record_type.to_string());
-                        ui.label(record.class.to_string());
-                        ui.label(record.time_to_live.to_string());
-                        ui.label(record.data.to_string());
+                        let remaining = clock.remaining(record);
+                        let is_expired = remaining == Some(0);
+                        if is_expired && clock.is_expired_hidden {
+                            continue;
+                        }
+                        let cell = |text: String| match is_expired {
+                            true => RichText::new(text).weak().strikethrough(),
+                            false => RichText::new(text),
+                        };
+
+                        let mut menu = RowMenu::default();
+                        menu.label(ui, (index + 1).to_string());
+                        menu.domain_label(ui, &record.name, cell, None);
+                        menu.label(ui, cell(record.record_type.to_string()));
+                        match &record.class {
+                            Some(class) => menu.label(ui, cell(class.to_string())),
+                            None => menu.label(ui, "-"),
+                        };
+                        menu.label(ui, cell(record.time_to_live.to_string()));
+                        match remaining {
+                            Some(seconds) => menu.label(ui, cell(seconds.to_string())),
+                            None => menu.label(ui, "-"),
+                        };
+                        menu.domain_label(ui, &record.data.to_string(), cell, data_field);
+                        menu.show_record(record, filters, record_action);
                         ui.end_row();
                     }
                 });
         }
     }
 
+    // Combined directions also show the windows, the segments are shown as is
+    pub fn tcp_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        let storage = &mut ctx.net_storage.inspector.tcp;
+        let devices = &ctx.net_storage.devices;
+        if self.is_combined() {
+            self.conversations_view(
+                ui,
+                storage,
+                devices,
+                "Inspector.TCP.Packets",
+                true,
+                Some(|(packet, _)| packet.window.and_then(|window| window.effective)),
+            );
+            return;
+        }
+        self.fields_view(
+            ui,
+            storage,
+            &ctx.net_storage.notes,
+            devices,
+            "Inspector.TCP.Packets",
+            true,
+        );
+    }
+
     pub fn ethernet_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
         let storage = &mut ctx.net_storage.inspector.ethernet;
+        if self.is_combined() {
+            let devices = &ctx.net_storage.devices;
+            self.conversations_view(
+                ui,
+                storage,
+                devices,
+                "Inspector.Ethernet.Packets",
+                false,
+                None,
+            );
+            return;
+        }
         self.protocol_view(
             ui,
             storage,
+            &ctx.net_storage.notes,
             "Inspector.Ethernet.Packets",
             7,
             &[
@@ -327,40 +1619,63 @@ impl InspectorTab {
                 "Tab.Inspector.Protocol.IpSender",
                 "Tab.Inspector.Protocol.IpTarget",
             ],
-            |ui, id, locator| {
-                let (source_ip, target_ip) = locator.ip_to_string();
-                let (source_mac, target_mac) =
-                    locator.mac_to_string(&ctx.net_storage.devices.aliases);
+            |ui, menu, id, locator| {
+                let devices = &ctx.net_storage.devices;
+                let (source_mac, target_mac) = locator.mac_to_string(&devices.aliases);
 
-                ui.label(id.to_string());
-                ui.label(source_mac);
-                ui.label(target_mac);
-                ui.label(source_ip);
-                ui.label(target_ip);
+                menu.label(ui, id.to_string());
+                menu.address_label(
+                    ui,
+                    source_mac,
+                    devices.tag(&locator.mac.0),
+                    &locator.mac.0,
+                );
+                menu.address_label(
+                    ui,
+                    target_mac,
+                    devices.tag(&locator.mac.1),
+                    &locator.mac.1,
+                );
+                menu.ip_labels(ui, locator);
             },
         );
     }
 
     pub fn http_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
         let storage = &mut ctx.net_storage.inspector.http;
-        if self.clear_pages_buttons(ui, storage) {
+        let Some(indices) = self.page_indices(ui, storage) else {
             return;
-        }
+        };
+        let color = self.protocol_color(ui);
+        let notes = &ctx.net_storage.notes;
+        let record_id = self.record_id(storage);
+        let requested = &mut self.requested;
+        let filters = FilterField::available(&self.protocol_chosen);
+        let record_action = &mut self.record_action;
 
         // Table
         ScrollArea::both()
             .auto_shrink([false, true])
             .show(ui, |ui| {
                 // Data rows
-                for (index, (packet, locator)) in
-                    Self::page_slice(storage, self.page).iter().enumerate()
-                {
-                    let record_number = (self.page - 1)
-                        .saturating_mul(Self::PAGE_SIZE)
-                        .saturating_add(index + 1);
+                for index in indices {
+                    let Some((packet, locator)) = storage.get(index) else {
+                        continue;
+                    };
+                    let record_number = index + 1;
 
-                    ui.collapsing(format!("HTTP Packet #{record_number}"), |ui| {
-                        Grid::new(format!("HTTP-Packet-{record_number}"))
+                    let id = record_id(index);
+                    let title = RichText::new(format!(
+                        "HTTP Packet #{record_number}{}{}",
+                        Self::note_mark(notes, id.as_ref()),
+                        Self::truncated_mark(storage.is_truncated(index))
+                    ))
+                    .color(color);
+                    let header = egui::CollapsingHeader::new(title)
+                        .id_salt(("HTTP-Packet-Header", record_number));
+                    let response = header.show(ui, |ui| {
+                        Self::note_row(ui, notes, id, requested);
+                        Grid::new(("HTTP-Packet", record_number))
                             .striped(false)
                             .num_columns(4)
                             .show(ui, |ui| {
@@ -396,257 +1711,740 @@ impl InspectorTab {
                                         )));
                                 ui.end_row();
 
-                                let (source_ip, target_ip) = locator.ip_to_string();
-                                let (source_mac, target_mac) =
-                                    locator.mac_to_string(&ctx.net_storage.devices.aliases);
+                                let mut menu = RowMenu::default();
                                 match packet {
                                     HttpDto::Request(request) => {
-                                        ui.label(request.method.to_string());
-                                        ui.label(request.target.to_string());
+                                        menu.label(ui, request.method.to_string());
+                                        menu.filter_label(
+                                            ui,
+                                            request.target.to_string(),
+                                            FilterField::HttpTarget,
+                                            &request.target,
+                                        );
                                     },
                                     HttpDto::Response(response) => {
-                                        ui.label(response.status_code.to_string());
-                                        ui.label(response.reason.to_string());
+                                        menu.label(ui, response.status_code.to_string());
+                                        menu.label(ui, response.reason.to_string());
                                     },
                                 }
-                                ui.label(source_ip);
-                                ui.label(target_ip);
-                                ui.label(source_mac);
-                                ui.label(target_mac);
+                                menu.ip_labels(ui, locator);
+                                menu.mac_labels(
+                                    ui,
+                                    locator,
+                                    &ctx.net_storage.devices.aliases,
+                                );
+                                menu.show_record(
+                                    &(packet, locator),
+                                    filters,
+                                    record_action,
+                                );
                                 ui.end_row();
                             });
 
-                        let headers = match packet {
-                            HttpDto::Request(value) => &value.headers,
-                            HttpDto::Response(value) => &value.headers,
+                        let (headers, body_anomaly) = match packet {
+                            HttpDto::Request(value) => {
+                                (&value.headers, value.body_anomaly)
+                            },
+                            HttpDto::Response(value) => {
+                                (&value.headers, value.body_anomaly)
+                            },
                         };
+                        if let Some(anomaly) = body_anomaly {
+                            let text = match anomaly {
+                                BodyAnomaly::Omitted(declared) => t!(
+                                    "Tab.Inspector.Protocol.HTTP.BodyOmitted",
+                                    "size" = format::bytes(declared)
+                                ),
+                                BodyAnomaly::ConflictingLength => t!(
+                                    "Tab.Inspector.Protocol.HTTP.ConflictingLength"
+                                ),
+                            };
+                            ui.label(
+                                RichText::new(text)
+                                    .color(styles::colors::get(Role::SeverityWarning)),
+                            );
+                        }
                         if !headers.is_empty() {
                             ui.label(styles::heading::grid(&t!(
                                             "Tab.Inspector.Protocol.HTTP.Headers"
                                         )));
-                            Grid::new(format!("HTTP-Headers-{record_number}"))
+                            Grid::new(("HTTP-Headers", record_number))
                                 .striped(false)
                                 .num_columns(2)
                                 .show(ui, |ui| {
-                                    for header in headers {
-                                        ui.label(&header.0);
-                                        ui.label(&header.1);
+                                    for (name, value) in headers {
+                                        let is_host = name
+                                            .trim()
+                                            .eq_ignore_ascii_case(filter::HOST_HEADER);
+                                        let field = match is_host {
+                                            true => FilterField::HttpHost,
+                                            false => FilterField::HttpHeaderValue,
+                                        };
+                                        let mut menu = RowMenu::default();
+                                        menu.label(ui, name);
+                                        match is_host {
+                                            true => menu.domain_label(
+                                                ui,
+                                                value.trim(),
+                                                |text| text,
+                                                Some(field),
+                                            ),
+                                            false => menu.filter_label(
+                                                ui,
+                                                value,
+                                                field,
+                                                value.trim(),
+                                            ),
+                                        }
+                                        menu.show(filters, record_action);
                                         ui.end_row();
                                     }
                                 });
                         }
                     });
+                    if storage.is_truncated(index) {
+                        response
+                            .header_response
+                            .on_hover_text(t!("Tab.Inspector.Hover.Truncated"));
+                    }
                 }
             });
     }
 
-    pub fn icmpv4_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
-        let storage = &mut ctx.net_storage.inspector.icmpv4;
+    pub fn ieee80211_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        let storage = &mut ctx.net_storage.inspector.ieee80211;
         self.protocol_view(
             ui,
             storage,
-            "Inspector.ICMPv4.Packets",
-            7,
+            &ctx.net_storage.notes,
+            "Inspector.IEEE80211.Packets",
+            8,
             &[
                 "Tab.Inspector.Label.Number",
-                "Tab.Inspector.Protocol.ICMPv4.MessageType",
-                "Tab.Inspector.Protocol.ICMPv4.Code",
-                "Tab.Inspector.Protocol.IpSender",
-                "Tab.Inspector.Protocol.IpTarget",
+                "Tab.Inspector.Protocol.IEEE80211.Frame",
+                "Tab.Inspector.Protocol.IEEE80211.SSID",
+                "Tab.Inspector.Protocol.IEEE80211.Channel",
+                "Tab.Inspector.Protocol.IEEE80211.Signal",
+                "Tab.Inspector.Protocol.IEEE80211.BSSID",
                 "Tab.Inspector.Protocol.MacSender",
                 "Tab.Inspector.Protocol.MacTarget",
             ],
-            |ui, id, package| {
-                let packet = &package.0;
+            |ui, menu, id, package| {
+                let packet = &package.0.frame;
+                let radio = package.0.radio.as_ref();
                 let locator = &package.1;
-                let (source_ip, target_ip) = locator.ip_to_string();
-                let (source_mac, target_mac) =
-                    locator.mac_to_string(&ctx.net_storage.devices.aliases);
 
-                ui.label(id.to_string());
-                ui.label(packet.message_type.to_string());
-                ui.label(packet.code.to_string());
-                ui.label(source_ip);
-                ui.label(target_ip);
-                ui.label(source_mac);
-                ui.label(target_mac);
+                let frame = match packet.is_protected {
+                    true => format!("{} 🔒", packet.subtype),
+                    false => packet.subtype.to_string(),
+                };
+                let ssid = match packet.ssid.as_deref() {
+                    Some("") => {
+                        t!("Tab.Inspector.Protocol.IEEE80211.SSID.Hidden").to_string()
+                    },
+                    Some(ssid) => ssid.to_string(),
+                    None => "-".to_string(),
+                };
+                let channel = radio
+                    .and_then(|radio| radio.channel)
+                    .or(packet.channel.map(u16::from))
+                    .map(|channel| channel.to_string())
+                    .unwrap_or("-".to_string());
+                let signal = radio
+                    .and_then(|radio| radio.signal)
+                    .map(|signal| format!("{signal} dBm"))
+                    .unwrap_or("-".to_string());
+                let bssid = packet
+                    .bssid
+                    .as_ref()
+                    .map(|bssid| bssid.to_string())
+                    .unwrap_or("-".to_string());
+
+                menu.label(ui, id.to_string());
+                menu.label(ui, frame);
+                menu.label(ui, ssid);
+                menu.label(ui, channel);
+                menu.label(ui, signal);
+                menu.label(ui, bssid);
+                menu.mac_labels(ui, locator, &ctx.net_storage.devices.aliases);
             },
         );
     }
 
-    pub fn icmpv6_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
-        let storage = &mut ctx.net_storage.inspector.icmpv6;
+    pub fn nbns_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        let storage = &mut ctx.net_storage.inspector.nbns;
         self.protocol_view(
             ui,
             storage,
-            "Inspector.ICMPv6.Packets",
-            7,
+            &ctx.net_storage.notes,
+            "Inspector.NBNS.Packets",
+            9,
             &[
                 "Tab.Inspector.Label.Number",
-                "Tab.Inspector.Protocol.ICMPv6.MessageType",
-                "Tab.Inspector.Protocol.ICMPv6.Code",
+                "Tab.Inspector.Protocol.NBNS.MessageType",
+                "Tab.Inspector.Protocol.NBNS.Operation",
+                "Tab.Inspector.Protocol.NBNS.Names",
+                "Tab.Inspector.Protocol.NBNS.Records",
                 "Tab.Inspector.Protocol.IpSender",
                 "Tab.Inspector.Protocol.IpTarget",
                 "Tab.Inspector.Protocol.MacSender",
                 "Tab.Inspector.Protocol.MacTarget",
             ],
-            |ui, id, package| {
+            |ui, menu, id, package| {
                 let packet = &package.0;
                 let locator = &package.1;
-                let (source_ip, target_ip) = locator.ip_to_string();
-                let (source_mac, target_mac) =
-                    locator.mac_to_string(&ctx.net_storage.devices.aliases);
+                let names = packet
+                    .question_section
+                    .iter()
+                    .map(|question| format::domain(&question.name).0)
+                    .collect::<Vec<_>>();
+                let records = packet
+                    .records
+                    .iter()
+                    .map(|record| {
+                        let (name, _) = format::domain(&record.name);
+                        let (data, _) = format::domain(&record.data.to_string());
+                        format!("{name}: {data}")
+                    })
+                    .collect::<Vec<_>>();
 
-                ui.label(id.to_string());
-                ui.label(packet.message_type.to_string());
-                ui.label(packet.code.to_string());
-                ui.label(source_ip);
-                ui.label(target_ip);
-                ui.label(source_mac);
-                ui.label(target_mac);
+                menu.label(ui, id.to_string());
+                menu.label(ui, packet.message_type.to_string());
+                menu.label(ui, packet.operation.to_string());
+                menu.label(ui, Self::join_or_dash(&names));
+                menu.label(ui, Self::join_or_dash(&records));
+                menu.ip_labels(ui, locator);
+                menu.mac_labels(ui, locator, &ctx.net_storage.devices.aliases);
             },
         );
     }
 
-    pub fn ipv4_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
-        let storage = &mut ctx.net_storage.inspector.ipv4;
-        self.protocol_view(
-            ui,
-            storage,
-            "Inspector.IPv4.Packets",
-            6,
-            &[
-                "Tab.Inspector.Label.Number",
-                "Tab.Inspector.Protocol.IPv4.AddressSource",
-                "Tab.Inspector.Protocol.IPv4.AddressDestination",
-                "Tab.Inspector.Protocol.IPv4.TimeToLive",
-                "Tab.Inspector.Protocol.MacSender",
-                "Tab.Inspector.Protocol.MacTarget",
-            ],
-            |ui, id, package| {
-                let packet = &package.0;
-                let locator = &package.1;
-                let (source_mac, target_mac) =
-                    locator.mac_to_string(&ctx.net_storage.devices.aliases);
+    pub fn rtp_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        let storage = &mut ctx.net_storage.inspector.rtp;
+        let Some(indices) = self.page_indices(ui, storage) else {
+            return;
+        };
+        let color = self.protocol_color(ui);
+        let notes = &ctx.net_storage.notes;
+        let record_id = self.record_id(storage);
+        let requested = &mut self.requested;
+        let filters = FilterField::available(&self.protocol_chosen);
+        let record_action = &mut self.record_action;
+        let heading = |ui: &mut egui::Ui, key: &str| {
+            ui.label(styles::heading::grid(&t!(key)).color(color));
+        };
 
-                ui.label(id.to_string());
-                ui.label(packet.address_source.to_string());
-                ui.label(packet.address_destination.to_string());
-                ui.label(packet.time_to_live.to_string());
-                ui.label(source_mac);
-                ui.label(target_mac);
-            },
-        );
+        ScrollArea::both()
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                let streams = &ctx.net_storage.inspector.rtp_streams;
+                if !streams.is_empty() {
+                    ui.collapsing(
+                        RichText::new(t!("Tab.Inspector.Protocol.RTP.Streams"))
+                            .color(color),
+                        |ui| {
+                            Grid::new("Inspector.RTP.Streams")
+                                .striped(true)
+                                .num_columns(7)
+                                .show(ui, |ui| {
+                                    for key in [
+                                        "Tab.Inspector.Protocol.RTP.SSRC",
+                                        "Tab.Inspector.Protocol.RTP.PayloadType",
+                                        "Tab.Inspector.Protocol.RTP.Packets",
+                                        "Tab.Inspector.Protocol.RTP.Jitter",
+                                        "Tab.Inspector.Protocol.RTP.Source",
+                                        "Tab.Inspector.Protocol.RTP.Destination",
+                                        "Tab.Inspector.Protocol.RTP.Negotiated",
+                                    ] {
+                                        heading(ui, key);
+                                    }
+                                    ui.end_row();
+
+                                    for stream in streams.iter() {
+                                        let jitter = match stream.jitter() {
+                                            Some(jitter) => format!("{jitter:.2} ms"),
+                                            None => "-".to_string(),
+                                        };
+                                        let mut menu = RowMenu::default();
+                                        menu.label(ui, format!("0x{:08X}", stream.ssrc));
+                                        menu.label(ui, stream.payload_type_name());
+                                        menu.label(ui, stream.packets.to_string());
+                                        menu.hover_label(
+                                            ui,
+                                            jitter,
+                                            t!("Tab.Inspector.Protocol.RTP.Jitter.Hint"),
+                                        );
+                                        menu.label(ui, stream.source.to_string());
+                                        menu.label(ui, stream.destination.to_string());
+                                        match stream.is_negotiated {
+                                            true => menu.hover_label(
+                                                ui,
+                                                "✔",
+                                                t!("Tab.Inspector.Protocol.RTP.Negotiated.Hint"),
+                                            ),
+                                            false => menu.label(ui, "-"),
+                                        };
+                                        menu.show(filters, record_action);
+                                        ui.end_row();
+                                    }
+                                });
+                        },
+                    );
+                }
+
+                Grid::new("Inspector.RTP.Packets")
+                    .striped(true)
+                    .num_columns(11)
+                    .show(ui, |ui| {
+                        if !indices.is_empty() {
+                            for key in [
+                                "Tab.Inspector.Label.Number",
+                                "Tab.Inspector.Protocol.RTP.SSRC",
+                                "Tab.Inspector.Protocol.RTP.PayloadType",
+                                "Tab.Inspector.Protocol.RTP.Sequence",
+                                "Tab.Inspector.Protocol.RTP.Timestamp",
+                                "Tab.Inspector.Protocol.RTP.Marker",
+                                "Tab.Inspector.Protocol.IpSender",
+                                "Tab.Inspector.Protocol.IpTarget",
+                                "Tab.Inspector.Protocol.MacSender",
+                                "Tab.Inspector.Protocol.MacTarget",
+                                "Tab.Inspector.Label.Note",
+                            ] {
+                                heading(ui, key);
+                            }
+                            ui.end_row();
+                        }
+
+                        for index in indices {
+                            let Some((packet, locator)) = storage.get(index) else {
+                                continue;
+                            };
+                            let mut menu = RowMenu::default();
+                            menu.label(ui, (index + 1).to_string());
+                            menu.label(ui, format!("0x{:08X}", packet.ssrc));
+                            menu.label(ui, packet.payload_type_name());
+                            menu.label(ui, packet.sequence_number.to_string());
+                            menu.label(ui, packet.timestamp.to_string());
+                            menu.label(ui, if packet.marker { "✔" } else { "-" });
+                            menu.ip_labels(ui, locator);
+                            let aliases = &ctx.net_storage.devices.aliases;
+                            menu.mac_labels(ui, locator, aliases);
+                            menu.show_record(
+                                &(packet, locator),
+                                filters,
+                                record_action,
+                            );
+                            ui.horizontal(|ui| {
+                                if let Some(id) = record_id(index) {
+                                    Self::record_buttons(ui, notes, id, requested);
+                                }
+                                if storage.is_truncated(index) {
+                                    Self::truncated_label(ui);
+                                }
+                            });
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+
+    pub fn sip_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        let storage = &mut ctx.net_storage.inspector.sip;
+        let Some(indices) = self.page_indices(ui, storage) else {
+            return;
+        };
+        let color = self.protocol_color(ui);
+        let notes = &ctx.net_storage.notes;
+        let record_id = self.record_id(storage);
+        let requested = &mut self.requested;
+        let filters = FilterField::available(&self.protocol_chosen);
+        let record_action = &mut self.record_action;
+
+        // Table
+        ScrollArea::both()
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                // Data rows
+                for index in indices {
+                    let Some((packet, locator)) = storage.get(index) else {
+                        continue;
+                    };
+                    let record_number = index + 1;
+
+                    let summary = match packet {
+                        SipDto::Request(request) => request.method.to_string(),
+                        SipDto::Response(response) => {
+                            format!("{} {}", response.status_code, response.reason)
+                        },
+                    };
+                    let id = record_id(index);
+                    let title = RichText::new(format!(
+                        "SIP Packet #{record_number}: {summary}{}{}",
+                        Self::note_mark(notes, id.as_ref()),
+                        Self::truncated_mark(storage.is_truncated(index))
+                    ))
+                    .color(color);
+                    let header = egui::CollapsingHeader::new(title)
+                        .id_salt(("SIP-Packet-Header", record_number));
+                    let response = header.show(ui, |ui| {
+                        Self::note_row(ui, notes, id, requested);
+                        Grid::new(("SIP-Packet", record_number))
+                            .striped(false)
+                            .num_columns(2)
+                            .show(ui, |ui| {
+                                let media = packet
+                                    .media()
+                                    .iter()
+                                    .map(ToString::to_string)
+                                    .collect::<Vec<_>>();
+                                let mut rows = match packet {
+                                    SipDto::Request(request) => vec![
+                                        (
+                                            "Tab.Inspector.Protocol.SIP.Request.Method",
+                                            request.method.to_string(),
+                                        ),
+                                        (
+                                            "Tab.Inspector.Protocol.SIP.Request.RequestUri",
+                                            request.request_uri.clone(),
+                                        ),
+                                    ],
+                                    SipDto::Response(response) => vec![
+                                        (
+                                            "Tab.Inspector.Protocol.SIP.Response.StatusCode",
+                                            response.status_code.to_string(),
+                                        ),
+                                        (
+                                            "Tab.Inspector.Protocol.SIP.Response.Reason",
+                                            response.reason.clone(),
+                                        ),
+                                    ],
+                                };
+                                rows.extend([
+                                    (
+                                        "Tab.Inspector.Protocol.SIP.CallId",
+                                        packet.call_id().unwrap_or("-").to_string(),
+                                    ),
+                                    (
+                                        "Tab.Inspector.Protocol.SIP.From",
+                                        packet.initiator().unwrap_or("-").to_string(),
+                                    ),
+                                    (
+                                        "Tab.Inspector.Protocol.SIP.To",
+                                        packet.recipient().unwrap_or("-").to_string(),
+                                    ),
+                                    (
+                                        "Tab.Inspector.Protocol.SIP.Media",
+                                        Self::join_or_dash(&media),
+                                    ),
+                                ]);
+
+                                for (key, value) in rows {
+                                    ui.label(styles::heading::grid(&t!(key)));
+                                    let mut menu = RowMenu::default();
+                                    menu.label(ui, value);
+                                    menu.show_record(
+                                        &(packet, locator),
+                                        filters,
+                                        record_action,
+                                    );
+                                    ui.end_row();
+                                }
+
+                                // Addresses are filterable, so they're added by the menu
+                                let aliases = &ctx.net_storage.devices.aliases;
+                                let (source_mac, target_mac) =
+                                    locator.mac_to_string(aliases);
+                                let ips = locator.ip();
+                                let addresses = [
+                                    (
+                                        "Tab.Inspector.Protocol.IpSender",
+                                        ips.map(|ips| ips.0.to_string()),
+                                        FilterField::SourceIp,
+                                    ),
+                                    (
+                                        "Tab.Inspector.Protocol.IpTarget",
+                                        ips.map(|ips| ips.1.to_string()),
+                                        FilterField::DestinationIp,
+                                    ),
+                                ];
+                                for (key, ip, field) in addresses {
+                                    ui.label(styles::heading::grid(&t!(key)));
+                                    let mut menu = RowMenu::default();
+                                    match ip {
+                                        Some(ip) => menu.filter_label(ui, &ip, field, &ip),
+                                        None => menu.label(ui, "-"),
+                                    }
+                                    menu.show(filters, record_action);
+                                    ui.end_row();
+                                }
+                                let macs = [
+                                    (
+                                        "Tab.Inspector.Protocol.MacSender",
+                                        source_mac,
+                                        &locator.mac.0,
+                                    ),
+                                    (
+                                        "Tab.Inspector.Protocol.MacTarget",
+                                        target_mac,
+                                        &locator.mac.1,
+                                    ),
+                                ];
+                                for (key, text, mac) in macs {
+                                    ui.label(styles::heading::grid(&t!(key)));
+                                    let mut menu = RowMenu::default();
+                                    menu.filter_label(ui, text, FilterField::Mac, mac);
+                                    menu.show(filters, record_action);
+                                    ui.end_row();
+                                }
+                            });
+
+                        let headers = packet.headers();
+                        if !headers.is_empty() {
+                            ui.label(styles::heading::grid(&t!(
+                                "Tab.Inspector.Protocol.SIP.Headers"
+                            )));
+                            Grid::new(("SIP-Headers", record_number))
+                                .striped(false)
+                                .num_columns(2)
+                                .show(ui, |ui| {
+                                    for (name, value) in headers {
+                                        let mut menu = RowMenu::default();
+                                        menu.label(ui, name);
+                                        menu.label(ui, value);
+                                        menu.show(filters, record_action);
+                                        ui.end_row();
+                                    }
+                                });
+                        }
+                    });
+                    if storage.is_truncated(index) {
+                        response
+                            .header_response
+                            .on_hover_text(t!("Tab.Inspector.Hover.Truncated"));
+                    }
+                }
+            });
     }
 
-    pub fn ipv6_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
-        let storage = &mut ctx.net_storage.inspector.ipv6;
+    pub fn smb2_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        let storage = &mut ctx.net_storage.inspector.smb2;
         self.protocol_view(
             ui,
             storage,
-            "Inspector.IPv6.Packets",
-            6,
+            &ctx.net_storage.notes,
+            "Inspector.SMB2.Packets",
+            10,
             &[
                 "Tab.Inspector.Label.Number",
-                "Tab.Inspector.Protocol.IPv6.AddressSource",
-                "Tab.Inspector.Protocol.IPv6.AddressDestination",
-                "Tab.Inspector.Protocol.IPv6.HopLimit",
+                "Tab.Inspector.Protocol.SMB2.Command",
+                "Tab.Inspector.Protocol.SMB2.Direction",
+                "Tab.Inspector.Protocol.SMB2.Status",
+                "Tab.Inspector.Protocol.SMB2.SessionId",
+                "Tab.Inspector.Protocol.SMB2.Details",
+                "Tab.Inspector.Protocol.IpSender",
+                "Tab.Inspector.Protocol.IpTarget",
                 "Tab.Inspector.Protocol.MacSender",
                 "Tab.Inspector.Protocol.MacTarget",
             ],
-            |ui, id, package| {
+            |ui, menu, id, package| {
                 let packet = &package.0;
                 let locator = &package.1;
-                let (source_mac, target_mac) =
-                    locator.mac_to_string(&ctx.net_storage.devices.aliases);
 
-                ui.label(id.to_string());
-                ui.label(packet.address_source.to_string());
-                ui.label(packet.address_destination.to_string());
-                ui.label(packet.hop_limit.to_string());
-                ui.label(source_mac);
-                ui.label(target_mac);
+                let command = match &packet.command {
+                    Some(command) => command.to_string(),
+                    None => t!("Tab.Inspector.Protocol.SMB2.Encrypted").to_string(),
+                };
+                let direction = match packet.is_response {
+                    true => t!("Tab.Inspector.Protocol.SMB2.Direction.Response"),
+                    false => t!("Tab.Inspector.Protocol.SMB2.Direction.Request"),
+                };
+                let details = if let Some(share) = &packet.share {
+                    t!("Tab.Inspector.Protocol.SMB2.Share", "share" = share).to_string()
+                } else if let Some(user) = &packet.user {
+                    t!(
+                        "Tab.Inspector.Protocol.SMB2.User",
+                        "user" = user,
+                        "workstation" = user.workstation
+                    )
+                    .to_string()
+                } else {
+                    "-".to_string()
+                };
+
+                menu.label(ui, id.to_string());
+                menu.label(ui, command);
+                menu.label(ui, direction);
+                menu.label(ui, format!("0x{:08X}", packet.status));
+                menu.label(ui, format!("0x{:016X}", packet.session_id));
+                menu.label(ui, details);
+                menu.ip_labels(ui, locator);
+                menu.mac_labels(ui, locator, &ctx.net_storage.devices.aliases);
             },
         );
     }
 
-    pub fn tcp_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
-        let storage = &mut ctx.net_storage.inspector.tcp;
+    pub fn syslog_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        let storage = &mut ctx.net_storage.inspector.syslog;
         self.protocol_view(
             ui,
             storage,
-            "Inspector.TCP.Packets",
-            8,
+            &ctx.net_storage.notes,
+            "Inspector.Syslog.Packets",
+            9,
             &[
                 "Tab.Inspector.Label.Number",
-                "Tab.Inspector.Protocol.TCP.PortSource",
-                "Tab.Inspector.Protocol.TCP.PortDestination",
-                "Tab.Inspector.Protocol.TCP.PossibleApplication",
+                "Tab.Inspector.Protocol.Syslog.Severity",
+                "Tab.Inspector.Protocol.Syslog.Facility",
+                "Tab.Inspector.Protocol.Syslog.Timestamp",
+                "Tab.Inspector.Protocol.Syslog.Hostname",
+                "Tab.Inspector.Protocol.Syslog.Application",
+                "Tab.Inspector.Protocol.Syslog.Message",
                 "Tab.Inspector.Protocol.IpSender",
-                "Tab.Inspector.Protocol.IpTarget",
                 "Tab.Inspector.Protocol.MacSender",
-                "Tab.Inspector.Protocol.MacTarget",
             ],
-            |ui, id, package| {
+            |ui, menu, id, package| {
                 let packet = &package.0;
                 let locator = &package.1;
-                let (source_ip, target_ip) = locator.ip_to_string();
-                let (source_mac, target_mac) =
+                let (source_ip, _) = locator.ip_to_string();
+                let (source_mac, _) =
                     locator.mac_to_string(&ctx.net_storage.devices.aliases);
+                let application = match (&packet.app_name, &packet.process_id) {
+                    (Some(app_name), Some(process_id)) => {
+                        format!("{app_name}[{process_id}]")
+                    },
+                    (Some(app_name), None) => app_name.clone(),
+                    (None, _) => "-".to_string(),
+                };
+
+                // Whole row is colored by the severity
+                let color = Self::severity_color(&packet.severity);
+                let text = |value: &str| {
+                    let text = RichText::new(value);
+                    match color {
+                        Some(color) => text.color(color),
+                        None => text,
+                    }
+                };
 
-                ui.label(id.to_string());
-                ui.label(packet.port_source.to_string());
-                ui.label(packet.port_destination.to_string());
-                ui.label(&packet.possible_application);
-                ui.label(source_ip);
-                ui.label(target_ip);
-                ui.label(source_mac);
-                ui.label(target_mac);
+                menu.label(ui, text(&id.to_string()));
+                menu.filter_label(
+                    ui,
+                    text(&packet.severity.to_string()),
+                    FilterField::SyslogSeverity,
+                    &packet.severity,
+                );
+                menu.label(ui, text(&packet.facility.to_string()));
+                menu.label(ui, text(packet.timestamp.as_deref().unwrap_or("-")));
+                menu.label(ui, text(packet.hostname.as_deref().unwrap_or("-")));
+                menu.label(ui, text(&application));
+                menu.filter_label(
+                    ui,
+                    text(&packet.message),
+                    FilterField::SyslogMessage,
+                    &packet.message,
+                );
+                menu.filter_label(
+                    ui,
+                    text(&source_ip),
+                    FilterField::SourceIp,
+                    &source_ip,
+                );
+                menu.filter_label(
+                    ui,
+                    text(&source_mac),
+                    FilterField::Mac,
+                    &locator.mac.0,
+                );
             },
         );
     }
 
-    pub fn udp_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
-        let storage = &mut ctx.net_storage.inspector.udp;
+    pub fn tftp_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        let storage = &mut ctx.net_storage.inspector.tftp;
         self.protocol_view(
             ui,
             storage,
-            "Inspector.UDP.Packets",
-            8,
+            &ctx.net_storage.notes,
+            "Inspector.TFTP.Packets",
+            9,
             &[
                 "Tab.Inspector.Label.Number",
-                "Tab.Inspector.Protocol.UDP.PortSource",
-                "Tab.Inspector.Protocol.UDP.PortDestination",
-                "Tab.Inspector.Protocol.UDP.PossibleApplication",
+                "Tab.Inspector.Protocol.TFTP.Opcode",
+                "Tab.Inspector.Protocol.TFTP.Details",
+                "Tab.Inspector.Protocol.TFTP.Mode",
+                "Tab.Inspector.Protocol.TFTP.Options",
                 "Tab.Inspector.Protocol.IpSender",
                 "Tab.Inspector.Protocol.IpTarget",
                 "Tab.Inspector.Protocol.MacSender",
                 "Tab.Inspector.Protocol.MacTarget",
             ],
-            |ui, id, package| {
+            |ui, menu, id, package| {
                 let packet = &package.0;
                 let locator = &package.1;
-                let (source_ip, target_ip) = locator.ip_to_string();
-                let (source_mac, target_mac) =
-                    locator.mac_to_string(&ctx.net_storage.devices.aliases);
 
-                ui.label(id.to_string());
-                ui.label(packet.port_source.to_string());
-                ui.label(packet.port_destination.to_string());
-                ui.label(&packet.possible_application);
-                ui.label(source_ip);
-                ui.label(target_ip);
-                ui.label(source_mac);
-                ui.label(target_mac);
+                let details = match packet.opcode {
+                    Opcode::ReadRequest | Opcode::WriteRequest => {
+                        packet.filename.clone().unwrap_or("-".to_string())
+                    },
+                    Opcode::Data => t!(
+                        "Tab.Inspector.Protocol.TFTP.BlockData",
+                        "block" = packet.block.unwrap_or_default(),
+                        "length" = packet.data_length.unwrap_or_default()
+                    )
+                    .to_string(),
+                    Opcode::Acknowledgement => t!(
+                        "Tab.Inspector.Protocol.TFTP.Block",
+                        "block" = packet.block.unwrap_or_default()
+                    )
+                    .to_string(),
+                    Opcode::Error => match (&packet.error_code, &packet.error_message) {
+                        (Some(code), Some(message)) if !message.is_empty() => {
+                            format!("{code}: {message}")
+                        },
+                        (Some(code), _) => code.to_string(),
+                        (None, _) => "-".to_string(),
+                    },
+                    Opcode::OptionAcknowledgement => "-".to_string(),
+                };
+                let mode = match &packet.mode {
+                    Some(mode) => mode.to_string(),
+                    None => "-".to_string(),
+                };
+                let options = packet
+                    .options
+                    .iter()
+                    .map(|option| option.to_string())
+                    .collect::<Vec<_>>();
+
+                menu.label(ui, id.to_string());
+                menu.label(ui, packet.opcode.to_string());
+                menu.label(ui, details);
+                menu.label(ui, mode);
+                menu.label(ui, Self::join_or_dash(&options));
+                menu.ip_labels(ui, locator);
+                menu.mac_labels(ui, locator, &ctx.net_storage.devices.aliases);
             },
         );
     }
 
+    fn severity_color(severity: &Severity) -> Option<egui::Color32> {
+        match severity {
+            Severity::Emergency | Severity::Alert | Severity::Critical => {
+                Some(styles::colors::get(Role::SeverityCritical))
+            },
+            Severity::Error => Some(styles::colors::get(Role::SeverityError)),
+            Severity::Warning => Some(styles::colors::get(Role::SeverityWarning)),
+            Severity::Notice | Severity::Informational => None,
+            Severity::Debug => Some(styles::colors::get(Role::SeverityDebug)),
+        }
+    }
+
     fn tab_heading(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
         ui.add_space(styles::space::TAB);
 
         ui.columns(3, |columns| {
             const LEFT_COLUMN: usize = 0;
+            const MIDDLE_COLUMN: usize = 1;
             const RIGHT_COLUMN: usize = 2;
             columns[LEFT_COLUMN].horizontal_wrapped(|ui| {
                 ui.heading(
@@ -655,11 +2453,19 @@ impl InspectorTab {
                 );
             });
 
+            self.search_view(&mut columns[MIDDLE_COLUMN], ctx);
+
             columns[RIGHT_COLUMN].with_layout(
                 egui::Layout::right_to_left(egui::Align::Min),
                 |ui| {
                     if ui.button(t!("Button.Clear")).clicked() {
-                        ctx.net_storage.inspector.clear();
+                        let inspector = &mut ctx.net_storage.inspector;
+                        self.cleared.extend(
+                            ProtocolsRegistered::iter().map(|protocol| {
+                                (protocol, inspector.generation(&protocol))
+                            }),
+                        );
+                        inspector.clear();
                         self.page = 1;
                     }
                 },
@@ -668,25 +2474,34 @@ impl InspectorTab {
     }
 
     fn clear_pages_buttons<T>(
-        &mut self, ui: &mut egui::Ui, storage: &mut Vec<T>,
+        &mut self, ui: &mut egui::Ui, storage: &mut Records<T>, total_items: usize,
     ) -> bool {
         let mut to_restart = false;
 
-        Grid::new("").num_columns(8).striped(false).show(ui, |ui| {
+        // Records may be removed or the page size changed since the last frame
+        let total_pages = Self::total_pages(total_items, self.page_size);
+        self.page = self.page.clamp(1, total_pages);
+
+        // Wrapped, so the pages stay reachable in the narrow window
+        ui.horizontal_wrapped(|ui| {
             if storage.is_empty() {
                 ui.label(format!("{}:", t!("Tab.Inspector.Label.Protocol")));
             }
 
             ui.with_layout(egui::Layout::top_down(egui::Align::Min), |ui| {
                 egui::ComboBox::from_id_salt("Combobox.Inspector.Protocols")
-                    .selected_text(self.protocol_chosen.to_string())
+                    .selected_text(self.protocol_label(ui, self.protocol_chosen))
                     .show_ui(ui, |ui| {
-                        for protocol in ProtocolId::iter() {
+                        // Radio information is shown along with 802.11 frames
+                        for protocol in ProtocolId::iter()
+                            .filter(|protocol| *protocol != ProtocolId::Radiotap)
+                        {
+                            let label = self.protocol_label(ui, protocol);
                             if ui
                                 .selectable_value(
                                     &mut self.protocol_chosen,
                                     protocol,
-                                    protocol.to_string(),
+                                    label,
                                 )
                                 .clicked()
                             {
@@ -699,12 +2514,17 @@ impl InspectorTab {
 
             // Clear button or empty label
             if !storage.is_empty() {
-                let total_pages = self.total_pages(storage.len());
-
                 const LEFT_FAR: isize = -5;
                 const LEFT: isize = -1;
                 const RIGHT: isize = 1;
                 const RIGHT_FAR: isize = 5;
+                if ui
+                    .add_enabled(self.page > 1, egui::Button::new("⏮"))
+                    .on_hover_text(t!("Tab.Inspector.Hover.FirstPage"))
+                    .clicked()
+                {
+                    self.page = 1;
+                };
                 if ui
                     .add_enabled(
                         Self::can_go_to_page(self.page, LEFT_FAR, total_pages),
@@ -723,7 +2543,16 @@ impl InspectorTab {
                 {
                     self.page = (self.page as isize + LEFT) as usize;
                 };
-                ui.label(format!("Page {} of {} total", self.page, total_pages));
+                ui.label(t!("Tab.Inspector.Label.Page"));
+                // Typed number is applied by Enter, not by every digit
+                ui.add(
+                    DragValue::new(&mut self.page)
+                        .speed(0.1)
+                        .range(1..=total_pages)
+                        .update_while_editing(false),
+                )
+                .on_hover_text(t!("Tab.Inspector.Hover.GoToPage"));
+                ui.label(t!("Tab.Inspector.Label.PageOf", "total" = total_pages));
                 if ui
                     .add_enabled(
                         Self::can_go_to_page(self.page, RIGHT, total_pages),
@@ -742,25 +2571,161 @@ impl InspectorTab {
                 {
                     self.page = (self.page as isize + RIGHT_FAR) as usize;
                 };
+                if ui
+                    .add_enabled(self.page < total_pages, egui::Button::new("⏭"))
+                    .on_hover_text(t!("Tab.Inspector.Hover.LastPage"))
+                    .clicked()
+                {
+                    self.page = total_pages;
+                };
                 if ui.button(t!("Button.Clear")).clicked() {
                     self.page = 1;
+                    if let Some(protocol) =
+                        ProtocolsRegistered::from_id(&self.protocol_chosen)
+                    {
+                        self.cleared.push((protocol, storage.generation()));
+                    }
                     storage.clear();
                     to_restart = true;
                 }
             } else {
                 ui.label(RichText::new(t!("Tab.Inspector.Label.Empty")).italics());
             }
-            ui.end_row();
         });
 
         to_restart
     }
 
-    const PAGE_SIZE: usize = 100;
-    fn page_slice<T>(items: &[T], page: usize) -> &[T] {
-        let start = (page - 1).saturating_mul(Self::PAGE_SIZE);
-        let end = (start + Self::PAGE_SIZE).min(items.len());
-        &items[start..end]
+    // Notes are addressed by the index within the current generation of the storage
+    fn record_id<T>(
+        &self, storage: &Records<T>,
+    ) -> impl Fn(usize) -> Option<RecordId> + use<T> {
+        let protocol = ProtocolsRegistered::from_id(&self.protocol_chosen);
+        let generation = storage.generation();
+        move |index| {
+            protocol.map(|protocol| RecordId {
+                protocol,
+                generation,
+                index,
+            })
+        }
+    }
+
+    // Returns `true`, if the note of the record is going to be edited
+    fn note_button(ui: &mut egui::Ui, notes: &RecordNotes, id: &RecordId) -> bool {
+        match notes.get(id) {
+            Some(note) => ui.button("📝").on_hover_text(note).clicked(),
+            None => ui
+                .add(egui::Button::new("✏").frame(false))
+                .on_hover_text(t!("Tab.Inspector.Hover.AddNote"))
+                .clicked(),
+        }
+    }
+
+    fn record_buttons(
+        ui: &mut egui::Ui, notes: &RecordNotes, id: RecordId, requested: &mut RowRequests,
+    ) {
+        if Self::note_button(ui, notes, &id) {
+            requested.note = Some(id);
+        }
+        if ui
+            .add(egui::Button::new("🔍").frame(false))
+            .on_hover_text(t!("Tab.Inspector.Hover.FrameDetail"))
+            .clicked()
+        {
+            requested.frame = Some(id);
+        }
+    }
+
+    // Records shown as the collapsing headers get the buttons in their body
+    fn note_row(
+        ui: &mut egui::Ui, notes: &RecordNotes, id: Option<RecordId>,
+        requested: &mut RowRequests,
+    ) {
+        let Some(id) = id else {
+            return;
+        };
+        ui.horizontal_wrapped(|ui| {
+            Self::record_buttons(ui, notes, id, requested);
+            if let Some(note) = notes.get(&id) {
+                ui.label(RichText::new(note).italics());
+            }
+        });
+    }
+
+    fn note_mark(notes: &RecordNotes, id: Option<&RecordId>) -> &'static str {
+        match id.and_then(|id| notes.get(id)) {
+            Some(_) => " 📝",
+            None => "",
+        }
+    }
+
+    // Records of the frames, cut off by the snapshot length, may miss their tail
+    fn truncated_label(ui: &mut egui::Ui) {
+        ui.label("✂")
+            .on_hover_text(t!("Tab.Inspector.Hover.Truncated"));
+    }
+
+    fn truncated_mark(is_truncated: bool) -> &'static str {
+        match is_truncated {
+            true => " ✂",
+            false => "",
+        }
+    }
+
+    fn protocol_color(&self, ui: &egui::Ui) -> egui::Color32 {
+        styles::protocols::color(ui, &self.protocol_chosen, &self.protocol_colors)
+    }
+
+    // Name with the amount of the records, e.g. "DNS (1 245)" or "DNS (12 / 1 245)",
+    // if only the part of them is shown.
+    fn protocol_label(&self, ui: &egui::Ui, protocol: ProtocolId) -> RichText {
+        let counts = ProtocolsRegistered::from_id(&protocol)
+            .and_then(|registered| self.counts.get(&registered));
+        let name = match counts {
+            None | Some((0, _)) => protocol.to_string(),
+            Some((total, in_range)) => {
+                let shown = match protocol == self.protocol_chosen {
+                    true => self.matched.or(*in_range),
+                    false => *in_range,
+                };
+                let total = format::count(*total as u64);
+                match shown {
+                    Some(shown) => {
+                        format!("{protocol} ({} / {total})", format::count(shown as u64))
+                    },
+                    None => format!("{protocol} ({total})"),
+                }
+            },
+        };
+
+        styles::protocols::label(ui, &protocol, &name, &self.protocol_colors)
+    }
+
+    fn join_or_dash(values: &[String]) -> String {
+        match values.is_empty() {
+            true => "-".to_string(),
+            false => values.join(", "),
+        }
+    }
+
+    // Position of the first record on the page
+    fn page_start(page: usize, page_size: usize) -> usize {
+        page.saturating_sub(1).saturating_mul(page_size)
+    }
+
+    // Empty, if the items are shrunk below the start of the page
+    fn page_slice<T>(items: &[T], page: usize, page_size: usize) -> &[T] {
+        let start = Self::page_start(page, page_size).min(items.len());
+        let end = start.saturating_add(page_size).min(items.len());
+        items.get(start..end).unwrap_or_default()
+    }
+
+    // Page with the same first record, so the view isn't thrown to the other records
+    fn page_resized(page: usize, size_old: usize, size_new: usize) -> usize {
+        Self::page_start(page, size_old)
+            .checked_div(size_new)
+            .map_or(1, |page| page.saturating_add(1))
     }
 
     fn can_go_to_page(current_page: usize, delta: isize, total_pages: usize) -> bool {
@@ -768,8 +2733,40 @@ impl InspectorTab {
         (1..=(total_pages as isize)).contains(&target)
     }
 
-    fn total_pages(&self, total_items: usize) -> usize {
-        let pages = total_items.div_ceil(Self::PAGE_SIZE);
+    fn total_pages(total_items: usize, page_size: usize) -> usize {
+        let pages = total_items.div_ceil(page_size.max(1));
         usize::max(1, pages)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_slice() {
+        let items: Vec<usize> = (0..250).collect();
+        assert_eq!(InspectorTab::page_slice(&items, 1, 100), &items[..100]);
+        assert_eq!(InspectorTab::page_slice(&items, 3, 100), &items[200..]);
+        // Storage is shrunk below the start of the page
+        assert!(InspectorTab::page_slice(&items[..150], 3, 100).is_empty());
+        assert!(InspectorTab::page_slice(&items, usize::MAX, 1000).is_empty());
+        // Page isn't clamped yet
+        assert_eq!(InspectorTab::page_slice(&items, 0, 25), &items[..25]);
+    }
+
+    #[test]
+    fn test_page_size_change() {
+        assert_eq!(InspectorTab::total_pages(0, 100), 1);
+        assert_eq!(InspectorTab::total_pages(1001, 500), 3);
+
+        // First record of the page stays visible
+        assert_eq!(InspectorTab::page_resized(1, 100, 500), 1);
+        assert_eq!(InspectorTab::page_resized(7, 100, 500), 2);
+        assert_eq!(InspectorTab::page_resized(2, 500, 25), 21);
+
+        assert!(InspectorTab::can_go_to_page(1, 1, 2));
+        assert!(!InspectorTab::can_go_to_page(1, -1, 2));
+        assert!(!InspectorTab::can_go_to_page(2, 5, 2));
+    }
+}
@@ -1,10 +1,25 @@
 use crate::context::Context;
+use crate::net::anonymize::AnonymizeSettings;
+use crate::net::lookup;
+use crate::net::lookup::ImportMode;
+use crate::notifications;
+use crate::notifications::NotificationSettings;
+use crate::self_check;
+use crate::ui;
+use crate::ui::format;
+use crate::ui::format::{IdnForm, Prefixes};
 use crate::ui::modals::message::MessageModal;
 use crate::ui::styles;
-use crate::ui::styles::{spacing, themes};
+use crate::ui::styles::colors::Role;
+use crate::ui::styles::{colors, layout, protocols, spacing, themes};
 use crate::ui::tabs::Tab;
+use crate::ui::tabs::inspector;
+use crate::ui::title::TitleSettings;
 use crate::{config, logging};
-use egui::{Checkbox, DragValue, Grid, RichText, TextEdit};
+use common::io::FileKind;
+use dpi::analysis::ports::DatabaseFormat;
+use dpi::protocols::ProtocolId;
+use egui::{Checkbox, Color32, DragValue, Grid, RichText, Slider, TextEdit};
 use log::LevelFilter;
 use std::collections::BTreeMap;
 use std::sync::LazyLock;
@@ -18,15 +33,44 @@ pub struct SettingsClientTab {
     language: config::Language,
     log_format_choice: String,
     log_level_choice: LevelFilter,
+    window_persistence: bool,
 
     // Fields that applied by button
+    anonymization: AnonymizeSettings,
+    arp_reply_window_ms: u32,
+    arp_unanswered_min_requests: u32,
+    arp_unanswered_ratio_percent: u32,
+    byte_prefixes: Prefixes,
+    color_palette: colors::Palette,
+    debug_overlay: bool,
+    idn_form: IdnForm,
+    inspector_page_size: usize,
+    ip_conflict_failover_alerts: bool,
+    ip_conflict_window_seconds: u32,
+    notifications: NotificationSettings,
     parsed_frames_limit_enabled: bool,
     parsed_frames_limit: usize,
     ping_delay_seconds: i64,
+    // Applied to the next import
+    port_import_format: DatabaseFormat,
+    port_import_mode: ImportMode,
+    // Override, that is edited
+    port_override_port: u16,
+    port_override_name: String,
+    protocol_color_chosen: ProtocolId,
+    // None, if the color isn't edited
+    protocol_color: Option<Color32>,
+    router_learning_seconds: u32,
+    storage_soft_limit_enabled: bool,
+    storage_soft_limit_mb: usize,
     theme: themes::Preference,
+    ui_scale: f32,
     unparsed_frames_drop: bool,
+    unparsed_frames_spill_enabled: bool,
+    unparsed_frames_spill_mb: usize,
     unparsed_frames_threshold_enabled: bool,
     unparsed_frames_threshold: usize,
+    window_title: TitleSettings,
 }
 
 type ViewFn = fn(&mut SettingsClientTab, &mut egui::Ui, &mut Context);
@@ -37,10 +81,54 @@ static VIEWS: LazyLock<BTreeMap<String, ViewFn>> = LazyLock::new(|| {
             t!("Tab.SettingsClient.Label.SaveConfig").to_string(),
             save_client_config_view as ViewFn,
         ),
+        (
+            t!("Tab.SettingsClient.Label.Anonymization").to_string(),
+            anonymization_view as ViewFn,
+        ),
+        (
+            t!("Tab.SettingsClient.Label.ArpReplyWindow").to_string(),
+            arp_reply_window_view as ViewFn,
+        ),
+        (
+            t!("Tab.SettingsClient.Label.ArpUnanswered").to_string(),
+            arp_unanswered_view as ViewFn,
+        ),
+        (
+            t!("Tab.SettingsClient.Label.BytePrefixes").to_string(),
+            byte_prefixes_view as ViewFn,
+        ),
+        (
+            t!("Tab.SettingsClient.Label.ColorPalette").to_string(),
+            color_palette_view as ViewFn,
+        ),
         (
             t!("Tab.SettingsClient.Label.Compression").to_string(),
             compression_view as ViewFn,
         ),
+        (
+            t!("Tab.SettingsClient.Label.DataDirectory").to_string(),
+            data_directory_view as ViewFn,
+        ),
+        (
+            t!("Tab.SettingsClient.Label.DebugOverlay").to_string(),
+            debug_overlay_view as ViewFn,
+        ),
+        (
+            t!("Tab.SettingsClient.Label.DomainNames").to_string(),
+            domain_names_view as ViewFn,
+        ),
+        (
+            t!("Tab.SettingsClient.Label.InspectorPageSize").to_string(),
+            inspector_page_size_view as ViewFn,
+        ),
+        (
+            t!("Tab.SettingsClient.Label.IpConflictFailoverAlerts").to_string(),
+            ip_conflict_failover_view as ViewFn,
+        ),
+        (
+            t!("Tab.SettingsClient.Label.IpConflictWindow").to_string(),
+            ip_conflict_window_view as ViewFn,
+        ),
         (
             t!("Tab.SettingsClient.Label.Language").to_string(),
             language_view as ViewFn,
@@ -53,14 +141,58 @@ static VIEWS: LazyLock<BTreeMap<String, ViewFn>> = LazyLock::new(|| {
             t!("Tab.SettingsClient.Label.LogLevel").to_string(),
             logs_level_view as ViewFn,
         ),
+        (
+            t!("Tab.SettingsClient.Label.Notifications").to_string(),
+            notifications_view as ViewFn,
+        ),
+        (
+            t!("Tab.SettingsClient.Label.NotificationInterval").to_string(),
+            notification_interval_view as ViewFn,
+        ),
+        (
+            t!("Tab.SettingsClient.Label.NotificationSeverity").to_string(),
+            notification_severity_view as ViewFn,
+        ),
+        (
+            t!("Tab.SettingsClient.Label.NotificationSoundFile").to_string(),
+            notification_sound_file_view as ViewFn,
+        ),
+        (
+            t!("Tab.SettingsClient.Label.PortNames").to_string(),
+            port_names_view as ViewFn,
+        ),
+        (
+            t!("Tab.SettingsClient.Label.PortOverrides").to_string(),
+            port_overrides_view as ViewFn,
+        ),
+        (
+            t!("Tab.SettingsClient.Label.ProtocolColors").to_string(),
+            protocol_colors_view as ViewFn,
+        ),
+        (
+            t!("Tab.SettingsClient.Label.RouterLearning").to_string(),
+            router_learning_view as ViewFn,
+        ),
+        (
+            t!("Tab.SettingsClient.Label.SelfCheck").to_string(),
+            self_check_view as ViewFn,
+        ),
         (
             t!("Tab.SettingsClient.Label.SyncDelay").to_string(),
             ping_delay_view as ViewFn,
         ),
+        (
+            t!("Tab.SettingsClient.Label.StorageSoftLimit").to_string(),
+            storage_limit_view as ViewFn,
+        ),
         (
             t!("Tab.SettingsClient.Label.Theme").to_string(),
             theme_view as ViewFn,
         ),
+        (
+            t!("Tab.SettingsClient.Label.UiScale").to_string(),
+            ui_scale_view as ViewFn,
+        ),
         (
             t!("Tab.SettingsClient.Label.UnparsedFramesDrop").to_string(),
             unparsed_drop_view as ViewFn,
@@ -69,10 +201,22 @@ static VIEWS: LazyLock<BTreeMap<String, ViewFn>> = LazyLock::new(|| {
             t!("Tab.SettingsClient.Label.ParsedFramesLimit").to_string(),
             parsed_limit_view as ViewFn,
         ),
+        (
+            t!("Tab.SettingsClient.Label.UnparsedFramesSpill").to_string(),
+            unparsed_spill_view as ViewFn,
+        ),
         (
             t!("Tab.SettingsClient.Label.UnparsedFramesThreshold").to_string(),
             unparsed_threshold_view as ViewFn,
         ),
+        (
+            t!("Tab.SettingsClient.Label.WindowState").to_string(),
+            window_state_view as ViewFn,
+        ),
+        (
+            t!("Tab.SettingsClient.Label.WindowTitle").to_string(),
+            window_title_view as ViewFn,
+        ),
     ])
 });
 
@@ -84,16 +228,64 @@ impl SettingsClientTab {
             language: ctx.config.language.clone(),
             log_format_choice: ctx.config.log_format.clone(),
             log_level_choice: ctx.config.log_level,
+            window_persistence: ctx.config.window_persistence,
 
+            anonymization: ctx.client_settings.anonymization,
+            arp_reply_window_ms: ctx.client_settings.arp_reply_window_ms,
+            arp_unanswered_min_requests: ctx.client_settings.arp_unanswered_min_requests,
+            arp_unanswered_ratio_percent: ctx
+                .client_settings
+                .arp_unanswered_ratio_percent,
+            byte_prefixes: ctx.client_settings.byte_prefixes,
+            color_palette: ctx.client_settings.color_palette,
+            debug_overlay: ctx.client_settings.debug_overlay,
+            idn_form: ctx.client_settings.idn_form,
+            inspector_page_size: ctx.client_settings.inspector_page_size,
+            ip_conflict_failover_alerts: ctx.client_settings.ip_conflict_failover_alerts,
+            ip_conflict_window_seconds: ctx.client_settings.ip_conflict_window_seconds,
+            notifications: ctx.client_settings.notifications.clone(),
             parsed_frames_limit_enabled: ctx
                 .client_settings
                 .parsed_frames_limit
                 .is_some(),
             parsed_frames_limit: ctx.client_settings.parsed_frames_limit.unwrap_or(0),
             ping_delay_seconds: ctx.client_settings.sync_delay_seconds,
+            port_import_format: ctx
+                .net_storage
+                .lookup
+                .port_import
+                .as_ref()
+                .map(|import| import.format)
+                .unwrap_or_default(),
+            port_import_mode: ctx
+                .net_storage
+                .lookup
+                .port_import
+                .as_ref()
+                .map(|import| import.mode)
+                .unwrap_or_default(),
+            port_override_port: 0,
+            port_override_name: String::new(),
+            protocol_color_chosen: ProtocolId::Arp,
+            protocol_color: None,
+            router_learning_seconds: ctx.client_settings.router_learning_seconds,
+            storage_soft_limit_enabled: ctx
+                .client_settings
+                .storage_soft_limit_mb
+                .is_some(),
+            storage_soft_limit_mb: ctx.client_settings.storage_soft_limit_mb.unwrap_or(0),
             theme: ctx.client_settings.theme,
+            ui_scale: ctx.client_settings.ui_scale,
 
             unparsed_frames_drop: ctx.client_settings.unparsed_frames_drop,
+            unparsed_frames_spill_enabled: ctx
+                .client_settings
+                .unparsed_frames_spill_mb
+                .is_some(),
+            unparsed_frames_spill_mb: ctx
+                .client_settings
+                .unparsed_frames_spill_mb
+                .unwrap_or(0),
             unparsed_frames_threshold_enabled: ctx
                 .client_settings
                 .unparsed_frames_threshold
@@ -102,6 +294,7 @@ impl SettingsClientTab {
                 .client_settings
                 .unparsed_frames_threshold
                 .unwrap_or(0),
+            window_title: ctx.client_settings.window_title,
         }
     }
 }
@@ -124,15 +317,14 @@ impl SettingsClientTab {
                     egui::Layout::top_down_justified(egui::Align::Center),
                     |ui| {
                         spacing::with_temp_y(ui, spacing::GRID, |ui| {
-                            Grid::new("Settings.Grid")
+                            let grid = Grid::new("Settings.Grid")
                                 .striped(false)
-                                .num_columns(GRID_COLUMNS)
-                                .show(ui, |ui| {
-                                    for (_, view) in VIEWS.iter() {
-                                        view(self, ui, ctx);
-                                        ui.end_row();
-                                    }
-                                });
+                                .num_columns(GRID_COLUMNS);
+                            layout::Rows::show(ui, grid, |ui, rows| {
+                                for (_, view) in VIEWS.iter() {
+                                    rows.row(ui, |ui| view(self, ui, ctx));
+                                }
+                            });
                         });
                     },
                 );
@@ -171,14 +363,57 @@ fn save_client_config_view(
         ctx.config.compression = ctx.client_settings.compression;
 
         // Fields that applied by button
+        let anonymization = &ctx.client_settings.anonymization;
+        ctx.config.anonymize_preserve_oui = anonymization.preserve_oui;
+        ctx.config.anonymize_preserve_private = anonymization.preserve_private;
+        ctx.config.arp_reply_window_ms = ctx.client_settings.arp_reply_window_ms;
+        ctx.config.arp_unanswered_min_requests =
+            ctx.client_settings.arp_unanswered_min_requests;
+        ctx.config.arp_unanswered_ratio_percent =
+            ctx.client_settings.arp_unanswered_ratio_percent;
+        ctx.config.byte_prefixes = ctx.client_settings.byte_prefixes;
+        ctx.config.color_palette = ctx.client_settings.color_palette;
+        ctx.config.idn_form = ctx.client_settings.idn_form;
+        ctx.config.inspector_page_size = ctx.client_settings.inspector_page_size;
+        ctx.config.ip_conflict_failover_alerts =
+            ctx.client_settings.ip_conflict_failover_alerts;
+        ctx.config.ip_conflict_window_seconds =
+            ctx.client_settings.ip_conflict_window_seconds;
+        let notifications = &ctx.client_settings.notifications;
+        ctx.config.notification_desktop = notifications.desktop;
+        ctx.config.notification_interval_seconds = notifications.interval_seconds;
+        ctx.config.notification_severity = notifications.severity;
+        ctx.config.notification_sound = notifications.sound;
+        ctx.config.notification_sound_file = notifications.sound_file.clone();
         ctx.config.parsed_frames_limit = ctx.client_settings.parsed_frames_limit;
+        let lookup = &ctx.net_storage.lookup;
+        ctx.config.port_overrides = lookup.port_overrides.clone();
+        match &lookup.port_import {
+            Some(import) => {
+                ctx.config.ports_import_file = import.path.clone();
+                ctx.config.ports_import_format = import.format;
+                ctx.config.ports_import_mode = import.mode;
+            },
+            None => ctx.config.ports_import_file = String::new(),
+        }
+        ctx.config.protocol_colors = ctx.client_settings.protocol_colors.clone();
+        ctx.config.router_learning_seconds = ctx.client_settings.router_learning_seconds;
+        ctx.config.storage_soft_limit_mb = ctx.client_settings.storage_soft_limit_mb;
         ctx.config.theme = ctx.client_settings.theme;
         ctx.config.sync_delay_seconds = ctx.client_settings.sync_delay_seconds;
+        ctx.config.ui_scale = ctx.client_settings.ui_scale;
         ctx.config.unparsed_frames_drop = ctx.client_settings.unparsed_frames_drop;
+        ctx.config.unparsed_frames_spill_mb =
+            ctx.client_settings.unparsed_frames_spill_mb;
         ctx.config.unparsed_frames_threshold =
             ctx.client_settings.unparsed_frames_threshold;
+        let window_title = &ctx.client_settings.window_title;
+        ctx.config.window_title_alerts = window_title.alerts;
+        ctx.config.window_title_devices = window_title.devices;
+        ctx.config.window_title_stats = window_title.enabled;
+        ctx.config.window_title_throughput = window_title.throughput;
 
-        match ctx.config.save_to_file() {
+        match ctx.config.save_to_file(&ctx.data_dir) {
             Ok(_) => {
                 log::info!("Client Settings: Successfully saved client config.");
                 MessageModal::info(&t!("Message.Success.ClientConfigSaved"))
@@ -196,290 +431,1292 @@ fn save_client_config_view(
     }
 }
 
-fn compression_view(tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context) {
-    let label = styles::heading::normal(&t!("Tab.SettingsClient.Label.Compression"));
-    let not_applied = tab.compression != ctx.client_settings.compression;
+fn byte_prefixes_view(tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context) {
+    let label = styles::heading::normal(&t!("Tab.SettingsClient.Label.BytePrefixes"));
+    let not_applied = tab.byte_prefixes != ctx.client_settings.byte_prefixes;
     styles::text::field_not_applied(ui, label, not_applied);
 
-    ui.add(Checkbox::without_text(&mut tab.compression));
     styles::invisible(ui);
 
+    ui.with_layout(egui::Layout::top_down(egui::Align::Min), |ui| {
+        egui::ComboBox::from_id_salt("Settings.BytePrefixes.ComboBox")
+            .width(200.0)
+            .selected_text(tab.byte_prefixes.localize())
+            .show_ui(ui, |ui| {
+                for prefixes in Prefixes::iter() {
+                    ui.selectable_value(
+                        &mut tab.byte_prefixes,
+                        prefixes,
+                        prefixes.localize(),
+                    );
+                }
+            });
+    });
+
     if ui
         .button(t!("Button.Apply"))
-        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedAfterLogout"))
+        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedImmediately"))
         .clicked()
     {
         log::info!(
-            "Client Settings: Compression changed to {}",
-            tab.compression
+            "Client Settings: Byte Prefixes changed to {}",
+            tab.byte_prefixes
         );
-        ctx.client_settings.compression = tab.compression;
+        ctx.client_settings.byte_prefixes = tab.byte_prefixes;
+        format::set_prefixes(tab.byte_prefixes);
     }
+
     if ui
         .add_enabled(not_applied, egui::Button::new("🔙"))
         .clicked()
     {
-        tab.compression = ctx.client_settings.compression;
+        tab.byte_prefixes = ctx.client_settings.byte_prefixes;
     }
 }
 
-fn language_view(tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context) {
-    let label = styles::heading::normal(&t!("Tab.SettingsClient.Label.Language"));
-    let not_applied = tab.language != ctx.config.language;
+fn domain_names_view(tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context) {
+    let label = styles::heading::normal(&t!("Tab.SettingsClient.Label.DomainNames"));
+    let not_applied = tab.idn_form != ctx.client_settings.idn_form;
     styles::text::field_not_applied(ui, label, not_applied);
 
     styles::invisible(ui);
 
-    ui.with_layout(
-        egui::Layout::top_down(egui::Align::Min), |ui| {
-            egui::ComboBox::from_label("")
-                .selected_text(tab.language.localize()) // Display the currently selected option.
-                .show_ui(ui, |ui| {
-                    for language in config::Language::iter() {
-                        ui.selectable_value(&mut tab.language, language.clone(), language.localize());
-                    }
-                });
-        }
-    );
+    ui.with_layout(egui::Layout::top_down(egui::Align::Min), |ui| {
+        egui::ComboBox::from_id_salt("Settings.DomainNames.ComboBox")
+            .width(200.0)
+            .selected_text(tab.idn_form.localize())
+            .show_ui(ui, |ui| {
+                for form in IdnForm::iter() {
+                    ui.selectable_value(&mut tab.idn_form, form, form.localize());
+                }
+            });
+    });
 
     if ui
         .button(t!("Button.Apply"))
-        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedAfterRestart"))
+        .on_hover_text(t!("Tab.SettingsClient.Label.DomainNames.Note"))
+        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedImmediately"))
         .clicked()
     {
-        log::info!("Client Settings: Language changed to {}", tab.language);
-        ctx.config.language = tab.language.clone();
+        log::info!("Client Settings: Domain Names changed to {}", tab.idn_form);
+        ctx.client_settings.idn_form = tab.idn_form;
+        format::set_idn_form(tab.idn_form);
     }
 
     if ui
         .add_enabled(not_applied, egui::Button::new("🔙"))
         .clicked()
     {
-        tab.language = ctx.config.language.clone();
+        tab.idn_form = ctx.client_settings.idn_form;
     }
 }
 
-fn logs_format_view(tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context) {
-    let label = styles::heading::normal(&t!("Tab.SettingsClient.Label.LogFormat"));
-    let not_applied = !tab
-        .log_format_choice
-        .eq_ignore_ascii_case(&ctx.config.log_format);
-    styles::text::field_not_applied(ui, label, not_applied);
+fn color_palette_view(tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context) {
+    let label = styles::heading::normal(&t!("Tab.SettingsClient.Label.ColorPalette"));
+    let not_applied = tab.color_palette != ctx.client_settings.color_palette;
+    styles::text::field_not_applied(ui, label, not_applied)
+        .on_hover_text(t!("Tab.SettingsClient.Label.ColorPalette.Note"));
 
     styles::invisible(ui);
 
-    ui.add(TextEdit::multiline(&mut tab.log_format_choice));
+    ui.with_layout(egui::Layout::top_down(egui::Align::Min), |ui| {
+        egui::ComboBox::from_id_salt("Settings.ColorPalette.ComboBox")
+            .width(200.0)
+            .selected_text(tab.color_palette.localize())
+            .show_ui(ui, |ui| {
+                for palette in colors::Palette::iter() {
+                    ui.selectable_value(
+                        &mut tab.color_palette,
+                        palette,
+                        palette.localize(),
+                    );
+                }
+            });
+    });
 
     if ui
         .button(t!("Button.Apply"))
-        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedAfterRestart"))
+        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedImmediately"))
         .clicked()
     {
         log::info!(
-            "Client Settings: Log Format changed to {}",
-            tab.log_format_choice
+            "Client Settings: Color Palette changed to {}",
+            tab.color_palette
         );
-        ctx.config.log_format = tab.log_format_choice.clone();
+        ctx.client_settings.color_palette = tab.color_palette;
+        colors::set_palette(tab.color_palette);
     }
 
     if ui
         .add_enabled(not_applied, egui::Button::new("🔙"))
         .clicked()
     {
-        tab.log_format_choice = ctx.config.log_format.clone();
+        tab.color_palette = ctx.client_settings.color_palette;
     }
 }
 
-fn logs_level_view(tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context) {
-    let label = styles::heading::normal(&t!("Tab.SettingsClient.Label.LogLevel"));
-    let not_applied = tab.log_level_choice != ctx.config.log_level;
+fn compression_view(tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context) {
+    let label = styles::heading::normal(&t!("Tab.SettingsClient.Label.Compression"));
+    let not_applied = tab.compression != ctx.client_settings.compression;
     styles::text::field_not_applied(ui, label, not_applied);
 
+    ui.add(Checkbox::without_text(&mut tab.compression));
     styles::invisible(ui);
 
-    ui.with_layout(egui::Layout::top_down(egui::Align::Min), |ui| {
-        egui::ComboBox::from_id_salt("Settings.Client.Log.Level.ComboBox")
-            .selected_text(logging::localize_log_level(&tab.log_level_choice))
-            .show_ui(ui, |ui| {
-                for level_filter in LevelFilter::iter() {
-                    ui.selectable_value(
-                        &mut tab.log_level_choice,
-                        level_filter,
-                        logging::localize_log_level(&level_filter),
-                    );
-                }
-            });
-    });
-
     if ui
         .button(t!("Button.Apply"))
-        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedAfterRestart"))
+        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedAfterLogout"))
         .clicked()
     {
-        log::info!("Client Settings: Log Level changed to {}", tab.language);
-        ctx.config.log_level = tab.log_level_choice;
+        log::info!(
+            "Client Settings: Compression changed to {}",
+            tab.compression
+        );
+        ctx.client_settings.compression = tab.compression;
     }
-
     if ui
         .add_enabled(not_applied, egui::Button::new("🔙"))
         .clicked()
     {
-        tab.log_level_choice = ctx.config.log_level;
+        tab.compression = ctx.client_settings.compression;
     }
 }
 
-fn ping_delay_view(tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context) {
-    let label = styles::heading::normal(&t!("Tab.SettingsClient.Label.SyncDelay"));
-    let not_applied = tab.ping_delay_seconds != ctx.client_settings.sync_delay_seconds;
-    styles::text::field_not_applied(ui, label, not_applied);
+fn data_directory_view(_: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context) {
+    ui.add(egui::Label::new(styles::heading::normal(&t!(
+        "Tab.SettingsClient.Label.DataDirectory"
+    ))));
+
+    ui.label(ctx.data_dir.localized_source());
+
+    // Platform default keeps config & data in different directories
+    let mut paths: Vec<String> = [FileKind::Config, FileKind::Data]
+        .into_iter()
+        .filter_map(|kind| ctx.data_dir.dir_path(kind).ok())
+        .map(|path| path.display().to_string())
+        .collect();
+    paths.dedup();
+    ui.label(RichText::new(paths.join("\n")).color(styles::colors::get(Role::Silent)))
+        .on_hover_text(t!("Tab.SettingsClient.Label.DataDirectory.Note"));
+}
+
+fn self_check_view(_: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context) {
+    ui.add(egui::Label::new(styles::heading::normal(&t!(
+        "Tab.SettingsClient.Label.SelfCheck"
+    ))))
+    .on_hover_text(t!("Tab.SettingsClient.Label.SelfCheck.Note"));
 
+    styles::invisible(ui);
     styles::invisible(ui);
 
-    ui.add(
-        DragValue::new(&mut tab.ping_delay_seconds)
-            .speed(1)
-            .range(1..=i64::MAX)
-            .suffix(format!(" {}", t!("Tab.SettingsClient.Suffix.SyncDelay"))),
-    );
+    if ui.button(t!("Button.Check")).clicked() {
+        let problems = self_check::run(&ctx.data_dir, &ctx.net_storage.lookup);
+        self_check::report(&problems, &ctx.modals_tx, true);
+    }
+}
+
+fn debug_overlay_view(tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context) {
+    let label = styles::heading::normal(&t!("Tab.SettingsClient.Label.DebugOverlay"));
+    let not_applied = tab.debug_overlay != ctx.client_settings.debug_overlay;
+    styles::text::field_not_applied(ui, label, not_applied);
+
+    ui.add(Checkbox::without_text(&mut tab.debug_overlay));
+
+    styles::invisible(ui);
 
     if ui
         .button(t!("Button.Apply"))
+        .on_hover_text(t!("Tab.SettingsClient.Label.DebugOverlay.Note"))
         .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedImmediately"))
         .clicked()
     {
         log::info!(
-            "Client Settings: Sync Delay seconds changed to {}",
-            tab.ping_delay_seconds
+            "Client Settings: `Debug Overlay` changed to {}",
+            tab.debug_overlay
         );
-        ctx.client_settings.sync_delay_seconds = tab.ping_delay_seconds;
+        ctx.client_settings.debug_overlay = tab.debug_overlay;
     }
-
     if ui
         .add_enabled(not_applied, egui::Button::new("🔙"))
         .clicked()
     {
-        tab.ping_delay_seconds = ctx.client_settings.sync_delay_seconds;
+        tab.debug_overlay = ctx.client_settings.debug_overlay;
     }
 }
 
-fn theme_view(tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context) {
-    let label = styles::heading::normal(&t!("Tab.SettingsClient.Label.Theme"));
-    let not_applied = tab.theme != ctx.client_settings.theme;
+fn inspector_page_size_view(
+    tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context,
+) {
+    let label =
+        styles::heading::normal(&t!("Tab.SettingsClient.Label.InspectorPageSize"));
+    let not_applied = tab.inspector_page_size != ctx.client_settings.inspector_page_size;
     styles::text::field_not_applied(ui, label, not_applied);
 
     styles::invisible(ui);
 
-    ui.with_layout(egui::Layout::top_down(egui::Align::Min), |ui| {
-        egui::ComboBox::from_id_salt("Settings.Theme.ComboBox")
-            .width(200.0)
-            .selected_text(tab.theme.title())
-            .show_ui(ui, |ui| {
-                for theme in themes::Preference::iter() {
-                    ui.selectable_value(&mut tab.theme, theme, theme.title());
-                }
-            });
-    });
+    ui.add(
+        DragValue::new(&mut tab.inspector_page_size)
+            .speed(5)
+            .range(inspector::PAGE_SIZE_RANGE)
+            .suffix(format!(" {}", t!("Tab.SettingsClient.Suffix.Records"))),
+    );
 
     if ui
         .button(t!("Button.Apply"))
+        .on_hover_text(t!("Tab.SettingsClient.Label.InspectorPageSize.Note"))
         .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedImmediately"))
         .clicked()
     {
-        ctx.client_settings.theme = tab.theme;
-        log::info!("Client Settings: Theme changed to {}", tab.theme.title());
-        ui.ctx()
-            .set_style(tab.theme.into_aesthetix_theme().custom_style());
+        log::info!(
+            "Client Settings: `Inspector Page Size` changed to {}",
+            tab.inspector_page_size
+        );
+        ctx.client_settings.inspector_page_size = tab.inspector_page_size;
     }
-
     if ui
         .add_enabled(not_applied, egui::Button::new("🔙"))
         .clicked()
     {
-        tab.theme = ctx.client_settings.theme;
+        tab.inspector_page_size = ctx.client_settings.inspector_page_size;
     }
 }
 
-fn unparsed_drop_view(tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context) {
+fn ip_conflict_failover_view(
+    tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context,
+) {
     let label =
-        styles::heading::normal(&t!("Tab.SettingsClient.Label.UnparsedFramesDrop"));
-    let not_applied =
-        tab.unparsed_frames_drop != ctx.client_settings.unparsed_frames_drop;
+        styles::heading::normal(&t!("Tab.SettingsClient.Label.IpConflictFailoverAlerts"));
+    let not_applied = tab.ip_conflict_failover_alerts
+        != ctx.client_settings.ip_conflict_failover_alerts;
     styles::text::field_not_applied(ui, label, not_applied);
 
-    ui.add(Checkbox::without_text(&mut tab.unparsed_frames_drop));
+    ui.add(Checkbox::without_text(&mut tab.ip_conflict_failover_alerts));
 
     styles::invisible(ui);
 
     if ui
         .button(t!("Button.Apply"))
-        .on_hover_text(t!("Tab.SettingsClient.Label.UnparsedFramesDrop.Note"))
+        .on_hover_text(t!("Tab.SettingsClient.Label.IpConflictFailoverAlerts.Note"))
         .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedImmediately"))
         .clicked()
     {
         log::info!(
-            "Client Settings: `Drop Unparsed Frames` changed to {}",
-            tab.unparsed_frames_drop
+            "Client Settings: `IP Conflict Failover Alerts` changed to {}",
+            tab.ip_conflict_failover_alerts
         );
-        ctx.client_settings.unparsed_frames_drop = tab.unparsed_frames_drop;
+        ctx.client_settings.ip_conflict_failover_alerts = tab.ip_conflict_failover_alerts;
     }
     if ui
         .add_enabled(not_applied, egui::Button::new("🔙"))
         .clicked()
     {
-        tab.unparsed_frames_drop = ctx.client_settings.unparsed_frames_drop;
+        tab.ip_conflict_failover_alerts = ctx.client_settings.ip_conflict_failover_alerts;
     }
 }
 
-fn parsed_limit_view(tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context) {
-    let setting = SettingsClientTab::option_to_setting(
-        tab.parsed_frames_limit_enabled,
-        tab.parsed_frames_limit,
-    );
-    let label =
-        styles::heading::normal(&t!("Tab.SettingsClient.Label.ParsedFramesLimit"));
-    let not_applied = setting != ctx.client_settings.parsed_frames_limit;
+fn anonymization_view(tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context) {
+    let label = styles::heading::normal(&t!("Tab.SettingsClient.Label.Anonymization"));
+    let not_applied = tab.anonymization != ctx.client_settings.anonymization;
     styles::text::field_not_applied(ui, label, not_applied);
 
-    ui.add(Checkbox::without_text(&mut tab.parsed_frames_limit_enabled));
-    ui.add_enabled(
-        tab.parsed_frames_limit_enabled,
-        DragValue::new(&mut tab.parsed_frames_limit)
-            .speed(1)
-            .range(1..=i64::MAX)
-            .suffix(format!(" {}", t!("Tab.SettingsClient.Suffix.Frames"))),
-    );
+    ui.checkbox(
+        &mut tab.anonymization.preserve_oui,
+        t!("Tab.SettingsClient.Label.Anonymization.PreserveOui"),
+    )
+    .on_hover_text(t!("Tab.SettingsClient.Hover.Anonymization.PreserveOui"));
+    ui.checkbox(
+        &mut tab.anonymization.preserve_private,
+        t!("Tab.SettingsClient.Label.Anonymization.PreservePrivate"),
+    )
+    .on_hover_text(t!("Tab.SettingsClient.Hover.Anonymization.PreservePrivate"));
 
     if ui
         .button(t!("Button.Apply"))
-        .on_hover_text(t!("Tab.SettingsClient.Label.ParsedFramesLimit.Note"))
+        .on_hover_text(t!("Tab.SettingsClient.Label.Anonymization.Note"))
         .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedImmediately"))
         .clicked()
     {
         log::info!(
-            "Client Settings: `Parsed Frames Limit` changed to {}:{}",
-            tab.parsed_frames_limit_enabled,
-            tab.parsed_frames_limit,
+            "Client Settings: `Anonymization` changed to OUI: {}, private: {}",
+            tab.anonymization.preserve_oui,
+            tab.anonymization.preserve_private
         );
-        ctx.client_settings.parsed_frames_limit = setting;
+        ctx.client_settings.anonymization = tab.anonymization;
     }
     if ui
         .add_enabled(not_applied, egui::Button::new("🔙"))
         .clicked()
     {
-        tab.parsed_frames_limit_enabled =
-            ctx.client_settings.parsed_frames_limit.is_some();
-        tab.parsed_frames_limit = ctx.client_settings.parsed_frames_limit.unwrap_or(0);
+        tab.anonymization = ctx.client_settings.anonymization;
     }
 }
 
-fn unparsed_threshold_view(
+fn arp_reply_window_view(
     tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context,
 ) {
-    let setting = SettingsClientTab::option_to_setting(
-        tab.unparsed_frames_threshold_enabled,
-        tab.unparsed_frames_threshold,
-    );
-    let label =
+    let label = styles::heading::normal(&t!("Tab.SettingsClient.Label.ArpReplyWindow"));
+    let not_applied = tab.arp_reply_window_ms != ctx.client_settings.arp_reply_window_ms;
+    styles::text::field_not_applied(ui, label, not_applied);
+
+    styles::invisible(ui);
+
+    ui.add(
+        DragValue::new(&mut tab.arp_reply_window_ms)
+            .speed(10)
+            .range(1..=u32::MAX)
+            .suffix(format!(" {}", t!("Tab.SettingsClient.Suffix.Milliseconds"))),
+    );
+
+    if ui
+        .button(t!("Button.Apply"))
+        .on_hover_text(t!("Tab.SettingsClient.Label.ArpReplyWindow.Note"))
+        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedImmediately"))
+        .clicked()
+    {
+        log::info!(
+            "Client Settings: `ARP Reply Window` milliseconds changed to {}",
+            tab.arp_reply_window_ms
+        );
+        ctx.client_settings.arp_reply_window_ms = tab.arp_reply_window_ms;
+    }
+    if ui
+        .add_enabled(not_applied, egui::Button::new("🔙"))
+        .clicked()
+    {
+        tab.arp_reply_window_ms = ctx.client_settings.arp_reply_window_ms;
+    }
+}
+
+fn arp_unanswered_view(
+    tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context,
+) {
+    let label = styles::heading::normal(&t!("Tab.SettingsClient.Label.ArpUnanswered"));
+    let not_applied = tab.arp_unanswered_min_requests
+        != ctx.client_settings.arp_unanswered_min_requests
+        || tab.arp_unanswered_ratio_percent
+            != ctx.client_settings.arp_unanswered_ratio_percent;
+    styles::text::field_not_applied(ui, label, not_applied);
+
+    ui.add(
+        DragValue::new(&mut tab.arp_unanswered_min_requests)
+            .speed(1)
+            .range(1..=u32::MAX)
+            .suffix(format!(" {}", t!("Tab.SettingsClient.Suffix.Requests"))),
+    );
+    ui.add(
+        DragValue::new(&mut tab.arp_unanswered_ratio_percent)
+            .speed(1)
+            .range(0..=100)
+            .suffix(" %"),
+    );
+
+    if ui
+        .button(t!("Button.Apply"))
+        .on_hover_text(t!("Tab.SettingsClient.Label.ArpUnanswered.Note"))
+        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedImmediately"))
+        .clicked()
+    {
+        log::info!(
+            "Client Settings: `ARP Unanswered Requesters` changed to {} requests, {}%",
+            tab.arp_unanswered_min_requests,
+            tab.arp_unanswered_ratio_percent
+        );
+        ctx.client_settings.arp_unanswered_min_requests = tab.arp_unanswered_min_requests;
+        ctx.client_settings.arp_unanswered_ratio_percent =
+            tab.arp_unanswered_ratio_percent;
+    }
+    if ui
+        .add_enabled(not_applied, egui::Button::new("🔙"))
+        .clicked()
+    {
+        tab.arp_unanswered_min_requests = ctx.client_settings.arp_unanswered_min_requests;
+        tab.arp_unanswered_ratio_percent =
+            ctx.client_settings.arp_unanswered_ratio_percent;
+    }
+}
+
+fn ip_conflict_window_view(
+    tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context,
+) {
+    let label = styles::heading::normal(&t!("Tab.SettingsClient.Label.IpConflictWindow"));
+    let not_applied =
+        tab.ip_conflict_window_seconds != ctx.client_settings.ip_conflict_window_seconds;
+    styles::text::field_not_applied(ui, label, not_applied);
+
+    styles::invisible(ui);
+
+    ui.add(
+        DragValue::new(&mut tab.ip_conflict_window_seconds)
+            .speed(1)
+            .range(1..=u32::MAX)
+            .suffix(format!(" {}", t!("Tab.SettingsClient.Suffix.Seconds"))),
+    );
+
+    if ui
+        .button(t!("Button.Apply"))
+        .on_hover_text(t!("Tab.SettingsClient.Label.IpConflictWindow.Note"))
+        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedImmediately"))
+        .clicked()
+    {
+        log::info!(
+            "Client Settings: `IP Conflict Window` seconds changed to {}",
+            tab.ip_conflict_window_seconds
+        );
+        ctx.client_settings.ip_conflict_window_seconds = tab.ip_conflict_window_seconds;
+    }
+    if ui
+        .add_enabled(not_applied, egui::Button::new("🔙"))
+        .clicked()
+    {
+        tab.ip_conflict_window_seconds = ctx.client_settings.ip_conflict_window_seconds;
+    }
+}
+
+fn router_learning_view(
+    tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context,
+) {
+    let label = styles::heading::normal(&t!("Tab.SettingsClient.Label.RouterLearning"));
+    let not_applied =
+        tab.router_learning_seconds != ctx.client_settings.router_learning_seconds;
+    styles::text::field_not_applied(ui, label, not_applied);
+
+    styles::invisible(ui);
+
+    ui.add(
+        DragValue::new(&mut tab.router_learning_seconds)
+            .speed(1)
+            .range(0..=u32::MAX)
+            .suffix(format!(" {}", t!("Tab.SettingsClient.Suffix.Seconds"))),
+    );
+
+    if ui
+        .button(t!("Button.Apply"))
+        .on_hover_text(t!("Tab.SettingsClient.Label.RouterLearning.Note"))
+        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedImmediately"))
+        .clicked()
+    {
+        log::info!(
+            "Client Settings: `Router Learning Period` seconds changed to {}",
+            tab.router_learning_seconds
+        );
+        ctx.client_settings.router_learning_seconds = tab.router_learning_seconds;
+    }
+    if ui
+        .add_enabled(not_applied, egui::Button::new("🔙"))
+        .clicked()
+    {
+        tab.router_learning_seconds = ctx.client_settings.router_learning_seconds;
+    }
+}
+
+fn language_view(tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context) {
+    let label = styles::heading::normal(&t!("Tab.SettingsClient.Label.Language"));
+    let not_applied = tab.language != ctx.config.language;
+    styles::text::field_not_applied(ui, label, not_applied);
+
+    styles::invisible(ui);
+
+    ui.with_layout(
+        egui::Layout::top_down(egui::Align::Min), |ui| {
+            egui::ComboBox::from_label("")
+                .selected_text(tab.language.localize()) // Display the currently selected option.
+                .show_ui(ui, |ui| {
+                    for language in config::Language::iter() {
+                        ui.selectable_value(&mut tab.language, language.clone(), language.localize());
+                    }
+                });
+        }
+    );
+
+    if ui
+        .button(t!("Button.Apply"))
+        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedAfterRestart"))
+        .clicked()
+    {
+        log::info!("Client Settings: Language changed to {}", tab.language);
+        ctx.config.language = tab.language.clone();
+    }
+
+    if ui
+        .add_enabled(not_applied, egui::Button::new("🔙"))
+        .clicked()
+    {
+        tab.language = ctx.config.language.clone();
+    }
+}
+
+fn logs_format_view(tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context) {
+    let label = styles::heading::normal(&t!("Tab.SettingsClient.Label.LogFormat"));
+    let not_applied = !tab
+        .log_format_choice
+        .eq_ignore_ascii_case(&ctx.config.log_format);
+    styles::text::field_not_applied(ui, label, not_applied);
+
+    styles::invisible(ui);
+
+    ui.add(TextEdit::multiline(&mut tab.log_format_choice));
+
+    if ui
+        .button(t!("Button.Apply"))
+        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedAfterRestart"))
+        .clicked()
+    {
+        log::info!(
+            "Client Settings: Log Format changed to {}",
+            tab.log_format_choice
+        );
+        ctx.config.log_format = tab.log_format_choice.clone();
+    }
+
+    if ui
+        .add_enabled(not_applied, egui::Button::new("🔙"))
+        .clicked()
+    {
+        tab.log_format_choice = ctx.config.log_format.clone();
+    }
+}
+
+fn logs_level_view(tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context) {
+    let label = styles::heading::normal(&t!("Tab.SettingsClient.Label.LogLevel"));
+    let not_applied = tab.log_level_choice != ctx.config.log_level;
+    styles::text::field_not_applied(ui, label, not_applied);
+
+    styles::invisible(ui);
+
+    ui.with_layout(egui::Layout::top_down(egui::Align::Min), |ui| {
+        egui::ComboBox::from_id_salt("Settings.Client.Log.Level.ComboBox")
+            .selected_text(logging::localize_log_level(&tab.log_level_choice))
+            .show_ui(ui, |ui| {
+                for level_filter in LevelFilter::iter() {
+                    ui.selectable_value(
+                        &mut tab.log_level_choice,
+                        level_filter,
+                        logging::localize_log_level(&level_filter),
+                    );
+                }
+            });
+    });
+
+    if ui
+        .button(t!("Button.Apply"))
+        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedAfterRestart"))
+        .clicked()
+    {
+        log::info!("Client Settings: Log Level changed to {}", tab.language);
+        ctx.config.log_level = tab.log_level_choice;
+    }
+
+    if ui
+        .add_enabled(not_applied, egui::Button::new("🔙"))
+        .clicked()
+    {
+        tab.log_level_choice = ctx.config.log_level;
+    }
+}
+
+fn ping_delay_view(tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context) {
+    let label = styles::heading::normal(&t!("Tab.SettingsClient.Label.SyncDelay"));
+    let not_applied = tab.ping_delay_seconds != ctx.client_settings.sync_delay_seconds;
+    styles::text::field_not_applied(ui, label, not_applied);
+
+    styles::invisible(ui);
+
+    ui.add(
+        DragValue::new(&mut tab.ping_delay_seconds)
+            .speed(1)
+            .range(1..=i64::MAX)
+            .suffix(format!(" {}", t!("Tab.SettingsClient.Suffix.SyncDelay"))),
+    );
+
+    if ui
+        .button(t!("Button.Apply"))
+        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedImmediately"))
+        .clicked()
+    {
+        log::info!(
+            "Client Settings: Sync Delay seconds changed to {}",
+            tab.ping_delay_seconds
+        );
+        ctx.client_settings.sync_delay_seconds = tab.ping_delay_seconds;
+    }
+
+    if ui
+        .add_enabled(not_applied, egui::Button::new("🔙"))
+        .clicked()
+    {
+        tab.ping_delay_seconds = ctx.client_settings.sync_delay_seconds;
+    }
+}
+
+fn notifications_view(tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context) {
+    let label = styles::heading::normal(&t!("Tab.SettingsClient.Label.Notifications"));
+    let applied = &ctx.client_settings.notifications;
+    let not_applied = tab.notifications.desktop != applied.desktop
+        || tab.notifications.sound != applied.sound;
+    styles::text::field_not_applied(ui, label, not_applied);
+
+    ui.checkbox(
+        &mut tab.notifications.desktop,
+        t!("Tab.SettingsClient.Label.Notifications.Desktop"),
+    );
+    ui.checkbox(
+        &mut tab.notifications.sound,
+        t!("Tab.SettingsClient.Label.Notifications.Sound"),
+    );
+
+    if ui
+        .button(t!("Button.Apply"))
+        .on_hover_text(t!("Tab.SettingsClient.Label.Notifications.Note"))
+        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedImmediately"))
+        .clicked()
+    {
+        log::info!(
+            "Client Settings: `Notifications` changed to desktop: {}, sound: {}",
+            tab.notifications.desktop,
+            tab.notifications.sound
+        );
+        ctx.client_settings.notifications.desktop = tab.notifications.desktop;
+        ctx.client_settings.notifications.sound = tab.notifications.sound;
+    }
+    if ui
+        .add_enabled(not_applied, egui::Button::new("🔙"))
+        .clicked()
+    {
+        tab.notifications.desktop = ctx.client_settings.notifications.desktop;
+        tab.notifications.sound = ctx.client_settings.notifications.sound;
+    }
+
+    // Chosen options are tested, even if they aren't applied yet
+    if ui
+        .add_enabled(
+            tab.notifications.is_enabled(),
+            egui::Button::new(t!("Tab.SettingsClient.Button.TestNotification")),
+        )
+        .on_hover_text(t!("Tab.SettingsClient.Hover.TestNotification"))
+        .clicked()
+    {
+        log::info!("Client Settings: Sending the test notification");
+        notifications::deliver(
+            tab.notifications.clone(),
+            t!("Notification.Test.Title").to_string(),
+            t!("Notification.Test.Summary").to_string(),
+            Some(ctx.modals_tx.clone()),
+        );
+    }
+}
+
+fn notification_interval_view(
+    tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context,
+) {
+    let label =
+        styles::heading::normal(&t!("Tab.SettingsClient.Label.NotificationInterval"));
+    let not_applied = tab.notifications.interval_seconds
+        != ctx.client_settings.notifications.interval_seconds;
+    styles::text::field_not_applied(ui, label, not_applied);
+
+    styles::invisible(ui);
+
+    ui.add(
+        DragValue::new(&mut tab.notifications.interval_seconds)
+            .speed(1)
+            .range(1..=u32::MAX)
+            .suffix(format!(" {}", t!("Tab.SettingsClient.Suffix.Seconds"))),
+    );
+
+    if ui
+        .button(t!("Button.Apply"))
+        .on_hover_text(t!("Tab.SettingsClient.Label.NotificationInterval.Note"))
+        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedImmediately"))
+        .clicked()
+    {
+        log::info!(
+            "Client Settings: `Notification Interval` seconds changed to {}",
+            tab.notifications.interval_seconds
+        );
+        ctx.client_settings.notifications.interval_seconds =
+            tab.notifications.interval_seconds;
+    }
+    if ui
+        .add_enabled(not_applied, egui::Button::new("🔙"))
+        .clicked()
+    {
+        tab.notifications.interval_seconds =
+            ctx.client_settings.notifications.interval_seconds;
+    }
+}
+
+fn notification_severity_view(
+    tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context,
+) {
+    let label =
+        styles::heading::normal(&t!("Tab.SettingsClient.Label.NotificationSeverity"));
+    let not_applied =
+        tab.notifications.severity != ctx.client_settings.notifications.severity;
+    styles::text::field_not_applied(ui, label, not_applied);
+
+    styles::invisible(ui);
+
+    ui.with_layout(egui::Layout::top_down(egui::Align::Min), |ui| {
+        egui::ComboBox::from_id_salt("Settings.NotificationSeverity.ComboBox")
+            .selected_text(tab.notifications.severity.localize())
+            .show_ui(ui, |ui| {
+                for severity in notifications::Severity::iter() {
+                    ui.selectable_value(
+                        &mut tab.notifications.severity,
+                        severity,
+                        severity.localize(),
+                    );
+                }
+            });
+    });
+
+    if ui
+        .button(t!("Button.Apply"))
+        .on_hover_text(t!("Tab.SettingsClient.Label.NotificationSeverity.Note"))
+        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedImmediately"))
+        .clicked()
+    {
+        log::info!(
+            "Client Settings: `Notification Severity` changed to {}",
+            tab.notifications.severity
+        );
+        ctx.client_settings.notifications.severity = tab.notifications.severity;
+    }
+    if ui
+        .add_enabled(not_applied, egui::Button::new("🔙"))
+        .clicked()
+    {
+        tab.notifications.severity = ctx.client_settings.notifications.severity;
+    }
+}
+
+fn notification_sound_file_view(
+    tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context,
+) {
+    let label =
+        styles::heading::normal(&t!("Tab.SettingsClient.Label.NotificationSoundFile"));
+    let not_applied =
+        tab.notifications.sound_file != ctx.client_settings.notifications.sound_file;
+    styles::text::field_not_applied(ui, label, not_applied);
+
+    if ui
+        .button("📂")
+        .on_hover_text(t!("Tab.SettingsClient.Hover.NotificationSoundFile"))
+        .clicked()
+    {
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter(
+                notifications::SOUND_FILTER_NAME,
+                notifications::SOUND_FILTER_EXTENSIONS,
+            )
+            .pick_file()
+        {
+            tab.notifications.sound_file = path.display().to_string();
+        }
+    }
+
+    ui.add(
+        TextEdit::singleline(&mut tab.notifications.sound_file)
+            .hint_text(t!("Tab.SettingsClient.Hint.BundledSound")),
+    );
+
+    if ui
+        .button(t!("Button.Apply"))
+        .on_hover_text(t!("Tab.SettingsClient.Label.NotificationSoundFile.Note"))
+        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedImmediately"))
+        .clicked()
+    {
+        tab.notifications.sound_file = tab.notifications.sound_file.trim().to_string();
+        log::info!(
+            "Client Settings: `Notification Sound File` changed to {}",
+            tab.notifications.sound_file
+        );
+        ctx.client_settings.notifications.sound_file =
+            tab.notifications.sound_file.clone();
+    }
+    if ui
+        .add_enabled(not_applied, egui::Button::new("🔙"))
+        .clicked()
+    {
+        tab.notifications.sound_file =
+            ctx.client_settings.notifications.sound_file.clone();
+    }
+}
+
+fn port_names_view(tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context) {
+    ui.add(egui::Label::new(styles::heading::normal(&t!(
+        "Tab.SettingsClient.Label.PortNames"
+    ))))
+    .on_hover_text(t!("Tab.SettingsClient.Label.PortNames.Note"));
+
+    egui::ComboBox::from_id_salt("Settings.PortNames.Format.ComboBox")
+        .selected_text(lookup::localize_format(&tab.port_import_format))
+        .show_ui(ui, |ui| {
+            for format in DatabaseFormat::iter() {
+                let text = lookup::localize_format(&format);
+                ui.selectable_value(&mut tab.port_import_format, format, text);
+            }
+        });
+
+    egui::ComboBox::from_id_salt("Settings.PortNames.Mode.ComboBox")
+        .selected_text(tab.port_import_mode.localize())
+        .show_ui(ui, |ui| {
+            for mode in ImportMode::iter() {
+                let text = mode.localize();
+                ui.selectable_value(&mut tab.port_import_mode, mode, text);
+            }
+        });
+    // Mode doesn't need the file to be read again
+    if let Some(import) = &mut ctx.net_storage.lookup.port_import {
+        if import.mode != tab.port_import_mode {
+            log::info!(
+                "Client Settings: Mode of the imported ports changed to {}",
+                tab.port_import_mode
+            );
+            import.mode = tab.port_import_mode;
+        }
+    }
+
+    if ui
+        .button("📂")
+        .on_hover_text(t!("Tab.SettingsClient.Hover.PortNamesImport"))
+        .clicked()
+    {
+        if let Some(path) = rfd::FileDialog::new().pick_file() {
+            let path = path.display().to_string();
+            let result = ctx.net_storage.lookup.import_ports(
+                &path,
+                tab.port_import_format,
+                tab.port_import_mode,
+            );
+            match result {
+                Ok(import) => {
+                    log::info!(
+                        "Client Settings: Imported ports from {path}: {} ports, {} \
+                        malformed lines skipped",
+                        import.table.len(),
+                        import.skipped
+                    );
+                    MessageModal::info(&t!(
+                        "Message.Success.PortsImported",
+                        "ports" = import.table.len(),
+                        "skipped" = import.skipped
+                    ))
+                    .try_send_by(&ctx.modals_tx);
+                },
+                Err(err) => {
+                    log::error!(
+                        "Client Settings: Failed to import ports from {path}: {err}"
+                    );
+                    MessageModal::error(&format!(
+                        "{} {err}",
+                        t!("Error.FailedImportPorts")
+                    ))
+                    .try_send_by(&ctx.modals_tx);
+                },
+            }
+        }
+    }
+
+    let status = match &ctx.net_storage.lookup.port_import {
+        Some(import) => t!(
+            "Tab.SettingsClient.Label.PortNames.Imported",
+            "ports" = import.table.len(),
+            "skipped" = import.skipped
+        )
+        .to_string(),
+        None => t!("Tab.SettingsClient.Label.PortNames.Bundled").to_string(),
+    };
+    let path = ctx
+        .net_storage
+        .lookup
+        .port_import
+        .as_ref()
+        .map(|import| import.path.clone())
+        .unwrap_or_default();
+    ui.label(RichText::new(status).color(styles::colors::get(Role::Silent)))
+        .on_hover_text(path);
+
+    if ui
+        .add_enabled(
+            ctx.net_storage.lookup.port_import.is_some(),
+            egui::Button::new(t!("Button.Reset")),
+        )
+        .on_hover_text(t!("Tab.SettingsClient.Hover.PortNamesReset"))
+        .clicked()
+    {
+        log::info!("Client Settings: Imported ports are removed");
+        ctx.net_storage.lookup.port_import = None;
+    }
+}
+
+fn port_overrides_view(
+    tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context,
+) {
+    ui.add(egui::Label::new(styles::heading::normal(&t!(
+        "Tab.SettingsClient.Label.PortOverrides"
+    ))))
+    .on_hover_text(t!("Tab.SettingsClient.Label.PortOverrides.Note"));
+
+    let overrides = &mut ctx.net_storage.lookup.port_overrides;
+    ui.add(DragValue::new(&mut tab.port_override_port).range(0..=u16::MAX));
+    ui.add(
+        TextEdit::singleline(&mut tab.port_override_name)
+            .hint_text(t!("Tab.SettingsClient.Hint.PortOverrideName")),
+    );
+
+    let name = tab.port_override_name.trim();
+    if ui
+        .add_enabled(!name.is_empty(), egui::Button::new(t!("Button.Apply")))
+        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedImmediately"))
+        .clicked()
+    {
+        log::info!(
+            "Client Settings: Port {} is named {name}",
+            tab.port_override_port
+        );
+        overrides.insert(tab.port_override_port, name.to_string());
+        tab.port_override_name.clear();
+    }
+
+    let mut removed = None;
+    Grid::new("Settings.PortOverrides.Grid")
+        .num_columns(3)
+        .show(ui, |ui| {
+            for (port, name) in overrides.iter() {
+                ui.label(port.to_string());
+                ui.label(name);
+                if ui.small_button("❌").clicked() {
+                    removed = Some(*port);
+                }
+                ui.end_row();
+            }
+        });
+    if let Some(port) = removed {
+        log::info!("Client Settings: Name of the port {port} reset to default");
+        overrides.remove(&port);
+    }
+}
+
+fn protocol_colors_view(
+    tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context,
+) {
+    let overrides = &mut ctx.client_settings.protocol_colors;
+    let applied = protocols::base_color(ui, &tab.protocol_color_chosen, overrides);
+    let label = styles::heading::normal(&t!("Tab.SettingsClient.Label.ProtocolColors"));
+    let not_applied = tab.protocol_color.is_some_and(|color| color != applied);
+    styles::text::field_not_applied(ui, label, not_applied);
+
+    ui.with_layout(egui::Layout::top_down(egui::Align::Min), |ui| {
+        egui::ComboBox::from_id_salt("Settings.ProtocolColors.ComboBox")
+            .selected_text(protocols::label(
+                ui,
+                &tab.protocol_color_chosen,
+                &tab.protocol_color_chosen.to_string(),
+                overrides,
+            ))
+            .show_ui(ui, |ui| {
+                for protocol in ProtocolId::iter() {
+                    let label =
+                        protocols::label(ui, &protocol, &protocol.to_string(), overrides);
+                    if ui
+                        .selectable_value(&mut tab.protocol_color_chosen, protocol, label)
+                        .clicked()
+                    {
+                        tab.protocol_color = None;
+                    }
+                }
+            });
+    });
+
+    let mut color = tab.protocol_color.unwrap_or(applied);
+    if ui.color_edit_button_srgba(&mut color).changed() {
+        tab.protocol_color = Some(color);
+    }
+
+    if ui
+        .button(t!("Button.Apply"))
+        .on_hover_text(t!("Tab.SettingsClient.Label.ProtocolColors.Note"))
+        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedImmediately"))
+        .clicked()
+    {
+        log::info!(
+            "Client Settings: Color of {} changed to {}",
+            tab.protocol_color_chosen,
+            color.to_hex()
+        );
+        overrides.insert(tab.protocol_color_chosen.to_string(), color);
+        tab.protocol_color = None;
+    }
+
+    if ui
+        .add_enabled(not_applied, egui::Button::new("🔙"))
+        .clicked()
+    {
+        tab.protocol_color = None;
+    }
+
+    let name = tab.protocol_color_chosen.to_string();
+    if ui
+        .add_enabled(
+            overrides.contains_key(&name),
+            egui::Button::new(t!("Button.Reset")),
+        )
+        .on_hover_text(t!("Tab.SettingsClient.Hover.ProtocolColorReset"))
+        .clicked()
+    {
+        log::info!("Client Settings: Color of {name} reset to default");
+        overrides.remove(&name);
+        tab.protocol_color = None;
+    }
+}
+
+fn storage_limit_view(tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context) {
+    let setting = SettingsClientTab::option_to_setting(
+        tab.storage_soft_limit_enabled,
+        tab.storage_soft_limit_mb,
+    );
+    let label = styles::heading::normal(&t!("Tab.SettingsClient.Label.StorageSoftLimit"));
+    let not_applied = setting != ctx.client_settings.storage_soft_limit_mb;
+    styles::text::field_not_applied(ui, label, not_applied);
+
+    ui.add(Checkbox::without_text(&mut tab.storage_soft_limit_enabled));
+    ui.add_enabled(
+        tab.storage_soft_limit_enabled,
+        DragValue::new(&mut tab.storage_soft_limit_mb)
+            .speed(1)
+            .range(1..=i64::MAX)
+            .suffix(format!(" {}", format::unit(2, Prefixes::Binary))),
+    );
+
+    if ui
+        .button(t!("Button.Apply"))
+        .on_hover_text(t!("Tab.SettingsClient.Label.StorageSoftLimit.Note"))
+        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedImmediately"))
+        .clicked()
+    {
+        log::info!(
+            "Client Settings: `Storage Soft Limit` changed to {}:{}",
+            tab.storage_soft_limit_enabled,
+            tab.storage_soft_limit_mb,
+        );
+        ctx.client_settings.storage_soft_limit_mb = setting;
+    }
+    if ui
+        .add_enabled(not_applied, egui::Button::new("🔙"))
+        .clicked()
+    {
+        tab.storage_soft_limit_enabled =
+            ctx.client_settings.storage_soft_limit_mb.is_some();
+        tab.storage_soft_limit_mb =
+            ctx.client_settings.storage_soft_limit_mb.unwrap_or(0);
+    }
+}
+
+fn theme_view(tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context) {
+    let label = styles::heading::normal(&t!("Tab.SettingsClient.Label.Theme"));
+    let not_applied = tab.theme != ctx.client_settings.theme;
+    styles::text::field_not_applied(ui, label, not_applied);
+
+    styles::invisible(ui);
+
+    ui.with_layout(egui::Layout::top_down(egui::Align::Min), |ui| {
+        egui::ComboBox::from_id_salt("Settings.Theme.ComboBox")
+            .width(200.0)
+            .selected_text(tab.theme.title())
+            .show_ui(ui, |ui| {
+                for theme in themes::Preference::iter() {
+                    ui.selectable_value(&mut tab.theme, theme, theme.title());
+                }
+            });
+    });
+
+    if ui
+        .button(t!("Button.Apply"))
+        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedImmediately"))
+        .clicked()
+    {
+        ctx.client_settings.theme = tab.theme;
+        log::info!("Client Settings: Theme changed to {}", tab.theme.title());
+        ui.ctx()
+            .set_style(tab.theme.into_aesthetix_theme().custom_style());
+    }
+
+    if ui
+        .add_enabled(not_applied, egui::Button::new("🔙"))
+        .clicked()
+    {
+        tab.theme = ctx.client_settings.theme;
+    }
+}
+
+fn ui_scale_view(tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context) {
+    let label = styles::heading::normal(&t!("Tab.SettingsClient.Label.UiScale"));
+    let not_applied = tab.ui_scale != ctx.client_settings.ui_scale;
+    styles::text::field_not_applied(ui, label, not_applied);
+
+    styles::invisible(ui);
+
+    let response = ui.add(
+        Slider::new(&mut tab.ui_scale, ui::UI_SCALE_RANGE)
+            .step_by(0.05)
+            .fixed_decimals(2)
+            .suffix("×"),
+    );
+    // Preview. Scaling under the dragged slider moves it away from the cursor
+    if response.drag_stopped() || (response.changed() && !response.dragged()) {
+        ui.ctx().set_zoom_factor(tab.ui_scale);
+    }
+
+    if ui
+        .button(t!("Button.Apply"))
+        .on_hover_text(t!("Tab.SettingsClient.Label.UiScale.Note"))
+        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedImmediately"))
+        .clicked()
+    {
+        log::info!("Client Settings: UI scale changed to {:.2}", tab.ui_scale);
+        ctx.client_settings.ui_scale = tab.ui_scale;
+        ui.ctx().set_zoom_factor(tab.ui_scale);
+    }
+
+    if ui
+        .add_enabled(not_applied, egui::Button::new("🔙"))
+        .clicked()
+    {
+        tab.ui_scale = ctx.client_settings.ui_scale;
+        ui.ctx().set_zoom_factor(tab.ui_scale);
+    }
+}
+
+fn unparsed_drop_view(tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context) {
+    let label =
+        styles::heading::normal(&t!("Tab.SettingsClient.Label.UnparsedFramesDrop"));
+    let not_applied =
+        tab.unparsed_frames_drop != ctx.client_settings.unparsed_frames_drop;
+    styles::text::field_not_applied(ui, label, not_applied);
+
+    ui.add(Checkbox::without_text(&mut tab.unparsed_frames_drop));
+
+    styles::invisible(ui);
+
+    if ui
+        .button(t!("Button.Apply"))
+        .on_hover_text(t!("Tab.SettingsClient.Label.UnparsedFramesDrop.Note"))
+        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedImmediately"))
+        .clicked()
+    {
+        log::info!(
+            "Client Settings: `Drop Unparsed Frames` changed to {}",
+            tab.unparsed_frames_drop
+        );
+        ctx.client_settings.unparsed_frames_drop = tab.unparsed_frames_drop;
+    }
+    if ui
+        .add_enabled(not_applied, egui::Button::new("🔙"))
+        .clicked()
+    {
+        tab.unparsed_frames_drop = ctx.client_settings.unparsed_frames_drop;
+    }
+}
+
+fn parsed_limit_view(tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context) {
+    let setting = SettingsClientTab::option_to_setting(
+        tab.parsed_frames_limit_enabled,
+        tab.parsed_frames_limit,
+    );
+    let label =
+        styles::heading::normal(&t!("Tab.SettingsClient.Label.ParsedFramesLimit"));
+    let not_applied = setting != ctx.client_settings.parsed_frames_limit;
+    styles::text::field_not_applied(ui, label, not_applied);
+
+    ui.add(Checkbox::without_text(&mut tab.parsed_frames_limit_enabled));
+    ui.add_enabled(
+        tab.parsed_frames_limit_enabled,
+        DragValue::new(&mut tab.parsed_frames_limit)
+            .speed(1)
+            .range(1..=i64::MAX)
+            .suffix(format!(" {}", t!("Tab.SettingsClient.Suffix.Frames"))),
+    );
+
+    if ui
+        .button(t!("Button.Apply"))
+        .on_hover_text(t!("Tab.SettingsClient.Label.ParsedFramesLimit.Note"))
+        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedImmediately"))
+        .clicked()
+    {
+        log::info!(
+            "Client Settings: `Parsed Frames Limit` changed to {}:{}",
+            tab.parsed_frames_limit_enabled,
+            tab.parsed_frames_limit,
+        );
+        ctx.client_settings.parsed_frames_limit = setting;
+    }
+    if ui
+        .add_enabled(not_applied, egui::Button::new("🔙"))
+        .clicked()
+    {
+        tab.parsed_frames_limit_enabled =
+            ctx.client_settings.parsed_frames_limit.is_some();
+        tab.parsed_frames_limit = ctx.client_settings.parsed_frames_limit.unwrap_or(0);
+    }
+}
+
+fn unparsed_spill_view(
+    tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context,
+) {
+    let setting = SettingsClientTab::option_to_setting(
+        tab.unparsed_frames_spill_enabled,
+        tab.unparsed_frames_spill_mb,
+    );
+    let label =
+        styles::heading::normal(&t!("Tab.SettingsClient.Label.UnparsedFramesSpill"));
+    let not_applied = setting != ctx.client_settings.unparsed_frames_spill_mb;
+    styles::text::field_not_applied(ui, label, not_applied);
+
+    ui.add(Checkbox::without_text(
+        &mut tab.unparsed_frames_spill_enabled,
+    ));
+    ui.add_enabled(
+        tab.unparsed_frames_spill_enabled,
+        DragValue::new(&mut tab.unparsed_frames_spill_mb)
+            .speed(1)
+            .range(1..=i64::MAX)
+            .suffix(format!(" {}", format::unit(2, Prefixes::Binary))),
+    );
+
+    if ui
+        .button(t!("Button.Apply"))
+        .on_hover_text(t!("Tab.SettingsClient.Label.UnparsedFramesSpill.Note"))
+        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedImmediately"))
+        .clicked()
+    {
+        log::info!(
+            "Client Settings: `Unparsed Frames Spill` changed to {}:{}",
+            tab.unparsed_frames_spill_enabled,
+            tab.unparsed_frames_spill_mb,
+        );
+        ctx.client_settings.unparsed_frames_spill_mb = setting;
+        ctx.net_storage.raw.set_spill_limit(setting);
+    }
+    if ui
+        .add_enabled(not_applied, egui::Button::new("🔙"))
+        .clicked()
+    {
+        tab.unparsed_frames_spill_enabled =
+            ctx.client_settings.unparsed_frames_spill_mb.is_some();
+        tab.unparsed_frames_spill_mb =
+            ctx.client_settings.unparsed_frames_spill_mb.unwrap_or(0);
+    }
+}
+
+fn unparsed_threshold_view(
+    tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context,
+) {
+    let setting = SettingsClientTab::option_to_setting(
+        tab.unparsed_frames_threshold_enabled,
+        tab.unparsed_frames_threshold,
+    );
+    let label =
         styles::heading::normal(&t!("Tab.SettingsClient.Label.UnparsedFramesThreshold"));
     let not_applied = setting != ctx.client_settings.unparsed_frames_threshold;
     styles::text::field_not_applied(ui, label, not_applied);
@@ -519,3 +1756,77 @@ fn unparsed_threshold_view(
             ctx.client_settings.unparsed_frames_threshold.unwrap_or(0);
     }
 }
+
+fn window_state_view(tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context) {
+    let label = styles::heading::normal(&t!("Tab.SettingsClient.Label.WindowState"));
+    let not_applied = tab.window_persistence != ctx.config.window_persistence;
+    styles::text::field_not_applied(ui, label, not_applied);
+
+    ui.add(Checkbox::without_text(&mut tab.window_persistence));
+
+    styles::invisible(ui);
+
+    if ui
+        .button(t!("Button.Apply"))
+        .on_hover_text(t!("Tab.SettingsClient.Label.WindowState.Note"))
+        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedAfterRestart"))
+        .clicked()
+    {
+        log::info!(
+            "Client Settings: `Window State` changed to {}",
+            tab.window_persistence
+        );
+        ctx.config.window_persistence = tab.window_persistence;
+    }
+    if ui
+        .add_enabled(not_applied, egui::Button::new("🔙"))
+        .clicked()
+    {
+        tab.window_persistence = ctx.config.window_persistence;
+    }
+}
+
+fn window_title_view(tab: &mut SettingsClientTab, ui: &mut egui::Ui, ctx: &mut Context) {
+    let label = styles::heading::normal(&t!("Tab.SettingsClient.Label.WindowTitle"));
+    let not_applied = tab.window_title != ctx.client_settings.window_title;
+    styles::text::field_not_applied(ui, label, not_applied);
+
+    ui.checkbox(
+        &mut tab.window_title.enabled,
+        t!("Tab.SettingsClient.Label.WindowTitle.Stats"),
+    );
+    ui.add_enabled_ui(tab.window_title.enabled, |ui| {
+        ui.checkbox(
+            &mut tab.window_title.throughput,
+            t!("Tab.SettingsClient.Label.WindowTitle.Throughput"),
+        );
+        ui.checkbox(
+            &mut tab.window_title.devices,
+            t!("Tab.SettingsClient.Label.WindowTitle.Devices"),
+        );
+        ui.checkbox(
+            &mut tab.window_title.alerts,
+            t!("Tab.SettingsClient.Label.WindowTitle.Alerts"),
+        )
+        .on_hover_text(t!("Tab.SettingsClient.Hover.WindowTitleAlerts"));
+    });
+
+    if ui
+        .button(t!("Button.Apply"))
+        .on_hover_text(t!("Tab.SettingsClient.Label.WindowTitle.Note"))
+        .on_hover_text(t!("Tab.SettingsClient.Note.FieldAppliedImmediately"))
+        .clicked()
+    {
+        log::info!(
+            "Client Settings: `Window Title` changed to {:?}",
+            tab.window_title
+        );
+        ctx.client_settings.window_title = tab.window_title;
+    }
+    if ui
+        .add_enabled(not_applied, egui::Button::new("🔙"))
+        .clicked()
+    {
+        tab.window_title = ctx.client_settings.window_title;
+    }
+}
@@ -1,20 +1,50 @@
 use crate::context::Context;
+use crate::net;
+use crate::ui::format;
+use crate::ui::modals::message::MessageModal;
 use crate::ui::styles;
-use crate::ui::styles::{colors, spacing};
+use crate::ui::styles::colors::Role;
+use crate::ui::styles::{colors, layout, spacing};
 use crate::ui::tabs::Tab;
 use crate::ws::request::UiClientRequest;
-use chrono::{DateTime, Local};
-use common::messages::Request;
-use egui::{Grid, RichText, TextBuffer, TextEdit};
+use chrono::{DateTime, Local, NaiveTime, Weekday};
+use common::messages::{
+    CaptureOptionsDto, FailureSamplesDto, MirrorDto, MirrorFilter, Request,
+    SEND_DELAY_MAX_MS,
+};
+use common::schedule::{CaptureWindow, ScheduleTimezone};
+use dpi::dto::frame::{OwnedFrame, save_pcap};
+use dpi::protocols::ProtocolId;
+use dpi::protocols::port_table::PortTable;
+use egui::{DragValue, Grid, RichText, TextBuffer, TextEdit};
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::str::FromStr;
+
+// Totals are accumulated for days, so the year is shown too
 
 #[derive(Default)]
 pub struct SettingsServerTab {
     pub reboot_requested: bool, // To logout after reboot
     reboot_confirm: bool,       // To show confirmation
+    statistics_reset_confirm: bool,
 
     password_field: String,
     interface_current: Option<String>,
 
+    // Edited values, taken from the config until changed
+    capture_snaplen: Option<i32>,
+    capture_buffer_size: Option<i32>,
+    // Milliseconds, taken from the connection until changed
+    send_delay: Option<u64>,
+    // Protocol name -> Ports, separated by commas
+    protocol_ports: HashMap<String, String>,
+    // Taken from the server until changed
+    schedule: Option<Vec<ScheduleRow>>,
+    // Taken from the running mirror until changed
+    mirror_target: Option<String>,
+    mirror_filter: Option<String>,
+
     last_request: Option<DateTime<Local>>, // For "Last Updated:"
 }
 
@@ -30,31 +60,32 @@ impl SettingsServerTab {
             ui.with_layout(
                 egui::Layout::top_down_justified(egui::Align::Center),
                 |ui| {
-                    Grid::new("Settings.Grid")
+                    let grid = Grid::new("Settings.Grid")
                         .striped(false)
                         .min_col_width(available_width / GRID_COLUMNS as f32)
-                        .num_columns(GRID_COLUMNS)
-                        .show(ui, |ui| {
-                            self.request_settings_view(ui, ctx);
-                            ui.end_row();
-
-                            self.save_server_config_view(ui, ctx);
-                            ui.end_row();
-
-                            self.reboot_view(ui, ctx);
-                            ui.end_row();
-
-                            self.compression_view(ui, ctx);
-                            ui.end_row();
-
-                            self.change_password_view(ui, ctx);
-                            ui.end_row();
-
-                            self.sending_unparsed_frames_view(ui, ctx);
-                            ui.end_row();
-                        });
+                        .num_columns(GRID_COLUMNS);
+                    layout::Rows::show(ui, grid, |ui, rows| {
+                        rows.row(ui, |ui| self.request_settings_view(ui, ctx));
+                        rows.row(ui, |ui| self.save_server_config_view(ui, ctx));
+                        rows.row(ui, |ui| self.reboot_view(ui, ctx));
+                        rows.row(ui, |ui| self.compression_view(ui, ctx));
+                        rows.row(ui, |ui| self.send_delay_view(ui, ctx));
+                        rows.row(ui, |ui| self.change_password_view(ui, ctx));
+                        rows.row(ui, |ui| self.sending_unparsed_frames_view(ui, ctx));
+                        rows.row(ui, |ui| self.flow_export_view(ui, ctx));
+                        rows.row(ui, |ui| self.pcap_writer_view(ui, ctx));
+                        rows.row(ui, |ui| self.statistics_view(ui, ctx));
+                    });
 
                     self.interfaces_view(ui, ctx);
+                    self.capture_options_view(ui, ctx);
+                    self.schedule_view(ui, ctx);
+                    self.protocol_ports_view(ui, ctx);
+                    self.mirror_view(ui, ctx);
+                    // Developer mode
+                    if ctx.client_settings.debug_overlay {
+                        self.failure_samples_view(ui, ctx);
+                    }
                 },
             );
         });
@@ -79,25 +110,24 @@ impl SettingsServerTab {
         let req_upd_timestamp =
             match (&self.last_request, &ctx.settings_server.last_updated) {
                 (Some(req), Some(upd)) => {
-                    let formatted = req.format(styles::TIME_FORMAT).to_string();
+                    let formatted = format::time(&req);
                     let color = if req > upd {
-                        colors::OUTDATED
+                        colors::get(Role::Outdated)
                     } else {
-                        colors::UPDATED
+                        colors::get(Role::Updated)
                     };
                     RichText::new(formatted).color(color)
                 },
                 (None, Some(upd)) => {
-                    let formatted = upd.format(styles::TIME_FORMAT).to_string();
-                    RichText::new(formatted).color(colors::UPDATED)
+                    let formatted = format::time(&upd);
+                    RichText::new(formatted).color(colors::get(Role::Updated))
                 },
                 (Some(req), None) => {
-                    let formatted = req.format(styles::TIME_FORMAT).to_string();
-                    RichText::new(formatted).color(colors::OUTDATED)
-                },
-                (None, None) => {
-                    RichText::new(t!("Text.LastUpdate.Never")).color(colors::OUTDATED)
+                    let formatted = format::time(&req);
+                    RichText::new(formatted).color(colors::get(Role::Outdated))
                 },
+                (None, None) => RichText::new(t!("Text.LastUpdate.Never"))
+                    .color(colors::get(Role::Outdated)),
             };
         ui.label(req_upd_timestamp);
     }
@@ -162,7 +192,7 @@ impl SettingsServerTab {
             styles::text::is_enabled(ctx.settings_server.compression_active);
         Self::different_from_config(ui, is_enabled_text, differ);
 
-        // We don't care what active field is - changes take effect only on config
+        // We don't care what active field is - changes take effect for new connections
         if ui
             .button(styles::text::action(ctx.settings_server.compression_config))
             .clicked()
@@ -174,11 +204,57 @@ impl SettingsServerTab {
         }
     }
 
+    // Of this connection only, the config value is shown as the default of the new ones
+    fn send_delay_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        let active = ctx.settings_server.send_delay_active;
+        let config = ctx.settings_server.send_delay_config;
+        let suffix = format!(" {}", t!("Tab.SettingsClient.Suffix.Milliseconds"));
+
+        ui.add(egui::Label::new(styles::heading::normal(&t!(
+            "Tab.SettingsServer.Label.SendDelay"
+        ))))
+        .on_hover_text(t!("Tab.SettingsServer.Note.SendDelay"));
+        Self::different_from_config(
+            ui,
+            RichText::new(format!("{active}{suffix}")),
+            active != config,
+        );
+
+        let delay = self.send_delay.get_or_insert(active);
+        ui.add(
+            DragValue::new(delay)
+                .speed(5)
+                .range(0..=SEND_DELAY_MAX_MS)
+                .suffix(suffix),
+        );
+
+        if ui.button(t!("Button.Apply")).clicked() {
+            let delay = self.send_delay.unwrap_or(active);
+            if let Err(err) = ctx
+                .ui_client_requests_tx
+                .try_send(UiClientRequest::Request(Request::SetSendDelay(delay)))
+            {
+                log::error!("Failed to send request (SetSendDelay): {err}");
+            }
+        }
+    }
+
     fn change_password_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
         ui.add(egui::Label::new(styles::heading::normal(&t!(
             "Tab.SettingsServer.Label.ChangePassword"
         ))));
 
+        // Password is changed by this client, the connection is kept with the old one
+        if ctx.settings_server.credentials_active
+            != ctx.settings_server.credentials_config
+        {
+            Self::different_from_config(
+                ui,
+                RichText::new(t!("Tab.SettingsServer.Label.PasswordChanged")),
+                true,
+            );
+        }
+
         ui.add(TextEdit::singleline(&mut self.password_field));
 
         if ui.button(t!("Button.Apply")).clicked() {
@@ -263,23 +339,727 @@ impl SettingsServerTab {
                         "{}:",
                         t!("Tab.SettingsServer.Label.Interfaces.Available")
                     ));
-                    ui.vertical_centered_justified(|ui| {
-                        for interface in &ctx.settings_server.interfaces_available {
-                            if ui.button(RichText::new(interface).monospace()).clicked() {
-                                self.interface_current = Some(interface.to_string());
-                            }
-                        }
-                    });
+                    ui.add_space(styles::space::SMALL);
+                    self.interfaces_table_view(ui, ctx);
                 } else {
                     ui.label(format!(
                         "{}:\t—",
-                        "Tab.SettingsServer.Label.Interfaces.Available"
+                        t!("Tab.SettingsServer.Label.Interfaces.Available")
                     ));
                 }
             },
         );
     }
 
+    fn interfaces_table_view(&mut self, ui: &mut egui::Ui, ctx: &Context) {
+        let unknown = t!("Text.Unknown").to_string();
+        Grid::new("Settings.Interfaces.Available.Grid")
+            .striped(true)
+            .num_columns(5)
+            .show(ui, |ui| {
+                for header in [
+                    t!("Tab.SettingsServer.Label.Interfaces.Name"),
+                    t!("Tab.SettingsServer.Label.Interfaces.Addresses"),
+                    t!("Tab.SettingsServer.Label.Interfaces.Mtu"),
+                    t!("Tab.SettingsServer.Label.Interfaces.Speed"),
+                    t!("Tab.SettingsServer.Label.Interfaces.State"),
+                ] {
+                    ui.label(styles::heading::grid(&header));
+                }
+                ui.end_row();
+
+                for interface in &ctx.settings_server.interfaces_available {
+                    let is_active = ctx.settings_server.interface_active.as_ref()
+                        == Some(&interface.name);
+                    let highlight = |text: RichText| match is_active {
+                        true => text.strong().color(colors::get(Role::InterfaceActive)),
+                        false => text,
+                    };
+
+                    let name = highlight(RichText::new(&interface.name).monospace());
+                    if ui.button(name).clicked() {
+                        self.interface_current = Some(interface.name.clone());
+                    }
+
+                    let addresses = match interface.addresses.is_empty() {
+                        true => "—".to_string(),
+                        false => interface
+                            .addresses
+                            .iter()
+                            .map(|address| address.to_string())
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                    };
+                    ui.label(highlight(RichText::new(addresses).monospace()));
+
+                    let mtu = interface
+                        .mtu
+                        .map(|mtu| mtu.to_string())
+                        .unwrap_or(unknown.clone());
+                    ui.label(highlight(RichText::new(mtu)));
+
+                    let speed = interface
+                        .speed_mbps
+                        .map(|speed| {
+                            t!(
+                                "Tab.SettingsServer.Label.Interfaces.Speed.Value",
+                                "speed" = speed
+                            )
+                            .to_string()
+                        })
+                        .unwrap_or(unknown.clone());
+                    ui.label(highlight(RichText::new(speed)));
+
+                    let state = match interface.is_up {
+                        Some(true) => RichText::new(t!(
+                            "Tab.SettingsServer.Label.Interfaces.State.Up"
+                        ))
+                        .color(colors::get(Role::Enabled)),
+                        Some(false) => RichText::new(t!(
+                            "Tab.SettingsServer.Label.Interfaces.State.Down"
+                        ))
+                        .color(colors::get(Role::Disabled)),
+                        None => RichText::new(&unknown),
+                    };
+                    ui.label(state);
+                    ui.end_row();
+                }
+            });
+    }
+
+    fn capture_options_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        ui.collapsing(
+            styles::heading::normal(&t!("Tab.SettingsServer.Label.CaptureOptions")),
+            |ui| {
+                ui.label(
+                    RichText::new(t!("Tab.SettingsServer.Note.CaptureOptions")).italics(),
+                );
+                ui.add_space(styles::space::SMALL);
+
+                let active = ctx.settings_server.capture_options_active;
+                let config = ctx.settings_server.capture_options_config;
+                let grid = Grid::new("Settings.CaptureOptions.Grid")
+                    .striped(false)
+                    .num_columns(4);
+                layout::Rows::show(ui, grid, |ui, rows| {
+                    rows.row(ui, |ui| {
+                        self.capture_toggle_view(
+                            ui,
+                            ctx,
+                            t!("Tab.SettingsServer.Label.CaptureOptions.Promiscuous")
+                                .to_string(),
+                            active.map(|options| options.promiscuous),
+                            config.promiscuous,
+                            Request::SetCapturePromiscuous,
+                        );
+                    });
+                    rows.row(ui, |ui| {
+                        self.capture_toggle_view(
+                            ui,
+                            ctx,
+                            t!("Tab.SettingsServer.Label.CaptureOptions.ImmediateMode")
+                                .to_string(),
+                            active.map(|options| options.immediate_mode),
+                            config.immediate_mode,
+                            Request::SetCaptureImmediateMode,
+                        );
+                    });
+                    rows.row(ui, |ui| self.capture_snaplen_view(ui, ctx, active, config));
+                    rows.row(ui, |ui| {
+                        self.capture_buffer_size_view(ui, ctx, active, config)
+                    });
+                });
+            },
+        );
+    }
+
+    fn capture_toggle_view(
+        &mut self, ui: &mut egui::Ui, ctx: &mut Context, label: String,
+        active: Option<bool>, config: bool, request: fn(bool) -> Request,
+    ) {
+        ui.label(format!("{label}:"));
+
+        let differ = active.is_some_and(|active| active != config);
+        let text = match active {
+            Some(active) => styles::text::is_enabled(active),
+            None => RichText::new(t!("Text.None")),
+        };
+        Self::different_from_config(ui, text, differ);
+
+        // Changes take effect only on config
+        if ui.button(styles::text::action(config)).clicked() {
+            let _ = ctx
+                .ui_client_requests_tx
+                .try_send(UiClientRequest::Request(request(!config)));
+            self.request_server_settings(ctx);
+        }
+    }
+
+    fn capture_snaplen_view(
+        &mut self, ui: &mut egui::Ui, ctx: &mut Context,
+        active: Option<CaptureOptionsDto>, config: CaptureOptionsDto,
+    ) {
+        ui.label(format!(
+            "{}:",
+            t!("Tab.SettingsServer.Label.CaptureOptions.Snaplen")
+        ));
+
+        let differ = active.is_some_and(|active| active.snaplen != config.snaplen);
+        let text = match active {
+            Some(active) => RichText::new(active.snaplen.to_string()),
+            None => RichText::new(t!("Text.None")),
+        };
+        Self::different_from_config(ui, text, differ);
+
+        let snaplen = self.capture_snaplen.get_or_insert(config.snaplen);
+        ui.add(
+            DragValue::new(snaplen)
+                .speed(64)
+                .range(1..=CaptureOptionsDto::SNAPLEN_MAX)
+                .suffix(format!(" {}", format::unit(0, format::prefixes()))),
+        )
+        .on_hover_text(t!("Tab.SettingsServer.Note.CaptureOptions.Snaplen"));
+
+        if ui.button(t!("Button.Apply")).clicked() {
+            let snaplen = self.capture_snaplen.unwrap_or(config.snaplen);
+            if let Err(err) = ctx.ui_client_requests_tx.try_send(
+                UiClientRequest::Request(Request::SetCaptureSnaplen(snaplen)),
+            ) {
+                log::error!("Failed to send request (SetCaptureSnaplen): {err}");
+            }
+            self.request_server_settings(ctx);
+        }
+    }
+
+    fn capture_buffer_size_view(
+        &mut self, ui: &mut egui::Ui, ctx: &mut Context,
+        active: Option<CaptureOptionsDto>, config: CaptureOptionsDto,
+    ) {
+        ui.label(format!(
+            "{}:",
+            t!("Tab.SettingsServer.Label.CaptureOptions.BufferSize")
+        ));
+
+        let differ =
+            active.is_some_and(|active| active.buffer_size != config.buffer_size);
+        let text = match active {
+            Some(active) if active.buffer_size == 0 => RichText::new(t!("Text.Default")),
+            Some(active) => RichText::new(active.buffer_size.to_string()),
+            None => RichText::new(t!("Text.None")),
+        };
+        Self::different_from_config(ui, text, differ);
+
+        let buffer_size = self.capture_buffer_size.get_or_insert(config.buffer_size);
+        ui.add(
+            DragValue::new(buffer_size)
+                .speed(1024)
+                .range(0..=i32::MAX)
+                .suffix(format!(" {}", format::unit(0, format::prefixes()))),
+        )
+        .on_hover_text(t!("Tab.SettingsServer.Note.CaptureOptions.BufferSize"));
+
+        if ui.button(t!("Button.Apply")).clicked() {
+            let buffer_size = self.capture_buffer_size.unwrap_or(config.buffer_size);
+            if let Err(err) = ctx.ui_client_requests_tx.try_send(
+                UiClientRequest::Request(Request::SetCaptureBufferSize(buffer_size)),
+            ) {
+                log::error!("Failed to send request (SetCaptureBufferSize): {err}");
+            }
+            self.request_server_settings(ctx);
+        }
+    }
+
+    fn schedule_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        ui.collapsing(
+            styles::heading::normal(&t!("Tab.SettingsServer.Label.Schedule")),
+            |ui| {
+                ui.label(RichText::new(t!("Tab.SettingsServer.Note.Schedule")).italics());
+                ui.add_space(styles::space::SMALL);
+                ui.label(RichText::new(Self::schedule_status(ctx)).strong());
+                ui.add_space(styles::space::SMALL);
+
+                let mut rows = self.schedule.clone().unwrap_or_else(|| {
+                    ctx.settings_server
+                        .schedule
+                        .iter()
+                        .map(ScheduleRow::from)
+                        .collect()
+                });
+                let mut is_changed = false;
+                let mut removed = None;
+                Grid::new("Settings.Schedule.Grid")
+                    .striped(false)
+                    .num_columns(5)
+                    .show(ui, |ui| {
+                        ui.label(t!("Tab.SettingsServer.Label.Schedule.Days"));
+                        ui.label(t!("Tab.SettingsServer.Label.Schedule.Start"));
+                        ui.label(t!("Tab.SettingsServer.Label.Schedule.End"));
+                        ui.label(t!("Tab.SettingsServer.Label.Schedule.Timezone"));
+                        ui.end_row();
+
+                        for (index, row) in rows.iter_mut().enumerate() {
+                            is_changed |= Self::schedule_row(ui, row);
+                            if ui.button("🗑").clicked() {
+                                removed = Some(index);
+                            }
+                            ui.end_row();
+                        }
+                    });
+                if let Some(index) = removed {
+                    rows.remove(index);
+                    is_changed = true;
+                }
+
+                // `None` if any of the rows is invalid
+                let windows: Option<Vec<CaptureWindow>> =
+                    rows.iter().map(ScheduleRow::parse).collect();
+                ui.horizontal(|ui| {
+                    if ui
+                        .button(t!("Tab.SettingsServer.Button.Schedule.Add"))
+                        .clicked()
+                    {
+                        rows.push(ScheduleRow::default());
+                        is_changed = true;
+                    }
+                    let apply = ui
+                        .add_enabled(
+                            windows.is_some(),
+                            egui::Button::new(t!("Button.Apply")),
+                        )
+                        .on_disabled_hover_text(t!(
+                            "Tab.SettingsServer.Hover.Schedule.Invalid"
+                        ));
+                    if apply.clicked() {
+                        if let Some(windows) = windows {
+                            self.send_schedule(ctx, windows);
+                        }
+                    }
+                    if ui.button(t!("Button.Reset")).clicked() {
+                        self.schedule = None;
+                    }
+                });
+                if is_changed {
+                    self.schedule = Some(rows);
+                }
+            },
+        );
+    }
+
+    // True, if the row is edited
+    fn schedule_row(ui: &mut egui::Ui, row: &mut ScheduleRow) -> bool {
+        let mut is_changed = false;
+        ui.horizontal(|ui| {
+            for (day, is_chosen) in WEEK.iter().zip(row.days.iter_mut()) {
+                is_changed |= ui.toggle_value(is_chosen, Self::weekday(day)).changed();
+            }
+        });
+        for field in [&mut row.start, &mut row.end] {
+            is_changed |= ui
+                .add(
+                    TextEdit::singleline(field)
+                        .desired_width(50.0)
+                        .hint_text("HH:MM"),
+                )
+                .changed();
+        }
+        is_changed |= ui
+            .add(TextEdit::singleline(&mut row.timezone).desired_width(70.0))
+            .on_hover_text(t!("Tab.SettingsServer.Hover.Schedule.Timezone"))
+            .changed();
+
+        is_changed
+    }
+
+    fn schedule_status(ctx: &Context) -> String {
+        if ctx.settings_server.schedule.is_empty() {
+            return t!("Tab.SettingsServer.Label.Schedule.Unrestricted").to_string();
+        }
+        let status = ctx.settings_server.schedule_status;
+        let time = status
+            .next_change
+            .map(|time| format::date_time(&time.with_timezone(&Local)));
+        match (status.is_active, time) {
+            (true, Some(time)) => {
+                t!(
+                    "Tab.SettingsServer.Label.Schedule.ActiveUntil",
+                    "time" = time
+                )
+            },
+            (true, None) => t!("Tab.SettingsServer.Label.Schedule.Active"),
+            (false, Some(time)) => {
+                t!(
+                    "Tab.SettingsServer.Label.Schedule.PausedUntil",
+                    "time" = time
+                )
+            },
+            (false, None) => t!("Tab.SettingsServer.Label.Schedule.Paused"),
+        }
+        .to_string()
+    }
+
+    fn weekday(day: &Weekday) -> String {
+        match day {
+            Weekday::Mon => t!("Text.Weekday.Mon"),
+            Weekday::Tue => t!("Text.Weekday.Tue"),
+            Weekday::Wed => t!("Text.Weekday.Wed"),
+            Weekday::Thu => t!("Text.Weekday.Thu"),
+            Weekday::Fri => t!("Text.Weekday.Fri"),
+            Weekday::Sat => t!("Text.Weekday.Sat"),
+            Weekday::Sun => t!("Text.Weekday.Sun"),
+        }
+        .to_string()
+    }
+
+    fn send_schedule(&mut self, ctx: &mut Context, windows: Vec<CaptureWindow>) {
+        if let Err(err) = ctx
+            .ui_client_requests_tx
+            .try_send(UiClientRequest::Request(Request::SetSchedule(windows)))
+        {
+            log::error!("Failed to send request (SetSchedule): {err}");
+        }
+        // Shown from the server again, after it confirms the schedule
+        self.schedule = None;
+        self.request_server_settings(ctx);
+    }
+
+    fn protocol_ports_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        ui.collapsing(
+            styles::heading::normal(&t!("Tab.SettingsServer.Label.ProtocolPorts")),
+            |ui| {
+                ui.label(
+                    RichText::new(t!("Tab.SettingsServer.Note.ProtocolPorts")).italics(),
+                );
+                ui.add_space(styles::space::SMALL);
+
+                Grid::new("Settings.ProtocolPorts.Grid")
+                    .striped(false)
+                    .num_columns(5)
+                    .show(ui, |ui| {
+                        for protocol in PortTable::configurable() {
+                            self.protocol_ports_row(ui, ctx, protocol);
+                            ui.end_row();
+                        }
+                    });
+            },
+        );
+    }
+
+    fn protocol_ports_row(
+        &mut self, ui: &mut egui::Ui, ctx: &mut Context, protocol: ProtocolId,
+    ) {
+        ui.label(format!("{protocol}:"));
+
+        let current = ctx.settings_server.protocol_ports.get(&protocol);
+        match current {
+            Some(ports) => ui.label(RichText::new(Self::join_ports(ports)).strong()),
+            None => ui.label(RichText::new(t!("Text.Default")).italics()),
+        };
+
+        let field = self
+            .protocol_ports
+            .entry(PortTable::name(&protocol))
+            .or_insert_with(|| current.map(Self::join_ports).unwrap_or_default());
+        ui.add(TextEdit::singleline(field));
+        let ports = Self::parse_ports(field);
+
+        let apply = ui
+            .add_enabled(ports.is_some(), egui::Button::new(t!("Button.Apply")))
+            .on_disabled_hover_text(t!("Tab.SettingsServer.Hover.ProtocolPorts.Invalid"));
+        if apply.clicked() {
+            if let Some(ports) = ports {
+                self.send_protocol_ports(ctx, protocol, ports);
+            }
+        }
+
+        if ui.button(t!("Button.Reset")).clicked() {
+            self.protocol_ports.remove(&PortTable::name(&protocol));
+            self.send_protocol_ports(ctx, protocol, vec![]);
+        }
+    }
+
+    fn send_protocol_ports(
+        &mut self, ctx: &mut Context, protocol: ProtocolId, ports: Vec<u16>,
+    ) {
+        if let Err(err) = ctx.ui_client_requests_tx.try_send(UiClientRequest::Request(
+            Request::SetProtocolPorts(protocol, ports),
+        )) {
+            log::error!("Failed to send request (SetProtocolPorts): {err}");
+        }
+        self.request_server_settings(ctx);
+    }
+
+    fn join_ports(ports: &[u16]) -> String {
+        ports
+            .iter()
+            .map(u16::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    // `None` if any of the values isn't a port
+    fn parse_ports(text: &str) -> Option<Vec<u16>> {
+        text.split([',', ' '])
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(|value| value.parse::<u16>().ok())
+            .collect()
+    }
+
+    fn mirror_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        ui.collapsing(
+            styles::heading::normal(&t!("Tab.SettingsServer.Label.Mirror")),
+            |ui| {
+                ui.label(RichText::new(t!("Tab.SettingsServer.Note.Mirror")).italics());
+                ui.add_space(styles::space::SMALL);
+
+                let active = ctx.settings_server.mirror_active.clone();
+                let grid = Grid::new("Settings.Mirror.Grid")
+                    .striped(false)
+                    .num_columns(4);
+                layout::Rows::show(ui, grid, |ui, rows| {
+                    rows.row(ui, |ui| self.mirror_status_view(ui, ctx, active.as_ref()));
+                    rows.row(ui, |ui| self.mirror_target_view(ui, ctx, active.as_ref()));
+                });
+            },
+        );
+    }
+
+    fn mirror_status_view(
+        &mut self, ui: &mut egui::Ui, ctx: &mut Context, active: Option<&MirrorDto>,
+    ) {
+        ui.label(format!("{}:", t!("Tab.SettingsServer.Label.Mirror.Status")));
+        ui.label(styles::text::is_enabled(active.is_some()));
+
+        let Some(mirror) = active else {
+            return;
+        };
+        let limits = t!(
+            "Tab.SettingsServer.Hover.Mirror.Limits",
+            "encapsulation" = mirror.encapsulation,
+            "packets" = Self::mirror_limit(mirror.packets_per_second, format::count),
+            // Kilobytes of the server config are binary ones
+            "bandwidth" = Self::mirror_limit(mirror.kilobytes_per_second, |limit| {
+                format::rate(limit.saturating_mul(1024) as f64)
+            })
+        );
+        match &mirror.error {
+            Some(err) => ui.label(
+                RichText::new(t!("Tab.SettingsServer.Label.Mirror.Error", "error" = err))
+                    .color(colors::get(Role::WritingFailed)),
+            ),
+            None => ui.label(t!(
+                "Tab.SettingsServer.Label.Mirror.Counters",
+                "filter" = mirror.filter,
+                "target" = mirror.target,
+                "mirrored" = format::count(mirror.mirrored),
+                "dropped" = format::count(mirror.dropped_by_limit)
+            )),
+        }
+        .on_hover_text(limits);
+
+        if ui.button(styles::text::action(true)).clicked() {
+            self.send_mirror(ctx, false, mirror.target, mirror.filter.clone());
+        }
+    }
+
+    fn mirror_target_view(
+        &mut self, ui: &mut egui::Ui, ctx: &mut Context, active: Option<&MirrorDto>,
+    ) {
+        ui.label(format!("{}:", t!("Tab.SettingsServer.Label.Mirror.Target")));
+        let target = self.mirror_target.get_or_insert_with(|| {
+            active
+                .map(|mirror| mirror.target.to_string())
+                .unwrap_or_default()
+        });
+        ui.add(
+            TextEdit::singleline(target)
+                .hint_text(t!("Tab.SettingsServer.Hint.Mirror.Target")),
+        );
+        let target = target.trim().parse::<SocketAddr>().ok();
+
+        let filter = self.mirror_filter.get_or_insert_with(|| {
+            active
+                .map(|mirror| mirror.filter.to_string())
+                .unwrap_or_default()
+        });
+        ui.add(
+            TextEdit::singleline(filter)
+                .hint_text(t!("Tab.SettingsServer.Hint.Mirror.Filter")),
+        );
+        let filter = filter.parse::<MirrorFilter>().ok();
+
+        let apply = ui
+            .add_enabled(
+                target.is_some() && filter.is_some(),
+                egui::Button::new(t!("Button.Apply")),
+            )
+            .on_disabled_hover_text(t!("Tab.SettingsServer.Hover.Mirror.Invalid"));
+        if apply.clicked() {
+            if let (Some(target), Some(filter)) = (target, filter) {
+                self.send_mirror(ctx, true, target, filter);
+            }
+        }
+    }
+
+    // Zero is unlimited
+    fn mirror_limit(limit: u64, show: impl Fn(u64) -> String) -> String {
+        match limit {
+            0 => t!("Tab.SettingsServer.Label.Mirror.Unlimited").to_string(),
+            limit => show(limit),
+        }
+    }
+
+    fn send_mirror(
+        &mut self, ctx: &mut Context, enabled: bool, target: SocketAddr,
+        filter: MirrorFilter,
+    ) {
+        if let Err(err) = ctx.ui_client_requests_tx.try_send(UiClientRequest::Request(
+            Request::SetMirror {
+                enabled,
+                target,
+                filter,
+            },
+        )) {
+            log::error!("Failed to send request (SetMirror): {err}");
+        }
+        self.request_server_settings(ctx);
+    }
+
+    fn failure_samples_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        ui.collapsing(
+            styles::heading::normal(&t!("Tab.SettingsServer.Label.FailureSamples")),
+            |ui| {
+                ui.label(
+                    RichText::new(t!("Tab.SettingsServer.Note.FailureSamples")).italics(),
+                );
+                ui.add_space(styles::space::SMALL);
+
+                if !ctx.settings_server.failure_samples_config {
+                    ui.label(
+                        RichText::new(t!(
+                            "Tab.SettingsServer.Label.FailureSamples.Disabled"
+                        ))
+                        .color(colors::get(Role::Outdated)),
+                    );
+                    return;
+                }
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .button(t!("Tab.SettingsServer.Label.FailureSamples.Request"))
+                        .clicked()
+                    {
+                        Self::send_failure_samples(ctx, Request::GetFailureSamples);
+                    }
+                    if ui.button(t!("Button.Reset")).clicked() {
+                        Self::send_failure_samples(ctx, Request::ResetFailureSamples);
+                    }
+                });
+
+                let Some(dto) = ctx.settings_server.failure_samples.clone() else {
+                    return;
+                };
+                self.failure_buckets_view(ui, ctx, &dto);
+            },
+        );
+    }
+
+    fn failure_buckets_view(
+        &mut self, ui: &mut egui::Ui, ctx: &mut Context, dto: &FailureSamplesDto,
+    ) {
+        if dto.buckets.is_empty() && dto.unbucketed == 0 {
+            ui.label(t!("Tab.SettingsServer.Label.FailureSamples.Empty"));
+            return;
+        }
+
+        let grid = Grid::new("Settings.FailureSamples.Grid")
+            .striped(true)
+            .num_columns(5);
+        grid.show(ui, |ui| {
+            ui.label(styles::heading::normal(&t!(
+                "Tab.SettingsServer.Label.FailureSamples.Protocol"
+            )));
+            ui.label(styles::heading::normal(&t!(
+                "Tab.SettingsServer.Label.FailureSamples.Reason"
+            )));
+            ui.label(styles::heading::normal(&t!(
+                "Tab.SettingsServer.Label.FailureSamples.Failures"
+            )));
+            ui.label(styles::heading::normal(&t!(
+                "Tab.SettingsServer.Label.FailureSamples.Samples"
+            )));
+            ui.label("");
+            ui.end_row();
+
+            for bucket in &dto.buckets {
+                ui.label(bucket.protocol.to_string());
+                ui.label(&bucket.reason);
+                ui.label(format::count(bucket.failures));
+                ui.label(bucket.samples.len().to_string());
+                let save = ui.add_enabled(
+                    !bucket.samples.is_empty(),
+                    egui::Button::new("Save .pcap"),
+                );
+                if save.clicked() {
+                    Self::save_failure_samples(ctx, dto, bucket.samples.iter());
+                }
+                ui.end_row();
+            }
+        });
+
+        if dto.unbucketed > 0 {
+            ui.label(t!(
+                "Tab.SettingsServer.Label.FailureSamples.Unbucketed",
+                "failures" = format::count(dto.unbucketed)
+            ));
+        }
+        let has_samples = dto.buckets.iter().any(|bucket| !bucket.samples.is_empty());
+        let save_all = ui.add_enabled(
+            has_samples,
+            egui::Button::new(t!("Tab.SettingsServer.Label.FailureSamples.SaveAll")),
+        );
+        if save_all.clicked() {
+            let samples = dto.buckets.iter().flat_map(|bucket| bucket.samples.iter());
+            Self::save_failure_samples(ctx, dto, samples);
+        }
+    }
+
+    fn send_failure_samples(ctx: &mut Context, request: Request) {
+        let result = ctx
+            .ui_client_requests_tx
+            .try_send(UiClientRequest::Request(request));
+        if let Err(err) = result {
+            log::error!("Failed to send request (Failure Samples): {err}");
+        }
+    }
+
+    // Samples are few & bounded by the server, so they're saved right away
+    fn save_failure_samples<'a>(
+        ctx: &mut Context, dto: &FailureSamplesDto,
+        samples: impl Iterator<Item = &'a OwnedFrame>,
+    ) {
+        let Some(link_type) = dto.link_type else {
+            MessageModal::error(&t!("Error.FailedUnpackLinkType"))
+                .try_send_by(&ctx.modals_tx);
+            return;
+        };
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter(net::PCAP_FILTER_NAME, net::PCAP_FILTER_EXTENSIONS)
+            .save_file()
+        else {
+            return;
+        };
+
+        let mut frames: VecDeque<OwnedFrame> = samples.cloned().collect();
+        frames
+            .make_contiguous()
+            .sort_by_key(|frame| (frame.header.tv_sec, frame.header.tv_usec));
+        if let Err(err) = save_pcap(&path, &frames, pcap::Linktype(link_type)) {
+            MessageModal::error(&format!("{}: {}", &t!("Error.Pcap"), err))
+                .try_send_by(&ctx.modals_tx);
+        }
+    }
+
     fn sending_unparsed_frames_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
         let differ = ctx.settings_server.send_unparsed_frames_active
             != ctx.settings_server.send_unparsed_frames_config;
@@ -307,6 +1087,126 @@ impl SettingsServerTab {
         }
     }
 
+    fn flow_export_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        let differ = ctx.settings_server.flow_export_active.is_some()
+            != ctx.settings_server.flow_export_config;
+
+        ui.add(egui::Label::new(styles::heading::normal(&t!(
+            "Tab.SettingsServer.Label.FlowExport"
+        ))))
+        .on_hover_text(t!("Tab.SettingsServer.Note.FlowExport"));
+        let is_enabled_text =
+            styles::text::is_enabled(ctx.settings_server.flow_export_active.is_some());
+        Self::different_from_config(ui, is_enabled_text, differ);
+
+        if let Some(exporter) = &ctx.settings_server.flow_export_active {
+            ui.label(format!(
+                "{} ({})",
+                exporter.collector,
+                exporter.protocol.to_uppercase()
+            ));
+            ui.label(t!(
+                "Tab.SettingsServer.Label.FlowExport.Status",
+                "active" = exporter.flows_active,
+                "exported" = exporter.flows_exported,
+                "datagrams" = exporter.datagrams_sent
+            ));
+        }
+    }
+
+    fn pcap_writer_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        let differ = ctx.settings_server.pcap_writer_active.is_some()
+            != ctx.settings_server.pcap_writer_config;
+
+        ui.add(egui::Label::new(styles::heading::normal(&t!(
+            "Tab.SettingsServer.Label.PcapWriter"
+        ))))
+        .on_hover_text(t!("Tab.SettingsServer.Note.PcapWriter"));
+        let is_enabled_text =
+            styles::text::is_enabled(ctx.settings_server.pcap_writer_active.is_some());
+        Self::different_from_config(ui, is_enabled_text, differ);
+
+        if let Some(writer) = &ctx.settings_server.pcap_writer_active {
+            ui.label(writer.file.as_deref().unwrap_or(writer.directory.as_str()));
+            match &writer.error {
+                Some(err) => {
+                    ui.label(
+                        RichText::new(t!(
+                            "Tab.SettingsServer.Label.PcapWriter.Error",
+                            "error" = err
+                        ))
+                        .color(colors::get(Role::WritingFailed)),
+                    );
+                },
+                None => {
+                    ui.label(t!(
+                        "Tab.SettingsServer.Label.PcapWriter.Status",
+                        "bytes" = format::bytes(writer.bytes_written),
+                        "kept" = writer.files_kept
+                    ));
+                },
+            }
+        }
+    }
+
+    fn statistics_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        ui.add(egui::Label::new(styles::heading::normal(&t!(
+            "Tab.SettingsServer.Label.Statistics"
+        ))))
+        .on_hover_text(t!("Tab.SettingsServer.Note.Statistics"));
+
+        match ctx.net_storage.capture.statistics() {
+            Some(dto) => {
+                let since_key = match dto.persistent {
+                    true => "Tab.SettingsServer.Label.Statistics.Since",
+                    false => "Tab.SettingsServer.Label.Statistics.SinceStart",
+                };
+                ui.label(t!(
+                    "Tab.SettingsServer.Label.Statistics.Totals",
+                    "frames" = format::count(dto.frames),
+                    "bytes" = format::bytes(dto.bytes),
+                    "dropped" = format::count(dto.dropped.saturating_add(dto.if_dropped))
+                ))
+                .on_hover_text(t!(
+                    "Tab.SettingsServer.Hover.Statistics.Uptime",
+                    "uptime" = format::duration(dto.uptime_seconds),
+                    "started" = format::unix_date_time(dto.process_started)
+                ));
+                ui.label(t!(since_key, "since" = format::unix_date_time(dto.since)));
+            },
+            None => {
+                ui.label(
+                    RichText::new(t!("Text.LastUpdate.Never"))
+                        .color(colors::get(Role::Outdated)),
+                );
+                ui.label("");
+            },
+        }
+
+        if !self.statistics_reset_confirm {
+            if ui.button(t!("Button.Reset")).clicked() {
+                self.statistics_reset_confirm = true;
+            }
+            return;
+        }
+        ui.horizontal(|ui| {
+            if ui.button(t!("Button.Confirm")).clicked() {
+                self.statistics_reset_confirm = false;
+                if let Err(err) = ctx
+                    .ui_client_requests_tx
+                    .try_send(UiClientRequest::Request(Request::ResetStatistics))
+                {
+                    log::error!("Failed to send command (Reset Statistics): {err}");
+                } else {
+                    log::info!("UI -> WS: Sent 'Reset Statistics' command.");
+                }
+            }
+            if ui.button(t!("Button.Cancel")).clicked() {
+                self.statistics_reset_confirm = false;
+            }
+        });
+    }
+
     fn different_from_config(
         ui: &mut egui::Ui, label: RichText, is_different: bool,
     ) -> egui::Response {
@@ -328,3 +1228,66 @@ impl SettingsServerTab {
         }
     }
 }
+
+const WEEK: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+const TIME_FORMAT: &str = "%H:%M";
+
+// Window of the schedule, edited as text. No chosen days means every day
+#[derive(Clone)]
+struct ScheduleRow {
+    days: [bool; 7], // From Monday
+    start: String,
+    end: String,
+    timezone: String,
+}
+
+impl Default for ScheduleRow {
+    fn default() -> Self {
+        Self {
+            days: [false; 7],
+            start: String::from("09:00"),
+            end: String::from("18:00"),
+            timezone: ScheduleTimezone::Local.to_string(),
+        }
+    }
+}
+
+impl From<&CaptureWindow> for ScheduleRow {
+    fn from(window: &CaptureWindow) -> Self {
+        let mut days = [false; 7];
+        for (day, is_chosen) in WEEK.iter().zip(days.iter_mut()) {
+            *is_chosen = window.days.contains(day);
+        }
+
+        Self {
+            days,
+            start: window.start.format(TIME_FORMAT).to_string(),
+            end: window.end.format(TIME_FORMAT).to_string(),
+            timezone: window.timezone.to_string(),
+        }
+    }
+}
+
+impl ScheduleRow {
+    fn parse(&self) -> Option<CaptureWindow> {
+        Some(CaptureWindow {
+            days: WEEK
+                .iter()
+                .zip(self.days)
+                .filter(|(_, is_chosen)| *is_chosen)
+                .map(|(day, _)| *day)
+                .collect(),
+            start: NaiveTime::parse_from_str(self.start.trim(), TIME_FORMAT).ok()?,
+            end: NaiveTime::parse_from_str(self.end.trim(), TIME_FORMAT).ok()?,
+            timezone: ScheduleTimezone::from_str(&self.timezone).ok()?,
+        })
+    }
+}
@@ -1,12 +1,42 @@
 use crate::context::Context;
+use crate::net::anonymize::Anonymizer;
+use crate::net::endpoints;
 use crate::net::inspector::ProtocolsRegistered;
+use crate::net::snapshot;
+use crate::net::snapshot::{DeviceSnapshot, HostSnapshot, Snapshot, SnapshotDiff};
+use crate::ui::format;
+use crate::ui::modals::message::MessageModal;
 use crate::ui::styles;
+use crate::ui::styles::colors::Role;
+use crate::ui::styles::protocols;
 use crate::ui::tabs::Tab;
-use egui::{Grid, RichText, ScrollArea};
+use chrono::Local;
+use egui::{DragValue, Grid, RichText, ScrollArea, TextEdit};
+use std::collections::HashSet;
+use std::net::IpAddr;
 use strum::IntoEnumIterator;
 
-#[derive(Default)]
-pub struct StatsTab;
+const DISTRIBUTION_HEIGHT: f32 = 250.0;
+
+pub struct StatsTab {
+    snapshot_name: String,
+    // Indices of the snapshots A & B, compared side by side
+    compared: [Option<usize>; 2],
+    share_threshold: f64,
+    // Saved snapshots are anonymized
+    anonymize: bool,
+}
+
+impl Default for StatsTab {
+    fn default() -> Self {
+        Self {
+            snapshot_name: String::new(),
+            compared: [None, None],
+            share_threshold: snapshot::DEFAULT_SHARE_THRESHOLD,
+            anonymize: false,
+        }
+    }
+}
 
 impl StatsTab {
     pub fn show(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
@@ -24,6 +54,9 @@ impl StatsTab {
                         self.main_statistics_view(ui, ctx);
                     });
                 });
+
+                ui.add_space(styles::space::SMALL);
+                self.snapshots_view(ui, ctx);
             });
     }
 
@@ -41,8 +74,23 @@ impl StatsTab {
                 ui.label(ctx.net_storage.inspector.len().to_string());
                 ui.end_row();
 
+                ui.label(format!("{}:", t!("Tab.Stats.Main.InvalidChecksums")))
+                    .on_hover_text(t!("Tab.Stats.Main.InvalidChecksums.Hint"));
+                ui.label(ctx.net_storage.inspector.invalid_checksums().to_string());
+                ui.end_row();
+
+                ui.label(format!("{}:", t!("Tab.Stats.Main.UdpLengthMismatches")))
+                    .on_hover_text(t!("Tab.Stats.Main.UdpLengthMismatches.Hint"));
+                ui.label(
+                    ctx.net_storage
+                        .inspector
+                        .udp_length_mismatches()
+                        .to_string(),
+                );
+                ui.end_row();
+
                 ui.label(format!("{}:", t!("Tab.Stats.Main.DeviceAliases")));
-                ui.label(ctx.net_storage.devices.list.len().to_string());
+                ui.label(ctx.net_storage.devices.list().len().to_string());
                 ui.end_row();
 
                 ui.label(format!("{}:", t!("Tab.Stats.Main.DevicesFound")));
@@ -64,13 +112,20 @@ impl StatsTab {
     }
 
     fn protocols_view(&self, ui: &mut egui::Ui, ctx: &mut Context) {
+        let overrides = &ctx.client_settings.protocol_colors;
+
         ui.heading(format!("{}:", t!("Tab.Stats.Protocols.Header")));
         Grid::new("Stats.Protocols.Grid")
             .striped(false)
             .num_columns(2)
             .show(ui, |ui| {
                 for protocol in ProtocolsRegistered::iter() {
-                    ui.label(format!("{protocol}:"));
+                    ui.label(protocols::label(
+                        ui,
+                        &protocol.id(),
+                        &format!("{protocol}:"),
+                        overrides,
+                    ));
                     ui.label(format!(
                         "{}",
                         ctx.net_storage.inspector.records_captured(&protocol)
@@ -78,6 +133,438 @@ impl StatsTab {
                     ui.end_row();
                 }
             });
+
+        ui.add_space(styles::space::SMALL);
+        self.distribution_view(ui, ctx);
+    }
+
+    // Colors of the bars match the protocol labels
+    fn distribution_view(&self, ui: &mut egui::Ui, ctx: &Context) {
+        use egui_plot::{Bar, BarChart, Legend, Plot};
+
+        let overrides = &ctx.client_settings.protocol_colors;
+        let charts = ProtocolsRegistered::iter()
+            .enumerate()
+            .map(|(index, protocol)| {
+                let records = ctx.net_storage.inspector.records_captured(&protocol);
+                let name = protocols::title(&protocol.id(), &protocol.to_string());
+                BarChart::new(
+                    name.clone(),
+                    vec![Bar::new(index as f64, records as f64).name(name)],
+                )
+                .color(protocols::color(ui, &protocol.id(), overrides))
+            })
+            .collect::<Vec<_>>();
+
+        Plot::new("Stats.Protocols.Distribution")
+            .legend(Legend::default())
+            .allow_boxed_zoom(false)
+            .allow_double_click_reset(false)
+            .allow_drag(false)
+            .allow_scroll(false)
+            .allow_zoom(false)
+            .show_axes([false, true])
+            .show_x(false)
+            .height(DISTRIBUTION_HEIGHT)
+            .show(ui, |plot_ui| {
+                for chart in charts {
+                    plot_ui.bar_chart(chart);
+                }
+            });
+    }
+
+    fn snapshots_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        ui.heading(format!("{}:", t!("Tab.Stats.Snapshots.Header")));
+        ui.horizontal(|ui| {
+            ui.add(
+                TextEdit::singleline(&mut self.snapshot_name)
+                    .hint_text(t!("Tab.Stats.Snapshots.Hint.Name")),
+            );
+            if ui
+                .button(t!("Tab.Stats.Snapshots.Button.Take"))
+                .on_hover_text(t!("Tab.Stats.Snapshots.Hover.Take"))
+                .clicked()
+            {
+                self.take_snapshot(ctx);
+            }
+            if ui.button(t!("Tab.Stats.Snapshots.Button.Load")).clicked() {
+                Self::load_snapshot(ctx);
+            }
+            ui.checkbox(&mut self.anonymize, t!("Text.Anonymize"))
+                .on_hover_text(t!("Text.Anonymize.Hover"));
+        });
+
+        if ctx.net_storage.snapshots.is_empty() {
+            ui.label(
+                RichText::new(t!("Tab.Stats.Snapshots.Empty"))
+                    .color(styles::colors::get(Role::Silent)),
+            );
+            return;
+        }
+
+        if let Some(index) = self.snapshots_list_view(ui, ctx) {
+            let snapshot = ctx.net_storage.snapshots.remove(index);
+            log::info!("Stats: Snapshot {} is deleted", snapshot.name);
+            self.compared = self.compared.map(|chosen| match chosen {
+                Some(chosen) if chosen == index => None,
+                Some(chosen) if chosen > index => Some(chosen - 1),
+                chosen => chosen,
+            });
+        }
+
+        ui.add_space(styles::space::SMALL);
+        let snapshots = &ctx.net_storage.snapshots;
+        let chosen = match self.compared {
+            [Some(first), Some(second)] if first != second => {
+                snapshots.get(first).zip(snapshots.get(second))
+            },
+            _ => None,
+        };
+        match chosen {
+            Some((first, second)) => {
+                let diff = SnapshotDiff::compare(first, second);
+                self.comparison_view(ui, ctx, &diff);
+            },
+            None => {
+                ui.label(
+                    RichText::new(t!("Tab.Stats.Snapshots.Compare.Hint"))
+                        .color(styles::colors::get(Role::Silent)),
+                );
+            },
+        }
+    }
+
+    // Returns the index of the snapshot, that should be deleted
+    fn snapshots_list_view(&mut self, ui: &mut egui::Ui, ctx: &Context) -> Option<usize> {
+        let mut removed = None;
+        Grid::new("Stats.Snapshots.Grid")
+            .num_columns(7)
+            .striped(true)
+            .show(ui, |ui| {
+                for column in [
+                    "Tab.Stats.Snapshots.Column.Name",
+                    "Tab.Stats.Snapshots.Column.Taken",
+                    "Tab.Stats.Snapshots.Column.Frames",
+                    "Tab.Stats.Snapshots.Column.Hosts",
+                    "Tab.Stats.Snapshots.Column.Devices",
+                    "Tab.Stats.Snapshots.Column.Compare",
+                ] {
+                    ui.label(styles::heading::grid(&t!(column)));
+                }
+                ui.end_row();
+
+                for (index, snapshot) in ctx.net_storage.snapshots.iter().enumerate() {
+                    ui.label(&snapshot.name);
+                    ui.label(format::time(&snapshot.taken));
+                    ui.label(format::count(snapshot.frames as u64));
+                    ui.label(snapshot.hosts.len().to_string());
+                    ui.label(snapshot.devices.len().to_string());
+                    ui.horizontal(|ui| {
+                        ui.radio_value(&mut self.compared[0], Some(index), "A");
+                        ui.radio_value(&mut self.compared[1], Some(index), "B");
+                    });
+                    ui.horizontal(|ui| {
+                        if ui
+                            .button("💾")
+                            .on_hover_text(t!("Tab.Stats.Snapshots.Hover.Save"))
+                            .clicked()
+                        {
+                            Self::save_snapshot(ctx, snapshot, self.anonymize);
+                        }
+                        if ui
+                            .button("🗑")
+                            .on_hover_text(t!("Tab.Stats.Snapshots.Hover.Delete"))
+                            .clicked()
+                        {
+                            removed = Some(index);
+                        }
+                    });
+                    ui.end_row();
+                }
+            });
+
+        removed
+    }
+
+    fn take_snapshot(&mut self, ctx: &mut Context) {
+        let now = Local::now();
+        let name = match self.snapshot_name.trim() {
+            "" => format::time(&now),
+            name => name.to_string(),
+        };
+        log::info!("Stats: Snapshot {name} is taken");
+
+        let snapshot = Snapshot::capture(name, &ctx.net_storage, now);
+        ctx.net_storage.snapshots.push(snapshot);
+        self.snapshot_name.clear();
+    }
+
+    fn load_snapshot(ctx: &mut Context) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter(snapshot::FILTER_NAME, snapshot::FILTER_EXTENSIONS)
+            .pick_file()
+        else {
+            return;
+        };
+
+        match Snapshot::from_file(&path) {
+            Ok(snapshot) => {
+                log::info!("Stats: Snapshot {} is loaded", snapshot.name);
+                let restored = ctx
+                    .net_storage
+                    .notes
+                    .import(&snapshot.notes, &ctx.net_storage.inspector);
+                if restored > 0 {
+                    log::info!("Stats: {restored} notes are restored from the snapshot");
+                }
+                ctx.net_storage.snapshots.push(snapshot);
+            },
+            Err(err) => {
+                log::error!("Stats: Failed to load snapshot {}: {err}", path.display());
+                let mut text = format!(
+                    "{}\n{}: {}.",
+                    t!("Tab.Stats.Snapshots.Error.Load"),
+                    t!("Error.AdditionalInfo"),
+                    err
+                );
+                if let Some(additional_info) = err.additional_info() {
+                    text.push_str(&format!("\n{additional_info}"));
+                }
+                MessageModal::error(&text).try_send_by(&ctx.modals_tx);
+            },
+        }
+    }
+
+    fn save_snapshot(ctx: &Context, snapshot: &Snapshot, anonymize: bool) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter(snapshot::FILTER_NAME, snapshot::FILTER_EXTENSIONS)
+            .set_file_name(format!("{}.json", snapshot.name))
+            .save_file()
+        else {
+            return;
+        };
+
+        let anonymizer =
+            anonymize.then(|| Anonymizer::new(ctx.client_settings.anonymization));
+        let modal = match snapshot.save_to_file(&path, anonymizer) {
+            Ok(_) => {
+                log::info!("Stats: Snapshot {} is saved", snapshot.name);
+                MessageModal::info(&t!("Tab.Stats.Snapshots.Message.Saved"))
+            },
+            Err(err) => {
+                log::error!("Stats: Failed to save snapshot {}: {err}", path.display());
+                let mut text = format!(
+                    "{}\n{}: {}.",
+                    t!("Tab.Stats.Snapshots.Error.Save"),
+                    t!("Error.AdditionalInfo"),
+                    err
+                );
+                if let Some(additional_info) = err.additional_info() {
+                    text.push_str(&format!("\n{additional_info}"));
+                }
+                MessageModal::error(&text)
+            },
+        };
+        modal.try_send_by(&ctx.modals_tx);
+    }
+
+    fn comparison_view(&mut self, ui: &mut egui::Ui, ctx: &Context, diff: &SnapshotDiff) {
+        ui.heading(format!(
+            "{}: {} → {}",
+            t!("Tab.Stats.Snapshots.Compare.Header"),
+            diff.before.name,
+            diff.after.name
+        ));
+        ui.horizontal(|ui| {
+            ui.label(format!("{}:", t!("Tab.Stats.Snapshots.Compare.Threshold")))
+                .on_hover_text(t!("Tab.Stats.Snapshots.Compare.Threshold.Hint"));
+            ui.add(
+                DragValue::new(&mut self.share_threshold)
+                    .speed(0.5)
+                    .range(0.0..=100.0)
+                    .suffix(" %"),
+            );
+        });
+
+        let overrides = &ctx.client_settings.protocol_colors;
+        Grid::new("Stats.Snapshots.Compare.Protocols")
+            .num_columns(4)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label(styles::heading::grid(&t!(
+                    "Tab.Stats.Snapshots.Compare.Column.Protocol"
+                )));
+                ui.label(styles::heading::grid(&diff.before.name));
+                ui.label(styles::heading::grid(&diff.after.name));
+                ui.label(styles::heading::grid(&t!(
+                    "Tab.Stats.Snapshots.Compare.Column.Change"
+                )));
+                ui.end_row();
+
+                for delta in &diff.protocols {
+                    match ProtocolsRegistered::iter()
+                        .find(|protocol| protocol.to_string() == delta.protocol)
+                    {
+                        Some(protocol) => {
+                            ui.label(protocols::label(
+                                ui,
+                                &protocol.id(),
+                                &delta.protocol,
+                                overrides,
+                            ));
+                        },
+                        None => {
+                            ui.label(&delta.protocol);
+                        },
+                    }
+                    ui.label(format!(
+                        "{} ({}%)",
+                        format::count(delta.before as u64),
+                        format::decimal(delta.share_before, 1)
+                    ));
+                    ui.label(format!(
+                        "{} ({}%)",
+                        format::count(delta.after as u64),
+                        format::decimal(delta.share_after, 1)
+                    ));
+                    let change = RichText::new(format::signed(delta.share_change(), 1));
+                    if delta.is_significant(self.share_threshold) {
+                        ui.label(
+                            change
+                                .color(styles::colors::get(Role::SnapshotChanged))
+                                .strong(),
+                        );
+                    } else {
+                        ui.label(change);
+                    }
+                    ui.end_row();
+                }
+            });
+
+        ui.add_space(styles::space::SMALL);
+        Self::devices_diff_view(
+            ui,
+            &t!("Tab.Stats.Snapshots.Compare.DevicesNew"),
+            &diff.devices_new,
+            styles::colors::get(Role::SnapshotNew),
+        );
+        Self::devices_diff_view(
+            ui,
+            &t!("Tab.Stats.Snapshots.Compare.DevicesGone"),
+            &diff.devices_gone,
+            styles::colors::get(Role::SnapshotGone),
+        );
+
+        ui.add_space(styles::space::SMALL);
+        Self::hosts_diff_view(
+            ui,
+            &t!("Tab.Stats.Snapshots.Compare.HostsNew"),
+            &diff.after.hosts,
+            &diff.hosts_new,
+            styles::colors::get(Role::SnapshotNew),
+        );
+        Self::hosts_diff_view(
+            ui,
+            &t!("Tab.Stats.Snapshots.Compare.HostsGone"),
+            &diff.before.hosts,
+            &diff.hosts_gone,
+            styles::colors::get(Role::SnapshotGone),
+        );
+
+        ui.add_space(styles::space::SMALL);
+        ui.label(styles::heading::normal(&t!(
+            "Tab.Stats.Snapshots.Compare.TopHosts"
+        )));
+        ui.columns(2, |columns| {
+            Self::top_hosts_view(
+                &mut columns[0],
+                diff.before,
+                &diff.hosts_gone,
+                styles::colors::get(Role::SnapshotGone),
+            );
+            Self::top_hosts_view(
+                &mut columns[1],
+                diff.after,
+                &diff.hosts_new,
+                styles::colors::get(Role::SnapshotNew),
+            );
+        });
+    }
+
+    fn devices_diff_view(
+        ui: &mut egui::Ui, title: &str, devices: &[&DeviceSnapshot], color: egui::Color32,
+    ) {
+        ui.horizontal_wrapped(|ui| {
+            ui.label(format!("{title}:"));
+            if devices.is_empty() {
+                ui.label(
+                    RichText::new(t!("Tab.Stats.Snapshots.Compare.None"))
+                        .color(styles::colors::get(Role::Silent)),
+                );
+            }
+            for device in devices {
+                let mut text = device.mac.to_string();
+                if let Some(name) = &device.name {
+                    text.push_str(&format!(" ({name})"));
+                }
+                let details = device
+                    .ip
+                    .iter()
+                    .map(|ip| ip.to_string())
+                    .chain(device.vendor.clone())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                ui.label(RichText::new(text).color(color))
+                    .on_hover_text(details);
+            }
+        });
+    }
+
+    // Hosts are taken in the order of the snapshot, so the most active go first
+    fn hosts_diff_view(
+        ui: &mut egui::Ui, title: &str, hosts: &[HostSnapshot], marked: &HashSet<IpAddr>,
+        color: egui::Color32,
+    ) {
+        ui.horizontal_wrapped(|ui| {
+            ui.label(format!("{title}:"));
+            if marked.is_empty() {
+                ui.label(
+                    RichText::new(t!("Tab.Stats.Snapshots.Compare.None"))
+                        .color(styles::colors::get(Role::Silent)),
+                );
+            }
+            for host in hosts.iter().filter(|host| marked.contains(&host.ip)) {
+                ui.label(RichText::new(host_title(host)).color(color))
+                    .on_hover_text(format::bytes(host.bytes as u64));
+            }
+        });
+    }
+
+    fn top_hosts_view(
+        ui: &mut egui::Ui, snapshot: &Snapshot, marked: &HashSet<IpAddr>,
+        color: egui::Color32,
+    ) {
+        ui.label(RichText::new(&snapshot.name).strong());
+        Grid::new(("Stats.Snapshots.Compare.TopHosts", snapshot.taken))
+            .num_columns(3)
+            .striped(true)
+            .show(ui, |ui| {
+                for host in snapshot.hosts.iter().take(endpoints::TOP_AMOUNT) {
+                    let title = RichText::new(host_title(host));
+                    if marked.contains(&host.ip) {
+                        ui.label(title.color(color));
+                    } else {
+                        ui.label(title);
+                    }
+                    ui.label(format::bytes(host.bytes as u64));
+                    ui.label(format!(
+                        "{} {}",
+                        format::count(host.packets as u64),
+                        t!("Tab.Stats.Snapshots.Compare.Packets")
+                    ));
+                    ui.end_row();
+                }
+            });
     }
 
     fn tab_heading(&self, ui: &mut egui::Ui) {
@@ -87,3 +574,10 @@ impl StatsTab {
         );
     }
 }
+
+fn host_title(host: &HostSnapshot) -> String {
+    match &host.name {
+        Some(name) => format!("{name} ({})", host.ip),
+        None => host.ip.to_string(),
+    }
+}
@@ -1,21 +1,91 @@
 use crate::context::Context;
 use crate::net;
+use crate::net::anonymize::Anonymizer;
+use crate::net::device;
 use crate::net::device::LocalDevice;
+use crate::net::endpoints;
+use crate::net::filter::TimeRange;
+use crate::net::inspector::ProtocolsRegistered;
+use crate::net::inventory::{Inventory, InventoryFormat};
+use crate::net::memory;
+use crate::net::raw::ExportOutcome;
+use crate::net::speed::{DEVICE_SERIES_MAX, MarkerKind, SpeedSnapshot};
+use crate::net::timeline;
+use crate::net::timeline::TimelineTarget;
+use crate::notifications::Severity;
 use crate::ui::components::throughput_settings::ThroughputSettings;
+use crate::ui::format;
 use crate::ui::modals::device::DeviceModal;
 use crate::ui::modals::message::MessageModal;
+use crate::ui::modals::note::OrphanedNotesModal;
+use crate::ui::modals::progress::{BackgroundTask, Progress};
 use crate::ui::styles;
+use crate::ui::styles::colors::Role;
+use crate::ui::styles::layout;
 use crate::ui::tabs::Tab;
-use egui::{Grid, RichText, ScrollArea};
+use crate::ui::tabs::inspector::InspectorRequest;
+use chrono::{DateTime, Local, TimeDelta};
+use dpi::dto::fields;
+use dpi::dto::frame::LinkType;
+use dpi::protocols::ProtocolId;
+use dpi::protocols::ethernet::mac::MacAddress;
+use egui::{Grid, RichText, ScrollArea, TextEdit};
+use std::collections::HashSet;
+use std::ops::ControlFlow;
+use std::path::PathBuf;
+use strum::IntoEnumIterator;
+
+const PLOT_MIN_HEIGHT: f32 = 180.0;
+// Records around the event are shown, when it's opened in Inspector
+const TIMELINE_RANGE_MARGIN_SECONDS: i64 = 5;
 
 pub struct StatusTab {
     throughput_settings: ThroughputSettings,
+    // Plot X coordinates of the time range being dragged
+    selection: Option<(f64, f64)>,
+    // Plot is frozen while hovered or pinned, the data is still collected
+    frozen: Option<SpeedSnapshot>,
+    is_plot_hovered: bool,
+    is_plot_pinned: bool,
+    // Peaks of the plotted device are shown instead of the whole traffic
+    peak_device: Option<MacAddress>,
+    pcap_export: Option<BackgroundTask<ExportOutcome>>,
+    // Every external host is listed, not only the top ones
+    is_all_hosts_shown: bool,
+    // Less severe timeline events are hidden
+    timeline_severity: Severity,
+    // Device cards get the checkboxes, the actions are applied to the checked ones
+    is_selecting: bool,
+    selected_devices: HashSet<MacAddress>,
+    alias_prefix: String,
+    is_hidden_shown: bool,
+    // Exports are anonymized, each one by its own checkbox
+    anonymize_pcap: bool,
+    anonymize_timeline: bool,
+    anonymize_devices: bool,
+    anonymize_inventory: bool,
 }
 
 impl StatusTab {
     pub fn new(ctx: &Context) -> Self {
         Self {
             throughput_settings: ThroughputSettings::new(ctx),
+            selection: None,
+            frozen: None,
+            is_plot_hovered: false,
+            is_plot_pinned: false,
+            peak_device: None,
+            pcap_export: None,
+            is_all_hosts_shown: false,
+            timeline_severity: Severity::Info,
+            is_selecting: false,
+            selected_devices: HashSet::new(),
+            alias_prefix: String::new(),
+            is_hidden_shown: false,
+            anonymize_pcap: false,
+            anonymize_timeline: false,
+            anonymize_devices: false,
+            anonymize_inventory: false,
         }
     }
 }
@@ -32,11 +102,20 @@ impl StatusTab {
         ScrollArea::vertical()
             .auto_shrink([false, false])
             .show(ui, |ui| {
+                self.capture_schedule_view(ui, ctx);
+                self.capture_idle_view(ui, ctx);
+                self.capture_drops_view(ui, ctx);
+                self.sampling_view(ui, ctx);
                 ui.horizontal_centered(|ui| {
                     self.plot_view(ui, ctx);
                 });
                 self.current_peak_stats_view(ui, ctx);
                 self.pcap_save_view(ui, ctx);
+                self.storage_view(ui, ctx);
+                self.conflicts_view(ui, ctx);
+                self.routers_view(ui, ctx);
+                self.timeline_view(ui, ctx);
+                self.endpoints_view(ui, ctx);
                 self.devices_view(ui, ctx);
             });
     }
@@ -68,28 +147,227 @@ impl StatusTab {
         });
     }
 
+    // Pause isn't a failure, so it's shown without the warning colors
+    fn capture_schedule_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        let status = ctx.settings_server.schedule_status;
+        if status.is_active {
+            return;
+        }
+
+        egui::Frame::group(&egui::Style::default())
+            .corner_radius(5.0)
+            .show(ui, |ui| {
+                ui.set_width(ui.available_width());
+                let text = match status.next_change {
+                    Some(time) => t!(
+                        "Tab.Status.Capture.Label.ScheduledResume",
+                        "time" = format::date_time(&time.with_timezone(&Local))
+                    ),
+                    None => t!("Tab.Status.Capture.Label.ScheduledPause"),
+                };
+                ui.label(RichText::new(format!("⏸ {text}")).strong());
+            });
+        ui.add_space(4.0);
+    }
+
+    fn capture_idle_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        let Some(seconds) = ctx.net_storage.capture.idle_seconds() else {
+            return;
+        };
+
+        egui::Frame::group(&egui::Style::default())
+            .fill(styles::colors::get(Role::IdleBackground))
+            .corner_radius(5.0)
+            .show(ui, |ui| {
+                ui.set_width(ui.available_width());
+                ui.label(
+                    RichText::new(format!(
+                        "⚠ {}",
+                        t!("Tab.Status.Capture.Warning.Idle", "seconds" = seconds)
+                    ))
+                    .color(styles::colors::get(Role::IdleText))
+                    .strong(),
+                );
+                ui.label(
+                    RichText::new(t!("Tab.Status.Capture.Label.IdleHint"))
+                        .color(styles::colors::get(Role::IdleText)),
+                );
+            });
+        ui.add_space(4.0);
+    }
+
+    fn capture_drops_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        let capture = &ctx.net_storage.capture;
+        let Some(stats) = capture.last() else {
+            return;
+        };
+
+        if capture.is_dropping() {
+            egui::Frame::group(&egui::Style::default())
+                .fill(styles::colors::get(Role::WarningBackground))
+                .corner_radius(5.0)
+                .show(ui, |ui| {
+                    ui.set_width(ui.available_width());
+                    ui.label(
+                        RichText::new(format!(
+                            "⚠ {}",
+                            t!(
+                                "Tab.Status.Capture.Warning.Dropping",
+                                "dropped" = capture.dropped_recently()
+                            )
+                        ))
+                        .color(styles::colors::get(Role::WarningText))
+                        .strong(),
+                    );
+                });
+            ui.add_space(4.0);
+        }
+
+        let layout = egui::Layout::right_to_left(egui::Align::Min).with_main_wrap(true);
+        ui.with_layout(layout, |ui| {
+            if let Some(last_updated) = capture.last_updated() {
+                ui.label(
+                    RichText::new(format::time(&last_updated))
+                        .color(styles::colors::get(Role::Silent)),
+                );
+            }
+            ui.label(format!(
+                "{}: {}",
+                t!("Tab.Status.Capture.Label.Received"),
+                stats.received
+            ));
+            let dropped = format!(
+                "{}: {}",
+                t!("Tab.Status.Capture.Label.DroppedTotal"),
+                capture.dropped_total()
+            );
+            if capture.dropped_total() > 0 {
+                ui.label(
+                    RichText::new(dropped).color(styles::colors::get(Role::Disabled)),
+                );
+            } else {
+                ui.label(dropped);
+            }
+            ui.label(format!("{}:", t!("Tab.Status.Capture.Label.Heading")));
+        });
+    }
+
+    fn sampling_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        let sampling = &ctx.net_storage.sampling;
+        let (Some(sample_rate), Some(summary)) =
+            (sampling.sample_rate(), sampling.last())
+        else {
+            return;
+        };
+        let seconds = summary.interval_ms as f64 / 1000.0;
+        let rate = match seconds > 0.0 {
+            true => summary.bytes as f64 / seconds,
+            false => 0.0,
+        };
+
+        egui::Frame::group(&egui::Style::default())
+            .fill(styles::colors::get(Role::WarningBackground))
+            .corner_radius(5.0)
+            .show(ui, |ui| {
+                ui.set_width(ui.available_width());
+                ui.label(
+                    RichText::new(format!(
+                        "⚠ {}",
+                        t!("Tab.Status.Sampling.Warning", "rate" = sample_rate)
+                    ))
+                    .color(styles::colors::get(Role::WarningText))
+                    .strong(),
+                );
+                ui.label(
+                    RichText::new(t!(
+                        "Tab.Status.Sampling.Label.Summary",
+                        "frames" = format::count(summary.frames),
+                        "bytes" = format::bytes(summary.bytes),
+                        "seconds" = format::decimal(seconds, 1),
+                        "rate" = format::rate(rate)
+                    ))
+                    .color(styles::colors::get(Role::WarningText)),
+                );
+            });
+        ui.add_space(4.0);
+    }
+
     fn plot_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
         use egui_plot::Legend;
         use egui_plot::Line;
         use egui_plot::Plot;
+        use egui_plot::PlotPoint;
         use egui_plot::PlotPoints;
+        use egui_plot::Polygon;
+        use egui_plot::Text;
+        use egui_plot::VLine;
 
         ctx.net_storage.speed.update_info(&ctx.client_settings);
-        let throughput_line = Line::new(
-            t!("Tab.Status.Legend.Throughput"),
-            PlotPoints::from_iter(ctx.net_storage.speed.throughput_iter()),
+        ctx.net_storage
+            .sampling
+            .clear_outdated(&ctx.client_settings);
+
+        // Snapshot is taken when the plot gets frozen, so the live data isn't shown
+        let is_frozen = self.is_plot_hovered || self.is_plot_pinned;
+        if !is_frozen {
+            self.frozen = None;
+        }
+        let snapshot = match &self.frozen {
+            Some(frozen) => frozen.clone(),
+            None => ctx.net_storage.speed.snapshot(),
+        };
+
+        // Lines differ by the dash style too, so they're told apart without the colors
+        let background = ui.visuals().panel_fill;
+        let series = |line: Line<'static>, index: usize| {
+            line.color(styles::protocols::readable(
+                styles::colors::series(index),
+                background,
+            ))
+            .style(styles::colors::series_style(index))
+        };
+        let throughput_line = series(
+            Line::new(
+                t!("Tab.Status.Legend.Throughput"),
+                PlotPoints::from_iter(snapshot.throughput_iter()),
+            ),
+            0,
         );
-        let send_line = Line::new(
-            t!("Tab.Status.Legend.Send"),
-            PlotPoints::from_iter(ctx.net_storage.speed.send_iter()),
+        let send_line = series(
+            Line::new(
+                t!("Tab.Status.Legend.Send"),
+                PlotPoints::from_iter(snapshot.send_iter()),
+            ),
+            1,
         );
-        let receive_line = Line::new(
-            t!("Tab.Status.Legend.Receive"),
-            PlotPoints::from_iter(ctx.net_storage.speed.receive_iter()),
+        let receive_line = series(
+            Line::new(
+                t!("Tab.Status.Legend.Receive"),
+                PlotPoints::from_iter(snapshot.receive_iter()),
+            ),
+            2,
         );
+        // Devices follow the colors of the common lines
+        let device_lines: Vec<Line> = snapshot
+            .devices_iter()
+            .enumerate()
+            .map(|(index, (mac, points))| {
+                series(
+                    Line::new(Self::device_name(ctx, mac), PlotPoints::from_iter(points)),
+                    index + 3,
+                )
+            })
+            .collect();
+
+        // Highlighting the range being dragged or the selected one
+        let now = snapshot.taken;
+        let highlighted = self
+            .selection
+            .or_else(|| ctx.time_range.map(|range| range.to_plot(now)));
 
-        let plot_height = ui.available_height() / 1.8;
-        Plot::new("SpeedFlow")
+        // Short windows still get the readable plot, the rest is scrolled
+        let plot_height = (ui.available_height() / 1.8).max(PLOT_MIN_HEIGHT);
+        let plot = Plot::new("SpeedFlow")
             .legend(Legend::default().follow_insertion_order(false))
             .allow_boxed_zoom(false)
             .allow_double_click_reset(false)
@@ -111,49 +389,293 @@ impl StatusTab {
                 ctx.client_settings.plot.units
             ))
             .height(plot_height)
+            .show_x(false)
+            .show_y(false)
             .show(ui, |plot_ui| {
+                // Periods, when the server sent only a part of the frames
+                for period in ctx.net_storage.sampling.periods() {
+                    let (x_first, x_second) = period.to_plot(now);
+                    let bounds = plot_ui.plot_bounds();
+                    let (y_min, y_max) = (bounds.min()[1], bounds.max()[1]);
+                    let area = vec![
+                        [x_first, y_min],
+                        [x_second, y_min],
+                        [x_second, y_max],
+                        [x_first, y_max],
+                    ];
+                    plot_ui.polygon(
+                        Polygon::new(
+                            t!("Tab.Status.Legend.Sampled"),
+                            PlotPoints::new(area),
+                        )
+                        .fill_color(styles::colors::get(Role::SampledArea))
+                        .stroke(egui::Stroke::new(
+                            0.0,
+                            styles::colors::get(Role::Sampled),
+                        )),
+                    );
+                }
+                if let Some((x_first, x_second)) = highlighted {
+                    let bounds = plot_ui.plot_bounds();
+                    let (y_min, y_max) = (bounds.min()[1], bounds.max()[1]);
+                    let area = vec![
+                        [x_first, y_min],
+                        [x_second, y_min],
+                        [x_second, y_max],
+                        [x_first, y_max],
+                    ];
+                    // Empty name hides the area from the legend
+                    plot_ui.polygon(
+                        Polygon::new("", PlotPoints::new(area))
+                            .fill_color(styles::colors::get(Role::TimeRangeSelection))
+                            .stroke(egui::Stroke::new(
+                                1.0,
+                                styles::colors::get(Role::TimeRange),
+                            )),
+                    );
+                }
                 plot_ui.line(throughput_line);
                 plot_ui.line(send_line);
                 plot_ui.line(receive_line);
+                for line in device_lines {
+                    plot_ui.line(line);
+                }
+
+                // Notable events, described by the readout of the crosshair
+                for (x, marker) in snapshot.markers_iter() {
+                    let color = match marker.kind {
+                        MarkerKind::Alert => styles::colors::get(Role::MarkerAlert),
+                        MarkerKind::Connection => {
+                            styles::colors::get(Role::MarkerConnection)
+                        },
+                        MarkerKind::Interface => {
+                            styles::colors::get(Role::MarkerInterface)
+                        },
+                        MarkerKind::Manual => styles::colors::get(Role::MarkerManual),
+                    };
+                    plot_ui
+                        .vline(VLine::new("", x).stroke(egui::Stroke::new(1.5, color)));
+                }
+
+                // Crosshair with the values of the nearest second
+                let Some(pointer) = plot_ui.pointer_coordinate() else {
+                    return;
+                };
+                let (Some(time), Some(values)) =
+                    (snapshot.time_at(pointer.x), snapshot.values_at(pointer.x))
+                else {
+                    return;
+                };
+                let x = pointer.x.round();
+                plot_ui.vline(VLine::new("", x).stroke(egui::Stroke::new(
+                    1.0,
+                    styles::colors::get(Role::Crosshair),
+                )));
+
+                let bounds = plot_ui.plot_bounds();
+                let anchor = match x < bounds.center().x {
+                    true => egui::Align2::LEFT_TOP,
+                    false => egui::Align2::RIGHT_TOP,
+                };
+                let readout = format!(
+                    "{}\n🔀 {}: {}\n⬆ {}: {}\n⬇ {}: {}",
+                    format::time(&time),
+                    t!("Tab.Status.Legend.Throughput"),
+                    format::decimal(values.throughput, 2),
+                    t!("Tab.Status.Legend.Send"),
+                    format::decimal(values.send, 2),
+                    t!("Tab.Status.Legend.Receive"),
+                    format::decimal(values.receive, 2),
+                );
+                let mut readout = match ctx.net_storage.sampling.is_sampled_at(&time) {
+                    true => format!("{readout}\n⚠ {}", t!("Tab.Status.Legend.Sampled")),
+                    false => readout,
+                };
+                for (mac, value) in snapshot.device_values_at(pointer.x) {
+                    readout.push_str(&format!(
+                        "\n📈 {}: {}",
+                        Self::device_name(ctx, mac),
+                        format::decimal(value, 2)
+                    ));
+                }
+                for marker in snapshot.markers_at(pointer.x) {
+                    readout.push_str(&format!(
+                        "\n{} {} {}",
+                        marker.kind.icon(),
+                        format::time(&marker.time),
+                        marker.label
+                    ));
+                }
+                plot_ui.text(
+                    Text::new("", PlotPoint::new(x, bounds.max()[1]), readout)
+                        .anchor(anchor),
+                );
             });
+        self.is_plot_hovered = plot.response.hovered();
+        if is_frozen && self.frozen.is_none() {
+            self.frozen = Some(snapshot);
+        }
+
+        // Dragging selects the time range for Inspector, click clears it
+        let response = &plot.response;
+        let pointer_x = response
+            .interact_pointer_pos()
+            .map(|position| plot.transform.value_from_position(position).x);
+        if response.drag_started() {
+            self.selection = pointer_x.map(|x| (x, x));
+        } else if response.dragged() {
+            if let (Some((start, _)), Some(x)) = (self.selection, pointer_x) {
+                self.selection = Some((start, x));
+            }
+        }
+        if response.drag_stopped() {
+            if let Some((start, end)) = self.selection.take() {
+                ctx.time_range = Some(TimeRange::from_plot(start, end, now));
+            }
+        } else if response.clicked() {
+            self.selection = None;
+            ctx.time_range = None;
+        }
     }
 
     fn current_peak_stats_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
-        ui.with_layout(egui::Layout::right_to_left(egui::Align::Min), |ui| {
+        let layout = egui::Layout::right_to_left(egui::Align::Min).with_main_wrap(true);
+        ui.with_layout(layout, |ui| {
+            ui.toggle_value(&mut self.is_plot_pinned, "📌")
+                .on_hover_text(t!("Tab.Status.Plot.Hover.Pin"));
+            if ctx.net_storage.speed.markers_amount() > 0
+                && ui
+                    .button("🗑")
+                    .on_hover_text(t!("Tab.Status.Plot.Hover.ClearMarkers"))
+                    .clicked()
+            {
+                ctx.net_storage.speed.clear_markers();
+            }
+            if ui
+                .button(MarkerKind::Manual.icon())
+                .on_hover_text(t!("Tab.Status.Plot.Hover.MarkNow"))
+                .clicked()
+            {
+                ctx.net_storage.speed.push_marker(
+                    Local::now(),
+                    MarkerKind::Manual,
+                    t!("Tab.Status.Plot.Marker.Manual"),
+                );
+            }
+            if self.frozen.is_some() {
+                ui.label(
+                    RichText::new(t!("Tab.Status.Plot.Paused"))
+                        .color(styles::colors::get(Role::Silent)),
+                );
+            }
+            if let Some(sample_rate) = ctx.net_storage.sampling.sample_rate() {
+                ui.label(
+                    RichText::new(format!(
+                        "⚠ {}",
+                        t!("Tab.Status.Plot.Sampled", "rate" = sample_rate)
+                    ))
+                    .color(styles::colors::get(Role::Sampled)),
+                )
+                .on_hover_text(t!("Tab.Status.Plot.Hover.Sampled"));
+            }
+            let speed = &ctx.net_storage.speed;
+            // Unplotted device falls back to the whole traffic
+            let device_peaks = self
+                .peak_device
+                .as_ref()
+                .and_then(|mac| speed.device_peaks(mac));
+            if device_peaks.is_none() {
+                self.peak_device = None;
+            }
+            let (sent, received, throughput) = match device_peaks {
+                Some(peaks) => (peaks.send, peaks.receive, peaks.throughput),
+                None => (
+                    speed.peak_sent(),
+                    speed.peak_received(),
+                    speed.peak_throughput(),
+                ),
+            };
             ui.label(format!(
-                "⬆ {}: {:.2}",
+                "⬆ {}: {}",
                 t!("Tab.Status.NetworkData.Label.Sent"),
-                ctx.net_storage.speed.peak_sent()
+                format::decimal(sent, 2)
             ));
             ui.label(format!(
-                "⬇ {}: {:.2}",
+                "⬇ {}: {}",
                 t!("Tab.Status.NetworkData.Label.Received"),
-                ctx.net_storage.speed.peak_received()
+                format::decimal(received, 2)
             ));
             ui.label(format!(
-                "🔀 {}: {:.2}",
+                "🔀 {}: {}",
                 t!("Tab.Status.NetworkData.Label.Throughput"),
-                ctx.net_storage.speed.peak_throughput()
+                format::decimal(throughput, 2)
             ));
             ui.label(format!(
                 "{} ({}):",
                 t!("Tab.Status.NetworkData.Label.Peak"),
                 ctx.client_settings.plot.units
             ));
+            self.peak_device_view(ui, ctx);
         });
     }
 
+    fn peak_device_view(&mut self, ui: &mut egui::Ui, ctx: &Context) {
+        let plotted: Vec<MacAddress> =
+            ctx.net_storage.speed.plotted_devices().cloned().collect();
+        if plotted.is_empty() {
+            return;
+        }
+        let all = t!("Tab.Status.Plot.Peak.All").to_string();
+        let selected = match &self.peak_device {
+            Some(mac) => Self::device_name(ctx, mac),
+            None => all.clone(),
+        };
+        egui::ComboBox::from_id_salt("Status.PeakDevice")
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut self.peak_device, None, all);
+                for mac in plotted {
+                    let name = Self::device_name(ctx, &mac);
+                    ui.selectable_value(&mut self.peak_device, Some(mac), name);
+                }
+            })
+            .response
+            .on_hover_text(t!("Tab.Status.Plot.Hover.PeakDevice"));
+    }
+
+    // Alias is shown in the legend, if the device has it
+    fn device_name(ctx: &Context, mac: &MacAddress) -> String {
+        ctx.net_storage
+            .devices
+            .aliases
+            .get(mac)
+            .cloned()
+            .unwrap_or_else(|| mac.to_string())
+    }
+
     fn pcap_save_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
         if !ctx.net_storage.raw.is_empty() {
             Grid::new("UnparsedFramesControls")
-                .num_columns(3)
+                .num_columns(4)
                 .striped(false)
                 .show(ui, |ui| {
-                    ui.label(format!(
-                        "Unparsed Frames: {}",
-                        ctx.net_storage.raw.amount()
-                    ));
-                    if ui.button("Save .pcap").clicked() {
+                    let raw = &ctx.net_storage.raw;
+                    if raw.spilled_amount() == 0 {
+                        ui.label(format!("Unparsed Frames: {}", raw.amount()));
+                    } else {
+                        ui.label(t!(
+                            "Tab.Status.Unparsed.Spilled",
+                            "memory" = raw.amount(),
+                            "disk" = raw.spilled_amount(),
+                            "size" = format::bytes(raw.spilled_size() as u64)
+                        ));
+                    }
+                    // Frames received during the saving wait for the next one
+                    let is_saving = self.pcap_export.is_some();
+                    if ui
+                        .add_enabled(!is_saving, egui::Button::new("Save .pcap"))
+                        .clicked()
+                    {
                         let link_type = match ctx.settings_server.link_type {
                             Some(value) => value,
                             None => {
@@ -162,6 +684,15 @@ impl StatusTab {
                                 return;
                             },
                         };
+                        if self.anonymize_pcap
+                            && !Anonymizer::is_supported(LinkType::from(link_type))
+                        {
+                            MessageModal::error(&t!(
+                                "Error.Anonymize.UnsupportedLinkType"
+                            ))
+                            .try_send_by(&ctx.modals_tx);
+                            return;
+                        }
 
                         if let Some(path) = rfd::FileDialog::new()
                             .add_filter(
@@ -170,82 +701,944 @@ impl StatusTab {
                             )
                             .save_file()
                         {
-                            if let Err(err) =
-                                ctx.net_storage.raw.save_pcap(path, link_type)
-                            {
-                                MessageModal::error(&format!(
-                                    "{}: {}",
-                                    &t!("Error.Pcap"),
-                                    err
-                                ))
-                                .try_send_by(&ctx.modals_tx);
-                            }
+                            self.pcap_export_start(ctx, path, link_type);
                         }
                     }
                     if ui.button("Reset").clicked() {
                         ctx.net_storage.raw.clear();
                     }
+                    ui.checkbox(&mut self.anonymize_pcap, t!("Text.Anonymize"))
+                        .on_hover_text(t!("Text.Anonymize.Hover"))
+                        .on_hover_text(t!("Text.Anonymize.Hover.Pcap"));
                 });
         }
     }
 
-    fn devices_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
-        ui.columns(2, |columns| {
-            columns[0].horizontal(|ui| {
-                ui.heading(format!("{}:", t!("Tab.Status.Devices.Heading")));
-                if ctx.net_storage.devices.list.is_empty() {
-                    ui.label(t!("Tab.Status.Devices.Empty"));
+    fn pcap_export_start(
+        &mut self, ctx: &mut Context, path: PathBuf, link_type: pcap::Linktype,
+    ) {
+        let export = ctx.net_storage.raw.take_export(link_type);
+        let total = export.amount();
+        let anonymizer = self
+            .anonymize_pcap
+            .then(|| Anonymizer::new(ctx.client_settings.anonymization));
+        let result = BackgroundTask::spawn(
+            t!("Tab.Status.Unparsed.Saving").to_string(),
+            &ctx.modals_tx,
+            move |reporter| {
+                let mut last = Progress::Indeterminate;
+                export.save_pcap(&path, anonymizer, |written| {
+                    let progress = Progress::of(written, total);
+                    if progress != last {
+                        reporter.report(progress);
+                        last = progress;
+                    }
+                    match reporter.is_cancelled() {
+                        true => ControlFlow::Break(()),
+                        false => ControlFlow::Continue(()),
+                    }
+                })
+            },
+        );
+
+        match result {
+            Ok(task) => self.pcap_export = Some(task),
+            Err(err) => MessageModal::error(&err.localized()).try_send_by(&ctx.modals_tx),
+        }
+    }
+
+    pub fn poll_tasks(&mut self, ctx: &mut Context) {
+        let Some(result) = self
+            .pcap_export
+            .as_ref()
+            .and_then(BackgroundTask::try_result)
+        else {
+            return;
+        };
+        self.pcap_export = None;
+
+        match result {
+            Ok(ExportOutcome::Saved) => {},
+            Ok(ExportOutcome::Cancelled(export)) => {
+                ctx.net_storage
+                    .raw
+                    .restore(export, ctx.settings_server.link_type);
+            },
+            Ok(ExportOutcome::Failed(export, err)) => {
+                ctx.net_storage
+                    .raw
+                    .restore(export, ctx.settings_server.link_type);
+                MessageModal::error(&format!("{}: {}", &t!("Error.Pcap"), err))
+                    .try_send_by(&ctx.modals_tx);
+            },
+            Err(err) => MessageModal::error(&err.localized()).try_send_by(&ctx.modals_tx),
+        }
+    }
+
+    /// Stops the workers. Their results belong to the old session.
+    pub fn cancel_tasks(&mut self) {
+        self.pcap_export = None;
+    }
+
+    fn storage_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        let total = ctx
+            .net_storage
+            .inspector
+            .estimated_size()
+            .saturating_add(ctx.net_storage.raw.estimated_size());
+        let is_over_limit = ctx
+            .client_settings
+            .storage_soft_limit_mb
+            .is_some_and(|limit| total > limit.saturating_mul(memory::BYTES_IN_MEGABYTE));
+
+        let mut heading = RichText::new(format!(
+            "{}: {}",
+            t!("Tab.Status.Storage.Heading"),
+            format::bytes(total as u64)
+        ));
+        if is_over_limit {
+            heading = heading.color(styles::colors::get(Role::OverLimit));
+        }
+
+        let mut cleared = vec![];
+        egui::CollapsingHeader::new(heading)
+            .id_salt("Status.Storage")
+            .show(ui, |ui| {
+                if is_over_limit {
+                    ui.label(
+                        RichText::new(format!(
+                            "⚠ {}",
+                            t!("Tab.Status.Storage.Warning.OverLimit")
+                        ))
+                        .color(styles::colors::get(Role::OverLimit)),
+                    );
                 }
+
+                Grid::new("Status.Storage.Grid")
+                    .num_columns(4)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label(styles::heading::grid(&t!(
+                            "Tab.Status.Storage.Column.Category"
+                        )));
+                        ui.label(styles::heading::grid(&t!(
+                            "Tab.Status.Storage.Column.Records"
+                        )));
+                        ui.label(styles::heading::grid(&t!(
+                            "Tab.Status.Storage.Column.Size"
+                        )));
+                        ui.end_row();
+
+                        for protocol in ProtocolsRegistered::iter() {
+                            let inspector = &mut ctx.net_storage.inspector;
+                            ui.label(styles::protocols::label(
+                                ui,
+                                &protocol.id(),
+                                &protocol.to_string(),
+                                &ctx.client_settings.protocol_colors,
+                            ));
+                            ui.label(inspector.records_captured(&protocol).to_string());
+                            ui.label(format::bytes(
+                                inspector.estimated_size_by_protocol(&protocol) as u64,
+                            ));
+                            if ui.button(t!("Button.Clear")).clicked() {
+                                cleared.push((protocol, inspector.generation(&protocol)));
+                                inspector.clear_by_protocol(&protocol);
+                            }
+                            ui.end_row();
+                        }
+
+                        ui.label(t!("Tab.Status.Storage.Category.Unparsed"));
+                        ui.label(ctx.net_storage.raw.amount().to_string());
+                        ui.label(format::bytes(
+                            ctx.net_storage.raw.estimated_size() as u64
+                        ));
+                        if ui.button(t!("Button.Clear")).clicked() {
+                            ctx.net_storage.raw.clear();
+                        }
+                        ui.end_row();
+
+                        // Files on the disk don't count towards the limit
+                        if ctx.net_storage.raw.spilled_amount() > 0 {
+                            ui.label(t!("Tab.Status.Storage.Category.UnparsedDisk"));
+                            ui.label(ctx.net_storage.raw.spilled_amount().to_string());
+                            ui.label(format::bytes(
+                                ctx.net_storage.raw.spilled_size() as u64
+                            ));
+                            ui.label("-");
+                            ui.end_row();
+                        }
+
+                        ui.label(t!("Tab.Status.Storage.Category.Devices"));
+                        ui.label(ctx.net_storage.devices.list().len().to_string());
+                        ui.label("-");
+                        if ui.button(t!("Button.Clear")).clicked() {
+                            ctx.net_storage.devices.clear();
+                        }
+                        ui.end_row();
+
+                        ui.label(t!("Tab.Status.Storage.Category.Notes"));
+                        ui.label(ctx.net_storage.notes.len().to_string());
+                        ui.label(format::bytes(
+                            ctx.net_storage.notes.estimated_size() as u64
+                        ));
+                        if ui.button(t!("Button.Clear")).clicked() {
+                            ctx.net_storage.notes.clear();
+                        }
+                        ui.end_row();
+                    });
             });
 
-            columns[1].with_layout(egui::Layout::right_to_left(egui::Align::Min), |ui| {
-                if ui
-                    .button(t!("Tab.Status.Devices.Button.SaveAliases"))
-                    .clicked()
-                {
-                    let modal = if let Err(err) =
-                        ctx.net_storage.devices.save_aliases_to_file()
-                    {
-                        let mut text = format!(
-                            "{}\n{}: {}.",
-                            t!("Tab.Status.Devices.Modal.ErrorSave"),
-                            t!("Error.AdditionalInfo"),
-                            err
-                        );
-                        if let Some(additional_info) = err.additional_info() {
-                            text.push_str(&format!("\n{additional_info}"));
+        OrphanedNotesModal::ask(cleared, ctx);
+    }
+
+    fn conflicts_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        let conflicts = &ctx.net_storage.conflicts;
+        let alerts: Vec<_> = conflicts
+            .alerts(ctx.client_settings.ip_conflict_failover_alerts)
+            .collect();
+        if alerts.is_empty() {
+            return;
+        }
+
+        let heading = RichText::new(format!(
+            "⚠ {}: {}",
+            t!("Tab.Status.Conflicts.Heading"),
+            alerts.len()
+        ))
+        .color(styles::colors::get(Role::IpConflict));
+
+        let mut is_cleared = false;
+        egui::CollapsingHeader::new(heading)
+            .id_salt("Status.Conflicts")
+            .default_open(true)
+            .show(ui, |ui| {
+                Grid::new("Status.Conflicts.Grid")
+                    .num_columns(5)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.label(styles::heading::grid(&t!(
+                            "Tab.Status.Conflicts.Column.IP"
+                        )));
+                        ui.label(styles::heading::grid(&t!(
+                            "Tab.Status.Conflicts.Column.MAC"
+                        )));
+                        ui.label(styles::heading::grid(&t!(
+                            "Tab.Status.Conflicts.Column.Vendor"
+                        )));
+                        ui.label(styles::heading::grid(&t!(
+                            "Tab.Status.Conflicts.Column.FirstSeen"
+                        )));
+                        ui.label(styles::heading::grid(&t!(
+                            "Tab.Status.Conflicts.Column.LastSeen"
+                        )));
+                        ui.end_row();
+
+                        for conflict in alerts {
+                            for (index, mac) in
+                                [&conflict.previous, &conflict.current].iter().enumerate()
+                            {
+                                ui.label(if index == 0 {
+                                    conflict.ip.to_string()
+                                } else {
+                                    String::new()
+                                });
+                                ui.label(
+                                    match ctx.net_storage.devices.aliases.get(mac) {
+                                        Some(alias) => format!("{mac} ({alias})"),
+                                        None => mac.to_string(),
+                                    },
+                                );
+                                let sighting = conflicts.sighting(&conflict.ip, mac);
+                                ui.label(
+                                    sighting
+                                        .and_then(|sighting| sighting.vendor.as_ref())
+                                        .map(|vendor| vendor.full.clone())
+                                        .unwrap_or(
+                                            t!(
+                                                "Tab.Status.Devices.Device.Vendor.Unknown"
+                                            )
+                                            .to_string(),
+                                        ),
+                                );
+                                match sighting {
+                                    Some(sighting) => {
+                                        ui.label(format::time(&sighting.first_seen));
+                                        ui.label(format::time(&sighting.last_seen));
+                                    },
+                                    None => {
+                                        ui.label("-");
+                                        ui.label("-");
+                                    },
+                                }
+                                ui.end_row();
+                            }
                         }
-                        MessageModal::error(&text)
-                    } else {
-                        MessageModal::info(&t!("Tab.Status.Devices.Modal.Success"))
-                    };
-                    let _ = ctx.modals_tx.try_send(Box::new(modal));
+                    });
+
+                if ui.button(t!("Button.Clear")).clicked() {
+                    is_cleared = true;
                 }
             });
+
+        if is_cleared {
+            ctx.net_storage.conflicts.clear();
+        }
+    }
+
+    fn routers_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        let routers = &ctx.net_storage.routers;
+        let sources = routers.sources();
+        if sources.is_empty() {
+            return;
+        }
+
+        let heading =
+            format!("📡 {}: {}", t!("Tab.Status.Routers.Heading"), sources.len());
+
+        // Trust is toggled after the grid, since the storage is borrowed by it
+        let mut toggled = None;
+        egui::CollapsingHeader::new(heading)
+            .id_salt("Status.Routers")
+            .show(ui, |ui| {
+                Grid::new("Status.Routers.Grid")
+                    .num_columns(6)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for column in [
+                            "Tab.Status.Routers.Column.MAC",
+                            "Tab.Status.Routers.Column.IP",
+                            "Tab.Status.Routers.Column.Prefixes",
+                            "Tab.Status.Routers.Column.Lifetime",
+                            "Tab.Status.Routers.Column.LastSeen",
+                            "Tab.Status.Routers.Column.Trusted",
+                        ] {
+                            ui.label(styles::heading::grid(&t!(column)));
+                        }
+                        ui.end_row();
+
+                        for source in sources {
+                            ui.label(source.mac.to_string());
+                            ui.label(source.ip.to_string());
+                            ui.label(fields::list(&source.prefixes));
+                            ui.label(format!(
+                                "{} {}",
+                                source.router_lifetime,
+                                t!("Tab.SettingsClient.Suffix.Seconds")
+                            ))
+                            .on_hover_text(t!("Tab.Status.Routers.Hover.Lifetime"));
+                            ui.label(format::time(&source.last_seen));
+                            let mut is_trusted = routers.is_trusted(&source.mac);
+                            if ui
+                                .checkbox(&mut is_trusted, "")
+                                .on_hover_text(t!("Tab.Status.Routers.Hover.Trusted"))
+                                .changed()
+                            {
+                                toggled = Some((source.mac.clone(), is_trusted));
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+
+        if let Some((mac, is_trusted)) = toggled {
+            log::info!("Routers: {mac} trusted: {is_trusted}");
+            ctx.net_storage.routers.set_trusted(&mac, is_trusted);
+            ctx.config.trusted_routers = ctx.net_storage.routers.trusted_list();
+            if let Err(err) = ctx.config.save_to_file(&ctx.data_dir) {
+                log::error!("Routers: Failed to save the trusted routers: {err}");
+                MessageModal::error(&format!(
+                    "{} {err}",
+                    t!("Error.FailedSaveClientConfigIntoFile"),
+                ))
+                .try_send_by(&ctx.modals_tx);
+            }
+        }
+    }
+
+    fn timeline_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        let timeline = &ctx.net_storage.timeline;
+        if timeline.is_empty() {
+            return;
+        }
+
+        let heading =
+            format!("{}: {}", t!("Tab.Status.Timeline.Heading"), timeline.len());
+
+        let mut is_cleared = false;
+        let mut is_save_requested = false;
+        let mut request = None;
+        egui::CollapsingHeader::new(heading)
+            .id_salt("Status.Timeline")
+            .show(ui, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label(format!("{}:", t!("Tab.Status.Timeline.Label.Severity")));
+                    egui::ComboBox::from_id_salt("Status.Timeline.Severity.ComboBox")
+                        .selected_text(self.timeline_severity.localize())
+                        .show_ui(ui, |ui| {
+                            for severity in Severity::iter() {
+                                ui.selectable_value(
+                                    &mut self.timeline_severity,
+                                    severity,
+                                    severity.localize(),
+                                );
+                            }
+                        });
+                    if timeline.evicted() > 0 {
+                        ui.separator();
+                        ui.label(format!(
+                            "{}: {}",
+                            t!("Tab.Status.Timeline.Label.Evicted"),
+                            timeline.evicted()
+                        ))
+                        .on_hover_text(t!(
+                            "Tab.Status.Timeline.Hover.Evicted",
+                            "capacity" = timeline::CAPACITY
+                        ));
+                    }
+                });
+
+                Grid::new("Status.Timeline.Grid")
+                    .num_columns(4)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for entry in timeline.filtered(self.timeline_severity) {
+                            ui.label(format::time(&entry.time));
+                            let severity = entry.event.severity();
+                            let icon = RichText::new(entry.event.icon());
+                            let icon = match Self::severity_color(&severity) {
+                                Some(color) => icon.color(color),
+                                None => icon,
+                            };
+                            ui.label(icon).on_hover_text(severity.localize());
+                            ui.label(entry.event.localize());
+                            match entry.event.target() {
+                                Some(target) => {
+                                    if ui
+                                        .button("🔍")
+                                        .on_hover_text(t!(
+                                            "Tab.Status.Timeline.Hover.Jump"
+                                        ))
+                                        .clicked()
+                                    {
+                                        request = Some((entry.time, target));
+                                    }
+                                },
+                                None => {
+                                    ui.label("");
+                                },
+                            }
+                            ui.end_row();
+                        }
+                    });
+
+                ui.horizontal(|ui| {
+                    if ui.button(t!("Button.Save")).clicked() {
+                        is_save_requested = true;
+                    }
+                    if ui.button(t!("Button.Clear")).clicked() {
+                        is_cleared = true;
+                    }
+                    ui.checkbox(&mut self.anonymize_timeline, t!("Text.Anonymize"))
+                        .on_hover_text(t!("Text.Anonymize.Hover"));
+                });
+            });
+
+        if let Some((time, target)) = request {
+            Self::open_timeline_target(ctx, time, target);
+        }
+        if is_save_requested {
+            Self::save_timeline(ctx, self.anonymize_timeline);
+        }
+        if is_cleared {
+            ctx.net_storage.timeline.clear();
+        }
+    }
+
+    fn severity_color(severity: &Severity) -> Option<egui::Color32> {
+        match severity {
+            Severity::Info => None,
+            Severity::Warning => Some(styles::colors::get(Role::SeverityWarning)),
+            Severity::Critical => Some(styles::colors::get(Role::SeverityCritical)),
+        }
+    }
+
+    fn open_timeline_target(
+        ctx: &mut Context, time: DateTime<Local>, target: TimelineTarget,
+    ) {
+        ctx.inspector_request = Some(match target {
+            // Every record of the device is shown
+            TimelineTarget::Device(mac) => {
+                ctx.time_range = None;
+                InspectorRequest {
+                    protocol: ProtocolId::Ethernet,
+                    mac: Some(mac),
+                }
+            },
+            TimelineTarget::Records(protocol) => {
+                let margin = TimeDelta::seconds(TIMELINE_RANGE_MARGIN_SECONDS);
+                ctx.time_range = Some(TimeRange {
+                    start: time - margin,
+                    end: time + margin,
+                });
+                InspectorRequest {
+                    protocol,
+                    mac: None,
+                }
+            },
         });
+    }
 
-        if ctx.net_storage.devices.list.is_empty() {
+    fn save_timeline(ctx: &Context, anonymize: bool) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter(timeline::FILTER_NAME, timeline::FILTER_EXTENSIONS)
+            .set_file_name("timeline.json")
+            .save_file()
+        else {
             return;
+        };
+
+        let anonymizer =
+            anonymize.then(|| Anonymizer::new(ctx.client_settings.anonymization));
+        let modal = match ctx.net_storage.timeline.save_to_file(&path, anonymizer) {
+            Ok(_) => {
+                log::info!("Status: Timeline is saved to {}", path.display());
+                MessageModal::info(&t!("Tab.Status.Timeline.Message.Saved"))
+            },
+            Err(err) => {
+                log::error!("Status: Failed to save timeline {}: {err}", path.display());
+                let mut text = format!(
+                    "{}\n{}: {}.",
+                    t!("Tab.Status.Timeline.Error.Save"),
+                    t!("Error.AdditionalInfo"),
+                    err
+                );
+                if let Some(additional_info) = err.additional_info() {
+                    text.push_str(&format!("\n{additional_info}"));
+                }
+                MessageModal::error(&text)
+            },
+        };
+        modal.try_send_by(&ctx.modals_tx);
+    }
+
+    fn endpoints_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        let storage = &ctx.net_storage.endpoints;
+        if storage.is_empty() {
+            return;
+        }
+
+        let heading =
+            format!("{}: {}", t!("Tab.Status.Endpoints.Heading"), storage.len());
+
+        let mut is_cleared = false;
+        egui::CollapsingHeader::new(heading)
+            .id_salt("Status.Endpoints")
+            .show(ui, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    ui.checkbox(
+                        &mut self.is_all_hosts_shown,
+                        t!("Tab.Status.Endpoints.Checkbox.ShowAll"),
+                    );
+                    if storage.evictions() > 0 {
+                        ui.separator();
+                        ui.label(format!(
+                            "{}: {}",
+                            t!("Tab.Status.Endpoints.Label.Evicted"),
+                            storage.evictions()
+                        ))
+                        .on_hover_text(t!("Tab.Status.Endpoints.Hover.Evicted"));
+                    }
+                });
+
+                let amount = match self.is_all_hosts_shown {
+                    true => None,
+                    false => Some(endpoints::TOP_AMOUNT),
+                };
+                Grid::new("Status.Endpoints.Grid")
+                    .num_columns(6)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for column in [
+                            "Tab.Status.Endpoints.Column.Host",
+                            "Tab.Status.Endpoints.Column.Name",
+                            "Tab.Status.Endpoints.Column.Sent",
+                            "Tab.Status.Endpoints.Column.Received",
+                            "Tab.Status.Endpoints.Column.Packets",
+                            "Tab.Status.Endpoints.Column.LastSeen",
+                        ] {
+                            ui.label(styles::heading::grid(&t!(column)));
+                        }
+                        ui.end_row();
+
+                        for host in storage.top(amount) {
+                            ui.label(host.ip.to_string());
+                            match host.name.as_deref().map(format::domain) {
+                                Some((name, Some(hover))) => {
+                                    ui.label(name).on_hover_text(hover);
+                                },
+                                Some((name, None)) => {
+                                    ui.label(name);
+                                },
+                                None => {
+                                    ui.label("-");
+                                },
+                            }
+                            ui.label(format::bytes(host.sent.bytes as u64));
+                            ui.label(format::bytes(host.received.bytes as u64));
+                            ui.label(format!(
+                                "{} / {}",
+                                host.sent.packets, host.received.packets
+                            ));
+                            ui.label(format::time(&host.last_seen));
+                            ui.end_row();
+                        }
+                    });
+
+                if ui.button(t!("Button.Clear")).clicked() {
+                    is_cleared = true;
+                }
+            });
+
+        if is_cleared {
+            ctx.net_storage.endpoints.clear();
+        }
+    }
+
+    fn devices_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        let is_narrow = layout::is_narrow(ui);
+        let id = ui.make_persistent_id("Status.Devices");
+        egui::collapsing_header::CollapsingState::load_with_default_open(
+            ui.ctx(),
+            id,
+            true,
+        )
+        .show_header(ui, |ui| {
+            Self::devices_heading_view(ui, ctx);
+            if !is_narrow {
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    Self::save_aliases_button(ui, ctx);
+                    self.inventory_button(ui, ctx);
+                    self.selection_toggle(ui);
+                });
+            }
+        })
+        .body(|ui| {
+            // Buttons go under the heading, if the panel is narrow
+            if is_narrow {
+                ui.horizontal_wrapped(|ui| {
+                    Self::save_aliases_button(ui, ctx);
+                    self.inventory_button(ui, ctx);
+                    self.selection_toggle(ui);
+                });
+            }
+            if self.is_selecting {
+                self.devices_selection_view(ui, ctx);
+            }
+
+            let devices = &ctx.net_storage.devices;
+            let hidden = devices
+                .list()
+                .iter()
+                .filter(|device| devices.is_hidden(&device.mac))
+                .count();
+            if hidden > 0 {
+                ui.checkbox(
+                    &mut self.is_hidden_shown,
+                    t!("Tab.Status.Devices.Checkbox.ShowHidden", "amount" = hidden),
+                );
+            }
+            if devices.evicted > 0 {
+                ui.label(
+                    RichText::new(t!(
+                        "Tab.Status.Devices.Label.Evicted",
+                        "amount" = devices.evicted
+                    ))
+                    .weak(),
+                )
+                .on_hover_text(t!(
+                    "Tab.Status.Devices.Label.Evicted.Hover",
+                    "capacity" = device::CAPACITY
+                ));
+            }
+
+            let mut plot_toggled = None;
+            ui.vertical_centered_justified(|ui| {
+                // Numbers are kept, so the generic names don't change with the hiding
+                for (index, device) in devices.list().iter().enumerate() {
+                    if (self.is_hidden_shown || !devices.is_hidden(&device.mac))
+                        && self.device_view(ui, ctx, device, index + 1)
+                    {
+                        plot_toggled = Some(device.mac.clone());
+                    }
+                }
+            });
+            if let Some(mac) = plot_toggled {
+                let speed = &mut ctx.net_storage.speed;
+                match speed.is_device_plotted(&mac) {
+                    true => speed.unplot_device(&mac),
+                    false => {
+                        speed.plot_device(&mac);
+                    },
+                }
+            }
+        });
+    }
+
+    fn selection_toggle(&mut self, ui: &mut egui::Ui) {
+        if ui
+            .toggle_value(&mut self.is_selecting, "☑")
+            .on_hover_text(t!("Tab.Status.Devices.Hover.Select"))
+            .changed()
+        {
+            self.selected_devices.clear();
         }
+    }
+
+    fn devices_selection_view(&mut self, ui: &mut egui::Ui, ctx: &mut Context) {
+        let devices = &ctx.net_storage.devices;
+        // Devices may be gone after the clear of the list or the eviction
+        self.selected_devices.retain(|mac| devices.contains(mac));
+        let is_empty = self.selected_devices.is_empty();
+
+        ui.horizontal_wrapped(|ui| {
+            ui.label(t!(
+                "Tab.Status.Devices.Selection.Label.Selected",
+                "amount" = self.selected_devices.len()
+            ));
+            if ui
+                .button(t!("Tab.Status.Devices.Selection.Button.All"))
+                .clicked()
+            {
+                self.selected_devices = devices
+                    .list()
+                    .iter()
+                    .filter(|device| {
+                        self.is_hidden_shown || !devices.is_hidden(&device.mac)
+                    })
+                    .map(|device| device.mac.clone())
+                    .collect();
+            }
+            if ui
+                .add_enabled(
+                    !is_empty,
+                    egui::Button::new(t!("Tab.Status.Devices.Selection.Button.None")),
+                )
+                .clicked()
+            {
+                self.selected_devices.clear();
+            }
+        });
 
-        ui.vertical_centered_justified(|ui| {
-            for (index, device) in ctx.net_storage.devices.list.iter().enumerate() {
-                self.device_view(ui, ctx, device, index + 1);
+        ui.horizontal_wrapped(|ui| {
+            ui.add(
+                TextEdit::singleline(&mut self.alias_prefix)
+                    .hint_text(t!("Tab.Status.Devices.Selection.Hint.Prefix")),
+            );
+            let prefix = self.alias_prefix.trim();
+            if ui
+                .add_enabled(
+                    !is_empty && !prefix.is_empty(),
+                    egui::Button::new(t!("Tab.Status.Devices.Selection.Button.Prefix")),
+                )
+                .clicked()
+            {
+                ctx.net_storage
+                    .devices
+                    .prefix_aliases(&self.selected_devices, prefix);
+            }
+
+            ui.separator();
+            if ui
+                .add_enabled(
+                    !is_empty,
+                    egui::Button::new(t!("Tab.Status.Devices.Selection.Button.Export")),
+                )
+                .clicked()
+            {
+                Self::export_devices(ctx, &self.selected_devices, self.anonymize_devices);
+            }
+            ui.checkbox(&mut self.anonymize_devices, t!("Text.Anonymize"))
+                .on_hover_text(t!("Text.Anonymize.Hover"))
+                .on_hover_text(t!("Text.Anonymize.Hover.UserFields"));
+            if ui
+                .add_enabled(
+                    !is_empty,
+                    egui::Button::new(t!("Tab.Status.Devices.Selection.Button.Hide")),
+                )
+                .on_hover_text(t!("Tab.Status.Devices.Selection.Hover.Hide"))
+                .clicked()
+            {
+                ctx.net_storage
+                    .devices
+                    .set_hidden(&self.selected_devices, true);
+            }
+            if ui
+                .add_enabled(
+                    !is_empty,
+                    egui::Button::new(t!("Tab.Status.Devices.Selection.Button.Unhide")),
+                )
+                .clicked()
+            {
+                ctx.net_storage
+                    .devices
+                    .set_hidden(&self.selected_devices, false);
             }
         });
     }
 
+    fn export_devices(ctx: &Context, selected: &HashSet<MacAddress>, anonymize: bool) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter(device::CSV_FILTER_NAME, device::CSV_FILTER_EXTENSIONS)
+            .set_file_name("devices.csv")
+            .save_file()
+        else {
+            return;
+        };
+
+        let anonymizer =
+            anonymize.then(|| Anonymizer::new(ctx.client_settings.anonymization));
+        let modal = match ctx
+            .net_storage
+            .devices
+            .export_csv(selected, &path, anonymizer)
+        {
+            Ok(_) => {
+                log::info!("Status: Devices are exported to {}", path.display());
+                MessageModal::info(&t!("Tab.Status.Devices.Export.Message.Saved"))
+            },
+            Err(err) => {
+                log::error!("Status: Failed to export devices {}: {err}", path.display());
+                let mut text = format!(
+                    "{}\n{}: {}.",
+                    t!("Tab.Status.Devices.Export.Error.Save"),
+                    t!("Error.AdditionalInfo"),
+                    err
+                );
+                if let Some(additional_info) = err.additional_info() {
+                    text.push_str(&format!("\n{additional_info}"));
+                }
+                MessageModal::error(&text)
+            },
+        };
+        modal.try_send_by(&ctx.modals_tx);
+    }
+
+    fn inventory_button(&mut self, ui: &mut egui::Ui, ctx: &Context) {
+        ui.menu_button(t!("Tab.Status.Devices.Button.ExportInventory"), |ui| {
+            ui.checkbox(&mut self.anonymize_inventory, t!("Text.Anonymize"))
+                .on_hover_text(t!("Text.Anonymize.Hover"))
+                .on_hover_text(t!("Text.Anonymize.Hover.UserFields"));
+            ui.separator();
+            for format in InventoryFormat::iter() {
+                if ui.button(format.to_string()).clicked() {
+                    ui.close_menu();
+                    Self::export_inventory(ctx, format, self.anonymize_inventory);
+                }
+            }
+        })
+        .response
+        .on_hover_text(t!("Tab.Status.Devices.Hover.ExportInventory"));
+    }
+
+    fn export_inventory(ctx: &Context, format: InventoryFormat, anonymize: bool) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter(format.filter_name(), format.filter_extensions())
+            .set_file_name(format.file_name())
+            .save_file()
+        else {
+            return;
+        };
+
+        let inventory = Inventory::new(
+            &ctx.net_storage.devices,
+            ctx.settings_server.interface_active.clone(),
+            anonymize.then(|| Anonymizer::new(ctx.client_settings.anonymization)),
+        );
+        let modal = match inventory.save(format, &path) {
+            Ok(_) => {
+                log::info!("Status: Inventory is exported to {}", path.display());
+                MessageModal::info(&t!("Tab.Status.Devices.Inventory.Message.Saved"))
+            },
+            Err(err) => {
+                log::error!(
+                    "Status: Failed to export inventory {}: {err}",
+                    path.display()
+                );
+                let mut text = format!(
+                    "{}\n{}: {}.",
+                    t!("Tab.Status.Devices.Inventory.Error.Save"),
+                    t!("Error.AdditionalInfo"),
+                    err
+                );
+                if let Some(additional_info) = err.additional_info() {
+                    text.push_str(&format!("\n{additional_info}"));
+                }
+                MessageModal::error(&text)
+            },
+        };
+        modal.try_send_by(&ctx.modals_tx);
+    }
+
+    fn devices_heading_view(ui: &mut egui::Ui, ctx: &Context) {
+        ui.horizontal(|ui| {
+            ui.heading(format!("{}:", t!("Tab.Status.Devices.Heading")));
+            if ctx.net_storage.devices.list().is_empty() {
+                ui.label(t!("Tab.Status.Devices.Empty"));
+            }
+        });
+    }
+
+    fn save_aliases_button(ui: &mut egui::Ui, ctx: &Context) {
+        if ui
+            .button(t!("Tab.Status.Devices.Button.SaveAliases"))
+            .clicked()
+        {
+            let modal = if let Err(err) =
+                ctx.net_storage.devices.save_aliases_to_file(&ctx.data_dir)
+            {
+                let mut text = format!(
+                    "{}\n{}: {}.",
+                    t!("Tab.Status.Devices.Modal.ErrorSave"),
+                    t!("Error.AdditionalInfo"),
+                    err
+                );
+                if let Some(additional_info) = err.additional_info() {
+                    text.push_str(&format!("\n{additional_info}"));
+                }
+                MessageModal::error(&text)
+            } else {
+                MessageModal::info(&t!("Tab.Status.Devices.Modal.Success"))
+            };
+            let _ = ctx.modals_tx.try_send(Box::new(modal));
+        }
+    }
+
+    /// Returns true, if the plot of the device is toggled.
     fn device_view(
         &mut self, ui: &mut egui::Ui, ctx: &Context, device: &LocalDevice, index: usize,
-    ) {
+    ) -> bool {
         let theme = ctx.client_settings.theme.into_aesthetix_theme();
+        let mut is_plot_toggled = false;
         egui::Frame::group(&egui::Style::default())
             .fill(ui.visuals().extreme_bg_color)
             .inner_margin(theme.margin_style())
             .corner_radius(5.0)
             .show(ui, |ui| {
                 ui.columns(2, |columns| {
-                    columns[0].vertical(|ui| {
+                    columns[0].horizontal(|ui| {
+                        if self.is_selecting {
+                            let mut is_selected =
+                                self.selected_devices.contains(&device.mac);
+                            if ui.checkbox(&mut is_selected, "").changed() {
+                                match is_selected {
+                                    true => {
+                                        self.selected_devices.insert(device.mac.clone())
+                                    },
+                                    false => self.selected_devices.remove(&device.mac),
+                                };
+                            }
+                        }
                         if let Some(name) =
                             ctx.net_storage.devices.aliases.get(&device.mac)
                         {
@@ -257,6 +1650,15 @@ impl StatusTab {
                                 index
                             ));
                         }
+                        if ctx.net_storage.devices.is_hidden(&device.mac) {
+                            ui.label("👁")
+                                .on_hover_text(t!("Tab.Status.Devices.Device.Hidden"));
+                        }
+                        if device.mac.is_locally_administered() {
+                            ui.label("🔀").on_hover_text(t!(
+                                "Tab.Status.Devices.Device.Randomized"
+                            ));
+                        }
                     });
 
                     columns[1].with_layout(
@@ -271,6 +1673,29 @@ impl StatusTab {
                                     DeviceModal::with_id(device.mac.clone(), ctx),
                                 ));
                             }
+                            is_plot_toggled = Self::device_plot_button(ui, ctx, device);
+
+                            let conflicts: Vec<String> = ctx
+                                .net_storage
+                                .conflicts
+                                .alerts(ctx.client_settings.ip_conflict_failover_alerts)
+                                .filter(|conflict| conflict.involves(&device.mac))
+                                .map(|conflict| conflict.ip.to_string())
+                                .collect();
+                            if !conflicts.is_empty() {
+                                ui.label(
+                                    RichText::new("⚠")
+                                        .color(styles::colors::get(Role::IpConflict)),
+                                )
+                                .on_hover_text(t!(
+                                    "Tab.Status.Devices.Device.IpConflict",
+                                    "ip" = conflicts.join(", ")
+                                ));
+                            }
+
+                            if let Some(tag) = ctx.net_storage.devices.tag(&device.mac) {
+                                styles::devices::tag_chip(ui, tag);
+                            }
                         },
                     );
                 });
@@ -318,6 +1743,13 @@ impl StatusTab {
                             });
                             ui.end_row();
 
+                            ui.label(format!(
+                                "{}:",
+                                t!("Tab.Status.Devices.Device.Hostname")
+                            ));
+                            ui.label(device.hostname.as_deref().unwrap_or("-"));
+                            ui.end_row();
+
                             ui.label(format!(
                                 "{}:",
                                 t!("Tab.Status.Devices.Device.Vendor")
@@ -333,10 +1765,54 @@ impl StatusTab {
                                     ),
                             );
                             ui.end_row();
+
+                            ui.label(format!(
+                                "{}:",
+                                t!("Tab.Status.Devices.Device.Type")
+                            ));
+                            Self::device_type_label(ui, ctx, device);
+                            ui.end_row();
                         });
                 });
             });
 
         ui.add_space(4.0);
+        is_plot_toggled
+    }
+
+    fn device_plot_button(
+        ui: &mut egui::Ui, ctx: &Context, device: &LocalDevice,
+    ) -> bool {
+        let speed = &ctx.net_storage.speed;
+        let mut is_plotted = speed.is_device_plotted(&device.mac);
+        let can_plot = is_plotted || speed.plotted_devices().count() < DEVICE_SERIES_MAX;
+        ui.add_enabled_ui(can_plot, |ui| {
+            ui.toggle_value(&mut is_plotted, "📈")
+                .on_hover_text(t!("Tab.Status.Devices.Device.Plot"))
+                .on_disabled_hover_text(t!(
+                    "Tab.Status.Devices.Device.Plot.Limit",
+                    "max" = DEVICE_SERIES_MAX
+                ))
+        })
+        .inner
+        .changed()
+    }
+
+    // Manual type takes precedence over the guessed one
+    fn device_type_label(ui: &mut egui::Ui, ctx: &Context, device: &LocalDevice) {
+        let devices = &ctx.net_storage.devices;
+        if let Some(device_type) = devices.types.get(&device.mac) {
+            ui.label(device_type.to_string())
+                .on_hover_text(t!("Tab.Status.Devices.Device.Type.Manual"));
+        } else if let Some(classification) = devices.classify(device) {
+            ui.label(classification.device_type.to_string())
+                .on_hover_text(t!(
+                    "Tab.Status.Devices.Device.Type.Guessed",
+                    "signal" = classification.signal.localized(),
+                    "confidence" = classification.confidence.localized()
+                ));
+        } else {
+            ui.label(t!("Tab.Status.Devices.Device.Type.Unknown"));
+        }
     }
 }
@@ -0,0 +1,161 @@
+// Live stats in the window title, so they're seen in the taskbar while the window is
+// minimized. Title is changed not more often than once per interval & only if its text
+// differs, so the window manager isn't spammed. Unseen alerts add the leading "[!]".
+
+use crate::ui;
+use crate::ui::format;
+use std::time::{Duration, Instant};
+
+pub const UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+const SEPARATOR: &str = " — ";
+const ALERT_MARK: &str = "[!]";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TitleSettings {
+    pub enabled: bool,
+    pub throughput: bool,
+    pub devices: bool,
+    pub alerts: bool,
+}
+
+impl Default for TitleSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            throughput: true,
+            devices: true,
+            alerts: true,
+        }
+    }
+}
+
+pub struct TitleStats {
+    // Bytes per second
+    pub received: f64,
+    pub sent: f64,
+    pub devices: usize,
+    // Alerts since the window was focused
+    pub alerts: u64,
+}
+
+#[derive(Default)]
+pub struct WindowTitle {
+    updated: Option<Instant>,
+    // None, if the default title is shown
+    shown: Option<String>,
+}
+
+impl WindowTitle {
+    /// Title, that should be set now. <br>
+    /// Stats are gathered only if the title is due. Disabled option restores the default
+    /// title once, and nothing is done after that.
+    pub fn next(
+        &mut self, settings: &TitleSettings, now: Instant,
+        stats: impl FnOnce() -> TitleStats,
+    ) -> Option<String> {
+        if !settings.enabled {
+            self.updated = None;
+            return self.shown.take().map(|_| ui::WINDOW_TITLE.to_string());
+        }
+        if self
+            .updated
+            .is_some_and(|updated| now.duration_since(updated) < UPDATE_INTERVAL)
+        {
+            return None;
+        }
+        self.updated = Some(now);
+
+        let title = compose(settings, &stats());
+        if self.shown.as_ref() == Some(&title) {
+            return None;
+        }
+        self.shown = Some(title.clone());
+
+        Some(title)
+    }
+}
+
+fn compose(settings: &TitleSettings, stats: &TitleStats) -> String {
+    let mut parts = vec![ui::WINDOW_TITLE.to_string()];
+    if settings.throughput {
+        parts.push(format!(
+            "{} ↓ {} ↑",
+            format::rate(stats.received),
+            format::rate(stats.sent)
+        ));
+    }
+    if settings.devices {
+        parts.push(t!("Window.Title.Devices", "amount" = stats.devices).to_string());
+    }
+    let is_alerted = settings.alerts && stats.alerts > 0;
+    if is_alerted {
+        parts.push(t!("Window.Title.Alerts", "amount" = stats.alerts).to_string());
+    }
+
+    let title = parts.join(SEPARATOR);
+    match is_alerted {
+        true => format!("{ALERT_MARK} {title}"),
+        false => title,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(alerts: u64) -> TitleStats {
+        TitleStats {
+            received: 0.0,
+            sent: 0.0,
+            devices: 37,
+            alerts,
+        }
+    }
+
+    #[test]
+    fn test_update_interval() {
+        let settings = TitleSettings {
+            enabled: true,
+            ..Default::default()
+        };
+        let mut title = WindowTitle::default();
+        let start = Instant::now();
+        let at = |millis: u64| start + Duration::from_millis(millis);
+
+        let first = title.next(&settings, at(0), || stats(0)).unwrap();
+        assert!(first.starts_with(ui::WINDOW_TITLE));
+        // Stats aren't gathered within the interval
+        assert_eq!(title.next(&settings, at(500), || unreachable!()), None);
+        // Same text isn't set again
+        assert_eq!(title.next(&settings, at(1000), || stats(0)), None);
+
+        let alerted = title.next(&settings, at(2000), || stats(2)).unwrap();
+        assert!(alerted.starts_with(ALERT_MARK));
+        assert_ne!(alerted, first);
+    }
+
+    #[test]
+    fn test_disabled() {
+        let mut settings = TitleSettings {
+            enabled: true,
+            throughput: false,
+            devices: false,
+            alerts: false,
+        };
+        let mut title = WindowTitle::default();
+        let now = Instant::now();
+
+        // Alerts aren't marked, if they aren't chosen
+        assert_eq!(
+            title.next(&settings, now, || stats(5)),
+            Some(ui::WINDOW_TITLE.to_string())
+        );
+
+        settings.enabled = false;
+        assert_eq!(
+            title.next(&settings, now, || unreachable!()),
+            Some(ui::WINDOW_TITLE.to_string())
+        );
+        assert_eq!(title.next(&settings, now, || unreachable!()), None);
+    }
+}
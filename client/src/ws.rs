@@ -1,46 +1,139 @@
+use crate::ws::link::Counted;
 use crate::ws::request::UiClientRequest;
-use common::auth::{AUTH_HEADER, COMPRESSION_HEADER};
-use common::compression::decompress;
-use common::cryptography::encrypt_password;
+use crate::ws::state::{ConnectionState, ConnectionUpdate};
+use common::auth;
+use common::auth::{
+    AUTH_HEADER, AUTH_NONCE_HEADER, AUTH_PARAMS_HEADER, COMPRESSION_HEADER,
+    FEATURES_HEADER, HEARTBEAT_HEADER, SESSION_TOKEN_HEADER,
+};
+use common::binary;
+use common::compression::decompress_bytes;
+use common::cryptography;
+use common::cryptography::CryptographyError;
 use common::messages::{CONNECTION_TIMEOUT, Request, Response};
-use crossbeam::channel::{Receiver, Sender};
+use common::recording::Entry;
+use common::suppression::{FailureStreak, Report};
+use crossbeam::channel::{Receiver, Sender, TrySendError};
+use dpi::dto::frame::FrameType;
 use http::{StatusCode, Uri};
 use std::net::{SocketAddr, TcpStream};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
+use std::time::{Duration, Instant};
 use thiserror::Error;
+use tungstenite::handshake::HandshakeError;
 use tungstenite::stream::MaybeTlsStream;
 use tungstenite::{Bytes, ClientRequestBuilder, Message, WebSocket};
 
-type WsStream = WebSocket<MaybeTlsStream<TcpStream>>;
+type WsStream = WebSocket<Counted<MaybeTlsStream<TcpStream>>>;
 
 pub struct WsHandler {
     pub compression: bool,
     pub shutdown_flag: Arc<AtomicBool>,
 
     pub stream: WsStream,
+    pub connection_tx: Sender<ConnectionUpdate>,
     pub data_response_tx: Sender<Response>,
     pub server_response_tx: Sender<Response>,
     pub ui_client_requests_rx: Receiver<UiClientRequest>,
+    // Set, if the session is recorded
+    pub recording_tx: Option<Sender<Entry<Response>>>,
+    pub keepalive: Keepalive,
+    // Unreadable messages (e.g. of the other protocol version) since the last readable one
+    pub dropped_messages: FailureStreak,
+    // Frames, that didn't fit into the data channel since the last one, that did
+    pub overflowed_frames: FailureStreak,
 }
 
+/// Pings of the WS thread itself. UI pings only when the responses stop, but the server
+/// needs a message at least once per heartbeat interval, so the connection isn't closed.
+pub struct Keepalive {
+    interval: Duration,
+    last_sent: Instant,
+}
+
+impl Keepalive {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last_sent: Instant::now(),
+        }
+    }
+
+    fn is_due(&self) -> bool {
+        self.last_sent.elapsed() >= self.interval
+    }
+
+    fn sent(&mut self) {
+        self.last_sent = Instant::now();
+    }
+}
+
+pub enum Credentials {
+    Password(String),
+    SessionToken(String),
+}
+
+// Returns the stream and the session token, if the server issued a new one.
+// Heartbeat is the interval of the keepalive pings, the server closes silent connections.
 pub fn connect(
-    address: SocketAddr, password: &str, compression: bool,
-) -> Result<WsStream, WsError> {
-    let uri: Uri = format!("ws://{}:{}/socket", address.ip(), address.port())
+    address: SocketAddr, credentials: &Credentials, compression: bool,
+    heartbeat: Duration, state_tx: &Sender<ConnectionUpdate>,
+) -> Result<(WsStream, Option<String>), WsError> {
+    let uri: Uri = format!("ws://{address}/socket")
         .parse()
         .map_err(|_| WsError::FailedParseUri)?;
-    let hashed_password = encrypt_password(password);
-    let request = ClientRequestBuilder::new(uri)
-        .with_header(AUTH_HEADER, hashed_password)
-        .with_header(COMPRESSION_HEADER, compression.to_string());
+    let tcp_stream = TcpStream::connect(address).map_err(|err| {
+        WsError::ConnectionFailed(Box::new(tungstenite::Error::Io(err)))
+    })?;
+    state::try_send(
+        state_tx,
+        ConnectionUpdate::State(ConnectionState::Authenticating(address)),
+    );
 
-    let mut stream = match tungstenite::connect(request) {
-        Ok((stream, _)) => stream,
-        Err(err) => return Err(WsError::ConnectionFailed(Box::new(err))),
+    let (request, tcp_stream) = match credentials {
+        Credentials::Password(password) => {
+            let (nonce, signature) =
+                answer_challenge(&uri, tcp_stream, password, compression)?;
+            // Challenge is answered by the new handshake
+            let tcp_stream = TcpStream::connect(address).map_err(|err| {
+                WsError::ConnectionFailed(Box::new(tungstenite::Error::Io(err)))
+            })?;
+            let request = ClientRequestBuilder::new(uri)
+                .with_header(AUTH_NONCE_HEADER, nonce)
+                .with_header(AUTH_HEADER, signature);
+            (request, tcp_stream)
+        },
+        Credentials::SessionToken(token) => {
+            let request =
+                ClientRequestBuilder::new(uri).with_header(SESSION_TOKEN_HEADER, token);
+            (request, tcp_stream)
+        },
     };
-    match stream.get_mut() {
+    let request = request
+        .with_header(COMPRESSION_HEADER, compression.to_string())
+        .with_header(HEARTBEAT_HEADER, heartbeat.as_secs().to_string())
+        .with_header(FEATURES_HEADER, auth::features::header_value());
+
+    let tcp_stream = Counted::new(MaybeTlsStream::Plain(tcp_stream));
+    let (mut stream, session_token) = match tungstenite::client(request, tcp_stream) {
+        Ok((stream, response)) => {
+            let session_token = response
+                .headers()
+                .get(SESSION_TOKEN_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| value.to_string());
+            (stream, session_token)
+        },
+        Err(HandshakeError::Failure(err)) => {
+            return Err(WsError::ConnectionFailed(Box::new(err)));
+        },
+        Err(HandshakeError::Interrupted(_)) => {
+            return Err(WsError::HandshakeInterrupted);
+        },
+    };
+    match stream.get_mut().get_mut() {
         MaybeTlsStream::Plain(stream) => stream
             .set_read_timeout(Some(CONNECTION_TIMEOUT))
             .map_err(WsError::BadReadTimeoutDuration),
@@ -54,7 +147,10 @@ pub fn connect(
         Ok(value) => {
             let result = stream.send(value);
             match result {
-                Ok(_) => log::info!("WS-Stream: Sent connection server settings request"),
+                Ok(_) => {
+                    stream.get_ref().counters().message_sent();
+                    log::info!("WS-Stream: Sent connection server settings request")
+                },
                 Err(_) => {
                     log::error!("WS-Stream: Failed to send connection settings request.")
                 },
@@ -67,17 +163,77 @@ pub fn connect(
         },
     };
 
-    Ok(stream)
+    Ok((stream, session_token))
+}
+
+// Handshake without the credentials is rejected with the challenge of the server.
+// Returns the nonce & its signature by the key, derived from the password.
+fn answer_challenge(
+    uri: &Uri, tcp_stream: TcpStream, password: &str, compression: bool,
+) -> Result<(String, String), WsError> {
+    let request = ClientRequestBuilder::new(uri.clone())
+        .with_header(COMPRESSION_HEADER, compression.to_string());
+    let response = match tungstenite::client(request, MaybeTlsStream::Plain(tcp_stream)) {
+        Err(HandshakeError::Failure(tungstenite::Error::Http(response))) => response,
+        Err(HandshakeError::Failure(err)) => {
+            return Err(WsError::ConnectionFailed(Box::new(err)));
+        },
+        Err(HandshakeError::Interrupted(_)) => return Err(WsError::HandshakeInterrupted),
+        Ok(_) => return Err(WsError::ChallengeNotReceived),
+    };
+
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string())
+    };
+    let (Some(nonce), Some(params)) =
+        (header(AUTH_NONCE_HEADER), header(AUTH_PARAMS_HEADER))
+    else {
+        // Rejected for another reason
+        return Err(WsError::ConnectionFailed(Box::new(
+            tungstenite::Error::Http(response),
+        )));
+    };
+
+    let key =
+        cryptography::derive_key(password, &params).map_err(WsError::ChallengeFailed)?;
+    let signature =
+        cryptography::sign_nonce(&key, &nonce).map_err(WsError::ChallengeFailed)?;
+
+    Ok((nonce, signature))
 }
 
 impl WsHandler {
-    pub fn send_receive_messages(&mut self) {
+    // Returns the error, if the connection is lost. Otherwise, it's closed by the client:
+    // on shutdown, or when the UI doesn't read the responses anymore (after logout).
+    pub fn send_receive_messages(&mut self) -> Result<(), Box<tungstenite::Error>> {
         while !self.shutdown_flag.load(Ordering::Acquire) {
-            if self.receive_messages().is_err() {
-                return;
+            self.receive_messages()?;
+            if !self.send_messages() {
+                log::info!("WS-Stream: UI is detached, closing the connection.");
+                if let Err(err) = self.stream.close(None) {
+                    log::warn!("WS-Stream: Can't close the connection. Error: {err}");
+                }
+                break;
             }
-            self.send_messages();
+            self.send_keepalive();
         }
+        Ok(())
+    }
+
+    fn send_keepalive(&mut self) {
+        if !self.keepalive.is_due() {
+            return;
+        }
+        match self.send(Message::Ping(Bytes::new())) {
+            Ok(_) => log::debug!("WS-Stream: Sent keepalive ping."),
+            Err(err) => log::error!("WS-Stream: Can't send keepalive ping. Error: {err}"),
+        }
+        // Failed ping isn't repeated right away, the lost connection is found by reading
+        self.keepalive.sent();
     }
 
     fn receive_messages(&mut self) -> Result<(), Box<tungstenite::Error>> {
@@ -114,8 +270,16 @@ impl WsHandler {
             },
         };
 
-        if msg.is_close() {
-            log::info!("WS-Stream: Server closed connection.");
+        self.stream.get_ref().counters().message_received();
+
+        if let Message::Close(frame) = &msg {
+            let reason = frame.as_ref().map(|frame| frame.reason.as_str());
+            match reason {
+                Some(auth::close_reasons::CREDENTIALS_CHANGED) => log::warn!(
+                    "WS-Stream: Server closed connection, the password is changed."
+                ),
+                _ => log::info!("WS-Stream: Server closed connection."),
+            }
             return Err(Box::new(tungstenite::Error::ConnectionClosed));
         }
 
@@ -130,7 +294,7 @@ impl WsHandler {
 
         // Server don't send ping messages
         if msg.is_ping() {
-            if let Err(err) = self.stream.send(Message::Pong(Bytes::new())) {
+            if let Err(err) = self.send(Message::Pong(Bytes::new())) {
                 log::error!("WS-Stream: Can't send message. Error: {err}");
             }
         }
@@ -143,24 +307,43 @@ impl WsHandler {
         Ok(())
     }
 
-    fn handle_binary_compressed(&self, msg: Message) {
+    fn handle_binary_compressed(&mut self, msg: Message) {
         if msg.is_empty() || msg.is_text() {
             log::warn!("WS-Stream: Received empty or non-compressed message.");
         }
 
         if msg.is_binary() {
-            let decompressed = match decompress(&msg.into_data()) {
+            let decompressed = match decompress_bytes(&msg.into_data()) {
                 Ok(value) => value,
                 Err(_) => {
-                    log::error!("WS-Stream: Failed to decompress message.");
+                    self.message_dropped(|| {
+                        log::error!("WS-Stream: Failed to decompress message.")
+                    });
                     return;
                 },
             };
-            self.pass_responses(&decompressed);
+            if binary::is_raw_frame(&decompressed) {
+                self.pass_raw_frame(decompressed);
+                return;
+            }
+            match String::from_utf8(decompressed) {
+                Ok(text) => self.pass_responses(&text),
+                Err(_) => self.message_dropped(|| {
+                    log::error!("WS-Stream: Decompressed message isn't UTF-8.")
+                }),
+            }
         }
     }
 
-    fn handle_text_uncompressed(&self, msg: Message) {
+    fn handle_text_uncompressed(&mut self, msg: Message) {
+        // Raw frames are binary even without the compression
+        let msg = match msg {
+            Message::Binary(bytes) if binary::is_raw_frame(&bytes) => {
+                self.pass_raw_frame(Vec::from(bytes));
+                return;
+            },
+            msg => msg,
+        };
         if msg.is_empty() || msg.is_binary() {
             log::warn!("WS-Stream: Received empty or binary message.");
         }
@@ -170,43 +353,157 @@ impl WsHandler {
         }
     }
 
-    fn pass_responses(&self, text: &str) {
+    // Frame is built from the bytes of the message, without the JSON in between
+    fn pass_raw_frame(&mut self, message: Vec<u8>) {
+        match binary::decode(message) {
+            Ok(frame) => {
+                self.message_passed();
+                let response = Response::Data(FrameType::Raw(frame));
+                self.record(&response);
+                self.route(response);
+            },
+            Err(err) => self.message_dropped(|| {
+                log::warn!("WS-Stream: Can't decode raw frame! Error: {err}")
+            }),
+        }
+    }
+
+    fn pass_responses(&mut self, text: &str) {
         let deserialized: Result<Response, serde_json::Error> =
             serde_json::from_str(text);
         match deserialized {
             Ok(message) => {
-                let result = match message {
-                    Response::Data(_) => self.data_response_tx.try_send(message),
-                    _ => self.server_response_tx.try_send(message),
-                };
-                if let Err(err) = result {
-                    log::error!("WS Channel: Can't send message. Error: {err}");
-                }
+                self.message_passed();
+                self.record(&message);
+                self.route(message);
             },
-            Err(err) => {
+            Err(err) => self.message_dropped(|| {
                 log::warn!(
                     "Serde: can't deserialize message! Error: {err}. Text: {text:#?}",
-                );
+                )
+            }),
+        }
+    }
+
+    // First failure is logged by the caller with the details, the rest by the summaries.
+    // UI is updated only along with the log, not on every message
+    fn message_dropped(&mut self, log_details: impl FnOnce()) {
+        match self.dropped_messages.failed(Instant::now()) {
+            Report::First => log_details(),
+            Report::Summary {
+                suppressed,
+                seconds,
+            } => log::warn!(
+                "WS-Stream: Suppressed {suppressed} similar errors in the last {seconds} s."
+            ),
+            Report::Suppressed => return,
+        }
+        let amount = self.dropped_messages.failures();
+        state::try_send(
+            &self.connection_tx,
+            ConnectionUpdate::DroppedMessages(amount),
+        );
+    }
+
+    fn message_passed(&mut self) {
+        if let Some(failures) = self.dropped_messages.succeeded() {
+            log::info!(
+                "WS-Stream: Messages are readable again, {failures} were dropped."
+            );
+            state::try_send(&self.connection_tx, ConnectionUpdate::DroppedMessages(0));
+        }
+    }
+
+    // Frames are dropped, while the UI is behind, instead of the backlog growing
+    fn route(&mut self, response: Response) {
+        let result =
+            route_response(response, &self.data_response_tx, &self.server_response_tx);
+        match result {
+            Ok(()) => {
+                if let Some(failures) = self.overflowed_frames.succeeded() {
+                    log::info!(
+                        "WS Channel: UI caught up, {failures} frames were dropped."
+                    );
+                }
             },
+            Err(TrySendError::Full(_)) => {
+                match self.overflowed_frames.failed(Instant::now()) {
+                    Report::First => {
+                        log::warn!("WS Channel: UI is behind, frames are dropped.")
+                    },
+                    Report::Summary {
+                        suppressed,
+                        seconds,
+                    } => log::warn!(
+                        "WS Channel: Dropped {suppressed} more frames in the last {seconds} s."
+                    ),
+                    Report::Suppressed => {},
+                }
+            },
+            Err(err) => log::error!("WS Channel: Can't send message. Error: {err}"),
         }
     }
 
-    fn send_messages(&mut self) {
-        if let Ok(command) = self.ui_client_requests_rx.try_recv() {
-            let message = match command.into_message(self.compression) {
-                Ok(value) => value,
-                Err(_) => {
-                    log::error!("Serde: Can't serialize message!");
-                    return;
-                },
-            };
+    fn record(&self, response: &Response) {
+        let Some(recording_tx) = &self.recording_tx else {
+            return;
+        };
+        // Recorder serializes it, after the anonymization (if it is on)
+        if let Err(err) = recording_tx.try_send(Entry::now(response.clone())) {
+            log::error!("Recording Channel: Can't send entry. Error: {err}");
+        }
+    }
 
-            if let Err(err) = self.stream.send(message) {
-                log::error!("WS-Stream: Can't send message. Error: {err}");
-            } else {
-                log::debug!("WS-Stream (Client -> Server): Sent command.");
-            }
+    fn send(&mut self, message: Message) -> Result<(), tungstenite::Error> {
+        self.stream.send(message)?;
+        self.stream.get_ref().counters().message_sent();
+        Ok(())
+    }
+
+    // Returns false, if the UI has dropped its requests channel (e.g. after logout).
+    // Requests, that are queued before it, are still sent.
+    fn send_messages(&mut self) -> bool {
+        let command = match self.ui_client_requests_rx.try_recv() {
+            Ok(command) => command,
+            Err(err) => return !err.is_disconnected(),
+        };
+        let message = match command.into_message(self.compression) {
+            Ok(value) => value,
+            Err(_) => {
+                log::error!("Serde: Can't serialize message!");
+                return true;
+            },
+        };
+
+        if let Err(err) = self.send(message) {
+            log::error!("WS-Stream: Can't send message. Error: {err}");
+        } else {
+            log::debug!("WS-Stream (Client -> Server): Sent command.");
+            self.keepalive.sent();
         }
+        true
+    }
+}
+
+// Channel of the data is bounded, so it's full, when the UI is behind.
+// Disconnected, if the UI doesn't receive the responses anymore (e.g. after logout)
+pub fn route_response(
+    response: Response, data_response_tx: &Sender<Response>,
+    server_response_tx: &Sender<Response>,
+) -> Result<(), TrySendError<Response>> {
+    match response {
+        Response::Data(_) => data_response_tx.try_send(response),
+        _ => server_response_tx.try_send(response),
+    }
+}
+
+// Reason of the lost connection, shown by the connection state
+pub fn disconnect_reason(err: &tungstenite::Error) -> String {
+    match err {
+        tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed => {
+            t!("Connection.Reason.Closed").to_string()
+        },
+        _ => format!("{}: {err}", t!("Connection.Reason.Lost")),
     }
 }
 
@@ -223,6 +520,15 @@ pub enum WsError {
 
     #[error("Unknown TLS stream type")]
     UnknownStreamType,
+
+    #[error("Handshake was interrupted")]
+    HandshakeInterrupted,
+
+    #[error("Server didn't send the password challenge")]
+    ChallengeNotReceived,
+
+    #[error("Failed to answer the password challenge")]
+    ChallengeFailed(CryptographyError),
 }
 
 impl WsError {
@@ -238,6 +544,15 @@ impl WsError {
             WsError::UnknownStreamType => {
                 t!("Error.Websockets.UnknownStreamType").to_string()
             },
+            WsError::HandshakeInterrupted => {
+                t!("Error.Websockets.HandshakeInterrupted").to_string()
+            },
+            WsError::ChallengeNotReceived => {
+                t!("Error.Websockets.ChallengeNotReceived").to_string()
+            },
+            WsError::ChallengeFailed(_) => {
+                t!("Error.Websockets.ChallengeFailed").to_string()
+            },
         }
     }
 
@@ -310,6 +625,7 @@ impl WsError {
                 },
             },
             WsError::BadReadTimeoutDuration(err) => Some(err.to_string()),
+            WsError::ChallengeFailed(err) => Some(err.to_string()),
             _ => None,
         }
     }
@@ -323,6 +639,191 @@ impl WsError {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ws::mock;
+    use crate::ws::mock::MockServer;
+    use crossbeam::channel::{bounded, unbounded};
+    use dpi::dto::frame::{FrameHeader, OwnedFrame};
+    use std::time::Duration;
+
+    const RECEIVE_TIMEOUT: Duration = Duration::from_secs(5);
+    const HEARTBEAT: Duration = Duration::from_secs(60);
+
+    fn exchange(compression: bool) {
+        let header = FrameHeader {
+            tv_sec: 1_700_000_000,
+            tv_usec: 0,
+            caplen: 60,
+            len: 60,
+        };
+        let raw = OwnedFrame {
+            header: header.clone(),
+            data: vec![0xAB; 60],
+        };
+        let server = MockServer::spawn(
+            vec![
+                Response::Data(FrameType::Header(header.clone())),
+                Response::Data(FrameType::Raw(raw.clone())),
+                Response::CaptureIdle { seconds: 5 },
+            ],
+            compression,
+        )
+        .unwrap();
+
+        let (state_tx, _state_rx) = unbounded();
+        let credentials = Credentials::Password(mock::PASSWORD.to_string());
+        let (stream, _) = connect(
+            server.address,
+            &credentials,
+            compression,
+            HEARTBEAT,
+            &state_tx,
+        )
+        .unwrap();
+
+        let (data_response_tx, data_response_rx) = unbounded();
+        let (server_response_tx, server_response_rx) = unbounded();
+        let (ui_client_requests_tx, ui_client_requests_rx) = unbounded();
+        let mut handler = WsHandler {
+            compression,
+            shutdown_flag: Arc::new(AtomicBool::new(false)),
+            stream,
+            connection_tx: state_tx,
+            data_response_tx,
+            server_response_tx,
+            ui_client_requests_rx,
+            recording_tx: None,
+            keepalive: Keepalive::new(HEARTBEAT),
+            dropped_messages: FailureStreak::default(),
+            overflowed_frames: FailureStreak::default(),
+        };
+        let handle = thread::spawn(move || handler.send_receive_messages());
+
+        // Responses are routed by their kind
+        let data = data_response_rx.recv_timeout(RECEIVE_TIMEOUT).unwrap();
+        assert!(
+            matches!(data, Response::Data(FrameType::Header(value)) if value == header)
+        );
+        // Binary message of the raw frame
+        let data = data_response_rx.recv_timeout(RECEIVE_TIMEOUT).unwrap();
+        assert!(matches!(data, Response::Data(FrameType::Raw(value)) if value == raw));
+        let idle = server_response_rx.recv_timeout(RECEIVE_TIMEOUT).unwrap();
+        assert!(matches!(idle, Response::CaptureIdle { seconds: 5 }));
+
+        ui_client_requests_tx
+            .send(UiClientRequest::CloseConnection)
+            .unwrap();
+        let result = handle.join().unwrap();
+        assert!(matches!(
+            result.map_err(|err| *err),
+            Err(tungstenite::Error::ConnectionClosed)
+        ));
+
+        let requests = server.join();
+        assert!(matches!(requests.as_slice(), [Request::ServerSettings]));
+    }
+
+    #[test]
+    fn test_exchange_uncompressed() {
+        exchange(false);
+    }
+
+    #[test]
+    fn test_exchange_compressed() {
+        exchange(true);
+    }
+
+    #[test]
+    fn test_detached_ui() {
+        let server =
+            MockServer::spawn(vec![Response::CaptureIdle { seconds: 5 }], false).unwrap();
+        let (state_tx, _state_rx) = unbounded();
+        let credentials = Credentials::Password(mock::PASSWORD.to_string());
+        let (stream, _) =
+            connect(server.address, &credentials, false, HEARTBEAT, &state_tx).unwrap();
+
+        let (data_response_tx, _data_response_rx) = unbounded();
+        let (server_response_tx, _server_response_rx) = unbounded();
+        let (ui_client_requests_tx, ui_client_requests_rx) = unbounded();
+        let mut handler = WsHandler {
+            compression: false,
+            shutdown_flag: Arc::new(AtomicBool::new(false)),
+            stream,
+            connection_tx: state_tx,
+            data_response_tx,
+            server_response_tx,
+            ui_client_requests_rx,
+            recording_tx: None,
+            keepalive: Keepalive::new(HEARTBEAT),
+            dropped_messages: FailureStreak::default(),
+            overflowed_frames: FailureStreak::default(),
+        };
+
+        // Logout is queued right before the UI drops the channel, it's still sent
+        let logout = Request::Logout("token".to_string());
+        ui_client_requests_tx
+            .send(UiClientRequest::Request(logout))
+            .unwrap();
+        drop(ui_client_requests_tx);
+        let result = thread::spawn(move || handler.send_receive_messages()).join();
+        assert!(result.unwrap().is_ok());
+
+        let requests = server.join();
+        assert!(matches!(
+            requests.as_slice(),
+            [Request::ServerSettings, Request::Logout(_)]
+        ));
+    }
+
+    #[test]
+    fn test_full_data_channel() {
+        let (data_response_tx, data_response_rx) = bounded(1);
+        let (server_response_tx, server_response_rx) = unbounded();
+        let frame = || Response::Data(FrameType::Header(FrameHeader::new(60)));
+
+        assert!(route_response(frame(), &data_response_tx, &server_response_tx).is_ok());
+        let result = route_response(frame(), &data_response_tx, &server_response_tx);
+        assert!(matches!(result, Err(TrySendError::Full(_))));
+        // Other responses aren't held up by the frames
+        let result = route_response(
+            Response::SuccessSync,
+            &data_response_tx,
+            &server_response_tx,
+        );
+        assert!(result.is_ok());
+        assert_eq!(data_response_rx.len(), 1);
+        assert_eq!(server_response_rx.len(), 1);
+
+        drop(data_response_rx);
+        let result = route_response(frame(), &data_response_tx, &server_response_tx);
+        assert!(matches!(result, Err(TrySendError::Disconnected(_))));
+    }
+
+    #[test]
+    fn test_wrong_password() {
+        let server = MockServer::spawn(vec![], false).unwrap();
+        let (state_tx, _state_rx) = unbounded();
+        let credentials = Credentials::Password("wrong".to_string());
+
+        let result = connect(server.address, &credentials, false, HEARTBEAT, &state_tx);
+        assert!(matches!(
+            result,
+            Err(WsError::ConnectionFailed(err))
+                if matches!(err.as_ref(), tungstenite::Error::Http(response)
+                    if response.status() == StatusCode::UNAUTHORIZED)
+        ));
+        assert!(server.join().is_empty());
+    }
+}
+
+pub mod address;
 pub mod data;
+pub mod link;
+#[cfg(test)]
+mod mock;
+pub mod recording;
 pub mod request;
 pub mod response;
+pub mod state;
@@ -0,0 +1,321 @@
+use std::fmt::{Display, Formatter};
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+
+/// Amount of the distinct addresses, that are kept in the login history.
+pub const RECENT_LIMIT: usize = 5;
+
+const MAX_HOSTNAME_LEN: usize = 253;
+const MAX_LABEL_LEN: usize = 63;
+
+/// Address of the server, entered in the login window. <br>
+/// Hostname is resolved before connecting, so it's done on the worker thread.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ServerAddress {
+    pub host: Host,
+    pub port: u16,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Host {
+    Ip(IpAddr),
+    Name(String),
+}
+
+impl ServerAddress {
+    pub fn parse(host: &str, port: &str) -> Result<Self, AddressError> {
+        Ok(Self {
+            host: parse_host(host)?,
+            port: parse_port(port)?,
+        })
+    }
+
+    /// Socket address without the resolution, if the host is given by IP.
+    pub fn socket(&self) -> Option<SocketAddr> {
+        match &self.host {
+            Host::Ip(ip) => Some(SocketAddr::new(*ip, self.port)),
+            Host::Name(_) => None,
+        }
+    }
+
+    /// Blocking, so it must not be called from the UI thread.
+    pub fn resolve(&self) -> Result<SocketAddr, AddressError> {
+        if let Some(socket) = self.socket() {
+            return Ok(socket);
+        }
+
+        let host = self.host.to_string();
+        let mut addresses =
+            (host.as_str(), self.port)
+                .to_socket_addrs()
+                .map_err(|err| {
+                    log::warn!("Failed to resolve {host}: {err}");
+                    AddressError::Unresolved(host.clone())
+                })?;
+        addresses
+            .next()
+            .ok_or_else(|| AddressError::Unresolved(host.clone()))
+    }
+}
+
+impl Display for ServerAddress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.host {
+            Host::Ip(IpAddr::V6(ip)) => write!(f, "[{ip}]:{}", self.port),
+            host => write!(f, "{host}:{}", self.port),
+        }
+    }
+}
+
+impl Display for Host {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Host::Ip(ip) => write!(f, "{ip}"),
+            Host::Name(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+/// Puts the address on top of the history, without the duplicates.
+pub fn remember(recent: &mut Vec<String>, address: &ServerAddress) {
+    let entry = address.to_string();
+    recent.retain(|value| !value.eq_ignore_ascii_case(&entry));
+    recent.insert(0, entry);
+    recent.truncate(RECENT_LIMIT);
+}
+
+/// Host & port of the history entry, as they are entered in the fields.
+pub fn split_recent(entry: &str) -> Option<(String, String)> {
+    let (host, port) = entry.rsplit_once(':')?;
+    let host = host
+        .strip_prefix('[')
+        .and_then(|host| host.strip_suffix(']'))
+        .unwrap_or(host);
+
+    Some((host.to_string(), port.to_string()))
+}
+
+fn parse_host(host: &str) -> Result<Host, AddressError> {
+    let host = host.trim();
+    if host.is_empty() {
+        return Err(AddressError::EmptyHost);
+    }
+    if host.contains("://") {
+        return Err(AddressError::SchemeInHost);
+    }
+
+    // IPv6 may be written in brackets, as in URLs
+    let unbracketed = host
+        .strip_prefix('[')
+        .and_then(|host| host.strip_suffix(']'))
+        .unwrap_or(host);
+    if let Ok(ip) = unbracketed.parse::<IpAddr>() {
+        return Ok(Host::Ip(ip));
+    }
+
+    if let Some((name, port)) = host.rsplit_once(':') {
+        // E.g. "my-server.local:4000"
+        if !name.contains(':')
+            && !port.is_empty()
+            && port.chars().all(|c| c.is_ascii_digit())
+        {
+            return Err(AddressError::PortInHost);
+        }
+        return Err(AddressError::InvalidIp(host.to_string()));
+    }
+    // E.g. "192.168.1.256" or "192.168.1"
+    if host.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return Err(AddressError::InvalidIp(host.to_string()));
+    }
+
+    parse_hostname(host)
+}
+
+// RFC 1123: letters, digits & hyphens in the labels, separated by dots
+fn parse_hostname(host: &str) -> Result<Host, AddressError> {
+    if let Some(character) = host
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() && *c != '-' && *c != '.')
+    {
+        return Err(AddressError::InvalidHostnameCharacter(character));
+    }
+
+    // Trailing dot is the fully qualified name
+    let name = host.strip_suffix('.').unwrap_or(host);
+    let is_label_valid = |label: &str| {
+        !label.is_empty()
+            && label.len() <= MAX_LABEL_LEN
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+    };
+    if name.len() > MAX_HOSTNAME_LEN || !name.split('.').all(is_label_valid) {
+        return Err(AddressError::InvalidHostname(host.to_string()));
+    }
+
+    Ok(Host::Name(name.to_ascii_lowercase()))
+}
+
+fn parse_port(port: &str) -> Result<u16, AddressError> {
+    let port = port.trim();
+    if port.is_empty() {
+        return Err(AddressError::EmptyPort);
+    }
+    if !port.chars().all(|c| c.is_ascii_digit()) {
+        return Err(AddressError::PortNotNumber);
+    }
+
+    match port.parse::<u16>() {
+        Ok(port) if port != 0 => Ok(port),
+        _ => Err(AddressError::PortOutOfRange),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AddressError {
+    EmptyHost,
+    EmptyPort,
+    InvalidHostname(String),
+    InvalidHostnameCharacter(char),
+    InvalidIp(String),
+    PortInHost,
+    PortNotNumber,
+    PortOutOfRange,
+    SchemeInHost,
+    Unresolved(String),
+}
+
+impl AddressError {
+    /// Field isn't filled yet, so there's nothing to point at.
+    pub fn is_empty_field(&self) -> bool {
+        matches!(self, AddressError::EmptyHost | AddressError::EmptyPort)
+    }
+
+    pub fn localize(&self) -> String {
+        match self {
+            AddressError::EmptyHost => t!("Component.Auth.Error.EmptyHost").to_string(),
+            AddressError::EmptyPort => t!("Component.Auth.Error.EmptyPort").to_string(),
+            AddressError::InvalidHostname(host) => {
+                t!("Component.Auth.Error.InvalidHostname", "host" = host).to_string()
+            },
+            AddressError::InvalidHostnameCharacter(character) => t!(
+                "Component.Auth.Error.InvalidHostnameCharacter",
+                "character" = character
+            )
+            .to_string(),
+            AddressError::InvalidIp(host) => {
+                t!("Component.Auth.Error.InvalidIp", "host" = host).to_string()
+            },
+            AddressError::PortInHost => t!("Component.Auth.Error.PortInHost").to_string(),
+            AddressError::PortNotNumber => {
+                t!("Component.Auth.Error.PortNotNumber").to_string()
+            },
+            AddressError::PortOutOfRange => {
+                t!("Component.Auth.Error.PortOutOfRange").to_string()
+            },
+            AddressError::SchemeInHost => {
+                t!("Component.Auth.Error.SchemeInHost").to_string()
+            },
+            AddressError::Unresolved(host) => {
+                t!("Component.Auth.Error.Unresolved", "host" = host).to_string()
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    #[test]
+    fn test_parse_address() {
+        let address = ServerAddress::parse(" 192.168.0.10 ", "4000").unwrap();
+        assert_eq!(
+            address.host,
+            Host::Ip(IpAddr::V4(Ipv4Addr::new(192, 168, 0, 10)))
+        );
+        assert_eq!(address.to_string(), "192.168.0.10:4000");
+
+        let address = ServerAddress::parse("[::1]", "4000").unwrap();
+        assert_eq!(address.host, Host::Ip(IpAddr::V6(Ipv6Addr::LOCALHOST)));
+        assert_eq!(address.to_string(), "[::1]:4000");
+
+        let address = ServerAddress::parse("My-Server.local", "4000").unwrap();
+        assert_eq!(address.host, Host::Name("my-server.local".to_string()));
+        assert_eq!(address.socket(), None);
+        assert_eq!(address.to_string(), "my-server.local:4000");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error =
+            |host: &str, port: &str| ServerAddress::parse(host, port).unwrap_err();
+
+        assert_eq!(error("", "4000"), AddressError::EmptyHost);
+        assert_eq!(
+            error("http://my-server", "4000"),
+            AddressError::SchemeInHost
+        );
+        assert_eq!(
+            error("my-server.local:4000", "4000"),
+            AddressError::PortInHost
+        );
+        assert_eq!(
+            error("192.168.1.256", "4000"),
+            AddressError::InvalidIp("192.168.1.256".to_string())
+        );
+        assert_eq!(
+            error("my_server", "4000"),
+            AddressError::InvalidHostnameCharacter('_')
+        );
+        assert_eq!(
+            error("my..server", "4000"),
+            AddressError::InvalidHostname("my..server".to_string())
+        );
+        assert_eq!(
+            error("-server", "4000"),
+            AddressError::InvalidHostname("-server".to_string())
+        );
+
+        assert_eq!(error("my-server", " "), AddressError::EmptyPort);
+        assert_eq!(error("my-server", "4OOO"), AddressError::PortNotNumber);
+        assert_eq!(error("my-server", "-1"), AddressError::PortNotNumber);
+        assert_eq!(error("my-server", "70000"), AddressError::PortOutOfRange);
+        assert_eq!(error("my-server", "0"), AddressError::PortOutOfRange);
+    }
+
+    #[test]
+    fn test_resolve_ip_without_lookup() {
+        let address = ServerAddress::parse("127.0.0.1", "4000").unwrap();
+        assert_eq!(
+            address.resolve().unwrap(),
+            SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 4000)
+        );
+    }
+
+    #[test]
+    fn test_remember_recent() {
+        let mut recent = vec![];
+        for port in ["4000", "4001", "4002", "4003", "4004", "4005"] {
+            let address = ServerAddress::parse("my-server.local", port).unwrap();
+            remember(&mut recent, &address);
+        }
+        assert_eq!(recent.len(), RECENT_LIMIT);
+        assert_eq!(recent[0], "my-server.local:4005");
+
+        // Duplicate goes on top instead of the new entry
+        let address = ServerAddress::parse("MY-SERVER.local", "4003").unwrap();
+        remember(&mut recent, &address);
+        assert_eq!(recent.len(), RECENT_LIMIT);
+        assert_eq!(recent[0], "my-server.local:4003");
+        assert_eq!(recent[1], "my-server.local:4005");
+
+        assert_eq!(
+            split_recent("[::1]:4000"),
+            Some(("::1".to_string(), "4000".to_string()))
+        );
+        assert_eq!(
+            split_recent(&recent[0]),
+            Some(("my-server.local".to_string(), "4003".to_string()))
+        );
+    }
+}
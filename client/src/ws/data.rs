@@ -1,192 +1,487 @@
-use crate::context::Context;
+use crate::context::ClientSettings;
+use crate::net::NetStorage;
+use crate::net::classification;
+use crate::net::conflicts;
+use crate::net::conflicts::IpConflict;
+use crate::net::conversation::{Conversation, Direction};
 use crate::net::device::{DeviceAliases, LocalDevice};
+use crate::net::endpoints;
+use crate::net::endpoints::Flow;
+use crate::net::flows::TcpWindow;
 use crate::net::lookup::Lookup;
-use crate::net::speed::{Sample, SampleDirection, SpeedError};
-use dpi::analysis::ports::PortInfo;
-use dpi::dto::frame::{FrameHeader, OwnedFrame};
+use crate::net::memory::{EstimatedSize, FrameId, Records};
+use crate::net::routers::RogueRouter;
+use crate::net::speed::{Sample, SampleDirection, SpeedData, SpeedError};
+use crate::net::timeline::TimelineEvent;
+use chrono::{DateTime, Duration, Local};
+use dpi::dto::fields;
+use dpi::dto::fields::FieldIter;
+use dpi::dto::frame::{FrameDirection, FrameHeader, OwnedFrame};
 use dpi::dto::metadata::{FrameMetadataDto, ProtocolDto};
 use dpi::protocols::ProtocolId;
 use dpi::protocols::ethernet::mac::MacAddress;
+use dpi::protocols::ieee80211::IEEE80211Dto;
+use dpi::protocols::ip::checksum::ChecksumStatus;
+use dpi::protocols::radiotap::RadiotapDto;
 use dpi::protocols::tcp::TcpDto;
-use dpi::protocols::udp::UdpDto;
-use std::net::{Ipv4Addr, Ipv6Addr};
+use dpi::protocols::udp::{LengthMismatch, UdpDto};
+use serde::Serialize;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use strum_macros::Display;
 use thiserror::Error;
 
+/// Returns the findings of the frame, that are alerted by the caller.
 pub fn metadata(
-    ctx: &mut Context, metadata: FrameMetadataDto,
-) -> Result<(), ProcessingError> {
-    let mut sample = Some(Sample::try_from(&metadata.header)?);
+    storage: &mut NetStorage, settings: &ProcessingSettings, metadata: FrameMetadataDto,
+) -> Result<FrameAlerts, ProcessingError> {
+    let sample = Sample::try_from(&metadata.header)?;
+    let captured = sample.time_captured;
+    let wire_bytes = sample.wire_bytes as usize;
+    let stamp = FrameStamp {
+        captured,
+        frame: storage.inspector.next_frame(),
+        is_truncated: metadata.is_truncated,
+    };
+    let mut sample = Some(sample);
+
+    // Pending ARP requests expire by the time of any frame, not only ARP
+    let reply_window = Duration::milliseconds(i64::from(settings.arp_reply_window_ms));
+    storage.inspector.arp_pairing.expire(captured, reply_window);
 
     if metadata.layers.is_empty() {
-        return header(ctx, metadata.header);
+        return header(storage, metadata.header).map(|()| FrameAlerts::default());
     }
 
-    let datalink_info = match metadata.layers.first() {
-        Some(ProtocolDto::Ethernet(ethernet_info)) => ethernet_info.clone(),
+    let direction = metadata.direction;
+    let mut layers = metadata.layers.into_iter();
+    // Monitor mode: radio information precedes the 802.11 frame
+    let mut radio = None;
+    let datalink = match layers.next() {
+        Some(ProtocolDto::Radiotap(value)) => {
+            radio = Some(value);
+            layers.next()
+        },
+        layer => layer,
+    };
+
+    let mut wireless: Option<WirelessDto> = None;
+    let (source_mac, destination_mac) = match datalink {
+        Some(ProtocolDto::Ethernet(ethernet_info)) => {
+            (ethernet_info.source_mac, ethernet_info.destination_mac)
+        },
+        Some(ProtocolDto::IEEE80211(frame)) => {
+            // Frames without transmitter (CTS, ACK) can't be located
+            let Some(addresses) = frame.addresses() else {
+                return header(storage, metadata.header).map(|()| FrameAlerts::default());
+            };
+            wireless = Some(WirelessDto { frame, radio });
+            addresses
+        },
+        // Raw IP link types: Inspector relies on MAC addresses, so only speed is counted
+        Some(ProtocolDto::IPv4(_) | ProtocolDto::IPv6(_)) | None => {
+            return header(storage, metadata.header).map(|()| FrameAlerts::default());
+        },
         _ => return Err(ProcessingError::DatalinkNotFirst),
     };
 
+    // Only the plotted devices keep the samples
+    if let Some(value) = &sample {
+        storage
+            .speed
+            .load_device_sample(&source_mac, &destination_mac, value);
+    }
+
     let mut locator = Locator {
-        mac: (
-            datalink_info.source_mac.clone(),
-            datalink_info.destination_mac.clone(),
-        ),
+        mac: (source_mac.clone(), destination_mac.clone()),
         ipv4: None,
         ipv6: None,
+        tunnel: None,
     };
 
-    let limit = &ctx.client_settings.parsed_frames_limit;
-    let frames_len = &ctx.net_storage.inspector.ethernet.len();
+    let limit = &settings.parsed_frames_limit;
+    let frames_len = &storage
+        .inspector
+        .ethernet
+        .len()
+        .saturating_add(storage.inspector.ieee80211.len());
 
+    let mut alerts = FrameAlerts::default();
     let mut device: Option<LocalDevice> = None;
-    for layer in metadata.layers.into_iter().skip(1) {
+    let mut hostnames: Vec<(String, Option<Ipv4Addr>)> = vec![];
+    // Transport ports, RTP streams are told apart by them
+    let mut ports: Option<(u16, u16)> = None;
+    // External host of the outermost IP layer
+    let mut remote: Option<(IpAddr, Flow)> = None;
+    for layer in layers {
         match layer {
-            ProtocolDto::Ethernet(_) => return Err(ProcessingError::DatalinkNotFirst),
+            ProtocolDto::Ethernet(_)
+            | ProtocolDto::IEEE80211(_)
+            | ProtocolDto::Radiotap(_) => return Err(ProcessingError::DatalinkNotFirst),
             ProtocolDto::Arp(value) => {
-                push_value(&mut ctx.net_storage.inspector.arp, value, limit, frames_len)
+                if let Some((ip, mac)) = conflicts::gratuitous_arp(&value) {
+                    storage.conflicts.announce(ip, &mac);
+                }
+                // Index of the record, if it's stored
+                let record = storage.inspector.arp.len();
+                storage.inspector.arp_pairing.observe(
+                    &value,
+                    captured,
+                    reply_window,
+                    record,
+                );
+                push_value(&mut storage.inspector.arp, value, stamp, limit, frames_len)
+            },
+            ProtocolDto::DHCPv4(value) => {
+                storage
+                    .devices
+                    .signals
+                    .entry(value.hardware_address_client.clone())
+                    .or_default()
+                    .observe_dhcp(&value);
+                if let Some((source_ip, _)) = locator.ipv4 {
+                    let event =
+                        storage.dhcp_servers.observe(&value, source_ip, &source_mac);
+                    if let Some(event) = event {
+                        storage.timeline.push(captured, event);
+                    }
+                }
+                push_value(
+                    &mut storage.inspector.dhcpv4,
+                    value,
+                    stamp,
+                    limit,
+                    frames_len,
+                )
             },
-            ProtocolDto::DHCPv4(value) => push_value(
-                &mut ctx.net_storage.inspector.dhcpv4,
-                value,
-                limit,
-                frames_len,
-            ),
             ProtocolDto::DHCPv6(value) => push_value(
-                &mut ctx.net_storage.inspector.dhcpv6,
+                &mut storage.inspector.dhcpv6,
                 value,
+                stamp,
                 limit,
                 frames_len,
             ),
             ProtocolDto::DNS(value) => {
-                push_value(&mut ctx.net_storage.inspector.dns, value, limit, frames_len)
+                // mDNS responses advertise the services of the sender
+                let services = classification::mdns_services(&value);
+                if !services.is_empty() {
+                    storage
+                        .devices
+                        .signals
+                        .entry(source_mac.clone())
+                        .or_default()
+                        .observe_mdns(services);
+                }
+                storage.endpoints.resolve_names(&value);
+                if let Some(event) = storage.nxdomain_bursts.observe(&value, captured) {
+                    storage.timeline.push(captured, event);
+                }
+                push_value(&mut storage.inspector.dns, value, stamp, limit, frames_len)
             },
             ProtocolDto::HTTP(value) => push_value(
-                &mut ctx.net_storage.inspector.http,
+                &mut storage.inspector.http,
+                (value, locator.clone()),
+                stamp,
+                limit,
+                frames_len,
+            ),
+            ProtocolDto::NBNS(value) => {
+                hostnames.extend(value.hosts());
+                push_value(
+                    &mut storage.inspector.nbns,
+                    (value, locator.clone()),
+                    stamp,
+                    limit,
+                    frames_len,
+                )
+            },
+            ProtocolDto::RTP(value) => {
+                if let (
+                    Some((port_source, port_destination)),
+                    Some((source, destination)),
+                ) = (ports, locator.ip())
+                {
+                    storage.inspector.rtp_streams.observe(
+                        &value,
+                        (
+                            SocketAddr::new(source, port_source),
+                            SocketAddr::new(destination, port_destination),
+                        ),
+                        captured,
+                    );
+                }
+                push_value(
+                    &mut storage.inspector.rtp,
+                    (value, locator.clone()),
+                    stamp,
+                    limit,
+                    frames_len,
+                )
+            },
+            ProtocolDto::SIP(value) => {
+                storage.inspector.rtp_streams.negotiate(&value);
+                push_value(
+                    &mut storage.inspector.sip,
+                    (value, locator.clone()),
+                    stamp,
+                    limit,
+                    frames_len,
+                )
+            },
+            ProtocolDto::SMB2(value) => push_value(
+                &mut storage.inspector.smb2,
+                (value, locator.clone()),
+                stamp,
+                limit,
+                frames_len,
+            ),
+            ProtocolDto::Syslog(value) => push_value(
+                &mut storage.inspector.syslog,
                 (value, locator.clone()),
+                stamp,
+                limit,
+                frames_len,
+            ),
+            ProtocolDto::TFTP(value) => push_value(
+                &mut storage.inspector.tftp,
+                (value, locator.clone()),
+                stamp,
                 limit,
                 frames_len,
             ),
             ProtocolDto::IPv4(ipv4) => {
-                if ipv4.address_source.is_private() {
-                    if let Some(sample) = sample.take() {
-                        ctx.net_storage
-                            .speed
-                            .load_complete_sample(SampleDirection::Send(sample));
+                // MAC of the tunneled packet belongs to the tunnel endpoint
+                let is_outer = locator.ip().is_none();
+                if is_outer
+                    && ipv4.address_source.is_private()
+                    && !source_mac.is_multicast()
+                    && !source_mac.is_broadcast()
+                {
+                    let lookup = &storage.lookup;
+                    let found = storage.conflicts.observe(
+                        ipv4.address_source,
+                        &source_mac,
+                        captured,
+                        Duration::seconds(i64::from(settings.ip_conflict_window_seconds)),
+                        |mac| lookup.find_vendor(mac),
+                    );
+                    for conflict in &found {
+                        storage.timeline.push(
+                            captured,
+                            TimelineEvent::IpConflict {
+                                ip: conflict.ip,
+                                previous: conflict.previous.clone(),
+                                current: conflict.current.clone(),
+                                is_failover: conflict.is_failover,
+                            },
+                        );
                     }
+                    alerts.ip_conflicts.extend(found);
+                }
+                load_directed_sample(
+                    &mut storage.speed,
+                    &mut sample,
+                    direction,
+                    ipv4.address_source.is_private(),
+                    ipv4.address_destination.is_private(),
+                );
+                if remote.is_none() {
+                    remote = endpoints::remote_side(
+                        IpAddr::V4(ipv4.address_source),
+                        IpAddr::V4(ipv4.address_destination),
+                        direction,
+                        ipv4.address_source.is_private(),
+                        ipv4.address_destination.is_private(),
+                    );
+                }
+                if is_outer && ipv4.address_source.is_private() {
                     device = Some(LocalDevice {
-                        mac: datalink_info.source_mac.clone(),
+                        mac: source_mac.clone(),
                         ip: vec![ipv4.address_source],
                         ipv6: vec![],
                         vendor: None,
+                        hostname: None,
+                        first_seen: captured,
+                        last_seen: captured,
                     });
                 }
-                if ipv4.address_destination.is_private() {
-                    if let Some(sample) = sample.take() {
-                        ctx.net_storage
-                            .speed
-                            .load_complete_sample(SampleDirection::Receive(sample));
-                    }
+                if is_outer && ipv4.address_destination.is_private() {
                     device = Some(LocalDevice {
-                        mac: datalink_info.destination_mac.clone(),
+                        mac: destination_mac.clone(),
                         ip: vec![ipv4.address_destination],
                         ipv6: vec![],
                         vendor: None,
+                        hostname: None,
+                        first_seen: captured,
+                        last_seen: captured,
                     });
                 }
-                locator.ipv4 = Some((ipv4.address_source, ipv4.address_destination));
+                locator.push_ipv4(ipv4.address_source, ipv4.address_destination);
                 push_value(
-                    &mut ctx.net_storage.inspector.ipv4,
+                    &mut storage.inspector.ipv4,
                     (ipv4, locator.clone()),
+                    stamp,
                     limit,
                     frames_len,
                 );
             },
             ProtocolDto::IPv6(ipv6) => {
-                if ipv6.address_source.is_unique_local() {
-                    if let Some(sample) = sample.take() {
-                        ctx.net_storage
-                            .speed
-                            .load_complete_sample(SampleDirection::Send(sample));
-                    }
+                let is_outer = locator.ip().is_none();
+                load_directed_sample(
+                    &mut storage.speed,
+                    &mut sample,
+                    direction,
+                    ipv6.address_source.is_unique_local(),
+                    ipv6.address_destination.is_unique_local(),
+                );
+                if remote.is_none() {
+                    remote = endpoints::remote_side(
+                        IpAddr::V6(ipv6.address_source),
+                        IpAddr::V6(ipv6.address_destination),
+                        direction,
+                        ipv6.address_source.is_unique_local(),
+                        ipv6.address_destination.is_unique_local(),
+                    );
+                }
+                if is_outer && ipv6.address_source.is_unique_local() {
                     device = Some(LocalDevice {
-                        mac: datalink_info.source_mac.clone(),
+                        mac: source_mac.clone(),
                         ip: vec![],
                         ipv6: vec![ipv6.address_source],
                         vendor: None,
+                        hostname: None,
+                        first_seen: captured,
+                        last_seen: captured,
                     });
                 }
-                if ipv6.address_destination.is_unique_local() {
-                    if let Some(sample) = sample.take() {
-                        ctx.net_storage
-                            .speed
-                            .load_complete_sample(SampleDirection::Receive(sample));
-                    }
+                if is_outer && ipv6.address_destination.is_unique_local() {
                     device = Some(LocalDevice {
-                        mac: datalink_info.destination_mac.clone(),
+                        mac: destination_mac.clone(),
                         ip: vec![],
                         ipv6: vec![ipv6.address_destination],
                         vendor: None,
+                        hostname: None,
+                        first_seen: captured,
+                        last_seen: captured,
                     });
                 }
-                locator.ipv6 = Some((ipv6.address_source, ipv6.address_destination));
+                locator.push_ipv6(ipv6.address_source, ipv6.address_destination);
                 push_value(
-                    &mut ctx.net_storage.inspector.ipv6,
+                    &mut storage.inspector.ipv6,
                     (ipv6, locator.clone()),
+                    stamp,
                     limit,
                     frames_len,
                 );
             },
             ProtocolDto::ICMPv4(value) => push_value(
-                &mut ctx.net_storage.inspector.icmpv4,
+                &mut storage.inspector.icmpv4,
                 (value, locator.clone()),
+                stamp,
                 limit,
                 frames_len,
             ),
-            ProtocolDto::ICMPv6(value) => push_value(
-                &mut ctx.net_storage.inspector.icmpv6,
-                (value, locator.clone()),
-                limit,
-                frames_len,
-            ),
-            ProtocolDto::TCP(value) => push_value(
-                &mut ctx.net_storage.inspector.tcp,
-                (
-                    PortDto::from_tcp(value, &ctx.net_storage.lookup),
-                    locator.clone(),
-                ),
-                limit,
-                frames_len,
-            ),
-            ProtocolDto::UDP(value) => push_value(
-                &mut ctx.net_storage.inspector.udp,
-                (
-                    PortDto::from_udp(value, &ctx.net_storage.lookup),
-                    locator.clone(),
-                ),
-                limit,
-                frames_len,
-            ),
+            ProtocolDto::ICMPv6(value) => {
+                if let (Some(advertisement), Some((source_ip, _))) =
+                    (&value.router_advertisement, locator.ipv6)
+                {
+                    let found = storage.routers.observe(
+                        advertisement,
+                        &source_mac,
+                        source_ip,
+                        captured,
+                        Duration::seconds(i64::from(settings.router_learning_seconds)),
+                    );
+                    if let Some(router) = found {
+                        storage.timeline.push(
+                            captured,
+                            TimelineEvent::RogueRouter {
+                                mac: router.mac.clone(),
+                                ip: router.ip,
+                                known: router.known.clone(),
+                            },
+                        );
+                        alerts.rogue_routers.push(router);
+                    }
+                }
+                push_value(
+                    &mut storage.inspector.icmpv6,
+                    (value, locator.clone()),
+                    stamp,
+                    limit,
+                    frames_len,
+                )
+            },
+            ProtocolDto::TCP(value) => {
+                // Frames without IP have no flow
+                let window = locator.ip().map(|(source, destination)| {
+                    storage.inspector.tcp_flows.observe(
+                        &value,
+                        (
+                            SocketAddr::new(source, value.port_source),
+                            SocketAddr::new(destination, value.port_destination),
+                        ),
+                        captured,
+                    )
+                });
+                push_value(
+                    &mut storage.inspector.tcp,
+                    (
+                        PortDto::from_tcp(value, window, &storage.lookup),
+                        locator.clone(),
+                    ),
+                    stamp,
+                    limit,
+                    frames_len,
+                )
+            },
+            ProtocolDto::UDP(value) => {
+                ports = Some((value.port_source, value.port_destination));
+                push_value(
+                    &mut storage.inspector.udp,
+                    (PortDto::from_udp(value, &storage.lookup), locator.clone()),
+                    stamp,
+                    limit,
+                    frames_len,
+                )
+            },
         }
     }
 
-    // Pushing ethernet
-    push_value(
-        &mut ctx.net_storage.inspector.ethernet,
-        locator,
-        limit,
-        frames_len,
-    );
+    let locator_source_ipv4 = locator.ipv4.map(|(source, _)| source);
+
+    // Pushing datalink
+    match wireless {
+        Some(wireless) => push_value(
+            &mut storage.inspector.ieee80211,
+            (wireless, locator),
+            stamp,
+            limit,
+            frames_len,
+        ),
+        None => push_value(
+            &mut storage.inspector.ethernet,
+            locator,
+            stamp,
+            limit,
+            frames_len,
+        ),
+    }
 
     // Pushing sample to speed plot (not pushed as sent or received yet)
     if let Some(sample) = sample {
-        ctx.net_storage
+        storage
             .speed
             .load_complete_sample(SampleDirection::Throughput(sample));
     }
 
+    if let Some((ip, flow)) = remote {
+        storage.endpoints.observe(ip, flow, wire_bytes, captured);
+    }
+
     // Adding info if device exists, adding device if not
     if let Some(mut template) = device {
-        if let Some(device) = ctx.net_storage.devices.find_by_mac(&template.mac) {
+        if let Some(device) = storage.devices.find_by_mac(&template.mac) {
+            device.last_seen = captured;
             for ip in template.ip.iter() {
                 if !device.ip.contains(ip) {
                     device.ip.push(*ip);
@@ -198,50 +493,195 @@ pub fn metadata(
                 }
             }
         } else if !template.mac.is_multicast() && !template.mac.is_broadcast() {
-            template.vendor = ctx.net_storage.lookup.find_vendor(&template.mac);
-            ctx.net_storage.devices.list.push(template);
+            template.vendor = storage.lookup.find_vendor(&template.mac);
+            // Hidden device is known, it's only back after the clear of the list
+            if !storage.devices.is_hidden(&template.mac) {
+                storage.timeline.push(
+                    captured,
+                    TimelineEvent::NewDevice {
+                        mac: template.mac.clone(),
+                        ip: template.ip.first().copied(),
+                    },
+                );
+            }
+            storage.devices.push(template);
         }
     }
 
-    Ok(())
+    // Enriching devices with NetBIOS names. Names without address belong to the sender
+    for (hostname, ip) in hostnames {
+        let Some(ip) = ip.or(locator_source_ipv4) else {
+            continue;
+        };
+        if let Some(device) = storage.devices.find_by_ipv4(&ip) {
+            device.hostname = Some(hostname);
+        }
+    }
+
+    Ok(alerts)
+}
+
+// Sample is left for the next IP layer (tunnels), if the direction isn't determined
+fn load_directed_sample(
+    speed: &mut SpeedData, sample: &mut Option<Sample>, hint: FrameDirection,
+    is_source_local: bool, is_destination_local: bool,
+) {
+    let Some(value) = sample.take() else {
+        return;
+    };
+    match SampleDirection::resolve(value, hint, is_source_local, is_destination_local) {
+        SampleDirection::Throughput(value) => *sample = Some(value),
+        directed => speed.load_complete_sample(directed),
+    }
 }
 
-pub fn header(ctx: &mut Context, header: FrameHeader) -> Result<(), ProcessingError> {
+pub fn header(
+    storage: &mut NetStorage, header: FrameHeader,
+) -> Result<(), ProcessingError> {
     let sample = Sample::try_from(&header)?;
-    ctx.net_storage.speed.load_raw_sample(sample);
+    storage.speed.load_raw_sample(sample);
 
     Ok(())
 }
 
-pub fn raw(ctx: &mut Context, raw: OwnedFrame) -> Result<(), ProcessingError> {
+pub fn raw(
+    storage: &mut NetStorage, settings: &ProcessingSettings,
+    link_type: Option<pcap::Linktype>, raw: OwnedFrame,
+) -> Result<(), ProcessingError> {
     let sample = Sample::try_from(&raw.header)?;
-    ctx.net_storage.speed.load_raw_sample(sample);
+    storage.speed.load_raw_sample(sample);
 
-    if !ctx.client_settings.unparsed_frames_drop {
-        ctx.net_storage.raw.add(raw);
+    if !settings.unparsed_frames_drop {
+        storage.raw.add(raw, link_type);
     }
     // Else - pass
 
     Ok(())
 }
 
-#[derive(Clone, Debug)]
+/// Client settings, that the frames are processed with.
+#[derive(Clone, Debug, Default)]
+pub struct ProcessingSettings {
+    pub arp_reply_window_ms: u32,
+    pub ip_conflict_window_seconds: u32,
+    pub parsed_frames_limit: Option<usize>,
+    pub router_learning_seconds: u32,
+    pub unparsed_frames_drop: bool,
+}
+
+impl From<&ClientSettings> for ProcessingSettings {
+    fn from(settings: &ClientSettings) -> Self {
+        Self {
+            arp_reply_window_ms: settings.arp_reply_window_ms,
+            ip_conflict_window_seconds: settings.ip_conflict_window_seconds,
+            parsed_frames_limit: settings.parsed_frames_limit,
+            router_learning_seconds: settings.router_learning_seconds,
+            unparsed_frames_drop: settings.unparsed_frames_drop,
+        }
+    }
+}
+
+/// Findings of the frame, that are shown to the user & sent as the notifications.
+#[derive(Debug, Default)]
+pub struct FrameAlerts {
+    pub ip_conflicts: Vec<IpConflict>,
+    pub rogue_routers: Vec<RogueRouter>,
+}
+
+#[derive(Clone, Debug, Serialize)]
 pub struct Locator {
     pub mac: (MacAddress, MacAddress),
+    // Innermost IP layer, if the packet is tunneled
     pub ipv4: Option<(Ipv4Addr, Ipv4Addr)>,
     pub ipv6: Option<(Ipv6Addr, Ipv6Addr)>,
+    pub tunnel: Option<Tunnel>,
+}
+
+/// Outermost IP layer of the tunneled packet (IPIP, 6in4, 4in6).
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Tunnel {
+    pub outer: (IpAddr, IpAddr),
+    pub encapsulation: Encapsulation,
+}
+
+#[derive(Clone, Copy, Debug, Display, PartialEq, Serialize)]
+pub enum Encapsulation {
+    #[strum(to_string = "IPIP")]
+    IPv4InIPv4,
+    #[strum(to_string = "6in4")]
+    IPv6InIPv4,
+    #[strum(to_string = "4in6")]
+    IPv4InIPv6,
+    #[strum(to_string = "IPv6-in-IPv6")]
+    IPv6InIPv6,
+}
+
+impl Encapsulation {
+    fn new(outer: &IpAddr, inner: &IpAddr) -> Self {
+        match (outer, inner) {
+            (IpAddr::V4(_), IpAddr::V4(_)) => Self::IPv4InIPv4,
+            (IpAddr::V4(_), IpAddr::V6(_)) => Self::IPv6InIPv4,
+            (IpAddr::V6(_), IpAddr::V4(_)) => Self::IPv4InIPv6,
+            (IpAddr::V6(_), IpAddr::V6(_)) => Self::IPv6InIPv6,
+        }
+    }
 }
 
 impl Locator {
+    /// Sender & target IP, "outer → inner" for the tunneled packets.
     pub fn ip_to_string(&self) -> (String, String) {
-        let (source_ip, target_ip) = match self.ipv4 {
-            Some(addresses) => (addresses.0.to_string(), addresses.1.to_string()),
-            None => match self.ipv6 {
-                Some(addresses) => (addresses.0.to_string(), addresses.1.to_string()),
-                None => ("-".to_string(), "-".to_string()),
-            },
+        let Some((source, destination)) = self.ip() else {
+            return ("-".to_string(), "-".to_string());
+        };
+        match &self.tunnel {
+            Some(tunnel) => (
+                format!("{} → {source}", tunnel.outer.0),
+                format!("{} → {destination}", tunnel.outer.1),
+            ),
+            None => (source.to_string(), destination.to_string()),
+        }
+    }
+
+    pub fn push_ipv4(&mut self, source: Ipv4Addr, destination: Ipv4Addr) {
+        self.enter(IpAddr::V4(source));
+        self.ipv4 = Some((source, destination));
+    }
+
+    pub fn push_ipv6(&mut self, source: Ipv6Addr, destination: Ipv6Addr) {
+        self.enter(IpAddr::V6(source));
+        self.ipv6 = Some((source, destination));
+    }
+
+    // Nested IP layer: the outermost addresses are kept, the middle ones are dropped
+    fn enter(&mut self, inner: IpAddr) {
+        let Some(outer) = self.ip() else {
+            return;
         };
-        (source_ip, target_ip)
+        if self.tunnel.is_none() {
+            self.tunnel = Some(Tunnel {
+                outer,
+                encapsulation: Encapsulation::new(&outer.0, &inner),
+            });
+        }
+        self.ipv4 = None;
+        self.ipv6 = None;
+    }
+
+    pub fn ip(&self) -> Option<(IpAddr, IpAddr)> {
+        match (self.ipv4, self.ipv6) {
+            (Some((source, destination)), _) => {
+                Some((IpAddr::V4(source), IpAddr::V4(destination)))
+            },
+            (None, Some((source, destination))) => {
+                Some((IpAddr::V6(source), IpAddr::V6(destination)))
+            },
+            (None, None) => None,
+        }
+    }
+
+    /// Endpoints of the frame, that don't depend on its direction.
+    pub fn conversation(&self) -> (Conversation, Direction) {
+        Conversation::new((&self.mac.0, &self.mac.1), self.ip())
     }
 
     pub fn mac_to_string(&self, aliases: &DeviceAliases) -> (String, String) {
@@ -258,41 +698,58 @@ impl Locator {
     }
 }
 
-#[derive(Clone, Debug)]
+/// 802.11 frame with the radio information, if captured in monitor mode.
+#[derive(Clone, Debug, Serialize)]
+pub struct WirelessDto {
+    pub frame: IEEE80211Dto,
+    pub radio: Option<RadiotapDto>,
+}
+
+// Frame detail: the datalink layer is kept only by the addresses
+impl FieldIter for Locator {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let (source_mac, destination_mac) = &self.mac;
+
+        vec![
+            ("source_mac", source_mac.to_string()),
+            ("destination_mac", destination_mac.to_string()),
+        ]
+    }
+}
+
+impl FieldIter for WirelessDto {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let Self { frame, radio } = self;
+
+        let mut values = frame.fields();
+        if let Some(radio) = radio {
+            values.extend(radio.fields());
+        }
+        values
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
 pub struct PortDto {
     pub port_source: u16,
     pub port_destination: u16,
     pub possible_application: String,
+    pub checksum_status: ChecksumStatus,
+    // Only UDP length field may disagree with the IP payload
+    pub length_mismatch: Option<LengthMismatch>,
+    // Only TCP segments have it
+    pub window: Option<TcpWindow>,
 }
 
 impl PortDto {
     pub fn find_app(ports: (u16, u16), lookup: &Lookup, protocol: ProtocolId) -> String {
-        if let Some(info_vec) = lookup.find_port(&ports.0) {
-            if let Some(app) = Self::find_app_by_protocol(info_vec, protocol) {
-                return app;
-            }
-        }
-
-        if let Some(info_vec) = lookup.find_port(&ports.1) {
-            if let Some(app) = Self::find_app_by_protocol(info_vec, protocol) {
-                return app;
-            }
-        }
-
-        String::from("-")
-    }
-
-    fn find_app_by_protocol(vec: &[PortInfo], protocol: ProtocolId) -> Option<String> {
-        vec.iter()
-            .find(|info| {
-                info.transport_protocol
-                    .to_ascii_lowercase()
-                    .eq(&protocol.to_string().to_ascii_lowercase())
-            })
-            .map(|info| info.service_name.clone())
+        lookup
+            .find_service(&ports.0, protocol)
+            .or_else(|| lookup.find_service(&ports.1, protocol))
+            .unwrap_or_else(|| String::from("-"))
     }
 
-    fn from_tcp(value: TcpDto, lookup: &Lookup) -> Self {
+    fn from_tcp(value: TcpDto, window: Option<TcpWindow>, lookup: &Lookup) -> Self {
         Self {
             port_source: value.port_source,
             port_destination: value.port_destination,
@@ -301,6 +758,10 @@ impl PortDto {
                 lookup,
                 ProtocolId::TCP,
             ),
+            checksum_status: value.checksum_status,
+            length_mismatch: None,
+            // Segment without the flow gets the unknown window, not the UDP columns
+            window: Some(window.unwrap_or_default()),
         }
     }
 
@@ -313,17 +774,63 @@ impl PortDto {
                 lookup,
                 ProtocolId::UDP,
             ),
+            checksum_status: value.checksum_status,
+            length_mismatch: value.length_mismatch,
+            window: None,
         }
     }
 }
 
-fn push_value<T>(vec: &mut Vec<T>, value: T, limit: &Option<usize>, frames_len: &usize) {
-    if let Some(limit) = limit {
-        if frames_len < limit {
-            vec.push(value);
+impl FieldIter for PortDto {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let Self {
+            port_source,
+            port_destination,
+            possible_application,
+            checksum_status,
+            length_mismatch,
+            window,
+        } = self;
+
+        let mut values = vec![
+            ("port_source", port_source.to_string()),
+            ("port_destination", port_destination.to_string()),
+            ("possible_application", possible_application.clone()),
+            ("checksum_status", checksum_status.to_string()),
+        ];
+        match window {
+            Some(window) => values.extend([
+                ("effective_window", fields::optional(&window.effective)),
+                (
+                    "maximum_segment_size",
+                    fields::optional(&window.maximum_segment_size),
+                ),
+            ]),
+            None => values.push(("length_mismatch", fields::optional(length_mismatch))),
         }
-    } else {
-        vec.push(value);
+
+        values
+    }
+}
+
+// Capture time, id of the frame & whether it's cut off by the snapshot length
+#[derive(Clone, Copy)]
+struct FrameStamp {
+    captured: DateTime<Local>,
+    frame: FrameId,
+    is_truncated: bool,
+}
+
+fn push_value<T: EstimatedSize>(
+    vec: &mut Records<T>, value: T, stamp: FrameStamp, limit: &Option<usize>,
+    frames_len: &usize,
+) {
+    if limit.is_some_and(|limit| *frames_len >= limit) {
+        return;
+    }
+    match stamp.is_truncated {
+        true => vec.push_truncated(value, stamp.captured, stamp.frame),
+        false => vec.push(value, stamp.captured, stamp.frame),
     }
 }
 
@@ -335,3 +842,333 @@ pub enum ProcessingError {
     #[error("Empty layers packet got to full metadata processing.")]
     DatalinkNotFirst,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::inspector::ProtocolsRegistered;
+    use crate::net::notes::RecordId;
+    use crate::net::raw::RawStorage;
+    use crate::notifications::Severity;
+    use dpi::protocols::ethernet::EthernetDto;
+    use dpi::protocols::icmpv4;
+    use dpi::protocols::icmpv4::ICMPv4Dto;
+    use dpi::protocols::icmpv6::{
+        ICMPv6Dto, MessageType, PrefixInformation, RouterAdvertisement,
+    };
+    use dpi::protocols::ipv4::IPv4Dto;
+    use dpi::protocols::ipv6::IPv6Dto;
+
+    const ROUTER: [u8; 6] = [0x00, 0x1a, 0x8c, 0x15, 0xf9, 0x80];
+    const LAPTOP: [u8; 6] = [0x40, 0x61, 0x86, 0x9a, 0xf1, 0xf5];
+    const MDNS: [u8; 6] = [0x01, 0x00, 0x5e, 0x00, 0x00, 0xfb];
+    const BROADCAST: [u8; 6] = [0xff; 6];
+
+    fn storage() -> NetStorage {
+        NetStorage {
+            capture: Default::default(),
+            conflicts: Default::default(),
+            devices: Default::default(),
+            dhcp_servers: Default::default(),
+            endpoints: Default::default(),
+            inspector: Default::default(),
+            lookup: Lookup::empty(),
+            notes: Default::default(),
+            nxdomain_bursts: Default::default(),
+            raw: RawStorage::new(None, None),
+            routers: Default::default(),
+            sampling: Default::default(),
+            snapshots: vec![],
+            speed: Default::default(),
+            timeline: Default::default(),
+        }
+    }
+
+    fn frame(layers: Vec<ProtocolDto>) -> FrameMetadataDto {
+        FrameMetadataDto {
+            header: FrameHeader {
+                tv_sec: 1_700_000_000,
+                tv_usec: 0,
+                caplen: 60,
+                len: 60,
+            },
+            layers,
+            direction: FrameDirection::Unknown,
+            is_truncated: false,
+        }
+    }
+
+    fn ethernet(source: [u8; 6], destination: [u8; 6]) -> ProtocolDto {
+        ProtocolDto::Ethernet(EthernetDto {
+            destination_mac: MacAddress::from(destination),
+            source_mac: MacAddress::from(source),
+        })
+    }
+
+    fn ipv4(source: [u8; 4], destination: [u8; 4]) -> ProtocolDto {
+        ProtocolDto::IPv4(IPv4Dto {
+            address_source: Ipv4Addr::from(source),
+            address_destination: Ipv4Addr::from(destination),
+            time_to_live: 64,
+            checksum_status: ChecksumStatus::Valid,
+            options: vec![],
+        })
+    }
+
+    #[test]
+    fn test_private_source_is_sent() {
+        let mut storage = storage();
+        let frame = frame(vec![
+            ethernet(LAPTOP, ROUTER),
+            ipv4([192, 168, 1, 10], [8, 8, 8, 8]),
+        ]);
+        let alerts = metadata(&mut storage, &Default::default(), frame).unwrap();
+        assert!(alerts.ip_conflicts.is_empty());
+
+        assert_eq!(storage.speed.samples_amount(), (1, 1, 0));
+        assert_eq!(storage.inspector.ethernet.len(), 1);
+        assert_eq!(storage.inspector.ipv4.len(), 1);
+
+        let device = storage
+            .devices
+            .find_by_mac(&MacAddress::from(LAPTOP))
+            .unwrap();
+        assert_eq!(device.ip, vec![Ipv4Addr::new(192, 168, 1, 10)]);
+        assert_eq!(storage.devices.list().len(), 1);
+    }
+
+    #[test]
+    fn test_public_ends_are_throughput_only() {
+        let mut storage = storage();
+        let frame = frame(vec![
+            ethernet(ROUTER, LAPTOP),
+            ipv4([93, 184, 216, 34], [8, 8, 8, 8]),
+        ]);
+        metadata(&mut storage, &Default::default(), frame).unwrap();
+
+        assert_eq!(storage.speed.samples_amount(), (1, 0, 0));
+        assert!(storage.devices.list().is_empty());
+    }
+
+    #[test]
+    fn test_parsed_frames_limit() {
+        let mut storage = storage();
+        let settings = ProcessingSettings {
+            parsed_frames_limit: Some(1),
+            ..Default::default()
+        };
+        for _ in 0..3 {
+            let frame = frame(vec![
+                ethernet(LAPTOP, ROUTER),
+                ipv4([192, 168, 1, 10], [8, 8, 8, 8]),
+            ]);
+            metadata(&mut storage, &settings, frame).unwrap();
+        }
+
+        // Records stop at the limit, the speed is counted anyway
+        assert_eq!(storage.inspector.ethernet.len(), 1);
+        assert_eq!(storage.inspector.ipv4.len(), 1);
+        assert_eq!(storage.speed.samples_amount(), (3, 3, 0));
+    }
+
+    #[test]
+    fn test_truncated_frame_is_marked() {
+        let mut storage = storage();
+        let mut frame = frame(vec![
+            ethernet(LAPTOP, ROUTER),
+            ipv4([192, 168, 1, 10], [93, 184, 216, 34]),
+        ]);
+        frame.header.len = 1514;
+        frame.is_truncated = true;
+        metadata(&mut storage, &Default::default(), frame).unwrap();
+
+        assert!(storage.inspector.ethernet.is_truncated(0));
+        assert!(storage.inspector.ipv4.is_truncated(0));
+        // Remote host is counted by the length on the wire
+        let host = &storage.endpoints.top(None)[0];
+        assert_eq!(host.total_bytes(), 1514);
+    }
+
+    #[test]
+    fn test_frame_layers_shared() {
+        let mut storage = storage();
+        for destination in [[8, 8, 8, 8], [1, 1, 1, 1]] {
+            let frame = frame(vec![
+                ethernet(LAPTOP, ROUTER),
+                ipv4([192, 168, 1, 10], destination),
+            ]);
+            metadata(&mut storage, &Default::default(), frame).unwrap();
+        }
+
+        // IP record of the second frame leads to its Ethernet record
+        let id = RecordId {
+            protocol: ProtocolsRegistered::IPv4,
+            generation: storage.inspector.ipv4.generation(),
+            index: 1,
+        };
+        let frame = storage.inspector.frame_of(&id).unwrap();
+        let layers = storage.inspector.frame_layers(frame);
+        let stack: Vec<_> = layers
+            .iter()
+            .map(|layer| (layer.protocol, layer.index))
+            .collect();
+        assert_eq!(
+            stack,
+            [
+                (ProtocolsRegistered::Ethernet, 1),
+                (ProtocolsRegistered::IPv4, 1)
+            ]
+        );
+        assert!(
+            layers[1]
+                .fields
+                .contains(&("address_destination", "1.1.1.1".to_string()))
+        );
+
+        // Cleared record has no frame anymore
+        storage.inspector.ipv4.clear();
+        assert_eq!(storage.inspector.frame_of(&id), None);
+    }
+
+    #[test]
+    fn test_rogue_router_advertisement() {
+        const ROGUE: [u8; 6] = [0x02, 0x00, 0x00, 0x00, 0x00, 0x66];
+        let advertisement = |source: [u8; 6], tv_sec: i64, prefix: &str| {
+            let mut frame = frame(vec![
+                ethernet(source, [0x33, 0x33, 0x00, 0x00, 0x00, 0x01]),
+                ProtocolDto::IPv6(IPv6Dto {
+                    address_source: Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1),
+                    address_destination: Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0, 1),
+                    hop_limit: 255,
+                    extension_headers: vec![],
+                }),
+                ProtocolDto::ICMPv6(ICMPv6Dto {
+                    message_type: MessageType::RouterAdvertisement,
+                    code: 0,
+                    router_advertisement: Some(RouterAdvertisement {
+                        router_lifetime: 1800,
+                        link_address: Some(MacAddress::from(source)),
+                        prefixes: vec![PrefixInformation {
+                            prefix: prefix.parse().unwrap(),
+                            length: 64,
+                            valid_lifetime: 2592000,
+                            preferred_lifetime: 604800,
+                        }],
+                    }),
+                }),
+            ]);
+            frame.header.tv_sec = tv_sec;
+            frame
+        };
+
+        let mut storage = storage();
+        let settings = ProcessingSettings {
+            router_learning_seconds: 60,
+            ..Default::default()
+        };
+        let legit = advertisement(ROUTER, 1_700_000_000, "2001:db8:1::");
+        let alerts = metadata(&mut storage, &settings, legit).unwrap();
+        assert!(alerts.rogue_routers.is_empty());
+
+        // Rogue one appears after the learning period
+        let rogue = advertisement(ROGUE, 1_700_000_600, "2001:db8:66::");
+        let alerts = metadata(&mut storage, &settings, rogue).unwrap();
+        assert_eq!(alerts.rogue_routers.len(), 1);
+        assert_eq!(alerts.rogue_routers[0].mac, MacAddress::from(ROGUE));
+        assert_eq!(
+            alerts.rogue_routers[0].known,
+            vec![MacAddress::from(ROUTER)]
+        );
+        assert!(matches!(
+            storage
+                .timeline
+                .filtered(Severity::Critical)
+                .next()
+                .map(|entry| &entry.event),
+            Some(TimelineEvent::RogueRouter { .. })
+        ));
+
+        let legit = advertisement(ROUTER, 1_700_000_700, "2001:db8:1::");
+        let alerts = metadata(&mut storage, &settings, legit).unwrap();
+        assert!(alerts.rogue_routers.is_empty());
+        assert_eq!(storage.routers.sources().len(), 2);
+        assert_eq!(storage.inspector.icmpv6.len(), 3);
+    }
+
+    #[test]
+    fn test_group_addresses_are_not_devices() {
+        let mut storage = storage();
+        let multicast = frame(vec![
+            ethernet(MDNS, ROUTER),
+            ipv4([192, 168, 1, 10], [8, 8, 8, 8]),
+        ]);
+        metadata(&mut storage, &Default::default(), multicast).unwrap();
+
+        let broadcast = frame(vec![
+            ethernet(ROUTER, BROADCAST),
+            ipv4([93, 184, 216, 34], [192, 168, 1, 255]),
+        ]);
+        metadata(&mut storage, &Default::default(), broadcast).unwrap();
+
+        assert!(storage.devices.list().is_empty());
+    }
+
+    #[test]
+    fn test_ipip_keeps_outer_addresses() {
+        let mut storage = storage();
+        let frame = frame(vec![
+            ethernet(ROUTER, LAPTOP),
+            ipv4([10, 0, 0, 1], [10, 0, 0, 2]),
+            ipv4([192, 168, 1, 10], [192, 168, 2, 10]),
+            ProtocolDto::ICMPv4(ICMPv4Dto {
+                message_type: icmpv4::MessageType::Echo,
+                code: 0,
+            }),
+        ]);
+        metadata(&mut storage, &Default::default(), frame).unwrap();
+
+        let (_, locator) = &storage.inspector.icmpv4[0];
+        let inner = (
+            Ipv4Addr::new(192, 168, 1, 10),
+            Ipv4Addr::new(192, 168, 2, 10),
+        );
+        assert_eq!(locator.ipv4, Some(inner));
+        assert_eq!(
+            locator.tunnel,
+            Some(Tunnel {
+                outer: (
+                    IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)),
+                    IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2))
+                ),
+                encapsulation: Encapsulation::IPv4InIPv4,
+            })
+        );
+        assert_eq!(
+            locator.ip_to_string(),
+            (
+                "10.0.0.1 → 192.168.1.10".to_string(),
+                "10.0.0.2 → 192.168.2.10".to_string()
+            )
+        );
+
+        // Outer layer isn't tunneled itself
+        assert_eq!(storage.inspector.ipv4.len(), 2);
+        assert_eq!(storage.inspector.ipv4[0].1.tunnel, None);
+        // Inner addresses don't belong to the MAC of the tunnel endpoint
+        assert!(
+            storage
+                .devices
+                .list()
+                .iter()
+                .all(|device| !device.ip.contains(&inner.0))
+        );
+    }
+
+    #[test]
+    fn test_datalink_not_first() {
+        let mut storage = storage();
+        let frame = frame(vec![ethernet(LAPTOP, ROUTER), ethernet(LAPTOP, ROUTER)]);
+        let result = metadata(&mut storage, &Default::default(), frame);
+        assert!(matches!(result, Err(ProcessingError::DatalinkNotFirst)));
+    }
+}
@@ -0,0 +1,211 @@
+// What the connection has agreed on & how much it carries. Bytes are counted on the
+// stream itself, so the handshake, frames of the WebSocket & the compressed payloads
+// are counted as they're on the wire. Counters are shared with the UI by the atomics.
+
+use chrono::{DateTime, Local};
+use common::auth;
+use common::messages::PROTOCOL_VERSION;
+use std::io::{Read, Write};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+pub const CLIENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+// Level isn't chosen, the default one of zlib is used by both sides
+const COMPRESSION_ALGORITHM: &str = "zlib";
+
+#[derive(Debug, Default)]
+pub struct LinkCounters {
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    messages_sent: AtomicU64,
+    messages_received: AtomicU64,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct LinkStats {
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    pub messages_sent: u64,
+    pub messages_received: u64,
+}
+
+impl LinkCounters {
+    pub fn message_sent(&self) {
+        self.messages_sent.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn message_received(&self) {
+        self.messages_received.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> LinkStats {
+        LinkStats {
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            messages_sent: self.messages_sent.load(Ordering::Relaxed),
+            messages_received: self.messages_received.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Stream, that counts the bytes read & written through it.
+pub struct Counted<S> {
+    inner: S,
+    counters: Arc<LinkCounters>,
+}
+
+impl<S> Counted<S> {
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            counters: Arc::default(),
+        }
+    }
+
+    pub fn counters(&self) -> &Arc<LinkCounters> {
+        &self.counters
+    }
+
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+}
+
+impl<S: Read> Read for Counted<S> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let amount = self.inner.read(buf)?;
+        self.counters
+            .bytes_received
+            .fetch_add(amount as u64, Ordering::Relaxed);
+        Ok(amount)
+    }
+}
+
+impl<S: Write> Write for Counted<S> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let amount = self.inner.write(buf)?;
+        self.counters
+            .bytes_sent
+            .fetch_add(amount as u64, Ordering::Relaxed);
+        Ok(amount)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Parameters of the handshake. Server accepts only the same compression,
+/// so the requested parameters are the negotiated ones.
+#[derive(Debug, Clone)]
+pub struct LinkInfo {
+    pub address: SocketAddr,
+    pub compression: bool,
+    pub heartbeat: Duration,
+    // Authenticated by the session token of the previous connection, not the password
+    pub is_session_resumed: bool,
+    pub counters: Arc<LinkCounters>,
+}
+
+impl LinkInfo {
+    pub fn compression(&self) -> String {
+        match self.compression {
+            true => format!(
+                "{COMPRESSION_ALGORITHM} ({})",
+                t!("Modal.Connection.Default")
+            ),
+            false => t!("Modal.Connection.Off").to_string(),
+        }
+    }
+
+    // JSON messages, raw frames are binary if the feature is supported
+    pub fn message_format(&self) -> String {
+        let features = auth::features::header_value();
+        format!("JSON, {features}")
+    }
+
+    pub fn authentication(&self) -> String {
+        match self.is_session_resumed {
+            true => t!("Modal.Connection.Authentication.Token").to_string(),
+            false => t!("Modal.Connection.Authentication.Password").to_string(),
+        }
+    }
+
+    /// Text block for the bug reports. Labels aren't localized, so it's read by anyone.
+    /// <br> Uptime is known only while connected.
+    pub fn diagnostics(&self, state: &str, since: Option<DateTime<Local>>) -> String {
+        let stats = self.counters.stats();
+        let uptime = since.map_or("-".to_string(), |since| {
+            format!("{} s", (Local::now() - since).num_seconds())
+        });
+        let compression = match self.compression {
+            true => COMPRESSION_ALGORITHM,
+            false => "off",
+        };
+
+        [
+            format!("Client version: {CLIENT_VERSION}"),
+            format!("Protocol version: {PROTOCOL_VERSION}"),
+            format!("OS: {} ({})", std::env::consts::OS, std::env::consts::ARCH),
+            format!("State: {state}"),
+            format!("Server: {}", self.address),
+            format!("Compression: {compression}"),
+            format!("Format: {}", self.message_format()),
+            format!("Heartbeat: {} s", self.heartbeat.as_secs()),
+            format!("Session resumed: {}", self.is_session_resumed),
+            format!("Uptime: {uptime}"),
+            format!(
+                "Messages: {} sent, {} received",
+                stats.messages_sent, stats.messages_received
+            ),
+            format!(
+                "Bytes: {} sent, {} received",
+                stats.bytes_sent, stats.bytes_received
+            ),
+        ]
+        .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_counted_stream() {
+        let mut stream = Counted::new(Cursor::new(vec![0u8; 10]));
+        let mut buffer = [0u8; 4];
+        stream.read_exact(&mut buffer).unwrap();
+        stream.write_all(b"abcdef").unwrap();
+        stream.counters().message_sent();
+
+        let stats = stream.counters().stats();
+        assert_eq!(stats.bytes_received, 4);
+        assert_eq!(stats.bytes_sent, 6);
+        assert_eq!(stats.messages_sent, 1);
+        assert_eq!(stats.messages_received, 0);
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let counters = Arc::new(LinkCounters::default());
+        counters.message_received();
+        let info = LinkInfo {
+            address: SocketAddr::from(([192, 168, 0, 10], 8080)),
+            compression: true,
+            heartbeat: Duration::from_secs(15),
+            is_session_resumed: false,
+            counters,
+        };
+
+        let text = info.diagnostics("Connected", None);
+        assert!(text.contains(&format!("Client version: {CLIENT_VERSION}")));
+        assert!(text.contains("Server: 192.168.0.10:8080"));
+        assert!(text.contains("Compression: zlib"));
+        assert!(text.contains("Uptime: -"));
+        assert!(text.contains("Messages: 0 sent, 1 received"));
+    }
+}
@@ -0,0 +1,134 @@
+use common::auth::{AUTH_HEADER, AUTH_NONCE_HEADER, AUTH_PARAMS_HEADER};
+use common::binary;
+use common::compression::{compress, compress_bytes, decompress};
+use common::cryptography;
+use common::messages::{Request, Response};
+use dpi::dto::frame::FrameType;
+use std::net::{SocketAddr, TcpListener};
+use std::thread;
+use std::thread::JoinHandle;
+use tungstenite::handshake::server;
+use tungstenite::http::StatusCode;
+use tungstenite::{Bytes, Message, WebSocket};
+
+pub const PASSWORD: &str = "secret";
+// Challenge & its answer
+const HANDSHAKES: usize = 2;
+
+/// In-process WS server on the localhost, that the client is tested against. <br>
+/// It challenges the client by the [`PASSWORD`] & accepts a single connection,
+/// answers the first request with the canned responses
+/// and collects the requests, until the client closes the connection.
+pub struct MockServer {
+    pub address: SocketAddr,
+    handle: JoinHandle<Vec<Request>>,
+}
+
+impl MockServer {
+    pub fn spawn(responses: Vec<Response>, compression: bool) -> std::io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let address = listener.local_addr()?;
+
+        let handle = thread::spawn(move || {
+            let mut requests = vec![];
+            let Some(mut socket) = Self::authenticate(&listener) else {
+                return requests;
+            };
+
+            let mut responses = Some(responses);
+            // Close frame of the client is answered by the read itself
+            while let Ok(message) = socket.read() {
+                let text = match message {
+                    Message::Text(text) => text.as_str().to_string(),
+                    Message::Binary(bytes) => match decompress(&bytes) {
+                        Ok(text) => text,
+                        Err(_) => continue,
+                    },
+                    _ => continue,
+                };
+                if let Ok(request) = serde_json::from_str::<Request>(&text) {
+                    requests.push(request);
+                }
+
+                for response in responses.take().unwrap_or_default() {
+                    let Ok(message) = Self::encode(&response, compression) else {
+                        continue;
+                    };
+                    if socket.send(message).is_err() {
+                        return requests;
+                    }
+                }
+            }
+            requests
+        });
+
+        Ok(Self { address, handle })
+    }
+
+    // Compression header isn't checked
+    fn authenticate(listener: &TcpListener) -> Option<WebSocket<std::net::TcpStream>> {
+        let hash = cryptography::hash_password(PASSWORD).ok()?;
+        let params = cryptography::challenge_params(&hash).ok()?;
+        let key = cryptography::stored_key(&hash).ok()?;
+        let nonce = cryptography::generate_nonce();
+
+        for _ in 0..HANDSHAKES {
+            let (stream, _) = listener.accept().ok()?;
+            let check = |request: &server::Request, response: server::Response| {
+                let header = |name: &str| {
+                    request
+                        .headers()
+                        .get(name)
+                        .and_then(|value| value.to_str().ok())
+                };
+                let is_signed = match (header(AUTH_NONCE_HEADER), header(AUTH_HEADER)) {
+                    (Some(given), Some(signature)) => {
+                        given == nonce
+                            && cryptography::verify_signature(&key, &nonce, signature)
+                    },
+                    _ => false,
+                };
+                if is_signed {
+                    return Ok(response);
+                }
+                Err(server::Response::builder()
+                    .status(StatusCode::UNAUTHORIZED)
+                    .header(AUTH_NONCE_HEADER, &nonce)
+                    .header(AUTH_PARAMS_HEADER, &params)
+                    .body(None)
+                    .unwrap_or_default())
+            };
+            if let Ok(socket) = tungstenite::accept_hdr(stream, check) {
+                return Some(socket);
+            }
+        }
+        None
+    }
+
+    // Returns the requests, that are received by the server
+    pub fn join(self) -> Vec<Request> {
+        self.handle.join().unwrap_or_default()
+    }
+
+    // Raw frames are binary, the client always tells the support of them
+    fn encode(response: &Response, compression: bool) -> Result<Message, String> {
+        if let Response::Data(FrameType::Raw(frame)) = response {
+            let message = binary::encode(frame);
+            return match compression {
+                true => compress_bytes(&message)
+                    .map(|compressed| Message::Binary(Bytes::from(compressed)))
+                    .map_err(|err| err.to_string()),
+                false => Ok(Message::Binary(Bytes::from(message))),
+            };
+        }
+
+        let serialized =
+            serde_json::to_string(response).map_err(|err| err.to_string())?;
+        if compression {
+            let compressed = compress(&serialized).map_err(|err| err.to_string())?;
+            Ok(Message::Binary(Bytes::from(compressed)))
+        } else {
+            Ok(Message::text(serialized))
+        }
+    }
+}
@@ -0,0 +1,386 @@
+use crate::net::anonymize::Anonymizer;
+use crate::ws;
+use crate::ws::request::UiClientRequest;
+use common::messages::Response;
+use common::recording::{Entry, Player, Recorder, RecordingError};
+use crossbeam::channel::{Receiver, RecvTimeoutError, Sender, TrySendError, unbounded};
+use dpi::dto::frame::{FrameType, LinkType, OwnedFrame};
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use std::{env, thread};
+
+pub const RECORD_ARGUMENT: &str = "--record";
+pub const ANONYMIZE_ARGUMENT: &str = "--anonymize";
+pub const REPLAY_ARGUMENT: &str = "--replay";
+pub const REPLAY_SPEED_ARGUMENT: &str = "--replay-speed";
+pub const DEFAULT_REPLAY_SPEED: f64 = 1.0;
+
+// Recording is flushed, if nothing is received for this time
+const IDLE_FLUSH: Duration = Duration::from_secs(1);
+// Long pauses of the replay are split, so the shutdown isn't delayed
+const SLEEP_STEP: Duration = Duration::from_millis(100);
+
+/// Session mode, set by the CLI arguments. Replay takes precedence over recording.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Mode {
+    Live,
+    // Received responses are written into the file, anonymized with the flag
+    Record { path: PathBuf, anonymize: bool },
+    // Responses are read from the file instead of the server
+    Replay { path: PathBuf, speed: f64 },
+}
+
+impl Mode {
+    pub fn resolve() -> Self {
+        Self::resolve_from(env::args_os().skip(1))
+    }
+
+    fn resolve_from(args: impl IntoIterator<Item = OsString>) -> Self {
+        let args = args
+            .into_iter()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+
+        if let Some(path) = Self::argument(&args, REPLAY_ARGUMENT) {
+            // Speed is a multiplier, e.g. 4 replays the session 4 times faster
+            let speed = Self::argument(&args, REPLAY_SPEED_ARGUMENT)
+                .and_then(|value| value.parse::<f64>().ok())
+                .filter(|speed| speed.is_finite() && *speed > 0.0)
+                .unwrap_or(DEFAULT_REPLAY_SPEED);
+            return Self::Replay {
+                path: PathBuf::from(path),
+                speed,
+            };
+        }
+        if let Some(path) = Self::argument(&args, RECORD_ARGUMENT) {
+            return Self::Record {
+                path: PathBuf::from(path),
+                anonymize: args.iter().any(|arg| arg == ANONYMIZE_ARGUMENT),
+            };
+        }
+
+        Self::Live
+    }
+
+    // Both "--name <value>" and "--name=<value>" are accepted
+    fn argument(args: &[String], name: &str) -> Option<String> {
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if arg == name {
+                return args.next().cloned();
+            }
+            if let Some(value) = arg
+                .strip_prefix(name)
+                .and_then(|rest| rest.strip_prefix('='))
+            {
+                return Some(value.to_string());
+            }
+        }
+
+        None
+    }
+}
+
+/// Starts the thread, that writes the responses received by the WS thread.
+/// <br> It lives through reconnects and stops, when every sender is dropped.
+pub fn start_recorder(
+    path: &Path, anonymizer: Option<Anonymizer>,
+) -> Result<Sender<Entry<Response>>, RecordingError> {
+    let mut recorder = Recorder::create(path)?;
+    let mut anonymizer = anonymizer.map(RecordingAnonymizer::new);
+    let (recording_tx, recording_rx) = unbounded::<Entry<Response>>();
+
+    thread::Builder::new()
+        .name("Recorder-Thread".to_string())
+        .spawn(move || {
+            loop {
+                let result = match recording_rx.recv_timeout(IDLE_FLUSH) {
+                    Ok(entry) => write_entry(&mut recorder, anonymizer.as_mut(), entry),
+                    Err(RecvTimeoutError::Timeout) => recorder.flush(),
+                    Err(RecvTimeoutError::Disconnected) => break,
+                };
+                if let Err(err) = result {
+                    log::error!("Recording: Failed to write. Error: {err}");
+                }
+            }
+            if let Err(err) = recorder.finish() {
+                log::error!("Recording: Failed to finish. Error: {err}");
+            }
+        })?;
+
+    Ok(recording_tx)
+}
+
+// Responses, that can't be anonymized, aren't recorded
+fn write_entry(
+    recorder: &mut Recorder<BufWriter<File>>,
+    anonymizer: Option<&mut RecordingAnonymizer>, mut entry: Entry<Response>,
+) -> Result<(), RecordingError> {
+    if anonymizer.is_some_and(|anonymizer| !anonymizer.apply(&mut entry.response)) {
+        return Ok(());
+    }
+
+    recorder.write_line(&entry.line()?)
+}
+
+/// Addresses of the recorded frames & interfaces are pseudonymized, as in the exports.
+/// The key lives with the recorder, so the pseudonyms are the same after reconnects.
+struct RecordingAnonymizer {
+    anonymizer: Anonymizer,
+    // Of the raw frames, told by the server settings
+    link_type: Option<LinkType>,
+}
+
+impl RecordingAnonymizer {
+    fn new(anonymizer: Anonymizer) -> Self {
+        Self {
+            anonymizer,
+            link_type: None,
+        }
+    }
+
+    // Returns false, if the response can't be anonymized, so it isn't recorded
+    fn apply(&mut self, response: &mut Response) -> bool {
+        match response {
+            Response::Data(FrameType::Metadata(metadata)) => {
+                self.anonymizer.metadata(metadata)
+            },
+            Response::Data(FrameType::Raw(frame)) => {
+                return self.frame(self.link_type, frame);
+            },
+            Response::FailureSamples(samples) => {
+                let link_type = samples.link_type.map(LinkType);
+                for bucket in &mut samples.buckets {
+                    // Counters of the bucket are kept without its samples
+                    bucket
+                        .samples
+                        .retain_mut(|frame| self.frame(link_type, frame));
+                }
+            },
+            Response::InterfaceAddressChanged(change) => {
+                for ip in change.removed.iter_mut().chain(change.added.iter_mut()) {
+                    *ip = self.anonymizer.ip(*ip);
+                }
+            },
+            Response::ServerSettings(settings) => {
+                self.link_type = settings.link_type.map(LinkType);
+                for interface in &mut settings.interfaces_available {
+                    for ip in &mut interface.addresses {
+                        *ip = self.anonymizer.ip(*ip);
+                    }
+                }
+            },
+            _ => {},
+        }
+
+        true
+    }
+
+    // Frames of the other link types can't be anonymized, as in the pcap export
+    fn frame(&mut self, link_type: Option<LinkType>, frame: &mut OwnedFrame) -> bool {
+        match link_type {
+            Some(link_type) if Anonymizer::is_supported(link_type) => {
+                self.anonymizer.frame(link_type, &mut frame.data);
+                true
+            },
+            _ => false,
+        }
+    }
+}
+
+/// Feeds the recorded responses into the same channels as the WS thread does.
+pub struct ReplayHandler {
+    pub player: Player<File>,
+    pub speed: f64,
+    pub shutdown_flag: Arc<AtomicBool>,
+
+    pub data_response_tx: Sender<Response>,
+    pub server_response_tx: Sender<Response>,
+    pub ui_client_requests_rx: Receiver<UiClientRequest>,
+}
+
+impl ReplayHandler {
+    // Returns the error, if the recording is broken. Otherwise, it's finished or stopped.
+    pub fn run(mut self) -> Result<(), RecordingError> {
+        let mut previous: Option<i64> = None;
+
+        for entry in &mut self.player {
+            let entry = entry?;
+
+            // Original pauses between the responses, divided by the speed
+            if let Some(previous) = previous {
+                let milliseconds = entry.received_ms.saturating_sub(previous).max(0);
+                let delay = Duration::try_from_secs_f64(
+                    milliseconds as f64 / 1000.0 / self.speed,
+                )
+                .unwrap_or_default();
+                Self::wait(&self.shutdown_flag, delay);
+            }
+            previous = Some(entry.received_ms);
+            if self.shutdown_flag.load(Ordering::Acquire) {
+                return Ok(());
+            }
+
+            let mut result = ws::route_response(
+                entry.response,
+                &self.data_response_tx,
+                &self.server_response_tx,
+            );
+            // Replay waits for the UI, instead of dropping the frames
+            while let Err(TrySendError::Full(response)) = result {
+                Self::wait(&self.shutdown_flag, SLEEP_STEP);
+                if self.shutdown_flag.load(Ordering::Acquire) {
+                    return Ok(());
+                }
+                result = ws::route_response(
+                    response,
+                    &self.data_response_tx,
+                    &self.server_response_tx,
+                );
+            }
+            // UI context is replaced by the logout
+            if result.is_err() {
+                return Ok(());
+            }
+
+            // There's no server, so the requests are dropped
+            while self.ui_client_requests_rx.try_recv().is_ok() {
+                log::debug!("Replay: Request is ignored, there's no server.");
+            }
+        }
+
+        Ok(())
+    }
+
+    fn wait(shutdown_flag: &AtomicBool, delay: Duration) {
+        let start = Instant::now();
+        while !shutdown_flag.load(Ordering::Acquire) {
+            let remaining = delay.saturating_sub(start.elapsed());
+            if remaining.is_zero() {
+                break;
+            }
+            thread::sleep(remaining.min(SLEEP_STEP));
+        }
+    }
+}
+
+pub fn localize_error(err: &RecordingError) -> String {
+    match err {
+        RecordingError::IncompatibleVersion { found, expected } => t!(
+            "Error.Recording.IncompatibleVersion",
+            "found" = found,
+            "expected" = expected
+        )
+        .to_string(),
+        RecordingError::HeaderMissing => t!("Error.Recording.HeaderMissing").to_string(),
+        RecordingError::IOError(_) | RecordingError::JsonError(_) => {
+            format!("{} {err}", t!("Error.Recording.Failed"))
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::anonymize::AnonymizeSettings;
+    use common::messages::{FailureBucketDto, FailureSamplesDto};
+    use dpi::dto::frame::FrameHeader;
+    use dpi::protocols::ProtocolId;
+
+    fn args(list: &[&str]) -> Vec<OsString> {
+        list.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn test_mode_resolution() {
+        assert_eq!(Mode::resolve_from(args(&[])), Mode::Live);
+        assert_eq!(
+            Mode::resolve_from(args(&["--record", "session.jsonl.gz"])),
+            Mode::Record {
+                path: PathBuf::from("session.jsonl.gz"),
+                anonymize: false
+            }
+        );
+        assert_eq!(
+            Mode::resolve_from(args(&["--anonymize", "--record=session.jsonl.gz"])),
+            Mode::Record {
+                path: PathBuf::from("session.jsonl.gz"),
+                anonymize: true
+            }
+        );
+        assert_eq!(
+            Mode::resolve_from(args(&[
+                "--record=other.jsonl.gz",
+                "--replay",
+                "session.jsonl.gz",
+                "--replay-speed=4"
+            ])),
+            Mode::Replay {
+                path: PathBuf::from("session.jsonl.gz"),
+                speed: 4.0
+            }
+        );
+
+        // Invalid speed falls back to the original one
+        for speed in ["0", "-2", "fast", "inf"] {
+            assert_eq!(
+                Mode::resolve_from(args(&["--replay=a.gz", "--replay-speed", speed])),
+                Mode::Replay {
+                    path: PathBuf::from("a.gz"),
+                    speed: DEFAULT_REPLAY_SPEED
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_anonymized_samples() {
+        let mut anonymizer =
+            RecordingAnonymizer::new(Anonymizer::new(AnonymizeSettings::default()));
+        let original = OwnedFrame {
+            header: FrameHeader::new(14),
+            data: vec![
+                0x00, 0x1A, 0x8C, 0x10, 0xAD, 0x30, 0x00, 0x1E, 0x68, 0x51, 0x4F, 0xA9,
+                0x88, 0xCC,
+            ],
+        };
+        let samples = |link_type: LinkType| {
+            Response::FailureSamples(FailureSamplesDto {
+                link_type: Some(link_type.0),
+                buckets: vec![FailureBucketDto {
+                    protocol: ProtocolId::Ethernet,
+                    reason: "Truncated".to_string(),
+                    failures: 1,
+                    samples: vec![original.clone()],
+                }],
+                unbucketed: 0,
+            })
+        };
+
+        let mut response = samples(LinkType::ETHERNET);
+        assert!(anonymizer.apply(&mut response));
+        let Response::FailureSamples(dto) = response else {
+            panic!();
+        };
+        assert_eq!(dto.buckets[0].samples.len(), 1);
+        assert_ne!(dto.buckets[0].samples[0], original);
+
+        // Addresses of 802.11 aren't replaced, so its samples aren't recorded
+        let mut response = samples(LinkType::IEEE802_11);
+        assert!(anonymizer.apply(&mut response));
+        let Response::FailureSamples(dto) = response else {
+            panic!();
+        };
+        assert!(dto.buckets[0].samples.is_empty());
+        assert_eq!(dto.buckets[0].failures, 1);
+
+        // Link type of the raw frames isn't known yet
+        let mut response = Response::Data(FrameType::Raw(original));
+        assert!(!anonymizer.apply(&mut response));
+    }
+}
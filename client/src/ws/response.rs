@@ -1,7 +1,15 @@
 use crate::context::Context;
+use crate::net::conflicts::IpConflict;
+use crate::net::routers::RogueRouter;
+use crate::net::speed::MarkerKind;
+use crate::notifications::{Alert, AlertRule, Severity};
+use crate::ui::format;
+use crate::ui::modals::message::MessageModal;
 use crate::ws::data;
-use crate::ws::data::ProcessingError;
-use common::messages::Response;
+use crate::ws::data::{FrameAlerts, ProcessingSettings};
+use chrono::Local;
+use common::messages::{Response, ServerError};
+use dpi::dto::fields;
 use dpi::dto::frame::FrameType;
 
 pub fn data(ctx: &mut Context, response: Response) {
@@ -13,37 +21,188 @@ pub fn data(ctx: &mut Context, response: Response) {
         },
     };
 
+    // Frames resumed
+    ctx.net_storage.capture.clear_idle();
+
+    let settings = ProcessingSettings::from(&ctx.client_settings);
+    let storage = &mut ctx.net_storage;
     let process_result = match frame {
-        FrameType::Metadata(metadata) => data::metadata(ctx, metadata),
-        FrameType::Header(header) => data::header(ctx, header),
-        FrameType::Raw(frame) => data::raw(ctx, frame),
+        FrameType::Metadata(metadata) => data::metadata(storage, &settings, metadata),
+        FrameType::Header(header) => {
+            data::header(storage, header).map(|()| FrameAlerts::default())
+        },
+        FrameType::Raw(frame) => {
+            let link_type = ctx.settings_server.link_type;
+            data::raw(storage, &settings, link_type, frame)
+                .map(|()| FrameAlerts::default())
+        },
     };
-    if let Err(err) = process_result {
-        if let ProcessingError::Speed(err) = err {
-            log::error!("Response: {err}");
-        } else {
-            log::error!("Response: {err}");
+    match process_result {
+        Ok(alerts) => {
+            alert_conflicts(ctx, alerts.ip_conflicts);
+            alert_routers(ctx, alerts.rogue_routers);
+        },
+        Err(err) => log::error!("Response: {err}"),
+    }
+}
+
+fn alert_conflicts(ctx: &mut Context, found: Vec<IpConflict>) {
+    for conflict in found {
+        if conflict.is_failover && !ctx.client_settings.ip_conflict_failover_alerts {
+            log::info!(
+                "IP conflict on {} recognized as failover: {} -> {}",
+                conflict.ip,
+                conflict.previous,
+                conflict.current
+            );
+            continue;
         }
+
+        log::warn!(
+            "IP conflict: {} is used by {} and {}",
+            conflict.ip,
+            conflict.previous,
+            conflict.current
+        );
+        let mut text = t!("Message.Warning.IpConflict", "ip" = conflict.ip).to_string();
+        for mac in [&conflict.previous, &conflict.current] {
+            let Some(sighting) = ctx.net_storage.conflicts.sighting(&conflict.ip, mac)
+            else {
+                continue;
+            };
+            text.push_str(&format!(
+                "\n{} ({}): {}",
+                mac,
+                sighting
+                    .vendor
+                    .as_ref()
+                    .map(|vendor| vendor.full.clone())
+                    .unwrap_or(
+                        t!("Tab.Status.Devices.Device.Vendor.Unknown").to_string()
+                    ),
+                t!(
+                    "Message.Warning.IpConflict.Seen",
+                    "first" = format::time(&sighting.first_seen),
+                    "last" = format::time(&sighting.last_seen)
+                )
+            ));
+        }
+        MessageModal::warning(&text).try_send_by(&ctx.modals_tx);
+
+        let alert = Alert {
+            rule: AlertRule::IpConflict,
+            severity: if conflict.is_failover {
+                Severity::Info
+            } else {
+                Severity::Warning
+            },
+            title: t!("Notification.IpConflict.Title").to_string(),
+            summary: text,
+        };
+        let now = Local::now();
+        ctx.notifier
+            .notify(&ctx.client_settings.notifications, alert, now);
+        ctx.net_storage.speed.push_marker(
+            now,
+            MarkerKind::Alert,
+            t!("Tab.Status.Plot.Marker.IpConflict", "ip" = conflict.ip),
+        );
+    }
+}
+
+fn alert_routers(ctx: &mut Context, found: Vec<RogueRouter>) {
+    for router in found {
+        let known = fields::list(&router.known);
+        log::warn!(
+            "Rogue IPv6 router: {} ({}) advertises, known: {known}",
+            router.mac,
+            router.ip
+        );
+        let text = t!(
+            "Message.Warning.RogueRouter",
+            "mac" = router.mac,
+            "ip" = router.ip,
+            "known" = known
+        )
+        .to_string();
+        MessageModal::warning(&text).try_send_by(&ctx.modals_tx);
+
+        let alert = Alert {
+            rule: AlertRule::RogueRouter,
+            severity: Severity::Critical,
+            title: t!("Notification.RogueRouter.Title").to_string(),
+            summary: text,
+        };
+        let now = Local::now();
+        ctx.notifier
+            .notify(&ctx.client_settings.notifications, alert, now);
+        ctx.net_storage.speed.push_marker(
+            now,
+            MarkerKind::Alert,
+            t!("Tab.Status.Plot.Marker.RogueRouter", "mac" = router.mac),
+        );
     }
 }
 
 pub fn process(ctx: &mut Context, response: Response) {
     match response {
+        Response::CaptureStats(dto) => process::capture_stats(ctx, dto),
+        Response::CaptureIdle { seconds } => process::capture_idle(ctx, seconds),
+        Response::CaptureSchedule(status) => process::capture_schedule(ctx, status),
+        Response::InterfaceAddressChanged(dto) => {
+            process::interface_address_changed(ctx, dto)
+        },
+        Response::FailureSamples(dto) => ctx.settings_server.failure_samples = Some(dto),
         Response::ServerSettings(dto) => process::server_settings(ctx, dto),
-        Response::SuccessChangePassword => {
+        Response::Summary(dto) => process::summary(ctx, dto),
+        Response::Statistics(dto) => ctx.net_storage.capture.set_statistics(dto),
+        Response::SuccessChangePassword(token) => {
+            // Server revokes all session tokens after password change,
+            // except the new one, that is issued for this client
+            match (token, &mut ctx.session) {
+                (Some(token), Some(session)) => session.token = token,
+                _ => ctx.session = None,
+            }
             modals::success::password_changed(&ctx.modals_tx)
         },
+        Response::SuccessResetStatistics(dto) => {
+            ctx.net_storage.capture.set_statistics(dto);
+            modals::success::statistics_reset(&ctx.modals_tx)
+        },
         Response::SuccessSaveConfig => modals::success::config_saved(&ctx.modals_tx),
-        Response::SuccessSetCompression(is_enabled) => {
-            modals::success::compression_set(&ctx.modals_tx, is_enabled)
+        Response::SuccessSetCaptureOptions(_) => {
+            modals::success::capture_options_set(&ctx.modals_tx)
+        },
+        Response::SuccessSetCompression { enabled, current } => {
+            modals::success::compression_set(&ctx.modals_tx, enabled, current)
         },
         Response::SuccessSetInterface(new) => {
             modals::success::interface_set(&ctx.modals_tx, new)
         },
+        Response::SuccessSetMirror(is_enabled) => {
+            modals::success::mirror_set(&ctx.modals_tx, is_enabled)
+        },
+        Response::SuccessSetProtocolPorts(table) => {
+            ctx.settings_server.protocol_ports = table;
+            modals::success::protocol_ports_set(&ctx.modals_tx)
+        },
+        Response::SuccessSetSchedule(windows) => {
+            ctx.settings_server.schedule = windows;
+            modals::success::schedule_set(&ctx.modals_tx)
+        },
+        Response::SuccessSetSendDelay(delay) => {
+            ctx.settings_server.send_delay_active = delay;
+            modals::success::send_delay_set(&ctx.modals_tx, delay)
+        },
         Response::SuccessSetSendUnparsedFrames(is_enabled) => {
             modals::success::send_unparsed_frames_set(&ctx.modals_tx, is_enabled)
         },
         Response::SuccessSync => process::pong(ctx),
+        Response::Error(ServerError::CredentialsChanged) => {
+            // Connection is closed by the server right after, the token is revoked
+            ctx.session = None;
+            modals::error::try_send(&ctx.modals_tx, ServerError::CredentialsChanged)
+        },
         Response::Error(error) => modals::error::try_send(&ctx.modals_tx, error),
 
         Response::Data(_) => {
@@ -60,7 +219,7 @@ mod modals {
     pub mod error {
         use crate::ui::modals::message::MessageModal;
         use crate::ws::response::modals::Sender;
-        use common::messages::ServerError;
+        use common::messages::{SEND_DELAY_MAX_MS, ServerError};
 
         pub fn try_send(tx: &Sender, error: ServerError) {
             MessageModal::error(&localize(&error)).try_send_by(tx);
@@ -68,24 +227,55 @@ mod modals {
 
         fn localize(err: &ServerError) -> String {
             match err {
+                ServerError::CredentialsChanged => {
+                    t!("Response.Error.CredentialsChanged").to_string()
+                },
                 ServerError::FailedToChangePassword => {
                     t!("Response.Error.PasswordChange").to_string()
                 },
+                ServerError::FailureSamplesDisabled => {
+                    t!("Response.Error.FailureSamplesDisabled").to_string()
+                },
                 ServerError::FailedToGetInterfaces => {
                     t!("Response.Error.InterfacesGet").to_string()
                 },
                 ServerError::FailedToSaveConfig => {
                     t!("Response.Error.ConfigSave").to_string()
                 },
+                ServerError::FailedToSaveStatistics => {
+                    t!("Response.Error.StatisticsSave").to_string()
+                },
+                ServerError::InvalidCaptureOption => {
+                    t!("Response.Error.InvalidCaptureOption").to_string()
+                },
                 ServerError::InvalidMessageFormat => {
                     t!("Response.Error.InvalidMessageFormat").to_string()
                 },
                 ServerError::InvalidInterface => {
                     t!("Response.Error.InvalidInterface").to_string()
                 },
+                ServerError::InvalidMirrorTarget(target) => {
+                    t!("Response.Error.InvalidMirrorTarget", "target" = target)
+                        .to_string()
+                },
+                ServerError::InvalidProtocolPorts(protocol) => {
+                    t!("Response.Error.InvalidProtocolPorts", "protocol" = protocol)
+                        .to_string()
+                },
+                ServerError::InvalidSendDelay(delay) => t!(
+                    "Response.Error.InvalidSendDelay",
+                    "max" = SEND_DELAY_MAX_MS,
+                    "delay" = delay
+                )
+                .to_string(),
                 ServerError::MutexPoisoned => {
                     t!("Response.Error.MutexPoisoned").to_string()
                 },
+                ServerError::UnsupportedLinkType(link_type) => t!(
+                    "Response.Error.UnsupportedLinkType",
+                    "link_type" = link_type
+                )
+                .to_string(),
             }
         }
     }
@@ -94,12 +284,23 @@ mod modals {
         use crate::ui::modals::message::MessageModal;
         use crate::ws::response::modals::Sender;
 
-        pub fn compression_set(tx: &Sender, is_enabled: bool) {
-            let text: String = if is_enabled {
+        pub fn capture_options_set(tx: &Sender) {
+            MessageModal::info(&t!("Response.SetCaptureOptions.Success")).try_send_by(tx);
+        }
+
+        // Running connection keeps its compression, only the new ones are affected
+        pub fn compression_set(tx: &Sender, is_enabled: bool, current: bool) {
+            let mut text: String = if is_enabled {
                 t!("Response.SetCompression.Success.On").to_string()
             } else {
                 t!("Response.SetCompression.Success.Off").to_string()
             };
+            if is_enabled != current {
+                text.push_str(&format!(
+                    "\n\n{}",
+                    t!("Response.SetCompression.Success.Reconnect")
+                ));
+            }
             MessageModal::info(&text).try_send_by(tx);
         }
 
@@ -112,10 +313,36 @@ mod modals {
                 .try_send_by(tx);
         }
 
+        pub fn mirror_set(tx: &Sender, is_enabled: bool) {
+            let text: String = if is_enabled {
+                t!("Response.SetMirror.Success.On").to_string()
+            } else {
+                t!("Response.SetMirror.Success.Off").to_string()
+            };
+            MessageModal::info(&text).try_send_by(tx);
+        }
+
+        pub fn statistics_reset(tx: &Sender) {
+            MessageModal::info(&t!("Response.ResetStatistics.Success")).try_send_by(tx);
+        }
+
         pub fn password_changed(tx: &Sender) {
             MessageModal::info(&t!("Response.PasswordChange.Success")).try_send_by(tx);
         }
 
+        pub fn protocol_ports_set(tx: &Sender) {
+            MessageModal::info(&t!("Response.SetProtocolPorts.Success")).try_send_by(tx);
+        }
+
+        pub fn schedule_set(tx: &Sender) {
+            MessageModal::info(&t!("Response.SetSchedule.Success")).try_send_by(tx);
+        }
+
+        pub fn send_delay_set(tx: &Sender, delay: u64) {
+            MessageModal::info(&t!("Response.SetSendDelay.Success", "delay" = delay))
+                .try_send_by(tx);
+        }
+
         pub fn send_unparsed_frames_set(tx: &Sender, is_enabled: bool) {
             let text: String = if is_enabled {
                 t!("Response.SetSendUnparsedFrames.Success.On").to_string()
@@ -129,23 +356,141 @@ mod modals {
 
 mod process {
     use crate::context::{Context, ServerSettings};
+    use crate::net::speed::MarkerKind;
+    use crate::net::timeline::TimelineEvent;
     use chrono::Local;
-    use common::messages::ServerSettingsDto;
+    use common::messages::{
+        AddressChangeDto, CaptureStatsDto, ServerSettingsDto, SummaryDto,
+    };
+    use common::schedule::ScheduleStatus;
+    use dpi::dto::fields;
+
+    pub fn capture_stats(ctx: &mut Context, dto: CaptureStatsDto) {
+        ctx.net_storage.capture.update(dto);
+        if ctx.net_storage.capture.is_dropping() {
+            log::warn!(
+                "Capture: Server dropped {} packets since the previous poll.",
+                ctx.net_storage.capture.dropped_recently()
+            );
+        }
+    }
+
+    pub fn capture_idle(ctx: &mut Context, seconds: u64) {
+        log::warn!("Capture: Server received no packets for {seconds} s.");
+        ctx.net_storage
+            .timeline
+            .push(Local::now(), TimelineEvent::CaptureIdle { seconds });
+        ctx.net_storage.capture.set_idle(seconds);
+    }
+
+    pub fn capture_schedule(ctx: &mut Context, status: ScheduleStatus) {
+        if status.is_active != ctx.settings_server.schedule_status.is_active {
+            match status.is_active {
+                true => {
+                    log::info!("Capture: Server resumed the capture by the schedule.")
+                },
+                false => {
+                    log::info!("Capture: Server paused the capture by the schedule.")
+                },
+            }
+        }
+        ctx.settings_server.schedule_status = status;
+    }
+
+    // Interface info itself comes with the server settings, that follow
+    pub fn interface_address_changed(ctx: &mut Context, dto: AddressChangeDto) {
+        log::warn!(
+            "Capture: Addresses of the server interface {} changed from {} to {}.",
+            dto.interface,
+            fields::list(&dto.removed),
+            fields::list(&dto.added)
+        );
+        let now = Local::now();
+        let event = TimelineEvent::InterfaceAddressChanged {
+            interface: dto.interface,
+            from: dto.removed,
+            to: dto.added,
+        };
+        ctx.net_storage
+            .speed
+            .push_marker(now, MarkerKind::Interface, event.localize());
+        ctx.net_storage.timeline.push(now, event);
+    }
+
+    pub fn summary(ctx: &mut Context, dto: SummaryDto) {
+        let was_sampled = ctx.net_storage.sampling.is_sampled();
+        if dto.sampled && !was_sampled {
+            log::warn!(
+                "Sampling: Server sends only 1 of {} frames, the connection can't keep up.",
+                dto.sample_rate
+            );
+        } else if !dto.sampled && was_sampled {
+            log::info!("Sampling: Server sends every frame again.");
+        }
+        ctx.net_storage.sampling.update(dto);
+    }
 
     pub fn pong(ctx: &mut Context) {
         ctx.heartbeat.update();
     }
 
     pub fn server_settings(ctx: &mut Context, dto: ServerSettingsDto) {
+        // Frames of different link types can't be saved into the same pcap file
+        let link_type = dto.link_type.map(pcap::Linktype);
+        if ctx.settings_server.link_type.is_some()
+            && ctx.settings_server.link_type != link_type
+            && !ctx.net_storage.raw.is_empty()
+        {
+            log::warn!("Link type changed. Unparsed frames are cleared.");
+            ctx.net_storage.raw.clear();
+        }
+        let now = Local::now();
+        if let Some(event) = ctx
+            .net_storage
+            .timeline
+            .observe_interface(now, &dto.interface_active)
+        {
+            ctx.net_storage.speed.push_marker(
+                now,
+                MarkerKind::Interface,
+                event.localize(),
+            );
+        }
+
         ctx.settings_server = ServerSettings {
+            capture_options_active: dto.capture_options_active,
+            capture_options_config: dto.capture_options_config,
+
             compression_active: dto.compression_active,
             compression_config: dto.compression_config,
 
+            credentials_active: dto.credentials_active,
+            credentials_config: dto.credentials_config,
+
+            failure_samples_config: dto.failure_samples_config,
+            failure_samples: ctx.settings_server.failure_samples.take(),
+
+            flow_export_active: dto.flow_export_active,
+            flow_export_config: dto.flow_export_config,
+
             interfaces_available: dto.interfaces_available,
             interface_active: dto.interface_active,
             interface_config: dto.interface_config,
 
-            link_type: dto.link_type.map(pcap::Linktype),
+            link_type,
+
+            mirror_active: dto.mirror_active,
+
+            pcap_writer_active: dto.pcap_writer_active,
+            pcap_writer_config: dto.pcap_writer_config,
+
+            protocol_ports: dto.protocol_ports,
+
+            schedule: dto.schedule,
+            schedule_status: dto.schedule_status,
+
+            send_delay_active: dto.send_delay_active,
+            send_delay_config: dto.send_delay_config,
 
             send_unparsed_frames_active: dto.send_unparsed_frames_active,
             send_unparsed_frames_config: dto.send_unparsed_frames_config,
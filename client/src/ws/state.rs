@@ -0,0 +1,241 @@
+use crate::context::{ClientSettings, Context, Session};
+use crate::net::heartbeat::Heartbeat;
+use crate::net::speed::MarkerKind;
+use crate::net::timeline::TimelineEvent;
+use crate::ui::format;
+use crate::ui::modals::message::MessageModal;
+use crate::ws::link::LinkInfo;
+use chrono::{DateTime, Local};
+use crossbeam::channel::Sender;
+use std::net::SocketAddr;
+
+/// Connection lifecycle. Updated by the WS thread, except for degradation,
+/// which is detected by the heartbeat.
+#[derive(Clone, Debug, Default)]
+pub enum ConnectionState {
+    Connecting(SocketAddr),
+    // WebSocket handshake with the credentials
+    Authenticating(SocketAddr),
+    Connected {
+        address: SocketAddr,
+        since: DateTime<Local>,
+    },
+    // Server doesn't answer pings
+    Degraded {
+        address: SocketAddr,
+        since: DateTime<Local>,
+        last_pong: DateTime<Local>,
+    },
+    // Responses are read from the session recording, there's no server
+    Replaying {
+        file: String,
+        speed: f64,
+    },
+    // With the reason, if the connection failed or was lost
+    #[default]
+    Disconnected(Option<String>),
+}
+
+/// Messages from the WS thread to the UI.
+pub enum ConnectionUpdate {
+    State(ConnectionState),
+    // Session token issued by the server on connection
+    SessionIssued(Session),
+    // Session token is invalid or expired, so the password is required again
+    SessionRejected,
+    // Unreadable messages since the last readable one. Zero clears the warning
+    DroppedMessages(u64),
+    // Parameters of the handshake & the counters, sent right before `Connected`
+    Link(LinkInfo),
+}
+
+impl ConnectionState {
+    pub fn is_pending(&self) -> bool {
+        matches!(self, Self::Connecting(_) | Self::Authenticating(_))
+    }
+
+    pub fn is_connected(&self) -> bool {
+        matches!(
+            self,
+            Self::Connected { .. } | Self::Degraded { .. } | Self::Replaying { .. }
+        )
+    }
+
+    // Only one connection at a time: a new one is started after the previous has ended
+    pub fn is_connect_allowed(&self) -> bool {
+        matches!(self, Self::Disconnected(_))
+    }
+
+    // Switching between connected & degraded by the ping results
+    pub fn apply_heartbeat(&mut self, heartbeat: &Heartbeat, settings: &ClientSettings) {
+        let is_timeout = heartbeat.is_timeout(settings);
+        match self {
+            Self::Connected { address, since } if is_timeout => {
+                let Some(last_pong) = heartbeat.last_sync else {
+                    return;
+                };
+                *self = Self::Degraded {
+                    address: *address,
+                    since: *since,
+                    last_pong,
+                };
+            },
+            Self::Degraded { address, since, .. } if !is_timeout => {
+                *self = Self::Connected {
+                    address: *address,
+                    since: *since,
+                };
+            },
+            _ => {},
+        }
+    }
+
+    pub fn localized(&self) -> String {
+        match self {
+            Self::Connecting(address) => {
+                t!("Connection.State.Connecting", "address" = address).to_string()
+            },
+            Self::Authenticating(address) => {
+                t!("Connection.State.Authenticating", "address" = address).to_string()
+            },
+            Self::Connected { address, since } => t!(
+                "Connection.State.Connected",
+                "address" = address,
+                "since" = format::time(&since)
+            )
+            .to_string(),
+            Self::Degraded { last_pong, .. } => t!(
+                "Connection.State.Degraded",
+                "seconds" = (Local::now() - last_pong).num_seconds()
+            )
+            .to_string(),
+            Self::Replaying { file, speed } => {
+                t!("Connection.State.Replaying", "file" = file, "speed" = speed)
+                    .to_string()
+            },
+            Self::Disconnected(None) => t!("Connection.State.Disconnected").to_string(),
+            Self::Disconnected(Some(reason)) => {
+                t!("Connection.State.Disconnected.Reason", "reason" = reason).to_string()
+            },
+        }
+    }
+}
+
+pub fn process(ctx: &mut Context, update: ConnectionUpdate) {
+    match update {
+        ConnectionUpdate::State(state) => {
+            // Failed attempt is also reported by the modal, lost connection only by the state
+            if let ConnectionState::Disconnected(Some(reason)) = &state {
+                if ctx.connection.is_pending() {
+                    MessageModal::error(reason).try_send_by(&ctx.modals_tx);
+                }
+            }
+            if let Some(event) = timeline_event(&ctx.connection, &state) {
+                let now = Local::now();
+                ctx.net_storage.speed.push_marker(
+                    now,
+                    MarkerKind::Connection,
+                    event.localize(),
+                );
+                ctx.net_storage.timeline.push(now, event);
+            }
+            ctx.connection = state;
+        },
+        ConnectionUpdate::SessionIssued(session) => ctx.session = Some(session),
+        ConnectionUpdate::SessionRejected => ctx.session = None,
+        ConnectionUpdate::DroppedMessages(amount) => ctx.dropped_messages = amount,
+        ConnectionUpdate::Link(link) => ctx.link = Some(link),
+    }
+}
+
+// Degradation & recovery aren't worth the timeline, only the session boundaries
+fn timeline_event(
+    previous: &ConnectionState, current: &ConnectionState,
+) -> Option<TimelineEvent> {
+    match current {
+        ConnectionState::Connected { address, .. } if !previous.is_connected() => {
+            Some(TimelineEvent::Connected {
+                address: address.to_string(),
+            })
+        },
+        ConnectionState::Disconnected(reason) if previous.is_connected() => {
+            Some(TimelineEvent::Disconnected {
+                reason: reason.clone(),
+            })
+        },
+        _ => None,
+    }
+}
+
+pub fn try_send(tx: &Sender<ConnectionUpdate>, update: ConnectionUpdate) {
+    if let Err(err) = tx.try_send(update) {
+        log::error!("Connection Channel: Can't send update. Error: {err}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn states() -> Vec<ConnectionState> {
+        let address = SocketAddr::from(([192, 168, 0, 10], 8080));
+        let now = Local::now();
+        vec![
+            ConnectionState::Connecting(address),
+            ConnectionState::Authenticating(address),
+            ConnectionState::Connected {
+                address,
+                since: now,
+            },
+            ConnectionState::Degraded {
+                address,
+                since: now,
+                last_pong: now,
+            },
+            ConnectionState::Replaying {
+                file: "session.jsonl.gz".to_string(),
+                speed: 1.0,
+            },
+            ConnectionState::Disconnected(None),
+            ConnectionState::Disconnected(Some("Connection refused".to_string())),
+        ]
+    }
+
+    #[test]
+    fn test_connect_allowed() {
+        let allowed: Vec<bool> = states()
+            .iter()
+            .map(ConnectionState::is_connect_allowed)
+            .collect();
+        assert_eq!(allowed, [false, false, false, false, false, true, true]);
+
+        // Attempt is either pending or established, never both
+        for state in states() {
+            assert!(!(state.is_pending() && state.is_connected()));
+            assert_eq!(
+                state.is_connect_allowed(),
+                !state.is_pending() && !state.is_connected()
+            );
+        }
+    }
+
+    #[test]
+    fn test_timeline_events() {
+        let [connecting, _, connected, degraded, _, disconnected, failed] =
+            <[ConnectionState; 7]>::try_from(states()).unwrap();
+
+        // Failed attempt isn't a session boundary
+        assert!(timeline_event(&connecting, &failed).is_none());
+        assert!(matches!(
+            timeline_event(&connecting, &connected),
+            Some(TimelineEvent::Connected { .. })
+        ));
+        // Degradation & recovery aren't either
+        assert!(timeline_event(&connected, &degraded).is_none());
+        assert!(timeline_event(&degraded, &connected).is_none());
+        assert!(matches!(
+            timeline_event(&degraded, &disconnected),
+            Some(TimelineEvent::Disconnected { reason: None })
+        ));
+    }
+}
@@ -1,9 +1,42 @@
+// Signature of the challenge nonce, made by the key derived from the password
 pub const AUTH_HEADER: &str = "AUTH-KEY";
+// Challenge of the server: one-time nonce & params of the password hash
+pub const AUTH_NONCE_HEADER: &str = "AUTH-NONCE";
+pub const AUTH_PARAMS_HEADER: &str = "AUTH-PARAMS";
 pub const COMPRESSION_HEADER: &str = "COMPRESSION-VALUE";
+// Comma-separated features of the client. Server sticks to the JSON messages without it
+pub const FEATURES_HEADER: &str = "FEATURES";
+// Seconds between the messages of the client, so the server closes the dead connections
+pub const HEARTBEAT_HEADER: &str = "HEARTBEAT-INTERVAL";
+pub const SESSION_TOKEN_HEADER: &str = "SESSION-TOKEN";
+
+pub mod features {
+    // Raw frames are sent as the binary messages, see [`crate::binary`]
+    pub const BINARY_FRAMES: &str = "binary-frames";
+
+    // Features, that the client supports
+    pub const SUPPORTED: &[&str] = &[BINARY_FRAMES];
+
+    pub fn header_value() -> String {
+        SUPPORTED.join(",")
+    }
+
+    pub fn contains(header_value: &str, feature: &str) -> bool {
+        header_value.split(',').any(|value| value.trim() == feature)
+    }
+}
 
 pub mod errors {
     pub const COMPRESSION_HEADER_NOT_FOUND: &str = "Compression header was not found.";
-    pub const PASSWORD_HEADER_NOT_FOUND: &str = "Password header was not found.";
+    pub const NONCE_HEADER_NOT_FOUND: &str = "Nonce header was not found.";
+    pub const PASSWORD_CHALLENGE: &str = "Sign the nonce by the password.";
+    pub const TOO_MANY_CHALLENGES: &str = "Too many pending challenges, try again later.";
     pub const WRONG_COMPRESSION: &str = "Server has other compression settings.";
     pub const WRONG_PASSWORD: &str = "Wrong password.";
+    pub const WRONG_SESSION_TOKEN: &str = "Session token is invalid or expired.";
+}
+
+// Reasons of the close frames, sent by the server
+pub mod close_reasons {
+    pub const CREDENTIALS_CHANGED: &str = "credentials changed";
 }
@@ -0,0 +1,119 @@
+// Raw frames are the largest messages, so they bypass JSON and go as the binary
+// messages: the tag, the fields of the frame header (big-endian) & the packet bytes.
+// Sent only to the clients, that tell the support by the features header.
+
+use dpi::dto::frame::{FrameHeader, OwnedFrame};
+use thiserror::Error;
+
+// Zlib streams start with the 0x?8 byte and JSON with the printable one,
+// so the tag isn't confused with the other messages
+pub const RAW_FRAME_TAG: u8 = 0x01;
+// Tag, seconds, microseconds, captured length, length
+pub const HEADER_LENGTH: usize = 1 + 8 + 8 + 4 + 4;
+
+pub fn is_raw_frame(message: &[u8]) -> bool {
+    message.first() == Some(&RAW_FRAME_TAG)
+}
+
+pub fn encode(frame: &OwnedFrame) -> Vec<u8> {
+    let mut message = Vec::with_capacity(HEADER_LENGTH + frame.data.len());
+    message.push(RAW_FRAME_TAG);
+    message.extend_from_slice(&frame.header.tv_sec.to_be_bytes());
+    message.extend_from_slice(&frame.header.tv_usec.to_be_bytes());
+    message.extend_from_slice(&frame.header.caplen.to_be_bytes());
+    message.extend_from_slice(&frame.header.len.to_be_bytes());
+    message.extend_from_slice(&frame.data);
+
+    message
+}
+
+/// Packet bytes are moved within the buffer of the message, it isn't copied.
+pub fn decode(mut message: Vec<u8>) -> Result<OwnedFrame, BinaryError> {
+    let Some((header, _)) = message.split_first_chunk::<HEADER_LENGTH>() else {
+        return Err(BinaryError::TooShort(message.len()));
+    };
+    let (tag, fields) = header.split_at(1);
+    if tag != [RAW_FRAME_TAG] {
+        return Err(BinaryError::UnknownTag);
+    }
+    let (tv_sec, fields) = fields.split_at(8);
+    let (tv_usec, fields) = fields.split_at(8);
+    let (caplen, len) = fields.split_at(4);
+
+    let header = FrameHeader {
+        tv_sec: i64::from_be_bytes(tv_sec.try_into()?),
+        tv_usec: i64::from_be_bytes(tv_usec.try_into()?),
+        caplen: u32::from_be_bytes(caplen.try_into()?),
+        len: u32::from_be_bytes(len.try_into()?),
+    };
+    message.drain(..HEADER_LENGTH);
+
+    Ok(OwnedFrame {
+        header,
+        data: message,
+    })
+}
+
+#[derive(Debug, Error)]
+pub enum BinaryError {
+    #[error("Message of {0} bytes is shorter than the frame header.")]
+    TooShort(usize),
+
+    #[error("Message has the unknown tag.")]
+    UnknownTag,
+
+    #[error("Field of the frame header is malformed.")]
+    MalformedField(#[from] std::array::TryFromSliceError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dpi::dto::frame::FrameType;
+
+    fn frame(length: usize) -> OwnedFrame {
+        OwnedFrame {
+            header: FrameHeader {
+                tv_sec: 1_700_000_000,
+                tv_usec: 999_999,
+                caplen: length as u32,
+                len: length as u32 + 4,
+            },
+            data: (0..length).map(|index| index as u8).collect(),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        // Empty, Ethernet MTU & the largest IP packet
+        for length in [0, 1500, u16::MAX as usize] {
+            let frame = frame(length);
+            let message = encode(&frame);
+            assert_eq!(message.len(), HEADER_LENGTH + length);
+            assert!(is_raw_frame(&message));
+            assert_eq!(decode(message).unwrap(), frame);
+        }
+    }
+
+    #[test]
+    fn test_malformed() {
+        let message = encode(&frame(0));
+        assert!(matches!(
+            decode(message[..HEADER_LENGTH - 1].to_vec()),
+            Err(BinaryError::TooShort(24))
+        ));
+
+        let mut message = encode(&frame(60));
+        message[0] = b'{';
+        assert!(!is_raw_frame(&message));
+        assert!(matches!(decode(message), Err(BinaryError::UnknownTag)));
+    }
+
+    #[test]
+    fn test_smaller_than_json() {
+        let frame = frame(1500);
+        let binary = encode(&frame).len();
+        let json = serde_json::to_string(&FrameType::Raw(frame)).unwrap().len();
+        assert!(binary * 3 < json, "binary {binary}, json {json}");
+    }
+}
@@ -1,12 +1,15 @@
 use crossbeam::channel::{Receiver, Sender, TrySendError};
 use std::collections::VecDeque;
+use std::sync::Arc;
 
-pub struct BroadcastPool<T: Clone> {
-    senders: VecDeque<Sender<T>>,
-    receivers: VecDeque<Receiver<T>>,
+// Messages are shared between the subscribers, so they're never cloned per subscriber.
+
+pub struct BroadcastPool<T> {
+    senders: VecDeque<Sender<Arc<T>>>,
+    receivers: VecDeque<Receiver<Arc<T>>>,
 }
 
-impl<T: Clone> Default for BroadcastPool<T> {
+impl<T> Default for BroadcastPool<T> {
     fn default() -> Self {
         Self {
             senders: VecDeque::with_capacity(8),
@@ -15,14 +18,14 @@ impl<T: Clone> Default for BroadcastPool<T> {
     }
 }
 
-impl<T: Clone> BroadcastPool<T> {
+impl<T> BroadcastPool<T> {
     pub fn create(&mut self) {
-        let (tx, rx) = crossbeam::channel::unbounded::<T>();
+        let (tx, rx) = crossbeam::channel::unbounded::<Arc<T>>();
         self.senders.push_back(tx);
         self.receivers.push_back(rx);
     }
 
-    pub fn last_receiver(&mut self) -> Option<Receiver<T>> {
+    pub fn last_receiver(&mut self) -> Option<Receiver<Arc<T>>> {
         self.receivers.pop_front()
     }
 
@@ -30,7 +33,7 @@ impl<T: Clone> BroadcastPool<T> {
         !self.receivers.is_empty()
     }
 
-    pub fn last_sender(&mut self) -> Option<Sender<T>> {
+    pub fn last_sender(&mut self) -> Option<Sender<Arc<T>>> {
         self.senders.pop_back()
     }
 
@@ -39,31 +42,32 @@ impl<T: Clone> BroadcastPool<T> {
     }
 }
 
-pub struct BroadcastChannel<T: Clone> {
-    senders: Vec<Sender<T>>,
+pub struct BroadcastChannel<T> {
+    senders: Vec<Sender<Arc<T>>>,
 }
 
-impl<T: Clone> Default for BroadcastChannel<T> {
+impl<T> Default for BroadcastChannel<T> {
     fn default() -> Self {
         Self::new()
     }
 }
 
-impl<T: Clone> BroadcastChannel<T> {
+impl<T> BroadcastChannel<T> {
     pub fn new() -> Self {
         Self {
             senders: Vec::with_capacity(8),
         }
     }
 
-    pub fn add_sender(&mut self, sender: Sender<T>) {
+    pub fn add_sender(&mut self, sender: Sender<Arc<T>>) {
         self.senders.push(sender);
     }
 
     pub fn send(&mut self, msg: T) {
+        let msg = Arc::new(msg);
         let mut disconnected: Vec<usize> = Vec::new();
         for (index, sender) in self.senders.iter().enumerate() {
-            let result = sender.try_send(msg.clone());
+            let result = sender.try_send(Arc::clone(&msg));
             if let Err(TrySendError::Disconnected(_)) = result {
                 disconnected.push(index);
             }
@@ -79,4 +83,47 @@ impl<T: Clone> BroadcastChannel<T> {
     pub fn subscribers(&self) -> usize {
         self.senders.len()
     }
+
+    /// Messages, that aren't taken yet by the slowest subscriber.
+    pub fn queued(&self) -> usize {
+        self.senders
+            .iter()
+            .map(Sender::len)
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_between_subscribers() {
+        let mut pool = BroadcastPool::<Vec<u8>>::default();
+        let mut channel = BroadcastChannel::new();
+        let mut receivers = vec![];
+        for _ in 0..3 {
+            pool.create();
+            receivers.extend(pool.last_receiver());
+            while let Some(sender) = pool.last_sender() {
+                channel.add_sender(sender);
+            }
+        }
+        // Disconnected subscriber is removed on the next send
+        receivers.pop();
+
+        channel.send(vec![0; 1500]);
+        assert_eq!(channel.subscribers(), 2);
+        let received = receivers
+            .iter()
+            .filter_map(|receiver| receiver.try_recv().ok())
+            .collect::<Vec<_>>();
+        assert_eq!(received.len(), 2);
+        assert!(Arc::ptr_eq(&received[0], &received[1]));
+
+        channel.send(vec![0; 1500]);
+        receivers[0].try_recv().unwrap();
+        assert_eq!(channel.queued(), 1);
+    }
 }
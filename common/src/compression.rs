@@ -4,9 +4,7 @@ use flate2::write::ZlibEncoder;
 use std::io::{Read, Write};
 
 pub fn compress(message: &str) -> Result<Vec<u8>, std::io::Error> {
-    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
-    encoder.write_all(message.as_bytes())?;
-    encoder.finish()
+    compress_bytes(message.as_bytes())
 }
 
 pub fn decompress(message: &[u8]) -> Result<String, std::io::Error> {
@@ -16,3 +14,18 @@ pub fn decompress(message: &[u8]) -> Result<String, std::io::Error> {
 
     Ok(buffer)
 }
+
+// Binary messages aren't UTF-8, e.g. the raw frames
+pub fn compress_bytes(message: &[u8]) -> Result<Vec<u8>, std::io::Error> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(message)?;
+    encoder.finish()
+}
+
+pub fn decompress_bytes(message: &[u8]) -> Result<Vec<u8>, std::io::Error> {
+    let mut decoder = ZlibDecoder::new(message);
+    let mut buffer = Vec::new();
+    decoder.read_to_end(&mut buffer)?;
+
+    Ok(buffer)
+}
@@ -1,7 +1,301 @@
+// Password of the server is stored in the SCRAM way (RFC 5802), on top of Argon2id.
+// Argon2id output of the password is the salted password, it isn't stored:
+// ClientKey = HMAC(output, "Client Key"), StoredKey = SHA-256(ClientKey).
+// The config keeps only StoredKey with the salt & params, as PHC string.
+//
+// Clients prove the password by the challenge-response: the server sends a one-time nonce
+// with the salt & params, the client derives the same ClientKey and returns
+// ClientKey XOR HMAC(StoredKey, nonce). The server recovers ClientKey from it
+// and compares its hash with StoredKey. So neither the password nor a replayable value
+// is sent, and the stored value isn't enough to sign the nonce.
+//
+// Passwords are pre-hashed by SHA-256 before Argon2id. So the configs with the old
+// SHA-256 hashes are upgraded without the plaintext, as the hashes with the raw output.
+
+use argon2::password_hash::{
+    Ident, Output, PasswordHash, PasswordHasher, Salt, SaltString,
+};
+use argon2::{Algorithm, Argon2, Params};
+use hmac::{Hmac, Mac};
+use rand::Rng;
 use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use thiserror::Error;
+
+pub const HASH_PREFIX: &str = "$scram-argon2id$";
+// Hashes of the older versions, with the raw Argon2id output
+pub const ARGON2_HASH_PREFIX: &str = "$argon2id$";
+const HASH_IDENT: &str = "scram-argon2id";
+const CLIENT_KEY_LABEL: &[u8] = b"Client Key";
+const NONCE_LENGTH_BYTES: usize = 32;
+const SALT_LENGTH_BYTES: usize = 16;
+// Params come from the server, so the client doesn't take any amount of memory
+const MAX_MEMORY_KIB: u32 = 256 * 1024;
+
+type HmacSha256 = Hmac<Sha256>;
 
-pub fn encrypt_password(password: &str) -> String {
+/// SHA-256 of the password, that's hashed by Argon2id.
+pub fn prehash_password(password: &str) -> String {
     let mut hasher = Sha256::new();
     hasher.update(password.as_bytes());
     hex::encode(hasher.finalize())
 }
+
+/// StoredKey of the password with the random salt & Argon2id params, as PHC string.
+pub fn hash_password(password: &str) -> Result<String, CryptographyError> {
+    hash_prehashed(&prehash_password(password))
+}
+
+/// Same as [`hash_password`], for the password that's already pre-hashed.
+pub fn hash_prehashed(prehash: &str) -> Result<String, CryptographyError> {
+    let salt_bytes: [u8; SALT_LENGTH_BYTES] = rand::rng().random();
+    let salt = SaltString::encode_b64(&salt_bytes)?;
+    let hash = Argon2::default().hash_password(prehash.as_bytes(), &salt)?;
+
+    scram_hash(hash)
+}
+
+/// Hash with the raw Argon2id output is upgraded to StoredKey, without the plaintext.
+pub fn upgrade_hash(hash: &str) -> Result<String, CryptographyError> {
+    scram_hash(PasswordHash::new(hash)?)
+}
+
+// Output of the Argon2id hash is replaced by StoredKey
+fn scram_hash(mut hash: PasswordHash) -> Result<String, CryptographyError> {
+    let output = hash.hash.ok_or(CryptographyError::HashNotFound)?;
+    let stored_key = Sha256::digest(client_key(output.as_bytes())?);
+    hash.algorithm = Ident::new(HASH_IDENT)?;
+    hash.hash = Some(Output::new(&stored_key)?);
+
+    Ok(hash.to_string())
+}
+
+pub fn is_hash(value: &str) -> bool {
+    value.starts_with(HASH_PREFIX)
+}
+
+pub fn is_argon2_hash(value: &str) -> bool {
+    value.starts_with(ARGON2_HASH_PREFIX)
+}
+
+/// Salt & params of the hash, sent to the client with the nonce. Hash itself is cut off.
+pub fn challenge_params(hash: &str) -> Result<String, CryptographyError> {
+    let mut parsed = PasswordHash::new(hash)?;
+    parsed.algorithm = Algorithm::Argon2id.ident();
+    parsed.hash = None;
+
+    Ok(parsed.to_string())
+}
+
+/// Salted password of the client, derived from the password & the challenge params.
+pub fn derive_key(password: &str, params: &str) -> Result<Vec<u8>, CryptographyError> {
+    let parsed = PasswordHash::new(params)?;
+    let argon_params = Params::try_from(&parsed)?;
+    if argon_params.m_cost() > MAX_MEMORY_KIB {
+        return Err(CryptographyError::ParamsTooExpensive);
+    }
+    let salt: Salt = parsed.salt.ok_or(CryptographyError::SaltNotFound)?;
+
+    let prehash = prehash_password(password);
+    let hash = Argon2::default().hash_password_customized(
+        prehash.as_bytes(),
+        Some(parsed.algorithm),
+        parsed.version,
+        argon_params,
+        salt,
+    )?;
+    let output = hash.hash.ok_or(CryptographyError::HashNotFound)?;
+
+    Ok(output.as_bytes().to_vec())
+}
+
+/// StoredKey of the server, it's the output of the stored hash.
+/// It verifies the responses, but doesn't sign the nonce.
+pub fn stored_key(hash: &str) -> Result<Vec<u8>, CryptographyError> {
+    let parsed = PasswordHash::new(hash)?;
+    let output = parsed.hash.ok_or(CryptographyError::HashNotFound)?;
+
+    Ok(output.as_bytes().to_vec())
+}
+
+/// SHA-256 of the secret key & the data. Without the key, the data isn't recovered from it.
+pub fn keyed_digest(key: &[u8], data: &[u8]) -> [u8; 32] {
+    Sha256::new()
+        .chain_update(key)
+        .chain_update(data)
+        .finalize()
+        .into()
+}
+
+pub fn generate_nonce() -> String {
+    let nonce: [u8; NONCE_LENGTH_BYTES] = rand::rng().random();
+    hex::encode(nonce)
+}
+
+/// Response of the client to the challenge: ClientKey XOR HMAC(StoredKey, nonce).
+pub fn sign_nonce(key: &[u8], nonce: &str) -> Result<String, CryptographyError> {
+    let client_key = client_key(key)?;
+    let signature = hmac(&Sha256::digest(client_key), nonce.as_bytes())?;
+
+    Ok(hex::encode(xor(&client_key, &signature)))
+}
+
+/// ClientKey is recovered from the response, its hash is compared in constant time.
+pub fn verify_signature(stored_key: &[u8], nonce: &str, proof: &str) -> bool {
+    let Ok(proof) = hex::decode(proof) else {
+        return false;
+    };
+    let Ok(signature) = hmac(stored_key, nonce.as_bytes()) else {
+        return false;
+    };
+    if proof.len() != signature.len() {
+        return false;
+    }
+    let client_key = xor(&proof, &signature);
+
+    Sha256::digest(client_key)
+        .as_slice()
+        .ct_eq(stored_key)
+        .into()
+}
+
+fn client_key(salted_password: &[u8]) -> Result<[u8; 32], CryptographyError> {
+    hmac(salted_password, CLIENT_KEY_LABEL)
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Result<[u8; 32], CryptographyError> {
+    let mut mac =
+        HmacSha256::new_from_slice(key).map_err(|_| CryptographyError::InvalidKey)?;
+    mac.update(data);
+
+    Ok(mac.finalize().into_bytes().into())
+}
+
+fn xor(first: &[u8], second: &[u8]) -> Vec<u8> {
+    first
+        .iter()
+        .zip(second)
+        .map(|(first, second)| first ^ second)
+        .collect()
+}
+
+#[derive(Debug, Error)]
+pub enum CryptographyError {
+    #[error("Password hash error. {0}")]
+    PasswordHash(#[from] argon2::password_hash::Error),
+
+    #[error("Argon2 error. {0}")]
+    Argon2(#[from] argon2::Error),
+
+    #[error("Hash params require too much memory.")]
+    ParamsTooExpensive,
+
+    #[error("Salt of the hash was not found.")]
+    SaltNotFound,
+
+    #[error("Hash output was not found.")]
+    HashNotFound,
+
+    #[error("Invalid key.")]
+    InvalidKey,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_challenge_response() {
+        let hash = hash_password("secret").unwrap();
+        assert!(is_hash(&hash));
+        // Salt is random
+        assert_ne!(hash, hash_password("secret").unwrap());
+
+        let params = challenge_params(&hash).unwrap();
+        assert!(params.starts_with(ARGON2_HASH_PREFIX));
+        assert!(!params.contains(&hash[hash.rfind('$').unwrap()..]));
+
+        let nonce = generate_nonce();
+        let server_key = stored_key(&hash).unwrap();
+        let client_key = derive_key("secret", &params).unwrap();
+        assert_ne!(client_key, server_key);
+
+        let signature = sign_nonce(&client_key, &nonce).unwrap();
+        assert!(verify_signature(&server_key, &nonce, &signature));
+        // Signature isn't valid for the other nonce
+        assert!(!verify_signature(
+            &server_key,
+            &generate_nonce(),
+            &signature
+        ));
+        assert!(!verify_signature(&server_key, &nonce, "not hex"));
+        // Stored value isn't enough to sign the nonce
+        let signature = sign_nonce(&server_key, &nonce).unwrap();
+        assert!(!verify_signature(&server_key, &nonce, &signature));
+    }
+
+    #[test]
+    fn test_wrong_password() {
+        let hash = hash_password("secret").unwrap();
+        let params = challenge_params(&hash).unwrap();
+        let nonce = generate_nonce();
+
+        let key = derive_key("Secret", &params).unwrap();
+        let signature = sign_nonce(&key, &nonce).unwrap();
+        assert!(!verify_signature(
+            &stored_key(&hash).unwrap(),
+            &nonce,
+            &signature
+        ));
+    }
+
+    #[test]
+    fn test_prehashed() {
+        let hash = hash_prehashed(&prehash_password("secret")).unwrap();
+        let params = challenge_params(&hash).unwrap();
+        let nonce = generate_nonce();
+
+        let signature = sign_nonce(&derive_key("secret", &params).unwrap(), &nonce);
+        assert!(verify_signature(
+            &stored_key(&hash).unwrap(),
+            &nonce,
+            &signature.unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_upgrade_hash() {
+        let salt = SaltString::encode_b64(b"saltsaltsaltsalt").unwrap();
+        let legacy = Argon2::default()
+            .hash_password(prehash_password("secret").as_bytes(), &salt)
+            .unwrap()
+            .to_string();
+        assert!(is_argon2_hash(&legacy));
+
+        let hash = upgrade_hash(&legacy).unwrap();
+        assert!(is_hash(&hash));
+        // Params are the same, so the clients derive the same key
+        assert_eq!(
+            challenge_params(&hash).unwrap(),
+            challenge_params(&legacy).unwrap()
+        );
+
+        let nonce = generate_nonce();
+        let key = derive_key("secret", &challenge_params(&hash).unwrap()).unwrap();
+        let signature = sign_nonce(&key, &nonce).unwrap();
+        assert!(verify_signature(
+            &stored_key(&hash).unwrap(),
+            &nonce,
+            &signature
+        ));
+    }
+
+    #[test]
+    fn test_expensive_params() {
+        let params = "$argon2id$v=19$m=4194304,t=2,p=1$c2FsdHNhbHRzYWx0";
+        assert!(matches!(
+            derive_key("secret", params),
+            Err(CryptographyError::ParamsTooExpensive)
+        ));
+    }
+}
@@ -9,14 +9,14 @@ pub const APPLICATION: &str = "xailyser";
 pub fn get_storage_file_path(
     file_name: &str, file_kind: FileKind,
 ) -> Result<PathBuf, std::io::Error> {
+    Ok(get_storage_dir_path(file_kind)?.join(file_name))
+}
+
+pub fn get_storage_dir_path(file_kind: FileKind) -> Result<PathBuf, std::io::Error> {
     let dirs = ProjectDirs::from(QUALIFIER, ORGANIZATION, APPLICATION);
     match dirs {
-        None => {
-            let mut current_dir = env::current_dir()?;
-            current_dir.push(file_name);
-            Ok(current_dir)
-        },
-        Some(value) => Ok(file_kind.into_path(&value).join(file_name)),
+        None => env::current_dir(),
+        Some(value) => Ok(file_kind.into_path(&value).to_path_buf()),
     }
 }
 
@@ -1,7 +1,11 @@
 pub mod auth;
+pub mod binary;
 pub mod channel;
 pub mod compression;
 pub mod cryptography;
 pub mod io;
 pub mod logging;
 pub mod messages;
+pub mod recording;
+pub mod schedule;
+pub mod suppression;
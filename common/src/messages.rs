@@ -1,22 +1,56 @@
-use dpi::dto::frame::FrameType;
+use crate::schedule::{CaptureWindow, ScheduleStatus};
+use dpi::dto::frame::{FrameHeader, FrameType, OwnedFrame};
+use dpi::protocols::ProtocolId;
+use dpi::protocols::ethernet::mac::MacAddress;
+use dpi::protocols::port_table::PortTable;
 use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
 use std::time::Duration;
+use strum_macros::{EnumDiscriminants, EnumIter};
 use thiserror::Error;
 
 pub const CONNECTION_TIMEOUT: Duration = Duration::from_millis(10);
+// Longest time, the frames are held by the server to be sent together
+pub const SEND_DELAY_MAX_MS: u64 = 200;
 
-#[derive(Debug, Serialize, Deserialize)]
+// Bumped on breaking changes of the messages. Session recordings are checked against it.
+pub const PROTOCOL_VERSION: u32 = 15;
+
+#[derive(Debug, Serialize, Deserialize, EnumDiscriminants)]
+#[strum_discriminants(name(RequestKind), derive(Hash, EnumIter))]
 pub enum Request {
     ChangePassword(String), // Change a password to another (not encrypted)
-    Reboot,         // Reboot server (needed to apply changing password, for example)
-    SaveConfig,     // Save the config
+    GetFailureSamples,      // Frames, the parser failed on. Only if enabled in the config
+    Logout(String),         // Revoke the session token
+    Reboot, // Reboot server (needed to apply changing password, for example)
+    ResetFailureSamples, // Drop the kept failure samples & their counts
+    ResetStatistics, // Zero the statistics, in memory & in the file
+    SaveConfig, // Save the config
     ServerSettings, // Interfaces, etc.
+    SetCaptureBufferSize(i32), // Capture options, applied on the next interface open
+    SetCaptureImmediateMode(bool),
+    SetCapturePromiscuous(bool),
+    SetCaptureSnaplen(i32),
     SetCompression(bool), // Compression: On or Off
     SetInterface(String), // Set an ethernet interface
+    // Frames, that match the filter, are sent to the target. Disabling ignores the rest
+    SetMirror {
+        enabled: bool,
+        target: SocketAddr,
+        filter: MirrorFilter,
+    },
+    // Ports of the protocol, empty list brings back the defaults. Applied at once
+    SetProtocolPorts(ProtocolId, Vec<u16>),
+    // Windows of the capture, empty list captures all the time. Applied at once
+    SetSchedule(Vec<CaptureWindow>),
+    // Milliseconds, the frames are held to be sent together. Of this connection only
+    SetSendDelay(u64),
     SetSendUnparsedFrames(bool), // Set "Send unparsed frames" option
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Response {
     // Data itself
     Data(FrameType),
@@ -24,49 +58,291 @@ pub enum Response {
     // Pong (Heartbeat)
     SuccessSync,
 
+    // Capture statistics: Received & dropped packets
+    CaptureStats(CaptureStatsDto),
+
+    // No packets on the interface for a while, while the capture is running
+    CaptureIdle { seconds: u64 },
+
+    // Capture is paused or resumed by the schedule
+    CaptureSchedule(ScheduleStatus),
+
+    // Addresses of the capture interface are changed, e.g. by the DHCP renew
+    InterfaceAddressChanged(AddressChangeDto),
+
+    // Buckets of the parse failures with the example frames
+    FailureSamples(FailureSamplesDto),
+
+    // Aggregates of the frames, while only a part of them is sent (sampled mode)
+    Summary(SummaryDto),
+
+    // Aggregate statistics of the server, accumulated across restarts if persisted
+    Statistics(StatisticsDto),
+
     // Settings: Interfaces, etc.
     ServerSettings(ServerSettingsDto), // Interfaces, etc.
 
     // Success
-    SuccessChangePassword,
+    // Session token of the issuing client. Other connections are closed, their tokens revoked
+    SuccessChangePassword(Option<String>),
+    SuccessResetStatistics(StatisticsDto),
     SuccessSaveConfig,
-    SuccessSetCompression(bool),
+    SuccessSetCaptureOptions(CaptureOptionsDto),
+    // Applied to the new connections only, the current one keeps its compression
+    SuccessSetCompression { enabled: bool, current: bool },
     SuccessSetInterface(String),
+    SuccessSetMirror(bool),
+    SuccessSetProtocolPorts(PortTable),
+    SuccessSetSchedule(Vec<CaptureWindow>),
+    SuccessSetSendDelay(u64),
     SuccessSetSendUnparsedFrames(bool),
 
     // Error
     Error(ServerError),
 }
 
-#[derive(Debug, Error, Serialize, Deserialize)]
+#[derive(Debug, Clone, Error, Serialize, Deserialize)]
 pub enum ServerError {
+    #[error("Password is changed by another client.")]
+    CredentialsChanged,
+
     #[error("Failed to change password.")]
     FailedToChangePassword,
 
+    #[error("Failure samples are disabled in the config.")]
+    FailureSamplesDisabled,
+
     #[error("Failed to get server network interfaces list.")]
     FailedToGetInterfaces,
 
     #[error("Failed to save config file.")]
     FailedToSaveConfig,
 
+    #[error("Failed to save statistics file.")]
+    FailedToSaveStatistics,
+
+    #[error("Invalid capture option.")]
+    InvalidCaptureOption,
+
     #[error("Invalid message format.")]
     InvalidMessageFormat,
 
     #[error("Invalid interface.")]
     InvalidInterface,
 
+    #[error("Invalid mirror target: {0}.")]
+    InvalidMirrorTarget(SocketAddr),
+
+    #[error("Protocol isn't recognized by the ports: {0}.")]
+    InvalidProtocolPorts(ProtocolId),
+
+    #[error("Send delay is longer than {SEND_DELAY_MAX_MS} ms: {0}.")]
+    InvalidSendDelay(u64),
+
     #[error("Mutex poisoned.")]
     MutexPoisoned,
+
+    #[error("Unsupported link type of the interface: {0}.")]
+    UnsupportedLinkType(i32),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerSettingsDto {
-    pub compression_active: bool,
+    pub capture_options_active: Option<CaptureOptionsDto>,
+    pub capture_options_config: CaptureOptionsDto,
+    pub compression_active: bool, // Of the connection, the new ones get the config value
     pub compression_config: bool,
+    // Revisions of the password: the connection is authorized with & the current one
+    pub credentials_active: u64,
+    pub credentials_config: u64,
+    pub failure_samples_config: bool, // Not switched at runtime
+    pub flow_export_active: Option<FlowExporterDto>,
+    pub flow_export_config: bool,
     pub interface_active: Option<String>,
     pub interface_config: Option<String>,
-    pub interfaces_available: Vec<String>,
+    pub interfaces_available: Vec<InterfaceDto>,
     pub link_type: Option<i32>,
+    pub mirror_active: Option<MirrorDto>, // Enabled by the request only, not by the config
+    pub pcap_writer_active: Option<PcapWriterDto>,
+    pub pcap_writer_config: bool,
+    pub protocol_ports: PortTable, // Applied at once, so it's the active one
+    pub schedule: Vec<CaptureWindow>, // Same
+    pub schedule_status: ScheduleStatus,
+    pub send_delay_active: u64, // Of the connection, the new ones get the config value
+    pub send_delay_config: u64,
     pub send_unparsed_frames_active: bool,
     pub send_unparsed_frames_config: bool,
 }
+
+// Fields, that can't be read on the platform of the server, are None
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InterfaceDto {
+    pub name: String,
+    pub addresses: Vec<IpAddr>,
+    pub mtu: Option<u32>,
+    pub speed_mbps: Option<u64>,
+    pub is_up: Option<bool>,
+}
+
+// Options the capture handle is opened with
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CaptureOptionsDto {
+    pub buffer_size: i32, // Bytes, zero keeps the libpcap default
+    pub immediate_mode: bool,
+    pub promiscuous: bool,
+    pub snaplen: i32,
+}
+
+impl CaptureOptionsDto {
+    // Maximum snapshot length of libpcap
+    pub const SNAPLEN_MAX: i32 = 262144;
+
+    pub fn is_valid_snaplen(snaplen: i32) -> bool {
+        (1..=Self::SNAPLEN_MAX).contains(&snaplen)
+    }
+
+    pub fn is_valid_buffer_size(buffer_size: i32) -> bool {
+        buffer_size >= 0
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlowExporterDto {
+    pub collector: String,
+    pub protocol: String,
+    pub flows_active: usize,
+    pub flows_exported: u64,
+    pub datagrams_sent: u64,
+}
+
+// Rotating pcap files on the disk of the server. Error is kept until the writing resumes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PcapWriterDto {
+    pub directory: String,
+    pub file: Option<String>, // Current file, None while the writing fails
+    pub bytes_written: u64,   // Since the start of the server, to all the files
+    pub files_kept: usize,
+    pub error: Option<String>,
+}
+
+// Traffic mirror to the other analyzer. Counters are reset, when the mirror is enabled.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MirrorDto {
+    pub target: SocketAddr,
+    pub filter: MirrorFilter,
+    pub encapsulation: String,
+    pub packets_per_second: u64, // Limits, zero is unlimited
+    pub kilobytes_per_second: u64,
+    pub mirrored: u64,
+    pub dropped_by_limit: u64,
+    pub error: Option<String>, // Last failed send, kept until the next successful one
+}
+
+// Addresses, that differ from the previous check of the capture interface
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressChangeDto {
+    pub interface: String,
+    pub removed: Vec<IpAddr>,
+    pub added: Vec<IpAddr>,
+}
+
+// Frames, that fail on the same protocol by the same reason
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailureBucketDto {
+    pub protocol: ProtocolId,
+    pub reason: String,
+    pub failures: u64,
+    pub samples: Vec<OwnedFrame>, // First ones of the bucket
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FailureSamplesDto {
+    pub link_type: Option<i32>, // Of the samples. Buckets are reset on its change
+    pub buckets: Vec<FailureBucketDto>,
+    // Failures, that didn't get the bucket over the limit
+    pub unbucketed: u64,
+}
+
+// Frame matches, if the address is its source or destination
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MirrorFilter {
+    Mac(MacAddress),
+    Ip(IpAddr),
+}
+
+impl Display for MirrorFilter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MirrorFilter::Mac(mac) => write!(f, "{mac}"),
+            MirrorFilter::Ip(ip) => write!(f, "{ip}"),
+        }
+    }
+}
+
+// IP address is tried first, so "::" isn't taken for the MAC
+impl FromStr for MirrorFilter {
+    type Err = MirrorFilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Ok(ip) = IpAddr::from_str(s) {
+            return Ok(MirrorFilter::Ip(ip));
+        }
+        MacAddress::try_from(s)
+            .map(MirrorFilter::Mac)
+            .map_err(|_| MirrorFilterError::InvalidAddress)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum MirrorFilterError {
+    #[error("Address is neither MAC nor IP.")]
+    InvalidAddress,
+}
+
+// Counters are cumulative since the capture was opened, as reported by libpcap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CaptureStatsDto {
+    pub received: u32,
+    pub dropped: u32,
+    pub if_dropped: u32,
+}
+
+// Sent periodically while the connection can't keep up with the traffic,
+// and only 1 of `sample_rate` frames is sent as `Response::Data`.
+// Counters cover all the frames of the interval, including the sent ones.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SummaryDto {
+    // False in the last summary, when the server switched back to sending every frame
+    pub sampled: bool,
+    pub sample_rate: u32,
+    pub interval_ms: u64,
+    pub frames: u64,
+    pub bytes: u64,
+    // Headers of the first & last frames of the interval, for the capture time
+    pub first: Option<FrameHeader>,
+    pub last: Option<FrameHeader>,
+    pub protocols: Vec<ProtocolCountersDto>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProtocolCountersDto {
+    pub protocol: ProtocolId,
+    pub frames: u64,
+    pub bytes: u64,
+}
+
+// Totals are accumulated since `since`, and survive the restarts if `persistent`.
+// Uptime is summed over the runs, `process_started` is the start of the current one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatisticsDto {
+    pub frames: u64,
+    pub bytes: u64,
+    pub protocols: Vec<ProtocolCountersDto>,
+    pub dropped: u64,
+    pub if_dropped: u64,
+    pub uptime_seconds: u64,
+    pub since: u64,           // Unix seconds
+    pub process_started: u64, // Unix seconds
+    pub persistent: bool,
+}
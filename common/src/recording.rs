@@ -0,0 +1,223 @@
+// Session recording: Responses received by the client, with the time of receipt.
+// Gzipped JSON Lines, the first line is the header with the protocol version.
+
+use crate::messages::{PROTOCOL_VERSION, Response};
+use chrono::Local;
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Lines, Read, Write};
+use std::path::Path;
+use thiserror::Error;
+
+// Entries are flushed in batches, so a crash loses only the last ones
+const FLUSH_EVERY: usize = 100;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Header {
+    pub protocol_version: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Entry<R> {
+    // Unix time, milliseconds
+    pub received_ms: i64,
+    pub response: R,
+}
+
+impl<R> Entry<R> {
+    /// Entry, received right now.
+    pub fn now(response: R) -> Self {
+        Self {
+            received_ms: Local::now().timestamp_millis(),
+            response,
+        }
+    }
+}
+
+impl<R: Serialize> Entry<R> {
+    pub fn line(&self) -> Result<String, RecordingError> {
+        serde_json::to_string(self).map_err(RecordingError::JsonError)
+    }
+}
+
+pub struct Recorder<W: Write> {
+    encoder: GzEncoder<W>,
+    unflushed: usize,
+}
+
+impl Recorder<BufWriter<File>> {
+    pub fn create(path: &Path) -> Result<Self, RecordingError> {
+        crate::io::create_parent_directories(path)?;
+        let file = File::create(path)?;
+
+        Self::new(BufWriter::new(file))
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    pub fn new(writer: W) -> Result<Self, RecordingError> {
+        let mut recorder = Self {
+            encoder: GzEncoder::new(writer, Compression::default()),
+            unflushed: 0,
+        };
+
+        let header = Header {
+            protocol_version: PROTOCOL_VERSION,
+        };
+        let header = serde_json::to_string(&header).map_err(RecordingError::JsonError)?;
+        recorder.write_line(&header)?;
+        recorder.flush()?;
+
+        Ok(recorder)
+    }
+
+    pub fn write_line(&mut self, line: &str) -> Result<(), RecordingError> {
+        self.encoder.write_all(line.as_bytes())?;
+        self.encoder.write_all(b"\n")?;
+
+        self.unflushed = self.unflushed.saturating_add(1);
+        if self.unflushed >= FLUSH_EVERY {
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), RecordingError> {
+        self.encoder.flush()?;
+        self.unflushed = 0;
+
+        Ok(())
+    }
+
+    pub fn finish(self) -> Result<W, RecordingError> {
+        Ok(self.encoder.finish()?)
+    }
+}
+
+pub struct Player<R: Read> {
+    lines: Lines<BufReader<GzDecoder<R>>>,
+}
+
+impl Player<File> {
+    pub fn open(path: &Path) -> Result<Self, RecordingError> {
+        Self::new(File::open(path)?)
+    }
+}
+
+impl<R: Read> Player<R> {
+    /// Fails, if the file is recorded with another protocol version.
+    pub fn new(reader: R) -> Result<Self, RecordingError> {
+        let mut lines = BufReader::new(GzDecoder::new(reader)).lines();
+
+        let header = lines.next().ok_or(RecordingError::HeaderMissing)??;
+        let header: Header =
+            serde_json::from_str(&header).map_err(|_| RecordingError::HeaderMissing)?;
+        if header.protocol_version != PROTOCOL_VERSION {
+            return Err(RecordingError::IncompatibleVersion {
+                found: header.protocol_version,
+                expected: PROTOCOL_VERSION,
+            });
+        }
+
+        Ok(Self { lines })
+    }
+}
+
+impl<R: Read> Iterator for Player<R> {
+    type Item = Result<Entry<Response>, RecordingError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let line = match self.lines.next()? {
+            Ok(line) => line,
+            // Recording is cut off, e.g. the client crashed
+            Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => return None,
+            Err(err) => return Some(Err(RecordingError::IOError(err))),
+        };
+
+        Some(serde_json::from_str(&line).map_err(RecordingError::JsonError))
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum RecordingError {
+    #[error("IO Error. {0}")]
+    IOError(#[from] std::io::Error),
+
+    #[error("Recording is made with the protocol version {found}, expected {expected}.")]
+    IncompatibleVersion { found: u32, expected: u32 },
+
+    #[error("JSON Error. {0}")]
+    JsonError(serde_json::Error),
+
+    #[error("Header is missing, the file is not a session recording.")]
+    HeaderMissing,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::ServerError;
+
+    #[test]
+    fn test_record_and_replay() {
+        let mut recorder = Recorder::new(Vec::new()).unwrap();
+        let responses = [
+            Response::SuccessSync,
+            Response::Error(ServerError::InvalidCaptureOption),
+        ];
+        for response in &responses {
+            let line = Entry::now(response).line().unwrap();
+            recorder.write_line(&line).unwrap();
+        }
+        let file = recorder.finish().unwrap();
+
+        let player = Player::new(file.as_slice()).unwrap();
+        let entries = player.collect::<Result<Vec<_>, _>>();
+        let entries = entries.unwrap();
+        assert_eq!(entries.len(), 2);
+        assert!(matches!(entries[0].response, Response::SuccessSync));
+        assert!(matches!(
+            entries[1].response,
+            Response::Error(ServerError::InvalidCaptureOption)
+        ));
+        assert!(entries[0].received_ms <= entries[1].received_ms);
+    }
+
+    #[test]
+    fn test_incompatible_version() {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        let header = format!(
+            "{{\"protocol_version\":{}}}\n",
+            PROTOCOL_VERSION.saturating_add(1)
+        );
+        encoder.write_all(header.as_bytes()).unwrap();
+        let file = encoder.finish().unwrap();
+
+        let result = Player::new(file.as_slice());
+        assert!(matches!(
+            result,
+            Err(RecordingError::IncompatibleVersion { .. })
+        ));
+
+        // Not a recording at all
+        let result = Player::new(b"plain text".as_slice());
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_cut_off_recording() {
+        let mut recorder = Recorder::new(Vec::new()).unwrap();
+        let line = Entry::now(Response::SuccessSync).line().unwrap();
+        recorder.write_line(&line).unwrap();
+        recorder.flush().unwrap();
+
+        // Trailer is missing
+        let file = recorder.encoder.get_ref().clone();
+        let player = Player::new(file.as_slice()).unwrap();
+        assert_eq!(player.filter(Result::is_ok).count(), 1);
+    }
+}
@@ -0,0 +1,334 @@
+// Capture runs only within the windows of the schedule, the pcap handle is closed outside.
+// Boundaries are recomputed from the wall clock on every check, instead of sleeping until
+// them, so the clock changes (DST, manual) are followed.
+
+use chrono::{
+    DateTime, Datelike, Days, FixedOffset, Local, LocalResult, NaiveDate, NaiveDateTime,
+    NaiveTime, TimeDelta, TimeZone, Utc, Weekday,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use thiserror::Error;
+
+// Days around the current one, the windows are built for. Overnight window of yesterday
+// may still run, and every weekday comes within the week ahead
+const DAYS_BEFORE: u64 = 1;
+const DAYS_AFTER: u64 = 8;
+// Capture, that runs longer than that, is shown without the stop
+const CONTINUOUS_AFTER: TimeDelta = TimeDelta::days(7);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CaptureWindow {
+    pub days: Vec<Weekday>, // Days, the window starts on. Empty list means every day
+    pub start: NaiveTime,
+    // Not later than the start means the next day (overnight). Equal one means 24 hours
+    pub end: NaiveTime,
+    #[serde(default)]
+    pub timezone: ScheduleTimezone,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub enum ScheduleTimezone {
+    #[default]
+    Local, // Of the server
+    Fixed(FixedOffset),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduleStatus {
+    pub is_active: bool,
+    // Next stop or start of the capture. None, if the schedule doesn't change it
+    pub next_change: Option<DateTime<Utc>>,
+}
+
+impl Default for ScheduleStatus {
+    // Empty schedule doesn't restrict the capture
+    fn default() -> Self {
+        Self {
+            is_active: true,
+            next_change: None,
+        }
+    }
+}
+
+/// Overlapping & adjacent windows are merged, so the capture isn't reopened between them.
+pub fn status(windows: &[CaptureWindow], now: DateTime<Utc>) -> ScheduleStatus {
+    if windows.is_empty() {
+        return ScheduleStatus::default();
+    }
+
+    let mut intervals: Vec<(DateTime<Utc>, DateTime<Utc>)> = windows
+        .iter()
+        .flat_map(|window| window.intervals(now))
+        .collect();
+    intervals.sort();
+    let mut merged: Vec<(DateTime<Utc>, DateTime<Utc>)> = Vec::new();
+    for (start, end) in intervals {
+        match merged.last_mut() {
+            Some((_, last_end)) if start <= *last_end => *last_end = (*last_end).max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+
+    let current = merged
+        .iter()
+        .find(|(start, end)| *start <= now && now < *end);
+    match current {
+        Some((_, end)) => ScheduleStatus {
+            is_active: true,
+            next_change: (*end - now <= CONTINUOUS_AFTER).then_some(*end),
+        },
+        None => ScheduleStatus {
+            is_active: false,
+            next_change: merged
+                .iter()
+                .map(|(start, _)| *start)
+                .find(|start| *start > now),
+        },
+    }
+}
+
+impl CaptureWindow {
+    fn intervals(&self, now: DateTime<Utc>) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+        let Some(first) = self
+            .timezone
+            .today(now)
+            .checked_sub_days(Days::new(DAYS_BEFORE))
+        else {
+            return vec![];
+        };
+
+        (0..=DAYS_BEFORE + DAYS_AFTER)
+            .filter_map(|offset| first.checked_add_days(Days::new(offset)))
+            .filter(|date| self.days.is_empty() || self.days.contains(&date.weekday()))
+            .filter_map(|date| {
+                let end_date = match self.end > self.start {
+                    true => date,
+                    false => date.succ_opt()?,
+                };
+                let start = self.timezone.to_utc(date.and_time(self.start))?;
+                let end = self.timezone.to_utc(end_date.and_time(self.end))?;
+                Some((start, end))
+            })
+            .filter(|(start, end)| start < end)
+            .collect()
+    }
+}
+
+impl ScheduleTimezone {
+    fn today(&self, now: DateTime<Utc>) -> NaiveDate {
+        match self {
+            Self::Local => now.with_timezone(&Local).date_naive(),
+            Self::Fixed(offset) => now.with_timezone(offset).date_naive(),
+        }
+    }
+
+    // Repeated time is taken at its first occurrence. Skipped one is moved by an hour,
+    // as the usual clock change, so the window isn't lost for the day
+    fn to_utc(&self, time: NaiveDateTime) -> Option<DateTime<Utc>> {
+        let resolve = |time: NaiveDateTime| match self {
+            Self::Local => earliest(Local.from_local_datetime(&time)),
+            Self::Fixed(offset) => earliest(offset.from_local_datetime(&time)),
+        };
+        resolve(time).or_else(|| resolve(time.checked_add_signed(TimeDelta::hours(1))?))
+    }
+}
+
+fn earliest<Tz: TimeZone>(result: LocalResult<DateTime<Tz>>) -> Option<DateTime<Utc>> {
+    result.earliest().map(|time| time.with_timezone(&Utc))
+}
+
+impl Display for CaptureWindow {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let days = match self.days.is_empty() {
+            true => "Every day".to_string(),
+            false => self
+                .days
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<String>>()
+                .join(", "),
+        };
+        write!(
+            f,
+            "{days} {}-{} ({})",
+            self.start.format("%H:%M"),
+            self.end.format("%H:%M"),
+            self.timezone
+        )
+    }
+}
+
+impl Display for ScheduleTimezone {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Local => write!(f, "local"),
+            Self::Fixed(offset) if offset.local_minus_utc() == 0 => write!(f, "UTC"),
+            Self::Fixed(offset) => write!(f, "{offset}"),
+        }
+    }
+}
+
+impl FromStr for ScheduleTimezone {
+    type Err = ScheduleError;
+
+    // "local", "UTC" or the offset: "+02:00", "-5"
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let error = || ScheduleError::InvalidTimezone(value.to_string());
+        let trimmed = value.trim();
+        if trimmed.eq_ignore_ascii_case("local") {
+            return Ok(Self::Local);
+        }
+        if trimmed.eq_ignore_ascii_case("utc") {
+            return Ok(Self::Fixed(FixedOffset::east_opt(0).ok_or_else(error)?));
+        }
+
+        let (sign, offset) = match trimmed.split_at_checked(1) {
+            Some(("+", offset)) => (1, offset),
+            Some(("-", offset)) => (-1, offset),
+            _ => return Err(error()),
+        };
+        let (hours, minutes) = offset.split_once(':').unwrap_or((offset, "0"));
+        let hours: i32 = hours.parse().map_err(|_| error())?;
+        let minutes: i32 = minutes.parse().map_err(|_| error())?;
+        if !(0..24).contains(&hours) || !(0..60).contains(&minutes) {
+            return Err(error());
+        }
+        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+            .map(Self::Fixed)
+            .ok_or_else(error)
+    }
+}
+
+impl From<ScheduleTimezone> for String {
+    fn from(value: ScheduleTimezone) -> Self {
+        value.to_string()
+    }
+}
+
+impl TryFrom<String> for ScheduleTimezone {
+    type Error = ScheduleError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::from_str(&value)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ScheduleError {
+    #[error("Invalid timezone: {0}. Expected \"local\", \"UTC\" or the offset (+02:00).")]
+    InvalidTimezone(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc() -> ScheduleTimezone {
+        ScheduleTimezone::Fixed(FixedOffset::east_opt(0).unwrap())
+    }
+
+    fn window(days: &[Weekday], start: (u32, u32), end: (u32, u32)) -> CaptureWindow {
+        CaptureWindow {
+            days: days.to_vec(),
+            start: NaiveTime::from_hms_opt(start.0, start.1, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(end.0, end.1, 0).unwrap(),
+            timezone: utc(),
+        }
+    }
+
+    // 2025-03-07 is Friday
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 3, day, hour, minute, 0)
+            .single()
+            .unwrap()
+    }
+
+    fn active(next_change: DateTime<Utc>) -> ScheduleStatus {
+        ScheduleStatus {
+            is_active: true,
+            next_change: Some(next_change),
+        }
+    }
+
+    fn paused(next_change: DateTime<Utc>) -> ScheduleStatus {
+        ScheduleStatus {
+            is_active: false,
+            next_change: Some(next_change),
+        }
+    }
+
+    #[test]
+    fn test_daily_window() {
+        let windows = [window(&[], (9, 0), (17, 30))];
+        assert_eq!(status(&windows, at(7, 8, 59)), paused(at(7, 9, 0)));
+        assert_eq!(status(&windows, at(7, 9, 0)), active(at(7, 17, 30)));
+        assert_eq!(status(&windows, at(7, 17, 30)), paused(at(8, 9, 0)));
+        assert_eq!(status(&[], at(7, 12, 0)), ScheduleStatus::default());
+    }
+
+    #[test]
+    fn test_overnight_window() {
+        let windows = [window(&[Weekday::Fri], (22, 0), (6, 0))];
+        assert_eq!(status(&windows, at(7, 21, 0)), paused(at(7, 22, 0)));
+        assert_eq!(status(&windows, at(8, 3, 0)), active(at(8, 6, 0)));
+        // Next Friday
+        assert_eq!(status(&windows, at(8, 6, 0)), paused(at(14, 22, 0)));
+    }
+
+    #[test]
+    fn test_overlapping_windows() {
+        let windows = [
+            window(&[], (8, 0), (12, 0)),
+            window(&[], (11, 0), (14, 0)),
+            // Adjacent, so the capture isn't reopened
+            window(&[], (14, 0), (15, 0)),
+        ];
+        assert_eq!(status(&windows, at(7, 9, 0)), active(at(7, 15, 0)));
+        assert_eq!(status(&windows, at(7, 15, 0)), paused(at(8, 8, 0)));
+
+        // Around the clock
+        let windows = [window(&[], (0, 0), (0, 0))];
+        assert_eq!(status(&windows, at(7, 12, 0)), ScheduleStatus::default());
+        let windows = [window(&[Weekday::Sat, Weekday::Sun], (0, 0), (0, 0))];
+        assert_eq!(status(&windows, at(8, 12, 0)), active(at(10, 0, 0)));
+    }
+
+    #[test]
+    fn test_timezone_offset() {
+        let mut window = window(&[Weekday::Mon], (9, 0), (10, 0));
+        window.timezone = ScheduleTimezone::from_str("+02:00").unwrap();
+        // Monday 09:00 at +02:00 is 07:00 UTC
+        assert_eq!(
+            status(&[window.clone()], at(7, 12, 0)),
+            paused(at(10, 7, 0))
+        );
+
+        // Status depends only on the given time, so the clock changes are followed
+        assert_eq!(status(&[window], at(10, 7, 30)), active(at(10, 8, 0)));
+    }
+
+    #[test]
+    fn test_timezone_parse() {
+        assert_eq!(
+            ScheduleTimezone::from_str("local").unwrap(),
+            ScheduleTimezone::Local
+        );
+        assert_eq!(ScheduleTimezone::from_str("UTC").unwrap(), utc());
+        assert_eq!(
+            ScheduleTimezone::from_str("-5").unwrap().to_string(),
+            "-05:00"
+        );
+        assert_eq!(utc().to_string(), "UTC");
+        assert!(ScheduleTimezone::from_str("+25:00").is_err());
+        assert!(ScheduleTimezone::from_str("Europe/Kyiv").is_err());
+
+        let window: CaptureWindow = serde_json::from_str(
+            r#"{"days":["Mon","Friday"],"start":"22:00","end":"06:00","timezone":"+02:00"}"#,
+        )
+        .unwrap();
+        assert_eq!(window.to_string(), "Mon, Fri 22:00-06:00 (+02:00)");
+    }
+}
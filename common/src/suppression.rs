@@ -0,0 +1,130 @@
+// Repeated failures of the same kind, e.g. every message of the incompatible or corrupted
+// stream. The first one is logged with the details, the rest only by the periodic
+// summaries, so the log stays readable & the formatting doesn't cost the CPU.
+// Streak ends with the first success.
+
+use std::time::{Duration, Instant};
+
+// Summaries of the suppressed failures aren't logged more often than that
+pub const SUMMARY_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, PartialEq)]
+pub enum Report {
+    // First failure of the streak, logged with the details
+    First,
+    // Failures since the previous report, this one included
+    Summary { suppressed: u64, seconds: u64 },
+    Suppressed,
+}
+
+pub struct FailureStreak {
+    interval: Duration,
+    // Since the last success
+    failures: u64,
+    // Not logged since the last report
+    suppressed: u64,
+    reported_last: Option<Instant>,
+}
+
+impl FailureStreak {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            failures: 0,
+            suppressed: 0,
+            reported_last: None,
+        }
+    }
+
+    /// What is logged for the failure.
+    pub fn failed(&mut self, now: Instant) -> Report {
+        self.failures = self.failures.saturating_add(1);
+        self.suppressed = self.suppressed.saturating_add(1);
+
+        let Some(reported_last) = self.reported_last else {
+            self.reported_last = Some(now);
+            self.suppressed = 0;
+            return Report::First;
+        };
+        let elapsed = now.saturating_duration_since(reported_last);
+        if elapsed < self.interval {
+            return Report::Suppressed;
+        }
+        self.reported_last = Some(now);
+
+        Report::Summary {
+            suppressed: std::mem::take(&mut self.suppressed),
+            seconds: elapsed.as_secs(),
+        }
+    }
+
+    /// Failures of the ended streak. None, if there was no streak.
+    pub fn succeeded(&mut self) -> Option<u64> {
+        self.suppressed = 0;
+        self.reported_last = None;
+        match std::mem::take(&mut self.failures) {
+            0 => None,
+            failures => Some(failures),
+        }
+    }
+
+    pub fn failures(&self) -> u64 {
+        self.failures
+    }
+}
+
+impl Default for FailureStreak {
+    fn default() -> Self {
+        Self::new(SUMMARY_INTERVAL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summaries() {
+        let mut streak = FailureStreak::new(Duration::from_secs(10));
+        let start = Instant::now();
+        let at = |millis: u64| start + Duration::from_millis(millis);
+
+        assert_eq!(streak.failed(at(0)), Report::First);
+        // 5 000 failures within the window are only counted
+        for index in 1..=5000 {
+            assert_eq!(streak.failed(at(index)), Report::Suppressed);
+        }
+        assert_eq!(
+            streak.failed(at(10_000)),
+            Report::Summary {
+                suppressed: 5001,
+                seconds: 10,
+            }
+        );
+        // Window starts from the summary
+        assert_eq!(streak.failed(at(15_000)), Report::Suppressed);
+        assert_eq!(
+            streak.failed(at(21_500)),
+            Report::Summary {
+                suppressed: 2,
+                seconds: 11,
+            }
+        );
+        assert_eq!(streak.failures(), 5004);
+    }
+
+    #[test]
+    fn test_reset_by_success() {
+        let mut streak = FailureStreak::new(Duration::from_secs(10));
+        let start = Instant::now();
+
+        assert_eq!(streak.succeeded(), None);
+        assert_eq!(streak.failed(start), Report::First);
+        assert_eq!(streak.failed(start), Report::Suppressed);
+        assert_eq!(streak.succeeded(), Some(2));
+        assert_eq!(streak.failures(), 0);
+
+        // New streak is logged with the details again
+        assert_eq!(streak.failed(start), Report::First);
+    }
+}
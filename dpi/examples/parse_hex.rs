@@ -0,0 +1,89 @@
+// Parses the frame, given as the hex string, and prints its layers with the fields.
+//
+// cargo run -p dpi --example parse_hex -- "00 1A 8C 10 AD 30 00 1E 68 51 4F A9 08 00 ..."
+// cargo run -p dpi --example parse_hex --no-default-features -- --link-type 228 "45 00 ..."
+
+use dpi::dto::fields::FieldIter;
+use dpi::dto::frame::{FrameHeader, FrameType, LinkType};
+use dpi::parser::ProtocolParser;
+use std::env;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: parse_hex [--link-type <LINKTYPE>] <HEX>...
+
+Spaces & colons in the hex are ignored, it may be split into several arguments.
+Link type is the LINKTYPE_ value of pcap, Ethernet (1) by default.";
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+
+    let (link_type, frame) = match parse_args(args) {
+        Ok(value) => value,
+        Err(message) => {
+            eprintln!("{message}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        },
+    };
+    if !ProtocolParser::is_supported(link_type) {
+        eprintln!("Unsupported link type: {}.", link_type.0);
+        return ExitCode::FAILURE;
+    }
+
+    let header = FrameHeader::new(frame.len());
+    let parser = ProtocolParser::new(link_type, false);
+    let Some((frame, failure)) = parser.process_bytes_traced(&header, &frame) else {
+        println!("Frame isn't parsed.");
+        return ExitCode::SUCCESS;
+    };
+    match frame {
+        FrameType::Metadata(metadata) => {
+            for layer in metadata.layers {
+                println!("{}", layer.id());
+                for (name, value) in layer.fields() {
+                    println!("    {name}: {value}");
+                }
+            }
+        },
+        _ => println!("Frame isn't parsed."),
+    }
+    // E.g. "TCP: Truncated: 20 bytes needed, 10 available."
+    if let Some(failure) = failure {
+        println!("Failed layer. {failure}");
+    }
+
+    ExitCode::SUCCESS
+}
+
+fn parse_args(args: Vec<String>) -> Result<(LinkType, Vec<u8>), String> {
+    let mut link_type = LinkType::ETHERNET;
+    let mut hex = String::new();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--link-type" {
+            let value = args.next().ok_or("Link type is missing.")?;
+            let value = value
+                .parse()
+                .map_err(|_| format!("Wrong link type: {value}."))?;
+            link_type = LinkType(value);
+        } else {
+            hex.push_str(&arg);
+        }
+    }
+
+    let hex = hex
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != ':')
+        .collect::<String>();
+    if hex.is_empty() {
+        return Err("Frame is missing.".to_string());
+    }
+    let frame = hex::decode(hex).map_err(|err| format!("Wrong hex: {err}."))?;
+
+    Ok((link_type, frame))
+}
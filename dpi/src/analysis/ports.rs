@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::io;
 use std::path::PathBuf;
+use strum_macros::{Display, EnumIter, EnumString};
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct PortInfo {
@@ -14,6 +15,22 @@ pub struct PortInfo {
 pub type Port = u16;
 pub type PortServiceTable = HashMap<Port, Vec<PortInfo>>;
 
+/// Formats of the port databases, that may be imported instead of the bundled one.
+#[derive(Clone, Copy, Debug, Default, Display, EnumIter, EnumString, PartialEq)]
+pub enum DatabaseFormat {
+    // service-names-port-numbers.csv, as the bundled database
+    #[default]
+    IanaCsv,
+    // nmap-services, also used by Wireshark as the "services" file
+    NmapServices,
+}
+
+pub struct ParsedDatabase {
+    pub table: PortServiceTable,
+    // Malformed lines, that are skipped
+    pub skipped: usize,
+}
+
 pub fn read_database(path: PathBuf) -> io::Result<PortServiceTable> {
     let file = std::fs::File::open(path)?;
     let mut reader = csv::ReaderBuilder::new().from_reader(io::BufReader::new(file));
@@ -62,6 +79,115 @@ pub fn read_database(path: PathBuf) -> io::Result<PortServiceTable> {
     Ok(map)
 }
 
+/// Entries without the service name (reserved & unassigned ports) aren't malformed,
+/// but they're skipped too: they don't name anything.
+pub fn parse_database(data: &str, format: DatabaseFormat) -> ParsedDatabase {
+    match format {
+        DatabaseFormat::IanaCsv => parse_iana(data),
+        DatabaseFormat::NmapServices => parse_nmap(data),
+    }
+}
+
+// Service Name,Port Number,Transport Protocol,Description,...
+// Port may be the range: x11,6000-6063,tcp,X Window System,...
+fn parse_iana(data: &str) -> ParsedDatabase {
+    let mut reader = csv::ReaderBuilder::new()
+        .flexible(true)
+        .from_reader(data.as_bytes());
+    let mut database = ParsedDatabase {
+        table: PortServiceTable::new(),
+        skipped: 0,
+    };
+
+    for result in reader.records() {
+        let Ok(record) = result else {
+            database.skipped = database.skipped.saturating_add(1);
+            continue;
+        };
+        let (Some(service_name), Some(ports), Some(transport_protocol)) =
+            (record.get(0), record.get(1), record.get(2))
+        else {
+            database.skipped = database.skipped.saturating_add(1);
+            continue;
+        };
+        let (service_name, ports) = (service_name.trim(), ports.trim());
+        // Services without the assigned port are listed too
+        if service_name.is_empty() || ports.is_empty() {
+            continue;
+        }
+        let Some((first, last)) = port_range(ports) else {
+            database.skipped = database.skipped.saturating_add(1);
+            continue;
+        };
+
+        let description = record.get(3).unwrap_or_default().trim();
+        for port in first..=last {
+            database.table.entry(port).or_default().push(PortInfo {
+                port,
+                service_name: service_name.to_string(),
+                transport_protocol: transport_protocol.trim().to_string(),
+                description: description.to_string(),
+            });
+        }
+    }
+
+    database
+}
+
+// http	80/tcp	0.484143	# World Wide Web HTTP
+fn parse_nmap(data: &str) -> ParsedDatabase {
+    let mut database = ParsedDatabase {
+        table: PortServiceTable::new(),
+        skipped: 0,
+    };
+
+    for line in data.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (entry, description) = match line.split_once('#') {
+            Some((entry, comment)) => (entry, comment.trim()),
+            None => (line, ""),
+        };
+        let mut fields = entry.split_whitespace();
+        let parsed = fields
+            .next()
+            .zip(fields.next().and_then(|field| field.split_once('/')))
+            .and_then(|(name, (port, protocol))| {
+                let port = port.parse::<u16>().ok()?;
+                (!protocol.is_empty()).then_some((name, port, protocol))
+            });
+        let Some((service_name, port, transport_protocol)) = parsed else {
+            database.skipped = database.skipped.saturating_add(1);
+            continue;
+        };
+        // Nmap names the ports, that it has seen open, but doesn't know
+        if service_name == "unknown" {
+            continue;
+        }
+
+        database.table.entry(port).or_default().push(PortInfo {
+            port,
+            service_name: service_name.to_string(),
+            transport_protocol: transport_protocol.to_string(),
+            description: description.to_string(),
+        });
+    }
+
+    database
+}
+
+fn port_range(value: &str) -> Option<(Port, Port)> {
+    match value.split_once('-') {
+        Some((first, last)) => {
+            let (first, last) = (first.parse().ok()?, last.parse().ok()?);
+            (first <= last).then_some((first, last))
+        },
+        None => value.parse().ok().map(|port| (port, port)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -146,4 +272,66 @@ mod tests {
 
         assert_eq!(actual_port_info, &expected_port_info);
     }
+
+    // Lines of service-names-port-numbers.csv, the last ones are malformed
+    const IANA_SNIPPET: &str = "\
+Service Name,Port Number,Transport Protocol,Description,Assignee,Contact,Registration Date,Modification Date,Reference,Service Code,Unauthorized Use Reported,Assignment Notes
+,0,tcp,Reserved,[Jon_Postel],[Jon_Postel],,,,,,
+snmp,161,udp,SNMP,,,,,,,,
+pcsync-https,8443,tcp,PCsync HTTPS,,,,,,,,
+x11,6000-6002,tcp,X Window System,[Stephen_Casner],[Stephen_Casner],,,,,,Multiple Assignment
+sun-dr,665,tcp,Sun DR,,,,,,,,
+kerberos-adm,,,kerberos administration,,,,,,,,
+pcsync,eighty,tcp,PCsync,,,,,,,,
+\"broken,9000,tcp
+";
+
+    #[test]
+    fn test_iana_import() {
+        let database = parse_database(IANA_SNIPPET, DatabaseFormat::IanaCsv);
+        assert_eq!(database.skipped, 2);
+        // Reserved, without the port & the malformed ones aren't added
+        assert_eq!(database.table.len(), 6);
+        assert_eq!(
+            database.table.get(&8443),
+            Some(&vec![PortInfo {
+                port: 8443,
+                service_name: "pcsync-https".to_string(),
+                transport_protocol: "tcp".to_string(),
+                description: "PCsync HTTPS".to_string(),
+            }])
+        );
+        for port in 6000..=6002 {
+            let info = database.table.get(&port).unwrap();
+            assert_eq!(info[0].service_name, "x11");
+        }
+        assert!(!database.table.contains_key(&0));
+    }
+
+    #[test]
+    fn test_nmap_import() {
+        const SNIPPET: &str = "\
+# Fields in this file are: Service name, portnum/protocol, open-frequency, optional comments
+#
+tcpmux\t1/tcp\t0.001995\t# TCP Port Service Multiplexer [rfc-1078]
+http\t80/tcp\t0.484143\t# World Wide Web HTTP
+unknown\t1010/tcp\t0.000100
+snmp\t161/udp\t0.433467
+broken\t80\t0.1
+http-alt\t99999/tcp\t0.1
+";
+        let database = parse_database(SNIPPET, DatabaseFormat::NmapServices);
+        assert_eq!(database.skipped, 2);
+        assert_eq!(database.table.len(), 3);
+        assert_eq!(
+            database.table.get(&80),
+            Some(&vec![PortInfo {
+                port: 80,
+                service_name: "http".to_string(),
+                transport_protocol: "tcp".to_string(),
+                description: "World Wide Web HTTP".to_string(),
+            }])
+        );
+        assert_eq!(database.table.get(&161).unwrap()[0].description, "");
+    }
 }
@@ -0,0 +1,127 @@
+use std::fmt::Display;
+
+// Value of the missing field
+pub const NONE: &str = "-";
+
+/// Fields of the protocol record, shown by the generic detail view.
+/// <br> Implementations destructure the whole struct, so a new field can't be missed.
+pub trait FieldIter {
+    /// Pairs of the field name key & display value, in order of the definition.
+    fn fields(&self) -> Vec<(&'static str, String)>;
+}
+
+pub fn optional<T: Display>(value: &Option<T>) -> String {
+    match value {
+        Some(value) => value.to_string(),
+        None => NONE.to_string(),
+    }
+}
+
+pub fn list<T: Display>(values: &[T]) -> String {
+    match values.is_empty() {
+        true => NONE.to_string(),
+        false => values
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", "),
+    }
+}
+
+#[cfg(all(test, feature = "ipv4", feature = "ipv6", feature = "tcp"))]
+mod tests {
+    use super::*;
+    use crate::dto::metadata::ProtocolDto;
+    use crate::protocols::ip::checksum::ChecksumStatus;
+    use crate::protocols::ipv4::IPv4Dto;
+    use crate::protocols::ipv4::option::OptionData;
+    use crate::protocols::ipv6::IPv6Dto;
+    use crate::protocols::ipv6::extension::{ExtensionHeader, Fragment};
+    use crate::protocols::tcp::TcpDto;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    fn keys(fields: &[(&'static str, String)]) -> Vec<&'static str> {
+        fields.iter().map(|(key, _)| *key).collect()
+    }
+
+    #[test]
+    fn test_ipv4_fields() {
+        let dto = IPv4Dto {
+            address_source: Ipv4Addr::new(192, 168, 0, 1),
+            address_destination: Ipv4Addr::new(192, 168, 0, 2),
+            time_to_live: 64,
+            checksum_status: ChecksumStatus::Invalid,
+            options: vec![OptionData::RouterAlert(0)],
+        };
+
+        let fields = dto.fields();
+        assert_eq!(
+            keys(&fields),
+            vec![
+                "address_source",
+                "address_destination",
+                "time_to_live",
+                "checksum_status",
+                "options"
+            ]
+        );
+        assert_eq!(fields[0].1, "192.168.0.1");
+        assert_eq!(fields[2].1, "64");
+        assert_eq!(fields[3].1, "✗");
+        assert_eq!(fields[4].1, "Router Alert");
+    }
+
+    #[test]
+    fn test_tcp_fields() {
+        let dto = ProtocolDto::TCP(TcpDto {
+            port_source: 50000,
+            port_destination: 443,
+            checksum_status: ChecksumStatus::NotChecked,
+            window: 502,
+            syn: false,
+            fin: false,
+            reset: false,
+            maximum_segment_size: None,
+            window_scale: None,
+        });
+
+        let fields = dto.fields();
+        assert_eq!(
+            keys(&fields),
+            vec![
+                "port_source",
+                "port_destination",
+                "checksum_status",
+                "window",
+                "maximum_segment_size",
+                "window_scale"
+            ]
+        );
+        assert_eq!(fields[2].1, NONE);
+        assert_eq!(fields[1].1, "443");
+        assert_eq!(fields[3].1, "502");
+        assert_eq!(fields[5].1, NONE);
+    }
+
+    #[test]
+    fn test_list_values() {
+        let dto = IPv6Dto {
+            address_source: Ipv6Addr::LOCALHOST,
+            address_destination: Ipv6Addr::LOCALHOST,
+            hop_limit: 1,
+            extension_headers: vec![],
+        };
+        assert_eq!(dto.fields()[3], ("extension_headers", NONE.to_string()));
+
+        let headers = vec![
+            ExtensionHeader::HopByHop,
+            ExtensionHeader::Fragment(Fragment {
+                offset: 2,
+                more_fragments: true,
+                identification: 0xff,
+            }),
+        ];
+        assert_eq!(list(&headers), "Hop-by-Hop, Fragment (16, ID 0xff)");
+        assert_eq!(optional::<u8>(&None), NONE);
+    }
+}
@@ -1,7 +1,9 @@
 use crate::dto::metadata::FrameMetadataDto;
 use crate::protocols::ProtocolData;
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "pcap")]
 use std::collections::VecDeque;
+#[cfg(feature = "pcap")]
 use std::path::Path;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -15,13 +17,16 @@ pub enum FrameType {
 pub struct FrameMetadata {
     pub header: FrameHeader,
     pub layers: Vec<ProtocolData>,
+    // Upper layers may be cut off, then they're parsed as far as the captured bytes allow
+    pub is_truncated: bool,
 }
 
 impl FrameMetadata {
-    pub fn from_header(header: &pcap::PacketHeader) -> Self {
+    pub fn from_header(header: &FrameHeader) -> Self {
         Self {
-            header: FrameHeader::from(header),
+            header: header.clone(),
             layers: vec![],
+            is_truncated: header.is_truncated(),
         }
     }
 }
@@ -32,6 +37,7 @@ pub struct OwnedFrame {
     pub data: Vec<u8>,
 }
 
+#[cfg(feature = "pcap")]
 impl<'a> From<pcap::Packet<'a>> for OwnedFrame {
     fn from(packet: pcap::Packet<'a>) -> Self {
         OwnedFrame {
@@ -41,14 +47,75 @@ impl<'a> From<pcap::Packet<'a>> for OwnedFrame {
     }
 }
 
+/// Direction of the frame relative to the capturing host.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FrameDirection {
+    // The host is neither the source nor the destination, or it's not determined
+    #[default]
+    Unknown,
+    Ingress,
+    Egress,
+}
+
+/// Link-layer header type, numbered as the `LINKTYPE_` values of pcap. <br>
+/// Frames are parsed from the types, that are listed as the constants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct LinkType(pub i32);
+
+impl LinkType {
+    pub const ETHERNET: Self = Self(1);
+    pub const IEEE802_11: Self = Self(105);
+    pub const IEEE802_11_RADIOTAP: Self = Self(127);
+    pub const IPV4: Self = Self(228);
+    pub const IPV6: Self = Self(229);
+}
+
+#[cfg(feature = "pcap")]
+impl From<pcap::Linktype> for LinkType {
+    fn from(link_type: pcap::Linktype) -> Self {
+        Self(link_type.0)
+    }
+}
+
+/// Capture information of the frame, that comes with its bytes.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct FrameHeader {
+    // Timestamp
+    #[serde(rename = "s")]
     pub tv_sec: i64,
+    #[serde(rename = "us")]
     pub tv_usec: i64,
+    // Amount of the captured bytes, may be less than the length of the frame
+    #[serde(rename = "c")]
     pub caplen: u32,
+    #[serde(rename = "l")]
     pub len: u32,
 }
 
+impl FrameHeader {
+    /// Frame, captured whole, without the timestamp. E.g. the bytes from other sources.
+    pub fn new(length: usize) -> Self {
+        Self::captured(length, length)
+    }
+
+    /// Only `caplen` bytes of the frame of `len` bytes are captured. <br>
+    /// Lengths over `u32::MAX` are saturated, as pcap can't store them.
+    pub fn captured(caplen: usize, len: usize) -> Self {
+        Self {
+            tv_sec: 0,
+            tv_usec: 0,
+            caplen: u32::try_from(caplen).unwrap_or(u32::MAX),
+            len: u32::try_from(len).unwrap_or(u32::MAX),
+        }
+    }
+
+    /// Frame is cut by the snapshot length (or the capture hardware).
+    pub fn is_truncated(&self) -> bool {
+        self.caplen < self.len
+    }
+}
+
+#[cfg(feature = "pcap")]
 impl From<&pcap::PacketHeader> for FrameHeader {
     fn from(header: &pcap::PacketHeader) -> Self {
         #[cfg(target_os = "linux")]
@@ -79,6 +146,7 @@ impl From<&pcap::PacketHeader> for FrameHeader {
     }
 }
 
+#[cfg(feature = "pcap")]
 impl From<&FrameHeader> for pcap::PacketHeader {
     fn from(header: &FrameHeader) -> Self {
         #[cfg(target_os = "linux")]
@@ -115,16 +183,60 @@ impl From<&FrameHeader> for pcap::PacketHeader {
     }
 }
 
+#[cfg(feature = "pcap")]
 pub fn save_pcap<P: AsRef<Path>>(
     path: P, frames: &VecDeque<OwnedFrame>, link_type: pcap::Linktype,
 ) -> Result<(), pcap::Error> {
     let mut file = pcap::Capture::dead(link_type)?.savefile(path)?;
+    write_frames(&mut file, frames);
+    file.flush()?;
 
+    Ok(())
+}
+
+/// Appends the frames to the opened pcap file. It isn't flushed.
+#[cfg(feature = "pcap")]
+pub fn write_frames<'a>(
+    file: &mut pcap::Savefile, frames: impl IntoIterator<Item = &'a OwnedFrame>,
+) {
     for frame in frames {
         let header = pcap::PacketHeader::from(&frame.header);
         file.write(&pcap::Packet::new(&header, &frame.data));
     }
-    file.flush()?;
+}
 
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constructors() {
+        let header = FrameHeader::new(60);
+        assert_eq!((header.caplen, header.len), (60, 60));
+        assert!(!header.is_truncated());
+
+        let header = FrameHeader::captured(64, 1514);
+        assert!(header.is_truncated());
+        assert!(FrameMetadata::from_header(&header).is_truncated);
+        assert_eq!(FrameHeader::new(usize::MAX).len, u32::MAX);
+    }
+
+    #[cfg(feature = "pcap")]
+    #[test]
+    fn test_pcap_header_conversions() {
+        let header = FrameHeader {
+            tv_sec: 1_700_000_000,
+            tv_usec: 250_000,
+            caplen: 64,
+            len: 1514,
+        };
+        let converted = pcap::PacketHeader::from(&header);
+        assert_eq!((converted.caplen, converted.len), (64, 1514));
+        assert_eq!(FrameHeader::from(&converted), header);
+
+        let packet = pcap::Packet::new(&converted, &[0xAB; 64]);
+        let frame = OwnedFrame::from(packet);
+        assert_eq!(frame.header, header);
+        assert_eq!(frame.data.len(), 64);
+    }
 }
@@ -1,14 +1,22 @@
-use crate::dto::frame::{FrameHeader, FrameMetadata};
-use crate::protocols::{
-    ProtocolData, arp, dhcpv4, dhcpv6, dns, ethernet, http, icmpv4, icmpv6, ipv4, ipv6,
-    tcp, udp,
-};
+use crate::dto::frame::{FrameDirection, FrameHeader, FrameMetadata};
+use crate::dto::wire;
 use serde::{Deserialize, Serialize};
 
+// Generated with the protocols, see `protocols!`
+pub use crate::protocols::ProtocolDto;
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct FrameMetadataDto {
+    #[serde(rename = "h")]
     pub header: FrameHeader,
+    #[serde(rename = "l")]
     pub layers: Vec<ProtocolDto>,
+    // Set by the server, parser doesn't know the addresses of the host
+    #[serde(rename = "d", default, skip_serializing_if = "wire::is_default")]
+    pub direction: FrameDirection,
+    // Captured length is less than the wire one
+    #[serde(rename = "t", default, skip_serializing_if = "wire::is_default")]
+    pub is_truncated: bool,
 }
 
 impl From<FrameMetadata> for FrameMetadataDto {
@@ -16,46 +24,8 @@ impl From<FrameMetadata> for FrameMetadataDto {
         Self {
             header: value.header,
             layers: value.layers.into_iter().map(Into::into).collect(),
-        }
-    }
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
-pub enum ProtocolDto {
-    Ethernet(ethernet::EthernetDto),
-
-    Arp(arp::ArpDto),
-
-    DHCPv4(dhcpv4::DHCPv4Dto),
-    DHCPv6(dhcpv6::DHCPv6Dto),
-    DNS(dns::DnsDto),
-    HTTP(http::HttpDto),
-
-    IPv4(ipv4::IPv4Dto),
-    IPv6(ipv6::IPv6Dto),
-
-    ICMPv4(icmpv4::ICMPv4Dto),
-    ICMPv6(icmpv6::ICMPv6Dto),
-
-    TCP(tcp::TcpDto),
-    UDP(udp::UdpDto),
-}
-
-impl From<ProtocolData> for ProtocolDto {
-    fn from(value: ProtocolData) -> Self {
-        match value {
-            ProtocolData::Ethernet(value) => ProtocolDto::Ethernet(value.into()),
-            ProtocolData::Arp(value) => ProtocolDto::Arp(value.into()),
-            ProtocolData::DHCPv4(value) => ProtocolDto::DHCPv4(value.into()),
-            ProtocolData::DHCPv6(value) => ProtocolDto::DHCPv6(value.into()),
-            ProtocolData::DNS(value) => ProtocolDto::DNS(value.into()),
-            ProtocolData::HTTP(value) => ProtocolDto::HTTP(value.into()),
-            ProtocolData::IPv4(value) => ProtocolDto::IPv4(value.into()),
-            ProtocolData::IPv6(value) => ProtocolDto::IPv6(value.into()),
-            ProtocolData::ICMPv4(value) => ProtocolDto::ICMPv4(value.into()),
-            ProtocolData::ICMPv6(value) => ProtocolDto::ICMPv6(value.into()),
-            ProtocolData::TCP(value) => ProtocolDto::TCP(value.into()),
-            ProtocolData::UDP(value) => ProtocolDto::UDP(value.into()),
+            direction: FrameDirection::Unknown,
+            is_truncated: value.is_truncated,
         }
     }
 }
@@ -0,0 +1,220 @@
+// Frames are the most of the traffic between the server & the client, so their DTOs
+// are serialized compactly: fields have the short names (RFC ones, where they exist),
+// empty collections, `None` & the default values are skipped.
+// Renaming is a breaking change of the messages, `PROTOCOL_VERSION` of `common` is bumped.
+
+/// Used by `skip_serializing_if`, the field must have `#[serde(default)]` as well.
+pub fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
+#[cfg(all(test, feature = "protocols"))]
+mod tests {
+    use crate::dto::fields::FieldIter;
+    use crate::dto::frame::{FrameDirection, FrameHeader, FrameType, LinkType};
+    use crate::parser::ProtocolParser;
+    use crate::parser::tests::udp_frame;
+    use crate::protocols::ProtocolId;
+
+    // TCP, LLMNR query, padded ARP request, ICMPv4 echo, padded DNS query
+    const CORPUS: [&str; 5] = [
+        "40 61 86 9A F1 F5 00 1A 8C 15 F9 80 08 00 45 00 00 63 2B B1 00 00 34 06 79 17 48 0E D5 93 C0 A8 03 83 01 BB CB B8 EE BA 71 7C 18 D9 CD D6 50 18 01 B4 3D CA 00 00 17 03 01 00 36 B5 2A 58 A3 3D BD EC F3 7C C9 C4 43 B9 5D 94 C9 3D 9D E5 75 11 47 6E 2E A0 E0 8B 1B 64 44 BE D8 06 FE 5B 00 69 B3 12 D0 D9 37 87 87 F4 1C 42 E3 00 16 EE 14 CA 69",
+        "01 00 5E 00 00 FC 40 61 86 9A F1 F5 08 00 45 00 00 32 6A 3D 00 00 01 11 AA 56 C0 A8 03 83 E0 00 00 FC D5 48 14 EB 00 1E 20 88 76 F2 00 00 00 01 00 00 00 00 00 00 04 77 70 61 64 00 00 01 00 01",
+        "FF FF FF FF FF FF 00 1E 68 51 4F A9 08 06 00 01 08 00 06 04 00 01 00 1E 68 51 4F A9 C0 A8 00 0A 00 00 00 00 00 00 C0 A8 00 01 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00",
+        "00 1A 8C 10 AD 30 00 1E 68 51 4F A9 08 00 45 00 00 24 7E 74 00 00 20 01 EC 17 AC 10 FF 01 43 D7 41 84 08 00 00 00 00 01 0F 55 00 00 00 00 00 00 00 00",
+        "00 1A 8C 10 AD 30 00 1E 68 51 4F A9 08 00 45 00 00 2D 12 34 00 00 40 11 00 00 C0 A8 00 0A C0 A8 00 01 C3 50 00 35 00 19 00 00 AB CD 01 00 00 01 00 00 00 00 00 00 00 00 02 00 01 00",
+    ];
+    // Ethernet frames of the protocols, that aren't in the corpus. HTTP goes over IPv6
+    const PROTOCOL_FRAMES: [(ProtocolId, &str); 6] = [
+        (
+            ProtocolId::DHCPv4,
+            "FF FF FF FF FF FF 00 19 B9 DA 15 A0 08 00 45 00 01 48 13 DE 00 00 80 11 F4 B0 AC 10 85 06 FF FF FF FF 00 43 00 44 01 34 38 ED 02 01 06 00 65 BB D3 BB 00 00 80 00 AC 10 85 27 00 00 00 00 00 00 00 00 00 00 00 00 D4 BE D9 28 21 33 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 63 82 53 63 35 01 05 36 04 AC 10 85 06 01 04 FF FF FF 00 0F 0B 6A 61 61 6C 61 6D 2E 6E 65 74 00 03 04 AC 10 85 01 06 08 AC 10 85 06 AC 10 80 CA FF 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00",
+        ),
+        (
+            ProtocolId::DHCPv6,
+            "33 33 00 01 00 02 7C E9 D3 7C D3 9B 86 DD 60 00 00 00 00 6A 11 01 FE 80 00 00 00 00 00 00 B5 6E 75 8F D6 E2 B7 9E FF 02 00 00 00 00 00 00 00 00 00 00 00 01 00 02 02 22 02 23 00 6A E6 A9 01 76 31 13 00 08 00 02 01 2C 00 01 00 0E 00 01 00 01 16 2B 8C FE 00 21 70 63 3A E9 00 03 00 0C 17 7C E9 D3 00 00 00 00 00 00 00 00 00 27 00 14 00 06 4A 44 54 31 33 33 06 6A 61 61 6C 61 6D 03 6E 65 74 00 00 10 00 0E 00 00 01 37 00 08 4D 53 46 54 20 35 2E 30 00 06 00 08 00 18 00 17 00 11 00 27",
+        ),
+        (
+            ProtocolId::HTTP,
+            "22 1A 95 D6 7A 23 86 93 23 D3 37 8E 86 DD 60 0D 68 4A 00 7D 06 40 FC 00 00 02 00 00 00 02 00 00 00 00 00 00 00 01 FC 00 00 02 00 00 00 01 00 00 00 00 00 00 00 01 A9 A0 1F 90 02 1B 63 8D BA 31 1E 8E 80 18 00 CF C9 2E 00 00 01 01 08 0A 80 1D A5 22 80 1D A5 22 47 45 54 20 2F 68 65 6C 6C 6F 2E 74 78 74 20 48 54 54 50 2F 31 2E 31 0D 0A 55 73 65 72 2D 41 67 65 6E 74 3A 20 63 75 72 6C 2F 37 2E 33 38 2E 30 0D 0A 48 6F 73 74 3A 20 5B 66 63 30 30 3A 32 3A 30 3A 31 3A 3A 31 5D 3A 38 30 38 30 0D 0A 41 63 63 65 70 74 3A 20 2A 2F 2A 0D 0A 0D 0A",
+        ),
+        (
+            ProtocolId::ICMPv6,
+            "00 60 97 07 69 EA 00 00 86 05 80 DA 86 DD 60 00 00 00 00 18 3A FF FE 80 00 00 00 00 00 00 02 00 86 FF FE 05 80 DA FE 80 00 00 00 00 00 00 02 60 97 FF FE 07 69 EA 88 00 2A 18 40 00 00 00 FE 80 00 00 00 00 00 00 02 00 86 FF FE 05 80 DA",
+        ),
+        (
+            ProtocolId::NBNS,
+            "FF FF FF FF FF FF 08 00 27 AA BB CC 08 00 45 00 00 60 12 34 00 00 80 11 00 00 C0 A8 01 14 C0 A8 01 FF 00 89 00 89 00 4C 00 00 80 01 29 10 00 01 00 00 00 00 00 01 20 46 48 45 50 46 43 45 4C 46 44 46 45 45 42 46 45 45 4A 45 50 45 4F 44 42 43 41 43 41 43 41 41 41 00 00 20 00 01 C0 0C 00 20 00 01 00 04 93 E0 00 06 00 00 C0 A8 01 14",
+        ),
+        (
+            ProtocolId::SMB2,
+            "52 54 00 12 34 56 08 00 27 AA BB CC 08 00 45 00 00 94 43 21 00 00 80 06 00 00 C0 A8 01 14 C0 A8 01 0A C2 30 01 BD 01 02 03 04 0A 0B 0C 0D 50 18 02 00 00 00 00 00 00 00 00 68 FE 53 4D 42 40 00 00 00 00 00 00 00 00 00 01 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 FF FE 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 24 00 02 00 01 00 00 00 00 00 00 00 00 11 22 33 44 55 66 77 88 99 AA BB CC DD EE FF 00 00 00 00 00 00 00 00 02 02 10 02",
+        ),
+    ];
+    // Radiotap + IEEE 802.11 beacon
+    const BEACON: &str = "00 00 0F 00 2E 00 00 00 10 02 85 09 A0 00 D3 80 00 00 00 FF FF FF FF FF FF 00 11 22 33 44 55 00 11 22 33 44 55 10 00 00 11 22 33 44 55 66 77 64 00 11 04 00 07 48 6F 6D 65 4C 41 4E 01 04 82 84 8B 96 03 01 06 DE AD BE EF";
+    // RFC 3164, 5.4: <34>Oct 11 22:14:15 mymachine su: 'su root' failed for lonvick on /dev/pts/8
+    const SYSLOG: &str = "3C 33 34 3E 4F 63 74 20 31 31 20 32 32 3A 31 34 3A 31 35 20 6D 79 6D 61 63 68 69 6E 65 20 73 75 3A 20 27 73 75 20 72 6F 6F 74 27 20 66 61 69 6C 65 64 20 66 6F 72 20 6C 6F 6E 76 69 63 6B 20 6F 6E 20 2F 64 65 76 2F 70 74 73 2F 38 0A";
+    // RRQ "pxelinux.0" octet, blksize 1456, tsize 0
+    const TFTP: &str = "00 01 70 78 65 6C 69 6E 75 78 2E 30 00 6F 63 74 65 74 00 62 6C 6B 73 69 7A 65 00 31 34 35 36 00 74 73 69 7A 65 00 30 00";
+    // RFC 3261, 11.1: OPTIONS Alice -> Carol
+    const SIP: &str = "OPTIONS sip:carol@chicago.example.com SIP/2.0\r\n\
+        Via: SIP/2.0/UDP pc33.atlanta.example.com;branch=z9hG4bKhjhs8ass877\r\n\
+        Max-Forwards: 70\r\n\
+        To: <sip:carol@chicago.example.com>\r\n\
+        From: Alice <sip:alice@atlanta.example.com>;tag=1928301774\r\n\
+        Call-ID: a84b4c76e66710\r\n\
+        CSeq: 63104 OPTIONS\r\n\
+        Content-Length: 0\r\n\
+        \r\n";
+
+    fn parse(link_type: LinkType, frame: &[u8], checksum_validation: bool) -> FrameType {
+        let header = FrameHeader {
+            tv_sec: 1700000000,
+            tv_usec: 500000,
+            caplen: frame.len() as u32,
+            len: frame.len() as u32,
+        };
+
+        let parser = ProtocolParser::new(link_type, false)
+            .with_checksum_validation(checksum_validation);
+        parser.process_bytes(&header, frame).unwrap()
+    }
+
+    fn frame(hex_actual: &str, checksum_validation: bool) -> FrameType {
+        let frame = hex::decode(hex_actual.replace(" ", "")).unwrap();
+        parse(LinkType::ETHERNET, &frame, checksum_validation)
+    }
+
+    // One frame per protocol, that isn't in the corpus
+    fn protocol_frames(checksum_validation: bool) -> Vec<(ProtocolId, FrameType)> {
+        let mut frames: Vec<(ProtocolId, FrameType)> = PROTOCOL_FRAMES
+            .iter()
+            .map(|(protocol, hex)| (*protocol, frame(hex, checksum_validation)))
+            .collect();
+
+        let beacon = hex::decode(BEACON.replace(" ", "")).unwrap();
+        let beacon = parse(LinkType::IEEE802_11_RADIOTAP, &beacon, checksum_validation);
+        frames.push((ProtocolId::Radiotap, beacon.clone()));
+        frames.push((ProtocolId::IEEE80211, beacon));
+
+        // Version 2, PCMU, SSRC 0x1A2B3C4D, 20 ms of the audio
+        let mut rtp = vec![0x80, 0x00, 0x03, 0xE8, 0x00, 0x02, 0x71, 0x00];
+        rtp.extend([0x1A, 0x2B, 0x3C, 0x4D]);
+        rtp.extend([0xFF; 160]);
+        let datagrams = [
+            (ProtocolId::RTP, 49172, 3456, rtp),
+            (ProtocolId::SIP, 5060, 5060, SIP.as_bytes().to_vec()),
+            (
+                ProtocolId::Syslog,
+                49152,
+                514,
+                hex::decode(SYSLOG.replace(" ", "")).unwrap(),
+            ),
+            (
+                ProtocolId::TFTP,
+                2070,
+                69,
+                hex::decode(TFTP.replace(" ", "")).unwrap(),
+            ),
+        ];
+        for (protocol, port_source, port_destination, payload) in datagrams {
+            let datagram = udp_frame(port_source, port_destination, &payload);
+            frames.push((
+                protocol,
+                parse(LinkType::IPV4, &datagram, checksum_validation),
+            ));
+        }
+
+        frames
+    }
+
+    fn round_trip(frame: &FrameType) {
+        let json = serde_json::to_string(frame).unwrap();
+        let restored: FrameType = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&restored).unwrap(), json);
+    }
+
+    // Frame with the full field names & all the values, as it was before the compaction.
+    // Layers are written by their fields, in the order of the definition
+    fn verbose_json(frame: &FrameType) -> String {
+        let FrameType::Metadata(metadata) = frame else {
+            return serde_json::to_string(frame).unwrap();
+        };
+        let layers: Vec<serde_json::Value> = metadata
+            .layers
+            .iter()
+            .map(|layer| {
+                let fields: serde_json::Map<String, serde_json::Value> = layer
+                    .fields()
+                    .into_iter()
+                    .map(|(name, value)| (name.to_string(), value.into()))
+                    .collect();
+                serde_json::json!({ layer.id().to_string(): fields })
+            })
+            .collect();
+        let header = &metadata.header;
+
+        serde_json::json!({
+            "Metadata": {
+                "header": {
+                    "tv_sec": header.tv_sec,
+                    "tv_usec": header.tv_usec,
+                    "caplen": header.caplen,
+                    "len": header.len,
+                },
+                "layers": layers,
+                "direction": metadata.direction,
+                "is_truncated": metadata.is_truncated,
+            }
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for hex_actual in CORPUS {
+            let mut frame = frame(hex_actual, true);
+            round_trip(&frame);
+
+            // Checksum statuses, direction & truncation aren't default
+            if let FrameType::Metadata(metadata) = &mut frame {
+                metadata.direction = FrameDirection::Egress;
+                metadata.is_truncated = true;
+            }
+            round_trip(&frame);
+        }
+    }
+
+    #[test]
+    fn test_round_trip_protocols() {
+        for (protocol, frame) in protocol_frames(true) {
+            let FrameType::Metadata(metadata) = &frame else {
+                panic!("{protocol} frame isn't parsed");
+            };
+            assert!(
+                metadata.layers.iter().any(|layer| layer.id() == protocol),
+                "{protocol} layer isn't parsed"
+            );
+            round_trip(&frame);
+        }
+    }
+
+    #[test]
+    fn test_compact_size() {
+        const FRAMES: usize = 1000;
+
+        let mut frames: Vec<FrameType> =
+            CORPUS.iter().map(|hex| frame(hex, false)).collect();
+        frames.extend(protocol_frames(false).into_iter().map(|(_, frame)| frame));
+        let corpus: Vec<&FrameType> = frames.iter().cycle().take(FRAMES).collect();
+        let compact: usize = corpus
+            .iter()
+            .map(|frame| serde_json::to_string(frame).unwrap().len())
+            .sum();
+        let verbose: usize = corpus.iter().map(|frame| verbose_json(frame).len()).sum();
+
+        // At least 30% less
+        assert!(compact * 10 <= verbose * 7, "{compact} of {verbose} bytes");
+    }
+}
@@ -1,14 +1,65 @@
+//! Deep packet inspection: frames are parsed into the layers of the protocols.
+//!
+//! Entry point is [`parser::ProtocolParser`]. It takes the bytes of the frame with the
+//! [`dto::frame::FrameHeader`], so the frames may come from any source:
+//!
+//! ```
+//! use dpi::dto::frame::{FrameHeader, FrameType, LinkType};
+//! use dpi::dto::metadata::ProtocolDto;
+//! use dpi::parser::ProtocolParser;
+//!
+//! // Ethernet + IPv4 + ICMPv4 echo request
+//! let frame = hex::decode(
+//!     "001A8C10AD30001E68514FA9080045000024\
+//!      7E7400002001EC17AC10FF0143D741840800\
+//!      000000010F550000000000000000",
+//! )
+//! .unwrap_or_default();
+//! let header = FrameHeader::new(frame.len());
+//!
+//! let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+//! match parser.process_bytes(&header, &frame) {
+//!     Some(FrameType::Metadata(metadata)) => {
+//!         assert!(matches!(metadata.layers[0], ProtocolDto::Ethernet(_)));
+//!         assert!(matches!(metadata.layers[1], ProtocolDto::IPv4(_)));
+//!     },
+//!     _ => unreachable!("Ethernet is supported"),
+//! }
+//! ```
+//!
+//! With the `pcap` feature (enabled by default), the captured packets are parsed by
+//! [`parser::ProtocolParser::process`] and the frames may be saved into pcap files.
+//! Without it, the crate doesn't depend on libpcap.
+//!
+//! Public modules:
+//! - [`parser`]: [`parser::ProtocolParser`] and the errors of the layers;
+//! - [`dto::frame`]: the frame as it's given to the parser & returned by it:
+//!   [`dto::frame::FrameHeader`] (with the conversions from & into the pcap headers),
+//!   [`dto::frame::FrameType`], [`dto::frame::OwnedFrame`], [`dto::frame::LinkType`];
+//! - [`dto::metadata`]: the parsed layers, [`dto::metadata::FrameMetadataDto`];
+//! - [`dto::fields`]: the fields of the layers as the text, for the views & the filters;
+//! - [`dto::wire`]: helpers of the compact serialization of the DTOs;
+//! - [`protocols`]: the parsers & the DTOs of every protocol, [`protocols::ProtocolId`];
+//! - [`analysis`]: the well-known ports and the vendors of the MAC addresses.
+//!
+//! Every protocol is behind the feature of its name (e.g. `dns`), all of them are
+//! enabled by default. Protocols of the disabled features aren't parsed: the frame is
+//! parsed as far as the enabled ones go.
+
 // Library lints
 #![deny(clippy::arithmetic_side_effects)]
 #![deny(clippy::indexing_slicing)]
 
 pub mod analysis {
     pub mod ports;
+    #[cfg(feature = "ethernet")]
     pub mod vendor;
 }
 pub mod dto {
+    pub mod fields;
     pub mod frame;
     pub mod metadata;
+    pub mod wire;
 }
 pub mod parser;
 pub mod protocols;
@@ -1,95 +1,272 @@
-use crate::dto::frame::{FrameMetadata, FrameType, OwnedFrame};
+use crate::dto::frame::{FrameHeader, FrameMetadata, FrameType, LinkType, OwnedFrame};
+use crate::protocols::ip::checksum;
+use crate::protocols::port_table::PortTable;
 use crate::protocols::{ProtocolData, ProtocolId};
-use nom::IResult;
 use nom::Parser;
 use nom::bytes::take;
+use nom::error::{ErrorKind, FromExternalError, ParseError};
 use nom::number::be_u8;
+use nom::{ErrorConvert, Needed};
+use thiserror::Error;
 
+// HTTP bodies, declared longer, aren't kept
+pub const HTTP_BODY_LIMIT_DEFAULT: u64 = 4 * 1024 * 1024;
+
+/// Parses the frames into the layers of the protocols, starting from the link layer.
+/// <br> Frames are given as the bytes with the [`FrameHeader`], so they may come from
+/// any source. With the `pcap` feature, the captured packets are taken as is.
 pub struct ProtocolParser {
     raw_needed: bool,
     root: Option<ProtocolId>,
+    checksum_validation: bool,
+    ports: PortTable,
+    http_body_limit: u64,
 }
 
 impl ProtocolParser {
-    pub fn new(link_type: &pcap::Linktype, raw_needed: bool) -> Self {
+    /// Frames of the unsupported link type aren't processed at all. <br>
+    /// If `raw_needed` is set, the frames, that aren't parsed completely, are returned
+    /// as the raw bytes instead of the parsed layers.
+    pub fn new(link_type: impl Into<LinkType>, raw_needed: bool) -> Self {
         Self {
             raw_needed,
-            root: ProtocolId::root(link_type),
+            root: ProtocolId::root(link_type.into()),
+            checksum_validation: false,
+            ports: PortTable::default(),
+            http_body_limit: HTTP_BODY_LIMIT_DEFAULT,
         }
     }
 
+    /// Validates IPv4, TCP & UDP checksums. Disabled by default, it costs CPU.
+    pub fn with_checksum_validation(mut self, is_enabled: bool) -> Self {
+        self.checksum_validation = is_enabled;
+        self
+    }
+
+    /// Ports of the application protocols, the defaults are used if not set.
+    pub fn with_protocol_ports(mut self, ports: PortTable) -> Self {
+        self.ports = ports;
+        self
+    }
+
+    /// HTTP bodies, declared longer, aren't kept: the message is marked instead,
+    /// so the absurd Content-Length isn't buffered.
+    pub fn with_http_body_limit(mut self, bytes: u64) -> Self {
+        self.http_body_limit = bytes;
+        self
+    }
+
+    /// Applied to the next processed frame.
+    pub fn set_protocol_ports(&mut self, ports: PortTable) {
+        self.ports = ports;
+    }
+
+    pub fn is_supported(link_type: impl Into<LinkType>) -> bool {
+        ProtocolId::root(link_type.into()).is_some()
+    }
+
+    /// Rebuilds the parser for another link type (e.g. capture interface is switched).
+    /// <br> Parser stays unchanged if the link type is not supported.
+    pub fn set_link_type(
+        &mut self, link_type: impl Into<LinkType>,
+    ) -> Result<(), LinkTypeError> {
+        let link_type = link_type.into();
+        let root =
+            ProtocolId::root(link_type).ok_or(LinkTypeError::Unsupported(link_type.0))?;
+        self.root = Some(root);
+        Ok(())
+    }
+
+    /// Parses the frame, that starts with the header of the link type. <br>
+    /// `data` holds the captured bytes only, `header.caplen` of them.
+    /// Returns `None` if the link type isn't supported.
+    pub fn process_bytes(&self, header: &FrameHeader, data: &[u8]) -> Option<FrameType> {
+        self.process_bytes_traced(header, data)
+            .map(|(frame, _)| frame)
+    }
+
+    /// Same as [`ProtocolParser::process_bytes`], but also returns the reason,
+    /// why the frame isn't parsed completely, if it's known.
+    pub fn process_bytes_traced(
+        &self, header: &FrameHeader, data: &[u8],
+    ) -> Option<(FrameType, Option<LayerFailure>)> {
+        let root_protocol = self.root.as_ref()?;
+        let mut metadata = FrameMetadata::from_header(header);
+
+        let result = traversal(
+            root_protocol,
+            data,
+            &mut metadata,
+            0,
+            self.checksum_validation,
+            &self.ports,
+            self.http_body_limit,
+        );
+        let raw = || OwnedFrame {
+            header: header.clone(),
+            data: data.to_vec(),
+        };
+        let traced = match result {
+            ProcessResult::Complete => (FrameType::Metadata(metadata.into()), None),
+            ProcessResult::Incomplete(failure) => match self.raw_needed {
+                true => (FrameType::Raw(raw()), failure),
+                false => (FrameType::Metadata(metadata.into()), failure),
+            },
+            ProcessResult::Failed(failure) => match self.raw_needed {
+                true => (FrameType::Raw(raw()), Some(failure)),
+                false => (FrameType::Header(metadata.header), Some(failure)),
+            },
+        };
+
+        Some(traced)
+    }
+
+    /// Same as [`ProtocolParser::process_bytes`] for the packet, captured by pcap.
+    #[cfg(feature = "pcap")]
     pub fn process(&self, packet: pcap::Packet) -> Option<FrameType> {
-        let mut metadata = FrameMetadata::from_header(packet.header);
-
-        if let Some(root_protocol) = &self.root {
-            let result = traversal(root_protocol, &packet, &mut metadata, 0);
-            return match result {
-                ProcessResult::Complete => Some(FrameType::Metadata(metadata.into())),
-                ProcessResult::Incomplete => match self.raw_needed {
-                    true => Some(FrameType::Raw(OwnedFrame::from(packet))),
-                    false => Some(FrameType::Metadata(metadata.into())),
-                },
-                ProcessResult::Failed => match self.raw_needed {
-                    true => Some(FrameType::Raw(OwnedFrame::from(packet))),
-                    false => Some(FrameType::Header(metadata.header)),
-                },
-            };
-        }
+        self.process_bytes(&FrameHeader::from(packet.header), packet.data)
+    }
 
-        None
+    /// Same as [`ProtocolParser::process_bytes_traced`] for the packet, captured by pcap.
+    #[cfg(feature = "pcap")]
+    pub fn process_traced(
+        &self, packet: pcap::Packet,
+    ) -> Option<(FrameType, Option<LayerFailure>)> {
+        self.process_bytes_traced(&FrameHeader::from(packet.header), packet.data)
     }
 }
 
 fn traversal(
     id: &ProtocolId, bytes: &[u8], metadata: &mut FrameMetadata, depth: usize,
+    checksum_validation: bool, ports: &PortTable, http_body_limit: u64,
 ) -> ProcessResult {
     const MAX_DEPTH: usize = 16;
+    const TOO_DEEP: ParserError = ParserError::ConstraintViolation {
+        description: "too many nested layers",
+    };
+    const CUT_OFF: ParserError = ParserError::ConstraintViolation {
+        description: "layer is cut off, while the frame isn't truncated",
+    };
+    let failed = |error| {
+        ProcessResult::Failed(LayerFailure {
+            protocol: *id,
+            error,
+        })
+    };
     if depth > MAX_DEPTH {
-        return ProcessResult::Failed;
+        return failed(TOO_DEEP);
     }
 
-    let result = id.parse()(bytes);
+    let result = parse_layer(id, bytes, http_body_limit).and_then(|(rest, mut layer)| {
+        // Cut off layer of the complete frame is malformed
+        if layer.is_truncated() && !metadata.is_truncated {
+            return Err(CUT_OFF.to_nom(bytes));
+        }
+        if checksum_validation {
+            checksum::validate(&mut layer, bytes, metadata);
+        }
+        Ok((rest, layer))
+    });
 
     match result {
         Ok(([], layer)) => {
             metadata.layers.push(layer);
             ProcessResult::Complete
         },
+        Ok((rest, layer)) if id.is_repeated() => {
+            metadata.layers.push(layer);
+
+            let mut rest = rest;
+            while let Ok((next, layer)) = parse_layer(id, rest, http_body_limit) {
+                if layer.is_truncated() && !metadata.is_truncated {
+                    break;
+                }
+                metadata.layers.push(layer);
+                // Stops on the end of the payload or if nothing is consumed
+                if next.is_empty() || next.len() == rest.len() {
+                    break;
+                }
+                rest = next;
+            }
+            ProcessResult::Complete
+        },
         Ok((rest, layer)) => {
             metadata.layers.push(layer);
 
-            if let Some(best) = id.best_children(metadata) {
+            if let Some(best) = id.best_children(metadata, ports, rest) {
                 return match depth.checked_add(1) {
-                    Some(new_depth) => traversal(&best, rest, metadata, new_depth),
-                    None => ProcessResult::Failed,
+                    // Upper layer may be cut off, but the parsed ones are still valid
+                    Some(new_depth) => {
+                        match traversal(
+                            &best,
+                            rest,
+                            metadata,
+                            new_depth,
+                            checksum_validation,
+                            ports,
+                            http_body_limit,
+                        ) {
+                            ProcessResult::Failed(failure) => {
+                                ProcessResult::Incomplete(Some(failure))
+                            },
+                            result => result,
+                        }
+                    },
+                    None => failed(TOO_DEEP),
                 };
             }
 
             let children = match id.children() {
                 Some(value) => value,
                 None => {
-                    return ProcessResult::Incomplete;
+                    return ProcessResult::Incomplete(None);
                 },
             };
 
-            for id in children {
+            // Protocols, recognized by the ports, aren't guessed on the other ones
+            for id in children.iter().filter(|id| id.check_ports().is_none()) {
                 let result = match depth.checked_add(1) {
-                    Some(new_depth) => traversal(&id, rest, metadata, new_depth),
-                    None => return ProcessResult::Failed,
+                    Some(new_depth) => traversal(
+                        id,
+                        rest,
+                        metadata,
+                        new_depth,
+                        checksum_validation,
+                        ports,
+                        http_body_limit,
+                    ),
+                    None => {
+                        return failed(TOO_DEEP);
+                    },
                 };
 
                 match result {
-                    ProcessResult::Complete | ProcessResult::Incomplete => {
+                    ProcessResult::Complete | ProcessResult::Incomplete(_) => {
                         return result;
                     },
-                    ProcessResult::Failed => continue,
+                    // Guessed protocols are expected to fail
+                    ProcessResult::Failed(_) => continue,
                 }
             }
 
-            ProcessResult::Incomplete
+            ProcessResult::Incomplete(None)
         },
-        Err(_) => ProcessResult::Failed,
+        Err(err) => failed(ParserError::from_nom(err, bytes.len())),
+    }
+}
+
+// Only HTTP has the settings of the parsing, other protocols are parsed as is
+fn parse_layer<'a>(
+    id: &ProtocolId, bytes: &'a [u8], http_body_limit: u64,
+) -> IResult<&'a [u8], ProtocolData> {
+    #[cfg(feature = "http")]
+    if *id == ProtocolId::HTTP {
+        return crate::protocols::http::parse_limited(bytes, http_body_limit);
     }
+    #[cfg(not(feature = "http"))]
+    let _ = http_body_limit;
+
+    id.parse()(bytes)
 }
 
 pub fn wire_format(input: &[u8]) -> IResult<&[u8], String> {
@@ -106,13 +283,17 @@ pub fn wire_format(input: &[u8]) -> IResult<&[u8], String> {
 
         // Unexpected length
         if len_byte as usize > rest.len() {
-            return Err(ParserError::ErrorVerify.to_nom(input));
+            return Err(ParserError::Truncated {
+                needed: len_byte as usize,
+                available: rest.len(),
+            }
+            .to_nom(input));
         }
 
         // Creating label
         let (rest, label): (&[u8], &[u8]) = take(len_byte).parse(rest)?;
         let label = String::from_utf8(label.to_vec())
-            .map_err(|_| ParserError::ErrorVerify.to_nom(input))?;
+            .map_err(|_| ParserError::InvalidValue { field: "label" }.to_nom(input))?;
         labels.push(label);
 
         rest_buffer = rest;
@@ -125,31 +306,116 @@ pub fn cast_to_bool(bit: u8) -> Result<bool, ParserError> {
     match bit {
         0 => Ok(false),
         1 => Ok(true),
-        _ => Err(ParserError::ErrorVerify),
+        _ => Err(ParserError::InvalidValue { field: "flag" }),
     }
 }
 
+/// Results of the protocol parsers, that keep the reason of the failure.
+pub type IResult<I, O, E = ParseFailure<I>> = nom::IResult<I, O, E>;
 pub type ParseFn = fn(&[u8]) -> IResult<&[u8], ProtocolData>;
 pub type PortFn = fn(u16, u16) -> bool;
+pub type SignatureFn = fn(&[u8]) -> bool;
 
+#[derive(Clone, Debug, Error, PartialEq)]
 pub enum ParserError {
+    #[error("Verification failed.")]
     ErrorVerify,
+
+    #[error("Verification failed.")]
     FailureVerify,
+
+    #[error("Truncated: {needed} bytes needed, {available} available.")]
+    Truncated { needed: usize, available: usize },
+
+    #[error("Invalid value of the field: {field}.")]
+    InvalidValue { field: &'static str },
+
+    #[error("Constraint violation: {description}.")]
+    ConstraintViolation { description: &'static str },
+
+    // Raised by the nom parsers themselves
+    #[error("Parsing error: {0:?}.")]
+    Nom(ErrorKind),
 }
 
 impl ParserError {
-    pub fn to_nom<T>(&self, input: T) -> nom::Err<nom::error::Error<T>> {
+    pub fn to_nom<T>(self, input: T) -> nom::Err<ParseFailure<T>> {
         match self {
-            Self::ErrorVerify => nom::Err::Error(nom::error::Error::new(
-                input,
-                nom::error::ErrorKind::Verify,
-            )),
-            Self::FailureVerify => nom::Err::Failure(nom::error::Error::new(
-                input,
-                nom::error::ErrorKind::Verify,
-            )),
+            Self::FailureVerify => nom::Err::Failure(ParseFailure::new(input, self)),
+            _ => nom::Err::Error(ParseFailure::new(input, self)),
         }
     }
+
+    /// Reason of the failed layer. `available` is the length of the layer.
+    pub(crate) fn from_nom(err: nom::Err<ParseFailure<&[u8]>>, available: usize) -> Self {
+        match err {
+            nom::Err::Error(failure) | nom::Err::Failure(failure) => failure.error,
+            // Layer is read from the start to the end, so the missing bytes are past it
+            nom::Err::Incomplete(Needed::Size(size)) => Self::Truncated {
+                needed: available.saturating_add(size.get()),
+                available,
+            },
+            nom::Err::Incomplete(Needed::Unknown) => Self::Nom(ErrorKind::Eof),
+        }
+    }
+}
+
+/// Error of the nom parsers. Unlike [`nom::error::Error`], it keeps the reason
+/// of the failure, given by the protocol module.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseFailure<I> {
+    pub input: I,
+    pub error: ParserError,
+}
+
+impl<I> ParseFailure<I> {
+    pub fn new(input: I, error: ParserError) -> Self {
+        Self { input, error }
+    }
+}
+
+impl<I> ParseError<I> for ParseFailure<I> {
+    fn from_error_kind(input: I, kind: ErrorKind) -> Self {
+        Self::new(input, ParserError::Nom(kind))
+    }
+
+    // The innermost reason is the most specific one
+    fn append(_: I, _: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+impl<I, E> FromExternalError<I, E> for ParseFailure<I> {
+    fn from_external_error(input: I, kind: ErrorKind, _: E) -> Self {
+        Self::from_error_kind(input, kind)
+    }
+}
+
+// Bit parsers take the position in the byte along with the input
+impl<I> ErrorConvert<ParseFailure<I>> for ParseFailure<(I, usize)> {
+    fn convert(self) -> ParseFailure<I> {
+        ParseFailure::new(self.input.0, self.error)
+    }
+}
+
+impl<I> ErrorConvert<ParseFailure<(I, usize)>> for ParseFailure<I> {
+    fn convert(self) -> ParseFailure<(I, usize)> {
+        ParseFailure::new((self.input, 0), self.error)
+    }
+}
+
+/// Protocol, that isn't parsed, with the reason.
+#[derive(Clone, Debug, Error, PartialEq)]
+#[error("{protocol}: {error}")]
+pub struct LayerFailure {
+    pub protocol: ProtocolId,
+    pub error: ParserError,
+}
+
+#[derive(Debug, Error)]
+pub enum LinkTypeError {
+    #[error("Unsupported link type: {0}.")]
+    Unsupported(i32),
 }
 
 #[derive(Clone, Debug)]
@@ -157,18 +423,22 @@ pub enum ProcessResult {
     // Fully parsed
     Complete,
 
-    // Some protocols parsed (we are going into the deep), but some in the deepness are not
-    Incomplete,
+    // Some protocols parsed (we are going into the deep), but some in the deepness are not.
+    // Failure is known, if the upper protocol was chosen, not guessed
+    Incomplete(Option<LayerFailure>),
 
     // Not matched
-    Failed,
+    Failed(LayerFailure),
 }
 
 #[cfg(test)]
+// Helpers are shared with the tests of the protocols, which features may be disabled
+#[allow(dead_code)]
 pub(crate) mod tests {
-    use crate::dto::frame::FrameMetadata;
-    use crate::parser::ProcessResult;
+    use crate::dto::frame::{FrameHeader, FrameMetadata, LinkType};
+    use crate::parser::{LayerFailure, ProcessResult};
     use crate::protocols::ProtocolId;
+    use crate::protocols::port_table::PortTable;
 
     pub enum FrameType {
         Metadata(FrameMetadata),
@@ -179,35 +449,325 @@ pub(crate) mod tests {
     pub struct ProtocolParser {
         raw_needed: bool,
         root: Option<ProtocolId>,
+        checksum_validation: bool,
+        ports: PortTable,
+        http_body_limit: u64,
     }
 
     impl ProtocolParser {
-        pub fn new(link_type: &pcap::Linktype, raw_needed: bool) -> Self {
+        pub fn new(link_type: impl Into<LinkType>, raw_needed: bool) -> Self {
             Self {
                 raw_needed,
-                root: ProtocolId::root(link_type),
+                root: ProtocolId::root(link_type.into()),
+                checksum_validation: false,
+                ports: PortTable::default(),
+                http_body_limit: super::HTTP_BODY_LIMIT_DEFAULT,
             }
         }
 
-        pub fn process(&self, packet: pcap::Packet) -> Option<FrameType> {
-            let mut metadata = FrameMetadata::from_header(packet.header);
+        pub fn with_checksum_validation(mut self, is_enabled: bool) -> Self {
+            self.checksum_validation = is_enabled;
+            self
+        }
 
-            if let Some(root_protocol) = &self.root {
-                let result = super::traversal(root_protocol, &packet, &mut metadata, 0);
-                return match result {
-                    ProcessResult::Complete => Some(FrameType::Metadata(metadata)),
-                    ProcessResult::Incomplete => match self.raw_needed {
-                        true => Some(FrameType::Raw(())),
-                        false => Some(FrameType::Metadata(metadata)),
-                    },
-                    ProcessResult::Failed => match self.raw_needed {
-                        true => Some(FrameType::Raw(())),
-                        false => Some(FrameType::Header(())),
-                    },
-                };
+        pub fn with_protocol_ports(mut self, ports: PortTable) -> Self {
+            self.ports = ports;
+            self
+        }
+
+        pub fn with_http_body_limit(mut self, bytes: u64) -> Self {
+            self.http_body_limit = bytes;
+            self
+        }
+
+        pub fn process_bytes(
+            &self, header: &FrameHeader, data: &[u8],
+        ) -> Option<FrameType> {
+            self.process_bytes_traced(header, data)
+                .map(|(frame, _)| frame)
+        }
+
+        pub fn process_bytes_traced(
+            &self, header: &FrameHeader, data: &[u8],
+        ) -> Option<(FrameType, Option<LayerFailure>)> {
+            let root_protocol = self.root.as_ref()?;
+            let mut metadata = FrameMetadata::from_header(header);
+
+            let result = super::traversal(
+                root_protocol,
+                data,
+                &mut metadata,
+                0,
+                self.checksum_validation,
+                &self.ports,
+                self.http_body_limit,
+            );
+            let traced = match result {
+                ProcessResult::Complete => (FrameType::Metadata(metadata), None),
+                ProcessResult::Incomplete(failure) => match self.raw_needed {
+                    true => (FrameType::Raw(()), failure),
+                    false => (FrameType::Metadata(metadata), failure),
+                },
+                ProcessResult::Failed(failure) => match self.raw_needed {
+                    true => (FrameType::Raw(()), Some(failure)),
+                    false => (FrameType::Header(()), Some(failure)),
+                },
+            };
+
+            Some(traced)
+        }
+    }
+
+    // Layers of the raw IPv4 datagram (192.168.1.10 -> 192.168.1.20) with the UDP payload
+    pub fn udp_layers(
+        port_source: u16, port_destination: u16, payload: &[u8],
+    ) -> Vec<crate::protocols::ProtocolData> {
+        let frame = udp_frame(port_source, port_destination, payload);
+        let header = FrameHeader::new(frame.len());
+
+        let parser = ProtocolParser::new(LinkType::IPV4, false);
+        match parser.process_bytes(&header, &frame) {
+            Some(FrameType::Metadata(metadata)) => metadata.layers,
+            _ => panic!(),
+        }
+    }
+
+    // Raw IPv4 datagram (192.168.1.10 -> 192.168.1.20) with the UDP payload
+    pub fn udp_frame(port_source: u16, port_destination: u16, payload: &[u8]) -> Vec<u8> {
+        let udp_length = (8 + payload.len()) as u16;
+        let mut frame = vec![0x45, 0x00];
+        frame.extend((20 + udp_length).to_be_bytes());
+        frame.extend([
+            0x00, 0x01, 0x40, 0x00, 0x40, 0x11, 0x00, 0x00, 0xC0, 0xA8, 0x01, 0x0A, 0xC0,
+            0xA8, 0x01, 0x14,
+        ]);
+        frame.extend(port_source.to_be_bytes());
+        frame.extend(port_destination.to_be_bytes());
+        frame.extend(udp_length.to_be_bytes());
+        frame.extend([0x00, 0x00]);
+        frame.extend(payload);
+        frame
+    }
+
+    // Layers of the raw IPv4 datagram (192.168.1.10 -> 192.168.1.20) with the TCP payload.
+    // Frame must be parsed completely.
+    pub fn tcp_layers(
+        port_source: u16, port_destination: u16, payload: &[u8],
+    ) -> Vec<crate::protocols::ProtocolData> {
+        // Incomplete frames are returned as raw ones
+        let parser = ProtocolParser::new(LinkType::IPV4, true);
+        match tcp_metadata(&parser, port_source, port_destination, payload) {
+            Some(FrameType::Metadata(metadata)) => metadata.layers,
+            _ => panic!(),
+        }
+    }
+
+    // Raw IPv4 datagram (192.168.1.10 -> 192.168.1.20) with the TCP payload,
+    // processed by the given parser
+    pub fn tcp_metadata(
+        parser: &ProtocolParser, port_source: u16, port_destination: u16, payload: &[u8],
+    ) -> Option<FrameType> {
+        let frame = tcp_frame(port_source, port_destination, payload);
+        let header = FrameHeader::new(frame.len());
+
+        parser.process_bytes(&header, &frame)
+    }
+
+    // Raw IPv4 datagram (192.168.1.10 -> 192.168.1.20) with the TCP payload
+    pub fn tcp_frame(port_source: u16, port_destination: u16, payload: &[u8]) -> Vec<u8> {
+        let total_length = (40 + payload.len()) as u16;
+        let mut frame = vec![0x45, 0x00];
+        frame.extend(total_length.to_be_bytes());
+        frame.extend([
+            0x00, 0x01, 0x40, 0x00, 0x40, 0x06, 0x00, 0x00, 0xC0, 0xA8, 0x01, 0x0A, 0xC0,
+            0xA8, 0x01, 0x14,
+        ]);
+        frame.extend(port_source.to_be_bytes());
+        frame.extend(port_destination.to_be_bytes());
+        // Sequence & acknowledgement numbers, header length, PSH+ACK, window
+        frame.extend([
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x50, 0x18, 0xFF, 0xFF, 0x00,
+            0x00, 0x00, 0x00,
+        ]);
+        frame.extend(payload);
+        frame
+    }
+
+    // Payloads of the random bytes & lengths. Generator is seeded, so the runs are repeated
+    pub fn random_payloads(amount: usize, max_length: usize) -> Vec<Vec<u8>> {
+        // Xorshift64
+        let mut state: u64 = 0x9E37_79B9_7F4A_7C15;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        (0..amount)
+            .map(|_| {
+                let length = (next() % (max_length as u64 + 1)) as usize;
+                (0..length).map(|_| next() as u8).collect()
+            })
+            .collect()
+    }
+
+    #[cfg(all(feature = "ethernet", feature = "icmpv4", feature = "ipv4"))]
+    #[test]
+    fn test_parser_link_type_rebuild() {
+        use crate::dto::frame::FrameType;
+        use crate::dto::metadata::ProtocolDto;
+
+        // Ethernet + IPv4 + ICMPv4
+        let hex_actual = "00 1A 8C 10 AD 30 00 1E 68 51 4F A9 08 00 45 00 00 3C 7E 74 00 00 20 01 EB DF AC 10 FF 01 43 D7 41 84 08 00 40 08 00 01 0F 55 41 42 43 44 45 46 47 48 49 4A 4B 4C 4D 4E 4F 50 51 52 53 54 55 56 57 41 42 43 44 45 46 47 48 49".replace(" ", "");
+        let ethernet_frame = hex::decode(hex_actual).unwrap();
+        // Same packet without Ethernet header
+        let ip_packet = &ethernet_frame[14..];
+        let header = FrameHeader::new(0);
+
+        let first_layers = |frame: Option<FrameType>| match frame {
+            Some(FrameType::Metadata(metadata)) => metadata.layers,
+            _ => panic!(),
+        };
+
+        let mut parser = super::ProtocolParser::new(LinkType::ETHERNET, false);
+        let layers = first_layers(parser.process_bytes(&header, &ethernet_frame));
+        assert!(matches!(layers[0], ProtocolDto::Ethernet(_)));
+        assert!(matches!(layers[1], ProtocolDto::IPv4(_)));
+        assert!(matches!(layers[2], ProtocolDto::ICMPv4(_)));
+
+        parser.set_link_type(LinkType::IPV4).unwrap();
+        let layers = first_layers(parser.process_bytes(&header, ip_packet));
+        assert_eq!(layers.len(), 2);
+        assert!(matches!(layers[0], ProtocolDto::IPv4(_)));
+        assert!(matches!(layers[1], ProtocolDto::ICMPv4(_)));
+
+        // Unsupported link type (LINKTYPE_USER0) keeps the previous root
+        assert!(parser.set_link_type(LinkType(147)).is_err());
+        assert!(!super::ProtocolParser::is_supported(LinkType(147)));
+        let layers = first_layers(parser.process_bytes(&header, ip_packet));
+        assert!(matches!(layers[0], ProtocolDto::IPv4(_)));
+    }
+
+    #[cfg(feature = "pcap")]
+    #[test]
+    fn test_process_packet_as_bytes() {
+        // Ethernet + IPv4 + ICMPv4
+        let hex_actual = "00 1A 8C 10 AD 30 00 1E 68 51 4F A9 08 00 45 00 00 3C 7E 74 00 00 20 01 EB DF AC 10 FF 01 43 D7 41 84 08 00 40 08 00 01 0F 55 41 42 43 44 45 46 47 48 49 4A 4B 4C 4D 4E 4F 50 51 52 53 54 55 56 57 41 42 43 44 45 46 47 48 49".replace(" ", "");
+        let frame = hex::decode(hex_actual).unwrap();
+
+        // Parsed completely, partially & not parsed
+        for caplen in [frame.len(), 30, 10] {
+            let header = FrameHeader {
+                tv_sec: 1700000000,
+                tv_usec: 500,
+                caplen: caplen as u32,
+                len: frame.len() as u32,
+            };
+            let data = &frame[..caplen];
+
+            for raw_needed in [false, true] {
+                let parser = super::ProtocolParser::new(LinkType::ETHERNET, raw_needed);
+                let from_bytes = parser.process_bytes(&header, data);
+                let from_packet = parser.process(pcap::Packet {
+                    header: &pcap::PacketHeader::from(&header),
+                    data,
+                });
+                assert_eq!(
+                    serde_json::to_string(&from_bytes).unwrap(),
+                    serde_json::to_string(&from_packet).unwrap()
+                );
             }
+        }
+    }
+
+    // Frame, captured with the snapshot length less than its length (caplen < len)
+    fn truncated_layers(
+        hex_actual: &str, caplen: usize,
+    ) -> Option<Vec<crate::dto::metadata::ProtocolDto>> {
+        use crate::dto::frame::FrameType;
 
-            None
+        let frame = hex::decode(hex_actual.replace(" ", "")).unwrap();
+        let header = FrameHeader::captured(caplen, frame.len());
+
+        let parser = super::ProtocolParser::new(LinkType::ETHERNET, false);
+        match parser.process_bytes(&header, &frame[..caplen]) {
+            Some(FrameType::Metadata(metadata)) => Some(metadata.layers),
+            _ => None,
         }
     }
+
+    #[cfg(all(feature = "ethernet", feature = "ipv4", feature = "tcp"))]
+    #[test]
+    fn test_truncated_ipv4_frame() {
+        use crate::dto::metadata::ProtocolDto;
+
+        // Ethernet + IPv4 + TCP with 100 bytes of payload
+        let hex_actual = format!(
+            "00 1A 8C 10 AD 30 00 1E 68 51 4F A9 08 00 45 00 00 8C 00 01 40 00 40 06 00 00 C0 A8 00 01 C0 A8 00 02 C3 50 30 39 00 00 00 01 00 00 00 00 50 18 FF FF 00 00 00 00 {}",
+            "41 ".repeat(100)
+        );
+
+        // Payload is cut off
+        let layers = truncated_layers(&hex_actual, 64).unwrap();
+        assert_eq!(layers.len(), 3);
+        assert!(matches!(layers[1], ProtocolDto::IPv4(_)));
+        assert!(matches!(layers[2], ProtocolDto::TCP(_)));
+
+        // TCP header is cut off
+        let layers = truncated_layers(&hex_actual, 44).unwrap();
+        assert_eq!(layers.len(), 2);
+        assert!(matches!(layers[0], ProtocolDto::Ethernet(_)));
+        assert!(matches!(layers[1], ProtocolDto::IPv4(_)));
+    }
+
+    #[cfg(all(feature = "ethernet", feature = "ipv6", feature = "tcp"))]
+    #[test]
+    fn test_truncated_ipv6_frame() {
+        use crate::dto::metadata::ProtocolDto;
+
+        // Ethernet + IPv6 + TCP with 100 bytes of payload
+        let hex_actual = format!(
+            "00 1A 8C 10 AD 30 00 1E 68 51 4F A9 86 DD 60 00 00 00 00 78 06 40 20 01 0D B8 00 00 00 00 00 00 00 00 00 00 00 01 20 01 0D B8 00 00 00 00 00 00 00 00 00 00 00 02 C3 50 30 39 00 00 00 01 00 00 00 00 50 18 FF FF 00 00 00 00 {}",
+            "41 ".repeat(100)
+        );
+
+        let layers = truncated_layers(&hex_actual, 96).unwrap();
+        assert_eq!(layers.len(), 3);
+        assert!(matches!(layers[1], ProtocolDto::IPv6(_)));
+        assert!(matches!(layers[2], ProtocolDto::TCP(_)));
+
+        let layers = truncated_layers(&hex_actual, 64).unwrap();
+        assert_eq!(layers.len(), 2);
+        assert!(matches!(layers[1], ProtocolDto::IPv6(_)));
+    }
+
+    #[cfg(all(feature = "ethernet", feature = "icmpv4", feature = "ipv4"))]
+    #[test]
+    fn test_ipip_frame() {
+        use crate::dto::metadata::ProtocolDto;
+        use std::net::Ipv4Addr;
+
+        // Ethernet + IPv4 (IPIP) + IPv4 + ICMPv4
+        let hex_actual = "00 1A 8C 10 AD 30 00 1E 68 51 4F A9 08 00 45 00 00 34 00 01 00 00 40 04 00 00 0A 00 00 01 0A 00 00 02 45 00 00 20 00 02 00 00 40 01 00 00 C0 A8 01 0A C0 A8 02 0A 08 00 00 00 00 01 00 01 41 42 43 44";
+        let layers = truncated_layers(hex_actual, 66).unwrap();
+        assert_eq!(layers.len(), 4);
+
+        let addresses = |layer: &ProtocolDto| match layer {
+            ProtocolDto::IPv4(ipv4) => (ipv4.address_source, ipv4.address_destination),
+            _ => panic!(),
+        };
+        assert_eq!(
+            addresses(&layers[1]),
+            (Ipv4Addr::new(10, 0, 0, 1), Ipv4Addr::new(10, 0, 0, 2))
+        );
+        assert_eq!(
+            addresses(&layers[2]),
+            (
+                Ipv4Addr::new(192, 168, 1, 10),
+                Ipv4Addr::new(192, 168, 2, 10)
+            )
+        );
+        assert!(matches!(layers[3], ProtocolDto::ICMPv4(_)));
+    }
 }
@@ -1,149 +1,383 @@
-use crate::dto::frame::FrameMetadata;
-use crate::parser::{ParseFn, PortFn};
+use crate::dto::fields::FieldIter;
+use crate::dto::frame::{FrameMetadata, LinkType};
+use crate::parser::{ParseFn, PortFn, SignatureFn};
+use crate::protocols::port_table::PortTable;
 use serde::{Deserialize, Serialize};
 use strum_macros::{Display, EnumIter};
 
+/// Bytes of the payload, that are enough to tell the protocol by its signature.
+pub const SIGNATURE_LENGTH: usize = 16;
+
 /// Guide: How to Add a Protocol
-/// 1. Add it to the `ProtocolId` enum.
+/// 1. Declare it in the `protocols!` invocation below: its id, cargo feature, module with the parsed & DTO types, port & signature checks. Add the feature to `Cargo.toml`, with the features of the protocols, which types are used by the module.
 /// 2. If the protocol is a root protocol, add a link to it in the `ProtocolId::root` method according to the linktype.
-/// 3. Add a parsing method with the signature `ParseFn` to the `ProtocolId::parse` method. The parsing method itself should be placed in your module, e.g., `protocols::custom_protocol`.
+/// 3. The parsing method with the signature `ParseFn` should be placed in your module, e.g., `protocols::custom_protocol::parse`.
 /// 4. If there is a way to determine the most suitable nested protocol, create a `best_children` method in your module, following the pattern of existing methods. Link your new method in `ProtocolId::best_children`.
 /// 5. In `ProtocolId::children`, specify whether there are any nested protocols.
+/// 6. If the protocol runs over TCP or UDP and its start is recognizable, create a `looks_like` method in your module and declare it as the signature, then add it to the order of `ProtocolId::by_signature`.
 ///
+/// Other protocols are referenced by [`enabled`], or under `#[cfg(feature = "...")]`,
+/// so the crate is built with any set of them.
 /// That's it! After that, write tests and verify that parsing works correctly.
 
-// FEATURE: FTP, TLS/SSL, IMAP, POP3, SMTP, SSH, SIP, RTP
-#[derive(Clone, Debug, EnumIter, Display, Serialize, Deserialize, PartialEq, Copy)]
-pub enum ProtocolId {
-    Arp,
-    DHCPv4,
-    DHCPv6,
-    DNS,
-    Ethernet,
-    HTTP,
-    ICMPv4,
-    ICMPv6,
-    IPv4,
-    IPv6,
-    TCP,
-    UDP,
+// FEATURE: FTP, TLS/SSL, IMAP, POP3, SMTP, SSH
+
+// Every protocol is declared once. Its module & the variants of `ProtocolId`,
+// `ProtocolData` & `ProtocolDto` exist only with its feature enabled.
+macro_rules! protocols {
+    ($(
+        $id:ident: $feature:literal, $module:ident::{$data:ident, $dto:ident},
+        ports: $ports:expr, signature: $signature:expr;
+    )*) => {
+        $(
+            #[cfg(feature = $feature)]
+            pub mod $module;
+        )*
+
+        #[derive(Clone, Debug, EnumIter, Display, Serialize, Deserialize, PartialEq, Copy)]
+        pub enum ProtocolId {
+            $(
+                #[cfg(feature = $feature)]
+                $id,
+            )*
+        }
+
+        impl ProtocolId {
+            pub fn parse(&self) -> ParseFn {
+                match *self {
+                    $(
+                        #[cfg(feature = $feature)]
+                        Self::$id => $module::parse,
+                    )*
+                }
+            }
+
+            pub fn check_ports(&self) -> Option<PortFn> {
+                match *self {
+                    $(
+                        #[cfg(feature = $feature)]
+                        Self::$id => $ports,
+                    )*
+                }
+            }
+
+            /// Cheap check of the payload start, so the services on the non-standard ports
+            /// are recognized. It gets no more than [`SIGNATURE_LENGTH`] bytes.
+            pub fn looks_like(&self) -> Option<SignatureFn> {
+                match *self {
+                    $(
+                        #[cfg(feature = $feature)]
+                        Self::$id => $signature,
+                    )*
+                }
+            }
+        }
+
+        #[derive(Clone, Debug)]
+        pub enum ProtocolData {
+            $(
+                #[cfg(feature = $feature)]
+                $id($module::$data),
+            )*
+        }
+
+        #[derive(Clone, Debug, Serialize, Deserialize)]
+        pub enum ProtocolDto {
+            $(
+                #[cfg(feature = $feature)]
+                $id($module::$dto),
+            )*
+        }
+
+        impl ProtocolDto {
+            pub fn id(&self) -> ProtocolId {
+                match *self {
+                    $(
+                        #[cfg(feature = $feature)]
+                        Self::$id(_) => ProtocolId::$id,
+                    )*
+                }
+            }
+        }
+
+        impl FieldIter for ProtocolDto {
+            fn fields(&self) -> Vec<(&'static str, String)> {
+                match *self {
+                    $(
+                        #[cfg(feature = $feature)]
+                        Self::$id(ref dto) => dto.fields(),
+                    )*
+                }
+            }
+        }
+
+        impl From<ProtocolData> for ProtocolDto {
+            fn from(value: ProtocolData) -> Self {
+                match value {
+                    $(
+                        #[cfg(feature = $feature)]
+                        ProtocolData::$id(value) => Self::$id(value.into()),
+                    )*
+                }
+            }
+        }
+
+        /// Ids of the protocols, `None` if the feature of the protocol is disabled.
+        /// <br> Other protocols are referenced by them, e.g. in the children lists.
+        #[allow(non_upper_case_globals)]
+        pub mod enabled {
+            use super::ProtocolId;
+
+            $(
+                #[cfg(feature = $feature)]
+                pub const $id: Option<ProtocolId> = Some(ProtocolId::$id);
+                #[cfg(not(feature = $feature))]
+                pub const $id: Option<ProtocolId> = None;
+            )*
+        }
+    };
+}
+
+protocols! {
+    Arp: "arp", arp::{Arp, ArpDto},
+        ports: None, signature: None;
+    DHCPv4: "dhcpv4", dhcpv4::{DHCPv4, DHCPv4Dto},
+        ports: Some(dhcpv4::is_protocol_default), signature: None;
+    DHCPv6: "dhcpv6", dhcpv6::{DHCPv6, DHCPv6Dto},
+        ports: Some(dhcpv6::is_protocol_default), signature: None;
+    DNS: "dns", dns::{DNS, DnsDto},
+        ports: Some(dns::is_protocol_default), signature: Some(dns::looks_like);
+    Ethernet: "ethernet", ethernet::{Ethernet, EthernetDto},
+        ports: None, signature: None;
+    HTTP: "http", http::{HTTP, HttpDto},
+        ports: Some(http::is_protocol_default), signature: Some(http::looks_like);
+    ICMPv4: "icmpv4", icmpv4::{ICMPv4, ICMPv4Dto},
+        ports: None, signature: None;
+    ICMPv6: "icmpv6", icmpv6::{ICMPv6, ICMPv6Dto},
+        ports: None, signature: None;
+    IEEE80211: "ieee80211", ieee80211::{IEEE80211, IEEE80211Dto},
+        ports: None, signature: None;
+    IPv4: "ipv4", ipv4::{IPv4, IPv4Dto},
+        ports: None, signature: None;
+    IPv6: "ipv6", ipv6::{IPv6, IPv6Dto},
+        ports: None, signature: None;
+    NBNS: "nbns", nbns::{NBNS, NbnsDto},
+        ports: Some(nbns::is_protocol_default), signature: None;
+    Radiotap: "radiotap", radiotap::{Radiotap, RadiotapDto},
+        ports: None, signature: None;
+    RTP: "rtp", rtp::{RTP, RtpDto},
+        ports: Some(rtp::is_protocol_default), signature: None;
+    SIP: "sip", sip::{SIP, SipDto},
+        ports: Some(sip::is_protocol_default), signature: Some(sip::looks_like);
+    SMB2: "smb2", smb2::{SMB2, Smb2Dto},
+        ports: Some(smb2::is_protocol_default), signature: Some(smb2::looks_like);
+    Syslog: "syslog", syslog::{Syslog, SyslogDto},
+        ports: Some(syslog::is_protocol_default), signature: None;
+    TCP: "tcp", tcp::{TCP, TcpDto},
+        ports: None, signature: None;
+    TFTP: "tftp", tftp::{TFTP, TftpDto},
+        ports: Some(tftp::is_protocol_default), signature: None;
+    UDP: "udp", udp::{UDP, UdpDto},
+        ports: None, signature: None;
 }
 
 impl ProtocolId {
-    pub fn root(link_type: &pcap::Linktype) -> Option<Self> {
+    pub fn root(link_type: LinkType) -> Option<Self> {
         match link_type {
-            pcap::Linktype(1) => Some(Self::Ethernet),
+            LinkType::ETHERNET => enabled::Ethernet,
+            LinkType::IEEE802_11 => enabled::IEEE80211,
+            LinkType::IEEE802_11_RADIOTAP => enabled::Radiotap,
+            LinkType::IPV4 => enabled::IPv4,
+            LinkType::IPV6 => enabled::IPv6,
             _ => None,
         }
     }
 
-    pub fn parse(&self) -> ParseFn {
-        match self {
-            Self::Ethernet => ethernet::parse,
-            Self::Arp => arp::parse,
-            Self::DHCPv4 => dhcpv4::parse,
-            Self::DHCPv6 => dhcpv6::parse,
-            Self::DNS => dns::parse,
-            Self::HTTP => http::parse,
-            Self::ICMPv4 => icmpv4::parse,
-            Self::ICMPv6 => icmpv6::parse,
-            Self::IPv4 => ipv4::parse,
-            Self::IPv6 => ipv6::parse,
-            Self::TCP => tcp::parse,
-            Self::UDP => udp::parse,
-        }
+    /// Child, which signature is found in the payload. Checked after the ports.
+    pub fn by_signature(children: &[Self], payload: &[u8]) -> Option<Self> {
+        // Ordered by the cost: magic numbers, text prefixes, header sanity.
+        // SIP goes before HTTP, they share the OPTIONS method
+        const ORDER: [Option<ProtocolId>; 4] =
+            [enabled::SMB2, enabled::SIP, enabled::HTTP, enabled::DNS];
+        let payload = payload.get(..SIGNATURE_LENGTH).unwrap_or(payload);
+
+        ORDER
+            .into_iter()
+            .flatten()
+            .filter(|id| children.contains(id))
+            .find(|id| {
+                id.looks_like()
+                    .is_some_and(|looks_like| looks_like(payload))
+            })
     }
 
-    pub fn check_ports(&self) -> Option<PortFn> {
-        match self {
-            Self::Ethernet => None,
-            Self::Arp => None,
-            Self::IPv4 => None,
-            Self::IPv6 => None,
-            Self::ICMPv4 => None,
-            Self::ICMPv6 => None,
-            Self::TCP => None,
-            Self::UDP => None,
-            Self::DHCPv4 => Some(dhcpv4::is_protocol_default),
-            Self::DHCPv6 => Some(dhcpv6::is_protocol_default),
-            Self::DNS => Some(dns::is_protocol_default),
-            Self::HTTP => Some(http::is_protocol_default),
-        }
+    /// Children, that aren't ruled out by the payload structure. Ports & signatures
+    /// only suggest the protocol, so the clearly foreign payload isn't parsed.
+    pub fn plausible(
+        children: Vec<Self>, payload: &[u8], is_truncated: bool,
+    ) -> Vec<Self> {
+        children
+            .into_iter()
+            .filter(|id| match *id {
+                #[cfg(feature = "dns")]
+                Self::DNS => dns::is_plausible(payload, is_truncated),
+                _ => true,
+            })
+            .collect()
     }
 
-    pub fn best_children(&self, metadata: &FrameMetadata) -> Option<Self> {
-        match self {
+    pub fn best_children(
+        &self, metadata: &FrameMetadata, ports: &PortTable, payload: &[u8],
+    ) -> Option<Self> {
+        match *self {
+            #[cfg(feature = "ethernet")]
             Self::Ethernet => ethernet::best_children(metadata),
-            Self::Arp => None,
-            Self::DHCPv4 => None,
-            Self::DHCPv6 => None,
-            Self::DNS => None,
-            Self::HTTP => None,
-            Self::ICMPv4 => None,
-            Self::ICMPv6 => None,
+            #[cfg(feature = "ieee80211")]
+            Self::IEEE80211 => ieee80211::best_children(metadata),
+            #[cfg(feature = "ipv4")]
             Self::IPv4 => ipv4::best_children(metadata),
+            #[cfg(feature = "ipv6")]
             Self::IPv6 => ipv6::best_children(metadata),
-            Self::TCP => tcp::best_children(metadata),
-            Self::UDP => udp::best_children(metadata),
+            #[cfg(feature = "radiotap")]
+            Self::Radiotap => radiotap::best_children(metadata),
+            #[cfg(feature = "tcp")]
+            Self::TCP => tcp::best_children(metadata, ports, payload),
+            #[cfg(feature = "udp")]
+            Self::UDP => udp::best_children(metadata, ports, payload),
+            _ => None,
         }
     }
 
     pub fn children(&self) -> Option<Vec<Self>> {
-        match self {
-            Self::Ethernet => Some(vec![Self::Arp, Self::IPv4, Self::IPv6]),
-            Self::Arp => None,
+        match *self {
+            #[cfg(feature = "ethernet")]
+            Self::Ethernet => {
+                Some(only_enabled(&[enabled::Arp, enabled::IPv4, enabled::IPv6]))
+            },
+            #[cfg(feature = "radiotap")]
+            Self::Radiotap => Some(only_enabled(&[enabled::IEEE80211])),
 
-            Self::IPv4 => Some(vec![Self::ICMPv4, Self::TCP, Self::UDP]),
-            Self::IPv6 => Some(vec![Self::TCP, Self::UDP, Self::ICMPv6, Self::IPv6]),
-            Self::ICMPv4 => None,
-            Self::ICMPv6 => None,
+            #[cfg(feature = "ipv4")]
+            Self::IPv4 => {
+                Some(only_enabled(&[enabled::ICMPv4, enabled::TCP, enabled::UDP]))
+            },
 
             // TODO: TCP, UDP: Add HTTPS
-            Self::TCP => Some(vec![Self::HTTP, Self::DNS, Self::DHCPv4, Self::DHCPv6]),
-            Self::UDP => Some(vec![Self::HTTP, Self::DNS, Self::DHCPv4, Self::DHCPv6]),
+            #[cfg(feature = "tcp")]
+            Self::TCP => Some(only_enabled(&[
+                enabled::HTTP,
+                enabled::DNS,
+                enabled::DHCPv4,
+                enabled::DHCPv6,
+                enabled::SIP,
+                enabled::SMB2,
+            ])),
+            #[cfg(feature = "udp")]
+            Self::UDP => Some(only_enabled(&[
+                enabled::HTTP,
+                enabled::DNS,
+                enabled::DHCPv4,
+                enabled::DHCPv6,
+                enabled::NBNS,
+                enabled::SIP,
+                enabled::Syslog,
+                enabled::TFTP,
+                // RTP isn't guessed on any ports, see `udp::best_children`
+            ])),
 
-            Self::DHCPv4 => None,
-            Self::DHCPv6 => None,
-            Self::DNS => None,
-            Self::HTTP => None,
+            // IEEE 802.11 passes the payload only by LLC/SNAP EtherType.
+            // IPv6 knows the upper layer from the extension headers, guessing it makes
+            // false positives on non-first fragments
+            _ => None,
         }
     }
-}
 
-#[derive(Clone, Debug)]
-pub enum ProtocolData {
-    Ethernet(ethernet::Ethernet),
-
-    Arp(arp::Arp),
-
-    DHCPv4(dhcpv4::DHCPv4),
-    DHCPv6(dhcpv6::DHCPv6),
-    DNS(dns::DNS),
-    HTTP(http::HTTP),
-
-    IPv4(ipv4::IPv4),
-    IPv6(ipv6::IPv6),
+    /// Several messages of the protocol may share the payload (pipelining, keep-alive).
+    /// <br> Every message is the separate layer, the trailing partial one is left over.
+    pub fn is_repeated(&self) -> bool {
+        Some(*self) == enabled::HTTP
+    }
+}
 
-    ICMPv4(icmpv4::ICMPv4),
-    ICMPv6(icmpv6::ICMPv6),
+// Children, which features are enabled
+fn only_enabled(ids: &[Option<ProtocolId>]) -> Vec<ProtocolId> {
+    ids.iter().flatten().copied().collect()
+}
 
-    TCP(tcp::TCP),
-    UDP(udp::UDP),
+impl ProtocolData {
+    /// Layer is parsed partially: its payload is cut off by the end of the bytes.
+    pub fn is_truncated(&self) -> bool {
+        match self {
+            #[cfg(feature = "dns")]
+            Self::DNS(value) => value.is_truncated,
+            #[cfg(feature = "http")]
+            Self::HTTP(value) => value.is_truncated(),
+            _ => false,
+        }
+    }
 }
 
-pub mod arp;
-pub mod dhcpv4;
-pub mod dhcpv6;
-pub mod dns;
-pub mod ethernet;
-pub mod http;
-pub mod icmpv4;
-pub mod icmpv6;
 pub mod ip {
     pub mod address;
+    pub mod checksum;
     pub mod protocol;
 }
-pub mod ipv4;
-pub mod ipv6;
-pub mod tcp;
-pub mod udp;
+pub mod port_table;
+
+#[cfg(all(
+    test,
+    feature = "http",
+    feature = "ipv4",
+    feature = "sip",
+    feature = "smb2",
+    feature = "tcp",
+    feature = "udp"
+))]
+mod tests {
+    use super::*;
+    use crate::parser::tests::random_payloads;
+    use crate::parser::tests::{FrameType, ProtocolParser, tcp_metadata};
+
+    #[test]
+    fn test_signatures() {
+        let children = ProtocolId::TCP.children().unwrap();
+        let probe = |payload: &[u8]| ProtocolId::by_signature(&children, payload);
+
+        assert_eq!(probe(b"GET / HTTP/1.1\r\n"), Some(ProtocolId::HTTP));
+        assert_eq!(probe(b"HTTP/1.1 200 OK\r\n"), Some(ProtocolId::HTTP));
+        assert_eq!(probe(b"OPTIONS * HTTP/1.1\r\n"), Some(ProtocolId::HTTP));
+        assert_eq!(
+            probe(b"OPTIONS sip:bob@example.com SIP/2.0\r\n"),
+            Some(ProtocolId::SIP)
+        );
+        assert_eq!(probe(b"SIP/2.0 180 Ringing\r\n"), Some(ProtocolId::SIP));
+        assert_eq!(
+            probe(b"\x00\x00\x00\x40\xFESMB\x40\x00"),
+            Some(ProtocolId::SMB2)
+        );
+        assert_eq!(probe(b"SSH-2.0-OpenSSH_9.6\r\n"), None);
+        assert_eq!(probe(b""), None);
+    }
+
+    #[test]
+    fn test_signatures_random_payloads() {
+        let payloads = random_payloads(1000, 64);
+        for protocol in [ProtocolId::TCP, ProtocolId::UDP] {
+            let children = protocol.children().unwrap();
+            for payload in payloads.iter() {
+                assert_eq!(ProtocolId::by_signature(&children, payload), None);
+            }
+        }
+
+        // Nothing is parsed above TCP on the unassigned port
+        let parser = ProtocolParser::new(LinkType::IPV4, false);
+        for payload in payloads.iter() {
+            match tcp_metadata(&parser, 12345, 54321, payload) {
+                Some(FrameType::Metadata(metadata)) => {
+                    assert_eq!(metadata.layers.len(), 2)
+                },
+                _ => panic!(),
+            }
+        }
+    }
+}
@@ -1,34 +1,34 @@
-use crate::parser::ParserError;
+use crate::dto::fields::FieldIter;
+use crate::parser::{IResult, ParserError};
 use crate::protocols::arp::hardware_type::HardwareType;
 pub use crate::protocols::arp::operation::Operation;
 use crate::protocols::ethernet::ether_type::EtherType;
 use crate::protocols::ethernet::mac::MacAddress;
 use crate::protocols::{ProtocolData, ethernet, ip};
+use nom::Finish;
 use nom::Parser;
+use nom::bytes::take;
 use nom::number::be_u8;
-use nom::{Finish, IResult};
 use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
 use std::net::Ipv4Addr;
 use thiserror::Error;
 
 // ARP Protocol
 // RFC 826: https://datatracker.ietf.org/doc/html/rfc826
+// RARP, RFC 903: https://datatracker.ietf.org/doc/html/rfc903
+// InARP, RFC 2390: https://datatracker.ietf.org/doc/html/rfc2390
 
-pub const PACKET_LENGTH: usize = 28;
+// Fixed part: HTYPE, PTYPE, HLEN, PLEN, OP
+pub const HEADER_LENGTH: usize = 8;
 
 pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
-    if bytes.len() < PACKET_LENGTH {
-        return Err(ParserError::ErrorVerify.to_nom(bytes));
-    };
-
-    // Cutting Ethernet padding & FCS
-    let bytes = if bytes.len() > PACKET_LENGTH {
-        match bytes.get(..PACKET_LENGTH) {
-            Some(value) => value,
-            None => return Err(ParserError::ErrorVerify.to_nom(bytes)),
+    if bytes.len() < HEADER_LENGTH {
+        return Err(ParserError::Truncated {
+            needed: HEADER_LENGTH,
+            available: bytes.len(),
         }
-    } else {
-        bytes
+        .to_nom(bytes));
     };
 
     // HTYPE
@@ -36,39 +36,56 @@ pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
 
     // PTYPE
     let (rest, protocol_type) = ethernet::ether_type::parse(rest)?;
-    if protocol_type != EtherType::Ipv4 {
-        return Err(ParserError::ErrorVerify.to_nom(bytes));
-    }
 
     // HLEN
     let (rest, hardware_address_length) = be_u8().parse(rest)?;
     hardware_type
         .validate_length(hardware_address_length as usize)
-        .map_err(|_| ParserError::ErrorVerify.to_nom(bytes))?;
+        .map_err(|_| ParserError::InvalidValue { field: "hlen" }.to_nom(bytes))?;
 
     // PLEN
     let (rest, protocol_address_length) = be_u8().parse(rest)?;
-    if protocol_address_length != ip::address::V4_LENGTH_BYTES as u8 {
-        return Err(ParserError::ErrorVerify.to_nom(bytes));
-    }
 
     // OP
     let (rest, operation) = operation::parse(rest)?;
 
+    // Cutting Ethernet padding & FCS
+    let addresses_length = (hardware_address_length as usize)
+        .checked_add(protocol_address_length as usize)
+        .and_then(|length| length.checked_mul(2))
+        .ok_or(ParserError::InvalidValue { field: "plen" }.to_nom(bytes))?;
+    let rest = match rest.get(..addresses_length) {
+        Some(value) => value,
+        None => {
+            return Err(ParserError::Truncated {
+                needed: HEADER_LENGTH.saturating_add(addresses_length),
+                available: bytes.len(),
+            }
+            .to_nom(bytes));
+        },
+    };
+
     // SENDER_HARDWARE_ADDRESS
-    let (rest, sender_hardware_address) = ethernet::mac::parse(rest)?;
+    let (rest, sender_hardware) =
+        hardware_address_parse(rest, &hardware_type, hardware_address_length)?;
 
     // SENDER_PROTOCOL_ADDRESS
-    let (rest, sender_protocol_address) = ip::address::v4_parse(rest)?;
+    let (rest, sender_protocol) =
+        protocol_address_parse(rest, &protocol_type, protocol_address_length)?;
 
     // TARGET_HARDWARE_ADDRESS
-    let (rest, target_hardware_address) = ethernet::mac::parse(rest)?;
+    let (rest, target_hardware) =
+        hardware_address_parse(rest, &hardware_type, hardware_address_length)?;
 
     // TARGET_PROTOCOL_ADDRESS
-    let (rest, target_protocol_address) = ip::address::v4_parse(rest)?;
+    let (rest, target_protocol) =
+        protocol_address_parse(rest, &protocol_type, protocol_address_length)?;
 
     if !rest.is_empty() {
-        return Err(ParserError::ErrorVerify.to_nom(bytes));
+        return Err(ParserError::ConstraintViolation {
+            description: "addresses don't match their lengths",
+        }
+        .to_nom(bytes));
     }
 
     let arp = Arp {
@@ -77,15 +94,41 @@ pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
         hardware_address_length,
         protocol_address_length,
         operation,
-        sender_mac: sender_hardware_address,
-        sender_ip: sender_protocol_address,
-        target_mac: target_hardware_address,
-        target_ip: target_protocol_address,
+        sender_hardware,
+        sender_protocol,
+        target_hardware,
+        target_protocol,
     };
 
     Finish::finish(Ok((rest, ProtocolData::Arp(arp))))
 }
 
+fn hardware_address_parse<'a>(
+    input: &'a [u8], hardware_type: &HardwareType, length: u8,
+) -> IResult<&'a [u8], HardwareAddress> {
+    if hardware_type.is_mac() && length as usize == ethernet::mac::LENGTH_BYTES {
+        let (rest, mac) = ethernet::mac::parse(input)?;
+        return Ok((rest, HardwareAddress::Mac(mac)));
+    }
+
+    let (rest, address) = take(length).parse(input)?;
+    Ok((rest, HardwareAddress::Raw(RawAddress(address.to_vec()))))
+}
+
+fn protocol_address_parse<'a>(
+    input: &'a [u8], protocol_type: &EtherType, length: u8,
+) -> IResult<&'a [u8], ProtocolAddress> {
+    if *protocol_type == EtherType::Ipv4
+        && length as usize == ip::address::V4_LENGTH_BYTES
+    {
+        let (rest, address) = ip::address::v4_parse(input)?;
+        return Ok((rest, ProtocolAddress::IPv4(address)));
+    }
+
+    let (rest, address) = take(length).parse(input)?;
+    Ok((rest, ProtocolAddress::Raw(RawAddress(address.to_vec()))))
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Arp {
     pub hardware_type: HardwareType,
@@ -96,11 +139,58 @@ pub struct Arp {
 
     pub operation: Operation,
 
-    pub sender_mac: MacAddress,
-    pub sender_ip: Ipv4Addr,
+    pub sender_hardware: HardwareAddress,
+    pub sender_protocol: ProtocolAddress,
+
+    pub target_hardware: HardwareAddress,
+    pub target_protocol: ProtocolAddress,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum HardwareAddress {
+    Mac(MacAddress),
+    Raw(RawAddress),
+}
+
+impl Display for HardwareAddress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HardwareAddress::Mac(mac) => write!(f, "{mac}"),
+            HardwareAddress::Raw(raw) => write!(f, "{raw}"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum ProtocolAddress {
+    IPv4(Ipv4Addr),
+    Raw(RawAddress),
+}
 
-    pub target_mac: MacAddress,
-    pub target_ip: Ipv4Addr,
+impl Display for ProtocolAddress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProtocolAddress::IPv4(address) => write!(f, "{address}"),
+            ProtocolAddress::Raw(raw) => write!(f, "{raw}"),
+        }
+    }
+}
+
+/// Address of unknown format. Displayed as hex bytes, separated by colons.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct RawAddress(pub Vec<u8>);
+
+impl Display for RawAddress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let string = self
+            .0
+            .iter()
+            .map(|byte| format!("{byte:02X}"))
+            .collect::<Vec<_>>()
+            .join(":");
+
+        write!(f, "{string}")
+    }
 }
 
 #[derive(Clone, Debug, Error, PartialEq)]
@@ -117,56 +207,71 @@ pub enum ArpError {
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct ArpDto {
+    #[serde(rename = "op")]
     pub operation: Operation,
 
-    pub sender_mac: MacAddress,
-    pub sender_ip: Ipv4Addr,
+    #[serde(rename = "sha")]
+    pub sender_hardware: HardwareAddress,
+    #[serde(rename = "spa")]
+    pub sender_protocol: ProtocolAddress,
 
-    pub target_mac: MacAddress,
-    pub target_ip: Ipv4Addr,
+    #[serde(rename = "tha")]
+    pub target_hardware: HardwareAddress,
+    #[serde(rename = "tpa")]
+    pub target_protocol: ProtocolAddress,
 }
 
 impl From<Arp> for ArpDto {
     fn from(value: Arp) -> Self {
         Self {
             operation: value.operation,
-            sender_mac: value.sender_mac,
-            sender_ip: value.sender_ip,
-            target_mac: value.target_mac,
-            target_ip: value.target_ip,
+            sender_hardware: value.sender_hardware,
+            sender_protocol: value.sender_protocol,
+            target_hardware: value.target_hardware,
+            target_protocol: value.target_protocol,
         }
     }
 }
 
+impl FieldIter for ArpDto {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let Self {
+            operation,
+            sender_hardware,
+            sender_protocol,
+            target_hardware,
+            target_protocol,
+        } = self;
+
+        vec![
+            ("operation", operation.to_string()),
+            ("sender_hardware", sender_hardware.to_string()),
+            ("sender_protocol", sender_protocol.to_string()),
+            ("target_hardware", target_hardware.to_string()),
+            ("target_protocol", target_protocol.to_string()),
+        ]
+    }
+}
+
 pub mod hardware_type;
 pub mod operation;
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::dto::frame::FrameHeader;
+    use crate::dto::frame::{FrameHeader, LinkType};
     use crate::parser::tests::FrameType;
     use crate::parser::tests::ProtocolParser;
     use crate::protocols::arp::operation::Operation;
-    use crate::protocols::ethernet::Ethernet;
 
     #[test]
-    fn test_arp_without_ethernet_padding() {
-        let hex_actual = "00 1A 8C 10 AD 30 00 1E 68 51 4F A9 08 06 00 01 08 00 06 04 00 02 00 1E 68 51 4F A9 AC 10 FF 01 00 1A 8C 10 AD 30 AC 10 00 01".replace(" ", "");
+    fn test_inarp_request() {
+        let hex_actual = "00 1A 8C 10 AD 30 00 1E 68 51 4F A9 08 06 00 01 08 00 06 04 00 08 00 1E 68 51 4F A9 AC 10 FF 01 00 1A 8C 10 AD 30 00 00 00 00".replace(" ", "");
         let frame = hex::decode(hex_actual).unwrap();
-        let header = FrameHeader {
-            tv_sec: 0,
-            tv_usec: 0,
-            caplen: 42,
-            len: 0,
-        };
+        let header = FrameHeader::new(42);
 
-        let parser = ProtocolParser::new(&pcap::Linktype(1), false);
-        let packet = pcap::Packet {
-            header: &pcap::PacketHeader::from(&header),
-            data: &frame,
-        };
-        let result = parser.process(packet);
+        let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+        let result = parser.process_bytes(&header, &frame);
         let metadata = match result {
             Some(value) => match value {
                 FrameType::Metadata(value) => value,
@@ -175,19 +280,6 @@ mod tests {
             None => panic!(),
         };
 
-        let actual_ethernet = match metadata.layers[0].clone() {
-            ProtocolData::Ethernet(value) => value,
-            _ => panic!(),
-        };
-
-        let expected_ethernet = Ethernet {
-            destination_mac: MacAddress::try_from("00:1A:8C:10:AD:30").unwrap(),
-            source_mac: MacAddress::try_from("00:1E:68:51:4F:A9").unwrap(),
-            ether_type: EtherType::Arp,
-        };
-
-        assert_eq!(actual_ethernet, expected_ethernet);
-
         let actual_arp = match metadata.layers[1].clone() {
             ProtocolData::Arp(value) => value,
             _ => panic!(),
@@ -198,33 +290,63 @@ mod tests {
             protocol_type: EtherType::Ipv4,
             hardware_address_length: ethernet::mac::LENGTH_BYTES as u8,
             protocol_address_length: ip::address::V4_LENGTH_BYTES as u8,
-            operation: Operation::Reply,
-            sender_mac: MacAddress::try_from("00:1E:68:51:4F:A9").unwrap(),
-            sender_ip: Ipv4Addr::new(172, 16, 255, 1),
-            target_mac: MacAddress::try_from("00:1A:8C:10:AD:30").unwrap(),
-            target_ip: Ipv4Addr::new(172, 16, 0, 1),
+            operation: Operation::InArpRequest,
+            sender_hardware: HardwareAddress::Mac(
+                MacAddress::try_from("00:1E:68:51:4F:A9").unwrap(),
+            ),
+            sender_protocol: ProtocolAddress::IPv4(Ipv4Addr::new(172, 16, 255, 1)),
+            target_hardware: HardwareAddress::Mac(
+                MacAddress::try_from("00:1A:8C:10:AD:30").unwrap(),
+            ),
+            target_protocol: ProtocolAddress::IPv4(Ipv4Addr::new(0, 0, 0, 0)),
         };
 
         assert_eq!(actual_arp, expected_arp);
     }
 
     #[test]
-    fn test_arp_with_ethernet_padding() {
-        let hex_actual = "00 1E 68 51 4F A9 00 1A 8C 10 AD 30 08 06 00 01 08 00 06 04 00 01 00 1A 8C 10 AD 30 AC 10 00 01 00 00 00 00 00 00 AC 10 FF 01 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00".replace(" ", "");
+    fn test_arp_ethernet_padding() {
+        // Minimal Ethernet frame is 60 bytes, so the request is padded with 18 zeros.
+        // Some drivers also keep the FCS.
+        let request = "FF FF FF FF FF FF 00 1E 68 51 4F A9 08 06 00 01 08 00 06 04 00 01 00 1E 68 51 4F A9 C0 A8 00 0A 00 00 00 00 00 00 C0 A8 00 01";
+        let padding = " 00".repeat(18);
+        for trailer in ["", " 5E 2A 91 07"] {
+            let hex_actual = format!("{request}{padding}{trailer}").replace(" ", "");
+            let frame = hex::decode(hex_actual).unwrap();
+            let header = FrameHeader::new(frame.len());
+
+            let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+            let (metadata, failure) = match parser.process_bytes_traced(&header, &frame) {
+                Some((FrameType::Metadata(metadata), failure)) => (metadata, failure),
+                _ => panic!(),
+            };
+            assert!(failure.is_none());
+            assert_eq!(metadata.layers.len(), 2);
+
+            let actual_arp = match metadata.layers[1].clone() {
+                ProtocolData::Arp(value) => value,
+                _ => panic!(),
+            };
+            assert_eq!(actual_arp.operation, Operation::Request);
+            assert_eq!(
+                actual_arp.sender_protocol,
+                ProtocolAddress::IPv4(Ipv4Addr::new(192, 168, 0, 10))
+            );
+            assert_eq!(
+                actual_arp.target_protocol,
+                ProtocolAddress::IPv4(Ipv4Addr::new(192, 168, 0, 1))
+            );
+        }
+    }
+
+    #[test]
+    fn test_arp_eight_byte_hardware_address() {
+        let hex_actual = "FF FF FF FF FF FF 00 1E 68 51 4F A9 08 06 00 1B 08 00 08 04 00 01 02 1E 68 FF FE 51 4F A9 C0 A8 00 0A 00 00 00 00 00 00 00 00 C0 A8 00 01 00 00 00 00 00 00 00 00 00 00 00 00".replace(" ", "");
         let frame = hex::decode(hex_actual).unwrap();
-        let header = FrameHeader {
-            tv_sec: 0,
-            tv_usec: 0,
-            caplen: 60,
-            len: 0,
-        };
+        let header = FrameHeader::new(60);
 
-        let parser = ProtocolParser::new(&pcap::Linktype(1), false);
-        let packet = pcap::Packet {
-            header: &pcap::PacketHeader::from(&header),
-            data: &frame,
-        };
-        let result = parser.process(packet);
+        let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+        let result = parser.process_bytes(&header, &frame);
         let metadata = match result {
             Some(value) => match value {
                 FrameType::Metadata(value) => value,
@@ -233,36 +355,29 @@ mod tests {
             None => panic!(),
         };
 
-        let actual_ethernet = match metadata.layers[0].clone() {
-            ProtocolData::Ethernet(value) => value,
-            _ => panic!(),
-        };
-
-        let expected_ethernet = Ethernet {
-            destination_mac: MacAddress::try_from("00:1E:68:51:4F:A9").unwrap(),
-            source_mac: MacAddress::try_from("00:1A:8C:10:AD:30").unwrap(),
-            ether_type: EtherType::Arp,
-        };
-
-        assert_eq!(actual_ethernet, expected_ethernet);
-
         let actual_arp = match metadata.layers[1].clone() {
             ProtocolData::Arp(value) => value,
             _ => panic!(),
         };
 
         let expected_arp = Arp {
-            hardware_type: HardwareType::Ethernet,
+            hardware_type: HardwareType::Eui64,
             protocol_type: EtherType::Ipv4,
-            hardware_address_length: ethernet::mac::LENGTH_BYTES as u8,
+            hardware_address_length: 8,
             protocol_address_length: ip::address::V4_LENGTH_BYTES as u8,
             operation: Operation::Request,
-            sender_mac: MacAddress::try_from("00:1A:8C:10:AD:30").unwrap(),
-            sender_ip: Ipv4Addr::new(172, 16, 0, 1),
-            target_mac: MacAddress::try_from("00:00:00:00:00:00").unwrap(),
-            target_ip: Ipv4Addr::new(172, 16, 255, 1),
+            sender_hardware: HardwareAddress::Raw(RawAddress(vec![
+                0x02, 0x1E, 0x68, 0xFF, 0xFE, 0x51, 0x4F, 0xA9,
+            ])),
+            sender_protocol: ProtocolAddress::IPv4(Ipv4Addr::new(192, 168, 0, 10)),
+            target_hardware: HardwareAddress::Raw(RawAddress(vec![0; 8])),
+            target_protocol: ProtocolAddress::IPv4(Ipv4Addr::new(192, 168, 0, 1)),
         };
 
         assert_eq!(actual_arp, expected_arp);
+        assert_eq!(
+            actual_arp.sender_hardware.to_string(),
+            "02:1E:68:FF:FE:51:4F:A9"
+        );
     }
 }
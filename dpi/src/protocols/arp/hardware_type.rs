@@ -1,37 +1,53 @@
-use crate::parser::ParserError;
+use crate::parser::{IResult, ParserError};
 use crate::protocols::arp::ArpError;
 use crate::protocols::ethernet;
-use nom::IResult;
 use nom::Parser;
 use nom::number::be_u16;
 use num_enum::TryFromPrimitive;
 use serde::{Deserialize, Serialize};
 
+// IANA: https://www.iana.org/assignments/arp-parameters/arp-parameters.xhtml#arp-parameters-2
+
 pub const LENGTH_BYTES: usize = 2;
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, TryFromPrimitive)]
 #[repr(u16)]
 pub enum HardwareType {
     Ethernet = 1,
+    Ieee802 = 6,
+    Arcnet = 7,
+    FrameRelay = 15,
+    Atm = 16,
+    Hdlc = 17,
+    FibreChannel = 18,
+    AtmRfc2225 = 19,
+    SerialLine = 20,
+    Ieee1394 = 24,
+    Eui64 = 27,
+    InfiniBand = 32,
 }
 
 impl HardwareType {
-    pub fn bytes(&self) -> &[u8] {
-        match self {
-            Self::Ethernet => &[0x00, 0x01],
-        }
+    pub fn bytes(&self) -> [u8; LENGTH_BYTES] {
+        (self.clone() as u16).to_be_bytes()
+    }
+
+    /// Hardware types with 48-bit MAC addresses.
+    pub fn is_mac(&self) -> bool {
+        matches!(self, Self::Ethernet | Self::Ieee802)
     }
 
     pub fn validate_length(&self, length: usize) -> Result<(), ArpError> {
-        match self {
-            Self::Ethernet => {
-                let is_validated = length == ethernet::mac::LENGTH_BYTES;
-                if is_validated {
-                    Ok(())
-                } else {
-                    Err(ArpError::BadHardwareLength)
-                }
-            },
+        let is_validated = match self {
+            Self::Ethernet | Self::Ieee802 => length == ethernet::mac::LENGTH_BYTES,
+            Self::Eui64 => length == 8,
+            _ => length > 0,
+        };
+
+        if is_validated {
+            Ok(())
+        } else {
+            Err(ArpError::BadHardwareLength)
         }
     }
 }
@@ -40,10 +56,8 @@ impl TryFrom<&[u8; 2]> for HardwareType {
     type Error = ArpError;
 
     fn try_from(value: &[u8; 2]) -> Result<Self, Self::Error> {
-        match value {
-            [0x00, 0x01] => Ok(Self::Ethernet),
-            _ => Err(ArpError::HardwareTypeUnknown),
-        }
+        Self::try_from(u16::from_be_bytes(*value))
+            .map_err(|_| ArpError::HardwareTypeUnknown)
     }
 }
 
@@ -51,7 +65,7 @@ pub fn parse(input: &[u8]) -> IResult<&[u8], HardwareType> {
     let (input, number) = be_u16().parse(input)?;
 
     let hardware_type = HardwareType::try_from(number)
-        .map_err(|_| ParserError::ErrorVerify.to_nom(input))?;
+        .map_err(|_| ParserError::InvalidValue { field: "htype" }.to_nom(input))?;
 
     Ok((input, hardware_type))
 }
@@ -1,26 +1,40 @@
-use crate::parser::ParserError;
+use crate::parser::{IResult, ParserError};
 use crate::protocols::arp::ArpError;
-use nom::IResult;
 use nom::Parser;
 use nom::number::be_u16;
 use num_enum::TryFromPrimitive;
 use serde::{Deserialize, Serialize};
 use strum_macros::Display;
 
+// IANA: https://www.iana.org/assignments/arp-parameters/arp-parameters.xhtml#arp-parameters-1
+
 pub const LENGTH_BYTES: usize = 2;
 #[derive(Clone, Debug, Display, Serialize, Deserialize, PartialEq, TryFromPrimitive)]
 #[repr(u16)]
 pub enum Operation {
     Request = 1,
     Reply = 2,
+    #[strum(to_string = "RARP Request")]
+    ReverseRequest = 3,
+    #[strum(to_string = "RARP Reply")]
+    ReverseReply = 4,
+    #[strum(to_string = "DRARP Request")]
+    DrarpRequest = 5,
+    #[strum(to_string = "DRARP Reply")]
+    DrarpReply = 6,
+    #[strum(to_string = "DRARP Error")]
+    DrarpError = 7,
+    #[strum(to_string = "InARP Request")]
+    InArpRequest = 8,
+    #[strum(to_string = "InARP Reply")]
+    InArpReply = 9,
+    #[strum(to_string = "ARP NAK")]
+    Nak = 10,
 }
 
 impl Operation {
-    pub fn bytes(&self) -> &[u8] {
-        match self {
-            Self::Request => &[0x00, 0x01],
-            Self::Reply => &[0x00, 0x02],
-        }
+    pub fn bytes(&self) -> [u8; LENGTH_BYTES] {
+        (self.clone() as u16).to_be_bytes()
     }
 }
 
@@ -28,11 +42,7 @@ impl TryFrom<&[u8; 2]> for Operation {
     type Error = ArpError;
 
     fn try_from(value: &[u8; 2]) -> Result<Self, Self::Error> {
-        match value {
-            [0x00, 0x01] => Ok(Self::Request),
-            [0x00, 0x02] => Ok(Self::Reply),
-            _ => Err(ArpError::OperationUnknown),
-        }
+        Self::try_from(u16::from_be_bytes(*value)).map_err(|_| ArpError::OperationUnknown)
     }
 }
 
@@ -40,7 +50,7 @@ pub fn parse(input: &[u8]) -> IResult<&[u8], Operation> {
     let (input, number) = be_u16().parse(input)?;
 
     let operation = Operation::try_from(number)
-        .map_err(|_| ParserError::ErrorVerify.to_nom(input))?;
+        .map_err(|_| ParserError::InvalidValue { field: "oper" }.to_nom(input))?;
 
     Ok((input, operation))
 }
@@ -1,10 +1,11 @@
-use crate::parser::ParserError;
+use crate::dto::fields::{self, FieldIter};
+use crate::parser::{IResult, ParseFailure, ParserError};
 use crate::protocols::arp::hardware_type::HardwareType;
 use crate::protocols::ethernet::mac::MacAddress;
 use crate::protocols::{ProtocolData, ethernet, ip};
 use nom::bytes::take;
 use nom::number::{be_u8, be_u16, be_u32};
-use nom::{Finish, IResult, Parser, bits};
+use nom::{Finish, Parser, bits};
 use num_enum::TryFromPrimitive;
 use serde::{Deserialize, Serialize};
 use std::net::Ipv4Addr;
@@ -21,18 +22,21 @@ pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
     // Operation, 1 byte.
     let (rest, op) = be_u8().parse(bytes)?;
     let op = OperationType::try_from(op)
-        .map_err(|_| ParserError::ErrorVerify.to_nom(bytes))?;
+        .map_err(|_| ParserError::InvalidValue { field: "op" }.to_nom(bytes))?;
 
     // Hardware Type, 1 byte
     let (rest, htype) = be_u8().parse(rest)?;
     let htype = HardwareType::try_from(htype as u16)
-        .map_err(|_| ParserError::ErrorVerify.to_nom(bytes))?;
+        .map_err(|_| ParserError::InvalidValue { field: "htype" }.to_nom(bytes))?;
 
     // Hardware Address Length, 1 byte
     let (rest, hlen) = be_u8().parse(rest)?;
-    htype
-        .validate_length(hlen as usize)
-        .map_err(|_| ParserError::ErrorVerify.to_nom(bytes))?;
+    htype.validate_length(hlen as usize).map_err(|_| {
+        ParserError::ConstraintViolation {
+            description: "hardware address length doesn't match its type",
+        }
+        .to_nom(bytes)
+    })?;
 
     // Hops, 1 byte
     let (rest, hops) = be_u8().parse(rest)?;
@@ -45,7 +49,7 @@ pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
 
     // Flags, 2 bytes
     let (rest, (broadcast_flag, _)): (&[u8], (u8, u16)) =
-        bits::bits::<_, _, nom::error::Error<_>, _, _>((
+        bits::bits::<_, _, ParseFailure<_>, _, _>((
             bits::complete::take(BROADCAST_FLAG_LENGTH_BITS),
             bits::complete::take(16 - BROADCAST_FLAG_LENGTH_BITS),
         ))(rest)?;
@@ -90,23 +94,29 @@ pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
         // Magic cookies
         const MAGIC_NUMBERS: [u8; 4] = [0x63, 0x82, 0x53, 0x63];
         let (rest, magic_octets) = take(MAGIC_NUMBERS.len()).parse(rest)?;
-        let magic_octets = <[u8; MAGIC_NUMBERS.len()]>::try_from(magic_octets)
-            .map_err(|_| ParserError::ErrorVerify.to_nom(rest))?;
+        let magic_octets =
+            <[u8; MAGIC_NUMBERS.len()]>::try_from(magic_octets).map_err(|_| {
+                ParserError::InvalidValue {
+                    field: "magic_cookie",
+                }
+                .to_nom(rest)
+            })?;
         if magic_octets != MAGIC_NUMBERS {
-            return Err(ParserError::ErrorVerify.to_nom(rest));
+            return Err(ParserError::InvalidValue {
+                field: "magic_cookie",
+            }
+            .to_nom(rest));
         }
 
         let mut rest_buffer = rest;
         loop {
             let (rest, option) = Options::parse(rest_buffer)?;
-            if option == OptionData::Pad {
-                continue;
-            } else if option == OptionData::End {
-                break;
-            }
-
-            options.push(option);
             rest_buffer = rest;
+            match option {
+                OptionData::Pad | OptionData::Unknown(_) => continue,
+                OptionData::End => break,
+                option => options.push(option),
+            }
         }
     }
 
@@ -175,6 +185,8 @@ pub enum Options {
     DomainName = 15,
     MessageType = 53,
     ServerIdentifier = 54,
+    ParameterRequestList = 55,
+    VendorClassIdentifier = 60,
 }
 
 impl Options {
@@ -182,7 +194,12 @@ impl Options {
         let (rest, code) = be_u8().parse(input)?;
         let option = match Options::try_from(code) {
             Ok(value) => value,
-            Err(_) => return Err(ParserError::ErrorVerify.to_nom(input)),
+            // Unsupported options are skipped, the rest may still be useful
+            Err(_) => {
+                let (rest, length) = be_u8().parse(rest)?;
+                let (rest, _) = take(length).parse(rest)?;
+                return Ok((rest, OptionData::Unknown(code)));
+            },
         };
 
         // Just skipping or breaking
@@ -198,7 +215,12 @@ impl Options {
         let data = match option {
             Options::DomainName => {
                 let domain_name = String::from_utf8(content.to_vec())
-                    .map_err(|_| ParserError::ErrorVerify.to_nom(rest))?
+                    .map_err(|_| {
+                        ParserError::InvalidValue {
+                            field: "domain_name",
+                        }
+                        .to_nom(rest)
+                    })?
                     .trim_matches('\0')
                     .to_string();
 
@@ -207,7 +229,10 @@ impl Options {
 
             Options::DomainNameServer => {
                 if length % 4 != 0 {
-                    return Err(ParserError::ErrorVerify.to_nom(input));
+                    return Err(ParserError::InvalidValue {
+                        field: "option_length",
+                    }
+                    .to_nom(input));
                 }
                 let addresses = length / 4;
 
@@ -224,14 +249,21 @@ impl Options {
 
             Options::MessageType => {
                 let (_, message_type) = be_u8().parse(content)?;
-                let value = MessageType::try_from(message_type)
-                    .map_err(|_| ParserError::ErrorVerify.to_nom(rest))?;
+                let value = MessageType::try_from(message_type).map_err(|_| {
+                    ParserError::InvalidValue {
+                        field: "message_type",
+                    }
+                    .to_nom(rest)
+                })?;
                 OptionData::MessageType(value)
             },
 
             Options::RouterOption => {
                 if length % 4 != 0 {
-                    return Err(ParserError::ErrorVerify.to_nom(input));
+                    return Err(ParserError::InvalidValue {
+                        field: "option_length",
+                    }
+                    .to_nom(input));
                 }
                 let addresses = length / 4;
 
@@ -246,6 +278,11 @@ impl Options {
                 OptionData::RouterOption(ips)
             },
 
+            // Order of the requested options is the fingerprint of the client's OS
+            Options::ParameterRequestList => {
+                OptionData::ParameterRequestList(content.to_vec())
+            },
+
             Options::ServerIdentifier => {
                 let (_, ip) = ip::address::v4_parse(content)?;
                 OptionData::ServerIdentifier(ip)
@@ -256,8 +293,19 @@ impl Options {
                 OptionData::SubnetMask(mask)
             },
 
+            Options::VendorClassIdentifier => {
+                let vendor_class = String::from_utf8_lossy(content)
+                    .trim_matches('\0')
+                    .to_string();
+
+                OptionData::VendorClassIdentifier(vendor_class)
+            },
+
             Options::Pad | Options::End => {
-                return Err(ParserError::ErrorVerify.to_nom(input));
+                return Err(ParserError::ConstraintViolation {
+                    description: "option without the length is parsed as the full one",
+                }
+                .to_nom(input));
             },
         };
 
@@ -273,9 +321,12 @@ pub enum OptionData {
     DomainName(String),
     DomainNameServer(Vec<Ipv4Addr>),
     MessageType(MessageType),
+    ParameterRequestList(Vec<u8>),
     RouterOption(Vec<Ipv4Addr>),
     ServerIdentifier(Ipv4Addr),
     SubnetMask(Ipv4Addr),
+    VendorClassIdentifier(String),
+    Unknown(u8),
 }
 
 #[derive(Clone, Debug, Display, Serialize, Deserialize, PartialEq, TryFromPrimitive)]
@@ -300,16 +351,36 @@ pub enum MessageType {
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct DHCPv4Dto {
+    #[serde(rename = "op")]
     pub message_type: OperationType,
+    #[serde(rename = "ciaddr")]
     pub old_client_address: Ipv4Addr,
+    #[serde(rename = "yiaddr")]
     pub new_client_address: Ipv4Addr,
+    #[serde(rename = "siaddr")]
     pub server_address: Ipv4Addr,
+    #[serde(rename = "giaddr")]
     pub relay_agent_address: Ipv4Addr,
+    #[serde(rename = "chaddr")]
     pub hardware_address_client: MacAddress,
+    #[serde(rename = "vc", default, skip_serializing_if = "Option::is_none")]
+    pub vendor_class: Option<String>,
+    #[serde(rename = "prl", default, skip_serializing_if = "Vec::is_empty")]
+    pub parameter_request_list: Vec<u8>,
 }
 
 impl From<DHCPv4> for DHCPv4Dto {
     fn from(value: DHCPv4) -> Self {
+        let mut vendor_class = None;
+        let mut parameter_request_list = vec![];
+        for option in value.options {
+            match option {
+                OptionData::VendorClassIdentifier(value) => vendor_class = Some(value),
+                OptionData::ParameterRequestList(value) => parameter_request_list = value,
+                _ => {},
+            }
+        }
+
         Self {
             message_type: value.message_type,
             old_client_address: value.old_client_address,
@@ -317,14 +388,48 @@ impl From<DHCPv4> for DHCPv4Dto {
             server_address: value.server_address,
             relay_agent_address: value.relay_agent_address,
             hardware_address_client: value.hardware_address_client,
+            vendor_class,
+            parameter_request_list,
         }
     }
 }
 
-#[cfg(test)]
+impl FieldIter for DHCPv4Dto {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let Self {
+            message_type,
+            old_client_address,
+            new_client_address,
+            server_address,
+            relay_agent_address,
+            hardware_address_client,
+            vendor_class,
+            parameter_request_list,
+        } = self;
+
+        vec![
+            ("message_type", message_type.to_string()),
+            ("old_client_address", old_client_address.to_string()),
+            ("new_client_address", new_client_address.to_string()),
+            ("server_address", server_address.to_string()),
+            ("relay_agent_address", relay_agent_address.to_string()),
+            (
+                "hardware_address_client",
+                hardware_address_client.to_string(),
+            ),
+            ("vendor_class", fields::optional(vendor_class)),
+            (
+                "parameter_request_list",
+                fields::list(parameter_request_list),
+            ),
+        ]
+    }
+}
+
+#[cfg(all(test, feature = "ipv4", feature = "udp"))]
 mod tests {
     use super::*;
-    use crate::dto::frame::FrameHeader;
+    use crate::dto::frame::{FrameHeader, LinkType};
     use crate::parser::tests::FrameType;
     use crate::parser::tests::ProtocolParser;
     use crate::protocols::ProtocolData;
@@ -332,6 +437,7 @@ mod tests {
     use crate::protocols::ethernet::Ethernet;
     use crate::protocols::ethernet::ether_type::EtherType;
     use crate::protocols::ethernet::mac::MacAddress;
+    use crate::protocols::ip::checksum::ChecksumStatus;
     use crate::protocols::ip::protocol::IpNextLevelProtocol;
     use crate::protocols::ipv4::IPv4;
     use crate::protocols::udp::UDP;
@@ -341,19 +447,10 @@ mod tests {
     fn test_dhcp() {
         let hex_actual = "FF FF FF FF FF FF 00 19 B9 DA 15 A0 08 00 45 00 01 48 13 DE 00 00 80 11 F4 B0 AC 10 85 06 FF FF FF FF 00 43 00 44 01 34 38 ED 02 01 06 00 65 BB D3 BB 00 00 80 00 AC 10 85 27 00 00 00 00 00 00 00 00 00 00 00 00 D4 BE D9 28 21 33 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 63 82 53 63 35 01 05 36 04 AC 10 85 06 01 04 FF FF FF 00 0F 0B 6A 61 61 6C 61 6D 2E 6E 65 74 00 03 04 AC 10 85 01 06 08 AC 10 85 06 AC 10 80 CA FF 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00".replace(" ", "");
         let frame = hex::decode(hex_actual).unwrap();
-        let header = FrameHeader {
-            tv_sec: 0,
-            tv_usec: 0,
-            caplen: 342,
-            len: 0,
-        };
+        let header = FrameHeader::new(342);
 
-        let parser = ProtocolParser::new(&pcap::Linktype(1), false);
-        let packet = pcap::Packet {
-            header: &pcap::PacketHeader::from(&header),
-            data: &frame,
-        };
-        let result = parser.process(packet);
+        let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+        let result = parser.process_bytes(&header, &frame);
         let metadata = match result {
             Some(value) => match value {
                 FrameType::Metadata(value) => value,
@@ -392,8 +489,10 @@ mod tests {
             time_to_live: 128,
             protocol_inner: IpNextLevelProtocol::UDP,
             checksum: 0xf4b0,
+            checksum_status: ChecksumStatus::NotChecked,
             address_source: Ipv4Addr::from_str("172.16.133.6").unwrap(),
             address_destination: Ipv4Addr::from_str("255.255.255.255").unwrap(),
+            options: vec![],
         };
 
         assert_eq!(actual_ipv4, expected_ipv4);
@@ -407,7 +506,9 @@ mod tests {
             port_source: 67,
             port_destination: 68,
             length: 308,
+            length_mismatch: None,
             checksum: 0x38ed,
+            checksum_status: ChecksumStatus::NotChecked,
         };
 
         assert_eq!(actual_udp, expected_udp);
@@ -449,4 +550,42 @@ mod tests {
 
         assert_eq!(actual_dhcp, expected_dhcp);
     }
+
+    #[test]
+    fn test_dhcp_client_options() {
+        // Discover: Client Identifier & Host Name are skipped, Pad before Vendor Class
+        let hex_actual = format!(
+            "FF FF FF FF FF FF 3C 5A B4 1E 2F 01 08 00 45 00 01 43 00 01 00 00 40 11 00 00 00 00 00 00 FF FF FF FF 00 44 00 43 01 2F 00 00 01 01 06 00 1A 2B 3C 4D 00 00 80 00 {}3C 5A B4 1E 2F 01 {}63 82 53 63 35 01 01 3D 07 01 3C 5A B4 1E 2F 01 0C 09 61 6E 64 72 6F 69 64 2D 31 00 3C 0F 61 6E 64 72 6F 69 64 2D 64 68 63 70 2D 31 31 37 0B 01 03 06 0F 1A 1C 33 3A 3B 2B 72 FF",
+            "00 ".repeat(16),
+            "00 ".repeat(202)
+        )
+        .replace(" ", "");
+        let frame = hex::decode(hex_actual).unwrap();
+        let header = FrameHeader::new(337);
+
+        let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+        let metadata = match parser.process_bytes(&header, &frame) {
+            Some(FrameType::Metadata(value)) => value,
+            _ => panic!(),
+        };
+
+        let actual_dhcp = match metadata.layers[3].clone() {
+            ProtocolData::DHCPv4(value) => value,
+            _ => panic!(),
+        };
+        assert_eq!(
+            actual_dhcp.options,
+            vec![
+                OptionData::MessageType(MessageType::Discover),
+                OptionData::VendorClassIdentifier("android-dhcp-11".to_string()),
+                OptionData::ParameterRequestList(vec![
+                    1, 3, 6, 15, 26, 28, 51, 58, 59, 43, 114
+                ]),
+            ]
+        );
+
+        let dto = DHCPv4Dto::from(actual_dhcp);
+        assert_eq!(dto.vendor_class.as_deref(), Some("android-dhcp-11"));
+        assert_eq!(dto.parameter_request_list.len(), 11);
+    }
 }
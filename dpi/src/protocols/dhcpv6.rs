@@ -1,7 +1,8 @@
+use crate::dto::fields;
+use crate::dto::fields::FieldIter;
 use crate::parser;
-use crate::parser::ParserError;
+use crate::parser::{IResult, ParserError};
 use crate::protocols::{ProtocolData, ip};
-use nom::IResult;
 use nom::bytes::take;
 use nom::number::{be_u8, be_u16, be_u32};
 use nom::{Finish, Parser};
@@ -17,13 +18,22 @@ pub const TRANSACTION_ID_LENGTH_BYTES: usize = 3;
 pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
     // Message Type, 1 byte.
     let (rest, message_type) = be_u8().parse(bytes)?;
-    let message_type = MessageType::try_from(message_type)
-        .map_err(|_| ParserError::ErrorVerify.to_nom(bytes))?;
+    let message_type = MessageType::try_from(message_type).map_err(|_| {
+        ParserError::InvalidValue {
+            field: "message_type",
+        }
+        .to_nom(bytes)
+    })?;
 
     // Transaction ID. 3 bytes
     let (rest, transaction_id) = take(TRANSACTION_ID_LENGTH_BYTES).parse(rest)?;
     let transaction_id = <&[u8; TRANSACTION_ID_LENGTH_BYTES]>::try_from(transaction_id)
-        .map_err(|_| ParserError::ErrorVerify.to_nom(bytes))?;
+        .map_err(|_| {
+        ParserError::InvalidValue {
+            field: "transaction_id",
+        }
+        .to_nom(bytes)
+    })?;
     let transaction_id = ((transaction_id[0] as u32) << 16)
         | ((transaction_id[1] as u32) << 8)
         | (transaction_id[2] as u32);
@@ -63,7 +73,7 @@ pub struct DHCPv6 {
     pub options: Vec<OptionData>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, TryFromPrimitive)]
+#[derive(Clone, Debug, Display, Serialize, Deserialize, PartialEq, TryFromPrimitive)]
 #[repr(u16)]
 pub enum Options {
     ClientIdentifier = 1,
@@ -84,8 +94,12 @@ pub enum Options {
 impl Options {
     pub fn parse(input: &[u8]) -> IResult<&[u8], OptionData> {
         let (rest, code) = be_u16().parse(input)?;
-        let option_variant =
-            Options::try_from(code).map_err(|_| ParserError::ErrorVerify.to_nom(rest))?;
+        let option_variant = Options::try_from(code).map_err(|_| {
+            ParserError::InvalidValue {
+                field: "option_code",
+            }
+            .to_nom(rest)
+        })?;
 
         let (rest, length) = be_u16().parse(rest)?;
         let (rest, content) = take(length).parse(rest)?;
@@ -114,8 +128,12 @@ impl Options {
                 let mut rest_buffer = content;
                 for _ in 0..length / 2 {
                     let (rest, option) = be_u16().parse(rest_buffer)?;
-                    let option = Options::try_from(option)
-                        .map_err(|_| ParserError::ErrorVerify.to_nom(input))?;
+                    let option = Options::try_from(option).map_err(|_| {
+                        ParserError::InvalidValue {
+                            field: "requested_option",
+                        }
+                        .to_nom(input)
+                    })?;
                     requested_options.push(option);
                     rest_buffer = rest;
                 }
@@ -139,7 +157,10 @@ impl Options {
 
             Options::DnsRecursiveNameServer => {
                 if length % 16 != 0 {
-                    return Err(ParserError::ErrorVerify.to_nom(rest));
+                    return Err(ParserError::InvalidValue {
+                        field: "option_length",
+                    }
+                    .to_nom(rest));
                 }
 
                 let mut addresses: Vec<Ipv6Addr> = Vec::new();
@@ -194,19 +215,126 @@ pub enum OptionData {
     },
 }
 
+impl OptionData {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ClientIdentifier(_) => "Client Identifier",
+            Self::ServerIdentifier(_) => "Server Identifier",
+            Self::IANA { .. } => "IA_NA",
+            Self::ElapsedTime(_) => "Elapsed Time",
+            Self::OptionRequest(_) => "Option Request",
+            Self::VendorData { .. } => "Vendor Data",
+            Self::DnsRecursiveServers(_) => "DNS Servers",
+            Self::DomainSearchList(_) => "Domain Search List",
+            Self::ClientFQDN { .. } => "Client FQDN",
+        }
+    }
+
+    // Opaque data (DUIDs, vendor data) is shown as hex
+    pub fn value(&self) -> String {
+        match self {
+            Self::ClientIdentifier(duid) | Self::ServerIdentifier(duid) => {
+                hex::encode(duid)
+            },
+            Self::IANA {
+                id,
+                client_server_contact_seconds,
+                client_any_server_contact_seconds,
+                options,
+            } => {
+                let mut value = format!(
+                    "IAID {id:#010x}, T1 {client_server_contact_seconds} s, \
+                     T2 {client_any_server_contact_seconds} s"
+                );
+                if !options.is_empty() {
+                    value.push_str(&format!(", {}", hex::encode(options)));
+                }
+                value
+            },
+            // Hundredths of a second
+            Self::ElapsedTime(time) => format!("{}.{:02} s", time / 100, time % 100),
+            Self::OptionRequest(options) => fields::list(options),
+            Self::VendorData {
+                enterprise_number,
+                vendor_class_data,
+            } => format!("{enterprise_number}: {}", hex::encode(vendor_class_data)),
+            Self::DnsRecursiveServers(addresses) => fields::list(addresses),
+            Self::DomainSearchList(bytes) => match domain_names(bytes) {
+                Some(names) => fields::list(&names),
+                None => hex::encode(bytes),
+            },
+            Self::ClientFQDN { flags, domain_name } => match flags {
+                0 => domain_name.clone(),
+                _ => format!("{domain_name} (flags {flags:#04x})"),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for OptionData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.name(), self.value())
+    }
+}
+
+// RFC 1035, 3.1: Sequence of the uncompressed names. None, if the list is malformed
+fn domain_names(bytes: &[u8]) -> Option<Vec<String>> {
+    let mut names = Vec::new();
+    let mut labels = Vec::new();
+    let mut rest = bytes;
+    while let Some((&length, tail)) = rest.split_first() {
+        if length == 0 {
+            names.push(labels.join("."));
+            labels.clear();
+            rest = tail;
+            continue;
+        }
+
+        let length = usize::from(length);
+        let label = tail.get(..length)?;
+        labels.push(String::from_utf8_lossy(label).into_owned());
+        rest = tail.get(length..)?;
+    }
+
+    labels.is_empty().then_some(names)
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct DHCPv6Dto {
+    #[serde(rename = "mt")]
     pub message_type: MessageType,
+    #[serde(rename = "ti", default)]
+    pub transaction_id: u32,
+    #[serde(rename = "op", default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<OptionData>,
 }
 
 impl From<DHCPv6> for DHCPv6Dto {
     fn from(value: DHCPv6) -> Self {
         Self {
             message_type: value.message_type,
+            transaction_id: value.transaction_id,
+            options: value.options,
         }
     }
 }
 
+impl FieldIter for DHCPv6Dto {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let Self {
+            message_type,
+            transaction_id,
+            options,
+        } = self;
+
+        vec![
+            ("message_type", message_type.to_string()),
+            ("transaction_id", format!("{transaction_id:#08x}")),
+            ("options", fields::list(options)),
+        ]
+    }
+}
+
 #[derive(Clone, Debug, Display, Serialize, Deserialize, PartialEq, TryFromPrimitive)]
 #[repr(u8)]
 pub enum MessageType {
@@ -235,40 +363,68 @@ pub enum MessageType {
     StartTLS = 23,
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "ethernet", feature = "ipv6", feature = "udp"))]
 mod tests {
-    use crate::dto::frame::FrameHeader;
+    use crate::dto::frame::{FrameHeader, LinkType};
     use crate::parser::tests::FrameType;
     use crate::parser::tests::ProtocolParser;
     use crate::protocols::ProtocolData;
     use crate::protocols::dhcpv6::OptionData::{ClientFQDN, VendorData};
-    use crate::protocols::dhcpv6::{DHCPv6, MessageType, OptionData, Options};
+    use crate::protocols::dhcpv6::{DHCPv6, DHCPv6Dto, MessageType, OptionData, Options};
     use crate::protocols::ethernet::Ethernet;
     use crate::protocols::ethernet::ether_type::EtherType;
     use crate::protocols::ethernet::mac::MacAddress;
+    use crate::protocols::ip::checksum::ChecksumStatus;
     use crate::protocols::ip::protocol::IpNextLevelProtocol;
     use crate::protocols::ipv6::IPv6;
     use crate::protocols::udp::UDP;
     use std::net::Ipv6Addr;
     use std::str::FromStr;
 
+    fn solicit() -> DHCPv6 {
+        DHCPv6 {
+            message_type: MessageType::Solicit,
+            transaction_id: 0x763113,
+            options: vec![
+                OptionData::ElapsedTime(300),
+                OptionData::ClientIdentifier(vec![
+                    0x00, 0x01, 0x00, 0x01, 0x16, 0x2b, 0x8c, 0xfe, 0x00, 0x21, 0x70,
+                    0x63, 0x3a, 0xe9,
+                ]),
+                OptionData::IANA {
+                    id: 0x177ce9d3,
+                    client_server_contact_seconds: 0,
+                    client_any_server_contact_seconds: 0,
+                    options: vec![],
+                },
+                ClientFQDN {
+                    flags: 0,
+                    domain_name: "JDT133.jaalam.net".to_string(),
+                },
+                VendorData {
+                    enterprise_number: 311,
+                    vendor_class_data: vec![
+                        0x00, 0x08, 0x4D, 0x53, 0x46, 0x54, 0x20, 0x35, 0x2E, 0x30,
+                    ],
+                },
+                OptionData::OptionRequest(vec![
+                    Options::DomainSearchList,
+                    Options::DnsRecursiveNameServer,
+                    Options::VendorSpecific,
+                    Options::ClientFQDN,
+                ]),
+            ],
+        }
+    }
+
     #[test]
     fn test_dhcpv6() {
         let hex_actual = "33 33 00 01 00 02 7C E9 D3 7C D3 9B 86 DD 60 00 00 00 00 6A 11 01 FE 80 00 00 00 00 00 00 B5 6E 75 8F D6 E2 B7 9E FF 02 00 00 00 00 00 00 00 00 00 00 00 01 00 02 02 22 02 23 00 6A E6 A9 01 76 31 13 00 08 00 02 01 2C 00 01 00 0E 00 01 00 01 16 2B 8C FE 00 21 70 63 3A E9 00 03 00 0C 17 7C E9 D3 00 00 00 00 00 00 00 00 00 27 00 14 00 06 4A 44 54 31 33 33 06 6A 61 61 6C 61 6D 03 6E 65 74 00 00 10 00 0E 00 00 01 37 00 08 4D 53 46 54 20 35 2E 30 00 06 00 08 00 18 00 17 00 11 00 27".replace(" ", "");
         let frame = hex::decode(hex_actual).unwrap();
-        let header = FrameHeader {
-            tv_sec: 0,
-            tv_usec: 0,
-            caplen: 160,
-            len: 0,
-        };
+        let header = FrameHeader::new(160);
 
-        let parser = ProtocolParser::new(&pcap::Linktype(1), false);
-        let packet = pcap::Packet {
-            header: &pcap::PacketHeader::from(&header),
-            data: &frame,
-        };
-        let result = parser.process(packet);
+        let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+        let result = parser.process_bytes(&header, &frame);
         let metadata = match result {
             Some(value) => match value {
                 FrameType::Metadata(value) => value,
@@ -304,6 +460,8 @@ mod tests {
             hop_limit: 1,
             address_source: Ipv6Addr::from_str("fe80::b56e:758f:d6e2:b79e").unwrap(),
             address_destination: Ipv6Addr::from_str("ff02::1:2").unwrap(),
+            extension_headers: vec![],
+            protocol_inner: IpNextLevelProtocol::UDP,
         };
 
         assert_eq!(actual_ipv6, expected_ipv6);
@@ -317,7 +475,9 @@ mod tests {
             port_source: 546,
             port_destination: 547,
             length: 106,
+            length_mismatch: None,
             checksum: 0xe6a9,
+            checksum_status: ChecksumStatus::NotChecked,
         };
 
         assert_eq!(actual_udp, expected_udp);
@@ -327,40 +487,38 @@ mod tests {
             _ => panic!(),
         };
 
-        let expected_dhcp = DHCPv6 {
-            message_type: MessageType::Solicit,
-            transaction_id: 0x763113,
-            options: vec![
-                OptionData::ElapsedTime(300),
-                OptionData::ClientIdentifier(vec![
-                    0x00, 0x01, 0x00, 0x01, 0x16, 0x2b, 0x8c, 0xfe, 0x00, 0x21, 0x70,
-                    0x63, 0x3a, 0xe9,
-                ]),
-                OptionData::IANA {
-                    id: 0x177ce9d3,
-                    client_server_contact_seconds: 0,
-                    client_any_server_contact_seconds: 0,
-                    options: vec![],
-                },
-                ClientFQDN {
-                    flags: 0,
-                    domain_name: "JDT133.jaalam.net".to_string(),
-                },
-                VendorData {
-                    enterprise_number: 311,
-                    vendor_class_data: vec![
-                        0x00, 0x08, 0x4D, 0x53, 0x46, 0x54, 0x20, 0x35, 0x2E, 0x30,
-                    ],
-                },
-                OptionData::OptionRequest(vec![
-                    Options::DomainSearchList,
-                    Options::DnsRecursiveNameServer,
-                    Options::VendorSpecific,
-                    Options::ClientFQDN,
-                ]),
-            ],
-        };
+        assert_eq!(actual_dhcp, solicit());
+    }
 
-        assert_eq!(actual_dhcp, expected_dhcp);
+    #[test]
+    fn test_options_rendering() {
+        let dto = DHCPv6Dto::from(solicit());
+        let rendered = dto
+            .options
+            .iter()
+            .map(|option| (option.name(), option.value()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rendered,
+            vec![
+                ("Elapsed Time", "3.00 s".to_string()),
+                ("Client Identifier", "00010001162b8cfe002170633ae9".to_string()),
+                ("IA_NA", "IAID 0x177ce9d3, T1 0 s, T2 0 s".to_string()),
+                ("Client FQDN", "JDT133.jaalam.net".to_string()),
+                ("Vendor Data", "311: 00084d53465420352e30".to_string()),
+                (
+                    "Option Request",
+                    "DomainSearchList, DnsRecursiveNameServer, VendorSpecific, ClientFQDN"
+                        .to_string()
+                ),
+            ]
+        );
+        assert_eq!(format!("{:#08x}", dto.transaction_id), "0x763113");
+
+        let search_list =
+            OptionData::DomainSearchList(b"\x07example\x03com\x00\x03lan\x00".to_vec());
+        assert_eq!(search_list.value(), "example.com, lan");
+        let malformed = OptionData::DomainSearchList(vec![0x07, 0x65]);
+        assert_eq!(malformed.value(), "0765");
     }
 }
@@ -1,7 +1,8 @@
+use crate::dto::fields::{self, FieldIter};
+use crate::dto::wire;
 use crate::parser;
-use crate::parser::ParserError;
+use crate::parser::{IResult, ParseFailure, ParserError};
 use crate::protocols::ProtocolData;
-use nom::IResult;
 use nom::bytes::take;
 use nom::number::{be_u8, be_u16, be_u32};
 use nom::{Finish, Parser, bits};
@@ -21,6 +22,10 @@ pub const RECURSION_DESIRED_LENGTH_BITS: usize = 1;
 pub const RECURSION_AVAILABLE_LENGTH_BITS: usize = 1;
 pub const RESERVED_LENGTH_BITS: usize = 3;
 pub const RESPONSE_CODE_LENGTH_BITS: usize = 4;
+// mDNS uses the top bit of the class as unicast-response (questions) & cache-flush (records) flag.
+// RFC 6762: https://datatracker.ietf.org/doc/html/rfc6762#section-18.12
+pub const CLASS_MASK: u16 = 0x7FFF;
+pub const HEADER_LENGTH: usize = 12;
 pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
     // HEADER
     // Identifier - 16 bits.
@@ -31,7 +36,7 @@ pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
     // Recursion Available (RA), Reserved (Z), Response Code (RCODE)
     type DnsHeaderBits = (u8, u8, u8, u8, u8, u8, u8, u8);
     let (rest, (qr, opcode, aa, tc, rd, ra, z, rcode)): (&[u8], DnsHeaderBits) =
-        bits::bits::<_, _, nom::error::Error<_>, _, _>((
+        bits::bits::<_, _, ParseFailure<_>, _, _>((
             bits::complete::take(MESSAGE_TYPE_LENGTH_BITS),
             bits::complete::take(OPERATION_CODE_LENGTH_BITS),
             bits::complete::take(AUTHORITATIVE_ANSWER_LENGTH_BITS),
@@ -41,10 +46,10 @@ pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
             bits::complete::take(RESERVED_LENGTH_BITS),
             bits::complete::take(RESPONSE_CODE_LENGTH_BITS),
         ))(rest)?;
-    let message_type =
-        MessageType::try_from(qr).map_err(|_| ParserError::ErrorVerify.to_nom(bytes))?;
+    let message_type = MessageType::try_from(qr)
+        .map_err(|_| ParserError::InvalidValue { field: "qr" }.to_nom(bytes))?;
     let operation_code = OperationCode::try_from(opcode)
-        .map_err(|_| ParserError::ErrorVerify.to_nom(bytes))?;
+        .map_err(|_| ParserError::InvalidValue { field: "opcode" }.to_nom(bytes))?;
     let authoritative_answer =
         parser::cast_to_bool(aa).map_err(|err| err.to_nom(bytes))?;
     let truncation = parser::cast_to_bool(tc).map_err(|err| err.to_nom(bytes))?;
@@ -52,10 +57,13 @@ pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
     let recursion_available =
         parser::cast_to_bool(ra).map_err(|err| err.to_nom(bytes))?;
     if z != 0 {
-        return Err(ParserError::ErrorVerify.to_nom(bytes));
+        return Err(ParserError::ConstraintViolation {
+            description: "reserved bits are set",
+        }
+        .to_nom(bytes));
     }
     let response_code = ResponseCode::try_from(rcode)
-        .map_err(|_| ParserError::ErrorVerify.to_nom(bytes))?;
+        .map_err(|_| ParserError::InvalidValue { field: "rcode" }.to_nom(bytes))?;
 
     // QDCOUNT - 16 bits
     let (rest, question_entries) = be_u16().parse(rest)?;
@@ -67,7 +75,7 @@ pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
     let (rest, authority_records) = be_u16().parse(rest)?;
 
     // ARCOUNT - 16 bits
-    let (mut rest, additional_records) = be_u16().parse(rest)?;
+    let (rest, additional_records) = be_u16().parse(rest)?;
 
     let header = Header {
         id,
@@ -85,29 +93,26 @@ pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
         additional_records,
     };
 
+    // Sections, that are cut off by the end of the bytes, are parsed as far as captured
+    let mut is_truncated = false;
+
     // QUESTION SECTION
-    let question_section: Vec<QuestionEntry> = match question_entries > 0 {
-        true => {
-            let mut vec: Vec<QuestionEntry> = vec![];
-            for _ in 0..question_entries {
-                let (section_rest, question) = parse_question_section(rest, bytes)?;
-                vec.push(question);
-                rest = section_rest;
-            }
-            vec
-        },
-        false => vec![],
-    };
+    let (rest, question_section) =
+        parse_section(rest, question_entries, &mut is_truncated, |input| {
+            parse_question_section(input, bytes)
+        })?;
 
     // ANSWER SECTION
-    let (rest, answer_section) = parse_record_section(rest, answer_records, bytes)?;
+    let (rest, answer_section) =
+        parse_record_section(rest, answer_records, bytes, &mut is_truncated)?;
 
     // AUTHORITY SECTION
-    let (rest, authority_section) = parse_record_section(rest, authority_records, bytes)?;
+    let (rest, authority_section) =
+        parse_record_section(rest, authority_records, bytes, &mut is_truncated)?;
 
     // ADDITIONAL SECTION
     let (rest, additional_section) =
-        parse_record_section(rest, additional_records, bytes)?;
+        parse_record_section(rest, additional_records, bytes, &mut is_truncated)?;
 
     let protocol = DNS {
         header,
@@ -115,10 +120,14 @@ pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
         answer_section,
         authority_section,
         additional_section,
+        is_truncated,
     };
 
     if !rest.is_empty() {
-        return Err(ParserError::ErrorVerify.to_nom(bytes));
+        return Err(ParserError::ConstraintViolation {
+            description: "bytes are left after the sections",
+        }
+        .to_nom(bytes));
     }
 
     Finish::finish(Ok((rest, ProtocolData::DNS(protocol))))
@@ -126,8 +135,102 @@ pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
 
 pub fn is_protocol_default(port_source: u16, port_destination: u16) -> bool {
     const PORT_DNS: u16 = 53;
+    // Multicast DNS shares the message format
+    const PORT_MDNS: u16 = 5353;
+
+    [PORT_DNS, PORT_MDNS]
+        .iter()
+        .any(|port| port_source == *port || port_destination == *port)
+}
+
+/// Header sanity: known codes, clear reserved bits, a few questions & bounded records.
+pub fn looks_like(payload: &[u8]) -> bool {
+    const MAX_QUESTIONS: u16 = 4;
+    const MAX_RECORDS: u16 = 64;
+
+    HeaderSummary::read(payload).is_some_and(|header| {
+        header.is_sane()
+            && ResponseCode::try_from(header.response_code)
+                .is_ok_and(|code| code != ResponseCode::Reserved)
+            && (1..=MAX_QUESTIONS).contains(&header.questions)
+            && header.records.iter().all(|count| *count <= MAX_RECORDS)
+    })
+}
+
+/// Checked before the parsing, on the standard ports too: the header is sane
+/// & the sections, that it announces, fit into the payload. <br>
+/// Truncated payload is checked only by the header.
+pub fn is_plausible(payload: &[u8], is_truncated: bool) -> bool {
+    HeaderSummary::read(payload).is_some_and(|header| {
+        header.is_sane()
+            && (is_truncated
+                || HEADER_LENGTH.saturating_add(header.sections_length())
+                    <= payload.len())
+    })
+}
+
+// Header fields, that are read without the parsing of the message
+struct HeaderSummary {
+    is_response: bool,
+    operation_code: u8,
+    reserved: u8,
+    response_code: u8,
+    questions: u16,
+    // ANCOUNT, NSCOUNT & ARCOUNT
+    records: [u16; 3],
+}
 
-    port_source == PORT_DNS || port_destination == PORT_DNS
+impl HeaderSummary {
+    // Smallest entries: root name, type & class. Records also have TTL & data length
+    const QUESTION_MIN_LENGTH: usize = 5;
+    const RECORD_MIN_LENGTH: usize = 11;
+
+    fn read(payload: &[u8]) -> Option<Self> {
+        let &[
+            _,
+            _,
+            flags_high,
+            flags_low,
+            qd_high,
+            qd_low,
+            an_high,
+            an_low,
+            ns_high,
+            ns_low,
+            ar_high,
+            ar_low,
+        ] = payload.first_chunk::<HEADER_LENGTH>()?;
+
+        // Flags, see the header parsing
+        Some(Self {
+            is_response: flags_high >> 7 == 1,
+            operation_code: (flags_high >> 3) & 0x0F,
+            reserved: (flags_low >> 4) & 0x07,
+            response_code: flags_low & 0x0F,
+            questions: u16::from_be_bytes([qd_high, qd_low]),
+            records: [
+                u16::from_be_bytes([an_high, an_low]),
+                u16::from_be_bytes([ns_high, ns_low]),
+                u16::from_be_bytes([ar_high, ar_low]),
+            ],
+        })
+    }
+
+    // Known operation, clear reserved bits & only responses carry the error
+    fn is_sane(&self) -> bool {
+        OperationCode::try_from(self.operation_code).is_ok()
+            && self.reserved == 0
+            && (self.is_response
+                || self.response_code == ResponseCode::NoErrorCondition as u8)
+    }
+
+    fn sections_length(&self) -> usize {
+        let records: usize = self.records.iter().map(|count| usize::from(*count)).sum();
+
+        usize::from(self.questions)
+            .saturating_mul(Self::QUESTION_MIN_LENGTH)
+            .saturating_add(records.saturating_mul(Self::RECORD_MIN_LENGTH))
+    }
 }
 
 fn parse_question_section<'a>(
@@ -138,13 +241,13 @@ fn parse_question_section<'a>(
 
     // QTYPE
     let (rest, qtype) = be_u16().parse(rest)?;
-    let qtype =
-        DnsType::try_from(qtype).map_err(|_| ParserError::ErrorVerify.to_nom(bytes))?;
+    let qtype = DnsType::try_from(qtype)
+        .map_err(|_| ParserError::InvalidValue { field: "qtype" }.to_nom(bytes))?;
 
     // QCLASS
     let (rest, qclass) = be_u16().parse(rest)?;
-    let qclass =
-        Class::try_from(qclass).map_err(|_| ParserError::ErrorVerify.to_nom(bytes))?;
+    let qclass = Class::try_from(qclass & CLASS_MASK)
+        .map_err(|_| ParserError::InvalidValue { field: "qclass" }.to_nom(bytes))?;
 
     let section = QuestionEntry {
         name: qname,
@@ -155,12 +258,15 @@ fn parse_question_section<'a>(
     Ok((rest, section))
 }
 
-fn parse_name<'a>(
+pub(crate) fn parse_name<'a>(
     bytes: &'a [u8], whole: &'a [u8], depth: u8,
 ) -> IResult<&'a [u8], String> {
     const MAX_DEPTH_LEVEL_RECURSION_NAME_PARSING: u8 = 7;
     if depth > MAX_DEPTH_LEVEL_RECURSION_NAME_PARSING {
-        return Err(ParserError::ErrorVerify.to_nom(bytes));
+        return Err(ParserError::ConstraintViolation {
+            description: "too many compression pointers in the name",
+        }
+        .to_nom(bytes));
     }
 
     let mut labels: Vec<String> = Vec::new();
@@ -178,8 +284,9 @@ fn parse_name<'a>(
         match is_simple_parsing {
             true => {
                 let (rest, word) = take(length_octet).parse(rest)?;
-                let word = String::from_utf8(word.to_vec())
-                    .map_err(|_| ParserError::ErrorVerify.to_nom(bytes))?;
+                let word = String::from_utf8(word.to_vec()).map_err(|_| {
+                    ParserError::InvalidValue { field: "label" }.to_nom(bytes)
+                })?;
                 labels.push(word);
                 main_rest = rest;
             },
@@ -190,15 +297,21 @@ fn parse_name<'a>(
                 let binary_original_octet = length_octet;
                 let low6 = (binary_original_octet & 0b0011_1111) as u16;
                 let combined = (low6 << 8) | (next_byte as u16);
-                let pointed_slice = whole
-                    .get(usize::from(combined)..)
-                    .ok_or(ParserError::ErrorVerify.to_nom(bytes))?;
+                let pointed_slice = whole.get(usize::from(combined)..).ok_or(
+                    ParserError::ConstraintViolation {
+                        description: "name pointer is out of the message",
+                    }
+                    .to_nom(bytes),
+                )?;
                 let (_, str) = parse_name(
                     pointed_slice,
                     whole,
-                    depth
-                        .checked_add(1)
-                        .ok_or(ParserError::ErrorVerify.to_nom(bytes))?,
+                    depth.checked_add(1).ok_or(
+                        ParserError::ConstraintViolation {
+                            description: "too many compression pointers in the name",
+                        }
+                        .to_nom(bytes),
+                    )?,
                 )?;
                 labels.push(str.to_string());
                 main_rest = rest;
@@ -212,23 +325,39 @@ fn parse_name<'a>(
 }
 
 fn parse_record_section<'a>(
-    bytes: &'a [u8], records: u16, whole: &'a [u8],
+    bytes: &'a [u8], records: u16, whole: &'a [u8], is_truncated: &mut bool,
 ) -> IResult<&'a [u8], Vec<ResourceRecord>> {
+    parse_section(bytes, records, is_truncated, |input| {
+        parse_resource_record(input, whole)
+    })
+}
+
+// Entry, that is cut off, ends the message: the following entries & sections are empty
+fn parse_section<'a, T>(
+    bytes: &'a [u8], entries: u16, is_truncated: &mut bool,
+    parse_entry: impl Fn(&'a [u8]) -> IResult<&'a [u8], T>,
+) -> IResult<&'a [u8], Vec<T>> {
     let mut rest = bytes;
-    let result = match records > 0 {
-        true => {
-            let mut vec: Vec<ResourceRecord> = vec![];
-            for _ in 0..records {
-                let (section_rest, record) = parse_resource_record(rest, whole)?;
-                vec.push(record);
-                rest = section_rest;
-            }
-            vec
-        },
-        false => vec![],
-    };
+    let mut vec: Vec<T> = vec![];
+    if *is_truncated {
+        return Ok((rest, vec));
+    }
 
-    Ok((rest, result))
+    for _ in 0..entries {
+        match parse_entry(rest) {
+            Ok((entry_rest, entry)) => {
+                vec.push(entry);
+                rest = entry_rest;
+            },
+            Err(nom::Err::Incomplete(_)) => {
+                *is_truncated = true;
+                return Ok((&[], vec));
+            },
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok((rest, vec))
 }
 
 fn parse_resource_record<'a>(
@@ -240,12 +369,19 @@ fn parse_resource_record<'a>(
     // TYPE
     let (rest, record_type) = be_u16().parse(rest)?;
     let record_type = DnsType::try_from(record_type)
-        .map_err(|_| ParserError::ErrorVerify.to_nom(bytes))?;
-
-    // CLASS
-    let (rest, class) = be_u16().parse(rest)?;
-    let class =
-        Class::try_from(class).map_err(|_| ParserError::ErrorVerify.to_nom(bytes))?;
+        .map_err(|_| ParserError::InvalidValue { field: "type" }.to_nom(bytes))?;
+
+    // CLASS. OPT pseudo-record keeps the UDP payload size there
+    let (rest, raw_class) = be_u16().parse(rest)?;
+    let class = match record_type {
+        DnsType::OPT => None,
+        _ => {
+            let class = Class::try_from(raw_class & CLASS_MASK).map_err(|_| {
+                ParserError::InvalidValue { field: "class" }.to_nom(bytes)
+            })?;
+            Some(class)
+        },
+    };
 
     // TTL
     let (rest, time_to_live) = be_u32().parse(rest)?;
@@ -255,7 +391,10 @@ fn parse_resource_record<'a>(
 
     // RDATA
     let (rest, data) = take(data_length).parse(rest)?;
-    let (_, data) = DnsTypeData::try_from_bytes(data, whole, &record_type)?;
+    let (_, data) = match record_type {
+        DnsType::OPT => DnsTypeData::parse_opt(data, raw_class, time_to_live)?,
+        _ => DnsTypeData::try_from_bytes(data, whole, &record_type)?,
+    };
 
     let record = ResourceRecord {
         name,
@@ -276,6 +415,8 @@ pub struct DNS {
     pub answer_section: Vec<ResourceRecord>,
     pub authority_section: Vec<ResourceRecord>,
     pub additional_section: Vec<ResourceRecord>,
+    // Some entries are cut off by the end of the captured bytes
+    pub is_truncated: bool,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -297,21 +438,43 @@ pub struct Header {
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct QuestionEntry {
+    #[serde(rename = "n")]
     pub name: String,
+    #[serde(rename = "t")]
     pub entry_type: DnsType,
+    #[serde(rename = "c")]
     pub class: Class,
 }
 
+impl std::fmt::Display for QuestionEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} ({})", self.name, self.entry_type)
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct ResourceRecord {
+    #[serde(rename = "n")]
     pub name: String,
+    #[serde(rename = "t")]
     pub record_type: DnsType,
-    pub class: Class,
+    // Not set for the OPT pseudo-record
+    #[serde(rename = "c", default, skip_serializing_if = "Option::is_none")]
+    pub class: Option<Class>,
+    #[serde(rename = "ttl")]
     pub time_to_live: u32,
+    #[serde(rename = "len")]
     pub data_length: u16,
+    #[serde(rename = "d")]
     pub data: DnsTypeData,
 }
 
+impl std::fmt::Display for ResourceRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}: {}", self.name, self.data)
+    }
+}
+
 #[derive(Clone, Debug, Display, Serialize, Deserialize, PartialEq, TryFromPrimitive)]
 #[repr(u8)]
 pub enum MessageType {
@@ -325,9 +488,11 @@ pub enum OperationCode {
     StandardQuery = 0,
     InverseQuery = 1,
     ServerStatusRequest = 2,
-
-    #[num_enum(alternatives = [3..15])]
-    Reserved = 15,
+    // Unassigned values are rejected
+    // RFC 6895: https://datatracker.ietf.org/doc/html/rfc6895#section-2.2
+    Notify = 4,
+    Update = 5,
+    StatefulOperations = 6,
 }
 
 #[derive(Clone, Debug, Display, Serialize, Deserialize, PartialEq, TryFromPrimitive)]
@@ -374,6 +539,7 @@ pub enum DnsType {
     KX = 36,         // 	Key Exchanger record
     CERT = 37,       // Certificate record
     DNAME = 39,      // 	Delegation name record
+    OPT = 41,        // EDNS0 pseudo-record
     APL = 42,        // Address Prefix List
     DS = 43,         // Delegation signer
     SSHFP = 44,      // SSH Public Key Fingerprint
@@ -427,9 +593,23 @@ pub enum DnsTypeData {
         expire_limit: u32,
         minimum_ttl: u32,
     },
+    // EDNS0. Extended RCODE is the upper 8 bits of the 12-bit response code
+    Opt {
+        payload_size: u16,
+        extended_rcode: u8,
+        version: u8,
+        dnssec_ok: bool,
+        options: Vec<EdnsOption>,
+    },
     Unknown,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct EdnsOption {
+    pub code: u16,
+    pub data: Vec<u8>,
+}
+
 impl std::fmt::Display for DnsTypeData {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let text = match self {
@@ -443,6 +623,15 @@ impl std::fmt::Display for DnsTypeData {
                 mailbox,
                 ..
             } => format!("{primary_name_server} <{mailbox}>"),
+            DnsTypeData::Opt {
+                payload_size,
+                version,
+                dnssec_ok,
+                ..
+            } => match dnssec_ok {
+                true => format!("EDNS{version}, {payload_size} B, DO"),
+                false => format!("EDNS{version}, {payload_size} B"),
+            },
             DnsTypeData::Unknown => "Unknown".to_string(),
         };
 
@@ -457,27 +646,30 @@ impl DnsTypeData {
         match dns_type {
             DnsType::A => match input.len() {
                 4 => {
-                    let address = <[u8; 4]>::try_from(input)
-                        .map_err(|_| ParserError::ErrorVerify.to_nom(input))?;
+                    let address = <[u8; 4]>::try_from(input).map_err(|_| {
+                        ParserError::InvalidValue { field: "rdata" }.to_nom(input)
+                    })?;
                     let address = Ipv4Addr::from(address);
                     Ok((&[], Self::AIPv4(address)))
                 },
                 16 => {
-                    let address = <[u8; 16]>::try_from(input)
-                        .map_err(|_| ParserError::ErrorVerify.to_nom(input))?;
+                    let address = <[u8; 16]>::try_from(input).map_err(|_| {
+                        ParserError::InvalidValue { field: "rdata" }.to_nom(input)
+                    })?;
                     let address = Ipv6Addr::from(address);
                     Ok((&[], Self::AIPv6(address)))
                 },
-                _ => Err(ParserError::ErrorVerify.to_nom(input)),
+                _ => Err(ParserError::InvalidValue { field: "rdlength" }.to_nom(input)),
             },
             DnsType::AAAA => match input.len() {
                 16 => {
-                    let address = <[u8; 16]>::try_from(input)
-                        .map_err(|_| ParserError::ErrorVerify.to_nom(input))?;
+                    let address = <[u8; 16]>::try_from(input).map_err(|_| {
+                        ParserError::InvalidValue { field: "rdata" }.to_nom(input)
+                    })?;
                     let address = Ipv6Addr::from(address);
                     Ok((&[], Self::AAAA(address)))
                 },
-                _ => Err(ParserError::ErrorVerify.to_nom(input)),
+                _ => Err(ParserError::InvalidValue { field: "rdlength" }.to_nom(input)),
             },
             DnsType::CNAME => {
                 let (rest, cname) = parse_name(input, whole, 1)?;
@@ -514,6 +706,45 @@ impl DnsTypeData {
             _ => Ok((&[], Self::Unknown)),
         }
     }
+
+    /// Class & TTL of the OPT pseudo-record are the EDNS0 fields, not the usual ones.
+    /// RFC 6891: https://datatracker.ietf.org/doc/html/rfc6891#section-6.1.3
+    pub fn parse_opt(input: &[u8], class: u16, ttl: u32) -> IResult<&[u8], Self> {
+        const DNSSEC_OK_MASK: u32 = 0x8000;
+        let [extended_rcode, version, _, _] = ttl.to_be_bytes();
+
+        // Options are bounded by RDLENGTH, so the missing bytes are malformed ones
+        let invalid = |_| ParserError::InvalidValue { field: "option" }.to_nom(input);
+        let mut options = vec![];
+        let mut rest = input;
+        while !rest.is_empty() {
+            let (next, code) = be_u16::<_, ParseFailure<_>>()
+                .parse(rest)
+                .map_err(invalid)?;
+            let (next, length) = be_u16::<_, ParseFailure<_>>()
+                .parse(next)
+                .map_err(invalid)?;
+            let (next, data) = take::<_, _, ParseFailure<_>>(length)
+                .parse(next)
+                .map_err(invalid)?;
+            options.push(EdnsOption {
+                code,
+                data: data.to_vec(),
+            });
+            rest = next;
+        }
+
+        Ok((
+            rest,
+            Self::Opt {
+                payload_size: class,
+                extended_rcode,
+                version,
+                dnssec_ok: ttl & DNSSEC_OK_MASK != 0,
+                options,
+            },
+        ))
+    }
 }
 
 #[derive(Clone, Debug, Display, Serialize, Deserialize, PartialEq, TryFromPrimitive)]
@@ -529,17 +760,21 @@ pub enum Class {
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct DnsDto {
+    #[serde(rename = "qr")]
     pub message_type: MessageType,
+    #[serde(rename = "op")]
     pub operation_code: OperationCode,
+    #[serde(rename = "aa", default, skip_serializing_if = "wire::is_default")]
     pub authoritative_answer: bool,
+    #[serde(rename = "rc")]
     pub response_code: ResponseCode,
-    #[serde(default)]
+    #[serde(rename = "qd", default, skip_serializing_if = "Vec::is_empty")]
     pub question_section: Vec<QuestionEntry>,
-    #[serde(default)]
+    #[serde(rename = "an", default, skip_serializing_if = "Vec::is_empty")]
     pub answer_section: Vec<ResourceRecord>,
-    #[serde(default)]
+    #[serde(rename = "ns", default, skip_serializing_if = "Vec::is_empty")]
     pub authority_section: Vec<ResourceRecord>,
-    #[serde(default)]
+    #[serde(rename = "ar", default, skip_serializing_if = "Vec::is_empty")]
     pub additional_section: Vec<ResourceRecord>,
 }
 
@@ -558,146 +793,79 @@ impl From<DNS> for DnsDto {
     }
 }
 
-#[cfg(test)]
+impl DnsDto {
+    /// UDP payload size & DNSSEC OK flag of the EDNS0 pseudo-record, if it's sent.
+    pub fn edns(&self) -> Option<(u16, bool)> {
+        self.additional_section
+            .iter()
+            .find_map(|record| match record.data {
+                DnsTypeData::Opt {
+                    payload_size,
+                    dnssec_ok,
+                    ..
+                } => Some((payload_size, dnssec_ok)),
+                _ => None,
+            })
+    }
+}
+
+impl FieldIter for DnsDto {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let Self {
+            message_type,
+            operation_code,
+            authoritative_answer,
+            response_code,
+            question_section,
+            answer_section,
+            authority_section,
+            additional_section,
+        } = self;
+
+        vec![
+            ("message_type", message_type.to_string()),
+            ("operation_code", operation_code.to_string()),
+            ("authoritative_answer", authoritative_answer.to_string()),
+            ("response_code", response_code.to_string()),
+            ("question_section", fields::list(question_section)),
+            ("answer_section", fields::list(answer_section)),
+            ("authority_section", fields::list(authority_section)),
+            ("additional_section", fields::list(additional_section)),
+        ]
+    }
+}
+
+#[cfg(all(
+    test,
+    feature = "ethernet",
+    feature = "ipv4",
+    feature = "ipv6",
+    feature = "udp"
+))]
 mod tests {
     use super::*;
-    use crate::dto::frame::FrameHeader;
+    use crate::dto::frame::{FrameHeader, LinkType};
     use crate::parser::tests::FrameType;
     use crate::parser::tests::ProtocolParser;
+    use crate::parser::tests::{udp_frame, udp_layers};
     use crate::protocols::ethernet::Ethernet;
     use crate::protocols::ethernet::ether_type::EtherType;
     use crate::protocols::ethernet::mac::MacAddress;
+    use crate::protocols::ip::checksum::ChecksumStatus;
     use crate::protocols::ip::protocol::IpNextLevelProtocol;
     use crate::protocols::ipv4::IPv4;
     use crate::protocols::ipv6::IPv6;
     use crate::protocols::udp::UDP;
     use std::str::FromStr;
 
-    #[test]
-    fn test_dns_single_query() {
-        let hex_actual = "84 D8 1B 6E C1 4A 04 E8 B9 18 55 10 08 00 45 00 00 44 D2 6E 00 00 80 11 00 00 C0 A8 00 67 C0 A8 00 01 E5 13 00 35 00 30 81 FA F3 31 01 00 00 01 00 00 00 00 00 00 08 64 6F 77 6E 6C 6F 61 64 09 6A 65 74 62 72 61 69 6E 73 03 63 6F 6D 00 00 01 00 01".replace(" ", "");
-        let frame = hex::decode(hex_actual).unwrap();
-        let header = FrameHeader {
-            tv_sec: 0,
-            tv_usec: 0,
-            caplen: 82,
-            len: 0,
-        };
-
-        let parser = ProtocolParser::new(&pcap::Linktype(1), false);
-        let packet = pcap::Packet {
-            header: &pcap::PacketHeader::from(&header),
-            data: &frame,
-        };
-        let result = parser.process(packet);
-        let metadata = match result {
-            Some(value) => match value {
-                FrameType::Metadata(value) => value,
-                _ => panic!(),
-            },
-            None => panic!(),
-        };
-
-        let actual_ethernet = match metadata.layers[0].clone() {
-            ProtocolData::Ethernet(value) => value,
-            _ => panic!(),
-        };
-
-        let expected_ethernet = Ethernet {
-            destination_mac: MacAddress::try_from("84:D8:1B:6E:C1:4A").unwrap(),
-            source_mac: MacAddress::try_from("04:E8:B9:18:55:10").unwrap(),
-            ether_type: EtherType::Ipv4,
-        };
-
-        assert_eq!(actual_ethernet, expected_ethernet);
-
-        let actual_ipv4 = match metadata.layers[1].clone() {
-            ProtocolData::IPv4(value) => value,
-            _ => panic!(),
-        };
-
-        let expected_ipv4 = IPv4 {
-            version: 4,
-            internet_header_length: 20,
-            differentiated_services_code_point: 0,
-            explicit_congestion_notification: 0,
-            total_length: 68,
-            identification: 0xd26e,
-            flags: 0,
-            fragment_offset: 0,
-            time_to_live: 128,
-            protocol_inner: IpNextLevelProtocol::UDP,
-            checksum: 0x0000,
-            address_source: Ipv4Addr::from_str("192.168.0.103").unwrap(),
-            address_destination: Ipv4Addr::from_str("192.168.0.1").unwrap(),
-        };
-
-        assert_eq!(actual_ipv4, expected_ipv4);
-
-        let actual_udp = match metadata.layers[2].clone() {
-            ProtocolData::UDP(value) => value,
-            _ => panic!(),
-        };
-
-        let expected_udp = UDP {
-            port_source: 58643,
-            port_destination: 53,
-            length: 48,
-            checksum: 0x81fa,
-        };
-
-        assert_eq!(actual_udp, expected_udp);
-
-        let actual_dns = match metadata.layers[3].clone() {
-            ProtocolData::DNS(value) => value,
-            _ => panic!(),
-        };
-
-        let expected_dns = DNS {
-            header: Header {
-                id: 0xf331,
-                message_type: MessageType::Query,
-                operation_code: OperationCode::StandardQuery,
-                authoritative_answer: false,
-                truncation: false,
-                recursion_desired: true,
-                recursion_available: false,
-                response_code: ResponseCode::NoErrorCondition,
-                question_entries: 1,
-                answer_records: 0,
-                authority_records: 0,
-                additional_records: 0,
-            },
-            question_section: vec![QuestionEntry {
-                name: "download.jetbrains.com".to_string(),
-                entry_type: DnsType::A,
-                class: Class::IN,
-            }],
-            answer_section: vec![],
-            authority_section: vec![],
-            additional_section: vec![],
-        };
-
-        assert_eq!(actual_dns, expected_dns);
-    }
-
     #[test]
     fn test_dns_query_authoritative_soa() {
         let hex_actual = "04 E8 B9 18 55 10 84 D8 1B 6E C1 4A 08 00 45 00 00 79 56 FF 00 00 3D 11 A4 BC C0 A8 00 01 C0 A8 00 67 00 35 C3 8C 00 65 89 02 BF 9D 81 80 00 01 00 00 00 01 00 00 03 77 77 77 0A 67 6F 6F 67 6C 65 61 70 69 73 03 63 6F 6D 00 00 41 00 01 C0 10 00 06 00 01 00 00 00 37 00 2D 03 6E 73 31 06 67 6F 6F 67 6C 65 C0 1B 09 64 6E 73 2D 61 64 6D 69 6E C0 34 2C C2 48 8D 00 00 03 84 00 00 03 84 00 00 07 08 00 00 00 3C".replace(" ", "");
         let frame = hex::decode(hex_actual).unwrap();
-        let header = FrameHeader {
-            tv_sec: 0,
-            tv_usec: 0,
-            caplen: 135,
-            len: 0,
-        };
+        let header = FrameHeader::new(135);
 
-        let parser = ProtocolParser::new(&pcap::Linktype(1), false);
-        let packet = pcap::Packet {
-            header: &pcap::PacketHeader::from(&header),
-            data: &frame,
-        };
-        let result = parser.process(packet);
+        let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+        let result = parser.process_bytes(&header, &frame);
         let metadata = match result {
             Some(value) => match value {
                 FrameType::Metadata(value) => value,
@@ -736,8 +904,10 @@ mod tests {
             time_to_live: 61,
             protocol_inner: IpNextLevelProtocol::UDP,
             checksum: 0xa4bc,
+            checksum_status: ChecksumStatus::NotChecked,
             address_source: Ipv4Addr::from_str("192.168.0.1").unwrap(),
             address_destination: Ipv4Addr::from_str("192.168.0.103").unwrap(),
+            options: vec![],
         };
 
         assert_eq!(actual_ipv4, expected_ipv4);
@@ -751,7 +921,9 @@ mod tests {
             port_source: 53,
             port_destination: 50060,
             length: 101,
+            length_mismatch: None,
             checksum: 0x8902,
+            checksum_status: ChecksumStatus::NotChecked,
         };
 
         assert_eq!(actual_udp, expected_udp);
@@ -785,7 +957,7 @@ mod tests {
             authority_section: vec![ResourceRecord {
                 name: "googleapis.com".to_string(),
                 record_type: DnsType::SOA,
-                class: Class::IN,
+                class: Some(Class::IN),
                 time_to_live: 55,
                 data_length: 45,
                 data: DnsTypeData::SOA {
@@ -799,6 +971,7 @@ mod tests {
                 },
             }],
             additional_section: vec![],
+            is_truncated: false,
         };
 
         assert_eq!(actual_dns, expected_dns);
@@ -808,19 +981,10 @@ mod tests {
     fn test_dns_aaaa_ns() {
         let hex_actual = "00 00 86 05 80 DA 00 60 97 07 69 EA 86 DD 60 00 00 00 01 0C 11 E6 3F FE 05 01 48 19 00 00 00 00 00 00 00 00 00 42 3F FE 05 07 00 00 00 01 02 00 86 FF FE 05 80 DA 00 35 09 65 01 0C 19 FC B3 62 85 80 00 01 00 02 00 03 00 03 03 77 77 77 04 77 69 64 65 02 61 64 02 6A 70 00 00 1C 00 01 C0 0C 00 05 00 01 00 00 0E 10 00 11 04 65 6E 64 6F 04 77 69 64 65 02 61 64 02 6A 70 00 04 65 6E 64 6F C0 10 00 1C 00 01 00 00 0E 10 00 10 3F FE 05 01 00 00 10 01 00 00 00 00 00 00 00 02 C0 10 00 02 00 01 00 00 0E 10 00 0F 02 6E 73 04 77 69 64 65 02 61 64 02 6A 70 00 C0 10 00 02 00 01 00 00 0E 10 00 15 02 6E 73 05 74 6F 6B 79 6F 04 77 69 64 65 02 61 64 02 6A 70 00 C0 10 00 02 00 01 00 00 0E 10 00 13 02 6E 73 04 72 63 61 63 03 74 64 69 02 63 6F 02 6A 70 00 02 6E 73 C0 10 00 01 00 01 00 00 0E 10 00 04 CB B2 88 3F 02 6E 73 05 74 6F 6B 79 6F C0 10 00 01 00 01 00 00 0E 10 00 04 CB B2 88 3D 02 6E 73 04 72 63 61 63 03 74 64 69 02 63 6F C0 18 00 01 00 01 00 01 51 80 00 04 CA F9 11 11".replace(" ", "");
         let frame = hex::decode(hex_actual).unwrap();
-        let header = FrameHeader {
-            tv_sec: 0,
-            tv_usec: 0,
-            caplen: 322,
-            len: 0,
-        };
+        let header = FrameHeader::new(322);
 
-        let parser = ProtocolParser::new(&pcap::Linktype(1), false);
-        let packet = pcap::Packet {
-            header: &pcap::PacketHeader::from(&header),
-            data: &frame,
-        };
-        let result = parser.process(packet);
+        let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+        let result = parser.process_bytes(&header, &frame);
         let metadata = match result {
             Some(value) => match value {
                 FrameType::Metadata(value) => value,
@@ -857,6 +1021,8 @@ mod tests {
             address_source: Ipv6Addr::from_str("3ffe:501:4819::42").unwrap(),
             address_destination: Ipv6Addr::from_str("3ffe:507:0:1:200:86ff:fe05:80da")
                 .unwrap(),
+            extension_headers: vec![],
+            protocol_inner: IpNextLevelProtocol::UDP,
         };
 
         assert_eq!(actual_ipv6, expected_ipv6);
@@ -870,7 +1036,9 @@ mod tests {
             port_source: 53,
             port_destination: 2405,
             length: 268,
+            length_mismatch: None,
             checksum: 0x19fc,
+            checksum_status: ChecksumStatus::NotChecked,
         };
 
         assert_eq!(actual_udp, expected_udp);
@@ -904,7 +1072,7 @@ mod tests {
                 ResourceRecord {
                     name: "www.wide.ad.jp".to_string(),
                     record_type: DnsType::CNAME,
-                    class: Class::IN,
+                    class: Some(Class::IN),
                     time_to_live: 3600,
                     data_length: 17,
                     data: DnsTypeData::CNAME("endo.wide.ad.jp".to_string()),
@@ -912,7 +1080,7 @@ mod tests {
                 ResourceRecord {
                     name: "endo.wide.ad.jp".to_string(),
                     record_type: DnsType::AAAA,
-                    class: Class::IN,
+                    class: Some(Class::IN),
                     time_to_live: 3600,
                     data_length: 16,
                     data: DnsTypeData::AAAA(
@@ -924,7 +1092,7 @@ mod tests {
                 ResourceRecord {
                     name: "wide.ad.jp".to_string(),
                     record_type: DnsType::NS,
-                    class: Class::IN,
+                    class: Some(Class::IN),
                     time_to_live: 3600,
                     data_length: 15,
                     data: DnsTypeData::NS("ns.wide.ad.jp".to_string()),
@@ -932,7 +1100,7 @@ mod tests {
                 ResourceRecord {
                     name: "wide.ad.jp".to_string(),
                     record_type: DnsType::NS,
-                    class: Class::IN,
+                    class: Some(Class::IN),
                     time_to_live: 3600,
                     data_length: 21,
                     data: DnsTypeData::NS("ns.tokyo.wide.ad.jp".to_string()),
@@ -940,7 +1108,7 @@ mod tests {
                 ResourceRecord {
                     name: "wide.ad.jp".to_string(),
                     record_type: DnsType::NS,
-                    class: Class::IN,
+                    class: Some(Class::IN),
                     time_to_live: 3600,
                     data_length: 19,
                     data: DnsTypeData::NS("ns.rcac.tdi.co.jp".to_string()),
@@ -950,7 +1118,7 @@ mod tests {
                 ResourceRecord {
                     name: "ns.wide.ad.jp".to_string(),
                     record_type: DnsType::A,
-                    class: Class::IN,
+                    class: Some(Class::IN),
                     time_to_live: 3600,
                     data_length: 4,
                     data: DnsTypeData::AIPv4(
@@ -960,7 +1128,7 @@ mod tests {
                 ResourceRecord {
                     name: "ns.tokyo.wide.ad.jp".to_string(),
                     record_type: DnsType::A,
-                    class: Class::IN,
+                    class: Some(Class::IN),
                     time_to_live: 3600,
                     data_length: 4,
                     data: DnsTypeData::AIPv4(
@@ -970,7 +1138,7 @@ mod tests {
                 ResourceRecord {
                     name: "ns.rcac.tdi.co.jp".to_string(),
                     record_type: DnsType::A,
-                    class: Class::IN,
+                    class: Some(Class::IN),
                     time_to_live: 86400,
                     data_length: 4,
                     data: DnsTypeData::AIPv4(
@@ -978,30 +1146,247 @@ mod tests {
                     ),
                 },
             ],
+            is_truncated: false,
         };
 
         assert_eq!(actual_dns, expected_dns);
     }
 
+    #[test]
+    fn test_mdns_response() {
+        // Cast device announcing its service, TXT record has the cache-flush bit
+        let hex_actual = "01 00 5E 00 00 FB F4 F5 D8 11 22 33 08 00 45 00 00 A2 00 01 00 00 FF 11 00 00 C0 A8 00 14 E0 00 00 FB 14 E9 14 E9 00 8E 00 00 00 00 84 00 00 00 00 02 00 00 00 00 0B 5F 67 6F 6F 67 6C 65 63 61 73 74 04 5F 74 63 70 05 6C 6F 63 61 6C 00 00 0C 00 01 00 00 00 78 00 24 0B 4C 69 76 69 6E 67 2D 52 6F 6F 6D 0B 5F 67 6F 6F 67 6C 65 63 61 73 74 04 5F 74 63 70 05 6C 6F 63 61 6C 00 0B 4C 69 76 69 6E 67 2D 52 6F 6F 6D 0B 5F 67 6F 6F 67 6C 65 63 61 73 74 04 5F 74 63 70 05 6C 6F 63 61 6C 00 00 10 80 01 00 00 11 94 00 06 05 6D 64 3D 54 56".replace(" ", "");
+        let frame = hex::decode(hex_actual).unwrap();
+        let header = FrameHeader::new(176);
+
+        let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+        let metadata = match parser.process_bytes(&header, &frame) {
+            Some(FrameType::Metadata(value)) => value,
+            _ => panic!(),
+        };
+
+        let actual_dns = match metadata.layers[3].clone() {
+            ProtocolData::DNS(value) => value,
+            _ => panic!(),
+        };
+        assert_eq!(actual_dns.header.message_type, MessageType::Response);
+        assert_eq!(actual_dns.answer_section.len(), 2);
+        assert_eq!(actual_dns.answer_section[0].name, "_googlecast._tcp.local");
+        assert_eq!(
+            actual_dns.answer_section[1].name,
+            "Living-Room._googlecast._tcp.local"
+        );
+        assert_eq!(actual_dns.answer_section[1].class, Some(Class::IN));
+    }
+
     #[test]
     fn test_overflow_name() {
         let hex_actual = "04 E8 B9 18 55 10 84 D8 1B 6E C1 4A 08 00 45 00 05 D4 FC 86 40 00 34 06 44 CF D4 7C 6A 42 C0 A8 00 67 01 BB FF 50 2C 27 E0 83 E8 40 00 EB 50 10 00 A5 BF D3 00 00 29 4A 72 09 DD 3C 71 24 6C 8D 9F F9 C0 0C 15 5C D9 F0 A5 F6 20 51 03 06 CD 99 CE 38 EB 19 CB 92 38 F5 AE 98 BA 8D 98 05 1D 1E DC 37 21 D9 DA DE 04 7B AB BD 6E 1B 80 4A 65 BA CC 3E 62 88 62 74 85 20 B4 A9 18 85 90 D6 66 7A 10 F6 E4 DC 85 55 68 89 4B AE 66 F9 B2 16 DF 00 A3 19 0C 86 97 6F 0F 4C D1 D6 2F 1B D0 A7 30 B2 0A C4 EF 10 AB BB 17 3B 4B 4E 11 D5 E0 05 CE 29 56 94 CB F4 30 CD F4 1C 56 54 30 2F C1 E9 D3 72 17 8F 1B E6 3B EF C7 54 38 97 62 89 3D 65 BE B5 A9 1A A1 07 08 5F 74 DA F0 EE BA E5 FC 2D 82 A9 F8 E8 6D 0A D2 03 D9 9F 26 C8 14 15 2C FD 37 DD 1B 31 1E 6E 46 16 F1 1C 8F 28 7A F7 D5 DB 66 24 41 23 0D F2 C7 1D B1 77 79 69 24 92 61 FB A3 B6 38 83 5C 48 CB AB D0 51 2A C1 0B 2A 51 61 22 A0 51 2A 89 98 5A 75 F3 58 AA B8 D0 6B E9 C8 7F 51 0B A7 22 90 B8 2D D6 F1 A9 16 3F B2 EF A0 E7 40 9D C2 66 3E 07 A7 99 E3 4E 0E A1 5F 22 FD 0E C2 70 A9 47 21 43 A8 09 2F CA 95 8C B1 15 45 52 B4 30 61 C2 27 F1 F1 8F 7D 52 F5 6A 07 21 0A 2B A6 4A 08 74 DD E8 95 87 BC 7B ED 38 78 CD BC 93 F5 2D E8 2E 0A C7 9A 65 30 E0 D8 DC 03 9C 88 08 88 27 21 37 65 0B EB AF 1E 24 BC 65 B6 B3 4E 71 FE 32 6B 5B 53 53 CA EB BE 41 4C B4 AF E1 16 05 50 CB DD FA 13 9A 6B 9F 71 08 1E 0A 79 3A 66 25 CB A7 78 9E 0B EB 79 71 D9 95 5A 8D E3 F7 21 3F E7 9C 1F CE 26 5B 4D 9D 53 C6 B1 5A 27 69 BD AC 1D 91 26 B2 15 F2 ED 7B 0B CD EE 50 EF DE 84 C7 BE 3B 27 C1 DE 20 B3 DB A4 04 5D FE A1 7A 52 E3 5D E9 29 CE 73 44 BD E8 B3 EE BA 89 27 2E 51 35 4D 63 7B 9E CF C2 2D 81 AF E9 8C C9 14 F5 8F B4 AE 6D B1 50 78 41 49 F9 EF 57 20 79 B8 53 5D 04 4E C4 3F 31 73 29 25 26 F7 06 52 62 AE EA 77 22 2B AB 59 FD BD B3 30 31 31 C0 F3 40 14 0C 74 3F D0 B8 E2 52 3A C8 E2 5D B2 27 89 78 C5 27 B3 0C 05 E0 6F 0F E8 8C E5 E9 64 A4 2F AA 62 44 FA 46 27 4F 7F C1 26 7B 13 32 7C 5D 3E 94 73 EA BD B6 0E 32 B0 40 FB 61 90 74 8C 35 B0 E8 86 76 00 37 84 8F 9B 9A 13 9F B5 77 9D 4B 1E 30 91 66 38 E1 17 8D 4C 1D 48 BE 98 8C 47 10 48 D6 A9 31 07 92 0A 57 80 9D 42 84 BD CD 19 AE 8D 98 CE 87 0C FF 83 FD 3B 9F CB E6 D1 F9 8F B4 9E 03 0A 3E 51 FE 41 15 B5 78 C7 1B 3C 77 F7 56 45 1F B9 3E 19 43 C0 BD 0C B0 E6 D2 30 8A 0E 2D 9F 31 52 1A A2 F1 1D BD 8E 89 5E 02 BA 6C DD A8 C3 15 FB CA B6 6C B3 52 5D 27 69 75 D8 45 4D 5A 98 A5 2C 13 11 73 0E 60 9C 75 B5 74 09 6D 79 F1 4A 94 8E FC FC 49 3D C3 17 A3 C8 EA B7 8A 03 38 44 E4 D3 44 5A 65 43 10 2A 7E 5A A7 42 A5 F4 74 6C B4 C7 65 39 40 1F F7 0D D5 9A 0D 00 82 6D 8B 9A 8D E9 FE 50 AB BF F9 23 6C 25 45 71 55 25 E7 D0 20 DF 94 21 82 69 4C 70 A9 EE 8D AE 10 E7 71 A9 9A 5C 75 32 B6 8C B5 C1 8F 5A A3 C0 59 E6 E9 FC 14 61 5C F4 A5 CF 85 B8 0A E6 73 24 2C B4 9E 3C 92 47 FC 1D 30 DC 9E ED FE F4 B1 FF F8 FF F9 6A F3 91 8A 5C F1 B3 28 16 64 4C 16 89 1B 23 55 83 6F 6F F5 CD 5D CC B3 20 54 87 4F CB A0 A2 68 AB A2 9C 04 64 F1 7B 13 B7 78 57 EA A8 1A 5E 24 9E D9 84 66 EA BE 9B 55 3F A4 EE D9 09 E2 05 8E 59 A5 04 9F 0D F4 F2 DC A6 11 25 3E BE 13 49 40 25 AD 6D 3D 65 58 54 4C 98 69 FF 7D 44 25 60 48 D9 2F E8 D3 B5 D0 00 84 7F 98 D8 14 D4 4C 4B 5B 92 9F 0D 6E 1A A2 97 7A E8 FC 66 D8 CB 48 5C AA C2 91 48 40 15 14 D7 20 AA 09 AD 6E 71 69 F7 45 2F B2 47 9E E3 80 3C 0E 1A 46 3A 58 9B 3A 0F 73 3F 36 B6 F3 F4 1A B3 6C BD 4A FF 10 A6 C2 AF AD B0 65 00 28 C4 93 25 3F C2 80 F8 23 62 B3 0B 67 F8 B9 89 66 5A 12 88 12 0A 3D 54 47 C1 76 B7 BA 1D 9C E8 A7 00 73 29 C9 C0 85 DE B8 96 AE D1 B4 DA B4 77 7A 6D B2 15 AF 85 3F D6 97 B3 71 15 91 36 0E D6 41 62 F9 C7 10 0C 41 43 FA 83 07 78 3F 61 EC 32 60 42 BF E8 7D 9D 20 AD 3E 7B 5E C5 BB 00 9A D2 E9 C9 31 36 DD 43 57 1C BF 72 41 8E 78 AA DC 7C 81 21 15 D6 78 55 F6 7D 70 94 1D 75 5A BF 8A E6 A9 CD 19 32 8D B4 E2 D0 26 41 01 C7 28 ED DF 97 F3 B3 40 25 5A F9 70 95 95 77 52 96 BE 78 EB B0 91 4D 6A 65 28 BB 38 2E 55 71 FD E6 05 C3 C6 DF C9 1F BE 3E E0 BA EC A2 A6 5B 50 9E 09 29 01 FE 4B AF BE 0D 70 A6 B7 6A DF 4C F1 DD A3 23 2D 0C 55 4E D5 C8 2F 93 1C 0F 5B 47 58 B7 45 86 07 ED A3 BF 24 9C 9D 09 CC D8 4C 77 EB B4 80 BE 01 B4 E6 BE 56 9B 79 D2 1C E4 60 76 83 0E A9 14 10 DD 2A 43 9D 5D 04 45 58 58 B5 68 1B 93 38 77 65 BD 1E BA A9 DE 85 78 22 9C 65 24 49 26 A9 80 CD B3 AD 38 B2 00 9D F6 34 2E E4 B1 D7 E5 F5 38 FB E8 7A AF C5 2B C8 9E 4C E9 67 2D 14 C4 57 76 FB 92 FA D2 73 FA 08 C0 96 AB 75 3F CA 7E 43 5E 99 77 C5 5D E3 2E 03 31 ED DD 69 C3 7C 6D 2B C0 1F 51 79 7C 20 B7 27 61 0E EC C0 2E 2B 54 90 CC B6 28 B0 13 CC 13 B4 D1 EA 1E 61 3F 5A 6B 8D 59 D1 5B 81 8B 07 97 45 BB BD E2 65 79 A4 E6 A6 BA 8C 1C 4A C2 C1 E2 2D 7C".replace(" ", "");
         let frame = hex::decode(hex_actual).unwrap();
 
-        let header = FrameHeader {
-            tv_sec: 0,
-            tv_usec: 0,
-            caplen: 1506,
-            len: 1506,
+        let header = FrameHeader::new(1506);
+        let parser = parser::ProtocolParser::new(LinkType::ETHERNET, false);
+        let frame_type = parser.process_bytes(&header, &frame);
+
+        assert!(frame_type.is_some())
+    }
+
+    #[test]
+    fn test_invalid_operation_code() {
+        // Standard query with the unassigned opcode 3
+        let hex_actual = "12 34 18 00 00 01 00 00 00 00 00 00 03 77 77 77 00 00 01 00 01"
+            .replace(" ", "");
+        let message = hex::decode(hex_actual).unwrap();
+
+        match parse(&message) {
+            Err(nom::Err::Error(failure)) => {
+                assert_eq!(failure.error, ParserError::InvalidValue { field: "opcode" })
+            },
+            _ => panic!(),
+        }
+
+        // Same query with the standard opcode
+        let mut message = message;
+        message[2] = 0x00;
+        assert!(parse(&message).is_ok());
+    }
+
+    // Response for example.com with two A records
+    const RESPONSE_EXAMPLE: &str = "12 34 81 80 00 01 00 02 00 00 00 00 07 65 78 61 6D 70 6C 65 03 63 6F 6D 00 00 01 00 01 C0 0C 00 01 00 01 00 00 0E 10 00 04 5D B8 D8 22 C0 0C 00 01 00 01 00 00 0E 10 00 04 5D B8 D8 23";
+
+    #[test]
+    fn test_dns_on_non_standard_port() {
+        // Recognized by the header, 5300 isn't the DNS port
+        let message = hex::decode(RESPONSE_EXAMPLE.replace(" ", "")).unwrap();
+        let layers = udp_layers(5300, 49152, &message);
+        assert_eq!(layers.len(), 3);
+
+        let actual_dns = match layers[2].clone() {
+            ProtocolData::DNS(value) => value,
+            _ => panic!(),
         };
-        let packet_header: pcap::PacketHeader = (&header).into();
+        assert_eq!(actual_dns.answer_section.len(), 2);
+    }
 
-        let parser = parser::ProtocolParser::new(&pcap::Linktype(1), false);
-        let frame_type = parser.process(pcap::Packet {
-            header: &packet_header,
-            data: &frame,
-        });
+    #[test]
+    fn test_dns_forwarded_query() {
+        // Query between the resolvers, neither port is the DNS one
+        let hex_actual = "AB CD 01 00 00 01 00 00 00 00 00 00 07 65 78 61 6D 70 6C 65 03 63 6F 6D 00 00 01 00 01".replace(" ", "");
+        let message = hex::decode(hex_actual).unwrap();
+        let layers = udp_layers(5301, 5300, &message);
+        assert_eq!(layers.len(), 3);
+
+        let actual_dns = match layers[2].clone() {
+            ProtocolData::DNS(value) => value,
+            _ => panic!(),
+        };
+        assert_eq!(actual_dns.header.message_type, MessageType::Query);
+        assert_eq!(actual_dns.question_section[0].name, "example.com");
+    }
 
-        assert!(frame_type.is_some())
+    #[test]
+    fn test_not_dns_on_dns_port() {
+        let parser = ProtocolParser::new(LinkType::IPV4, false);
+        let payloads = [
+            // Health check
+            b"HEALTHCHECK\n".to_vec(),
+            // Sane header, but 3 questions & 2 answers don't fit into the payload
+            hex::decode("000000000003000200000000000001000100").unwrap(),
+            // Query with the response code
+            hex::decode("1234000300010000000000000000010001").unwrap(),
+        ];
+        for payload in payloads {
+            let frame = udp_frame(49152, 53, &payload);
+            let header = FrameHeader::new(frame.len());
+            let (metadata, failure) = match parser.process_bytes_traced(&header, &frame) {
+                Some((FrameType::Metadata(metadata), failure)) => (metadata, failure),
+                _ => panic!(),
+            };
+            // UDP payload isn't parsed, so there's no failure
+            assert!(failure.is_none());
+            assert_eq!(metadata.layers.len(), 2);
+        }
+    }
+
+    #[test]
+    fn test_dns_plausible() {
+        let message = hex::decode(RESPONSE_EXAMPLE.replace(" ", "")).unwrap();
+        assert!(is_plausible(&message, false));
+
+        // Cut off answers are fine only in the truncated capture
+        let cut = &message[..HEADER_LENGTH + 20];
+        assert!(!is_plausible(cut, false));
+        assert!(is_plausible(cut, true));
+
+        // Header itself is checked anyway
+        assert!(!is_plausible(&message[..HEADER_LENGTH - 1], true));
+        let mut reserved = message.clone();
+        reserved[3] |= 0x40;
+        assert!(!is_plausible(&reserved, true));
+    }
+
+    #[test]
+    fn test_dns_truncated_answer() {
+        // Second record is cut off
+        let message = hex::decode(RESPONSE_EXAMPLE.replace(" ", "")).unwrap();
+        let frame = udp_frame(53, 49152, &message);
+        let captured = &frame[..frame.len() - 3];
+        let parser = ProtocolParser::new(LinkType::IPV4, false);
+
+        let header = FrameHeader::captured(captured.len(), frame.len());
+        let metadata = match parser.process_bytes(&header, captured) {
+            Some(FrameType::Metadata(value)) => value,
+            _ => panic!(),
+        };
+        assert!(metadata.is_truncated);
+
+        let actual_dns = match metadata.layers[2].clone() {
+            ProtocolData::DNS(value) => value,
+            _ => panic!(),
+        };
+        assert!(actual_dns.is_truncated);
+        assert_eq!(actual_dns.header.answer_records, 2);
+        assert_eq!(actual_dns.question_section.len(), 1);
+        assert_eq!(actual_dns.answer_section.len(), 1);
+        assert_eq!(
+            actual_dns.answer_section[0].data,
+            DnsTypeData::AIPv4(Ipv4Addr::new(93, 184, 216, 34))
+        );
+
+        // Same bytes aren't DNS, if they are the whole frame
+        let header = FrameHeader::new(captured.len());
+        let metadata = match parser.process_bytes(&header, captured) {
+            Some(FrameType::Metadata(value)) => value,
+            _ => panic!(),
+        };
+        assert_eq!(metadata.layers.len(), 2);
+    }
+
+    #[test]
+    fn test_dns_query_ethernet_padding() {
+        // Root NS query is 59 bytes, so the frame is padded to the minimal 60 bytes.
+        // Padding & FCS are cut off by the total length of IPv4.
+        let query = "00 1A 8C 10 AD 30 00 1E 68 51 4F A9 08 00 45 00 00 2D 12 34 00 00 40 11 00 00 C0 A8 00 0A C0 A8 00 01 C3 50 00 35 00 19 00 00 AB CD 01 00 00 01 00 00 00 00 00 00 00 00 02 00 01 00";
+        for trailer in ["", " 5E 2A 91 07"] {
+            let hex_actual = format!("{query}{trailer}").replace(" ", "");
+            let frame = hex::decode(hex_actual).unwrap();
+            let header = FrameHeader::new(frame.len());
+
+            let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+            let (metadata, failure) = match parser.process_bytes_traced(&header, &frame) {
+                Some((FrameType::Metadata(metadata), failure)) => (metadata, failure),
+                _ => panic!(),
+            };
+            assert!(failure.is_none());
+            assert_eq!(metadata.layers.len(), 4);
+
+            match &metadata.layers[2] {
+                ProtocolData::UDP(udp) => assert_eq!(udp.length_mismatch, None),
+                _ => panic!(),
+            }
+            let actual_dns = match metadata.layers[3].clone() {
+                ProtocolData::DNS(value) => value,
+                _ => panic!(),
+            };
+            assert_eq!(actual_dns.question_section.len(), 1);
+            assert_eq!(actual_dns.question_section[0].name, "");
+        }
+    }
+
+    #[test]
+    fn test_dns_edns0() {
+        // Response for example.com with the OPT record: 1232 bytes, DNSSEC OK & cookie.
+        // Type 41 & class 1232 were rejected before.
+        let hex_actual = "8D 2C 81 80 00 01 00 01 00 00 00 01 07 65 78 61 6D 70 6C 65 03 63 6F 6D 00 00 01 00 01 C0 0C 00 01 00 01 00 00 0B 3F 00 04 5D B8 D7 0E 00 00 29 04 D0 00 00 80 00 00 1C 00 0A 00 18 D1 52 7A 3C 91 0E 6B 2F 65 8B 4A 0D 00 00 00 00 9E 1C 27 C3 4D 50 A8 61".replace(" ", "");
+        let message = hex::decode(hex_actual).unwrap();
+        let layers = udp_layers(53, 50000, &message);
+
+        let actual_dns = match layers[2].clone() {
+            ProtocolData::DNS(value) => value,
+            _ => panic!(),
+        };
+        assert_eq!(actual_dns.answer_section.len(), 1);
+        assert_eq!(actual_dns.additional_section.len(), 1);
+
+        let expected_opt = ResourceRecord {
+            name: "".to_string(),
+            record_type: DnsType::OPT,
+            class: None,
+            time_to_live: 0x8000,
+            data_length: 28,
+            data: DnsTypeData::Opt {
+                payload_size: 1232,
+                extended_rcode: 0,
+                version: 0,
+                dnssec_ok: true,
+                options: vec![EdnsOption {
+                    code: 10,
+                    data: hex::decode("D1527A3C910E6B2F658B4A0D000000009E1C27C34D50A861")
+                        .unwrap(),
+                }],
+            },
+        };
+        assert_eq!(actual_dns.additional_section[0], expected_opt);
+        assert_eq!(DnsDto::from(actual_dns).edns(), Some((1232, true)));
     }
 }
@@ -1,9 +1,9 @@
+use crate::dto::fields::FieldIter;
 use crate::dto::frame::FrameMetadata;
-use crate::parser::ParserError;
+use crate::parser::{IResult, ParserError};
 use crate::protocols::ethernet::ether_type::EtherType;
 use crate::protocols::ethernet::mac::MacAddress;
-use crate::protocols::{ProtocolData, ProtocolId};
-use nom::IResult;
+use crate::protocols::{ProtocolData, ProtocolId, enabled};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
@@ -26,6 +26,8 @@ pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
         ether_type,
     };
 
+    // Rest may end with the padding up to 60 bytes & the FCS. Children cut it off:
+    // IPv4 by the total length, IPv6 by the payload length, ARP by the address lengths.
     Ok((rest, ProtocolData::Ethernet(layer)))
 }
 
@@ -36,9 +38,9 @@ pub fn best_children(metadata: &FrameMetadata) -> Option<ProtocolId> {
         _ => return None,
     };
     match ethernet.ether_type {
-        EtherType::Arp => Some(ProtocolId::Arp),
-        EtherType::Ipv4 => Some(ProtocolId::IPv4),
-        EtherType::Ipv6 => Some(ProtocolId::IPv6),
+        EtherType::Arp | EtherType::ArpReverse => enabled::Arp,
+        EtherType::Ipv4 => enabled::IPv4,
+        EtherType::Ipv6 => enabled::IPv6,
         _ => None,
     }
 }
@@ -52,7 +54,9 @@ pub struct Ethernet {
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct EthernetDto {
+    #[serde(rename = "dst")]
     pub destination_mac: MacAddress,
+    #[serde(rename = "src")]
     pub source_mac: MacAddress,
 }
 
@@ -65,6 +69,20 @@ impl From<Ethernet> for EthernetDto {
     }
 }
 
+impl FieldIter for EthernetDto {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let Self {
+            destination_mac,
+            source_mac,
+        } = self;
+
+        vec![
+            ("destination_mac", destination_mac.to_string()),
+            ("source_mac", source_mac.to_string()),
+        ]
+    }
+}
+
 #[derive(Clone, Debug, Error, Serialize, Deserialize, PartialEq)]
 pub enum EthernetError {
     #[error("Unknown EtherType")]
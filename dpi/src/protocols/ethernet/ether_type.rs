@@ -1,6 +1,5 @@
-use crate::parser::ParserError;
+use crate::parser::{IResult, ParserError};
 use crate::protocols::ethernet::EthernetError;
-use nom::IResult;
 use nom::Parser;
 use nom::number::be_u16;
 use num_enum::TryFromPrimitive;
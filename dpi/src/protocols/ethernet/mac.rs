@@ -1,6 +1,5 @@
-use crate::parser::ParserError;
+use crate::parser::{IResult, ParserError};
 use crate::protocols::ethernet::EthernetError;
-use nom::IResult;
 use nom::bytes::complete::take;
 use serde::{Deserialize, Serialize};
 use std::fmt::Formatter;
@@ -24,6 +23,11 @@ impl MacAddress {
         self.0[0] & 0b00000001 == 1
     }
 
+    // Set by the randomized MACs of the phones & the guest networks
+    pub fn is_locally_administered(&self) -> bool {
+        self.0[0] & 0b00000010 != 0
+    }
+
     pub fn to_bit_string(&self) -> String {
         self.0.map(|num| format!("{num:08b}")).join("")
     }
@@ -119,4 +123,12 @@ mod tests {
         let mac = MacAddress::try_from("00:1A:2B:3C:4D:5E").unwrap();
         assert_eq!(mac.is_multicast(), false);
     }
+
+    #[test]
+    fn test_is_locally_administered() {
+        let randomized = MacAddress::try_from("DA:A1:19:3C:4D:5E").unwrap();
+        assert_eq!(randomized.is_locally_administered(), true);
+        let universal = MacAddress::try_from("00:1A:2B:3C:4D:5E").unwrap();
+        assert_eq!(universal.is_locally_administered(), false);
+    }
 }
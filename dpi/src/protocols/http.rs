@@ -1,101 +1,137 @@
-use crate::parser::ParserError;
+use crate::dto::fields::{self, FieldIter};
+use crate::parser::{HTTP_BODY_LIMIT_DEFAULT, IResult, ParserError};
 use crate::protocols::ProtocolData;
-use nom::IResult;
 use nom::Parser;
 use nom::bytes::complete::take;
 use nom::bytes::{tag, take_until};
 use nom::sequence::terminated;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 use strum_macros::Display;
 
 // HTTP Protocol
 // RFC 2616: https://datatracker.ietf.org/doc/html/rfc2616
 
 pub const CRLF: &str = "\r\n";
+const CONTENT_LENGTH: &str = "Content-Length";
+// Rest of the message, which body is cut off
+const END: &[u8] = &[];
+
 pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
-    let (rest, potential_starting_line) =
-        terminated(take_until(CRLF), tag(CRLF)).parse(bytes)?;
-    let starting_line = std::str::from_utf8(potential_starting_line)
-        .map_err(|_| ParserError::ErrorVerify.to_nom(bytes))?;
+    parse_limited(bytes, HTTP_BODY_LIMIT_DEFAULT)
+}
+
+/// Same as [`parse`], but the bodies, declared longer than `body_limit` bytes,
+/// are skipped instead of being kept.
+pub fn parse_limited(bytes: &[u8], body_limit: u64) -> IResult<&[u8], ProtocolData> {
+    let (rest, starting_line) = parse_starting_line(bytes)?;
 
     let (rest, http) = if starting_line.starts_with("HTTP/") {
-        parse_response(rest, starting_line)
+        parse_response(rest, starting_line, body_limit)
     } else {
-        parse_request(rest, starting_line)
-    }
-    .map_err(|_| ParserError::ErrorVerify.to_nom(bytes))?;
-
-    if !rest.is_empty() {
-        return Err(ParserError::ErrorVerify.to_nom(bytes));
+        parse_request(rest, starting_line, body_limit)
     }
+    .map_err(|err| ParserError::from_nom(err, bytes.len()).to_nom(bytes))?;
 
+    // Rest may hold the next messages, see `ProtocolId::is_repeated`
     Ok((rest, ProtocolData::HTTP(http)))
 }
 
+/// Line before the headers. SIP messages share it with HTTP.
+pub fn parse_starting_line(bytes: &[u8]) -> IResult<&[u8], &str> {
+    let (rest, starting_line) = terminated(take_until(CRLF), tag(CRLF)).parse(bytes)?;
+    let starting_line = std::str::from_utf8(starting_line).map_err(|_| {
+        ParserError::InvalidValue {
+            field: "starting_line",
+        }
+        .to_nom(bytes)
+    })?;
+
+    Ok((rest, starting_line))
+}
+
 pub fn parse_request<'a>(
-    input: &'a [u8], starting_line: &str,
+    input: &'a [u8], starting_line: &str, body_limit: u64,
 ) -> IResult<&'a [u8], HTTP> {
     let mut starting_line_parts = starting_line.splitn(3, " ");
     let method = Methods::try_from(
         starting_line_parts
             .next()
-            .ok_or(ParserError::ErrorVerify.to_nom(input))?,
+            .ok_or(ParserError::InvalidValue { field: "method" }.to_nom(input))?,
     )
-    .map_err(|_| ParserError::ErrorVerify.to_nom(input))?;
+    .map_err(|_| ParserError::InvalidValue { field: "method" }.to_nom(input))?;
     let target = starting_line_parts
         .next()
-        .ok_or(ParserError::ErrorVerify.to_nom(input))?;
+        .ok_or(ParserError::InvalidValue { field: "target" }.to_nom(input))?;
     let version = starting_line_parts
         .next()
-        .ok_or(ParserError::ErrorVerify.to_nom(input))?;
+        .filter(|version| version.starts_with("HTTP/"))
+        .ok_or(ParserError::InvalidValue { field: "version" }.to_nom(input))?;
 
     let (rest, headers) = parse_headers(input)?;
 
-    let (rest, body) = parse_body(rest, &headers)?;
+    let (rest, body) = parse_body(rest, &headers, body_limit)?;
 
     let protocol = HTTPRequest {
         method,
         target: target.to_string(),
         version: version.to_string(),
         headers,
-        body,
+        body: body.data,
+        is_truncated: body.is_truncated,
+        body_anomaly: body.anomaly,
     };
 
     Ok((rest, HTTP::Request(protocol)))
 }
 
 pub fn parse_response<'a>(
-    input: &'a [u8], starting_line: &str,
+    input: &'a [u8], starting_line: &str, body_limit: u64,
 ) -> IResult<&'a [u8], HTTP> {
     let mut starting_line_parts = starting_line.splitn(3, " ");
     let version = starting_line_parts
         .next()
-        .ok_or(ParserError::ErrorVerify.to_nom(input))?;
+        .ok_or(ParserError::InvalidValue { field: "version" }.to_nom(input))?;
     let status_code = starting_line_parts
         .next()
-        .ok_or(ParserError::ErrorVerify.to_nom(input))?
+        .ok_or(
+            ParserError::InvalidValue {
+                field: "status_code",
+            }
+            .to_nom(input),
+        )?
         .parse::<u16>()
-        .map_err(|_| ParserError::ErrorVerify.to_nom(input))?;
-    let reason_phrase = starting_line_parts
-        .next()
-        .ok_or(ParserError::ErrorVerify.to_nom(input))?;
+        .map_err(|_| {
+            ParserError::InvalidValue {
+                field: "status_code",
+            }
+            .to_nom(input)
+        })?;
+    let reason_phrase = starting_line_parts.next().ok_or(
+        ParserError::InvalidValue {
+            field: "reason_phrase",
+        }
+        .to_nom(input),
+    )?;
 
     let (rest, headers) = parse_headers(input)?;
 
-    let (rest, body) = parse_body(rest, &headers)?;
+    let (rest, body) = parse_body(rest, &headers, body_limit)?;
 
     let protocol = HTTPResponse {
         version: version.to_string(),
         status_code,
         reason: reason_phrase.to_string(),
         headers,
-        body,
+        body: body.data,
+        is_truncated: body.is_truncated,
+        body_anomaly: body.anomaly,
     };
 
     Ok((rest, HTTP::Response(protocol)))
 }
 
-fn parse_headers(input: &[u8]) -> IResult<&[u8], Vec<Header>> {
+pub fn parse_headers(input: &[u8]) -> IResult<&[u8], Vec<Header>> {
     let mut headers: Vec<Header> = Vec::new();
     let mut rest_buffer = input;
 
@@ -109,8 +145,9 @@ fn parse_headers(input: &[u8]) -> IResult<&[u8], Vec<Header>> {
 
         let (rest, header_bytes) =
             terminated(take_until(CRLF), tag(CRLF)).parse(rest_buffer)?;
-        let header_line = std::str::from_utf8(header_bytes)
-            .map_err(|_| ParserError::ErrorVerify.to_nom(rest_buffer))?;
+        let header_line = std::str::from_utf8(header_bytes).map_err(|_| {
+            ParserError::InvalidValue { field: "header" }.to_nom(rest_buffer)
+        })?;
         if let Some((key, value)) = header_line.split_once(": ") {
             headers.push((key.to_string(), value.to_string()));
         }
@@ -121,42 +158,127 @@ fn parse_headers(input: &[u8]) -> IResult<&[u8], Vec<Header>> {
     Ok((rest_buffer, headers))
 }
 
-fn parse_body<'a>(input: &'a [u8], headers: &[Header]) -> IResult<&'a [u8], Vec<u8>> {
-    // Seeking for 'Content-Length'
-    if let Some((_, value)) = headers
-        .iter()
-        .find(|(n, _)| n.eq_ignore_ascii_case("Content-Length"))
-    {
-        let len: usize = value
-            .parse::<usize>()
-            .map_err(|_| ParserError::ErrorVerify.to_nom(input))?;
-        let (rest, body) = take(len)(input)?;
-        return Ok((rest, body.to_vec()));
+struct Body {
+    data: Vec<u8>,
+    is_truncated: bool,
+    anomaly: Option<BodyAnomaly>,
+}
+
+impl Body {
+    fn new(data: Vec<u8>, is_truncated: bool) -> Self {
+        Self {
+            data,
+            is_truncated,
+            anomaly: None,
+        }
+    }
+
+    fn omitted(anomaly: BodyAnomaly) -> Self {
+        Self {
+            data: Vec::new(),
+            is_truncated: false,
+            anomaly: Some(anomaly),
+        }
+    }
+}
+
+// Body, that is cut off by the end of the bytes, is returned as is, with the flag
+fn parse_body<'a>(
+    input: &'a [u8], headers: &[Header], body_limit: u64,
+) -> IResult<&'a [u8], Body> {
+    match content_length(headers).map_err(|err| err.to_nom(input))? {
+        Some(ContentLength::Declared(declared)) => {
+            return Ok(sized_body(input, declared, body_limit));
+        },
+        // Where the message ends is unknown, so the rest isn't parsed
+        Some(ContentLength::Conflicting) => {
+            return Ok((END, Body::omitted(BodyAnomaly::ConflictingLength)));
+        },
+        None => {},
     }
     // Otherwise seeking for 'Transfer-Encoding'
     if headers.iter().any(|(key, value)| {
         key.eq_ignore_ascii_case("Transfer-Encoding")
             && value.eq_ignore_ascii_case("chunked")
     }) {
-        return parse_chunked(input);
+        let (rest, (data, is_truncated)) = parse_chunked(input)?;
+        return Ok((rest, Body::new(data, is_truncated)));
     }
     // No body
-    Ok((input, Vec::new()))
+    Ok((input, Body::new(Vec::new(), false)))
 }
 
-// Parser of chunked
-fn parse_chunked(input: &[u8]) -> IResult<&[u8], Vec<u8>> {
+// Absurd lengths aren't buffered. Bytes of the segment are skipped anyway,
+// so the next message is found
+fn sized_body(input: &[u8], declared: u64, body_limit: u64) -> (&[u8], Body) {
+    let length = usize::try_from(declared).unwrap_or(usize::MAX);
+    let (body, rest) = input.split_at(length.min(input.len()));
+    match (declared <= body_limit, length <= input.len()) {
+        (false, _) => (rest, Body::omitted(BodyAnomaly::Omitted(declared))),
+        (true, true) => (rest, Body::new(body.to_vec(), false)),
+        (true, false) => (END, Body::new(input.to_vec(), true)),
+    }
+}
+
+enum ContentLength {
+    Declared(u64),
+    Conflicting,
+}
+
+// RFC 7230, 3.3.2: the header may be repeated or hold the list, if the values are same.
+// Only the digits are valid, so "+5" or "-1" aren't taken as the numbers
+fn content_length(headers: &[Header]) -> Result<Option<ContentLength>, ParserError> {
+    const INVALID: ParserError = ParserError::InvalidValue {
+        field: "content_length",
+    };
+
+    let mut found: Option<u64> = None;
+    let values = headers
+        .iter()
+        .filter(|(name, _)| name.trim().eq_ignore_ascii_case(CONTENT_LENGTH))
+        .flat_map(|(_, value)| value.split(','));
+    for value in values {
+        let value = value.trim();
+        if value.is_empty() || !value.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(INVALID);
+        }
+        let value = value.parse::<u64>().map_err(|_| INVALID)?;
+        match found {
+            Some(previous) if previous != value => {
+                return Ok(Some(ContentLength::Conflicting));
+            },
+            _ => found = Some(value),
+        }
+    }
+
+    Ok(found.map(ContentLength::Declared))
+}
+
+// Parser of chunked. Chunks, that are cut off, are the end of the body
+fn parse_chunked(input: &[u8]) -> IResult<&[u8], (Vec<u8>, bool)> {
     let mut body = Vec::new();
     let mut rest_buffer = input;
 
     loop {
         // 1) Reading chunk size (HEX) until CRLF
         let (rest, size_line) =
-            terminated(take_until(CRLF), tag(CRLF)).parse(rest_buffer)?;
-        let size_str = std::str::from_utf8(size_line)
-            .map_err(|_| ParserError::ErrorVerify.to_nom(input))?;
-        let size = usize::from_str_radix(size_str.trim(), 16)
-            .map_err(|_| ParserError::ErrorVerify.to_nom(input))?;
+            match terminated(take_until(CRLF), tag(CRLF)).parse(rest_buffer) {
+                Ok(value) => value,
+                Err(nom::Err::Incomplete(_)) => return Ok((END, (body, true))),
+                Err(err) => return Err(err),
+            };
+        let size_str = std::str::from_utf8(size_line).map_err(|_| {
+            ParserError::InvalidValue {
+                field: "chunk_size",
+            }
+            .to_nom(input)
+        })?;
+        let size = usize::from_str_radix(size_str.trim(), 16).map_err(|_| {
+            ParserError::InvalidValue {
+                field: "chunk_size",
+            }
+            .to_nom(input)
+        })?;
         rest_buffer = rest;
 
         // 2) 0 Size - End
@@ -164,14 +286,23 @@ fn parse_chunked(input: &[u8]) -> IResult<&[u8], Vec<u8>> {
             if let Ok((rest, _)) =
                 tag::<_, _, (&[u8], nom::error::ErrorKind)>(CRLF).parse(rest_buffer)
             {
-                return Ok((rest, body));
+                return Ok((rest, (body, false)));
             }
         }
 
         // 3) Reading chunk + CRLF
-        let (rest, chunk) = take(size).parse(rest_buffer)?;
-        let (rest, _) = tag(CRLF).parse(rest)?;
+        let Ok((rest, chunk)) =
+            take::<_, _, (&[u8], nom::error::ErrorKind)>(size).parse(rest_buffer)
+        else {
+            body.extend_from_slice(rest_buffer);
+            return Ok((END, (body, true)));
+        };
         body.extend_from_slice(chunk);
+        let (rest, _) = match tag(CRLF).parse(rest) {
+            Ok(value) => value,
+            Err(nom::Err::Incomplete(_)) => return Ok((END, (body, true))),
+            Err(err) => return Err(err),
+        };
         rest_buffer = rest;
     }
 }
@@ -182,14 +313,62 @@ pub fn is_protocol_default(port_source: u16, port_destination: u16) -> bool {
     port_source == PORT || port_destination == PORT
 }
 
+/// Starting line of the response or the request with the known method.
+pub fn looks_like(payload: &[u8]) -> bool {
+    const METHODS: [&[u8]; 9] = [
+        b"GET ",
+        b"POST ",
+        b"PUT ",
+        b"DELETE ",
+        b"HEAD ",
+        b"OPTIONS ",
+        b"TRACE ",
+        b"PATCH ",
+        b"CONNECT ",
+    ];
+
+    payload.starts_with(b"HTTP/1.")
+        || METHODS.iter().any(|method| payload.starts_with(method))
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum HTTP {
     Request(HTTPRequest),
     Response(HTTPResponse),
 }
 
+impl HTTP {
+    /// Body is cut off by the end of the bytes.
+    pub fn is_truncated(&self) -> bool {
+        match self {
+            Self::Request(request) => request.is_truncated,
+            Self::Response(response) => response.is_truncated,
+        }
+    }
+}
+
 pub type Header = (String, String);
 
+/// Why the body isn't kept, while the message is parsed.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum BodyAnomaly {
+    // Declared length (bytes) is over the limit
+    Omitted(u64),
+    // Content-Length headers differ, the message is suspicious (e.g. request smuggling)
+    ConflictingLength,
+}
+
+impl fmt::Display for BodyAnomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Omitted(declared) => {
+                write!(f, "Body omitted (declared {declared} bytes)")
+            },
+            Self::ConflictingLength => write!(f, "Conflicting Content-Length"),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct HTTPRequest {
     pub method: Methods,
@@ -197,6 +376,8 @@ pub struct HTTPRequest {
     pub version: String,
     pub headers: Vec<Header>,
     pub body: Vec<u8>,
+    pub is_truncated: bool,
+    pub body_anomaly: Option<BodyAnomaly>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -206,6 +387,8 @@ pub struct HTTPResponse {
     pub reason: String,
     pub headers: Vec<Header>,
     pub body: Vec<u8>,
+    pub is_truncated: bool,
+    pub body_anomaly: Option<BodyAnomaly>,
 }
 
 #[derive(Clone, Debug, Display, Serialize, Deserialize, PartialEq)]
@@ -236,7 +419,7 @@ impl TryFrom<&str> for Methods {
             "TRACE" => Ok(Methods::TRACE),
             "PATCH" => Ok(Methods::PATCH),
             "CONNECT" => Ok(Methods::CONNECT),
-            _ => Err(ParserError::ErrorVerify),
+            _ => Err(ParserError::InvalidValue { field: "method" }),
         }
     }
 }
@@ -258,9 +441,14 @@ impl From<HTTP> for HttpDto {
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct HTTPRequestDto {
+    #[serde(rename = "m")]
     pub method: Methods,
+    #[serde(rename = "t")]
     pub target: String,
+    #[serde(rename = "h", default, skip_serializing_if = "Vec::is_empty")]
     pub headers: Vec<Header>,
+    #[serde(rename = "b", default, skip_serializing_if = "Option::is_none")]
+    pub body_anomaly: Option<BodyAnomaly>,
 }
 
 impl From<HTTPRequest> for HTTPRequestDto {
@@ -269,15 +457,21 @@ impl From<HTTPRequest> for HTTPRequestDto {
             method: value.method,
             target: value.target,
             headers: value.headers,
+            body_anomaly: value.body_anomaly,
         }
     }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct HTTPResponseDto {
+    #[serde(rename = "s")]
     pub status_code: u16,
+    #[serde(rename = "r")]
     pub reason: String,
+    #[serde(rename = "h", default, skip_serializing_if = "Vec::is_empty")]
     pub headers: Vec<Header>,
+    #[serde(rename = "b", default, skip_serializing_if = "Option::is_none")]
+    pub body_anomaly: Option<BodyAnomaly>,
 }
 
 impl From<HTTPResponse> for HTTPResponseDto {
@@ -286,21 +480,80 @@ impl From<HTTPResponse> for HTTPResponseDto {
             status_code: value.status_code,
             reason: value.reason,
             headers: value.headers,
+            body_anomaly: value.body_anomaly,
+        }
+    }
+}
+
+impl FieldIter for HttpDto {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        match self {
+            Self::Request(request) => request.fields(),
+            Self::Response(response) => response.fields(),
         }
     }
 }
 
-#[cfg(test)]
+impl FieldIter for HTTPRequestDto {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let Self {
+            method,
+            target,
+            headers,
+            body_anomaly,
+        } = self;
+
+        vec![
+            ("method", method.to_string()),
+            ("target", target.clone()),
+            ("headers", headers_to_string(headers)),
+            ("body_anomaly", fields::optional(body_anomaly)),
+        ]
+    }
+}
+
+impl FieldIter for HTTPResponseDto {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let Self {
+            status_code,
+            reason,
+            headers,
+            body_anomaly,
+        } = self;
+
+        vec![
+            ("status_code", status_code.to_string()),
+            ("reason", reason.clone()),
+            ("headers", headers_to_string(headers)),
+            ("body_anomaly", fields::optional(body_anomaly)),
+        ]
+    }
+}
+
+fn headers_to_string(headers: &[Header]) -> String {
+    let headers = headers
+        .iter()
+        .map(|(name, value)| format!("{name}: {value}"))
+        .collect::<Vec<_>>();
+    fields::list(&headers)
+}
+
+#[cfg(all(test, feature = "ethernet", feature = "ipv4", feature = "tcp"))]
 mod tests {
     use super::*;
-    use crate::dto::frame::FrameHeader;
+    use crate::dto::frame::{FrameHeader, LinkType};
     use crate::parser::tests::FrameType;
     use crate::parser::tests::ProtocolParser;
+    use crate::parser::tests::tcp_layers;
+    use crate::parser::tests::{tcp_frame, tcp_metadata};
+    use crate::protocols::ProtocolId;
     use crate::protocols::ethernet::Ethernet;
     use crate::protocols::ethernet::ether_type::EtherType;
     use crate::protocols::ethernet::mac::MacAddress;
+    use crate::protocols::ip::checksum::ChecksumStatus;
     use crate::protocols::ip::protocol::IpNextLevelProtocol;
     use crate::protocols::ipv4::IPv4;
+    use crate::protocols::port_table::PortTable;
     use crate::protocols::tcp;
     use crate::protocols::tcp::TCP;
     use std::net::Ipv4Addr;
@@ -310,19 +563,10 @@ mod tests {
     fn test_http_request() {
         let hex_actual = "00 1F F3 3C E1 13 F8 1E DF E5 84 3A 08 00 45 00 02 09 C7 2A 40 00 40 06 2D 58 AC 10 0B 0C D8 22 B5 2D FC 45 00 50 E8 B7 30 BC EC B9 A3 7A 80 18 FF FF 1A 7E 00 00 01 01 08 0A 1A 7D 84 38 AA E7 7F C8 47 45 54 20 2F 20 48 54 54 50 2F 31 2E 31 0D 0A 48 6F 73 74 3A 20 73 6C 61 73 68 64 6F 74 2E 6F 72 67 0D 0A 55 73 65 72 2D 41 67 65 6E 74 3A 20 4D 6F 7A 69 6C 6C 61 2F 35 2E 30 20 28 4D 61 63 69 6E 74 6F 73 68 3B 20 55 3B 20 49 6E 74 65 6C 20 4D 61 63 20 4F 53 20 58 20 31 30 2E 36 3B 20 65 6E 2D 55 53 3B 20 72 76 3A 31 2E 39 2E 32 2E 36 29 20 47 65 63 6B 6F 2F 32 30 31 30 30 36 32 35 20 46 69 72 65 66 6F 78 2F 33 2E 36 2E 36 0D 0A 41 63 63 65 70 74 3A 20 74 65 78 74 2F 68 74 6D 6C 2C 61 70 70 6C 69 63 61 74 69 6F 6E 2F 78 68 74 6D 6C 2B 78 6D 6C 2C 61 70 70 6C 69 63 61 74 69 6F 6E 2F 78 6D 6C 3B 71 3D 30 2E 39 2C 2A 2F 2A 3B 71 3D 30 2E 38 0D 0A 41 63 63 65 70 74 2D 4C 61 6E 67 75 61 67 65 3A 20 65 6E 2D 75 73 2C 65 6E 3B 71 3D 30 2E 35 0D 0A 41 63 63 65 70 74 2D 45 6E 63 6F 64 69 6E 67 3A 20 67 7A 69 70 2C 64 65 66 6C 61 74 65 0D 0A 41 63 63 65 70 74 2D 43 68 61 72 73 65 74 3A 20 49 53 4F 2D 38 38 35 39 2D 31 2C 75 74 66 2D 38 3B 71 3D 30 2E 37 2C 2A 3B 71 3D 30 2E 37 0D 0A 4B 65 65 70 2D 41 6C 69 76 65 3A 20 31 31 35 0D 0A 43 6F 6E 6E 65 63 74 69 6F 6E 3A 20 6B 65 65 70 2D 61 6C 69 76 65 0D 0A 43 6F 6F 6B 69 65 3A 20 5F 5F 75 74 6D 61 3D 39 32 37 33 38 34 37 2E 31 38 36 38 36 30 35 31 37 36 2E 31 31 34 31 33 32 33 37 35 38 2E 31 31 35 31 30 33 39 38 38 34 2E 31 31 36 37 35 38 37 30 32 34 2E 34 0D 0A 43 61 63 68 65 2D 43 6F 6E 74 72 6F 6C 3A 20 6D 61 78 2D 61 67 65 3D 30 0D 0A 0D 0A".replace(" ", "");
         let frame = hex::decode(hex_actual).unwrap();
-        let header = FrameHeader {
-            tv_sec: 0,
-            tv_usec: 0,
-            caplen: 535,
-            len: 0,
-        };
+        let header = FrameHeader::new(535);
 
-        let parser = ProtocolParser::new(&pcap::Linktype(1), false);
-        let packet = pcap::Packet {
-            header: &pcap::PacketHeader::from(&header),
-            data: &frame,
-        };
-        let result = parser.process(packet);
+        let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+        let result = parser.process_bytes(&header, &frame);
         let metadata = match result {
             Some(value) => match value {
                 FrameType::Metadata(value) => value,
@@ -361,8 +605,10 @@ mod tests {
             time_to_live: 64,
             protocol_inner: IpNextLevelProtocol::TCP,
             checksum: 0x2d58,
+            checksum_status: ChecksumStatus::NotChecked,
             address_source: Ipv4Addr::from_str("172.16.11.12").unwrap(),
             address_destination: Ipv4Addr::from_str("216.34.181.45").unwrap(),
+            options: vec![],
         };
 
         assert_eq!(actual_ipv4, expected_ipv4);
@@ -391,6 +637,7 @@ mod tests {
             },
             window: 65535,
             checksum: 0x1a7e,
+            checksum_status: ChecksumStatus::NotChecked,
             urgent_pointer: 0,
             options: vec![
                 tcp::OptionData::NoOperation,
@@ -423,6 +670,8 @@ mod tests {
                 ("Cache-Control".to_string(), "max-age=0".to_string()),
             ],
             body: vec![],
+            is_truncated: false,
+            body_anomaly: None,
         };
 
         assert_eq!(actual_http, expected_http);
@@ -432,19 +681,14 @@ mod tests {
     fn test_http_response() {
         let hex_actual = "00 21 70 61 E1 F8 00 90 7F 3E 02 D0 08 00 45 00 00 C0 1F BF 40 00 7E 06 7E 5D AC 10 80 A9 AC 10 85 51 1F 4E E6 8E ED 44 84 44 F8 AA 17 EB 50 19 FF FF 28 1F 00 00 48 54 54 50 2F 31 2E 31 20 32 30 30 20 4F 4B 0D 0A 44 61 74 65 3A 20 54 75 65 2C 20 32 36 20 46 65 62 20 32 30 31 33 20 32 31 3A 35 37 3A 30 35 20 47 4D 54 0D 0A 53 65 72 76 65 72 3A 20 41 70 61 63 68 65 0D 0A 43 6F 6E 6E 65 63 74 69 6F 6E 3A 20 63 6C 6F 73 65 0D 0A 43 6F 6E 74 65 6E 74 2D 4C 65 6E 67 74 68 3A 20 32 0D 0A 43 6F 6E 74 65 6E 74 2D 54 79 70 65 3A 20 61 70 70 6C 69 63 61 74 69 6F 6E 2F 78 2D 6D 73 64 6F 77 6E 6C 6F 61 64 0D 0A 0D 0A 4F 4B".replace(" ", "");
         let frame = hex::decode(hex_actual).unwrap();
-        let header = FrameHeader {
-            tv_sec: 0,
-            tv_usec: 0,
-            caplen: 206,
-            len: 0,
-        };
-
-        let parser = ProtocolParser::new(&pcap::Linktype(1), false);
-        let packet = pcap::Packet {
-            header: &pcap::PacketHeader::from(&header),
-            data: &frame,
-        };
-        let result = parser.process(packet);
+        let header = FrameHeader::new(206);
+
+        // Server is on the non-default port 8014
+        let mut ports = PortTable::default();
+        ports.set(ProtocolId::HTTP, &[80, 8014]).unwrap();
+        let parser =
+            ProtocolParser::new(LinkType::ETHERNET, false).with_protocol_ports(ports);
+        let result = parser.process_bytes(&header, &frame);
         let metadata = match result {
             Some(value) => match value {
                 FrameType::Metadata(value) => value,
@@ -483,8 +727,10 @@ mod tests {
             time_to_live: 126,
             protocol_inner: IpNextLevelProtocol::TCP,
             checksum: 0x7e5d,
+            checksum_status: ChecksumStatus::NotChecked,
             address_source: Ipv4Addr::from_str("172.16.128.169").unwrap(),
             address_destination: Ipv4Addr::from_str("172.16.133.81").unwrap(),
+            options: vec![],
         };
 
         assert_eq!(actual_ipv4, expected_ipv4);
@@ -513,6 +759,7 @@ mod tests {
             },
             window: 65535,
             checksum: 0x281f,
+            checksum_status: ChecksumStatus::NotChecked,
             urgent_pointer: 0,
             options: vec![],
         };
@@ -542,8 +789,182 @@ mod tests {
                 ),
             ],
             body: vec![0x4F, 0x4B],
+            is_truncated: false,
+            body_anomaly: None,
         };
 
         assert_eq!(actual_http, expected_http);
     }
+
+    const RESPONSE_OK: &str = "HTTP/1.1 200 OK\r\n\
+        Content-Type: text/plain\r\n\
+        Content-Length: 2\r\n\
+        \r\n\
+        OK";
+    const RESPONSE_NOT_MODIFIED: &str = "HTTP/1.1 304 Not Modified\r\n\
+        ETag: \"33a64df5\"\r\n\
+        \r\n";
+
+    #[test]
+    fn test_http_two_responses() {
+        let payload = format!("{RESPONSE_OK}{RESPONSE_NOT_MODIFIED}");
+        let layers = tcp_layers(80, 49152, payload.as_bytes());
+        assert_eq!(layers.len(), 4);
+
+        let status_codes = layers[2..]
+            .iter()
+            .map(|layer| match layer {
+                ProtocolData::HTTP(HTTP::Response(value)) => value.status_code,
+                _ => panic!(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(status_codes, vec![200, 304]);
+
+        let actual_http = match layers[2].clone() {
+            ProtocolData::HTTP(HTTP::Response(value)) => value,
+            _ => panic!(),
+        };
+        assert_eq!(actual_http.body, b"OK".to_vec());
+    }
+
+    #[test]
+    fn test_http_on_non_standard_port() {
+        // Recognized by the signature, 8081 isn't the HTTP port
+        let layers = tcp_layers(8081, 49152, RESPONSE_OK.as_bytes());
+        assert_eq!(layers.len(), 3);
+
+        let actual_http = match layers[2].clone() {
+            ProtocolData::HTTP(HTTP::Response(value)) => value,
+            _ => panic!(),
+        };
+        assert_eq!(actual_http.status_code, 200);
+    }
+
+    #[test]
+    fn test_http_response_with_partial_request() {
+        // Start of the next request is sent in the following segment
+        let payload = format!("{RESPONSE_OK}GET /index.html HTTP/1.1\r\nHost: exa");
+        let layers = tcp_layers(80, 49152, payload.as_bytes());
+        assert_eq!(layers.len(), 3);
+
+        let actual_http = match layers[2].clone() {
+            ProtocolData::HTTP(HTTP::Response(value)) => value,
+            _ => panic!(),
+        };
+        assert_eq!(actual_http.status_code, 200);
+    }
+
+    #[test]
+    fn test_http_truncated_response() {
+        // Body is cut off by the snapshot length
+        let payload = "HTTP/1.1 200 OK\r\n\
+            Content-Type: text/plain\r\n\
+            Content-Length: 10\r\n\
+            \r\n\
+            0123456789";
+        let frame = tcp_frame(80, 49152, payload.as_bytes());
+        let captured = &frame[..frame.len() - 6];
+        let parser = ProtocolParser::new(LinkType::IPV4, false);
+
+        let header = FrameHeader::captured(captured.len(), frame.len());
+        let metadata = match parser.process_bytes(&header, captured) {
+            Some(FrameType::Metadata(value)) => value,
+            _ => panic!(),
+        };
+        assert!(metadata.is_truncated);
+
+        let actual_http = match metadata.layers[2].clone() {
+            ProtocolData::HTTP(HTTP::Response(value)) => value,
+            _ => panic!(),
+        };
+        assert!(actual_http.is_truncated);
+        assert_eq!(actual_http.status_code, 200);
+        assert_eq!(actual_http.headers.len(), 2);
+        assert_eq!(actual_http.body, b"0123".to_vec());
+
+        // Same bytes aren't HTTP, if they are the whole frame
+        let cut_payload = &payload.as_bytes()[..payload.len() - 6];
+        match tcp_metadata(&parser, 80, 49152, cut_payload) {
+            Some(FrameType::Metadata(metadata)) => assert_eq!(metadata.layers.len(), 2),
+            _ => panic!(),
+        }
+    }
+
+    fn response(headers: &str, body: &str) -> String {
+        format!("HTTP/1.1 200 OK\r\n{headers}\r\n{body}")
+    }
+
+    fn http_response(layer: &ProtocolData) -> &HTTPResponse {
+        match layer {
+            ProtocolData::HTTP(HTTP::Response(value)) => value,
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn test_http_oversized_body() {
+        // Declared 4 GiB on the small segment
+        let payload = response("Content-Length: 4294967296\r\n", "0123456789");
+        let layers = tcp_layers(80, 49152, payload.as_bytes());
+        let actual_http = http_response(&layers[2]);
+        assert!(actual_http.body.is_empty());
+        assert!(!actual_http.is_truncated);
+        assert_eq!(
+            actual_http.body_anomaly,
+            Some(BodyAnomaly::Omitted(4_294_967_296))
+        );
+
+        // Omitted body is still skipped, so the next message is found
+        let parser = ProtocolParser::new(LinkType::IPV4, false).with_http_body_limit(1);
+        let payload = format!("{RESPONSE_OK}{RESPONSE_NOT_MODIFIED}");
+        let layers = match tcp_metadata(&parser, 80, 49152, payload.as_bytes()) {
+            Some(FrameType::Metadata(metadata)) => metadata.layers,
+            _ => panic!(),
+        };
+        assert_eq!(layers.len(), 4);
+        assert_eq!(
+            http_response(&layers[2]).body_anomaly,
+            Some(BodyAnomaly::Omitted(2))
+        );
+        assert_eq!(http_response(&layers[3]).status_code, 304);
+    }
+
+    #[test]
+    fn test_http_conflicting_length() {
+        let payload = response("Content-Length: 2\r\nContent-Length: 10\r\n", "OK");
+        let layers = tcp_layers(80, 49152, payload.as_bytes());
+        let actual_http = http_response(&layers[2]);
+        assert!(actual_http.body.is_empty());
+        assert_eq!(
+            actual_http.body_anomaly,
+            Some(BodyAnomaly::ConflictingLength)
+        );
+
+        // Same values are valid, repeated or as the list
+        for headers in [
+            "Content-Length: 2\r\ncontent-length: 2\r\n",
+            "Content-Length: 2, 2\r\n",
+            "Content-Length:  2 \r\n",
+        ] {
+            let payload = response(headers, "OK");
+            let layers = tcp_layers(80, 49152, payload.as_bytes());
+            let actual_http = http_response(&layers[2]);
+            assert_eq!(actual_http.body, b"OK".to_vec(), "{headers}");
+            assert_eq!(actual_http.body_anomaly, None);
+        }
+    }
+
+    #[test]
+    fn test_http_invalid_length() {
+        let parser = ProtocolParser::new(LinkType::IPV4, false);
+        for value in ["abc", "-1", "+2", "0x02", "2 2", "99999999999999999999999"] {
+            let payload = response(&format!("Content-Length: {value}\r\n"), "OK");
+            match tcp_metadata(&parser, 80, 49152, payload.as_bytes()) {
+                Some(FrameType::Metadata(metadata)) => {
+                    assert_eq!(metadata.layers.len(), 2, "{value}")
+                },
+                _ => panic!(),
+            }
+        }
+    }
 }
@@ -1,6 +1,6 @@
-use crate::parser::ParserError;
+use crate::dto::fields::FieldIter;
+use crate::parser::{IResult, ParserError};
 use crate::protocols::ProtocolData;
-use nom::IResult;
 use nom::number::{be_u8, be_u16};
 use nom::{Finish, Parser};
 use num_enum::TryFromPrimitive;
@@ -92,7 +92,9 @@ pub enum MessageType {
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct ICMPv4Dto {
+    #[serde(rename = "t")]
     pub message_type: MessageType,
+    #[serde(rename = "c")]
     pub code: u8,
 }
 
@@ -105,15 +107,27 @@ impl From<ICMPv4> for ICMPv4Dto {
     }
 }
 
-#[cfg(test)]
+impl FieldIter for ICMPv4Dto {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let Self { message_type, code } = self;
+
+        vec![
+            ("message_type", message_type.to_string()),
+            ("code", code.to_string()),
+        ]
+    }
+}
+
+#[cfg(all(test, feature = "ethernet", feature = "ipv4"))]
 mod tests {
     use super::*;
-    use crate::dto::frame::FrameHeader;
+    use crate::dto::frame::{FrameHeader, LinkType};
     use crate::parser::tests::FrameType;
     use crate::parser::tests::ProtocolParser;
     use crate::protocols::ethernet::Ethernet;
     use crate::protocols::ethernet::ether_type::EtherType;
     use crate::protocols::ethernet::mac::MacAddress;
+    use crate::protocols::ip::checksum::ChecksumStatus;
     use crate::protocols::ip::protocol::IpNextLevelProtocol;
     use crate::protocols::ipv4::IPv4;
     use std::net::Ipv4Addr;
@@ -122,19 +136,10 @@ mod tests {
     fn test_icmpv4() {
         let hex_actual = "00 1A 8C 10 AD 30 00 1E 68 51 4F A9 08 00 45 00 00 3C 7E 74 00 00 20 01 EB DF AC 10 FF 01 43 D7 41 84 08 00 40 08 00 01 0F 55 41 42 43 44 45 46 47 48 49 4A 4B 4C 4D 4E 4F 50 51 52 53 54 55 56 57 41 42 43 44 45 46 47 48 49".replace(" ", "");
         let frame = hex::decode(hex_actual).unwrap();
-        let header = FrameHeader {
-            tv_sec: 0,
-            tv_usec: 0,
-            caplen: 74,
-            len: 0,
-        };
+        let header = FrameHeader::new(74);
 
-        let parser = ProtocolParser::new(&pcap::Linktype(1), false);
-        let packet = pcap::Packet {
-            header: &pcap::PacketHeader::from(&header),
-            data: &frame,
-        };
-        let result = parser.process(packet);
+        let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+        let result = parser.process_bytes(&header, &frame);
         let metadata = match result {
             Some(value) => match value {
                 FrameType::Metadata(value) => value,
@@ -173,8 +178,10 @@ mod tests {
             time_to_live: 32,
             protocol_inner: IpNextLevelProtocol::ICMP,
             checksum: 0xebdf,
+            checksum_status: ChecksumStatus::NotChecked,
             address_source: Ipv4Addr::new(172, 16, 255, 1),
             address_destination: Ipv4Addr::new(67, 215, 65, 132),
+            options: vec![],
         };
 
         assert_eq!(actual_ipv4, expected_ipv4);
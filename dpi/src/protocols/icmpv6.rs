@@ -1,14 +1,27 @@
-use crate::parser::ParserError;
+use crate::dto::fields;
+use crate::dto::fields::FieldIter;
+use crate::parser::{IResult, ParserError};
 use crate::protocols::ProtocolData;
-use nom::IResult;
-use nom::number::{be_u8, be_u16};
+use crate::protocols::ethernet::mac;
+use crate::protocols::ethernet::mac::MacAddress;
+use nom::bytes::take;
+use nom::number::{be_u8, be_u16, be_u32};
 use nom::{Finish, Parser};
 use num_enum::TryFromPrimitive;
 use serde::{Deserialize, Serialize};
+use std::fmt::Formatter;
+use std::net::Ipv6Addr;
 use strum_macros::Display;
 
 // ICMPv6 Protocol
 // RFC 4443: https://datatracker.ietf.org/doc/html/rfc4443
+// Router Advertisement, RFC 4861: https://datatracker.ietf.org/doc/html/rfc4861#section-4.2
+
+// Neighbor Discovery option types
+const OPTION_SOURCE_LINK_ADDRESS: u8 = 1;
+const OPTION_PREFIX_INFORMATION: u8 = 3;
+// Option length is counted in units of 8 bytes, including the type & length
+const OPTION_LENGTH_UNIT: usize = 8;
 
 pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
     // Message type. 1 byte
@@ -94,31 +107,182 @@ pub enum MessageType {
     ReservedExpansionInfoMessages = 255,
 }
 
+/// Router announces itself & the prefixes of the link, that the hosts configure addresses from.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct RouterAdvertisement {
+    // Seconds. Zero, if the router isn't a default one
+    pub router_lifetime: u16,
+    // Source link-layer address option, it's omitted by the load-balancing routers
+    pub link_address: Option<MacAddress>,
+    pub prefixes: Vec<PrefixInformation>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct PrefixInformation {
+    pub prefix: Ipv6Addr,
+    pub length: u8,
+    // Seconds, u32::MAX is infinity
+    pub valid_lifetime: u32,
+    pub preferred_lifetime: u32,
+}
+
+impl RouterAdvertisement {
+    fn parse(data: &[u8]) -> IResult<&[u8], Self> {
+        // Current hop limit & flags. 1 byte each
+        let (rest, _) = take(2usize).parse(data)?;
+
+        // Router lifetime. 2 bytes
+        let (rest, router_lifetime) = be_u16().parse(rest)?;
+
+        // Reachable time & retransmission timer. 4 bytes each
+        let (mut rest, _) = take(8usize).parse(rest)?;
+
+        let mut link_address = None;
+        let mut prefixes = vec![];
+        while !rest.is_empty() {
+            let (after_type, option_type) = be_u8().parse(rest)?;
+            let (after_length, length) = be_u8().parse(after_type)?;
+            // Zero length would loop forever, RFC says to discard the message
+            let length = (usize::from(length) * OPTION_LENGTH_UNIT)
+                .checked_sub(2)
+                .ok_or_else(|| ParserError::ErrorVerify.to_nom(rest))?;
+            let (after_option, value) = take(length).parse(after_length)?;
+
+            match option_type {
+                OPTION_SOURCE_LINK_ADDRESS => {
+                    let (_, address) = mac::parse(value)?;
+                    link_address = Some(address);
+                },
+                OPTION_PREFIX_INFORMATION => {
+                    let (_, prefix) = PrefixInformation::parse(value)?;
+                    prefixes.push(prefix);
+                },
+                _ => {},
+            }
+            rest = after_option;
+        }
+
+        Ok((
+            rest,
+            Self {
+                router_lifetime,
+                link_address,
+                prefixes,
+            },
+        ))
+    }
+}
+
+impl PrefixInformation {
+    fn parse(value: &[u8]) -> IResult<&[u8], Self> {
+        // Prefix length. 1 byte
+        let (rest, length) = be_u8().parse(value)?;
+
+        // On-link & autonomous flags. 1 byte
+        let (rest, _) = be_u8().parse(rest)?;
+
+        // Valid & preferred lifetimes. 4 bytes each
+        let (rest, valid_lifetime) = be_u32().parse(rest)?;
+        let (rest, preferred_lifetime) = be_u32().parse(rest)?;
+
+        // Reserved. 4 bytes
+        let (rest, _) = take(4usize).parse(rest)?;
+
+        // Prefix. 16 bytes
+        let (rest, prefix) = take(16usize).parse(rest)?;
+        let prefix = <[u8; 16]>::try_from(prefix)
+            .map_err(|_| ParserError::ErrorVerify.to_nom(value))?;
+
+        Ok((
+            rest,
+            Self {
+                prefix: Ipv6Addr::from(prefix),
+                length,
+                valid_lifetime,
+                preferred_lifetime,
+            },
+        ))
+    }
+}
+
+impl std::fmt::Display for PrefixInformation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.prefix, self.length)
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct ICMPv6Dto {
+    #[serde(rename = "t")]
     pub message_type: MessageType,
+    #[serde(rename = "c")]
     pub code: u8,
+    // Missing in the frames of the older servers
+    #[serde(rename = "ra", default, skip_serializing_if = "Option::is_none")]
+    pub router_advertisement: Option<RouterAdvertisement>,
 }
 
 impl From<ICMPv6> for ICMPv6Dto {
     fn from(value: ICMPv6) -> Self {
+        // Malformed advertisement is kept as the plain message
+        let router_advertisement = match value.message_type {
+            MessageType::RouterAdvertisement => RouterAdvertisement::parse(&value.data)
+                .ok()
+                .map(|(_, advertisement)| advertisement),
+            _ => None,
+        };
+
         Self {
             message_type: value.message_type,
             code: value.code,
+            router_advertisement,
         }
     }
 }
 
-#[cfg(test)]
+impl FieldIter for ICMPv6Dto {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let Self {
+            message_type,
+            code,
+            router_advertisement,
+        } = self;
+
+        let (router_lifetime, link_address, prefixes) = match router_advertisement {
+            Some(advertisement) => (
+                advertisement.router_lifetime.to_string(),
+                fields::optional(&advertisement.link_address),
+                fields::list(&advertisement.prefixes),
+            ),
+            None => (
+                fields::NONE.to_string(),
+                fields::NONE.to_string(),
+                fields::NONE.to_string(),
+            ),
+        };
+
+        vec![
+            ("message_type", message_type.to_string()),
+            ("code", code.to_string()),
+            ("router_lifetime", router_lifetime),
+            ("link_address", link_address),
+            ("prefixes", prefixes),
+        ]
+    }
+}
+
+#[cfg(all(test, feature = "ipv6"))]
 mod tests {
-    use crate::dto::frame::FrameHeader;
+    use crate::dto::frame::{FrameHeader, LinkType};
     use crate::parser::tests::FrameType;
     use crate::parser::tests::ProtocolParser;
     use crate::protocols::ProtocolData;
     use crate::protocols::ethernet::Ethernet;
     use crate::protocols::ethernet::ether_type::EtherType;
     use crate::protocols::ethernet::mac::MacAddress;
-    use crate::protocols::icmpv6::{ICMPv6, MessageType};
+    use crate::protocols::icmpv6::{
+        ICMPv6, ICMPv6Dto, MessageType, PrefixInformation, RouterAdvertisement,
+    };
     use crate::protocols::ip::protocol::IpNextLevelProtocol;
     use crate::protocols::ipv6::IPv6;
     use std::net::Ipv6Addr;
@@ -128,19 +292,10 @@ mod tests {
     fn test_icmpv6_advertisement() {
         let hex_actual = "00 60 97 07 69 EA 00 00 86 05 80 DA 86 DD 60 00 00 00 00 18 3A FF FE 80 00 00 00 00 00 00 02 00 86 FF FE 05 80 DA FE 80 00 00 00 00 00 00 02 60 97 FF FE 07 69 EA 88 00 2A 18 40 00 00 00 FE 80 00 00 00 00 00 00 02 00 86 FF FE 05 80 DA".replace(" ", "");
         let frame = hex::decode(hex_actual).unwrap();
-        let header = FrameHeader {
-            tv_sec: 0,
-            tv_usec: 0,
-            caplen: 78,
-            len: 0,
-        };
+        let header = FrameHeader::new(78);
 
-        let parser = ProtocolParser::new(&pcap::Linktype(1), false);
-        let packet = pcap::Packet {
-            header: &pcap::PacketHeader::from(&header),
-            data: &frame,
-        };
-        let result = parser.process(packet);
+        let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+        let result = parser.process_bytes(&header, &frame);
         let metadata = match result {
             Some(value) => match value {
                 FrameType::Metadata(value) => value,
@@ -176,6 +331,8 @@ mod tests {
             hop_limit: 255,
             address_source: Ipv6Addr::from_str("fe80::200:86ff:fe05:80da").unwrap(),
             address_destination: Ipv6Addr::from_str("fe80::260:97ff:fe07:69ea").unwrap(),
+            extension_headers: vec![],
+            protocol_inner: IpNextLevelProtocol::Ipv6Icmp,
         };
 
         assert_eq!(actual_ipv6, expected_ipv6);
@@ -202,19 +359,10 @@ mod tests {
     fn test_icmpv6_ping() {
         let hex_actual = "00 00 86 05 80 DA 00 60 97 07 69 EA 86 DD 60 00 00 00 00 10 3A 40 3F FE 05 07 00 00 00 01 02 60 97 FF FE 07 69 EA 3F FE 05 07 00 00 00 01 02 00 86 FF FE 05 80 DA 81 00 1E 76 7B 20 00 00 19 C9 E7 36 44 E0 0B 00".replace(" ", "");
         let frame = hex::decode(hex_actual).unwrap();
-        let header = FrameHeader {
-            tv_sec: 0,
-            tv_usec: 0,
-            caplen: 70,
-            len: 0,
-        };
+        let header = FrameHeader::new(70);
 
-        let parser = ProtocolParser::new(&pcap::Linktype(1), false);
-        let packet = pcap::Packet {
-            header: &pcap::PacketHeader::from(&header),
-            data: &frame,
-        };
-        let result = parser.process(packet);
+        let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+        let result = parser.process_bytes(&header, &frame);
         let metadata = match result {
             Some(value) => match value {
                 FrameType::Metadata(value) => value,
@@ -252,6 +400,8 @@ mod tests {
                 .unwrap(),
             address_destination: Ipv6Addr::from_str("3ffe:507:0:1:200:86ff:fe05:80da")
                 .unwrap(),
+            extension_headers: vec![],
+            protocol_inner: IpNextLevelProtocol::Ipv6Icmp,
         };
 
         assert_eq!(actual_ipv6, expected_ipv6);
@@ -272,4 +422,38 @@ mod tests {
 
         assert_eq!(actual_icmp, expected_icmp);
     }
+
+    #[test]
+    fn test_icmpv6_router_advertisement() {
+        let hex_actual = "40 00 07 08 00 00 00 00 00 00 00 00 01 01 00 11 22 33 44 55 03 04 40 C0 00 27 8D 00 00 09 3A 80 00 00 00 00 20 01 0D B8 00 01 00 00 00 00 00 00 00 00 00 00".replace(" ", "");
+        let icmp = ICMPv6 {
+            message_type: MessageType::RouterAdvertisement,
+            code: 0,
+            checksum: 0,
+            data: hex::decode(hex_actual).unwrap(),
+        };
+
+        let dto = ICMPv6Dto::from(icmp.clone());
+        let expected = RouterAdvertisement {
+            router_lifetime: 1800,
+            link_address: Some(MacAddress::try_from("00:11:22:33:44:55").unwrap()),
+            prefixes: vec![PrefixInformation {
+                prefix: Ipv6Addr::from_str("2001:db8:1::").unwrap(),
+                length: 64,
+                valid_lifetime: 2592000,
+                preferred_lifetime: 604800,
+            }],
+        };
+        assert_eq!(dto.router_advertisement, Some(expected));
+
+        // Option is cut off
+        let mut truncated = icmp.clone();
+        truncated.data.truncate(40);
+        assert_eq!(ICMPv6Dto::from(truncated).router_advertisement, None);
+
+        // Zero option length
+        let mut zero = icmp;
+        zero.data[13] = 0;
+        assert_eq!(ICMPv6Dto::from(zero).router_advertisement, None);
+    }
 }
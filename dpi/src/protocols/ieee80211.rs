@@ -0,0 +1,564 @@
+use crate::dto::fields::{self, FieldIter};
+use crate::dto::frame::FrameMetadata;
+use crate::dto::wire;
+use crate::parser::{IResult, ParserError};
+use crate::protocols::ethernet::ether_type::EtherType;
+use crate::protocols::ethernet::mac::MacAddress;
+use crate::protocols::{ProtocolData, ProtocolId, enabled, ethernet};
+use nom::Parser;
+use nom::bytes::take;
+use nom::number::{be_u8, be_u16, le_u16};
+use serde::{Deserialize, Serialize};
+use strum_macros::Display;
+
+// IEEE 802.11 MAC frame
+// IEEE Std 802.11-2020, 9.2 "MAC frame formats"
+// Data frames are followed by LLC/SNAP (RFC 1042) with the EtherType of the payload.
+// Encrypted payloads & aggregated MSDUs (A-MSDU) are not parsed.
+
+pub const VERSION: u8 = 0;
+pub const FRAME_CONTROL_LENGTH: usize = 2;
+pub const DURATION_LENGTH: usize = 2;
+pub const SEQUENCE_CONTROL_LENGTH: usize = 2;
+pub const HT_CONTROL_LENGTH: usize = 4;
+
+// Frame Control flags
+pub const FLAG_TO_DS: u8 = 0x01;
+pub const FLAG_FROM_DS: u8 = 0x02;
+pub const FLAG_RETRY: u8 = 0x08;
+pub const FLAG_PROTECTED: u8 = 0x40;
+pub const FLAG_ORDER: u8 = 0x80;
+
+// QoS Control field
+pub const QOS_AMSDU_PRESENT: u16 = 0x0080;
+
+// LLC: DSAP, SSAP, Control. SNAP: OUI
+pub const LLC_SNAP_HEADER: &[u8] = &[0xAA, 0xAA, 0x03, 0x00, 0x00, 0x00];
+
+// Management frame body: Timestamp, Beacon Interval, Capability Information
+pub const BEACON_FIXED_LENGTH: usize = 12;
+// Management frame body: Capability Information, Listen Interval
+pub const ASSOCIATION_FIXED_LENGTH: usize = 4;
+pub const ELEMENT_SSID: u8 = 0;
+pub const ELEMENT_DS_PARAMETER_SET: u8 = 3;
+
+pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
+    let (rest, frame_control) = be_u8().parse(bytes)?;
+    let (rest, flags) = be_u8().parse(rest)?;
+
+    let version = frame_control & 0b11;
+    if version != VERSION {
+        return Err(ParserError::ErrorVerify.to_nom(bytes));
+    }
+    let frame_type = FrameType::from((frame_control >> 2) & 0b11);
+    let subtype = Subtype::new(&frame_type, frame_control >> 4);
+
+    let (rest, _duration) = take(DURATION_LENGTH).parse(rest)?;
+    let (rest, receiver) = ethernet::mac::parse(rest)?;
+
+    let mut frame = IEEE80211 {
+        frame_type,
+        subtype,
+        flags,
+        receiver: receiver.clone(),
+        transmitter: None,
+        source: None,
+        destination: receiver,
+        bssid: None,
+        ether_type: None,
+        ssid: None,
+        channel: None,
+    };
+
+    match frame.frame_type {
+        FrameType::Control => {
+            if frame.subtype.has_transmitter() {
+                let (_, transmitter) = ethernet::mac::parse(rest)?;
+                frame.source = Some(transmitter.clone());
+                frame.transmitter = Some(transmitter);
+            }
+
+            // Control frames don't carry payload
+            let rest: &[u8] = &[];
+            Ok((rest, ProtocolData::IEEE80211(frame)))
+        },
+        FrameType::Management => {
+            let (rest, transmitter) = ethernet::mac::parse(rest)?;
+            let (rest, bssid) = ethernet::mac::parse(rest)?;
+            let (rest, _sequence) = take(SEQUENCE_CONTROL_LENGTH).parse(rest)?;
+            let (body, _) = match frame.has_flag(FLAG_ORDER) {
+                true => take(HT_CONTROL_LENGTH).parse(rest)?,
+                false => (rest, &[][..]),
+            };
+
+            frame.source = Some(transmitter.clone());
+            frame.transmitter = Some(transmitter);
+            frame.bssid = Some(bssid);
+            if !frame.is_protected() {
+                frame.parse_management_body(body);
+            }
+
+            // Elements are consumed
+            let rest: &[u8] = &[];
+            Ok((rest, ProtocolData::IEEE80211(frame)))
+        },
+        FrameType::Data => {
+            let (rest, address_2) = ethernet::mac::parse(rest)?;
+            let (rest, address_3) = ethernet::mac::parse(rest)?;
+            let (rest, _sequence) = take(SEQUENCE_CONTROL_LENGTH).parse(rest)?;
+
+            let to_ds = frame.has_flag(FLAG_TO_DS);
+            let from_ds = frame.has_flag(FLAG_FROM_DS);
+            let (rest, address_4) = match to_ds && from_ds {
+                true => {
+                    ethernet::mac::parse(rest).map(|(rest, mac)| (rest, Some(mac)))?
+                },
+                false => (rest, None),
+            };
+
+            let receiver = frame.receiver.clone();
+            let (source, destination, bssid) = match (to_ds, from_ds, address_4) {
+                (false, false, _) => (address_2.clone(), receiver, Some(address_3)),
+                (true, false, _) => (address_2.clone(), address_3, Some(receiver)),
+                (false, true, _) => (address_3, receiver, Some(address_2.clone())),
+                // Wireless Distribution System (4-address frame) has no BSSID
+                (true, true, Some(address_4)) => (address_4, address_3, None),
+                (true, true, None) => return Err(ParserError::ErrorVerify.to_nom(bytes)),
+            };
+            frame.source = Some(source);
+            frame.destination = destination;
+            frame.transmitter = Some(address_2);
+            frame.bssid = bssid;
+
+            parse_data_body(rest, frame)
+        },
+        FrameType::Extension => Err(ParserError::ErrorVerify.to_nom(bytes)),
+    }
+}
+
+fn parse_data_body(bytes: &[u8], mut frame: IEEE80211) -> IResult<&[u8], ProtocolData> {
+    let is_qos = frame.subtype.is_qos();
+
+    let (rest, qos_control) = match is_qos {
+        true => le_u16()
+            .parse(bytes)
+            .map(|(rest, value)| (rest, Some(value)))?,
+        false => (bytes, None),
+    };
+    let (rest, _) = match is_qos && frame.has_flag(FLAG_ORDER) {
+        true => take(HT_CONTROL_LENGTH).parse(rest)?,
+        false => (rest, &[][..]),
+    };
+
+    // Null frames
+    if !frame.subtype.has_payload() {
+        let rest: &[u8] = &[];
+        return Ok((rest, ProtocolData::IEEE80211(frame)));
+    }
+
+    // Payload can't be passed further: encrypted or contains several subframes
+    let is_aggregated = qos_control.is_some_and(|value| value & QOS_AMSDU_PRESENT != 0);
+    if frame.is_protected() || is_aggregated {
+        return Ok((rest, ProtocolData::IEEE80211(frame)));
+    }
+
+    let rest = match rest.strip_prefix(LLC_SNAP_HEADER) {
+        Some(snap) => {
+            let (payload, ether_type) = be_u16().parse(snap)?;
+            frame.ether_type = EtherType::try_from(ether_type).ok();
+            payload
+        },
+        None => rest,
+    };
+
+    Ok((rest, ProtocolData::IEEE80211(frame)))
+}
+
+pub fn best_children(metadata: &FrameMetadata) -> Option<ProtocolId> {
+    let frame = metadata.layers.iter().find_map(|layer| match layer {
+        ProtocolData::IEEE80211(value) => Some(value),
+        _ => None,
+    })?;
+
+    match frame.ether_type {
+        Some(EtherType::Arp | EtherType::ArpReverse) => enabled::Arp,
+        Some(EtherType::Ipv4) => enabled::IPv4,
+        Some(EtherType::Ipv6) => enabled::IPv6,
+        _ => None,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct IEEE80211 {
+    pub frame_type: FrameType,
+    pub subtype: Subtype,
+    pub flags: u8,
+
+    // Address 1
+    pub receiver: MacAddress,
+    // Address 2, absent in some control frames (CTS, ACK)
+    pub transmitter: Option<MacAddress>,
+    pub source: Option<MacAddress>,
+    pub destination: MacAddress,
+    pub bssid: Option<MacAddress>,
+
+    // Data frames with LLC/SNAP header
+    pub ether_type: Option<EtherType>,
+
+    // Beacons, probes & association requests
+    pub ssid: Option<String>,
+    pub channel: Option<u8>,
+}
+
+impl IEEE80211 {
+    pub fn has_flag(&self, flag: u8) -> bool {
+        self.flags & flag != 0
+    }
+
+    pub fn is_protected(&self) -> bool {
+        self.has_flag(FLAG_PROTECTED)
+    }
+
+    fn parse_management_body(&mut self, body: &[u8]) {
+        let fixed_length = match self.subtype {
+            Subtype::Beacon | Subtype::ProbeResponse => BEACON_FIXED_LENGTH,
+            Subtype::ProbeRequest => 0,
+            Subtype::AssociationRequest => ASSOCIATION_FIXED_LENGTH,
+            _ => return,
+        };
+        let Some(mut elements) = body.get(fixed_length..) else {
+            return;
+        };
+
+        // Truncated elements are ignored
+        while let [id, length, rest @ ..] = elements {
+            let Some((value, next)) = rest.split_at_checked(usize::from(*length)) else {
+                break;
+            };
+            match (*id, value) {
+                (ELEMENT_SSID, _) => {
+                    self.ssid = Some(String::from_utf8_lossy(value).to_string())
+                },
+                (ELEMENT_DS_PARAMETER_SET, [channel]) => self.channel = Some(*channel),
+                _ => {},
+            }
+            elements = next;
+        }
+    }
+}
+
+#[derive(Clone, Debug, Display, Serialize, Deserialize, PartialEq)]
+pub enum FrameType {
+    Management,
+    Control,
+    Data,
+    Extension,
+}
+
+impl From<u8> for FrameType {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::Management,
+            1 => Self::Control,
+            2 => Self::Data,
+            _ => Self::Extension,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Display, Serialize, Deserialize, PartialEq)]
+pub enum Subtype {
+    // Management
+    #[strum(to_string = "Association Request")]
+    AssociationRequest,
+    #[strum(to_string = "Association Response")]
+    AssociationResponse,
+    #[strum(to_string = "Reassociation Request")]
+    ReassociationRequest,
+    #[strum(to_string = "Reassociation Response")]
+    ReassociationResponse,
+    #[strum(to_string = "Probe Request")]
+    ProbeRequest,
+    #[strum(to_string = "Probe Response")]
+    ProbeResponse,
+    Beacon,
+    Disassociation,
+    Authentication,
+    Deauthentication,
+    Action,
+
+    // Control
+    #[strum(to_string = "Block Ack Request")]
+    BlockAckRequest,
+    #[strum(to_string = "Block Ack")]
+    BlockAck,
+    #[strum(to_string = "PS-Poll")]
+    PsPoll,
+    #[strum(to_string = "RTS")]
+    Rts,
+    #[strum(to_string = "CTS")]
+    Cts,
+    #[strum(to_string = "ACK")]
+    Ack,
+    #[strum(to_string = "CF-End")]
+    CfEnd,
+
+    // Data
+    Data,
+    Null,
+    #[strum(to_string = "QoS Data")]
+    QosData,
+    #[strum(to_string = "QoS Null")]
+    QosNull,
+
+    #[strum(to_string = "Unknown ({0})")]
+    Unknown(u8),
+}
+
+impl Subtype {
+    pub fn new(frame_type: &FrameType, value: u8) -> Self {
+        match (frame_type, value) {
+            (FrameType::Management, 0) => Self::AssociationRequest,
+            (FrameType::Management, 1) => Self::AssociationResponse,
+            (FrameType::Management, 2) => Self::ReassociationRequest,
+            (FrameType::Management, 3) => Self::ReassociationResponse,
+            (FrameType::Management, 4) => Self::ProbeRequest,
+            (FrameType::Management, 5) => Self::ProbeResponse,
+            (FrameType::Management, 8) => Self::Beacon,
+            (FrameType::Management, 10) => Self::Disassociation,
+            (FrameType::Management, 11) => Self::Authentication,
+            (FrameType::Management, 12) => Self::Deauthentication,
+            (FrameType::Management, 13) => Self::Action,
+
+            (FrameType::Control, 8) => Self::BlockAckRequest,
+            (FrameType::Control, 9) => Self::BlockAck,
+            (FrameType::Control, 10) => Self::PsPoll,
+            (FrameType::Control, 11) => Self::Rts,
+            (FrameType::Control, 12) => Self::Cts,
+            (FrameType::Control, 13) => Self::Ack,
+            (FrameType::Control, 14) => Self::CfEnd,
+
+            (FrameType::Data, 0) => Self::Data,
+            (FrameType::Data, 4) => Self::Null,
+            (FrameType::Data, 8) => Self::QosData,
+            (FrameType::Data, 12) => Self::QosNull,
+
+            (_, value) => Self::Unknown(value),
+        }
+    }
+
+    pub fn has_transmitter(&self) -> bool {
+        matches!(
+            self,
+            Self::BlockAckRequest
+                | Self::BlockAck
+                | Self::PsPoll
+                | Self::Rts
+                | Self::CfEnd
+        )
+    }
+
+    pub fn is_qos(&self) -> bool {
+        matches!(self, Self::QosData | Self::QosNull)
+    }
+
+    pub fn has_payload(&self) -> bool {
+        matches!(self, Self::Data | Self::QosData)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct IEEE80211Dto {
+    #[serde(rename = "ft")]
+    pub frame_type: FrameType,
+    #[serde(rename = "st")]
+    pub subtype: Subtype,
+    #[serde(rename = "p", default, skip_serializing_if = "wire::is_default")]
+    pub is_protected: bool,
+    #[serde(rename = "r", default, skip_serializing_if = "wire::is_default")]
+    pub is_retry: bool,
+
+    #[serde(rename = "ra")]
+    pub receiver: MacAddress,
+    #[serde(rename = "ta", default, skip_serializing_if = "Option::is_none")]
+    pub transmitter: Option<MacAddress>,
+    #[serde(rename = "sa", default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<MacAddress>,
+    #[serde(rename = "da")]
+    pub destination: MacAddress,
+    #[serde(rename = "bss", default, skip_serializing_if = "Option::is_none")]
+    pub bssid: Option<MacAddress>,
+
+    #[serde(rename = "ssid", default, skip_serializing_if = "Option::is_none")]
+    pub ssid: Option<String>,
+    #[serde(rename = "ch", default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<u8>,
+}
+
+impl IEEE80211Dto {
+    /// Source & destination addresses. <br>
+    /// Frames without transmitter address (CTS, ACK) can't be located.
+    pub fn addresses(&self) -> Option<(MacAddress, MacAddress)> {
+        self.source
+            .clone()
+            .map(|source| (source, self.destination.clone()))
+    }
+}
+
+impl From<IEEE80211> for IEEE80211Dto {
+    fn from(value: IEEE80211) -> Self {
+        Self {
+            is_protected: value.is_protected(),
+            is_retry: value.has_flag(FLAG_RETRY),
+            frame_type: value.frame_type,
+            subtype: value.subtype,
+            receiver: value.receiver,
+            transmitter: value.transmitter,
+            source: value.source,
+            destination: value.destination,
+            bssid: value.bssid,
+            ssid: value.ssid,
+            channel: value.channel,
+        }
+    }
+}
+
+impl FieldIter for IEEE80211Dto {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let Self {
+            frame_type,
+            subtype,
+            is_protected,
+            is_retry,
+            receiver,
+            transmitter,
+            source,
+            destination,
+            bssid,
+            ssid,
+            channel,
+        } = self;
+
+        vec![
+            ("frame_type", frame_type.to_string()),
+            ("subtype", subtype.to_string()),
+            ("is_protected", is_protected.to_string()),
+            ("is_retry", is_retry.to_string()),
+            ("receiver", receiver.to_string()),
+            ("transmitter", fields::optional(transmitter)),
+            ("source", fields::optional(source)),
+            ("destination", destination.to_string()),
+            ("bssid", fields::optional(bssid)),
+            ("ssid", fields::optional(ssid)),
+            ("channel", fields::optional(channel)),
+        ]
+    }
+}
+
+#[cfg(all(test, feature = "arp", feature = "radiotap"))]
+mod tests {
+    use super::*;
+    use crate::dto::frame::{FrameHeader, LinkType};
+    use crate::parser::tests::FrameType as ParsedFrame;
+    use crate::parser::tests::ProtocolParser;
+    use crate::protocols::arp::{HardwareAddress, ProtocolAddress};
+    use crate::protocols::radiotap::{Radiotap, RadiotapDto};
+    use std::net::Ipv4Addr;
+
+    fn layers(hex_actual: &str) -> Vec<ProtocolData> {
+        let frame = hex::decode(hex_actual.replace(" ", "")).unwrap();
+        let header = FrameHeader::new(frame.len());
+
+        let parser = ProtocolParser::new(LinkType::IEEE802_11_RADIOTAP, false);
+        match parser.process_bytes(&header, &frame) {
+            Some(ParsedFrame::Metadata(value)) => value.layers,
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn test_beacon() {
+        // Radiotap (Flags with FCS, Rate, Channel, Antenna Signal) + Beacon + FCS
+        let layers = layers(
+            "00 00 0F 00 2E 00 00 00 10 02 85 09 A0 00 D3 80 00 00 00 FF FF FF FF FF FF 00 11 22 33 44 55 00 11 22 33 44 55 10 00 00 11 22 33 44 55 66 77 64 00 11 04 00 07 48 6F 6D 65 4C 41 4E 01 04 82 84 8B 96 03 01 06 DE AD BE EF",
+        );
+        assert_eq!(layers.len(), 2);
+
+        let radiotap = match layers[0].clone() {
+            ProtocolData::Radiotap(value) => value,
+            _ => panic!(),
+        };
+        let expected_radiotap = Radiotap {
+            length: 15,
+            flags: Some(0x10),
+            rate: Some(2),
+            frequency: Some(2437),
+            signal: Some(-45),
+        };
+        assert_eq!(radiotap, expected_radiotap);
+        assert_eq!(RadiotapDto::from(radiotap).channel, Some(6));
+
+        let actual = match layers[1].clone() {
+            ProtocolData::IEEE80211(value) => value,
+            _ => panic!(),
+        };
+        let access_point = MacAddress::try_from("00:11:22:33:44:55").unwrap();
+        let expected = IEEE80211 {
+            frame_type: FrameType::Management,
+            subtype: Subtype::Beacon,
+            flags: 0,
+            receiver: MacAddress::try_from("FF:FF:FF:FF:FF:FF").unwrap(),
+            transmitter: Some(access_point.clone()),
+            source: Some(access_point.clone()),
+            destination: MacAddress::try_from("FF:FF:FF:FF:FF:FF").unwrap(),
+            bssid: Some(access_point),
+            ether_type: None,
+            ssid: Some("HomeLAN".to_string()),
+            channel: Some(6),
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_qos_data_with_arp() {
+        // Radiotap without fields + QoS Data (To DS) + LLC/SNAP + ARP request
+        let layers = layers(
+            "00 00 08 00 00 00 00 00 88 01 2C 00 00 11 22 33 44 55 00 1E 68 51 4F A9 FF FF FF FF FF FF 20 00 00 00 AA AA 03 00 00 00 08 06 00 01 08 00 06 04 00 01 00 1E 68 51 4F A9 C0 A8 01 64 00 00 00 00 00 00 C0 A8 01 01",
+        );
+        assert_eq!(layers.len(), 3);
+
+        let actual = match layers[1].clone() {
+            ProtocolData::IEEE80211(value) => value,
+            _ => panic!(),
+        };
+        let station = MacAddress::try_from("00:1E:68:51:4F:A9").unwrap();
+        let expected = IEEE80211 {
+            frame_type: FrameType::Data,
+            subtype: Subtype::QosData,
+            flags: FLAG_TO_DS,
+            receiver: MacAddress::try_from("00:11:22:33:44:55").unwrap(),
+            transmitter: Some(station.clone()),
+            source: Some(station.clone()),
+            destination: MacAddress::try_from("FF:FF:FF:FF:FF:FF").unwrap(),
+            bssid: Some(MacAddress::try_from("00:11:22:33:44:55").unwrap()),
+            ether_type: Some(EtherType::Arp),
+            ssid: None,
+            channel: None,
+        };
+        assert_eq!(actual, expected);
+
+        let arp = match layers[2].clone() {
+            ProtocolData::Arp(value) => value,
+            _ => panic!(),
+        };
+        assert_eq!(arp.sender_hardware, HardwareAddress::Mac(station));
+        assert_eq!(
+            arp.sender_protocol,
+            ProtocolAddress::IPv4(Ipv4Addr::new(192, 168, 1, 100))
+        );
+        assert_eq!(
+            arp.target_protocol,
+            ProtocolAddress::IPv4(Ipv4Addr::new(192, 168, 1, 1))
+        );
+    }
+}
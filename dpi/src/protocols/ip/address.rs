@@ -1,5 +1,4 @@
-use crate::parser::ParserError;
-use nom::IResult;
+use crate::parser::{IResult, ParserError};
 use nom::Parser;
 use nom::bytes::take;
 use std::net::{Ipv4Addr, Ipv6Addr};
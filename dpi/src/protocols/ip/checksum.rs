@@ -0,0 +1,264 @@
+use crate::dto::fields;
+use crate::dto::frame::FrameMetadata;
+use crate::protocols::ProtocolData;
+#[cfg(any(feature = "tcp", feature = "udp"))]
+use crate::protocols::ip::protocol::IpNextLevelProtocol;
+#[cfg(feature = "udp")]
+use crate::protocols::udp::LengthMismatch;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+// Internet checksum of IPv4 header, TCP & UDP segments.
+// RFC 1071: https://datatracker.ietf.org/doc/html/rfc1071
+// Parsers only read the checksums. Validation is done by the parser afterward, if enabled.
+// Ethernet FCS is not validated, capture drivers strip it in most cases.
+
+// More Fragments flag of IPv4
+#[cfg(all(feature = "ipv4", any(feature = "tcp", feature = "udp")))]
+const MORE_FRAGMENTS_FLAG: u8 = 0b001;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChecksumStatus {
+    // Validation is disabled, or the data is incomplete (truncated, fragmented)
+    #[default]
+    NotChecked,
+    Valid,
+    Invalid,
+}
+
+impl ChecksumStatus {
+    fn from_sum(sum: u16) -> Self {
+        // Sum of the data with its checksum is all ones
+        match sum {
+            0xFFFF => Self::Valid,
+            _ => Self::Invalid,
+        }
+    }
+}
+
+impl Display for ChecksumStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Self::NotChecked => fields::NONE,
+            Self::Valid => "✓",
+            Self::Invalid => "✗",
+        };
+
+        write!(f, "{text}")
+    }
+}
+
+/// Sets the checksum status of the freshly parsed layer.
+/// <br> `bytes` are the input of its parser, `metadata` holds the layers below.
+pub fn validate(layer: &mut ProtocolData, bytes: &[u8], metadata: &FrameMetadata) {
+    match layer {
+        #[cfg(feature = "ipv4")]
+        ProtocolData::IPv4(ipv4) => {
+            ipv4.checksum_status = ipv4_header(bytes, ipv4.internet_header_length);
+        },
+        #[cfg(feature = "tcp")]
+        ProtocolData::TCP(tcp) => {
+            tcp.checksum_status = segment(bytes, metadata, IpNextLevelProtocol::TCP);
+        },
+        #[cfg(feature = "udp")]
+        ProtocolData::UDP(udp) => {
+            // Zero means the sender hasn't computed it. It's mandatory over IPv6,
+            // but the tunnels (RFC 6935) & some embedded devices still send it.
+            udp.checksum_status = match (udp.checksum, udp.length_mismatch) {
+                (0, _) => ChecksumStatus::NotChecked,
+                // Datagram is incomplete
+                (_, Some(LengthMismatch::Truncated)) => ChecksumStatus::NotChecked,
+                // Length field cuts the trailer off
+                _ => match bytes.get(..usize::from(udp.length)) {
+                    Some(bytes) => segment(bytes, metadata, IpNextLevelProtocol::UDP),
+                    None => ChecksumStatus::NotChecked,
+                },
+            };
+        },
+        _ => {},
+    }
+}
+
+#[cfg(feature = "ipv4")]
+fn ipv4_header(bytes: &[u8], header_length: u16) -> ChecksumStatus {
+    match bytes.get(..usize::from(header_length)) {
+        Some(header) => ChecksumStatus::from_sum(fold(sum(header, 0))),
+        None => ChecksumStatus::NotChecked,
+    }
+}
+
+// TCP & UDP checksums cover the pseudo-header of the IP layer below
+#[cfg(any(feature = "tcp", feature = "udp"))]
+fn segment(
+    bytes: &[u8], metadata: &FrameMetadata, protocol: IpNextLevelProtocol,
+) -> ChecksumStatus {
+    // Segment is cut off by the snapshot length
+    if metadata.header.caplen < metadata.header.len {
+        return ChecksumStatus::NotChecked;
+    }
+    let Ok(length) = u32::try_from(bytes.len()) else {
+        return ChecksumStatus::NotChecked;
+    };
+
+    let pseudo_header = match metadata.layers.last() {
+        #[cfg(feature = "ipv4")]
+        Some(ProtocolData::IPv4(ipv4)) => {
+            let is_fragment =
+                ipv4.flags & MORE_FRAGMENTS_FLAG != 0 || ipv4.fragment_offset != 0;
+            if is_fragment {
+                return ChecksumStatus::NotChecked;
+            }
+            pseudo_header(
+                &ipv4.address_source.octets(),
+                &ipv4.address_destination.octets(),
+                protocol,
+                length,
+            )
+        },
+        #[cfg(feature = "ipv6")]
+        Some(ProtocolData::IPv6(ipv6)) => {
+            if ipv6.fragment().is_some() {
+                return ChecksumStatus::NotChecked;
+            }
+            pseudo_header(
+                &ipv6.address_source.octets(),
+                &ipv6.address_destination.octets(),
+                protocol,
+                length,
+            )
+        },
+        _ => return ChecksumStatus::NotChecked,
+    };
+
+    ChecksumStatus::from_sum(fold(sum(bytes, pseudo_header)))
+}
+
+// IPv4 (RFC 9293) & IPv6 (RFC 8200) pseudo-headers differ only in the field widths,
+// which don't matter for the sum
+#[cfg(any(feature = "tcp", feature = "udp"))]
+fn pseudo_header(
+    source: &[u8], destination: &[u8], protocol: IpNextLevelProtocol, length: u32,
+) -> u64 {
+    let length = length.to_be_bytes();
+    let protocol = [0, protocol as u8];
+
+    [source, destination, &length, &protocol]
+        .into_iter()
+        .fold(0, |accumulator, bytes| sum(bytes, accumulator))
+}
+
+// 16-bit words, odd byte is padded with zero
+fn sum(bytes: &[u8], mut accumulator: u64) -> u64 {
+    let mut words = bytes.chunks_exact(2);
+    for word in words.by_ref() {
+        if let [high, low] = word {
+            accumulator =
+                accumulator.wrapping_add(u64::from(u16::from_be_bytes([*high, *low])));
+        }
+    }
+    if let [last] = words.remainder() {
+        accumulator = accumulator.wrapping_add(u64::from(u16::from_be_bytes([*last, 0])));
+    }
+
+    accumulator
+}
+
+// One's complement: carries are added back
+fn fold(mut accumulator: u64) -> u16 {
+    while accumulator > u64::from(u16::MAX) {
+        accumulator = (accumulator & u64::from(u16::MAX)).wrapping_add(accumulator >> 16);
+    }
+
+    u16::try_from(accumulator).unwrap_or_default()
+}
+
+#[cfg(all(
+    test,
+    feature = "ethernet",
+    feature = "ipv4",
+    feature = "tcp",
+    feature = "udp"
+))]
+mod tests {
+    use super::*;
+    use crate::dto::frame::{FrameHeader, LinkType};
+    use crate::parser::tests::{FrameType, ProtocolParser};
+
+    // Ethernet + IPv4 + TCP
+    const TCP_FRAME: &str = "40 61 86 9A F1 F5 00 1A 8C 15 F9 80 08 00 45 00 00 63 2B B1 00 00 34 06 79 17 48 0E D5 93 C0 A8 03 83 01 BB CB B8 EE BA 71 7C 18 D9 CD D6 50 18 01 B4 3D CA 00 00 17 03 01 00 36 B5 2A 58 A3 3D BD EC F3 7C C9 C4 43 B9 5D 94 C9 3D 9D E5 75 11 47 6E 2E A0 E0 8B 1B 64 44 BE D8 06 FE 5B 00 69 B3 12 D0 D9 37 87 87 F4 1C 42 E3 00 16 EE 14 CA 69";
+    // Ethernet + IPv4 + UDP (LLMNR)
+    const UDP_FRAME: &str = "01 00 5E 00 00 FC 40 61 86 9A F1 F5 08 00 45 00 00 32 6A 3D 00 00 01 11 AA 56 C0 A8 03 83 E0 00 00 FC D5 48 14 EB 00 1E 20 88 76 F2 00 00 00 01 00 00 00 00 00 00 04 77 70 61 64 00 00 01 00 01";
+
+    // Statuses of IPv4 & the transport layer
+    fn statuses(
+        hex_actual: &str, corrupt: &[usize], is_enabled: bool, caplen: usize,
+    ) -> (ChecksumStatus, ChecksumStatus) {
+        let mut frame = hex::decode(hex_actual.replace(" ", "")).unwrap();
+        for index in corrupt {
+            frame[*index] ^= 0xFF;
+        }
+        let header = FrameHeader::captured(caplen, frame.len());
+
+        let parser = ProtocolParser::new(LinkType::ETHERNET, false)
+            .with_checksum_validation(is_enabled);
+        let layers = match parser.process_bytes(&header, &frame[..caplen]) {
+            Some(FrameType::Metadata(metadata)) => metadata.layers,
+            _ => panic!(),
+        };
+
+        let ipv4 = match &layers[1] {
+            ProtocolData::IPv4(value) => value.checksum_status,
+            _ => panic!(),
+        };
+        let transport = match &layers[2] {
+            ProtocolData::TCP(value) => value.checksum_status,
+            ProtocolData::UDP(value) => value.checksum_status,
+            _ => panic!(),
+        };
+        (ipv4, transport)
+    }
+
+    #[test]
+    fn test_sum_odd_length() {
+        // RFC 1071, 3. Numerical Examples
+        let bytes = [0x00, 0x01, 0xF2, 0x03, 0xF4, 0xF5, 0xF6, 0xF7];
+        assert_eq!(fold(sum(&bytes, 0)), 0xDDF2);
+
+        assert_eq!(fold(sum(&[0xAB], 0)), 0xAB00);
+        assert_eq!(ChecksumStatus::default().to_string(), fields::NONE);
+    }
+
+    #[test]
+    fn test_tcp_checksums() {
+        use ChecksumStatus::*;
+
+        assert_eq!(statuses(TCP_FRAME, &[], true, 113), (Valid, Valid));
+        // Last byte of the payload
+        assert_eq!(statuses(TCP_FRAME, &[112], true, 113), (Valid, Invalid));
+        // Time to Live isn't covered by the pseudo-header
+        assert_eq!(statuses(TCP_FRAME, &[22], true, 113), (Invalid, Valid));
+
+        // Payload is cut off by the snapshot length
+        assert_eq!(statuses(TCP_FRAME, &[], true, 80), (Valid, NotChecked));
+        assert_eq!(
+            statuses(TCP_FRAME, &[112], false, 113),
+            (NotChecked, NotChecked)
+        );
+    }
+
+    #[test]
+    fn test_udp_checksums() {
+        use ChecksumStatus::*;
+
+        assert_eq!(statuses(UDP_FRAME, &[], true, 64), (Valid, Valid));
+        // Query name
+        assert_eq!(statuses(UDP_FRAME, &[55], true, 64), (Valid, Invalid));
+        // Ethernet padding isn't covered
+        let padded = format!("{UDP_FRAME} 00 00 00 00");
+        assert_eq!(statuses(&padded, &[66], true, 68), (Valid, Valid));
+
+        // Zero checksum isn't computed by the sender
+        let not_computed = UDP_FRAME.replace("20 88", "00 00");
+        assert_eq!(statuses(&not_computed, &[], true, 64), (Valid, NotChecked));
+    }
+}
@@ -1,10 +1,14 @@
+use crate::dto::fields::{self, FieldIter};
 use crate::dto::frame::FrameMetadata;
-use crate::parser::ParserError;
+use crate::dto::wire;
+use crate::parser::{IResult, ParseFailure, ParserError};
+use crate::protocols::ip::checksum::ChecksumStatus;
 use crate::protocols::ip::protocol::IpNextLevelProtocol;
-use crate::protocols::{ProtocolData, ProtocolId, ip};
+use crate::protocols::ipv4::option::OptionData;
+use crate::protocols::{ProtocolData, ProtocolId, enabled, ip};
 use nom::Parser;
 use nom::number::{be_u8, be_u16};
-use nom::{IResult, bits, sequence};
+use nom::{bits, sequence};
 use serde::{Deserialize, Serialize};
 use std::net::Ipv4Addr;
 
@@ -20,21 +24,25 @@ pub const PACKET_NECESSARY_LENGTH_BYTES: usize = 20;
 pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
     // Version (4 bits), Internet Header Length (4 bits)
     let (rest, (version, ihl)): (&[u8], (u8, u16)) =
-        bits::bits::<_, _, nom::error::Error<_>, _, _>(sequence::pair(
+        bits::bits::<_, _, ParseFailure<_>, _, _>(sequence::pair(
             bits::complete::take(PROTOCOL_VERSION_LENGTH_BITS),
             bits::complete::take(IHL_LENGTH_BITS),
         ))(bytes)?;
     if version != 4 {
-        return Err(ParserError::ErrorVerify.to_nom(bytes));
+        return Err(ParserError::InvalidValue { field: "version" }.to_nom(bytes));
     }
     // IHL is stored in 32bit words. So, we are doing IHL * 32 / 8 (bits in bytes)
     let ihl = ihl
         .checked_mul(4)
-        .ok_or(ParserError::ErrorVerify.to_nom(bytes))?;
+        .ok_or(ParserError::InvalidValue { field: "ihl" }.to_nom(bytes))?;
+    // Options are the only variable part, so the rest of the header is required
+    if (ihl as usize) < PACKET_NECESSARY_LENGTH_BYTES {
+        return Err(ParserError::InvalidValue { field: "ihl" }.to_nom(bytes));
+    }
 
     // Differentiated Services Code Point (6 bits), Explicit Congestion Notification (2 bits)
     let (rest, (dscp, ecn)): (&[u8], (u8, u8)) =
-        bits::bits::<_, _, nom::error::Error<_>, _, _>(sequence::pair(
+        bits::bits::<_, _, ParseFailure<_>, _, _>(sequence::pair(
             bits::complete::take(DSCP_LENGTH_BITS),
             bits::complete::take(ECN_LENGTH_BITS),
         ))(rest)?;
@@ -43,29 +51,39 @@ pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
     let (rest, total_len) = be_u16().parse(rest)?;
 
     // Totally parsed = 4 bytes. So, we can cut ethernet padding there.
-    let packet = rest
-        .get(
-            ..total_len
-                .checked_sub(4)
-                .ok_or(ParserError::ErrorVerify.to_nom(bytes))? as usize,
-        )
-        .ok_or(ParserError::ErrorVerify.to_nom(bytes))?;
+    // Packet may be truncated by the snapshot length, then only captured bytes are left.
+    let packet_length = total_len.checked_sub(4).ok_or(
+        ParserError::InvalidValue {
+            field: "total_length",
+        }
+        .to_nom(bytes),
+    )? as usize;
+    let packet = rest.get(..packet_length.min(rest.len())).ok_or(
+        ParserError::InvalidValue {
+            field: "total_length",
+        }
+        .to_nom(bytes),
+    )?;
     let boundary = ihl
         .checked_sub(4)
-        .ok_or(ParserError::ErrorVerify.to_nom(bytes))? as usize;
-    let rest = packet
-        .get(..boundary)
-        .ok_or(ParserError::ErrorVerify.to_nom(bytes))?;
-    let payload = packet
-        .get(boundary..)
-        .ok_or(ParserError::ErrorVerify.to_nom(bytes))?;
+        .ok_or(ParserError::InvalidValue { field: "ihl" }.to_nom(bytes))?
+        as usize;
+    let truncated = || {
+        ParserError::Truncated {
+            needed: ihl as usize,
+            available: packet.len().saturating_add(4),
+        }
+        .to_nom(bytes)
+    };
+    let rest = packet.get(..boundary).ok_or_else(truncated)?;
+    let payload = packet.get(boundary..).ok_or_else(truncated)?;
 
     // Identification - 2 bytes
     let (rest, identification) = be_u16().parse(rest)?;
 
     // Flags, Fragment offset - 16 bits.
     let (rest, (flags, fragment_offset)): (&[u8], (u8, u16)) =
-        bits::bits::<_, _, nom::error::Error<_>, _, _>(sequence::pair(
+        bits::bits::<_, _, ParseFailure<_>, _, _>(sequence::pair(
             bits::complete::take(FLAGS_LENGTH_BITS),
             bits::complete::take(FRAGMENT_OFFSET_LENGTH_BITS),
         ))(rest)?;
@@ -76,7 +94,7 @@ pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
     // Protocol field
     let (rest, inner_protocol) = be_u8().parse(rest)?;
     let protocol_inner = IpNextLevelProtocol::try_from(inner_protocol)
-        .map_err(|_| ParserError::ErrorVerify.to_nom(bytes))?;
+        .map_err(|_| ParserError::InvalidValue { field: "protocol" }.to_nom(bytes))?;
 
     // Checksum
     let (rest, checksum) = be_u16().parse(rest)?;
@@ -86,13 +104,8 @@ pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
     // Destination Address
     let (rest, address_destination) = ip::address::v4_parse(rest)?;
 
-    if rest.len()
-        != (ihl as usize)
-            .checked_sub(PACKET_NECESSARY_LENGTH_BYTES)
-            .ok_or(ParserError::ErrorVerify.to_nom(bytes))?
-    {
-        return Err(ParserError::ErrorVerify.to_nom(bytes));
-    }
+    // Options (Header length - 20 bytes). Region is already cut by the header length
+    let (_, options) = option::parse(rest)?;
 
     let protocol = IPv4 {
         version,
@@ -106,8 +119,10 @@ pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
         time_to_live: ttl,
         protocol_inner,
         checksum,
+        checksum_status: ChecksumStatus::NotChecked,
         address_source,
         address_destination,
+        options,
     };
 
     Ok((payload, ProtocolData::IPv4(protocol)))
@@ -120,10 +135,11 @@ pub fn best_children(metadata: &FrameMetadata) -> Option<ProtocolId> {
         _ => return None,
     };
     match ipv4.protocol_inner {
-        IpNextLevelProtocol::ICMP => Some(ProtocolId::ICMPv4),
-        IpNextLevelProtocol::IPv6 => Some(ProtocolId::IPv6),
-        IpNextLevelProtocol::TCP => Some(ProtocolId::TCP),
-        IpNextLevelProtocol::UDP => Some(ProtocolId::UDP),
+        IpNextLevelProtocol::ICMP => enabled::ICMPv4,
+        IpNextLevelProtocol::IPv4 => enabled::IPv4,
+        IpNextLevelProtocol::IPv6 => enabled::IPv6,
+        IpNextLevelProtocol::TCP => enabled::TCP,
+        IpNextLevelProtocol::UDP => enabled::UDP,
         _ => None,
     }
 }
@@ -141,15 +157,25 @@ pub struct IPv4 {
     pub time_to_live: u8,
     pub protocol_inner: IpNextLevelProtocol,
     pub checksum: u16,
+    pub checksum_status: ChecksumStatus,
     pub address_source: Ipv4Addr,
     pub address_destination: Ipv4Addr,
+    pub options: Vec<OptionData>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct IPv4Dto {
+    #[serde(rename = "src")]
     pub address_source: Ipv4Addr,
+    #[serde(rename = "dst")]
     pub address_destination: Ipv4Addr,
+    #[serde(rename = "ttl")]
     pub time_to_live: u8,
+    #[serde(rename = "cs", default, skip_serializing_if = "wire::is_default")]
+    pub checksum_status: ChecksumStatus,
+    // Without the padding
+    #[serde(rename = "opt", default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<OptionData>,
 }
 
 impl From<IPv4> for IPv4Dto {
@@ -158,38 +184,58 @@ impl From<IPv4> for IPv4Dto {
             address_source: value.address_source,
             address_destination: value.address_destination,
             time_to_live: value.time_to_live,
+            checksum_status: value.checksum_status,
+            options: value
+                .options
+                .into_iter()
+                .filter(|option| !option.is_padding())
+                .collect(),
         }
     }
 }
 
-#[cfg(test)]
+impl FieldIter for IPv4Dto {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let Self {
+            address_source,
+            address_destination,
+            time_to_live,
+            checksum_status,
+            options,
+        } = self;
+
+        vec![
+            ("address_source", address_source.to_string()),
+            ("address_destination", address_destination.to_string()),
+            ("time_to_live", time_to_live.to_string()),
+            ("checksum_status", checksum_status.to_string()),
+            ("options", fields::list(options)),
+        ]
+    }
+}
+
+pub mod option;
+
+#[cfg(all(test, feature = "ethernet", feature = "icmpv4"))]
 mod tests {
     use super::*;
-    use crate::dto::frame::FrameHeader;
+    use crate::dto::frame::{FrameHeader, LinkType};
     use crate::parser::tests::FrameType;
     use crate::parser::tests::ProtocolParser;
     use crate::protocols::ProtocolData;
     use crate::protocols::ethernet::Ethernet;
     use crate::protocols::ethernet::ether_type::EtherType;
     use crate::protocols::ethernet::mac::MacAddress;
+    use crate::protocols::icmpv4::MessageType;
 
     #[test]
     fn test_ipv4_with_ethernet_padding() {
         let hex_actual = "40 61 86 9A F1 F5 00 1A 8C 15 F9 80 08 00 45 00 00 28 2B AE 00 00 34 06 79 55 48 0E D5 93 C0 A8 03 83 01 BB CB B8 EE BA 6C 0B 18 D9 CD D6 50 10 01 B4 BD 69 00 00 00 00 00 00 00 00".replace(" ", "");
         let frame = hex::decode(hex_actual).unwrap();
-        let header = FrameHeader {
-            tv_sec: 0,
-            tv_usec: 0,
-            caplen: 60,
-            len: 0,
-        };
+        let header = FrameHeader::new(60);
 
-        let parser = ProtocolParser::new(&pcap::Linktype(1), false);
-        let packet = pcap::Packet {
-            header: &pcap::PacketHeader::from(&header),
-            data: &frame,
-        };
-        let result = parser.process(packet);
+        let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+        let result = parser.process_bytes(&header, &frame);
         let metadata = match result {
             Some(value) => match value {
                 FrameType::Metadata(value) => value,
@@ -228,8 +274,10 @@ mod tests {
             time_to_live: 52,
             protocol_inner: IpNextLevelProtocol::TCP,
             checksum: 0x7955,
+            checksum_status: ChecksumStatus::NotChecked,
             address_source: Ipv4Addr::new(72, 14, 213, 147),
             address_destination: Ipv4Addr::new(192, 168, 3, 131),
+            options: vec![],
         };
 
         assert_eq!(actual_ipv4, expected_ipv4);
@@ -239,19 +287,10 @@ mod tests {
     fn test_ipv4_without_ethernet_padding() {
         let hex_actual = "40 61 86 9A F1 F5 00 1A 8C 15 F9 80 08 00 45 00 00 63 2B B1 00 00 34 06 79 17 48 0E D5 93 C0 A8 03 83 01 BB CB B8 EE BA 71 7C 18 D9 CD D6 50 18 01 B4 3D CA 00 00 17 03 01 00 36 B5 2A 58 A3 3D BD EC F3 7C C9 C4 43 B9 5D 94 C9 3D 9D E5 75 11 47 6E 2E A0 E0 8B 1B 64 44 BE D8 06 FE 5B 00 69 B3 12 D0 D9 37 87 87 F4 1C 42 E3 00 16 EE 14 CA 69".replace(" ", "");
         let frame = hex::decode(hex_actual).unwrap();
-        let header = FrameHeader {
-            tv_sec: 0,
-            tv_usec: 0,
-            caplen: 113,
-            len: 0,
-        };
+        let header = FrameHeader::new(113);
 
-        let parser = ProtocolParser::new(&pcap::Linktype(1), false);
-        let packet = pcap::Packet {
-            header: &pcap::PacketHeader::from(&header),
-            data: &frame,
-        };
-        let result = parser.process(packet);
+        let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+        let result = parser.process_bytes(&header, &frame);
         let metadata = match result {
             Some(value) => match value {
                 FrameType::Metadata(value) => value,
@@ -290,10 +329,99 @@ mod tests {
             time_to_live: 52,
             protocol_inner: IpNextLevelProtocol::TCP,
             checksum: 0x7917,
+            checksum_status: ChecksumStatus::NotChecked,
             address_source: Ipv4Addr::new(72, 14, 213, 147),
             address_destination: Ipv4Addr::new(192, 168, 3, 131),
+            options: vec![],
+        };
+
+        assert_eq!(actual_ipv4, expected_ipv4);
+    }
+
+    #[test]
+    fn test_ipv4_with_router_alert() {
+        // IGMPv2 Membership Report
+        let hex_actual = "01 00 5E 00 00 FB 00 1A 8C 15 F9 80 08 00 46 C0 00 20 00 00 40 00 01 02 3E F1 C0 A8 03 83 E0 00 00 FB 94 04 00 00 16 00 09 04 E0 00 00 FB".replace(" ", "");
+        let frame = hex::decode(hex_actual).unwrap();
+        let header = FrameHeader::new(46);
+
+        let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+        let metadata = match parser.process_bytes(&header, &frame) {
+            Some(FrameType::Metadata(value)) => value,
+            _ => panic!(),
+        };
+
+        let actual_ipv4 = match metadata.layers[1].clone() {
+            ProtocolData::IPv4(value) => value,
+            _ => panic!(),
+        };
+
+        let expected_ipv4 = IPv4 {
+            version: 4,
+            internet_header_length: 24,
+            differentiated_services_code_point: 48,
+            explicit_congestion_notification: 0,
+            total_length: 32,
+            identification: 0,
+            flags: 0b010,
+            fragment_offset: 0,
+            time_to_live: 1,
+            protocol_inner: IpNextLevelProtocol::IGMP,
+            checksum: 0x3ef1,
+            checksum_status: ChecksumStatus::NotChecked,
+            address_source: Ipv4Addr::new(192, 168, 3, 131),
+            address_destination: Ipv4Addr::new(224, 0, 0, 251),
+            options: vec![OptionData::RouterAlert(0)],
         };
 
         assert_eq!(actual_ipv4, expected_ipv4);
+        assert_eq!(
+            IPv4Dto::from(actual_ipv4).fields().last(),
+            Some(&("options", "Router Alert".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_ipv4_with_unknown_option() {
+        // Record Route with one slot & NOP, then ICMP Echo
+        let hex_actual = "40 61 86 9A F1 F5 00 1A 8C 15 F9 80 08 00 47 00 00 24 1A 2B 00 00 40 01 7F 6B C0 A8 03 83 08 08 08 08 07 07 04 00 00 00 00 01 08 00 F7 FD 00 01 00 01".replace(" ", "");
+        let frame = hex::decode(hex_actual).unwrap();
+        let header = FrameHeader::new(50);
+
+        let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+        let metadata = match parser.process_bytes(&header, &frame) {
+            Some(FrameType::Metadata(value)) => value,
+            _ => panic!(),
+        };
+
+        let actual_ipv4 = match metadata.layers[1].clone() {
+            ProtocolData::IPv4(value) => value,
+            _ => panic!(),
+        };
+        assert_eq!(actual_ipv4.internet_header_length, 28);
+        assert_eq!(
+            actual_ipv4.options,
+            vec![
+                OptionData::Unknown {
+                    kind: 7,
+                    data: vec![0x04, 0x00, 0x00, 0x00, 0x00],
+                },
+                OptionData::NoOperation,
+            ]
+        );
+
+        // Payload starts after the options
+        let actual_icmp = match metadata.layers[2].clone() {
+            ProtocolData::ICMPv4(value) => value,
+            _ => panic!(),
+        };
+        assert_eq!(actual_icmp.message_type, MessageType::Echo);
+        assert_eq!(actual_icmp.data, vec![0x00, 0x01, 0x00, 0x01]);
+
+        // Padding isn't shown
+        assert_eq!(
+            IPv4Dto::from(actual_ipv4).fields().last(),
+            Some(&("options", "Unknown (7)".to_string()))
+        );
     }
 }
@@ -0,0 +1,190 @@
+use crate::parser::{IResult, ParseFailure, ParserError};
+use crate::protocols::ip;
+use nom::Parser;
+use nom::number::{be_u8, be_u16, be_u32};
+use serde::{Deserialize, Serialize};
+use std::net::Ipv4Addr;
+
+// IPv4 Options
+// RFC 791, Section 3.1: https://datatracker.ietf.org/doc/html/rfc791#section-3.1
+// Router Alert, RFC 2113: https://datatracker.ietf.org/doc/html/rfc2113
+
+pub const TYPE_END: u8 = 0;
+pub const TYPE_NO_OPERATION: u8 = 1;
+pub const TYPE_TIMESTAMP: u8 = 68;
+pub const TYPE_ROUTER_ALERT: u8 = 148;
+// Type & Length octets are included in the option length
+pub const HEADER_LENGTH_BYTES: usize = 2;
+// Pointer is counted from the start of the option, the first entry is at the 5th octet
+pub const TIMESTAMP_FIRST_POINTER: usize = 5;
+
+const LENGTH_ERROR: ParserError = ParserError::InvalidValue {
+    field: "option_length",
+};
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum OptionData {
+    End,
+    NoOperation,
+    // 0 - every router examines the packet
+    RouterAlert(u16),
+    Timestamp(Timestamp),
+    Unknown { kind: u8, data: Vec<u8> },
+}
+
+impl OptionData {
+    // Used only for the alignment, so it's not worth showing
+    pub fn is_padding(&self) -> bool {
+        matches!(self, Self::End | Self::NoOperation)
+    }
+}
+
+impl std::fmt::Display for OptionData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::End => write!(f, "End of Options"),
+            Self::NoOperation => write!(f, "No Operation"),
+            Self::RouterAlert(0) => write!(f, "Router Alert"),
+            Self::RouterAlert(value) => write!(f, "Router Alert ({value})"),
+            Self::Timestamp(timestamp) => {
+                write!(f, "Timestamp ({})", timestamp.entries.len())
+            },
+            Self::Unknown { kind, .. } => write!(f, "Unknown ({kind})"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Timestamp {
+    pub pointer: u8,
+    // Amount of the modules, that couldn't register the timestamp
+    pub overflow: u8,
+    pub flag: TimestampFlag,
+    // Only the entries, that are already recorded
+    pub entries: Vec<TimestampEntry>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum TimestampFlag {
+    TimestampsOnly,
+    AddressAndTimestamp,
+    Prespecified,
+}
+
+impl TimestampFlag {
+    fn has_address(&self) -> bool {
+        !matches!(self, Self::TimestampsOnly)
+    }
+}
+
+impl TryFrom<u8> for TimestampFlag {
+    type Error = ParserError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::TimestampsOnly),
+            1 => Ok(Self::AddressAndTimestamp),
+            3 => Ok(Self::Prespecified),
+            _ => Err(ParserError::InvalidValue {
+                field: "timestamp_flag",
+            }),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TimestampEntry {
+    pub address: Option<Ipv4Addr>,
+    // Milliseconds since midnight UT
+    pub timestamp: u32,
+}
+
+/// Parses the options region of the header. It has to be already cut by the header length.
+pub fn parse(bytes: &[u8]) -> IResult<&[u8], Vec<OptionData>> {
+    let mut options = vec![];
+    let mut rest = bytes;
+
+    while !rest.is_empty() {
+        let (after, kind) = be_u8().parse(rest)?;
+        match kind {
+            // Everything after the end of the list is padding
+            TYPE_END => {
+                options.push(OptionData::End);
+                return Ok((&[], options));
+            },
+            TYPE_NO_OPERATION => {
+                options.push(OptionData::NoOperation);
+                rest = after;
+                continue;
+            },
+            _ => {},
+        }
+
+        let (after, length) = be_u8().parse(after)?;
+        let (data, after) = usize::from(length)
+            .checked_sub(HEADER_LENGTH_BYTES)
+            .and_then(|length| after.split_at_checked(length))
+            .ok_or(LENGTH_ERROR.to_nom(rest))?;
+
+        let option = match kind {
+            TYPE_ROUTER_ALERT => {
+                if data.len() != 2 {
+                    return Err(LENGTH_ERROR.to_nom(rest));
+                }
+                let (_, value) = be_u16().parse(data)?;
+                OptionData::RouterAlert(value)
+            },
+            TYPE_TIMESTAMP => OptionData::Timestamp(timestamp(data)?),
+            _ => OptionData::Unknown {
+                kind,
+                data: data.to_vec(),
+            },
+        };
+        options.push(option);
+        rest = after;
+    }
+
+    Ok((rest, options))
+}
+
+fn timestamp(bytes: &[u8]) -> Result<Timestamp, nom::Err<ParseFailure<&[u8]>>> {
+    let (rest, pointer) = be_u8().parse(bytes)?;
+    let (rest, flags) = be_u8().parse(rest)?;
+    let overflow = flags >> 4;
+    let flag = TimestampFlag::try_from(flags & 0x0F).map_err(|err| err.to_nom(bytes))?;
+
+    let recorded = usize::from(pointer)
+        .checked_sub(TIMESTAMP_FIRST_POINTER)
+        .ok_or(
+            ParserError::InvalidValue {
+                field: "timestamp_pointer",
+            }
+            .to_nom(bytes),
+        )?;
+    // Pointer is beyond the data, if the option is full
+    let recorded = rest.get(..recorded.min(rest.len())).unwrap_or_default();
+
+    let entry_length = match flag.has_address() {
+        true => 8,
+        false => 4,
+    };
+    let mut entries = vec![];
+    for entry in recorded.chunks_exact(entry_length) {
+        let (entry, address) = match flag.has_address() {
+            true => {
+                let (entry, address) = ip::address::v4_parse(entry)?;
+                (entry, Some(address))
+            },
+            false => (entry, None),
+        };
+        let (_, timestamp) = be_u32().parse(entry)?;
+        entries.push(TimestampEntry { address, timestamp });
+    }
+
+    Ok(Timestamp {
+        pointer,
+        overflow,
+        flag,
+        entries,
+    })
+}
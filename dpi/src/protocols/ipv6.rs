@@ -1,10 +1,12 @@
+use crate::dto::fields::{self, FieldIter};
 use crate::dto::frame::FrameMetadata;
-use crate::parser::ParserError;
+use crate::parser::{IResult, ParseFailure, ParserError};
 use crate::protocols::ip::protocol::IpNextLevelProtocol;
-use crate::protocols::{ProtocolData, ProtocolId, ip};
+use crate::protocols::ipv6::extension::ExtensionHeader;
+use crate::protocols::{ProtocolData, ProtocolId, enabled, ip};
 use nom::Parser;
+use nom::bits;
 use nom::number::{be_u8, be_u16};
-use nom::{IResult, bits};
 use serde::{Deserialize, Serialize};
 use std::net::Ipv6Addr;
 // IPv6 Protocol
@@ -16,13 +18,13 @@ pub const FLOW_LABEL_LENGTH_BITS: usize = 20;
 pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
     // Version (4 bits), Traffic Class (8 bits), Flow Label (20 bits)
     let (rest, (version, traffic_class, flow_label)): (&[u8], (u8, u8, u32)) =
-        bits::bits::<_, _, nom::error::Error<_>, _, _>((
+        bits::bits::<_, _, ParseFailure<_>, _, _>((
             bits::complete::take(VERSION_LENGTH_BITS),
             bits::complete::take(TRAFFIC_CLASS_LENGTH_BITS),
             bits::complete::take(FLOW_LABEL_LENGTH_BITS),
         ))(bytes)?;
     if version != 6 {
-        return Err(ParserError::ErrorVerify.to_nom(bytes));
+        return Err(ParserError::InvalidValue { field: "version" }.to_nom(bytes));
     }
 
     // Payload Length (2 bytes)
@@ -30,8 +32,12 @@ pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
 
     // Next Header (1 byte)
     let (rest, next_header) = be_u8().parse(rest)?;
-    let next_header = IpNextLevelProtocol::try_from(next_header)
-        .map_err(|_| ParserError::ErrorVerify.to_nom(bytes))?;
+    let next_header = IpNextLevelProtocol::try_from(next_header).map_err(|_| {
+        ParserError::InvalidValue {
+            field: "next_header",
+        }
+        .to_nom(bytes)
+    })?;
 
     // Hop Limit (1 byte)
     let (rest, hop_limit) = be_u8().parse(rest)?;
@@ -41,10 +47,19 @@ pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
     // Destination Address
     let (rest, address_destination) = ip::address::v6_parse(rest)?;
 
-    // Cutting ethernet padding
+    // Cutting ethernet padding. Payload may be truncated by the snapshot length
     let payload = rest
-        .get(..payload_length as usize)
-        .ok_or(ParserError::ErrorVerify.to_nom(bytes))?;
+        .get(..(payload_length as usize).min(rest.len()))
+        .ok_or(
+            ParserError::InvalidValue {
+                field: "payload_length",
+            }
+            .to_nom(bytes),
+        )?;
+
+    // Extension headers are between the fixed header and the upper-layer one
+    let (payload, (extension_headers, protocol_inner)) =
+        extension::parse_chain(payload, next_header.clone())?;
 
     let protocol = IPv6 {
         version,
@@ -55,6 +70,8 @@ pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
         hop_limit,
         address_source,
         address_destination,
+        extension_headers,
+        protocol_inner,
     };
 
     Ok((payload, ProtocolData::IPv6(protocol)))
@@ -66,11 +83,16 @@ pub fn best_children(metadata: &FrameMetadata) -> Option<ProtocolId> {
         Some(ProtocolData::IPv6(value)) => value,
         _ => return None,
     };
-    match ipv6.next_header {
-        IpNextLevelProtocol::Ipv6Icmp => Some(ProtocolId::ICMPv6),
-        IpNextLevelProtocol::IPv6 => Some(ProtocolId::IPv6),
-        IpNextLevelProtocol::TCP => Some(ProtocolId::TCP),
-        IpNextLevelProtocol::UDP => Some(ProtocolId::UDP),
+    // Upper-layer header is only in the first fragment
+    if ipv6.fragment().is_some_and(|fragment| !fragment.is_first()) {
+        return None;
+    }
+    match ipv6.protocol_inner {
+        IpNextLevelProtocol::Ipv6Icmp => enabled::ICMPv6,
+        IpNextLevelProtocol::IPv4 => enabled::IPv4,
+        IpNextLevelProtocol::IPv6 => enabled::IPv6,
+        IpNextLevelProtocol::TCP => enabled::TCP,
+        IpNextLevelProtocol::UDP => enabled::UDP,
         _ => None,
     }
 }
@@ -85,13 +107,32 @@ pub struct IPv6 {
     pub hop_limit: u8,
     pub address_source: Ipv6Addr,
     pub address_destination: Ipv6Addr,
+    pub extension_headers: Vec<ExtensionHeader>,
+    // Protocol after the extension headers
+    pub protocol_inner: IpNextLevelProtocol,
+}
+
+impl IPv6 {
+    pub fn fragment(&self) -> Option<&extension::Fragment> {
+        self.extension_headers
+            .iter()
+            .find_map(|header| match header {
+                ExtensionHeader::Fragment(fragment) => Some(fragment),
+                _ => None,
+            })
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct IPv6Dto {
+    #[serde(rename = "src")]
     pub address_source: Ipv6Addr,
+    #[serde(rename = "dst")]
     pub address_destination: Ipv6Addr,
+    #[serde(rename = "hl")]
     pub hop_limit: u8,
+    #[serde(rename = "ext", default, skip_serializing_if = "Vec::is_empty")]
+    pub extension_headers: Vec<ExtensionHeader>,
 }
 
 impl From<IPv6> for IPv6Dto {
@@ -100,21 +141,50 @@ impl From<IPv6> for IPv6Dto {
             address_source: value.address_source,
             address_destination: value.address_destination,
             hop_limit: value.hop_limit,
+            extension_headers: value.extension_headers,
         }
     }
 }
 
-#[cfg(test)]
+impl FieldIter for IPv6Dto {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let Self {
+            address_source,
+            address_destination,
+            hop_limit,
+            extension_headers,
+        } = self;
+
+        vec![
+            ("address_source", address_source.to_string()),
+            ("address_destination", address_destination.to_string()),
+            ("hop_limit", hop_limit.to_string()),
+            ("extension_headers", fields::list(extension_headers)),
+        ]
+    }
+}
+
+pub mod extension;
+
+#[cfg(all(
+    test,
+    feature = "ethernet",
+    feature = "ipv4",
+    feature = "tcp",
+    feature = "udp"
+))]
 mod tests {
-    use crate::dto::frame::FrameHeader;
+    use crate::dto::frame::{FrameHeader, LinkType};
     use crate::parser::tests::FrameType;
     use crate::parser::tests::ProtocolParser;
     use crate::protocols::ethernet::Ethernet;
     use crate::protocols::ethernet::ether_type::EtherType;
     use crate::protocols::ethernet::mac::MacAddress;
+    use crate::protocols::ip::checksum::ChecksumStatus;
     use crate::protocols::ip::protocol::IpNextLevelProtocol;
     use crate::protocols::ipv4::IPv4;
     use crate::protocols::ipv6::IPv6;
+    use crate::protocols::ipv6::extension::{ExtensionHeader, Fragment};
     use crate::protocols::tcp::TCP;
     use crate::protocols::{ProtocolData, tcp};
     use std::net::{Ipv4Addr, Ipv6Addr};
@@ -124,19 +194,10 @@ mod tests {
     fn test_ipv6_tcp_http() {
         let hex_actual = "22 1A 95 D6 7A 23 86 93 23 D3 37 8E 86 DD 60 0D 68 4A 00 7D 06 40 FC 00 00 02 00 00 00 02 00 00 00 00 00 00 00 01 FC 00 00 02 00 00 00 01 00 00 00 00 00 00 00 01 A9 A0 1F 90 02 1B 63 8D BA 31 1E 8E 80 18 00 CF C9 2E 00 00 01 01 08 0A 80 1D A5 22 80 1D A5 22 47 45 54 20 2F 68 65 6C 6C 6F 2E 74 78 74 20 48 54 54 50 2F 31 2E 31 0D 0A 55 73 65 72 2D 41 67 65 6E 74 3A 20 63 75 72 6C 2F 37 2E 33 38 2E 30 0D 0A 48 6F 73 74 3A 20 5B 66 63 30 30 3A 32 3A 30 3A 31 3A 3A 31 5D 3A 38 30 38 30 0D 0A 41 63 63 65 70 74 3A 20 2A 2F 2A 0D 0A 0D 0A".replace(" ", "");
         let frame = hex::decode(hex_actual).unwrap();
-        let header = FrameHeader {
-            tv_sec: 0,
-            tv_usec: 0,
-            caplen: 179,
-            len: 0,
-        };
+        let header = FrameHeader::new(179);
 
-        let parser = ProtocolParser::new(&pcap::Linktype(1), false);
-        let packet = pcap::Packet {
-            header: &pcap::PacketHeader::from(&header),
-            data: &frame,
-        };
-        let result = parser.process(packet);
+        let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+        let result = parser.process_bytes(&header, &frame);
         let metadata = match result {
             Some(value) => match value {
                 FrameType::Metadata(value) => value,
@@ -172,6 +233,8 @@ mod tests {
             hop_limit: 64,
             address_source: Ipv6Addr::from_str("fc00:2:0:2::1").unwrap(),
             address_destination: Ipv6Addr::from_str("fc00:2:0:1::1").unwrap(),
+            extension_headers: vec![],
+            protocol_inner: IpNextLevelProtocol::TCP,
         };
 
         assert_eq!(actual_ipv6, expected_ipv6);
@@ -200,6 +263,7 @@ mod tests {
             },
             window: 207,
             checksum: 0xc92e,
+            checksum_status: ChecksumStatus::NotChecked,
             urgent_pointer: 0,
             options: vec![
                 tcp::OptionData::NoOperation,
@@ -215,19 +279,10 @@ mod tests {
     fn test_ipv6_tcp() {
         let hex_actual = "22 1A 95 D6 7A 23 86 93 23 D3 37 8E 86 DD 60 0D 68 4A 00 20 06 40 FC 00 00 02 00 00 00 02 00 00 00 00 00 00 00 01 FC 00 00 02 00 00 00 01 00 00 00 00 00 00 00 01 A9 A0 1F 90 02 1B 63 EB BA 31 1F 86 80 10 00 D8 2A 66 00 00 01 01 08 0A 80 1D A5 25 80 1D A5 25".replace(" ", "");
         let frame = hex::decode(hex_actual).unwrap();
-        let header = FrameHeader {
-            tv_sec: 0,
-            tv_usec: 0,
-            caplen: 86,
-            len: 0,
-        };
+        let header = FrameHeader::new(86);
 
-        let parser = ProtocolParser::new(&pcap::Linktype(1), false);
-        let packet = pcap::Packet {
-            header: &pcap::PacketHeader::from(&header),
-            data: &frame,
-        };
-        let result = parser.process(packet);
+        let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+        let result = parser.process_bytes(&header, &frame);
         let metadata = match result {
             Some(value) => match value {
                 FrameType::Metadata(value) => value,
@@ -263,6 +318,8 @@ mod tests {
             hop_limit: 64,
             address_source: Ipv6Addr::from_str("fc00:2:0:2::1").unwrap(),
             address_destination: Ipv6Addr::from_str("fc00:2:0:1::1").unwrap(),
+            extension_headers: vec![],
+            protocol_inner: IpNextLevelProtocol::TCP,
         };
 
         assert_eq!(actual_ipv6, expected_ipv6);
@@ -291,6 +348,7 @@ mod tests {
             },
             window: 216,
             checksum: 0x2a66,
+            checksum_status: ChecksumStatus::NotChecked,
             urgent_pointer: 0,
             options: vec![
                 tcp::OptionData::NoOperation,
@@ -306,19 +364,10 @@ mod tests {
     fn test_ipv4_ipv6_tcp() {
         let hex_actual = "01 00 01 00 00 00 1A 43 20 00 01 00 08 00 45 00 00 81 2A 50 00 00 10 29 46 CB 8B 12 19 21 51 83 43 83 60 04 40 E8 00 45 06 3F 20 01 06 38 09 02 00 01 02 01 02 FF FE E2 75 96 20 02 51 83 43 83 00 00 00 00 00 00 51 83 43 83 00 15 04 02 E5 37 A5 73 62 6B F3 08 50 18 81 60 98 72 00 00 33 33 31 20 47 75 65 73 74 20 6C 6F 67 69 6E 20 6F 6B 2C 20 74 79 70 65 20 79 6F 75 72 20 6E 61 6D 65 20 61 73 20 70 61 73 73 77 6F 72 64 2E 0D 0A".replace(" ", "");
         let frame = hex::decode(hex_actual).unwrap();
-        let header = FrameHeader {
-            tv_sec: 0,
-            tv_usec: 0,
-            caplen: 143,
-            len: 0,
-        };
+        let header = FrameHeader::new(143);
 
-        let parser = ProtocolParser::new(&pcap::Linktype(1), false);
-        let packet = pcap::Packet {
-            header: &pcap::PacketHeader::from(&header),
-            data: &frame,
-        };
-        let result = parser.process(packet);
+        let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+        let result = parser.process_bytes(&header, &frame);
         let metadata = match result {
             Some(value) => match value {
                 FrameType::Metadata(value) => value,
@@ -357,8 +406,10 @@ mod tests {
             time_to_live: 16,
             protocol_inner: IpNextLevelProtocol::IPv6,
             checksum: 0x46cb,
+            checksum_status: ChecksumStatus::NotChecked,
             address_source: Ipv4Addr::from_str("139.18.25.33").unwrap(),
             address_destination: Ipv4Addr::from_str("81.131.67.131").unwrap(),
+            options: vec![],
         };
 
         assert_eq!(actual_ipv4, expected_ipv4);
@@ -378,6 +429,8 @@ mod tests {
             address_source: Ipv6Addr::from_str("2001:638:902:1:201:2ff:fee2:7596")
                 .unwrap(),
             address_destination: Ipv6Addr::from_str("2002:5183:4383::5183:4383").unwrap(),
+            extension_headers: vec![],
+            protocol_inner: IpNextLevelProtocol::TCP,
         };
 
         assert_eq!(actual_ipv6, expected_ipv6);
@@ -406,10 +459,99 @@ mod tests {
             },
             window: 33120,
             checksum: 0x9872,
+            checksum_status: ChecksumStatus::NotChecked,
             urgent_pointer: 0,
             options: vec![],
         };
 
         assert_eq!(actual_tcp, expected_tcp);
     }
+
+    fn parse_layers(hex_actual: &str) -> Vec<ProtocolData> {
+        let frame = hex::decode(hex_actual.replace(" ", "")).unwrap();
+        let header = FrameHeader::new(frame.len());
+
+        let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+        match parser.process_bytes(&header, &frame) {
+            Some(FrameType::Metadata(value)) => value.layers,
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn test_ipv6_hop_by_hop_udp() {
+        // Hop-by-Hop Options header with PadN option, then UDP
+        let layers = parse_layers(
+            "22 1A 95 D6 7A 23 86 93 23 D3 37 8E 86 DD 60 00 00 00 00 10 00 40 FC 00 00 02 00 00 00 02 00 00 00 00 00 00 00 01 FC 00 00 02 00 00 00 01 00 00 00 00 00 00 00 01 11 00 01 04 00 00 00 00 04 D2 16 2E 00 08 00 00",
+        );
+        assert_eq!(layers.len(), 3);
+
+        let actual_ipv6 = match layers[1].clone() {
+            ProtocolData::IPv6(value) => value,
+            _ => panic!(),
+        };
+
+        let expected_ipv6 = IPv6 {
+            version: 6,
+            traffic_class: 0,
+            flow_label: 0,
+            payload_length: 16,
+            next_header: IpNextLevelProtocol::HOPOPT,
+            hop_limit: 64,
+            address_source: Ipv6Addr::from_str("fc00:2:0:2::1").unwrap(),
+            address_destination: Ipv6Addr::from_str("fc00:2:0:1::1").unwrap(),
+            extension_headers: vec![ExtensionHeader::HopByHop],
+            protocol_inner: IpNextLevelProtocol::UDP,
+        };
+
+        assert_eq!(actual_ipv6, expected_ipv6);
+
+        let actual_udp = match layers[2].clone() {
+            ProtocolData::UDP(value) => value,
+            _ => panic!(),
+        };
+        assert_eq!(actual_udp.port_source, 1234);
+        assert_eq!(actual_udp.port_destination, 5678);
+    }
+
+    #[test]
+    fn test_ipv6_fragments() {
+        // First fragment (offset 0, more fragments) contains the UDP header
+        let layers = parse_layers(
+            "22 1A 95 D6 7A 23 86 93 23 D3 37 8E 86 DD 60 00 00 00 00 10 2C 40 FC 00 00 02 00 00 00 02 00 00 00 00 00 00 00 01 FC 00 00 02 00 00 00 01 00 00 00 00 00 00 00 01 11 00 00 01 00 00 12 34 04 D2 16 2E 05 D0 00 00",
+        );
+        assert_eq!(layers.len(), 3);
+        let actual_ipv6 = match layers[1].clone() {
+            ProtocolData::IPv6(value) => value,
+            _ => panic!(),
+        };
+        assert_eq!(
+            actual_ipv6.extension_headers,
+            vec![ExtensionHeader::Fragment(Fragment {
+                offset: 0,
+                more_fragments: true,
+                identification: 0x1234,
+            })]
+        );
+        assert_eq!(actual_ipv6.protocol_inner, IpNextLevelProtocol::UDP);
+        assert!(matches!(layers[2], ProtocolData::UDP(_)));
+
+        // Last fragment (offset 1480 bytes) has no upper-layer header, so it's incomplete
+        let layers = parse_layers(
+            "22 1A 95 D6 7A 23 86 93 23 D3 37 8E 86 DD 60 00 00 00 00 10 2C 40 FC 00 00 02 00 00 00 02 00 00 00 00 00 00 00 01 FC 00 00 02 00 00 00 01 00 00 00 00 00 00 00 01 11 00 05 C8 00 00 12 34 DE AD BE EF DE AD BE EF",
+        );
+        assert_eq!(layers.len(), 2);
+        let actual_ipv6 = match layers[1].clone() {
+            ProtocolData::IPv6(value) => value,
+            _ => panic!(),
+        };
+        assert_eq!(
+            actual_ipv6.fragment(),
+            Some(&Fragment {
+                offset: 185,
+                more_fragments: false,
+                identification: 0x1234,
+            })
+        );
+    }
 }
@@ -0,0 +1,170 @@
+use crate::parser::{IResult, ParseFailure, ParserError};
+use crate::protocols::ip::protocol::IpNextLevelProtocol;
+use nom::bytes::take;
+use nom::number::{be_u8, be_u32};
+use nom::{Parser, bits};
+use serde::{Deserialize, Serialize};
+
+// IPv6 Extension Headers
+// RFC 8200, Section 4: https://datatracker.ietf.org/doc/html/rfc8200#section-4
+
+pub const FRAGMENT_OFFSET_LENGTH_BITS: usize = 13;
+pub const FRAGMENT_RESERVED_LENGTH_BITS: usize = 2;
+pub const FRAGMENT_MORE_LENGTH_BITS: usize = 1;
+// Header Extension Length is in 8-octet units, not including the first 8 octets.
+// Next Header & Header Extension Length are already parsed, so 6 bytes are left.
+pub const OPTIONS_MINIMUM_LENGTH_BYTES: usize = 6;
+// Next Header, Header Extension Length, Routing Type & Segments Left are parsed
+pub const ROUTING_MINIMUM_LENGTH_BYTES: usize = 4;
+// Limits the chain, so crafted packets can't make the parser walk for too long
+pub const MAX_HEADERS: usize = 8;
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum ExtensionHeader {
+    HopByHop,
+    Routing { routing_type: u8, segments_left: u8 },
+    Fragment(Fragment),
+    DestinationOptions,
+}
+
+impl std::fmt::Display for ExtensionHeader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::HopByHop => write!(f, "Hop-by-Hop"),
+            Self::Routing { routing_type, .. } => write!(f, "Routing ({routing_type})"),
+            // Offset in bytes
+            Self::Fragment(fragment) => write!(
+                f,
+                "Fragment ({}, ID {:#x})",
+                u32::from(fragment.offset).saturating_mul(8),
+                fragment.identification
+            ),
+            Self::DestinationOptions => write!(f, "Destination Options"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Fragment {
+    // In 8-octet units, relative to the start of the fragmentable part
+    pub offset: u16,
+    pub more_fragments: bool,
+    pub identification: u32,
+}
+
+impl Fragment {
+    // Only the first fragment contains the upper-layer header
+    pub fn is_first(&self) -> bool {
+        self.offset == 0
+    }
+}
+
+/// Walks the chain of extension headers, until the upper-layer protocol is reached.
+/// <br> Returns the upper-layer payload, parsed headers & upper-layer protocol.
+pub fn parse_chain(
+    bytes: &[u8], next_header: IpNextLevelProtocol,
+) -> IResult<&[u8], (Vec<ExtensionHeader>, IpNextLevelProtocol)> {
+    let mut headers = vec![];
+    let mut rest = bytes;
+    let mut next_header = next_header;
+
+    for _ in 0..MAX_HEADERS {
+        let (after, (header, next)) = match next_header {
+            IpNextLevelProtocol::HOPOPT => options(rest, ExtensionHeader::HopByHop)?,
+            IpNextLevelProtocol::Ipv6Opts => {
+                options(rest, ExtensionHeader::DestinationOptions)?
+            },
+            IpNextLevelProtocol::Ipv6Route => routing(rest)?,
+            IpNextLevelProtocol::Ipv6Frag => fragment(rest)?,
+            _ => return Ok((rest, (headers, next_header))),
+        };
+
+        headers.push(header);
+        rest = after;
+        next_header = next;
+    }
+
+    Err(ParserError::ConstraintViolation {
+        description: "too many extension headers",
+    }
+    .to_nom(bytes))
+}
+
+fn next_header(bytes: &[u8]) -> IResult<&[u8], IpNextLevelProtocol> {
+    let (rest, next_header) = be_u8().parse(bytes)?;
+    let next_header = IpNextLevelProtocol::try_from(next_header).map_err(|_| {
+        ParserError::InvalidValue {
+            field: "next_header",
+        }
+        .to_nom(bytes)
+    })?;
+
+    Ok((rest, next_header))
+}
+
+fn data_length(
+    bytes: &[u8], extension_length: u8, parsed: usize,
+) -> Result<usize, nom::Err<ParseFailure<&[u8]>>> {
+    usize::from(extension_length)
+        .checked_mul(8)
+        .and_then(|length| length.checked_add(parsed))
+        .ok_or(
+            ParserError::InvalidValue {
+                field: "extension_length",
+            }
+            .to_nom(bytes),
+        )
+}
+
+// Hop-by-Hop & Destination Options. Options themselves are skipped
+fn options(
+    bytes: &[u8], header: ExtensionHeader,
+) -> IResult<&[u8], (ExtensionHeader, IpNextLevelProtocol)> {
+    let (rest, next) = next_header(bytes)?;
+    let (rest, extension_length) = be_u8().parse(rest)?;
+
+    let length = data_length(bytes, extension_length, OPTIONS_MINIMUM_LENGTH_BYTES)?;
+    let (rest, _) = take(length).parse(rest)?;
+
+    Ok((rest, (header, next)))
+}
+
+fn routing(bytes: &[u8]) -> IResult<&[u8], (ExtensionHeader, IpNextLevelProtocol)> {
+    let (rest, next) = next_header(bytes)?;
+    let (rest, extension_length) = be_u8().parse(rest)?;
+    let (rest, routing_type) = be_u8().parse(rest)?;
+    let (rest, segments_left) = be_u8().parse(rest)?;
+
+    let length = data_length(bytes, extension_length, ROUTING_MINIMUM_LENGTH_BYTES)?;
+    let (rest, _) = take(length).parse(rest)?;
+
+    let header = ExtensionHeader::Routing {
+        routing_type,
+        segments_left,
+    };
+    Ok((rest, (header, next)))
+}
+
+fn fragment(bytes: &[u8]) -> IResult<&[u8], (ExtensionHeader, IpNextLevelProtocol)> {
+    let (rest, next) = next_header(bytes)?;
+    // Reserved (1 byte)
+    let (rest, _) = be_u8().parse(rest)?;
+
+    // Fragment Offset (13 bits), Reserved (2 bits), More Fragments flag (1 bit)
+    let (rest, (offset, _, more_fragments)): (&[u8], (u16, u8, u8)) =
+        bits::bits::<_, _, ParseFailure<_>, _, _>((
+            bits::complete::take(FRAGMENT_OFFSET_LENGTH_BITS),
+            bits::complete::take(FRAGMENT_RESERVED_LENGTH_BITS),
+            bits::complete::take(FRAGMENT_MORE_LENGTH_BITS),
+        ))(rest)?;
+
+    // Identification (4 bytes)
+    let (rest, identification) = be_u32().parse(rest)?;
+
+    let header = ExtensionHeader::Fragment(Fragment {
+        offset,
+        more_fragments: more_fragments == 1,
+        identification,
+    });
+    Ok((rest, (header, next)))
+}
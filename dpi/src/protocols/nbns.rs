@@ -0,0 +1,584 @@
+use crate::dto::fields::{self, FieldIter};
+use crate::dto::wire;
+use crate::parser;
+use crate::parser::{IResult, ParseFailure, ParserError};
+use crate::protocols::ProtocolData;
+use crate::protocols::dns::{self, Class, MessageType};
+use crate::protocols::ip;
+use nom::bytes::take;
+use nom::number::{be_u8, be_u16, be_u32};
+use nom::{Finish, Parser, bits};
+use num_enum::TryFromPrimitive;
+use serde::{Deserialize, Serialize};
+use std::net::Ipv4Addr;
+use strum_macros::Display;
+
+// NetBIOS Name Service
+// RFC 1002: https://datatracker.ietf.org/doc/html/rfc1002#section-4.2
+// Packets are laid out like DNS ones, but names are encoded in the "half-ASCII" form.
+
+pub const MESSAGE_TYPE_LENGTH_BITS: usize = 1;
+pub const OPERATION_CODE_LENGTH_BITS: usize = 4;
+pub const FLAG_LENGTH_BITS: usize = 1;
+pub const RESERVED_LENGTH_BITS: usize = 2;
+pub const RESPONSE_CODE_LENGTH_BITS: usize = 4;
+pub const ENCODED_NAME_LENGTH: usize = 32;
+pub const NAME_LENGTH: usize = 15;
+pub const ADDRESS_ENTRY_LENGTH: usize = 6;
+pub const GROUP_NAME_FLAG: u16 = 0x8000;
+pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
+    // Name Transaction ID - 16 bits.
+    let (rest, id) = be_u16().parse(bytes)?;
+
+    // Response (R), Operation Code (OPCODE),
+    // NM Flags: Authoritative Answer (AA), Truncation (TC), Recursion Desired (RD),
+    // Recursion Available (RA), Reserved (2 bits), Broadcast (B).
+    // Response Code (RCODE)
+    type NbnsHeaderBits = (u8, u8, u8, u8, u8, u8, u8, u8, u8);
+    let (rest, (r, opcode, aa, tc, rd, ra, z, b, rcode)): (&[u8], NbnsHeaderBits) =
+        bits::bits::<_, _, ParseFailure<_>, _, _>((
+            bits::complete::take(MESSAGE_TYPE_LENGTH_BITS),
+            bits::complete::take(OPERATION_CODE_LENGTH_BITS),
+            bits::complete::take(FLAG_LENGTH_BITS),
+            bits::complete::take(FLAG_LENGTH_BITS),
+            bits::complete::take(FLAG_LENGTH_BITS),
+            bits::complete::take(FLAG_LENGTH_BITS),
+            bits::complete::take(RESERVED_LENGTH_BITS),
+            bits::complete::take(FLAG_LENGTH_BITS),
+            bits::complete::take(RESPONSE_CODE_LENGTH_BITS),
+        ))(rest)?;
+    let message_type = MessageType::try_from(r)
+        .map_err(|_| ParserError::InvalidValue { field: "r" }.to_nom(bytes))?;
+    let operation = Operation::try_from(opcode)
+        .map_err(|_| ParserError::InvalidValue { field: "opcode" }.to_nom(bytes))?;
+    let authoritative_answer =
+        parser::cast_to_bool(aa).map_err(|err| err.to_nom(bytes))?;
+    let truncation = parser::cast_to_bool(tc).map_err(|err| err.to_nom(bytes))?;
+    let recursion_desired = parser::cast_to_bool(rd).map_err(|err| err.to_nom(bytes))?;
+    let recursion_available =
+        parser::cast_to_bool(ra).map_err(|err| err.to_nom(bytes))?;
+    if z != 0 {
+        return Err(ParserError::ConstraintViolation {
+            description: "reserved bits are set",
+        }
+        .to_nom(bytes));
+    }
+    let broadcast = parser::cast_to_bool(b).map_err(|err| err.to_nom(bytes))?;
+
+    // QDCOUNT, ANCOUNT, NSCOUNT, ARCOUNT - 16 bits each
+    let (rest, question_entries) = be_u16().parse(rest)?;
+    let (rest, answer_records) = be_u16().parse(rest)?;
+    let (rest, authority_records) = be_u16().parse(rest)?;
+    let (mut rest, additional_records) = be_u16().parse(rest)?;
+
+    // QUESTION SECTION
+    let mut question_section: Vec<NameQuestion> = vec![];
+    for _ in 0..question_entries {
+        let (section_rest, question) = parse_question(rest, bytes)?;
+        question_section.push(question);
+        rest = section_rest;
+    }
+
+    // ANSWER, AUTHORITY, ADDITIONAL SECTIONS
+    let (rest, answer_section) = parse_record_section(rest, answer_records, bytes)?;
+    let (rest, authority_section) = parse_record_section(rest, authority_records, bytes)?;
+    let (rest, additional_section) =
+        parse_record_section(rest, additional_records, bytes)?;
+
+    if !rest.is_empty() {
+        return Err(ParserError::ConstraintViolation {
+            description: "bytes are left after the sections",
+        }
+        .to_nom(bytes));
+    }
+
+    let protocol = NBNS {
+        id,
+        message_type,
+        operation,
+        authoritative_answer,
+        truncation,
+        recursion_desired,
+        recursion_available,
+        broadcast,
+        response_code: rcode,
+
+        question_section,
+        answer_section,
+        authority_section,
+        additional_section,
+    };
+
+    Finish::finish(Ok((rest, ProtocolData::NBNS(protocol))))
+}
+
+pub fn is_protocol_default(port_source: u16, port_destination: u16) -> bool {
+    const PORT_NBNS: u16 = 137;
+
+    port_source == PORT_NBNS || port_destination == PORT_NBNS
+}
+
+fn parse_question<'a>(
+    bytes: &'a [u8], whole: &'a [u8],
+) -> IResult<&'a [u8], NameQuestion> {
+    let (rest, name) = parse_name(bytes, whole)?;
+
+    let (rest, question_type) = be_u16().parse(rest)?;
+    let question_type = RecordType::try_from(question_type)
+        .map_err(|_| ParserError::InvalidValue { field: "qtype" }.to_nom(bytes))?;
+
+    let (rest, class) = be_u16().parse(rest)?;
+    let class = Class::try_from(class)
+        .map_err(|_| ParserError::InvalidValue { field: "qclass" }.to_nom(bytes))?;
+
+    let question = NameQuestion {
+        name,
+        question_type,
+        class,
+    };
+
+    Ok((rest, question))
+}
+
+fn parse_record_section<'a>(
+    bytes: &'a [u8], records: u16, whole: &'a [u8],
+) -> IResult<&'a [u8], Vec<NameRecord>> {
+    let mut rest = bytes;
+    let mut vec: Vec<NameRecord> = vec![];
+    for _ in 0..records {
+        let (section_rest, record) = parse_record(rest, whole)?;
+        vec.push(record);
+        rest = section_rest;
+    }
+
+    Ok((rest, vec))
+}
+
+fn parse_record<'a>(bytes: &'a [u8], whole: &'a [u8]) -> IResult<&'a [u8], NameRecord> {
+    // RR_NAME
+    let (rest, name) = parse_name(bytes, whole)?;
+
+    // RR_TYPE
+    let (rest, record_type) = be_u16().parse(rest)?;
+    let record_type = RecordType::try_from(record_type)
+        .map_err(|_| ParserError::InvalidValue { field: "rr_type" }.to_nom(bytes))?;
+
+    // RR_CLASS
+    let (rest, class) = be_u16().parse(rest)?;
+    let class = Class::try_from(class)
+        .map_err(|_| ParserError::InvalidValue { field: "rr_class" }.to_nom(bytes))?;
+
+    // TTL
+    let (rest, time_to_live) = be_u32().parse(rest)?;
+
+    // RDLENGTH, RDATA
+    let (rest, data_length) = be_u16().parse(rest)?;
+    let (rest, data) = take(data_length).parse(rest)?;
+    let (_, data) = match record_type {
+        RecordType::NB => parse_addresses(data)?,
+        RecordType::NBSTAT => parse_node_status(data)?,
+        _ => (data, NameRecordData::Unknown),
+    };
+
+    let record = NameRecord {
+        name,
+        record_type,
+        class,
+        time_to_live,
+        data,
+    };
+
+    Ok((rest, record))
+}
+
+// NB_FLAGS (16 bits) & NB_ADDRESS (32 bits), repeated.
+fn parse_addresses(bytes: &[u8]) -> IResult<&[u8], NameRecordData> {
+    if bytes.len() % ADDRESS_ENTRY_LENGTH != 0 {
+        return Err(ParserError::ConstraintViolation {
+            description: "address entries aren't whole",
+        }
+        .to_nom(bytes));
+    }
+
+    let mut addresses = vec![];
+    let mut rest = bytes;
+    while !rest.is_empty() {
+        let (entry_rest, flags) = be_u16().parse(rest)?;
+        let (entry_rest, address) = ip::address::v4_parse(entry_rest)?;
+        addresses.push(NameAddress {
+            is_group: flags & GROUP_NAME_FLAG != 0,
+            address,
+        });
+        rest = entry_rest;
+    }
+
+    Ok((rest, NameRecordData::Addresses(addresses)))
+}
+
+// NUM_NAMES (8 bits), NODE_NAME array, STATISTICS.
+fn parse_node_status(bytes: &[u8]) -> IResult<&[u8], NameRecordData> {
+    let (mut rest, names_amount) = be_u8().parse(bytes)?;
+
+    let mut names = vec![];
+    for _ in 0..names_amount {
+        let (entry_rest, name) = take(NAME_LENGTH).parse(rest)?;
+        let (entry_rest, suffix) = be_u8().parse(entry_rest)?;
+        let (entry_rest, flags) = be_u16().parse(entry_rest)?;
+        names.push(NodeName {
+            name: NetbiosName {
+                name: String::from_utf8_lossy(name).trim_end().to_string(),
+                suffix,
+            },
+            is_group: flags & GROUP_NAME_FLAG != 0,
+        });
+        rest = entry_rest;
+    }
+
+    // Statistics are skipped
+    Ok((rest, NameRecordData::NodeStatus(names)))
+}
+
+// Names are compressed like DNS ones. The first label is the encoded NetBIOS name,
+// the rest ones are the scope.
+fn parse_name<'a>(bytes: &'a [u8], whole: &'a [u8]) -> IResult<&'a [u8], NetbiosName> {
+    let (rest, name) = dns::parse_name(bytes, whole, 1)?;
+    let encoded = name.split('.').next().unwrap_or_default();
+    let name = decode_name(encoded)
+        .ok_or(ParserError::InvalidValue { field: "name" }.to_nom(bytes))?;
+
+    Ok((rest, name))
+}
+
+// RFC 1001, 14.1: Every half-byte of the 16-byte name is added to 'A'.
+pub fn decode_name(encoded: &str) -> Option<NetbiosName> {
+    let encoded = encoded.as_bytes();
+    if encoded.len() != ENCODED_NAME_LENGTH {
+        return None;
+    }
+
+    let mut decoded: Vec<u8> = Vec::with_capacity(ENCODED_NAME_LENGTH / 2);
+    for pair in encoded.chunks_exact(2) {
+        let [high, low] = pair else {
+            return None;
+        };
+        let high = high.checked_sub(b'A').filter(|value| *value < 16)?;
+        let low = low.checked_sub(b'A').filter(|value| *value < 16)?;
+        decoded.push((high << 4) | low);
+    }
+
+    let (suffix, name) = decoded.split_last()?;
+    Some(NetbiosName {
+        name: String::from_utf8_lossy(name).trim_end().to_string(),
+        suffix: *suffix,
+    })
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NBNS {
+    pub id: u16,
+    pub message_type: MessageType,
+    pub operation: Operation,
+    pub authoritative_answer: bool,
+    pub truncation: bool,
+    pub recursion_desired: bool,
+    pub recursion_available: bool,
+    pub broadcast: bool,
+    pub response_code: u8,
+
+    pub question_section: Vec<NameQuestion>,
+    pub answer_section: Vec<NameRecord>,
+    pub authority_section: Vec<NameRecord>,
+    pub additional_section: Vec<NameRecord>,
+}
+
+#[derive(Clone, Debug, Display, Serialize, Deserialize, PartialEq, TryFromPrimitive)]
+#[repr(u8)]
+pub enum Operation {
+    Query = 0,
+    Registration = 5,
+    Release = 6,
+    #[strum(to_string = "Wait for Acknowledgement")]
+    WaitForAcknowledgement = 7,
+    #[num_enum(alternatives = [9])]
+    Refresh = 8,
+    #[strum(to_string = "Multi-Homed Registration")]
+    MultiHomedRegistration = 15,
+}
+
+#[derive(Clone, Debug, Display, Serialize, Deserialize, PartialEq, TryFromPrimitive)]
+#[repr(u16)]
+pub enum RecordType {
+    A = 0x0001,
+    NS = 0x0002,
+    NULL = 0x000A,
+    NB = 0x0020,
+    NBSTAT = 0x0021,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct NetbiosName {
+    pub name: String,
+    // 16th byte of the name, e.g. 0x00 - Workstation, 0x20 - File Server.
+    pub suffix: u8,
+}
+
+impl NetbiosName {
+    pub const SUFFIX_WORKSTATION: u8 = 0x00;
+    pub const SUFFIX_FILE_SERVER: u8 = 0x20;
+
+    pub fn is_host(&self) -> bool {
+        self.suffix == Self::SUFFIX_WORKSTATION || self.suffix == Self::SUFFIX_FILE_SERVER
+    }
+}
+
+impl std::fmt::Display for NetbiosName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}<{:02X}>", self.name, self.suffix)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct NameQuestion {
+    pub name: NetbiosName,
+    pub question_type: RecordType,
+    pub class: Class,
+}
+
+impl std::fmt::Display for NameQuestion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct NameRecord {
+    pub name: NetbiosName,
+    pub record_type: RecordType,
+    pub class: Class,
+    pub time_to_live: u32,
+    pub data: NameRecordData,
+}
+
+impl std::fmt::Display for NameRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.name, self.data)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum NameRecordData {
+    Addresses(Vec<NameAddress>),
+    NodeStatus(Vec<NodeName>),
+    Unknown,
+}
+
+impl std::fmt::Display for NameRecordData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            NameRecordData::Addresses(addresses) => addresses
+                .iter()
+                .map(|entry| entry.address.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            NameRecordData::NodeStatus(names) => names
+                .iter()
+                .map(|entry| entry.name.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+            NameRecordData::Unknown => "-".to_string(),
+        };
+
+        write!(f, "{text}")
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct NameAddress {
+    pub is_group: bool,
+    pub address: Ipv4Addr,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct NodeName {
+    pub name: NetbiosName,
+    pub is_group: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct NbnsDto {
+    #[serde(rename = "r")]
+    pub message_type: MessageType,
+    #[serde(rename = "op")]
+    pub operation: Operation,
+    #[serde(rename = "b", default, skip_serializing_if = "wire::is_default")]
+    pub broadcast: bool,
+    #[serde(rename = "rc")]
+    pub response_code: u8,
+    #[serde(rename = "qd", default, skip_serializing_if = "Vec::is_empty")]
+    pub question_section: Vec<NameQuestion>,
+    // Answer, authority & additional sections together
+    #[serde(rename = "rr", default, skip_serializing_if = "Vec::is_empty")]
+    pub records: Vec<NameRecord>,
+}
+
+impl NbnsDto {
+    /// Host names announced by the packet. <br>
+    /// Address is `None` when the name belongs to the sender (node status response).
+    pub fn hosts(&self) -> Vec<(String, Option<Ipv4Addr>)> {
+        let mut hosts = vec![];
+        for record in &self.records {
+            match &record.data {
+                NameRecordData::Addresses(addresses) if record.name.is_host() => {
+                    for entry in addresses.iter().filter(|entry| !entry.is_group) {
+                        hosts.push((record.name.name.clone(), Some(entry.address)));
+                    }
+                },
+                NameRecordData::NodeStatus(names) => {
+                    if let Some(entry) = names.iter().find(|entry| {
+                        !entry.is_group
+                            && entry.name.suffix == NetbiosName::SUFFIX_WORKSTATION
+                    }) {
+                        hosts.push((entry.name.name.clone(), None));
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        hosts
+    }
+}
+
+impl From<NBNS> for NbnsDto {
+    fn from(value: NBNS) -> Self {
+        Self {
+            message_type: value.message_type,
+            operation: value.operation,
+            broadcast: value.broadcast,
+            response_code: value.response_code,
+            question_section: value.question_section,
+            records: value
+                .answer_section
+                .into_iter()
+                .chain(value.authority_section)
+                .chain(value.additional_section)
+                .collect(),
+        }
+    }
+}
+
+impl FieldIter for NbnsDto {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let Self {
+            message_type,
+            operation,
+            broadcast,
+            response_code,
+            question_section,
+            records,
+        } = self;
+
+        vec![
+            ("message_type", message_type.to_string()),
+            ("operation", operation.to_string()),
+            ("broadcast", broadcast.to_string()),
+            ("response_code", response_code.to_string()),
+            ("question_section", fields::list(question_section)),
+            ("records", fields::list(records)),
+        ]
+    }
+}
+
+#[cfg(all(test, feature = "ethernet", feature = "ipv4", feature = "udp"))]
+mod tests {
+    use super::*;
+    use crate::dto::frame::{FrameHeader, LinkType};
+    use crate::parser::tests::FrameType;
+    use crate::parser::tests::ProtocolParser;
+    use crate::protocols::ip::checksum::ChecksumStatus;
+    use crate::protocols::udp::UDP;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_nbns_registration() {
+        let hex_actual = "FF FF FF FF FF FF 08 00 27 AA BB CC 08 00 45 00 00 60 12 34 00 00 80 11 00 00 C0 A8 01 14 C0 A8 01 FF 00 89 00 89 00 4C 00 00 80 01 29 10 00 01 00 00 00 00 00 01 20 46 48 45 50 46 43 45 4C 46 44 46 45 45 42 46 45 45 4A 45 50 45 4F 44 42 43 41 43 41 43 41 41 41 00 00 20 00 01 C0 0C 00 20 00 01 00 04 93 E0 00 06 00 00 C0 A8 01 14".replace(" ", "");
+        let frame = hex::decode(hex_actual).unwrap();
+        let header = FrameHeader::new(110);
+
+        let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+        let result = parser.process_bytes(&header, &frame);
+        let metadata = match result {
+            Some(value) => match value {
+                FrameType::Metadata(value) => value,
+                _ => panic!(),
+            },
+            None => panic!(),
+        };
+
+        let actual_udp = match metadata.layers[2].clone() {
+            ProtocolData::UDP(value) => value,
+            _ => panic!(),
+        };
+
+        let expected_udp = UDP {
+            port_source: 137,
+            port_destination: 137,
+            length: 76,
+            length_mismatch: None,
+            checksum: 0x0000,
+            checksum_status: ChecksumStatus::NotChecked,
+        };
+
+        assert_eq!(actual_udp, expected_udp);
+
+        let actual_nbns = match metadata.layers[3].clone() {
+            ProtocolData::NBNS(value) => value,
+            _ => panic!(),
+        };
+
+        let name = NetbiosName {
+            name: "WORKSTATION1".to_string(),
+            suffix: NetbiosName::SUFFIX_WORKSTATION,
+        };
+        let expected_nbns = NBNS {
+            id: 0x8001,
+            message_type: MessageType::Query,
+            operation: Operation::Registration,
+            authoritative_answer: false,
+            truncation: false,
+            recursion_desired: true,
+            recursion_available: false,
+            broadcast: true,
+            response_code: 0,
+
+            question_section: vec![NameQuestion {
+                name: name.clone(),
+                question_type: RecordType::NB,
+                class: Class::IN,
+            }],
+            answer_section: vec![],
+            authority_section: vec![],
+            additional_section: vec![NameRecord {
+                name,
+                record_type: RecordType::NB,
+                class: Class::IN,
+                time_to_live: 300000,
+                data: NameRecordData::Addresses(vec![NameAddress {
+                    is_group: false,
+                    address: Ipv4Addr::from_str("192.168.1.20").unwrap(),
+                }]),
+            }],
+        };
+
+        assert_eq!(actual_nbns, expected_nbns);
+
+        let dto = NbnsDto::from(actual_nbns);
+        assert_eq!(
+            dto.hosts(),
+            vec![(
+                "WORKSTATION1".to_string(),
+                Some(Ipv4Addr::from_str("192.168.1.20").unwrap())
+            )]
+        );
+    }
+}
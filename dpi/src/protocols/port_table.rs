@@ -0,0 +1,226 @@
+use crate::protocols::ProtocolId;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use strum::IntoEnumIterator;
+use thiserror::Error;
+
+/// Ports, by which the application protocols are recognized over TCP & UDP. <br>
+/// Protocol with the overridden ports is matched only by them, the rest are matched
+/// by their defaults (`is_protocol_default`).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PortTable {
+    overrides: Vec<PortOverride>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PortOverride {
+    pub protocol: ProtocolId,
+    pub ports: Vec<u16>,
+}
+
+impl PortTable {
+    /// Protocols, that are recognized by the ports.
+    pub fn configurable() -> impl Iterator<Item = ProtocolId> {
+        ProtocolId::iter().filter(|id| id.check_ports().is_some())
+    }
+
+    /// Name, used in the config (e.g. `http`, `dhcpv4`).
+    pub fn name(protocol: &ProtocolId) -> String {
+        protocol.to_string().to_ascii_lowercase()
+    }
+
+    /// Case-insensitive, only the configurable protocols are found.
+    pub fn protocol_by_name(name: &str) -> Option<ProtocolId> {
+        Self::configurable().find(|id| id.to_string().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Table & the names, that aren't the configurable protocols.
+    pub fn from_names(names: &BTreeMap<String, Vec<u16>>) -> (Self, Vec<String>) {
+        let mut table = Self::default();
+        let mut unknown = vec![];
+        for (name, ports) in names {
+            match Self::protocol_by_name(name) {
+                Some(protocol) => table.set_unchecked(protocol, ports),
+                None => unknown.push(name.clone()),
+            }
+        }
+
+        (table, unknown)
+    }
+
+    pub fn to_names(&self) -> BTreeMap<String, Vec<u16>> {
+        self.overrides
+            .iter()
+            .map(|entry| (Self::name(&entry.protocol), entry.ports.clone()))
+            .collect()
+    }
+
+    /// Empty list brings back the default ports of the protocol.
+    pub fn set(
+        &mut self, protocol: ProtocolId, ports: &[u16],
+    ) -> Result<(), PortTableError> {
+        if protocol.check_ports().is_none() {
+            return Err(PortTableError::NotConfigurable(protocol));
+        }
+        self.set_unchecked(protocol, ports);
+
+        Ok(())
+    }
+
+    /// Overridden ports of the protocol, `None` if the defaults are used.
+    pub fn get(&self, protocol: &ProtocolId) -> Option<&[u16]> {
+        self.overrides
+            .iter()
+            .find(|entry| entry.protocol == *protocol)
+            .map(|entry| entry.ports.as_slice())
+    }
+
+    pub fn overrides(&self) -> &[PortOverride] {
+        &self.overrides
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.overrides.is_empty()
+    }
+
+    pub fn is_matched(
+        &self, protocol: &ProtocolId, port_source: u16, port_destination: u16,
+    ) -> bool {
+        match self.get(protocol) {
+            Some(ports) => {
+                ports.contains(&port_source) || ports.contains(&port_destination)
+            },
+            None => protocol
+                .check_ports()
+                .is_some_and(|is_default| is_default(port_source, port_destination)),
+        }
+    }
+
+    /// The overridden protocols go first, so the configured port is taken
+    /// even if it's the default one of another protocol.
+    /// The rest are checked in the order of the children.
+    pub fn best(
+        &self, children: &[ProtocolId], port_source: u16, port_destination: u16,
+    ) -> Option<ProtocolId> {
+        let (overridden, defaults): (Vec<&ProtocolId>, Vec<&ProtocolId>) =
+            children.iter().partition(|id| self.get(id).is_some());
+
+        overridden
+            .into_iter()
+            .chain(defaults)
+            .find(|id| self.is_matched(id, port_source, port_destination))
+            .copied()
+    }
+
+    fn set_unchecked(&mut self, protocol: ProtocolId, ports: &[u16]) {
+        self.overrides.retain(|entry| entry.protocol != protocol);
+        if ports.is_empty() {
+            return;
+        }
+
+        let mut ports = ports.to_vec();
+        ports.sort_unstable();
+        ports.dedup();
+        self.overrides.push(PortOverride { protocol, ports });
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PortTableError {
+    #[error("{0} isn't recognized by the ports.")]
+    NotConfigurable(ProtocolId),
+}
+
+#[cfg(all(
+    test,
+    feature = "dns",
+    feature = "http",
+    feature = "ipv4",
+    feature = "sip",
+    feature = "tcp",
+    feature = "udp"
+))]
+mod tests {
+    use super::*;
+    use crate::dto::frame::LinkType;
+    use crate::parser::tests::{FrameType, ProtocolParser, tcp_metadata};
+    use crate::protocols::ProtocolData;
+
+    #[test]
+    fn test_from_names() {
+        let names = BTreeMap::from([
+            ("HTTP".to_string(), vec![8080, 80, 3128, 8080]),
+            ("dns".to_string(), vec![53, 5300]),
+            ("gopher".to_string(), vec![70]),
+            // Not recognized by the ports
+            ("tcp".to_string(), vec![1]),
+        ]);
+
+        let (table, unknown) = PortTable::from_names(&names);
+        assert_eq!(
+            table.get(&ProtocolId::HTTP),
+            Some([80, 3128, 8080].as_slice())
+        );
+        assert_eq!(table.get(&ProtocolId::DNS), Some([53, 5300].as_slice()));
+        assert_eq!(table.get(&ProtocolId::SIP), None);
+        assert_eq!(unknown, vec!["gopher".to_string(), "tcp".to_string()]);
+
+        let names = table.to_names();
+        assert_eq!(names.get("http"), Some(&vec![80, 3128, 8080]));
+        assert_eq!(names.len(), 2);
+    }
+
+    #[test]
+    fn test_set_ports() {
+        let mut table = PortTable::default();
+        assert!(table.set(ProtocolId::TCP, &[80]).is_err());
+
+        table.set(ProtocolId::DNS, &[5300]).unwrap();
+        assert!(table.is_matched(&ProtocolId::DNS, 49152, 5300));
+        // Overridden list replaces the defaults
+        assert!(!table.is_matched(&ProtocolId::DNS, 49152, 53));
+
+        table.set(ProtocolId::DNS, &[]).unwrap();
+        assert!(table.is_empty());
+        assert!(table.is_matched(&ProtocolId::DNS, 49152, 53));
+    }
+
+    #[test]
+    fn test_overridden_port_goes_first() {
+        let mut table = PortTable::default();
+        let children = ProtocolId::UDP.children().unwrap();
+        assert_eq!(table.best(&children, 5060, 5060), Some(ProtocolId::SIP));
+
+        // HTTP is the first child, but the port is explicitly given to SIP
+        table.set(ProtocolId::SIP, &[80]).unwrap();
+        assert_eq!(table.best(&children, 49152, 80), Some(ProtocolId::SIP));
+        assert_eq!(table.best(&children, 5060, 5060), None);
+    }
+
+    // HTTP request on 8080
+    fn http_layers(table: PortTable) -> Vec<ProtocolData> {
+        let request = "GET / HTTP/1.1\r\nHost: example.com\r\n\r\n";
+        let parser =
+            ProtocolParser::new(LinkType::IPV4, false).with_protocol_ports(table);
+        match tcp_metadata(&parser, 49152, 8080, request.as_bytes()) {
+            Some(FrameType::Metadata(metadata)) => metadata.layers,
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn test_http_on_configured_port() {
+        // 8080 isn't the HTTP port by default, the request is recognized by the signature
+        let layers = http_layers(PortTable::default());
+        assert_eq!(layers.len(), 3);
+        assert!(matches!(layers[2], ProtocolData::HTTP(_)));
+
+        let (table, _) = PortTable::from_names(&BTreeMap::from([(
+            "http".to_string(),
+            vec![80, 8080, 3128],
+        )]));
+        let layers = http_layers(table);
+        assert_eq!(layers.len(), 3);
+        assert!(matches!(layers[2], ProtocolData::HTTP(_)));
+    }
+}
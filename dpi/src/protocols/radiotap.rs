@@ -0,0 +1,217 @@
+use crate::dto::fields::{self, FieldIter};
+use crate::dto::frame::FrameMetadata;
+use crate::parser::{IResult, ParserError};
+use crate::protocols::{ProtocolData, ProtocolId};
+use nom::Parser;
+use nom::number::{le_u16, le_u32};
+use serde::{Deserialize, Serialize};
+
+// Radiotap header for 802.11 frames captured in monitor mode.
+// Specification: https://www.radiotap.org/
+// Only the fields up to "Antenna Signal" are extracted, other fields are skipped by the header length.
+
+pub const HEADER_LENGTH: usize = 8;
+pub const VERSION: u8 = 0;
+
+// Bits of the "present" word
+pub const PRESENT_TSFT: u32 = 1 << 0;
+pub const PRESENT_FLAGS: u32 = 1 << 1;
+pub const PRESENT_RATE: u32 = 1 << 2;
+pub const PRESENT_CHANNEL: u32 = 1 << 3;
+pub const PRESENT_FHSS: u32 = 1 << 4;
+pub const PRESENT_ANTENNA_SIGNAL: u32 = 1 << 5;
+pub const PRESENT_EXTENDED: u32 = 1 << 31;
+
+// Frame includes FCS at the end
+pub const FLAG_FCS: u8 = 0x10;
+pub const FCS_LENGTH: usize = 4;
+
+// Fields in the order of their bits: (bit, alignment, size)
+const FIELDS: [(u32, usize, usize); 6] = [
+    (PRESENT_TSFT, 8, 8),
+    (PRESENT_FLAGS, 1, 1),
+    (PRESENT_RATE, 1, 1),
+    (PRESENT_CHANNEL, 2, 4),
+    (PRESENT_FHSS, 2, 2),
+    (PRESENT_ANTENNA_SIGNAL, 1, 1),
+];
+
+pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
+    let truncated = |needed: usize| {
+        ParserError::Truncated {
+            needed,
+            available: bytes.len(),
+        }
+        .to_nom(bytes)
+    };
+    if bytes.len() < HEADER_LENGTH {
+        return Err(truncated(HEADER_LENGTH));
+    }
+
+    // Version, Pad
+    let (version, rest) = match bytes {
+        [version, _, rest @ ..] => (*version, rest),
+        _ => return Err(truncated(HEADER_LENGTH)),
+    };
+    if version != VERSION {
+        return Err(ParserError::InvalidValue { field: "version" }.to_nom(bytes));
+    }
+
+    // Length of the whole radiotap header
+    let (rest, length) = le_u16().parse(rest)?;
+    if usize::from(length) < HEADER_LENGTH {
+        return Err(ParserError::InvalidValue { field: "length" }.to_nom(bytes));
+    }
+    let (header, frame) = bytes
+        .split_at_checked(usize::from(length))
+        .ok_or_else(|| truncated(usize::from(length)))?;
+
+    // Present words. Extended bitmaps are skipped, only the first one is used
+    let (mut fields, present) = le_u32().parse(rest)?;
+    let mut word = present;
+    while word & PRESENT_EXTENDED != 0 {
+        let (rest, next) = le_u32().parse(fields)?;
+        fields = rest;
+        word = next;
+    }
+
+    let mut radiotap = Radiotap {
+        length,
+        flags: None,
+        rate: None,
+        frequency: None,
+        signal: None,
+    };
+
+    // Fields are aligned relative to the start of the header
+    let mut offset = bytes
+        .len()
+        .checked_sub(fields.len())
+        .ok_or_else(|| truncated(usize::from(length)))?;
+    for (bit, alignment, size) in FIELDS {
+        if present & bit == 0 {
+            continue;
+        }
+
+        let value = offset
+            .checked_next_multiple_of(alignment)
+            .and_then(|start| {
+                offset = start.checked_add(size)?;
+                header.get(start..offset)
+            })
+            .ok_or(
+                ParserError::ConstraintViolation {
+                    description: "field is past the header length",
+                }
+                .to_nom(bytes),
+            )?;
+
+        match (bit, value) {
+            (PRESENT_FLAGS, [flags]) => radiotap.flags = Some(*flags),
+            (PRESENT_RATE, [rate]) => radiotap.rate = Some(*rate),
+            (PRESENT_CHANNEL, [low, high, ..]) => {
+                radiotap.frequency = Some(u16::from_le_bytes([*low, *high]))
+            },
+            (PRESENT_ANTENNA_SIGNAL, [signal]) => {
+                radiotap.signal = Some(i8::from_le_bytes([*signal]))
+            },
+            _ => {},
+        }
+    }
+
+    // Cutting FCS
+    let frame = match radiotap.has_fcs() {
+        true => frame
+            .len()
+            .checked_sub(FCS_LENGTH)
+            .and_then(|length| frame.get(..length))
+            .ok_or(
+                ParserError::ConstraintViolation {
+                    description: "frame is shorter than the FCS",
+                }
+                .to_nom(bytes),
+            )?,
+        false => frame,
+    };
+
+    Ok((frame, ProtocolData::Radiotap(radiotap)))
+}
+
+pub fn best_children(metadata: &FrameMetadata) -> Option<ProtocolId> {
+    match metadata.layers.first() {
+        Some(ProtocolData::Radiotap(_)) => Some(ProtocolId::IEEE80211),
+        _ => None,
+    }
+}
+
+/// Converts the center frequency (MHz) into the channel number.
+pub fn channel(frequency: u16) -> Option<u16> {
+    const CHANNEL_WIDTH: u16 = 5;
+    const CHANNEL_14: u16 = 2484;
+
+    let base = match frequency {
+        CHANNEL_14 => return Some(14),
+        2412..=2472 => 2407,
+        5000..=5925 => 5000,
+        5955..=7115 => 5950,
+        _ => return None,
+    };
+
+    frequency
+        .checked_sub(base)
+        .and_then(|offset| offset.checked_div(CHANNEL_WIDTH))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Radiotap {
+    pub length: u16,
+    pub flags: Option<u8>,
+    // In 500 Kbps units
+    pub rate: Option<u8>,
+    // MHz
+    pub frequency: Option<u16>,
+    // dBm
+    pub signal: Option<i8>,
+}
+
+impl Radiotap {
+    pub fn has_fcs(&self) -> bool {
+        self.flags.is_some_and(|flags| flags & FLAG_FCS != 0)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct RadiotapDto {
+    #[serde(rename = "f", default, skip_serializing_if = "Option::is_none")]
+    pub frequency: Option<u16>,
+    #[serde(rename = "ch", default, skip_serializing_if = "Option::is_none")]
+    pub channel: Option<u16>,
+    #[serde(rename = "s", default, skip_serializing_if = "Option::is_none")]
+    pub signal: Option<i8>,
+}
+
+impl From<Radiotap> for RadiotapDto {
+    fn from(value: Radiotap) -> Self {
+        Self {
+            frequency: value.frequency,
+            channel: value.frequency.and_then(channel),
+            signal: value.signal,
+        }
+    }
+}
+
+impl FieldIter for RadiotapDto {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let Self {
+            frequency,
+            channel,
+            signal,
+        } = self;
+
+        vec![
+            ("frequency", fields::optional(frequency)),
+            ("channel", fields::optional(channel)),
+            ("signal", fields::optional(signal)),
+        ]
+    }
+}
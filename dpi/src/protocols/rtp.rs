@@ -0,0 +1,352 @@
+use crate::dto::fields::FieldIter;
+use crate::dto::wire;
+use crate::parser;
+use crate::parser::{IResult, ParseFailure, ParserError};
+use crate::protocols::ProtocolData;
+use nom::bytes::take;
+use nom::number::{be_u16, be_u32};
+use nom::{Parser, bits};
+use serde::{Deserialize, Serialize};
+
+// Real-time Transport Protocol
+// RFC 3550: https://datatracker.ietf.org/doc/html/rfc3550
+// It has no assigned port, so it's recognized heuristically: by the header fields & even
+// unprivileged ports (RFC 3550, 11). Ports negotiated via SDP are matched by the client.
+
+pub const VERSION: u8 = 2;
+pub const VERSION_LENGTH_BITS: usize = 2;
+pub const FLAG_LENGTH_BITS: usize = 1;
+pub const CSRC_COUNT_LENGTH_BITS: usize = 4;
+pub const PAYLOAD_TYPE_LENGTH_BITS: usize = 7;
+pub const EXTENSION_WORD_LENGTH: usize = 4;
+pub const DYNAMIC_PAYLOAD_TYPES: std::ops::RangeInclusive<u8> = 96..=127;
+pub const MIN_PORT: u16 = 1024;
+
+// RFC 3551, 6. Payload Type Definitions: Type, encoding & clock rate
+pub const STATIC_PAYLOAD_TYPES: &[(u8, &str, u32)] = &[
+    (0, "PCMU", 8000),
+    (3, "GSM", 8000),
+    (4, "G723", 8000),
+    (5, "DVI4", 8000),
+    (6, "DVI4", 16000),
+    (7, "LPC", 8000),
+    (8, "PCMA", 8000),
+    (9, "G722", 8000),
+    (10, "L16", 44100),
+    (11, "L16", 44100),
+    (12, "QCELP", 8000),
+    (13, "CN", 8000),
+    (14, "MPA", 90000),
+    (15, "G728", 8000),
+    (16, "DVI4", 11025),
+    (17, "DVI4", 22050),
+    (18, "G729", 8000),
+    (25, "CelB", 90000),
+    (26, "JPEG", 90000),
+    (28, "nv", 90000),
+    (31, "H261", 90000),
+    (32, "MPV", 90000),
+    (33, "MP2T", 90000),
+    (34, "H263", 90000),
+];
+pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
+    // Version (2 bits), Padding (1 bit), Extension (1 bit), CSRC Count (4 bits),
+    // Marker (1 bit), Payload Type (7 bits)
+    type RtpHeaderBits = (u8, u8, u8, u8, u8, u8);
+    let (rest, (version, p, x, csrc_count, m, payload_type)): (&[u8], RtpHeaderBits) =
+        bits::bits::<_, _, ParseFailure<_>, _, _>((
+            bits::complete::take(VERSION_LENGTH_BITS),
+            bits::complete::take(FLAG_LENGTH_BITS),
+            bits::complete::take(FLAG_LENGTH_BITS),
+            bits::complete::take(CSRC_COUNT_LENGTH_BITS),
+            bits::complete::take(FLAG_LENGTH_BITS),
+            bits::complete::take(PAYLOAD_TYPE_LENGTH_BITS),
+        ))(bytes)?;
+    if version != VERSION {
+        return Err(ParserError::InvalidValue { field: "version" }.to_nom(bytes));
+    }
+    // Unassigned types are rejected, RTCP packets fall there too
+    if !is_payload_type_known(payload_type) {
+        return Err(ParserError::InvalidValue {
+            field: "payload_type",
+        }
+        .to_nom(bytes));
+    }
+    let padding = parser::cast_to_bool(p).map_err(|err| err.to_nom(bytes))?;
+    let extension = parser::cast_to_bool(x).map_err(|err| err.to_nom(bytes))?;
+    let marker = parser::cast_to_bool(m).map_err(|err| err.to_nom(bytes))?;
+
+    let (rest, sequence_number) = be_u16().parse(rest)?;
+    let (rest, timestamp) = be_u32().parse(rest)?;
+    let (mut rest, ssrc) = be_u32().parse(rest)?;
+
+    let mut csrc = vec![];
+    for _ in 0..csrc_count {
+        let (csrc_rest, value) = be_u32().parse(rest)?;
+        csrc.push(value);
+        rest = csrc_rest;
+    }
+
+    if extension {
+        // Profile-defined (16 bits), Length in 32-bit words (16 bits)
+        let (extension_rest, _) = be_u16().parse(rest)?;
+        let (extension_rest, length) = be_u16().parse(extension_rest)?;
+        let length = usize::from(length)
+            .checked_mul(EXTENSION_WORD_LENGTH)
+            .ok_or(
+                ParserError::InvalidValue {
+                    field: "extension_length",
+                }
+                .to_nom(bytes),
+            )?;
+        let (extension_rest, _) = take(length).parse(extension_rest)?;
+        rest = extension_rest;
+    }
+
+    // Last octet of the padding is its length, including itself
+    let padding_length = match (padding, rest.last()) {
+        (false, _) => 0,
+        (true, Some(length)) if *length != 0 => usize::from(*length),
+        (true, _) => {
+            return Err(ParserError::InvalidValue {
+                field: "padding_length",
+            }
+            .to_nom(bytes));
+        },
+    };
+    let payload_length = rest.len().checked_sub(padding_length).ok_or(
+        ParserError::ConstraintViolation {
+            description: "padding is longer than the payload",
+        }
+        .to_nom(bytes),
+    )?;
+
+    let protocol = RTP {
+        version,
+        padding,
+        extension,
+        marker,
+        payload_type,
+        sequence_number,
+        timestamp,
+        ssrc,
+        csrc,
+        payload_length,
+    };
+
+    // Payload isn't parsed
+    let rest: &[u8] = &[];
+    Ok((rest, ProtocolData::RTP(protocol)))
+}
+
+pub fn is_protocol_default(port_source: u16, port_destination: u16) -> bool {
+    let is_rtp_port = |port: u16| port >= MIN_PORT && port % 2 == 0;
+
+    is_rtp_port(port_source) && is_rtp_port(port_destination)
+}
+
+pub fn is_payload_type_known(payload_type: u8) -> bool {
+    DYNAMIC_PAYLOAD_TYPES.contains(&payload_type)
+        || STATIC_PAYLOAD_TYPES
+            .iter()
+            .any(|(value, _, _)| *value == payload_type)
+}
+
+/// Encoding of the static payload type. Dynamic ones are negotiated via SDP.
+pub fn encoding(payload_type: u8) -> Option<&'static str> {
+    STATIC_PAYLOAD_TYPES
+        .iter()
+        .find(|(value, _, _)| *value == payload_type)
+        .map(|(_, encoding, _)| *encoding)
+}
+
+/// Clock rate of the static payload type, in Hz. Dynamic ones are negotiated via SDP.
+pub fn clock_rate(payload_type: u8) -> Option<u32> {
+    STATIC_PAYLOAD_TYPES
+        .iter()
+        .find(|(value, _, _)| *value == payload_type)
+        .map(|(_, _, clock_rate)| *clock_rate)
+}
+
+/// Type with its encoding, if it's known: "PCMU (0)".
+pub fn payload_type_name(payload_type: u8, encoding: Option<&str>) -> String {
+    match encoding {
+        Some(encoding) => format!("{encoding} ({payload_type})"),
+        None => payload_type.to_string(),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RTP {
+    pub version: u8,
+    pub padding: bool,
+    pub extension: bool,
+    pub marker: bool,
+    pub payload_type: u8,
+    pub sequence_number: u16,
+    pub timestamp: u32,
+    pub ssrc: u32,
+    pub csrc: Vec<u32>,
+    pub payload_length: usize,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct RtpDto {
+    #[serde(rename = "ssrc")]
+    pub ssrc: u32,
+    #[serde(rename = "pt")]
+    pub payload_type: u8,
+    #[serde(rename = "m", default, skip_serializing_if = "wire::is_default")]
+    pub marker: bool,
+    #[serde(rename = "seq")]
+    pub sequence_number: u16,
+    #[serde(rename = "ts")]
+    pub timestamp: u32,
+    #[serde(rename = "len")]
+    pub payload_length: usize,
+}
+
+impl RtpDto {
+    pub fn payload_type_name(&self) -> String {
+        payload_type_name(self.payload_type, encoding(self.payload_type))
+    }
+}
+
+impl From<RTP> for RtpDto {
+    fn from(value: RTP) -> Self {
+        Self {
+            ssrc: value.ssrc,
+            payload_type: value.payload_type,
+            marker: value.marker,
+            sequence_number: value.sequence_number,
+            timestamp: value.timestamp,
+            payload_length: value.payload_length,
+        }
+    }
+}
+
+impl FieldIter for RtpDto {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let Self {
+            ssrc,
+            payload_type: _,
+            marker,
+            sequence_number,
+            timestamp,
+            payload_length,
+        } = self;
+
+        vec![
+            ("ssrc", format!("0x{ssrc:08X}")),
+            ("payload_type", self.payload_type_name()),
+            ("marker", marker.to_string()),
+            ("sequence_number", sequence_number.to_string()),
+            ("timestamp", timestamp.to_string()),
+            ("payload_length", payload_length.to_string()),
+        ]
+    }
+}
+
+#[cfg(all(test, feature = "ipv4", feature = "udp"))]
+mod tests {
+    use super::*;
+    use crate::parser::tests::udp_layers;
+
+    // Version 2, Payload Type: PCMU, SSRC: 0x1A2B3C4D
+    fn packet(marker: bool, sequence_number: u16, timestamp: u32) -> Vec<u8> {
+        let mut packet = vec![0x80, if marker { 0x80 } else { 0x00 }];
+        packet.extend(sequence_number.to_be_bytes());
+        packet.extend(timestamp.to_be_bytes());
+        packet.extend([0x1A, 0x2B, 0x3C, 0x4D]);
+        packet.extend([0xFF; 160]);
+        packet
+    }
+
+    fn rtp(layers: &[ProtocolData]) -> Option<RTP> {
+        match layers.get(2) {
+            Some(ProtocolData::RTP(value)) => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_rtp_stream() {
+        let packets = [
+            packet(true, 1000, 160_000),
+            packet(false, 1001, 160_160),
+            packet(false, 1002, 160_320),
+            packet(false, 1004, 160_640),
+        ];
+        let actual = packets
+            .iter()
+            .map(|packet| rtp(&udp_layers(49172, 3456, packet)).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            actual[0],
+            RTP {
+                version: 2,
+                padding: false,
+                extension: false,
+                marker: true,
+                payload_type: 0,
+                sequence_number: 1000,
+                timestamp: 160_000,
+                ssrc: 0x1A2B3C4D,
+                csrc: vec![],
+                payload_length: 160,
+            }
+        );
+        assert!(actual.iter().all(|rtp| rtp.ssrc == 0x1A2B3C4D));
+        assert_eq!(
+            actual
+                .iter()
+                .map(|rtp| rtp.sequence_number)
+                .collect::<Vec<_>>(),
+            vec![1000, 1001, 1002, 1004]
+        );
+        assert_eq!(
+            RtpDto::from(actual[3].clone()).payload_type_name(),
+            "PCMU (0)"
+        );
+    }
+
+    #[test]
+    fn test_rtp_csrc_extension_padding() {
+        // Padding, Extension, 1 CSRC; Marker, Dynamic Payload Type 111 (Opus)
+        let mut packet = vec![0xB1, 0xEF, 0x00, 0x01, 0x00, 0x00, 0x03, 0xC0];
+        packet.extend([0x1A, 0x2B, 0x3C, 0x4D, 0x00, 0x00, 0x00, 0x07]);
+        packet.extend([0xBE, 0xDE, 0x00, 0x01, 0x10, 0xAA, 0x00, 0x00]);
+        packet.extend([0x01; 20]);
+        packet.extend([0x00, 0x00, 0x03]);
+
+        let actual = rtp(&udp_layers(49172, 3456, &packet)).unwrap();
+        assert!(actual.marker);
+        assert_eq!(actual.payload_type, 111);
+        assert_eq!(actual.csrc, vec![7]);
+        assert_eq!(actual.payload_length, 20);
+        assert_eq!(clock_rate(actual.payload_type), None);
+    }
+
+    #[test]
+    fn test_rtp_heuristic_rejections() {
+        let valid = packet(false, 1, 160);
+        // Odd port is used by RTCP
+        assert!(rtp(&udp_layers(49173, 3456, &valid)).is_none());
+        // Privileged port
+        assert!(rtp(&udp_layers(49172, 512, &valid)).is_none());
+
+        // RTCP Receiver Report (PT 201) on the even ports
+        let mut rtcp = valid.clone();
+        rtcp[1] = 0xC9;
+        assert!(rtp(&udp_layers(49172, 3456, &rtcp)).is_none());
+        // Version 1
+        let mut version = valid.clone();
+        version[0] = 0x40;
+        assert!(rtp(&udp_layers(49172, 3456, &version)).is_none());
+        // Padding is longer than the payload
+        let mut padding = valid;
+        padding[0] = 0xA0;
+        assert!(rtp(&udp_layers(49172, 3456, &padding)).is_none());
+    }
+}
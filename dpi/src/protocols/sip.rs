@@ -0,0 +1,597 @@
+use crate::dto::fields::{self, FieldIter};
+use crate::parser::{IResult, ParserError};
+use crate::protocols::ProtocolData;
+use crate::protocols::http::{self, Header};
+use nom::Parser;
+use nom::bytes::complete::take;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use strum_macros::Display;
+
+// Session Initiation Protocol
+// RFC 3261: https://datatracker.ietf.org/doc/html/rfc3261
+// Messages are laid out like HTTP ones. SDP body (RFC 8866) is parsed only for the media,
+// it tells the ports of the RTP streams.
+
+pub const VERSION: &str = "SIP/2.0";
+pub const CONTENT_TYPE_SDP: &str = "application/sdp";
+
+// Headers, that may be sent in the compact form (RFC 3261, 7.3.3)
+pub const CALL_ID: HeaderName = HeaderName::new("Call-ID", "i");
+pub const CONTENT_LENGTH: HeaderName = HeaderName::new("Content-Length", "l");
+pub const CONTENT_TYPE: HeaderName = HeaderName::new("Content-Type", "c");
+pub const FROM: HeaderName = HeaderName::new("From", "f");
+pub const TO: HeaderName = HeaderName::new("To", "t");
+pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
+    let (rest, starting_line) = http::parse_starting_line(bytes)?;
+
+    let (rest, sip) = if starting_line.starts_with(VERSION) {
+        parse_response(rest, starting_line)
+    } else {
+        parse_request(rest, starting_line)
+    }
+    .map_err(|err| ParserError::from_nom(err, bytes.len()).to_nom(bytes))?;
+
+    if !rest.is_empty() {
+        return Err(ParserError::ConstraintViolation {
+            description: "bytes are left after the message",
+        }
+        .to_nom(bytes));
+    }
+
+    Ok((rest, ProtocolData::SIP(sip)))
+}
+
+pub fn parse_request<'a>(input: &'a [u8], starting_line: &str) -> IResult<&'a [u8], SIP> {
+    let mut starting_line_parts = starting_line.splitn(3, " ");
+    let method = Methods::try_from(
+        starting_line_parts
+            .next()
+            .ok_or(ParserError::InvalidValue { field: "method" }.to_nom(input))?,
+    )
+    .map_err(|_| ParserError::InvalidValue { field: "method" }.to_nom(input))?;
+    let request_uri = starting_line_parts.next().ok_or(
+        ParserError::InvalidValue {
+            field: "request_uri",
+        }
+        .to_nom(input),
+    )?;
+    let version = starting_line_parts
+        .next()
+        .filter(|version| *version == VERSION)
+        .ok_or(ParserError::InvalidValue { field: "version" }.to_nom(input))?;
+
+    let (rest, headers) = http::parse_headers(input)?;
+
+    let (rest, body) = parse_body(rest, &headers)?;
+    let media = parse_media(&headers, body);
+
+    let protocol = SIPRequest {
+        method,
+        request_uri: request_uri.to_string(),
+        version: version.to_string(),
+        headers,
+        media,
+    };
+
+    Ok((rest, SIP::Request(protocol)))
+}
+
+pub fn parse_response<'a>(
+    input: &'a [u8], starting_line: &str,
+) -> IResult<&'a [u8], SIP> {
+    let mut starting_line_parts = starting_line.splitn(3, " ");
+    let version = starting_line_parts
+        .next()
+        .filter(|version| *version == VERSION)
+        .ok_or(ParserError::InvalidValue { field: "version" }.to_nom(input))?;
+    let status_code = starting_line_parts
+        .next()
+        .ok_or(
+            ParserError::InvalidValue {
+                field: "status_code",
+            }
+            .to_nom(input),
+        )?
+        .parse::<u16>()
+        .map_err(|_| {
+            ParserError::InvalidValue {
+                field: "status_code",
+            }
+            .to_nom(input)
+        })?;
+    let reason_phrase = starting_line_parts.next().ok_or(
+        ParserError::InvalidValue {
+            field: "reason_phrase",
+        }
+        .to_nom(input),
+    )?;
+
+    let (rest, headers) = http::parse_headers(input)?;
+
+    let (rest, body) = parse_body(rest, &headers)?;
+    let media = parse_media(&headers, body);
+
+    let protocol = SIPResponse {
+        version: version.to_string(),
+        status_code,
+        reason: reason_phrase.to_string(),
+        headers,
+        media,
+    };
+
+    Ok((rest, SIP::Response(protocol)))
+}
+
+fn parse_body<'a>(input: &'a [u8], headers: &[Header]) -> IResult<&'a [u8], &'a [u8]> {
+    match find_header(headers, &CONTENT_LENGTH) {
+        Some(value) => {
+            let len = value.trim().parse::<usize>().map_err(|_| {
+                ParserError::InvalidValue {
+                    field: "content_length",
+                }
+                .to_nom(input)
+            })?;
+            let (rest, body) = take(len).parse(input)?;
+            Ok((rest, body))
+        },
+        // It's optional over UDP, the datagram holds a single message
+        None => {
+            let rest: &[u8] = &[];
+            Ok((rest, input))
+        },
+    }
+}
+
+fn parse_media(headers: &[Header], body: &[u8]) -> Vec<SdpMedia> {
+    let is_sdp = find_header(headers, &CONTENT_TYPE)
+        .is_some_and(|value| value.trim().eq_ignore_ascii_case(CONTENT_TYPE_SDP));
+    if !is_sdp {
+        return vec![];
+    }
+    let Ok(body) = std::str::from_utf8(body) else {
+        return vec![];
+    };
+
+    let mut media: Vec<SdpMedia> = vec![];
+    for line in body.lines() {
+        if let Some(description) = line.strip_prefix("m=") {
+            if let Some(value) = SdpMedia::parse(description) {
+                media.push(value);
+            }
+        } else if let Some(attribute) = line.strip_prefix("a=rtpmap:") {
+            // Session-level attributes precede the first media description
+            if let (Some(last), Some(rtp_map)) =
+                (media.last_mut(), RtpMap::parse(attribute))
+            {
+                last.rtp_maps.push(rtp_map);
+            }
+        }
+    }
+
+    media
+}
+
+pub fn find_header<'a>(headers: &'a [Header], name: &HeaderName) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(key, _)| name.matches(key))
+        .map(|(_, value)| value.as_str())
+}
+
+pub fn is_protocol_default(port_source: u16, port_destination: u16) -> bool {
+    const PORT: u16 = 5060;
+
+    port_source == PORT || port_destination == PORT
+}
+
+/// Status line or the request line with the SIP URI. <br>
+/// Requests are told apart from HTTP by the URI, as they share the OPTIONS method.
+pub fn looks_like(payload: &[u8]) -> bool {
+    if payload.starts_with(VERSION.as_bytes()) {
+        return true;
+    }
+    let Some(space) = payload.iter().position(|byte| *byte == b' ') else {
+        return false;
+    };
+    let (method, target) = payload.split_at(space);
+
+    let is_method =
+        std::str::from_utf8(method).is_ok_and(|method| Methods::try_from(method).is_ok());
+    is_method && (target.starts_with(b" sip:") || target.starts_with(b" sips:"))
+}
+
+pub struct HeaderName {
+    pub full: &'static str,
+    pub compact: &'static str,
+}
+
+impl HeaderName {
+    const fn new(full: &'static str, compact: &'static str) -> Self {
+        Self { full, compact }
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        name.eq_ignore_ascii_case(self.full) || name.eq_ignore_ascii_case(self.compact)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SIP {
+    Request(SIPRequest),
+    Response(SIPResponse),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SIPRequest {
+    pub method: Methods,
+    pub request_uri: String,
+    pub version: String,
+    pub headers: Vec<Header>,
+    pub media: Vec<SdpMedia>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SIPResponse {
+    pub version: String,
+    pub status_code: u16,
+    pub reason: String,
+    pub headers: Vec<Header>,
+    pub media: Vec<SdpMedia>,
+}
+
+#[derive(Clone, Debug, Display, Serialize, Deserialize, PartialEq)]
+pub enum Methods {
+    ACK,
+    BYE,
+    CANCEL,
+    INFO,
+    INVITE,
+    MESSAGE,
+    NOTIFY,
+    OPTIONS,
+    PRACK,
+    PUBLISH,
+    REFER,
+    REGISTER,
+    SUBSCRIBE,
+    UPDATE,
+}
+
+impl TryFrom<&str> for Methods {
+    type Error = ParserError;
+    fn try_from(method: &str) -> Result<Self, Self::Error> {
+        // Methods are case-sensitive, unlike the HTTP ones
+        match method.trim() {
+            "ACK" => Ok(Methods::ACK),
+            "BYE" => Ok(Methods::BYE),
+            "CANCEL" => Ok(Methods::CANCEL),
+            "INFO" => Ok(Methods::INFO),
+            "INVITE" => Ok(Methods::INVITE),
+            "MESSAGE" => Ok(Methods::MESSAGE),
+            "NOTIFY" => Ok(Methods::NOTIFY),
+            "OPTIONS" => Ok(Methods::OPTIONS),
+            "PRACK" => Ok(Methods::PRACK),
+            "PUBLISH" => Ok(Methods::PUBLISH),
+            "REFER" => Ok(Methods::REFER),
+            "REGISTER" => Ok(Methods::REGISTER),
+            "SUBSCRIBE" => Ok(Methods::SUBSCRIBE),
+            "UPDATE" => Ok(Methods::UPDATE),
+            _ => Err(ParserError::InvalidValue { field: "method" }),
+        }
+    }
+}
+
+/// Media description of SDP: "m=audio 49170 RTP/AVP 0 97" & its "a=rtpmap" attributes.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SdpMedia {
+    pub media: String,
+    pub port: u16,
+    pub protocol: String,
+    pub payload_types: Vec<u8>,
+    pub rtp_maps: Vec<RtpMap>,
+}
+
+impl SdpMedia {
+    fn parse(description: &str) -> Option<Self> {
+        let mut parts = description.split_whitespace();
+        let media = parts.next()?.to_string();
+        // Port may be followed by the amount of ports: "49170/2"
+        let port = parts.next()?.split('/').next()?.parse::<u16>().ok()?;
+        let protocol = parts.next()?.to_string();
+        let payload_types = parts.filter_map(|format| format.parse().ok()).collect();
+
+        Some(Self {
+            media,
+            port,
+            protocol,
+            payload_types,
+            rtp_maps: vec![],
+        })
+    }
+}
+
+impl Display for SdpMedia {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.media, self.port)?;
+        if !self.rtp_maps.is_empty() {
+            write!(f, " ({})", fields::list(&self.rtp_maps))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Encoding of the dynamic payload type: "a=rtpmap:97 iLBC/8000".
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct RtpMap {
+    pub payload_type: u8,
+    pub encoding: String,
+    pub clock_rate: u32,
+}
+
+impl RtpMap {
+    fn parse(attribute: &str) -> Option<Self> {
+        let (payload_type, encoding) = attribute.split_once(' ')?;
+        // Encoding may be followed by the amount of channels: "opus/48000/2"
+        let mut encoding = encoding.trim().split('/');
+
+        Some(Self {
+            payload_type: payload_type.parse().ok()?,
+            encoding: encoding.next()?.to_string(),
+            clock_rate: encoding.next()?.parse().ok()?,
+        })
+    }
+}
+
+impl Display for RtpMap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.encoding, self.clock_rate)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum SipDto {
+    Request(SIPRequestDto),
+    Response(SIPResponseDto),
+}
+
+impl SipDto {
+    pub fn headers(&self) -> &[Header] {
+        match self {
+            Self::Request(request) => &request.headers,
+            Self::Response(response) => &response.headers,
+        }
+    }
+
+    pub fn media(&self) -> &[SdpMedia] {
+        match self {
+            Self::Request(request) => &request.media,
+            Self::Response(response) => &response.media,
+        }
+    }
+
+    pub fn call_id(&self) -> Option<&str> {
+        find_header(self.headers(), &CALL_ID)
+    }
+
+    /// "From" header: Initiator of the request.
+    pub fn initiator(&self) -> Option<&str> {
+        find_header(self.headers(), &FROM)
+    }
+
+    /// "To" header: Recipient of the request.
+    pub fn recipient(&self) -> Option<&str> {
+        find_header(self.headers(), &TO)
+    }
+}
+
+impl From<SIP> for SipDto {
+    fn from(value: SIP) -> Self {
+        match value {
+            SIP::Request(value) => Self::Request(value.into()),
+            SIP::Response(value) => Self::Response(value.into()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SIPRequestDto {
+    #[serde(rename = "m")]
+    pub method: Methods,
+    #[serde(rename = "uri")]
+    pub request_uri: String,
+    #[serde(rename = "h", default, skip_serializing_if = "Vec::is_empty")]
+    pub headers: Vec<Header>,
+    #[serde(rename = "sdp", default, skip_serializing_if = "Vec::is_empty")]
+    pub media: Vec<SdpMedia>,
+}
+
+impl From<SIPRequest> for SIPRequestDto {
+    fn from(value: SIPRequest) -> Self {
+        Self {
+            method: value.method,
+            request_uri: value.request_uri,
+            headers: value.headers,
+            media: value.media,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SIPResponseDto {
+    #[serde(rename = "s")]
+    pub status_code: u16,
+    #[serde(rename = "r")]
+    pub reason: String,
+    #[serde(rename = "h", default, skip_serializing_if = "Vec::is_empty")]
+    pub headers: Vec<Header>,
+    #[serde(rename = "sdp", default, skip_serializing_if = "Vec::is_empty")]
+    pub media: Vec<SdpMedia>,
+}
+
+impl From<SIPResponse> for SIPResponseDto {
+    fn from(value: SIPResponse) -> Self {
+        Self {
+            status_code: value.status_code,
+            reason: value.reason,
+            headers: value.headers,
+            media: value.media,
+        }
+    }
+}
+
+impl FieldIter for SipDto {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = match self {
+            Self::Request(request) => request.fields(),
+            Self::Response(response) => response.fields(),
+        };
+        fields.extend([
+            ("call_id", optional_header(self.call_id())),
+            ("from", optional_header(self.initiator())),
+            ("to", optional_header(self.recipient())),
+        ]);
+
+        fields
+    }
+}
+
+impl FieldIter for SIPRequestDto {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let Self {
+            method,
+            request_uri,
+            headers: _,
+            media,
+        } = self;
+
+        vec![
+            ("method", method.to_string()),
+            ("request_uri", request_uri.clone()),
+            ("media", fields::list(media)),
+        ]
+    }
+}
+
+impl FieldIter for SIPResponseDto {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let Self {
+            status_code,
+            reason,
+            headers: _,
+            media,
+        } = self;
+
+        vec![
+            ("status_code", status_code.to_string()),
+            ("reason", reason.clone()),
+            ("media", fields::list(media)),
+        ]
+    }
+}
+
+fn optional_header(value: Option<&str>) -> String {
+    value.unwrap_or(fields::NONE).to_string()
+}
+
+#[cfg(all(test, feature = "ipv4", feature = "udp"))]
+mod tests {
+    use super::*;
+    use crate::parser::tests::udp_layers;
+
+    // RFC 3665, 3.1. Successful Session Establishment: F1 INVITE Alice -> Bob
+    const INVITE: &str = "INVITE sip:bob@biloxi.example.com SIP/2.0\r\n\
+        Via: SIP/2.0/UDP client.atlanta.example.com:5060;branch=z9hG4bK74bf9\r\n\
+        Max-Forwards: 70\r\n\
+        From: Alice <sip:alice@atlanta.example.com>;tag=9fxced76sl\r\n\
+        To: Bob <sip:bob@biloxi.example.com>\r\n\
+        Call-ID: 3848276298220188511@atlanta.example.com\r\n\
+        CSeq: 1 INVITE\r\n\
+        Contact: <sip:alice@client.atlanta.example.com;transport=udp>\r\n\
+        Content-Type: application/sdp\r\n\
+        Content-Length: 151\r\n\
+        \r\n\
+        v=0\r\n\
+        o=alice 2890844526 2890844526 IN IP4 client.atlanta.example.com\r\n\
+        s=-\r\n\
+        c=IN IP4 192.0.2.101\r\n\
+        t=0 0\r\n\
+        m=audio 49172 RTP/AVP 0\r\n\
+        a=rtpmap:0 PCMU/8000\r\n";
+
+    #[test]
+    fn test_sip_invite() {
+        let layers = udp_layers(5060, 5060, INVITE.as_bytes());
+        assert_eq!(layers.len(), 3);
+
+        let actual_sip = match layers[2].clone() {
+            ProtocolData::SIP(SIP::Request(value)) => value,
+            _ => panic!(),
+        };
+
+        assert_eq!(actual_sip.method, Methods::INVITE);
+        assert_eq!(actual_sip.request_uri, "sip:bob@biloxi.example.com");
+        assert_eq!(actual_sip.version, VERSION);
+        assert_eq!(actual_sip.headers.len(), 9);
+        assert_eq!(
+            actual_sip.media,
+            vec![SdpMedia {
+                media: "audio".to_string(),
+                port: 49172,
+                protocol: "RTP/AVP".to_string(),
+                payload_types: vec![0],
+                rtp_maps: vec![RtpMap {
+                    payload_type: 0,
+                    encoding: "PCMU".to_string(),
+                    clock_rate: 8000,
+                }],
+            }]
+        );
+
+        let dto = SipDto::from(SIP::Request(actual_sip));
+        assert_eq!(
+            dto.call_id(),
+            Some("3848276298220188511@atlanta.example.com")
+        );
+        assert_eq!(
+            dto.initiator(),
+            Some("Alice <sip:alice@atlanta.example.com>;tag=9fxced76sl")
+        );
+        assert_eq!(dto.recipient(), Some("Bob <sip:bob@biloxi.example.com>"));
+        assert_eq!(dto.media()[0].to_string(), "audio 49172 (PCMU/8000)");
+    }
+
+    #[test]
+    fn test_sip_response_compact_headers() {
+        let response = "SIP/2.0 180 Ringing\r\n\
+            f: Alice <sip:alice@atlanta.example.com>;tag=9fxced76sl\r\n\
+            t: Bob <sip:bob@biloxi.example.com>;tag=8321234356\r\n\
+            i: 3848276298220188511@atlanta.example.com\r\n\
+            l: 0\r\n\
+            \r\n";
+        let layers = udp_layers(5060, 5060, response.as_bytes());
+
+        let dto = match layers[2].clone() {
+            ProtocolData::SIP(value) => SipDto::from(value),
+            _ => panic!(),
+        };
+        match &dto {
+            SipDto::Response(response) => {
+                assert_eq!(response.status_code, 180);
+                assert_eq!(response.reason, "Ringing");
+                assert!(response.media.is_empty());
+            },
+            SipDto::Request(_) => panic!(),
+        }
+        assert_eq!(
+            dto.call_id(),
+            Some("3848276298220188511@atlanta.example.com")
+        );
+
+        // HTTP message on the SIP port isn't taken
+        let http = "GET / HTTP/1.1\r\nHost: example.com\r\n\r\n";
+        let layers = udp_layers(5060, 5060, http.as_bytes());
+        assert!(!matches!(layers.get(2), Some(ProtocolData::SIP(_))));
+    }
+}
@@ -0,0 +1,388 @@
+use crate::dto::fields::{self, FieldIter};
+use crate::dto::wire;
+use crate::parser::{IResult, ParserError};
+use crate::protocols::ProtocolData;
+use nom::bytes::take;
+use nom::number::{be_u8, be_u24, le_u16, le_u32, le_u64};
+use nom::{Finish, Parser};
+use num_enum::TryFromPrimitive;
+use serde::{Deserialize, Serialize};
+use strum_macros::Display;
+
+// Server Message Block Protocol Versions 2 and 3
+// MS-SMB2: https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-smb2
+// Only the header of the first message in the segment is parsed, the rest bytes are consumed.
+
+pub const SESSION_MESSAGE: u8 = 0x00;
+pub const PROTOCOL_ID_LENGTH: usize = 4;
+pub const PROTOCOL_ID_SYNC: &[u8] = b"\xFESMB";
+pub const PROTOCOL_ID_TRANSFORM: &[u8] = b"\xFDSMB";
+pub const HEADER_LENGTH: u16 = 64;
+pub const SIGNATURE_LENGTH: usize = 16;
+pub const NONCE_LENGTH: usize = 16;
+pub const FLAG_SERVER_TO_REDIR: u32 = 0x0000_0001;
+pub const FLAG_ASYNC_COMMAND: u32 = 0x0000_0002;
+pub const NTLMSSP_SIGNATURE: &[u8] = b"NTLMSSP\0";
+pub const NTLMSSP_AUTHENTICATE: u32 = 0x0000_0003;
+pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
+    // Direct TCP & NetBIOS Session Service: Type (8 bits), Length (24 bits)
+    let (rest, message_type) = be_u8().parse(bytes)?;
+    if message_type != SESSION_MESSAGE {
+        return Err(ParserError::InvalidValue {
+            field: "message_type",
+        }
+        .to_nom(bytes));
+    }
+    let (message, _) = be_u24().parse(rest)?;
+
+    let (rest, protocol_id) = take(PROTOCOL_ID_LENGTH).parse(message)?;
+    let (_, protocol) = match protocol_id {
+        PROTOCOL_ID_SYNC => parse_header(rest, message)?,
+        PROTOCOL_ID_TRANSFORM => parse_transform_header(rest)?,
+        _ => {
+            return Err(ParserError::InvalidValue {
+                field: "protocol_id",
+            }
+            .to_nom(bytes));
+        },
+    };
+
+    // Following messages & payload aren't parsed
+    let rest: &[u8] = &[];
+    Finish::finish(Ok((rest, ProtocolData::SMB2(protocol))))
+}
+
+pub fn is_protocol_default(port_source: u16, port_destination: u16) -> bool {
+    const PORT_SMB: u16 = 445;
+    const PORT_NETBIOS_SESSION: u16 = 139;
+
+    [PORT_SMB, PORT_NETBIOS_SESSION]
+        .iter()
+        .any(|port| *port == port_source || *port == port_destination)
+}
+
+/// Protocol ID after the session header.
+pub fn looks_like(payload: &[u8]) -> bool {
+    payload.first() == Some(&SESSION_MESSAGE)
+        && matches!(
+            payload.get(4..4 + PROTOCOL_ID_LENGTH),
+            Some(PROTOCOL_ID_SYNC | PROTOCOL_ID_TRANSFORM)
+        )
+}
+
+fn parse_header<'a>(bytes: &'a [u8], message: &'a [u8]) -> IResult<&'a [u8], SMB2> {
+    let (rest, structure_size) = le_u16().parse(bytes)?;
+    if structure_size != HEADER_LENGTH {
+        return Err(ParserError::InvalidValue {
+            field: "structure_size",
+        }
+        .to_nom(bytes));
+    }
+    let (rest, _credit_charge) = le_u16().parse(rest)?;
+    let (rest, status) = le_u32().parse(rest)?;
+    let (rest, command) = le_u16().parse(rest)?;
+    let command = Command::try_from(command)
+        .map_err(|_| ParserError::InvalidValue { field: "command" }.to_nom(bytes))?;
+    let (rest, _credits) = le_u16().parse(rest)?;
+    let (rest, flags) = le_u32().parse(rest)?;
+    let (rest, _next_command) = le_u32().parse(rest)?;
+    let (rest, message_id) = le_u64().parse(rest)?;
+
+    // Async header has AsyncId instead of Reserved & TreeId
+    let (rest, tree_id) = if flags & FLAG_ASYNC_COMMAND != 0 {
+        let (rest, _async_id) = le_u64().parse(rest)?;
+        (rest, None)
+    } else {
+        let (rest, _reserved) = le_u32().parse(rest)?;
+        let (rest, tree_id) = le_u32().parse(rest)?;
+        (rest, Some(tree_id))
+    };
+
+    let (rest, session_id) = le_u64().parse(rest)?;
+    let (body, _signature) = take(SIGNATURE_LENGTH).parse(rest)?;
+
+    let is_response = flags & FLAG_SERVER_TO_REDIR != 0;
+    let mut protocol = SMB2 {
+        is_encrypted: false,
+        command: Some(command),
+        is_response,
+        status,
+        message_id: Some(message_id),
+        tree_id,
+        session_id,
+        share: None,
+        user: None,
+    };
+
+    // Names are sent by the client only
+    if !is_response {
+        match protocol.command {
+            Some(Command::TreeConnect) => {
+                protocol.share = tree_connect_path(body, message)
+            },
+            Some(Command::SessionSetup) => {
+                protocol.user = session_setup_user(body, message)
+            },
+            _ => {},
+        }
+    }
+
+    Ok((rest, protocol))
+}
+
+// Encrypted message: only the session is known.
+fn parse_transform_header(bytes: &[u8]) -> IResult<&[u8], SMB2> {
+    let (rest, _signature) = take(SIGNATURE_LENGTH).parse(bytes)?;
+    let (rest, _nonce) = take(NONCE_LENGTH).parse(rest)?;
+    let (rest, _original_message_size) = le_u32().parse(rest)?;
+    let (rest, _reserved) = le_u16().parse(rest)?;
+    let (rest, _flags) = le_u16().parse(rest)?;
+    let (rest, session_id) = le_u64().parse(rest)?;
+
+    let protocol = SMB2 {
+        is_encrypted: true,
+        command: None,
+        is_response: false,
+        status: 0,
+        message_id: None,
+        tree_id: None,
+        session_id,
+        share: None,
+        user: None,
+    };
+
+    Ok((rest, protocol))
+}
+
+// Request body: StructureSize (2), Flags (2), PathOffset (2), PathLength (2), Buffer.
+// Offsets are counted from the beginning of the SMB2 header.
+fn tree_connect_path(body: &[u8], message: &[u8]) -> Option<String> {
+    let offset = read_u16(body, 4)?;
+    let length = read_u16(body, 6)?;
+    let path = slice_at(message, usize::from(offset), usize::from(length))?;
+
+    utf16_string(path)
+}
+
+// Request body: StructureSize (2), Flags (1), SecurityMode (1), Capabilities (4),
+// Channel (4), SecurityBufferOffset (2), SecurityBufferLength (2), ...
+// Security buffer is the GSS-API token, NTLM authentication message is looked up inside it.
+fn session_setup_user(body: &[u8], message: &[u8]) -> Option<SessionUser> {
+    let offset = read_u16(body, 12)?;
+    let length = read_u16(body, 14)?;
+    let buffer = slice_at(message, usize::from(offset), usize::from(length))?;
+
+    let start = buffer
+        .windows(NTLMSSP_SIGNATURE.len())
+        .position(|window| window == NTLMSSP_SIGNATURE)?;
+    let ntlm = buffer.get(start..)?;
+    if read_u32(ntlm, 8)? != NTLMSSP_AUTHENTICATE {
+        return None;
+    }
+
+    // DomainNameFields, UserNameFields, WorkstationFields
+    let user = SessionUser {
+        domain: ntlm_field(ntlm, 28).unwrap_or_default(),
+        user: ntlm_field(ntlm, 36)?,
+        workstation: ntlm_field(ntlm, 44).unwrap_or_default(),
+    };
+
+    Some(user)
+}
+
+// Field: Length (2), MaxLength (2), Offset (4). Offset is counted from the NTLMSSP signature.
+fn ntlm_field(ntlm: &[u8], position: usize) -> Option<String> {
+    let length = read_u16(ntlm, position)?;
+    let offset = read_u32(ntlm, position.checked_add(4)?)?;
+    let value = slice_at(ntlm, usize::try_from(offset).ok()?, usize::from(length))?;
+
+    utf16_string(value)
+}
+
+fn slice_at(bytes: &[u8], offset: usize, length: usize) -> Option<&[u8]> {
+    bytes.get(offset..offset.checked_add(length)?)
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Option<u16> {
+    let value = slice_at(bytes, offset, 2)?;
+    value.try_into().ok().map(u16::from_le_bytes)
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Option<u32> {
+    let value = slice_at(bytes, offset, 4)?;
+    value.try_into().ok().map(u32::from_le_bytes)
+}
+
+fn utf16_string(bytes: &[u8]) -> Option<String> {
+    let units = bytes
+        .chunks_exact(2)
+        .map(|pair| pair.try_into().ok().map(u16::from_le_bytes))
+        .collect::<Option<Vec<u16>>>()?;
+
+    String::from_utf16(&units).ok()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SMB2 {
+    pub is_encrypted: bool,
+    pub command: Option<Command>,
+    pub is_response: bool,
+    pub status: u32,
+    pub message_id: Option<u64>,
+    pub tree_id: Option<u32>,
+    pub session_id: u64,
+
+    // TREE_CONNECT request
+    pub share: Option<String>,
+    // SESSION_SETUP request with NTLM authentication
+    pub user: Option<SessionUser>,
+}
+
+#[derive(Clone, Debug, Display, Serialize, Deserialize, PartialEq, TryFromPrimitive)]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+#[repr(u16)]
+pub enum Command {
+    Negotiate = 0x0000,
+    SessionSetup = 0x0001,
+    Logoff = 0x0002,
+    TreeConnect = 0x0003,
+    TreeDisconnect = 0x0004,
+    Create = 0x0005,
+    Close = 0x0006,
+    Flush = 0x0007,
+    Read = 0x0008,
+    Write = 0x0009,
+    Lock = 0x000A,
+    Ioctl = 0x000B,
+    Cancel = 0x000C,
+    Echo = 0x000D,
+    QueryDirectory = 0x000E,
+    ChangeNotify = 0x000F,
+    QueryInfo = 0x0010,
+    SetInfo = 0x0011,
+    OplockBreak = 0x0012,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SessionUser {
+    pub domain: String,
+    pub user: String,
+    pub workstation: String,
+}
+
+impl std::fmt::Display for SessionUser {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.domain.is_empty() {
+            true => write!(f, "{}", self.user),
+            false => write!(f, "{}\\{}", self.domain, self.user),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Smb2Dto {
+    #[serde(rename = "enc", default, skip_serializing_if = "wire::is_default")]
+    pub is_encrypted: bool,
+    #[serde(rename = "cmd", default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<Command>,
+    #[serde(rename = "rsp", default, skip_serializing_if = "wire::is_default")]
+    pub is_response: bool,
+    #[serde(rename = "st")]
+    pub status: u32,
+    #[serde(rename = "sid")]
+    pub session_id: u64,
+    #[serde(rename = "tid", default, skip_serializing_if = "Option::is_none")]
+    pub tree_id: Option<u32>,
+    #[serde(rename = "share", default, skip_serializing_if = "Option::is_none")]
+    pub share: Option<String>,
+    #[serde(rename = "user", default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<SessionUser>,
+}
+
+impl From<SMB2> for Smb2Dto {
+    fn from(value: SMB2) -> Self {
+        Self {
+            is_encrypted: value.is_encrypted,
+            command: value.command,
+            is_response: value.is_response,
+            status: value.status,
+            session_id: value.session_id,
+            tree_id: value.tree_id,
+            share: value.share,
+            user: value.user,
+        }
+    }
+}
+
+impl FieldIter for Smb2Dto {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let Self {
+            is_encrypted,
+            command,
+            is_response,
+            status,
+            session_id,
+            tree_id,
+            share,
+            user,
+        } = self;
+
+        vec![
+            ("is_encrypted", is_encrypted.to_string()),
+            ("command", fields::optional(command)),
+            ("is_response", is_response.to_string()),
+            ("status", format!("0x{status:08X}")),
+            ("session_id", format!("0x{session_id:016X}")),
+            ("tree_id", fields::optional(tree_id)),
+            ("share", fields::optional(share)),
+            ("user", fields::optional(user)),
+        ]
+    }
+}
+
+#[cfg(all(test, feature = "ethernet", feature = "ipv4", feature = "tcp"))]
+mod tests {
+    use super::*;
+    use crate::dto::frame::{FrameHeader, LinkType};
+    use crate::parser::tests::FrameType;
+    use crate::parser::tests::ProtocolParser;
+
+    #[test]
+    fn test_smb2_negotiate_request() {
+        let hex_actual = "52 54 00 12 34 56 08 00 27 AA BB CC 08 00 45 00 00 94 43 21 00 00 80 06 00 00 C0 A8 01 14 C0 A8 01 0A C2 30 01 BD 01 02 03 04 0A 0B 0C 0D 50 18 02 00 00 00 00 00 00 00 00 68 FE 53 4D 42 40 00 00 00 00 00 00 00 00 00 01 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 FF FE 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 00 24 00 02 00 01 00 00 00 00 00 00 00 00 11 22 33 44 55 66 77 88 99 AA BB CC DD EE FF 00 00 00 00 00 00 00 00 02 02 10 02".replace(" ", "");
+        let frame = hex::decode(hex_actual).unwrap();
+        let header = FrameHeader::new(162);
+
+        let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+        let result = parser.process_bytes(&header, &frame);
+        let metadata = match result {
+            Some(value) => match value {
+                FrameType::Metadata(value) => value,
+                _ => panic!(),
+            },
+            None => panic!(),
+        };
+
+        assert_eq!(metadata.layers.len(), 4);
+
+        let actual_smb2 = match metadata.layers[3].clone() {
+            ProtocolData::SMB2(value) => value,
+            _ => panic!(),
+        };
+
+        let expected_smb2 = SMB2 {
+            is_encrypted: false,
+            command: Some(Command::Negotiate),
+            is_response: false,
+            status: 0,
+            message_id: Some(0),
+            tree_id: Some(0),
+            session_id: 0,
+            share: None,
+            user: None,
+        };
+
+        assert_eq!(actual_smb2, expected_smb2);
+        assert_eq!(Command::SessionSetup.to_string(), "SESSION_SETUP");
+    }
+}
@@ -0,0 +1,529 @@
+use crate::dto::fields::{self, FieldIter};
+use crate::parser::{IResult, ParserError};
+use crate::protocols::ProtocolData;
+use num_enum::TryFromPrimitive;
+use serde::{Deserialize, Serialize};
+use strum_macros::{Display, EnumIter};
+
+// Syslog
+// RFC 3164 (BSD): https://datatracker.ietf.org/doc/html/rfc3164
+// RFC 5424: https://datatracker.ietf.org/doc/html/rfc5424
+// Both formats start with the priority "<PRI>", RFC 5424 puts the version right after it.
+// BSD messages are loosely formatted by the devices, so all of their header fields are optional.
+
+pub const MAX_PRIORITY: u8 = 191;
+pub const NIL: &str = "-";
+pub const BOM: &str = "\u{FEFF}";
+pub const BSD_TIMESTAMP_LENGTH: usize = 15;
+pub const MAX_TAG_LENGTH: usize = 32;
+pub const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
+    let text = String::from_utf8_lossy(bytes);
+    // Some senders terminate the message, though it's not needed over UDP
+    let text = text.trim_end_matches(['\r', '\n', '\0']);
+
+    let (priority, rest) =
+        parse_priority(text).ok_or(ParserError::ErrorVerify.to_nom(bytes))?;
+    let base = Syslog::new(priority).ok_or(ParserError::ErrorVerify.to_nom(bytes))?;
+    // BSD message may start with the number too, it's taken as is then
+    let syslog = parse_version(rest)
+        .and_then(|(version, header)| parse_rfc5424(&base, version, header))
+        .unwrap_or_else(|| parse_rfc3164(base, rest));
+
+    let rest: &[u8] = &[];
+    Ok((rest, ProtocolData::Syslog(syslog)))
+}
+
+pub fn is_protocol_default(port_source: u16, port_destination: u16) -> bool {
+    const PORT: u16 = 514;
+
+    port_source == PORT || port_destination == PORT
+}
+
+// "<PRI>": 1-3 digits, facility * 8 + severity
+fn parse_priority(text: &str) -> Option<(u8, &str)> {
+    let (priority, rest) = text.strip_prefix('<')?.split_once('>')?;
+    if priority.is_empty() || !priority.chars().all(|char| char.is_ascii_digit()) {
+        return None;
+    }
+    let priority = priority.parse::<u8>().ok()?;
+    if priority > MAX_PRIORITY {
+        return None;
+    }
+
+    Some((priority, rest))
+}
+
+fn parse_version(text: &str) -> Option<(u8, &str)> {
+    let (version, rest) = text.split_once(' ')?;
+    if version.starts_with('0') || !version.chars().all(|char| char.is_ascii_digit()) {
+        return None;
+    }
+
+    Some((version.parse::<u8>().ok()?, rest))
+}
+
+// TIMESTAMP HOSTNAME APP-NAME PROCID MSGID STRUCTURED-DATA [MSG]
+fn parse_rfc5424(base: &Syslog, version: u8, text: &str) -> Option<Syslog> {
+    let mut parts = text.splitn(6, ' ');
+    let timestamp = nil_or_value(parts.next()?);
+    if timestamp.as_ref().is_some_and(|timestamp| {
+        !timestamp.starts_with(|char: char| char.is_ascii_digit())
+    }) {
+        return None;
+    }
+    let hostname = nil_or_value(parts.next()?);
+    let app_name = nil_or_value(parts.next()?);
+    let process_id = nil_or_value(parts.next()?);
+    let message_id = nil_or_value(parts.next()?);
+    let (structured_data, message) = split_structured_data(parts.next()?)?;
+
+    Some(Syslog {
+        version: Some(version),
+        timestamp,
+        hostname,
+        app_name,
+        process_id,
+        message_id,
+        structured_data,
+        message: message.trim_start_matches(BOM).to_string(),
+        ..base.clone()
+    })
+}
+
+// [TIMESTAMP HOSTNAME ]TAG[PID]: MSG
+fn parse_rfc3164(base: Syslog, text: &str) -> Syslog {
+    let (timestamp, hostname, content) = match parse_bsd_timestamp(text) {
+        Some((timestamp, rest)) => match rest.split_once(' ') {
+            // Hostname is often skipped by the devices, then the tag goes right away
+            Some((hostname, content)) if is_hostname(hostname) => {
+                (Some(timestamp), Some(hostname.to_string()), content)
+            },
+            _ => (Some(timestamp), None, rest),
+        },
+        None => (None, None, text),
+    };
+    let (app_name, process_id, message) = split_tag(content);
+
+    Syslog {
+        timestamp: timestamp.map(str::to_string),
+        hostname,
+        app_name,
+        process_id,
+        message: message.to_string(),
+        ..base
+    }
+}
+
+// "Mmm dd hh:mm:ss", day is padded by the space
+fn parse_bsd_timestamp(text: &str) -> Option<(&str, &str)> {
+    let timestamp = text.get(..BSD_TIMESTAMP_LENGTH)?;
+    let rest = text.get(BSD_TIMESTAMP_LENGTH..)?;
+    let rest = match rest.is_empty() {
+        true => rest,
+        false => rest.strip_prefix(' ')?,
+    };
+
+    let (month, rest_timestamp) = timestamp.split_once(' ')?;
+    if !MONTHS.contains(&month) {
+        return None;
+    }
+    let (day, time) = rest_timestamp.trim_start().split_once(' ')?;
+    if !(1..=31).contains(&day.parse::<u8>().ok()?) {
+        return None;
+    }
+    let is_time = time.split(':').count() == 3
+        && time.split(':').all(|part| {
+            part.len() == 2 && part.chars().all(|char| char.is_ascii_digit())
+        });
+    if !is_time {
+        return None;
+    }
+
+    Some((timestamp, rest))
+}
+
+fn is_hostname(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || "-_.:".contains(char))
+        && !value.ends_with(':')
+}
+
+// "TAG[PID]: MSG", the whole content is the message, if there is no tag
+fn split_tag(content: &str) -> (Option<String>, Option<String>, &str) {
+    let end = content
+        .find(|char: char| !(char.is_ascii_alphanumeric() || "-_./".contains(char)))
+        .unwrap_or(content.len());
+    if end == 0 || end > MAX_TAG_LENGTH {
+        return (None, None, content);
+    }
+    let Some((tag, rest)) = content.split_at_checked(end) else {
+        return (None, None, content);
+    };
+
+    let (process_id, rest) =
+        match rest.strip_prefix('[').and_then(|rest| rest.split_once(']')) {
+            Some((process_id, rest)) => (Some(process_id.to_string()), rest),
+            None => (None, rest),
+        };
+    match rest.strip_prefix(':') {
+        Some(message) => (
+            Some(tag.to_string()),
+            process_id,
+            message.strip_prefix(' ').unwrap_or(message),
+        ),
+        None => (None, None, content),
+    }
+}
+
+// "-" or the elements "[ID PARAM="VALUE" ...]", values may hold the escaped '"' & ']'
+fn split_structured_data(text: &str) -> Option<(Option<String>, &str)> {
+    let end = if text.starts_with(NIL) {
+        NIL.len()
+    } else if text.starts_with('[') {
+        let mut is_quoted = false;
+        let mut is_escaped = false;
+        let mut end = None;
+        for (index, char) in text.char_indices() {
+            if is_escaped {
+                is_escaped = false;
+                continue;
+            }
+            match char {
+                '\\' if is_quoted => is_escaped = true,
+                '"' => is_quoted = !is_quoted,
+                ']' if !is_quoted => {
+                    let element_end = index.checked_add(1)?;
+                    // Next element follows right away
+                    if !text.get(element_end..)?.starts_with('[') {
+                        end = Some(element_end);
+                        break;
+                    }
+                },
+                _ => {},
+            }
+        }
+        end?
+    } else {
+        return None;
+    };
+
+    let (data, message) = text.split_at_checked(end)?;
+    let message = match message.is_empty() {
+        true => message,
+        false => message.strip_prefix(' ')?,
+    };
+
+    Some((nil_or_value(data), message))
+}
+
+fn nil_or_value(value: &str) -> Option<String> {
+    match value {
+        NIL => None,
+        value => Some(value.to_string()),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Syslog {
+    pub priority: u8,
+    pub facility: Facility,
+    pub severity: Severity,
+    // RFC 5424 only
+    pub version: Option<u8>,
+    pub timestamp: Option<String>,
+    pub hostname: Option<String>,
+    // Tag of the BSD message
+    pub app_name: Option<String>,
+    pub process_id: Option<String>,
+    // RFC 5424 only
+    pub message_id: Option<String>,
+    pub structured_data: Option<String>,
+    pub message: String,
+}
+
+impl Syslog {
+    fn new(priority: u8) -> Option<Self> {
+        // Lower 3 bits are the severity, the rest ones are the facility
+        let facility = Facility::try_from(priority >> 3).ok()?;
+        let severity = Severity::try_from(priority & 0x07).ok()?;
+
+        Some(Self {
+            priority,
+            facility,
+            severity,
+            version: None,
+            timestamp: None,
+            hostname: None,
+            app_name: None,
+            process_id: None,
+            message_id: None,
+            structured_data: None,
+            message: String::new(),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Display, Serialize, Deserialize, PartialEq, TryFromPrimitive)]
+#[repr(u8)]
+pub enum Facility {
+    Kernel = 0,
+    User = 1,
+    Mail = 2,
+    #[strum(to_string = "System Daemons")]
+    SystemDaemons = 3,
+    Security = 4,
+    Syslog = 5,
+    Printer = 6,
+    #[strum(to_string = "Network News")]
+    NetworkNews = 7,
+    UUCP = 8,
+    Clock = 9,
+    Authorization = 10,
+    FTP = 11,
+    NTP = 12,
+    #[strum(to_string = "Log Audit")]
+    LogAudit = 13,
+    #[strum(to_string = "Log Alert")]
+    LogAlert = 14,
+    #[strum(to_string = "Clock (2)")]
+    Clock2 = 15,
+    Local0 = 16,
+    Local1 = 17,
+    Local2 = 18,
+    Local3 = 19,
+    Local4 = 20,
+    Local5 = 21,
+    Local6 = 22,
+    Local7 = 23,
+}
+
+/// Ordered from the most severe one.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Display,
+    EnumIter,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    TryFromPrimitive,
+)]
+#[repr(u8)]
+pub enum Severity {
+    Emergency = 0,
+    Alert = 1,
+    Critical = 2,
+    Error = 3,
+    Warning = 4,
+    Notice = 5,
+    Informational = 6,
+    Debug = 7,
+}
+
+impl Severity {
+    /// Severity is the same as the threshold or worse, e.g. `Critical` is at least `Error`.
+    pub fn is_at_least(&self, threshold: &Self) -> bool {
+        self <= threshold
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SyslogDto {
+    #[serde(rename = "f")]
+    pub facility: Facility,
+    #[serde(rename = "s")]
+    pub severity: Severity,
+    #[serde(rename = "v", default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u8>,
+    #[serde(rename = "ts", default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    #[serde(rename = "host", default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    #[serde(rename = "app", default, skip_serializing_if = "Option::is_none")]
+    pub app_name: Option<String>,
+    #[serde(rename = "pid", default, skip_serializing_if = "Option::is_none")]
+    pub process_id: Option<String>,
+    #[serde(rename = "mid", default, skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<String>,
+    #[serde(rename = "sd", default, skip_serializing_if = "Option::is_none")]
+    pub structured_data: Option<String>,
+    #[serde(rename = "msg")]
+    pub message: String,
+}
+
+impl From<Syslog> for SyslogDto {
+    fn from(value: Syslog) -> Self {
+        Self {
+            facility: value.facility,
+            severity: value.severity,
+            version: value.version,
+            timestamp: value.timestamp,
+            hostname: value.hostname,
+            app_name: value.app_name,
+            process_id: value.process_id,
+            message_id: value.message_id,
+            structured_data: value.structured_data,
+            message: value.message,
+        }
+    }
+}
+
+impl FieldIter for SyslogDto {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let Self {
+            facility,
+            severity,
+            version,
+            timestamp,
+            hostname,
+            app_name,
+            process_id,
+            message_id,
+            structured_data,
+            message,
+        } = self;
+
+        vec![
+            ("facility", facility.to_string()),
+            ("severity", severity.to_string()),
+            ("version", fields::optional(version)),
+            ("timestamp", fields::optional(timestamp)),
+            ("hostname", fields::optional(hostname)),
+            ("app_name", fields::optional(app_name)),
+            ("process_id", fields::optional(process_id)),
+            ("message_id", fields::optional(message_id)),
+            ("structured_data", fields::optional(structured_data)),
+            ("message", message.clone()),
+        ]
+    }
+}
+
+#[cfg(all(test, feature = "ipv4", feature = "udp"))]
+mod tests {
+    use super::*;
+    use crate::parser::tests::udp_layers;
+
+    fn syslog_layer(hex_actual: &str) -> Syslog {
+        let payload = hex::decode(hex_actual.replace(" ", "")).unwrap();
+        let layers = udp_layers(49152, 514, &payload);
+        assert_eq!(layers.len(), 3);
+
+        match layers[2].clone() {
+            ProtocolData::Syslog(value) => value,
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn test_syslog_rfc3164() {
+        // RFC 3164, 5.4: <34>Oct 11 22:14:15 mymachine su: 'su root' failed for lonvick on /dev/pts/8
+        let hex_actual = "3C 33 34 3E 4F 63 74 20 31 31 20 32 32 3A 31 34 3A 31 35 20 6D 79 6D 61 63 68 69 6E 65 20 73 75 3A 20 27 73 75 20 72 6F 6F 74 27 20 66 61 69 6C 65 64 20 66 6F 72 20 6C 6F 6E 76 69 63 6B 20 6F 6E 20 2F 64 65 76 2F 70 74 73 2F 38 0A";
+        let actual = syslog_layer(hex_actual);
+
+        let expected = Syslog {
+            priority: 34,
+            facility: Facility::Security,
+            severity: Severity::Critical,
+            version: None,
+            timestamp: Some("Oct 11 22:14:15".to_string()),
+            hostname: Some("mymachine".to_string()),
+            app_name: Some("su".to_string()),
+            process_id: None,
+            message_id: None,
+            structured_data: None,
+            message: "'su root' failed for lonvick on /dev/pts/8".to_string(),
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_syslog_rfc3164_process_id() {
+        // <30>Mar  5 08:01:02 switch dhcpd[412]: DHCPACK on 192.168.1.20
+        let hex_actual = "3C 33 30 3E 4D 61 72 20 20 35 20 30 38 3A 30 31 3A 30 32 20 73 77 69 74 63 68 20 64 68 63 70 64 5B 34 31 32 5D 3A 20 44 48 43 50 41 43 4B 20 6F 6E 20 31 39 32 2E 31 36 38 2E 31 2E 32 30";
+        let actual = syslog_layer(hex_actual);
+
+        assert_eq!(actual.facility, Facility::SystemDaemons);
+        assert_eq!(actual.severity, Severity::Informational);
+        assert_eq!(actual.timestamp.as_deref(), Some("Mar  5 08:01:02"));
+        assert_eq!(actual.hostname.as_deref(), Some("switch"));
+        assert_eq!(actual.app_name.as_deref(), Some("dhcpd"));
+        assert_eq!(actual.process_id.as_deref(), Some("412"));
+        assert_eq!(actual.message, "DHCPACK on 192.168.1.20");
+
+        // <11>ntpd: time reset -0.5 s
+        // Timestamp & hostname are skipped by the sender
+        let hex_actual = "3C 31 31 3E 6E 74 70 64 3A 20 74 69 6D 65 20 72 65 73 65 74 20 2D 30 2E 35 20 73";
+        let actual = syslog_layer(hex_actual);
+
+        assert_eq!(actual.facility, Facility::User);
+        assert_eq!(actual.severity, Severity::Error);
+        assert_eq!(actual.timestamp, None);
+        assert_eq!(actual.hostname, None);
+        assert_eq!(actual.app_name.as_deref(), Some("ntpd"));
+        assert_eq!(actual.message, "time reset -0.5 s");
+    }
+
+    #[test]
+    fn test_syslog_rfc5424() {
+        // RFC 5424, 6.5, Example 3
+        let hex_actual = "3C 31 36 35 3E 31 20 32 30 30 33 2D 31 30 2D 31 31 54 32 32 3A 31 34 3A 31 35 2E 30 30 33 5A 20 6D 79 6D 61 63 68 69 6E 65 2E 65 78 61 6D 70 6C 65 2E 63 6F 6D 20 65 76 6E 74 73 6C 6F 67 20 2D 20 49 44 34 37 20 5B 65 78 61 6D 70 6C 65 53 44 49 44 40 33 32 34 37 33 20 69 75 74 3D 22 33 22 20 65 76 65 6E 74 53 6F 75 72 63 65 3D 22 41 70 70 6C 69 63 61 74 69 6F 6E 22 20 65 76 65 6E 74 49 44 3D 22 31 30 31 31 22 5D 20 41 6E 20 61 70 70 6C 69 63 61 74 69 6F 6E 20 65 76 65 6E 74 20 6C 6F 67 20 65 6E 74 72 79";
+        let actual = syslog_layer(hex_actual);
+
+        let expected = Syslog {
+            priority: 165,
+            facility: Facility::Local4,
+            severity: Severity::Notice,
+            version: Some(1),
+            timestamp: Some("2003-10-11T22:14:15.003Z".to_string()),
+            hostname: Some("mymachine.example.com".to_string()),
+            app_name: Some("evntslog".to_string()),
+            process_id: None,
+            message_id: Some("ID47".to_string()),
+            structured_data: Some(
+                "[exampleSDID@32473 iut=\"3\" eventSource=\"Application\" eventID=\"1011\"]"
+                    .to_string(),
+            ),
+            message: "An application event log entry".to_string(),
+        };
+        assert_eq!(actual, expected);
+
+        let dto = SyslogDto::from(actual);
+        assert_eq!(dto.fields()[1], ("severity", "Notice".to_string()));
+        assert!(dto.severity.is_at_least(&Severity::Informational));
+        assert!(!dto.severity.is_at_least(&Severity::Warning));
+    }
+
+    #[test]
+    fn test_syslog_structured_data() {
+        let (data, message) =
+            split_structured_data(r#"[a@1 x="q\"]"][b@1 y="2"] text"#).unwrap();
+        assert_eq!(data.as_deref(), Some(r#"[a@1 x="q\"]"][b@1 y="2"]"#));
+        assert_eq!(message, "text");
+
+        assert_eq!(split_structured_data("-"), Some((None, "")));
+        assert_eq!(split_structured_data("[a@1 x=\"1\""), None);
+    }
+
+    #[test]
+    fn test_syslog_malformed() {
+        // Priority is out of range, missing or isn't a number
+        for text in [
+            "<192>Oct 11 22:14:15 host su: text",
+            "Oct 11 host",
+            "<1a>text",
+        ] {
+            let layers = udp_layers(49152, 514, text.as_bytes());
+            assert!(!matches!(layers.get(2), Some(ProtocolData::Syslog(_))));
+        }
+    }
+}
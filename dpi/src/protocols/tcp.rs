@@ -1,9 +1,13 @@
+use crate::dto::fields::{self, FieldIter};
 use crate::dto::frame::FrameMetadata;
+use crate::dto::wire;
 use crate::parser;
-use crate::parser::ParserError;
+use crate::parser::{IResult, ParseFailure, ParserError};
+use crate::protocols::ip::checksum::ChecksumStatus;
+use crate::protocols::port_table::PortTable;
 use crate::protocols::{ProtocolData, ProtocolId};
 use nom::number::{be_u8, be_u16, be_u32, be_u64, be_u128};
-use nom::{IResult, Parser, bits};
+use nom::{Parser, bits};
 use num_enum::TryFromPrimitive;
 use serde::{Deserialize, Serialize};
 // TCP Protocol
@@ -12,8 +16,18 @@ use serde::{Deserialize, Serialize};
 pub const DATA_OFFSET_LENGTH_BITS: usize = 4;
 pub const RESERVED_LENGTH_BITS: usize = 4;
 pub const FLAG_LENGTH_BITS: usize = 1;
+// Header without the options, bytes
+pub const MIN_HEADER_LENGTH: usize = 20;
 type TcpFlags = (u8, u8, u8, u8, u8, u8, u8, u8);
 pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
+    if bytes.len() < MIN_HEADER_LENGTH {
+        return Err(ParserError::Truncated {
+            needed: MIN_HEADER_LENGTH,
+            available: bytes.len(),
+        }
+        .to_nom(bytes));
+    }
+
     // Source port. 2 bytes
     let (rest, port_source) = be_u16().parse(bytes)?;
     // Destination port. 2 bytes
@@ -26,40 +40,48 @@ pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
 
     // Data Offset, Reserved. Both - 4 bits
     let (rest, (data_offset, reserved)): (&[u8], (u16, u8)) =
-        bits::bits::<_, _, nom::error::Error<_>, _, _>(nom::sequence::pair(
+        bits::bits::<_, _, ParseFailure<_>, _, _>(nom::sequence::pair(
             bits::complete::take(DATA_OFFSET_LENGTH_BITS),
             bits::complete::take(RESERVED_LENGTH_BITS),
         ))(rest)?;
     // Data Offset is stored in 32bit words. So, we are doing DOffset * 32 / 8 (bits in bytes)
-    let data_offset = data_offset
-        .checked_mul(4)
-        .ok_or(ParserError::ErrorVerify.to_nom(bytes))?;
+    let data_offset = data_offset.checked_mul(4).ok_or(
+        ParserError::InvalidValue {
+            field: "data_offset",
+        }
+        .to_nom(bytes),
+    )?;
+    if (data_offset as usize) < MIN_HEADER_LENGTH {
+        return Err(ParserError::ConstraintViolation {
+            description: "data offset is less than the minimal header length",
+        }
+        .to_nom(bytes));
+    }
+    let truncated = || {
+        ParserError::Truncated {
+            needed: data_offset as usize,
+            available: bytes.len(),
+        }
+        .to_nom(bytes)
+    };
 
     // Already parsed 13 bytes, so doing sub 13.
-    let boundary = data_offset
-        .checked_sub(13)
-        .ok_or(ParserError::ErrorVerify.to_nom(bytes))? as usize;
-    let payload = rest
-        .get(boundary..)
-        .ok_or(ParserError::ErrorVerify.to_nom(bytes))?;
-    let rest = rest
-        .get(..boundary)
-        .ok_or(ParserError::ErrorVerify.to_nom(bytes))?;
+    let boundary = (data_offset as usize).saturating_sub(13);
+    let payload = rest.get(boundary..).ok_or_else(truncated)?;
+    let rest = rest.get(..boundary).ok_or_else(truncated)?;
 
     // Flags: 8 flags by 1 bit.
-    let (rest, flags): (&[u8], TcpFlags) =
-        bits::bits::<_, _, nom::error::Error<_>, _, _>((
-            bits::complete::take(FLAG_LENGTH_BITS),
-            bits::complete::take(FLAG_LENGTH_BITS),
-            bits::complete::take(FLAG_LENGTH_BITS),
-            bits::complete::take(FLAG_LENGTH_BITS),
-            bits::complete::take(FLAG_LENGTH_BITS),
-            bits::complete::take(FLAG_LENGTH_BITS),
-            bits::complete::take(FLAG_LENGTH_BITS),
-            bits::complete::take(FLAG_LENGTH_BITS),
-        ))(rest)?;
-    let flags =
-        Flags::try_from(flags).map_err(|_| ParserError::ErrorVerify.to_nom(bytes))?;
+    let (rest, flags): (&[u8], TcpFlags) = bits::bits::<_, _, ParseFailure<_>, _, _>((
+        bits::complete::take(FLAG_LENGTH_BITS),
+        bits::complete::take(FLAG_LENGTH_BITS),
+        bits::complete::take(FLAG_LENGTH_BITS),
+        bits::complete::take(FLAG_LENGTH_BITS),
+        bits::complete::take(FLAG_LENGTH_BITS),
+        bits::complete::take(FLAG_LENGTH_BITS),
+        bits::complete::take(FLAG_LENGTH_BITS),
+        bits::complete::take(FLAG_LENGTH_BITS),
+    ))(rest)?;
+    let flags = Flags::try_from(flags).map_err(|err| err.to_nom(bytes))?;
 
     // Window: 2 bytes.
     let (rest, window) = be_u16().parse(rest)?;
@@ -73,10 +95,21 @@ pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
     let mut option_bytes_buffer = rest;
     while !option_bytes_buffer.is_empty() {
         let (rest, kind) = be_u8().parse(option_bytes_buffer)?;
-        let (rest, value) = OptionId::try_from(kind)
-            .map_err(|_| ParserError::ErrorVerify.to_nom(rest))?
-            .parse(rest)?;
+        let (rest, value) = match OptionId::try_from(kind) {
+            Ok(id) => id.parse(rest)?,
+            // Unknown kinds are skipped by their length, so the segment isn't lost
+            Err(_) => {
+                let (rest, data) = OptionId::value(rest)?;
+                let data = data.to_vec();
+                (rest, OptionData::Unknown { kind, data })
+            },
+        };
+        let is_end = value == OptionData::EndOfOptionList;
         options.push(value);
+        // Everything after the end of the list is padding
+        if is_end {
+            break;
+        }
         option_bytes_buffer = rest;
     }
 
@@ -90,6 +123,7 @@ pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
         flags,
         window,
         checksum,
+        checksum_status: ChecksumStatus::NotChecked,
         urgent_pointer,
         options,
     };
@@ -97,23 +131,26 @@ pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
     Ok((payload, ProtocolData::TCP(protocol)))
 }
 
-pub fn best_children(metadata: &FrameMetadata) -> Option<ProtocolId> {
+pub fn best_children(
+    metadata: &FrameMetadata, ports: &PortTable, payload: &[u8],
+) -> Option<ProtocolId> {
     // Checking ports
     let layer = match metadata.layers.last() {
         Some(ProtocolData::TCP(value)) => value,
         _ => return None,
     };
 
-    for children in ProtocolId::TCP.children()? {
-        if let Some(port_validate) = children.check_ports() {
-            let applicable = port_validate(layer.port_source, layer.port_destination);
-            if applicable {
-                return Some(children);
-            }
-        }
+    let children = ProtocolId::plausible(
+        ProtocolId::TCP.children()?,
+        payload,
+        metadata.is_truncated,
+    );
+    if let Some(best) = ports.best(&children, layer.port_source, layer.port_destination) {
+        return Some(best);
     }
 
-    None
+    // Services on the non-standard ports
+    ProtocolId::by_signature(&children, payload)
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -127,25 +164,88 @@ pub struct TCP {
     pub flags: Flags,
     pub window: u16,
     pub checksum: u16,
+    pub checksum_status: ChecksumStatus,
     pub urgent_pointer: u16,
     pub options: Vec<OptionData>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct TcpDto {
+    #[serde(rename = "sp")]
     pub port_source: u16,
+    #[serde(rename = "dp")]
     pub port_destination: u16,
+    #[serde(rename = "cs", default, skip_serializing_if = "wire::is_default")]
+    pub checksum_status: ChecksumStatus,
+    // Raw value, scaled by the option of the handshake
+    #[serde(rename = "w")]
+    pub window: u16,
+    #[serde(default, skip_serializing_if = "wire::is_default")]
+    pub syn: bool,
+    #[serde(default, skip_serializing_if = "wire::is_default")]
+    pub fin: bool,
+    #[serde(rename = "rst", default, skip_serializing_if = "wire::is_default")]
+    pub reset: bool,
+    // Options of the handshake segments
+    #[serde(rename = "mss", default, skip_serializing_if = "Option::is_none")]
+    pub maximum_segment_size: Option<u16>,
+    #[serde(rename = "ws", default, skip_serializing_if = "Option::is_none")]
+    pub window_scale: Option<u8>,
 }
 
 impl From<TCP> for TcpDto {
     fn from(value: TCP) -> Self {
+        let maximum_segment_size = value.options.iter().find_map(|option| match option {
+            OptionData::MaximumSegmentSize(value) => Some(*value),
+            _ => None,
+        });
+        let window_scale = value.options.iter().find_map(|option| match option {
+            OptionData::WindowScaling(value) => Some(*value),
+            _ => None,
+        });
+
         Self {
             port_source: value.port_source,
             port_destination: value.port_destination,
+            checksum_status: value.checksum_status,
+            window: value.window,
+            syn: value.flags.syn,
+            fin: value.flags.fin,
+            reset: value.flags.reset,
+            maximum_segment_size,
+            window_scale,
         }
     }
 }
 
+impl FieldIter for TcpDto {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let Self {
+            port_source,
+            port_destination,
+            checksum_status,
+            window,
+            syn: _,
+            fin: _,
+            reset: _,
+            maximum_segment_size,
+            window_scale,
+        } = self;
+
+        vec![
+            ("port_source", port_source.to_string()),
+            ("port_destination", port_destination.to_string()),
+            ("checksum_status", checksum_status.to_string()),
+            ("window", window.to_string()),
+            (
+                "maximum_segment_size",
+                fields::optional(maximum_segment_size),
+            ),
+            ("window_scale", fields::optional(window_scale)),
+        ]
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct Flags {
     pub congestion_window_reduced: bool,
@@ -186,10 +286,22 @@ pub enum OptionId {
     SAck = 5,
 
     Timestamps = 8,
+    // RFC 5925: https://datatracker.ietf.org/doc/html/rfc5925
+    Authentication = 29,
+    // RFC 8684: https://datatracker.ietf.org/doc/html/rfc8684
+    Multipath = 30,
     FastOpen = 34,
 }
 
 impl OptionId {
+    const LENGTH_ERROR: ParserError = ParserError::InvalidValue {
+        field: "option_length",
+    };
+    // Kind & Length octets are included in the option length
+    const HEADER_LENGTH_BYTES: usize = 2;
+    // Left & right edges of the block, 4 bytes each
+    const SACK_BLOCK_LENGTH_BYTES: usize = 8;
+
     pub fn parse<'a>(&self, bytes: &'a [u8]) -> IResult<&'a [u8], OptionData> {
         match self {
             Self::EndOfOptionList => Ok((bytes, OptionData::EndOfOptionList)),
@@ -197,70 +309,91 @@ impl OptionId {
             Self::NoOperation => Ok((bytes, OptionData::NoOperation)),
 
             Self::MaximumSegmentSize => {
-                let (rest, length) = be_u8().parse(bytes)?;
-                if length != 4 {
-                    return Err(ParserError::ErrorVerify.to_nom(bytes));
+                let (rest, data) = Self::value(bytes)?;
+                if data.len() != 2 {
+                    return Err(Self::LENGTH_ERROR.to_nom(bytes));
                 }
-                let (rest, maximum_segment_size) = be_u16().parse(rest)?;
+                let (_, maximum_segment_size) = be_u16().parse(data)?;
                 Ok((rest, OptionData::MaximumSegmentSize(maximum_segment_size)))
             },
 
             Self::WindowScaling => {
-                let (rest, length) = be_u8().parse(bytes)?;
-                if length != 3 {
-                    return Err(ParserError::ErrorVerify.to_nom(bytes));
+                let (rest, data) = Self::value(bytes)?;
+                if data.len() != 1 {
+                    return Err(Self::LENGTH_ERROR.to_nom(bytes));
                 }
-                let (rest, window) = be_u8().parse(rest)?;
+                let (_, window) = be_u8().parse(data)?;
                 Ok((rest, OptionData::WindowScaling(window)))
             },
 
             Self::SAckPermitted => {
-                let (rest, length) = be_u8().parse(bytes)?;
-                if length != 2 {
-                    return Err(ParserError::ErrorVerify.to_nom(bytes));
+                let (rest, data) = Self::value(bytes)?;
+                if !data.is_empty() {
+                    return Err(Self::LENGTH_ERROR.to_nom(bytes));
                 }
                 Ok((rest, OptionData::SAckPermitted))
             },
 
             Self::SAck => {
-                let (rest, length) = be_u8().parse(bytes)?;
-                if length % 8 != 0 {
-                    return Err(ParserError::ErrorVerify.to_nom(bytes));
+                let (rest, data) = Self::value(bytes)?;
+                let blocks = data.chunks_exact(Self::SACK_BLOCK_LENGTH_BYTES);
+                if !blocks.remainder().is_empty() {
+                    return Err(Self::LENGTH_ERROR.to_nom(bytes));
                 }
-                let mut values = Vec::with_capacity(length as usize / 8);
-                let mut buffer = rest;
-                for _ in 0..(length / 8) {
-                    let (rest, value) = be_u64().parse(buffer)?;
+                let mut values = Vec::with_capacity(blocks.len());
+                for block in blocks {
+                    let (_, value) = be_u64().parse(block)?;
                     values.push(value);
-                    buffer = rest;
                 }
 
                 Ok((rest, OptionData::SAck(values)))
             },
 
             Self::Timestamps => {
-                let (rest, length) = be_u8().parse(bytes)?;
-                if length != 10 {
-                    return Err(ParserError::ErrorVerify.to_nom(bytes));
+                let (rest, data) = Self::value(bytes)?;
+                if data.len() != 8 {
+                    return Err(Self::LENGTH_ERROR.to_nom(bytes));
                 }
 
-                let (rest, initial_time) = be_u32().parse(rest)?;
-                let (rest, reply_time) = be_u32().parse(rest)?;
+                let (data, initial_time) = be_u32().parse(data)?;
+                let (_, reply_time) = be_u32().parse(data)?;
 
                 Ok((rest, OptionData::Timestamps(initial_time, reply_time)))
             },
 
+            Self::Authentication => {
+                let (rest, data) = Self::value(bytes)?;
+                Ok((rest, OptionData::Authentication(data.to_vec())))
+            },
+
+            Self::Multipath => {
+                let (rest, data) = Self::value(bytes)?;
+                Ok((rest, OptionData::Multipath(data.to_vec())))
+            },
+
             Self::FastOpen => {
-                let (rest, length) = be_u8().parse(bytes)?;
-                if length != 18 {
-                    return Err(ParserError::ErrorVerify.to_nom(bytes));
+                let (rest, data) = Self::value(bytes)?;
+                if data.len() != 16 {
+                    return Err(Self::LENGTH_ERROR.to_nom(bytes));
                 }
-                let (rest, cookie) = be_u128().parse(rest)?;
+                let (_, cookie) = be_u128().parse(data)?;
 
                 Ok((rest, OptionData::FastOpen(cookie)))
             },
         }
     }
+
+    // Data of the option, that has the length. Bytes start after the kind.
+    // Length less than the header is malformed, it would never move the parser further
+    fn value(bytes: &[u8]) -> IResult<&[u8], &[u8]> {
+        let (rest, length) = be_u8().parse(bytes)?;
+        let (data, rest) = usize::from(length)
+            .checked_sub(Self::HEADER_LENGTH_BYTES)
+            .and_then(|length| rest.split_at_checked(length))
+            .ok_or(Self::LENGTH_ERROR.to_nom(bytes))?;
+
+        Ok((rest, data))
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -272,13 +405,18 @@ pub enum OptionData {
     SAckPermitted,
     SAck(Vec<u64>),
     Timestamps(u32, u32),
+    // Opaque: key IDs & MAC of TCP-AO
+    Authentication(Vec<u8>),
+    // Opaque: subtype of MPTCP is in the first 4 bits
+    Multipath(Vec<u8>),
     FastOpen(u128),
+    Unknown { kind: u8, data: Vec<u8> },
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "ethernet", feature = "ipv4"))]
 mod tests {
     use super::*;
-    use crate::dto::frame::FrameHeader;
+    use crate::dto::frame::{FrameHeader, LinkType};
     use crate::parser::tests::FrameType;
     use crate::parser::tests::ProtocolParser;
     use crate::protocols::ProtocolData;
@@ -293,19 +431,10 @@ mod tests {
     fn test_tcp_with_options() {
         let hex_actual = "40 61 86 9A F1 F5 00 1A 8C 15 F9 80 08 00 45 00 00 34 94 15 00 00 34 06 11 0F 48 0E D5 66 C0 A8 03 83 00 50 DA 8E B2 61 2D 93 5D 1A BE A5 80 12 16 58 A0 94 00 00 02 04 05 96 01 01 04 02 01 03 03 06".replace(" ", "");
         let frame = hex::decode(hex_actual).unwrap();
-        let header = FrameHeader {
-            tv_sec: 0,
-            tv_usec: 0,
-            caplen: 66,
-            len: 0,
-        };
+        let header = FrameHeader::new(66);
 
-        let parser = ProtocolParser::new(&pcap::Linktype(1), false);
-        let packet = pcap::Packet {
-            header: &pcap::PacketHeader::from(&header),
-            data: &frame,
-        };
-        let result = parser.process(packet);
+        let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+        let result = parser.process_bytes(&header, &frame);
         let metadata = match result {
             Some(value) => match value {
                 FrameType::Metadata(value) => value,
@@ -344,8 +473,10 @@ mod tests {
             time_to_live: 52,
             protocol_inner: IpNextLevelProtocol::TCP,
             checksum: 0x110f,
+            checksum_status: ChecksumStatus::NotChecked,
             address_source: Ipv4Addr::new(72, 14, 213, 102),
             address_destination: Ipv4Addr::new(192, 168, 3, 131),
+            options: vec![],
         };
 
         assert_eq!(actual_ipv4, expected_ipv4);
@@ -374,6 +505,7 @@ mod tests {
             },
             window: 5720,
             checksum: 0xa094,
+            checksum_status: ChecksumStatus::NotChecked,
             urgent_pointer: 0,
             options: vec![
                 OptionData::MaximumSegmentSize(1430),
@@ -392,19 +524,10 @@ mod tests {
     fn test_tcp_without_options() {
         let hex_actual = "40 61 86 9A F1 F5 00 1A 8C 15 F9 80 08 00 45 00 00 56 2B 9A 00 00 34 06 79 3B 48 0E D5 93 C0 A8 03 83 01 BB CB B8 EE BA 28 1D 18 D9 BD 5F 50 18 00 D5 37 24 00 00 DE A9 06 7D DE 13 B6 78 A0 EA 50 53 29 A3 75 9C 1B B3 B0 3B 4D E5 21 DD 11 D4 75 A8 79 D5 58 B6 9F 6D 32 EA 72 F8 B0 54 C3 2F E9 AF 98 E4".replace(" ", "");
         let frame = hex::decode(hex_actual).unwrap();
-        let header = FrameHeader {
-            tv_sec: 0,
-            tv_usec: 0,
-            caplen: 100,
-            len: 0,
-        };
+        let header = FrameHeader::new(100);
 
-        let parser = ProtocolParser::new(&pcap::Linktype(1), false);
-        let packet = pcap::Packet {
-            header: &pcap::PacketHeader::from(&header),
-            data: &frame,
-        };
-        let result = parser.process(packet);
+        let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+        let result = parser.process_bytes(&header, &frame);
         let metadata = match result {
             Some(value) => match value {
                 FrameType::Metadata(value) => value,
@@ -443,8 +566,10 @@ mod tests {
             time_to_live: 52,
             protocol_inner: IpNextLevelProtocol::TCP,
             checksum: 0x793b,
+            checksum_status: ChecksumStatus::NotChecked,
             address_source: Ipv4Addr::new(72, 14, 213, 147),
             address_destination: Ipv4Addr::new(192, 168, 3, 131),
+            options: vec![],
         };
 
         assert_eq!(actual_ipv4, expected_ipv4);
@@ -473,10 +598,159 @@ mod tests {
             },
             window: 213,
             checksum: 0x3724,
+            checksum_status: ChecksumStatus::NotChecked,
             urgent_pointer: 0,
             options: vec![],
         };
 
         assert_eq!(actual_tcp, expected_tcp);
     }
+
+    #[test]
+    fn test_truncated_header() {
+        // IPv4 (192.168.1.10 -> 192.168.1.20) with 10 bytes of the TCP header
+        let hex_actual = "45 00 00 1E 00 01 40 00 40 06 00 00 C0 A8 01 0A C0 A8 01 14 01 BB CB B8 EE BA 36 1D 18 D9".replace(" ", "");
+        let frame = hex::decode(hex_actual).unwrap();
+
+        match parse(&frame[20..]) {
+            Err(nom::Err::Error(failure)) => assert_eq!(
+                failure.error,
+                ParserError::Truncated {
+                    needed: MIN_HEADER_LENGTH,
+                    available: 10
+                }
+            ),
+            _ => panic!(),
+        }
+
+        // Parsed layers are kept, the failure is given with the protocol
+        let header = FrameHeader::new(30);
+        let parser = parser::ProtocolParser::new(LinkType::IPV4, false);
+        let (_, failure) = parser.process_bytes_traced(&header, &frame).unwrap();
+        assert_eq!(
+            failure,
+            Some(parser::LayerFailure {
+                protocol: ProtocolId::TCP,
+                error: ParserError::Truncated {
+                    needed: MIN_HEADER_LENGTH,
+                    available: 10
+                },
+            })
+        );
+    }
+
+    // Header of SYN (48112 -> 443) with the options, padded to the 32-bit words
+    fn segment(options: &str) -> Vec<u8> {
+        let options = hex::decode(options.replace(" ", "")).unwrap();
+        assert_eq!(options.len() % 4, 0);
+        let data_offset = ((MIN_HEADER_LENGTH + options.len()) / 4) as u8;
+        let mut bytes = hex::decode("BBF001BB5A3C91E200000000").unwrap();
+        bytes.extend([data_offset << 4, 0x02, 0xFA, 0xF0, 0, 0, 0, 0]);
+        bytes.extend(options);
+        bytes
+    }
+
+    fn options(bytes: &[u8]) -> Vec<OptionData> {
+        match parse(bytes) {
+            Ok((_, ProtocolData::TCP(value))) => value.options,
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn test_tcp_multipath_syn() {
+        // IPv4 (192.168.1.10 -> 203.0.113.7), SYN with MP_CAPABLE of MPTCP v1
+        let hex_actual = "45 00 00 40 6F 21 40 00 40 06 CD DC C0 A8 01 0A CB 00 71 07 BB F0 01 BB 5A 3C 91 E2 00 00 00 00 B0 02 FA F0 AF AC 00 00 02 04 05 B4 04 02 08 0A 9C 3D 2A 17 00 00 00 00 01 03 03 07 1E 04 01 81".replace(" ", "");
+        let frame = hex::decode(hex_actual).unwrap();
+        let header = FrameHeader::new(64);
+
+        let parser = ProtocolParser::new(LinkType::IPV4, false);
+        let metadata = match parser.process_bytes(&header, &frame) {
+            Some(FrameType::Metadata(value)) => value,
+            _ => panic!(),
+        };
+        let actual_tcp = match metadata.layers[1].clone() {
+            ProtocolData::TCP(value) => value,
+            _ => panic!(),
+        };
+
+        assert_eq!(actual_tcp.data_offset, 44);
+        assert!(actual_tcp.flags.syn);
+        assert_eq!(
+            actual_tcp.options,
+            vec![
+                OptionData::MaximumSegmentSize(1460),
+                OptionData::SAckPermitted,
+                OptionData::Timestamps(0x9c3d2a17, 0),
+                OptionData::NoOperation,
+                OptionData::WindowScaling(7),
+                OptionData::Multipath(vec![0x01, 0x81]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unknown_options() {
+        // TCP-AO is known, but opaque. Experimental kind 253 is skipped by its length
+        let bytes = segment("1D 06 01 02 AA BB FD 04 CA FE 01 01 01 00 00 00");
+        assert_eq!(
+            options(&bytes),
+            vec![
+                OptionData::Authentication(vec![0x01, 0x02, 0xAA, 0xBB]),
+                OptionData::Unknown {
+                    kind: 253,
+                    data: vec![0xCA, 0xFE],
+                },
+                OptionData::NoOperation,
+                OptionData::NoOperation,
+                OptionData::NoOperation,
+                OptionData::EndOfOptionList,
+            ]
+        );
+
+        // SACK with the two blocks
+        let bytes =
+            segment("01 01 05 12 00 00 00 01 00 00 00 02 00 00 00 03 00 00 00 04");
+        assert_eq!(
+            options(&bytes)[2],
+            OptionData::SAck(vec![0x0000_0001_0000_0002, 0x0000_0003_0000_0004])
+        );
+    }
+
+    #[test]
+    fn test_end_of_options() {
+        // Bytes after the end are padding, even if they look like the broken options
+        let bytes = segment("02 04 05 B4 01 00 FF 00");
+        assert_eq!(
+            options(&bytes),
+            vec![
+                OptionData::MaximumSegmentSize(1460),
+                OptionData::NoOperation,
+                OptionData::EndOfOptionList,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_malformed_option_length() {
+        let length_error = ParserError::InvalidValue {
+            field: "option_length",
+        };
+        // Zero & one would never move the parser, the others go beyond the header
+        for options in [
+            "FD 00 00 00",
+            "FD 01 00 00",
+            "1E 01 00 00",
+            "02 01 05 B4",
+            "02 08 05 B4",
+            "FD 06 00 00",
+        ] {
+            match parse(&segment(options)) {
+                Err(nom::Err::Error(failure)) => {
+                    assert_eq!(failure.error, length_error, "{options}")
+                },
+                _ => panic!("{options}"),
+            }
+        }
+    }
 }
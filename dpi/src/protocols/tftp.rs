@@ -0,0 +1,437 @@
+use crate::dto::fields::{self, FieldIter};
+use crate::parser::{IResult, ParserError};
+use crate::protocols::ProtocolData;
+use nom::Parser;
+use nom::bytes::{tag, take_until};
+use nom::number::be_u16;
+use nom::sequence::terminated;
+use num_enum::TryFromPrimitive;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use strum_macros::Display;
+
+// Trivial File Transfer Protocol
+// RFC 1350: https://datatracker.ietf.org/doc/html/rfc1350
+// RFC 2347 (Option Extension): https://datatracker.ietf.org/doc/html/rfc2347
+// Only the requests are sent to the port 69, the transfer itself goes between
+// the ports chosen by both sides. Data packets are recognized, if their ports are configured.
+
+pub const NUL: &[u8] = &[0];
+
+pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
+    let (rest, opcode) = be_u16().parse(bytes)?;
+    let opcode = Opcode::try_from(opcode)
+        .map_err(|_| ParserError::InvalidValue { field: "opcode" }.to_nom(bytes))?;
+
+    let (rest, packet) = match opcode {
+        Opcode::ReadRequest | Opcode::WriteRequest => {
+            let (rest, filename) = parse_string(rest)?;
+            if filename.is_empty() {
+                return Err(ParserError::InvalidValue { field: "filename" }.to_nom(bytes));
+            }
+            let (rest, mode) = parse_string(rest)?;
+            let mode = Mode::try_from(mode.as_str()).map_err(|err| err.to_nom(bytes))?;
+            let (rest, options) = parse_options(rest)?;
+
+            let request = Request {
+                filename,
+                mode,
+                options,
+            };
+            match opcode {
+                Opcode::ReadRequest => (rest, TFTP::ReadRequest(request)),
+                _ => (rest, TFTP::WriteRequest(request)),
+            }
+        },
+        Opcode::Data => {
+            let (rest, block) = be_u16().parse(rest)?;
+            // Data takes the rest of the datagram, it isn't kept
+            let data = Data {
+                block,
+                length: rest.len(),
+            };
+            let rest: &[u8] = &[];
+            (rest, TFTP::Data(data))
+        },
+        Opcode::Acknowledgement => {
+            let (rest, block) = be_u16().parse(rest)?;
+            (rest, TFTP::Acknowledgement(block))
+        },
+        Opcode::Error => {
+            let (rest, code) = be_u16().parse(rest)?;
+            let code = ErrorCode::try_from(code).map_err(|_| {
+                ParserError::InvalidValue {
+                    field: "error_code",
+                }
+                .to_nom(bytes)
+            })?;
+            let (rest, message) = parse_string(rest)?;
+            (rest, TFTP::Error(TftpError { code, message }))
+        },
+        Opcode::OptionAcknowledgement => {
+            let (rest, options) = parse_options(rest)?;
+            (rest, TFTP::OptionAcknowledgement(options))
+        },
+    };
+
+    if !rest.is_empty() {
+        return Err(ParserError::ConstraintViolation {
+            description: "bytes are left after the packet",
+        }
+        .to_nom(bytes));
+    }
+
+    Ok((rest, ProtocolData::TFTP(packet)))
+}
+
+pub fn is_protocol_default(port_source: u16, port_destination: u16) -> bool {
+    const PORT: u16 = 69;
+
+    port_source == PORT || port_destination == PORT
+}
+
+// Netascii string, terminated by the zero byte
+fn parse_string(bytes: &[u8]) -> IResult<&[u8], String> {
+    let (rest, value) = terminated(take_until(NUL), tag(NUL)).parse(bytes)?;
+    let value = std::str::from_utf8(value)
+        .map_err(|_| ParserError::InvalidValue { field: "string" }.to_nom(bytes))?;
+    if value.chars().any(char::is_control) {
+        return Err(ParserError::InvalidValue { field: "string" }.to_nom(bytes));
+    }
+
+    Ok((rest, value.to_string()))
+}
+
+// Pairs of the option name & value, up to the end of the packet
+fn parse_options(bytes: &[u8]) -> IResult<&[u8], Vec<TftpOption>> {
+    let mut rest = bytes;
+    let mut options = vec![];
+    while !rest.is_empty() {
+        let (option_rest, name) = parse_string(rest)?;
+        let (option_rest, value) = parse_string(option_rest)?;
+        options.push(TftpOption {
+            name: name.to_ascii_lowercase(),
+            value,
+        });
+        rest = option_rest;
+    }
+
+    Ok((rest, options))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TFTP {
+    ReadRequest(Request),
+    WriteRequest(Request),
+    Data(Data),
+    Acknowledgement(u16),
+    Error(TftpError),
+    OptionAcknowledgement(Vec<TftpOption>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Request {
+    pub filename: String,
+    pub mode: Mode,
+    pub options: Vec<TftpOption>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Data {
+    pub block: u16,
+    // Less than the block size in the last packet of the transfer
+    pub length: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TftpError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+#[derive(
+    Clone, Copy, Debug, Display, Serialize, Deserialize, PartialEq, TryFromPrimitive,
+)]
+#[repr(u16)]
+pub enum Opcode {
+    #[strum(to_string = "RRQ")]
+    ReadRequest = 1,
+    #[strum(to_string = "WRQ")]
+    WriteRequest = 2,
+    #[strum(to_string = "DATA")]
+    Data = 3,
+    #[strum(to_string = "ACK")]
+    Acknowledgement = 4,
+    #[strum(to_string = "ERROR")]
+    Error = 5,
+    #[strum(to_string = "OACK")]
+    OptionAcknowledgement = 6,
+}
+
+#[derive(Clone, Debug, Display, Serialize, Deserialize, PartialEq)]
+pub enum Mode {
+    #[strum(to_string = "netascii")]
+    NetAscii,
+    #[strum(to_string = "octet")]
+    Octet,
+    #[strum(to_string = "mail")]
+    Mail,
+}
+
+impl TryFrom<&str> for Mode {
+    type Error = ParserError;
+    fn try_from(mode: &str) -> Result<Self, Self::Error> {
+        // Mode is case-insensitive
+        match mode.to_ascii_lowercase().as_str() {
+            "netascii" => Ok(Mode::NetAscii),
+            "octet" => Ok(Mode::Octet),
+            "mail" => Ok(Mode::Mail),
+            _ => Err(ParserError::InvalidValue { field: "mode" }),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Display, Serialize, Deserialize, PartialEq, TryFromPrimitive)]
+#[repr(u16)]
+pub enum ErrorCode {
+    #[strum(to_string = "Not defined")]
+    NotDefined = 0,
+    #[strum(to_string = "File not found")]
+    FileNotFound = 1,
+    #[strum(to_string = "Access violation")]
+    AccessViolation = 2,
+    #[strum(to_string = "Disk full")]
+    DiskFull = 3,
+    #[strum(to_string = "Illegal operation")]
+    IllegalOperation = 4,
+    #[strum(to_string = "Unknown transfer ID")]
+    UnknownTransferId = 5,
+    #[strum(to_string = "File already exists")]
+    FileAlreadyExists = 6,
+    #[strum(to_string = "No such user")]
+    NoSuchUser = 7,
+    #[strum(to_string = "Option negotiation")]
+    OptionNegotiation = 8,
+}
+
+/// Option of the request, e.g. "blksize" or "tsize".
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TftpOption {
+    pub name: String,
+    pub value: String,
+}
+
+impl Display for TftpOption {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}={}", self.name, self.value)
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct TftpDto {
+    #[serde(rename = "op")]
+    pub opcode: Opcode,
+    // Requests only
+    #[serde(rename = "file", default, skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    #[serde(rename = "mode", default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<Mode>,
+    // Data & acknowledgements only
+    #[serde(rename = "blk", default, skip_serializing_if = "Option::is_none")]
+    pub block: Option<u16>,
+    #[serde(rename = "len", default, skip_serializing_if = "Option::is_none")]
+    pub data_length: Option<usize>,
+    // Errors only
+    #[serde(rename = "ec", default, skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<ErrorCode>,
+    #[serde(rename = "em", default, skip_serializing_if = "Option::is_none")]
+    pub error_message: Option<String>,
+    #[serde(rename = "opt", default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<TftpOption>,
+}
+
+impl TftpDto {
+    fn new(opcode: Opcode) -> Self {
+        Self {
+            opcode,
+            filename: None,
+            mode: None,
+            block: None,
+            data_length: None,
+            error_code: None,
+            error_message: None,
+            options: vec![],
+        }
+    }
+}
+
+impl From<TFTP> for TftpDto {
+    fn from(value: TFTP) -> Self {
+        let request = |opcode: Opcode, request: Request| Self {
+            filename: Some(request.filename),
+            mode: Some(request.mode),
+            options: request.options,
+            ..Self::new(opcode)
+        };
+
+        match value {
+            TFTP::ReadRequest(value) => request(Opcode::ReadRequest, value),
+            TFTP::WriteRequest(value) => request(Opcode::WriteRequest, value),
+            TFTP::Data(value) => Self {
+                block: Some(value.block),
+                data_length: Some(value.length),
+                ..Self::new(Opcode::Data)
+            },
+            TFTP::Acknowledgement(block) => Self {
+                block: Some(block),
+                ..Self::new(Opcode::Acknowledgement)
+            },
+            TFTP::Error(value) => Self {
+                error_code: Some(value.code),
+                error_message: Some(value.message),
+                ..Self::new(Opcode::Error)
+            },
+            TFTP::OptionAcknowledgement(options) => Self {
+                options,
+                ..Self::new(Opcode::OptionAcknowledgement)
+            },
+        }
+    }
+}
+
+impl FieldIter for TftpDto {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let Self {
+            opcode,
+            filename,
+            mode,
+            block,
+            data_length,
+            error_code,
+            error_message,
+            options,
+        } = self;
+
+        vec![
+            ("opcode", opcode.to_string()),
+            ("filename", fields::optional(filename)),
+            ("mode", fields::optional(mode)),
+            ("block", fields::optional(block)),
+            ("data_length", fields::optional(data_length)),
+            ("error_code", fields::optional(error_code)),
+            ("error_message", fields::optional(error_message)),
+            ("options", fields::list(options)),
+        ]
+    }
+}
+
+#[cfg(all(test, feature = "ipv4", feature = "udp"))]
+mod tests {
+    use super::*;
+    use crate::parser::tests::udp_layers;
+
+    fn tftp_layer(port_source: u16, port_destination: u16, hex_actual: &str) -> TFTP {
+        let payload = hex::decode(hex_actual.replace(" ", "")).unwrap();
+        let layers = udp_layers(port_source, port_destination, &payload);
+        assert_eq!(layers.len(), 3);
+
+        match layers[2].clone() {
+            ProtocolData::TFTP(value) => value,
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn test_tftp_read_request() {
+        // RRQ "pxelinux.0" octet, blksize 1456, tsize 0
+        let hex_actual = "00 01 70 78 65 6C 69 6E 75 78 2E 30 00 6F 63 74 65 74 00 62 6C 6B 73 69 7A 65 00 31 34 35 36 00 74 73 69 7A 65 00 30 00";
+        let actual = tftp_layer(2070, 69, hex_actual);
+
+        let expected = TFTP::ReadRequest(Request {
+            filename: "pxelinux.0".to_string(),
+            mode: Mode::Octet,
+            options: vec![
+                TftpOption {
+                    name: "blksize".to_string(),
+                    value: "1456".to_string(),
+                },
+                TftpOption {
+                    name: "tsize".to_string(),
+                    value: "0".to_string(),
+                },
+            ],
+        });
+        assert_eq!(actual, expected);
+
+        let dto = TftpDto::from(actual);
+        assert_eq!(dto.opcode, Opcode::ReadRequest);
+        assert_eq!(dto.filename.as_deref(), Some("pxelinux.0"));
+        assert_eq!(fields::list(&dto.options), "blksize=1456, tsize=0");
+    }
+
+    #[test]
+    fn test_tftp_write_request_mode_case() {
+        // WRQ "running-config" NETASCII
+        let hex_actual = "00 02 72 75 6E 6E 69 6E 67 2D 63 6F 6E 66 69 67 00 4E 45 54 41 53 43 49 49 00";
+        let actual = tftp_layer(49152, 69, hex_actual);
+
+        let expected = TFTP::WriteRequest(Request {
+            filename: "running-config".to_string(),
+            mode: Mode::NetAscii,
+            options: vec![],
+        });
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_tftp_data_and_acknowledgement() {
+        // DATA block 1 with 4 bytes
+        let actual = tftp_layer(69, 2070, "00 03 00 01 DE AD BE EF");
+        assert_eq!(
+            actual,
+            TFTP::Data(Data {
+                block: 1,
+                length: 4
+            })
+        );
+
+        let actual = tftp_layer(2070, 69, "00 04 00 01");
+        assert_eq!(actual, TFTP::Acknowledgement(1));
+
+        let dto = TftpDto::from(actual);
+        assert_eq!(dto.block, Some(1));
+        assert_eq!(dto.data_length, None);
+    }
+
+    #[test]
+    fn test_tftp_error() {
+        // ERROR 1 "File not found"
+        let hex_actual = "00 05 00 01 46 69 6C 65 20 6E 6F 74 20 66 6F 75 6E 64 00";
+        let actual = tftp_layer(69, 2070, hex_actual);
+
+        let expected = TFTP::Error(TftpError {
+            code: ErrorCode::FileNotFound,
+            message: "File not found".to_string(),
+        });
+        assert_eq!(actual, expected);
+
+        let dto = TftpDto::from(actual);
+        let fields = dto.fields();
+        assert_eq!(fields[0], ("opcode", "ERROR".to_string()));
+        assert_eq!(fields[5], ("error_code", "File not found".to_string()));
+    }
+
+    #[test]
+    fn test_tftp_malformed() {
+        // Unknown opcode, unknown mode & unterminated filename aren't taken
+        for hex_actual in [
+            "00 09 00 01",
+            "00 01 61 00 62 69 6E 61 72 79 00",
+            "00 01 70 78 65 6C 69 6E 75 78",
+        ] {
+            let payload = hex::decode(hex_actual.replace(" ", "")).unwrap();
+            let layers = udp_layers(2070, 69, &payload);
+            assert!(!matches!(layers.get(2), Some(ProtocolData::TFTP(_))));
+        }
+    }
+}
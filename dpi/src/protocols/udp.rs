@@ -1,12 +1,22 @@
+use crate::dto::fields;
+use crate::dto::fields::FieldIter;
 use crate::dto::frame::FrameMetadata;
-use crate::protocols::{ProtocolData, ProtocolId};
-use nom::IResult;
+use crate::dto::wire;
+use crate::parser::IResult;
+use crate::protocols::ip::checksum::ChecksumStatus;
+use crate::protocols::port_table::PortTable;
+use crate::protocols::{ProtocolData, ProtocolId, enabled};
 use nom::Parser;
 use nom::number::be_u16;
 use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
 // UDP Protocol
 // RFC 768: https://datatracker.ietf.org/doc/html/rfc768
 
+// Header itself is counted by the length field
+const HEADER_LENGTH: usize = 8;
+
 pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
     // Source port. 2 bytes
     let (rest, port_source) = be_u16().parse(bytes)?;
@@ -17,34 +27,56 @@ pub fn parse(bytes: &[u8]) -> IResult<&[u8], ProtocolData> {
     // Checksum. 2 bytes
     let (rest, checksum) = be_u16().parse(rest)?;
 
-    let payload = rest;
+    // Some devices set the length, that disagrees with the payload of the IP layer.
+    // Datagram isn't dropped for that, the shorter one of them is parsed.
+    let announced = usize::from(length).saturating_sub(HEADER_LENGTH);
+    let length_mismatch = match announced.cmp(&rest.len()) {
+        Ordering::Less => Some(LengthMismatch::Trailer),
+        Ordering::Equal => None,
+        Ordering::Greater => Some(LengthMismatch::Truncated),
+    };
+    let payload = rest.get(..announced).unwrap_or(rest);
+
     let protocol = UDP {
         port_source,
         port_destination,
         length,
+        length_mismatch,
         checksum,
+        checksum_status: ChecksumStatus::NotChecked,
     };
 
     Ok((payload, ProtocolData::UDP(protocol)))
 }
 
-pub fn best_children(metadata: &FrameMetadata) -> Option<ProtocolId> {
+pub fn best_children(
+    metadata: &FrameMetadata, ports: &PortTable, payload: &[u8],
+) -> Option<ProtocolId> {
     // Checking ports
     let layer = match metadata.layers.last() {
         Some(ProtocolData::UDP(value)) => value,
         _ => return None,
     };
 
-    for children in ProtocolId::UDP.children()? {
-        if let Some(port_validate) = children.check_ports() {
-            let applicable = port_validate(layer.port_source, layer.port_destination);
-            if applicable {
-                return Some(children);
-            }
-        }
+    let is_truncated =
+        metadata.is_truncated || layer.length_mismatch == Some(LengthMismatch::Truncated);
+    let children =
+        ProtocolId::plausible(ProtocolId::UDP.children()?, payload, is_truncated);
+    if let Some(best) = ports.best(&children, layer.port_source, layer.port_destination) {
+        return Some(best);
+    }
+
+    // Services on the non-standard ports. Signatures are stricter than RTP ports
+    if let Some(best) = ProtocolId::by_signature(&children, payload) {
+        return Some(best);
     }
 
-    None
+    // Any even ports are matched, so it goes after the assigned ones
+    let rtp = enabled::RTP?;
+    match ports.is_matched(&rtp, layer.port_source, layer.port_destination) {
+        true => Some(rtp),
+        false => None,
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -52,13 +84,41 @@ pub struct UDP {
     pub port_source: u16,
     pub port_destination: u16,
     pub length: u16,
+    pub length_mismatch: Option<LengthMismatch>,
     pub checksum: u16,
+    pub checksum_status: ChecksumStatus,
+}
+
+/// Disagreement of the length field with the payload, that's left by the IP layer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LengthMismatch {
+    // Length field is smaller, the bytes after the datagram are skipped
+    Trailer,
+    // Length field is larger, only the available bytes are parsed
+    Truncated,
+}
+
+impl Display for LengthMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Self::Trailer => "⚠ trailer",
+            Self::Truncated => "⚠ truncated",
+        };
+
+        write!(f, "{text}")
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct UdpDto {
+    #[serde(rename = "sp")]
     pub port_source: u16,
+    #[serde(rename = "dp")]
     pub port_destination: u16,
+    #[serde(rename = "cs", default, skip_serializing_if = "wire::is_default")]
+    pub checksum_status: ChecksumStatus,
+    #[serde(rename = "lm", default, skip_serializing_if = "Option::is_none")]
+    pub length_mismatch: Option<LengthMismatch>,
 }
 
 impl From<UDP> for UdpDto {
@@ -66,14 +126,34 @@ impl From<UDP> for UdpDto {
         Self {
             port_source: value.port_source,
             port_destination: value.port_destination,
+            checksum_status: value.checksum_status,
+            length_mismatch: value.length_mismatch,
         }
     }
 }
 
-#[cfg(test)]
+impl FieldIter for UdpDto {
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let Self {
+            port_source,
+            port_destination,
+            checksum_status,
+            length_mismatch,
+        } = self;
+
+        vec![
+            ("port_source", port_source.to_string()),
+            ("port_destination", port_destination.to_string()),
+            ("checksum_status", checksum_status.to_string()),
+            ("length_mismatch", fields::optional(length_mismatch)),
+        ]
+    }
+}
+
+#[cfg(all(test, feature = "ethernet", feature = "ipv4"))]
 mod tests {
     use super::*;
-    use crate::dto::frame::FrameHeader;
+    use crate::dto::frame::{FrameHeader, LinkType};
     use crate::parser::tests::FrameType;
     use crate::parser::tests::ProtocolParser;
     use crate::protocols::ProtocolData;
@@ -88,19 +168,10 @@ mod tests {
     fn test_udp_without_ethernet_padding() {
         let hex_actual = "01 00 5E 00 00 FC 40 61 86 9A F1 F5 08 00 45 00 00 32 6A 3D 00 00 01 11 AA 56 C0 A8 03 83 E0 00 00 FC D5 48 14 EB 00 1E 20 88 76 F2 00 00 00 01 00 00 00 00 00 00 04 77 70 61 64 00 00 01 00 01".replace(" ", "");
         let frame = hex::decode(hex_actual).unwrap();
-        let header = FrameHeader {
-            tv_sec: 0,
-            tv_usec: 0,
-            caplen: 64,
-            len: 0,
-        };
+        let header = FrameHeader::new(64);
 
-        let parser = ProtocolParser::new(&pcap::Linktype(1), false);
-        let packet = pcap::Packet {
-            header: &pcap::PacketHeader::from(&header),
-            data: &frame,
-        };
-        let result = parser.process(packet);
+        let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+        let result = parser.process_bytes(&header, &frame);
         let metadata = match result {
             Some(value) => match value {
                 FrameType::Metadata(value) => value,
@@ -139,8 +210,10 @@ mod tests {
             time_to_live: 1,
             protocol_inner: IpNextLevelProtocol::UDP,
             checksum: 0xaa56,
+            checksum_status: ChecksumStatus::NotChecked,
             address_source: Ipv4Addr::new(192, 168, 3, 131),
             address_destination: Ipv4Addr::new(224, 0, 0, 252),
+            options: vec![],
         };
 
         assert_eq!(actual_ipv4, expected_ipv4);
@@ -154,7 +227,9 @@ mod tests {
             port_source: 54600,
             port_destination: 5355,
             length: 30,
+            length_mismatch: None,
             checksum: 0x2088,
+            checksum_status: ChecksumStatus::NotChecked,
         };
 
         assert_eq!(actual_udp, expected_udp);
@@ -164,19 +239,10 @@ mod tests {
     fn test_udp_with_ethernet_padding() {
         let hex_actual = "00 1E 68 51 4F A9 00 1A 8C 10 AD 30 08 00 45 00 00 2D 41 23 00 00 71 11 F7 55 58 C6 0D 6F AC 10 FF 01 53 78 C7 27 00 19 BB 26 B4 DC 02 B9 57 01 B1 11 45 7F BB A7 6C 79 6D 64 7E 00".replace(" ", "");
         let frame = hex::decode(hex_actual).unwrap();
-        let header = FrameHeader {
-            tv_sec: 0,
-            tv_usec: 0,
-            caplen: 60,
-            len: 0,
-        };
+        let header = FrameHeader::new(60);
 
-        let parser = ProtocolParser::new(&pcap::Linktype(1), false);
-        let packet = pcap::Packet {
-            header: &pcap::PacketHeader::from(&header),
-            data: &frame,
-        };
-        let result = parser.process(packet);
+        let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+        let result = parser.process_bytes(&header, &frame);
         let metadata = match result {
             Some(value) => match value {
                 FrameType::Metadata(value) => value,
@@ -215,8 +281,10 @@ mod tests {
             time_to_live: 113,
             protocol_inner: IpNextLevelProtocol::UDP,
             checksum: 0xf755,
+            checksum_status: ChecksumStatus::NotChecked,
             address_source: Ipv4Addr::new(88, 198, 13, 111),
             address_destination: Ipv4Addr::new(172, 16, 255, 1),
+            options: vec![],
         };
 
         assert_eq!(actual_ipv4, expected_ipv4);
@@ -230,9 +298,83 @@ mod tests {
             port_source: 21368,
             port_destination: 50983,
             length: 25,
+            length_mismatch: None,
             checksum: 0xbb26,
+            checksum_status: ChecksumStatus::NotChecked,
         };
 
         assert_eq!(actual_udp, expected_udp);
     }
+
+    // Ports 54600 -> 5355, 8 bytes of the payload
+    fn datagram(length: u16) -> Vec<u8> {
+        let mut bytes = vec![0xD5, 0x48, 0x14, 0xEB];
+        bytes.extend(length.to_be_bytes());
+        bytes.extend([0x00, 0x00, 1, 2, 3, 4, 5, 6, 7, 8]);
+        bytes
+    }
+
+    fn parsed(bytes: &[u8]) -> (&[u8], UDP) {
+        match parse(bytes).unwrap() {
+            (payload, ProtocolData::UDP(value)) => (payload, value),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn test_length_mismatch() {
+        let bytes = datagram(16);
+        let (payload, udp) = parsed(&bytes);
+        assert_eq!(payload, [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(udp.length_mismatch, None);
+
+        // Length field is trusted, the rest is skipped
+        let bytes = datagram(12);
+        let (payload, udp) = parsed(&bytes);
+        assert_eq!(payload, [1, 2, 3, 4]);
+        assert_eq!(udp.length_mismatch, Some(LengthMismatch::Trailer));
+
+        // Length is less than the header itself
+        let bytes = datagram(4);
+        let (payload, udp) = parsed(&bytes);
+        assert!(payload.is_empty());
+        assert_eq!(udp.length_mismatch, Some(LengthMismatch::Trailer));
+
+        // Available bytes are parsed
+        let bytes = datagram(20);
+        let (payload, udp) = parsed(&bytes);
+        assert_eq!(payload, [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(udp.length_mismatch, Some(LengthMismatch::Truncated));
+
+        let dto = UdpDto::from(udp);
+        let fields = dto.fields();
+        assert_eq!(
+            fields.last(),
+            Some(&("length_mismatch", "⚠ truncated".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_length_mismatch_in_frame() {
+        // LLMNR query, UDP length field is 0x28 instead of 0x1E. Checksum isn't computed.
+        let hex_actual = "01 00 5E 00 00 FC 40 61 86 9A F1 F5 08 00 45 00 00 32 6A 3D 00 00 01 11 AA 56 C0 A8 03 83 E0 00 00 FC D5 48 14 EB 00 28 00 00 76 F2 00 00 00 01 00 00 00 00 00 00 04 77 70 61 64 00 00 01 00 01".replace(" ", "");
+        let frame = hex::decode(hex_actual).unwrap();
+        let header = FrameHeader::new(64);
+
+        let parser =
+            ProtocolParser::new(LinkType::ETHERNET, false).with_checksum_validation(true);
+        let metadata = match parser.process_bytes(&header, &frame) {
+            Some(FrameType::Metadata(value)) => value,
+            _ => panic!(),
+        };
+
+        let udp = match &metadata.layers[2] {
+            ProtocolData::UDP(value) => value,
+            _ => panic!(),
+        };
+        assert_eq!(udp.length, 40);
+        assert_eq!(udp.length_mismatch, Some(LengthMismatch::Truncated));
+        // Zero checksum isn't reported as invalid
+        assert_eq!(udp.checksum_status, ChecksumStatus::NotChecked);
+    }
 }
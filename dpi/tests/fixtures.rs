@@ -0,0 +1,129 @@
+// Parser outcomes over the captured fixtures. Every fixture is the pair in `tests/fixtures`:
+// `<name>.pcap` & `<name>.json` with the expected layers & selected field values per frame.
+//
+// Expectations are regenerated by `REGENERATE_FIXTURES=1 cargo test -p dpi --test fixtures`.
+// Values of the selected fields are updated, the new expectation files get all the fields.
+
+use dpi::dto::fields::FieldIter;
+use dpi::dto::frame::FrameType;
+use dpi::parser::ProtocolParser;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+const REGENERATE_VARIABLE: &str = "REGENERATE_FIXTURES";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Expectation {
+    frames: Vec<ExpectedFrame>,
+}
+
+// Fields are keyed as "Protocol.field". If the protocol is repeated (tunnels),
+// the outermost layer is taken.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ExpectedFrame {
+    layers: Vec<String>,
+    #[serde(default)]
+    fields: BTreeMap<String, String>,
+}
+
+fn fixture_path(name: &str, extension: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(format!("{name}.{extension}"))
+}
+
+// Every layer & field of the frames. Layers of the incomplete frames are kept.
+fn parse(name: &str) -> Vec<ExpectedFrame> {
+    let mut capture = pcap::Capture::from_file(fixture_path(name, "pcap")).unwrap();
+    let parser = ProtocolParser::new(capture.get_datalink(), false);
+
+    let mut frames = vec![];
+    while let Ok(packet) = capture.next_packet() {
+        let mut frame = ExpectedFrame::default();
+        if let Some(FrameType::Metadata(metadata)) = parser.process(packet) {
+            for layer in metadata.layers {
+                let protocol = layer.id().to_string();
+                for (field, value) in layer.fields() {
+                    frame
+                        .fields
+                        .entry(format!("{protocol}.{field}"))
+                        .or_insert(value);
+                }
+                frame.layers.push(protocol);
+            }
+        }
+        frames.push(frame);
+    }
+
+    frames
+}
+
+fn check(name: &str) {
+    let actual = parse(name);
+    let path = fixture_path(name, "json");
+    if env::var_os(REGENERATE_VARIABLE).is_some() {
+        regenerate(&path, actual);
+        return;
+    }
+
+    let expected: Expectation =
+        serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(
+        actual.len(),
+        expected.frames.len(),
+        "{name}: amount of frames"
+    );
+    for (index, (actual, expected)) in actual.iter().zip(&expected.frames).enumerate() {
+        let number = index + 1;
+        assert_eq!(
+            actual.layers, expected.layers,
+            "{name}, frame #{number}: layers"
+        );
+        for (key, value) in &expected.fields {
+            assert_eq!(
+                actual.fields.get(key),
+                Some(value),
+                "{name}, frame #{number}: {key}"
+            );
+        }
+    }
+}
+
+// Selected fields of the existing expectation are kept, the new one gets all of them
+fn regenerate(path: &Path, actual: Vec<ExpectedFrame>) {
+    let previous: Option<Expectation> = fs::read_to_string(path)
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok());
+
+    let frames = actual
+        .into_iter()
+        .enumerate()
+        .map(|(index, mut frame)| {
+            let selected = previous
+                .as_ref()
+                .and_then(|previous| previous.frames.get(index));
+            if let Some(selected) = selected {
+                frame
+                    .fields
+                    .retain(|key, _| selected.fields.contains_key(key));
+            }
+            frame
+        })
+        .collect();
+
+    let text = serde_json::to_string_pretty(&Expectation { frames }).unwrap();
+    fs::write(path, format!("{text}\n")).unwrap();
+}
+
+#[test]
+fn test_arp_exchange() {
+    check("arp_exchange");
+}
+
+#[test]
+fn test_dns_exchange() {
+    check("dns_exchange");
+}
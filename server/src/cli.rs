@@ -0,0 +1,278 @@
+use crate::config::{Config, ConfigError};
+use crate::net::interface;
+use crate::net::interface::InterfaceError;
+use std::ffi::OsString;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::Path;
+use std::{env, io};
+use thiserror::Error;
+
+pub const USAGE: &str = "\
+Usage: server [COMMAND]
+
+Commands:
+  run            Start the server (default)
+  interfaces     List the capture interfaces
+  check-config   Validate the config file and exit
+  set-password   Prompt for the password and write its hash into the config
+  help           Print this message";
+
+/// Exit codes of the process, so the scripts could tell the config errors
+/// from the runtime ones. Values are taken from `sysexits.h`.
+pub mod exit_code {
+    pub const RUNTIME_ERROR: i32 = 1;
+    pub const USAGE_ERROR: i32 = 64;
+    pub const CONFIG_ERROR: i32 = 78;
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Command {
+    #[default]
+    Run,
+    Interfaces,
+    CheckConfig,
+    SetPassword,
+    Help,
+}
+
+impl Command {
+    pub fn resolve() -> Result<Self, CliError> {
+        Self::parse(env::args_os().skip(1))
+    }
+
+    fn parse(args: impl IntoIterator<Item = OsString>) -> Result<Self, CliError> {
+        let mut args = args
+            .into_iter()
+            .map(|arg| arg.to_string_lossy().to_string());
+
+        let command = match args.next().as_deref() {
+            None | Some("run") => Self::Run,
+            Some("interfaces") => Self::Interfaces,
+            Some("check-config") => Self::CheckConfig,
+            Some("set-password") => Self::SetPassword,
+            Some("help" | "--help" | "-h") => Self::Help,
+            Some(unknown) => return Err(CliError::UnknownCommand(unknown.to_string())),
+        };
+        // Commands don't take arguments
+        if let Some(argument) = args.next() {
+            return Err(CliError::UnexpectedArgument(argument));
+        }
+
+        Ok(command)
+    }
+}
+
+/// Prints the interfaces, that could be set in the config.
+pub fn print_interfaces() -> Result<(), CliError> {
+    let interfaces = interface::usable_sorted()?;
+    if interfaces.is_empty() {
+        println!("No usable interfaces.");
+        return Ok(());
+    }
+
+    for device in &interfaces {
+        let addresses = device
+            .addresses
+            .iter()
+            .map(|address| address.addr.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!(
+            "{}\t{addresses}",
+            interface::get_network_interface_name(device)
+        );
+    }
+
+    Ok(())
+}
+
+pub fn check_config(path: &Path) -> Result<(), CliError> {
+    let config = Config::read(path)?;
+    if let Some(warning) = config.protocol_ports_warning() {
+        println!("Warning: {warning}");
+    }
+    println!("Config is valid: {}", path.display());
+
+    Ok(())
+}
+
+pub fn set_password() -> Result<(), CliError> {
+    let mut config = Config::from_file()?;
+
+    let password = match io::stdin().is_terminal() {
+        // Not echoed, so it isn't left on the screen, in the scrollback & the recordings
+        true => {
+            read_password(|text| Ok(rpassword::prompt_password(format!("{text}: "))?))?
+        },
+        // Piped by the scripts, there's nothing to hide
+        false => read_password(lines(&mut io::stdin().lock(), &mut io::stdout()))?,
+    };
+    config.set_password_hash(&password)?;
+    config.save_to_file()?;
+    println!("Password is changed. Restart the server to apply it.");
+
+    Ok(())
+}
+
+// Password is asked twice, so the typo doesn't lock out the clients
+fn read_password(
+    mut prompt: impl FnMut(&str) -> Result<String, CliError>,
+) -> Result<String, CliError> {
+    let password = prompt("New password")?;
+    if password.is_empty() {
+        return Err(CliError::EmptyPassword);
+    }
+    if prompt("Repeat password")? != password {
+        return Err(CliError::PasswordMismatch);
+    }
+
+    Ok(password)
+}
+
+// Prompt, that reads the lines as is. For the input, that isn't the terminal
+fn lines(
+    input: &mut impl BufRead, output: &mut impl Write,
+) -> impl FnMut(&str) -> Result<String, CliError> {
+    move |text| {
+        write!(output, "{text}: ")?;
+        output.flush()?;
+
+        let mut line = String::new();
+        input.read_line(&mut line)?;
+        Ok(line.trim_end_matches(['\r', '\n']).to_string())
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum CliError {
+    #[error("Config Error: {0}")]
+    ConfigError(#[from] ConfigError),
+
+    #[error("Password is empty.")]
+    EmptyPassword,
+
+    #[error("Interface Error: {0}")]
+    InterfaceError(#[from] InterfaceError),
+
+    #[error("IO Error.")]
+    IOError(#[from] io::Error),
+
+    #[error("Passwords don't match.")]
+    PasswordMismatch,
+
+    #[error("Unexpected argument: {0}.")]
+    UnexpectedArgument(String),
+
+    #[error("Unknown command: {0}.")]
+    UnknownCommand(String),
+}
+
+impl CliError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::ConfigError(_) => exit_code::CONFIG_ERROR,
+            Self::UnexpectedArgument(_) | Self::UnknownCommand(_) => {
+                exit_code::USAGE_ERROR
+            },
+            Self::EmptyPassword
+            | Self::InterfaceError(_)
+            | Self::IOError(_)
+            | Self::PasswordMismatch => exit_code::RUNTIME_ERROR,
+        }
+    }
+
+    pub fn additional_info(&self) -> Option<String> {
+        match self {
+            Self::ConfigError(err) => err.additional_info(),
+            Self::InterfaceError(InterfaceError::PcapError(err)) => Some(err.to_string()),
+            Self::IOError(err) => Some(err.to_string()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use std::{fs, process};
+
+    fn parse(args: &[&str]) -> Result<Command, CliError> {
+        Command::parse(args.iter().map(OsString::from))
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(parse(&[]).unwrap(), Command::Run);
+        assert_eq!(parse(&["run"]).unwrap(), Command::Run);
+        assert_eq!(parse(&["interfaces"]).unwrap(), Command::Interfaces);
+        assert_eq!(parse(&["check-config"]).unwrap(), Command::CheckConfig);
+        assert_eq!(parse(&["set-password"]).unwrap(), Command::SetPassword);
+        assert_eq!(parse(&["--help"]).unwrap(), Command::Help);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let unknown = parse(&["start"]).unwrap_err();
+        assert!(matches!(unknown, CliError::UnknownCommand(_)));
+        assert_eq!(unknown.exit_code(), exit_code::USAGE_ERROR);
+
+        let unexpected = parse(&["check-config", "config.toml"]).unwrap_err();
+        assert!(matches!(unexpected, CliError::UnexpectedArgument(_)));
+        assert_eq!(unexpected.exit_code(), exit_code::USAGE_ERROR);
+    }
+
+    #[test]
+    fn test_check_config() {
+        let path =
+            env::temp_dir().join(format!("xailyser-config-{}.toml", process::id()));
+
+        let valid = toml::to_string(&Config::default()).unwrap();
+        fs::write(&path, &valid).unwrap();
+        assert!(check_config(&path).is_ok());
+
+        // Unknown log level
+        fs::write(
+            &path,
+            valid.replace("log_level = \"INFO\"", "log_level = \"LOUD\""),
+        )
+        .unwrap();
+        let err = check_config(&path).unwrap_err();
+        assert!(matches!(
+            err,
+            CliError::ConfigError(ConfigError::UnknownLogLevel)
+        ));
+        assert_eq!(err.exit_code(), exit_code::CONFIG_ERROR);
+
+        // Broken TOML
+        fs::write(&path, "port = ").unwrap();
+        let err = check_config(&path).unwrap_err();
+        assert!(matches!(
+            err,
+            CliError::ConfigError(ConfigError::TomlDeserializationError(_))
+        ));
+        assert_eq!(err.exit_code(), exit_code::CONFIG_ERROR);
+
+        // Missing config isn't created by the check
+        fs::remove_file(&path).unwrap();
+        let err = check_config(&path).unwrap_err();
+        assert_eq!(err.exit_code(), exit_code::CONFIG_ERROR);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_read_password() {
+        let mut output = vec![];
+        let password =
+            read_password(lines(&mut Cursor::new("secret\r\nsecret\n"), &mut output))
+                .unwrap();
+        assert_eq!(password, "secret");
+
+        let mismatch =
+            read_password(lines(&mut Cursor::new("secret\nsecert\n"), &mut output));
+        assert!(matches!(mismatch, Err(CliError::PasswordMismatch)));
+
+        let empty = read_password(lines(&mut Cursor::new("\n"), &mut output));
+        assert!(matches!(empty, Err(CliError::EmptyPassword)));
+    }
+}
@@ -1,33 +1,130 @@
+use crate::net::flow::ExportProtocol;
+use crate::net::mirror::Encapsulation;
+use common::cryptography;
+use common::cryptography::CryptographyError;
 use common::logging;
+use common::messages::{CaptureOptionsDto, SEND_DELAY_MAX_MS};
+use common::schedule::CaptureWindow;
+use dpi::parser;
+use dpi::protocols::port_table::PortTable;
 use log::LevelFilter;
 use serde::ser::SerializeStruct;
 use serde::{Deserialize, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::path::Path;
 use std::str::FromStr;
 use thiserror::Error;
 
-const CONFIG_FILENAME: &str = "config.toml";
+pub const CONFIG_FILENAME: &str = "config.toml";
+// Old format of the hash. Passwords without any prefix are the plaintext ones
+const LEGACY_HASH_PREFIX: &str = "sha256:";
 
 #[derive(Debug, Clone)]
 pub struct Config {
+    pub capture_buffer_size: i32,
+    pub capture_idle_timeout: u64,
+    pub capture_immediate_mode: bool,
+    pub capture_promiscuous: bool,
+    // Capture runs only within the windows. Empty list captures all the time
+    pub capture_schedule: Vec<CaptureWindow>,
+    pub capture_snaplen: i32,
+    pub capture_stats_interval: u64,
+    pub checksum_validation: bool,
     pub compression: bool,
+    // Frames, the parser fails on, are kept as the examples. For the parser development
+    pub failure_samples_enabled: bool,
+    pub flow_export_enabled: bool,
+    pub flow_export_collector: String,
+    pub flow_export_protocol: ExportProtocol,
+    pub flow_export_active_timeout: u64,
+    pub flow_export_inactive_timeout: u64,
+    // Client is closed after that many of its heartbeat intervals without messages.
+    // Zero disables it
+    pub heartbeat_tolerance: u32,
+    // HTTP bodies, declared longer, aren't kept. Zero disables the limit
+    pub http_body_limit_kilobytes: u64,
     pub interface: Option<String>,
     pub log_format: String,
     pub log_level: LevelFilter,
+    // Prometheus metrics over HTTP. Nothing is counted, if disabled
+    pub metrics_enabled: bool,
+    pub metrics_address: String,
+    // Mirrored frames are sent as is, or after the header with the capture time
+    pub mirror_encapsulation: Encapsulation,
+    // Limits of the mirror, zero disables the limit
+    pub mirror_kilobytes_per_second: u64,
+    pub mirror_packets_per_second: u64,
     pub password: String,
+    // Everything captured is written to the rotating pcap files
+    pub pcap_writer_enabled: bool,
+    pub pcap_writer_directory: String,
+    // Boundaries of the file, zero disables the boundary
+    pub pcap_writer_file_megabytes: u64,
+    pub pcap_writer_file_seconds: u64,
+    // Oldest files beyond that are deleted, zero keeps all of them
+    pub pcap_writer_files_kept: usize,
     pub port: u16,
+    pub protocol_ports: PortTable,
+    // Names from the config, that aren't the protocols. Warned at startup, not saved
+    pub protocol_ports_unknown: Vec<String>,
+    pub sampling_delay: u64,
+    pub sampling_rate: u32,
+    pub sampling_threshold: usize,
+    // Milliseconds, the frames are held to be sent together. Zero sends them at once
+    pub send_delay_ms: u64,
     pub send_unparsed_frames: bool,
+    pub session_token_ttl: u64,
+    // Statistics are saved to the file & restored at startup
+    pub statistics_persistence: bool,
+    // Seconds between the saves
+    pub statistics_save_interval: u64,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
+            capture_buffer_size: 0,
+            capture_idle_timeout: 30,
+            capture_immediate_mode: true,
+            capture_promiscuous: false,
+            capture_schedule: vec![],
+            capture_snaplen: 65535,
+            capture_stats_interval: 5,
+            checksum_validation: false,
             compression: true,
+            failure_samples_enabled: false,
+            flow_export_enabled: false,
+            flow_export_collector: String::from("127.0.0.1:2055"),
+            flow_export_protocol: ExportProtocol::NetflowV5,
+            flow_export_active_timeout: 1800,
+            flow_export_inactive_timeout: 15,
+            heartbeat_tolerance: 3,
+            http_body_limit_kilobytes: parser::HTTP_BODY_LIMIT_DEFAULT / 1024,
             interface: None,
             log_format: logging::DEFAULT_FORMAT.to_string(),
             log_level: LevelFilter::Info,
+            metrics_enabled: false,
+            metrics_address: String::from("127.0.0.1:9184"),
+            mirror_encapsulation: Encapsulation::Header,
+            mirror_kilobytes_per_second: 1024,
+            mirror_packets_per_second: 1000,
             password: String::new(),
+            pcap_writer_enabled: false,
+            pcap_writer_directory: String::from("captures"),
+            pcap_writer_file_megabytes: 100,
+            pcap_writer_file_seconds: 0,
+            pcap_writer_files_kept: 10,
             port: 8080,
+            protocol_ports: PortTable::default(),
+            protocol_ports_unknown: vec![],
+            sampling_delay: 3,
+            sampling_rate: 10,
+            sampling_threshold: 5000,
+            send_delay_ms: 0,
             send_unparsed_frames: false,
+            session_token_ttl: 3600,
+            statistics_persistence: true,
+            statistics_save_interval: 60,
         }
     }
 }
@@ -37,9 +134,37 @@ impl Serialize for Config {
     where
         S: Serializer,
     {
-        let mut state = serializer.serialize_struct("Config", 5)?;
+        let mut state = serializer.serialize_struct("Config", 40)?;
 
+        state.serialize_field("capture_buffer_size", &self.capture_buffer_size)?;
+        state.serialize_field("capture_idle_timeout", &self.capture_idle_timeout)?;
+        state.serialize_field("capture_immediate_mode", &self.capture_immediate_mode)?;
+        state.serialize_field("capture_promiscuous", &self.capture_promiscuous)?;
+        state.serialize_field("capture_snaplen", &self.capture_snaplen)?;
+        state.serialize_field("capture_stats_interval", &self.capture_stats_interval)?;
+        state.serialize_field("checksum_validation", &self.checksum_validation)?;
         state.serialize_field("compression", &self.compression)?;
+        state
+            .serialize_field("failure_samples_enabled", &self.failure_samples_enabled)?;
+        state.serialize_field("flow_export_enabled", &self.flow_export_enabled)?;
+        state.serialize_field("flow_export_collector", &self.flow_export_collector)?;
+        state.serialize_field(
+            "flow_export_protocol",
+            &self.flow_export_protocol.to_string(),
+        )?;
+        state.serialize_field(
+            "flow_export_active_timeout",
+            &self.flow_export_active_timeout,
+        )?;
+        state.serialize_field(
+            "flow_export_inactive_timeout",
+            &self.flow_export_inactive_timeout,
+        )?;
+        state.serialize_field("heartbeat_tolerance", &self.heartbeat_tolerance)?;
+        state.serialize_field(
+            "http_body_limit_kilobytes",
+            &self.http_body_limit_kilobytes,
+        )?;
 
         if let Some(interface) = &self.interface {
             state.serialize_field("interface", interface)?;
@@ -49,9 +174,47 @@ impl Serialize for Config {
 
         state.serialize_field("log_format", &self.log_format.to_string())?;
         state.serialize_field("log_level", &self.log_level.to_string())?;
+        state.serialize_field("metrics_enabled", &self.metrics_enabled)?;
+        state.serialize_field("metrics_address", &self.metrics_address)?;
+        state.serialize_field(
+            "mirror_encapsulation",
+            &self.mirror_encapsulation.to_string(),
+        )?;
+        state.serialize_field(
+            "mirror_kilobytes_per_second",
+            &self.mirror_kilobytes_per_second,
+        )?;
+        state.serialize_field(
+            "mirror_packets_per_second",
+            &self.mirror_packets_per_second,
+        )?;
         state.serialize_field("password", &self.password)?;
+        state.serialize_field("pcap_writer_enabled", &self.pcap_writer_enabled)?;
+        state.serialize_field("pcap_writer_directory", &self.pcap_writer_directory)?;
+        state.serialize_field(
+            "pcap_writer_file_megabytes",
+            &self.pcap_writer_file_megabytes,
+        )?;
+        state.serialize_field(
+            "pcap_writer_file_seconds",
+            &self.pcap_writer_file_seconds,
+        )?;
+        state.serialize_field("pcap_writer_files_kept", &self.pcap_writer_files_kept)?;
         state.serialize_field("port", &self.port)?;
+        state.serialize_field("sampling_delay", &self.sampling_delay)?;
+        state.serialize_field("sampling_rate", &self.sampling_rate)?;
+        state.serialize_field("sampling_threshold", &self.sampling_threshold)?;
+        state.serialize_field("send_delay_ms", &self.send_delay_ms)?;
         state.serialize_field("send_unparsed_frames", &self.send_unparsed_frames)?;
+        state.serialize_field("session_token_ttl", &self.session_token_ttl)?;
+        state.serialize_field("statistics_persistence", &self.statistics_persistence)?;
+        state.serialize_field(
+            "statistics_save_interval",
+            &self.statistics_save_interval,
+        )?;
+        // Tables go last, TOML values after them would belong to the tables
+        state.serialize_field("capture_schedule", &self.capture_schedule)?;
+        state.serialize_field("protocol_ports", &self.protocol_ports.to_names())?;
         state.end()
     }
 }
@@ -65,7 +228,18 @@ impl Config {
             return Ok(config);
         }
 
-        toml::from_str::<ConfigDto>(&data.unwrap_or_default())
+        Self::parse(&data.unwrap_or_default())
+    }
+
+    /// Reads the existing config. Unlike `from_file`, the missing file is an error.
+    pub fn read(path: &Path) -> Result<Self, ConfigError> {
+        let data = std::fs::read_to_string(path).map_err(ConfigError::IOError)?;
+
+        Self::parse(&data)
+    }
+
+    fn parse(data: &str) -> Result<Self, ConfigError> {
+        toml::from_str::<ConfigDto>(data)
             .map_err(ConfigError::TomlDeserializationError)?
             .into_config()
     }
@@ -77,17 +251,285 @@ impl Config {
 
         Ok(())
     }
+
+    /// Plaintext & SHA-256 passwords are rehashed by Argon2id.
+    /// Argon2id hashes with the raw output are upgraded to StoredKey.
+    /// Returns `true` if the password is changed, so the config has to be saved.
+    pub fn migrate_password(&mut self) -> Result<bool, ConfigError> {
+        if cryptography::is_hash(&self.password) {
+            return Ok(false);
+        }
+        self.password = match self.password.strip_prefix(LEGACY_HASH_PREFIX) {
+            Some(hash) => {
+                cryptography::hash_prehashed(&hash.trim().to_ascii_lowercase())?
+            },
+            None if cryptography::is_argon2_hash(&self.password) => {
+                cryptography::upgrade_hash(&self.password)?
+            },
+            None => cryptography::hash_password(&self.password)?,
+        };
+
+        Ok(true)
+    }
+
+    /// The password is kept hashed, so it isn't readable from the file.
+    pub fn set_password_hash(&mut self, password: &str) -> Result<(), ConfigError> {
+        self.password = cryptography::hash_password(password)?;
+
+        Ok(())
+    }
+
+    /// Warning about the names of `protocol_ports`, that aren't the protocols.
+    pub fn protocol_ports_warning(&self) -> Option<String> {
+        if self.protocol_ports_unknown.is_empty() {
+            return None;
+        }
+        let valid = PortTable::configurable()
+            .map(|protocol| PortTable::name(&protocol))
+            .collect::<Vec<_>>();
+
+        Some(format!(
+            "Unknown protocols in protocol_ports: {}. Valid names: {}.",
+            self.protocol_ports_unknown.join(", "),
+            valid.join(", ")
+        ))
+    }
+
+    /// Limit of the parser in bytes.
+    pub fn http_body_limit(&self) -> u64 {
+        match self.http_body_limit_kilobytes {
+            0 => u64::MAX,
+            kilobytes => kilobytes.saturating_mul(1024),
+        }
+    }
+
+    pub fn capture_options(&self) -> CaptureOptionsDto {
+        CaptureOptionsDto {
+            buffer_size: self.capture_buffer_size,
+            immediate_mode: self.capture_immediate_mode,
+            promiscuous: self.capture_promiscuous,
+            snaplen: self.capture_snaplen,
+        }
+    }
 }
 
 #[derive(Deserialize)]
 struct ConfigDto {
+    // Absent in the configs of the older versions
+    #[serde(default = "default_capture_buffer_size")]
+    capture_buffer_size: i32,
+    // Absent in the configs of the older versions
+    #[serde(default = "default_capture_idle_timeout")]
+    capture_idle_timeout: u64,
+    #[serde(default = "default_capture_immediate_mode")]
+    capture_immediate_mode: bool,
+    #[serde(default = "default_capture_promiscuous")]
+    capture_promiscuous: bool,
+    // Absent in the configs of the older versions
+    #[serde(default)]
+    capture_schedule: Vec<CaptureWindow>,
+    #[serde(default = "default_capture_snaplen")]
+    capture_snaplen: i32,
+    // Absent in the configs of the older versions
+    #[serde(default = "default_capture_stats_interval")]
+    capture_stats_interval: u64,
+    // Absent in the configs of the older versions
+    #[serde(default = "default_checksum_validation")]
+    checksum_validation: bool,
     compression: bool,
+    // Absent in the configs of the older versions
+    #[serde(default)]
+    failure_samples_enabled: bool,
+    // Absent in the configs of the older versions
+    #[serde(default = "default_flow_export_enabled")]
+    flow_export_enabled: bool,
+    #[serde(default = "default_flow_export_collector")]
+    flow_export_collector: String,
+    #[serde(default = "default_flow_export_protocol")]
+    flow_export_protocol: String,
+    #[serde(default = "default_flow_export_active_timeout")]
+    flow_export_active_timeout: u64,
+    #[serde(default = "default_flow_export_inactive_timeout")]
+    flow_export_inactive_timeout: u64,
+    // Absent in the configs of the older versions
+    #[serde(default = "default_heartbeat_tolerance")]
+    heartbeat_tolerance: u32,
+    // Absent in the configs of the older versions
+    #[serde(default = "default_http_body_limit_kilobytes")]
+    http_body_limit_kilobytes: u64,
     interface: String,
     log_format: String,
     log_level: String,
+    // Absent in the configs of the older versions
+    #[serde(default = "default_metrics_enabled")]
+    metrics_enabled: bool,
+    #[serde(default = "default_metrics_address")]
+    metrics_address: String,
+    // Absent in the configs of the older versions
+    #[serde(default = "default_mirror_encapsulation")]
+    mirror_encapsulation: String,
+    #[serde(default = "default_mirror_kilobytes_per_second")]
+    mirror_kilobytes_per_second: u64,
+    #[serde(default = "default_mirror_packets_per_second")]
+    mirror_packets_per_second: u64,
     password: String,
+    // Absent in the configs of the older versions
+    #[serde(default = "default_pcap_writer_enabled")]
+    pcap_writer_enabled: bool,
+    #[serde(default = "default_pcap_writer_directory")]
+    pcap_writer_directory: String,
+    #[serde(default = "default_pcap_writer_file_megabytes")]
+    pcap_writer_file_megabytes: u64,
+    #[serde(default = "default_pcap_writer_file_seconds")]
+    pcap_writer_file_seconds: u64,
+    #[serde(default = "default_pcap_writer_files_kept")]
+    pcap_writer_files_kept: usize,
     port: u16,
+    #[serde(default)]
+    protocol_ports: BTreeMap<String, Vec<u16>>,
+    // Absent in the configs of the older versions
+    #[serde(default = "default_sampling_delay")]
+    sampling_delay: u64,
+    #[serde(default = "default_sampling_rate")]
+    sampling_rate: u32,
+    #[serde(default = "default_sampling_threshold")]
+    sampling_threshold: usize,
+    // Absent in the configs of the older versions
+    #[serde(default = "default_send_delay_ms")]
+    send_delay_ms: u64,
     send_unparsed_frames: bool,
+    // Absent in the configs of the older versions
+    #[serde(default = "default_session_token_ttl")]
+    session_token_ttl: u64,
+    // Absent in the configs of the older versions
+    #[serde(default = "default_statistics_persistence")]
+    statistics_persistence: bool,
+    #[serde(default = "default_statistics_save_interval")]
+    statistics_save_interval: u64,
+}
+
+fn default_capture_buffer_size() -> i32 {
+    Config::default().capture_buffer_size
+}
+
+fn default_capture_idle_timeout() -> u64 {
+    Config::default().capture_idle_timeout
+}
+
+fn default_capture_immediate_mode() -> bool {
+    Config::default().capture_immediate_mode
+}
+
+fn default_capture_promiscuous() -> bool {
+    Config::default().capture_promiscuous
+}
+
+fn default_capture_snaplen() -> i32 {
+    Config::default().capture_snaplen
+}
+
+fn default_capture_stats_interval() -> u64 {
+    Config::default().capture_stats_interval
+}
+
+fn default_checksum_validation() -> bool {
+    Config::default().checksum_validation
+}
+
+fn default_flow_export_active_timeout() -> u64 {
+    Config::default().flow_export_active_timeout
+}
+
+fn default_flow_export_collector() -> String {
+    Config::default().flow_export_collector
+}
+
+fn default_flow_export_enabled() -> bool {
+    Config::default().flow_export_enabled
+}
+
+fn default_flow_export_inactive_timeout() -> u64 {
+    Config::default().flow_export_inactive_timeout
+}
+
+fn default_flow_export_protocol() -> String {
+    Config::default().flow_export_protocol.to_string()
+}
+
+fn default_heartbeat_tolerance() -> u32 {
+    Config::default().heartbeat_tolerance
+}
+
+fn default_http_body_limit_kilobytes() -> u64 {
+    Config::default().http_body_limit_kilobytes
+}
+
+fn default_metrics_enabled() -> bool {
+    Config::default().metrics_enabled
+}
+
+fn default_metrics_address() -> String {
+    Config::default().metrics_address
+}
+
+fn default_mirror_encapsulation() -> String {
+    Config::default().mirror_encapsulation.to_string()
+}
+
+fn default_mirror_kilobytes_per_second() -> u64 {
+    Config::default().mirror_kilobytes_per_second
+}
+
+fn default_mirror_packets_per_second() -> u64 {
+    Config::default().mirror_packets_per_second
+}
+
+fn default_pcap_writer_enabled() -> bool {
+    Config::default().pcap_writer_enabled
+}
+
+fn default_pcap_writer_directory() -> String {
+    Config::default().pcap_writer_directory
+}
+
+fn default_pcap_writer_file_megabytes() -> u64 {
+    Config::default().pcap_writer_file_megabytes
+}
+
+fn default_pcap_writer_file_seconds() -> u64 {
+    Config::default().pcap_writer_file_seconds
+}
+
+fn default_pcap_writer_files_kept() -> usize {
+    Config::default().pcap_writer_files_kept
+}
+
+fn default_sampling_delay() -> u64 {
+    Config::default().sampling_delay
+}
+
+fn default_sampling_rate() -> u32 {
+    Config::default().sampling_rate
+}
+
+fn default_sampling_threshold() -> usize {
+    Config::default().sampling_threshold
+}
+
+fn default_send_delay_ms() -> u64 {
+    Config::default().send_delay_ms
+}
+
+fn default_session_token_ttl() -> u64 {
+    Config::default().session_token_ttl
+}
+
+fn default_statistics_persistence() -> bool {
+    Config::default().statistics_persistence
+}
+
+fn default_statistics_save_interval() -> u64 {
+    Config::default().statistics_save_interval
 }
 
 impl ConfigDto {
@@ -98,15 +540,55 @@ impl ConfigDto {
             Some(self.interface)
         };
 
+        let (protocol_ports, protocol_ports_unknown) =
+            PortTable::from_names(&self.protocol_ports);
+
         let config = Config {
+            capture_buffer_size: self.capture_buffer_size,
+            capture_idle_timeout: self.capture_idle_timeout,
+            capture_immediate_mode: self.capture_immediate_mode,
+            capture_promiscuous: self.capture_promiscuous,
+            capture_schedule: self.capture_schedule,
+            capture_snaplen: self.capture_snaplen,
+            capture_stats_interval: self.capture_stats_interval,
+            checksum_validation: self.checksum_validation,
             compression: self.compression,
+            failure_samples_enabled: self.failure_samples_enabled,
+            flow_export_enabled: self.flow_export_enabled,
+            flow_export_collector: self.flow_export_collector,
+            flow_export_protocol: ExportProtocol::from_str(&self.flow_export_protocol)
+                .map_err(|_| ConfigError::UnknownFlowExportProtocol)?,
+            flow_export_active_timeout: self.flow_export_active_timeout,
+            flow_export_inactive_timeout: self.flow_export_inactive_timeout,
+            heartbeat_tolerance: self.heartbeat_tolerance,
+            http_body_limit_kilobytes: self.http_body_limit_kilobytes,
             interface,
             log_format: self.log_format,
             log_level: LevelFilter::from_str(&self.log_level)
                 .map_err(|_| ConfigError::UnknownLogLevel)?,
+            metrics_enabled: self.metrics_enabled,
+            metrics_address: self.metrics_address,
+            mirror_encapsulation: Encapsulation::from_str(&self.mirror_encapsulation)
+                .map_err(|_| ConfigError::UnknownMirrorEncapsulation)?,
+            mirror_kilobytes_per_second: self.mirror_kilobytes_per_second,
+            mirror_packets_per_second: self.mirror_packets_per_second,
             password: self.password,
+            pcap_writer_enabled: self.pcap_writer_enabled,
+            pcap_writer_directory: self.pcap_writer_directory,
+            pcap_writer_file_megabytes: self.pcap_writer_file_megabytes,
+            pcap_writer_file_seconds: self.pcap_writer_file_seconds,
+            pcap_writer_files_kept: self.pcap_writer_files_kept,
             port: self.port,
+            protocol_ports,
+            protocol_ports_unknown,
+            sampling_delay: self.sampling_delay,
+            sampling_rate: self.sampling_rate,
+            sampling_threshold: self.sampling_threshold,
+            send_delay_ms: self.send_delay_ms.min(SEND_DELAY_MAX_MS),
             send_unparsed_frames: self.send_unparsed_frames,
+            session_token_ttl: self.session_token_ttl,
+            statistics_persistence: self.statistics_persistence,
+            statistics_save_interval: self.statistics_save_interval,
         };
 
         Ok(config)
@@ -124,8 +606,17 @@ pub enum ConfigError {
     #[error("TOML Deserialization Error.")]
     TomlDeserializationError(#[from] toml::de::Error),
 
+    #[error("Unknown flow export protocol. Possible values: netflow5, ipfix.")]
+    UnknownFlowExportProtocol,
+
     #[error("Unknown log level.")]
     UnknownLogLevel,
+
+    #[error("Unknown mirror encapsulation. Possible values: plain, header.")]
+    UnknownMirrorEncapsulation,
+
+    #[error("Failed to hash the password.")]
+    PasswordHashError(#[from] CryptographyError),
 }
 
 impl ConfigError {
@@ -134,7 +625,364 @@ impl ConfigError {
             ConfigError::IOError(err) => Some(err.to_string()),
             ConfigError::TomlSerializationError(err) => Some(err.to_string()),
             ConfigError::TomlDeserializationError(err) => Some(err.to_string()),
+            ConfigError::PasswordHashError(err) => Some(err.to_string()),
             _ => None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dpi::protocols::ProtocolId;
+
+    // Client proves the password by the key, derived from the params of the hash
+    fn is_password(config: &Config, password: &str) -> bool {
+        let params = cryptography::challenge_params(&config.password).unwrap();
+        let nonce = cryptography::generate_nonce();
+        let key = cryptography::derive_key(password, &params).unwrap();
+        let signature = cryptography::sign_nonce(&key, &nonce).unwrap();
+
+        cryptography::verify_signature(
+            &cryptography::stored_key(&config.password).unwrap(),
+            &nonce,
+            &signature,
+        )
+    }
+
+    #[test]
+    fn test_password_hash() {
+        let mut config = Config::default();
+        config.set_password_hash("secret").unwrap();
+        assert!(cryptography::is_hash(&config.password));
+        assert!(is_password(&config, "secret"));
+        assert!(!is_password(&config, "Secret"));
+
+        // Already in the new format
+        let hash = config.password.clone();
+        assert!(!config.migrate_password().unwrap());
+        assert_eq!(config.password, hash);
+    }
+
+    #[test]
+    fn test_password_migration() {
+        let mut plaintext = Config {
+            password: "secret".to_string(),
+            ..Default::default()
+        };
+        assert!(plaintext.migrate_password().unwrap());
+        assert!(is_password(&plaintext, "secret"));
+
+        // Rehashed without the plaintext
+        let legacy = format!(
+            "{LEGACY_HASH_PREFIX}{}",
+            cryptography::prehash_password("secret").to_ascii_uppercase()
+        );
+        let mut config = Config::parse(
+            &toml::to_string(&Config {
+                password: legacy,
+                ..Default::default()
+            })
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(config.migrate_password().unwrap());
+        assert!(is_password(&config, "secret"));
+        assert!(!is_password(&config, "other"));
+
+        // Saved hash is read back as is
+        let saved = Config::parse(&toml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(saved.password, config.password);
+    }
+
+    #[test]
+    fn test_argon2_hash_migration() {
+        // Raw Argon2id output of "secret", as it's saved by the older versions
+        let mut config = Config {
+            password: "$argon2id$v=19$m=19456,t=2,p=1$eGFpbHlzZXItc2FsdC0xNg\
+                $fxgF4tcfGiow3UVeD+ffkTIGTMlmfDfikPTgEKAa9sM"
+                .to_string(),
+            ..Default::default()
+        };
+        assert!(config.migrate_password().unwrap());
+        assert!(cryptography::is_hash(&config.password));
+        assert!(!config.password.contains("fxgF4tcfGiow3UVeD"));
+        assert!(is_password(&config, "secret"));
+        assert!(!is_password(&config, "other"));
+    }
+
+    #[test]
+    fn test_protocol_ports() {
+        let mut data = toml::to_string(&Config::default()).unwrap();
+        assert!(Config::parse(&data).unwrap().protocol_ports.is_empty());
+
+        data.push_str("http = [80, 8080, 3128]\ndns = [53, 5300]\ngopher = [70]\n");
+        let config = Config::parse(&data).unwrap();
+        assert_eq!(
+            config.protocol_ports.get(&ProtocolId::HTTP),
+            Some([80, 3128, 8080].as_slice())
+        );
+        assert_eq!(config.protocol_ports_unknown, vec!["gopher".to_string()]);
+        assert!(
+            config
+                .protocol_ports_warning()
+                .unwrap()
+                .contains("Valid names: dhcpv4, dhcpv6, dns, http")
+        );
+
+        // Unknown names aren't saved
+        let saved = Config::parse(&toml::to_string(&config).unwrap()).unwrap();
+        assert_eq!(saved.protocol_ports, config.protocol_ports);
+        assert!(saved.protocol_ports_warning().is_none());
+    }
+
+    #[test]
+    fn test_capture_schedule() {
+        // Config of the older version, without the schedule
+        let data = toml::to_string(&Config::default())
+            .unwrap()
+            .lines()
+            .filter(|line| !line.starts_with("capture_schedule"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert!(Config::parse(&data).unwrap().capture_schedule.is_empty());
+
+        let data = format!(
+            "{data}\n[[capture_schedule]]\ndays = [\"Mon\", \"Tue\"]\n\
+             start = \"22:00\"\nend = \"06:00\"\ntimezone = \"+02:00\"\n"
+        );
+        let config = Config::parse(&data).unwrap();
+        assert_eq!(config.capture_schedule.len(), 1);
+        assert_eq!(
+            config.capture_schedule[0].to_string(),
+            "Mon, Tue 22:00-06:00 (+02:00)"
+        );
+
+        // Saved along with the port table
+        let mut with_ports = config.clone();
+        with_ports
+            .protocol_ports
+            .set(ProtocolId::HTTP, &[8080])
+            .unwrap();
+        let saved = Config::parse(&toml::to_string(&with_ports).unwrap()).unwrap();
+        assert_eq!(saved.capture_schedule, config.capture_schedule);
+        assert_eq!(saved.protocol_ports, with_ports.protocol_ports);
+    }
+
+    #[test]
+    fn test_metrics_defaults() {
+        // Config of the older version, without the metrics fields
+        let data = toml::to_string(&Config::default())
+            .unwrap()
+            .lines()
+            .filter(|line| !line.starts_with("metrics_"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let config = Config::parse(&data).unwrap();
+        assert!(!config.metrics_enabled);
+        assert_eq!(config.metrics_address, "127.0.0.1:9184");
+    }
+
+    #[test]
+    fn test_heartbeat_defaults() {
+        // Config of the older version, without the heartbeat field
+        let data = toml::to_string(&Config::default())
+            .unwrap()
+            .lines()
+            .filter(|line| !line.starts_with("heartbeat_"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let config = Config::parse(&data).unwrap();
+        assert_eq!(config.heartbeat_tolerance, 3);
+    }
+
+    #[test]
+    fn test_http_body_limit() {
+        // Config of the older version, without the limit
+        let data = toml::to_string(&Config::default())
+            .unwrap()
+            .lines()
+            .filter(|line| !line.starts_with("http_body_"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let config = Config::parse(&data).unwrap();
+        assert_eq!(config.http_body_limit(), parser::HTTP_BODY_LIMIT_DEFAULT);
+
+        let config =
+            Config::parse(&format!("http_body_limit_kilobytes = 0\n{data}")).unwrap();
+        assert_eq!(config.http_body_limit(), u64::MAX);
+    }
+
+    #[test]
+    fn test_mirror_defaults() {
+        // Config of the older version, without the mirror fields
+        let data = toml::to_string(&Config::default())
+            .unwrap()
+            .lines()
+            .filter(|line| !line.starts_with("mirror_"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let config = Config::parse(&data).unwrap();
+        assert_eq!(config.mirror_encapsulation, Encapsulation::Header);
+        assert_eq!(config.mirror_kilobytes_per_second, 1024);
+        assert_eq!(config.mirror_packets_per_second, 1000);
+
+        let config = Config::parse(&format!("mirror_encapsulation = \"plain\"\n{data}"));
+        assert_eq!(config.unwrap().mirror_encapsulation, Encapsulation::Plain);
+    }
+
+    #[test]
+    fn test_pcap_writer_defaults() {
+        // Config of the older version, without the writer fields
+        let data = toml::to_string(&Config::default())
+            .unwrap()
+            .lines()
+            .filter(|line| !line.starts_with("pcap_writer_"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let config = Config::parse(&data).unwrap();
+        assert!(!config.pcap_writer_enabled);
+        assert_eq!(config.pcap_writer_directory, "captures");
+        assert_eq!(config.pcap_writer_files_kept, 10);
+    }
+
+    #[test]
+    fn test_send_delay() {
+        // Config of the older version, without the delay
+        let data = toml::to_string(&Config::default())
+            .unwrap()
+            .lines()
+            .filter(|line| !line.starts_with("send_delay_"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(Config::parse(&data).unwrap().send_delay_ms, 0);
+
+        // Longer delay is cut to the maximum
+        let config = Config::parse(&format!("send_delay_ms = 5000\n{data}")).unwrap();
+        assert_eq!(config.send_delay_ms, SEND_DELAY_MAX_MS);
+    }
+
+    #[test]
+    fn test_statistics_defaults() {
+        // Config of the older version, without the statistics fields
+        let data = toml::to_string(&Config::default())
+            .unwrap()
+            .lines()
+            .filter(|line| !line.starts_with("statistics_"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let config = Config::parse(&data).unwrap();
+        assert!(config.statistics_persistence);
+        assert_eq!(config.statistics_save_interval, 60);
+    }
+
+    #[test]
+    fn test_flow_export_defaults() {
+        // Config of the older version, without the flow export fields
+        let data = toml::to_string(&Config::default())
+            .unwrap()
+            .lines()
+            .filter(|line| !line.starts_with("flow_export_"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let config = Config::parse(&data).unwrap();
+        assert!(!config.flow_export_enabled);
+        assert_eq!(config.flow_export_collector, "127.0.0.1:2055");
+        assert_eq!(config.flow_export_protocol, ExportProtocol::NetflowV5);
+        assert_eq!(config.flow_export_active_timeout, 1800);
+        assert_eq!(config.flow_export_inactive_timeout, 15);
+    }
+
+    #[test]
+    fn test_capture_stats_defaults() {
+        // Config of the older version, without the capture statistics interval
+        let data = toml::to_string(&Config::default())
+            .unwrap()
+            .lines()
+            .filter(|line| !line.starts_with("capture_stats_"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let config = Config::parse(&data).unwrap();
+        assert_eq!(config.capture_stats_interval, 5);
+    }
+
+    #[test]
+    fn test_session_token_defaults() {
+        // Config of the older version, without the session token lifetime
+        let data = toml::to_string(&Config::default())
+            .unwrap()
+            .lines()
+            .filter(|line| !line.starts_with("session_token_"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let config = Config::parse(&data).unwrap();
+        assert_eq!(config.session_token_ttl, 3600);
+    }
+
+    #[test]
+    fn test_sampling_defaults() {
+        // Config of the older version, without the sampling fields
+        let data = toml::to_string(&Config::default())
+            .unwrap()
+            .lines()
+            .filter(|line| !line.starts_with("sampling_"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let config = Config::parse(&data).unwrap();
+        assert_eq!(config.sampling_delay, 3);
+        assert_eq!(config.sampling_rate, 10);
+        assert_eq!(config.sampling_threshold, 5000);
+    }
+
+    #[test]
+    fn test_capture_options_defaults() {
+        // Config of the older version, without the capture options
+        let data = toml::to_string(&Config::default())
+            .unwrap()
+            .lines()
+            .filter(|line| {
+                ![
+                    "capture_buffer_",
+                    "capture_immediate_",
+                    "capture_promiscuous",
+                    "capture_snaplen",
+                ]
+                .iter()
+                .any(|prefix| line.starts_with(prefix))
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let config = Config::parse(&data).unwrap();
+        // Same as the capture before the options: only the immediate mode was set
+        assert_eq!(config.capture_buffer_size, 0);
+        assert!(config.capture_immediate_mode);
+        assert!(!config.capture_promiscuous);
+        assert_eq!(config.capture_snaplen, 65535);
+    }
+
+    #[test]
+    fn test_capture_idle_defaults() {
+        // Config of the older version, without the idle timeout
+        let data = toml::to_string(&Config::default())
+            .unwrap()
+            .lines()
+            .filter(|line| !line.starts_with("capture_idle_"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let config = Config::parse(&data).unwrap();
+        assert_eq!(config.capture_idle_timeout, 30);
+    }
+
+    #[test]
+    fn test_checksum_validation_defaults() {
+        // Config of the older version, without the checksum validation
+        let data = toml::to_string(&Config::default())
+            .unwrap()
+            .lines()
+            .filter(|line| !line.starts_with("checksum_"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let config = Config::parse(&data).unwrap();
+        assert!(!config.checksum_validation);
+    }
+}
@@ -1,23 +1,61 @@
-use crate::config::Config;
+use crate::config::{Config, ConfigError};
 use crate::net::interface;
 use crate::net::interface::InterfaceError;
-use common::cryptography::encrypt_password;
+use crate::net::mirror::MirrorSwitch;
+use crate::net::samples::FailureSamples;
+use crate::session::{Challenges, SessionTokens};
+use crate::statistics::Statistics;
+use common::messages::{
+    AddressChangeDto, CaptureOptionsDto, CaptureStatsDto, FlowExporterDto, MirrorDto,
+    PcapWriterDto,
+};
+use common::schedule::ScheduleStatus;
+use dpi::protocols::port_table::PortTable;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use thiserror::Error;
 
 pub struct Context {
-    pub compression: bool,
+    // Seconds without packets, when the capture is idle
+    pub capture_idle: Option<u64>,
+    // Options the running capture was opened with
+    pub capture_options: Option<CaptureOptionsDto>,
+    // Published by the sniffer, that pauses & resumes the capture
+    pub capture_schedule: ScheduleStatus,
+    pub capture_stats: Option<CaptureStatsDto>,
+    // Nonces of the password handshakes
+    pub challenges: Challenges,
     pub config: Config,
-    pub encrypted_password: String,
+    // Incremented on the password change. Connections, authorized before, are closed
+    pub credentials_revision: u64,
+    // Merged from the sniffer. Empty, if disabled in the config
+    pub failure_samples: FailureSamples,
+    pub flow_exporter: Option<FlowExporterDto>,
+    // Last change of the interface addresses, noticed by the sniffer
+    pub interface_address_change: Option<AddressChangeDto>,
+    // Incremented on every change, so connected clients are notified once
+    pub interface_address_revision: u64,
+    pub interface_switch: Option<pcap::Device>,
     pub link_type: Option<pcap::Linktype>,
+    // Status of the running mirror
+    pub mirror: Option<MirrorDto>,
+    // Start or stop of the mirror, taken by the sniffer
+    pub mirror_switch: Option<MirrorSwitch>,
     pub network_interface: Option<pcap::Device>,
+    pub pcap_writer: Option<PcapWriterDto>,
+    // Port table for the running parser, taken by the sniffer
+    pub protocol_ports_switch: Option<PortTable>,
     pub send_unparsed_frames: bool,
+    pub session_tokens: SessionTokens,
+    // Incremented when server settings are changed by the server itself,
+    // so connected clients receive the update.
+    pub settings_revision: u64,
+    // Counted by the sniffer, restored from the file at startup if persisted
+    pub statistics: Statistics,
 }
 
 impl Context {
     pub fn new(config: Config) -> Result<Self, ContextError> {
-        let encrypted_password = encrypt_password(&config.password);
-
         let interface: Option<pcap::Device> = match &config.interface {
             None => None,
             Some(interface_name) => {
@@ -33,11 +71,29 @@ impl Context {
         };
 
         Ok(Self {
-            compression: config.compression,
-            encrypted_password,
+            capture_idle: None,
+            capture_options: None,
+            capture_schedule: ScheduleStatus::default(),
+            capture_stats: None,
+            challenges: Challenges::default(),
+            credentials_revision: 0,
+            failure_samples: FailureSamples::default(),
+            flow_exporter: None,
+            interface_address_change: None,
+            interface_address_revision: 0,
+            interface_switch: None,
             link_type: None,
+            mirror: None,
+            mirror_switch: None,
             network_interface: interface,
+            pcap_writer: None,
+            protocol_ports_switch: None,
             send_unparsed_frames: config.send_unparsed_frames,
+            session_tokens: SessionTokens::new(Duration::from_secs(
+                config.session_token_ttl,
+            )),
+            settings_revision: 0,
+            statistics: Statistics::new(),
 
             config,
         })
@@ -48,9 +104,12 @@ impl Context {
         self.config.interface = Some(name);
     }
 
-    pub fn change_password(&mut self, new_password: String) {
-        self.encrypted_password = encrypt_password(&new_password);
-        self.config.password = new_password;
+    pub fn change_password(&mut self, new_password: &str) -> Result<(), ConfigError> {
+        self.config.set_password_hash(new_password)?;
+        self.session_tokens.revoke_all();
+        self.credentials_revision = self.credentials_revision.wrapping_add(1);
+
+        Ok(())
     }
 }
 
@@ -1,10 +1,13 @@
 use crate::config::Config;
 use crate::context;
 use crate::context::Context;
+use crate::metrics::{Metrics, MetricsEndpoint};
 use crate::net::PacketSnifferBuilder;
+use crate::statistics::{STATISTICS_FILENAME, Statistics};
 use crate::tcp::TcpHandlerBuilder;
 use common::channel::BroadcastPool;
 use dpi::dto::frame::FrameType;
+use std::path::Path;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex, RwLock};
 use std::thread;
@@ -17,10 +20,24 @@ pub fn start(config: Config) {
             std::process::exit(1);
         },
     }));
+    context::lock(&context, |ctx| {
+        if ctx.config.statistics_persistence {
+            ctx.statistics =
+                Statistics::new().with_persistence(Path::new(STATISTICS_FILENAME));
+        }
+    });
     let shutdown_flag = Arc::new(AtomicBool::new(false));
     let frame_channels_pool =
         Arc::new(RwLock::new(BroadcastPool::<FrameType>::default()));
     let ws_active_counter = Arc::new(AtomicUsize::new(0));
+    let metrics_address = context::lock(&context, |ctx| {
+        ctx.config
+            .metrics_enabled
+            .then(|| ctx.config.metrics_address.clone())
+    });
+    let metrics = metrics_address
+        .as_ref()
+        .map(|_| Arc::new(Metrics::new(Arc::clone(&ws_active_counter))));
 
     if let Err(err) = ctrlc::set_handler({
         let shutdown_flag = Arc::clone(&shutdown_flag);
@@ -43,6 +60,7 @@ pub fn start(config: Config) {
             let shutdown_flag = Arc::clone(&shutdown_flag);
             let frame_channels_pool = Arc::clone(&frame_channels_pool);
             let ws_active_counter = Arc::clone(&ws_active_counter);
+            let metrics = metrics.clone();
             Some(
                 thread::Builder::new()
                     .name("Network-Sniffing-Thread".to_owned())
@@ -51,6 +69,7 @@ pub fn start(config: Config) {
                         let result = PacketSnifferBuilder {
                             frame_channels_pool,
                             context,
+                            metrics,
                             shutdown_flag: shutdown_flag.clone(),
                             ws_active_counter,
                         }
@@ -76,6 +95,29 @@ pub fn start(config: Config) {
         }
     };
 
+    // Server works without the metrics, if the endpoint isn't started
+    let metrics_thread_handle = match (metrics_address, metrics) {
+        (Some(address), Some(metrics)) => Some(
+            thread::Builder::new()
+                .name("Metrics-Thread".to_owned())
+                .spawn({
+                    let shutdown_flag = Arc::clone(&shutdown_flag);
+                    move || match MetricsEndpoint::bind(&address, metrics) {
+                        Ok(endpoint) => {
+                            log::info!("Metrics: Serving on http://{address}/metrics");
+                            endpoint.serve(&shutdown_flag);
+                        },
+                        Err(err) => log::error!("Metrics Error: {err}"),
+                    }
+                })
+                .unwrap_or_else(|err| {
+                    log::error!("Failed to spawn metrics thread: {err}");
+                    std::process::exit(1);
+                }),
+        ),
+        _ => None,
+    };
+
     let tcp_thread_handle = thread::Builder::new()
         .name("TCP-Thread".to_owned())
         .spawn({
@@ -112,6 +154,15 @@ pub fn start(config: Config) {
     if tcp_thread_handle.join().is_err() {
         log::error!("Failed to join TCP listening thread!");
     }
+    if let Some(handle) = metrics_thread_handle {
+        if handle.join().is_err() {
+            log::error!("Failed to join metrics thread!");
+        }
+    }
+
+    if let Err(err) = context::lock(&context, |ctx| ctx.statistics.persist()) {
+        log::error!("Statistics: Failed to save. {err}");
+    }
 
     log::info!("Shutdown complete");
 }
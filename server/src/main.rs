@@ -1,7 +1,39 @@
-use crate::config::Config;
+use crate::cli::{Command, exit_code};
+use crate::config::{CONFIG_FILENAME, Config};
+use std::path::Path;
 
 fn main() {
-    let config = match Config::from_file() {
+    let command = Command::resolve().unwrap_or_else(|err| {
+        eprintln!("Error: {err}\n\n{}", cli::USAGE);
+        std::process::exit(err.exit_code());
+    });
+
+    let result = match command {
+        Command::Run => {
+            run();
+            Ok(())
+        },
+        Command::Interfaces => cli::print_interfaces(),
+        Command::CheckConfig => cli::check_config(Path::new(CONFIG_FILENAME)),
+        Command::SetPassword => cli::set_password(),
+        Command::Help => {
+            println!("{}", cli::USAGE);
+            Ok(())
+        },
+    };
+
+    if let Err(err) = result {
+        let mut message = format!("Error: {err}");
+        if let Some(additional_info) = err.additional_info() {
+            message.push_str(&format!(" Additional_info: {additional_info}"));
+        }
+        eprintln!("{message}");
+        std::process::exit(err.exit_code());
+    }
+}
+
+fn run() {
+    let mut config = match Config::from_file() {
         Ok(value) => value,
         Err(err) => {
             let mut message = format!("Config initialization failed. Error: {err}.");
@@ -9,7 +41,7 @@ fn main() {
                 message.push_str(&format!(" Additional_info: {additional_info}"));
             }
             eprintln!("{message}");
-            std::process::exit(1);
+            std::process::exit(exit_code::CONFIG_ERROR);
         },
     };
 
@@ -19,21 +51,47 @@ fn main() {
             message.push_str(&format!(" Additional_info: {additional_info}"));
         }
         eprintln!("Error: {message}");
-        std::process::exit(1);
+        std::process::exit(exit_code::RUNTIME_ERROR);
     });
 
     log::info!("Starting...");
+    // Before the config is logged, so the plaintext password isn't in the log
+    migrate_password(&mut config);
     log::info!("Config loaded: {config:#?}");
     log::info!("Logger initialized.");
+    if let Some(warning) = config.protocol_ports_warning() {
+        log::warn!("{warning}");
+    }
 
     core::start(config);
 }
 
+// Config of the older versions keeps the plaintext or SHA-256 password
+fn migrate_password(config: &mut Config) {
+    match config.migrate_password() {
+        Ok(true) => match config.save_to_file() {
+            Ok(()) => log::info!("Password is rehashed by Argon2id, config is saved."),
+            Err(err) => {
+                log::error!("Password is rehashed, but config isn't saved. {err}")
+            },
+        },
+        Ok(false) => {},
+        Err(err) => {
+            log::error!("Password migration failed. {err}");
+            std::process::exit(exit_code::CONFIG_ERROR);
+        },
+    }
+}
+
+mod cli;
 mod config;
 mod context;
 mod core;
 mod logging;
+mod metrics;
 mod net;
+mod session;
+mod statistics;
 mod request {
     pub mod commands;
     pub mod core;
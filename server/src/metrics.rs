@@ -0,0 +1,400 @@
+// Prometheus metrics of the server, served over HTTP in the text format.
+// Counters are the atomics, shared with the sniffer. They're created only if the
+// endpoint is enabled in the config, so nothing is counted otherwise.
+// Responder is hand-rolled: it answers only GET /metrics, one request per connection.
+
+use crate::statistics;
+use common::channel::BroadcastChannel;
+use common::messages::{CaptureStatsDto, ProtocolCountersDto};
+use dpi::dto::frame::FrameType;
+use std::fmt::Display;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+const PATH: &str = "/metrics";
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+// How often the listener is checked for the connections & the shutdown
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+// Slow or silent scrapers don't block the responder
+const READ_TIMEOUT: Duration = Duration::from_secs(2);
+// Headers of the request are skipped up to this amount of lines
+const MAX_HEADER_LINES: usize = 100;
+
+pub struct Metrics {
+    started: Instant,
+    frames_processed: AtomicU64,
+    frames_parsed: AtomicU64,
+    frames_failed: AtomicU64,
+    bytes: AtomicU64,
+    // Counters of the running capture, they start over when it's reopened
+    pcap_dropped: AtomicU64,
+    pcap_if_dropped: AtomicU64,
+    ws_queue_depth: AtomicUsize,
+    ws_active_counter: Arc<AtomicUsize>,
+    protocols: Mutex<Vec<ProtocolCountersDto>>,
+}
+
+impl Metrics {
+    pub fn new(ws_active_counter: Arc<AtomicUsize>) -> Self {
+        Self {
+            started: Instant::now(),
+            frames_processed: AtomicU64::new(0),
+            frames_parsed: AtomicU64::new(0),
+            frames_failed: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+            pcap_dropped: AtomicU64::new(0),
+            pcap_if_dropped: AtomicU64::new(0),
+            ws_queue_depth: AtomicUsize::new(0),
+            ws_active_counter,
+            protocols: Mutex::new(vec![]),
+        }
+    }
+
+    /// Counts the frame, that came out of the parser.
+    /// <br> Frame is failed, if any of its layers isn't parsed.
+    pub fn observe(&self, frame: &FrameType, is_failed: bool) {
+        let header = match frame {
+            FrameType::Metadata(metadata) => &metadata.header,
+            FrameType::Header(header) => header,
+            FrameType::Raw(frame) => &frame.header,
+        };
+        let bytes = u64::from(header.len);
+
+        self.frames_processed.fetch_add(1, Ordering::Relaxed);
+        match is_failed {
+            true => self.frames_failed.fetch_add(1, Ordering::Relaxed),
+            false => self.frames_parsed.fetch_add(1, Ordering::Relaxed),
+        };
+        self.bytes.fetch_add(bytes, Ordering::Relaxed);
+
+        let mut protocols = self
+            .protocols
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        statistics::count_protocols(&mut protocols, frame, bytes);
+    }
+
+    pub fn set_drops(&self, stats: &CaptureStatsDto) {
+        self.pcap_dropped
+            .store(u64::from(stats.dropped), Ordering::Relaxed);
+        self.pcap_if_dropped
+            .store(u64::from(stats.if_dropped), Ordering::Relaxed);
+    }
+
+    pub fn set_queue_depth(&self, channel: &BroadcastChannel<FrameType>) {
+        self.ws_queue_depth
+            .store(channel.queued(), Ordering::Relaxed);
+    }
+
+    /// Metrics in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let mut text = String::new();
+        let load = |counter: &AtomicU64| counter.load(Ordering::Relaxed);
+
+        metric(
+            &mut text,
+            "xailyser_frames_processed_total",
+            "counter",
+            "Frames, that came out of the parser.",
+            load(&self.frames_processed),
+        );
+        metric(
+            &mut text,
+            "xailyser_frames_parsed_total",
+            "counter",
+            "Frames, which layers are parsed without the failures.",
+            load(&self.frames_parsed),
+        );
+        metric(
+            &mut text,
+            "xailyser_frames_failed_total",
+            "counter",
+            "Frames, which layer isn't parsed.",
+            load(&self.frames_failed),
+        );
+        metric(
+            &mut text,
+            "xailyser_bytes_total",
+            "counter",
+            "Bytes of the frames on the wire.",
+            load(&self.bytes),
+        );
+        metric(
+            &mut text,
+            "xailyser_pcap_dropped_total",
+            "counter",
+            "Packets, dropped by the capture buffer. Starts over, when the capture is reopened.",
+            load(&self.pcap_dropped),
+        );
+        metric(
+            &mut text,
+            "xailyser_pcap_if_dropped_total",
+            "counter",
+            "Packets, dropped by the interface. Starts over, when the capture is reopened.",
+            load(&self.pcap_if_dropped),
+        );
+        metric(
+            &mut text,
+            "xailyser_clients_connected",
+            "gauge",
+            "Connected clients.",
+            self.ws_active_counter.load(Ordering::Acquire),
+        );
+        metric(
+            &mut text,
+            "xailyser_ws_queue_depth",
+            "gauge",
+            "Frames, that aren't sent yet to the slowest client.",
+            self.ws_queue_depth.load(Ordering::Relaxed),
+        );
+        metric(
+            &mut text,
+            "xailyser_uptime_seconds",
+            "gauge",
+            "Seconds since the server is started.",
+            self.started.elapsed().as_secs(),
+        );
+
+        let name = "xailyser_protocol_frames_total";
+        header(
+            &mut text,
+            name,
+            "counter",
+            "Frames, that contain the protocol.",
+        );
+        let protocols = self
+            .protocols
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        for counters in protocols.iter() {
+            text.push_str(&format!(
+                "{name}{{protocol=\"{}\"}} {}\n",
+                counters.protocol, counters.frames
+            ));
+        }
+
+        text
+    }
+}
+
+fn header(text: &mut String, name: &str, kind: &str, help: &str) {
+    text.push_str(&format!("# HELP {name} {help}\n# TYPE {name} {kind}\n"));
+}
+
+fn metric(text: &mut String, name: &str, kind: &str, help: &str, value: impl Display) {
+    header(text, name, kind, help);
+    text.push_str(&format!("{name} {value}\n"));
+}
+
+pub struct MetricsEndpoint {
+    listener: TcpListener,
+    metrics: Arc<Metrics>,
+}
+
+impl MetricsEndpoint {
+    pub fn bind(address: &str, metrics: Arc<Metrics>) -> Result<Self, MetricsError> {
+        let address = address
+            .to_socket_addrs()
+            .map_err(MetricsError::InvalidAddress)?
+            .next()
+            .ok_or(MetricsError::AddressNotResolved)?;
+        let listener = TcpListener::bind(address).map_err(MetricsError::BindError)?;
+        listener
+            .set_nonblocking(true)
+            .map_err(MetricsError::FailedSetNonBlocking)?;
+
+        Ok(Self { listener, metrics })
+    }
+
+    pub fn local_address(&self) -> Result<SocketAddr, MetricsError> {
+        self.listener.local_addr().map_err(MetricsError::BindError)
+    }
+
+    pub fn serve(&self, shutdown_flag: &AtomicBool) {
+        loop {
+            if shutdown_flag.load(Ordering::Acquire) {
+                log::info!("Shutting down metrics thread.");
+                break;
+            }
+
+            match self.listener.accept() {
+                Ok((stream, _)) => {
+                    if let Err(err) = self.respond(stream) {
+                        log::warn!("Metrics: Failed to respond. {err}");
+                    }
+                },
+                Err(ref err) if err.kind() == std::io::ErrorKind::WouldBlock => {
+                    thread::sleep(POLL_INTERVAL);
+                },
+                Err(err) => log::warn!("Metrics: Connection failed. {err}"),
+            }
+        }
+    }
+
+    fn respond(&self, stream: TcpStream) -> std::io::Result<()> {
+        // Accepted stream may inherit the mode of the listener
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+
+        let mut reader = BufReader::new(&stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+        // Headers are read out, so the connection isn't reset on close
+        let mut line = String::new();
+        for _ in 0..MAX_HEADER_LINES {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+                break;
+            }
+        }
+
+        let mut parts = request_line.split_whitespace();
+        let (status, body) = match (parts.next(), parts.next()) {
+            (Some("GET"), Some(PATH)) => ("200 OK", self.metrics.render()),
+            (Some("GET"), _) => ("404 Not Found", String::from("Not Found\n")),
+            _ => (
+                "405 Method Not Allowed",
+                String::from("Method Not Allowed\n"),
+            ),
+        };
+        let response = format!(
+            "HTTP/1.1 {status}\r\nContent-Type: {CONTENT_TYPE}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+
+        let mut stream = &stream;
+        stream.write_all(response.as_bytes())?;
+        stream.flush()
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum MetricsError {
+    #[error("Invalid address. {0}")]
+    InvalidAddress(std::io::Error),
+
+    #[error("Address is not resolved.")]
+    AddressNotResolved,
+
+    #[error("Failed to bind address. {0}")]
+    BindError(std::io::Error),
+
+    #[error("Failed to set nonblocking mode. {0}")]
+    FailedSetNonBlocking(std::io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dpi::dto::frame::{FrameHeader, LinkType};
+    use dpi::parser::ProtocolParser;
+    use std::io::Read;
+
+    // Ethernet + IPv4 + UDP + DNS query
+    const DNS_FRAME: &str = "01 00 5E 00 00 FC 40 61 86 9A F1 F5 08 00 45 00 00 32 6A 3D 00 00 01 11 AA 56 C0 A8 03 83 E0 00 00 FC D5 48 00 35 00 1E 20 88 76 F2 00 00 00 01 00 00 00 00 00 00 04 77 70 61 64 00 00 01 00 01";
+    // Ethernet + the cut off IPv4 header
+    const BROKEN_FRAME: &str = "01 00 5E 00 00 FC 40 61 86 9A F1 F5 08 00 46 00";
+
+    fn decode(hex: &str) -> Vec<u8> {
+        let hex = hex.replace(' ', "");
+        (0..hex.len())
+            .step_by(2)
+            .map(|index| u8::from_str_radix(&hex[index..index + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn feed(metrics: &Metrics, hex: &str) {
+        let data = decode(hex);
+        let header = FrameHeader::new(data.len());
+        let parser = ProtocolParser::new(LinkType::ETHERNET, false);
+        let (frame, failure) = parser.process_bytes_traced(&header, &data).unwrap();
+        metrics.observe(&frame, failure.is_some());
+    }
+
+    fn scrape(address: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(stream, "GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    fn value(response: &str, name: &str) -> u64 {
+        response
+            .lines()
+            .find_map(|line| line.strip_prefix(&format!("{name} ")))
+            .unwrap()
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_scrape() {
+        let clients = Arc::new(AtomicUsize::new(2));
+        let metrics = Arc::new(Metrics::new(Arc::clone(&clients)));
+        let endpoint =
+            MetricsEndpoint::bind("127.0.0.1:0", Arc::clone(&metrics)).unwrap();
+        let address = endpoint.local_address().unwrap();
+        let shutdown_flag = Arc::new(AtomicBool::new(false));
+        let handle = thread::spawn({
+            let shutdown_flag = Arc::clone(&shutdown_flag);
+            move || endpoint.serve(&shutdown_flag)
+        });
+
+        feed(&metrics, DNS_FRAME);
+        feed(&metrics, BROKEN_FRAME);
+        let first = scrape(address, PATH);
+        assert!(first.starts_with("HTTP/1.1 200 OK"));
+        for name in [
+            "xailyser_frames_processed_total",
+            "xailyser_frames_parsed_total",
+            "xailyser_frames_failed_total",
+            "xailyser_bytes_total",
+            "xailyser_pcap_dropped_total",
+            "xailyser_pcap_if_dropped_total",
+            "xailyser_ws_queue_depth",
+            "xailyser_uptime_seconds",
+        ] {
+            assert!(first.contains(&format!("# TYPE {name} ")), "{name}");
+        }
+        assert_eq!(value(&first, "xailyser_frames_processed_total"), 2);
+        assert_eq!(value(&first, "xailyser_frames_parsed_total"), 1);
+        assert_eq!(value(&first, "xailyser_frames_failed_total"), 1);
+        assert_eq!(value(&first, "xailyser_clients_connected"), 2);
+        assert_eq!(
+            value(&first, "xailyser_protocol_frames_total{protocol=\"DNS\"}"),
+            1
+        );
+
+        for _ in 0..3 {
+            feed(&metrics, DNS_FRAME);
+        }
+        metrics.set_drops(&CaptureStatsDto {
+            received: 10,
+            dropped: 4,
+            if_dropped: 1,
+        });
+        let second = scrape(address, PATH);
+        // Counters only grow
+        for name in [
+            "xailyser_frames_processed_total",
+            "xailyser_frames_parsed_total",
+            "xailyser_bytes_total",
+            "xailyser_protocol_frames_total{protocol=\"Ethernet\"}",
+        ] {
+            assert!(value(&second, name) > value(&first, name), "{name}");
+        }
+        assert_eq!(value(&second, "xailyser_frames_failed_total"), 1);
+        assert_eq!(value(&second, "xailyser_pcap_dropped_total"), 4);
+
+        assert!(scrape(address, "/").starts_with("HTTP/1.1 404"));
+
+        shutdown_flag.store(true, Ordering::Release);
+        handle.join().unwrap();
+    }
+}
@@ -1,46 +1,148 @@
 use crate::context;
 use crate::context::Context;
+use crate::metrics::Metrics;
+use crate::net::direction::LocalAddresses;
+use crate::net::flow::FlowExporter;
+use crate::net::idle::IdleDetector;
 use crate::net::interface::InterfaceError;
+use crate::net::mirror::{Mirror, MirrorSwitch};
+use crate::net::pcap_writer::{PcapWriter, RotationSettings};
+use crate::net::samples::FailureSamples;
+use crate::statistics::Totals;
 use common::channel::{BroadcastChannel, BroadcastPool};
-use dpi::dto::frame::FrameType;
+use common::messages::{AddressChangeDto, CaptureStatsDto};
+use common::schedule;
+use dpi::dto::fields;
+use dpi::dto::frame::{FrameHeader, FrameType, OwnedFrame};
 use dpi::parser::ProtocolParser;
 use pcap::{Active, Capture};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex, RwLock};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use thiserror::Error;
 
 const TIMEOUT_MS: i32 = 10;
+// How often the switch requests (interface, protocol ports, mirror) & the schedule
+// are checked.
+const SWITCH_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+// How often the addresses of the interface are compared, e.g. to notice the DHCP renew.
+const ADDRESS_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+// How often the counted statistics are added to the context.
+const STATISTICS_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
 
 pub struct PacketSniffer {
-    capture: Capture<Active>,
+    address_check_last: Instant,
+    // None, while the capture is paused by the schedule
+    capture: Option<Capture<Active>>,
+    capture_stats_interval: Duration,
+    capture_stats_last: Instant,
+    context: Arc<Mutex<Context>>,
+    // Found since the last flush. None, if the samples are disabled
+    failure_samples: Option<FailureSamples>,
+    // Generation of the context samples, the search was started at
+    failure_samples_generation: u64,
+    flow_exporter: Option<FlowExporter>,
     frame_channel: BroadcastChannel<FrameType>,
     frame_channels_pool: Arc<RwLock<BroadcastPool<FrameType>>>,
+    idle_detector: IdleDetector,
+    link_type: pcap::Linktype,
+    local_addresses: LocalAddresses,
+    // None, if the metrics endpoint is disabled
+    metrics: Option<Arc<Metrics>>,
+    // None, if nothing is mirrored
+    mirror: Option<Mirror>,
     parser: ProtocolParser,
+    // None, if the capture isn't written to the disk
+    pcap_writer: Option<PcapWriter>,
     shutdown_flag: Arc<AtomicBool>,
+    // Counted since the last flush, so the context isn't locked on every frame
+    statistics: Totals,
+    statistics_flush_last: Instant,
+    // Generation of the context statistics, the counts were started at
+    statistics_generation: u64,
+    statistics_save_interval: Duration,
+    statistics_save_last: Instant,
+    switch_check_last: Instant,
     ws_active_counter: Arc<AtomicUsize>,
 }
 
 impl PacketSniffer {
     pub fn listen(&mut self) -> Result<(), NetworkError> {
+        self.update_schedule();
         loop {
             if self.shutdown_flag.load(Ordering::Acquire) {
                 log::info!("Shutting down net-capturing thread.");
+                self.flush_flows();
+                self.flush_pcap_writer();
+                self.flush_statistics();
                 break;
             }
 
-            if self.ws_active_counter.load(Ordering::Acquire) > 0 {
+            self.apply_switches();
+            self.watch_addresses();
+            self.export_flows();
+            self.update_pcap_writer();
+            self.update_mirror();
+            self.poll_capture_stats();
+            self.update_statistics();
+
+            let clients_connected = self.ws_active_counter.load(Ordering::Acquire) > 0;
+            let is_consumed = clients_connected
+                || self.flow_exporter.is_some()
+                || self.pcap_writer.is_some()
+                || self.mirror.is_some();
+            if is_consumed && self.capture.is_some() {
                 self.synchronize_frame_senders();
-                match self.capture.next_packet() {
+                let Some(capture) = &mut self.capture else {
+                    continue;
+                };
+                match capture.next_packet() {
                     Ok(packet) => {
-                        let frame = match self.parser.process(packet) {
-                            Some(frame) => frame,
-                            None => continue,
+                        let now = Instant::now();
+                        if let Some(writer) = &mut self.pcap_writer {
+                            writer.write(&packet, now);
+                        }
+                        if let Some(mirror) = &mut self.mirror {
+                            mirror.mirror(&packet, now);
+                        }
+                        let header = FrameHeader::from(packet.header);
+                        let traced =
+                            self.parser.process_bytes_traced(&header, packet.data);
+                        if let Some((_, Some(failure))) = &traced {
+                            if let Some(samples) = &mut self.failure_samples {
+                                samples.record(failure, || OwnedFrame {
+                                    header,
+                                    data: packet.data.to_vec(),
+                                });
+                            }
+                        }
+                        self.mark_active();
+                        let Some((mut frame, failure)) = traced else {
+                            continue;
                         };
-                        self.frame_channel.send(frame);
+                        if let Some(failure) = &failure {
+                            log::debug!("Parser: Layer isn't parsed. {failure}");
+                        }
+                        if let FrameType::Metadata(metadata) = &mut frame {
+                            self.local_addresses.annotate(metadata);
+                        }
+                        if let Some(exporter) = &mut self.flow_exporter {
+                            exporter.observe(&frame);
+                        }
+                        self.statistics.count(&frame);
+                        if let Some(metrics) = &self.metrics {
+                            metrics.observe(&frame, failure.is_some());
+                        }
+                        if clients_connected {
+                            self.frame_channel.send(frame);
+                        }
+                        if let Some(metrics) = &self.metrics {
+                            metrics.set_queue_depth(&self.frame_channel);
+                        }
                     },
                     Err(pcap::Error::TimeoutExpired) => {
+                        self.detect_idle();
                         thread::sleep(Duration::from_millis(TIMEOUT_MS as u64));
                     },
                     Err(err) => {
@@ -48,6 +150,8 @@ impl PacketSniffer {
                     },
                 }
             } else {
+                // Capture is paused, it's not idle
+                self.reset_idle();
                 thread::sleep(Duration::from_millis(TIMEOUT_MS as u64));
             }
         }
@@ -55,6 +159,392 @@ impl PacketSniffer {
         Ok(())
     }
 
+    fn apply_switches(&mut self) {
+        if self.switch_check_last.elapsed() < SWITCH_CHECK_INTERVAL {
+            return;
+        }
+        self.switch_check_last = Instant::now();
+
+        self.switch_protocol_ports();
+        self.switch_interface();
+        self.switch_mirror();
+        self.update_schedule();
+    }
+
+    fn switch_protocol_ports(&mut self) {
+        let Some(ports) =
+            context::lock(&self.context, |ctx| ctx.protocol_ports_switch.take())
+        else {
+            return;
+        };
+        self.parser.set_protocol_ports(ports);
+        log::info!("Protocol Ports: Port table is updated.");
+    }
+
+    // Capture is reopened on the new interface, and parser is rebuilt for its link type.
+    fn switch_interface(&mut self) {
+        let Some(device) =
+            context::lock(&self.context, |ctx| ctx.interface_switch.take())
+        else {
+            return;
+        };
+        let name = interface::get_network_interface_name(&device);
+
+        // Options changed since the previous open are applied there
+        let options = context::lock(&self.context, |ctx| ctx.config.capture_options());
+        let capture = match interface::get_capture(device.clone(), TIMEOUT_MS, &options) {
+            Ok(capture) => capture,
+            Err(err) => {
+                log::error!("Interface Switch: Failed to open capture on {name}. {err}");
+                return;
+            },
+        };
+        let link_type = capture.get_datalink();
+        if let Err(err) = self.parser.set_link_type(link_type) {
+            log::error!("Interface Switch: {name}. {err}");
+            return;
+        }
+        // Capture, that is paused by the schedule, is closed on the next check
+        self.capture = Some(capture);
+        self.link_type = link_type;
+        if let Some(writer) = &mut self.pcap_writer {
+            writer.set_link_type(link_type);
+        }
+        if let Some(mirror) = &mut self.mirror {
+            mirror.set_link_type(link_type);
+        }
+        self.local_addresses = LocalAddresses::from_device(&device);
+        self.idle_detector.reset(Instant::now());
+
+        context::lock(&self.context, |ctx| {
+            ctx.capture_idle = None;
+            ctx.network_interface = Some(device);
+            ctx.link_type = Some(link_type);
+            ctx.capture_options = Some(options);
+            ctx.capture_stats = None;
+            ctx.statistics.capture_reopened();
+            // Samples of the other link type can't be saved into the same file
+            ctx.failure_samples.reset();
+            ctx.settings_revision = ctx.settings_revision.wrapping_add(1);
+        });
+        if let Some(samples) = &mut self.failure_samples {
+            *samples = FailureSamples::default();
+        }
+        log::info!(
+            "Interface Switch: Capturing on {name}, link type {}.",
+            link_type.0
+        );
+    }
+
+    // Direction is resolved by the refreshed addresses. Clients get the change & settings
+    fn watch_addresses(&mut self) {
+        if self.address_check_last.elapsed() < ADDRESS_CHECK_INTERVAL {
+            return;
+        }
+        self.address_check_last = Instant::now();
+
+        let Some(device) =
+            context::lock(&self.context, |ctx| ctx.network_interface.clone())
+        else {
+            return;
+        };
+        let current = match interface::refreshed(&device) {
+            Ok(Some(current)) => current,
+            Ok(None) => return,
+            Err(err) => {
+                log::warn!("Address Watch: Failed to list the interfaces. {err}");
+                return;
+            },
+        };
+        let addresses = LocalAddresses::from_device(&current);
+        let Some(change) = self.local_addresses.changes(&addresses) else {
+            return;
+        };
+        self.local_addresses = addresses;
+
+        let name = interface::get_network_interface_name(&current);
+        log::warn!(
+            "Address Watch: Addresses of {name} are changed from {} to {}.",
+            fields::list(&change.removed),
+            fields::list(&change.added)
+        );
+        let dto = AddressChangeDto {
+            interface: name,
+            removed: change.removed,
+            added: change.added,
+        };
+        context::lock(&self.context, |ctx| {
+            ctx.network_interface = Some(current);
+            ctx.interface_address_change = Some(dto);
+            ctx.interface_address_revision =
+                ctx.interface_address_revision.wrapping_add(1);
+            ctx.settings_revision = ctx.settings_revision.wrapping_add(1);
+        });
+    }
+
+    // Start & stop of the mirror are pushed to the clients at once
+    fn switch_mirror(&mut self) {
+        let Some(switch) = context::lock(&self.context, |ctx| ctx.mirror_switch.take())
+        else {
+            return;
+        };
+
+        self.mirror = match switch {
+            MirrorSwitch::Start { target, filter } => {
+                let link_type = self.link_type;
+                if link_type != pcap::Linktype::ETHERNET {
+                    log::warn!(
+                        "Mirror: Addresses of the link type {} aren't matched.",
+                        link_type.0
+                    );
+                }
+                let description = format!("{filter} to {target}");
+                let config = context::lock(&self.context, |ctx| ctx.config.clone());
+                match Mirror::new(target, filter, &config, link_type) {
+                    Ok(mirror) => {
+                        log::info!("Mirror: Mirroring {description}.");
+                        Some(mirror)
+                    },
+                    Err(err) => {
+                        log::error!(
+                            "Mirror: Failed to start mirroring {description}. {err}"
+                        );
+                        None
+                    },
+                }
+            },
+            MirrorSwitch::Stop => {
+                if self.mirror.is_some() {
+                    log::info!("Mirror: Mirroring is stopped.");
+                }
+                None
+            },
+        };
+
+        let status = self.mirror.as_ref().map(Mirror::status);
+        context::lock(&self.context, |ctx| {
+            ctx.mirror = status;
+            ctx.settings_revision = ctx.settings_revision.wrapping_add(1);
+        });
+    }
+
+    // Outside the windows the capture is closed, and it's reopened at their start.
+    // Status is recomputed from the wall clock, so the clock changes are followed
+    fn update_schedule(&mut self) {
+        let windows =
+            context::lock(&self.context, |ctx| ctx.config.capture_schedule.clone());
+        let status = schedule::status(&windows, chrono::Utc::now());
+
+        match (status.is_active, self.capture.is_some()) {
+            (true, false) => self.resume_capture(),
+            (false, true) => {
+                self.capture = None;
+                self.reset_idle();
+                let until = status
+                    .next_change
+                    .map(|time| time.with_timezone(&chrono::Local).to_string())
+                    .unwrap_or_default();
+                log::info!("Schedule: Capture is paused until {until}.");
+            },
+            _ => {},
+        }
+
+        context::lock(&self.context, |ctx| ctx.capture_schedule = status);
+    }
+
+    fn resume_capture(&mut self) {
+        let (device, options) = context::lock(&self.context, |ctx| {
+            (ctx.network_interface.clone(), ctx.config.capture_options())
+        });
+        let Some(device) = device else {
+            return;
+        };
+        let name = interface::get_network_interface_name(&device);
+
+        match interface::get_capture(device, TIMEOUT_MS, &options) {
+            Ok(capture) => {
+                self.capture = Some(capture);
+                self.idle_detector.reset(Instant::now());
+                context::lock(&self.context, |ctx| {
+                    ctx.capture_options = Some(options);
+                    ctx.capture_stats = None;
+                    ctx.statistics.capture_reopened();
+                });
+                log::info!("Schedule: Capture is resumed on {name}.");
+            },
+            Err(err) => log::error!("Schedule: Failed to open capture on {name}. {err}"),
+        }
+    }
+
+    // Kernel drops are invisible in the parsed traffic, so they're polled separately.
+    fn poll_capture_stats(&mut self) {
+        if self.capture_stats_interval.is_zero()
+            || self.capture_stats_last.elapsed() < self.capture_stats_interval
+        {
+            return;
+        }
+        self.capture_stats_last = Instant::now();
+        let Some(capture) = &mut self.capture else {
+            return;
+        };
+
+        match capture.stats() {
+            Ok(stats) => {
+                let dto = CaptureStatsDto {
+                    received: stats.received,
+                    dropped: stats.dropped,
+                    if_dropped: stats.if_dropped,
+                };
+                context::lock(&self.context, |ctx| {
+                    ctx.capture_stats = Some(dto);
+                    ctx.statistics.count_drops(&dto);
+                });
+                if let Some(metrics) = &self.metrics {
+                    metrics.set_drops(&dto);
+                }
+            },
+            Err(err) => log::warn!("Capture Stats: {err}"),
+        }
+    }
+
+    fn flush_statistics(&mut self) {
+        let counted = std::mem::take(&mut self.statistics);
+        self.statistics_generation = context::lock(&self.context, |ctx| {
+            ctx.statistics.merge(&counted, self.statistics_generation);
+            ctx.statistics.generation()
+        });
+
+        let Some(samples) = &mut self.failure_samples else {
+            return;
+        };
+        let found = std::mem::take(samples);
+        self.failure_samples_generation = context::lock(&self.context, |ctx| {
+            ctx.failure_samples
+                .merge(found, self.failure_samples_generation);
+            ctx.failure_samples.generation()
+        });
+    }
+
+    // File is written under the lock, so the reset can't be overwritten by older totals.
+    fn update_statistics(&mut self) {
+        if self.statistics_flush_last.elapsed() >= STATISTICS_FLUSH_INTERVAL {
+            self.statistics_flush_last = Instant::now();
+            self.flush_statistics();
+        }
+        if self.statistics_save_interval.is_zero()
+            || self.statistics_save_last.elapsed() < self.statistics_save_interval
+        {
+            return;
+        }
+        self.statistics_save_last = Instant::now();
+
+        if let Err(err) = context::lock(&self.context, |ctx| ctx.statistics.persist()) {
+            log::warn!("Statistics: Failed to save. {err}");
+        }
+    }
+
+    fn detect_idle(&mut self) {
+        let Some(seconds) = self.idle_detector.check(Instant::now()) else {
+            return;
+        };
+
+        let name = context::lock(&self.context, |ctx| {
+            ctx.capture_idle = Some(seconds);
+            ctx.network_interface
+                .as_ref()
+                .map(interface::get_network_interface_name)
+        });
+        log::warn!(
+            "Capture: No packets on {} for {seconds} s. Check the interface.",
+            name.unwrap_or_default()
+        );
+    }
+
+    fn mark_active(&mut self) {
+        if self.idle_detector.on_packet(Instant::now()) {
+            log::info!("Capture: Packets are received again.");
+            context::lock(&self.context, |ctx| ctx.capture_idle = None);
+        }
+    }
+
+    fn reset_idle(&mut self) {
+        if self.idle_detector.reset(Instant::now()) {
+            context::lock(&self.context, |ctx| ctx.capture_idle = None);
+        }
+    }
+
+    fn export_flows(&mut self) {
+        let Some(exporter) = &mut self.flow_exporter else {
+            return;
+        };
+        match exporter.tick() {
+            Ok(true) => {
+                let status = exporter.status();
+                context::lock(&self.context, |ctx| ctx.flow_exporter = Some(status));
+            },
+            Ok(false) => {},
+            Err(err) => log::warn!("Flow Export: {err}"),
+        }
+    }
+
+    fn flush_flows(&mut self) {
+        if let Some(exporter) = &mut self.flow_exporter {
+            if let Err(err) = exporter.flush() {
+                log::warn!("Flow Export: {err}");
+            }
+        }
+    }
+
+    // Failures & recoveries of the writing are pushed to the clients at once
+    fn update_pcap_writer(&mut self) {
+        let Some(writer) = &mut self.pcap_writer else {
+            return;
+        };
+        if !writer.tick(Instant::now()) {
+            return;
+        }
+
+        let status = writer.status();
+        context::lock(&self.context, |ctx| {
+            let was_failed = ctx
+                .pcap_writer
+                .as_ref()
+                .is_some_and(|writer| writer.error.is_some());
+            if was_failed != status.error.is_some() {
+                ctx.settings_revision = ctx.settings_revision.wrapping_add(1);
+            }
+            ctx.pcap_writer = Some(status);
+        });
+    }
+
+    // Failures & recoveries of the sending are pushed to the clients at once
+    fn update_mirror(&mut self) {
+        let Some(mirror) = &mut self.mirror else {
+            return;
+        };
+        if !mirror.tick(Instant::now()) {
+            return;
+        }
+
+        let status = mirror.status();
+        context::lock(&self.context, |ctx| {
+            let was_failed = ctx
+                .mirror
+                .as_ref()
+                .is_some_and(|mirror| mirror.error.is_some());
+            if was_failed != status.error.is_some() {
+                ctx.settings_revision = ctx.settings_revision.wrapping_add(1);
+            }
+            ctx.mirror = Some(status);
+        });
+    }
+
+    fn flush_pcap_writer(&mut self) {
+        if let Some(writer) = &mut self.pcap_writer {
+            writer.flush();
+        }
+    }
+
     fn synchronize_frame_senders(&mut self) {
         let mut sender_ready = false;
         if let Ok(frame_pool) = self.frame_channels_pool.try_read() {
@@ -72,7 +562,14 @@ impl PacketSniffer {
     }
 }
 
+pub mod direction;
+pub mod flow;
+pub mod idle;
 pub mod interface;
+pub mod link;
+pub mod mirror;
+pub mod pcap_writer;
+pub mod samples;
 
 #[derive(Debug, Error)]
 pub enum NetworkError {
@@ -89,6 +586,7 @@ pub enum NetworkError {
 pub struct PacketSnifferBuilder {
     pub frame_channels_pool: Arc<RwLock<BroadcastPool<FrameType>>>,
     pub context: Arc<Mutex<Context>>,
+    pub metrics: Option<Arc<Metrics>>,
     pub shutdown_flag: Arc<AtomicBool>,
     pub ws_active_counter: Arc<AtomicUsize>,
 }
@@ -97,24 +595,104 @@ impl PacketSnifferBuilder {
     pub fn build(self) -> Result<PacketSniffer, NetworkError> {
         let interface = context::lock(&self.context, |ctx| ctx.network_interface.clone())
             .ok_or(NetworkError::NoInterface)?;
-        let capture = interface::get_capture(interface, TIMEOUT_MS)
+        let options = context::lock(&self.context, |ctx| ctx.config.capture_options());
+        let local_addresses = LocalAddresses::from_device(&interface);
+        let capture = interface::get_capture(interface, TIMEOUT_MS, &options)
             .map_err(NetworkError::InterfaceError)?;
 
         let link_type = capture.get_datalink();
         context::lock(&self.context, |ctx| {
             ctx.link_type = Some(link_type);
+            ctx.capture_options = Some(options);
+            // Mirror of the previous capture isn't restored
+            ctx.mirror = None;
         });
 
         let send_unparsed_frames =
             context::lock(&self.context, |ctx| ctx.send_unparsed_frames);
-        let parser = ProtocolParser::new(&link_type, send_unparsed_frames);
+        let config = context::lock(&self.context, |ctx| ctx.config.clone());
+        let parser = ProtocolParser::new(link_type, send_unparsed_frames)
+            .with_checksum_validation(config.checksum_validation)
+            .with_protocol_ports(config.protocol_ports.clone())
+            .with_http_body_limit(config.http_body_limit());
+
+        let flow_exporter = if config.flow_export_enabled {
+            // Exporter is optional, the capture goes on without it
+            match FlowExporter::new(&config) {
+                Ok(exporter) => {
+                    log::info!(
+                        "Flow Export: Exporting flows ({}) to {}.",
+                        config.flow_export_protocol,
+                        config.flow_export_collector
+                    );
+                    let status = exporter.status();
+                    context::lock(&self.context, |ctx| ctx.flow_exporter = Some(status));
+                    Some(exporter)
+                },
+                Err(err) => {
+                    log::warn!(
+                        "Flow Export: Failed to export flows to {}, it's disabled. {err}",
+                        config.flow_export_collector
+                    );
+                    None
+                },
+            }
+        } else {
+            None
+        };
+
+        let pcap_writer = if config.pcap_writer_enabled {
+            let writer =
+                PcapWriter::new(RotationSettings::from_config(&config), link_type);
+            log::info!(
+                "Pcap Writer: Writing the capture to {}.",
+                config.pcap_writer_directory
+            );
+            let status = writer.status();
+            context::lock(&self.context, |ctx| ctx.pcap_writer = Some(status));
+            Some(writer)
+        } else {
+            None
+        };
 
+        // Counts of the sniffer are merged into the context of the same generation
+        let (statistics_generation, failure_samples_generation) =
+            context::lock(&self.context, |ctx| {
+                (
+                    ctx.statistics.generation(),
+                    ctx.failure_samples.generation(),
+                )
+            });
         let sniffer = PacketSniffer {
-            capture,
+            address_check_last: Instant::now(),
+            capture: Some(capture),
+            capture_stats_interval: Duration::from_secs(config.capture_stats_interval),
+            capture_stats_last: Instant::now(),
+            context: self.context,
+            failure_samples: config.failure_samples_enabled.then(FailureSamples::default),
+            failure_samples_generation,
+            flow_exporter,
             frame_channel: BroadcastChannel::<FrameType>::new(),
             frame_channels_pool: self.frame_channels_pool,
+            idle_detector: IdleDetector::new(
+                Duration::from_secs(config.capture_idle_timeout),
+                Instant::now(),
+            ),
+            link_type,
+            local_addresses,
+            metrics: self.metrics,
+            mirror: None,
             parser,
+            pcap_writer,
             shutdown_flag: self.shutdown_flag,
+            statistics: Totals::default(),
+            statistics_flush_last: Instant::now(),
+            statistics_generation,
+            statistics_save_interval: Duration::from_secs(
+                config.statistics_save_interval,
+            ),
+            statistics_save_last: Instant::now(),
+            switch_check_last: Instant::now(),
             ws_active_counter: self.ws_active_counter,
         };
         Ok(sniffer)
@@ -0,0 +1,153 @@
+// Direction of the captured frames. Libpcap doesn't report it for most link types,
+// so frames are compared against the own addresses of the capture interface.
+
+use dpi::dto::frame::FrameDirection;
+use dpi::dto::metadata::{FrameMetadataDto, ProtocolDto};
+use std::net::IpAddr;
+
+#[derive(Debug, Default)]
+pub struct LocalAddresses {
+    addresses: Vec<IpAddr>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct AddressChange {
+    pub removed: Vec<IpAddr>,
+    pub added: Vec<IpAddr>,
+}
+
+impl LocalAddresses {
+    pub fn new(addresses: Vec<IpAddr>) -> Self {
+        Self { addresses }
+    }
+
+    pub fn from_device(device: &pcap::Device) -> Self {
+        Self::new(
+            device
+                .addresses
+                .iter()
+                .map(|address| address.addr)
+                .collect(),
+        )
+    }
+
+    /// Addresses, that differ in the refreshed list (e.g. after the DHCP renew).
+    /// Order & repeats aren't compared.
+    pub fn changes(&self, current: &LocalAddresses) -> Option<AddressChange> {
+        let mut removed: Vec<IpAddr> = self
+            .addresses
+            .iter()
+            .filter(|address| !current.addresses.contains(address))
+            .copied()
+            .collect();
+        let mut added: Vec<IpAddr> = current
+            .addresses
+            .iter()
+            .filter(|address| !self.addresses.contains(address))
+            .copied()
+            .collect();
+        if removed.is_empty() && added.is_empty() {
+            return None;
+        }
+        removed.sort();
+        removed.dedup();
+        added.sort();
+        added.dedup();
+
+        Some(AddressChange { removed, added })
+    }
+
+    pub fn annotate(&self, metadata: &mut FrameMetadataDto) {
+        metadata.direction = self.direction(&metadata.layers);
+    }
+
+    // The outermost IP layer is taken, inner ones belong to tunnels
+    fn direction(&self, layers: &[ProtocolDto]) -> FrameDirection {
+        let addresses = layers.iter().find_map(|layer| match layer {
+            ProtocolDto::IPv4(ipv4) => Some((
+                IpAddr::V4(ipv4.address_source),
+                IpAddr::V4(ipv4.address_destination),
+            )),
+            ProtocolDto::IPv6(ipv6) => Some((
+                IpAddr::V6(ipv6.address_source),
+                IpAddr::V6(ipv6.address_destination),
+            )),
+            _ => None,
+        });
+
+        match addresses {
+            Some((source, destination)) => self.resolve(&source, &destination),
+            None => FrameDirection::Unknown,
+        }
+    }
+
+    fn resolve(&self, source: &IpAddr, destination: &IpAddr) -> FrameDirection {
+        if self.addresses.contains(source) {
+            FrameDirection::Egress
+        } else if self.addresses.contains(destination) {
+            FrameDirection::Ingress
+        } else {
+            FrameDirection::Unknown
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    #[test]
+    fn test_resolve() {
+        let own_ipv4 = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 5));
+        let own_ipv6 = IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 5));
+        let local = LocalAddresses::new(vec![own_ipv4, own_ipv6]);
+
+        // Both ends are private
+        let neighbor = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 7));
+        assert_eq!(local.resolve(&own_ipv4, &neighbor), FrameDirection::Egress);
+        assert_eq!(local.resolve(&neighbor, &own_ipv4), FrameDirection::Ingress);
+
+        // Both ends are public
+        let remote = IpAddr::V6(Ipv6Addr::new(0x2606, 0x4700, 0, 0, 0, 0, 0, 1111));
+        assert_eq!(local.resolve(&own_ipv6, &remote), FrameDirection::Egress);
+        assert_eq!(local.resolve(&remote, &own_ipv6), FrameDirection::Ingress);
+
+        // Promiscuous mode: traffic of the other hosts
+        assert_eq!(local.resolve(&neighbor, &remote), FrameDirection::Unknown);
+        assert_eq!(
+            LocalAddresses::default().resolve(&own_ipv4, &neighbor),
+            FrameDirection::Unknown
+        );
+    }
+    #[test]
+    fn test_changes() {
+        let leased = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 10));
+        let renewed = IpAddr::V4(Ipv4Addr::new(192, 168, 0, 23));
+        let link_local = IpAddr::V6(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 5));
+        let previous = LocalAddresses::new(vec![leased, link_local]);
+
+        // Same addresses in the other order
+        let reordered = LocalAddresses::new(vec![link_local, leased, leased]);
+        assert_eq!(previous.changes(&reordered), None);
+
+        // DHCP renew gave the other address, link-local one is kept
+        let current = LocalAddresses::new(vec![link_local, renewed]);
+        assert_eq!(
+            previous.changes(&current),
+            Some(AddressChange {
+                removed: vec![leased],
+                added: vec![renewed],
+            })
+        );
+
+        // Lease is lost
+        assert_eq!(
+            previous.changes(&LocalAddresses::default()),
+            Some(AddressChange {
+                removed: vec![leased, link_local],
+                added: vec![],
+            })
+        );
+    }
+}
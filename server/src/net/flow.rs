@@ -0,0 +1,297 @@
+// Flow aggregation & export to NetFlow/IPFIX collectors.
+// Flows are keyed by the IPv4 5-tuple. A flow is expired (and exported) when
+// it has been idle for the inactive timeout, or when it has been alive
+// for longer than the active timeout.
+
+use crate::config::Config;
+use common::messages::FlowExporterDto;
+use dpi::dto::frame::{FrameHeader, FrameType};
+use dpi::dto::metadata::{FrameMetadataDto, ProtocolDto};
+use dpi::protocols::ip::protocol::IpNextLevelProtocol;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::net::{Ipv4Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+// How often the flow table is checked for expired flows.
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportProtocol {
+    NetflowV5,
+    Ipfix,
+}
+
+impl Display for ExportProtocol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            ExportProtocol::NetflowV5 => "netflow5",
+            ExportProtocol::Ipfix => "ipfix",
+        };
+        write!(f, "{text}")
+    }
+}
+
+impl FromStr for ExportProtocol {
+    type Err = FlowExportError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "netflow5" => Ok(ExportProtocol::NetflowV5),
+            "ipfix" => Ok(ExportProtocol::Ipfix),
+            _ => Err(FlowExportError::UnknownProtocol),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct FlowKey {
+    pub address_source: Ipv4Addr,
+    pub address_destination: Ipv4Addr,
+    pub port_source: u16,
+    pub port_destination: u16,
+    pub protocol: u8,
+}
+
+impl FlowKey {
+    pub fn from_metadata(metadata: &FrameMetadataDto) -> Option<Self> {
+        let mut addresses = None;
+        let mut ports = (0, 0);
+        let mut protocol = 0;
+        for layer in &metadata.layers {
+            match layer {
+                ProtocolDto::IPv4(ipv4) => {
+                    addresses = Some((ipv4.address_source, ipv4.address_destination));
+                },
+                ProtocolDto::ICMPv4(_) => {
+                    protocol = IpNextLevelProtocol::ICMP as u8;
+                },
+                ProtocolDto::TCP(tcp) => {
+                    ports = (tcp.port_source, tcp.port_destination);
+                    protocol = IpNextLevelProtocol::TCP as u8;
+                },
+                ProtocolDto::UDP(udp) => {
+                    ports = (udp.port_source, udp.port_destination);
+                    protocol = IpNextLevelProtocol::UDP as u8;
+                },
+                _ => {},
+            }
+        }
+
+        let (address_source, address_destination) = addresses?;
+        Some(Self {
+            address_source,
+            address_destination,
+            port_source: ports.0,
+            port_destination: ports.1,
+            protocol,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowRecord {
+    pub key: FlowKey,
+    pub packets: u64,
+    pub bytes: u64,
+    // Unix time, milliseconds
+    pub start_ms: u64,
+    pub end_ms: u64,
+}
+
+pub struct FlowTable {
+    flows: HashMap<FlowKey, FlowRecord>,
+    active_timeout_ms: u64,
+    inactive_timeout_ms: u64,
+}
+
+impl FlowTable {
+    pub fn new(active_timeout: Duration, inactive_timeout: Duration) -> Self {
+        Self {
+            flows: HashMap::new(),
+            active_timeout_ms: active_timeout.as_millis() as u64,
+            inactive_timeout_ms: inactive_timeout.as_millis() as u64,
+        }
+    }
+
+    pub fn observe(&mut self, metadata: &FrameMetadataDto) {
+        let Some(key) = FlowKey::from_metadata(metadata) else {
+            return;
+        };
+        let timestamp = header_timestamp_ms(&metadata.header);
+        let bytes = metadata.header.len as u64;
+
+        let record = self.flows.entry(key).or_insert(FlowRecord {
+            key,
+            packets: 0,
+            bytes: 0,
+            start_ms: timestamp,
+            end_ms: timestamp,
+        });
+        record.packets = record.packets.saturating_add(1);
+        record.bytes = record.bytes.saturating_add(bytes);
+        record.end_ms = record.end_ms.max(timestamp);
+    }
+
+    pub fn expire(&mut self, now_ms: u64) -> Vec<FlowRecord> {
+        let expired: Vec<FlowKey> = self
+            .flows
+            .values()
+            .filter(|record| {
+                now_ms.saturating_sub(record.end_ms) >= self.inactive_timeout_ms
+                    || now_ms.saturating_sub(record.start_ms) >= self.active_timeout_ms
+            })
+            .map(|record| record.key)
+            .collect();
+
+        expired
+            .iter()
+            .filter_map(|key| self.flows.remove(key))
+            .collect()
+    }
+
+    pub fn drain(&mut self) -> Vec<FlowRecord> {
+        self.flows.drain().map(|(_, record)| record).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.flows.len()
+    }
+}
+
+pub struct FlowExporter {
+    collector: SocketAddr,
+    protocol: ExportProtocol,
+    socket: UdpSocket,
+    table: FlowTable,
+
+    boot_ms: u64,
+    last_check: Instant,
+
+    flows_exported: u64,
+    datagrams_sent: u64,
+}
+
+impl FlowExporter {
+    pub fn new(config: &Config) -> Result<Self, FlowExportError> {
+        let collector = config
+            .flow_export_collector
+            .to_socket_addrs()
+            .map_err(FlowExportError::IOError)?
+            .next()
+            .ok_or(FlowExportError::CollectorUnresolved)?;
+
+        let bind_address = match collector {
+            SocketAddr::V4(_) => "0.0.0.0:0",
+            SocketAddr::V6(_) => "[::]:0",
+        };
+        let socket = UdpSocket::bind(bind_address).map_err(FlowExportError::IOError)?;
+
+        Ok(Self {
+            collector,
+            protocol: config.flow_export_protocol,
+            socket,
+            table: FlowTable::new(
+                Duration::from_secs(config.flow_export_active_timeout),
+                Duration::from_secs(config.flow_export_inactive_timeout),
+            ),
+
+            boot_ms: now_ms(),
+            last_check: Instant::now(),
+
+            flows_exported: 0,
+            datagrams_sent: 0,
+        })
+    }
+
+    pub fn observe(&mut self, frame: &FrameType) {
+        if let FrameType::Metadata(metadata) = frame {
+            self.table.observe(metadata);
+        }
+    }
+
+    /// Exports expired flows. Returns `true` if the check was performed.
+    pub fn tick(&mut self) -> Result<bool, FlowExportError> {
+        if self.last_check.elapsed() < CHECK_INTERVAL {
+            return Ok(false);
+        }
+        self.last_check = Instant::now();
+
+        let expired = self.table.expire(now_ms());
+        self.export(&expired)?;
+        Ok(true)
+    }
+
+    /// Exports all flows regardless of timeouts. Used on shutdown.
+    pub fn flush(&mut self) -> Result<(), FlowExportError> {
+        let records = self.table.drain();
+        self.export(&records)
+    }
+
+    pub fn status(&self) -> FlowExporterDto {
+        FlowExporterDto {
+            collector: self.collector.to_string(),
+            protocol: self.protocol.to_string(),
+            flows_active: self.table.len(),
+            flows_exported: self.flows_exported,
+            datagrams_sent: self.datagrams_sent,
+        }
+    }
+
+    fn export(&mut self, records: &[FlowRecord]) -> Result<(), FlowExportError> {
+        let chunk_size = match self.protocol {
+            ExportProtocol::NetflowV5 => netflow::MAX_RECORDS,
+            ExportProtocol::Ipfix => ipfix::MAX_RECORDS,
+        };
+
+        for chunk in records.chunks(chunk_size) {
+            let sequence = self.flows_exported as u32;
+            let datagram = match self.protocol {
+                ExportProtocol::NetflowV5 => {
+                    netflow::serialize(chunk, self.boot_ms, now_ms(), sequence)
+                },
+                ExportProtocol::Ipfix => ipfix::serialize(chunk, now_ms(), sequence),
+            };
+            self.socket
+                .send_to(&datagram, self.collector)
+                .map_err(FlowExportError::IOError)?;
+
+            self.flows_exported = self.flows_exported.saturating_add(chunk.len() as u64);
+            self.datagrams_sent = self.datagrams_sent.saturating_add(1);
+        }
+
+        Ok(())
+    }
+}
+
+fn header_timestamp_ms(header: &FrameHeader) -> u64 {
+    let seconds = u64::try_from(header.tv_sec).unwrap_or(0);
+    let microseconds = u64::try_from(header.tv_usec).unwrap_or(0);
+    seconds
+        .saturating_mul(1000)
+        .saturating_add(microseconds / 1000)
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+pub mod ipfix;
+pub mod netflow;
+
+#[derive(Debug, Error)]
+pub enum FlowExportError {
+    #[error("Failed to resolve collector address.")]
+    CollectorUnresolved,
+
+    #[error("IO Error. {0}")]
+    IOError(std::io::Error),
+
+    #[error("Unknown flow export protocol.")]
+    UnknownProtocol,
+}
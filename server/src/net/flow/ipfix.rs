@@ -0,0 +1,79 @@
+// IP Flow Information Export (IPFIX)
+// https://datatracker.ietf.org/doc/html/rfc7011
+// Information Elements: https://www.iana.org/assignments/ipfix/ipfix.xhtml
+
+use crate::net::flow::FlowRecord;
+
+pub const VERSION: u16 = 10;
+pub const MESSAGE_HEADER_LENGTH: usize = 16;
+pub const SET_HEADER_LENGTH: usize = 4;
+pub const MAX_RECORDS: usize = 24;
+
+const TEMPLATE_SET_ID: u16 = 2;
+const TEMPLATE_ID: u16 = 256;
+const OBSERVATION_DOMAIN_ID: u32 = 0;
+
+// (Information Element ID, Length)
+const TEMPLATE_FIELDS: [(u16, u16); 9] = [
+    (8, 4),   // sourceIPv4Address
+    (12, 4),  // destinationIPv4Address
+    (7, 2),   // sourceTransportPort
+    (11, 2),  // destinationTransportPort
+    (4, 1),   // protocolIdentifier
+    (2, 8),   // packetDeltaCount
+    (1, 8),   // octetDeltaCount
+    (152, 8), // flowStartMilliseconds
+    (153, 8), // flowEndMilliseconds
+];
+
+/// Serializes up to [`MAX_RECORDS`] flows into a single IPFIX message. <br>
+/// The template is sent with every message, since the transport is UDP.
+pub fn serialize(records: &[FlowRecord], now_ms: u64, sequence: u32) -> Vec<u8> {
+    let records = records.get(..MAX_RECORDS).unwrap_or(records);
+
+    let mut template_set = Vec::new();
+    template_set.extend_from_slice(&TEMPLATE_ID.to_be_bytes());
+    template_set.extend_from_slice(&(TEMPLATE_FIELDS.len() as u16).to_be_bytes());
+    for (id, length) in TEMPLATE_FIELDS {
+        template_set.extend_from_slice(&id.to_be_bytes());
+        template_set.extend_from_slice(&length.to_be_bytes());
+    }
+
+    let mut data_set = Vec::new();
+    for record in records {
+        data_set.extend_from_slice(&record.key.address_source.octets());
+        data_set.extend_from_slice(&record.key.address_destination.octets());
+        data_set.extend_from_slice(&record.key.port_source.to_be_bytes());
+        data_set.extend_from_slice(&record.key.port_destination.to_be_bytes());
+        data_set.push(record.key.protocol);
+        data_set.extend_from_slice(&record.packets.to_be_bytes());
+        data_set.extend_from_slice(&record.bytes.to_be_bytes());
+        data_set.extend_from_slice(&record.start_ms.to_be_bytes());
+        data_set.extend_from_slice(&record.end_ms.to_be_bytes());
+    }
+
+    let mut buffer = Vec::new();
+    push_set(&mut buffer, TEMPLATE_SET_ID, &template_set);
+    if !records.is_empty() {
+        // Data Sets use the Template ID as Set ID
+        push_set(&mut buffer, TEMPLATE_ID, &data_set);
+    }
+
+    let length = (MESSAGE_HEADER_LENGTH + buffer.len()) as u16;
+    let mut message = Vec::with_capacity(length as usize);
+    message.extend_from_slice(&VERSION.to_be_bytes());
+    message.extend_from_slice(&length.to_be_bytes());
+    message.extend_from_slice(&((now_ms / 1000) as u32).to_be_bytes());
+    message.extend_from_slice(&sequence.to_be_bytes());
+    message.extend_from_slice(&OBSERVATION_DOMAIN_ID.to_be_bytes());
+    message.extend_from_slice(&buffer);
+
+    message
+}
+
+fn push_set(buffer: &mut Vec<u8>, set_id: u16, content: &[u8]) {
+    let length = (SET_HEADER_LENGTH + content.len()) as u16;
+    buffer.extend_from_slice(&set_id.to_be_bytes());
+    buffer.extend_from_slice(&length.to_be_bytes());
+    buffer.extend_from_slice(content);
+}
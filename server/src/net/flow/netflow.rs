@@ -0,0 +1,154 @@
+// NetFlow Version 5 Export Format
+// https://www.cisco.com/c/en/us/td/docs/net_mgmt/netflow_collection_engine/3-6/user/guide/format.html#wp1006108
+
+use crate::net::flow::FlowRecord;
+
+pub const VERSION: u16 = 5;
+pub const HEADER_LENGTH: usize = 24;
+pub const RECORD_LENGTH: usize = 48;
+pub const MAX_RECORDS: usize = 30;
+
+/// Serializes up to [`MAX_RECORDS`] flows into a single v5 datagram. <br>
+/// `boot_ms` is the exporter start time, `sequence` is the amount of flows exported
+/// before this datagram. All timestamps are Unix time in milliseconds.
+pub fn serialize(
+    records: &[FlowRecord], boot_ms: u64, now_ms: u64, sequence: u32,
+) -> Vec<u8> {
+    let records = records.get(..MAX_RECORDS).unwrap_or(records);
+    let mut buffer = Vec::with_capacity(HEADER_LENGTH + RECORD_LENGTH * records.len());
+
+    // Header
+    buffer.extend_from_slice(&VERSION.to_be_bytes());
+    buffer.extend_from_slice(&(records.len() as u16).to_be_bytes());
+    buffer.extend_from_slice(&uptime(now_ms, boot_ms).to_be_bytes());
+    buffer.extend_from_slice(&((now_ms / 1000) as u32).to_be_bytes());
+    buffer.extend_from_slice(&(((now_ms % 1000) * 1_000_000) as u32).to_be_bytes());
+    buffer.extend_from_slice(&sequence.to_be_bytes());
+    buffer.push(0); // Engine type
+    buffer.push(0); // Engine ID
+    buffer.extend_from_slice(&0u16.to_be_bytes()); // Sampling interval
+
+    // Records
+    for record in records {
+        buffer.extend_from_slice(&record.key.address_source.octets());
+        buffer.extend_from_slice(&record.key.address_destination.octets());
+        buffer.extend_from_slice(&[0; 4]); // Next hop
+        buffer.extend_from_slice(&0u16.to_be_bytes()); // Input interface index
+        buffer.extend_from_slice(&0u16.to_be_bytes()); // Output interface index
+        buffer.extend_from_slice(&saturate(record.packets).to_be_bytes());
+        buffer.extend_from_slice(&saturate(record.bytes).to_be_bytes());
+        buffer.extend_from_slice(&uptime(record.start_ms, boot_ms).to_be_bytes());
+        buffer.extend_from_slice(&uptime(record.end_ms, boot_ms).to_be_bytes());
+        buffer.extend_from_slice(&record.key.port_source.to_be_bytes());
+        buffer.extend_from_slice(&record.key.port_destination.to_be_bytes());
+        buffer.push(0); // Pad
+        buffer.push(0); // TCP flags
+        buffer.push(record.key.protocol);
+        buffer.push(0); // Type of service
+        buffer.extend_from_slice(&0u16.to_be_bytes()); // Source AS
+        buffer.extend_from_slice(&0u16.to_be_bytes()); // Destination AS
+        buffer.push(0); // Source mask
+        buffer.push(0); // Destination mask
+        buffer.extend_from_slice(&0u16.to_be_bytes()); // Pad
+    }
+
+    buffer
+}
+
+// SysUptime wraps around every ~49.7 days, as on real devices.
+fn uptime(timestamp_ms: u64, boot_ms: u64) -> u32 {
+    timestamp_ms.saturating_sub(boot_ms) as u32
+}
+
+fn saturate(value: u64) -> u32 {
+    u32::try_from(value).unwrap_or(u32::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::flow::FlowKey;
+    use std::net::Ipv4Addr;
+
+    fn u16_at(buffer: &[u8], offset: usize) -> u16 {
+        u16::from_be_bytes([buffer[offset], buffer[offset + 1]])
+    }
+
+    fn u32_at(buffer: &[u8], offset: usize) -> u32 {
+        u32::from_be_bytes(buffer[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn test_v5_two_flows_layout() {
+        let boot_ms = 1_700_000_000_000;
+        let now_ms = boot_ms + 65_250;
+        let records = vec![
+            FlowRecord {
+                key: FlowKey {
+                    address_source: Ipv4Addr::new(192, 168, 0, 103),
+                    address_destination: Ipv4Addr::new(8, 8, 8, 8),
+                    port_source: 52311,
+                    port_destination: 53,
+                    protocol: 17,
+                },
+                packets: 2,
+                bytes: 160,
+                start_ms: boot_ms + 1_000,
+                end_ms: boot_ms + 1_500,
+            },
+            FlowRecord {
+                key: FlowKey {
+                    address_source: Ipv4Addr::new(10, 0, 0, 2),
+                    address_destination: Ipv4Addr::new(93, 184, 216, 34),
+                    port_source: 40000,
+                    port_destination: 80,
+                    protocol: 6,
+                },
+                packets: 12,
+                bytes: 9000,
+                start_ms: boot_ms + 2_000,
+                end_ms: boot_ms + 60_000,
+            },
+        ];
+
+        let datagram = serialize(&records, boot_ms, now_ms, 7);
+        assert_eq!(datagram.len(), HEADER_LENGTH + 2 * RECORD_LENGTH);
+
+        // Header
+        assert_eq!(u16_at(&datagram, 0), 5);
+        assert_eq!(u16_at(&datagram, 2), 2);
+        assert_eq!(u32_at(&datagram, 4), 65_250);
+        assert_eq!(u32_at(&datagram, 8), ((boot_ms + 65_250) / 1000) as u32);
+        assert_eq!(u32_at(&datagram, 12), 250_000_000);
+        assert_eq!(u32_at(&datagram, 16), 7);
+        assert_eq!(datagram[20], 0);
+        assert_eq!(datagram[21], 0);
+        assert_eq!(u16_at(&datagram, 22), 0);
+
+        // First record
+        let record = &datagram[HEADER_LENGTH..HEADER_LENGTH + RECORD_LENGTH];
+        assert_eq!(record[0..4], [192, 168, 0, 103]);
+        assert_eq!(record[4..8], [8, 8, 8, 8]);
+        assert_eq!(record[8..12], [0, 0, 0, 0]);
+        assert_eq!(u32_at(record, 16), 2);
+        assert_eq!(u32_at(record, 20), 160);
+        assert_eq!(u32_at(record, 24), 1_000);
+        assert_eq!(u32_at(record, 28), 1_500);
+        assert_eq!(u16_at(record, 32), 52311);
+        assert_eq!(u16_at(record, 34), 53);
+        assert_eq!(record[38], 17);
+
+        // Second record
+        let record = &datagram[HEADER_LENGTH + RECORD_LENGTH..];
+        assert_eq!(record[0..4], [10, 0, 0, 2]);
+        assert_eq!(record[4..8], [93, 184, 216, 34]);
+        assert_eq!(u32_at(record, 16), 12);
+        assert_eq!(u32_at(record, 20), 9000);
+        assert_eq!(u32_at(record, 24), 2_000);
+        assert_eq!(u32_at(record, 28), 60_000);
+        assert_eq!(u16_at(record, 32), 40000);
+        assert_eq!(u16_at(record, 34), 80);
+        assert_eq!(record[38], 6);
+        assert_eq!(record[44..48], [0, 0, 0, 0]);
+    }
+}
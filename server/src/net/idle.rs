@@ -0,0 +1,96 @@
+// Idle detector of the capture.
+// Interface may be the wrong one, or the mirrored traffic may stop coming
+// (e.g. SPAN session died). Capture still works, but there's nothing to capture.
+// Timer starts over when the capture is (re)started or resumed after the pause,
+// so the startup doesn't count as idle.
+
+use std::time::{Duration, Instant};
+
+pub struct IdleDetector {
+    // Zero disables the detector
+    timeout: Duration,
+    // Last packet or the start of the capture
+    last_activity: Instant,
+    is_idle: bool,
+}
+
+impl IdleDetector {
+    pub fn new(timeout: Duration, now: Instant) -> Self {
+        Self {
+            timeout,
+            last_activity: now,
+            is_idle: false,
+        }
+    }
+
+    /// Starts the grace period over, e.g. capture is paused or reopened.
+    /// <br> Returns true if the capture was idle before.
+    pub fn reset(&mut self, now: Instant) -> bool {
+        self.last_activity = now;
+        std::mem::take(&mut self.is_idle)
+    }
+
+    /// Returns true if the capture was idle before this packet.
+    pub fn on_packet(&mut self, now: Instant) -> bool {
+        self.last_activity = now;
+        std::mem::take(&mut self.is_idle)
+    }
+
+    /// Returns idle seconds once, when there are no packets for the timeout.
+    pub fn check(&mut self, now: Instant) -> Option<u64> {
+        if self.timeout.is_zero() || self.is_idle {
+            return None;
+        }
+
+        let idle = now.saturating_duration_since(self.last_activity);
+        if idle < self.timeout {
+            return None;
+        }
+        self.is_idle = true;
+
+        Some(idle.as_secs())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_idle_once() {
+        let start = Instant::now();
+        let mut detector = IdleDetector::new(Duration::from_secs(30), start);
+
+        // Grace period after the start
+        assert!(detector.check(start + Duration::from_secs(29)).is_none());
+        assert_eq!(detector.check(start + Duration::from_secs(30)), Some(30));
+        assert!(detector.check(start + Duration::from_secs(60)).is_none());
+
+        // Traffic resumed
+        assert!(detector.on_packet(start + Duration::from_secs(61)));
+        assert!(!detector.on_packet(start + Duration::from_secs(62)));
+        assert!(detector.check(start + Duration::from_secs(91)).is_none());
+        assert_eq!(detector.check(start + Duration::from_secs(92)), Some(30));
+    }
+
+    #[test]
+    fn test_reset_after_pause() {
+        let start = Instant::now();
+        let mut detector = IdleDetector::new(Duration::from_secs(30), start);
+
+        assert!(detector.check(start + Duration::from_secs(40)).is_some());
+
+        // Capture is resumed after the long pause
+        assert!(detector.reset(start + Duration::from_secs(100)));
+        assert!(detector.check(start + Duration::from_secs(110)).is_none());
+        assert!(detector.check(start + Duration::from_secs(130)).is_some());
+    }
+
+    #[test]
+    fn test_disabled() {
+        let start = Instant::now();
+        let mut detector = IdleDetector::new(Duration::ZERO, start);
+
+        assert!(detector.check(start + Duration::from_secs(3600)).is_none());
+    }
+}
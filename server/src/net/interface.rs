@@ -1,5 +1,14 @@
+use common::messages::CaptureOptionsDto;
 use thiserror::Error;
 
+/// Options for opening the interface only to get its link type.
+pub const PROBE_OPTIONS: CaptureOptionsDto = CaptureOptionsDto {
+    buffer_size: 0,
+    immediate_mode: true,
+    promiscuous: false,
+    snaplen: CaptureOptionsDto::SNAPLEN_MAX,
+};
+
 /// Usable interfaces. <br>
 /// Necessary: Presence of adresses.
 pub fn usable_sorted() -> Result<Vec<pcap::Device>, InterfaceError> {
@@ -44,15 +53,31 @@ pub fn get_network_interface(device_name: &str) -> Result<pcap::Device, Interfac
         .ok_or(InterfaceError::UnknownInterface)
 }
 
+/// Current state of the device, e.g. its addresses. <br>
+/// Device without addresses is found too, so the lost lease is noticed.
+pub fn refreshed(device: &pcap::Device) -> Result<Option<pcap::Device>, InterfaceError> {
+    let found = pcap::Device::list()
+        .map_err(InterfaceError::PcapError)?
+        .into_iter()
+        .find(|current| current.name == device.name);
+
+    Ok(found)
+}
+
 pub fn get_capture(
-    device: pcap::Device, timeout: i32,
+    device: pcap::Device, timeout: i32, options: &CaptureOptionsDto,
 ) -> Result<pcap::Capture<pcap::Active>, InterfaceError> {
-    pcap::Capture::from_device(device)
+    let mut capture = pcap::Capture::from_device(device)
         .map_err(InterfaceError::PcapError)?
         .timeout(timeout)
-        .immediate_mode(true)
-        .open()
-        .map_err(InterfaceError::PcapError)
+        .immediate_mode(options.immediate_mode)
+        .promisc(options.promiscuous)
+        .snaplen(options.snaplen);
+    if options.buffer_size > 0 {
+        capture = capture.buffer_size(options.buffer_size);
+    }
+
+    capture.open().map_err(InterfaceError::PcapError)
 }
 
 #[derive(Debug, Error)]
@@ -0,0 +1,103 @@
+use crate::net::interface;
+use common::messages::InterfaceDto;
+
+/// Information about the interface for choosing the capture one. <br>
+/// MTU, link speed & state aren't exposed by pcap, so they're read from the system,
+/// where it's possible. Otherwise, they're unknown (state falls back to the pcap flags).
+pub fn describe(device: &pcap::Device) -> InterfaceDto {
+    let (mtu, speed_mbps, is_up) = read_system(&device.name);
+
+    InterfaceDto {
+        name: interface::get_network_interface_name(device),
+        addresses: device
+            .addresses
+            .iter()
+            .map(|address| address.addr)
+            .collect(),
+        mtu,
+        speed_mbps,
+        is_up: is_up.or_else(|| state_from_flags(&device.flags)),
+    }
+}
+
+fn state_from_flags(flags: &pcap::DeviceFlags) -> Option<bool> {
+    match flags.connection_status {
+        pcap::ConnectionStatus::Connected => Some(true),
+        pcap::ConnectionStatus::Disconnected => Some(false),
+        pcap::ConnectionStatus::Unknown | pcap::ConnectionStatus::NotApplicable => {
+            Some(flags.is_up() && flags.is_running())
+        },
+    }
+}
+
+// MTU, link speed & state of the interface
+#[cfg(target_os = "linux")]
+fn read_system(name: &str) -> (Option<u32>, Option<u64>, Option<bool>) {
+    let read = |attribute: &str| {
+        std::fs::read_to_string(format!("/sys/class/net/{name}/{attribute}")).ok()
+    };
+
+    let mtu = read("mtu").and_then(|value| value.trim().parse().ok());
+    // Reading the speed fails with EINVAL, if the link is down
+    let speed_mbps = read("speed").and_then(|value| parse_speed(&value));
+    let is_up = read("operstate").and_then(|value| parse_operstate(&value));
+
+    (mtu, speed_mbps, is_up)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_system(_: &str) -> (Option<u32>, Option<u64>, Option<bool>) {
+    (None, None, None)
+}
+
+// Drivers report -1 (or 0) if the speed isn't determined
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_speed(value: &str) -> Option<u64> {
+    value
+        .trim()
+        .parse::<i64>()
+        .ok()
+        .and_then(|speed| u64::try_from(speed).ok())
+        .filter(|speed| *speed > 0)
+}
+
+// RFC 2863 operational states. Loopback & some virtual interfaces are always "unknown"
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_operstate(value: &str) -> Option<bool> {
+    match value.trim() {
+        "up" => Some(true),
+        "down" | "lowerlayerdown" | "notpresent" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_speed() {
+        assert_eq!(parse_speed("1000\n"), Some(1000));
+        assert_eq!(parse_speed("-1\n"), None);
+        assert_eq!(parse_speed("0"), None);
+        assert_eq!(parse_speed(""), None);
+    }
+
+    #[test]
+    fn test_parse_operstate() {
+        assert_eq!(parse_operstate("up\n"), Some(true));
+        assert_eq!(parse_operstate("lowerlayerdown\n"), Some(false));
+        assert_eq!(parse_operstate("unknown\n"), None);
+        assert_eq!(parse_operstate("dormant"), None);
+    }
+
+    #[test]
+    fn test_describe_without_system_info() {
+        let device = pcap::Device::from("xailyser-absent0");
+        let dto = describe(&device);
+        assert_eq!(dto.name, "xailyser-absent0");
+        assert!(dto.addresses.is_empty());
+        assert_eq!(dto.mtu, None);
+        assert_eq!(dto.speed_mbps, None);
+    }
+}
@@ -0,0 +1,415 @@
+// Mirror of the selected frames to the other analyzer over UDP, like a remote SPAN.
+// Frames are matched by the addresses of the raw bytes, so nothing is parsed twice:
+// only Ethernet frames are matched (with a single 802.1Q tag at most).
+// Each frame is sent in its own datagram, plain or after the header:
+// "XMIR", seconds & microseconds of the capture, original length (big-endian u32).
+// Frames beyond the limits are dropped, not queued.
+
+use crate::config::Config;
+use common::messages::{MirrorDto, MirrorFilter};
+use pcap::{Linktype, Packet};
+use std::fmt::{Display, Formatter};
+use std::net::{IpAddr, SocketAddr, UdpSocket};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+// How often the status is updated in the context.
+const STATUS_INTERVAL: Duration = Duration::from_secs(1);
+const HEADER_MAGIC: &[u8; 4] = b"XMIR";
+const HEADER_BYTES: usize = 16;
+// Payload of the UDP datagram over IPv4. Longer frames are truncated
+const DATAGRAM_BYTES_MAX: usize = 65507;
+
+const ETHERNET_HEADER_BYTES: usize = 14;
+const ETHER_TYPE_IPV4: u16 = 0x0800;
+const ETHER_TYPE_IPV6: u16 = 0x86DD;
+const ETHER_TYPE_VLAN: u16 = 0x8100;
+const VLAN_TAG_BYTES: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encapsulation {
+    Plain,
+    Header,
+}
+
+impl Display for Encapsulation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Encapsulation::Plain => "plain",
+            Encapsulation::Header => "header",
+        };
+        write!(f, "{text}")
+    }
+}
+
+impl FromStr for Encapsulation {
+    type Err = MirrorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "plain" => Ok(Encapsulation::Plain),
+            "header" => Ok(Encapsulation::Header),
+            _ => Err(MirrorError::UnknownEncapsulation),
+        }
+    }
+}
+
+// Requested by the client, taken by the sniffer
+#[derive(Debug, Clone, PartialEq)]
+pub enum MirrorSwitch {
+    Start {
+        target: SocketAddr,
+        filter: MirrorFilter,
+    },
+    Stop,
+}
+
+pub struct Mirror {
+    target: SocketAddr,
+    filter: MirrorFilter,
+    encapsulation: Encapsulation,
+    link_type: Linktype,
+    socket: UdpSocket,
+    limiter: Limiter,
+    packets_per_second: u64,
+    kilobytes_per_second: u64,
+    // Reused for every datagram
+    buffer: Vec<u8>,
+
+    mirrored: u64,
+    dropped_by_limit: u64,
+    error: Option<String>,
+    last_status: Instant,
+}
+
+impl Mirror {
+    pub fn new(
+        target: SocketAddr, filter: MirrorFilter, config: &Config, link_type: Linktype,
+    ) -> Result<Self, MirrorError> {
+        let bind_address = match target {
+            SocketAddr::V4(_) => "0.0.0.0:0",
+            SocketAddr::V6(_) => "[::]:0",
+        };
+        let socket = UdpSocket::bind(bind_address).map_err(MirrorError::IOError)?;
+        // Slow target drops the frames, it doesn't stall the capture
+        socket.set_nonblocking(true).map_err(MirrorError::IOError)?;
+
+        let now = Instant::now();
+        let bytes_per_second = config.mirror_kilobytes_per_second.saturating_mul(1024);
+        Ok(Self {
+            target,
+            filter,
+            encapsulation: config.mirror_encapsulation,
+            link_type,
+            socket,
+            limiter: Limiter {
+                packets: TokenBucket::new(config.mirror_packets_per_second, now),
+                bytes: TokenBucket::new(bytes_per_second, now),
+            },
+            packets_per_second: config.mirror_packets_per_second,
+            kilobytes_per_second: config.mirror_kilobytes_per_second,
+            buffer: Vec::with_capacity(DATAGRAM_BYTES_MAX),
+
+            mirrored: 0,
+            dropped_by_limit: 0,
+            error: None,
+            last_status: now,
+        })
+    }
+
+    pub fn mirror(&mut self, packet: &Packet, now: Instant) {
+        if !is_match(self.link_type, packet.data, &self.filter) {
+            return;
+        }
+
+        encapsulate(self.encapsulation, packet, &mut self.buffer);
+        if !self.limiter.allow(self.buffer.len(), now) {
+            self.dropped_by_limit = self.dropped_by_limit.saturating_add(1);
+            return;
+        }
+
+        match self.socket.send_to(&self.buffer, self.target) {
+            Ok(_) => {
+                self.mirrored = self.mirrored.saturating_add(1);
+                if self.error.take().is_some() {
+                    log::info!("Mirror: Sending to {} is resumed.", self.target);
+                }
+            },
+            Err(err) => {
+                if self.error.is_none() {
+                    log::warn!("Mirror: Failed to send to {}. {err}", self.target);
+                }
+                self.error = Some(err.to_string());
+            },
+        }
+    }
+
+    /// Returns `true` if the status has to be updated.
+    pub fn tick(&mut self, now: Instant) -> bool {
+        if now.duration_since(self.last_status) < STATUS_INTERVAL {
+            return false;
+        }
+        self.last_status = now;
+        true
+    }
+
+    pub fn set_link_type(&mut self, link_type: Linktype) {
+        self.link_type = link_type;
+    }
+
+    pub fn status(&self) -> MirrorDto {
+        MirrorDto {
+            target: self.target,
+            filter: self.filter.clone(),
+            encapsulation: self.encapsulation.to_string(),
+            packets_per_second: self.packets_per_second,
+            kilobytes_per_second: self.kilobytes_per_second,
+            mirrored: self.mirrored,
+            dropped_by_limit: self.dropped_by_limit,
+            error: self.error.clone(),
+        }
+    }
+}
+
+// Addresses of the other link types aren't read, so nothing is matched there
+fn is_match(link_type: Linktype, frame: &[u8], filter: &MirrorFilter) -> bool {
+    if link_type != Linktype::ETHERNET {
+        return false;
+    }
+
+    match filter {
+        MirrorFilter::Mac(mac) => {
+            let mac = mac.0.as_slice();
+            frame.get(0..6) == Some(mac) || frame.get(6..12) == Some(mac)
+        },
+        MirrorFilter::Ip(ip) => ip_addresses(frame)
+            .is_some_and(|(source, destination)| source == *ip || destination == *ip),
+    }
+}
+
+fn ip_addresses(frame: &[u8]) -> Option<(IpAddr, IpAddr)> {
+    let mut ether_type = read_u16(frame, 12)?;
+    let mut offset = ETHERNET_HEADER_BYTES;
+    if ether_type == ETHER_TYPE_VLAN {
+        ether_type = read_u16(frame, 16)?;
+        offset += VLAN_TAG_BYTES;
+    }
+    let packet = frame.get(offset..)?;
+
+    match ether_type {
+        ETHER_TYPE_IPV4 => {
+            let source: [u8; 4] = packet.get(12..16)?.try_into().ok()?;
+            let destination: [u8; 4] = packet.get(16..20)?.try_into().ok()?;
+            Some((IpAddr::from(source), IpAddr::from(destination)))
+        },
+        ETHER_TYPE_IPV6 => {
+            let source: [u8; 16] = packet.get(8..24)?.try_into().ok()?;
+            let destination: [u8; 16] = packet.get(24..40)?.try_into().ok()?;
+            Some((IpAddr::from(source), IpAddr::from(destination)))
+        },
+        _ => None,
+    }
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> Option<u16> {
+    let bytes: [u8; 2] = bytes.get(offset..offset + 2)?.try_into().ok()?;
+    Some(u16::from_be_bytes(bytes))
+}
+
+fn encapsulate(encapsulation: Encapsulation, packet: &Packet, buffer: &mut Vec<u8>) {
+    buffer.clear();
+    if encapsulation == Encapsulation::Header {
+        let seconds = u32::try_from(packet.header.ts.tv_sec).unwrap_or_default();
+        let micros = u32::try_from(packet.header.ts.tv_usec).unwrap_or_default();
+        buffer.extend_from_slice(HEADER_MAGIC);
+        buffer.extend_from_slice(&seconds.to_be_bytes());
+        buffer.extend_from_slice(&micros.to_be_bytes());
+        buffer.extend_from_slice(&packet.header.len.to_be_bytes());
+    }
+
+    let room = DATAGRAM_BYTES_MAX.saturating_sub(buffer.len());
+    let length = packet.data.len().min(room);
+    buffer.extend_from_slice(packet.data.get(..length).unwrap_or_default());
+}
+
+// Both limits have to allow the datagram
+struct Limiter {
+    packets: Option<TokenBucket>,
+    bytes: Option<TokenBucket>,
+}
+
+impl Limiter {
+    fn allow(&mut self, bytes: usize, now: Instant) -> bool {
+        let is_available = [&mut self.packets, &mut self.bytes]
+            .into_iter()
+            .flatten()
+            .all(|bucket| bucket.refill(now));
+        if !is_available {
+            return false;
+        }
+
+        if let Some(packets) = &mut self.packets {
+            packets.take(1);
+        }
+        if let Some(bytes_bucket) = &mut self.bytes {
+            bytes_bucket.take(bytes as u64);
+        }
+        true
+    }
+}
+
+// Rate per second with the burst of one second. Tokens may go below zero,
+// so the datagram longer than the rate is sent, and the debt is paid afterwards.
+struct TokenBucket {
+    rate: f64,
+    tokens: f64,
+    refilled: Instant,
+}
+
+impl TokenBucket {
+    // Zero rate disables the limit
+    fn new(rate: u64, now: Instant) -> Option<Self> {
+        match rate {
+            0 => None,
+            rate => Some(Self {
+                rate: rate as f64,
+                tokens: rate as f64,
+                refilled: now,
+            }),
+        }
+    }
+
+    /// Returns `true` if the tokens are available.
+    fn refill(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.refilled).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.refilled = now;
+        self.tokens >= 1.0
+    }
+
+    fn take(&mut self, amount: u64) {
+        self.tokens -= amount as f64;
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum MirrorError {
+    #[error("IO Error. {0}")]
+    IOError(std::io::Error),
+
+    #[error("Unknown encapsulation.")]
+    UnknownEncapsulation,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dpi::dto::frame::FrameHeader;
+    use dpi::protocols::ethernet::mac::MacAddress;
+    use std::net::Ipv4Addr;
+
+    const MAC_SOURCE: [u8; 6] = [0x02, 0, 0, 0, 0, 0x01];
+    const MAC_DESTINATION: [u8; 6] = [0x02, 0, 0, 0, 0, 0x02];
+
+    // Ethernet & IPv4 headers, optionally with the VLAN tag
+    fn frame(is_tagged: bool) -> Vec<u8> {
+        let mut frame = [MAC_DESTINATION, MAC_SOURCE].concat();
+        if is_tagged {
+            frame.extend_from_slice(&ETHER_TYPE_VLAN.to_be_bytes());
+            frame.extend_from_slice(&[0x00, 0x0A]);
+        }
+        frame.extend_from_slice(&ETHER_TYPE_IPV4.to_be_bytes());
+        let mut ipv4 = vec![0x45, 0, 0, 20, 0, 0, 0, 0, 64, 17, 0, 0];
+        ipv4.extend_from_slice(&[192, 168, 1, 10]);
+        ipv4.extend_from_slice(&[8, 8, 8, 8]);
+        frame.extend(ipv4);
+        frame
+    }
+
+    #[test]
+    fn test_filter_match() {
+        let ethernet = Linktype::ETHERNET;
+        for is_tagged in [false, true] {
+            let frame = frame(is_tagged);
+            let filter = MirrorFilter::Mac(MacAddress(MAC_SOURCE));
+            assert!(is_match(ethernet, &frame, &filter));
+            let filter = MirrorFilter::Mac(MacAddress(MAC_DESTINATION));
+            assert!(is_match(ethernet, &frame, &filter));
+            let filter = MirrorFilter::Mac(MacAddress([0x02, 0, 0, 0, 0, 0x03]));
+            assert!(!is_match(ethernet, &frame, &filter));
+
+            let filter = MirrorFilter::Ip(IpAddr::from(Ipv4Addr::new(8, 8, 8, 8)));
+            assert!(is_match(ethernet, &frame, &filter));
+            let filter = MirrorFilter::Ip(IpAddr::from(Ipv4Addr::new(8, 8, 4, 4)));
+            assert!(!is_match(ethernet, &frame, &filter));
+            // Addresses of the other link types aren't read
+            let filter = MirrorFilter::Ip(IpAddr::from(Ipv4Addr::new(8, 8, 8, 8)));
+            assert!(!is_match(Linktype::LINUX_SLL, &frame, &filter));
+        }
+
+        // Truncated frame isn't matched by IP
+        let frame = frame(false);
+        let filter = MirrorFilter::Ip(IpAddr::from(Ipv4Addr::new(8, 8, 8, 8)));
+        assert!(!is_match(ethernet, &frame[..30], &filter));
+    }
+
+    #[test]
+    fn test_encapsulation() {
+        let data = frame(false);
+        let header = pcap::PacketHeader::from(&FrameHeader {
+            tv_sec: 1_700_000_000,
+            tv_usec: 250_000,
+            caplen: data.len() as u32,
+            len: 1500,
+        });
+        let packet = Packet::new(&header, &data);
+        let mut buffer = vec![];
+
+        encapsulate(Encapsulation::Plain, &packet, &mut buffer);
+        assert_eq!(buffer, data);
+
+        encapsulate(Encapsulation::Header, &packet, &mut buffer);
+        assert_eq!(buffer.len(), HEADER_BYTES + data.len());
+        assert_eq!(&buffer[0..4], HEADER_MAGIC);
+        assert_eq!(&buffer[4..8], &1_700_000_000u32.to_be_bytes());
+        assert_eq!(&buffer[8..12], &250_000u32.to_be_bytes());
+        assert_eq!(&buffer[12..16], &1500u32.to_be_bytes());
+        assert_eq!(&buffer[HEADER_BYTES..], data.as_slice());
+
+        assert_eq!(
+            Encapsulation::from_str(" Plain ").unwrap(),
+            Encapsulation::Plain
+        );
+        assert!(Encapsulation::from_str("gre").is_err());
+    }
+
+    #[test]
+    fn test_limits() {
+        let now = Instant::now();
+        let mut limiter = Limiter {
+            packets: TokenBucket::new(3, now),
+            bytes: None,
+        };
+        let allowed = (0..5).filter(|_| limiter.allow(100, now)).count();
+        assert_eq!(allowed, 3);
+        // Third of the second refills one token
+        assert!(limiter.allow(100, now + Duration::from_millis(334)));
+        assert!(!limiter.allow(100, now + Duration::from_millis(334)));
+
+        let mut limiter = Limiter {
+            packets: None,
+            bytes: TokenBucket::new(1000, now),
+        };
+        // Longer datagram is sent, the next ones wait for the debt
+        assert!(limiter.allow(1500, now));
+        assert!(!limiter.allow(100, now + Duration::from_millis(400)));
+        assert!(limiter.allow(100, now + Duration::from_millis(600)));
+
+        let mut unlimited = Limiter {
+            packets: TokenBucket::new(0, now),
+            bytes: TokenBucket::new(0, now),
+        };
+        assert!((0..10_000).all(|_| unlimited.allow(65_000, now)));
+    }
+}
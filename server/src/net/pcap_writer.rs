@@ -0,0 +1,431 @@
+// Continuous capture to the rotating pcap files, like `tcpdump -C -G -W`.
+// Files are rotated by the size or the age, the oldest ones beyond the retention
+// count are deleted. Writing errors (e.g. the disk is full) don't stop the capture:
+// the file is closed, packets are skipped and the writer retries after a while.
+
+use crate::config::Config;
+use common::messages::PcapWriterDto;
+use pcap::{Linktype, Packet, Savefile};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+const FILE_PREFIX: &str = "xailyser-";
+const FILE_EXTENSION: &str = "pcap";
+// Global header of the pcap file & the header of each packet record
+const FILE_HEADER_BYTES: u64 = 24;
+const RECORD_HEADER_BYTES: u64 = 16;
+// How often the current file is flushed, so the errors are noticed.
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+// How long the packets are skipped after the failure.
+const RETRY_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RotationSettings {
+    pub directory: PathBuf,
+    // Bytes of the file, zero disables the size boundary
+    pub file_size: u64,
+    // Age of the file, zero disables the time boundary
+    pub file_duration: Duration,
+    // Oldest files beyond that are deleted, zero keeps all of them
+    pub files_kept: usize,
+}
+
+impl RotationSettings {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            directory: PathBuf::from(&config.pcap_writer_directory),
+            file_size: config
+                .pcap_writer_file_megabytes
+                .saturating_mul(1024 * 1024),
+            file_duration: Duration::from_secs(config.pcap_writer_file_seconds),
+            files_kept: config.pcap_writer_files_kept,
+        }
+    }
+}
+
+pub struct PcapWriter {
+    settings: RotationSettings,
+    link_type: Linktype,
+    current: Option<CurrentFile>,
+    // Files of the directory, the oldest first. The current one is the last
+    files: VecDeque<PathBuf>,
+    // Number of the next file, continued from the files of the previous runs
+    sequence: u64,
+    bytes_written: u64,
+
+    error: Option<String>,
+    failed_at: Option<Instant>,
+    last_flush: Instant,
+}
+
+struct CurrentFile {
+    path: PathBuf,
+    savefile: Savefile,
+    bytes: u64,
+    opened: Instant,
+}
+
+impl PcapWriter {
+    /// Files of the previous runs are counted in the retention.
+    pub fn new(settings: RotationSettings, link_type: Linktype) -> Self {
+        let files = match existing_files(&settings.directory) {
+            Ok(files) => files,
+            Err(err) => {
+                // Directory is created with the first file
+                log::debug!("Pcap Writer: Existing files aren't listed. {err}");
+                vec![]
+            },
+        };
+        let sequence = files
+            .last()
+            .map(|(sequence, _)| sequence.saturating_add(1))
+            .unwrap_or_default();
+
+        Self {
+            settings,
+            link_type,
+            current: None,
+            files: files.into_iter().map(|(_, path)| path).collect(),
+            sequence,
+            bytes_written: 0,
+
+            error: None,
+            failed_at: None,
+            last_flush: Instant::now(),
+        }
+    }
+
+    pub fn write(&mut self, packet: &Packet, now: Instant) {
+        if self
+            .failed_at
+            .is_some_and(|failed_at| now.duration_since(failed_at) < RETRY_INTERVAL)
+        {
+            return;
+        }
+
+        let record = RECORD_HEADER_BYTES.saturating_add(u64::from(packet.header.caplen));
+        if self
+            .current
+            .as_ref()
+            .is_some_and(|current| self.is_rotation_due(current, record, now))
+        {
+            self.close_current();
+        }
+        if self.current.is_none() {
+            if let Err(err) = self.open(now) {
+                self.fail(err, now);
+                return;
+            }
+        }
+
+        if let Some(current) = &mut self.current {
+            current.savefile.write(packet);
+            current.bytes = current.bytes.saturating_add(record);
+            self.bytes_written = self.bytes_written.saturating_add(record);
+        }
+    }
+
+    /// Flushes the current file. Returns `true` if the flush was performed.
+    pub fn tick(&mut self, now: Instant) -> bool {
+        if now.duration_since(self.last_flush) < FLUSH_INTERVAL {
+            return false;
+        }
+        self.last_flush = now;
+
+        if let Some(current) = &mut self.current {
+            if let Err(err) = current.savefile.flush() {
+                self.fail(PcapWriterError::PcapError(err), now);
+            }
+        }
+        true
+    }
+
+    /// File of the other link type is started with the next packet.
+    pub fn set_link_type(&mut self, link_type: Linktype) {
+        if self.link_type != link_type {
+            self.link_type = link_type;
+            self.close_current();
+        }
+    }
+
+    /// Used on shutdown.
+    pub fn flush(&mut self) {
+        self.close_current();
+    }
+
+    pub fn status(&self) -> PcapWriterDto {
+        PcapWriterDto {
+            directory: self.settings.directory.display().to_string(),
+            file: self
+                .current
+                .as_ref()
+                .and_then(|current| current.path.file_name())
+                .map(|name| name.to_string_lossy().to_string()),
+            bytes_written: self.bytes_written,
+            files_kept: self.files.len(),
+            error: self.error.clone(),
+        }
+    }
+
+    // Boundaries are checked only for the files with packets, so each file has one
+    fn is_rotation_due(&self, current: &CurrentFile, record: u64, now: Instant) -> bool {
+        if current.bytes <= FILE_HEADER_BYTES {
+            return false;
+        }
+        let is_full = self.settings.file_size > 0
+            && current.bytes.saturating_add(record) > self.settings.file_size;
+        let is_old = !self.settings.file_duration.is_zero()
+            && now.duration_since(current.opened) >= self.settings.file_duration;
+
+        is_full || is_old
+    }
+
+    fn open(&mut self, now: Instant) -> Result<(), PcapWriterError> {
+        fs::create_dir_all(&self.settings.directory).map_err(PcapWriterError::IOError)?;
+        self.remove_oldest();
+
+        let path = self.settings.directory.join(format!(
+            "{FILE_PREFIX}{:06}-{}.{FILE_EXTENSION}",
+            self.sequence,
+            unix_seconds()
+        ));
+        let savefile = pcap::Capture::dead(self.link_type)
+            .and_then(|capture| capture.savefile(&path))
+            .map_err(PcapWriterError::PcapError)?;
+        self.sequence = self.sequence.saturating_add(1);
+        self.files.push_back(path.clone());
+        self.current = Some(CurrentFile {
+            path,
+            savefile,
+            bytes: FILE_HEADER_BYTES,
+            opened: now,
+        });
+        self.bytes_written = self.bytes_written.saturating_add(FILE_HEADER_BYTES);
+
+        if self.error.take().is_some() {
+            self.failed_at = None;
+            log::info!("Pcap Writer: Writing is resumed.");
+        }
+        Ok(())
+    }
+
+    // Room for the new file
+    fn remove_oldest(&mut self) {
+        if self.settings.files_kept == 0 {
+            return;
+        }
+        while self.files.len() >= self.settings.files_kept {
+            let Some(path) = self.files.pop_front() else {
+                break;
+            };
+            if let Err(err) = fs::remove_file(&path) {
+                log::warn!("Pcap Writer: Failed to delete {}. {err}", path.display());
+            }
+        }
+    }
+
+    fn close_current(&mut self) {
+        if let Some(mut current) = self.current.take() {
+            if let Err(err) = current.savefile.flush() {
+                log::warn!(
+                    "Pcap Writer: Failed to flush {}. {err}",
+                    current.path.display()
+                );
+            }
+        }
+    }
+
+    fn fail(&mut self, err: PcapWriterError, now: Instant) {
+        log::error!(
+            "Pcap Writer: {err} Packets are skipped for {} s.",
+            RETRY_INTERVAL.as_secs()
+        );
+        self.current = None;
+        self.error = Some(err.to_string());
+        self.failed_at = Some(now);
+    }
+}
+
+// Files of the writer, sorted by their numbers
+fn existing_files(directory: &Path) -> Result<Vec<(u64, PathBuf)>, std::io::Error> {
+    let mut files = fs::read_dir(directory)?
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let path = entry.path();
+            let sequence = file_sequence(&path)?;
+            Some((sequence, path))
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+
+    Ok(files)
+}
+
+fn file_sequence(path: &Path) -> Option<u64> {
+    if path.extension()? != FILE_EXTENSION {
+        return None;
+    }
+    let stem = path.file_stem()?.to_str()?;
+    let (sequence, _) = stem.strip_prefix(FILE_PREFIX)?.split_once('-')?;
+    sequence.parse().ok()
+}
+
+fn unix_seconds() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+#[derive(Debug, Error)]
+pub enum PcapWriterError {
+    #[error("IO Error. {0}")]
+    IOError(std::io::Error),
+
+    #[error("Pcap library error. {0}")]
+    PcapError(pcap::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dpi::dto::frame::FrameHeader;
+
+    fn directory(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "xailyser-pcap-writer-{}-{name}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&path);
+        path
+    }
+
+    fn settings(directory: &Path, file_size: u64, files_kept: usize) -> RotationSettings {
+        RotationSettings {
+            directory: directory.to_path_buf(),
+            file_size,
+            file_duration: Duration::ZERO,
+            files_kept,
+        }
+    }
+
+    fn write(writer: &mut PcapWriter, second: i64, now: Instant) {
+        let data = [0u8; 60];
+        let header = pcap::PacketHeader::from(&FrameHeader {
+            tv_sec: second,
+            tv_usec: 0,
+            caplen: 60,
+            len: 60,
+        });
+        writer.write(&Packet::new(&header, &data), now);
+    }
+
+    fn packets(path: &Path) -> Vec<i64> {
+        let mut capture = pcap::Capture::from_file(path).unwrap();
+        let mut seconds = vec![];
+        while let Ok(packet) = capture.next_packet() {
+            seconds.push(packet.header.ts.tv_sec);
+        }
+        seconds
+    }
+
+    #[test]
+    fn test_rotation_by_size() {
+        let directory = directory("size");
+        // Header & two packets
+        let file_size = FILE_HEADER_BYTES + 2 * (RECORD_HEADER_BYTES + 60);
+        let mut writer =
+            PcapWriter::new(settings(&directory, file_size, 3), Linktype::ETHERNET);
+        let now = Instant::now();
+        for second in 0..9 {
+            write(&mut writer, second, now);
+        }
+        writer.flush();
+
+        // Two packets in each file, the oldest files are deleted
+        let files = existing_files(&directory).unwrap();
+        let numbers: Vec<u64> = files.iter().map(|(sequence, _)| *sequence).collect();
+        assert_eq!(numbers, vec![2, 3, 4]);
+        let contents: Vec<Vec<i64>> =
+            files.iter().map(|(_, path)| packets(path)).collect();
+        assert_eq!(contents, vec![vec![4, 5], vec![6, 7], vec![8]]);
+
+        let status = writer.status();
+        assert_eq!(status.files_kept, 3);
+        assert!(status.error.is_none());
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_rotation_by_time() {
+        let directory = directory("time");
+        let mut settings = settings(&directory, 0, 0);
+        settings.file_duration = Duration::from_secs(60);
+        let mut writer = PcapWriter::new(settings, Linktype::ETHERNET);
+
+        let now = Instant::now();
+        write(&mut writer, 0, now);
+        write(&mut writer, 1, now + Duration::from_secs(59));
+        write(&mut writer, 2, now + Duration::from_secs(60));
+        // Other link type starts the new file
+        writer.set_link_type(Linktype::LINUX_SLL);
+        write(&mut writer, 3, now + Duration::from_secs(61));
+        writer.flush();
+
+        let files = existing_files(&directory).unwrap();
+        let contents: Vec<Vec<i64>> =
+            files.iter().map(|(_, path)| packets(path)).collect();
+        assert_eq!(contents, vec![vec![0, 1], vec![2], vec![3]]);
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_retention_across_runs() {
+        let directory = directory("runs");
+        let now = Instant::now();
+        let mut writer = PcapWriter::new(settings(&directory, 1, 2), Linktype::ETHERNET);
+        for second in 0..2 {
+            write(&mut writer, second, now);
+        }
+        writer.flush();
+        drop(writer);
+
+        // Numbers are continued, files of the previous run are deleted first
+        let mut writer = PcapWriter::new(settings(&directory, 1, 2), Linktype::ETHERNET);
+        write(&mut writer, 2, now);
+        writer.flush();
+        let files = existing_files(&directory).unwrap();
+        let numbers: Vec<u64> = files.iter().map(|(sequence, _)| *sequence).collect();
+        assert_eq!(numbers, vec![1, 2]);
+        assert_eq!(writer.status().files_kept, 2);
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    fn test_failure_is_retried() {
+        // File in place of the directory
+        let directory = directory("failure");
+        fs::write(&directory, b"").unwrap();
+        let now = Instant::now();
+        let mut writer = PcapWriter::new(settings(&directory, 0, 0), Linktype::ETHERNET);
+        write(&mut writer, 0, now);
+        assert!(writer.status().error.is_some());
+        assert!(writer.status().file.is_none());
+
+        // Packets are skipped until the retry
+        fs::remove_file(&directory).unwrap();
+        write(&mut writer, 1, now + Duration::from_secs(1));
+        assert!(writer.status().error.is_some());
+        write(&mut writer, 2, now + RETRY_INTERVAL);
+        assert!(writer.status().error.is_none());
+        writer.flush();
+
+        let files = existing_files(&directory).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(packets(&files[0].1), vec![2]);
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,239 @@
+// Frames, that the parser fails on, kept as the examples for the parser tests.
+// They're bucketed by the protocol & the reason of the failure, every bucket keeps only
+// its first frames. Amount of the buckets and the bytes of the frames are limited,
+// so the memory doesn't grow with the capture. Failures over the limits are only counted.
+
+use common::messages::{FailureBucketDto, FailureSamplesDto};
+use dpi::dto::frame::OwnedFrame;
+use dpi::parser::{LayerFailure, ParserError};
+use dpi::protocols::ProtocolId;
+
+pub const SAMPLES_PER_BUCKET: usize = 5;
+pub const BUCKETS_LIMIT: usize = 32;
+// Bytes of the kept frames, of all the buckets
+pub const SAMPLES_BYTES_LIMIT: usize = 4 * 1024 * 1024;
+
+#[derive(Default)]
+pub struct FailureSamples {
+    buckets: Vec<Bucket>,
+    bytes: usize,
+    unbucketed: u64,
+    // Incremented on the reset, so the samples found before it aren't merged
+    generation: u64,
+}
+
+struct Bucket {
+    protocol: ProtocolId,
+    reason: String,
+    failures: u64,
+    samples: Vec<OwnedFrame>,
+}
+
+impl FailureSamples {
+    /// Frame is made only if it's kept.
+    pub fn record(&mut self, failure: &LayerFailure, frame: impl FnOnce() -> OwnedFrame) {
+        let reason = reason(&failure.error);
+        let Some(index) = self.bucket(failure.protocol, reason) else {
+            self.unbucketed = self.unbucketed.saturating_add(1);
+            return;
+        };
+        let bytes = self.bytes;
+        let Some(bucket) = self.buckets.get_mut(index) else {
+            return;
+        };
+        bucket.failures = bucket.failures.saturating_add(1);
+        if bucket.samples.len() >= SAMPLES_PER_BUCKET {
+            return;
+        }
+
+        let frame = frame();
+        let bytes = bytes.saturating_add(frame.data.len());
+        if bytes <= SAMPLES_BYTES_LIMIT {
+            bucket.samples.push(frame);
+            self.bytes = bytes;
+        }
+    }
+
+    /// Samples found before the reset are dropped.
+    pub fn merge(&mut self, found: FailureSamples, generation: u64) {
+        if generation != self.generation {
+            return;
+        }
+        self.unbucketed = self.unbucketed.saturating_add(found.unbucketed);
+
+        for other in found.buckets {
+            let Some(index) = self.bucket(other.protocol, other.reason) else {
+                self.unbucketed = self.unbucketed.saturating_add(other.failures);
+                continue;
+            };
+            let Some(bucket) = self.buckets.get_mut(index) else {
+                continue;
+            };
+            bucket.failures = bucket.failures.saturating_add(other.failures);
+            for frame in other.samples {
+                let bytes = self.bytes.saturating_add(frame.data.len());
+                if bucket.samples.len() >= SAMPLES_PER_BUCKET
+                    || bytes > SAMPLES_BYTES_LIMIT
+                {
+                    break;
+                }
+                bucket.samples.push(frame);
+                self.bytes = bytes;
+            }
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.buckets.clear();
+        self.bytes = 0;
+        self.unbucketed = 0;
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buckets.is_empty() && self.unbucketed == 0
+    }
+
+    pub fn to_dto(&self, link_type: Option<i32>) -> FailureSamplesDto {
+        FailureSamplesDto {
+            link_type,
+            buckets: self
+                .buckets
+                .iter()
+                .map(|bucket| FailureBucketDto {
+                    protocol: bucket.protocol,
+                    reason: bucket.reason.clone(),
+                    failures: bucket.failures,
+                    samples: bucket.samples.clone(),
+                })
+                .collect(),
+            unbucketed: self.unbucketed,
+        }
+    }
+
+    // New bucket is added, if the limit isn't reached
+    fn bucket(&mut self, protocol: ProtocolId, reason: String) -> Option<usize> {
+        let found = self
+            .buckets
+            .iter()
+            .position(|bucket| bucket.protocol == protocol && bucket.reason == reason);
+        if found.is_some() || self.buckets.len() >= BUCKETS_LIMIT {
+            return found;
+        }
+
+        self.buckets.push(Bucket {
+            protocol,
+            reason,
+            failures: 0,
+            samples: vec![],
+        });
+        Some(self.buckets.len().saturating_sub(1))
+    }
+}
+
+// Lengths of the truncated layers differ from frame to frame, so they aren't the reason
+fn reason(error: &ParserError) -> String {
+    match error {
+        ParserError::Truncated { .. } => "Truncated.".to_string(),
+        _ => error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dpi::dto::frame::FrameHeader;
+    use strum::IntoEnumIterator;
+
+    fn frame(length: usize) -> OwnedFrame {
+        OwnedFrame {
+            header: FrameHeader {
+                tv_sec: 1_700_000_000,
+                tv_usec: 0,
+                caplen: length as u32,
+                len: length as u32,
+            },
+            data: vec![0xAB; length],
+        }
+    }
+
+    fn failure(protocol: ProtocolId, error: ParserError) -> LayerFailure {
+        LayerFailure { protocol, error }
+    }
+
+    #[test]
+    fn test_buckets() {
+        let mut samples = FailureSamples::default();
+        for needed in 0..10 {
+            let error = ParserError::Truncated {
+                needed: 20 + needed,
+                available: 10,
+            };
+            samples.record(&failure(ProtocolId::IPv4, error), || frame(60));
+        }
+        let invalid = ParserError::InvalidValue { field: "opcode" };
+        samples.record(&failure(ProtocolId::DNS, invalid.clone()), || frame(80));
+
+        let dto = samples.to_dto(Some(1));
+        assert_eq!(dto.buckets.len(), 2);
+        // Truncated by the different lengths are the same bucket
+        assert_eq!(dto.buckets[0].protocol, ProtocolId::IPv4);
+        assert_eq!(dto.buckets[0].failures, 10);
+        assert_eq!(dto.buckets[0].samples.len(), SAMPLES_PER_BUCKET);
+        assert_eq!(dto.buckets[1].reason, invalid.to_string());
+        assert_eq!(dto.buckets[1].samples, vec![frame(80)]);
+    }
+
+    #[test]
+    fn test_limits() {
+        let mut samples = FailureSamples::default();
+        let mut recorded: u64 = 0;
+        for protocol in ProtocolId::iter() {
+            for field in ["type", "length", "flags"] {
+                let error = ParserError::InvalidValue { field };
+                samples.record(&failure(protocol, error), || frame(1500));
+                recorded += 1;
+            }
+        }
+        assert!(recorded > BUCKETS_LIMIT as u64);
+        assert_eq!(samples.buckets.len(), BUCKETS_LIMIT);
+        assert_eq!(samples.unbucketed, recorded - BUCKETS_LIMIT as u64);
+
+        // Large frames aren't kept over the limit of the bytes, but they're counted
+        let mut samples = FailureSamples::default();
+        let large = SAMPLES_BYTES_LIMIT / 2 + 1;
+        for _ in 0..3 {
+            samples.record(&failure(ProtocolId::HTTP, ParserError::ErrorVerify), || {
+                frame(large)
+            });
+        }
+        let dto = samples.to_dto(None);
+        assert_eq!(dto.buckets[0].failures, 3);
+        assert_eq!(dto.buckets[0].samples.len(), 1);
+    }
+
+    #[test]
+    fn test_merge_after_reset() {
+        let mut kept = FailureSamples::default();
+        let mut found = FailureSamples::default();
+        let error = || failure(ProtocolId::UDP, ParserError::ErrorVerify);
+        found.record(&error(), || frame(42));
+
+        let generation = kept.generation();
+        kept.reset();
+        kept.merge(found, generation);
+        assert!(kept.is_empty());
+
+        let mut found = FailureSamples::default();
+        found.record(&error(), || frame(42));
+        found.record(&error(), || frame(43));
+        kept.merge(found, kept.generation());
+        let dto = kept.to_dto(None);
+        assert_eq!(dto.buckets[0].failures, 2);
+        assert_eq!(dto.buckets[0].samples, vec![frame(42), frame(43)]);
+    }
+}
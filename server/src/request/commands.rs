@@ -1,7 +1,47 @@
 use crate::net;
-use common::messages::ServerError;
+use crate::net::interface::InterfaceError;
+use common::messages::{InterfaceDto, ServerError};
 
-pub fn interfaces() -> Result<Vec<String>, ServerError> {
+/// Calls to the host system made by the request handlers. <br>
+/// Replaced in tests, so they don't need a real network interface.
+pub trait System {
+    fn interfaces(&self) -> Result<Vec<InterfaceDto>, ServerError>;
+
+    /// Finds the interface and opens the capture on it to get its link type.
+    fn open_interface(
+        &self, name: &str,
+    ) -> Result<(pcap::Device, pcap::Linktype), InterfaceError>;
+
+    fn exit_reboot(&self);
+}
+
+pub struct Host;
+
+impl System for Host {
+    fn interfaces(&self) -> Result<Vec<InterfaceDto>, ServerError> {
+        interfaces()
+    }
+
+    fn open_interface(
+        &self, name: &str,
+    ) -> Result<(pcap::Device, pcap::Linktype), InterfaceError> {
+        let network_interface = net::interface::get_network_interface(name)?;
+        let capture = net::interface::get_capture(
+            network_interface.clone(),
+            100,
+            &net::interface::PROBE_OPTIONS,
+        )?;
+
+        Ok((network_interface, capture.get_datalink()))
+    }
+
+    fn exit_reboot(&self) {
+        exit_reboot();
+    }
+}
+
+// Described each time, so the state & addresses are up to date
+pub fn interfaces() -> Result<Vec<InterfaceDto>, ServerError> {
     let list = match net::interface::usable_sorted() {
         Ok(list) => list,
         Err(err) => {
@@ -12,7 +52,7 @@ pub fn interfaces() -> Result<Vec<String>, ServerError> {
 
     let interfaces = list
         .into_iter()
-        .map(|interface| net::interface::get_network_interface_name(&interface))
+        .map(|interface| net::link::describe(&interface))
         .collect();
     Ok(interfaces)
 }
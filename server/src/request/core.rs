@@ -1,108 +1,454 @@
+use crate::config::Config;
 use crate::context::Context;
 use crate::net::interface;
-use crate::request::commands;
-use common::messages::{Request, Response, ServerError, ServerSettingsDto};
+use crate::net::mirror::MirrorSwitch;
+use crate::request::commands::{Host, System};
+use common::messages::{
+    CaptureOptionsDto, Request, RequestKind, Response, SEND_DELAY_MAX_MS, ServerError,
+    ServerSettingsDto,
+};
+use dpi::parser::ProtocolParser;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
+pub type Handler = fn(
+    request: &Request,
+    context: &Arc<Mutex<Context>>,
+    shutdown_flag: &Arc<AtomicBool>,
+    system: &dyn System,
+) -> Option<Response>;
+
+/// Dispatch table: Every request kind is processed by its own handler.
+const HANDLERS: &[(RequestKind, Handler)] = &[
+    (RequestKind::ChangePassword, change_password),
+    (RequestKind::GetFailureSamples, get_failure_samples),
+    (RequestKind::Logout, logout),
+    (RequestKind::Reboot, reboot),
+    (RequestKind::ResetFailureSamples, reset_failure_samples),
+    (RequestKind::ResetStatistics, reset_statistics),
+    (RequestKind::SaveConfig, save_config),
+    (RequestKind::ServerSettings, server_settings),
+    (RequestKind::SetCaptureBufferSize, set_capture_buffer_size),
+    (
+        RequestKind::SetCaptureImmediateMode,
+        set_capture_immediate_mode,
+    ),
+    (RequestKind::SetCapturePromiscuous, set_capture_promiscuous),
+    (RequestKind::SetCaptureSnaplen, set_capture_snaplen),
+    (RequestKind::SetCompression, set_compression),
+    (RequestKind::SetInterface, set_interface),
+    (RequestKind::SetMirror, set_mirror),
+    (RequestKind::SetProtocolPorts, set_protocol_ports),
+    (RequestKind::SetSchedule, set_schedule),
+    (RequestKind::SetSendDelay, set_send_delay),
+    (RequestKind::SetSendUnparsedFrames, set_send_unparsed_frames),
+];
+
 pub fn process(
     request: Request, context: &Arc<Mutex<Context>>, shutdown_flag: &Arc<AtomicBool>,
 ) -> Option<Response> {
-    match request {
-        Request::ChangePassword(password) => {
-            let response = lock_with_response(context, |ctx| {
-                ctx.change_password(password);
-                Response::SuccessChangePassword
-            });
+    dispatch(&request, context, shutdown_flag, &Host)
+}
 
-            Some(response)
+fn dispatch(
+    request: &Request, context: &Arc<Mutex<Context>>, shutdown_flag: &Arc<AtomicBool>,
+    system: &dyn System,
+) -> Option<Response> {
+    let kind = RequestKind::from(request);
+    match HANDLERS
+        .iter()
+        .find(|(handler_kind, _)| *handler_kind == kind)
+    {
+        Some((_, handler)) => handler(request, context, shutdown_flag, system),
+        None => {
+            log::error!("Request Processing: There's no handler for {kind:?}.");
+            None
         },
+    }
+}
 
-        Request::Reboot => {
-            shutdown_flag.store(true, Ordering::Release);
-            commands::exit_reboot();
+fn change_password(
+    request: &Request, context: &Arc<Mutex<Context>>, _: &Arc<AtomicBool>, _: &dyn System,
+) -> Option<Response> {
+    let Request::ChangePassword(password) = request else {
+        return None;
+    };
 
-            None
-        },
+    let response =
+        lock_with_response(context, |ctx| match ctx.change_password(password) {
+            // Issuing client keeps its session, the other connections are closed
+            Ok(()) => Response::SuccessChangePassword(ctx.session_tokens.issue()),
+            Err(err) => {
+                log::error!("Failed to change password. {err}");
+                Response::Error(ServerError::FailedToChangePassword)
+            },
+        });
 
-        Request::SaveConfig => {
-            let response =
-                lock_with_response(context, |ctx| match ctx.config.save_to_file() {
-                    Ok(_) => Response::SuccessSaveConfig,
-                    Err(_) => Response::Error(ServerError::FailedToSaveConfig),
-                });
-            Some(response)
-        },
+    Some(response)
+}
 
-        Request::ServerSettings => {
-            let interfaces_available = match commands::interfaces() {
-                Ok(interfaces) => interfaces,
-                Err(err) => return Some(Response::Error(err)),
-            };
+fn get_failure_samples(
+    _: &Request, context: &Arc<Mutex<Context>>, _: &Arc<AtomicBool>, _: &dyn System,
+) -> Option<Response> {
+    let response = lock_with_response(context, |ctx| {
+        if !ctx.config.failure_samples_enabled {
+            return Response::Error(ServerError::FailureSamplesDisabled);
+        }
+        let link_type = ctx.link_type.map(|lt| lt.0);
+        Response::FailureSamples(ctx.failure_samples.to_dto(link_type))
+    });
 
-            let response = lock_with_response(context, |ctx| {
-                let dto = ServerSettingsDto {
-                    compression_active: ctx.compression,
-                    compression_config: ctx.config.compression,
-                    interface_active: ctx
-                        .network_interface
-                        .as_ref()
-                        .map(interface::get_network_interface_name),
-                    interface_config: ctx.config.interface.clone(),
-                    interfaces_available,
-                    link_type: ctx.link_type.map(|lt| lt.0),
-                    send_unparsed_frames_active: ctx.send_unparsed_frames,
-                    send_unparsed_frames_config: ctx.config.send_unparsed_frames,
-                };
-
-                Response::ServerSettings(dto)
-            });
-            Some(response)
-        },
+    Some(response)
+}
 
-        Request::SetCompression(is_compression_enabled) => {
-            let response = lock_with_response(context, |ctx| {
-                ctx.config.compression = is_compression_enabled;
-                Response::SuccessSetCompression(is_compression_enabled)
-            });
+fn logout(
+    request: &Request, context: &Arc<Mutex<Context>>, _: &Arc<AtomicBool>, _: &dyn System,
+) -> Option<Response> {
+    let Request::Logout(token) = request else {
+        return None;
+    };
 
-            Some(response)
+    match context.lock() {
+        Ok(mut ctx) => ctx.session_tokens.revoke(token),
+        Err(err) => {
+            log::error!("Request Processing: Error locking mutex on context. {err}")
         },
+    }
 
-        Request::SetInterface(interface_name) => {
-            let network_interface =
-                match interface::get_network_interface(&interface_name) {
-                    Ok(interface) => interface,
-                    Err(err) => {
-                        log::error!("Request Processing: Network Interface error. {err}");
-                        let response = Response::Error(ServerError::InvalidInterface);
-                        return Some(response);
-                    },
-                };
-
-            let result = interface::get_capture(network_interface.clone(), 100);
-            if let Err(err) = result {
-                log::error!("Request Processing: Network Interface error. {err}");
-                let response = Response::Error(ServerError::InvalidInterface);
-                return Some(response);
-            }
+    None
+}
 
-            let response = lock_with_response(context, |ctx| {
-                ctx.change_config_network_interface(network_interface);
-                Response::SuccessSetInterface(interface_name)
-            });
-            Some(response)
-        },
+fn reboot(
+    _: &Request, context: &Arc<Mutex<Context>>, shutdown_flag: &Arc<AtomicBool>,
+    system: &dyn System,
+) -> Option<Response> {
+    shutdown_flag.store(true, Ordering::Release);
+    // Process exits right away, the threads don't save them on the shutdown
+    if let Ok(mut ctx) = context.lock() {
+        if let Err(err) = ctx.statistics.persist() {
+            log::error!("Statistics: Failed to save before reboot. {err}");
+        }
+    }
+    system.exit_reboot();
+
+    None
+}
+
+fn reset_failure_samples(
+    _: &Request, context: &Arc<Mutex<Context>>, _: &Arc<AtomicBool>, _: &dyn System,
+) -> Option<Response> {
+    let response = lock_with_response(context, |ctx| {
+        if !ctx.config.failure_samples_enabled {
+            return Response::Error(ServerError::FailureSamplesDisabled);
+        }
+        ctx.failure_samples.reset();
+        log::info!("Failure Samples: Reset by the client.");
+        let link_type = ctx.link_type.map(|lt| lt.0);
+        Response::FailureSamples(ctx.failure_samples.to_dto(link_type))
+    });
+
+    Some(response)
+}
+
+fn reset_statistics(
+    _: &Request, context: &Arc<Mutex<Context>>, _: &Arc<AtomicBool>, _: &dyn System,
+) -> Option<Response> {
+    let response = lock_with_response(context, |ctx| {
+        ctx.statistics.reset();
+        match ctx.statistics.persist() {
+            Ok(_) => Response::SuccessResetStatistics(ctx.statistics.to_dto()),
+            Err(err) => {
+                log::error!("Statistics: Failed to save after reset. {err}");
+                Response::Error(ServerError::FailedToSaveStatistics)
+            },
+        }
+    });
+
+    Some(response)
+}
+
+fn save_config(
+    _: &Request, context: &Arc<Mutex<Context>>, _: &Arc<AtomicBool>, _: &dyn System,
+) -> Option<Response> {
+    let response = lock_with_response(context, |ctx| match ctx.config.save_to_file() {
+        Ok(_) => Response::SuccessSaveConfig,
+        Err(_) => Response::Error(ServerError::FailedToSaveConfig),
+    });
 
-        Request::SetSendUnparsedFrames(is_sending_enabled) => {
-            let response = lock_with_response(context, |ctx| {
-                ctx.config.send_unparsed_frames = is_sending_enabled;
-                Response::SuccessSetSendUnparsedFrames(is_sending_enabled)
-            });
+    Some(response)
+}
+
+fn server_settings(
+    _: &Request, context: &Arc<Mutex<Context>>, _: &Arc<AtomicBool>, system: &dyn System,
+) -> Option<Response> {
+    let interfaces_available = match system.interfaces() {
+        Ok(interfaces) => interfaces,
+        Err(err) => return Some(Response::Error(err)),
+    };
 
-            Some(response)
+    let response = lock_with_response(context, |ctx| {
+        let dto = ServerSettingsDto {
+            capture_options_active: ctx.capture_options,
+            capture_options_config: ctx.config.capture_options(),
+            // Overwritten by the connection, that sends the settings
+            compression_active: ctx.config.compression,
+            compression_config: ctx.config.compression,
+            credentials_active: ctx.credentials_revision,
+            credentials_config: ctx.credentials_revision,
+            failure_samples_config: ctx.config.failure_samples_enabled,
+            flow_export_active: ctx.flow_exporter.clone(),
+            flow_export_config: ctx.config.flow_export_enabled,
+            interface_active: ctx
+                .network_interface
+                .as_ref()
+                .map(interface::get_network_interface_name),
+            interface_config: ctx.config.interface.clone(),
+            interfaces_available,
+            link_type: ctx.link_type.map(|lt| lt.0),
+            mirror_active: ctx.mirror.clone(),
+            pcap_writer_active: ctx.pcap_writer.clone(),
+            pcap_writer_config: ctx.config.pcap_writer_enabled,
+            protocol_ports: ctx.config.protocol_ports.clone(),
+            schedule: ctx.config.capture_schedule.clone(),
+            schedule_status: ctx.capture_schedule,
+            // Overwritten by the connection, that sends the settings
+            send_delay_active: ctx.config.send_delay_ms,
+            send_delay_config: ctx.config.send_delay_ms,
+            send_unparsed_frames_active: ctx.send_unparsed_frames,
+            send_unparsed_frames_config: ctx.config.send_unparsed_frames,
+        };
+
+        Response::ServerSettings(dto)
+    });
+
+    Some(response)
+}
+
+fn set_capture_buffer_size(
+    request: &Request, context: &Arc<Mutex<Context>>, _: &Arc<AtomicBool>, _: &dyn System,
+) -> Option<Response> {
+    let Request::SetCaptureBufferSize(buffer_size) = *request else {
+        return None;
+    };
+    if !CaptureOptionsDto::is_valid_buffer_size(buffer_size) {
+        return Some(Response::Error(ServerError::InvalidCaptureOption));
+    }
+
+    let response = set_capture_option(context, |config| {
+        config.capture_buffer_size = buffer_size;
+    });
+
+    Some(response)
+}
+
+fn set_capture_immediate_mode(
+    request: &Request, context: &Arc<Mutex<Context>>, _: &Arc<AtomicBool>, _: &dyn System,
+) -> Option<Response> {
+    let Request::SetCaptureImmediateMode(is_enabled) = *request else {
+        return None;
+    };
+
+    let response = set_capture_option(context, |config| {
+        config.capture_immediate_mode = is_enabled;
+    });
+
+    Some(response)
+}
+
+fn set_capture_promiscuous(
+    request: &Request, context: &Arc<Mutex<Context>>, _: &Arc<AtomicBool>, _: &dyn System,
+) -> Option<Response> {
+    let Request::SetCapturePromiscuous(is_enabled) = *request else {
+        return None;
+    };
+
+    let response = set_capture_option(context, |config| {
+        config.capture_promiscuous = is_enabled;
+    });
+
+    Some(response)
+}
+
+fn set_capture_snaplen(
+    request: &Request, context: &Arc<Mutex<Context>>, _: &Arc<AtomicBool>, _: &dyn System,
+) -> Option<Response> {
+    let Request::SetCaptureSnaplen(snaplen) = *request else {
+        return None;
+    };
+    if !CaptureOptionsDto::is_valid_snaplen(snaplen) {
+        return Some(Response::Error(ServerError::InvalidCaptureOption));
+    }
+
+    let response = set_capture_option(context, |config| {
+        config.capture_snaplen = snaplen;
+    });
+
+    Some(response)
+}
+
+// Capture options are applied on the next interface open
+fn set_capture_option(
+    context: &Arc<Mutex<Context>>, f: impl FnOnce(&mut Config),
+) -> Response {
+    lock_with_response(context, |ctx| {
+        f(&mut ctx.config);
+        Response::SuccessSetCaptureOptions(ctx.config.capture_options())
+    })
+}
+
+fn set_compression(
+    request: &Request, context: &Arc<Mutex<Context>>, _: &Arc<AtomicBool>, _: &dyn System,
+) -> Option<Response> {
+    let Request::SetCompression(is_compression_enabled) = *request else {
+        return None;
+    };
+
+    // Connected clients can't switch the compression of the running connection,
+    // so only the new connections are affected
+    let response = lock_with_response(context, |ctx| {
+        let current = ctx.config.compression;
+        ctx.config.compression = is_compression_enabled;
+        ctx.settings_revision = ctx.settings_revision.wrapping_add(1);
+        Response::SuccessSetCompression {
+            enabled: is_compression_enabled,
+            current,
+        }
+    });
+
+    Some(response)
+}
+
+fn set_interface(
+    request: &Request, context: &Arc<Mutex<Context>>, _: &Arc<AtomicBool>,
+    system: &dyn System,
+) -> Option<Response> {
+    let Request::SetInterface(interface_name) = request else {
+        return None;
+    };
+
+    let (network_interface, link_type) = match system.open_interface(interface_name) {
+        Ok(value) => value,
+        Err(err) => {
+            log::error!("Request Processing: Network Interface error. {err}");
+            return Some(Response::Error(ServerError::InvalidInterface));
         },
+    };
+    if !ProtocolParser::is_supported(link_type) {
+        log::error!(
+            "Request Processing: Interface {interface_name} has unsupported link type {}.",
+            link_type.0
+        );
+        let response = Response::Error(ServerError::UnsupportedLinkType(link_type.0));
+        return Some(response);
+    }
+
+    let response = lock_with_response(context, |ctx| {
+        ctx.change_config_network_interface(network_interface.clone());
+        // Running capture is switched by the sniffer
+        if ctx.link_type.is_some() {
+            ctx.interface_switch = Some(network_interface);
+        }
+        Response::SuccessSetInterface(interface_name.clone())
+    });
+
+    Some(response)
+}
+
+fn set_mirror(
+    request: &Request, context: &Arc<Mutex<Context>>, _: &Arc<AtomicBool>, _: &dyn System,
+) -> Option<Response> {
+    let Request::SetMirror {
+        enabled,
+        target,
+        filter,
+    } = request
+    else {
+        return None;
+    };
+    if *enabled && (target.ip().is_unspecified() || target.port() == 0) {
+        return Some(Response::Error(ServerError::InvalidMirrorTarget(*target)));
     }
+
+    let response = lock_with_response(context, |ctx| {
+        // Running capture is mirrored by the sniffer. Without it, the switch waits
+        ctx.mirror_switch = match *enabled {
+            true => Some(MirrorSwitch::Start {
+                target: *target,
+                filter: filter.clone(),
+            }),
+            false => Some(MirrorSwitch::Stop),
+        };
+        Response::SuccessSetMirror(*enabled)
+    });
+
+    Some(response)
+}
+
+fn set_protocol_ports(
+    request: &Request, context: &Arc<Mutex<Context>>, _: &Arc<AtomicBool>, _: &dyn System,
+) -> Option<Response> {
+    let Request::SetProtocolPorts(protocol, ports) = request else {
+        return None;
+    };
+
+    let response = lock_with_response(context, |ctx| {
+        if ctx.config.protocol_ports.set(*protocol, ports).is_err() {
+            return Response::Error(ServerError::InvalidProtocolPorts(*protocol));
+        }
+        // Running parser is updated by the sniffer
+        ctx.protocol_ports_switch = Some(ctx.config.protocol_ports.clone());
+        Response::SuccessSetProtocolPorts(ctx.config.protocol_ports.clone())
+    });
+
+    Some(response)
+}
+
+fn set_schedule(
+    request: &Request, context: &Arc<Mutex<Context>>, _: &Arc<AtomicBool>, _: &dyn System,
+) -> Option<Response> {
+    let Request::SetSchedule(windows) = request else {
+        return None;
+    };
+
+    // Running capture is paused or resumed by the sniffer on its next check
+    let response = lock_with_response(context, |ctx| {
+        ctx.config.capture_schedule = windows.clone();
+        ctx.settings_revision = ctx.settings_revision.wrapping_add(1);
+        Response::SuccessSetSchedule(windows.clone())
+    });
+
+    Some(response)
+}
+
+// Applied by the connection, that is asked. The config value is for the new connections
+fn set_send_delay(
+    request: &Request, _: &Arc<Mutex<Context>>, _: &Arc<AtomicBool>, _: &dyn System,
+) -> Option<Response> {
+    let Request::SetSendDelay(delay) = *request else {
+        return None;
+    };
+
+    let response = match delay > SEND_DELAY_MAX_MS {
+        true => Response::Error(ServerError::InvalidSendDelay(delay)),
+        false => Response::SuccessSetSendDelay(delay),
+    };
+
+    Some(response)
+}
+
+fn set_send_unparsed_frames(
+    request: &Request, context: &Arc<Mutex<Context>>, _: &Arc<AtomicBool>, _: &dyn System,
+) -> Option<Response> {
+    let Request::SetSendUnparsedFrames(is_sending_enabled) = *request else {
+        return None;
+    };
+
+    let response = lock_with_response(context, |ctx| {
+        ctx.config.send_unparsed_frames = is_sending_enabled;
+        Response::SuccessSetSendUnparsedFrames(is_sending_enabled)
+    });
+
+    Some(response)
 }
 
 fn lock_with_response(
@@ -116,3 +462,456 @@ fn lock_with_response(
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::interface::InterfaceError;
+    use crate::statistics;
+    use crate::statistics::{Statistics, Totals};
+    use chrono::{NaiveTime, Weekday};
+    use common::cryptography;
+    use common::messages::{InterfaceDto, MirrorFilter};
+    use common::schedule::{CaptureWindow, ScheduleStatus, ScheduleTimezone};
+    use dpi::protocols::ProtocolId;
+    use std::cell::Cell;
+    use std::net::{IpAddr, SocketAddr};
+    use strum::IntoEnumIterator;
+
+    struct MockSystem {
+        interfaces: Option<Vec<String>>,
+        link_type: pcap::Linktype,
+        rebooted: Cell<bool>,
+    }
+
+    impl Default for MockSystem {
+        fn default() -> Self {
+            Self {
+                interfaces: Some(vec!["eth0".to_string(), "wlan0".to_string()]),
+                link_type: pcap::Linktype::ETHERNET,
+                rebooted: Cell::new(false),
+            }
+        }
+    }
+
+    impl System for MockSystem {
+        fn interfaces(&self) -> Result<Vec<InterfaceDto>, ServerError> {
+            let names = self
+                .interfaces
+                .as_ref()
+                .ok_or(ServerError::FailedToGetInterfaces)?;
+            let interfaces = names
+                .iter()
+                .map(|name| InterfaceDto {
+                    name: name.clone(),
+                    addresses: vec![],
+                    mtu: Some(1500),
+                    speed_mbps: None,
+                    is_up: Some(true),
+                })
+                .collect();
+            Ok(interfaces)
+        }
+
+        fn open_interface(
+            &self, name: &str,
+        ) -> Result<(pcap::Device, pcap::Linktype), InterfaceError> {
+            match self.interfaces.iter().flatten().any(|known| known == name) {
+                true => Ok((pcap::Device::from(name), self.link_type)),
+                false => Err(InterfaceError::UnknownInterface),
+            }
+        }
+
+        fn exit_reboot(&self) {
+            self.rebooted.set(true);
+        }
+    }
+
+    fn context() -> Arc<Mutex<Context>> {
+        let context = Context::new(Config::default()).unwrap();
+        Arc::new(Mutex::new(context))
+    }
+
+    fn run(
+        request: Request, context: &Arc<Mutex<Context>>, system: &MockSystem,
+    ) -> Option<Response> {
+        dispatch(&request, context, &Arc::new(AtomicBool::new(false)), system)
+    }
+
+    #[test]
+    fn test_every_request_has_handler() {
+        for kind in RequestKind::iter() {
+            let handlers = HANDLERS
+                .iter()
+                .filter(|(handler_kind, _)| *handler_kind == kind);
+            assert_eq!(handlers.count(), 1, "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn test_failure_samples_disabled_by_default() {
+        let context = context();
+        let system = MockSystem::default();
+
+        for request in [Request::GetFailureSamples, Request::ResetFailureSamples] {
+            let response = run(request, &context, &system);
+            assert!(matches!(
+                response,
+                Some(Response::Error(ServerError::FailureSamplesDisabled))
+            ));
+        }
+
+        context.lock().unwrap().config.failure_samples_enabled = true;
+        let response = run(Request::GetFailureSamples, &context, &system);
+        assert!(matches!(
+            response,
+            Some(Response::FailureSamples(dto)) if dto.buckets.is_empty()
+        ));
+    }
+
+    #[test]
+    fn test_set_compression() {
+        let context = context();
+        let system = MockSystem::default();
+
+        let response = run(Request::SetCompression(false), &context, &system);
+        assert!(matches!(
+            response,
+            Some(Response::SuccessSetCompression {
+                enabled: false,
+                current: true,
+            })
+        ));
+
+        let ctx = context.lock().unwrap();
+        // New connections are checked against the config
+        assert!(!ctx.config.compression);
+        assert_eq!(ctx.settings_revision, 1);
+    }
+
+    #[test]
+    fn test_set_capture_options() {
+        let context = context();
+        let system = MockSystem::default();
+
+        let response = run(Request::SetCaptureSnaplen(128), &context, &system);
+        assert!(matches!(
+            response,
+            Some(Response::SuccessSetCaptureOptions(options)) if options.snaplen == 128
+        ));
+        run(Request::SetCapturePromiscuous(true), &context, &system);
+        run(Request::SetCaptureImmediateMode(false), &context, &system);
+        run(Request::SetCaptureBufferSize(4_194_304), &context, &system);
+
+        for request in [
+            Request::SetCaptureSnaplen(0),
+            Request::SetCaptureSnaplen(CaptureOptionsDto::SNAPLEN_MAX + 1),
+            Request::SetCaptureBufferSize(-1),
+        ] {
+            let response = run(request, &context, &system);
+            assert!(matches!(
+                response,
+                Some(Response::Error(ServerError::InvalidCaptureOption))
+            ));
+        }
+
+        let ctx = context.lock().unwrap();
+        assert_eq!(ctx.config.capture_snaplen, 128);
+        assert!(ctx.config.capture_promiscuous);
+        assert!(!ctx.config.capture_immediate_mode);
+        assert_eq!(ctx.config.capture_buffer_size, 4_194_304);
+        // Applied only on the next interface open
+        assert!(ctx.capture_options.is_none());
+    }
+
+    #[test]
+    fn test_set_protocol_ports() {
+        let context = context();
+        let system = MockSystem::default();
+
+        let request = Request::SetProtocolPorts(ProtocolId::HTTP, vec![8080, 80]);
+        let response = run(request, &context, &system);
+        assert!(matches!(
+            response,
+            Some(Response::SuccessSetProtocolPorts(table))
+                if table.get(&ProtocolId::HTTP) == Some([80, 8080].as_slice())
+        ));
+        {
+            let mut ctx = context.lock().unwrap();
+            assert!(ctx.protocol_ports_switch.take().is_some());
+            assert!(!ctx.config.protocol_ports.is_empty());
+        }
+
+        let response = run(
+            Request::SetProtocolPorts(ProtocolId::TCP, vec![8080]),
+            &context,
+            &system,
+        );
+        assert!(matches!(
+            response,
+            Some(Response::Error(ServerError::InvalidProtocolPorts(
+                ProtocolId::TCP
+            )))
+        ));
+        assert!(context.lock().unwrap().protocol_ports_switch.is_none());
+
+        // Empty list brings back the defaults
+        run(
+            Request::SetProtocolPorts(ProtocolId::HTTP, vec![]),
+            &context,
+            &system,
+        );
+        assert!(context.lock().unwrap().config.protocol_ports.is_empty());
+    }
+
+    #[test]
+    fn test_change_password() {
+        let context = context();
+        let system = MockSystem::default();
+        let token = context.lock().unwrap().session_tokens.issue().unwrap();
+
+        let response = run(Request::ChangePassword("secret".into()), &context, &system);
+        let Some(Response::SuccessChangePassword(Some(issued))) = response else {
+            panic!("Unexpected response: {response:?}");
+        };
+
+        let mut ctx = context.lock().unwrap();
+        // Stored in the new format, so the client's response is verified by it
+        let hash = &ctx.config.password;
+        let params = cryptography::challenge_params(hash).unwrap();
+        let nonce = cryptography::generate_nonce();
+        let key = cryptography::derive_key("secret", &params).unwrap();
+        assert!(cryptography::verify_signature(
+            &cryptography::stored_key(hash).unwrap(),
+            &nonce,
+            &cryptography::sign_nonce(&key, &nonce).unwrap()
+        ));
+        assert!(!ctx.session_tokens.is_valid(&token));
+        assert!(ctx.session_tokens.is_valid(&issued));
+        assert_eq!(ctx.credentials_revision, 1);
+    }
+
+    #[test]
+    fn test_set_interface() {
+        let context = context();
+        let system = MockSystem::default();
+
+        let response = run(Request::SetInterface("wlan0".into()), &context, &system);
+        assert!(
+            matches!(response, Some(Response::SuccessSetInterface(name)) if name == "wlan0")
+        );
+        {
+            let ctx = context.lock().unwrap();
+            assert_eq!(ctx.config.interface.as_deref(), Some("wlan0"));
+            // Capture isn't running, nothing to switch
+            assert!(ctx.interface_switch.is_none());
+        }
+
+        context.lock().unwrap().link_type = Some(pcap::Linktype::ETHERNET);
+        run(Request::SetInterface("eth0".into()), &context, &system);
+        let ctx = context.lock().unwrap();
+        assert_eq!(ctx.config.interface.as_deref(), Some("eth0"));
+        assert_eq!(
+            ctx.interface_switch
+                .as_ref()
+                .map(|device| device.name.as_str()),
+            Some("eth0")
+        );
+    }
+
+    #[test]
+    fn test_set_interface_errors() {
+        let context = context();
+        let mut system = MockSystem::default();
+
+        let response = run(Request::SetInterface("unknown".into()), &context, &system);
+        assert!(matches!(
+            response,
+            Some(Response::Error(ServerError::InvalidInterface))
+        ));
+
+        system.link_type = pcap::Linktype::LINUX_SLL;
+        let response = run(Request::SetInterface("wlan0".into()), &context, &system);
+        assert!(matches!(
+            response,
+            Some(Response::Error(ServerError::UnsupportedLinkType(113)))
+        ));
+        assert!(context.lock().unwrap().config.interface.is_none());
+    }
+
+    #[test]
+    fn test_reboot() {
+        let context = context();
+        let system = MockSystem::default();
+        let shutdown_flag = Arc::new(AtomicBool::new(false));
+
+        let response = dispatch(&Request::Reboot, &context, &shutdown_flag, &system);
+        assert!(response.is_none());
+        assert!(shutdown_flag.load(Ordering::Acquire));
+        assert!(system.rebooted.get());
+    }
+
+    #[test]
+    fn test_reset_statistics() {
+        let context = context();
+        let system = MockSystem::default();
+        let path = std::env::temp_dir().join(format!(
+            "xailyser-reset-statistics-{}.json",
+            std::process::id()
+        ));
+        {
+            let mut ctx = context.lock().unwrap();
+            ctx.statistics = Statistics::new().with_persistence(&path);
+            let generation = ctx.statistics.generation();
+            let counted = Totals {
+                frames: 10,
+                bytes: 1000,
+                ..Default::default()
+            };
+            ctx.statistics.merge(&counted, generation);
+            ctx.statistics.persist().unwrap();
+        }
+
+        let response = run(Request::ResetStatistics, &context, &system);
+        let Some(Response::SuccessResetStatistics(dto)) = response else {
+            panic!("Unexpected response: {response:?}");
+        };
+        assert_eq!(dto.frames, 0);
+        assert_eq!(dto.bytes, 0);
+        assert!(dto.persistent);
+        let saved = statistics::read_totals(&path).unwrap().unwrap();
+        assert_eq!(saved.frames, 0);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_set_mirror() {
+        let context = context();
+        let system = MockSystem::default();
+        let filter = MirrorFilter::Ip(IpAddr::from([192, 168, 1, 10]));
+
+        let target = SocketAddr::from(([10, 0, 0, 2], 37008));
+        let request = Request::SetMirror {
+            enabled: true,
+            target,
+            filter: filter.clone(),
+        };
+        let response = run(request, &context, &system);
+        assert!(matches!(response, Some(Response::SuccessSetMirror(true))));
+        assert_eq!(
+            context.lock().unwrap().mirror_switch.take(),
+            Some(MirrorSwitch::Start {
+                target,
+                filter: filter.clone()
+            })
+        );
+
+        for target in [
+            SocketAddr::from(([0, 0, 0, 0], 37008)),
+            SocketAddr::from(([10, 0, 0, 2], 0)),
+        ] {
+            let request = Request::SetMirror {
+                enabled: true,
+                target,
+                filter: filter.clone(),
+            };
+            assert!(matches!(
+                run(request, &context, &system),
+                Some(Response::Error(ServerError::InvalidMirrorTarget(_)))
+            ));
+        }
+        assert!(context.lock().unwrap().mirror_switch.is_none());
+
+        // Target isn't checked, when the mirror is disabled
+        let request = Request::SetMirror {
+            enabled: false,
+            target: SocketAddr::from(([0, 0, 0, 0], 0)),
+            filter,
+        };
+        let response = run(request, &context, &system);
+        assert!(matches!(response, Some(Response::SuccessSetMirror(false))));
+        assert_eq!(
+            context.lock().unwrap().mirror_switch,
+            Some(MirrorSwitch::Stop)
+        );
+    }
+
+    #[test]
+    fn test_set_schedule() {
+        let context = context();
+        let system = MockSystem::default();
+        let window = CaptureWindow {
+            days: vec![Weekday::Sat, Weekday::Sun],
+            start: NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(6, 0, 0).unwrap(),
+            timezone: ScheduleTimezone::Local,
+        };
+
+        let response = run(
+            Request::SetSchedule(vec![window.clone()]),
+            &context,
+            &system,
+        );
+        assert!(matches!(
+            response,
+            Some(Response::SuccessSetSchedule(windows)) if windows == vec![window.clone()]
+        ));
+        let response = run(Request::ServerSettings, &context, &system);
+        let Some(Response::ServerSettings(dto)) = response else {
+            panic!("Unexpected response: {response:?}");
+        };
+        assert_eq!(dto.schedule, vec![window]);
+        // Status is published by the sniffer
+        assert_eq!(dto.schedule_status, ScheduleStatus::default());
+        assert_eq!(context.lock().unwrap().settings_revision, 1);
+    }
+
+    #[test]
+    fn test_set_send_delay() {
+        let context = context();
+        let system = MockSystem::default();
+
+        let response = run(Request::SetSendDelay(200), &context, &system);
+        assert!(matches!(response, Some(Response::SuccessSetSendDelay(200))));
+        let response = run(Request::SetSendDelay(201), &context, &system);
+        assert!(matches!(
+            response,
+            Some(Response::Error(ServerError::InvalidSendDelay(201)))
+        ));
+
+        // Config isn't changed, only the connection is
+        let ctx = context.lock().unwrap();
+        assert_eq!(ctx.config.send_delay_ms, 0);
+        assert_eq!(ctx.settings_revision, 0);
+    }
+
+    #[test]
+    fn test_server_settings() {
+        let context = context();
+        let mut system = MockSystem::default();
+        context.lock().unwrap().link_type = Some(pcap::Linktype::ETHERNET);
+
+        let response = run(Request::ServerSettings, &context, &system);
+        let Some(Response::ServerSettings(dto)) = response else {
+            panic!("Unexpected response: {response:?}");
+        };
+        let names: Vec<&str> = dto
+            .interfaces_available
+            .iter()
+            .map(|interface| interface.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["eth0", "wlan0"]);
+        assert_eq!(dto.link_type, Some(1));
+        assert!(dto.compression_active && dto.compression_config);
+        assert!(dto.interface_active.is_none());
+        assert!(dto.capture_options_active.is_none());
+        assert_eq!(dto.capture_options_config.snaplen, 65535);
+
+        system.interfaces = None;
+        let response = run(Request::ServerSettings, &context, &system);
+        assert!(matches!(
+            response,
+            Some(Response::Error(ServerError::FailedToGetInterfaces))
+        ));
+    }
+}
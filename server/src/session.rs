@@ -0,0 +1,166 @@
+use common::cryptography;
+use rand::Rng;
+use rand::distr::Alphanumeric;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+const TOKEN_LENGTH: usize = 48;
+// Client answers the challenge right away, by the repeated handshake
+const CHALLENGE_TTL: Duration = Duration::from_secs(30);
+// Handshakes without the credentials don't take the memory without bound.
+// New challenges are refused, so the pending ones aren't evicted by the flood
+const CHALLENGES_CAPACITY: usize = 256;
+// So one peer doesn't take all the capacity
+const CHALLENGES_PER_PEER: usize = 8;
+
+// Tokens issued after the password handshake. Clients use them on reconnect,
+// so the password isn't sent over and over again.
+pub struct SessionTokens {
+    tokens: HashMap<String, Instant>,
+    ttl: Duration,
+}
+
+impl SessionTokens {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            tokens: HashMap::new(),
+            ttl,
+        }
+    }
+
+    // Zero TTL disables tokens, so the password is required on every connection.
+    pub fn is_enabled(&self) -> bool {
+        !self.ttl.is_zero()
+    }
+
+    pub fn issue(&mut self) -> Option<String> {
+        if !self.is_enabled() {
+            return None;
+        }
+        self.remove_expired();
+
+        let token: String = rand::rng()
+            .sample_iter(&Alphanumeric)
+            .take(TOKEN_LENGTH)
+            .map(char::from)
+            .collect();
+        let expires_at = Instant::now().checked_add(self.ttl)?;
+        self.tokens.insert(token.clone(), expires_at);
+
+        Some(token)
+    }
+
+    pub fn is_valid(&mut self, token: &str) -> bool {
+        self.remove_expired();
+        self.tokens.contains_key(token)
+    }
+
+    pub fn revoke(&mut self, token: &str) {
+        self.tokens.remove(token);
+    }
+
+    pub fn revoke_all(&mut self) {
+        self.tokens.clear();
+    }
+
+    fn remove_expired(&mut self) {
+        let now = Instant::now();
+        self.tokens.retain(|_, expires_at| *expires_at > now);
+    }
+}
+
+// Nonces of the password challenges. Each one is accepted once, so the signed
+// nonce can't be replayed.
+#[derive(Default)]
+pub struct Challenges {
+    nonces: HashMap<String, Challenge>,
+}
+
+struct Challenge {
+    peer: IpAddr,
+    expires_at: Instant,
+}
+
+impl Challenges {
+    // None, if there are too many pending challenges in total or from the peer
+    pub fn issue(&mut self, peer: IpAddr) -> Option<String> {
+        self.remove_expired();
+        let pending = self
+            .nonces
+            .values()
+            .filter(|challenge| challenge.peer == peer)
+            .count();
+        if self.nonces.len() >= CHALLENGES_CAPACITY || pending >= CHALLENGES_PER_PEER {
+            return None;
+        }
+
+        let nonce = cryptography::generate_nonce();
+        let expires_at = Instant::now().checked_add(CHALLENGE_TTL)?;
+        self.nonces
+            .insert(nonce.clone(), Challenge { peer, expires_at });
+
+        Some(nonce)
+    }
+
+    // Nonce is removed even if the signature turns out to be wrong
+    pub fn take(&mut self, nonce: &str) -> bool {
+        self.remove_expired();
+        self.nonces.remove(nonce).is_some()
+    }
+
+    fn remove_expired(&mut self) {
+        let now = Instant::now();
+        self.nonces
+            .retain(|_, challenge| challenge.expires_at > now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peer(last: u8) -> IpAddr {
+        IpAddr::from([192, 168, 0, last])
+    }
+
+    #[test]
+    fn test_challenge_once() {
+        let mut challenges = Challenges::default();
+        let nonce = challenges.issue(peer(1)).unwrap();
+        assert_ne!(Some(&nonce), challenges.issue(peer(1)).as_ref());
+
+        assert!(challenges.take(&nonce));
+        assert!(!challenges.take(&nonce));
+        assert!(!challenges.take("unknown"));
+    }
+
+    #[test]
+    fn test_challenges_per_peer() {
+        let mut challenges = Challenges::default();
+        let first = challenges.issue(peer(1)).unwrap();
+        for _ in 1..CHALLENGES_PER_PEER {
+            assert!(challenges.issue(peer(1)).is_some());
+        }
+        assert!(challenges.issue(peer(1)).is_none());
+        // Other peers aren't refused
+        assert!(challenges.issue(peer(2)).is_some());
+
+        // Pending challenge isn't evicted
+        assert!(challenges.take(&first));
+        assert!(challenges.issue(peer(1)).is_some());
+    }
+
+    #[test]
+    fn test_challenges_capacity() {
+        let mut challenges = Challenges::default();
+        let first = challenges.issue(peer(0)).unwrap();
+        for index in 1..CHALLENGES_CAPACITY {
+            let peer = peer((index / CHALLENGES_PER_PEER) as u8);
+            assert!(challenges.issue(peer).is_some());
+        }
+        assert_eq!(challenges.nonces.len(), CHALLENGES_CAPACITY);
+        assert!(challenges.issue(peer(255)).is_none());
+        assert!(challenges.take(&first));
+    }
+}
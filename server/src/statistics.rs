@@ -0,0 +1,381 @@
+// Aggregate statistics of the server.
+// With the persistence, they're saved to the file next to the config and merged at
+// startup, so the totals are accumulated across the restarts until they're reset.
+
+use common::messages::{CaptureStatsDto, ProtocolCountersDto, StatisticsDto};
+use dpi::dto::frame::FrameType;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+
+pub const STATISTICS_FILENAME: &str = "statistics.json";
+
+/// Counters, saved to the file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Totals {
+    pub frames: u64,
+    pub bytes: u64,
+    pub protocols: Vec<ProtocolCountersDto>,
+    pub dropped: u64,
+    pub if_dropped: u64,
+    pub uptime_seconds: u64,
+    // Unix seconds of the first start, or of the last reset
+    pub since: u64,
+}
+
+pub struct Statistics {
+    totals: Totals,
+    // None, if the persistence is disabled
+    path: Option<PathBuf>,
+    // Unix seconds
+    process_started: u64,
+    // Uptime is counted into the totals up to this moment
+    uptime_counted: Instant,
+    // Cumulative counters of the running capture at the previous poll
+    capture_last: Option<CaptureStatsDto>,
+    // Incremented on reset, so the counts made before it aren't added after it
+    generation: u64,
+}
+
+impl Totals {
+    pub fn count(&mut self, frame: &FrameType) {
+        let header = match frame {
+            FrameType::Metadata(metadata) => &metadata.header,
+            FrameType::Header(header) => header,
+            FrameType::Raw(frame) => &frame.header,
+        };
+        let bytes = u64::from(header.len);
+
+        self.frames = self.frames.saturating_add(1);
+        self.bytes = self.bytes.saturating_add(bytes);
+        count_protocols(&mut self.protocols, frame, bytes);
+    }
+
+    /// Adds the counters of another one. The earliest "since" is kept.
+    pub fn merge(&mut self, other: &Totals) {
+        self.frames = self.frames.saturating_add(other.frames);
+        self.bytes = self.bytes.saturating_add(other.bytes);
+        self.dropped = self.dropped.saturating_add(other.dropped);
+        self.if_dropped = self.if_dropped.saturating_add(other.if_dropped);
+        self.uptime_seconds = self.uptime_seconds.saturating_add(other.uptime_seconds);
+        if other.since > 0 {
+            self.since = match self.since {
+                0 => other.since,
+                since => since.min(other.since),
+            };
+        }
+
+        for counters in &other.protocols {
+            match self
+                .protocols
+                .iter_mut()
+                .find(|existing| existing.protocol == counters.protocol)
+            {
+                Some(existing) => {
+                    existing.frames = existing.frames.saturating_add(counters.frames);
+                    existing.bytes = existing.bytes.saturating_add(counters.bytes);
+                },
+                None => self.protocols.push(counters.clone()),
+            }
+        }
+    }
+}
+
+impl Statistics {
+    pub fn new() -> Self {
+        let now = unix_now();
+        Self {
+            totals: Totals {
+                since: now,
+                ..Default::default()
+            },
+            path: None,
+            process_started: now,
+            uptime_counted: Instant::now(),
+            capture_last: None,
+            generation: 0,
+        }
+    }
+
+    /// Totals saved by the previous runs are merged into the current ones.
+    /// Unreadable file is reported, and it's overwritten on the next save.
+    pub fn with_persistence(mut self, path: &Path) -> Self {
+        match read_totals(path) {
+            Ok(Some(saved)) => {
+                log::info!(
+                    "Statistics: Restored {} frames, accumulated since {}.",
+                    saved.frames,
+                    saved.since
+                );
+                self.totals.merge(&saved);
+            },
+            Ok(None) => {},
+            Err(err) => log::warn!("Statistics: Failed to restore. {err}"),
+        }
+        self.path = Some(path.to_path_buf());
+
+        self
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Counts made before the reset are dropped.
+    pub fn merge(&mut self, counted: &Totals, generation: u64) {
+        if generation == self.generation {
+            self.totals.merge(counted);
+        }
+    }
+
+    /// Counters of libpcap are cumulative since the capture was opened.
+    /// Smaller value means the capture was reopened, so it's counted since then.
+    pub fn count_drops(&mut self, current: &CaptureStatsDto) {
+        let delta = |last: Option<u32>, current: u32| match last {
+            Some(last) if current >= last => current - last,
+            _ => current,
+        };
+        let last = self.capture_last;
+        let dropped = delta(last.map(|stats| stats.dropped), current.dropped);
+        let if_dropped = delta(last.map(|stats| stats.if_dropped), current.if_dropped);
+
+        self.totals.dropped = self.totals.dropped.saturating_add(u64::from(dropped));
+        self.totals.if_dropped =
+            self.totals.if_dropped.saturating_add(u64::from(if_dropped));
+        self.capture_last = Some(*current);
+    }
+
+    /// New capture counts from zero.
+    pub fn capture_reopened(&mut self) {
+        self.capture_last = None;
+    }
+
+    pub fn reset(&mut self) {
+        self.totals = Totals {
+            since: unix_now(),
+            ..Default::default()
+        };
+        self.uptime_counted = Instant::now();
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    pub fn is_persistent(&self) -> bool {
+        self.path.is_some()
+    }
+
+    /// Saves the totals, if the persistence is enabled.
+    pub fn persist(&mut self) -> Result<(), StatisticsError> {
+        self.count_uptime();
+        match &self.path {
+            Some(path) => write_totals(&self.totals, path),
+            None => Ok(()),
+        }
+    }
+
+    pub fn to_dto(&mut self) -> StatisticsDto {
+        self.count_uptime();
+        StatisticsDto {
+            frames: self.totals.frames,
+            bytes: self.totals.bytes,
+            protocols: self.totals.protocols.clone(),
+            dropped: self.totals.dropped,
+            if_dropped: self.totals.if_dropped,
+            uptime_seconds: self.totals.uptime_seconds,
+            since: self.totals.since,
+            process_started: self.process_started,
+            persistent: self.is_persistent(),
+        }
+    }
+
+    // Only the whole seconds are counted, the rest goes to the next time
+    fn count_uptime(&mut self) {
+        let seconds = self.uptime_counted.elapsed().as_secs();
+        if seconds == 0 {
+            return;
+        }
+        self.totals.uptime_seconds = self.totals.uptime_seconds.saturating_add(seconds);
+        self.uptime_counted += Duration::from_secs(seconds);
+    }
+}
+
+impl Default for Statistics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Consecutive layers of the protocol (e.g. pipelined HTTP messages) count once.
+pub fn count_protocols(
+    counters: &mut Vec<ProtocolCountersDto>, frame: &FrameType, bytes: u64,
+) {
+    let FrameType::Metadata(metadata) = frame else {
+        return;
+    };
+    let mut protocols = metadata
+        .layers
+        .iter()
+        .map(|layer| layer.id())
+        .collect::<Vec<_>>();
+    protocols.dedup();
+    for protocol in protocols {
+        match counters
+            .iter_mut()
+            .find(|counters| counters.protocol == protocol)
+        {
+            Some(counters) => {
+                counters.frames = counters.frames.saturating_add(1);
+                counters.bytes = counters.bytes.saturating_add(bytes);
+            },
+            None => counters.push(ProtocolCountersDto {
+                protocol,
+                frames: 1,
+                bytes,
+            }),
+        }
+    }
+}
+
+pub fn read_totals(path: &Path) -> Result<Option<Totals>, StatisticsError> {
+    let data = match std::fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(StatisticsError::IOError(err)),
+    };
+
+    let totals = serde_json::from_str(&data).map_err(StatisticsError::JsonError)?;
+    Ok(Some(totals))
+}
+
+/// Written to the temporary file first, and renamed over the old one.
+/// So the crash in the middle of the write leaves the previous file intact.
+pub fn write_totals(totals: &Totals, path: &Path) -> Result<(), StatisticsError> {
+    let data =
+        serde_json::to_string_pretty(totals).map_err(StatisticsError::JsonError)?;
+
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+    let temporary = PathBuf::from(temporary);
+    {
+        let mut file = std::fs::File::create(&temporary)?;
+        file.write_all(data.as_bytes())?;
+        file.sync_all()?;
+    }
+    std::fs::rename(&temporary, path)?;
+
+    Ok(())
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Error)]
+pub enum StatisticsError {
+    #[error("IO Error. {0}")]
+    IOError(#[from] std::io::Error),
+
+    #[error("JSON Error. {0}")]
+    JsonError(#[from] serde_json::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dpi::protocols::ProtocolId;
+
+    fn path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "xailyser-statistics-{}-{name}.json",
+            std::process::id()
+        ))
+    }
+
+    fn totals(frames: u64, since: u64) -> Totals {
+        Totals {
+            frames,
+            bytes: frames * 100,
+            protocols: vec![ProtocolCountersDto {
+                protocol: ProtocolId::DNS,
+                frames,
+                bytes: frames * 100,
+            }],
+            dropped: 1,
+            if_dropped: 0,
+            uptime_seconds: 60,
+            since,
+        }
+    }
+
+    #[test]
+    fn test_save_load() {
+        let path = path("save-load");
+        assert!(read_totals(&path).unwrap().is_none());
+
+        write_totals(&totals(10, 1_700_000_000), &path).unwrap();
+        // Overwritten by the rename, no temporary file is left
+        write_totals(&totals(20, 1_700_000_000), &path).unwrap();
+        assert_eq!(read_totals(&path).unwrap(), Some(totals(20, 1_700_000_000)));
+        assert!(!path.with_extension("json.tmp").exists());
+
+        std::fs::write(&path, "{ \"frames\": ").unwrap();
+        assert!(read_totals(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_restore_merges() {
+        let path = path("restore");
+        write_totals(&totals(10, 1_700_000_000), &path).unwrap();
+
+        let mut statistics = Statistics::new().with_persistence(&path);
+        let generation = statistics.generation();
+        statistics.merge(&totals(5, 0), generation);
+        let dto = statistics.to_dto();
+        assert_eq!(dto.frames, 15);
+        assert_eq!(dto.protocols[0].frames, 15);
+        assert_eq!(dto.dropped, 2);
+        assert!(dto.uptime_seconds >= 120);
+        // Totals are older than the process
+        assert_eq!(dto.since, 1_700_000_000);
+        assert!(dto.process_started > dto.since);
+        assert!(dto.persistent);
+
+        statistics.reset();
+        statistics.merge(&totals(5, 0), generation);
+        let dto = statistics.to_dto();
+        assert_eq!(dto.frames, 0);
+        assert!(dto.since >= dto.process_started);
+        statistics.persist().unwrap();
+        assert_eq!(read_totals(&path).unwrap().unwrap().frames, 0);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_count_drops() {
+        let stats = |dropped, if_dropped| CaptureStatsDto {
+            received: 0,
+            dropped,
+            if_dropped,
+        };
+        let mut statistics = Statistics::new();
+
+        statistics.count_drops(&stats(5, 1));
+        statistics.count_drops(&stats(8, 1));
+        // Capture is reopened by the reboot of the interface
+        statistics.count_drops(&stats(2, 0));
+        statistics.capture_reopened();
+        statistics.count_drops(&stats(4, 3));
+
+        let dto = statistics.to_dto();
+        assert_eq!(dto.dropped, 5 + 3 + 2 + 4);
+        assert_eq!(dto.if_dropped, 1 + 3);
+        assert!(!dto.persistent);
+        // Nothing to save without the persistence
+        assert!(statistics.persist().is_ok());
+    }
+}
@@ -1,5 +1,6 @@
 use crate::context;
 use crate::context::Context;
+use crate::ws::encoding::EncodedFrames;
 use crate::ws::{WsError, WsHandlerBuilder};
 use common::channel::BroadcastPool;
 use common::messages::CONNECTION_TIMEOUT;
@@ -15,6 +16,7 @@ const LOCALHOST: IpAddr = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
 
 pub struct TcpHandler {
     context: Arc<Mutex<Context>>,
+    encoded_frames: Arc<Mutex<EncodedFrames>>,
     frame_channels_pool: Arc<RwLock<BroadcastPool<FrameType>>>,
     shutdown_flag: Arc<AtomicBool>,
     ws_active_counter: Arc<AtomicUsize>,
@@ -58,6 +60,7 @@ impl TcpHandler {
                         .spawn({
                             let context = Arc::clone(&self.context);
                             let shutdown_flag = Arc::clone(&self.shutdown_flag);
+                            let encoded_frames = Arc::clone(&self.encoded_frames);
                             let frame_receiver = match self.frame_channels_pool.write() {
                                 Ok(mut value) => {
                                     value.create();
@@ -83,6 +86,7 @@ impl TcpHandler {
                                     id: thread_counter,
                                     frame_receiver,
                                     context,
+                                    encoded_frames,
                                     shutdown_flag,
                                     ws_active_counter,
                                 }
@@ -155,6 +159,7 @@ impl TcpHandlerBuilder {
         TcpHandler {
             frame_channels_pool: self.frame_channels_pool,
             context: self.context,
+            encoded_frames: Arc::new(Mutex::new(EncodedFrames::default())),
             shutdown_flag: self.shutdown_flag,
             ws_active_counter: self.ws_active_counter,
 
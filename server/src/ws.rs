@@ -1,16 +1,23 @@
 use crate::context::Context;
+use crate::ws::coalescing::Coalescer;
+use crate::ws::encoding::{EncodedFrames, Encoding, EncodingError, encode};
+use crate::ws::sampling::{Sampler, SamplingMode, SamplingSettings};
 use crate::{context, request};
 use bytes::Bytes;
 use common::auth;
 use common::compression::{compress, decompress};
+use common::cryptography;
 use common::messages::{CONNECTION_TIMEOUT, Request, Response, ServerError};
+use common::schedule::ScheduleStatus;
+use common::suppression::{FailureStreak, Report};
 use crossbeam::channel::{Receiver, RecvTimeoutError};
 use dpi::dto::frame::FrameType;
 use std::collections::VecDeque;
-use std::net::TcpStream;
+use std::net::{IpAddr, Ipv4Addr, TcpStream};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 use thiserror::Error;
 use tungstenite::handshake::server;
 use tungstenite::http::{HeaderValue, StatusCode};
@@ -20,34 +27,82 @@ use tungstenite::{Message, Utf8Bytes, WebSocket};
 
 pub struct WsHandler {
     id: u16,
+    // Raw frames go as the binary messages, if the client supports them
+    binary_frames: bool,
+    // Idle warning is sent to this client
+    capture_idle_sent: bool,
+    // Status of the schedule, that is sent to this client
+    capture_schedule_sent: ScheduleStatus,
+    capture_stats_interval: Duration,
+    capture_stats_last: Instant,
+    // Frames are held by the delay of the connection, to be sent together
+    coalescer: Coalescer,
+    // Set at the handshake, it's kept until the client reconnects
     compression: bool,
     context: Arc<Mutex<Context>>,
-    frame_receiver: Receiver<FrameType>,
-    response_queue: VecDeque<Response>,
+    // Revision of the password, the connection is authorized with
+    credentials_authorized: u64,
+    // Connection is closed, when the password is changed past that revision.
+    // Moved forward, when the password is changed by this client
+    credentials_accepted: u64,
+    encoded_frames: Arc<Mutex<EncodedFrames>>,
+    // Messages, that failed to encode since the last encoded one
+    encoding_failures: FailureStreak,
+    frame_receiver: Receiver<Arc<FrameType>>,
+    // Multiple of the client heartbeat interval, that the connection is silent for
+    heartbeat_tolerance: u32,
+    // Connection is closed after that time without messages. Only if the client has
+    // told its heartbeat interval
+    heartbeat_timeout: Option<Duration>,
+    // Revision of the interface address change, that is sent to this client
+    interface_address_revision: u64,
+    last_received: Instant,
+    response_queue: VecDeque<Outgoing>,
+    sampler: Sampler,
+    settings_revision: u64,
     shutdown_flag: Arc<AtomicBool>,
 
     _connection_guard: WsConnectionGuard,
 }
 
 type WSStream = WebSocket<TcpStream>;
+
+#[derive(Debug)]
+enum Outgoing {
+    // Shared with the other connections
+    Frame(Arc<FrameType>),
+    Response(Response),
+}
+
 const BATCH_SIZE: usize = 100;
 
 impl WsHandler {
     pub fn start(&mut self, tcp_stream: TcpStream) -> Result<(), WsError> {
-        let ws_stream = match self.connect(tcp_stream) {
+        let (ws_stream, heartbeat, credentials) = match self.connect(tcp_stream) {
             Ok(value) => {
                 log::info!("WS-{}. Websocket connection established.", self.id);
                 value
             },
             Err(err) => return Err(err),
         };
+        self.credentials_authorized = credentials;
+        self.credentials_accepted = credentials;
+        self.heartbeat_timeout = heartbeat
+            .filter(|_| self.heartbeat_tolerance > 0)
+            .map(|interval| interval.saturating_mul(self.heartbeat_tolerance));
+        self.last_received = Instant::now();
 
         self.send_receive_messages(ws_stream);
         Ok(())
     }
 
-    fn connect(&self, tcp_stream: TcpStream) -> Result<WSStream, WsError> {
-        if let Ok(peer_addr) = &tcp_stream.peer_addr() {
+    // Returns the heartbeat interval of the client, if it's told by the header,
+    // and the revision of the password, the connection is authorized with
+    fn connect(
+        &mut self, tcp_stream: TcpStream,
+    ) -> Result<(WSStream, Option<Duration>, u64), WsError> {
+        let peer_addr = tcp_stream.peer_addr();
+        if let Ok(peer_addr) = &peer_addr {
             log::info!(
                 "WS-{}. Received a new handshake from {}:{}",
                 self.id,
@@ -58,87 +113,176 @@ impl WsHandler {
             log::info!("WS-{}. Received a new handshake!", self.id);
         }
 
-        let server_password_header = context::lock(&self.context, |ctx| {
-            HeaderValue::from_str(&ctx.encrypted_password)
-                .map_err(|_| WsError::InvalidPasswordHeader)
-        })?;
-        let server_compression_header = context::lock(&self.context, |ctx| {
-            HeaderValue::from_str(&ctx.compression.to_string())
-                .map_err(|_| WsError::InvalidCompressionHeader)
-        })?;
-
-        let check_authentication = |req: &server::Request, response: server::Response| {
-            let password_header = req.headers().get(auth::AUTH_HEADER);
-            let compression_header = req.headers().get(auth::COMPRESSION_HEADER);
-
-            match password_header {
-                Some(given_password) if given_password == server_password_header => {},
-                Some(_) => {
-                    return Err(server::Response::builder()
-                        .status(StatusCode::UNAUTHORIZED)
-                        .body(Some(auth::errors::WRONG_PASSWORD.to_string()))
-                        .unwrap_or_default());
-                },
-                None => {
-                    return Err(server::Response::builder()
-                        .status(StatusCode::BAD_REQUEST)
-                        .body(Some(auth::errors::PASSWORD_HEADER_NOT_FOUND.to_string()))
-                        .unwrap_or_default());
-                },
-            };
+        let (password_hash, credentials, compression) =
+            context::lock(&self.context, |ctx| {
+                (
+                    ctx.config.password.clone(),
+                    ctx.credentials_revision,
+                    ctx.config.compression,
+                )
+            });
+        self.compression = compression;
+        let server_key = cryptography::stored_key(&password_hash)
+            .map_err(|_| WsError::InvalidPasswordHash)?;
+        let challenge_params = cryptography::challenge_params(&password_hash)
+            .map_err(|_| WsError::InvalidPasswordHash)?;
+        let server_compression_header = HeaderValue::from_str(&compression.to_string())
+            .map_err(|_| WsError::InvalidCompressionHeader)?;
+        // Peers without the known address share the limit of the challenges
+        let peer_ip =
+            peer_addr.map_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED), |addr| addr.ip());
+        let mut heartbeat = None;
+        let mut binary_frames = false;
+
+        let check_authentication =
+            |req: &server::Request, mut response: server::Response| {
+                let password_header = req.headers().get(auth::AUTH_HEADER);
+                let nonce_header = req.headers().get(auth::AUTH_NONCE_HEADER);
+                let session_token_header = req.headers().get(auth::SESSION_TOKEN_HEADER);
+                let compression_header = req.headers().get(auth::COMPRESSION_HEADER);
+                heartbeat = heartbeat_interval(req.headers().get(auth::HEARTBEAT_HEADER));
+                binary_frames = req
+                    .headers()
+                    .get(auth::FEATURES_HEADER)
+                    .and_then(|value| value.to_str().ok())
+                    .is_some_and(|value| {
+                        auth::features::contains(value, auth::features::BINARY_FRAMES)
+                    });
 
-            match compression_header {
-                Some(given_compression)
-                    if given_compression == server_compression_header =>
+                // Session token has priority over the password
+                let is_session_token_used = match (session_token_header, password_header)
                 {
-                    Ok(response)
-                },
-                Some(_) => Err(server::Response::builder()
-                    .status(StatusCode::PRECONDITION_FAILED)
-                    .body(Some(auth::errors::WRONG_COMPRESSION.to_string()))
-                    .unwrap_or_default()),
-                None => Err(server::Response::builder()
-                    .status(StatusCode::BAD_REQUEST)
-                    .body(Some(auth::errors::COMPRESSION_HEADER_NOT_FOUND.to_string()))
-                    .unwrap_or_default()),
-            }
-        };
+                    (Some(given_token), _) => {
+                        let is_valid = given_token.to_str().is_ok_and(|token| {
+                            context::lock(&self.context, |ctx| {
+                                ctx.session_tokens.is_valid(token)
+                            })
+                        });
+                        if !is_valid {
+                            return Err(server::Response::builder()
+                                .status(StatusCode::UNAUTHORIZED)
+                                .body(Some(auth::errors::WRONG_SESSION_TOKEN.to_string()))
+                                .unwrap_or_default());
+                        }
+                        true
+                    },
+                    (None, Some(given_signature)) => {
+                        let Some(nonce) =
+                            nonce_header.and_then(|nonce| nonce.to_str().ok())
+                        else {
+                            return Err(server::Response::builder()
+                                .status(StatusCode::BAD_REQUEST)
+                                .body(Some(
+                                    auth::errors::NONCE_HEADER_NOT_FOUND.to_string(),
+                                ))
+                                .unwrap_or_default());
+                        };
+                        // Nonce is used up by any attempt
+                        let is_issued = context::lock(&self.context, |ctx| {
+                            ctx.challenges.take(nonce)
+                        });
+                        let is_valid = is_issued
+                            && given_signature.to_str().is_ok_and(|signature| {
+                                cryptography::verify_signature(
+                                    &server_key,
+                                    nonce,
+                                    signature,
+                                )
+                            });
+                        if !is_valid {
+                            return Err(server::Response::builder()
+                                .status(StatusCode::UNAUTHORIZED)
+                                .body(Some(auth::errors::WRONG_PASSWORD.to_string()))
+                                .unwrap_or_default());
+                        }
+                        false
+                    },
+                    // Challenge: the client repeats the handshake with the signed nonce
+                    (None, None) => {
+                        let nonce = context::lock(&self.context, |ctx| {
+                            ctx.challenges.issue(peer_ip)
+                        });
+                        let Some(nonce) = nonce else {
+                            log::warn!(
+                                "WS-{}. Too many pending challenges, handshake from {} \
+                                 is refused.",
+                                self.id,
+                                peer_ip
+                            );
+                            return Err(server::Response::builder()
+                                .status(StatusCode::SERVICE_UNAVAILABLE)
+                                .body(Some(auth::errors::TOO_MANY_CHALLENGES.to_string()))
+                                .unwrap_or_default());
+                        };
+                        return Err(server::Response::builder()
+                            .status(StatusCode::UNAUTHORIZED)
+                            .header(auth::AUTH_NONCE_HEADER, nonce)
+                            .header(auth::AUTH_PARAMS_HEADER, &challenge_params)
+                            .body(Some(auth::errors::PASSWORD_CHALLENGE.to_string()))
+                            .unwrap_or_default());
+                    },
+                };
+
+                match compression_header {
+                    Some(given_compression)
+                        if given_compression == server_compression_header => {},
+                    Some(_) => {
+                        return Err(server::Response::builder()
+                            .status(StatusCode::PRECONDITION_FAILED)
+                            .body(Some(auth::errors::WRONG_COMPRESSION.to_string()))
+                            .unwrap_or_default());
+                    },
+                    None => {
+                        return Err(server::Response::builder()
+                            .status(StatusCode::BAD_REQUEST)
+                            .body(Some(
+                                auth::errors::COMPRESSION_HEADER_NOT_FOUND.to_string(),
+                            ))
+                            .unwrap_or_default());
+                    },
+                }
+
+                if !is_session_token_used {
+                    let token =
+                        context::lock(&self.context, |ctx| ctx.session_tokens.issue());
+                    if let Some(value) =
+                        token.and_then(|token| HeaderValue::from_str(&token).ok())
+                    {
+                        response
+                            .headers_mut()
+                            .insert(auth::SESSION_TOKEN_HEADER, value);
+                    }
+                }
+
+                Ok(response)
+            };
 
         let stream = tungstenite::accept_hdr(tcp_stream, check_authentication)
             .map_err(|err| WsError::AuthFailed(err.to_string()))?;
+        self.binary_frames = binary_frames;
 
         stream
             .get_ref()
             .set_nonblocking(true)
             .map_err(|_| WsError::FailedSetNonBlockingStream)?;
 
-        Ok(stream)
+        Ok((stream, heartbeat, credentials))
     }
 
     fn send_receive_messages(&mut self, mut stream: WSStream) {
         while !self.shutdown_flag.load(Ordering::Acquire) {
             match self.frame_receiver.recv_timeout(CONNECTION_TIMEOUT) {
                 Ok(first) => {
-                    self.response_queue.push_back(Response::Data(first));
-                    log::debug!(
-                        "WS-{}. Pushing data from frame receiver to queue.",
-                        self.id
-                    );
+                    self.push_frame(first);
 
                     // Catching others without waiting
                     for _ in 1..=BATCH_SIZE {
                         match self.frame_receiver.try_recv() {
-                            Ok(frame) => {
-                                self.response_queue.push_back(Response::Data(frame));
-                                log::debug!(
-                                    "WS-{}. Pushing data from frame receiver to queue.",
-                                    self.id
-                                );
-                            },
+                            Ok(frame) => self.push_frame(frame),
                             _ => break,
                         }
                     }
-                    self.send_messages(&mut stream);
+                    self.send_coalesced(&mut stream);
                 },
                 Err(err) if err == RecvTimeoutError::Disconnected => {
                     log::error!(
@@ -150,6 +294,12 @@ impl WsHandler {
                 },
                 _ => {},
             }
+            self.send_summary(&mut stream);
+            self.send_capture_stats(&mut stream);
+            self.send_capture_idle(&mut stream);
+            self.send_capture_schedule(&mut stream);
+            self.send_interface_address_change(&mut stream);
+            self.send_server_settings_update(&mut stream);
             if let Err(err) = self.receive_messages(&mut stream) {
                 log::debug!(
                     "WS-{}. Got error while receiving messages: {}",
@@ -158,6 +308,15 @@ impl WsHandler {
                 );
                 return;
             }
+            self.send_coalesced(&mut stream);
+            if self.is_silent() {
+                self.close_silent(stream);
+                return;
+            }
+            if self.is_credentials_changed() {
+                self.close_credentials_changed(stream);
+                return;
+            }
         }
 
         if let Ok(address) = stream.get_ref().peer_addr() {
@@ -177,6 +336,193 @@ impl WsHandler {
         }));
     }
 
+    fn is_silent(&self) -> bool {
+        self.heartbeat_timeout
+            .is_some_and(|timeout| self.last_received.elapsed() > timeout)
+    }
+
+    // Client vanished without closing (e.g. sleep, NAT timeout), its slot is released
+    fn close_silent(&self, mut stream: WSStream) {
+        log::warn!(
+            "WS-{}. No messages from the client for {} s, closing the dead connection.",
+            self.id,
+            self.last_received.elapsed().as_secs()
+        );
+        let _ = stream.close(Some(CloseFrame {
+            code: CloseCode::Away,
+            reason: Default::default(),
+        }));
+    }
+
+    fn is_credentials_changed(&self) -> bool {
+        let revision = context::lock(&self.context, |ctx| ctx.credentials_revision);
+        revision != self.credentials_accepted
+    }
+
+    // Password is changed by another client, so the session of this one is over
+    fn close_credentials_changed(&mut self, mut stream: WSStream) {
+        log::warn!(
+            "WS-{}. Password is changed by another client, closing the connection.",
+            self.id
+        );
+        self.response_queue
+            .push_back(Outgoing::Response(Response::Error(
+                ServerError::CredentialsChanged,
+            )));
+        self.send_messages(&mut stream);
+        let _ = stream.close(Some(CloseFrame {
+            code: CloseCode::Policy,
+            reason: Utf8Bytes::from_static(auth::close_reasons::CREDENTIALS_CHANGED),
+        }));
+        // Non-blocking stream may leave the close frame queued
+        let _ = stream.flush();
+    }
+
+    // Values of the settings, that differ by the connection
+    fn with_connection_values(&mut self, response: Response) -> Response {
+        match response {
+            Response::ServerSettings(mut dto) => {
+                dto.compression_active = self.compression;
+                dto.credentials_active = self.credentials_authorized;
+                dto.send_delay_active = self.coalescer.delay().as_millis() as u64;
+                Response::ServerSettings(dto)
+            },
+            Response::SuccessSetCompression { enabled, .. } => {
+                Response::SuccessSetCompression {
+                    enabled,
+                    current: self.compression,
+                }
+            },
+            Response::SuccessChangePassword(token) => {
+                self.credentials_accepted =
+                    context::lock(&self.context, |ctx| ctx.credentials_revision);
+                Response::SuccessChangePassword(token)
+            },
+            Response::SuccessSetSendDelay(delay) => {
+                self.coalescer.set_delay(Duration::from_millis(delay));
+                Response::SuccessSetSendDelay(delay)
+            },
+            response => response,
+        }
+    }
+
+    fn push_frame(&mut self, frame: Arc<FrameType>) {
+        if let Some(frame) = self.sampler.sample(frame) {
+            self.response_queue.push_back(Outgoing::Frame(frame));
+            log::debug!("WS-{}. Pushing data from frame receiver to queue.", self.id);
+        }
+    }
+
+    // Switching the sampled mode by the backlog of the frames not sent yet
+    fn send_summary(&mut self, stream: &mut WSStream) {
+        let now = Instant::now();
+        if self.sampler.update(self.frame_receiver.len(), now) {
+            match self.sampler.mode() {
+                SamplingMode::Sampled => log::warn!(
+                    "WS-{}. Client can't keep up with the traffic, sending 1 of {} frames.",
+                    self.id,
+                    self.sampler.rate()
+                ),
+                SamplingMode::Full => {
+                    log::info!("WS-{}. Backlog cleared, sending every frame.", self.id)
+                },
+            }
+        }
+
+        if let Some(summary) = self.sampler.take_summary(now) {
+            self.response_queue
+                .push_back(Outgoing::Response(Response::Summary(summary)));
+            self.send_messages(stream);
+        }
+    }
+
+    fn send_capture_stats(&mut self, stream: &mut WSStream) {
+        if self.capture_stats_interval.is_zero()
+            || self.capture_stats_last.elapsed() < self.capture_stats_interval
+        {
+            return;
+        }
+        self.capture_stats_last = Instant::now();
+
+        let (capture_stats, statistics) = context::lock(&self.context, |ctx| {
+            (ctx.capture_stats, ctx.statistics.to_dto())
+        });
+        if let Some(stats) = capture_stats {
+            self.response_queue
+                .push_back(Outgoing::Response(Response::CaptureStats(stats)));
+        }
+        // Aggregates go at the same interval, they include the capture drops
+        self.response_queue
+            .push_back(Outgoing::Response(Response::Statistics(statistics)));
+        self.send_messages(stream);
+    }
+
+    // Sent once per idle period, client clears the warning when frames resume
+    fn send_capture_idle(&mut self, stream: &mut WSStream) {
+        let idle = context::lock(&self.context, |ctx| ctx.capture_idle);
+        match (idle, self.capture_idle_sent) {
+            (Some(seconds), false) => {
+                self.capture_idle_sent = true;
+                self.response_queue
+                    .push_back(Outgoing::Response(Response::CaptureIdle { seconds }));
+                self.send_messages(stream);
+            },
+            (None, true) => self.capture_idle_sent = false,
+            _ => {},
+        }
+    }
+
+    // Pauses & resumes by the schedule. Nothing is sent, while the capture isn't scheduled
+    fn send_capture_schedule(&mut self, stream: &mut WSStream) {
+        let status = context::lock(&self.context, |ctx| ctx.capture_schedule);
+        if status == self.capture_schedule_sent {
+            return;
+        }
+        self.capture_schedule_sent = status;
+        self.response_queue
+            .push_back(Outgoing::Response(Response::CaptureSchedule(status)));
+        self.send_messages(stream);
+    }
+
+    // Changes before the connection aren't sent, the settings already have the addresses
+    fn send_interface_address_change(&mut self, stream: &mut WSStream) {
+        let (revision, change) = context::lock(&self.context, |ctx| {
+            (
+                ctx.interface_address_revision,
+                ctx.interface_address_change.clone(),
+            )
+        });
+        if revision == self.interface_address_revision {
+            return;
+        }
+        self.interface_address_revision = revision;
+        let Some(change) = change else {
+            return;
+        };
+        self.response_queue.push_back(Outgoing::Response(
+            Response::InterfaceAddressChanged(change),
+        ));
+        self.send_messages(stream);
+    }
+
+    fn send_server_settings_update(&mut self, stream: &mut WSStream) {
+        let revision = context::lock(&self.context, |ctx| ctx.settings_revision);
+        if revision == self.settings_revision {
+            return;
+        }
+        self.settings_revision = revision;
+
+        if let Some(response) = request::core::process(
+            Request::ServerSettings,
+            &self.context,
+            &self.shutdown_flag,
+        ) {
+            let response = self.with_connection_values(response);
+            self.response_queue.push_back(Outgoing::Response(response));
+            self.send_messages(stream);
+        }
+    }
+
     fn receive_messages(
         &mut self, stream: &mut WSStream,
     ) -> Result<(), Box<tungstenite::Error>> {
@@ -186,6 +532,7 @@ impl WsHandler {
             Err(err) => return self.handle_read_error(err),
         };
         log::debug!("WS-{}. Message successfully read.", self.id);
+        self.last_received = Instant::now();
 
         if msg.is_close() {
             log::info!("WS-{}. Client closed connection.", self.id);
@@ -207,37 +554,80 @@ impl WsHandler {
         Ok(())
     }
 
+    // Frames wait for the coalescing delay. Other responses (settings, errors) are sent
+    // at once, the frames before them go along
+    fn send_coalesced(&mut self, stream: &mut WSStream) {
+        let has_responses = self
+            .response_queue
+            .iter()
+            .any(|outgoing| matches!(outgoing, Outgoing::Response(_)));
+        if has_responses
+            || self
+                .coalescer
+                .is_due(self.response_queue.len(), Instant::now())
+        {
+            self.send_messages(stream);
+        }
+    }
+
+    // Messages are written to the buffer, and the socket is flushed once for all of them
     fn send_messages(&mut self, stream: &mut WSStream) {
-        while let Some(response) = self.response_queue.pop_front() {
+        let encoding = Encoding {
+            compression: self.compression,
+            binary_frames: self.binary_frames,
+        };
+        let mut is_written = false;
+        while let Some(outgoing) = self.response_queue.pop_front() {
             log::debug!("WS-{}. Response from queue popped out.", self.id);
-            if let Ok(serialized) = serde_json::to_string(&response) {
-                if self.compression {
-                    match compress(&serialized) {
-                        Ok(bytes) => {
-                            log::debug!(
-                                "WS-{}. Will send compressed message now..",
-                                self.id
-                            );
-                            let _ = stream.send(Message::Binary(Bytes::from(bytes)));
-                            log::debug!("WS-{}. Message successfully sent.", self.id);
-                        },
-                        Err(_) => {
-                            log::error!(
-                                "WS-{}. Can't compress message! {:#?}",
-                                self.id,
-                                response
-                            );
-                        },
+            let message = match &outgoing {
+                Outgoing::Frame(frame) => self.encode_frame(frame, encoding),
+                Outgoing::Response(response) => encode(response, encoding),
+            };
+            match message {
+                Ok(message) => {
+                    if let Some(failures) = self.encoding_failures.succeeded() {
+                        log::info!(
+                            "WS-{}. Messages are encoded again, {failures} were dropped.",
+                            self.id
+                        );
                     }
-                } else {
-                    log::debug!("WS-{}. Will send uncompressed message now..", self.id);
-                    let _ = stream.send(Message::text(serialized));
+                    log::debug!("WS-{}. Will send message now..", self.id);
+                    let _ = stream.write(message);
+                    is_written = true;
                     log::debug!("WS-{}. Message successfully sent.", self.id);
-                }
-            } else {
-                log::error!("WS-{}. Can't serialize message! {:#?}", self.id, response);
+                },
+                // Only the first failure is logged with the message
+                Err(err) => match self.encoding_failures.failed(Instant::now()) {
+                    Report::First => {
+                        log::error!("WS-{}. {} {:#?}", self.id, err, outgoing)
+                    },
+                    Report::Summary {
+                        suppressed,
+                        seconds,
+                    } => log::error!(
+                        "WS-{}. Suppressed {suppressed} similar errors in the last {seconds} s.",
+                        self.id
+                    ),
+                    Report::Suppressed => {},
+                },
             }
         }
+        if is_written {
+            let _ = stream.flush();
+        }
+        self.coalescer.flushed();
+    }
+
+    fn encode_frame(
+        &self, frame: &Arc<FrameType>, encoding: Encoding,
+    ) -> Result<Message, EncodingError> {
+        match self.encoded_frames.lock() {
+            Ok(mut frames) => frames.get_or_encode(frame, encoding),
+            Err(err) => {
+                log::error!("Encoded frames lock failed: {err}");
+                std::process::exit(1);
+            },
+        }
     }
 
     fn handle_read_error(
@@ -321,7 +711,8 @@ impl WsHandler {
                 if let Some(response) =
                     request::core::process(message, &self.context, &self.shutdown_flag)
                 {
-                    self.response_queue.push_back(response);
+                    let response = self.with_connection_values(response);
+                    self.response_queue.push_back(Outgoing::Response(response));
                     log::debug!(
                         "WS-{}. Pushed back processed request to queue.",
                         self.id
@@ -357,6 +748,19 @@ impl WsHandler {
     }
 }
 
+// Interval of zero seconds isn't the heartbeat
+fn heartbeat_interval(header: Option<&HeaderValue>) -> Option<Duration> {
+    header
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .filter(|seconds| *seconds > 0)
+        .map(Duration::from_secs)
+}
+
+pub mod coalescing;
+pub mod encoding;
+pub mod sampling;
+
 #[derive(Debug, Error)]
 pub enum WsError {
     #[error("Authentication failed")]
@@ -365,8 +769,8 @@ pub enum WsError {
     #[error("Invalid compression header")]
     InvalidCompressionHeader,
 
-    #[error("Invalid password header")]
-    InvalidPasswordHeader,
+    #[error("Password of the config isn't hashed")]
+    InvalidPasswordHash,
 
     #[error("Failed to set non-blocking stream")]
     FailedSetNonBlockingStream,
@@ -374,23 +778,58 @@ pub enum WsError {
 
 pub struct WsHandlerBuilder {
     pub id: u16,
-    pub frame_receiver: Receiver<FrameType>,
+    pub frame_receiver: Receiver<Arc<FrameType>>,
     pub context: Arc<Mutex<Context>>,
+    pub encoded_frames: Arc<Mutex<EncodedFrames>>,
     pub shutdown_flag: Arc<AtomicBool>,
     pub ws_active_counter: Arc<AtomicUsize>,
 }
 
 impl WsHandlerBuilder {
     pub fn build(self) -> WsHandler {
-        let compression = context::lock(&self.context, |context| context.compression);
+        let compression =
+            context::lock(&self.context, |context| context.config.compression);
+        let capture_stats_interval = context::lock(&self.context, |context| {
+            Duration::from_secs(context.config.capture_stats_interval)
+        });
+        let sampling = context::lock(&self.context, |context| SamplingSettings {
+            threshold: context.config.sampling_threshold,
+            rate: context.config.sampling_rate,
+            delay: Duration::from_secs(context.config.sampling_delay),
+        });
+        let settings_revision =
+            context::lock(&self.context, |context| context.settings_revision);
+        let interface_address_revision =
+            context::lock(&self.context, |context| context.interface_address_revision);
+        let heartbeat_tolerance =
+            context::lock(&self.context, |context| context.config.heartbeat_tolerance);
+        let send_delay = context::lock(&self.context, |context| {
+            Duration::from_millis(context.config.send_delay_ms)
+        });
         let connection_guard = WsConnectionGuard::new(self.ws_active_counter);
 
         WsHandler {
             id: self.id,
+            binary_frames: false,
+            capture_idle_sent: false,
+            capture_schedule_sent: ScheduleStatus::default(),
+            capture_stats_interval,
+            capture_stats_last: Instant::now(),
+            coalescer: Coalescer::new(send_delay),
             compression,
             context: self.context,
+            credentials_authorized: 0,
+            credentials_accepted: 0,
+            encoded_frames: self.encoded_frames,
+            encoding_failures: FailureStreak::default(),
             frame_receiver: self.frame_receiver,
+            heartbeat_tolerance,
+            heartbeat_timeout: None,
+            interface_address_revision,
+            last_received: Instant::now(),
             response_queue: VecDeque::new(),
+            sampler: Sampler::new(sampling),
+            settings_revision,
             shutdown_flag: self.shutdown_flag,
 
             _connection_guard: connection_guard,
@@ -414,3 +853,187 @@ impl Drop for WsConnectionGuard {
         self.counter.fetch_sub(1, Ordering::Release);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crossbeam::channel::unbounded;
+    use std::net::TcpListener;
+    use tungstenite::ClientRequestBuilder;
+    use tungstenite::stream::MaybeTlsStream;
+
+    type ClientStream = WebSocket<MaybeTlsStream<TcpStream>>;
+
+    // Responses are read until the one, that is looked for
+    fn read_until(
+        client: &mut ClientStream, f: impl Fn(&Response) -> bool,
+    ) -> Option<Response> {
+        loop {
+            match client.read().unwrap() {
+                Message::Text(text) => {
+                    let response = serde_json::from_str::<Response>(&text).unwrap();
+                    if f(&response) {
+                        return Some(response);
+                    }
+                },
+                Message::Close(_) => return None,
+                _ => {},
+            }
+        }
+    }
+
+    #[test]
+    fn test_heartbeat_interval() {
+        let header = |value: &str| HeaderValue::from_str(value).ok();
+        assert_eq!(
+            heartbeat_interval(header("5").as_ref()),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(heartbeat_interval(header("0").as_ref()), None);
+        assert_eq!(heartbeat_interval(header("soon").as_ref()), None);
+        assert_eq!(heartbeat_interval(None), None);
+    }
+
+    #[test]
+    fn test_silent_client_reaped() {
+        let mut config = Config {
+            heartbeat_tolerance: 1,
+            ..Default::default()
+        };
+        config.set_password_hash("secret").unwrap();
+        let context = Arc::new(Mutex::new(Context::new(config).unwrap()));
+        let token = context::lock(&context, |ctx| ctx.session_tokens.issue()).unwrap();
+        let compression = context::lock(&context, |ctx| ctx.config.compression);
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        // Sender is kept, so the connection isn't closed by the broadcast channel
+        let (_frame_sender, frame_receiver) = unbounded();
+        let counter = Arc::new(AtomicUsize::new(0));
+        let handle = thread::spawn({
+            let counter = Arc::clone(&counter);
+            move || {
+                let (tcp_stream, _) = listener.accept().unwrap();
+                WsHandlerBuilder {
+                    id: 0,
+                    frame_receiver,
+                    context,
+                    encoded_frames: Default::default(),
+                    shutdown_flag: Arc::new(AtomicBool::new(false)),
+                    ws_active_counter: counter,
+                }
+                .build()
+                .start(tcp_stream)
+            }
+        });
+
+        // Client is connected, but doesn't send anything after the handshake
+        let uri = format!("ws://{address}/socket").parse().unwrap();
+        let request = ClientRequestBuilder::new(uri)
+            .with_header(auth::SESSION_TOKEN_HEADER, token)
+            .with_header(auth::COMPRESSION_HEADER, compression.to_string())
+            .with_header(auth::HEARTBEAT_HEADER, "1");
+        let (_client, _) = tungstenite::connect(request).unwrap();
+        let connected = Instant::now();
+
+        assert!(handle.join().unwrap().is_ok());
+        assert!(connected.elapsed() >= Duration::from_millis(900));
+        // Slot of the connection is released
+        assert_eq!(counter.load(Ordering::Acquire), 0);
+    }
+
+    #[test]
+    fn test_password_change_closes_other_connections() {
+        let mut config = Config {
+            compression: false,
+            ..Default::default()
+        };
+        config.set_password_hash("secret").unwrap();
+        let context = Arc::new(Mutex::new(Context::new(config).unwrap()));
+        let shutdown_flag = Arc::new(AtomicBool::new(false));
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let (_frame_sender, frame_receiver) = unbounded();
+        let handles: Vec<_> = (0..2)
+            .map(|id| {
+                let listener = listener.try_clone().unwrap();
+                let builder = WsHandlerBuilder {
+                    id,
+                    frame_receiver: frame_receiver.clone(),
+                    context: Arc::clone(&context),
+                    encoded_frames: Default::default(),
+                    shutdown_flag: Arc::clone(&shutdown_flag),
+                    ws_active_counter: Arc::new(AtomicUsize::new(0)),
+                };
+                thread::spawn(move || {
+                    let (tcp_stream, _) = listener.accept().unwrap();
+                    builder.build().start(tcp_stream)
+                })
+            })
+            .collect();
+
+        let connect = || {
+            let token =
+                context::lock(&context, |ctx| ctx.session_tokens.issue()).unwrap();
+            let uri = format!("ws://{address}/socket").parse().unwrap();
+            let request = ClientRequestBuilder::new(uri)
+                .with_header(auth::SESSION_TOKEN_HEADER, token)
+                .with_header(auth::COMPRESSION_HEADER, "false");
+            tungstenite::connect(request).unwrap().0
+        };
+        let mut issuer = connect();
+        let mut other = connect();
+
+        let request = serde_json::to_string(&Request::ChangePassword("new".into()));
+        issuer.send(Message::text(request.unwrap())).unwrap();
+
+        // Other client is told the reason before the close
+        let error = read_until(&mut other, |response| {
+            matches!(response, Response::Error(ServerError::CredentialsChanged))
+        });
+        assert!(error.is_some());
+        let reason = loop {
+            match other.read() {
+                Ok(Message::Close(frame)) => break frame.map(|frame| frame.reason),
+                Ok(_) => {},
+                Err(err) => panic!("Close frame isn't received: {err}"),
+            }
+        };
+        assert_eq!(
+            reason.as_deref(),
+            Some(auth::close_reasons::CREDENTIALS_CHANGED)
+        );
+
+        // Issuing client keeps the connection & gets the new session
+        let response = read_until(&mut issuer, |response| {
+            matches!(response, Response::SuccessChangePassword(_))
+        });
+        let Some(Response::SuccessChangePassword(Some(token))) = response else {
+            panic!("Unexpected response: {response:?}");
+        };
+        assert!(context::lock(&context, |ctx| ctx
+            .session_tokens
+            .is_valid(&token)));
+        issuer
+            .send(Message::text(
+                serde_json::to_string(&Request::ServerSettings).unwrap(),
+            ))
+            .unwrap();
+        let response = read_until(&mut issuer, |response| {
+            matches!(response, Response::ServerSettings(_))
+        });
+        let Some(Response::ServerSettings(dto)) = response else {
+            panic!("Unexpected response: {response:?}");
+        };
+        // Authorized with the previous password, so it's shown as not applied
+        assert_eq!(dto.credentials_active, 0);
+        assert_eq!(dto.credentials_config, 1);
+
+        shutdown_flag.store(true, Ordering::Release);
+        for handle in handles {
+            assert!(handle.join().unwrap().is_ok());
+        }
+    }
+}
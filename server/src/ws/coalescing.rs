@@ -0,0 +1,108 @@
+// Coalescing of the frames, sent to the connection.
+// Every send is a separate write to the socket, so under the moderate load many small
+// writes are made. With the delay, the frames are held until the oldest of them waits
+// for the delay, or until enough of them are queued, and then they're sent at once.
+// Other responses aren't held: they're sent right away, along with the frames before them.
+
+use std::time::{Duration, Instant};
+
+// Frames, that are sent without waiting for the delay
+pub const FRAMES_LIMIT: usize = 500;
+
+pub struct Coalescer {
+    delay: Duration,
+    // Since when the oldest frame, not sent yet, is held
+    pending_since: Option<Instant>,
+}
+
+impl Coalescer {
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            pending_since: None,
+        }
+    }
+
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    /// New delay applies to the frames, that are already held.
+    pub fn set_delay(&mut self, delay: Duration) {
+        self.delay = delay;
+    }
+
+    /// Whether the queued frames should be sent now. Zero delay sends them at once.
+    pub fn is_due(&mut self, frames: usize, now: Instant) -> bool {
+        if frames == 0 {
+            self.pending_since = None;
+            return false;
+        }
+        if self.delay.is_zero() || frames >= FRAMES_LIMIT {
+            return true;
+        }
+
+        let since = *self.pending_since.get_or_insert(now);
+        now.duration_since(since) >= self.delay
+    }
+
+    /// Queue is sent, the next frame starts the new wait.
+    pub fn flushed(&mut self) {
+        self.pending_since = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Frames come every millisecond, the queue is sent when it's due
+    fn sends(delay: Duration, frames: usize) -> usize {
+        let mut coalescer = Coalescer::new(delay);
+        let start = Instant::now();
+        let mut queued = 0;
+        let mut sends = 0;
+        for index in 0..frames {
+            queued += 1;
+            let now = start + Duration::from_millis(index as u64);
+            if coalescer.is_due(queued, now) {
+                sends += 1;
+                queued = 0;
+                coalescer.flushed();
+            }
+        }
+        // The rest is sent after the delay
+        let end = start + Duration::from_millis(frames as u64) + delay;
+        if coalescer.is_due(queued, end) {
+            sends += 1;
+        }
+        sends
+    }
+
+    #[test]
+    fn test_fewer_sends_with_delay() {
+        assert_eq!(sends(Duration::ZERO, 1000), 1000);
+        // 1000 frames within a second are sent 5 times, by 200 ms
+        assert_eq!(sends(Duration::from_millis(200), 1000), 5);
+        // Burst is sent by the limit, without waiting
+        let mut coalescer = Coalescer::new(Duration::from_millis(200));
+        assert!(coalescer.is_due(FRAMES_LIMIT, Instant::now()));
+    }
+
+    #[test]
+    fn test_wait_starts_with_first_frame() {
+        let mut coalescer = Coalescer::new(Duration::from_millis(100));
+        let start = Instant::now();
+        // Nothing is queued, nothing to wait for
+        assert!(!coalescer.is_due(0, start));
+        assert!(!coalescer.is_due(1, start + Duration::from_millis(50)));
+        assert!(!coalescer.is_due(2, start + Duration::from_millis(149)));
+        assert!(coalescer.is_due(2, start + Duration::from_millis(150)));
+
+        coalescer.flushed();
+        assert!(!coalescer.is_due(1, start + Duration::from_millis(200)));
+        // Shorter delay is applied to the held frames
+        coalescer.set_delay(Duration::from_millis(10));
+        assert!(coalescer.is_due(1, start + Duration::from_millis(210)));
+    }
+}
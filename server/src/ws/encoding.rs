@@ -0,0 +1,233 @@
+// Frames are broadcast as the same Arc to every connection, so each of them is
+// serialized (and compressed) once per encoding, and the bytes are shared between
+// the connections with the same settings. Raw frames are sent as the binary messages
+// to the clients, that support them. While the frame is cached, the Arc is
+// held, so the address of the frame identifies it and can't be reused.
+
+use bytes::Bytes;
+use common::binary;
+use common::compression::{compress, compress_bytes};
+use dpi::dto::frame::{FrameType, OwnedFrame};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use thiserror::Error;
+use tungstenite::Message;
+
+// How many frames the connections may lag behind each other.
+// The connection, that is behind more than that, serializes the frames itself.
+pub const CAPACITY: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Encoding {
+    pub compression: bool,
+    // Raw frames go as the binary messages, instead of JSON
+    pub binary_frames: bool,
+}
+
+#[derive(Default)]
+pub struct EncodedFrames {
+    messages: HashMap<(usize, Encoding), Message>,
+    // Oldest first
+    order: VecDeque<(Arc<FrameType>, Encoding)>,
+    // Frames, that were actually serialized
+    serializations: u64,
+}
+
+impl EncodedFrames {
+    pub fn get_or_encode(
+        &mut self, frame: &Arc<FrameType>, encoding: Encoding,
+    ) -> Result<Message, EncodingError> {
+        let key = (Arc::as_ptr(frame) as usize, encoding);
+        if let Some(message) = self.messages.get(&key) {
+            return Ok(message.clone());
+        }
+
+        let message = match (frame.as_ref(), encoding.binary_frames) {
+            (FrameType::Raw(raw), true) => encode_raw(raw, encoding)?,
+            _ => encode(&FrameResponse::Data(frame), encoding)?,
+        };
+        self.serializations = self.serializations.saturating_add(1);
+        log::debug!("Frame encoded, {} in total.", self.serializations);
+
+        if self.order.len() >= CAPACITY {
+            if let Some((evicted, encoding)) = self.order.pop_front() {
+                self.messages
+                    .remove(&(Arc::as_ptr(&evicted) as usize, encoding));
+            }
+        }
+        self.order.push_back((Arc::clone(frame), encoding));
+        self.messages.insert(key, message.clone());
+
+        Ok(message)
+    }
+}
+
+// Serialized the same way as `Response::Data`, without copying the frame
+#[derive(Serialize)]
+#[serde(rename = "Response")]
+enum FrameResponse<'a> {
+    Data(&'a FrameType),
+}
+
+pub fn encode<T: Serialize>(
+    value: &T, encoding: Encoding,
+) -> Result<Message, EncodingError> {
+    let serialized = serde_json::to_string(value)?;
+    match encoding.compression {
+        true => {
+            let compressed =
+                compress(&serialized).map_err(EncodingError::CompressionFailed)?;
+            Ok(Message::Binary(Bytes::from(compressed)))
+        },
+        false => Ok(Message::text(serialized)),
+    }
+}
+
+fn encode_raw(frame: &OwnedFrame, encoding: Encoding) -> Result<Message, EncodingError> {
+    let message = binary::encode(frame);
+    match encoding.compression {
+        true => {
+            let compressed =
+                compress_bytes(&message).map_err(EncodingError::CompressionFailed)?;
+            Ok(Message::Binary(Bytes::from(compressed)))
+        },
+        false => Ok(Message::Binary(Bytes::from(message))),
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum EncodingError {
+    #[error("Can't serialize message. {0}")]
+    SerializationFailed(#[from] serde_json::Error),
+
+    #[error("Can't compress message. {0}")]
+    CompressionFailed(std::io::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::messages::Response;
+    use dpi::dto::frame::FrameHeader;
+
+    const CLIENTS: usize = 3;
+
+    fn frame(len: u32) -> Arc<FrameType> {
+        Arc::new(FrameType::Header(FrameHeader {
+            tv_sec: 1_700_000_000,
+            tv_usec: 0,
+            caplen: len,
+            len,
+        }))
+    }
+
+    #[test]
+    fn test_serialized_once_per_encoding() {
+        let mut frames = EncodedFrames::default();
+        let frame = frame(60);
+        let compressed = Encoding {
+            compression: true,
+            binary_frames: false,
+        };
+        let uncompressed = Encoding {
+            compression: false,
+            binary_frames: false,
+        };
+
+        let messages = (0..CLIENTS)
+            .map(|_| frames.get_or_encode(&frame, compressed).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(frames.serializations, 1);
+        assert!(messages.iter().all(|message| *message == messages[0]));
+
+        frames.get_or_encode(&frame, uncompressed).unwrap();
+        assert_eq!(frames.serializations, 2);
+
+        // Equal, but another frame
+        let copy = Arc::new(FrameType::clone(&frame));
+        frames.get_or_encode(&copy, uncompressed).unwrap();
+        assert_eq!(frames.serializations, 3);
+    }
+
+    #[test]
+    fn test_same_as_response() {
+        let mut frames = EncodedFrames::default();
+        let frame = frame(60);
+        let encoding = Encoding {
+            compression: false,
+            binary_frames: false,
+        };
+
+        let shared = frames.get_or_encode(&frame, encoding).unwrap();
+        let response = Response::Data(FrameType::clone(&frame));
+        assert_eq!(shared, encode(&response, encoding).unwrap());
+    }
+
+    #[test]
+    fn test_capacity() {
+        let mut frames = EncodedFrames::default();
+        let encoding = Encoding {
+            compression: false,
+            binary_frames: false,
+        };
+        let first = frame(1);
+        frames.get_or_encode(&first, encoding).unwrap();
+        for len in 0..CAPACITY as u32 {
+            frames.get_or_encode(&frame(len), encoding).unwrap();
+        }
+        assert_eq!(frames.messages.len(), CAPACITY);
+
+        // Evicted, so it's serialized again
+        frames.get_or_encode(&first, encoding).unwrap();
+        assert_eq!(frames.serializations, CAPACITY as u64 + 2);
+    }
+
+    #[test]
+    fn test_binary_raw_frames() {
+        const FRAMES: usize = 10_000;
+        const LENGTH: usize = 1500;
+
+        let raw = |index: usize| OwnedFrame {
+            header: FrameHeader {
+                tv_sec: 1_700_000_000,
+                tv_usec: index as i64,
+                caplen: LENGTH as u32,
+                len: LENGTH as u32,
+            },
+            data: (0..LENGTH).map(|byte| (byte ^ index) as u8).collect(),
+        };
+        let size = |binary_frames: bool| {
+            let mut frames = EncodedFrames::default();
+            let encoding = Encoding {
+                compression: false,
+                binary_frames,
+            };
+            (0..FRAMES)
+                .map(|index| {
+                    let frame = Arc::new(FrameType::Raw(raw(index)));
+                    frames.get_or_encode(&frame, encoding).unwrap().len()
+                })
+                .sum::<usize>()
+        };
+        let (json, binary) = (size(false), size(true));
+        assert!(binary * 3 < json, "binary {binary}, json {json}");
+
+        // Frames of the other kinds stay JSON
+        let mut frames = EncodedFrames::default();
+        let encoding = Encoding {
+            compression: true,
+            binary_frames: true,
+        };
+        let header = frames.get_or_encode(&frame(60), encoding).unwrap();
+        let decompressed = common::compression::decompress(&header.into_data()).unwrap();
+        assert!(serde_json::from_str::<Response>(&decompressed).is_ok());
+
+        let message = frames
+            .get_or_encode(&Arc::new(FrameType::Raw(raw(0))), encoding)
+            .unwrap();
+        let decompressed =
+            common::compression::decompress_bytes(&message.into_data()).unwrap();
+        assert_eq!(binary::decode(decompressed).unwrap(), raw(0));
+    }
+}
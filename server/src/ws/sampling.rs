@@ -0,0 +1,284 @@
+// Sampled mode of the connection.
+// If the frames are queued faster than they're sent, the backlog keeps growing.
+// When it stays above the threshold for the delay, only 1 of N frames is sent,
+// and the rest are counted into the periodic summaries. When the backlog stays
+// below the half of the threshold for the same delay, every frame is sent again.
+
+use crate::statistics;
+use common::messages::{ProtocolCountersDto, SummaryDto};
+use dpi::dto::frame::{FrameHeader, FrameType};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// How often the summaries are sent in the sampled mode.
+pub const SUMMARY_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SamplingMode {
+    Full,
+    Sampled,
+}
+
+#[derive(Debug, Clone)]
+pub struct SamplingSettings {
+    // Frames in the queue. Zero disables the sampled mode
+    pub threshold: usize,
+    pub rate: u32,
+    pub delay: Duration,
+}
+
+pub struct Sampler {
+    settings: SamplingSettings,
+    mode: SamplingMode,
+    // Since when the backlog is enough to switch the mode
+    switch_pending_since: Option<Instant>,
+    counter: u32,
+    summary: Option<Summary>,
+}
+
+struct Summary {
+    started: Instant,
+    frames: u64,
+    bytes: u64,
+    first: Option<FrameHeader>,
+    last: Option<FrameHeader>,
+    protocols: Vec<ProtocolCountersDto>,
+}
+
+impl Sampler {
+    pub fn new(settings: SamplingSettings) -> Self {
+        Self {
+            settings,
+            mode: SamplingMode::Full,
+            switch_pending_since: None,
+            counter: 0,
+            summary: None,
+        }
+    }
+
+    pub fn mode(&self) -> SamplingMode {
+        self.mode
+    }
+
+    pub fn rate(&self) -> u32 {
+        self.settings.rate.max(1)
+    }
+
+    /// Switches the mode by the backlog of the queue. Returns true if it was switched.
+    pub fn update(&mut self, backlog: usize, now: Instant) -> bool {
+        if self.settings.threshold == 0 {
+            return false;
+        }
+
+        let is_switch_needed = match self.mode {
+            SamplingMode::Full => backlog > self.settings.threshold,
+            SamplingMode::Sampled => backlog <= self.settings.threshold / 2,
+        };
+        if !is_switch_needed {
+            self.switch_pending_since = None;
+            return false;
+        }
+
+        let since = *self.switch_pending_since.get_or_insert(now);
+        if now.duration_since(since) < self.settings.delay {
+            return false;
+        }
+        self.switch_pending_since = None;
+
+        match self.mode {
+            SamplingMode::Full => {
+                self.mode = SamplingMode::Sampled;
+                self.counter = 0;
+                self.summary = Some(Summary::new(now));
+            },
+            // The last summary is kept until it's taken
+            SamplingMode::Sampled => self.mode = SamplingMode::Full,
+        }
+
+        true
+    }
+
+    /// Returns the frame if it should be sent. In the sampled mode the frame is counted
+    /// into the summary, and only 1 of N frames is returned.
+    pub fn sample(&mut self, frame: Arc<FrameType>) -> Option<Arc<FrameType>> {
+        if self.mode == SamplingMode::Full {
+            return Some(frame);
+        }
+
+        if let Some(summary) = &mut self.summary {
+            summary.count(&frame);
+        }
+        let is_sent = self.counter == 0;
+        self.counter = (self.counter + 1) % self.rate();
+
+        is_sent.then_some(frame)
+    }
+
+    /// Summary of the interval, if it's time to send it.
+    /// After the switch back to the full mode, the last summary is returned at once.
+    pub fn take_summary(&mut self, now: Instant) -> Option<SummaryDto> {
+        let summary = self.summary.as_ref()?;
+        match self.mode {
+            SamplingMode::Sampled => {
+                if now.duration_since(summary.started) < SUMMARY_INTERVAL {
+                    return None;
+                }
+                let summary = self.summary.replace(Summary::new(now))?;
+                Some(summary.into_dto(true, self.rate(), now))
+            },
+            SamplingMode::Full => {
+                let summary = self.summary.take()?;
+                Some(summary.into_dto(false, self.rate(), now))
+            },
+        }
+    }
+}
+
+impl Summary {
+    fn new(started: Instant) -> Self {
+        Self {
+            started,
+            frames: 0,
+            bytes: 0,
+            first: None,
+            last: None,
+            protocols: vec![],
+        }
+    }
+
+    fn count(&mut self, frame: &FrameType) {
+        let header = match frame {
+            FrameType::Metadata(metadata) => &metadata.header,
+            FrameType::Header(header) => header,
+            FrameType::Raw(frame) => &frame.header,
+        };
+        let bytes = u64::from(header.len);
+
+        self.frames += 1;
+        self.bytes += bytes;
+        if self.first.is_none() {
+            self.first = Some(header.clone());
+        }
+        self.last = Some(header.clone());
+
+        statistics::count_protocols(&mut self.protocols, frame, bytes);
+    }
+
+    fn into_dto(self, sampled: bool, sample_rate: u32, now: Instant) -> SummaryDto {
+        SummaryDto {
+            sampled,
+            sample_rate,
+            interval_ms: now.duration_since(self.started).as_millis() as u64,
+            frames: self.frames,
+            bytes: self.bytes,
+            first: self.first,
+            last: self.last,
+            protocols: self.protocols,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sampler(threshold: usize, rate: u32) -> Sampler {
+        Sampler::new(SamplingSettings {
+            threshold,
+            rate,
+            delay: Duration::from_secs(2),
+        })
+    }
+
+    fn header(len: u32) -> Arc<FrameType> {
+        Arc::new(FrameType::Header(FrameHeader {
+            tv_sec: 1_700_000_000,
+            tv_usec: 0,
+            caplen: len,
+            len,
+        }))
+    }
+
+    #[test]
+    fn test_switch_hysteresis() {
+        let mut sampler = sampler(100, 10);
+        let start = Instant::now();
+        let at = |millis: u64| start + Duration::from_millis(millis);
+
+        // Short spike isn't enough
+        assert!(!sampler.update(150, at(0)));
+        assert!(!sampler.update(150, at(1000)));
+        assert!(!sampler.update(50, at(1500)));
+        assert!(!sampler.update(150, at(2000)));
+        assert_eq!(sampler.mode(), SamplingMode::Full);
+
+        // Backlog stays above the threshold for the delay
+        assert!(!sampler.update(150, at(3900)));
+        assert!(sampler.update(150, at(4000)));
+        assert_eq!(sampler.mode(), SamplingMode::Sampled);
+
+        // Below the threshold, but above the half of it
+        assert!(!sampler.update(80, at(5000)));
+        assert!(!sampler.update(80, at(9000)));
+        assert_eq!(sampler.mode(), SamplingMode::Sampled);
+
+        // Backlog clears, but grows back before the delay
+        assert!(!sampler.update(10, at(10000)));
+        assert!(!sampler.update(60, at(11000)));
+        assert!(!sampler.update(10, at(12000)));
+        assert_eq!(sampler.mode(), SamplingMode::Sampled);
+
+        assert!(sampler.update(0, at(14000)));
+        assert_eq!(sampler.mode(), SamplingMode::Full);
+    }
+
+    #[test]
+    fn test_disabled() {
+        let mut sampler = sampler(0, 10);
+        let start = Instant::now();
+
+        assert!(!sampler.update(1_000_000, start));
+        assert!(!sampler.update(1_000_000, start + Duration::from_secs(60)));
+        assert_eq!(sampler.mode(), SamplingMode::Full);
+    }
+
+    #[test]
+    fn test_sampled_frames_summary() {
+        let mut sampler = sampler(100, 3);
+        let start = Instant::now();
+
+        assert!(sampler.sample(header(10)).is_some());
+        assert!(sampler.take_summary(start).is_none());
+
+        sampler.update(150, start);
+        sampler.update(150, start + Duration::from_secs(2));
+        let sent = (0..9).filter_map(|_| sampler.sample(header(10))).count();
+        assert_eq!(sent, 3);
+
+        // Interval isn't over yet
+        assert!(
+            sampler
+                .take_summary(start + Duration::from_millis(2500))
+                .is_none()
+        );
+        let summary = sampler.take_summary(start + Duration::from_secs(3));
+        assert!(summary.as_ref().is_some_and(|summary| summary.sampled
+            && summary.sample_rate == 3
+            && summary.frames == 9
+            && summary.bytes == 90));
+
+        // Last summary is sent right after switching back
+        sampler.sample(header(20));
+        sampler.update(0, start + Duration::from_secs(3));
+        sampler.update(0, start + Duration::from_secs(5));
+        let summary = sampler.take_summary(start + Duration::from_secs(5));
+        assert!(summary.as_ref().is_some_and(|summary| !summary.sampled
+            && summary.frames == 1
+            && summary.bytes == 20));
+        assert!(
+            sampler
+                .take_summary(start + Duration::from_secs(10))
+                .is_none()
+        );
+    }
+}